@@ -12,6 +12,9 @@ interface PaginationParams {
 interface UserSessionJson {
   id: ID
   device: string | undefined
+  ip_address: string | undefined
+  last_used_at: UTC
+  current: boolean
   created_at: UTC
   updated_at: UTC
 }
@@ -21,12 +24,58 @@ interface UserSessionResponse {
   num_pages: number
 }
 
+interface OrganizationJson {
+  id: ID
+  name: string
+  role: string
+  active: boolean
+  created_at: UTC
+}
+
+interface MemberJson {
+  user_id: ID
+  email: string
+  role: string
+  created_at: UTC
+}
+
 interface AccessTokenClaims {
   exp: number
   sub: ID
   token_type: string
   roles: Array<string>
   permissions: Array<Permission>
+  impersonator: ID | undefined
+  organization: ActiveOrganization | undefined
+}
+
+interface ActiveOrganization {
+  id: ID
+  role: string
+}
+
+interface Organization {
+  id: ID
+  name: string
+  created_at: UTC
+}
+
+interface OrganizationChangeset {
+  name: string
+}
+
+interface Membership {
+  organization_id: ID
+  user_id: ID
+  role: string
+  active_at: UTC | undefined
+  created_at: UTC
+}
+
+interface MembershipChangeset {
+  organization_id: ID
+  user_id: ID
+  role: string
 }
 
 interface Permission {
@@ -67,6 +116,46 @@ interface UserChangeset {
   activated: boolean
 }
 
+interface UserImpersonation {
+  id: ID
+  impersonator_id: ID | undefined
+  user_id: ID
+  reason: string | undefined
+  ip_address: string | undefined
+  started_at: UTC
+  ended_at: UTC | undefined
+}
+
+interface UserImpersonationChangeset {
+  impersonator_id: ID
+  user_id: ID
+  reason: string | undefined
+  ip_address: string | undefined
+}
+
+interface UserInvitation {
+  id: ID
+  email: string
+  roles: string
+  token: string
+  invited_by: ID
+  organization_id: ID | undefined
+  organization_role: string | undefined
+  accepted_at: UTC | undefined
+  expires_at: UTC
+  created_at: UTC
+}
+
+interface UserInvitationChangeset {
+  email: string
+  roles: string
+  token: string
+  invited_by: ID
+  organization_id: ID | undefined
+  organization_role: string | undefined
+  expires_at: UTC
+}
+
 interface UserSession {
   id: ID
   user_id: ID
@@ -74,10 +163,24 @@ interface UserSession {
   device: string | undefined
   created_at: UTC
   updated_at: UTC
+  ip_address: string | undefined
+  last_used_at: UTC
 }
 
 interface UserSessionChangeset {
   user_id: ID
   refresh_token: string
   device: string | undefined
+  ip_address: string | undefined
+}
+
+interface UserSessionRotatedToken {
+  refresh_token: string
+  session_id: ID
+  created_at: UTC
+}
+
+interface UserSessionRotatedTokenChangeset {
+  refresh_token: string
+  session_id: ID
 }
@@ -0,0 +1,25 @@
+/* This file is generated and managed by tsync */
+
+interface UsageEvent {
+  id: number
+  idempotency_key: string
+  subject: string
+  metric: string
+  quantity: number
+  recorded_at: Date
+}
+
+interface UsageEventChangeset {
+  idempotency_key: string
+  subject: string
+  metric: string
+  quantity: number
+}
+
+interface UsageAggregate {
+  subject: string
+  metric: string
+  period_start: Date
+  quantity: number
+  updated_at: Date
+}
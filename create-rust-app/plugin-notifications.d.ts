@@ -0,0 +1,29 @@
+/* This file is generated and managed by tsync */
+
+interface Notification {
+  id: number
+  user_id: number
+  kind: string
+  title: string
+  body: string
+  link?: string
+  read_at?: Date
+  created_at: Date
+}
+
+interface NotificationChangeset {
+  user_id: number
+  kind: string
+  title: string
+  body: string
+  link?: string
+}
+
+type DigestFrequency =
+  | "never" | "daily" | "weekly";
+
+interface NotificationPreference {
+  user_id: number
+  digest_frequency: string
+  last_digest_at?: Date
+}
@@ -0,0 +1,809 @@
+use crate::{Connection, Database};
+use diesel::{
+    query_dsl::RunQueryDsl,
+    sql_query,
+    sql_types::{BigInt, Bool, Nullable, Text},
+    QueryResult,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{search::like_pattern, AdminSearch, SearchResult};
+#[cfg(debug_assertions)]
+use crate::build_info::{self, BuildInfo, Dependency, EnvVar};
+
+type StatusCode = i32;
+type Message = &'static str;
+
+/// the `(table, column)`s holding secrets (password hashes, tokens, signing secrets): the portal
+/// shows [`REDACTED`] instead of their values, and can't search, sort or set them
+///
+/// the bodies of queued emails are secrets too: activation, password reset, email change and
+/// invitation emails carry their tokens in links
+pub const SECRET_COLUMNS: &[(&str, &str)] = &[
+    ("users", "hash_password"),
+    ("user_sessions", "refresh_token"),
+    ("user_session_rotated_tokens", "refresh_token"),
+    ("user_invitations", "token"),
+    ("webhook_endpoints", "secret"),
+    ("push_subscriptions", "auth"),
+    ("mail_queue", "text_body"),
+    ("mail_queue", "html_body"),
+];
+
+/// what the values of the [`SECRET_COLUMNS`] are replaced with (`null`s stay `null`)
+pub const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Serialize)]
+/// what the portal can show, see [`info`]
+pub struct AdminInfo {
+    /// whether every request is let through, see [`super::is_open`]
+    pub open: bool,
+    /// whether the development-only tools (the SQL console, migrations, mailbox... at `/api/development`) are available
+    pub dev_tools: bool,
+    /// whether the SQL console is enabled (it's opt-in, with `CRA_SQL_CONSOLE=true`)
+    pub sql_console: bool,
+    /// whether the [email log](`crate::mail_log`) is enabled, its API is expected at `/api/mail-log`
+    pub mail_log: bool,
+    /// whether the [audit log](`crate::audit`) is enabled, its API is expected at `/api/audit-log`
+    pub audit_log: bool,
+    /// whether the [scheduler](`crate::scheduler`) is enabled, its API is expected at `/api/scheduler`
+    pub scheduler: bool,
+    /// whether the [feature flags](`crate::flags`) are enabled, their API is expected at `/api/flags`
+    pub flags: bool,
+    /// whether [webhooks](`crate::webhooks`) are enabled, their API is expected at `/api/webhooks`
+    pub webhooks: bool,
+    /// whether the user management is available (it needs the auth plugin), see [`super::users`]
+    pub users: bool,
+    /// whether the app's environment and build info are available (debug builds only), see
+    /// [`environment`]
+    pub environment: bool,
+}
+
+#[derive(Debug, Serialize, QueryableByName)]
+pub struct TableInfo {
+    #[diesel(sql_type=Text)]
+    pub name: String,
+    #[diesel(sql_type=BigInt)]
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, QueryableByName)]
+pub struct TableColumn {
+    #[diesel(sql_type=Text)]
+    pub column_name: String,
+    #[diesel(sql_type=Text)]
+    pub data_type: String,
+}
+
+#[derive(Debug, Serialize, QueryableByName)]
+/// a column, as the schema diagram shows it
+pub struct SchemaColumn {
+    #[diesel(sql_type=Text)]
+    pub column_name: String,
+    #[diesel(sql_type=Text)]
+    pub data_type: String,
+    #[diesel(sql_type=Bool)]
+    pub nullable: bool,
+    #[diesel(sql_type=Bool)]
+    pub primary_key: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchemaTable {
+    pub name: String,
+    pub columns: Vec<SchemaColumn>,
+}
+
+#[derive(Debug, Serialize, QueryableByName)]
+/// `table_name.column_name` references `foreign_table_name.foreign_column_name`
+pub struct ForeignKey {
+    #[diesel(sql_type=Text)]
+    pub table_name: String,
+    #[diesel(sql_type=Text)]
+    pub column_name: String,
+    #[diesel(sql_type=Text)]
+    pub foreign_table_name: String,
+    /// empty when the foreign table's primary key is referenced implicitly (sqlite)
+    #[diesel(sql_type=Text)]
+    pub foreign_column_name: String,
+}
+
+#[derive(Debug, Serialize)]
+/// the data model, see [`schema`]
+pub struct Schema {
+    pub tables: Vec<SchemaTable>,
+    pub foreign_keys: Vec<ForeignKey>,
+}
+
+#[derive(Debug, Serialize)]
+/// a page of a table's rows
+pub struct TableRows {
+    pub columns: Vec<TableColumn>,
+    /// the columns whose values are [`REDACTED`], see [`SECRET_COLUMNS`]
+    pub secret_columns: Vec<String>,
+    /// the rows, as json objects keyed by column name
+    pub rows: serde_json::Value,
+    /// number of rows matching the query, across all pages
+    pub total: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TableRowsQuery {
+    /// zero-based (default: 0)
+    pub page: Option<i64>,
+    /// (default: 50, at most 100)
+    pub page_size: Option<i64>,
+    /// only show the row whose `id` column matches
+    pub id: Option<String>,
+    /// only show the rows where a column contains this text (case insensitive)
+    pub search: Option<String>,
+    /// the column to sort by (default: the first one)
+    pub sort: Option<String>,
+    /// sort in descending order (default: false)
+    pub desc: Option<bool>,
+}
+
+/// the values of a record, keyed by column name, see [`create_record`] and [`update_record`]
+pub type RecordValues = serde_json::Map<String, serde_json::Value>;
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    /// maximum number of results per resource (default: 5)
+    pub limit: Option<i64>,
+}
+
+#[derive(QueryableByName)]
+struct Name {
+    #[diesel(sql_type=Text)]
+    name: String,
+}
+
+#[derive(QueryableByName)]
+struct Count {
+    #[diesel(sql_type=BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct Json {
+    #[diesel(sql_type=Text)]
+    json: String,
+}
+
+/// /info
+pub fn info() -> AdminInfo {
+    AdminInfo {
+        open: super::is_open(),
+        dev_tools: cfg!(all(feature = "plugin_dev", debug_assertions)),
+        #[cfg(all(feature = "plugin_dev", debug_assertions))]
+        sql_console: crate::dev::controller::sql_console_enabled(),
+        #[cfg(not(all(feature = "plugin_dev", debug_assertions)))]
+        sql_console: false,
+        mail_log: cfg!(feature = "plugin_mail_log"),
+        audit_log: cfg!(feature = "plugin_audit"),
+        scheduler: cfg!(feature = "plugin_scheduler"),
+        flags: cfg!(feature = "plugin_flags"),
+        webhooks: cfg!(feature = "plugin_webhooks"),
+        users: cfg!(feature = "plugin_auth"),
+        environment: cfg!(debug_assertions),
+    }
+}
+
+#[cfg(debug_assertions)]
+#[derive(Debug, Serialize)]
+/// what's running, see [`environment`]
+pub struct Environment {
+    /// the app's crate name, `None` unless its [`BuildInfo`] was registered
+    pub name: Option<&'static str>,
+    pub version: Option<&'static str>,
+    pub git_commit: Option<&'static str>,
+    /// unix timestamp, in seconds
+    pub built_at: Option<&'static str>,
+    /// `debug` or `release`
+    pub profile: &'static str,
+    pub create_rust_app_version: &'static str,
+    /// see [`build_info::features`]
+    pub features: Vec<&'static str>,
+    pub dependencies: Vec<Dependency>,
+    /// see [`build_info::environment`]
+    pub env: Vec<EnvVar>,
+}
+
+/// /environment
+///
+/// the app's build (if its [`BuildInfo`] was registered), the features create-rust-app was
+/// compiled with and the sanitized environment variables
+///
+/// like the other development tools, it's only compiled into debug builds: even sanitized, the
+/// environment tells too much about a production deployment
+#[cfg(debug_assertions)]
+pub fn environment(build: Option<&BuildInfo>) -> Environment {
+    Environment {
+        name: build.map(|build| build.name),
+        version: build.map(|build| build.version),
+        git_commit: build.and_then(|build| build.git_commit),
+        built_at: build.and_then(|build| build.built_at),
+        profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        },
+        create_rust_app_version: build_info::create_rust_app_version(),
+        features: build_info::features(),
+        dependencies: build.map(BuildInfo::dependencies).unwrap_or_default(),
+        env: build_info::environment(),
+    }
+}
+
+/// /tables
+///
+/// the tables of the database (for postgres: of the current schema), with their number of rows
+///
+/// # Returns [`Result`]
+/// - Ok([`Vec<TableInfo>`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn tables(db: &Database) -> Result<Vec<TableInfo>, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let names = match table_names(&mut db) {
+        Ok(names) => names,
+        Err(err) => {
+            println!("{err:#?}");
+            return Err((500, "Could not list the tables."));
+        }
+    };
+
+    let mut tables = vec![];
+    for name in names {
+        let count = sql_query(format!("SELECT COUNT(*) AS count FROM {}", quote(&name)))
+            .get_result::<Count>(&mut db)
+            .map(|count| count.count)
+            .unwrap_or(-1);
+
+        tables.push(TableInfo { name, count });
+    }
+
+    Ok(tables)
+}
+
+/// /schema
+///
+/// the tables of the database (for postgres: of the current schema) with their columns, and the
+/// foreign keys between them, for the portal's schema diagram. It's the data model Diesel's
+/// `schema.rs` is generated from, its `joinable!`s are the foreign keys.
+///
+/// # Returns [`Result`]
+/// - Ok([`Schema`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn schema(db: &Database) -> Result<Schema, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    match read_schema(&mut db) {
+        Ok(schema) => Ok(schema),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not read the schema."))
+        }
+    }
+}
+
+/// /tables/{name}
+///
+/// a page of [`table`]'s rows, or the row whose `id` is [`query.id`](`TableRowsQuery`), with their
+/// [`SECRET_COLUMNS`] redacted
+///
+/// # Returns [`Result`]
+/// - Ok([`TableRows`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn table(
+    db: &Database,
+    table: &str,
+    query: &TableRowsQuery,
+) -> Result<TableRows, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let columns = existing_table(&mut db, table)?;
+    if let Some(sort) = &query.sort {
+        if !columns.iter().any(|column| &column.column_name == sort) {
+            return Err((400, "Unknown column."));
+        }
+        if is_secret(table, sort) {
+            return Err((400, "Secret columns can't be sorted by."));
+        }
+    }
+
+    match read_table(&mut db, table, columns, query) {
+        Ok(rows) => Ok(rows),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not read the table."))
+        }
+    }
+}
+
+/// POST /tables/{name}
+///
+/// inserts a record in [`table`], the columns missing from [`values`] get their default value
+///
+/// the [`SECRET_COLUMNS`] can't be set
+///
+/// # Returns [`Result`]
+/// - Ok(())
+/// - Err([`StatusCode`], [`Message`])
+pub fn create_record(
+    db: &Database,
+    table: &str,
+    values: &RecordValues,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let columns = existing_table(&mut db, table)?;
+    let names = value_columns(table, &columns, values, false)?;
+    let quoted = names
+        .iter()
+        .map(|name| quote(name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    #[cfg(not(feature = "database_sqlite"))]
+    let statement = format!(
+        "INSERT INTO {table} ({quoted}) SELECT {quoted} FROM json_populate_record(NULL::{table}, CAST($1 AS json))",
+        table = quote(table)
+    );
+    #[cfg(feature = "database_sqlite")]
+    let statement = format!(
+        "INSERT INTO {} ({quoted}) SELECT {}",
+        quote(table),
+        names
+            .iter()
+            .map(|name| json_value(name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    match sql_query(statement)
+        .bind::<Text, _>(serde_json::Value::Object(values.clone()).to_string())
+        .execute(&mut db)
+    {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((400, "Could not save the record."))
+        }
+    }
+}
+
+/// PUT /tables/{name}/{id}
+///
+/// sets the columns in [`values`] of the [`table`] record whose `id` is [`id`]
+///
+/// the [`SECRET_COLUMNS`] can't be set
+///
+/// # Returns [`Result`]
+/// - Ok(())
+/// - Err([`StatusCode`], [`Message`])
+pub fn update_record(
+    db: &Database,
+    table: &str,
+    id: &str,
+    values: &RecordValues,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let columns = existing_table(&mut db, table)?;
+    if !columns.iter().any(|column| column.column_name == "id") {
+        return Err((400, "The table has no id column."));
+    }
+    let names = value_columns(table, &columns, values, true)?;
+
+    #[cfg(not(feature = "database_sqlite"))]
+    let statement = format!(
+        "UPDATE {table} SET {} FROM json_populate_record(NULL::{table}, CAST($1 AS json)) AS r WHERE CAST({table}.id AS TEXT) = $2",
+        names
+            .iter()
+            .map(|name| format!("{column} = r.{column}", column = quote(name)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        table = quote(table)
+    );
+    #[cfg(feature = "database_sqlite")]
+    let statement = format!(
+        "UPDATE {} SET {} WHERE CAST(id AS TEXT) = $2",
+        quote(table),
+        names
+            .iter()
+            .map(|name| format!("{} = {}", quote(name), json_value(name)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    match sql_query(statement)
+        .bind::<Text, _>(serde_json::Value::Object(values.clone()).to_string())
+        .bind::<Text, _>(id)
+        .execute(&mut db)
+    {
+        Ok(0) => Err((404, "Record not found.")),
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((400, "Could not save the record."))
+        }
+    }
+}
+
+/// DELETE /tables/{name}/{id}
+///
+/// deletes the [`table`] record whose `id` is [`id`]
+///
+/// # Returns [`Result`]
+/// - Ok(())
+/// - Err([`StatusCode`], [`Message`])
+pub fn delete_record(db: &Database, table: &str, id: &str) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let columns = existing_table(&mut db, table)?;
+    if !columns.iter().any(|column| column.column_name == "id") {
+        return Err((400, "The table has no id column."));
+    }
+
+    match sql_query(format!(
+        "DELETE FROM {} WHERE CAST(id AS TEXT) = $1",
+        quote(table)
+    ))
+    .bind::<Text, _>(id)
+    .execute(&mut db)
+    {
+        Ok(0) => Err((404, "Record not found.")),
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((400, "Could not delete the record."))
+        }
+    }
+}
+
+/// /search
+///
+/// searches every resource registered in [`admin_search`](`AdminSearch`)
+pub fn search(db: &Database, admin_search: &AdminSearch, query: &SearchQuery) -> Vec<SearchResult> {
+    let mut db = db.pool.get().unwrap();
+
+    admin_search.search(&mut db, &query.q, query.limit.unwrap_or(5).clamp(1, 50))
+}
+
+fn read_table(
+    db: &mut Connection,
+    table: &str,
+    columns: Vec<TableColumn>,
+    query: &TableRowsQuery,
+) -> QueryResult<TableRows> {
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+    let offset = query.page.unwrap_or(0).max(0) * page_size;
+
+    let id_filter = match &query.id {
+        Some(_) if columns.iter().any(|column| column.column_name == "id") => {
+            "CAST(id AS TEXT) = $1"
+        }
+        _ => "$1 IS NULL",
+    };
+    let id = query.id.clone();
+
+    let search_filter = columns
+        .iter()
+        .filter(|column| !is_secret(table, &column.column_name))
+        .map(|column| {
+            format!(
+                " OR LOWER(CAST({} AS TEXT)) LIKE $2 ESCAPE '\\'",
+                quote(&column.column_name)
+            )
+        })
+        .collect::<String>();
+    let filter = format!("WHERE {id_filter} AND ($2 IS NULL{search_filter})");
+    let search = query
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|search| !search.is_empty())
+        .map(like_pattern);
+
+    let order = match &query.sort {
+        Some(sort) => quote(sort),
+        None => "1".to_string(),
+    };
+    let direction = match query.desc {
+        Some(true) => "DESC",
+        _ => "ASC",
+    };
+
+    let total = sql_query(format!(
+        "SELECT COUNT(*) AS count FROM {} {filter}",
+        quote(table)
+    ))
+    .bind::<Nullable<Text>, _>(id.clone())
+    .bind::<Nullable<Text>, _>(search.clone())
+    .get_result::<Count>(db)?
+    .count;
+
+    let selected = columns
+        .iter()
+        .map(|column| {
+            let name = quote(&column.column_name);
+
+            if is_secret(table, &column.column_name) {
+                format!("CASE WHEN {name} IS NULL THEN NULL ELSE '{REDACTED}' END AS {name}")
+            } else {
+                name
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let page = format!(
+        "SELECT {selected} FROM {} {filter} ORDER BY {order} {direction} LIMIT $3 OFFSET $4",
+        quote(table)
+    );
+
+    #[cfg(not(feature = "database_sqlite"))]
+    let statement =
+        format!("SELECT CAST(COALESCE(json_agg(q), '[]') AS TEXT) AS json FROM ({page}) q");
+    #[cfg(feature = "database_sqlite")]
+    let statement = format!(
+        "SELECT COALESCE(json_group_array(json_object({})), '[]') AS json FROM ({page}) q",
+        columns
+            .iter()
+            .map(|column| format!(
+                "'{}', q.{}",
+                column.column_name.replace('\'', "''"),
+                quote(&column.column_name)
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let rows = sql_query(statement)
+        .bind::<Nullable<Text>, _>(id)
+        .bind::<Nullable<Text>, _>(search)
+        .bind::<BigInt, _>(page_size)
+        .bind::<BigInt, _>(offset)
+        .get_result::<Json>(db)?
+        .json;
+
+    let secret_columns = columns
+        .iter()
+        .filter(|column| is_secret(table, &column.column_name))
+        .map(|column| column.column_name.clone())
+        .collect();
+
+    Ok(TableRows {
+        columns,
+        secret_columns,
+        rows: serde_json::from_str(&rows).unwrap_or(serde_json::Value::Null),
+        total,
+    })
+}
+
+/// the columns of [`table`], if it exists
+///
+/// only tables that exist are interpolated into queries
+fn existing_table(
+    db: &mut Connection,
+    table: &str,
+) -> Result<Vec<TableColumn>, (StatusCode, Message)> {
+    match table_names(db) {
+        Ok(names) if names.iter().any(|name| name == table) => {}
+        Ok(_) => return Err((404, "Table not found.")),
+        Err(_) => return Err((500, "Could not list the tables.")),
+    }
+
+    table_columns(db, table).map_err(|err| {
+        println!("{err:#?}");
+        (500, "Could not read the table.")
+    })
+}
+
+/// whether [`column`] of [`table`] is one of the [`SECRET_COLUMNS`]
+fn is_secret(table: &str, column: &str) -> bool {
+    SECRET_COLUMNS
+        .iter()
+        .any(|(secret_table, secret_column)| *secret_table == table && *secret_column == column)
+}
+
+/// the names of the columns set by [`values`], which must all exist (and not be secret)
+///
+/// when [`updating`], the `id` column is left out
+fn value_columns<'a>(
+    table: &str,
+    columns: &[TableColumn],
+    values: &'a RecordValues,
+    updating: bool,
+) -> Result<Vec<&'a String>, (StatusCode, Message)> {
+    let mut names = vec![];
+    for name in values.keys() {
+        if !columns.iter().any(|column| &column.column_name == name) {
+            return Err((400, "Unknown column."));
+        }
+        if is_secret(table, name) {
+            return Err((400, "Secret columns can't be set."));
+        }
+        if !(updating && name == "id") {
+            names.push(name);
+        }
+    }
+
+    if names.is_empty() {
+        return Err((400, "No values given."));
+    }
+
+    Ok(names)
+}
+
+#[cfg(feature = "database_sqlite")]
+/// the [`column`] field of the json object bound to `$1`
+fn json_value(column: &str) -> String {
+    format!("json_extract($1, '$.\"{}\"')", column.replace('\'', "''"))
+}
+
+fn table_names(db: &mut Connection) -> QueryResult<Vec<String>> {
+    #[cfg(not(feature = "database_sqlite"))]
+    let statement = "SELECT CAST(tablename AS TEXT) AS name FROM pg_catalog.pg_tables WHERE schemaname = current_schema() ORDER BY tablename";
+    #[cfg(feature = "database_sqlite")]
+    let statement = "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name";
+
+    Ok(sql_query(statement)
+        .load::<Name>(db)?
+        .into_iter()
+        .map(|row| row.name)
+        .collect())
+}
+
+fn table_columns(db: &mut Connection, table: &str) -> QueryResult<Vec<TableColumn>> {
+    #[cfg(not(feature = "database_sqlite"))]
+    let statement = "SELECT CAST(column_name AS TEXT) AS column_name, CAST(data_type AS TEXT) AS data_type FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1 ORDER BY ordinal_position";
+    #[cfg(feature = "database_sqlite")]
+    let statement = "SELECT name AS column_name, type AS data_type FROM pragma_table_info($1)";
+
+    sql_query(statement)
+        .bind::<Text, _>(table)
+        .load::<TableColumn>(db)
+}
+
+fn read_schema(db: &mut Connection) -> QueryResult<Schema> {
+    #[cfg(not(feature = "database_sqlite"))]
+    let columns_statement = r#"
+      SELECT
+        CAST(c.column_name AS TEXT) AS column_name,
+        CAST(c.data_type AS TEXT) AS data_type,
+        c.is_nullable = 'YES' AS nullable,
+        EXISTS (
+          SELECT 1
+          FROM information_schema.table_constraints tc
+          INNER JOIN information_schema.key_column_usage kcu
+            ON kcu.constraint_name = tc.constraint_name AND kcu.table_schema = tc.table_schema
+          WHERE tc.constraint_type = 'PRIMARY KEY'
+            AND tc.table_schema = c.table_schema
+            AND tc.table_name = c.table_name
+            AND kcu.column_name = c.column_name
+        ) AS primary_key
+      FROM information_schema.columns c
+      WHERE c.table_schema = current_schema() AND c.table_name = $1
+      ORDER BY c.ordinal_position
+    "#;
+    #[cfg(feature = "database_sqlite")]
+    let columns_statement = r#"SELECT name AS column_name, type AS data_type, "notnull" = 0 AS nullable, pk > 0 AS primary_key FROM pragma_table_info($1)"#;
+
+    let mut tables = vec![];
+    for name in table_names(db)? {
+        let columns = sql_query(columns_statement)
+            .bind::<Text, _>(&name)
+            .load::<SchemaColumn>(db)?;
+
+        tables.push(SchemaTable { name, columns });
+    }
+
+    #[cfg(not(feature = "database_sqlite"))]
+    let foreign_keys = sql_query(
+        r#"
+      SELECT
+        CAST(cl.relname AS TEXT) AS table_name,
+        CAST(a.attname AS TEXT) AS column_name,
+        CAST(fcl.relname AS TEXT) AS foreign_table_name,
+        CAST(fa.attname AS TEXT) AS foreign_column_name
+      FROM pg_constraint con
+      INNER JOIN pg_class cl ON cl.oid = con.conrelid
+      INNER JOIN pg_namespace n ON n.oid = cl.relnamespace
+      INNER JOIN pg_class fcl ON fcl.oid = con.confrelid
+      CROSS JOIN LATERAL unnest(con.conkey, con.confkey) AS k(attnum, fattnum)
+      INNER JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+      INNER JOIN pg_attribute fa ON fa.attrelid = con.confrelid AND fa.attnum = k.fattnum
+      WHERE con.contype = 'f' AND n.nspname = current_schema()
+      ORDER BY 1, 2
+    "#,
+    )
+    .load::<ForeignKey>(db)?;
+
+    #[cfg(feature = "database_sqlite")]
+    let foreign_keys = {
+        let mut foreign_keys = vec![];
+        for table in &tables {
+            foreign_keys.extend(
+                sql_query(r#"SELECT $1 AS table_name, "from" AS column_name, "table" AS foreign_table_name, COALESCE("to", '') AS foreign_column_name FROM pragma_foreign_key_list($1)"#)
+                    .bind::<Text, _>(&table.name)
+                    .load::<ForeignKey>(db)?,
+            );
+        }
+        foreign_keys
+    };
+
+    Ok(Schema {
+        tables,
+        foreign_keys,
+    })
+}
+
+/// quotes an identifier so it can be interpolated in a query
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(all(test, feature = "database_sqlite"))]
+mod admin_controller_tests {
+    use super::{table, TableRowsQuery, REDACTED};
+    use crate::Database;
+    use diesel::{sql_query, RunQueryDsl};
+
+    #[test]
+    fn test_queued_mail_bodies_are_redacted() {
+        let path = std::env::temp_dir().join(format!("cra-admin-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        std::env::set_var("DATABASE_URL", path.to_str().unwrap());
+
+        let db = Database::new();
+        let mut connection = db.get_connection();
+        sql_query(
+            "CREATE TABLE mail_queue (
+              id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+              to_address TEXT NOT NULL,
+              subject TEXT NOT NULL,
+              text_body TEXT NOT NULL,
+              html_body TEXT NOT NULL,
+              template TEXT,
+              status TEXT NOT NULL DEFAULT 'pending'
+            )",
+        )
+        .execute(&mut connection)
+        .unwrap();
+        sql_query(
+            "INSERT INTO mail_queue (to_address, subject, text_body, html_body, template)
+             VALUES ('user@example.com', 'Reset Password Instructions',
+               'http://localhost:3000/reset?token=secret-reset-token',
+               '<a href=\"http://localhost:3000/reset?token=secret-reset-token\">reset</a>',
+               'auth_password_reset')",
+        )
+        .execute(&mut connection)
+        .unwrap();
+        drop(connection);
+
+        let rows = table(
+            &db,
+            "mail_queue",
+            &TableRowsQuery {
+                page: None,
+                page_size: None,
+                id: Some("1".to_string()),
+                search: None,
+                sort: None,
+                desc: None,
+            },
+        )
+        .unwrap();
+
+        let row = &rows.rows[0];
+        assert_eq!(row["subject"], "Reset Password Instructions");
+        assert_eq!(row["text_body"], REDACTED);
+        assert_eq!(row["html_body"], REDACTED);
+        assert!(!rows.rows.to_string().contains("secret-reset-token"));
+        assert_eq!(rows.secret_columns, vec!["text_body", "html_body"]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
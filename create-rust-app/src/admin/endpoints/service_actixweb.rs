@@ -0,0 +1,503 @@
+#[cfg(debug_assertions)]
+use crate::build_info::BuildInfo;
+use crate::{
+    admin::controller,
+    admin::controller::{RecordValues, SearchQuery, TableRowsQuery},
+    admin::AdminSearch,
+    Database,
+};
+#[cfg(feature = "plugin_auth")]
+use crate::{
+    admin::users as admin_users,
+    admin::users::{BanInput, PasswordInput, PermissionInput, RoleInput, UsersQuery},
+    auth::AuthConfig,
+};
+use actix_http::StatusCode;
+use actix_web::{
+    delete,
+    dev::Payload,
+    get, post, put,
+    web::{self, Data, Json, Path, Query},
+    Error as AWError, FromRequest, HttpRequest, HttpResponse, Result, Scope,
+};
+use futures::future::{ready, Ready};
+use serde_json::json;
+
+/// extractor guarding the admin endpoints (and the development-only ones)
+///
+/// the request must be made by a user with the [`ADMIN_ROLE`](`crate::admin::ADMIN_ROLE`), unless
+/// the portal is [open](`crate::admin::is_open`)
+pub struct AdminAccess {
+    /// `None` when the portal is open
+    #[cfg(feature = "plugin_auth")]
+    auth: Option<crate::auth::Auth>,
+}
+
+impl AdminAccess {
+    /// whether the user can create, edit and delete [`table`]'s records, see
+    /// [`ADMIN_WRITE_PERMISSION`](`crate::admin::ADMIN_WRITE_PERMISSION`)
+    #[cfg(not(feature = "plugin_auth"))]
+    pub fn can_write(&self, _table: &str) -> bool {
+        true
+    }
+
+    /// whether the user can create, edit and delete [`table`]'s records, see
+    /// [`ADMIN_WRITE_PERMISSION`](`crate::admin::ADMIN_WRITE_PERMISSION`)
+    #[cfg(feature = "plugin_auth")]
+    pub fn can_write(&self, table: &str) -> bool {
+        let permission = crate::admin::ADMIN_WRITE_PERMISSION;
+
+        match &self.auth {
+            Some(auth) => {
+                auth.has_permission(permission.to_string())
+                    || auth.has_permission(format!("{permission}:{table}"))
+            }
+            None => true,
+        }
+    }
+
+    /// the admin making the request, `None` when the portal is [open](`crate::admin::is_open`)
+    #[cfg(feature = "plugin_auth")]
+    pub fn user_id(&self) -> Option<i32> {
+        self.auth.as_ref().map(|auth| auth.user_id)
+    }
+}
+
+impl FromRequest for AdminAccess {
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = AWError;
+
+    #[cfg(not(feature = "plugin_auth"))]
+    fn from_request(_req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(AdminAccess {}))
+    }
+
+    #[cfg(feature = "plugin_auth")]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if crate::admin::is_open() {
+            return ready(Ok(AdminAccess { auth: None }));
+        }
+
+        let auth = match crate::auth::Auth::from_request(req, payload).into_inner() {
+            Ok(auth) => auth,
+            Err(err) => return ready(Err(err.into())),
+        };
+
+        if !auth.has_role(crate::admin::ADMIN_ROLE.to_string()) {
+            return ready(Err(actix_web::error::InternalError::from_response(
+                "Forbidden.",
+                error_response(StatusCode::FORBIDDEN.as_u16() as i32, "Forbidden."),
+            )
+            .into()));
+        }
+
+        ready(Ok(AdminAccess { auth: Some(auth) }))
+    }
+}
+
+fn error_response(status_code: i32, message: &'static str) -> HttpResponse {
+    HttpResponse::build(StatusCode::from_u16(status_code as u16).unwrap())
+        .body(json!({ "message": message }).to_string())
+}
+
+#[get("/info")]
+async fn info(_admin: AdminAccess) -> HttpResponse {
+    HttpResponse::Ok().json(controller::info())
+}
+
+/// the app's build and (sanitized) environment (debug builds only), see [`controller::environment`]
+#[cfg(debug_assertions)]
+#[get("/environment")]
+async fn environment(_admin: AdminAccess, build: Option<Data<BuildInfo>>) -> HttpResponse {
+    HttpResponse::Ok().json(controller::environment(build.as_deref()))
+}
+
+#[get("/tables")]
+async fn tables(db: Data<Database>, _admin: AdminAccess) -> Result<HttpResponse> {
+    let result = web::block(move || controller::tables(&db)).await?;
+
+    match result {
+        Ok(tables) => Ok(HttpResponse::Ok().json(tables)),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[get("/schema")]
+async fn schema(db: Data<Database>, _admin: AdminAccess) -> Result<HttpResponse> {
+    let result = web::block(move || controller::schema(&db)).await?;
+
+    match result {
+        Ok(schema) => Ok(HttpResponse::Ok().json(schema)),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[get("/tables/{name}")]
+async fn table(
+    db: Data<Database>,
+    _admin: AdminAccess,
+    name: Path<String>,
+    Query(query): Query<TableRowsQuery>,
+) -> Result<HttpResponse> {
+    let result = web::block(move || controller::table(&db, &name, &query)).await?;
+
+    match result {
+        Ok(rows) => Ok(HttpResponse::Ok().json(rows)),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[post("/tables/{name}")]
+async fn create_record(
+    db: Data<Database>,
+    admin: AdminAccess,
+    name: Path<String>,
+    Json(values): Json<RecordValues>,
+) -> Result<HttpResponse> {
+    if !admin.can_write(&name) {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let result = web::block(move || controller::create_record(&db, &name, &values)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Created().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[put("/tables/{name}/{id}")]
+async fn update_record(
+    db: Data<Database>,
+    admin: AdminAccess,
+    path: Path<(String, String)>,
+    Json(values): Json<RecordValues>,
+) -> Result<HttpResponse> {
+    let (name, id) = path.into_inner();
+    if !admin.can_write(&name) {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let result = web::block(move || controller::update_record(&db, &name, &id, &values)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[delete("/tables/{name}/{id}")]
+async fn delete_record(
+    db: Data<Database>,
+    admin: AdminAccess,
+    path: Path<(String, String)>,
+) -> Result<HttpResponse> {
+    let (name, id) = path.into_inner();
+    if !admin.can_write(&name) {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let result = web::block(move || controller::delete_record(&db, &name, &id)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[get("/search")]
+async fn search(
+    db: Data<Database>,
+    _admin: AdminAccess,
+    admin_search: Option<Data<AdminSearch>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<HttpResponse> {
+    let admin_search = admin_search.unwrap_or_else(|| Data::new(AdminSearch::default()));
+
+    let results = web::block(move || controller::search(&db, &admin_search, &query)).await?;
+
+    Ok(HttpResponse::Ok().json(results))
+}
+
+#[cfg(feature = "plugin_auth")]
+fn auth_config(config: Option<Data<AuthConfig>>) -> Data<AuthConfig> {
+    config.unwrap_or_else(|| Data::new(AuthConfig::default()))
+}
+
+#[cfg(feature = "plugin_auth")]
+#[get("/users")]
+async fn users(
+    db: Data<Database>,
+    _admin: AdminAccess,
+    Query(query): Query<UsersQuery>,
+) -> Result<HttpResponse> {
+    let result = web::block(move || admin_users::list(&db, &query)).await?;
+
+    match result {
+        Ok(users) => Ok(HttpResponse::Ok().json(users)),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[get("/users/{id}")]
+async fn user(
+    db: Data<Database>,
+    _admin: AdminAccess,
+    config: Option<Data<AuthConfig>>,
+    user_id: Path<i32>,
+) -> Result<HttpResponse> {
+    let config = auth_config(config);
+    let user_id = user_id.into_inner();
+
+    let result = web::block(move || admin_users::read(&db, user_id, &config)).await?;
+
+    match result {
+        Ok(user) => Ok(HttpResponse::Ok().json(user)),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[post("/users/{id}/verify")]
+async fn verify_user_email(
+    db: Data<Database>,
+    admin: AdminAccess,
+    config: Option<Data<AuthConfig>>,
+    user_id: Path<i32>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let config = auth_config(config);
+    let user_id = user_id.into_inner();
+
+    let result = web::block(move || admin_users::verify_email(&db, user_id, &config)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[put("/users/{id}/password")]
+async fn set_user_password(
+    db: Data<Database>,
+    admin: AdminAccess,
+    config: Option<Data<AuthConfig>>,
+    user_id: Path<i32>,
+    Json(item): Json<PasswordInput>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let config = auth_config(config);
+    let user_id = user_id.into_inner();
+
+    let result =
+        web::block(move || admin_users::set_password(&db, user_id, &item, &config)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[post("/users/{id}/roles")]
+async fn assign_user_role(
+    db: Data<Database>,
+    admin: AdminAccess,
+    config: Option<Data<AuthConfig>>,
+    user_id: Path<i32>,
+    Json(item): Json<RoleInput>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let config = auth_config(config);
+    let user_id = user_id.into_inner();
+
+    let result =
+        web::block(move || admin_users::assign_role(&db, user_id, &item.role, &config)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[delete("/users/{id}/roles/{role}")]
+async fn unassign_user_role(
+    db: Data<Database>,
+    admin: AdminAccess,
+    path: Path<(i32, String)>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let (user_id, role) = path.into_inner();
+
+    let result = web::block(move || admin_users::unassign_role(&db, user_id, &role)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[post("/users/{id}/permissions")]
+async fn grant_user_permission(
+    db: Data<Database>,
+    admin: AdminAccess,
+    config: Option<Data<AuthConfig>>,
+    user_id: Path<i32>,
+    Json(item): Json<PermissionInput>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let config = auth_config(config);
+    let user_id = user_id.into_inner();
+
+    let result =
+        web::block(move || admin_users::grant_permission(&db, user_id, &item.permission, &config))
+            .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[delete("/users/{id}/permissions/{permission}")]
+async fn revoke_user_permission(
+    db: Data<Database>,
+    admin: AdminAccess,
+    path: Path<(i32, String)>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let (user_id, permission) = path.into_inner();
+
+    let result =
+        web::block(move || admin_users::revoke_permission(&db, user_id, &permission)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[post("/users/{id}/ban")]
+async fn ban_user(
+    db: Data<Database>,
+    admin: AdminAccess,
+    config: Option<Data<AuthConfig>>,
+    user_id: Path<i32>,
+    Json(item): Json<BanInput>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let config = auth_config(config);
+    let user_id = user_id.into_inner();
+    let banned_by = admin.user_id();
+
+    let result =
+        web::block(move || admin_users::ban(&db, user_id, &item, banned_by, &config)).await?;
+
+    match result {
+        Ok(ban) => Ok(HttpResponse::Ok().json(ban)),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[delete("/users/{id}/ban")]
+async fn unban_user(
+    db: Data<Database>,
+    admin: AdminAccess,
+    user_id: Path<i32>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let user_id = user_id.into_inner();
+
+    let result = web::block(move || admin_users::unban(&db, user_id)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[delete("/users/{id}/sessions")]
+async fn revoke_user_sessions(
+    db: Data<Database>,
+    admin: AdminAccess,
+    config: Option<Data<AuthConfig>>,
+    user_id: Path<i32>,
+) -> Result<HttpResponse> {
+    if !admin.can_write("users") {
+        return Ok(error_response(403, "Forbidden."));
+    }
+
+    let config = auth_config(config);
+    let user_id = user_id.into_inner();
+
+    let result = web::block(move || admin_users::revoke_sessions(&db, user_id, &config)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err((status_code, message)) => Ok(error_response(status_code, message)),
+    }
+}
+
+/// returns the endpoints of the admin portal
+pub fn endpoints(scope: Scope) -> Scope {
+    let scope = scope
+        .service(info)
+        .service(tables)
+        .service(schema)
+        .service(table)
+        .service(create_record)
+        .service(update_record)
+        .service(delete_record)
+        .service(search);
+
+    #[cfg(debug_assertions)]
+    let scope = scope.service(environment);
+
+    #[cfg(feature = "plugin_auth")]
+    let scope = scope
+        .service(users)
+        .service(user)
+        .service(verify_user_email)
+        .service(set_user_password)
+        .service(assign_user_role)
+        .service(unassign_user_role)
+        .service(grant_user_permission)
+        .service(revoke_user_permission)
+        .service(ban_user)
+        .service(unban_user)
+        .service(revoke_user_sessions);
+
+    scope
+}
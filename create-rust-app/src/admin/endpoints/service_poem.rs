@@ -0,0 +1,431 @@
+use poem::{
+    async_trait, get, handler,
+    http::StatusCode,
+    put,
+    web::{Data, Json, Path, Query},
+    Error, FromRequest, IntoResponse, Request, RequestBody, Result, Route,
+};
+#[cfg(feature = "plugin_auth")]
+use poem::{delete, post};
+use serde_json::json;
+
+use crate::admin::{
+    controller,
+    controller::{AdminInfo, RecordValues, SearchQuery, TableRowsQuery},
+    AdminSearch, SearchResult,
+};
+use crate::Database;
+#[cfg(debug_assertions)]
+use crate::{admin::controller::Environment, build_info::BuildInfo};
+#[cfg(feature = "plugin_auth")]
+use crate::{
+    admin::users as admin_users,
+    admin::users::{BanInput, PasswordInput, PermissionInput, RoleInput, UsersQuery},
+    auth::AuthConfig,
+};
+
+fn error_response(status_code: i32, message: &'static str) -> Error {
+    Error::from_string(
+        json!({ "message": message }).to_string(),
+        StatusCode::from_u16(status_code as u16).unwrap(),
+    )
+}
+
+/// extractor guarding the admin endpoints (and the development-only ones)
+///
+/// the request must be made by a user with the [`ADMIN_ROLE`](`crate::admin::ADMIN_ROLE`), unless
+/// the portal is [open](`crate::admin::is_open`)
+pub struct AdminAccess {
+    /// `None` when the portal is open
+    #[cfg(feature = "plugin_auth")]
+    auth: Option<crate::auth::Auth>,
+}
+
+impl AdminAccess {
+    /// whether the user can create, edit and delete [`table`]'s records, see
+    /// [`ADMIN_WRITE_PERMISSION`](`crate::admin::ADMIN_WRITE_PERMISSION`)
+    #[cfg(not(feature = "plugin_auth"))]
+    pub fn can_write(&self, _table: &str) -> bool {
+        true
+    }
+
+    /// whether the user can create, edit and delete [`table`]'s records, see
+    /// [`ADMIN_WRITE_PERMISSION`](`crate::admin::ADMIN_WRITE_PERMISSION`)
+    #[cfg(feature = "plugin_auth")]
+    pub fn can_write(&self, table: &str) -> bool {
+        let permission = crate::admin::ADMIN_WRITE_PERMISSION;
+
+        match &self.auth {
+            Some(auth) => {
+                auth.has_permission(permission.to_string())
+                    || auth.has_permission(format!("{permission}:{table}"))
+            }
+            None => true,
+        }
+    }
+
+    /// the admin making the request, `None` when the portal is [open](`crate::admin::is_open`)
+    #[cfg(feature = "plugin_auth")]
+    pub fn user_id(&self) -> Option<i32> {
+        self.auth.as_ref().map(|auth| auth.user_id)
+    }
+}
+
+#[async_trait]
+impl<'a> FromRequest<'a> for AdminAccess {
+    #[cfg(not(feature = "plugin_auth"))]
+    async fn from_request(_req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(AdminAccess {})
+    }
+
+    #[cfg(feature = "plugin_auth")]
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        if crate::admin::is_open() {
+            return Ok(AdminAccess { auth: None });
+        }
+
+        let auth = crate::auth::Auth::from_request(req, body).await?;
+
+        if !auth.has_role(crate::admin::ADMIN_ROLE.to_string()) {
+            return Err(error_response(403, "Forbidden."));
+        }
+
+        Ok(AdminAccess { auth: Some(auth) })
+    }
+}
+
+#[handler]
+async fn info(_admin: AdminAccess) -> Json<AdminInfo> {
+    Json(controller::info())
+}
+
+#[handler]
+async fn tables(db: Data<&Database>, _admin: AdminAccess) -> Result<impl IntoResponse> {
+    match controller::tables(db.0) {
+        Ok(tables) => Ok(Json(tables)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+async fn schema(db: Data<&Database>, _admin: AdminAccess) -> Result<impl IntoResponse> {
+    match controller::schema(db.0) {
+        Ok(schema) => Ok(Json(schema)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+async fn table(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+    Path(name): Path<String>,
+    Query(query): Query<TableRowsQuery>,
+) -> Result<impl IntoResponse> {
+    match controller::table(db.0, &name, &query) {
+        Ok(rows) => Ok(Json(rows)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+async fn create_record(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    Path(name): Path<String>,
+    Json(values): Json<RecordValues>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write(&name) {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match controller::create_record(db.0, &name, &values) {
+        Ok(()) => Ok(StatusCode::CREATED),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+async fn update_record(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    Path((name, id)): Path<(String, String)>,
+    Json(values): Json<RecordValues>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write(&name) {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match controller::update_record(db.0, &name, &id, &values) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+async fn delete_record(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write(&name) {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match controller::delete_record(db.0, &name, &id) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(debug_assertions)]
+#[handler]
+/// the app's build and (sanitized) environment (debug builds only), see [`controller::environment`]
+async fn environment(_admin: AdminAccess, build: Option<Data<&BuildInfo>>) -> Json<Environment> {
+    Json(controller::environment(build.map(|build| build.0)))
+}
+
+#[handler]
+async fn search(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+    admin_search: Option<Data<&AdminSearch>>,
+    Query(query): Query<SearchQuery>,
+) -> Json<Vec<SearchResult>> {
+    let results = match admin_search {
+        Some(admin_search) => controller::search(db.0, admin_search.0, &query),
+        None => controller::search(db.0, &AdminSearch::default(), &query),
+    };
+
+    Json(results)
+}
+
+#[cfg(feature = "plugin_auth")]
+fn auth_config(config: Option<Data<&AuthConfig>>) -> AuthConfig {
+    config.map(|config| config.0.clone()).unwrap_or_default()
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn users(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+    Query(query): Query<UsersQuery>,
+) -> Result<impl IntoResponse> {
+    match admin_users::list(db.0, &query) {
+        Ok(users) => Ok(Json(users)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn user(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+    config: Option<Data<&AuthConfig>>,
+    Path(user_id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    match admin_users::read(db.0, user_id, &auth_config(config)) {
+        Ok(user) => Ok(Json(user)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn verify_user_email(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    config: Option<Data<&AuthConfig>>,
+    Path(user_id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::verify_email(db.0, user_id, &auth_config(config)) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn set_user_password(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    config: Option<Data<&AuthConfig>>,
+    Path(user_id): Path<i32>,
+    Json(item): Json<PasswordInput>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::set_password(db.0, user_id, &item, &auth_config(config)) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn assign_user_role(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    config: Option<Data<&AuthConfig>>,
+    Path(user_id): Path<i32>,
+    Json(item): Json<RoleInput>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::assign_role(db.0, user_id, &item.role, &auth_config(config)) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn unassign_user_role(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    Path((user_id, role)): Path<(i32, String)>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::unassign_role(db.0, user_id, &role) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn grant_user_permission(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    config: Option<Data<&AuthConfig>>,
+    Path(user_id): Path<i32>,
+    Json(item): Json<PermissionInput>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::grant_permission(db.0, user_id, &item.permission, &auth_config(config)) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn revoke_user_permission(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    Path((user_id, permission)): Path<(i32, String)>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::revoke_permission(db.0, user_id, &permission) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn ban_user(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    config: Option<Data<&AuthConfig>>,
+    Path(user_id): Path<i32>,
+    Json(item): Json<BanInput>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::ban(db.0, user_id, &item, admin.user_id(), &auth_config(config)) {
+        Ok(ban) => Ok(Json(ban)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn unban_user(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    Path(user_id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::unban(db.0, user_id) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+async fn revoke_user_sessions(
+    db: Data<&Database>,
+    admin: AdminAccess,
+    config: Option<Data<&AuthConfig>>,
+    Path(user_id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    if !admin.can_write("users") {
+        return Err(error_response(403, "Forbidden."));
+    }
+
+    match admin_users::revoke_sessions(db.0, user_id, &auth_config(config)) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+/// returns the endpoints of the admin portal
+pub fn api() -> Route {
+    let route = Route::new()
+        .at("/info", get(info))
+        .at("/tables", get(tables))
+        .at("/schema", get(schema))
+        .at("/tables/:name", get(table).post(create_record))
+        .at(
+            "/tables/:name/:id",
+            put(update_record).delete(delete_record),
+        )
+        .at("/search", get(search));
+
+    #[cfg(debug_assertions)]
+    let route = route.at("/environment", get(environment));
+
+    #[cfg(feature = "plugin_auth")]
+    let route = route
+        .at("/users", get(users))
+        .at("/users/:id", get(user))
+        .at("/users/:id/verify", post(verify_user_email))
+        .at("/users/:id/password", put(set_user_password))
+        .at("/users/:id/roles", post(assign_user_role))
+        .at("/users/:id/roles/:role", delete(unassign_user_role))
+        .at("/users/:id/permissions", post(grant_user_permission))
+        .at(
+            "/users/:id/permissions/:permission",
+            delete(revoke_user_permission),
+        )
+        .at("/users/:id/ban", post(ban_user).delete(unban_user))
+        .at("/users/:id/sessions", delete(revoke_user_sessions));
+
+    route
+}
@@ -0,0 +1,57 @@
+//! Backend of the admin portal (served at `/admin`).
+//!
+//! The portal browses, searches and sorts the rows of every table of the database, and creates,
+//! edits and deletes records through forms generated from the tables' columns. Its endpoints don't
+//! take raw SQL, so it can be compiled into production builds with the `plugin_admin` feature
+//! (without it, the portal only exists in debug builds). The columns holding secrets (password
+//! hashes, tokens...) are redacted and read-only, see [`controller::SECRET_COLUMNS`].
+//!
+//! Every endpoint requires the [`ADMIN_ROLE`] from the auth plugin, and changing records also
+//! requires the [`ADMIN_WRITE_PERMISSION`] (for every table) or `admin:write:{table}` (for one of
+//! them). The development-only API (`/api/development`) is guarded the same way. In debug builds,
+//! the portal can be opened to everyone with `CRA_ADMIN_OPEN=true` (ex: to assign the first admin),
+//! and it's always open without the auth plugin, see [`is_open`].
+//!
+//! With the auth plugin, the portal also manages the app's users (see [`users`]): it searches them,
+//! verifies their email, resets their password, assigns their roles and permissions, bans them and
+//! logs them out. Those actions require the write permission for the `users` table.
+//!
+//! The development-only tools (the SQL console) live in the `dev` module, which is only mounted
+//! in debug builds. The portal hides them when [`controller::AdminInfo::dev_tools`] is `false`.
+//!
+//! ```rust,ignore
+//! // actix-web
+//! api_scope = api_scope.service(create_rust_app::admin::endpoints(web::scope("/admin")));
+//! // poem
+//! api_routes = api_routes.nest("/admin", create_rust_app::admin::api());
+//! ```
+pub mod controller;
+mod endpoints;
+mod search;
+#[cfg(feature = "plugin_auth")]
+pub mod users;
+
+pub use endpoints::*;
+pub use search::{AdminSearch, AdminSearchable, SearchResult, TableSearch};
+
+/// role required to use the admin portal, unless it's [open](`is_open`)
+///
+/// assign it with [`Role::assign`](`crate::auth::Role::assign`)
+pub const ADMIN_ROLE: &str = "admin";
+
+/// permission required to create, edit and delete records, for every table
+///
+/// `admin:write:{table}` grants it for a single table
+pub const ADMIN_WRITE_PERMISSION: &str = "admin:write";
+
+/// whether the portal (and the development-only API) lets every request through
+///
+/// it's only ever open in debug builds: without the auth plugin (there are no roles to check), or
+/// with `CRA_ADMIN_OPEN=true`
+pub fn is_open() -> bool {
+    cfg!(debug_assertions)
+        && (!cfg!(feature = "plugin_auth")
+            || std::env::var("CRA_ADMIN_OPEN")
+                .map(|open| open.eq_ignore_ascii_case("true"))
+                .unwrap_or(false))
+}
@@ -0,0 +1,182 @@
+//! Global search for the admin portal's command palette.
+//!
+//! A search fans out to every registered [`AdminSearchable`] resource. Users (auth plugin) and
+//! background jobs (tasks plugin) are registered by default; register your own models with
+//! [`AdminSearch::table`], or implement [`AdminSearchable`] for anything that isn't a plain table:
+//!
+//! ```rust,ignore
+//! let admin_search = create_rust_app::admin::AdminSearch::default()
+//!     .table("todos", "id", &["text"]);
+//!
+//! app = app.app_data(Data::new(admin_search.clone()));
+//! ```
+use crate::Connection;
+use diesel::{
+    query_dsl::RunQueryDsl,
+    sql_query,
+    sql_types::{BigInt, Nullable, Text},
+    QueryResult,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize, Clone, QueryableByName)]
+/// a record matching the search
+pub struct SearchResult {
+    /// the resource (table) the record belongs to
+    #[diesel(sql_type=Text)]
+    pub resource: String,
+    /// the record's primary key, as text
+    #[diesel(sql_type=Text)]
+    pub id: String,
+    /// what the palette shows for the record
+    #[diesel(sql_type=Text)]
+    pub title: String,
+    #[diesel(sql_type=Nullable<Text>)]
+    pub subtitle: Option<String>,
+}
+
+/// something the admin portal can search
+pub trait AdminSearchable: Send + Sync {
+    /// name of the resource, shown next to its results
+    fn resource(&self) -> &str;
+
+    /// at most [`limit`] records matching [`query`]
+    fn search(
+        &self,
+        db: &mut Connection,
+        query: &str,
+        limit: i64,
+    ) -> QueryResult<Vec<SearchResult>>;
+}
+
+/// searches the text [`columns`](`TableSearch::columns`) of a table, case insensitively
+pub struct TableSearch {
+    pub table: String,
+    pub primary_key: String,
+    /// the first column is used as the results' title, the second as their subtitle
+    pub columns: Vec<String>,
+}
+
+impl AdminSearchable for TableSearch {
+    fn resource(&self) -> &str {
+        &self.table
+    }
+
+    fn search(
+        &self,
+        db: &mut Connection,
+        query: &str,
+        limit: i64,
+    ) -> QueryResult<Vec<SearchResult>> {
+        if self.columns.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let conditions = self
+            .columns
+            .iter()
+            .map(|column| format!("LOWER(CAST({column} AS TEXT)) LIKE $1 ESCAPE '\\'"))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        let subtitle = match self.columns.get(1) {
+            Some(column) => format!("CAST({column} AS TEXT)"),
+            None => "NULL".to_string(),
+        };
+
+        let statement = format!(
+            "SELECT '{table}' AS resource, CAST({primary_key} AS TEXT) AS id, COALESCE(CAST({title} AS TEXT), '') AS title, {subtitle} AS subtitle FROM {table} WHERE {conditions} LIMIT $2",
+            table = self.table,
+            primary_key = self.primary_key,
+            title = self.columns[0],
+        );
+
+        sql_query(statement)
+            .bind::<Text, _>(like_pattern(query))
+            .bind::<BigInt, _>(limit)
+            .get_results::<SearchResult>(db)
+    }
+}
+
+#[derive(Clone)]
+/// the resources searched by the admin portal, see the [module docs](`self`)
+///
+/// register it in the app's data, the default one is used otherwise
+pub struct AdminSearch {
+    resources: Vec<Arc<dyn AdminSearchable>>,
+}
+
+impl Default for AdminSearch {
+    fn default() -> Self {
+        #[allow(unused_mut)]
+        let mut search = Self { resources: vec![] };
+
+        #[cfg(feature = "plugin_auth")]
+        {
+            search = search.table("users", "id", &["email"]);
+        }
+
+        #[cfg(feature = "plugin_tasks")]
+        {
+            search = search.table(
+                "fang_tasks",
+                "id",
+                &["task_type", "error_message", "metadata"],
+            );
+        }
+
+        search
+    }
+}
+
+impl AdminSearch {
+    /// searches [`columns`] of [`table`], see [`TableSearch`]
+    pub fn table(self, table: &str, primary_key: &str, columns: &[&str]) -> Self {
+        self.resource(TableSearch {
+            table: table.to_string(),
+            primary_key: primary_key.to_string(),
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+        })
+    }
+
+    pub fn resource(mut self, resource: impl AdminSearchable + 'static) -> Self {
+        self.resources.push(Arc::new(resource));
+        self
+    }
+
+    /// up to [`limit`] results from each resource
+    ///
+    /// a resource that fails (for example because its table doesn't exist yet) is skipped
+    pub fn search(&self, db: &mut Connection, query: &str, limit: i64) -> Vec<SearchResult> {
+        let query = query.trim();
+        if query.is_empty() {
+            return vec![];
+        }
+
+        let mut results = vec![];
+
+        for resource in &self.resources {
+            match resource.search(db, query, limit) {
+                Ok(found) => results.extend(found),
+                Err(err) => println!(
+                    "admin search: could not search '{}': {err}",
+                    resource.resource()
+                ),
+            }
+        }
+
+        results
+    }
+}
+
+/// `%query%`, lowercased, with `LIKE`'s wildcards escaped
+pub(super) fn like_pattern(query: &str) -> String {
+    let escaped = query
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{escaped}%")
+}
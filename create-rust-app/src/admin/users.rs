@@ -0,0 +1,475 @@
+//! The admin portal's user management (with the `plugin_auth` feature): searching users, verifying
+//! their email, resetting their password, managing their roles and permissions, banning them and
+//! revoking their sessions.
+//!
+//! Users are read from the auth plugin's `users` table (searches bypass custom
+//! [`UserStore`](`crate::auth::UserStore`)s), and updated through [`AuthConfig::stores`].
+use crate::auth::{
+    AuthConfig, Permission, Role, SessionRevocation, SessionRevokedEvent, User, UserBan,
+    UserBanChangeset, UserChangeset,
+};
+use crate::{Connection, Database};
+use serde::{Deserialize, Serialize};
+
+type StatusCode = i32;
+type Message = &'static str;
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize)]
+/// a user as the portal shows it, without their password hash
+pub struct AdminUser {
+    pub id: ID,
+    pub email: Option<String>,
+    pub activated: bool,
+    pub guest: bool,
+    pub roles: Vec<String>,
+    /// `None` unless the user is banned
+    pub ban: Option<UserBan>,
+    pub created_at: Utc,
+}
+
+#[derive(Debug, Serialize)]
+/// a page of [`AdminUser`]s, see [`list`]
+pub struct AdminUsers {
+    pub users: Vec<AdminUser>,
+    /// number of users matching the query, across all pages
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize)]
+/// everything the portal shows about a user, see [`read`]
+pub struct AdminUserDetails {
+    #[serde(flatten)]
+    pub user: AdminUser,
+    /// the user's permissions, granted directly or through their roles
+    pub permissions: Vec<Permission>,
+    /// number of active sessions
+    pub sessions: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UsersQuery {
+    /// part of the email, or the id, of the users to list (default: every user)
+    pub q: Option<String>,
+    /// zero-based (default: 0)
+    pub page: Option<i64>,
+    /// (default: 50, at most 100)
+    pub page_size: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PasswordInput {
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RoleInput {
+    pub role: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PermissionInput {
+    pub permission: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BanInput {
+    /// shown in the portal, never to the user
+    pub reason: Option<String>,
+}
+
+fn admin_user(db: &mut Connection, user: User) -> Result<AdminUser, (StatusCode, Message)> {
+    let roles = Role::fetch_all(db, user.id).map_err(|err| {
+        println!("{err:#?}");
+        (500, "Could not fetch the user's roles.")
+    })?;
+    let ban = UserBan::find_for_user(db, user.id).map_err(|err| {
+        println!("{err:#?}");
+        (500, "Could not fetch the user's ban.")
+    })?;
+
+    Ok(AdminUser {
+        id: user.id,
+        guest: user.is_guest(),
+        email: user.email,
+        activated: user.activated,
+        roles,
+        ban,
+        created_at: user.created_at,
+    })
+}
+
+fn read_user(
+    db: &mut Connection,
+    user_id: ID,
+    config: &AuthConfig,
+) -> Result<User, (StatusCode, Message)> {
+    match config.stores.users.read(db, user_id) {
+        Ok(user) => Ok(user),
+        Err(diesel::result::Error::NotFound) => Err((404, "User not found.")),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not fetch the user."))
+        }
+    }
+}
+
+/// ends every session of [`user_id`](`ID`), and revokes their access tokens
+fn end_sessions(
+    db: &mut Connection,
+    user_id: ID,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    if let Err(err) = config.stores.sessions.delete_all_for_user(db, user_id) {
+        println!("{err:#?}");
+        return Err((500, "Could not delete sessions."));
+    }
+
+    config.revoke_user(user_id);
+
+    config.hooks.session_revoked(
+        db,
+        SessionRevokedEvent {
+            user_id,
+            session_id: None,
+            reason: SessionRevocation::Admin,
+        },
+    );
+
+    Ok(())
+}
+
+/// /users
+///
+/// the users matching [`query`](`UsersQuery`), newest first
+///
+/// # Returns [`Result`]
+/// - Ok([`AdminUsers`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn list(db: &Database, query: &UsersQuery) -> Result<AdminUsers, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let q = query.q.as_deref().unwrap_or_default().trim();
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    let (users, total) = User::search(&mut db, q, page, page_size).map_err(|err| {
+        println!("{err:#?}");
+        (500, "Could not fetch users.")
+    })?;
+
+    let users = users
+        .into_iter()
+        .map(|user| admin_user(&mut db, user))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(AdminUsers { users, total })
+}
+
+/// /users/{id}
+///
+/// # Returns [`Result`]
+/// - Ok([`AdminUserDetails`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn read(
+    db: &Database,
+    user_id: ID,
+    config: &AuthConfig,
+) -> Result<AdminUserDetails, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let user = read_user(&mut db, user_id, config)?;
+    let user = admin_user(&mut db, user)?;
+
+    let permissions = Permission::fetch_all(&mut db, user_id).map_err(|err| {
+        println!("{err:#?}");
+        (500, "Could not fetch the user's permissions.")
+    })?;
+    let sessions = config
+        .stores
+        .sessions
+        .count_all(&mut db, user_id)
+        .map_err(|err| {
+            println!("{err:#?}");
+            (500, "Could not fetch the user's sessions.")
+        })?;
+
+    Ok(AdminUserDetails {
+        user,
+        permissions,
+        sessions,
+    })
+}
+
+/// /users/{id}/verify
+///
+/// activates the user's account, as if they had followed the link of their activation email
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn verify_email(
+    db: &Database,
+    user_id: ID,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let user = read_user(&mut db, user_id, config)?;
+    if user.activated {
+        return Ok(());
+    }
+
+    let updated = config.stores.users.update(
+        &mut db,
+        user_id,
+        &UserChangeset {
+            email: user.email,
+            hash_password: user.hash_password,
+            activated: true,
+        },
+    );
+
+    match updated {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not update the user."))
+        }
+    }
+}
+
+/// /users/{id}/password
+///
+/// replaces the user's password, and ends their sessions
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn set_password(
+    db: &Database,
+    user_id: ID,
+    item: &PasswordInput,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    if item.password.is_empty() {
+        return Err((400, "Missing password."));
+    }
+
+    let mut db = db.pool.get().unwrap();
+
+    let user = read_user(&mut db, user_id, config)?;
+    if user.is_guest() {
+        return Err((400, "Guests don't have a password."));
+    }
+
+    let updated = config.stores.users.update(
+        &mut db,
+        user_id,
+        &UserChangeset {
+            email: user.email,
+            hash_password: Some(config.password_hashing.hash(&item.password)),
+            activated: user.activated,
+        },
+    );
+
+    if let Err(err) = updated {
+        println!("{err:#?}");
+        return Err((500, "Could not update the user."));
+    }
+
+    end_sessions(&mut db, user_id, config)
+}
+
+/// /users/{id}/roles
+///
+/// takes effect the next time the user's access token is refreshed
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn assign_role(
+    db: &Database,
+    user_id: ID,
+    role: &str,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    if role.trim().is_empty() {
+        return Err((400, "Missing role."));
+    }
+
+    let mut db = db.pool.get().unwrap();
+
+    read_user(&mut db, user_id, config)?;
+
+    match Role::assign(&mut db, user_id, role.trim()) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not assign the role."))
+        }
+    }
+}
+
+/// /users/{id}/roles/{role}
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn unassign_role(db: &Database, user_id: ID, role: &str) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    match Role::unassign(&mut db, user_id, role) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not unassign the role."))
+        }
+    }
+}
+
+/// /users/{id}/permissions
+///
+/// takes effect the next time the user's access token is refreshed
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn grant_permission(
+    db: &Database,
+    user_id: ID,
+    permission: &str,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    if permission.trim().is_empty() {
+        return Err((400, "Missing permission."));
+    }
+
+    let mut db = db.pool.get().unwrap();
+
+    read_user(&mut db, user_id, config)?;
+
+    match Permission::grant_to_user(&mut db, user_id, permission.trim()) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not grant the permission."))
+        }
+    }
+}
+
+/// /users/{id}/permissions/{permission}
+///
+/// only revokes the permissions granted directly, not the ones coming from a role
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn revoke_permission(
+    db: &Database,
+    user_id: ID,
+    permission: &str,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    match Permission::revoke_from_user(&mut db, user_id, permission) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not revoke the permission."))
+        }
+    }
+}
+
+/// /users/{id}/ban
+///
+/// keeps the user from logging in, and ends their sessions; [`banned_by`](`ID`) is the admin
+/// banning them, when there's one
+///
+/// # Returns [`Result`]
+/// - Ok([`UserBan`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn ban(
+    db: &Database,
+    user_id: ID,
+    item: &BanInput,
+    banned_by: Option<ID>,
+    config: &AuthConfig,
+) -> Result<UserBan, (StatusCode, Message)> {
+    if banned_by == Some(user_id) {
+        return Err((400, "You can't ban yourself."));
+    }
+
+    let mut db = db.pool.get().unwrap();
+
+    read_user(&mut db, user_id, config)?;
+
+    match UserBan::find_for_user(&mut db, user_id) {
+        Ok(None) => {}
+        Ok(Some(_)) => return Err((409, "The user is already banned.")),
+        Err(err) => {
+            println!("{err:#?}");
+            return Err((500, "Could not ban the user."));
+        }
+    }
+
+    let ban = UserBan::create(
+        &mut db,
+        &UserBanChangeset {
+            user_id,
+            reason: item.reason.clone().filter(|reason| !reason.is_empty()),
+            banned_by,
+        },
+    )
+    .map_err(|err| {
+        println!("{err:#?}");
+        (500, "Could not ban the user.")
+    })?;
+
+    end_sessions(&mut db, user_id, config)?;
+
+    Ok(ban)
+}
+
+/// /users/{id}/ban
+///
+/// lets the user log in again
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn unban(db: &Database, user_id: ID) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    match UserBan::delete_for_user(&mut db, user_id) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not unban the user."))
+        }
+    }
+}
+
+/// /users/{id}/sessions
+///
+/// logs the user out of every device
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn revoke_sessions(
+    db: &Database,
+    user_id: ID,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    read_user(&mut db, user_id, config)?;
+
+    end_sessions(&mut db, user_id, config)
+}
@@ -0,0 +1,113 @@
+use actix_http::StatusCode;
+use actix_web::dev::Payload;
+use actix_web::web::{Data, Path, Query};
+use actix_web::{get, web, Error as AWError, FromRequest, HttpRequest, HttpResponse, Result};
+use futures::future::{ready, Ready};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::audit::{AuditEntry, AuditFilter, Auditor, ADMIN_PERMISSION};
+use crate::auth::Auth;
+use crate::Database;
+
+impl FromRequest for Auditor {
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = AWError;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let actor_id = Auth::from_request(req, payload)
+            .into_inner()
+            .ok()
+            .map(|auth| auth.user_id);
+        let ip_address = crate::client_ip(req);
+
+        ready(Ok(Auditor {
+            actor_id,
+            ip_address,
+        }))
+    }
+}
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../ endpoint
+pub struct ListQuery {
+    actor_id: Option<i32>,
+    action: Option<String>,
+    target_type: Option<String>,
+    target_id: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the .../ endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// returns the audit log's entries, most recent first (50 per page by default)
+#[get("")]
+async fn list(
+    db: Data<Database>,
+    auth: Auth,
+    Query(query): Query<ListQuery>,
+) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let filter = AuditFilter {
+        actor_id: query.actor_id,
+        action: query.action.filter(|action| !action.is_empty()),
+        target_type: query
+            .target_type
+            .filter(|target_type| !target_type.is_empty()),
+        target_id: query.target_id.filter(|target_id| !target_id.is_empty()),
+    };
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    let result =
+        web::block(move || AuditEntry::list(&mut db.get_connection(), &filter, page, page_size))
+            .await?;
+
+    match result {
+        Ok(entries) => Ok(HttpResponse::Ok().json(entries)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the audit log.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../{id} endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+#[get("/{id}")]
+async fn read(db: Data<Database>, auth: Auth, item_id: Path<i32>) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let item_id = item_id.into_inner();
+    let result = web::block(move || AuditEntry::read(&mut db.get_connection(), item_id)).await?;
+
+    match result {
+        Ok(entry) => Ok(HttpResponse::Ok().json(entry)),
+        Err(diesel::result::Error::NotFound) => Ok(error_response(
+            StatusCode::NOT_FOUND,
+            "Audit log entry not found.",
+        )),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the audit log.",
+        )),
+    }
+}
+
+/// returns the endpoints of the audit log's API
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(list).service(read)
+}
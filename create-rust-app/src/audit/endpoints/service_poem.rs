@@ -0,0 +1,123 @@
+use poem::{
+    async_trait, get, handler,
+    http::StatusCode,
+    web::{Data, Json, Path, Query},
+    Error, FromRequest, IntoResponse, Request, RequestBody, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::audit::{AuditEntry, AuditFilter, Auditor, ADMIN_PERMISSION};
+use crate::auth::Auth;
+use crate::Database;
+
+#[async_trait]
+impl<'a> FromRequest<'a> for Auditor {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let actor_id = Auth::from_request(req, body)
+            .await
+            .ok()
+            .map(|auth| auth.user_id);
+        let ip_address = crate::client_ip(req);
+
+        Ok(Auditor {
+            actor_id,
+            ip_address,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../ endpoint
+pub struct ListQuery {
+    actor_id: Option<i32>,
+    action: Option<String>,
+    target_type: Option<String>,
+    target_id: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for GET requests at the .../ endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`AuditEntry`]s, most recent first (50 per page by default)
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch the audit log."}
+async fn list(
+    db: Data<&Database>,
+    auth: Auth,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let filter = AuditFilter {
+        actor_id: query.actor_id,
+        action: query.action.filter(|action| !action.is_empty()),
+        target_type: query
+            .target_type
+            .filter(|target_type| !target_type.is_empty()),
+        target_id: query.target_id.filter(|target_id| !target_id.is_empty()),
+    };
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    AuditEntry::list(&mut db.get_connection(), &filter, page, page_size)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the audit log.",
+            )
+        })
+}
+
+#[handler]
+/// handler for GET requests at the .../:id endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : [`AuditEntry`]
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Audit log entry not found."}
+/// | 500 | Json payload : {"message": "Could not fetch the audit log."}
+async fn read(
+    db: Data<&Database>,
+    auth: Auth,
+    Path(item_id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    match AuditEntry::read(&mut db.get_connection(), item_id) {
+        Ok(entry) => Ok(Json(entry)),
+        Err(diesel::result::Error::NotFound) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            "Audit log entry not found.",
+        )),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the audit log.",
+        )),
+    }
+}
+
+/// returns the endpoints of the audit log's API
+pub fn api() -> Route {
+    Route::new().at("/", get(list)).at("/:id", get(read))
+}
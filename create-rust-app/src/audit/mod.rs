@@ -0,0 +1,297 @@
+//! An audit log of who did what in the app (`plugin_audit` feature), for accountability and
+//! support.
+//!
+//! Every entry of the `audit_log` table records the actor (the user who made the request, if any),
+//! the action, its target (a table and the id of a record in it), the changes made to the target
+//! and the client's IP address. Entries are recorded by the app's services, not automatically:
+//! the [`Auditor`] extractor knows who made the request, and an [`AuditEvent`] describes what they
+//! did.
+//!
+//! ```rust,ignore
+//! #[put("/{id}")]
+//! async fn update(db: Data<Database>, auditor: Auditor, item_id: Path<i32>, item: Json<UpdateTodo>) -> HttpResponse {
+//!     let mut db = db.pool.get().unwrap();
+//!     let item_id = item_id.into_inner();
+//!
+//!     let before = Todo::read(&mut db, item_id).ok();
+//!     let result = Todo::update(&mut db, item_id, &item);
+//!     if let Ok(after) = &result {
+//!         auditor.record(&mut db, AuditEvent::updated("todos", item_id, before.as_ref(), after)).ok();
+//!     }
+//!     // ...
+//! }
+//! ```
+//!
+//! Services generated by the CLI can record their changes this way (it asks when the resource is
+//! created).
+//!
+//! The API (mount it like the other services) requires the [`ADMIN_PERMISSION`], and backs the
+//! admin portal's "Audit log" page:
+//!
+//! - `GET /` lists the entries, filtered by `?actor_id=`, `?action=`, `?target_type=` and `?target_id=`
+//! - `GET /{id}` reads one
+use crate::diesel::*;
+use crate::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// permission required to use the API
+pub const ADMIN_PERMISSION: &str = "admin:audit";
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=audit_log)]
+pub struct AuditEntry {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    /// the user who did it, `None` for anonymous requests and the app's own jobs
+    pub actor_id: Option<ID>,
+    /// ex: `create`, `update`, `delete` or anything the app records
+    pub action: String,
+    /// what it was done to, usually a table's name
+    pub target_type: String,
+    /// the id of the record it was done to
+    pub target_id: Option<String>,
+    /// the changed fields, as JSON: `{"field": {"from": ..., "to": ...}}`, see [`diff`]
+    pub diff: Option<String>,
+    pub ip_address: Option<String>,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=audit_log)]
+pub struct AuditEntryChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub actor_id: Option<ID>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub diff: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+/// which entries [`AuditEntry::list`] returns, every field is optional
+pub struct AuditFilter {
+    pub actor_id: Option<ID>,
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+}
+
+impl AuditEntry {
+    /// Create an entry in [`db`](`Connection`)'s `audit_log` table using the data in [`item`](`AuditEntryChangeset`)
+    pub fn record(db: &mut Connection, item: &AuditEntryChangeset) -> QueryResult<Self> {
+        use schema::audit_log::dsl::*;
+
+        insert_into(audit_log)
+            .values(item)
+            .get_result::<AuditEntry>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `audit_log`
+    /// who's primary key matches [`item_id`](`ID`)
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use schema::audit_log::dsl::*;
+
+        audit_log.filter(id.eq(item_id)).first::<AuditEntry>(db)
+    }
+
+    /// the entries matching `filter`, most recent first
+    pub fn list(
+        db: &mut Connection,
+        filter: &AuditFilter,
+        page: i64,
+        page_size: i64,
+    ) -> QueryResult<Vec<Self>> {
+        use schema::audit_log::dsl::*;
+
+        let mut query = audit_log.into_boxed();
+        if let Some(item_actor_id) = filter.actor_id {
+            query = query.filter(actor_id.eq(item_actor_id));
+        }
+        if let Some(item_action) = &filter.action {
+            query = query.filter(action.eq(item_action.clone()));
+        }
+        if let Some(item_target_type) = &filter.target_type {
+            query = query.filter(target_type.eq(item_target_type.clone()));
+        }
+        if let Some(item_target_id) = &filter.target_id {
+            query = query.filter(target_id.eq(item_target_id.clone()));
+        }
+
+        query
+            .order((created_at.desc(), id.desc()))
+            .limit(page_size)
+            .offset(page * page_size)
+            .load::<AuditEntry>(db)
+    }
+
+    /// deletes the entries recorded before `before`, returns how many there were
+    ///
+    /// the log isn't pruned automatically, call this periodically (ex: from a scheduled task) to
+    /// keep it from growing forever
+    pub fn delete_before(db: &mut Connection, before: Utc) -> QueryResult<usize> {
+        use schema::audit_log::dsl::*;
+
+        diesel::delete(audit_log.filter(created_at.lt(before))).execute(db)
+    }
+}
+
+/// the fields that differ between `before` and `after`, as `{"field": {"from": ..., "to": ...}}`
+///
+/// `None` stands for a record that doesn't exist (yet, or anymore): every field of the other one
+/// is a change. Values that aren't objects are compared as a whole. Returns `None` when nothing
+/// changed.
+pub fn diff<T: Serialize>(before: Option<&T>, after: Option<&T>) -> Option<Value> {
+    let before = before
+        .and_then(|before| serde_json::to_value(before).ok())
+        .unwrap_or(Value::Null);
+    let after = after
+        .and_then(|after| serde_json::to_value(after).ok())
+        .unwrap_or(Value::Null);
+
+    let fields = |value: &Value| match value {
+        Value::Object(fields) => fields.clone(),
+        Value::Null => Map::new(),
+        value => Map::from_iter([("value".to_string(), value.clone())]),
+    };
+    let (before, after) = (fields(&before), fields(&after));
+
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let from = before.get(key).cloned().unwrap_or(Value::Null);
+        let to = after.get(key).cloned().unwrap_or(Value::Null);
+
+        if from != to && !changes.contains_key(key) {
+            changes.insert(key.clone(), json!({ "from": from, "to": to }));
+        }
+    }
+
+    (!changes.is_empty()).then_some(Value::Object(changes))
+}
+
+#[derive(Debug, Clone)]
+/// what was done, recorded by an [`Auditor`]
+///
+/// ```rust,ignore
+/// AuditEvent::new("export", "invoices").target(invoice_id)
+/// ```
+pub struct AuditEvent {
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub diff: Option<Value>,
+}
+
+impl AuditEvent {
+    pub fn new(action: impl Into<String>, target_type: impl Into<String>) -> Self {
+        Self {
+            action: action.into(),
+            target_type: target_type.into(),
+            target_id: None,
+            diff: None,
+        }
+    }
+
+    /// a `create` of `item`, its target is `item`'s `id` field (if it has one)
+    pub fn created<T: Serialize>(target_type: impl Into<String>, item: &T) -> Self {
+        let target_id = serde_json::to_value(item)
+            .ok()
+            .and_then(|item| match item.get("id")? {
+                Value::String(id) => Some(id.clone()),
+                Value::Null => None,
+                id => Some(id.to_string()),
+            });
+
+        Self {
+            target_id,
+            diff: diff(None, Some(item)),
+            ..Self::new("create", target_type)
+        }
+    }
+
+    /// an `update` of the record `target_id`, from `before` (`None` if it couldn't be read) to `after`
+    pub fn updated<T: Serialize>(
+        target_type: impl Into<String>,
+        target_id: impl ToString,
+        before: Option<&T>,
+        after: &T,
+    ) -> Self {
+        Self::new("update", target_type)
+            .target(target_id)
+            .changes(before, Some(after))
+    }
+
+    /// a `delete` of the record `target_id`, which was `before` (`None` if it couldn't be read)
+    pub fn deleted<T: Serialize>(
+        target_type: impl Into<String>,
+        target_id: impl ToString,
+        before: Option<&T>,
+    ) -> Self {
+        Self::new("delete", target_type)
+            .target(target_id)
+            .changes(before, None)
+    }
+
+    /// the record it was done to
+    pub fn target(mut self, target_id: impl ToString) -> Self {
+        self.target_id = Some(target_id.to_string());
+        self
+    }
+
+    /// the changes made to the target, see [`diff`]
+    pub fn changes<T: Serialize>(mut self, before: Option<&T>, after: Option<&T>) -> Self {
+        self.diff = diff(before, after);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// who did it: records [`AuditEvent`]s for the user making the request, from their IP address
+///
+/// it's an extractor for both backends; the actor is `None` for requests without a valid access
+/// token, they aren't rejected
+pub struct Auditor {
+    pub actor_id: Option<ID>,
+    pub ip_address: Option<String>,
+}
+
+impl Auditor {
+    /// records `event` in [`db`](`Connection`)'s `audit_log`
+    pub fn record(&self, db: &mut Connection, event: AuditEvent) -> QueryResult<AuditEntry> {
+        AuditEntry::record(
+            db,
+            &AuditEntryChangeset {
+                actor_id: self.actor_id,
+                action: event.action,
+                target_type: event.target_type,
+                target_id: event.target_id,
+                diff: event.diff.map(|diff| diff.to_string()),
+                ip_address: self.ip_address.clone(),
+            },
+        )
+    }
+}
@@ -0,0 +1,12 @@
+table! {
+  audit_log (id) {
+      id -> Integer,
+      actor_id -> Nullable<Integer>,
+      action -> Text,
+      target_type -> Text,
+      target_id -> Nullable<Text>,
+      diff -> Nullable<Text>,
+      ip_address -> Nullable<Text>,
+      created_at -> Timestamp,
+  }
+}
@@ -0,0 +1,361 @@
+//! Exporting and deleting a user's account and everything attached to it (GDPR's right of access and right to erasure).
+//!
+//! The auth plugin takes care of its own tables, and of the data kept by the other plugins
+//! (attachments on the user record, accepted policies, notifications, referrals). Data your app stores
+//! about users is covered by registering an [`AccountHook`] in the [`AuthConfig`](`super::AuthConfig`):
+//!
+//! ```rust,ignore
+//! use create_rust_app::auth::account::AccountHook;
+//! use create_rust_app::Connection;
+//! use diesel::QueryResult;
+//!
+//! struct Todos;
+//!
+//! impl AccountHook for Todos {
+//!     fn name(&self) -> &'static str {
+//!         "todos"
+//!     }
+//!
+//!     fn export(&self, db: &mut Connection, user_id: i32) -> QueryResult<serde_json::Value> {
+//!         Ok(serde_json::json!(Todo::all_for_owner(db, user_id)?))
+//!     }
+//!
+//!     fn delete(&self, db: &mut Connection, user_id: i32) -> QueryResult<()> {
+//!         Todo::delete_all_for_owner(db, user_id).map(|_| ())
+//!     }
+//!
+//!     fn merge(&self, db: &mut Connection, guest_id: i32, user_id: i32) -> QueryResult<()> {
+//!         Todo::transfer_all(db, guest_id, user_id).map(|_| ())
+//!     }
+//! }
+//!
+//! let auth_config = create_rust_app::auth::Auth::configure().account_hook(Todos);
+//! ```
+use super::permissions::{UserPermission, UserRole};
+use super::{
+    AuthConfig, Membership, Permission, Role, UserBan, UserChangeset, UserIdentity,
+    UserImpersonation, UserInvitation, ID,
+};
+use crate::Connection;
+use diesel::QueryResult;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// exports and deletes the data an app or plugin keeps about a user
+///
+/// both run inside the transaction of the export or deletion, returning an error aborts it
+pub trait AccountHook: Send + Sync {
+    /// key of this hook's data in [`AccountExport::data`], must be unique
+    fn name(&self) -> &'static str;
+
+    /// the data kept about [`user_id`](`ID`), it is included in their export as is
+    fn export(&self, _db: &mut Connection, _user_id: ID) -> QueryResult<Value> {
+        Ok(Value::Null)
+    }
+
+    /// removes (or anonymizes) the data kept about [`user_id`](`ID`), called before the user is deleted
+    fn delete(&self, _db: &mut Connection, _user_id: ID) -> QueryResult<()> {
+        Ok(())
+    }
+
+    /// moves the data kept about [`guest_id`](`ID`) to [`user_id`](`ID`), when a guest logs into
+    /// an existing account (see [`controller::merge_guest`](`super::controller::merge_guest`)).
+    /// What's left is removed by [`AccountHook::delete`] along with the guest.
+    fn merge(&self, _db: &mut Connection, _guest_id: ID, _user_id: ID) -> QueryResult<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default)]
+/// the [`AccountHook`]s registered by the app, in registration order
+pub struct AccountHooks(Vec<Arc<dyn AccountHook>>);
+
+impl AccountHooks {
+    pub fn push(&mut self, hook: impl AccountHook + 'static) {
+        self.0.push(Arc::new(hook));
+    }
+
+    /// the app's hooks followed by the ones of the enabled plugins
+    fn all(&self) -> Vec<Arc<dyn AccountHook>> {
+        let mut hooks = self.0.clone();
+
+        #[cfg(feature = "plugin_consent")]
+        hooks.push(Arc::new(crate::consent::ConsentAccountHook));
+        #[cfg(feature = "plugin_notifications")]
+        hooks.push(Arc::new(crate::notifications::NotificationsAccountHook));
+        #[cfg(feature = "plugin_referrals")]
+        hooks.push(Arc::new(crate::referrals::ReferralsAccountHook));
+        #[cfg(feature = "plugin_payments")]
+        hooks.push(Arc::new(crate::payments::PaymentsAccountHook));
+        #[cfg(feature = "plugin_webhooks")]
+        hooks.push(Arc::new(crate::webhooks::WebhooksAccountHook));
+
+        hooks
+    }
+}
+
+impl std::fmt::Debug for AccountHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|hook| hook.name()))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// what happens to the `users` row when an account is deleted
+pub enum AccountDeletion {
+    /// the row is deleted, tables referencing it must be cleaned up by an [`AccountHook`] or `ON DELETE CASCADE`
+    Delete,
+    /// the row is kept (so rows referencing it stay valid) but its email and password are replaced,
+    /// and the account is deactivated
+    Anonymize,
+    /// the row's `deleted_at` is set, and the user is logged out everywhere: the account can't be
+    /// used anymore, but it can be brought back with [`restore`] until [`purge`] deletes it (its
+    /// email is taken until then)
+    #[cfg(feature = "plugin_soft_delete")]
+    SoftDelete,
+}
+
+#[derive(Debug, Serialize)]
+/// everything stored about a user, see [`export`]
+pub struct AccountExport {
+    /// the `users` row, without the password hash
+    pub user: Value,
+    pub roles: Vec<String>,
+    pub permissions: Vec<Permission>,
+    /// the user's sessions, without their refresh tokens
+    pub sessions: Vec<Value>,
+    /// the accounts at OAuth providers linked to the user
+    pub identities: Vec<UserIdentity>,
+    /// the times an admin acted as this user
+    pub impersonations: Vec<UserImpersonation>,
+    /// the organizations the user is a member of
+    pub memberships: Vec<Membership>,
+    #[cfg(feature = "plugin_storage")]
+    /// files attached to the user record (`record_type = "users"`), with their blob's metadata
+    pub attachments: Vec<Value>,
+    /// the data of each [`AccountHook`], by [`AccountHook::name`]
+    pub data: BTreeMap<&'static str, Value>,
+}
+
+#[derive(Debug, Default)]
+/// what's left to do once the deletion's transaction was committed
+pub struct DeletedAccount {
+    #[cfg(feature = "plugin_storage")]
+    /// keys of the deleted attachment blobs, which still have to be removed from the bucket
+    pub storage_keys: Vec<String>,
+}
+
+/// the `record_type` of attachments on the user record
+#[cfg(feature = "plugin_storage")]
+pub const ATTACHMENT_RECORD_TYPE: &str = "users";
+
+/// collects everything stored about [`user_id`](`ID`), with the [`stores`](`AuthConfig::stores`)
+/// and [`account_hooks`](`AuthConfig::account_hooks`) of [`config`](`AuthConfig`)
+pub fn export(db: &mut Connection, user_id: ID, config: &AuthConfig) -> QueryResult<AccountExport> {
+    diesel::connection::Connection::transaction(db, |db| {
+        let user = config.stores.users.read(db, user_id)?;
+
+        let sessions = config
+            .stores
+            .sessions
+            .fetch_all_for_user(db, user_id)?
+            .iter()
+            .map(|session| redact(session, "refresh_token"))
+            .collect();
+
+        let mut data = BTreeMap::new();
+        for hook in config.account_hooks.all() {
+            data.insert(hook.name(), hook.export(db, user_id)?);
+        }
+
+        Ok(AccountExport {
+            user: redact(&user, "hash_password"),
+            roles: Role::fetch_all(db, user_id).map_err(to_query_error)?,
+            permissions: Permission::fetch_all(db, user_id).map_err(to_query_error)?,
+            sessions,
+            identities: UserIdentity::fetch_all_for_user(db, user_id)?,
+            impersonations: UserImpersonation::fetch_all_for_user(db, user_id)?,
+            memberships: Membership::fetch_all_for_user(db, user_id)?,
+            #[cfg(feature = "plugin_storage")]
+            attachments: export_attachments(db, user_id)?,
+            data,
+        })
+    })
+}
+
+/// deletes (or anonymizes, see [`AccountDeletion`]) [`user_id`](`ID`) and everything attached to them
+///
+/// the [`AccountHook`]s run first, then the auth plugin's own tables are cleaned up. Everything
+/// happens in a single transaction.
+pub fn delete(
+    db: &mut Connection,
+    user_id: ID,
+    config: &AuthConfig,
+    deletion: AccountDeletion,
+) -> QueryResult<DeletedAccount> {
+    // everything is kept, for the account to be restored
+    #[cfg(feature = "plugin_soft_delete")]
+    if deletion == AccountDeletion::SoftDelete {
+        return diesel::connection::Connection::transaction(db, |db| {
+            config.stores.sessions.delete_all_for_user(db, user_id)?;
+            <super::User as crate::soft_delete::SoftDelete>::soft_delete(db, user_id)?;
+
+            Ok(DeletedAccount::default())
+        });
+    }
+
+    diesel::connection::Connection::transaction(db, |db| {
+        for hook in config.account_hooks.all() {
+            hook.delete(db, user_id)?;
+        }
+
+        #[allow(unused_mut)]
+        let mut deleted = DeletedAccount::default();
+
+        #[cfg(feature = "plugin_storage")]
+        {
+            deleted.storage_keys = crate::storage::Attachment::delete_all_for_record(
+                db,
+                ATTACHMENT_RECORD_TYPE.to_string(),
+                user_id,
+            )?;
+        }
+
+        config.stores.sessions.delete_all_for_user(db, user_id)?;
+        UserIdentity::delete_all_for_user(db, user_id)?;
+        UserImpersonation::delete_all_for_user(db, user_id)?;
+        UserBan::delete_all_for_user(db, user_id)?;
+        UserInvitation::delete_all_by_inviter(db, user_id)?;
+        Membership::delete_all_for_user(db, user_id)?;
+        UserRole::delete_all(db, user_id)?;
+        UserPermission::delete_all(db, user_id)?;
+
+        match deletion {
+            AccountDeletion::Delete => {
+                config.stores.users.delete(db, user_id)?;
+            }
+            AccountDeletion::Anonymize => {
+                let user = config.stores.users.read(db, user_id)?;
+                let salt = super::controller::generate_salt();
+
+                config.stores.users.update(
+                    db,
+                    user_id,
+                    &UserChangeset {
+                        email: Some(format!("deleted-{}@anonymized.invalid", user.id)),
+                        hash_password: Some(
+                            argon2::hash_encoded(
+                                super::controller::generate_jti().as_bytes(),
+                                &salt,
+                                &argon2::Config::default(),
+                            )
+                            .unwrap(),
+                        ),
+                        activated: false,
+                    },
+                )?;
+            }
+            #[cfg(feature = "plugin_soft_delete")]
+            AccountDeletion::SoftDelete => unreachable!("soft-deleted accounts are kept"),
+        }
+
+        Ok(deleted)
+    })
+}
+
+/// brings back the account [`user_id`](`ID`), soft-deleted by [`AccountDeletion::SoftDelete`]
+///
+/// returns 0 when there's no such soft-deleted account
+#[cfg(feature = "plugin_soft_delete")]
+pub fn restore(db: &mut Connection, user_id: ID) -> QueryResult<usize> {
+    <super::User as crate::soft_delete::SoftDelete>::restore(db, user_id)
+}
+
+/// deletes the accounts soft-deleted before `deleted_before` (ex: 30 days ago) for good, like
+/// [`AccountDeletion::Delete`] does, returns the deletions' leftovers
+#[cfg(feature = "plugin_soft_delete")]
+pub fn purge(
+    db: &mut Connection,
+    deleted_before: crate::soft_delete::Utc,
+    config: &AuthConfig,
+) -> QueryResult<Vec<DeletedAccount>> {
+    use super::schema::users;
+    use diesel::prelude::*;
+
+    let user_ids = super::User::only_deleted()
+        .filter(users::deleted_at.lt(deleted_before))
+        .select(users::id)
+        .load::<ID>(db)?;
+
+    user_ids
+        .into_iter()
+        .map(|user_id| delete(db, user_id, config, AccountDeletion::Delete))
+        .collect()
+}
+
+/// moves the data of the guest [`guest_id`](`ID`) to [`user_id`](`ID`) with the [`AccountHook`]s,
+/// then deletes the guest, in a single transaction
+pub fn merge(
+    db: &mut Connection,
+    guest_id: ID,
+    user_id: ID,
+    config: &AuthConfig,
+) -> QueryResult<DeletedAccount> {
+    diesel::connection::Connection::transaction(db, |db| {
+        for hook in config.account_hooks.all() {
+            hook.merge(db, guest_id, user_id)?;
+        }
+
+        delete(db, guest_id, config, AccountDeletion::Delete)
+    })
+}
+
+#[cfg(feature = "plugin_storage")]
+fn export_attachments(db: &mut Connection, user_id: ID) -> QueryResult<Vec<Value>> {
+    use crate::storage::{Attachment, AttachmentBlob};
+
+    let attachments =
+        Attachment::find_all_for_record_type(db, ATTACHMENT_RECORD_TYPE.to_string(), user_id)?;
+    let blobs = AttachmentBlob::find_all_by_id(
+        db,
+        attachments
+            .iter()
+            .map(|attachment| attachment.blob_id)
+            .collect(),
+    )?;
+
+    Ok(attachments
+        .iter()
+        .map(|attachment| {
+            let blob = blobs.iter().find(|blob| blob.id == attachment.blob_id);
+
+            serde_json::json!({
+                "name": attachment.name,
+                "file_name": blob.map(|blob| blob.file_name.clone()),
+                "content_type": blob.and_then(|blob| blob.content_type.clone()),
+                "byte_size": blob.map(|blob| blob.byte_size),
+                "created_at": attachment.created_at,
+            })
+        })
+        .collect())
+}
+
+/// serializes [`item`] without its [`field`]
+fn redact<T: Serialize>(item: &T, field: &str) -> Value {
+    let mut value = serde_json::to_value(item).unwrap_or(Value::Null);
+    if let Some(object) = value.as_object_mut() {
+        object.remove(field);
+    }
+    value
+}
+
+fn to_query_error(err: anyhow::Error) -> diesel::result::Error {
+    match err.downcast::<diesel::result::Error>() {
+        Ok(err) => err,
+        Err(err) => diesel::result::Error::QueryBuilderError(err.into()),
+    }
+}
@@ -0,0 +1,163 @@
+//! Optional CAPTCHA verification on the login, register and forgot password endpoints, with
+//! [hCaptcha](https://www.hcaptcha.com/), [Cloudflare Turnstile](https://www.cloudflare.com/products/turnstile/)
+//! or [reCAPTCHA](https://www.google.com/recaptcha/) (v2, or v3 with a minimum score).
+//!
+//! Configure it in your `.env`:
+//!
+//! ```text
+//! CAPTCHA_PROVIDER=turnstile # hcaptcha, turnstile or recaptcha
+//! CAPTCHA_SITE_KEY=...
+//! CAPTCHA_SECRET_KEY=...
+//! ```
+//!
+//! The site key is served at `/api/auth/captcha`, where the frontend's `Captcha` component
+//! reads which widget to show. Its token is sent as `captcha_token` with the forms.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    HCaptcha,
+    Turnstile,
+    ReCaptcha,
+}
+
+impl CaptchaProvider {
+    fn verify_url(&self) -> &'static str {
+        match self {
+            CaptchaProvider::HCaptcha => "https://api.hcaptcha.com/siteverify",
+            CaptchaProvider::Turnstile => {
+                "https://challenges.cloudflare.com/turnstile/v0/siteverify"
+            }
+            CaptchaProvider::ReCaptcha => "https://www.google.com/recaptcha/api/siteverify",
+        }
+    }
+}
+
+#[derive(Clone)]
+/// a CAPTCHA provider and its keys
+pub struct Captcha {
+    pub provider: CaptchaProvider,
+    /// public key, used by the frontend's widget
+    pub site_key: String,
+    secret_key: String,
+    /// reCAPTCHA v3 only: the lowest score accepted (default: 0.5)
+    pub min_score: f64,
+}
+
+impl std::fmt::Debug for Captcha {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Captcha")
+            .field("provider", &self.provider)
+            .field("site_key", &self.site_key)
+            .field("min_score", &self.min_score)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// what the frontend needs to show the widget, see the .../captcha endpoint
+pub struct CaptchaSettings {
+    /// `null` when CAPTCHAs are disabled
+    pub provider: Option<CaptchaProvider>,
+    pub site_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VerifyResponse {
+    success: bool,
+    score: Option<f64>,
+}
+
+impl Captcha {
+    pub fn new(
+        provider: CaptchaProvider,
+        site_key: impl Into<String>,
+        secret_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider,
+            site_key: site_key.into(),
+            secret_key: secret_key.into(),
+            min_score: 0.5,
+        }
+    }
+
+    pub fn min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// reads `CAPTCHA_PROVIDER`, `CAPTCHA_SITE_KEY` and `CAPTCHA_SECRET_KEY`,
+    /// returns `None` (CAPTCHAs are disabled) when `CAPTCHA_PROVIDER` isn't set
+    pub fn from_env() -> Option<Self> {
+        let provider = match std::env::var("CAPTCHA_PROVIDER")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" => return None,
+            "hcaptcha" => CaptchaProvider::HCaptcha,
+            "turnstile" => CaptchaProvider::Turnstile,
+            "recaptcha" => CaptchaProvider::ReCaptcha,
+            other => panic!(
+                "unknown CAPTCHA_PROVIDER `{other}`, expected hcaptcha, turnstile or recaptcha"
+            ),
+        };
+
+        Some(Self::new(
+            provider,
+            std::env::var("CAPTCHA_SITE_KEY")
+                .expect("No CAPTCHA_SITE_KEY environment variable set!"),
+            std::env::var("CAPTCHA_SECRET_KEY")
+                .expect("No CAPTCHA_SECRET_KEY environment variable set!"),
+        ))
+    }
+
+    /// asks the provider whether `token` (from the widget) is valid
+    pub async fn verify(&self, token: Option<&str>, ip_address: Option<&str>) -> bool {
+        let token = match token {
+            Some(token) if !token.is_empty() => token,
+            _ => return false,
+        };
+
+        let mut form = vec![("secret", self.secret_key.as_str()), ("response", token)];
+        if let Some(ip_address) = ip_address {
+            form.push(("remoteip", ip_address));
+        }
+
+        let response = reqwest::Client::new()
+            .post(self.provider.verify_url())
+            .form(&form)
+            .send()
+            .await;
+
+        let body = match response {
+            Ok(response) => response.text().await.unwrap_or_default(),
+            Err(err) => {
+                println!("could not verify a CAPTCHA: {err}");
+                return false;
+            }
+        };
+
+        match serde_json::from_str::<VerifyResponse>(&body) {
+            Ok(VerifyResponse { success, score }) => {
+                success && score.map_or(true, |score| score >= self.min_score)
+            }
+            Err(err) => {
+                println!("could not verify a CAPTCHA: {err}");
+                false
+            }
+        }
+    }
+
+    /// the settings served to the frontend, see [`CaptchaSettings`]
+    pub fn settings(captcha: Option<&Captcha>) -> CaptchaSettings {
+        CaptchaSettings {
+            provider: captcha.map(|captcha| captcha.provider),
+            site_key: captcha.map(|captcha| captcha.site_key.clone()),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use super::account::{AccountDeletion, AccountHook, AccountHooks};
+use super::{
+    Auth, AuthHooks, AuthStores, Captcha, OAuthProvider, PasswordHashing, Profiles,
+    RateLimitedAction, RateLimiter, SessionStore, TokenRevocation, UserProfile, UserStore, ID,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// the `SameSite` attribute of the refresh token cookie
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+#[derive(Debug, Clone)]
+/// token lifetimes and refresh token cookie attributes used by the auth endpoints
+///
+/// build one with [`Auth::configure`] and register it next to the [`Database`](`crate::Database`)
+/// in your app's data. When none is registered, the defaults below are used.
+///
+/// ```rust,ignore
+/// let auth_config = create_rust_app::auth::Auth::configure()
+///     .access_ttl(std::time::Duration::from_secs(5 * 60))
+///     .refresh_ttl(std::time::Duration::from_secs(7 * 24 * 60 * 60))
+///     .cookie_same_site(create_rust_app::auth::SameSite::Lax);
+/// ```
+pub struct AuthConfig {
+    /// lifetime of access tokens, unless the client asks for a shorter or longer one when logging in (default: 15 minutes)
+    pub access_ttl: Duration,
+    /// lifetime of refresh tokens (default: 24 hours)
+    pub refresh_ttl: Duration,
+    /// (default: [`SameSite::Strict`])
+    pub cookie_same_site: SameSite,
+    /// (default: `true`)
+    pub cookie_secure: bool,
+    /// (default: none, the cookie is only sent to the host that set it)
+    pub cookie_domain: Option<String>,
+    /// (default: none, the cookie is sent for every path)
+    pub cookie_path: Option<String>,
+    /// whether the refresh tokens every refresh replaces are remembered, so that reusing one revokes
+    /// the session (default: `true`, or `false` if `LEGACY_REFRESH_TOKENS=true` is set)
+    ///
+    /// see [`UserSessionRotatedToken`](`super::UserSessionRotatedToken`)
+    pub rotate_refresh_tokens: bool,
+    /// export and clean up the app's own data about a user, see [`account`](`super::account`)
+    pub account_hooks: AccountHooks,
+    /// what the .../account endpoint does with the `users` row (default: [`AccountDeletion::Delete`])
+    pub account_deletion: AccountDeletion,
+    /// whether anyone can register, or only invitees (default: `true`)
+    ///
+    /// see [`controller::create_invitation`](`super::controller::create_invitation`)
+    pub open_registration: bool,
+    /// how long invite links stay valid (default: 7 days)
+    pub invitation_ttl: Duration,
+    /// whether visitors can start a session without an account (default: `false`)
+    ///
+    /// see [`controller::create_guest`](`super::controller::create_guest`)
+    pub guest_sessions: bool,
+    /// callbacks run when users register, log in, change their password or lose a session
+    pub hooks: AuthHooks,
+    /// limits attempts at logging in, registering and resetting passwords (default: [`RateLimiter::default`])
+    ///
+    /// `None` disables it
+    pub rate_limit: Option<RateLimiter>,
+    /// Argon2id parameters for new password hashes, older hashes are migrated when users log in
+    /// (default: [`PasswordHashing::default`])
+    pub password_hashing: PasswordHashing,
+    /// revokes access tokens on logout, and rejects revoked tokens (default: [`TokenRevocation::default`])
+    ///
+    /// `None` disables it: access tokens stay valid until they expire
+    pub revocation: Option<TokenRevocation>,
+    /// CAPTCHA checked by the login, register and forgot password endpoints (default: from the
+    /// `CAPTCHA_*` environment variables, see [`Captcha::from_env`])
+    ///
+    /// `None` disables it
+    pub captcha: Option<Captcha>,
+    /// providers users can link their account to (default: from the `GOOGLE_*` and `GITHUB_*`
+    /// environment variables, see [`OAuthProvider::from_env`])
+    pub oauth_providers: Vec<OAuthProvider>,
+    /// where users and sessions are kept (default: the plugin's `users` and `user_sessions` tables)
+    ///
+    /// see [`UserStore`] and [`SessionStore`]
+    pub stores: AuthStores,
+    /// the app's user fields, served by the .../profile endpoints (default: `None`, disabled)
+    ///
+    /// see [`UserProfile`] and [`AuthConfig::user_profile`]
+    pub profile: Option<Profiles>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            access_ttl: Duration::from_secs(15 * 60),
+            refresh_ttl: Duration::from_secs(24 * 60 * 60),
+            cookie_same_site: SameSite::Strict,
+            cookie_secure: true,
+            cookie_domain: None,
+            cookie_path: None,
+            rotate_refresh_tokens: !std::env::var("LEGACY_REFRESH_TOKENS")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            account_hooks: AccountHooks::default(),
+            account_deletion: AccountDeletion::Delete,
+            open_registration: true,
+            invitation_ttl: Duration::from_secs(7 * 24 * 60 * 60),
+            guest_sessions: false,
+            hooks: AuthHooks::default(),
+            rate_limit: Some(RateLimiter::default()),
+            password_hashing: PasswordHashing::default(),
+            revocation: Some(TokenRevocation::default()),
+            captcha: Captcha::from_env(),
+            oauth_providers: OAuthProvider::from_env(),
+            stores: AuthStores::default(),
+            profile: None,
+        }
+    }
+}
+
+impl AuthConfig {
+    pub fn access_ttl(mut self, ttl: Duration) -> Self {
+        self.access_ttl = ttl;
+        self
+    }
+
+    pub fn refresh_ttl(mut self, ttl: Duration) -> Self {
+        self.refresh_ttl = ttl;
+        self
+    }
+
+    pub fn cookie_same_site(mut self, same_site: SameSite) -> Self {
+        self.cookie_same_site = same_site;
+        self
+    }
+
+    /// browsers only accept `SameSite=None` cookies if they are also `secure`
+    pub fn cookie_secure(mut self, secure: bool) -> Self {
+        self.cookie_secure = secure;
+        self
+    }
+
+    pub fn cookie_domain(mut self, domain: impl Into<String>) -> Self {
+        self.cookie_domain = Some(domain.into());
+        self
+    }
+
+    pub fn cookie_path(mut self, path: impl Into<String>) -> Self {
+        self.cookie_path = Some(path.into());
+        self
+    }
+
+    pub fn rotate_refresh_tokens(mut self, rotate: bool) -> Self {
+        self.rotate_refresh_tokens = rotate;
+        self
+    }
+
+    pub fn account_hook(mut self, hook: impl AccountHook + 'static) -> Self {
+        self.account_hooks.push(hook);
+        self
+    }
+
+    pub fn account_deletion(mut self, deletion: AccountDeletion) -> Self {
+        self.account_deletion = deletion;
+        self
+    }
+
+    pub fn open_registration(mut self, open: bool) -> Self {
+        self.open_registration = open;
+        self
+    }
+
+    pub fn invitation_ttl(mut self, ttl: Duration) -> Self {
+        self.invitation_ttl = ttl;
+        self
+    }
+
+    pub fn guest_sessions(mut self, enabled: bool) -> Self {
+        self.guest_sessions = enabled;
+        self
+    }
+
+    pub fn hooks(mut self, hooks: AuthHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    pub fn rate_limit(mut self, rate_limit: Option<RateLimiter>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    pub fn password_hashing(mut self, password_hashing: PasswordHashing) -> Self {
+        self.password_hashing = password_hashing;
+        self
+    }
+
+    pub fn revocation(mut self, revocation: Option<TokenRevocation>) -> Self {
+        self.revocation = revocation;
+        self
+    }
+
+    /// revokes every access token issued to [`user_id`](`ID`) until now (ex: when banning them),
+    /// see [`TokenRevocation::revoke_user`]
+    ///
+    /// their sessions aren't touched: delete them too, or they can get new access tokens
+    pub fn revoke_user(&self, user_id: ID) {
+        if let Some(revocation) = &self.revocation {
+            // clients can ask for longer lived access tokens when logging in, assume they don't outlive their session
+            revocation.revoke_user(user_id, self.access_ttl.max(self.refresh_ttl));
+        }
+    }
+
+    pub fn captcha(mut self, captcha: Option<Captcha>) -> Self {
+        self.captcha = captcha;
+        self
+    }
+
+    /// adds [`provider`](`OAuthProvider`), replacing the one with the same name if any
+    pub fn oauth_provider(mut self, provider: OAuthProvider) -> Self {
+        self.oauth_providers
+            .retain(|existing| existing.name != provider.name);
+        self.oauth_providers.push(provider);
+        self
+    }
+
+    /// the [`OAuthProvider`] called `name`
+    pub(crate) fn find_oauth_provider(&self, name: &str) -> Option<&OAuthProvider> {
+        self.oauth_providers
+            .iter()
+            .find(|provider| provider.name == name)
+    }
+
+    pub fn user_store(mut self, store: impl UserStore + 'static) -> Self {
+        self.stores.users = Arc::new(store);
+        self
+    }
+
+    pub fn session_store(mut self, store: impl SessionStore + 'static) -> Self {
+        self.stores.sessions = Arc::new(store);
+        self
+    }
+
+    /// enables the .../profile endpoints for `P`, and includes it in account exports and deletions
+    pub fn user_profile<P: UserProfile>(mut self) -> Self {
+        self.profile = Some(Profiles::new::<P>());
+        self.account_hooks.push(Profiles::account_hook::<P>());
+        self
+    }
+
+    /// checks the CAPTCHA's `token` when [`captcha`](`AuthConfig::captcha`) is set
+    pub(crate) async fn verify_captcha(
+        &self,
+        token: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> bool {
+        match &self.captcha {
+            Some(captcha) => captcha.verify(token, ip_address).await,
+            None => true,
+        }
+    }
+
+    /// counts an attempt at [`action`](`RateLimitedAction`), see [`RateLimiter::check`]
+    pub(crate) fn check_rate_limit(
+        &self,
+        action: RateLimitedAction,
+        keys: &[Option<&str>],
+    ) -> Result<(), Duration> {
+        match &self.rate_limit {
+            Some(rate_limit) => rate_limit.check(action, keys),
+            None => Ok(()),
+        }
+    }
+
+    /// `exp` claim for an access token issued now
+    pub(crate) fn access_token_exp(&self, ttl: Option<i64>) -> usize {
+        let duration = match ttl {
+            Some(seconds) => chrono::Duration::seconds(std::cmp::max(seconds, 1)),
+            None => chrono::Duration::from_std(self.access_ttl).unwrap(),
+        };
+
+        (chrono::Utc::now() + duration).timestamp() as usize
+    }
+
+    /// `exp` claim for a refresh token issued now
+    pub(crate) fn refresh_token_exp(&self) -> usize {
+        (chrono::Utc::now() + chrono::Duration::from_std(self.refresh_ttl).unwrap()).timestamp()
+            as usize
+    }
+}
+
+impl Auth {
+    /// start building an [`AuthConfig`] from the defaults
+    pub fn configure() -> AuthConfig {
+        AuthConfig::default()
+    }
+}
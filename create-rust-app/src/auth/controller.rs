@@ -1,27 +1,35 @@
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-
+use crate::auth::account::{self, AccountExport, DeletedAccount};
+use crate::auth::permissions::{UserRole, UserRoleChangeset};
 use crate::auth::{
-    AccessTokenClaims, Auth, PaginationParams, Permission, Role, User, UserChangeset, UserSession,
-    UserSessionChangeset, UserSessionJson, UserSessionResponse, ID,
+    jwt, AccessTokenClaims, ActiveOrganization, Auth, AuthConfig, LoginEvent, MemberJson,
+    Membership, MembershipChangeset, OAuthProfile, Organization, OrganizationChangeset,
+    OrganizationJson, PaginationParams, PasswordChangedEvent, Permission, RegisteredEvent, Role,
+    SessionRevocation, SessionRevokedEvent, User, UserBan, UserChangeset, UserIdentity,
+    UserIdentityChangeset, UserIdentityJson, UserIdentityResponse, UserImpersonation,
+    UserImpersonationChangeset, UserInvitation, UserInvitationChangeset, UserSession,
+    UserSessionChangeset, UserSessionJson, UserSessionResponse, ID, ORGANIZATION_ADMIN,
+    ORGANIZATION_MEMBER, ORGANIZATION_OWNER,
 };
-use crate::{Database, Mailer};
+use crate::{Connection, Database, Mailer};
 
-use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
 pub const COOKIE_NAME: &str = "refresh_token";
-
-lazy_static! {
-    static ref ARGON_CONFIG: argon2::Config<'static> = argon2::Config {
-        variant: argon2::Variant::Argon2id,
-        version: argon2::Version::Version13,
-        secret: match std::env::var("SECRET_KEY") {
-            Ok(s) => Box::leak(s.into_boxed_str()).as_bytes(),
-            Err(_) => panic!("No SECRET_KEY environment variable set!"),
-        },
-        ..Default::default()
-    };
-}
+/// header clients without cookies (ex: desktop apps, see [`CLIENT_HEADER`]) send their refresh
+/// token in, instead of the [`COOKIE_NAME`] cookie
+pub const REFRESH_TOKEN_HEADER: &str = "X-Refresh-Token";
+/// header desktop clients send (`X-Auth-Client: desktop`) to get their refresh tokens in the
+/// bodies of the responses starting or refreshing a session, see [`session_json`]
+pub const CLIENT_HEADER: &str = "X-Auth-Client";
+/// permission required to act as another user, see [`impersonate`]
+pub const IMPERSONATE_PERMISSION: &str = "admin:impersonate";
+/// role of the administrators (like the admin plugin's), who can't be impersonated, see
+/// [`impersonate`]
+pub const ADMIN_ROLE: &str = "admin";
+/// permission required to invite users, see [`create_invitation`]
+pub const INVITE_PERMISSION: &str = "admin:invite";
+/// role of users without an account yet, see [`create_guest`]
+pub const GUEST_ROLE: &str = "guest";
 
 #[cfg(not(debug_assertions))]
 type Seconds = i64;
@@ -33,13 +41,16 @@ type Message = &'static str;
 /// Rust struct representing the Json body of
 /// POST requests to the .../login endpoint
 pub struct LoginInput {
-    email: String,
+    pub(crate) email: String,
     password: String,
     device: Option<String>,
     #[cfg(not(debug_assertions))]
     ttl: Option<Seconds>, // Seconds
     #[cfg(debug_assertions)]
     ttl: Option<i64>, // Seconds
+    /// the CAPTCHA widget's token, required when CAPTCHAs are enabled (see [`AuthConfig::captcha`])
+    #[serde(default)]
+    pub(crate) captcha_token: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -48,6 +59,9 @@ pub struct RefreshTokenClaims {
     exp: usize,
     sub: ID,
     token_type: String,
+    /// unique token id, ensures a rotated refresh token never equals the one it replaced
+    #[serde(default)]
+    jti: String,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -57,6 +71,12 @@ pub struct RefreshTokenClaims {
 pub struct RegisterInput {
     email: String,
     password: String,
+    /// the token from an invite link, required unless registration is open (see [`AuthConfig::open_registration`])
+    #[serde(default)]
+    invitation_token: Option<String>,
+    /// the CAPTCHA widget's token, required when CAPTCHAs are enabled (see [`AuthConfig::captcha`])
+    #[serde(default)]
+    pub(crate) captcha_token: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,7 +100,10 @@ pub struct ActivationInput {
 /// Rust struct representing the Json body of
 /// POST requests to the /forgot endpoint
 pub struct ForgotInput {
-    email: String,
+    pub(crate) email: String,
+    /// the CAPTCHA widget's token, required when CAPTCHAs are enabled (see [`AuthConfig::captcha`])
+    #[serde(default)]
+    pub(crate) captcha_token: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -100,6 +123,36 @@ pub struct ChangeInput {
     new_password: String,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /email/change endpoint
+pub struct EmailChangeInput {
+    /// the user's current password, to confirm the change
+    password: String,
+    new_email: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /email/confirm endpoint
+pub struct EmailConfirmInput {
+    email_change_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// claims of the token in the link sent to the new address, see [`change_email`]
+pub struct EmailChangeClaims {
+    exp: usize,
+    sub: ID,
+    token_type: String,
+    /// the address the user asked for
+    new_email: String,
+    /// the user's address when they asked, the token is refused once it changed
+    old_email: String,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
 /// Rust struct representing the Json body of
@@ -109,6 +162,125 @@ pub struct ResetInput {
     new_password: String,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /impersonate endpoint
+pub struct ImpersonateInput {
+    user_id: ID,
+    /// why the user is being impersonated, stored in the audit record
+    reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /invitations endpoint
+pub struct InvitationInput {
+    email: String,
+    /// roles assigned to the user once they register
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::IntoParams))]
+/// Rust struct representing the query of
+/// GET requests to the /invitation endpoint
+pub struct InvitationQuery {
+    invitation_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// claims of the token in invite links, `jti` matches the invitation's `token`
+pub struct InvitationClaims {
+    exp: usize,
+    sub: ID,
+    token_type: String,
+    jti: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /organizations endpoint
+pub struct OrganizationInput {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /organizations/{id}/members endpoint
+pub struct MemberInput {
+    email: String,
+    /// the member's role in the organization (default: "member")
+    role: Option<String>,
+}
+
+#[derive(Debug)]
+/// what [`add_member`] did
+pub enum AddedMember {
+    /// the user was already registered and is now a member
+    Added(Membership),
+    /// the user was invited to register, they join the organization once they do
+    Invited(UserInvitation),
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// DELETE requests to the /account endpoint
+pub struct DeleteAccountInput {
+    /// the user's current password, to confirm the deletion (ignored for guests)
+    password: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /guest/upgrade and /guest/merge endpoints
+pub struct GuestInput {
+    pub(crate) email: String,
+    password: String,
+    /// /guest/upgrade: the token from an invite link, required unless registration is open (see [`AuthConfig::open_registration`])
+    #[serde(default)]
+    invitation_token: Option<String>,
+    /// /guest/upgrade: the CAPTCHA widget's token, required when CAPTCHAs are enabled (see [`AuthConfig::captcha`])
+    #[serde(default)]
+    pub(crate) captcha_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /identities/{provider}/authorize endpoint
+pub struct AuthorizeIdentityInput {
+    /// where the provider sends the user back to, must be registered in the provider's settings
+    redirect_uri: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /identities endpoint
+pub struct IdentityInput {
+    /// the `code` query parameter the provider sent the user back with
+    code: String,
+    /// the `state` query parameter the provider sent the user back with
+    state: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// claims of the `state` passed through the provider while linking an account, see [`authorize_identity`]
+pub struct OAuthStateClaims {
+    exp: usize,
+    sub: ID,
+    token_type: String,
+    provider: String,
+    redirect_uri: String,
+}
+
 /// /sessions
 ///
 /// queries [`db`](`Database`) for all sessions owned by the User
@@ -116,6 +288,7 @@ pub struct ResetInput {
 ///
 /// breaks up the results of that query as defined by [`info`](`PaginationParams`)
 ///
+/// the session matching [`refresh_token`](`RefreshToken`) (the requesting device) is flagged as `current`
 ///
 /// # Returns [`Result`]
 /// - Ok([`UserSessionResponse`])
@@ -125,10 +298,12 @@ pub fn get_sessions(
     db: &Database,
     auth: &Auth,
     info: &PaginationParams,
+    refresh_token: Option<&'_ str>,
+    config: &AuthConfig,
 ) -> Result<UserSessionResponse, (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
-    let sessions = UserSession::read_all(&mut db, info, auth.user_id);
+    let sessions = config.stores.sessions.read_all(&mut db, info, auth.user_id);
 
     if sessions.is_err() {
         return Err((500, "Could not fetch sessions."));
@@ -141,6 +316,9 @@ pub fn get_sessions(
         let session_json = UserSessionJson {
             id: session.id,
             device: session.device,
+            ip_address: session.ip_address,
+            last_used_at: session.last_used_at,
+            current: refresh_token.map_or(false, |t| t == session.refresh_token),
             created_at: session.created_at,
             #[cfg(not(feature = "database_sqlite"))]
             updated_at: session.updated_at,
@@ -149,7 +327,7 @@ pub fn get_sessions(
         sessions_json.push(session_json);
     }
 
-    let num_sessions = UserSession::count_all(&mut db, auth.user_id);
+    let num_sessions = config.stores.sessions.count_all(&mut db, auth.user_id);
     if num_sessions.is_err() {
         return Err((500, "Could not fetch sessions."));
     }
@@ -177,10 +355,11 @@ pub fn destroy_session(
     db: &Database,
     auth: &Auth,
     item_id: ID,
+    config: &AuthConfig,
 ) -> Result<(), (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
-    let user_session = UserSession::read(&mut db, item_id);
+    let user_session = config.stores.sessions.read(&mut db, item_id);
 
     if user_session.is_err() {
         return Err((500, "Internal error."));
@@ -192,10 +371,24 @@ pub fn destroy_session(
         return Err((404, "Session not found."));
     }
 
-    if UserSession::delete(&mut db, user_session.id).is_err() {
+    if config
+        .stores
+        .sessions
+        .delete(&mut db, user_session.id)
+        .is_err()
+    {
         return Err((500, "Could not delete session."));
     }
 
+    config.hooks.session_revoked(
+        &mut db,
+        SessionRevokedEvent {
+            user_id: auth.user_id,
+            session_id: Some(user_session.id),
+            reason: SessionRevocation::Revoked,
+        },
+    );
+
     Ok(())
 }
 
@@ -207,13 +400,90 @@ pub fn destroy_session(
 /// # Returns [`Result`]
 /// - Ok(`()`)
 /// - Err([`StatusCode`], [`Message`])
-pub fn destroy_sessions(db: &Database, auth: &Auth) -> Result<(), (StatusCode, Message)> {
+pub fn destroy_sessions(
+    db: &Database,
+    auth: &Auth,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if config
+        .stores
+        .sessions
+        .delete_all_for_user(&mut db, auth.user_id)
+        .is_err()
+    {
+        return Err((500, "Could not delete sessions."));
+    }
+
+    config.revoke_user(auth.user_id);
+
+    config.hooks.session_revoked(
+        &mut db,
+        SessionRevokedEvent {
+            user_id: auth.user_id,
+            session_id: None,
+            reason: SessionRevocation::RevokedAll,
+        },
+    );
+
+    Ok(())
+}
+
+/// /sessions/others
+///
+/// destroys all entries in the `user_session` table in [`db`](`Database`) owned
+/// by the User associated with [`auth`](`Auth`), except for the session associated
+/// with [`refresh_token`](`RefreshToken`) (the requesting device)
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn destroy_other_sessions(
+    db: &Database,
+    auth: &Auth,
+    refresh_token: Option<&'_ str>,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
-    if UserSession::delete_all_for_user(&mut db, auth.user_id).is_err() {
+    if refresh_token.is_none() {
+        return Err((401, "Invalid session."));
+    }
+
+    let session = config
+        .stores
+        .sessions
+        .find_by_refresh_token(&mut db, refresh_token.unwrap());
+
+    if session.is_err() {
+        return Err((401, "Invalid session."));
+    }
+
+    let session = session.unwrap();
+
+    if session.user_id != auth.user_id {
+        return Err((401, "Invalid session."));
+    }
+
+    if config
+        .stores
+        .sessions
+        .delete_all_for_user_except(&mut db, auth.user_id, session.id)
+        .is_err()
+    {
         return Err((500, "Could not delete sessions."));
     }
 
+    config.hooks.session_revoked(
+        &mut db,
+        SessionRevokedEvent {
+            user_id: auth.user_id,
+            session_id: None,
+            reason: SessionRevocation::RevokedOthers,
+        },
+    );
+
     Ok(())
 }
 
@@ -225,14 +495,24 @@ type RefreshToken = String;
 /// creates a user session for the user associated with [`item`](`LoginInput`)
 /// in the request body (have the `content-type` header set to `application/json` and content that can be deserialized into [`LoginInput`])
 ///
+/// [`ip_address`] is the address of the client and is stored alongside the session
+///
+/// token lifetimes are taken from [`config`](`AuthConfig`)
+///
 /// # Returns [`Result`]
 /// - Ok([`AccessToken`], [`RefreshToken`])
 ///     - an access token that should be sent to the user in the response body,
-///     - a reset token that should be sent as a secure, http-only, and same_site=strict cookie.
+///     - a refresh token that should be sent as an http-only cookie (see [`AuthConfig`] for its other attributes).
 /// - Err([`StatusCode`], [`Message`])
+#[cfg_attr(
+    feature = "plugin_observability",
+    tracing::instrument(name = "auth.login", skip_all)
+)]
 pub fn login(
     db: &Database,
     item: &LoginInput,
+    ip_address: Option<String>,
+    config: &AuthConfig,
 ) -> Result<(AccessToken, RefreshToken), (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
@@ -247,7 +527,7 @@ pub fn login(
         }
     }
 
-    let user = User::find_by_email(&mut db, item.email.clone());
+    let user = config.stores.users.find_by_email(&mut db, &item.email);
 
     if user.is_err() {
         return Err((401, "Invalid credentials."));
@@ -259,90 +539,156 @@ pub fn login(
         return Err((400, "Account has not been activated."));
     }
 
-    let is_valid = argon2::verify_encoded_ext(
-        &user.hash_password,
-        item.password.as_bytes(),
-        ARGON_CONFIG.secret,
-        ARGON_CONFIG.ad,
-    )
-    .unwrap();
-
-    if !is_valid {
+    if !verify_password(&user, &item.password, config) {
         return Err((401, "Invalid credentials."));
     }
 
-    let permissions = Permission::fetch_all(&mut db, user.id);
+    // migrate hashes made with older parameters, now that we have the password
+    let needs_rehash = user
+        .hash_password
+        .as_deref()
+        .map_or(false, |hash| config.password_hashing.needs_rehash(hash));
+    let user = if needs_rehash {
+        let rehashed = config.stores.users.update(
+            &mut db,
+            user.id,
+            &UserChangeset {
+                email: user.email.clone(),
+                hash_password: Some(config.password_hashing.hash(&item.password)),
+                activated: user.activated,
+            },
+        );
+
+        match rehashed {
+            Ok(rehashed) => rehashed,
+            Err(err) => {
+                println!(
+                    "could not rehash the password of user {}: {err:#?}",
+                    user.id
+                );
+                user
+            }
+        }
+    } else {
+        user
+    };
+
+    let (access_token, refresh_token, session_id) = start_session(
+        &mut db,
+        user.id,
+        device.clone(),
+        ip_address.clone(),
+        item.ttl,
+        config,
+    )?;
+
+    config.hooks.login(
+        &mut db,
+        LoginEvent {
+            user,
+            session_id,
+            device,
+            ip_address,
+        },
+    );
+
+    Ok((access_token, refresh_token))
+}
+
+/// creates a session for [`user_id`](`ID`), returns its access and refresh tokens, and its id
+///
+/// fails with a 403 if the user is banned, see [`UserBan`]
+fn start_session(
+    db: &mut Connection,
+    user_id: ID,
+    device: Option<String>,
+    ip_address: Option<String>,
+    ttl: Option<i64>,
+    config: &AuthConfig,
+) -> Result<(AccessToken, RefreshToken, ID), (StatusCode, Message)> {
+    match UserBan::find_for_user(db, user_id) {
+        Ok(None) => {}
+        Ok(Some(_)) => return Err((403, "This account has been banned.")),
+        Err(err) => {
+            println!("{err:#?}");
+            return Err((500, "An internal server error occurred."));
+        }
+    }
+
+    let permissions = Permission::fetch_all(db, user_id);
     if permissions.is_err() {
         println!("{:#?}", permissions.err());
         return Err((500, "An internal server error occurred."));
     }
     let permissions = permissions.unwrap();
 
-    let roles = Role::fetch_all(&mut db, user.id);
+    let roles = Role::fetch_all(db, user_id);
     if roles.is_err() {
         println!("{:#?}", roles.err());
         return Err((500, "An internal server error occurred."));
     }
     let roles = roles.unwrap();
 
-    let access_token_duration = chrono::Duration::seconds(if item.ttl.is_some() {
-        std::cmp::max(item.ttl.unwrap(), 1)
-    } else {
-        /* 15 minutes */
-        15 * 60
-    });
-
     let access_token_claims = AccessTokenClaims {
-        exp: (chrono::Utc::now() + access_token_duration).timestamp() as usize,
-        sub: user.id,
+        exp: config.access_token_exp(ttl),
+        iat: chrono::Utc::now().timestamp() as usize,
+        jti: generate_jti(),
+        sub: user_id,
         token_type: "access_token".to_string(),
         roles,
         permissions,
+        impersonator: None,
+        organization: active_organization(db, user_id)?,
     };
 
     let refresh_token_claims = RefreshTokenClaims {
-        exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp() as usize,
-        sub: user.id,
+        exp: config.refresh_token_exp(),
+        sub: user_id,
         token_type: "refresh_token".to_string(),
+        jti: generate_jti(),
     };
 
-    let access_token = encode(
-        &Header::default(),
-        &access_token_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
-
-    let refresh_token = encode(
-        &Header::default(),
-        &refresh_token_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
-
-    let user_session = UserSession::create(
-        &mut db,
+    let access_token = jwt::encode_token(&access_token_claims).unwrap();
+
+    let refresh_token = jwt::encode_token(&refresh_token_claims).unwrap();
+
+    let user_session = config.stores.sessions.create(
+        db,
         &UserSessionChangeset {
-            user_id: user.id,
+            user_id,
             refresh_token: refresh_token.clone(),
             device,
+            ip_address,
         },
     );
 
-    if user_session.is_err() {
-        return Err((500, "Could not create a session."));
+    match user_session {
+        Ok(user_session) => Ok((access_token, refresh_token, user_session.id)),
+        Err(_) => Err((500, "Could not create a session.")),
     }
+}
 
-    Ok((access_token, refresh_token))
+/// whether `password` is [`user`](`User`)'s (guests have none)
+fn verify_password(user: &User, password: &str, config: &AuthConfig) -> bool {
+    user.hash_password
+        .as_deref()
+        .map_or(false, |hash| config.password_hashing.verify(hash, password))
 }
 
 /// /logout
 /// If this is successful, delete the cookie storing the refresh token
 ///
+/// the access token used to log out, if any, is revoked (see [`TokenRevocation`](`super::TokenRevocation`))
+///
 /// # Returns [`Result`]
 /// - Ok(`()`)
 /// - Err([`StatusCode`], [`Message`])
-pub fn logout(db: &Database, refresh_token: Option<&'_ str>) -> Result<(), (StatusCode, Message)> {
+pub fn logout(
+    db: &Database,
+    auth: Option<&Auth>,
+    refresh_token: Option<&'_ str>,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
     if refresh_token.is_none() {
@@ -351,7 +697,10 @@ pub fn logout(db: &Database, refresh_token: Option<&'_ str>) -> Result<(), (Stat
 
     let refresh_token = refresh_token.unwrap();
 
-    let session = UserSession::find_by_refresh_token(&mut db, refresh_token);
+    let session = config
+        .stores
+        .sessions
+        .find_by_refresh_token(&mut db, refresh_token);
 
     if session.is_err() {
         return Err((401, "Invalid session."));
@@ -359,12 +708,25 @@ pub fn logout(db: &Database, refresh_token: Option<&'_ str>) -> Result<(), (Stat
 
     let session = session.unwrap();
 
-    let is_deleted = UserSession::delete(&mut db, session.id);
+    let is_deleted = config.stores.sessions.delete(&mut db, session.id);
 
     if is_deleted.is_err() {
         return Err((401, "Could not delete session."));
     }
 
+    if let (Some(auth), Some(revocation)) = (auth, &config.revocation) {
+        revocation.revoke_token(&auth.jti, auth.exp);
+    }
+
+    config.hooks.session_revoked(
+        &mut db,
+        SessionRevokedEvent {
+            user_id: session.user_id,
+            session_id: Some(session.id),
+            reason: SessionRevocation::Logout,
+        },
+    );
+
     Ok(())
 }
 
@@ -372,14 +734,26 @@ pub fn logout(db: &Database, refresh_token: Option<&'_ str>) -> Result<(), (Stat
 ///
 /// refreshes the user session associated with the clients refresh_token cookie
 ///
+/// [`ip_address`] is the address of the client, the session's last known address is updated with it
+///
+/// the refresh token is replaced with a new one and, unless [`config`](`AuthConfig`) disables
+/// rotation, presenting an already-rotated token revokes the whole session (see
+/// [`SessionStore::rotate`](`super::SessionStore::rotate`))
+///
 /// # Returns [`Result`]
 /// - Ok([`AccessToken`], [`RefreshToken`])
 ///     - an access token that should be sent to the user in the response body,
-///     - a reset token that should be sent as a secure, http-only, and same_site=strict cookie.
+///     - a refresh token that should be sent as an http-only cookie (see [`AuthConfig`] for its other attributes).
 /// - Err([`StatusCode`], [`Message`])
+#[cfg_attr(
+    feature = "plugin_observability",
+    tracing::instrument(name = "auth.refresh", skip_all)
+)]
 pub fn refresh(
     db: &Database,
     refresh_token_str: Option<&'_ str>,
+    ip_address: Option<String>,
+    config: &AuthConfig,
 ) -> Result<(AccessToken, RefreshToken), (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
@@ -389,11 +763,7 @@ pub fn refresh(
 
     let refresh_token_str = refresh_token_str.unwrap();
 
-    let refresh_token = decode::<RefreshTokenClaims>(
-        refresh_token_str,
-        &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-        &Validation::default(),
-    );
+    let refresh_token = jwt::decode_token::<RefreshTokenClaims>(refresh_token_str);
 
     if refresh_token.is_err() {
         return Err((401, "Invalid token."));
@@ -409,9 +779,40 @@ pub fn refresh(
         return Err((401, "Invalid token."));
     }
 
-    let session = UserSession::find_by_refresh_token(&mut db, refresh_token_str);
+    let session = config
+        .stores
+        .sessions
+        .find_by_refresh_token(&mut db, refresh_token_str);
 
     if session.is_err() {
+        // a rotated token being presented again means it was most likely stolen,
+        // revoke the whole session family so neither party can keep using it
+        if let Ok(rotated_session_id) = config
+            .stores
+            .sessions
+            .find_by_rotated_token(&mut db, refresh_token_str)
+        {
+            if config
+                .stores
+                .sessions
+                .delete(&mut db, rotated_session_id)
+                .is_err()
+            {
+                return Err((500, "Could not revoke the session."));
+            }
+
+            config.hooks.session_revoked(
+                &mut db,
+                SessionRevokedEvent {
+                    user_id: refresh_token.claims.sub,
+                    session_id: Some(rotated_session_id),
+                    reason: SessionRevocation::RefreshTokenReuse,
+                },
+            );
+
+            return Err((401, "Refresh token reuse detected."));
+        }
+
         return Err((401, "Invalid session."));
     }
 
@@ -430,49 +831,58 @@ pub fn refresh(
     let roles = roles.unwrap();
 
     let access_token_claims = AccessTokenClaims {
-        exp: (chrono::Utc::now() + chrono::Duration::minutes(15)).timestamp() as usize,
+        exp: config.access_token_exp(None),
+        iat: chrono::Utc::now().timestamp() as usize,
+        jti: generate_jti(),
         sub: session.user_id,
         token_type: "access_token".to_string(),
         roles,
         permissions,
+        impersonator: None,
+        organization: active_organization(&mut db, session.user_id)?,
     };
 
+    let access_token = jwt::encode_token(&access_token_claims).unwrap();
+
+    if config.stores.sessions.touch(&mut db, session.id).is_err() {
+        return Err((500, "Could not update the session."));
+    }
+
     let refresh_token_claims = RefreshTokenClaims {
-        exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp() as usize,
+        exp: config.refresh_token_exp(),
         sub: session.user_id,
         token_type: "refresh_token".to_string(),
+        jti: generate_jti(),
     };
 
-    let access_token = encode(
-        &Header::default(),
-        &access_token_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
-
-    let refresh_token_str = encode(
-        &Header::default(),
-        &refresh_token_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
-
-    // update session with the new refresh token
-    let session_update = UserSession::update(
-        &mut db,
-        session.id,
-        &UserSessionChangeset {
-            user_id: session.user_id,
-            refresh_token: refresh_token_str.clone(),
-            device: session.device,
-        },
-    );
+    let new_refresh_token = jwt::encode_token(&refresh_token_claims).unwrap();
+
+    let session_changeset = UserSessionChangeset {
+        user_id: session.user_id,
+        refresh_token: new_refresh_token.clone(),
+        device: session.device,
+        ip_address: ip_address.or(session.ip_address),
+    };
+
+    // update session with the new refresh token, remembering the one we replace (when rotating) so
+    // we can detect if it's reused
+    let session_update = if config.rotate_refresh_tokens {
+        config
+            .stores
+            .sessions
+            .rotate(&mut db, session.id, refresh_token_str, &session_changeset)
+    } else {
+        config
+            .stores
+            .sessions
+            .update(&mut db, session.id, &session_changeset)
+    };
 
     if session_update.is_err() {
         return Err((500, "Could not update the session."));
     }
 
-    Ok((access_token, refresh_token_str))
+    Ok((access_token, new_refresh_token))
 }
 
 /// /register
@@ -483,38 +893,90 @@ pub fn refresh(
 /// that contains a unique link that allows the recipient to activate the account associated with
 /// that email address
 ///
+/// when [`item.invitation_token`](`RegisterInput`) is set, the invite link already proved the
+/// email address: the User is activated right away and given the invitation's roles (and, for
+/// invitations to an organization, made a member of it). Without one,
+/// registering fails unless [`config.open_registration`](`AuthConfig::open_registration`) is set.
+///
 /// # Returns [`Result`]
-/// - Ok(`()`)
+/// - Ok([`User`])
+///     - the new User, `activated` if they registered with an invitation
 /// - Err([`StatusCode`], [`Message`])
+#[cfg_attr(
+    feature = "plugin_observability",
+    tracing::instrument(name = "auth.register", skip_all)
+)]
 pub fn register(
     db: &Database,
     item: &RegisterInput,
     mailer: &Mailer,
-) -> Result<(), (StatusCode, Message)> {
+    config: &AuthConfig,
+) -> Result<User, (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
-    let user = User::find_by_email(&mut db, item.email.to_string());
+    let invitation = match &item.invitation_token {
+        Some(token) => Some(find_invitation(&mut db, token)?),
+        None if !config.open_registration => {
+            return Err((403, "Registration is by invitation only."))
+        }
+        None => None,
+    };
+
+    if let Some(invitation) = &invitation {
+        if !invitation.email.eq_ignore_ascii_case(&item.email) {
+            return Err((400, "The invitation was sent to another email address."));
+        }
+    }
+
+    let user = config.stores.users.find_by_email(&mut db, &item.email);
 
     if let Ok(user) = user {
         if !user.activated {
-            User::delete(&mut db, user.id).unwrap();
+            config.stores.users.delete(&mut db, user.id).unwrap();
         } else {
             return Err((400, "Already registered."));
         }
     }
 
-    let salt = generate_salt();
-    let hash = argon2::hash_encoded(item.password.as_bytes(), &salt, &ARGON_CONFIG).unwrap();
+    let hash = config.password_hashing.hash(&item.password);
 
-    let user = User::create(
-        &mut db,
-        &UserChangeset {
-            activated: false,
-            email: item.email.clone(),
-            hash_password: hash,
-        },
-    )
-    .unwrap();
+    let item = UserChangeset {
+        activated: invitation.is_some(),
+        email: Some(item.email.clone()),
+        hash_password: Some(hash),
+    };
+
+    if let Some(invitation) = invitation {
+        let user = diesel::connection::Connection::transaction(&mut db, |db| {
+            let user = config.stores.users.create(db, &item)?;
+
+            accept_invitation(db, user.id, &invitation)?;
+
+            Ok::<User, diesel::result::Error>(user)
+        });
+
+        return match user {
+            Ok(user) => {
+                mailer
+                    .templates
+                    .send_activated(mailer, user.email.as_deref().unwrap_or_default());
+                config.hooks.registered(
+                    &mut db,
+                    RegisteredEvent {
+                        user: user.clone(),
+                        invitation: Some(invitation),
+                    },
+                );
+                Ok(user)
+            }
+            Err(err) => {
+                println!("{err:#?}");
+                Err((500, "Could not register."))
+            }
+        };
+    }
+
+    let user = config.stores.users.create(&mut db, &item).unwrap();
 
     let registration_claims = RegistrationClaims {
         exp: (chrono::Utc::now() + chrono::Duration::days(30)).timestamp() as usize,
@@ -522,16 +984,55 @@ pub fn register(
         token_type: "activation_token".to_string(),
     };
 
-    let token = encode(
-        &Header::default(),
-        &registration_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
+    let token = jwt::encode_token(&registration_claims).unwrap();
 
-    mailer
-        .templates
-        .send_register(mailer, &user.email, &format!("activate?token={token}"));
+    mailer.templates.send_register(
+        mailer,
+        user.email.as_deref().unwrap_or_default(),
+        &format!("activate?token={token}"),
+    );
+
+    config.hooks.registered(
+        &mut db,
+        RegisteredEvent {
+            user: user.clone(),
+            invitation: None,
+        },
+    );
+
+    Ok(user)
+}
+
+/// grants the roles and organization membership of [`invitation`](`UserInvitation`) to the user
+/// with [`user_id`](`ID`) and marks it accepted, for [`register`] and [`upgrade_guest`]
+fn accept_invitation(
+    db: &mut Connection,
+    user_id: ID,
+    invitation: &UserInvitation,
+) -> diesel::QueryResult<()> {
+    UserRole::create_many(
+        db,
+        invitation
+            .roles()
+            .into_iter()
+            .map(|role| UserRoleChangeset { user_id, role })
+            .collect(),
+    )?;
+    if let Some(organization_id) = invitation.organization_id {
+        Membership::create(
+            db,
+            &MembershipChangeset {
+                organization_id,
+                user_id,
+                role: invitation
+                    .organization_role
+                    .clone()
+                    .unwrap_or_else(|| ORGANIZATION_MEMBER.to_string()),
+            },
+        )?;
+        Membership::activate(db, organization_id, user_id)?;
+    }
+    UserInvitation::accept(db, invitation.id)?;
 
     Ok(())
 }
@@ -547,14 +1048,11 @@ pub fn activate(
     db: &Database,
     item: &ActivationInput,
     mailer: &Mailer,
+    config: &AuthConfig,
 ) -> Result<(), (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
-    let token = decode::<RegistrationClaims>(
-        &item.activation_token,
-        &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-        &Validation::default(),
-    );
+    let token = jwt::decode_token::<RegistrationClaims>(&item.activation_token);
 
     if token.is_err() {
         return Err((401, "Invalid token."));
@@ -570,7 +1068,7 @@ pub fn activate(
         return Err((401, "Invalid token."));
     }
 
-    let user = User::read(&mut db, token.claims.sub);
+    let user = config.stores.users.read(&mut db, token.claims.sub);
 
     if user.is_err() {
         return Err((400, "Invalid token."));
@@ -582,21 +1080,31 @@ pub fn activate(
         return Err((200, "Already activated!"));
     }
 
-    let activated_user = User::update(
-        &mut db,
-        user.id,
-        &UserChangeset {
-            activated: true,
-            email: user.email.clone(),
-            hash_password: user.hash_password,
-        },
-    );
+    // an upgraded guest (see [`upgrade_guest`]) keeps the [`GUEST_ROLE`] until now
+    let activated_user = diesel::connection::Connection::transaction(&mut db, |db| {
+        let activated_user = config.stores.users.update(
+            db,
+            user.id,
+            &UserChangeset {
+                activated: true,
+                email: user.email.clone(),
+                hash_password: user.hash_password.clone(),
+            },
+        )?;
+
+        UserRole::delete(db, user.id, GUEST_ROLE.to_string())?;
+
+        Ok::<User, diesel::result::Error>(activated_user)
+    });
 
     if activated_user.is_err() {
         return Err((500, "Could not activate user."));
     }
 
-    mailer.templates.send_activated(mailer, &user.email);
+    let mailer = &localized(mailer, &mut db, user.id, config);
+    mailer
+        .templates
+        .send_activated(mailer, user.email.as_deref().unwrap_or_default());
 
     Ok(())
 }
@@ -617,10 +1125,11 @@ pub fn forgot_password(
     db: &Database,
     item: &ForgotInput,
     mailer: &Mailer,
+    config: &AuthConfig,
 ) -> Result<(), (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
-    let user_result = User::find_by_email(&mut db, item.email.clone());
+    let user_result = config.stores.users.find_by_email(&mut db, &item.email);
 
     if let Ok(user) = user_result {
         // if !user.activated {
@@ -633,17 +1142,13 @@ pub fn forgot_password(
             token_type: "reset_token".to_string(),
         };
 
-        let reset_token = encode(
-            &Header::default(),
-            &reset_token_claims,
-            &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-        )
-        .unwrap();
+        let reset_token = jwt::encode_token(&reset_token_claims).unwrap();
 
         let link = &format!("reset?token={reset_token}");
+        let mailer = &localized(mailer, &mut db, user.id, config);
         mailer
             .templates
-            .send_recover_existent_account(mailer, &user.email, link);
+            .send_recover_existent_account(mailer, &item.email, link);
     } else {
         let link = &"register".to_string();
         mailer
@@ -667,6 +1172,7 @@ pub fn change_password(
     item: &ChangeInput,
     auth: &Auth,
     mailer: &Mailer,
+    config: &AuthConfig,
 ) -> Result<(), (StatusCode, Message)> {
     if item.old_password.is_empty() || item.new_password.is_empty() {
         return Err((400, "Missing password"));
@@ -678,7 +1184,7 @@ pub fn change_password(
 
     let mut db = db.pool.get().unwrap();
 
-    let user = User::read(&mut db, auth.user_id);
+    let user = config.stores.users.read(&mut db, auth.user_id);
 
     if user.is_err() {
         return Err((500, "Could not find user"));
@@ -690,28 +1196,20 @@ pub fn change_password(
         return Err((400, "Account has not been activated"));
     }
 
-    let is_old_password_valid = argon2::verify_encoded_ext(
-        &user.hash_password,
-        item.old_password.as_bytes(),
-        ARGON_CONFIG.secret,
-        ARGON_CONFIG.ad,
-    )
-    .unwrap();
+    let is_old_password_valid = verify_password(&user, &item.old_password, config);
 
     if !is_old_password_valid {
         return Err((400, "Invalid credentials"));
     }
 
-    let salt = generate_salt();
-    let new_hash =
-        argon2::hash_encoded(item.new_password.as_bytes(), &salt, &ARGON_CONFIG).unwrap();
+    let new_hash = config.password_hashing.hash(&item.new_password);
 
-    let updated_user = User::update(
+    let updated_user = config.stores.users.update(
         &mut db,
         auth.user_id,
         &UserChangeset {
             email: user.email.clone(),
-            hash_password: new_hash,
+            hash_password: Some(new_hash),
             activated: user.activated,
         },
     );
@@ -720,21 +1218,1532 @@ pub fn change_password(
         return Err((500, "Could not update password"));
     }
 
-    mailer.templates.send_password_changed(mailer, &user.email);
+    let mailer = &localized(mailer, &mut db, user.id, config);
+    mailer
+        .templates
+        .send_password_changed(mailer, user.email.as_deref().unwrap_or_default());
+
+    config.hooks.password_changed(
+        &mut db,
+        PasswordChangedEvent {
+            user: updated_user.unwrap(),
+            reset: false,
+        },
+    );
 
     Ok(())
 }
 
-/// /check
-///
-/// just a lifeline function, clients can post to this endpoint to check
-/// if the auth service is running
-pub fn check(_: &Auth) {}
-
-/// reset
+/// /email/change
 ///
-/// changes the password of the user associated with [`item.reset_token`](`ResetInput`)
-/// to [`item.new_password`](`ResetInput`)
+/// emails a confirmation link to [`item.new_email`](`EmailChangeInput`), the email of the User
+/// associated with [`auth`](`Auth`) is only changed once it's followed (see [`confirm_email`])
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn change_email(
+    db: &Database,
+    item: &EmailChangeInput,
+    auth: &Auth,
+    mailer: &Mailer,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    if auth.impersonator.is_some() {
+        return Err((403, "Forbidden."));
+    }
+
+    let new_email = item.new_email.trim();
+
+    if item.password.is_empty() || new_email.is_empty() {
+        return Err((400, "Missing email or password"));
+    }
+
+    let mut db = db.pool.get().unwrap();
+
+    let user = config.stores.users.read(&mut db, auth.user_id);
+
+    if user.is_err() {
+        return Err((500, "Could not find user"));
+    }
+
+    let user = user.unwrap();
+
+    let old_email = match &user.email {
+        Some(email) if user.activated => email.clone(),
+        _ => return Err((400, "Account has not been activated")),
+    };
+
+    if !verify_password(&user, &item.password, config) {
+        return Err((400, "Invalid credentials"));
+    }
+
+    if old_email.eq_ignore_ascii_case(new_email) {
+        return Err((400, "The new email must be different"));
+    }
+
+    if config
+        .stores
+        .users
+        .find_by_email(&mut db, new_email)
+        .is_ok()
+    {
+        return Err((400, "Already registered."));
+    }
+
+    let email_change_claims = EmailChangeClaims {
+        exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp() as usize,
+        sub: user.id,
+        token_type: "email_change_token".to_string(),
+        new_email: new_email.to_string(),
+        old_email,
+    };
+
+    let email_change_token = jwt::encode_token(&email_change_claims).unwrap();
+
+    let link = &format!("confirm-email?token={email_change_token}");
+    let mailer = &localized(mailer, &mut db, user.id, config);
+    mailer.templates.send_email_change(mailer, new_email, link);
+
+    Ok(())
+}
+
+/// /email/confirm
+///
+/// switches the User's email to the one confirmed by [`item.email_change_token`](`EmailConfirmInput`),
+/// and tells their previous address about it
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn confirm_email(
+    db: &Database,
+    item: &EmailConfirmInput,
+    mailer: &Mailer,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let token = jwt::decode_token::<EmailChangeClaims>(&item.email_change_token);
+
+    if token.is_err() {
+        return Err((401, "Invalid token."));
+    }
+
+    let token = token.unwrap();
+
+    if !token
+        .claims
+        .token_type
+        .eq_ignore_ascii_case("email_change_token")
+    {
+        return Err((401, "Invalid token."));
+    }
+
+    let user = config.stores.users.read(&mut db, token.claims.sub);
+
+    if user.is_err() {
+        return Err((400, "Invalid token."));
+    }
+
+    let user = user.unwrap();
+
+    // the token was already used, or the email changed since it was sent
+    if user.email.as_deref() != Some(token.claims.old_email.as_str()) {
+        return Err((400, "Invalid token."));
+    }
+
+    if config
+        .stores
+        .users
+        .find_by_email(&mut db, &token.claims.new_email)
+        .is_ok()
+    {
+        return Err((400, "Already registered."));
+    }
+
+    let update = config.stores.users.update(
+        &mut db,
+        user.id,
+        &UserChangeset {
+            email: Some(token.claims.new_email.clone()),
+            hash_password: user.hash_password.clone(),
+            activated: user.activated,
+        },
+    );
+
+    if update.is_err() {
+        return Err((500, "Could not update email"));
+    }
+
+    let mailer = &localized(mailer, &mut db, user.id, config);
+    mailer
+        .templates
+        .send_email_changed(mailer, &token.claims.old_email, &token.claims.new_email);
+
+    Ok(())
+}
+
+/// /check
+///
+/// just a lifeline function, clients can post to this endpoint to check
+/// if the auth service is running
+pub fn check(_: &Auth) {}
+
+/// /impersonate
+///
+/// lets the user associated with [`auth`](`Auth`), who must have the [`IMPERSONATE_PERMISSION`],
+/// act as the user in [`item`](`ImpersonateInput`). The returned access token belongs to that user
+/// and carries the admin's id in its `impersonator` claim. Each impersonation is recorded in the
+/// `user_impersonations` table (see [`UserImpersonation`]).
+///
+/// administrators can't be impersonated: users with the [`ADMIN_ROLE`] or the
+/// [`IMPERSONATE_PERMISSION`] are refused with a 403, so that impersonating can't escalate
+/// privileges.
+///
+/// no refresh token is issued: the admin's own session (and refresh token cookie) stays untouched,
+/// and the impersonation ends at the latest when the access token expires.
+///
+/// # Returns [`Result`]
+/// - Ok([`AccessToken`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn impersonate(
+    db: &Database,
+    auth: &Auth,
+    item: &ImpersonateInput,
+    ip_address: Option<String>,
+    config: &AuthConfig,
+) -> Result<AccessToken, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if !auth.has_permission(IMPERSONATE_PERMISSION.to_string()) {
+        return Err((403, "Forbidden."));
+    }
+
+    if auth.impersonator.is_some() {
+        return Err((400, "Already impersonating a user."));
+    }
+
+    if item.user_id == auth.user_id {
+        return Err((400, "Cannot impersonate yourself."));
+    }
+
+    if config.stores.users.read(&mut db, item.user_id).is_err() {
+        return Err((404, "User not found."));
+    }
+
+    let roles = Role::fetch_all(&mut db, item.user_id);
+    let permissions = Permission::fetch_all(&mut db, item.user_id);
+    let (roles, permissions) = match (roles, permissions) {
+        (Ok(roles), Ok(permissions)) => (roles, permissions),
+        (Err(err), _) | (_, Err(err)) => {
+            println!("{err:#?}");
+            return Err((500, "An internal server error occurred."));
+        }
+    };
+
+    if roles.iter().any(|role| role == ADMIN_ROLE)
+        || permissions
+            .iter()
+            .any(|permission| permission.permission == IMPERSONATE_PERMISSION)
+    {
+        return Err((403, "Administrators can't be impersonated."));
+    }
+
+    let impersonation = UserImpersonation::create(
+        &mut db,
+        &UserImpersonationChangeset {
+            impersonator_id: auth.user_id,
+            user_id: item.user_id,
+            reason: item.reason.clone(),
+            ip_address,
+        },
+    );
+
+    if impersonation.is_err() {
+        return Err((500, "Could not record the impersonation."));
+    }
+
+    issue_access_token(&mut db, item.user_id, Some(auth.user_id), config)
+}
+
+/// /impersonate/stop
+///
+/// ends the impersonation [`auth`](`Auth`) was issued for, and hands the admin back an access token of their own
+///
+/// # Returns [`Result`]
+/// - Ok([`AccessToken`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn stop_impersonating(
+    db: &Database,
+    auth: &Auth,
+    config: &AuthConfig,
+) -> Result<AccessToken, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let impersonator = match auth.impersonator {
+        Some(impersonator) => impersonator,
+        None => return Err((400, "Not impersonating a user.")),
+    };
+
+    if UserImpersonation::end(&mut db, impersonator, auth.user_id).is_err() {
+        return Err((500, "Could not record the end of the impersonation."));
+    }
+
+    if let Some(revocation) = &config.revocation {
+        revocation.revoke_token(&auth.jti, auth.exp);
+    }
+
+    issue_access_token(&mut db, impersonator, None, config)
+}
+
+/// /account/export
+///
+/// collects everything stored about the User associated with [`auth`](`Auth`), including
+/// the data of the [`AccountHook`](`account::AccountHook`)s in [`config`](`AuthConfig`)
+///
+/// # Returns [`Result`]
+/// - Ok([`AccountExport`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn export_account(
+    db: &Database,
+    auth: &Auth,
+    config: &AuthConfig,
+) -> Result<AccountExport, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if auth.impersonator.is_some() {
+        return Err((403, "Forbidden."));
+    }
+
+    match account::export(&mut db, auth.user_id, config) {
+        Ok(export) => Ok(export),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not export the account."))
+        }
+    }
+}
+
+/// /account
+///
+/// deletes (or anonymizes, see [`AccountDeletion`](`account::AccountDeletion`)) the User associated
+/// with [`auth`](`Auth`) once [`item.password`](`DeleteAccountInput`) is confirmed, running the
+/// [`AccountHook`](`account::AccountHook`)s in [`config`](`AuthConfig`) first
+///
+/// # Returns [`Result`]
+/// - Ok([`DeletedAccount`])
+///     - the stored objects of the user's attachments, which the caller still has to delete
+/// - Err([`StatusCode`], [`Message`])
+pub fn delete_account(
+    db: &Database,
+    auth: &Auth,
+    item: &DeleteAccountInput,
+    config: &AuthConfig,
+) -> Result<DeletedAccount, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if auth.impersonator.is_some() {
+        return Err((403, "Forbidden."));
+    }
+
+    let user = config.stores.users.read(&mut db, auth.user_id);
+
+    if user.is_err() {
+        return Err((500, "Could not find user"));
+    }
+
+    let user = user.unwrap();
+
+    // guests have no password to confirm
+    let is_valid = user.is_guest() || verify_password(&user, &item.password, config);
+
+    if !is_valid {
+        return Err((400, "Invalid credentials"));
+    }
+
+    match account::delete(&mut db, user.id, config, config.account_deletion) {
+        Ok(deleted) => {
+            config.revoke_user(user.id);
+            Ok(deleted)
+        }
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not delete the account."))
+        }
+    }
+}
+
+/// /guest
+///
+/// creates a guest, a [`User`] without an email address or password (and with the [`GUEST_ROLE`]),
+/// and a session for them, so visitors can fill a cart or write drafts before they register
+///
+/// requires [`config.guest_sessions`](`AuthConfig::guest_sessions`). Guests keep their id when
+/// they register with [`upgrade_guest`], or hand their data over to an existing account with
+/// [`merge_guest`].
+///
+/// # Returns [`Result`]
+/// - Ok([`AccessToken`], [`RefreshToken`]), like [`login`]
+/// - Err([`StatusCode`], [`Message`])
+pub fn create_guest(
+    db: &Database,
+    ip_address: Option<String>,
+    config: &AuthConfig,
+) -> Result<(AccessToken, RefreshToken), (StatusCode, Message)> {
+    if !config.guest_sessions {
+        return Err((403, "Guest sessions are disabled."));
+    }
+
+    let mut db = db.pool.get().unwrap();
+
+    let guest = diesel::connection::Connection::transaction(&mut db, |db| {
+        let guest = config.stores.users.create(
+            db,
+            &UserChangeset {
+                email: None,
+                hash_password: None,
+                activated: true,
+            },
+        )?;
+
+        UserRole::create(
+            db,
+            &UserRoleChangeset {
+                user_id: guest.id,
+                role: GUEST_ROLE.to_string(),
+            },
+        )?;
+
+        Ok::<User, diesel::result::Error>(guest)
+    });
+
+    let guest = match guest {
+        Ok(guest) => guest,
+        Err(err) => {
+            println!("{err:#?}");
+            return Err((500, "Could not create a guest."));
+        }
+    };
+
+    let (access_token, refresh_token, _) =
+        start_session(&mut db, guest.id, None, ip_address, None, config)?;
+
+    Ok((access_token, refresh_token))
+}
+
+/// /guest/upgrade
+///
+/// turns the guest associated with [`auth`](`Auth`) into a full account with the email address
+/// and password in [`item`](`GuestInput`): the user keeps their id, and so everything attached to it
+///
+/// like [`register`], it requires an invitation unless registration is open, and the account is
+/// activated right away with one. Otherwise it's only activated once the link [`mailer`](`Mailer`)
+/// sends to the email address is followed (see [`activate`]): until then, the user keeps their
+/// current session and the [`GUEST_ROLE`], but can't log in with the password.
+///
+/// # Returns [`Result`]
+/// - Ok([`AccessToken`])
+///     - an access token with the user's new roles
+/// - Err([`StatusCode`], [`Message`])
+pub fn upgrade_guest(
+    db: &Database,
+    auth: &Auth,
+    item: &GuestInput,
+    mailer: &Mailer,
+    config: &AuthConfig,
+) -> Result<AccessToken, (StatusCode, Message)> {
+    if auth.impersonator.is_some() {
+        return Err((403, "Forbidden."));
+    }
+
+    if item.email.trim().is_empty() || item.password.is_empty() {
+        return Err((400, "Missing email or password."));
+    }
+
+    let mut db = db.pool.get().unwrap();
+
+    let guest = match config.stores.users.read(&mut db, auth.user_id) {
+        Ok(guest) => guest,
+        Err(_) => return Err((500, "Could not find user")),
+    };
+
+    if !guest.is_guest() {
+        return Err((400, "Not a guest."));
+    }
+
+    let invitation = match &item.invitation_token {
+        Some(token) => Some(find_invitation(&mut db, token)?),
+        None if !config.open_registration => {
+            return Err((403, "Registration is by invitation only."))
+        }
+        None => None,
+    };
+
+    if let Some(invitation) = &invitation {
+        if !invitation.email.eq_ignore_ascii_case(&item.email) {
+            return Err((400, "The invitation was sent to another email address."));
+        }
+    }
+
+    if config
+        .stores
+        .users
+        .find_by_email(&mut db, &item.email)
+        .is_ok()
+    {
+        return Err((400, "Already registered."));
+    }
+
+    let user = diesel::connection::Connection::transaction(&mut db, |db| {
+        let user = config.stores.users.update(
+            db,
+            guest.id,
+            &UserChangeset {
+                email: Some(item.email.clone()),
+                hash_password: Some(config.password_hashing.hash(&item.password)),
+                activated: invitation.is_some(),
+            },
+        )?;
+
+        if let Some(invitation) = &invitation {
+            accept_invitation(db, user.id, invitation)?;
+            UserRole::delete(db, user.id, GUEST_ROLE.to_string())?;
+        }
+
+        Ok::<User, diesel::result::Error>(user)
+    });
+
+    let user = match user {
+        Ok(user) => user,
+        Err(err) => {
+            println!("{err:#?}");
+            return Err((500, "Could not upgrade the guest."));
+        }
+    };
+
+    let mailer = &localized(mailer, &mut db, user.id, config);
+    if invitation.is_some() {
+        mailer.templates.send_activated(mailer, &item.email);
+    } else {
+        let registration_claims = RegistrationClaims {
+            exp: (chrono::Utc::now() + chrono::Duration::days(30)).timestamp() as usize,
+            sub: user.id,
+            token_type: "activation_token".to_string(),
+        };
+
+        let token = jwt::encode_token(&registration_claims).unwrap();
+
+        mailer
+            .templates
+            .send_register(mailer, &item.email, &format!("activate?token={token}"));
+    }
+
+    config.hooks.registered(
+        &mut db,
+        RegisteredEvent {
+            user: user.clone(),
+            invitation,
+        },
+    );
+
+    issue_access_token(&mut db, user.id, None, config)
+}
+
+/// /guest/merge
+///
+/// logs the guest associated with [`auth`](`Auth`) into the existing account with the credentials
+/// in [`item`](`GuestInput`): the guest's data is moved to the account by the
+/// [`AccountHook`](`account::AccountHook`)s in [`config`](`AuthConfig`) and the guest is deleted
+///
+/// [`ip_address`] is stored alongside the account's new session
+///
+/// # Returns [`Result`]
+/// - Ok([`AccessToken`], [`RefreshToken`], [`DeletedAccount`])
+///     - the tokens of the account's new session, like [`login`]
+///     - the stored objects of the guest's attachments, which the caller still has to delete
+/// - Err([`StatusCode`], [`Message`])
+pub fn merge_guest(
+    db: &Database,
+    auth: &Auth,
+    item: &GuestInput,
+    ip_address: Option<String>,
+    config: &AuthConfig,
+) -> Result<(AccessToken, RefreshToken, DeletedAccount), (StatusCode, Message)> {
+    if auth.impersonator.is_some() {
+        return Err((403, "Forbidden."));
+    }
+
+    let mut db = db.pool.get().unwrap();
+
+    let guest = match config.stores.users.read(&mut db, auth.user_id) {
+        Ok(guest) => guest,
+        Err(_) => return Err((500, "Could not find user")),
+    };
+
+    if !guest.is_guest() {
+        return Err((400, "Not a guest."));
+    }
+
+    let user = match config.stores.users.find_by_email(&mut db, &item.email) {
+        Ok(user) => user,
+        Err(_) => return Err((401, "Invalid credentials.")),
+    };
+
+    if !user.activated {
+        return Err((400, "Account has not been activated."));
+    }
+
+    if !verify_password(&user, &item.password, config) {
+        return Err((401, "Invalid credentials."));
+    }
+
+    let deleted = match account::merge(&mut db, guest.id, user.id, config) {
+        Ok(deleted) => deleted,
+        Err(err) => {
+            println!("{err:#?}");
+            return Err((500, "Could not merge the guest."));
+        }
+    };
+
+    config.revoke_user(guest.id);
+
+    let (access_token, refresh_token, session_id) =
+        start_session(&mut db, user.id, None, ip_address.clone(), None, config)?;
+
+    config.hooks.login(
+        &mut db,
+        LoginEvent {
+            user,
+            session_id,
+            device: None,
+            ip_address,
+        },
+    );
+
+    Ok((access_token, refresh_token, deleted))
+}
+
+/// /identities
+///
+/// the accounts linked to the user associated with [`auth`](`Auth`), and the names of the
+/// [`OAuthProvider`](`super::OAuthProvider`)s accounts can be linked from
+///
+/// # Returns [`Result`]
+/// - Ok([`UserIdentityResponse`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn get_identities(
+    db: &Database,
+    auth: &Auth,
+    config: &AuthConfig,
+) -> Result<UserIdentityResponse, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let identities = UserIdentity::fetch_all_for_user(&mut db, auth.user_id);
+
+    if identities.is_err() {
+        return Err((500, "Could not fetch identities."));
+    }
+
+    Ok(UserIdentityResponse {
+        identities: identities
+            .unwrap()
+            .into_iter()
+            .map(|identity| UserIdentityJson {
+                id: identity.id,
+                provider: identity.provider,
+                email: identity.email,
+                created_at: identity.created_at,
+            })
+            .collect(),
+        providers: config
+            .oauth_providers
+            .iter()
+            .map(|provider| provider.name.clone())
+            .collect(),
+    })
+}
+
+/// /identities/{provider}/authorize
+///
+/// starts linking an account at [`provider`] to the user associated with [`auth`](`Auth`): returns
+/// the url of the provider's consent screen. Once the user accepts, the provider sends them back to
+/// [`item.redirect_uri`](`AuthorizeIdentityInput`) with a `code` and a `state` (valid for 10
+/// minutes), see [`fetch_identity`].
+///
+/// # Returns [`Result`]
+/// - Ok([`String`]) the url to send the user to
+/// - Err([`StatusCode`], [`Message`])
+pub fn authorize_identity(
+    auth: &Auth,
+    provider: &str,
+    item: &AuthorizeIdentityInput,
+    config: &AuthConfig,
+) -> Result<String, (StatusCode, Message)> {
+    if auth.impersonator.is_some() || auth.has_role(GUEST_ROLE.to_string()) {
+        return Err((403, "Forbidden."));
+    }
+
+    let provider = match config.find_oauth_provider(provider) {
+        Some(provider) => provider,
+        None => return Err((404, "Provider not found.")),
+    };
+
+    if item.redirect_uri.trim().is_empty() {
+        return Err((400, "Missing redirect_uri"));
+    }
+
+    let state_claims = OAuthStateClaims {
+        exp: (chrono::Utc::now() + chrono::Duration::minutes(10)).timestamp() as usize,
+        sub: auth.user_id,
+        token_type: "oauth_state".to_string(),
+        provider: provider.name.clone(),
+        redirect_uri: item.redirect_uri.clone(),
+    };
+
+    let state = jwt::encode_token(&state_claims).unwrap();
+
+    provider
+        .authorization_url(&item.redirect_uri, &state)
+        .map_err(|err| {
+            println!("{err:#?}");
+            (400, "Invalid redirect_uri")
+        })
+}
+
+/// /identities
+///
+/// checks that the `state` in [`item`](`IdentityInput`) was issued to the user associated with
+/// [`auth`](`Auth`) by [`authorize_identity`], then exchanges the `code` for the user's profile at the
+/// provider. The profile is linked to the user by [`link_identity`].
+///
+/// unlike the other functions of this module, this one is `async`: it calls the provider
+///
+/// # Returns [`Result`]
+/// - Ok([`OAuthProfile`])
+/// - Err([`StatusCode`], [`Message`])
+pub async fn fetch_identity(
+    auth: &Auth,
+    item: &IdentityInput,
+    config: &AuthConfig,
+) -> Result<OAuthProfile, (StatusCode, Message)> {
+    let token = jwt::decode_token::<OAuthStateClaims>(&item.state);
+
+    if token.is_err() {
+        return Err((401, "Invalid state."));
+    }
+
+    let claims = token.unwrap().claims;
+
+    if !claims.token_type.eq_ignore_ascii_case("oauth_state") || claims.sub != auth.user_id {
+        return Err((401, "Invalid state."));
+    }
+
+    let provider = match config.find_oauth_provider(&claims.provider) {
+        Some(provider) => provider,
+        None => return Err((404, "Provider not found.")),
+    };
+
+    provider
+        .fetch_profile(&item.code, &claims.redirect_uri)
+        .await
+        .map_err(|err| {
+            println!("could not fetch an OAuth profile: {err}");
+            (400, "Could not verify the account with the provider.")
+        })
+}
+
+/// /identities
+///
+/// links the provider account in [`profile`](`OAuthProfile`) (see [`fetch_identity`]) to the user
+/// associated with [`auth`](`Auth`)
+///
+/// responds with a conflict (409) when the provider account is already linked, when the user already
+/// linked an account at the same provider, or when another user registered with the provider
+/// account's email (they should link it from that user instead)
+///
+/// # Returns [`Result`]
+/// - Ok([`UserIdentityJson`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn link_identity(
+    db: &Database,
+    auth: &Auth,
+    profile: &OAuthProfile,
+    config: &AuthConfig,
+) -> Result<UserIdentityJson, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if auth.impersonator.is_some() || auth.has_role(GUEST_ROLE.to_string()) {
+        return Err((403, "Forbidden."));
+    }
+
+    if let Ok(identity) = UserIdentity::find(&mut db, &profile.provider, &profile.id) {
+        if identity.user_id == auth.user_id {
+            return Err((409, "Account already linked."));
+        }
+
+        return Err((409, "Account already linked to another user."));
+    }
+
+    let identities = UserIdentity::fetch_all_for_user(&mut db, auth.user_id);
+
+    if identities.is_err() {
+        return Err((500, "Could not link the account."));
+    }
+
+    if identities
+        .unwrap()
+        .iter()
+        .any(|identity| identity.provider == profile.provider)
+    {
+        return Err((409, "An account from this provider is already linked."));
+    }
+
+    if let Some(email) = &profile.email {
+        if let Ok(user) = config.stores.users.find_by_email(&mut db, email) {
+            if user.id != auth.user_id {
+                return Err((409, "Another user is registered with this account's email."));
+            }
+        }
+    }
+
+    let identity = UserIdentity::create(
+        &mut db,
+        &UserIdentityChangeset {
+            user_id: auth.user_id,
+            provider: profile.provider.clone(),
+            provider_user_id: profile.id.clone(),
+            email: profile.email.clone(),
+        },
+    );
+
+    if identity.is_err() {
+        return Err((500, "Could not link the account."));
+    }
+
+    let identity = identity.unwrap();
+
+    Ok(UserIdentityJson {
+        id: identity.id,
+        provider: identity.provider,
+        email: identity.email,
+        created_at: identity.created_at,
+    })
+}
+
+/// /identities/{id}
+///
+/// unlinks the identity [`item_id`](`ID`) from the user associated with [`auth`](`Auth`). Users
+/// without a password can't unlink their last identity, they would be locked out.
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn unlink_identity(
+    db: &Database,
+    auth: &Auth,
+    item_id: ID,
+    config: &AuthConfig,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if auth.impersonator.is_some() {
+        return Err((403, "Forbidden."));
+    }
+
+    let identities = UserIdentity::fetch_all_for_user(&mut db, auth.user_id);
+
+    if identities.is_err() {
+        return Err((500, "Could not unlink the account."));
+    }
+
+    let identities = identities.unwrap();
+
+    if !identities.iter().any(|identity| identity.id == item_id) {
+        return Err((404, "Identity not found."));
+    }
+
+    let user = config.stores.users.read(&mut db, auth.user_id);
+
+    if user.is_err() {
+        return Err((500, "Could not find user"));
+    }
+
+    if user.unwrap().hash_password.is_none() && identities.len() == 1 {
+        return Err((400, "Cannot unlink the only way to log in."));
+    }
+
+    if UserIdentity::delete(&mut db, item_id).is_err() {
+        return Err((500, "Could not unlink the account."));
+    }
+
+    Ok(())
+}
+
+/// /profile
+///
+/// the [`UserProfile`](`super::UserProfile`) of the user associated with [`auth`](`Auth`), see
+/// [`AuthConfig::user_profile`]
+///
+/// # Returns [`Result`]
+/// - Ok([`Value`](`serde_json::Value`))
+/// - Err([`StatusCode`], [`Message`])
+pub fn get_profile(
+    db: &Database,
+    auth: &Auth,
+    config: &AuthConfig,
+) -> Result<serde_json::Value, (StatusCode, Message)> {
+    let profiles = match &config.profile {
+        Some(profiles) => profiles,
+        None => return Err((404, "Profiles are not enabled.")),
+    };
+
+    let mut db = db.pool.get().unwrap();
+
+    match profiles.read(&mut db, auth.user_id) {
+        Ok(profile) => Ok(profile),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not fetch the profile."))
+        }
+    }
+}
+
+/// /profile
+///
+/// merges the fields in `changes` into the [`UserProfile`](`super::UserProfile`) of the user
+/// associated with [`auth`](`Auth`), fields left out are kept as they are
+///
+/// # Returns [`Result`]
+/// - Ok([`Value`](`serde_json::Value`)) the updated profile
+/// - Err([`StatusCode`], [`Message`])
+pub fn update_profile(
+    db: &Database,
+    auth: &Auth,
+    changes: serde_json::Map<String, serde_json::Value>,
+    config: &AuthConfig,
+) -> Result<serde_json::Value, (StatusCode, Message)> {
+    let profiles = match &config.profile {
+        Some(profiles) => profiles,
+        None => return Err((404, "Profiles are not enabled.")),
+    };
+
+    let mut db = db.pool.get().unwrap();
+
+    match profiles.update(&mut db, auth.user_id, changes) {
+        Ok(Ok(profile)) => Ok(profile),
+        Ok(Err(_)) => Err((400, "Invalid profile.")),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not update the profile."))
+        }
+    }
+}
+
+/// /invitations
+///
+/// invites the email address in [`item`](`InvitationInput`) to register, on behalf of the user
+/// associated with [`auth`](`Auth`), who must have the [`INVITE_PERMISSION`]
+///
+/// sends an email, using [`mailer`](`Mailer`), with an invite link that is valid for
+/// [`config.invitation_ttl`](`AuthConfig::invitation_ttl`)
+///
+/// # Returns [`Result`]
+/// - Ok([`UserInvitation`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn create_invitation(
+    db: &Database,
+    auth: &Auth,
+    item: &InvitationInput,
+    mailer: &Mailer,
+    config: &AuthConfig,
+) -> Result<UserInvitation, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if !auth.has_permission(INVITE_PERMISSION.to_string()) {
+        return Err((403, "Forbidden."));
+    }
+
+    let email = item.email.trim();
+
+    if email.is_empty() {
+        return Err((400, "Missing email"));
+    }
+
+    if let Ok(user) = config.stores.users.find_by_email(&mut db, email) {
+        if user.activated {
+            return Err((400, "Already registered."));
+        }
+    }
+
+    let expires_at =
+        chrono::Utc::now() + chrono::Duration::from_std(config.invitation_ttl).unwrap();
+    let jti = generate_jti();
+
+    let invitation = UserInvitation::create(
+        &mut db,
+        &UserInvitationChangeset {
+            email: email.to_string(),
+            roles: item
+                .roles
+                .iter()
+                .map(|role| role.trim())
+                .filter(|role| !role.is_empty())
+                .collect::<Vec<_>>()
+                .join(","),
+            token: jti.clone(),
+            invited_by: auth.user_id,
+            organization_id: None,
+            organization_role: None,
+            #[cfg(not(feature = "database_sqlite"))]
+            expires_at,
+            #[cfg(feature = "database_sqlite")]
+            expires_at: expires_at.naive_utc(),
+        },
+    );
+
+    if invitation.is_err() {
+        return Err((500, "Could not create the invitation."));
+    }
+
+    let invitation = invitation.unwrap();
+
+    let invitation_claims = InvitationClaims {
+        exp: expires_at.timestamp() as usize,
+        sub: invitation.id,
+        token_type: "invitation_token".to_string(),
+        jti,
+    };
+
+    let token = jwt::encode_token(&invitation_claims).unwrap();
+
+    mailer.templates.send_invitation(
+        mailer,
+        &invitation.email,
+        &format!("register?invitation_token={token}"),
+    );
+
+    Ok(invitation)
+}
+
+/// /invitations
+///
+/// the invitations that weren't accepted yet, the user associated with [`auth`](`Auth`)
+/// must have the [`INVITE_PERMISSION`]
+///
+/// # Returns [`Result`]
+/// - Ok([`Vec<UserInvitation>`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn get_invitations(
+    db: &Database,
+    auth: &Auth,
+) -> Result<Vec<UserInvitation>, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if !auth.has_permission(INVITE_PERMISSION.to_string()) {
+        return Err((403, "Forbidden."));
+    }
+
+    let invitations = UserInvitation::fetch_all_pending(&mut db);
+
+    if invitations.is_err() {
+        return Err((500, "Could not fetch invitations."));
+    }
+
+    Ok(invitations.unwrap())
+}
+
+/// /invitations/{id}
+///
+/// revokes the invitation [`item_id`](`ID`), its invite link stops working. The user associated
+/// with [`auth`](`Auth`) must have the [`INVITE_PERMISSION`]
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn revoke_invitation(
+    db: &Database,
+    auth: &Auth,
+    item_id: ID,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if !auth.has_permission(INVITE_PERMISSION.to_string()) {
+        return Err((403, "Forbidden."));
+    }
+
+    match UserInvitation::delete(&mut db, item_id) {
+        Ok(0) => Err((404, "Invitation not found.")),
+        Ok(_) => Ok(()),
+        Err(_) => Err((500, "Could not revoke the invitation.")),
+    }
+}
+
+/// /invitation
+///
+/// the invitation behind the token in [`item`](`InvitationQuery`), so the registration form
+/// can be filled in with its email address
+///
+/// # Returns [`Result`]
+/// - Ok([`UserInvitation`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn read_invitation(
+    db: &Database,
+    item: &InvitationQuery,
+) -> Result<UserInvitation, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    find_invitation(&mut db, &item.invitation_token)
+}
+
+/// the pending invitation [`token`] (from an invite link) was issued for
+fn find_invitation(
+    db: &mut Connection,
+    token: &str,
+) -> Result<UserInvitation, (StatusCode, Message)> {
+    let token = jwt::decode_token::<InvitationClaims>(token);
+
+    if token.is_err() {
+        return Err((401, "Invalid invitation."));
+    }
+
+    let claims = token.unwrap().claims;
+
+    if !claims.token_type.eq_ignore_ascii_case("invitation_token") {
+        return Err((401, "Invalid invitation."));
+    }
+
+    let invitation = UserInvitation::read(db, claims.sub);
+
+    if invitation.is_err() {
+        return Err((401, "Invalid invitation."));
+    }
+
+    let invitation = invitation.unwrap();
+
+    if invitation.token != claims.jti {
+        return Err((401, "Invalid invitation."));
+    }
+
+    if invitation.accepted_at.is_some() {
+        return Err((400, "Invitation already accepted."));
+    }
+
+    if invitation.is_expired() {
+        return Err((400, "Invitation expired."));
+    }
+
+    Ok(invitation)
+}
+
+/// /organizations
+///
+/// creates an organization named after [`item`](`OrganizationInput`), the user associated with
+/// [`auth`](`Auth`) becomes its owner. It becomes their active organization if they had none yet
+/// (their next access token will carry it).
+///
+/// # Returns [`Result`]
+/// - Ok([`Organization`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn create_organization(
+    db: &Database,
+    auth: &Auth,
+    item: &OrganizationInput,
+) -> Result<Organization, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let name = item.name.trim();
+
+    if name.is_empty() {
+        return Err((400, "Missing name"));
+    }
+
+    if name.len() > 256 {
+        return Err((400, "'name' cannot be longer than 256 characters."));
+    }
+
+    let organization = diesel::connection::Connection::transaction(&mut db, |db| {
+        let has_active = Membership::active(db, auth.user_id)?.is_some();
+
+        let organization = Organization::create(
+            db,
+            &OrganizationChangeset {
+                name: name.to_string(),
+            },
+        )?;
+        Membership::create(
+            db,
+            &MembershipChangeset {
+                organization_id: organization.id,
+                user_id: auth.user_id,
+                role: ORGANIZATION_OWNER.to_string(),
+            },
+        )?;
+
+        if !has_active {
+            Membership::activate(db, organization.id, auth.user_id)?;
+        }
+
+        Ok::<Organization, diesel::result::Error>(organization)
+    });
+
+    match organization {
+        Ok(organization) => Ok(organization),
+        Err(err) => {
+            println!("{err:#?}");
+            Err((500, "Could not create the organization."))
+        }
+    }
+}
+
+/// /organizations
+///
+/// the organizations the user associated with [`auth`](`Auth`) is a member of
+///
+/// # Returns [`Result`]
+/// - Ok([`Vec<OrganizationJson>`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn get_organizations(
+    db: &Database,
+    auth: &Auth,
+) -> Result<Vec<OrganizationJson>, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let active = active_organization(&mut db, auth.user_id)?.map(|active| active.id);
+
+    let organizations = Organization::fetch_all_for_user(&mut db, auth.user_id);
+
+    if organizations.is_err() {
+        return Err((500, "Could not fetch organizations."));
+    }
+
+    Ok(organizations
+        .unwrap()
+        .into_iter()
+        .map(|(organization, membership)| OrganizationJson {
+            id: organization.id,
+            name: organization.name,
+            role: membership.role,
+            active: active == Some(organization.id),
+            created_at: organization.created_at,
+        })
+        .collect())
+}
+
+/// /organizations/{id}/switch
+///
+/// makes [`organization_id`](`ID`) the active organization of the user associated with
+/// [`auth`](`Auth`), and hands them an access token that carries it. Their refresh token stays
+/// valid, refreshed access tokens carry the new organization too.
+///
+/// # Returns [`Result`]
+/// - Ok([`AccessToken`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn switch_organization(
+    db: &Database,
+    auth: &Auth,
+    organization_id: ID,
+    config: &AuthConfig,
+) -> Result<AccessToken, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    if auth.impersonator.is_some() {
+        return Err((403, "Forbidden."));
+    }
+
+    find_membership(&mut db, organization_id, auth.user_id)?;
+
+    if Membership::activate(&mut db, organization_id, auth.user_id).is_err() {
+        return Err((500, "Could not switch organizations."));
+    }
+
+    issue_access_token(&mut db, auth.user_id, None, config)
+}
+
+/// /organizations/{id}/members
+///
+/// the members of [`organization_id`](`ID`), the user associated with [`auth`](`Auth`) must be one of them
+///
+/// # Returns [`Result`]
+/// - Ok([`Vec<MemberJson>`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn get_members(
+    db: &Database,
+    auth: &Auth,
+    organization_id: ID,
+) -> Result<Vec<MemberJson>, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    find_membership(&mut db, organization_id, auth.user_id)?;
+
+    let members = Membership::fetch_all_with_email(&mut db, organization_id);
+
+    if members.is_err() {
+        return Err((500, "Could not fetch members."));
+    }
+
+    Ok(members
+        .unwrap()
+        .into_iter()
+        .map(|(membership, email)| MemberJson {
+            user_id: membership.user_id,
+            email,
+            role: membership.role,
+            created_at: membership.created_at,
+        })
+        .collect())
+}
+
+/// /organizations/{id}/members
+///
+/// adds the email address in [`item`](`MemberInput`) to [`organization_id`](`ID`), on behalf of
+/// the user associated with [`auth`](`Auth`), who must be one of its owners or admins (only owners
+/// can add owners)
+///
+/// registered users become members right away. Anyone else is invited to register (see
+/// [`create_invitation`]), using [`mailer`](`Mailer`), and joins the organization once they do.
+///
+/// # Returns [`Result`]
+/// - Ok([`AddedMember`])
+/// - Err([`StatusCode`], [`Message`])
+pub fn add_member(
+    db: &Database,
+    auth: &Auth,
+    organization_id: ID,
+    item: &MemberInput,
+    mailer: &Mailer,
+    config: &AuthConfig,
+) -> Result<AddedMember, (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let membership = find_membership(&mut db, organization_id, auth.user_id)?;
+
+    if !membership.can_manage_members() {
+        return Err((403, "Forbidden."));
+    }
+
+    let role = item
+        .role
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(ORGANIZATION_MEMBER);
+
+    if ![ORGANIZATION_OWNER, ORGANIZATION_ADMIN, ORGANIZATION_MEMBER].contains(&role) {
+        return Err((400, "Invalid role."));
+    }
+
+    if role == ORGANIZATION_OWNER && membership.role != ORGANIZATION_OWNER {
+        return Err((403, "Forbidden."));
+    }
+
+    let email = item.email.trim();
+
+    if email.is_empty() {
+        return Err((400, "Missing email"));
+    }
+
+    if let Ok(user) = config.stores.users.find_by_email(&mut db, email) {
+        if user.activated {
+            if Membership::read(&mut db, organization_id, user.id).is_ok() {
+                return Err((400, "Already a member."));
+            }
+
+            let member = Membership::create(
+                &mut db,
+                &MembershipChangeset {
+                    organization_id,
+                    user_id: user.id,
+                    role: role.to_string(),
+                },
+            );
+
+            if member.is_err() {
+                return Err((500, "Could not add the member."));
+            }
+
+            return Ok(AddedMember::Added(member.unwrap()));
+        }
+    }
+
+    let expires_at =
+        chrono::Utc::now() + chrono::Duration::from_std(config.invitation_ttl).unwrap();
+    let jti = generate_jti();
+
+    let invitation = UserInvitation::create(
+        &mut db,
+        &UserInvitationChangeset {
+            email: email.to_string(),
+            roles: String::new(),
+            token: jti.clone(),
+            invited_by: auth.user_id,
+            organization_id: Some(organization_id),
+            organization_role: Some(role.to_string()),
+            #[cfg(not(feature = "database_sqlite"))]
+            expires_at,
+            #[cfg(feature = "database_sqlite")]
+            expires_at: expires_at.naive_utc(),
+        },
+    );
+
+    if invitation.is_err() {
+        return Err((500, "Could not create the invitation."));
+    }
+
+    let invitation = invitation.unwrap();
+
+    let invitation_claims = InvitationClaims {
+        exp: expires_at.timestamp() as usize,
+        sub: invitation.id,
+        token_type: "invitation_token".to_string(),
+        jti,
+    };
+
+    let token = jwt::encode_token(&invitation_claims).unwrap();
+
+    mailer.templates.send_invitation(
+        mailer,
+        &invitation.email,
+        &format!("register?invitation_token={token}"),
+    );
+
+    Ok(AddedMember::Invited(invitation))
+}
+
+/// /organizations/{id}/members/{user_id}
+///
+/// removes [`user_id`](`ID`) from [`organization_id`](`ID`). Members can leave on their own,
+/// owners and admins can remove other members (only owners can remove owners). The last owner
+/// can't be removed.
+///
+/// # Returns [`Result`]
+/// - Ok(`()`)
+/// - Err([`StatusCode`], [`Message`])
+pub fn remove_member(
+    db: &Database,
+    auth: &Auth,
+    organization_id: ID,
+    user_id: ID,
+) -> Result<(), (StatusCode, Message)> {
+    let mut db = db.pool.get().unwrap();
+
+    let membership = find_membership(&mut db, organization_id, auth.user_id)?;
+
+    let member = if user_id == auth.user_id {
+        membership
+    } else {
+        if !membership.can_manage_members() {
+            return Err((403, "Forbidden."));
+        }
+
+        let member = Membership::read(&mut db, organization_id, user_id);
+
+        if member.is_err() {
+            return Err((404, "Member not found."));
+        }
+
+        let member = member.unwrap();
+
+        if member.role == ORGANIZATION_OWNER && membership.role != ORGANIZATION_OWNER {
+            return Err((403, "Forbidden."));
+        }
+
+        member
+    };
+
+    if member.role == ORGANIZATION_OWNER {
+        match Membership::count_owners(&mut db, organization_id) {
+            Ok(owners) if owners <= 1 => {
+                return Err((400, "An organization needs at least one owner."))
+            }
+            Ok(_) => {}
+            Err(_) => return Err((500, "Could not remove the member.")),
+        }
+    }
+
+    match Membership::delete(&mut db, organization_id, user_id) {
+        Ok(_) => Ok(()),
+        Err(_) => Err((500, "Could not remove the member.")),
+    }
+}
+
+/// the membership of [`user_id`](`ID`) in [`organization_id`](`ID`), organizations the user
+/// isn't part of are reported as not found
+fn find_membership(
+    db: &mut Connection,
+    organization_id: ID,
+    user_id: ID,
+) -> Result<Membership, (StatusCode, Message)> {
+    match Membership::read(db, organization_id, user_id) {
+        Ok(membership) => Ok(membership),
+        Err(diesel::result::Error::NotFound) => Err((404, "Organization not found.")),
+        Err(_) => Err((500, "An internal server error occurred.")),
+    }
+}
+
+/// `mailer`, sending [`user_id`](`ID`)'s emails in the locale stored in their profile (its `locale`
+/// field, see [`Profiles::locale`](`crate::auth::Profiles::locale`))
+fn localized(mailer: &Mailer, db: &mut Connection, user_id: ID, config: &AuthConfig) -> Mailer {
+    let locale = config
+        .profile
+        .as_ref()
+        .and_then(|profiles| profiles.locale(db, user_id));
+
+    match locale {
+        Some(locale) => mailer.clone().with_locale(locale),
+        None => mailer.clone(),
+    }
+}
+
+/// creates an access token for [`user_id`](`ID`) with their current roles, permissions and active organization
+fn issue_access_token(
+    db: &mut Connection,
+    user_id: ID,
+    impersonator: Option<ID>,
+    config: &AuthConfig,
+) -> Result<AccessToken, (StatusCode, Message)> {
+    let permissions = Permission::fetch_all(db, user_id);
+    if permissions.is_err() {
+        return Err((500, "An internal server error occurred."));
+    }
+
+    let roles = Role::fetch_all(db, user_id);
+    if roles.is_err() {
+        return Err((500, "An internal server error occurred."));
+    }
+
+    let access_token_claims = AccessTokenClaims {
+        exp: config.access_token_exp(None),
+        iat: chrono::Utc::now().timestamp() as usize,
+        jti: generate_jti(),
+        sub: user_id,
+        token_type: "access_token".to_string(),
+        roles: roles.unwrap(),
+        permissions: permissions.unwrap(),
+        impersonator,
+        organization: active_organization(db, user_id)?,
+    };
+
+    Ok(jwt::encode_token(&access_token_claims).unwrap())
+}
+
+/// the organization [`user_id`](`ID`) switched to last, for their access tokens
+fn active_organization(
+    db: &mut Connection,
+    user_id: ID,
+) -> Result<Option<ActiveOrganization>, (StatusCode, Message)> {
+    match Membership::active(db, user_id) {
+        Ok(membership) => Ok(membership.map(|membership| ActiveOrganization {
+            id: membership.organization_id,
+            role: membership.role,
+        })),
+        Err(_) => Err((500, "An internal server error occurred.")),
+    }
+}
+
+/// reset
+///
+/// changes the password of the user associated with [`item.reset_token`](`ResetInput`)
+/// to [`item.new_password`](`ResetInput`)
 ///
 /// # Returns [`Result`]
 /// - Ok(`()`)
@@ -743,6 +2752,7 @@ pub fn reset_password(
     db: &Database,
     item: &ResetInput,
     mailer: &Mailer,
+    config: &AuthConfig,
 ) -> Result<(), (StatusCode, Message)> {
     let mut db = db.pool.get().unwrap();
 
@@ -750,11 +2760,7 @@ pub fn reset_password(
         return Err((400, "Missing password"));
     }
 
-    let token = decode::<ResetTokenClaims>(
-        &item.reset_token,
-        &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-        &Validation::default(),
-    );
+    let token = jwt::decode_token::<ResetTokenClaims>(&item.reset_token);
 
     if token.is_err() {
         return Err((401, "Invalid token."));
@@ -766,7 +2772,7 @@ pub fn reset_password(
         return Err((401, "Invalid token."));
     }
 
-    let user = User::read(&mut db, token.claims.sub);
+    let user = config.stores.users.read(&mut db, token.claims.sub);
 
     if user.is_err() {
         return Err((400, "Invalid token."));
@@ -778,16 +2784,14 @@ pub fn reset_password(
         return Err((400, "Account has not been activated"));
     }
 
-    let salt = generate_salt();
-    let new_hash =
-        argon2::hash_encoded(item.new_password.as_bytes(), &salt, &ARGON_CONFIG).unwrap();
+    let new_hash = config.password_hashing.hash(&item.new_password);
 
-    let update = User::update(
+    let update = config.stores.users.update(
         &mut db,
         user.id,
         &UserChangeset {
             email: user.email.clone(),
-            hash_password: new_hash,
+            hash_password: Some(new_hash),
             activated: user.activated,
         },
     );
@@ -796,7 +2800,18 @@ pub fn reset_password(
         return Err((500, "Could not update password"));
     }
 
-    mailer.templates.send_password_reset(mailer, &user.email);
+    let mailer = &localized(mailer, &mut db, user.id, config);
+    mailer
+        .templates
+        .send_password_reset(mailer, user.email.as_deref().unwrap_or_default());
+
+    config.hooks.password_changed(
+        &mut db,
+        PasswordChangedEvent {
+            user: update.unwrap(),
+            reset: true,
+        },
+    );
 
     Ok(())
 }
@@ -807,3 +2822,21 @@ pub fn generate_salt() -> [u8; 16] {
     salt.try_fill(&mut rand::thread_rng()).unwrap();
     salt
 }
+
+/// a random, hex-encoded identifier used for the `jti` claim of refresh tokens
+pub fn generate_jti() -> String {
+    use rand::Rng;
+    format!("{:032x}", rand::thread_rng().gen::<u128>())
+}
+
+/// the body of the responses starting or refreshing a session: `{"access_token": ...}`, with the
+/// `refresh_token` too when the [`CLIENT_HEADER`] is `desktop` (they store it themselves, a
+/// desktop app's webview doesn't keep the cookie reliably)
+pub fn session_json(access_token: &str, refresh_token: &str, client: Option<&str>) -> String {
+    if client.map_or(false, |client| client.eq_ignore_ascii_case("desktop")) {
+        serde_json::json!({ "access_token": access_token, "refresh_token": refresh_token })
+            .to_string()
+    } else {
+        serde_json::json!({ "access_token": access_token }).to_string()
+    }
+}
@@ -1,10 +1,12 @@
 #[cfg(feature = "plugin_utoipa")]
 use crate::auth::{
-    AuthMessageResponse, AuthTokenResponse, JwtSecurityAddon, UserSessionJson, UserSessionResponse,
+    jwt::{Jwk, Jwks},
+    AuthMessageResponse, AuthTokenResponse, CaptchaProvider, CaptchaSettings, JwtSecurityAddon,
+    UserIdentityJson, UserIdentityResponse, UserSessionJson, UserSessionResponse,
 };
 use actix_http::StatusCode;
 use actix_web::cookie::{Cookie, SameSite};
-use actix_web::{delete, get, post, web, Error as AWError, Result};
+use actix_web::{delete, get, patch, post, web, Error as AWError, Result};
 use actix_web::{
     web::{Data, Json, Path, Query},
     HttpRequest, HttpResponse,
@@ -16,13 +18,78 @@ use utoipa::OpenApi;
 use crate::auth::{
     controller,
     controller::{
-        ActivationInput, ChangeInput, ForgotInput, LoginInput, RegisterInput, ResetInput,
-        COOKIE_NAME,
+        ActivationInput, AddedMember, AuthorizeIdentityInput, ChangeInput, DeleteAccountInput,
+        EmailChangeInput, EmailConfirmInput, ForgotInput, GuestInput, IdentityInput,
+        ImpersonateInput, InvitationInput, InvitationQuery, LoginInput, MemberInput,
+        OrganizationInput, RegisterInput, ResetInput, CLIENT_HEADER, COOKIE_NAME,
+        REFRESH_TOKEN_HEADER,
     },
-    Auth, PaginationParams, ID,
+    jwt, Auth, AuthConfig, Captcha, PaginationParams, RateLimitedAction, ID,
 };
 use crate::Database;
 use crate::Mailer;
+#[cfg(feature = "plugin_storage")]
+use crate::Storage;
+
+/// the [`AuthConfig`] registered in the app's data, or the default one
+fn auth_config(config: Option<Data<AuthConfig>>) -> Data<AuthConfig> {
+    config.unwrap_or_else(|| Data::new(AuthConfig::default()))
+}
+
+/// the requesting device's refresh token: its cookie, or the [`REFRESH_TOKEN_HEADER`] of the
+/// clients without cookies
+fn refresh_token_of(req: &HttpRequest) -> Option<String> {
+    req.cookie(COOKIE_NAME)
+        .map(|cookie| String::from(cookie.value()))
+        .or_else(|| {
+            req.headers()
+                .get(REFRESH_TOKEN_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+        })
+}
+
+/// the [`CLIENT_HEADER`], see [`controller::session_json`]
+fn client_of(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(CLIENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// the response when [`RateLimiter`](`crate::auth::RateLimiter`) rejects an attempt
+fn too_many_requests(retry_after: std::time::Duration) -> HttpResponse {
+    HttpResponse::build(StatusCode::TOO_MANY_REQUESTS)
+        .insert_header(("Retry-After", retry_after.as_secs().max(1).to_string()))
+        .body(json!({ "message": "Too many attempts, please try again later." }).to_string())
+}
+
+/// the response when the [`Captcha`](`crate::auth::Captcha`) token is missing or invalid
+fn captcha_failed() -> HttpResponse {
+    HttpResponse::build(StatusCode::BAD_REQUEST)
+        .body(json!({ "message": "CAPTCHA verification failed." }).to_string())
+}
+
+/// builds the refresh token cookie with the attributes from [`config`](`AuthConfig`)
+fn refresh_token_cookie(config: &AuthConfig, refresh_token: String) -> Cookie<'static> {
+    let mut cookie = Cookie::build(COOKIE_NAME, refresh_token)
+        .secure(config.cookie_secure)
+        .http_only(true)
+        .same_site(match config.cookie_same_site {
+            crate::auth::SameSite::Strict => SameSite::Strict,
+            crate::auth::SameSite::Lax => SameSite::Lax,
+            crate::auth::SameSite::None => SameSite::None,
+        })
+        .finish();
+
+    if let Some(domain) = &config.cookie_domain {
+        cookie.set_domain(domain.clone());
+    }
+    if let Some(path) = &config.cookie_path {
+        cookie.set_path(path.clone());
+    }
+
+    cookie
+}
 
 /// handler for GET requests at the .../sessions endpoint,
 ///
@@ -35,6 +102,8 @@ use crate::Mailer;
 ///
 /// Items are arranged in the database in such a way that the most recently added or updated items are last
 /// and are paginated accordingly
+///
+/// the session belonging to the requesting device (identified by the refresh_token cookie) is flagged as `current`
 #[cfg_attr(feature = "plugin_utoipa", utoipa::path(
     context_path = "/api/auth",
     params(PaginationParams),
@@ -49,12 +118,24 @@ use crate::Mailer;
 #[get("/sessions")]
 async fn sessions(
     db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
     auth: Auth,
     Query(info): Query<PaginationParams>,
+    req: HttpRequest,
 ) -> Result<HttpResponse> {
-    let result =
-        web::block(move || controller::get_sessions(db.into_inner().as_ref(), &auth, &info))
-            .await?;
+    let config = auth_config(config);
+    let refresh_token = refresh_token_of(&req);
+
+    let result = web::block(move || {
+        controller::get_sessions(
+            db.into_inner().as_ref(),
+            &auth,
+            &info,
+            refresh_token.as_deref(),
+            &config,
+        )
+    })
+    .await?;
 
     match result {
         Ok(sessions) => Ok(HttpResponse::Ok().json(sessions)),
@@ -65,6 +146,49 @@ async fn sessions(
     }
 }
 
+/// handler for DELETE requests at the .../sessions/others endpoint.
+///
+/// requires auth
+///
+/// destroys all entries in the `user_session` table in [`db`](`Database`) owned
+/// by the User associated with [`auth`](`Auth`), except the one belonging to the
+/// requesting device (identified by the refresh_token cookie)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Deleted", body = AuthMessageResponse),
+        (status = 401, description = "Invalid session.", body = AuthMessageResponse),
+        (status = 500, description = "Could not delete sessions.", body = AuthMessageResponse),
+    ),
+    tag = "Sessions",
+    security ( ("JWT" = []))
+))]
+#[delete("/sessions/others")]
+async fn destroy_other_sessions(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let refresh_token = refresh_token_of(&req);
+
+    let result = web::block(move || {
+        controller::destroy_other_sessions(&db, &auth, refresh_token.as_deref(), &config)
+    })
+    .await?;
+
+    match result {
+        Ok(_) => Ok(
+            HttpResponse::build(StatusCode::OK).body(json!({"message": "Deleted."}).to_string())
+        ),
+        Err((status_code, error_message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": error_message }).to_string())),
+    }
+}
+
 /// handler for DELETE requests at the .../sessions/{id} endpoint.
 ///
 /// requires auth
@@ -86,11 +210,15 @@ async fn sessions(
 #[delete("/sessions/{id}")]
 async fn destroy_session(
     db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
     item_id: Path<ID>,
     auth: Auth,
 ) -> Result<HttpResponse> {
+    let config = auth_config(config);
+
     let result =
-        web::block(move || controller::destroy_session(&db, &auth, item_id.into_inner())).await?;
+        web::block(move || controller::destroy_session(&db, &auth, item_id.into_inner(), &config))
+            .await?;
 
     match result {
         Ok(_) => Ok(
@@ -120,8 +248,14 @@ async fn destroy_session(
     security ( ("JWT" = []))
 ))]
 #[delete("/sessions")]
-async fn destroy_sessions(db: Data<Database>, auth: Auth) -> Result<HttpResponse, AWError> {
-    let result = web::block(move || controller::destroy_sessions(&db, &auth)).await?;
+async fn destroy_sessions(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result = web::block(move || controller::destroy_sessions(&db, &auth, &config)).await?;
 
     match result {
         Ok(_) => Ok(
@@ -145,26 +279,51 @@ async fn destroy_sessions(db: Data<Database>, auth: Auth) -> Result<HttpResponse
         (status = 200, description = "session created", body = AuthTokenResponse),
         (status = 400, description = "'device' cannot be longer than 256 characters.", body = AuthMessageResponse),
         (status = 400, description = "Account has not been activated.", body = AuthMessageResponse),
+        (status = 400, description = "CAPTCHA verification failed.", body = AuthMessageResponse),
         (status = 401, description = "Invalid credentials.", body = AuthMessageResponse),
         (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
         (status = 500, description = "Could not create a session.", body = AuthMessageResponse),
+        (status = 429, description = "Too many attempts, please try again later.", body = AuthMessageResponse),
     ),
     tag = "Sessions",
 ))]
 #[post("/login")]
-async fn login(db: Data<Database>, Json(item): Json<LoginInput>) -> Result<HttpResponse, AWError> {
-    let result = web::block(move || controller::login(&db, &item)).await?;
+async fn login(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    Json(item): Json<LoginInput>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(&req);
+
+    if let Err(retry_after) = config.check_rate_limit(
+        RateLimitedAction::Login,
+        &[ip_address.as_deref(), Some(&item.email)],
+    ) {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    if !config
+        .verify_captcha(item.captcha_token.as_deref(), ip_address.as_deref())
+        .await
+    {
+        return Ok(captcha_failed());
+    }
+
+    let result = {
+        let config = config.clone();
+        web::block(move || controller::login(&db, &item, ip_address, &config)).await?
+    };
 
     match result {
         Ok((access_token, refresh_token)) => Ok(HttpResponse::build(StatusCode::OK)
-            .cookie(
-                Cookie::build(COOKIE_NAME, refresh_token)
-                    .secure(true)
-                    .http_only(true)
-                    .same_site(SameSite::Strict)
-                    .finish(),
-            )
-            .body(json!({ "access_token": access_token }).to_string())),
+            .cookie(refresh_token_cookie(&config, refresh_token.clone()))
+            .body(controller::session_json(
+                &access_token,
+                &refresh_token,
+                client_of(&req),
+            ))),
         Err((status_code, message)) => Ok(HttpResponse::build(
             StatusCode::from_u16(status_code as u16).unwrap(),
         )
@@ -175,6 +334,7 @@ async fn login(db: Data<Database>, Json(item): Json<LoginInput>) -> Result<HttpR
 /// handler for POST requests to the .../logout endpount
 ///
 /// If this is successful, delete the cookie storing the refresh token
+/// and revoke the access token it was called with, if any
 ///
 /// TODO: document that it creates a refresh_token
 #[cfg_attr(feature = "plugin_utoipa", utoipa::path(
@@ -187,18 +347,31 @@ async fn login(db: Data<Database>, Json(item): Json<LoginInput>) -> Result<HttpR
     tag = "Sessions",
 ))]
 #[post("/logout")]
-async fn logout(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, AWError> {
-    let refresh_token = req
-        .cookie(COOKIE_NAME)
-        .map(|cookie| String::from(cookie.value()));
+async fn logout(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Option<Auth>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let refresh_token = refresh_token_of(&req);
 
-    let result =
-        web::block(move || controller::logout(&db, refresh_token.as_ref().map(|t| t.as_ref())))
-            .await?;
+    let result = {
+        let config = config.clone();
+        web::block(move || {
+            controller::logout(
+                &db,
+                auth.as_ref(),
+                refresh_token.as_ref().map(|t| t.as_ref()),
+                &config,
+            )
+        })
+        .await?
+    };
 
     match result {
         Ok(_) => {
-            let mut cookie = Cookie::named(COOKIE_NAME);
+            let mut cookie = refresh_token_cookie(&config, String::new());
             cookie.make_removal();
 
             Ok(HttpResponse::Ok().cookie(cookie).finish())
@@ -225,25 +398,37 @@ async fn logout(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, AW
     tag = "Sessions",
 ))]
 #[post("/refresh")]
-async fn refresh(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, AWError> {
-    let refresh_token = req
-        .cookie(COOKIE_NAME)
-        .map(|cookie| String::from(cookie.value()));
+async fn refresh(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let refresh_token = refresh_token_of(&req);
 
-    let result =
-        web::block(move || controller::refresh(&db, refresh_token.as_ref().map(|t| t.as_ref())))
-            .await?;
+    let ip_address = crate::client_ip(&req);
+
+    let result = {
+        let config = config.clone();
+        web::block(move || {
+            controller::refresh(
+                &db,
+                refresh_token.as_ref().map(|t| t.as_ref()),
+                ip_address,
+                &config,
+            )
+        })
+        .await?
+    };
 
     match result {
         Ok((access_token, refresh_token)) => Ok(HttpResponse::build(StatusCode::OK)
-            .cookie(
-                Cookie::build(COOKIE_NAME, refresh_token)
-                    .secure(true)
-                    .http_only(true)
-                    .same_site(SameSite::Strict)
-                    .finish(),
-            )
-            .body(json!({ "access_token": access_token }).to_string())),
+            .cookie(refresh_token_cookie(&config, refresh_token.clone()))
+            .body(controller::session_json(
+                &access_token,
+                &refresh_token,
+                client_of(&req),
+            ))),
         Err((status_code, message)) => Ok(HttpResponse::build(
             StatusCode::from_u16(status_code as u16).unwrap(),
         )
@@ -257,26 +442,53 @@ async fn refresh(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, A
 ///
 /// sends an email, using [`mailer`](`Mailer`), to the email address in [`item`](`RegisterInput`)
 /// that contains a unique link that allows the recipient to activate the account associated with
-/// that email address
+/// that email address, unless they registered with an invite link
 #[cfg_attr(feature = "plugin_utoipa", utoipa::path(
     context_path = "/api/auth",
     request_body(content = RegisterInput, content_type = "application/json"),
     responses(
         (status = 200, description = "Success, sends an email to the user with a link that will let them activate their account", body=AuthMessageResponse),
         (status = 400, description = "Already registered.", body = AuthMessageResponse),
+        (status = 400, description = "The invitation was sent to another email address.", body = AuthMessageResponse),
+        (status = 400, description = "Invitation already accepted.", body = AuthMessageResponse),
+        (status = 400, description = "Invitation expired.", body = AuthMessageResponse),
+        (status = 400, description = "CAPTCHA verification failed.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid invitation.", body = AuthMessageResponse),
+        (status = 403, description = "Registration is by invitation only.", body = AuthMessageResponse),
+        (status = 429, description = "Too many attempts, please try again later.", body = AuthMessageResponse),
     ),
     tag = "Users",
 ))]
 #[post("/register")]
 async fn register(
     db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
     Json(item): Json<RegisterInput>,
     mailer: Data<Mailer>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::register(&db, &item, &mailer);
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(&req);
+
+    if let Err(retry_after) =
+        config.check_rate_limit(RateLimitedAction::Register, &[ip_address.as_deref()])
+    {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    if !config
+        .verify_captcha(item.captcha_token.as_deref(), ip_address.as_deref())
+        .await
+    {
+        return Ok(captcha_failed());
+    }
+
+    let result = controller::register(&db, &item, &mailer, &config);
 
     match result {
-        Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
+        Ok(user) if user.activated => Ok(HttpResponse::build(StatusCode::OK)
+            .body("{ \"message\": \"Registered! You can now log in.\" }")),
+        Ok(_) => Ok(HttpResponse::build(StatusCode::OK)
             .body("{ \"message\": \"Registered! Check your email to activate your account.\" }")),
         Err((status_code, message)) => Ok(HttpResponse::build(
             StatusCode::from_u16(status_code as u16).unwrap(),
@@ -303,10 +515,12 @@ async fn register(
 #[get("/activate")]
 async fn activate(
     db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
     Query(item): Query<ActivationInput>,
     mailer: Data<Mailer>,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::activate(&db, &item, &mailer);
+    let config = auth_config(config);
+    let result = controller::activate(&db, &item, &mailer, &config);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK).body("{ \"message\": \"Activated!\" }")),
@@ -331,16 +545,37 @@ async fn activate(
     request_body(content = ForgotInput, content_type = "application/json"),
     responses(
         (status = 200, description = "Success, password reset email is sent to users email", body=AuthMessageResponse),
+        (status = 400, description = "CAPTCHA verification failed.", body = AuthMessageResponse),
+        (status = 429, description = "Too many attempts, please try again later.", body = AuthMessageResponse),
     ),
     tag = "Users",
 ))]
 #[post("/forgot")]
 async fn forgot_password(
     db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
     Json(item): Json<ForgotInput>,
     mailer: Data<Mailer>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::forgot_password(&db, &item, &mailer);
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(&req);
+
+    if let Err(retry_after) = config.check_rate_limit(
+        RateLimitedAction::PasswordReset,
+        &[ip_address.as_deref(), Some(&item.email)],
+    ) {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    if !config
+        .verify_captcha(item.captcha_token.as_deref(), ip_address.as_deref())
+        .await
+    {
+        return Ok(captcha_failed());
+    }
+
+    let result = controller::forgot_password(&db, &item, &mailer, &config);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
@@ -376,11 +611,14 @@ async fn forgot_password(
 #[post("/change")]
 async fn change_password(
     db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
     Json(item): Json<ChangeInput>,
     auth: Auth,
     mailer: Data<Mailer>,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::change_password(&db, &item, &auth, &mailer);
+    let config = auth_config(config);
+
+    let result = controller::change_password(&db, &item, &auth, &mailer, &config);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
@@ -392,6 +630,88 @@ async fn change_password(
     }
 }
 
+/// handler for POST requests to the .../email/change endpoint
+///
+/// requires auth
+///
+/// emails a confirmation link to [`item.new_email`](`EmailChangeInput`), see [`controller::change_email`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = EmailChangeInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success, confirmation link sent", body=AuthMessageResponse),
+        (status = 400, description = "Missing email or password.", body=AuthMessageResponse),
+        (status = 400, description = "Account has not been activated.", body=AuthMessageResponse),
+        (status = 400, description = "Invalid credentials.", body=AuthMessageResponse),
+        (status = 400, description = "The new email must be different.", body=AuthMessageResponse),
+        (status = 400, description = "Already registered.", body=AuthMessageResponse),
+        (status = 403, description = "Forbidden.", body=AuthMessageResponse),
+        (status = 500, description = "Could not find user.", body=AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[post("/email/change")]
+async fn change_email(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    Json(item): Json<EmailChangeInput>,
+    auth: Auth,
+    mailer: Data<Mailer>,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result =
+        web::block(move || controller::change_email(&db, &item, &auth, &mailer, &config)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::build(StatusCode::OK).body(
+            json!({"message": "Please check the new address for a confirmation link."}).to_string(),
+        )),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../email/confirm endpoint
+///
+/// switches the User's email to the one in [`item.email_change_token`](`EmailConfirmInput`), see [`controller::confirm_email`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = EmailConfirmInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success, email changed", body=AuthMessageResponse),
+        (status = 400, description = "Invalid token.", body=AuthMessageResponse),
+        (status = 400, description = "Already registered.", body=AuthMessageResponse),
+        (status = 401, description = "Invalid token.", body=AuthMessageResponse),
+        (status = 500, description = "Could not update email.", body=AuthMessageResponse),
+    ),
+    tag = "Users",
+))]
+#[post("/email/confirm")]
+async fn confirm_email(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    Json(item): Json<EmailConfirmInput>,
+    mailer: Data<Mailer>,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result =
+        web::block(move || controller::confirm_email(&db, &item, &mailer, &config)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
+            .body(json!({"message": "Email changed."}).to_string())),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
 /// handler for POST requests to the .../check endpoint
 ///
 /// requires auth, but doesn't match it to a user
@@ -423,16 +743,28 @@ async fn check(auth: Auth) -> HttpResponse {
         (status = 400, description = "The new password must be different.", body=AuthMessageResponse),
         (status = 401, description = "Invalid token.", body=AuthMessageResponse),
         (status = 500, description = "Could not update password.", body=AuthMessageResponse),
+        (status = 429, description = "Too many attempts, please try again later.", body = AuthMessageResponse),
     ),
     tag = "Users",
 ))]
 #[post("/reset")]
 async fn reset_password(
     db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
     Json(item): Json<ResetInput>,
     mailer: Data<Mailer>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::reset_password(&db, &item, &mailer);
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(&req);
+
+    if let Err(retry_after) =
+        config.check_rate_limit(RateLimitedAction::PasswordReset, &[ip_address.as_deref()])
+    {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    let result = controller::reset_password(&db, &item, &mailer, &config);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
@@ -444,10 +776,1021 @@ async fn reset_password(
     }
 }
 
+/// handler for GET requests to the .../.well-known/jwks.json endpoint
+///
+/// returns the public keys that can be used to verify the tokens issued by this service
+/// (the key set is empty when tokens are signed with a shared secret)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "JSON Web Key Set", body = Jwks),
+    ),
+    tag = "Auth",
+))]
+#[get("/.well-known/jwks.json")]
+async fn jwks() -> HttpResponse {
+    HttpResponse::Ok().json(jwt::jwks())
+}
+
+/// handler for GET requests to the .../captcha endpoint
+///
+/// returns which CAPTCHA widget the login, register and forgot password forms should show
+/// (the provider is `null` when CAPTCHAs are disabled)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "CAPTCHA provider and site key", body = CaptchaSettings),
+    ),
+    tag = "Auth",
+))]
+#[get("/captcha")]
+async fn captcha(config: Option<Data<AuthConfig>>) -> HttpResponse {
+    HttpResponse::Ok().json(Captcha::settings(auth_config(config).captcha.as_ref()))
+}
+
+/// handler for POST requests to the .../impersonate endpoint
+///
+/// requires auth, and the [`IMPERSONATE_PERMISSION`](`controller::IMPERSONATE_PERMISSION`)
+///
+/// returns an access token to act as the user in [`item`](`ImpersonateInput`)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = ImpersonateInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "access token of the impersonated user", body = AuthTokenResponse),
+        (status = 400, description = "Already impersonating a user.", body = AuthMessageResponse),
+        (status = 400, description = "Cannot impersonate yourself.", body = AuthMessageResponse),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 403, description = "Administrators can't be impersonated.", body = AuthMessageResponse),
+        (status = 404, description = "User not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not record the impersonation.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[post("/impersonate")]
+async fn impersonate(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<ImpersonateInput>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(&req);
+
+    let result =
+        web::block(move || controller::impersonate(&db, &auth, &item, ip_address, &config)).await?;
+
+    match result {
+        Ok(access_token) => {
+            Ok(HttpResponse::Ok().body(json!({ "access_token": access_token }).to_string()))
+        }
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../impersonate/stop endpoint
+///
+/// requires auth, with an access token obtained from the .../impersonate endpoint
+///
+/// ends the impersonation and returns an access token for the admin
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "access token of the admin", body = AuthTokenResponse),
+        (status = 400, description = "Not impersonating a user.", body = AuthMessageResponse),
+        (status = 500, description = "Could not record the end of the impersonation.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[post("/impersonate/stop")]
+async fn stop_impersonating(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result = web::block(move || controller::stop_impersonating(&db, &auth, &config)).await?;
+
+    match result {
+        Ok(access_token) => {
+            Ok(HttpResponse::Ok().body(json!({ "access_token": access_token }).to_string()))
+        }
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for GET requests to the .../account/export endpoint
+///
+/// requires auth (and not impersonating the user)
+///
+/// responds with everything stored about the user, see [`AccountExport`](`crate::auth::account::AccountExport`)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "json payload with the user's data"),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 500, description = "Could not export the account.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[get("/account/export")]
+async fn export_account(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result = web::block(move || controller::export_account(&db, &auth, &config)).await?;
+
+    match result {
+        Ok(export) => Ok(HttpResponse::Ok()
+            .insert_header((
+                "Content-Disposition",
+                "attachment; filename=\"account.json\"",
+            ))
+            .json(export)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for DELETE requests to the .../account endpoint
+///
+/// requires auth (and not impersonating the user)
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`DeleteAccountInput`]
+///
+/// deletes the account, its attachments' files, and the refresh_token cookie
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = DeleteAccountInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Deleted, and the \"refresh_token\" cookie removed"),
+        (status = 400, description = "Invalid credentials", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 500, description = "Could not delete the account.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[delete("/account")]
+async fn delete_account(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<DeleteAccountInput>,
+    _req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let cookie_config = config.clone();
+
+    let result = web::block(move || controller::delete_account(&db, &auth, &item, &config)).await?;
+
+    match result {
+        Ok(_deleted) => {
+            #[cfg(feature = "plugin_storage")]
+            if let Some(storage) = _req.app_data::<Data<Storage>>() {
+                if let Err(err) = storage.delete_many(_deleted.storage_keys).await {
+                    println!("{err}");
+                }
+            }
+
+            let mut cookie = refresh_token_cookie(&cookie_config, String::new());
+            cookie.make_removal();
+
+            Ok(HttpResponse::Ok().cookie(cookie).finish())
+        }
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../guest endpoint
+///
+/// starts a session for a new guest (a user without credentials), see [`controller::create_guest`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "guest session created", body = AuthTokenResponse),
+        (status = 403, description = "Guest sessions are disabled.", body = AuthMessageResponse),
+        (status = 429, description = "Too many attempts, please try again later.", body = AuthMessageResponse),
+        (status = 500, description = "Could not create a guest.", body = AuthMessageResponse),
+    ),
+    tag = "Sessions",
+))]
+#[post("/guest")]
+async fn create_guest(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(&req);
+
+    if let Err(retry_after) =
+        config.check_rate_limit(RateLimitedAction::Register, &[ip_address.as_deref()])
+    {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    let result = {
+        let config = config.clone();
+        web::block(move || controller::create_guest(&db, ip_address, &config)).await?
+    };
+
+    match result {
+        Ok((access_token, refresh_token)) => Ok(HttpResponse::build(StatusCode::OK)
+            .cookie(refresh_token_cookie(&config, refresh_token.clone()))
+            .body(controller::session_json(
+                &access_token,
+                &refresh_token,
+                client_of(&req),
+            ))),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../guest/upgrade endpoint
+///
+/// requires auth, as a guest
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`GuestInput`]
+///
+/// turns the guest into a full account, keeping its id, see [`controller::upgrade_guest`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = GuestInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "access token with the user's new roles", body = AuthTokenResponse),
+        (status = 400, description = "Missing email or password.", body = AuthMessageResponse),
+        (status = 400, description = "Not a guest.", body = AuthMessageResponse),
+        (status = 400, description = "Already registered.", body = AuthMessageResponse),
+        (status = 400, description = "The invitation was sent to another email address.", body = AuthMessageResponse),
+        (status = 400, description = "Invitation already accepted.", body = AuthMessageResponse),
+        (status = 400, description = "Invitation expired.", body = AuthMessageResponse),
+        (status = 400, description = "CAPTCHA verification failed.", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 401, description = "Invalid invitation.", body = AuthMessageResponse),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 403, description = "Registration is by invitation only.", body = AuthMessageResponse),
+        (status = 429, description = "Too many attempts, please try again later.", body = AuthMessageResponse),
+        (status = 500, description = "Could not upgrade the guest.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[post("/guest/upgrade")]
+async fn upgrade_guest(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<GuestInput>,
+    mailer: Data<Mailer>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(&req);
+
+    if let Err(retry_after) =
+        config.check_rate_limit(RateLimitedAction::Register, &[ip_address.as_deref()])
+    {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    if !config
+        .verify_captcha(item.captcha_token.as_deref(), ip_address.as_deref())
+        .await
+    {
+        return Ok(captcha_failed());
+    }
+
+    let result =
+        web::block(move || controller::upgrade_guest(&db, &auth, &item, &mailer, &config)).await?;
+
+    match result {
+        Ok(access_token) => {
+            Ok(HttpResponse::Ok().body(json!({ "access_token": access_token }).to_string()))
+        }
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../guest/merge endpoint
+///
+/// requires auth, as a guest
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`GuestInput`]
+///
+/// moves the guest's data to the existing account and logs into it, see [`controller::merge_guest`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = GuestInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "session created for the existing account", body = AuthTokenResponse),
+        (status = 400, description = "Not a guest.", body = AuthMessageResponse),
+        (status = 400, description = "Account has not been activated.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid credentials.", body = AuthMessageResponse),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 429, description = "Too many attempts, please try again later.", body = AuthMessageResponse),
+        (status = 500, description = "Could not merge the guest.", body = AuthMessageResponse),
+    ),
+    tag = "Sessions",
+    security ( ("JWT" = []))
+))]
+#[post("/guest/merge")]
+async fn merge_guest(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<GuestInput>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(&req);
+
+    if let Err(retry_after) = config.check_rate_limit(
+        RateLimitedAction::Login,
+        &[ip_address.as_deref(), Some(&item.email)],
+    ) {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    let result = {
+        let config = config.clone();
+        web::block(move || controller::merge_guest(&db, &auth, &item, ip_address, &config)).await?
+    };
+
+    match result {
+        Ok((access_token, refresh_token, _deleted)) => {
+            #[cfg(feature = "plugin_storage")]
+            if let Some(storage) = req.app_data::<Data<Storage>>() {
+                if let Err(err) = storage.delete_many(_deleted.storage_keys).await {
+                    println!("{err}");
+                }
+            }
+
+            Ok(HttpResponse::build(StatusCode::OK)
+                .cookie(refresh_token_cookie(&config, refresh_token.clone()))
+                .body(controller::session_json(
+                    &access_token,
+                    &refresh_token,
+                    client_of(&req),
+                )))
+        }
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for GET requests to the .../identities endpoint
+///
+/// requires auth
+///
+/// responds with the accounts linked to the user, and the providers accounts can be linked from
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "the linked accounts and the available providers", body = UserIdentityResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 500, description = "Could not fetch identities.", body = AuthMessageResponse),
+    ),
+    tag = "Identities",
+    security ( ("JWT" = []))
+))]
+#[get("/identities")]
+async fn get_identities(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result = web::block(move || controller::get_identities(&db, &auth, &config)).await?;
+
+    match result {
+        Ok(identities) => Ok(HttpResponse::Ok().json(identities)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../identities/{provider}/authorize endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`AuthorizeIdentityInput`]
+///
+/// responds with the url of the provider's consent screen, see [`controller::authorize_identity`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = AuthorizeIdentityInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Json payload with a \"url\" field to send the user to"),
+        (status = 400, description = "Missing redirect_uri", body = AuthMessageResponse),
+        (status = 400, description = "Invalid redirect_uri", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Provider not found.", body = AuthMessageResponse),
+    ),
+    tag = "Identities",
+    security ( ("JWT" = []))
+))]
+#[post("/identities/{provider}/authorize")]
+async fn authorize_identity(
+    config: Option<Data<AuthConfig>>,
+    provider: Path<String>,
+    auth: Auth,
+    Json(item): Json<AuthorizeIdentityInput>,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    match controller::authorize_identity(&auth, &provider, &item, &config) {
+        Ok(url) => Ok(HttpResponse::Ok().body(json!({ "url": url }).to_string())),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../identities endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`IdentityInput`]
+///
+/// links the provider account the user authorized, see [`controller::fetch_identity`] and [`controller::link_identity`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = IdentityInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "the linked account", body = UserIdentityJson),
+        (status = 400, description = "Could not verify the account with the provider.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid state.", body = AuthMessageResponse),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Provider not found.", body = AuthMessageResponse),
+        (status = 409, description = "Account already linked.", body = AuthMessageResponse),
+        (status = 409, description = "Account already linked to another user.", body = AuthMessageResponse),
+        (status = 409, description = "An account from this provider is already linked.", body = AuthMessageResponse),
+        (status = 409, description = "Another user is registered with this account's email.", body = AuthMessageResponse),
+        (status = 500, description = "Could not link the account.", body = AuthMessageResponse),
+    ),
+    tag = "Identities",
+    security ( ("JWT" = []))
+))]
+#[post("/identities")]
+async fn link_identity(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<IdentityInput>,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let profile = match controller::fetch_identity(&auth, &item, &config).await {
+        Ok(profile) => profile,
+        Err((status_code, message)) => {
+            return Ok(
+                HttpResponse::build(StatusCode::from_u16(status_code as u16).unwrap())
+                    .body(json!({ "message": message }).to_string()),
+            )
+        }
+    };
+
+    let result =
+        web::block(move || controller::link_identity(&db, &auth, &profile, &config)).await?;
+
+    match result {
+        Ok(identity) => Ok(HttpResponse::Ok().json(identity)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for DELETE requests to the .../identities/{id} endpoint
+///
+/// requires auth
+///
+/// unlinks the provider account from the user, see [`controller::unlink_identity`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Unlinked.", body = AuthMessageResponse),
+        (status = 400, description = "Cannot unlink the only way to log in.", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Identity not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not unlink the account.", body = AuthMessageResponse),
+    ),
+    tag = "Identities",
+    security ( ("JWT" = []))
+))]
+#[delete("/identities/{id}")]
+async fn unlink_identity(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    item_id: Path<ID>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result =
+        web::block(move || controller::unlink_identity(&db, &auth, item_id.into_inner(), &config))
+            .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body(json!({ "message": "Unlinked." }).to_string())),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for GET requests to the .../profile endpoint
+///
+/// requires auth
+///
+/// responds with the user's profile, see [`controller::get_profile`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "the app's profile fields"),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 404, description = "Profiles are not enabled.", body = AuthMessageResponse),
+        (status = 500, description = "Could not fetch the profile.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[get("/profile")]
+async fn get_profile(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result = web::block(move || controller::get_profile(&db, &auth, &config)).await?;
+
+    match result {
+        Ok(profile) => Ok(HttpResponse::Ok().json(profile)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for PATCH requests to the .../profile endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json object payload with the fields to change
+///
+/// responds with the updated profile, see [`controller::update_profile`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "the updated profile"),
+        (status = 400, description = "Invalid profile.", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 404, description = "Profiles are not enabled.", body = AuthMessageResponse),
+        (status = 500, description = "Could not update the profile.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[patch("/profile")]
+async fn update_profile(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+    Json(changes): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result =
+        web::block(move || controller::update_profile(&db, &auth, changes, &config)).await?;
+
+    match result {
+        Ok(profile) => Ok(HttpResponse::Ok().json(profile)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../invitations endpoint
+///
+/// requires auth, and the [`INVITE_PERMISSION`](`controller::INVITE_PERMISSION`)
+///
+/// invites the email address in [`item`](`InvitationInput`) to register, and emails them the invite link
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = InvitationInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "the created invitation"),
+        (status = 400, description = "Missing email", body = AuthMessageResponse),
+        (status = 400, description = "Already registered.", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 500, description = "Could not create the invitation.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[post("/invitations")]
+async fn create_invitation(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<InvitationInput>,
+    mailer: Data<Mailer>,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result =
+        web::block(move || controller::create_invitation(&db, &auth, &item, &mailer, &config))
+            .await?;
+
+    match result {
+        Ok(invitation) => Ok(HttpResponse::Ok().json(invitation)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for GET requests to the .../invitations endpoint
+///
+/// requires auth, and the [`INVITE_PERMISSION`](`controller::INVITE_PERMISSION`)
+///
+/// responds with the invitations that weren't accepted yet
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "the pending invitations"),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 500, description = "Could not fetch invitations.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[get("/invitations")]
+async fn get_invitations(db: Data<Database>, auth: Auth) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || controller::get_invitations(&db, &auth)).await?;
+
+    match result {
+        Ok(invitations) => Ok(HttpResponse::Ok().json(invitations)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for DELETE requests to the .../invitations/{id} endpoint
+///
+/// requires auth, and the [`INVITE_PERMISSION`](`controller::INVITE_PERMISSION`)
+///
+/// revokes the invitation, its invite link stops working
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Revoked.", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Invitation not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not revoke the invitation.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[delete("/invitations/{id}")]
+async fn revoke_invitation(
+    db: Data<Database>,
+    item_id: Path<ID>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let result =
+        web::block(move || controller::revoke_invitation(&db, &auth, item_id.into_inner())).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body(json!({ "message": "Revoked." }).to_string())),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for GET requests to the .../invitation endpoint
+///
+/// responds with the invitation behind the token of an invite link, see [`InvitationQuery`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    params(InvitationQuery),
+    responses(
+        (status = 200, description = "the invitation"),
+        (status = 400, description = "Invitation already accepted.", body = AuthMessageResponse),
+        (status = 400, description = "Invitation expired.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid invitation.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+))]
+#[get("/invitation")]
+async fn read_invitation(
+    db: Data<Database>,
+    Query(item): Query<InvitationQuery>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || controller::read_invitation(&db, &item)).await?;
+
+    match result {
+        Ok(invitation) => Ok(HttpResponse::Ok().json(invitation)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../organizations endpoint
+///
+/// requires auth
+///
+/// creates an organization owned by the user
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = OrganizationInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "the created organization"),
+        (status = 400, description = "Missing name", body = AuthMessageResponse),
+        (status = 400, description = "'name' cannot be longer than 256 characters.", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 500, description = "Could not create the organization.", body = AuthMessageResponse),
+    ),
+    tag = "Organizations",
+    security ( ("JWT" = []))
+))]
+#[post("/organizations")]
+async fn create_organization(
+    db: Data<Database>,
+    auth: Auth,
+    Json(item): Json<OrganizationInput>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || controller::create_organization(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(organization) => Ok(HttpResponse::Ok().json(organization)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for GET requests to the .../organizations endpoint
+///
+/// requires auth
+///
+/// responds with the organizations the user is a member of
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "the user's organizations"),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 500, description = "Could not fetch organizations.", body = AuthMessageResponse),
+    ),
+    tag = "Organizations",
+    security ( ("JWT" = []))
+))]
+#[get("/organizations")]
+async fn get_organizations(db: Data<Database>, auth: Auth) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || controller::get_organizations(&db, &auth)).await?;
+
+    match result {
+        Ok(organizations) => Ok(HttpResponse::Ok().json(organizations)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../organizations/{id}/switch endpoint
+///
+/// requires auth, the user must be a member of the organization
+///
+/// makes it the user's active organization and returns an access token that carries it
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "access token for the organization", body = AuthTokenResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Organization not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not switch organizations.", body = AuthMessageResponse),
+    ),
+    tag = "Organizations",
+    security ( ("JWT" = []))
+))]
+#[post("/organizations/{id}/switch")]
+async fn switch_organization(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    organization_id: Path<ID>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result = web::block(move || {
+        controller::switch_organization(&db, &auth, organization_id.into_inner(), &config)
+    })
+    .await?;
+
+    match result {
+        Ok(access_token) => {
+            Ok(HttpResponse::Ok().body(json!({ "access_token": access_token }).to_string()))
+        }
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for GET requests to the .../organizations/{id}/members endpoint
+///
+/// requires auth, the user must be a member of the organization
+///
+/// responds with the organization's members
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "the organization's members"),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 404, description = "Organization not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not fetch members.", body = AuthMessageResponse),
+    ),
+    tag = "Organizations",
+    security ( ("JWT" = []))
+))]
+#[get("/organizations/{id}/members")]
+async fn get_members(
+    db: Data<Database>,
+    organization_id: Path<ID>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let result =
+        web::block(move || controller::get_members(&db, &auth, organization_id.into_inner()))
+            .await?;
+
+    match result {
+        Ok(members) => Ok(HttpResponse::Ok().json(members)),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for POST requests to the .../organizations/{id}/members endpoint
+///
+/// requires auth, the user must be an owner or an admin of the organization
+///
+/// adds the email address in [`item`](`MemberInput`) to the organization, or invites it to
+/// register if it doesn't belong to a user yet
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = MemberInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Added. / Invited.", body = AuthMessageResponse),
+        (status = 400, description = "Missing email", body = AuthMessageResponse),
+        (status = 400, description = "Invalid role.", body = AuthMessageResponse),
+        (status = 400, description = "Already a member.", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Organization not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not add the member.", body = AuthMessageResponse),
+    ),
+    tag = "Organizations",
+    security ( ("JWT" = []))
+))]
+#[post("/organizations/{id}/members")]
+async fn add_member(
+    db: Data<Database>,
+    config: Option<Data<AuthConfig>>,
+    organization_id: Path<ID>,
+    auth: Auth,
+    Json(item): Json<MemberInput>,
+    mailer: Data<Mailer>,
+) -> Result<HttpResponse, AWError> {
+    let config = auth_config(config);
+
+    let result = web::block(move || {
+        controller::add_member(
+            &db,
+            &auth,
+            organization_id.into_inner(),
+            &item,
+            &mailer,
+            &config,
+        )
+    })
+    .await?;
+
+    match result {
+        Ok(AddedMember::Added(_)) => {
+            Ok(HttpResponse::Ok().body(json!({ "message": "Added." }).to_string()))
+        }
+        Ok(AddedMember::Invited(_)) => {
+            Ok(HttpResponse::Ok().body(json!({ "message": "Invited." }).to_string()))
+        }
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
+/// handler for DELETE requests to the .../organizations/{id}/members/{user_id} endpoint
+///
+/// requires auth, members can remove themselves, owners and admins can remove others
+///
+/// removes the member from the organization
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Removed.", body = AuthMessageResponse),
+        (status = 400, description = "An organization needs at least one owner.", body = AuthMessageResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Organization not found.", body = AuthMessageResponse),
+        (status = 404, description = "Member not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not remove the member.", body = AuthMessageResponse),
+    ),
+    tag = "Organizations",
+    security ( ("JWT" = []))
+))]
+#[delete("/organizations/{id}/members/{user_id}")]
+async fn remove_member(
+    db: Data<Database>,
+    path: Path<(ID, ID)>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let (organization_id, user_id) = path.into_inner();
+
+    let result =
+        web::block(move || controller::remove_member(&db, &auth, organization_id, user_id)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body(json!({ "message": "Removed." }).to_string())),
+        Err((status_code, message)) => Ok(HttpResponse::build(
+            StatusCode::from_u16(status_code as u16).unwrap(),
+        )
+        .body(json!({ "message": message }).to_string())),
+    }
+}
+
 /// returns the endpoints for the Auth service
 pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
     scope
         .service(sessions)
+        .service(destroy_other_sessions)
         .service(destroy_session)
         .service(destroy_sessions)
         .service(login)
@@ -458,21 +1801,50 @@ pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
         .service(activate)
         .service(forgot_password)
         .service(change_password)
+        .service(change_email)
+        .service(confirm_email)
         .service(reset_password)
+        .service(impersonate)
+        .service(stop_impersonating)
+        .service(export_account)
+        .service(delete_account)
+        .service(create_guest)
+        .service(upgrade_guest)
+        .service(merge_guest)
+        .service(get_identities)
+        .service(authorize_identity)
+        .service(link_identity)
+        .service(unlink_identity)
+        .service(get_profile)
+        .service(update_profile)
+        .service(create_invitation)
+        .service(get_invitations)
+        .service(revoke_invitation)
+        .service(read_invitation)
+        .service(create_organization)
+        .service(get_organizations)
+        .service(switch_organization)
+        .service(get_members)
+        .service(add_member)
+        .service(remove_member)
+        .service(jwks)
+        .service(captcha)
 }
 
 // swagger
 #[cfg(feature = "plugin_utoipa")]
 #[derive(OpenApi)]
 #[openapi(
-    paths(sessions, destroy_session, destroy_sessions, login, logout, refresh, register, activate, forgot_password, change_password, check, reset_password),
+    paths(sessions, destroy_other_sessions, destroy_session, destroy_sessions, login, logout, refresh, register, activate, forgot_password, change_password, change_email, confirm_email, check, reset_password, impersonate, stop_impersonating, export_account, delete_account, create_guest, upgrade_guest, merge_guest, get_identities, authorize_identity, link_identity, unlink_identity, get_profile, update_profile, create_invitation, get_invitations, revoke_invitation, read_invitation, create_organization, get_organizations, switch_organization, get_members, add_member, remove_member, jwks, captcha),
     components(
-        schemas(UserSessionResponse, UserSessionJson, AuthMessageResponse, AuthTokenResponse, LoginInput, RegisterInput, ForgotInput, ChangeInput, ResetInput)
+        schemas(Jwks, Jwk, CaptchaSettings, CaptchaProvider, UserSessionResponse, UserSessionJson, AuthMessageResponse, AuthTokenResponse, LoginInput, RegisterInput, ForgotInput, ChangeInput, EmailChangeInput, EmailConfirmInput, ResetInput, ImpersonateInput, DeleteAccountInput, GuestInput, AuthorizeIdentityInput, IdentityInput, UserIdentityJson, UserIdentityResponse, InvitationInput, OrganizationInput, MemberInput)
     ),
     tags(
         (name = "Auth", description = "users and user_sessions management endpoints"),
         (name = "Sessions", description = "Endpoints for user_sessions management"),
         (name = "Users", description = "Endpoints for useres management"),
+        (name = "Identities", description = "Endpoints for linking accounts at OAuth providers"),
+        (name = "Organizations", description = "Endpoints for organizations and memberships management"),
     ),
     modifiers(&JwtSecurityAddon)
 )]
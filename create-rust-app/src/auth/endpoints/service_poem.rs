@@ -1,19 +1,27 @@
 use poem::{
     delete, get, handler,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     post,
     web::{
         cookie::{Cookie, CookieJar, SameSite},
         Data, Json, Path, Query,
     },
-    Error, IntoResponse, Response, Result, Route,
+    Error, IntoResponse, Request, Response, Result, Route,
 };
 use serde_json::json;
 
 use crate::auth::controller::{
-    ActivationInput, ChangeInput, ForgotInput, LoginInput, RegisterInput, ResetInput, COOKIE_NAME,
+    ActivationInput, AddedMember, AuthorizeIdentityInput, ChangeInput, DeleteAccountInput,
+    EmailChangeInput, EmailConfirmInput, ForgotInput, GuestInput, IdentityInput, ImpersonateInput,
+    InvitationInput, InvitationQuery, LoginInput, MemberInput, OrganizationInput, RegisterInput,
+    ResetInput, CLIENT_HEADER, COOKIE_NAME, REFRESH_TOKEN_HEADER,
 };
-use crate::auth::{controller, Auth, PaginationParams, ID};
+use crate::auth::{
+    controller, jwt, Auth, AuthConfig, Captcha, CaptchaSettings, PaginationParams,
+    RateLimitedAction, ID,
+};
+#[cfg(feature = "plugin_storage")]
+use crate::Storage;
 use crate::{Database, Mailer};
 
 fn error_response(status_code: i32, message: &'static str) -> Error {
@@ -23,6 +31,67 @@ fn error_response(status_code: i32, message: &'static str) -> Error {
     )
 }
 
+/// the response when [`RateLimiter`](`crate::auth::RateLimiter`) rejects an attempt
+fn too_many_requests(retry_after: std::time::Duration) -> Error {
+    Error::from_response(
+        Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header("Retry-After", retry_after.as_secs().max(1).to_string())
+            .body(json!({ "message": "Too many attempts, please try again later." }).to_string()),
+    )
+}
+
+/// the response when the [`Captcha`](`crate::auth::Captcha`) token is missing or invalid
+fn captcha_failed() -> Error {
+    error_response(400, "CAPTCHA verification failed.")
+}
+
+/// the [`AuthConfig`] added to the app's data, or the default one
+/// the requesting device's refresh token: its cookie, or the [`REFRESH_TOKEN_HEADER`] of the
+/// clients without cookies
+fn refresh_token_of(cookie_jar: &CookieJar, headers: &HeaderMap) -> Option<String> {
+    cookie_jar
+        .get(COOKIE_NAME)
+        .map(|cookie| String::from(cookie.value_str()))
+        .or_else(|| {
+            headers
+                .get(REFRESH_TOKEN_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+        })
+}
+
+/// the [`CLIENT_HEADER`], see [`controller::session_json`]
+fn client_of(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(CLIENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+}
+
+fn auth_config(config: Option<Data<&AuthConfig>>) -> AuthConfig {
+    config.map(|config| config.0.clone()).unwrap_or_default()
+}
+
+/// builds the refresh token cookie with the attributes from [`config`](`AuthConfig`)
+fn refresh_token_cookie(config: &AuthConfig, refresh_token: String) -> Cookie {
+    let mut cookie = Cookie::new(COOKIE_NAME, refresh_token);
+    cookie.set_secure(config.cookie_secure);
+    cookie.set_http_only(true);
+    cookie.set_same_site(match config.cookie_same_site {
+        crate::auth::SameSite::Strict => SameSite::Strict,
+        crate::auth::SameSite::Lax => SameSite::Lax,
+        crate::auth::SameSite::None => SameSite::None,
+    });
+    if let Some(domain) = &config.cookie_domain {
+        cookie.set_domain(domain);
+    }
+    if let Some(path) = &config.cookie_path {
+        cookie.set_path(path);
+    }
+
+    cookie
+}
+
 #[handler]
 /// handler for GET requests at the .../sessions endpoint,
 ///
@@ -40,10 +109,16 @@ fn error_response(status_code: i32, message: &'static str) -> Error {
 /// TODO: document the rest of the possible StatusCodes
 async fn sessions(
     db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
     auth: Auth,
     Query(info): Query<PaginationParams>,
+    cookie_jar: &CookieJar,
+    headers: &HeaderMap,
 ) -> Result<impl IntoResponse> {
-    let result = controller::get_sessions(db.0, &auth, &info);
+    let config = auth_config(config);
+    let refresh_token = refresh_token_of(cookie_jar, headers);
+
+    let result = controller::get_sessions(db.0, &auth, &info, refresh_token.as_deref(), &config);
 
     match result {
         Ok(sessions) => Ok(Json(sessions)),
@@ -66,8 +141,49 @@ async fn sessions(
 /// | 200 | Json payload : {"message": "Deleted."}
 /// | 500 | Json payload : {"message": "Could not delete sessions."}
 /// TODO: document the rest of the possible StatusCodes
-async fn destroy_sessions(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
-    let result = controller::destroy_sessions(db.0, &auth);
+async fn destroy_sessions(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let result = controller::destroy_sessions(db.0, &auth, &auth_config(config));
+
+    match result {
+        Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../sessions/others endpoint
+///
+/// requires auth
+///
+/// deletes all sessions belonging to the user, except the one of the requesting device
+///
+/// see [`controller::destroy_other_sessions`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 401 | Json payload : {"message": "Invalid session."}
+/// | 500 | Json payload : {"message": "Could not delete sessions."}
+async fn destroy_other_sessions(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+    cookie_jar: &CookieJar,
+    headers: &HeaderMap,
+) -> Result<impl IntoResponse> {
+    let refresh_token = refresh_token_of(cookie_jar, headers);
+
+    let result = controller::destroy_other_sessions(
+        db.0,
+        &auth,
+        refresh_token.as_deref(),
+        &auth_config(config),
+    );
 
     match result {
         Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
@@ -93,10 +209,11 @@ async fn destroy_sessions(db: Data<&Database>, auth: Auth) -> Result<impl IntoRe
 /// TODO: document the rest of the possible StatusCodes
 async fn destroy_session(
     db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
     Path(item_id): Path<ID>,
     auth: Auth,
 ) -> Result<impl IntoResponse> {
-    let result = controller::destroy_session(db.0, &auth, item_id);
+    let result = controller::destroy_session(db.0, &auth, item_id, &auth_config(config));
 
     match result {
         Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
@@ -117,26 +234,44 @@ async fn destroy_session(
 /// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
 /// | 400 | Json payload : {"message": "'device' cannot be longer than 256 characters."}
 /// | 400 | Json payload : {"message": "Account has not been activated."}
+/// | 400 | Json payload : {"message": "CAPTCHA verification failed."}
 /// | 401 | Json payload : {"message": "Invalid credentials."}
 /// | 500 | Json payload : {"message": "An internal server error occurred."}
 /// | 500 | Json payload : {"message": "Could not create a session."}
+/// | 429 | Json payload : {"message": "Too many attempts, please try again later."} (with a `Retry-After` header)
 /// TODO: document the rest of the possible StatusCodes
 async fn login(
     db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
     Json(item): Json<LoginInput>,
     cookie_jar: &CookieJar,
+    headers: &HeaderMap,
+    req: &Request,
 ) -> Result<impl IntoResponse> {
-    let result = controller::login(db.0, &item);
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(req);
+
+    if let Err(retry_after) = config.check_rate_limit(
+        RateLimitedAction::Login,
+        &[ip_address.as_deref(), Some(&item.email)],
+    ) {
+        return Err(too_many_requests(retry_after));
+    }
+
+    if !config
+        .verify_captcha(item.captcha_token.as_deref(), ip_address.as_deref())
+        .await
+    {
+        return Err(captcha_failed());
+    }
+
+    let result = controller::login(db.0, &item, ip_address, &config);
 
     match result {
         Ok((access_token, refresh_token)) => {
-            let mut cookie = Cookie::new(COOKIE_NAME, refresh_token);
-            cookie.set_secure(true);
-            cookie.set_http_only(true);
-            cookie.set_same_site(SameSite::Strict);
-            cookie_jar.add(cookie);
+            cookie_jar.add(refresh_token_cookie(&config, refresh_token.clone()));
 
-            let json = json!({ "access_token": access_token }).to_string();
+            let json = controller::session_json(&access_token, &refresh_token, client_of(headers));
             let response = Response::builder().status(StatusCode::OK).body(json);
 
             Ok(response)
@@ -150,6 +285,8 @@ async fn login(
 ///
 /// see [`controller::logout']
 ///
+/// also revokes the access token it was called with, if any
+///
 /// # Responses
 /// | StatusCode | content |
 /// |:------------|---------|
@@ -157,16 +294,21 @@ async fn login(
 /// | 401 | Json payload : {"message": "Invalid session."}
 /// | 401 | Json payload : {"message": "Could not delete session."}
 /// TODO: document the rest of the possible StatusCodes
-async fn logout(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl IntoResponse> {
-    let refresh_token = cookie_jar
-        .get(COOKIE_NAME)
-        .map(|cookie| String::from(cookie.value_str()));
+async fn logout(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Option<Auth>,
+    cookie_jar: &CookieJar,
+    headers: &HeaderMap,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+    let refresh_token = refresh_token_of(cookie_jar, headers);
 
-    let result = controller::logout(db.0, refresh_token.as_deref());
+    let result = controller::logout(db.0, auth.as_ref(), refresh_token.as_deref(), &config);
 
     match result {
         Ok(_) => {
-            let mut cookie = Cookie::named(COOKIE_NAME);
+            let mut cookie = refresh_token_cookie(&config, String::new());
             cookie.make_removal();
 
             cookie_jar.add(cookie);
@@ -189,24 +331,30 @@ async fn logout(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl Into
 /// | 401 | Json payload : {"message": "Invalid session."}
 /// | 401 | Json payload : {"message": "Invalid token."}
 /// TODO: document the rest of the possible StatusCodes
-async fn refresh(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl IntoResponse> {
-    let refresh_token = cookie_jar
-        .get(COOKIE_NAME)
-        .map(|cookie| String::from(cookie.value_str()));
+async fn refresh(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    cookie_jar: &CookieJar,
+    headers: &HeaderMap,
+    req: &Request,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+    let refresh_token = refresh_token_of(cookie_jar, headers);
+    let ip_address = crate::client_ip(req);
 
-    let result = controller::refresh(db.0, refresh_token.as_deref());
+    let result = controller::refresh(db.0, refresh_token.as_deref(), ip_address, &config);
 
     match result {
         Ok((access_token, refresh_token)) => {
-            let mut cookie = Cookie::new(COOKIE_NAME, refresh_token);
-            cookie.set_secure(true);
-            cookie.set_http_only(true);
-            cookie.set_same_site(SameSite::Strict);
-            cookie_jar.add(cookie);
+            cookie_jar.add(refresh_token_cookie(&config, refresh_token.clone()));
 
             Ok(Response::builder()
                 .status(StatusCode::OK)
-                .body(json!({ "access_token": access_token }).to_string()))
+                .body(controller::session_json(
+                    &access_token,
+                    &refresh_token,
+                    client_of(headers),
+                )))
         }
         Err((s, m)) => Err(error_response(s, m)),
     }
@@ -223,16 +371,45 @@ async fn refresh(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl Int
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload : {"message": "Registered! Check your email to activate your account."}
+/// | 200 | Json payload : {"message": "Registered! You can now log in."} (with an invitation)
 /// | 400 | Json payload : {"message": "Already registered."}
+/// | 400 | Json payload : {"message": "The invitation was sent to another email address."}
+/// | 400 | Json payload : {"message": "Invitation already accepted."}
+/// | 400 | Json payload : {"message": "Invitation expired."}
+/// | 400 | Json payload : {"message": "CAPTCHA verification failed."}
+/// | 401 | Json payload : {"message": "Invalid invitation."}
+/// | 403 | Json payload : {"message": "Registration is by invitation only."}
+/// | 429 | Json payload : {"message": "Too many attempts, please try again later."} (with a `Retry-After` header)
 /// TODO: document the rest of the possible StatusCodes
 async fn register(
     db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
     Json(item): Json<RegisterInput>,
     mailer: Data<&Mailer>,
+    req: &Request,
 ) -> Result<impl IntoResponse> {
-    let result = controller::register(db.0, &item, mailer.0);
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(req);
+
+    if let Err(retry_after) =
+        config.check_rate_limit(RateLimitedAction::Register, &[ip_address.as_deref()])
+    {
+        return Err(too_many_requests(retry_after));
+    }
+
+    if !config
+        .verify_captcha(item.captcha_token.as_deref(), ip_address.as_deref())
+        .await
+    {
+        return Err(captcha_failed());
+    }
+
+    let result = controller::register(db.0, &item, mailer.0, &config);
 
     match result {
+        Ok(user) if user.activated => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body("{ \"message\": \"Registered! You can now log in.\" }")),
         Ok(_) => Ok(Response::builder()
             .status(StatusCode::OK)
             .body("{ \"message\": \"Registered! Check your email to activate your account.\" }")),
@@ -256,10 +433,12 @@ async fn register(
 /// TODO: document the rest of the possible StatusCodes
 async fn activate(
     db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
     Query(item): Query<ActivationInput>,
     mailer: Data<&Mailer>,
 ) -> Result<impl IntoResponse> {
-    let result = controller::activate(db.0, &item, mailer.0);
+    let config = auth_config(config);
+    let result = controller::activate(db.0, &item, mailer.0, &config);
 
     match result {
         Ok(_) => Ok(Response::builder()
@@ -281,13 +460,34 @@ async fn activate(
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload : {"message": "Please check your email."}
+/// | 400 | Json payload : {"message": "CAPTCHA verification failed."}
+/// | 429 | Json payload : {"message": "Too many attempts, please try again later."} (with a `Retry-After` header)
 /// TODO: document the rest of the possible StatusCodes
 async fn forgot_password(
     db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
     Json(item): Json<ForgotInput>,
     mailer: Data<&Mailer>,
+    req: &Request,
 ) -> Result<impl IntoResponse> {
-    let result = controller::forgot_password(db.0, &item, mailer.0);
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(req);
+
+    if let Err(retry_after) = config.check_rate_limit(
+        RateLimitedAction::PasswordReset,
+        &[ip_address.as_deref(), Some(&item.email)],
+    ) {
+        return Err(too_many_requests(retry_after));
+    }
+
+    if !config
+        .verify_captcha(item.captcha_token.as_deref(), ip_address.as_deref())
+        .await
+    {
+        return Err(captcha_failed());
+    }
+
+    let result = controller::forgot_password(db.0, &item, mailer.0, &config);
 
     match result {
         Ok(_) => Ok(Response::builder()
@@ -319,11 +519,12 @@ async fn forgot_password(
 /// TODO: document the rest of the possible StatusCodes
 async fn change_password(
     db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
     Json(item): Json<ChangeInput>,
     auth: Auth,
     mailer: Data<&Mailer>,
 ) -> Result<impl IntoResponse> {
-    let result = controller::change_password(db.0, &item, &auth, mailer.0);
+    let result = controller::change_password(db.0, &item, &auth, mailer.0, &auth_config(config));
 
     match result {
         Ok(_) => Ok(Response::builder()
@@ -333,6 +534,74 @@ async fn change_password(
     }
 }
 
+#[handler]
+/// handler for POST requests to the .../email/change endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`EmailChangeInput`]
+///
+/// see [`controller::change_email`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Please check the new address for a confirmation link."}
+/// | 400 | Json payload : {"message": "Missing email or password."}
+/// | 400 | Json payload : {"message": "Account has not been activated."}
+/// | 400 | Json payload : {"message": "Invalid credentials."}
+/// | 400 | Json payload : {"message": "The new email must be different."}
+/// | 400 | Json payload : {"message": "Already registered."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not find user."}
+async fn change_email(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    Json(item): Json<EmailChangeInput>,
+    auth: Auth,
+    mailer: Data<&Mailer>,
+) -> Result<impl IntoResponse> {
+    let result = controller::change_email(db.0, &item, &auth, mailer.0, &auth_config(config));
+
+    match result {
+        Ok(_) => Ok(Response::builder().status(StatusCode::OK).body(
+            json!({"message": "Please check the new address for a confirmation link."}).to_string(),
+        )),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../email/confirm endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`EmailConfirmInput`]
+///
+/// see [`controller::confirm_email`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Email changed."}
+/// | 400 | Json payload : {"message": "Invalid token."}
+/// | 400 | Json payload : {"message": "Already registered."}
+/// | 401 | Json payload : {"message": "Invalid token."}
+/// | 500 | Json payload : {"message": "Could not update email."}
+async fn confirm_email(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    Json(item): Json<EmailConfirmInput>,
+    mailer: Data<&Mailer>,
+) -> Result<impl IntoResponse> {
+    let result = controller::confirm_email(db.0, &item, mailer.0, &auth_config(config));
+
+    match result {
+        Ok(_) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Email changed."}).to_string())),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
 #[handler]
 /// handler for POST requests to the .../reset endpoint
 ///
@@ -349,12 +618,24 @@ async fn change_password(
 /// | 400 | Json payload : {"message": "The new password must be different."}
 /// | 401 | Json payload : {"message": "Invalid token."}
 /// | 500 | Json payload : {"message": "Could not update password."}
+/// | 429 | Json payload : {"message": "Too many attempts, please try again later."} (with a `Retry-After` header)
 async fn reset_password(
     db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
     Json(item): Json<ResetInput>,
     mailer: Data<&Mailer>,
+    req: &Request,
 ) -> Result<impl IntoResponse> {
-    let result = controller::reset_password(db.0, &item, mailer.0);
+    let config = auth_config(config);
+
+    if let Err(retry_after) = config.check_rate_limit(
+        RateLimitedAction::PasswordReset,
+        &[crate::client_ip(req).as_deref()],
+    ) {
+        return Err(too_many_requests(retry_after));
+    }
+
+    let result = controller::reset_password(db.0, &item, mailer.0, &config);
 
     match result {
         Ok(_) => Ok(Response::builder()
@@ -380,10 +661,790 @@ async fn check(auth: Auth) -> Result<impl IntoResponse> {
     Ok(Response::builder().status(StatusCode::OK).finish())
 }
 
+#[handler]
+/// handler for GET requests to the .../.well-known/jwks.json endpoint
+///
+/// see [`jwt::jwks`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the JSON Web Key Set tokens can be verified with
+async fn jwks() -> Json<&'static jwt::Jwks> {
+    Json(jwt::jwks())
+}
+
+#[handler]
+/// handler for GET requests to the .../captcha endpoint
+///
+/// see [`Captcha::settings`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"provider": "hcaptcha" \| "turnstile" \| "recaptcha" \| null, "site_key": ...}
+async fn captcha(config: Option<Data<&AuthConfig>>) -> Json<CaptchaSettings> {
+    Json(Captcha::settings(auth_config(config).captcha.as_ref()))
+}
+
+#[handler]
+/// handler for POST requests to the .../impersonate endpoint
+///
+/// requires auth, and the [`IMPERSONATE_PERMISSION`](`controller::IMPERSONATE_PERMISSION`)
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ImpersonateInput`]
+///
+/// see [`controller::impersonate`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "access_token" field containing a JWT of the impersonated user
+/// | 400 | Json payload : {"message": "Already impersonating a user."}
+/// | 400 | Json payload : {"message": "Cannot impersonate yourself."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 403 | Json payload : {"message": "Administrators can't be impersonated."}
+/// | 404 | Json payload : {"message": "User not found."}
+/// | 500 | Json payload : {"message": "Could not record the impersonation."}
+async fn impersonate(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<ImpersonateInput>,
+    req: &Request,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(req);
+
+    match controller::impersonate(db.0, &auth, &item, ip_address, &config) {
+        Ok(access_token) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({ "access_token": access_token }).to_string())),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../impersonate/stop endpoint
+///
+/// requires auth, with an access token obtained from the .../impersonate endpoint
+///
+/// see [`controller::stop_impersonating`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "access_token" field containing a JWT of the admin
+/// | 400 | Json payload : {"message": "Not impersonating a user."}
+/// | 500 | Json payload : {"message": "Could not record the end of the impersonation."}
+async fn stop_impersonating(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::stop_impersonating(db.0, &auth, &config) {
+        Ok(access_token) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({ "access_token": access_token }).to_string())),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for GET requests to the .../account/export endpoint
+///
+/// requires auth (and not impersonating the user)
+///
+/// see [`controller::export_account`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AccountExport`](`crate::auth::account::AccountExport`) serialized into a Json payload
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not export the account."}
+async fn export_account(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::export_account(db.0, &auth, &config) {
+        Ok(export) => Ok(Json(export).with_header(
+            "Content-Disposition",
+            "attachment; filename=\"account.json\"",
+        )),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests to the .../account endpoint
+///
+/// requires auth (and not impersonating the user)
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`DeleteAccountInput`]
+///
+/// see [`controller::delete_account`], also deletes the account's attachments' files and the refresh_token cookie
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 400 | Json payload : {"message": "Invalid credentials"}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not delete the account."}
+async fn delete_account(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<DeleteAccountInput>,
+    cookie_jar: &CookieJar,
+    _req: &Request,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::delete_account(db.0, &auth, &item, &config) {
+        Ok(_deleted) => {
+            #[cfg(feature = "plugin_storage")]
+            if let Some(storage) = _req.data::<Storage>() {
+                if let Err(err) = storage.delete_many(_deleted.storage_keys).await {
+                    println!("{err}");
+                }
+            }
+
+            let mut cookie = refresh_token_cookie(&config, String::new());
+            cookie.make_removal();
+
+            cookie_jar.add(cookie);
+
+            Ok(Response::builder().status(StatusCode::OK).finish())
+        }
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../guest endpoint
+///
+/// see [`controller::create_guest`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "access_token" field containing a JWT of the new guest
+/// | 403 | Json payload : {"message": "Guest sessions are disabled."}
+/// | 429 | Json payload : {"message": "Too many attempts, please try again later."} (with a `Retry-After` header)
+/// | 500 | Json payload : {"message": "Could not create a guest."}
+async fn create_guest(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    cookie_jar: &CookieJar,
+    headers: &HeaderMap,
+    req: &Request,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(req);
+
+    if let Err(retry_after) =
+        config.check_rate_limit(RateLimitedAction::Register, &[ip_address.as_deref()])
+    {
+        return Err(too_many_requests(retry_after));
+    }
+
+    match controller::create_guest(db.0, ip_address, &config) {
+        Ok((access_token, refresh_token)) => {
+            cookie_jar.add(refresh_token_cookie(&config, refresh_token.clone()));
+
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .body(controller::session_json(
+                    &access_token,
+                    &refresh_token,
+                    client_of(headers),
+                )))
+        }
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../guest/upgrade endpoint
+///
+/// requires auth, as a guest
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`GuestInput`]
+///
+/// see [`controller::upgrade_guest`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "access_token" field containing a JWT with the user's new roles
+/// | 400 | Json payload : {"message": "Missing email or password."}
+/// | 400 | Json payload : {"message": "Not a guest."}
+/// | 400 | Json payload : {"message": "Already registered."}
+/// | 400 | Json payload : {"message": "The invitation was sent to another email address."}
+/// | 400 | Json payload : {"message": "Invitation already accepted."}
+/// | 400 | Json payload : {"message": "Invitation expired."}
+/// | 400 | Json payload : {"message": "CAPTCHA verification failed."}
+/// | 401 | Json payload : {"message": "Invalid invitation."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 403 | Json payload : {"message": "Registration is by invitation only."}
+/// | 429 | Json payload : {"message": "Too many attempts, please try again later."}
+/// | 500 | Json payload : {"message": "Could not upgrade the guest."}
+async fn upgrade_guest(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<GuestInput>,
+    mailer: Data<&Mailer>,
+    req: &Request,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(req);
+
+    if let Err(retry_after) =
+        config.check_rate_limit(RateLimitedAction::Register, &[ip_address.as_deref()])
+    {
+        return Err(too_many_requests(retry_after));
+    }
+
+    if !config
+        .verify_captcha(item.captcha_token.as_deref(), ip_address.as_deref())
+        .await
+    {
+        return Err(captcha_failed());
+    }
+
+    match controller::upgrade_guest(db.0, &auth, &item, mailer.0, &config) {
+        Ok(access_token) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({ "access_token": access_token }).to_string())),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../guest/merge endpoint
+///
+/// requires auth, as a guest
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`GuestInput`]
+///
+/// see [`controller::merge_guest`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "access_token" field containing a JWT of the existing account
+/// | 400 | Json payload : {"message": "Not a guest."}
+/// | 400 | Json payload : {"message": "Account has not been activated."}
+/// | 401 | Json payload : {"message": "Invalid credentials."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 429 | Json payload : {"message": "Too many attempts, please try again later."} (with a `Retry-After` header)
+/// | 500 | Json payload : {"message": "Could not merge the guest."}
+async fn merge_guest(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<GuestInput>,
+    cookie_jar: &CookieJar,
+    headers: &HeaderMap,
+    req: &Request,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+    let ip_address = crate::client_ip(req);
+
+    if let Err(retry_after) = config.check_rate_limit(
+        RateLimitedAction::Login,
+        &[ip_address.as_deref(), Some(&item.email)],
+    ) {
+        return Err(too_many_requests(retry_after));
+    }
+
+    match controller::merge_guest(db.0, &auth, &item, ip_address, &config) {
+        Ok((access_token, refresh_token, _deleted)) => {
+            #[cfg(feature = "plugin_storage")]
+            if let Some(storage) = req.data::<Storage>() {
+                if let Err(err) = storage.delete_many(_deleted.storage_keys).await {
+                    println!("{err}");
+                }
+            }
+
+            cookie_jar.add(refresh_token_cookie(&config, refresh_token.clone()));
+
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .body(controller::session_json(
+                    &access_token,
+                    &refresh_token,
+                    client_of(headers),
+                )))
+        }
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for GET requests to the .../identities endpoint
+///
+/// requires auth
+///
+/// see [`controller::get_identities`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with the linked accounts and the available providers
+/// | 500 | Json payload : {"message": "Could not fetch identities."}
+async fn get_identities(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::get_identities(db.0, &auth, &config) {
+        Ok(identities) => Ok(Json(identities)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../identities/:provider/authorize endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`AuthorizeIdentityInput`]
+///
+/// see [`controller::authorize_identity`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with a "url" field to send the user to
+/// | 400 | Json payload : {"message": "Missing redirect_uri"}
+/// | 400 | Json payload : {"message": "Invalid redirect_uri"}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Provider not found."}
+async fn authorize_identity(
+    config: Option<Data<&AuthConfig>>,
+    Path(provider): Path<String>,
+    auth: Auth,
+    Json(item): Json<AuthorizeIdentityInput>,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::authorize_identity(&auth, &provider, &item, &config) {
+        Ok(url) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({ "url": url }).to_string())),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../identities endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`IdentityInput`]
+///
+/// see [`controller::fetch_identity`] and [`controller::link_identity`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with the linked account
+/// | 400 | Json payload : {"message": "Could not verify the account with the provider."}
+/// | 401 | Json payload : {"message": "Invalid state."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Provider not found."}
+/// | 409 | Json payload : {"message": "Account already linked."}
+/// | 409 | Json payload : {"message": "Account already linked to another user."}
+/// | 409 | Json payload : {"message": "An account from this provider is already linked."}
+/// | 409 | Json payload : {"message": "Another user is registered with this account's email."}
+/// | 500 | Json payload : {"message": "Could not link the account."}
+async fn link_identity(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<IdentityInput>,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    let profile = match controller::fetch_identity(&auth, &item, &config).await {
+        Ok(profile) => profile,
+        Err((s, m)) => return Err(error_response(s, m)),
+    };
+
+    match controller::link_identity(db.0, &auth, &profile, &config) {
+        Ok(identity) => Ok(Json(identity)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests to the .../identities/:id endpoint
+///
+/// requires auth
+///
+/// see [`controller::unlink_identity`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 400 | Json payload : {"message": "Cannot unlink the only way to log in."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Identity not found."}
+/// | 500 | Json payload : {"message": "Could not unlink the account."}
+async fn unlink_identity(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    Path(item_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::unlink_identity(db.0, &auth, item_id, &config) {
+        Ok(()) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for GET requests to the .../profile endpoint
+///
+/// requires auth
+///
+/// see [`controller::get_profile`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the app's profile fields
+/// | 404 | Json payload : {"message": "Profiles are not enabled."}
+/// | 500 | Json payload : {"message": "Could not fetch the profile."}
+async fn get_profile(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::get_profile(db.0, &auth, &config) {
+        Ok(profile) => Ok(Json(profile)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for PATCH requests to the .../profile endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json object payload with the fields to change
+///
+/// see [`controller::update_profile`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the updated profile
+/// | 400 | Json payload : {"message": "Invalid profile."}
+/// | 404 | Json payload : {"message": "Profiles are not enabled."}
+/// | 500 | Json payload : {"message": "Could not update the profile."}
+async fn update_profile(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+    Json(changes): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::update_profile(db.0, &auth, changes, &config) {
+        Ok(profile) => Ok(Json(profile)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../invitations endpoint
+///
+/// requires auth, and the [`INVITE_PERMISSION`](`controller::INVITE_PERMISSION`)
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`InvitationInput`]
+///
+/// see [`controller::create_invitation`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with the created invitation
+/// | 400 | Json payload : {"message": "Missing email"}
+/// | 400 | Json payload : {"message": "Already registered."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not create the invitation."}
+async fn create_invitation(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    auth: Auth,
+    Json(item): Json<InvitationInput>,
+    mailer: Data<&Mailer>,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::create_invitation(db.0, &auth, &item, mailer.0, &config) {
+        Ok(invitation) => Ok(Json(invitation)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for GET requests to the .../invitations endpoint
+///
+/// requires auth, and the [`INVITE_PERMISSION`](`controller::INVITE_PERMISSION`)
+///
+/// see [`controller::get_invitations`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with the pending invitations
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch invitations."}
+async fn get_invitations(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    match controller::get_invitations(db.0, &auth) {
+        Ok(invitations) => Ok(Json(invitations)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests to the .../invitations/:id endpoint
+///
+/// requires auth, and the [`INVITE_PERMISSION`](`controller::INVITE_PERMISSION`)
+///
+/// see [`controller::revoke_invitation`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Invitation not found."}
+/// | 500 | Json payload : {"message": "Could not revoke the invitation."}
+async fn revoke_invitation(
+    db: Data<&Database>,
+    Path(item_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    match controller::revoke_invitation(db.0, &auth, item_id) {
+        Ok(()) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for GET requests to the .../invitation endpoint
+///
+/// see [`controller::read_invitation`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with the invitation
+/// | 400 | Json payload : {"message": "Invitation already accepted."}
+/// | 400 | Json payload : {"message": "Invitation expired."}
+/// | 401 | Json payload : {"message": "Invalid invitation."}
+async fn read_invitation(
+    db: Data<&Database>,
+    Query(item): Query<InvitationQuery>,
+) -> Result<impl IntoResponse> {
+    match controller::read_invitation(db.0, &item) {
+        Ok(invitation) => Ok(Json(invitation)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../organizations endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`OrganizationInput`]
+///
+/// see [`controller::create_organization`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with the created organization
+/// | 400 | Json payload : {"message": "Missing name"}
+/// | 400 | Json payload : {"message": "'name' cannot be longer than 256 characters."}
+/// | 500 | Json payload : {"message": "Could not create the organization."}
+async fn create_organization(
+    db: Data<&Database>,
+    auth: Auth,
+    Json(item): Json<OrganizationInput>,
+) -> Result<impl IntoResponse> {
+    match controller::create_organization(db.0, &auth, &item) {
+        Ok(organization) => Ok(Json(organization)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for GET requests to the .../organizations endpoint
+///
+/// requires auth
+///
+/// see [`controller::get_organizations`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with the user's organizations
+/// | 500 | Json payload : {"message": "Could not fetch organizations."}
+async fn get_organizations(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    match controller::get_organizations(db.0, &auth) {
+        Ok(organizations) => Ok(Json(organizations)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../organizations/:id/switch endpoint
+///
+/// requires auth, the user must be a member of the organization
+///
+/// see [`controller::switch_organization`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "access_token" field containing a JWT that carries the organization
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Organization not found."}
+/// | 500 | Json payload : {"message": "Could not switch organizations."}
+async fn switch_organization(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    Path(organization_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    match controller::switch_organization(db.0, &auth, organization_id, &config) {
+        Ok(access_token) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({ "access_token": access_token }).to_string())),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for GET requests to the .../organizations/:id/members endpoint
+///
+/// requires auth, the user must be a member of the organization
+///
+/// see [`controller::get_members`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with the organization's members
+/// | 404 | Json payload : {"message": "Organization not found."}
+/// | 500 | Json payload : {"message": "Could not fetch members."}
+async fn get_members(
+    db: Data<&Database>,
+    Path(organization_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    match controller::get_members(db.0, &auth, organization_id) {
+        Ok(members) => Ok(Json(members)),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../organizations/:id/members endpoint
+///
+/// requires auth, the user must be an owner or an admin of the organization
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`MemberInput`]
+///
+/// see [`controller::add_member`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Added."} or {"message": "Invited."}
+/// | 400 | Json payload : {"message": "Missing email"}
+/// | 400 | Json payload : {"message": "Invalid role."}
+/// | 400 | Json payload : {"message": "Already a member."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Organization not found."}
+/// | 500 | Json payload : {"message": "Could not add the member."}
+async fn add_member(
+    db: Data<&Database>,
+    config: Option<Data<&AuthConfig>>,
+    Path(organization_id): Path<ID>,
+    auth: Auth,
+    Json(item): Json<MemberInput>,
+    mailer: Data<&Mailer>,
+) -> Result<impl IntoResponse> {
+    let config = auth_config(config);
+
+    let message =
+        match controller::add_member(db.0, &auth, organization_id, &item, mailer.0, &config) {
+            Ok(AddedMember::Added(_)) => "Added.",
+            Ok(AddedMember::Invited(_)) => "Invited.",
+            Err((s, m)) => return Err(error_response(s, m)),
+        };
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .body(json!({ "message": message }).to_string()))
+}
+
+#[handler]
+/// handler for DELETE requests to the .../organizations/:id/members/:user_id endpoint
+///
+/// requires auth, members can remove themselves, owners and admins can remove others
+///
+/// see [`controller::remove_member`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 400 | Json payload : {"message": "An organization needs at least one owner."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Organization not found."}
+/// | 404 | Json payload : {"message": "Member not found."}
+/// | 500 | Json payload : {"message": "Could not remove the member."}
+async fn remove_member(
+    db: Data<&Database>,
+    Path((organization_id, user_id)): Path<(ID, ID)>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    match controller::remove_member(db.0, &auth, organization_id, user_id) {
+        Ok(()) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err((s, m)) => Err(error_response(s, m)),
+    }
+}
+
 /// returns endpoints for the Auth service
 pub fn api() -> Route {
     Route::new()
         .at("/sessions", get(sessions).delete(destroy_sessions))
+        .at("/sessions/others", delete(destroy_other_sessions))
         .at("/sessions/:id", delete(destroy_session))
         .at("/login", post(login))
         .at("/logout", post(logout))
@@ -393,5 +1454,33 @@ pub fn api() -> Route {
         .at("/activate", get(activate))
         .at("/forgot", post(forgot_password))
         .at("/change", post(change_password))
+        .at("/email/change", post(change_email))
+        .at("/email/confirm", post(confirm_email))
         .at("/reset", post(reset_password))
+        .at("/impersonate", post(impersonate))
+        .at("/impersonate/stop", post(stop_impersonating))
+        .at("/account", delete(delete_account))
+        .at("/guest", post(create_guest))
+        .at("/guest/upgrade", post(upgrade_guest))
+        .at("/guest/merge", post(merge_guest))
+        .at("/account/export", get(export_account))
+        .at("/identities", get(get_identities).post(link_identity))
+        .at("/identities/:id", delete(unlink_identity))
+        .at("/identities/:provider/authorize", post(authorize_identity))
+        .at("/profile", get(get_profile).patch(update_profile))
+        .at("/invitations", get(get_invitations).post(create_invitation))
+        .at("/invitations/:id", delete(revoke_invitation))
+        .at("/invitation", get(read_invitation))
+        .at(
+            "/organizations",
+            get(get_organizations).post(create_organization),
+        )
+        .at("/organizations/:id/switch", post(switch_organization))
+        .at(
+            "/organizations/:id/members",
+            get(get_members).post(add_member),
+        )
+        .at("/organizations/:id/members/:user_id", delete(remove_member))
+        .at("/.well-known/jwks.json", get(jwks))
+        .at("/captcha", get(captcha))
 }
@@ -4,10 +4,10 @@ use poem::{
 };
 use std::collections::HashSet;
 
-use crate::auth::{permissions::Permission, AccessTokenClaims, ID};
-use jsonwebtoken::decode;
-use jsonwebtoken::DecodingKey;
-use jsonwebtoken::Validation;
+use crate::auth::{
+    jwt, permissions::Permission, AccessTokenClaims, ActiveOrganization, AuthConfig,
+    TokenRevocation, ID, ORGANIZATION_ADMIN, ORGANIZATION_OWNER,
+};
 use std::iter::FromIterator;
 
 #[derive(Debug, Clone)]
@@ -18,6 +18,14 @@ pub struct Auth {
     pub user_id: ID,
     pub roles: HashSet<String>,
     pub permissions: HashSet<Permission>,
+    /// id of the admin acting as this user, if this request was made while impersonating
+    pub impersonator: Option<ID>,
+    /// the user's active organization when the access token was issued, see [`OrgAuth`]
+    pub organization: Option<ActiveOrganization>,
+    /// id of the access token, see [`TokenRevocation`]
+    pub jti: String,
+    /// when the access token expires (unix time, in seconds)
+    pub exp: usize,
 }
 
 impl Auth {
@@ -53,6 +61,71 @@ impl Auth {
     pub fn has_any_roles(&self, roles: Vec<String>) -> bool {
         roles.iter().any(|r| self.has_role(r.to_string()))
     }
+
+    /// authenticates the bearer of `access_token` (with or without its `Bearer ` prefix), for
+    /// connections that don't go through the extractor, ex: GraphQL subscriptions' websockets
+    ///
+    /// fails with the reason the token was rejected; `revocation` is checked like the extractor
+    /// does, `None` skips it
+    pub fn from_access_token(
+        access_token: &str,
+        revocation: Option<&TokenRevocation>,
+    ) -> std::result::Result<Auth, String> {
+        let access_token = access_token.trim_start_matches("Bearer ");
+        let claims = match jwt::decode_token::<AccessTokenClaims>(access_token) {
+            Ok(access_token) => access_token.claims,
+            Err(_) => return Err("Invalid access token".to_string()),
+        };
+
+        if !claims.token_type.eq_ignore_ascii_case("access_token") {
+            return Err("Invalid access token".to_string());
+        }
+
+        if revocation.map_or(false, |revocation| {
+            revocation.is_revoked(claims.sub, &claims.jti, claims.iat)
+        }) {
+            return Err("Revoked access token".to_string());
+        }
+
+        let permissions: HashSet<Permission> =
+            HashSet::from_iter(claims.permissions.iter().cloned());
+        let roles: HashSet<String> = HashSet::from_iter(claims.roles.iter().cloned());
+
+        Ok(Auth {
+            user_id: claims.sub,
+            roles,
+            permissions,
+            impersonator: claims.impersonator,
+            organization: claims.organization,
+            jti: claims.jti,
+            exp: claims.exp,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// [`Auth`] of a user acting within their active organization
+///
+/// use it instead of [`Auth`] in handlers that work with organization-scoped data, requests
+/// made without an active organization are rejected with a 403. The organization and role are
+/// the ones the access token was issued with.
+pub struct OrgAuth {
+    pub auth: Auth,
+    pub organization_id: ID,
+    /// the user's role in the organization
+    pub role: String,
+}
+
+impl OrgAuth {
+    /// is the user an owner of the organization
+    pub fn is_owner(&self) -> bool {
+        self.role == ORGANIZATION_OWNER
+    }
+
+    /// is the user an owner or an admin of the organization
+    pub fn is_admin(&self) -> bool {
+        self.is_owner() || self.role == ORGANIZATION_ADMIN
+    }
 }
 
 #[async_trait]
@@ -77,41 +150,37 @@ impl<'a> FromRequest<'a> for Auth {
             ));
         }
 
-        let access_token = decode::<AccessTokenClaims>(
-            access_token_str.trim_start_matches("Bearer "),
-            &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-            &Validation::default(),
-        );
+        let revocation = match req.data::<AuthConfig>() {
+            Some(config) => config.revocation.clone(),
+            None => Some(TokenRevocation::default()),
+        };
 
-        if access_token.is_err() {
-            return Err(Error::from_string(
-                "Invalid access token",
-                StatusCode::UNAUTHORIZED,
-            ));
-        }
-
-        let access_token = access_token.unwrap();
+        let auth = Auth::from_access_token(access_token_str, revocation.as_ref())
+            .map_err(|reason| Error::from_string(reason, StatusCode::UNAUTHORIZED))?;
 
-        if !access_token
-            .claims
-            .token_type
-            .eq_ignore_ascii_case("access_token")
-        {
-            return Err(Error::from_string(
-                "Invalid access token",
-                StatusCode::UNAUTHORIZED,
-            ));
-        }
+        #[cfg(feature = "plugin_sentry")]
+        crate::sentry::set_user(auth.user_id);
 
-        let user_id = access_token.claims.sub;
-        let permissions: HashSet<Permission> =
-            HashSet::from_iter(access_token.claims.permissions.iter().cloned());
-        let roles: HashSet<String> = HashSet::from_iter(access_token.claims.roles.iter().cloned());
+        Ok(auth)
+    }
+}
 
-        return Ok(Auth {
-            user_id,
-            roles,
-            permissions,
-        });
+#[async_trait]
+impl<'a> FromRequest<'a> for OrgAuth {
+    /// extracts [`OrgAuth`] from the given [`req`](`Request`)
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let auth = Auth::from_request(req, body).await?;
+
+        match auth.organization.clone() {
+            Some(organization) => Ok(OrgAuth {
+                auth,
+                organization_id: organization.id,
+                role: organization.role,
+            }),
+            None => Err(Error::from_string(
+                "No active organization",
+                StatusCode::FORBIDDEN,
+            )),
+        }
     }
 }
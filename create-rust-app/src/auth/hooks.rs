@@ -0,0 +1,187 @@
+//! Callbacks run by the auth endpoints when something happens to an account, so apps can send
+//! welcome emails, sync users to a CRM or record analytics without forking the endpoints.
+//!
+//! Register them on the [`AuthConfig`](`super::AuthConfig`):
+//!
+//! ```rust,ignore
+//! use create_rust_app::auth::AuthHooks;
+//!
+//! let auth_config = create_rust_app::auth::Auth::configure().hooks(
+//!     AuthHooks::default()
+//!         .on_registered(|_db, event| println!("welcome {:?}!", event.user.email))
+//!         .on_login(|_db, event| println!("{} logged in from {:?}", event.user.id, event.ip_address)),
+//! );
+//! ```
+//!
+//! Hooks run once the change was saved, on the thread handling the request (with its database
+//! connection). They can't fail the request: do slow work (like calling an external API) in the
+//! background.
+//!
+//! With the events plugin, the events are also published on the [event bus](`crate::events`),
+//! after the hooks ran.
+use super::{User, UserInvitation, ID};
+use crate::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+type Hook<Event> = Arc<dyn Fn(&mut Connection, &Event) + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a user registered, see [`AuthHooks::on_registered`]
+pub struct RegisteredEvent {
+    /// the new user, `activated` if they registered with an invitation
+    pub user: User,
+    /// the invitation they registered with, if any
+    pub invitation: Option<UserInvitation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a user logged in, see [`AuthHooks::on_login`]
+pub struct LoginEvent {
+    pub user: User,
+    /// id of the session created for this login
+    pub session_id: ID,
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a user's password was changed, see [`AuthHooks::on_password_changed`]
+pub struct PasswordChangedEvent {
+    pub user: User,
+    /// `true` if it was reset through a recovery link, `false` if the user changed it
+    pub reset: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// why sessions were revoked, see [`SessionRevokedEvent`]
+pub enum SessionRevocation {
+    /// the user logged out
+    Logout,
+    /// the user revoked one of their sessions
+    Revoked,
+    /// the user revoked all of their sessions
+    RevokedAll,
+    /// the user revoked all of their sessions but the current one
+    RevokedOthers,
+    /// a rotated refresh token was presented again, see [`UserSessionRotatedToken`](`super::UserSessionRotatedToken`)
+    RefreshTokenReuse,
+    /// an admin revoked all of the user's sessions (ex: from the admin portal's user management)
+    Admin,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// sessions of a user were revoked, see [`AuthHooks::on_session_revoked`]
+pub struct SessionRevokedEvent {
+    pub user_id: ID,
+    /// the revoked session, or `None` when several were revoked at once
+    pub session_id: Option<ID>,
+    pub reason: SessionRevocation,
+}
+
+#[derive(Clone, Default)]
+/// the callbacks registered by the app, run in registration order
+pub struct AuthHooks {
+    registered: Vec<Hook<RegisteredEvent>>,
+    login: Vec<Hook<LoginEvent>>,
+    password_changed: Vec<Hook<PasswordChangedEvent>>,
+    session_revoked: Vec<Hook<SessionRevokedEvent>>,
+}
+
+impl AuthHooks {
+    /// called when a user registers (before they activate their account, unless they were invited)
+    pub fn on_registered(
+        mut self,
+        hook: impl Fn(&mut Connection, &RegisteredEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.registered.push(Arc::new(hook));
+        self
+    }
+
+    /// called when a user logs in with their email and password
+    pub fn on_login(
+        mut self,
+        hook: impl Fn(&mut Connection, &LoginEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.login.push(Arc::new(hook));
+        self
+    }
+
+    /// called when a user changes their password, or resets it
+    pub fn on_password_changed(
+        mut self,
+        hook: impl Fn(&mut Connection, &PasswordChangedEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.password_changed.push(Arc::new(hook));
+        self
+    }
+
+    /// called when sessions are revoked: on logout, from the .../sessions endpoints, or when
+    /// refresh token reuse is detected
+    pub fn on_session_revoked(
+        mut self,
+        hook: impl Fn(&mut Connection, &SessionRevokedEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.session_revoked.push(Arc::new(hook));
+        self
+    }
+
+    pub(crate) fn registered(&self, db: &mut Connection, event: RegisteredEvent) {
+        self.registered.iter().for_each(|hook| hook(db, &event));
+        #[cfg(feature = "plugin_events")]
+        crate::events::publish_with(db, event);
+    }
+
+    pub(crate) fn login(&self, db: &mut Connection, event: LoginEvent) {
+        self.login.iter().for_each(|hook| hook(db, &event));
+        #[cfg(feature = "plugin_events")]
+        crate::events::publish_with(db, event);
+    }
+
+    pub(crate) fn password_changed(&self, db: &mut Connection, event: PasswordChangedEvent) {
+        self.password_changed
+            .iter()
+            .for_each(|hook| hook(db, &event));
+        #[cfg(feature = "plugin_events")]
+        crate::events::publish_with(db, event);
+    }
+
+    pub(crate) fn session_revoked(&self, db: &mut Connection, event: SessionRevokedEvent) {
+        self.session_revoked
+            .iter()
+            .for_each(|hook| hook(db, &event));
+        #[cfg(feature = "plugin_events")]
+        crate::events::publish_with(db, event);
+    }
+}
+
+impl std::fmt::Debug for AuthHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthHooks")
+            .field("registered", &self.registered.len())
+            .field("login", &self.login.len())
+            .field("password_changed", &self.password_changed.len())
+            .field("session_revoked", &self.session_revoked.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "plugin_events")]
+impl crate::events::Event for RegisteredEvent {
+    const NAME: &'static str = "auth.registered";
+}
+
+#[cfg(feature = "plugin_events")]
+impl crate::events::Event for LoginEvent {
+    const NAME: &'static str = "auth.login";
+}
+
+#[cfg(feature = "plugin_events")]
+impl crate::events::Event for PasswordChangedEvent {
+    const NAME: &'static str = "auth.password_changed";
+}
+
+#[cfg(feature = "plugin_events")]
+impl crate::events::Event for SessionRevokedEvent {
+    const NAME: &'static str = "auth.session_revoked";
+}
@@ -0,0 +1,250 @@
+//! Signing and verification of the JWTs issued by the auth plugin.
+//!
+//! Tokens are signed with HS256 and the `SECRET_KEY` by default. To sign with an asymmetric key
+//! (so other services can verify tokens without knowing a secret), set:
+//!
+//! - `JWT_ALGORITHM`: `HS256` (default), `RS256` or `EdDSA`
+//! - `JWT_PRIVATE_KEY` or `JWT_PRIVATE_KEY_FILE`: PEM encoded private key used for signing
+//! - `JWT_PUBLIC_KEY` or `JWT_PUBLIC_KEY_FILE`: PEM encoded public key matching the private key
+//! - `JWT_KEY_ID`: the `kid` put in the header of issued tokens (default: `default`)
+//! - `JWT_PREVIOUS_PUBLIC_KEYS`: optional, comma separated `kid=path/to/public.pem` pairs of
+//!   retired keys (using the same algorithm). Tokens signed with them are still accepted until
+//!   they expire, which allows rotating keys without logging everyone out.
+//!
+//! The public keys are served as a JWK set at `/api/auth/.well-known/jwks.json`.
+use base64::Engine;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use lazy_static::lazy_static;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// a single public key, as described in RFC 7517
+pub struct Jwk {
+    pub kty: String,
+    pub kid: String,
+    pub alg: String,
+    #[serde(rename = "use")]
+    pub use_: String,
+    /// RSA modulus (base64url)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    /// RSA public exponent (base64url)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    /// curve name for OKP keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    /// OKP public key (base64url)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// the JSON Web Key Set served by the jwks endpoint
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+struct JwtKeys {
+    algorithm: Algorithm,
+    kid: Option<String>,
+    encoding_key: EncodingKey,
+    decoding_keys: HashMap<String, DecodingKey>,
+    jwks: Jwks,
+}
+
+lazy_static! {
+    static ref KEYS: JwtKeys = JwtKeys::from_env();
+}
+
+/// reads `{name}` or, if it isn't set, the file at `{name}_FILE`
+fn read_key(name: &str) -> Option<String> {
+    if let Ok(key) = std::env::var(name) {
+        // allow single-line env values with escaped newlines
+        return Some(key.replace("\\n", "\n"));
+    }
+
+    std::env::var(format!("{name}_FILE")).ok().map(|path| {
+        std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("Could not read {name}_FILE ('{path}')"))
+    })
+}
+
+fn pem_to_der(pem: &str) -> Vec<u8> {
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+
+    base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .expect("Invalid PEM encoded key")
+}
+
+fn decoding_key(algorithm: Algorithm, pem: &str) -> DecodingKey {
+    match algorithm {
+        Algorithm::RS256 => DecodingKey::from_rsa_pem(pem.as_bytes()),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(pem.as_bytes()),
+        _ => unreachable!(),
+    }
+    .expect("Invalid JWT public key")
+}
+
+fn jwk(algorithm: Algorithm, kid: &str, pem: &str) -> Jwk {
+    let b64 = |bytes: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+
+    match algorithm {
+        Algorithm::RS256 => {
+            use rsa::pkcs1::DecodeRsaPublicKey;
+            use rsa::pkcs8::DecodePublicKey;
+            use rsa::traits::PublicKeyParts;
+
+            let key = rsa::RsaPublicKey::from_public_key_pem(pem)
+                .or_else(|_| rsa::RsaPublicKey::from_pkcs1_pem(pem))
+                .expect("Invalid RSA public key");
+
+            Jwk {
+                kty: "RSA".to_string(),
+                kid: kid.to_string(),
+                alg: "RS256".to_string(),
+                use_: "sig".to_string(),
+                n: Some(b64(&key.n().to_bytes_be())),
+                e: Some(b64(&key.e().to_bytes_be())),
+                crv: None,
+                x: None,
+            }
+        }
+        Algorithm::EdDSA => {
+            // the raw ed25519 key is the last 32 bytes of the SubjectPublicKeyInfo
+            let der = pem_to_der(pem);
+            let raw = &der[der.len().saturating_sub(32)..];
+
+            Jwk {
+                kty: "OKP".to_string(),
+                kid: kid.to_string(),
+                alg: "EdDSA".to_string(),
+                use_: "sig".to_string(),
+                n: None,
+                e: None,
+                crv: Some("Ed25519".to_string()),
+                x: Some(b64(raw)),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+impl JwtKeys {
+    fn from_env() -> Self {
+        let algorithm = match std::env::var("JWT_ALGORITHM")
+            .unwrap_or_else(|_| "HS256".to_string())
+            .to_uppercase()
+            .as_str()
+        {
+            "HS256" => Algorithm::HS256,
+            "RS256" => Algorithm::RS256,
+            "EDDSA" => Algorithm::EdDSA,
+            other => panic!("Unsupported JWT_ALGORITHM '{other}' (options: HS256, RS256, EdDSA)"),
+        };
+
+        if algorithm == Algorithm::HS256 {
+            let secret = match std::env::var("SECRET_KEY") {
+                Ok(s) => s,
+                Err(_) => panic!("No SECRET_KEY environment variable set!"),
+            };
+
+            let mut decoding_keys = HashMap::new();
+            decoding_keys.insert(String::new(), DecodingKey::from_secret(secret.as_ref()));
+
+            return Self {
+                algorithm,
+                kid: None,
+                encoding_key: EncodingKey::from_secret(secret.as_ref()),
+                decoding_keys,
+                // symmetric keys must never be published
+                jwks: Jwks::default(),
+            };
+        }
+
+        let kid = std::env::var("JWT_KEY_ID").unwrap_or_else(|_| "default".to_string());
+        let private_key = read_key("JWT_PRIVATE_KEY").expect(
+            "JWT_PRIVATE_KEY or JWT_PRIVATE_KEY_FILE must be set when using asymmetric JWTs",
+        );
+        let public_key = read_key("JWT_PUBLIC_KEY")
+            .expect("JWT_PUBLIC_KEY or JWT_PUBLIC_KEY_FILE must be set when using asymmetric JWTs");
+
+        let encoding_key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(private_key.as_bytes()),
+            _ => EncodingKey::from_ed_pem(private_key.as_bytes()),
+        }
+        .expect("Invalid JWT private key");
+
+        let mut decoding_keys = HashMap::new();
+        let mut jwks = Jwks::default();
+
+        decoding_keys.insert(kid.clone(), decoding_key(algorithm, &public_key));
+        jwks.keys.push(jwk(algorithm, &kid, &public_key));
+
+        for entry in std::env::var("JWT_PREVIOUS_PUBLIC_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (previous_kid, path) = entry
+                .split_once('=')
+                .expect("JWT_PREVIOUS_PUBLIC_KEYS entries must look like 'kid=path/to/key.pem'");
+            let pem = std::fs::read_to_string(path)
+                .unwrap_or_else(|_| panic!("Could not read previous JWT public key '{path}'"));
+
+            decoding_keys.insert(previous_kid.to_string(), decoding_key(algorithm, &pem));
+            jwks.keys.push(jwk(algorithm, previous_kid, &pem));
+        }
+
+        Self {
+            algorithm,
+            kid: Some(kid),
+            encoding_key,
+            decoding_keys,
+            jwks,
+        }
+    }
+}
+
+/// signs [`claims`] with the current signing key
+pub fn encode_token<T: Serialize>(claims: &T) -> jsonwebtoken::errors::Result<String> {
+    let mut header = Header::new(KEYS.algorithm);
+    header.kid = KEYS.kid.clone();
+
+    jsonwebtoken::encode(&header, claims, &KEYS.encoding_key)
+}
+
+/// verifies [`token`] with the key matching its `kid` (or the current key if it has none)
+/// and decodes its claims
+pub fn decode_token<T: DeserializeOwned>(
+    token: &str,
+) -> jsonwebtoken::errors::Result<TokenData<T>> {
+    let header = jsonwebtoken::decode_header(token)?;
+
+    let key = match (&KEYS.kid, header.kid) {
+        (None, _) => KEYS.decoding_keys.get(""),
+        (Some(current), None) => KEYS.decoding_keys.get(current),
+        (Some(_), Some(kid)) => KEYS.decoding_keys.get(&kid),
+    };
+
+    let key = match key {
+        Some(key) => key,
+        None => return Err(jsonwebtoken::errors::ErrorKind::InvalidKeyFormat.into()),
+    };
+
+    jsonwebtoken::decode::<T>(token, key, &Validation::new(KEYS.algorithm))
+}
+
+/// the public keys tokens can be verified with (empty when using HS256)
+pub fn jwks() -> &'static Jwks {
+    &KEYS.jwks
+}
@@ -1,24 +1,11 @@
+use serde_json::json;
+
 use crate::Mailer;
 
+/// renders the `auth_activated` mail templates
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str) {
-    let subject = "Account activated";
-    let text = r#"
-(This is an automated message.)
-
-Hello,
-
-Your account has been activated!
-"#
-    .to_string();
-    let html = r#"
-<p>(This is an automated message.)</p>
-
-<p>Hello,</p>
-
-<p>Your account has been activated!</p>
-"#
-    .to_string();
-
-    mailer.send(to_email, subject, &text, &html);
+    if let Err(err) = mailer.send_template(to_email, "auth_activated", None, &json!({})) {
+        println!("{err}");
+    }
 }
@@ -0,0 +1,16 @@
+use serde_json::json;
+
+use crate::Mailer;
+
+/// renders the `auth_email_change` mail templates, with `link`
+#[allow(dead_code)]
+pub fn send(mailer: &Mailer, to_email: &str, link: &str) {
+    if let Err(err) = mailer.send_template(
+        to_email,
+        "auth_email_change",
+        None,
+        &json!({ "link": link }),
+    ) {
+        println!("{err}");
+    }
+}
@@ -0,0 +1,16 @@
+use serde_json::json;
+
+use crate::Mailer;
+
+/// renders the `auth_email_changed` mail templates, with `new_email`
+#[allow(dead_code)]
+pub fn send(mailer: &Mailer, to_email: &str, new_email: &str) {
+    if let Err(err) = mailer.send_template(
+        to_email,
+        "auth_email_changed",
+        None,
+        &json!({ "new_email": new_email }),
+    ) {
+        println!("{err}");
+    }
+}
@@ -0,0 +1,13 @@
+use serde_json::json;
+
+use crate::Mailer;
+
+/// renders the `auth_invitation` mail templates, with `link`
+#[allow(dead_code)]
+pub fn send(mailer: &Mailer, to_email: &str, link: &str) {
+    if let Err(err) =
+        mailer.send_template(to_email, "auth_invitation", None, &json!({ "link": link }))
+    {
+        println!("{err}");
+    }
+}
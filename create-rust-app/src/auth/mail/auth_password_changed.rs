@@ -1,24 +1,11 @@
+use serde_json::json;
+
 use crate::Mailer;
 
+/// renders the `auth_password_changed` mail templates
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str) {
-    let subject = "Your password was changed";
-    let text = r#"
-(This is an automated message.)
-
-Hello,
-
-Your password was changed successfully!
-"#
-    .to_string();
-    let html = r#"
-<p>(This is an automated message.)</p>
-
-<p>Hello,</p>
-
-<p>Your password was changed successfully!</p>
-"#
-    .to_string();
-
-    mailer.send(to_email, subject, &text, &html);
+    if let Err(err) = mailer.send_template(to_email, "auth_password_changed", None, &json!({})) {
+        println!("{err}");
+    }
 }
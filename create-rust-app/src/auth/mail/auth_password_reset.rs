@@ -1,25 +1,11 @@
+use serde_json::json;
+
 use crate::Mailer;
 
+/// renders the `auth_password_reset` mail templates
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str) {
-    let subject = "Your password was reset";
-    let text = r#"
-(This is an automated message.)
-
-Hello,
-
-Your password was successfully reset!
-"#
-    .to_string();
-
-    let html = r#"
-<p>(This is an automated message.)</p>
-
-<p>Hello,</p>
-
-<p>Your password was successfully reset!</p>
-"#
-    .to_string();
-
-    mailer.send(to_email, subject, &text, &html);
+    if let Err(err) = mailer.send_template(to_email, "auth_password_reset", None, &json!({})) {
+        println!("{err}");
+    }
 }
@@ -1,28 +1,13 @@
+use serde_json::json;
+
 use crate::Mailer;
 
+/// renders the `auth_register` mail templates, with `link`
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str, link: &str) {
-    let subject = "Registration Confirmation";
-    let text = format!(
-        r#"
-(This is an automated message.)
-
-Hello,
-
-Please follow the link below to complete your registration:
-{link}
-"#
-    );
-    let html = format!(
-        r#"
-<p>(This is an automated message.)</p>
-
-<p>Hello,</p>
-
-<p>Please follow the link below to complete your registration:</p>
-<p><a href="{link}">{link}</a></p>
-"#
-    );
-
-    mailer.send(to_email, subject, &text, &html);
+    if let Err(err) =
+        mailer.send_template(to_email, "auth_register", None, &json!({ "link": link }))
+    {
+        println!("{err}");
+    }
 }
@@ -1,7 +1,123 @@
 // Mail
 pub mod auth_activated;
+pub mod auth_email_change;
+pub mod auth_email_changed;
+pub mod auth_invitation;
 pub mod auth_password_changed;
 pub mod auth_password_reset;
 pub mod auth_recover_existent_account;
 pub mod auth_recover_nonexistent_account;
 pub mod auth_register;
+
+/// the auth emails' templates, used unless the app's `backend/mail/templates` directory overrides
+/// them (see [`Mailer::send_template`](`crate::Mailer::send_template`))
+pub(crate) const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "auth_activated.subject.txt",
+        include_str!("templates/auth_activated.subject.txt"),
+    ),
+    (
+        "auth_activated.txt",
+        include_str!("templates/auth_activated.txt"),
+    ),
+    (
+        "auth_activated.html",
+        include_str!("templates/auth_activated.html"),
+    ),
+    (
+        "auth_email_change.subject.txt",
+        include_str!("templates/auth_email_change.subject.txt"),
+    ),
+    (
+        "auth_email_change.txt",
+        include_str!("templates/auth_email_change.txt"),
+    ),
+    (
+        "auth_email_change.html",
+        include_str!("templates/auth_email_change.html"),
+    ),
+    (
+        "auth_email_changed.subject.txt",
+        include_str!("templates/auth_email_changed.subject.txt"),
+    ),
+    (
+        "auth_email_changed.txt",
+        include_str!("templates/auth_email_changed.txt"),
+    ),
+    (
+        "auth_email_changed.html",
+        include_str!("templates/auth_email_changed.html"),
+    ),
+    (
+        "auth_invitation.subject.txt",
+        include_str!("templates/auth_invitation.subject.txt"),
+    ),
+    (
+        "auth_invitation.txt",
+        include_str!("templates/auth_invitation.txt"),
+    ),
+    (
+        "auth_invitation.html",
+        include_str!("templates/auth_invitation.html"),
+    ),
+    (
+        "auth_password_changed.subject.txt",
+        include_str!("templates/auth_password_changed.subject.txt"),
+    ),
+    (
+        "auth_password_changed.txt",
+        include_str!("templates/auth_password_changed.txt"),
+    ),
+    (
+        "auth_password_changed.html",
+        include_str!("templates/auth_password_changed.html"),
+    ),
+    (
+        "auth_password_reset.subject.txt",
+        include_str!("templates/auth_password_reset.subject.txt"),
+    ),
+    (
+        "auth_password_reset.txt",
+        include_str!("templates/auth_password_reset.txt"),
+    ),
+    (
+        "auth_password_reset.html",
+        include_str!("templates/auth_password_reset.html"),
+    ),
+    (
+        "auth_recover_existent_account.subject.txt",
+        include_str!("templates/auth_recover_existent_account.subject.txt"),
+    ),
+    (
+        "auth_recover_existent_account.txt",
+        include_str!("templates/auth_recover_existent_account.txt"),
+    ),
+    (
+        "auth_recover_existent_account.html",
+        include_str!("templates/auth_recover_existent_account.html"),
+    ),
+    (
+        "auth_recover_nonexistent_account.subject.txt",
+        include_str!("templates/auth_recover_nonexistent_account.subject.txt"),
+    ),
+    (
+        "auth_recover_nonexistent_account.txt",
+        include_str!("templates/auth_recover_nonexistent_account.txt"),
+    ),
+    (
+        "auth_recover_nonexistent_account.html",
+        include_str!("templates/auth_recover_nonexistent_account.html"),
+    ),
+    (
+        "auth_register.subject.txt",
+        include_str!("templates/auth_register.subject.txt"),
+    ),
+    (
+        "auth_register.txt",
+        include_str!("templates/auth_register.txt"),
+    ),
+    (
+        "auth_register.html",
+        include_str!("templates/auth_register.html"),
+    ),
+];
@@ -15,18 +15,62 @@ pub mod controller;
 mod endpoints;
 pub use endpoints::*;
 
+pub mod account;
+mod captcha;
+pub use captcha::{Captcha, CaptchaProvider, CaptchaSettings};
+mod config;
+pub use config::{AuthConfig, SameSite};
+
+mod hooks;
+pub use hooks::{
+    AuthHooks, LoginEvent, PasswordChangedEvent, RegisteredEvent, SessionRevocation,
+    SessionRevokedEvent,
+};
+
+pub mod jwt;
 pub(crate) mod mail;
+mod oauth;
+pub use oauth::{OAuthProfile, OAuthProvider};
+mod organization;
+mod password;
+pub use password::PasswordHashing;
 mod permissions;
+mod profile;
+pub use profile::{Profiles, UserProfile};
+mod rate_limit;
+#[cfg(feature = "plugin_redis")]
+pub use rate_limit::RedisStore;
+pub use rate_limit::{MemoryStore, RateLimit, RateLimitStore, RateLimitedAction, RateLimiter};
+mod revocation;
+#[cfg(feature = "plugin_redis")]
+pub use revocation::RedisRevocationStore;
+pub use revocation::{MemoryRevocationStore, RevocationStore, TokenRevocation};
 mod schema;
+mod store;
+pub use store::{AuthStores, DieselSessionStore, DieselUserStore, SessionStore, UserStore};
 mod user;
+mod user_ban;
+mod user_identity;
+mod user_impersonation;
+mod user_invitation;
 mod user_session;
+mod user_session_rotated_token;
 
+pub use organization::{
+    Membership, MembershipChangeset, Organization, OrganizationChangeset, ORGANIZATION_ADMIN,
+    ORGANIZATION_MEMBER, ORGANIZATION_OWNER,
+};
 pub use permissions::{
     Permission, Role, RolePermission, RolePermissionChangeset, UserPermission,
     UserPermissionChangeset,
 };
 pub use user::{User, UserChangeset};
+pub use user_ban::{UserBan, UserBanChangeset};
+pub use user_identity::{UserIdentity, UserIdentityChangeset};
+pub use user_impersonation::{UserImpersonation, UserImpersonationChangeset};
+pub use user_invitation::{UserInvitation, UserInvitationChangeset};
 pub use user_session::{UserSession, UserSessionChangeset};
+pub use user_session_rotated_token::{UserSessionRotatedToken, UserSessionRotatedTokenChangeset};
 
 #[tsync::tsync]
 type ID = i32;
@@ -65,6 +109,10 @@ impl PaginationParams {
 pub struct UserSessionJson {
     pub id: ID,
     pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub last_used_at: Utc,
+    /// `true` if this is the session the request was made with
+    pub current: bool,
     pub created_at: Utc,
     #[cfg(not(feature = "database_sqlite"))]
     pub updated_at: Utc,
@@ -80,15 +128,82 @@ pub struct UserSessionResponse {
     pub num_pages: i64,
 }
 
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representation of an account at an [`OAuthProvider`] linked to the user,
+/// serialized into Json
+pub struct UserIdentityJson {
+    pub id: ID,
+    pub provider: String,
+    pub email: Option<String>,
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representation of the
+/// backends JSON response to a GET request at the /identities endpoint
+pub struct UserIdentityResponse {
+    pub identities: Vec<UserIdentityJson>,
+    /// names of the [`OAuthProvider`]s accounts can be linked from
+    pub providers: Vec<String>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Rust struct representation of an organization the user is a member of, serialized into Json
+pub struct OrganizationJson {
+    pub id: ID,
+    pub name: String,
+    /// the user's role in the organization
+    pub role: String,
+    /// `true` if this is the user's active organization
+    pub active: bool,
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Rust struct representation of a member of an organization, serialized into Json
+pub struct MemberJson {
+    pub user_id: ID,
+    /// `None` for guests
+    pub email: Option<String>,
+    pub role: String,
+    pub created_at: Utc,
+}
+
 #[tsync::tsync]
 #[derive(Debug, Serialize, Deserialize)]
 /// TODO: documentation
 pub struct AccessTokenClaims {
     pub exp: usize,
+    /// when the token was issued (unix time, in seconds)
+    #[serde(default)]
+    pub iat: usize,
+    /// unique id of the token, see [`TokenRevocation`]
+    #[serde(default)]
+    pub jti: String,
     pub sub: ID,
     pub token_type: String,
     pub roles: Vec<String>,
     pub permissions: Vec<Permission>,
+    /// id of the admin acting as this user, see [`controller::impersonate`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<ID>,
+    /// the user's active organization, see [`controller::switch_organization`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<ActiveOrganization>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// the organization a user is currently working in, and their role in it
+pub struct ActiveOrganization {
+    pub id: ID,
+    pub role: String,
 }
 
 #[cfg(feature = "plugin_utoipa")]
@@ -0,0 +1,219 @@
+//! OAuth 2 providers users can link to their account (ex: "Sign in with Google" identities),
+//! see the .../identities endpoints.
+//!
+//! Google and GitHub are configured from your `.env` when their keys are set:
+//!
+//! ```text
+//! GOOGLE_CLIENT_ID=...
+//! GOOGLE_CLIENT_SECRET=...
+//! GITHUB_CLIENT_ID=...
+//! GITHUB_CLIENT_SECRET=...
+//! ```
+//!
+//! Other providers can be added with [`OAuthProvider::new`]:
+//!
+//! ```rust,ignore
+//! use create_rust_app::auth::{Auth, OAuthProvider};
+//!
+//! let auth_config = Auth::configure().oauth_provider(OAuthProvider::new(
+//!     "gitlab",
+//!     "https://gitlab.com/oauth/authorize",
+//!     "https://gitlab.com/oauth/token",
+//!     "https://gitlab.com/api/v4/user",
+//!     std::env::var("GITLAB_CLIENT_ID").unwrap(),
+//!     std::env::var("GITLAB_CLIENT_SECRET").unwrap(),
+//! ).scopes(&["read_user"]));
+//! ```
+//!
+//! The provider redirects back to the `redirect_uri` the frontend asked for (the account page),
+//! which must be registered in the provider's settings.
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Clone)]
+/// an OAuth 2 provider (authorization code flow) and the app's credentials
+pub struct OAuthProvider {
+    /// identifies the provider in the `user_identities` table and the endpoints' paths
+    pub name: String,
+    pub authorize_url: String,
+    pub token_url: String,
+    /// responds with the user's profile, which must have an `id` (or `sub`) and can have an `email`
+    pub userinfo_url: String,
+    pub scopes: Vec<String>,
+    pub client_id: String,
+    client_secret: String,
+}
+
+impl std::fmt::Debug for OAuthProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuthProvider")
+            .field("name", &self.name)
+            .field("authorize_url", &self.authorize_url)
+            .field("scopes", &self.scopes)
+            .field("client_id", &self.client_id)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// the user's account at an [`OAuthProvider`]
+pub struct OAuthProfile {
+    /// [`OAuthProvider::name`]
+    pub provider: String,
+    /// the user's id at the provider
+    pub id: String,
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+impl OAuthProvider {
+    pub fn new(
+        name: impl Into<String>,
+        authorize_url: impl Into<String>,
+        token_url: impl Into<String>,
+        userinfo_url: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            authorize_url: authorize_url.into(),
+            token_url: token_url.into(),
+            userinfo_url: userinfo_url.into(),
+            scopes: vec![],
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+        }
+    }
+
+    pub fn scopes(mut self, scopes: &[&str]) -> Self {
+        self.scopes = scopes.iter().map(|scope| scope.to_string()).collect();
+        self
+    }
+
+    pub fn google(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self::new(
+            "google",
+            "https://accounts.google.com/o/oauth2/v2/auth",
+            "https://oauth2.googleapis.com/token",
+            "https://openidconnect.googleapis.com/v1/userinfo",
+            client_id,
+            client_secret,
+        )
+        .scopes(&["openid", "email"])
+    }
+
+    pub fn github(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self::new(
+            "github",
+            "https://github.com/login/oauth/authorize",
+            "https://github.com/login/oauth/access_token",
+            "https://api.github.com/user",
+            client_id,
+            client_secret,
+        )
+        .scopes(&["read:user", "user:email"])
+    }
+
+    /// the providers whose `{PROVIDER}_CLIENT_ID` and `{PROVIDER}_CLIENT_SECRET` are set
+    /// (Google and GitHub)
+    pub fn from_env() -> Vec<Self> {
+        let credentials = |prefix: &str| match (
+            std::env::var(format!("{prefix}_CLIENT_ID")),
+            std::env::var(format!("{prefix}_CLIENT_SECRET")),
+        ) {
+            (Ok(id), Ok(secret)) if !id.is_empty() => Some((id, secret)),
+            _ => None,
+        };
+
+        let mut providers = vec![];
+        if let Some((id, secret)) = credentials("GOOGLE") {
+            providers.push(Self::google(id, secret));
+        }
+        if let Some((id, secret)) = credentials("GITHUB") {
+            providers.push(Self::github(id, secret));
+        }
+        providers
+    }
+
+    /// where to send the user to authorize the app, `state` is echoed back to `redirect_uri`
+    pub fn authorization_url(&self, redirect_uri: &str, state: &str) -> Result<String, String> {
+        let scope = self.scopes.join(" ");
+
+        reqwest::Url::parse_with_params(
+            &self.authorize_url,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", redirect_uri),
+                ("scope", scope.as_str()),
+                ("state", state),
+            ],
+        )
+        .map(String::from)
+        .map_err(|err| err.to_string())
+    }
+
+    /// exchanges the authorization `code` for an access token, and reads the user's profile with it
+    pub async fn fetch_profile(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+    ) -> Result<OAuthProfile, String> {
+        let client = reqwest::Client::new();
+
+        let body = client
+            .post(&self.token_url)
+            .header("Accept", "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|err| err.to_string())?
+            .text()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let token = serde_json::from_str::<TokenResponse>(&body)
+            .map_err(|err| format!("unexpected token response ({err}): {body}"))?;
+
+        let body = client
+            .get(&self.userinfo_url)
+            .bearer_auth(token.access_token)
+            .header("Accept", "application/json")
+            // required by GitHub's api
+            .header("User-Agent", "create-rust-app")
+            .send()
+            .await
+            .map_err(|err| err.to_string())?
+            .text()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let profile = serde_json::from_str::<Value>(&body).map_err(|err| err.to_string())?;
+
+        let id = match profile.get("sub").or_else(|| profile.get("id")) {
+            Some(Value::String(id)) => id.clone(),
+            Some(Value::Number(id)) => id.to_string(),
+            _ => return Err(format!("the profile has no id: {body}")),
+        };
+
+        Ok(OAuthProfile {
+            provider: self.name.clone(),
+            id,
+            email: profile
+                .get("email")
+                .and_then(Value::as_str)
+                .map(String::from),
+        })
+    }
+}
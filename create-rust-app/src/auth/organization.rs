@@ -0,0 +1,242 @@
+use super::schema::*;
+use crate::diesel::*;
+
+use super::user::User;
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+/// role of the member who created the organization, owners can do everything admins can
+/// and can make other members owners
+pub const ORGANIZATION_OWNER: &str = "owner";
+/// role of members who can add, invite and remove other members
+pub const ORGANIZATION_ADMIN: &str = "admin";
+/// role of everyone else
+pub const ORGANIZATION_MEMBER: &str = "member";
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable)]
+#[diesel(table_name=organizations)]
+/// Rust struct modeling an entry in the organizations table, users are part of it through
+/// their [`Membership`]
+pub struct Organization {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+    pub name: String,
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=organizations)]
+/// Rust struct modeling mutable data in an entry in the organizations table
+pub struct OrganizationChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub name: String,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Associations)]
+#[diesel(table_name=memberships, belongs_to(Organization), belongs_to(User))]
+/// Rust struct modeling an entry in the memberships table: `user_id` is part of `organization_id`
+/// with the given `role` ([`ORGANIZATION_OWNER`], [`ORGANIZATION_ADMIN`] or [`ORGANIZATION_MEMBER`])
+///
+/// the membership the user switched to last (the latest `active_at`) is their active organization,
+/// which ends up in their access tokens
+pub struct Membership {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub organization_id: ID,
+    pub user_id: ID,
+    pub role: String,
+    pub active_at: Option<Utc>,
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=memberships)]
+/// Rust struct modeling mutable data in an entry in the memberships table
+pub struct MembershipChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub organization_id: ID,
+    pub user_id: ID,
+    pub role: String,
+}
+
+impl Organization {
+    /// Create an entry in [`db`](`Connection`)'s organizations table using the data in [`item`](`OrganizationChangeset`)
+    pub fn create(db: &mut Connection, item: &OrganizationChangeset) -> QueryResult<Self> {
+        use super::schema::organizations::dsl::*;
+
+        insert_into(organizations)
+            .values(item)
+            .get_result::<Organization>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the organizations table
+    /// who's primary key matches [`item_id`](`ID`)
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use super::schema::organizations::dsl::*;
+
+        organizations
+            .filter(id.eq(item_id))
+            .first::<Organization>(db)
+    }
+
+    /// the organizations [`item_user_id`](`ID`) is a member of, with their membership, oldest first
+    pub fn fetch_all_for_user(
+        db: &mut Connection,
+        item_user_id: ID,
+    ) -> QueryResult<Vec<(Self, Membership)>> {
+        organizations::table
+            .inner_join(memberships::table)
+            .filter(memberships::user_id.eq(item_user_id))
+            .order(memberships::created_at.asc())
+            .load::<(Organization, Membership)>(db)
+    }
+}
+
+impl Membership {
+    /// Create an entry in [`db`](`Connection`)'s memberships table using the data in [`item`](`MembershipChangeset`)
+    pub fn create(db: &mut Connection, item: &MembershipChangeset) -> QueryResult<Self> {
+        use super::schema::memberships::dsl::*;
+
+        insert_into(memberships)
+            .values(item)
+            .get_result::<Membership>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the memberships table that has
+    /// (`item_organization_id`,`item_user_id`) as it's primary keys
+    pub fn read(
+        db: &mut Connection,
+        item_organization_id: ID,
+        item_user_id: ID,
+    ) -> QueryResult<Self> {
+        use super::schema::memberships::dsl::*;
+
+        memberships
+            .filter(
+                organization_id
+                    .eq(item_organization_id)
+                    .and(user_id.eq(item_user_id)),
+            )
+            .first::<Membership>(db)
+    }
+
+    /// the memberships of [`item_user_id`](`ID`), oldest first
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use super::schema::memberships::dsl::*;
+
+        memberships
+            .filter(user_id.eq(item_user_id))
+            .order(created_at.asc())
+            .load::<Membership>(db)
+    }
+
+    /// the members of [`item_organization_id`](`ID`) with their email address (`None` for guests), oldest first
+    pub fn fetch_all_with_email(
+        db: &mut Connection,
+        item_organization_id: ID,
+    ) -> QueryResult<Vec<(Self, Option<String>)>> {
+        memberships::table
+            .inner_join(users::table)
+            .filter(memberships::organization_id.eq(item_organization_id))
+            .order(memberships::created_at.asc())
+            .select((memberships::all_columns, users::email))
+            .load::<(Membership, Option<String>)>(db)
+    }
+
+    /// the membership [`item_user_id`](`ID`) switched to last, if any
+    pub fn active(db: &mut Connection, item_user_id: ID) -> QueryResult<Option<Self>> {
+        use super::schema::memberships::dsl::*;
+
+        memberships
+            .filter(user_id.eq(item_user_id).and(active_at.is_not_null()))
+            .order(active_at.desc())
+            .first::<Membership>(db)
+            .optional()
+    }
+
+    /// makes [`item_organization_id`](`ID`) the active organization of [`item_user_id`](`ID`)
+    pub fn activate(
+        db: &mut Connection,
+        item_organization_id: ID,
+        item_user_id: ID,
+    ) -> QueryResult<usize> {
+        use super::schema::memberships::dsl::*;
+
+        #[cfg(not(feature = "database_sqlite"))]
+        let now = chrono::Utc::now();
+        #[cfg(feature = "database_sqlite")]
+        let now = chrono::Utc::now().naive_utc();
+
+        diesel::update(
+            memberships.filter(
+                organization_id
+                    .eq(item_organization_id)
+                    .and(user_id.eq(item_user_id)),
+            ),
+        )
+        .set(active_at.eq(Some(now)))
+        .execute(db)
+    }
+
+    /// the number of owners of [`item_organization_id`](`ID`)
+    pub fn count_owners(db: &mut Connection, item_organization_id: ID) -> QueryResult<i64> {
+        use super::schema::memberships::dsl::*;
+
+        memberships
+            .filter(
+                organization_id
+                    .eq(item_organization_id)
+                    .and(role.eq(ORGANIZATION_OWNER)),
+            )
+            .count()
+            .get_result(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s memberships table that has
+    /// (`item_organization_id`,`item_user_id`) as it's primary keys
+    pub fn delete(
+        db: &mut Connection,
+        item_organization_id: ID,
+        item_user_id: ID,
+    ) -> QueryResult<usize> {
+        use super::schema::memberships::dsl::*;
+
+        diesel::delete(
+            memberships.filter(
+                organization_id
+                    .eq(item_organization_id)
+                    .and(user_id.eq(item_user_id)),
+            ),
+        )
+        .execute(db)
+    }
+
+    /// deletes every membership of [`item_user_id`](`ID`)
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::memberships::dsl::*;
+
+        diesel::delete(memberships.filter(user_id.eq(item_user_id))).execute(db)
+    }
+
+    /// can this member add, invite and remove members
+    pub fn can_manage_members(&self) -> bool {
+        self.role == ORGANIZATION_OWNER || self.role == ORGANIZATION_ADMIN
+    }
+}
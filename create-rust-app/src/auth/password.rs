@@ -0,0 +1,89 @@
+//! Password hashing: new passwords are hashed with Argon2id using the [`PasswordHashing`]
+//! parameters of the [`AuthConfig`](`super::AuthConfig`).
+//!
+//! Hashes made with other parameters (or an older variant, like the Argon2i hashes of previous
+//! versions) keep working, and are replaced with a hash using the current parameters the next
+//! time their user logs in. Raising the parameters migrates existing users transparently.
+use lazy_static::lazy_static;
+
+lazy_static! {
+    /// `SECRET_KEY`, mixed into every hash
+    static ref SECRET: &'static [u8] = match std::env::var("SECRET_KEY") {
+        Ok(s) => Box::leak(s.into_boxed_str()).as_bytes(),
+        Err(_) => panic!("No SECRET_KEY environment variable set!"),
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Argon2id cost parameters
+///
+/// the defaults follow [OWASP's recommendation](https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html#argon2id)
+pub struct PasswordHashing {
+    /// memory used, in KiB (default: 19456, i.e. 19 MiB)
+    pub mem_cost: u32,
+    /// number of passes (default: 2)
+    pub time_cost: u32,
+    /// degree of parallelism (default: 1)
+    pub lanes: u32,
+}
+
+impl Default for PasswordHashing {
+    fn default() -> Self {
+        Self {
+            mem_cost: 19 * 1024,
+            time_cost: 2,
+            lanes: 1,
+        }
+    }
+}
+
+impl PasswordHashing {
+    pub fn new(mem_cost: u32, time_cost: u32, lanes: u32) -> Self {
+        Self {
+            mem_cost,
+            time_cost,
+            lanes,
+        }
+    }
+
+    fn argon2_config(&self) -> argon2::Config<'static> {
+        argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: self.mem_cost,
+            time_cost: self.time_cost,
+            lanes: self.lanes,
+            secret: *SECRET,
+            ..Default::default()
+        }
+    }
+
+    /// hashes `password` with a new random salt
+    pub fn hash(&self, password: &str) -> String {
+        let salt = super::controller::generate_salt();
+
+        argon2::hash_encoded(password.as_bytes(), &salt, &self.argon2_config()).unwrap()
+    }
+
+    /// whether `password` matches `hash`, whichever parameters it was made with
+    pub fn verify(&self, hash: &str, password: &str) -> bool {
+        argon2::verify_encoded_ext(hash, password.as_bytes(), *SECRET, &[]).unwrap_or_else(|err| {
+            println!("could not verify a password hash: {err}");
+            false
+        })
+    }
+
+    /// whether `hash` was made with another variant or other parameters, and should be replaced
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        // $argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>
+        let mut parts = hash.split('$').skip(1);
+        let (variant, version, params) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(variant), Some(version), Some(params)) => (variant, version, params),
+            _ => return true,
+        };
+
+        variant != "argon2id"
+            || version != "v=19"
+            || params != format!("m={},t={},p={}", self.mem_cost, self.time_cost, self.lanes)
+    }
+}
@@ -109,4 +109,12 @@ impl UserRole {
         diesel::delete(user_roles.filter(user_id.eq(item_user_id).and(role.eq_any(item_roles))))
             .execute(db)
     }
+
+    /// Delete every entry in [`db`](`Connection`)'s user_roles table that has
+    /// `item_user_id` as one of its primary keys
+    pub fn delete_all(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use crate::auth::schema::user_roles::dsl::*;
+
+        diesel::delete(user_roles.filter(user_id.eq(item_user_id))).execute(db)
+    }
 }
@@ -0,0 +1,167 @@
+//! App-specific user fields (display name, avatar, preferences, ...), kept in a table your app owns
+//! so upgrading the auth plugin never touches them.
+//!
+//! The CLI generates a `user_profiles` migration and its model in `backend/models/user_profiles.rs`:
+//! add columns with a new migration, add them to the model, and they're served (and `tsync`ed)
+//! with the rest of your models.
+//!
+//! ```rust,ignore
+//! use create_rust_app::auth::{Auth, UserProfile};
+//!
+//! impl UserProfile for Profile {
+//!     fn read(db: &mut Connection, user_id: i32) -> QueryResult<Option<Self>> {
+//!         user_profiles::table.find(user_id).first(db).optional()
+//!     }
+//!     // ...
+//! }
+//!
+//! let auth_config = Auth::configure().user_profile::<Profile>();
+//! ```
+//!
+//! The .../profile endpoints read it, and update it with partial changes: the fields sent are merged
+//! into the current profile. Profiles are included in account exports, deleted with the account,
+//! and moved over when a guest logs into an account without a profile.
+//!
+//! A `locale` field, if the profile has one, selects the language of the auth emails (see
+//! [`Profiles::locale`]).
+use super::account::AccountHook;
+use super::ID;
+use crate::Connection;
+use diesel::QueryResult;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// a row of the app's profile table, one per user at most
+pub trait UserProfile: Serialize + DeserializeOwned + Default + Send + Sync + 'static {
+    /// [`user_id`](`ID`)'s profile, `None` if they don't have one yet
+    fn read(db: &mut Connection, user_id: ID) -> QueryResult<Option<Self>>;
+
+    /// creates or replaces [`user_id`](`ID`)'s profile
+    fn save(db: &mut Connection, user_id: ID, profile: Self) -> QueryResult<Self>;
+
+    fn delete(db: &mut Connection, user_id: ID) -> QueryResult<usize>;
+}
+
+/// [`UserProfile`] with json values, so [`Profiles`] doesn't need to know the app's type
+trait ProfileFields: Send + Sync {
+    fn read(&self, db: &mut Connection, user_id: ID) -> QueryResult<Value>;
+
+    fn update(
+        &self,
+        db: &mut Connection,
+        user_id: ID,
+        changes: Map<String, Value>,
+    ) -> QueryResult<Result<Value, String>>;
+}
+
+struct Fields<P>(PhantomData<fn() -> P>);
+
+impl<P: UserProfile> Fields<P> {
+    fn current(db: &mut Connection, user_id: ID) -> QueryResult<P> {
+        Ok(P::read(db, user_id)?.unwrap_or_default())
+    }
+}
+
+impl<P: UserProfile> ProfileFields for Fields<P> {
+    fn read(&self, db: &mut Connection, user_id: ID) -> QueryResult<Value> {
+        Ok(serde_json::to_value(Self::current(db, user_id)?).unwrap_or_default())
+    }
+
+    fn update(
+        &self,
+        db: &mut Connection,
+        user_id: ID,
+        changes: Map<String, Value>,
+    ) -> QueryResult<Result<Value, String>> {
+        let mut profile = match serde_json::to_value(Self::current(db, user_id)?) {
+            Ok(Value::Object(profile)) => profile,
+            _ => Map::new(),
+        };
+        profile.extend(changes);
+
+        let profile = match serde_json::from_value::<P>(Value::Object(profile)) {
+            Ok(profile) => profile,
+            Err(err) => return Ok(Err(err.to_string())),
+        };
+
+        let saved = P::save(db, user_id, profile)?;
+
+        Ok(Ok(serde_json::to_value(saved).unwrap_or_default()))
+    }
+}
+
+impl<P: UserProfile> AccountHook for Fields<P> {
+    fn name(&self) -> &'static str {
+        "profile"
+    }
+
+    fn export(&self, db: &mut Connection, user_id: ID) -> QueryResult<Value> {
+        Ok(serde_json::to_value(P::read(db, user_id)?).unwrap_or_default())
+    }
+
+    fn delete(&self, db: &mut Connection, user_id: ID) -> QueryResult<()> {
+        P::delete(db, user_id).map(|_| ())
+    }
+
+    fn merge(&self, db: &mut Connection, guest_id: ID, user_id: ID) -> QueryResult<()> {
+        if P::read(db, user_id)?.is_some() {
+            return Ok(());
+        }
+
+        if let Some(profile) = P::read(db, guest_id)? {
+            P::delete(db, guest_id)?;
+            P::save(db, user_id, profile)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+/// the app's [`UserProfile`], see [`AuthConfig::user_profile`](`super::AuthConfig::user_profile`)
+pub struct Profiles(Arc<dyn ProfileFields>);
+
+impl std::fmt::Debug for Profiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Profiles").finish()
+    }
+}
+
+impl Profiles {
+    pub(crate) fn new<P: UserProfile>() -> Self {
+        Self(Arc::new(Fields::<P>(PhantomData)))
+    }
+
+    pub(crate) fn account_hook<P: UserProfile>() -> impl AccountHook {
+        Fields::<P>(PhantomData)
+    }
+
+    /// [`user_id`](`ID`)'s profile, the default one if they don't have one yet
+    pub fn read(&self, db: &mut Connection, user_id: ID) -> QueryResult<Value> {
+        self.0.read(db, user_id)
+    }
+
+    /// the `locale` field of [`user_id`](`ID`)'s profile (ex: "fr" or "pt-BR"), if it has one, which
+    /// the auth plugin's emails are sent in, see [`Mailer::send_template`](`crate::Mailer::send_template`)
+    pub fn locale(&self, db: &mut Connection, user_id: ID) -> Option<String> {
+        let profile = self.read(db, user_id).ok()?;
+
+        profile["locale"]
+            .as_str()
+            .filter(|locale| !locale.is_empty())
+            .map(String::from)
+    }
+
+    /// merges `changes` into [`user_id`](`ID`)'s profile, `Err` when the result isn't a valid profile
+    pub fn update(
+        &self,
+        db: &mut Connection,
+        user_id: ID,
+        changes: Map<String, Value>,
+    ) -> QueryResult<Result<Value, String>> {
+        self.0.update(db, user_id, changes)
+    }
+}
@@ -0,0 +1,333 @@
+//! Limits how often the login, register and password reset endpoints can be called, so
+//! passwords can't be brute-forced and inboxes can't be flooded.
+//!
+//! Attempts are counted per IP address, and for login and password resets, per email address
+//! too. Once a limit is reached, the endpoints respond with `429 Too Many Requests` and a
+//! `Retry-After` header until the window is over.
+//!
+//! It's enabled by default with an in-memory store, which is only shared by the workers of a
+//! single process: use a [`RedisStore`] (`plugin_redis` feature) when running several instances.
+//!
+//! ```rust,ignore
+//! use create_rust_app::auth::{Auth, RateLimit, RateLimiter};
+//! use std::time::Duration;
+//!
+//! let auth_config = Auth::configure().rate_limit(Some(
+//!     RateLimiter::default()
+//!         .login(RateLimit::new(5, Duration::from_secs(10 * 60)))
+//!         .store(create_rust_app::auth::RedisStore::new("redis://127.0.0.1/").unwrap()),
+//! ));
+//! ```
+//!
+//! IP addresses are the connections' peer addresses: behind a reverse proxy, list it in
+//! `TRUSTED_PROXIES` (ex: `TRUSTED_PROXIES=10.0.0.0/8`) to read the client's address from the
+//! `X-Forwarded-For`/`Forwarded` headers it sets, see [`client_ip`](`crate::util::net::client_ip`).
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// the endpoints that are rate limited
+pub enum RateLimitedAction {
+    Login,
+    Register,
+    /// requesting a password reset email, and resetting the password with it
+    PasswordReset,
+}
+
+impl RateLimitedAction {
+    fn key(&self) -> &'static str {
+        match self {
+            RateLimitedAction::Login => "login",
+            RateLimitedAction::Register => "register",
+            RateLimitedAction::PasswordReset => "password_reset",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// at most `max_attempts` per `window`
+pub struct RateLimit {
+    pub max_attempts: u32,
+    pub window: Duration,
+}
+
+impl RateLimit {
+    pub fn new(max_attempts: u32, window: Duration) -> Self {
+        Self {
+            max_attempts,
+            window,
+        }
+    }
+}
+
+/// where attempts are counted
+pub trait RateLimitStore: Send + Sync {
+    /// counts an attempt for `key`, returns the number of attempts in the current window
+    /// (including this one) and how long until the window is over
+    fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), String>;
+}
+
+/// how many keys a [`MemoryStore`] keeps at most, the ones closest to the end of their window are
+/// forgotten first past it
+const MEMORY_STORE_MAX_KEYS: usize = 10_000;
+
+#[derive(Default)]
+/// counts attempts in this process' memory
+pub struct MemoryStore {
+    windows: Mutex<MemoryWindows>,
+}
+
+#[derive(Default)]
+struct MemoryWindows {
+    /// the end of each key's window, and the number of attempts in it
+    counts: HashMap<String, (Instant, u32)>,
+    /// the same windows, by their end
+    expiries: BTreeSet<(Instant, String)>,
+}
+
+impl MemoryWindows {
+    fn forget_first(&mut self) {
+        if let Some(first) = self.expiries.iter().next().cloned() {
+            self.expiries.remove(&first);
+            self.counts.remove(&first.1);
+        }
+    }
+}
+
+impl RateLimitStore for MemoryStore {
+    fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), String> {
+        let mut windows = self.windows.lock().map_err(|err| err.to_string())?;
+        let now = Instant::now();
+
+        // forget about windows that are over, so the map doesn't grow forever
+        while matches!(windows.expiries.iter().next(), Some((expires_at, _)) if *expires_at <= now)
+        {
+            windows.forget_first();
+        }
+
+        if let Some((expires_at, count)) = windows.counts.get_mut(key) {
+            *count += 1;
+            return Ok((*count, *expires_at - now));
+        }
+
+        if windows.counts.len() >= MEMORY_STORE_MAX_KEYS {
+            windows.forget_first();
+        }
+
+        let expires_at = now + window;
+        windows.counts.insert(key.to_string(), (expires_at, 1));
+        windows.expiries.insert((expires_at, key.to_string()));
+
+        Ok((1, window))
+    }
+}
+
+#[cfg(feature = "plugin_redis")]
+/// counts attempts in Redis, so they're shared by every instance of the app
+pub struct RedisStore {
+    client: redis::Client,
+    prefix: String,
+}
+
+#[cfg(feature = "plugin_redis")]
+impl RedisStore {
+    /// `url` like `redis://127.0.0.1/`, keys are prefixed with `cra:rate_limit:`
+    pub fn new(url: &str) -> redis::RedisResult<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            prefix: "cra:rate_limit:".to_string(),
+        })
+    }
+
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+}
+
+#[cfg(feature = "plugin_redis")]
+impl RateLimitStore for RedisStore {
+    fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), String> {
+        let key = format!("{}{key}", self.prefix);
+        let mut conn = self
+            .client
+            .get_connection()
+            .map_err(|err| err.to_string())?;
+
+        let (count, ttl): (u32, i64) = redis::pipe()
+            .atomic()
+            .incr(&key, 1)
+            .pttl(&key)
+            .query(&mut conn)
+            .map_err(|err| err.to_string())?;
+
+        // the key was just created (or lost its expiry): start the window
+        if ttl < 0 {
+            redis::cmd("PEXPIRE")
+                .arg(&key)
+                .arg(window.as_millis() as u64)
+                .query::<()>(&mut conn)
+                .map_err(|err| err.to_string())?;
+
+            return Ok((count, window));
+        }
+
+        Ok((count, Duration::from_millis(ttl as u64)))
+    }
+}
+
+lazy_static::lazy_static! {
+    /// shared by every [`RateLimiter::default`], so the endpoints share it even when the app
+    /// doesn't register an [`AuthConfig`](`super::AuthConfig`)
+    static ref MEMORY_STORE: Arc<MemoryStore> = Arc::new(MemoryStore::default());
+}
+
+#[derive(Clone)]
+/// the limits of each endpoint, and where attempts are counted
+pub struct RateLimiter {
+    /// (default: 10 attempts per 15 minutes)
+    pub login: RateLimit,
+    /// (default: 5 attempts per hour)
+    pub register: RateLimit,
+    /// (default: 5 attempts per hour)
+    pub password_reset: RateLimit,
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            login: RateLimit::new(10, Duration::from_secs(15 * 60)),
+            register: RateLimit::new(5, Duration::from_secs(60 * 60)),
+            password_reset: RateLimit::new(5, Duration::from_secs(60 * 60)),
+            store: MEMORY_STORE.clone(),
+        }
+    }
+}
+
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("login", &self.login)
+            .field("register", &self.register)
+            .field("password_reset", &self.password_reset)
+            .finish()
+    }
+}
+
+impl RateLimiter {
+    pub fn login(mut self, limit: RateLimit) -> Self {
+        self.login = limit;
+        self
+    }
+
+    pub fn register(mut self, limit: RateLimit) -> Self {
+        self.register = limit;
+        self
+    }
+
+    pub fn password_reset(mut self, limit: RateLimit) -> Self {
+        self.password_reset = limit;
+        self
+    }
+
+    pub fn store(mut self, store: impl RateLimitStore + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// counts an attempt at [`action`](`RateLimitedAction`) for each of `keys` (like the IP and
+    /// email addresses), returns how long to wait if any of them is over the limit
+    ///
+    /// attempts are let through when the store fails (ex: Redis is down)
+    pub fn check(&self, action: RateLimitedAction, keys: &[Option<&str>]) -> Result<(), Duration> {
+        let limit = match action {
+            RateLimitedAction::Login => self.login,
+            RateLimitedAction::Register => self.register,
+            RateLimitedAction::PasswordReset => self.password_reset,
+        };
+
+        let mut retry_after: Option<Duration> = None;
+        for key in keys.iter().flatten() {
+            let key = format!("{}:{}", action.key(), key.to_lowercase());
+
+            match self.store.hit(&key, limit.window) {
+                Ok((count, reset_in)) if count > limit.max_attempts => {
+                    retry_after = Some(retry_after.map_or(reset_in, |d| d.max(reset_in)));
+                }
+                Ok(_) => {}
+                Err(err) => println!("rate limiting failed: {err}"),
+            }
+        }
+
+        match retry_after {
+            Some(retry_after) => Err(retry_after),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod rate_limit_tests {
+    use super::{MemoryStore, RateLimitStore, MEMORY_STORE_MAX_KEYS};
+    use std::time::Duration;
+
+    #[test]
+    fn test_memory_store_counts_each_window() {
+        let store = MemoryStore::default();
+
+        assert_eq!(store.hit("login:a", Duration::from_secs(60)).unwrap().0, 1);
+        assert_eq!(store.hit("login:a", Duration::from_secs(60)).unwrap().0, 2);
+        assert_eq!(store.hit("login:b", Duration::from_secs(60)).unwrap().0, 1);
+
+        // a window that's over starts again
+        store.hit("register:a", Duration::from_millis(1)).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(
+            store.hit("register:a", Duration::from_millis(1)).unwrap().0,
+            1
+        );
+    }
+
+    #[test]
+    fn test_memory_store_prunes_by_each_keys_window() {
+        let store = MemoryStore::default();
+
+        store.hit("login:a", Duration::from_secs(60 * 60)).unwrap();
+        for i in 0..MEMORY_STORE_MAX_KEYS {
+            store
+                .hit(&format!("register:{i}"), Duration::from_millis(1))
+                .unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(5));
+
+        // a shorter window doesn't forget about longer ones
+        store.hit("register:b", Duration::from_millis(1)).unwrap();
+        assert_eq!(
+            store
+                .hit("login:a", Duration::from_secs(60 * 60))
+                .unwrap()
+                .0,
+            2
+        );
+        assert_eq!(store.windows.lock().unwrap().counts.len(), 2);
+    }
+
+    #[test]
+    fn test_memory_store_is_capped() {
+        let store = MemoryStore::default();
+
+        for i in 0..MEMORY_STORE_MAX_KEYS + 10 {
+            store
+                .hit(&format!("login:{i}"), Duration::from_secs(60))
+                .unwrap();
+        }
+
+        let windows = store.windows.lock().unwrap();
+        assert_eq!(windows.counts.len(), MEMORY_STORE_MAX_KEYS);
+        assert_eq!(windows.expiries.len(), MEMORY_STORE_MAX_KEYS);
+        // the oldest window was forgotten first
+        assert!(!windows.counts.contains_key("login:0"));
+    }
+}
@@ -0,0 +1,193 @@
+//! Revokes access tokens before they expire: logging out revokes the access token it was made
+//! with, and [`TokenRevocation::revoke_user`] revokes every token a user was issued so far (ex:
+//! when banning them).
+//!
+//! Revoked tokens are remembered until they expire, and rejected by the [`Auth`](`super::Auth`)
+//! extractor. It's enabled by default with an in-memory store, which is only shared by the workers
+//! of a single process: use a [`RedisRevocationStore`] (`plugin_redis` feature) when running
+//! several instances.
+//!
+//! ```rust,ignore
+//! use create_rust_app::auth::{Auth, RedisRevocationStore, TokenRevocation};
+//!
+//! let auth_config = Auth::configure().revocation(Some(
+//!     TokenRevocation::default().store(RedisRevocationStore::new("redis://127.0.0.1/").unwrap()),
+//! ));
+//!
+//! // banning a user: delete their sessions so they can't refresh, then revoke their access tokens
+//! auth_config.revoke_user(user_id);
+//! ```
+use super::ID;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// where revocations are kept
+pub trait RevocationStore: Send + Sync {
+    /// remembers `value` under `key` for `ttl`
+    fn set(&self, key: &str, value: u64, ttl: Duration) -> Result<(), String>;
+
+    /// the value under `key`, if it was set and hasn't expired
+    fn get(&self, key: &str) -> Result<Option<u64>, String>;
+}
+
+#[derive(Default)]
+/// keeps revocations in this process' memory
+pub struct MemoryRevocationStore {
+    entries: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl RevocationStore for MemoryRevocationStore {
+    fn set(&self, key: &str, value: u64, ttl: Duration) -> Result<(), String> {
+        let mut entries = self.entries.lock().map_err(|err| err.to_string())?;
+        let now = Instant::now();
+
+        // forget about expired entries, so the map doesn't grow forever
+        if entries.len() > 10_000 {
+            entries.retain(|_, (_, expires_at)| *expires_at > now);
+        }
+
+        entries.insert(key.to_string(), (value, now + ttl));
+
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<u64>, String> {
+        let entries = self.entries.lock().map_err(|err| err.to_string())?;
+
+        Ok(entries
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(value, _)| *value))
+    }
+}
+
+#[cfg(feature = "plugin_redis")]
+/// keeps revocations in Redis, so they're shared by every instance of the app
+pub struct RedisRevocationStore {
+    client: redis::Client,
+    prefix: String,
+}
+
+#[cfg(feature = "plugin_redis")]
+impl RedisRevocationStore {
+    /// `url` like `redis://127.0.0.1/`, keys are prefixed with `cra:revoked:`
+    pub fn new(url: &str) -> redis::RedisResult<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            prefix: "cra:revoked:".to_string(),
+        })
+    }
+
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+}
+
+#[cfg(feature = "plugin_redis")]
+impl RevocationStore for RedisRevocationStore {
+    fn set(&self, key: &str, value: u64, ttl: Duration) -> Result<(), String> {
+        let mut conn = self
+            .client
+            .get_connection()
+            .map_err(|err| err.to_string())?;
+
+        redis::cmd("SET")
+            .arg(format!("{}{key}", self.prefix))
+            .arg(value)
+            .arg("PX")
+            .arg((ttl.as_millis() as u64).max(1))
+            .query::<()>(&mut conn)
+            .map_err(|err| err.to_string())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<u64>, String> {
+        let mut conn = self
+            .client
+            .get_connection()
+            .map_err(|err| err.to_string())?;
+
+        redis::cmd("GET")
+            .arg(format!("{}{key}", self.prefix))
+            .query::<Option<u64>>(&mut conn)
+            .map_err(|err| err.to_string())
+    }
+}
+
+lazy_static::lazy_static! {
+    /// shared by every [`TokenRevocation::default`], so the endpoints and the extractors share it
+    /// even when the app doesn't register an [`AuthConfig`](`super::AuthConfig`)
+    static ref MEMORY_STORE: Arc<MemoryRevocationStore> = Arc::new(MemoryRevocationStore::default());
+}
+
+#[derive(Clone)]
+/// revokes access tokens, see the [module documentation](`self`)
+pub struct TokenRevocation {
+    store: Arc<dyn RevocationStore>,
+}
+
+impl Default for TokenRevocation {
+    fn default() -> Self {
+        Self {
+            store: MEMORY_STORE.clone(),
+        }
+    }
+}
+
+impl std::fmt::Debug for TokenRevocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenRevocation").finish()
+    }
+}
+
+impl TokenRevocation {
+    pub fn store(mut self, store: impl RevocationStore + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// revokes the access token `jti`, until it expires at `exp` (unix time, in seconds)
+    pub fn revoke_token(&self, jti: &str, exp: usize) {
+        let ttl = Duration::from_secs((exp as u64).saturating_sub(now()));
+
+        if let Err(err) = self.store.set(&format!("token:{jti}"), 1, ttl) {
+            println!("could not revoke an access token: {err}");
+        }
+    }
+
+    /// revokes every access token issued to [`user_id`](`ID`) until now, remembered for `ttl`
+    /// (the longest an access token can live)
+    pub fn revoke_user(&self, user_id: ID, ttl: Duration) {
+        if let Err(err) = self.store.set(&format!("user:{user_id}"), now(), ttl) {
+            println!("could not revoke the access tokens of user {user_id}: {err}");
+        }
+    }
+
+    /// whether the access token `jti`, issued to [`user_id`](`ID`) at `iat`, was revoked
+    ///
+    /// tokens are let through when the store fails (ex: Redis is down)
+    pub fn is_revoked(&self, user_id: ID, jti: &str, iat: usize) -> bool {
+        let token_revoked = match self.store.get(&format!("token:{jti}")) {
+            Ok(revoked) => revoked.is_some(),
+            Err(err) => {
+                println!("could not check whether an access token was revoked: {err}");
+                false
+            }
+        };
+
+        // tokens issued in the second the user was revoked are kept, so they can log back in right away
+        token_revoked
+            || match self.store.get(&format!("user:{user_id}")) {
+                Ok(revoked_at) => revoked_at.map_or(false, |revoked_at| (iat as u64) < revoked_at),
+                Err(err) => {
+                    println!("could not check whether an access token was revoked: {err}");
+                    false
+                }
+            }
+    }
+}
+
+fn now() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
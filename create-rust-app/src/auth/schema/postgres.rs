@@ -1,3 +1,21 @@
+table! {
+  memberships (organization_id, user_id) {
+      organization_id -> Int4,
+      user_id -> Int4,
+      role -> Text,
+      active_at -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  organizations (id) {
+      id -> Int4,
+      name -> Text,
+      created_at -> Timestamptz,
+  }
+}
+
 table! {
   role_permissions (role) {
       role -> Text,
@@ -6,6 +24,55 @@ table! {
   }
 }
 
+table! {
+  user_bans (id) {
+      id -> Int4,
+      user_id -> Int4,
+      reason -> Nullable<Text>,
+      banned_by -> Nullable<Int4>,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  user_identities (id) {
+      id -> Int4,
+      user_id -> Int4,
+      provider -> Text,
+      provider_user_id -> Text,
+      email -> Nullable<Text>,
+      created_at -> Timestamptz,
+      updated_at -> Timestamptz,
+  }
+}
+
+table! {
+  user_impersonations (id) {
+      id -> Int4,
+      impersonator_id -> Nullable<Int4>,
+      user_id -> Int4,
+      reason -> Nullable<Text>,
+      ip_address -> Nullable<Text>,
+      started_at -> Timestamptz,
+      ended_at -> Nullable<Timestamptz>,
+  }
+}
+
+table! {
+  user_invitations (id) {
+      id -> Int4,
+      email -> Text,
+      roles -> Text,
+      token -> Text,
+      invited_by -> Int4,
+      organization_id -> Nullable<Int4>,
+      organization_role -> Nullable<Text>,
+      accepted_at -> Nullable<Timestamptz>,
+      expires_at -> Timestamptz,
+      created_at -> Timestamptz,
+  }
+}
+
 table! {
   user_permissions (user_id, permission) {
       user_id -> Int4,
@@ -22,6 +89,14 @@ table! {
   }
 }
 
+table! {
+  user_session_rotated_tokens (refresh_token) {
+      refresh_token -> Text,
+      session_id -> Int4,
+      created_at -> Timestamptz,
+  }
+}
+
 table! {
   user_sessions (id) {
       id -> Int4,
@@ -30,28 +105,59 @@ table! {
       device -> Nullable<Text>,
       created_at -> Timestamptz,
       updated_at -> Timestamptz,
+      ip_address -> Nullable<Text>,
+      last_used_at -> Timestamptz,
   }
 }
 
+#[cfg(not(feature = "plugin_soft_delete"))]
 table! {
   users (id) {
       id -> Int4,
-      email -> Text,
-      hash_password -> Text,
+      email -> Nullable<Text>,
+      hash_password -> Nullable<Text>,
+      activated -> Bool,
+      created_at -> Timestamptz,
+      updated_at -> Timestamptz,
+  }
+}
+
+// `users` with the `deleted_at` column of soft-deleted accounts, see `AccountDeletion::SoftDelete`
+#[cfg(feature = "plugin_soft_delete")]
+table! {
+  users (id) {
+      id -> Int4,
+      email -> Nullable<Text>,
+      hash_password -> Nullable<Text>,
       activated -> Bool,
       created_at -> Timestamptz,
       updated_at -> Timestamptz,
+      deleted_at -> Nullable<Timestamptz>,
   }
 }
 
+joinable!(memberships -> organizations (organization_id));
+joinable!(memberships -> users (user_id));
+joinable!(user_bans -> users (user_id));
+joinable!(user_identities -> users (user_id));
+joinable!(user_impersonations -> users (user_id));
+joinable!(user_invitations -> users (invited_by));
 joinable!(user_permissions -> users (user_id));
 joinable!(user_roles -> users (user_id));
+joinable!(user_session_rotated_tokens -> user_sessions (session_id));
 joinable!(user_sessions -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    memberships,
+    organizations,
     role_permissions,
+    user_bans,
+    user_identities,
+    user_impersonations,
+    user_invitations,
     user_permissions,
     user_roles,
+    user_session_rotated_tokens,
     user_sessions,
     users,
 );
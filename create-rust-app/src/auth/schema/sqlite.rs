@@ -1,3 +1,21 @@
+table! {
+  memberships (organization_id, user_id) {
+      organization_id -> Integer,
+      user_id -> Integer,
+      role -> Text,
+      active_at -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  organizations (id) {
+      id -> Integer,
+      name -> Text,
+      created_at -> Timestamp,
+  }
+}
+
 table! {
   role_permissions (role, permission) {
       role -> Text,
@@ -6,6 +24,54 @@ table! {
   }
 }
 
+table! {
+  user_bans (id) {
+      id -> Integer,
+      user_id -> Integer,
+      reason -> Nullable<Text>,
+      banned_by -> Nullable<Integer>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  user_identities (id) {
+      id -> Integer,
+      user_id -> Integer,
+      provider -> Text,
+      provider_user_id -> Text,
+      email -> Nullable<Text>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  user_impersonations (id) {
+      id -> Integer,
+      impersonator_id -> Nullable<Integer>,
+      user_id -> Integer,
+      reason -> Nullable<Text>,
+      ip_address -> Nullable<Text>,
+      started_at -> Timestamp,
+      ended_at -> Nullable<Timestamp>,
+  }
+}
+
+table! {
+  user_invitations (id) {
+      id -> Integer,
+      email -> Text,
+      roles -> Text,
+      token -> Text,
+      invited_by -> Integer,
+      organization_id -> Nullable<Integer>,
+      organization_role -> Nullable<Text>,
+      accepted_at -> Nullable<Timestamp>,
+      expires_at -> Timestamp,
+      created_at -> Timestamp,
+  }
+}
+
 table! {
   user_permissions (user_id, permission) {
       user_id -> Integer,
@@ -22,6 +88,14 @@ table! {
   }
 }
 
+table! {
+  user_session_rotated_tokens (refresh_token) {
+      refresh_token -> Text,
+      session_id -> Integer,
+      created_at -> Timestamp,
+  }
+}
+
 table! {
   user_sessions (id) {
       id -> Integer,
@@ -29,27 +103,57 @@ table! {
       refresh_token -> Text,
       device -> Nullable<Text>,
       created_at -> Timestamp,
+      ip_address -> Nullable<Text>,
+      last_used_at -> Timestamp,
   }
 }
 
+#[cfg(not(feature = "plugin_soft_delete"))]
 table! {
   users (id) {
       id -> Integer,
-      email -> Text,
-      hash_password -> Text,
+      email -> Nullable<Text>,
+      hash_password -> Nullable<Text>,
+      activated -> Bool,
+      created_at -> Timestamp,
+  }
+}
+
+// `users` with the `deleted_at` column of soft-deleted accounts, see `AccountDeletion::SoftDelete`
+#[cfg(feature = "plugin_soft_delete")]
+table! {
+  users (id) {
+      id -> Integer,
+      email -> Nullable<Text>,
+      hash_password -> Nullable<Text>,
       activated -> Bool,
       created_at -> Timestamp,
+      deleted_at -> Nullable<Timestamp>,
   }
 }
 
+joinable!(memberships -> organizations (organization_id));
+joinable!(memberships -> users (user_id));
+joinable!(user_bans -> users (user_id));
+joinable!(user_identities -> users (user_id));
+joinable!(user_impersonations -> users (user_id));
+joinable!(user_invitations -> users (invited_by));
 joinable!(user_permissions -> users (user_id));
 joinable!(user_roles -> users (user_id));
+joinable!(user_session_rotated_tokens -> user_sessions (session_id));
 joinable!(user_sessions -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    memberships,
+    organizations,
     role_permissions,
+    user_bans,
+    user_identities,
+    user_impersonations,
+    user_invitations,
     user_permissions,
     user_roles,
+    user_session_rotated_tokens,
     user_sessions,
     users,
 );
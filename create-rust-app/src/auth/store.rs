@@ -0,0 +1,276 @@
+//! Where the auth plugin keeps its users and sessions.
+//!
+//! By default they're the `users` and `user_sessions` tables created by the plugin's migration
+//! ([`DieselUserStore`] and [`DieselSessionStore`]). Apps with an existing user table, or whose users
+//! live in another identity system, can plug in their own [`UserStore`] (and [`SessionStore`]),
+//! mapping their records to [`User`]s and [`UserSession`]s:
+//!
+//! ```rust,ignore
+//! use create_rust_app::auth::{Auth, User, UserChangeset, UserStore};
+//! use create_rust_app::Connection;
+//! use diesel::QueryResult;
+//!
+//! struct LegacyAccounts;
+//!
+//! impl UserStore for LegacyAccounts {
+//!     fn read(&self, db: &mut Connection, id: i32) -> QueryResult<User> {
+//!         Account::read(db, id).map(Account::into_user)
+//!     }
+//!     // ...
+//! }
+//!
+//! let auth_config = Auth::configure().user_store(LegacyAccounts);
+//! ```
+//!
+//! Every method gets the request's database connection (inside its transaction, if any), stores
+//! backed by something else can ignore it. Roles, permissions, organizations and the other plugins
+//! (ex: notification digests) still use the plugin's own tables.
+use super::{
+    PaginationParams, User, UserChangeset, UserSession, UserSessionChangeset,
+    UserSessionRotatedToken, UserSessionRotatedTokenChangeset, ID,
+};
+use crate::Connection;
+use diesel::QueryResult;
+use std::sync::Arc;
+
+/// reads and writes users, return [`diesel::result::Error::NotFound`] for missing ones
+pub trait UserStore: Send + Sync {
+    fn read(&self, db: &mut Connection, id: ID) -> QueryResult<User>;
+
+    fn find_by_email(&self, db: &mut Connection, email: &str) -> QueryResult<User>;
+
+    fn create(&self, db: &mut Connection, item: &UserChangeset) -> QueryResult<User>;
+
+    fn update(&self, db: &mut Connection, id: ID, item: &UserChangeset) -> QueryResult<User>;
+
+    fn delete(&self, db: &mut Connection, id: ID) -> QueryResult<usize>;
+}
+
+/// reads and writes sessions (one per logged in device), return
+/// [`diesel::result::Error::NotFound`] for missing ones
+pub trait SessionStore: Send + Sync {
+    fn create(&self, db: &mut Connection, item: &UserSessionChangeset) -> QueryResult<UserSession>;
+
+    fn read(&self, db: &mut Connection, id: ID) -> QueryResult<UserSession>;
+
+    fn find_by_refresh_token(
+        &self,
+        db: &mut Connection,
+        refresh_token: &str,
+    ) -> QueryResult<UserSession>;
+
+    /// the id of the session that used to have `refresh_token`, before [`SessionStore::rotate`]
+    /// replaced it
+    fn find_by_rotated_token(&self, db: &mut Connection, refresh_token: &str) -> QueryResult<ID>;
+
+    /// every session of [`user_id`](`ID`), most recently used first
+    fn fetch_all_for_user(&self, db: &mut Connection, user_id: ID)
+        -> QueryResult<Vec<UserSession>>;
+
+    /// a page of the sessions of [`user_id`](`ID`), oldest first
+    fn read_all(
+        &self,
+        db: &mut Connection,
+        pagination: &PaginationParams,
+        user_id: ID,
+    ) -> QueryResult<Vec<UserSession>>;
+
+    fn count_all(&self, db: &mut Connection, user_id: ID) -> QueryResult<i64>;
+
+    fn update(
+        &self,
+        db: &mut Connection,
+        id: ID,
+        item: &UserSessionChangeset,
+    ) -> QueryResult<UserSession>;
+
+    /// updates the session [`id`](`ID`) with a new refresh token, and remembers `rotated_token`
+    /// (the one it replaces) so its reuse can be detected
+    fn rotate(
+        &self,
+        db: &mut Connection,
+        id: ID,
+        rotated_token: &str,
+        item: &UserSessionChangeset,
+    ) -> QueryResult<UserSession>;
+
+    /// sets `last_used_at` to now
+    fn touch(&self, db: &mut Connection, id: ID) -> QueryResult<usize>;
+
+    fn delete(&self, db: &mut Connection, id: ID) -> QueryResult<usize>;
+
+    fn delete_all_for_user(&self, db: &mut Connection, user_id: ID) -> QueryResult<usize>;
+
+    fn delete_all_for_user_except(
+        &self,
+        db: &mut Connection,
+        user_id: ID,
+        keep_id: ID,
+    ) -> QueryResult<usize>;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// keeps users in the `users` table, see [`User`]
+pub struct DieselUserStore;
+
+impl UserStore for DieselUserStore {
+    fn read(&self, db: &mut Connection, id: ID) -> QueryResult<User> {
+        User::read(db, id)
+    }
+
+    fn find_by_email(&self, db: &mut Connection, email: &str) -> QueryResult<User> {
+        User::find_by_email(db, email.to_string())
+    }
+
+    fn create(&self, db: &mut Connection, item: &UserChangeset) -> QueryResult<User> {
+        User::create(db, item)
+    }
+
+    fn update(&self, db: &mut Connection, id: ID, item: &UserChangeset) -> QueryResult<User> {
+        User::update(db, id, item)
+    }
+
+    fn delete(&self, db: &mut Connection, id: ID) -> QueryResult<usize> {
+        User::delete(db, id)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// keeps sessions in the `user_sessions` table, see [`UserSession`] and [`UserSessionRotatedToken`]
+pub struct DieselSessionStore;
+
+impl SessionStore for DieselSessionStore {
+    fn create(&self, db: &mut Connection, item: &UserSessionChangeset) -> QueryResult<UserSession> {
+        UserSession::create(db, item)
+    }
+
+    fn read(&self, db: &mut Connection, id: ID) -> QueryResult<UserSession> {
+        UserSession::read(db, id)
+    }
+
+    fn find_by_refresh_token(
+        &self,
+        db: &mut Connection,
+        refresh_token: &str,
+    ) -> QueryResult<UserSession> {
+        UserSession::find_by_refresh_token(db, refresh_token)
+    }
+
+    fn find_by_rotated_token(&self, db: &mut Connection, refresh_token: &str) -> QueryResult<ID> {
+        UserSessionRotatedToken::find_by_refresh_token(db, refresh_token)
+            .map(|rotated| rotated.session_id)
+    }
+
+    fn fetch_all_for_user(
+        &self,
+        db: &mut Connection,
+        user_id: ID,
+    ) -> QueryResult<Vec<UserSession>> {
+        UserSession::fetch_all_for_user(db, user_id)
+    }
+
+    fn read_all(
+        &self,
+        db: &mut Connection,
+        pagination: &PaginationParams,
+        user_id: ID,
+    ) -> QueryResult<Vec<UserSession>> {
+        UserSession::read_all(db, pagination, user_id)
+    }
+
+    fn count_all(&self, db: &mut Connection, user_id: ID) -> QueryResult<i64> {
+        UserSession::count_all(db, user_id)
+    }
+
+    fn update(
+        &self,
+        db: &mut Connection,
+        id: ID,
+        item: &UserSessionChangeset,
+    ) -> QueryResult<UserSession> {
+        UserSession::update(db, id, item)
+    }
+
+    fn rotate(
+        &self,
+        db: &mut Connection,
+        id: ID,
+        rotated_token: &str,
+        item: &UserSessionChangeset,
+    ) -> QueryResult<UserSession> {
+        diesel::connection::Connection::transaction(db, |db| {
+            UserSessionRotatedToken::create(
+                db,
+                &UserSessionRotatedTokenChangeset {
+                    refresh_token: rotated_token.to_string(),
+                    session_id: id,
+                },
+            )?;
+
+            UserSession::update(db, id, item)
+        })
+    }
+
+    fn touch(&self, db: &mut Connection, id: ID) -> QueryResult<usize> {
+        UserSession::touch(db, id)
+    }
+
+    // the rotated tokens are deleted first, SQLite doesn't enforce their `ON DELETE CASCADE` unless
+    // its foreign keys are turned on
+    fn delete(&self, db: &mut Connection, id: ID) -> QueryResult<usize> {
+        diesel::connection::Connection::transaction(db, |db| {
+            UserSessionRotatedToken::delete_all_for_session(db, id)?;
+
+            UserSession::delete(db, id)
+        })
+    }
+
+    fn delete_all_for_user(&self, db: &mut Connection, user_id: ID) -> QueryResult<usize> {
+        diesel::connection::Connection::transaction(db, |db| {
+            for session in UserSession::fetch_all_for_user(db, user_id)? {
+                UserSessionRotatedToken::delete_all_for_session(db, session.id)?;
+            }
+
+            UserSession::delete_all_for_user(db, user_id)
+        })
+    }
+
+    fn delete_all_for_user_except(
+        &self,
+        db: &mut Connection,
+        user_id: ID,
+        keep_id: ID,
+    ) -> QueryResult<usize> {
+        diesel::connection::Connection::transaction(db, |db| {
+            for session in UserSession::fetch_all_for_user(db, user_id)? {
+                if session.id != keep_id {
+                    UserSessionRotatedToken::delete_all_for_session(db, session.id)?;
+                }
+            }
+
+            UserSession::delete_all_for_user_except(db, user_id, keep_id)
+        })
+    }
+}
+
+#[derive(Clone)]
+/// the [`UserStore`] and [`SessionStore`] used by the auth plugin (default: the Diesel ones)
+pub struct AuthStores {
+    pub users: Arc<dyn UserStore>,
+    pub sessions: Arc<dyn SessionStore>,
+}
+
+impl Default for AuthStores {
+    fn default() -> Self {
+        Self {
+            users: Arc::new(DieselUserStore),
+            sessions: Arc::new(DieselSessionStore),
+        }
+    }
+}
+
+impl std::fmt::Debug for AuthStores {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthStores").finish()
+    }
+}
@@ -33,13 +33,33 @@ pub struct User {
     -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
     pub id: ID,
 
-    pub email: String,
-    pub hash_password: String,
+    /// `None` for guests, see [`controller::create_guest`](`super::controller::create_guest`)
+    pub email: Option<String>,
+    /// `None` for guests
+    pub hash_password: Option<String>,
     pub activated: bool,
 
     pub created_at: Utc,
     #[cfg(not(feature = "database_sqlite"))]
     pub updated_at: Utc,
+    #[cfg(feature = "plugin_soft_delete")]
+    /// set when the account was soft-deleted, see
+    /// [`AccountDeletion::SoftDelete`](`super::account::AccountDeletion::SoftDelete`)
+    pub deleted_at: Option<Utc>,
+}
+
+#[cfg(feature = "plugin_soft_delete")]
+crate::soft_delete!(User, users);
+
+/// the users the queries below see: all of them, except the soft-deleted ones with the
+/// `plugin_soft_delete` feature
+#[cfg(feature = "plugin_soft_delete")]
+fn scope() -> users::BoxedQuery<'static, crate::soft_delete::Backend> {
+    User::active()
+}
+#[cfg(not(feature = "plugin_soft_delete"))]
+fn scope() -> users::table {
+    users::table
 }
 
 #[tsync::tsync]
@@ -51,12 +71,17 @@ pub struct UserChangeset {
     Don't include non-mutable columns
     (ex: id, created_at/updated_at)
     -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
-    pub email: String,
-    pub hash_password: String,
+    pub email: Option<String>,
+    pub hash_password: Option<String>,
     pub activated: bool,
 }
 
 impl User {
+    /// whether this user has no credentials yet, see [`controller::create_guest`](`super::controller::create_guest`)
+    pub fn is_guest(&self) -> bool {
+        self.email.is_none()
+    }
+
     /// Create an entry in [`db`](`Connection`)'s `users` table using the data in [`item`](`UserChangeset`)
     pub fn create(db: &mut Connection, item: &UserChangeset) -> QueryResult<Self> {
         use super::schema::users::dsl::*;
@@ -69,7 +94,15 @@ impl User {
     pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
         use super::schema::users::dsl::*;
 
-        users.filter(id.eq(item_id)).first::<User>(db)
+        scope().filter(id.eq(item_id)).first::<User>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for the entries in the `users` whose primary
+    /// keys are in [`item_ids`](`ID`) (ex: to batch the lookups of a GraphQL dataloader)
+    pub fn read_many(db: &mut Connection, item_ids: &[ID]) -> QueryResult<Vec<Self>> {
+        use super::schema::users::dsl::*;
+
+        scope().filter(id.eq_any(item_ids)).load::<User>(db)
     }
 
     /// Queries [`db`](`Connection`)'s `users` table for an entry
@@ -77,7 +110,7 @@ impl User {
     pub fn find_by_email(db: &mut Connection, item_email: String) -> QueryResult<Self> {
         use super::schema::users::dsl::*;
 
-        users.filter(email.eq(item_email)).first::<User>(db)
+        scope().filter(email.eq(item_email)).first::<User>(db)
     }
 
     /// Read from [`db`](`Connection`), return entries of the `users` table,
@@ -85,7 +118,7 @@ impl User {
     pub fn read_all(db: &mut Connection, pagination: &PaginationParams) -> QueryResult<Vec<Self>> {
         use super::schema::users::dsl::*;
 
-        users
+        scope()
             .order(created_at)
             .limit(pagination.page_size)
             .offset(
@@ -95,6 +128,52 @@ impl User {
             .load::<User>(db)
     }
 
+    /// Read from [`db`](`Connection`), return the entries of the `users` table whose email contains
+    /// `query` (case insensitive) or whose id is `query`, newest first, along with how many match
+    pub fn search(
+        db: &mut Connection,
+        query: &str,
+        page: i64,
+        page_size: i64,
+    ) -> QueryResult<(Vec<Self>, i64)> {
+        use super::schema::users::dsl::*;
+
+        let pattern = format!(
+            "%{}%",
+            query
+                .to_lowercase()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let item_id = query.trim().parse::<ID>().ok();
+
+        let filtered = || {
+            let mut filtered = scope().into_boxed();
+            if !query.is_empty() {
+                #[cfg(not(feature = "database_sqlite"))]
+                let matches_email = email.ilike(pattern.clone()).escape('\\');
+                #[cfg(feature = "database_sqlite")]
+                let matches_email = email.like(pattern.clone()).escape('\\');
+
+                filtered = match item_id {
+                    Some(item_id) => filtered.filter(matches_email.or(id.eq(item_id))),
+                    None => filtered.filter(matches_email),
+                };
+            }
+            filtered
+        };
+
+        let total = filtered().count().get_result::<i64>(db)?;
+        let found = filtered()
+            .order(id.desc())
+            .limit(page_size)
+            .offset(page * page_size)
+            .load::<User>(db)?;
+
+        Ok((found, total))
+    }
+
     /// Update the entry in [`db`](`Connection`)'s `users` table who's primary key matches
     /// [`item_id`](`ID`), with the data in [`item`](`UserChangeset`)
     pub fn update(db: &mut Connection, item_id: ID, item: &UserChangeset) -> QueryResult<Self> {
@@ -0,0 +1,83 @@
+use super::schema::*;
+use crate::diesel::*;
+
+use super::user::User;
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable, Associations,
+)]
+#[diesel(table_name=user_bans, belongs_to(User))]
+/// A user who isn't allowed to log in anymore, see [`controller::login`](`super::controller::login`).
+///
+/// Banning a user doesn't end their sessions by itself, revoke them as well.
+pub struct UserBan {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub user_id: ID,
+    pub reason: Option<String>,
+    /// the admin who banned the user, `None` when it wasn't done by a user (ex: in development)
+    pub banned_by: Option<ID>,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=user_bans)]
+pub struct UserBanChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub reason: Option<String>,
+    pub banned_by: Option<ID>,
+}
+
+impl UserBan {
+    /// Create an entry in [`db`](`Connection`)'s `user_bans` table using the data in [`item`](`UserBanChangeset`)
+    pub fn create(db: &mut Connection, item: &UserBanChangeset) -> QueryResult<Self> {
+        use super::schema::user_bans::dsl::*;
+
+        insert_into(user_bans)
+            .values(item)
+            .get_result::<UserBan>(db)
+    }
+
+    /// the ban of [`item_user_id`](`ID`), `None` if they aren't banned
+    pub fn find_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Option<Self>> {
+        use super::schema::user_bans::dsl::*;
+
+        user_bans
+            .filter(user_id.eq(item_user_id))
+            .first::<UserBan>(db)
+            .optional()
+    }
+
+    /// lifts the ban of [`item_user_id`](`ID`)
+    pub fn delete_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::user_bans::dsl::*;
+
+        diesel::delete(user_bans.filter(user_id.eq(item_user_id))).execute(db)
+    }
+
+    /// deletes the ban of [`item_user_id`](`ID`), and forgets they banned others as an admin
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::user_bans::dsl::*;
+
+        diesel::update(user_bans.filter(banned_by.eq(item_user_id)))
+            .set(banned_by.eq(None::<ID>))
+            .execute(db)?;
+
+        Self::delete_for_user(db, item_user_id)
+    }
+}
@@ -0,0 +1,101 @@
+use super::schema::*;
+use crate::diesel::*;
+
+use super::user::User;
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable, Associations,
+)]
+#[diesel(table_name=user_identities, belongs_to(User))]
+/// An account at an [`OAuthProvider`](`super::OAuthProvider`) linked to a user.
+///
+/// each provider account can only be linked to one user, and each user can link one
+/// account per provider
+pub struct UserIdentity {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub user_id: ID,
+    pub provider: String,
+    /// the user's id at the provider
+    pub provider_user_id: String,
+    /// the email of the provider account, when it shared it
+    pub email: Option<String>,
+
+    pub created_at: Utc,
+    #[cfg(not(feature = "database_sqlite"))]
+    pub updated_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=user_identities)]
+pub struct UserIdentityChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub provider: String,
+    pub provider_user_id: String,
+    pub email: Option<String>,
+}
+
+impl UserIdentity {
+    /// Create an entry in [`db`](`Connection`)'s `user_identities` table using the data in [`item`](`UserIdentityChangeset`)
+    pub fn create(db: &mut Connection, item: &UserIdentityChangeset) -> QueryResult<Self> {
+        use super::schema::user_identities::dsl::*;
+
+        insert_into(user_identities)
+            .values(item)
+            .get_result::<UserIdentity>(db)
+    }
+
+    /// the identity of the account [`item_provider_user_id`] at [`item_provider`], whoever it's linked to
+    pub fn find(
+        db: &mut Connection,
+        item_provider: &str,
+        item_provider_user_id: &str,
+    ) -> QueryResult<Self> {
+        use super::schema::user_identities::dsl::*;
+
+        user_identities
+            .filter(provider.eq(item_provider))
+            .filter(provider_user_id.eq(item_provider_user_id))
+            .first::<UserIdentity>(db)
+    }
+
+    /// the identities linked to [`item_user_id`](`ID`), oldest first
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use super::schema::user_identities::dsl::*;
+
+        user_identities
+            .filter(user_id.eq(item_user_id))
+            .order(created_at)
+            .load::<UserIdentity>(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `user_identities` table who's
+    /// primary key matches [`item_id`](`ID`)
+    pub fn delete(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::user_identities::dsl::*;
+
+        diesel::delete(user_identities.filter(id.eq(item_id))).execute(db)
+    }
+
+    /// Delete all entries in [`db`](`Connection`)'s `user_identities` table who's
+    /// 'user_id' matches [`item_user_id`](`ID`)
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::user_identities::dsl::*;
+
+        diesel::delete(user_identities.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
@@ -0,0 +1,105 @@
+use super::schema::*;
+use crate::diesel::*;
+
+use super::user::User;
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable, Associations,
+)]
+#[diesel(table_name=user_impersonations, belongs_to(User))]
+/// Audit record of an admin (the `impersonator`) acting as another user.
+///
+/// `ended_at` is set when the admin stops impersonating, it stays empty if they
+/// let the impersonation token expire instead.
+pub struct UserImpersonation {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    /// the admin who impersonated the user, `None` once their account was deleted (the record is
+    /// kept for the audit trail)
+    pub impersonator_id: Option<ID>,
+    pub user_id: ID,
+    pub reason: Option<String>,
+    pub ip_address: Option<String>,
+
+    pub started_at: Utc,
+    pub ended_at: Option<Utc>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=user_impersonations)]
+pub struct UserImpersonationChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub impersonator_id: ID,
+    pub user_id: ID,
+    pub reason: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl UserImpersonation {
+    /// Create an entry in [`db`](`Connection`)'s `user_impersonations` table using the data in [`item`](`UserImpersonationChangeset`)
+    pub fn create(db: &mut Connection, item: &UserImpersonationChangeset) -> QueryResult<Self> {
+        use super::schema::user_impersonations::dsl::*;
+
+        insert_into(user_impersonations)
+            .values(item)
+            .get_result::<UserImpersonation>(db)
+    }
+
+    /// sets `ended_at` on every open impersonation of [`item_user_id`](`ID`) by [`item_impersonator_id`](`ID`)
+    pub fn end(
+        db: &mut Connection,
+        item_impersonator_id: ID,
+        item_user_id: ID,
+    ) -> QueryResult<usize> {
+        use super::schema::user_impersonations::dsl::*;
+
+        #[cfg(not(feature = "database_sqlite"))]
+        let now = chrono::Utc::now();
+        #[cfg(feature = "database_sqlite")]
+        let now = chrono::Utc::now().naive_utc();
+
+        diesel::update(
+            user_impersonations
+                .filter(impersonator_id.eq(item_impersonator_id))
+                .filter(user_id.eq(item_user_id))
+                .filter(ended_at.is_null()),
+        )
+        .set(ended_at.eq(Some(now)))
+        .execute(db)
+    }
+
+    /// the impersonations of [`item_user_id`](`ID`), most recent first
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use super::schema::user_impersonations::dsl::*;
+
+        user_impersonations
+            .filter(user_id.eq(item_user_id))
+            .order(started_at.desc())
+            .load::<UserImpersonation>(db)
+    }
+
+    /// deletes the impersonations of [`item_user_id`](`ID`), and forgets they made the others as
+    /// an admin: those records are kept, without an impersonator
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::user_impersonations::dsl::*;
+
+        diesel::update(user_impersonations.filter(impersonator_id.eq(item_user_id)))
+            .set(impersonator_id.eq(None::<ID>))
+            .execute(db)?;
+
+        diesel::delete(user_impersonations.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
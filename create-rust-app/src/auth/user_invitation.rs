@@ -0,0 +1,138 @@
+use super::schema::*;
+use crate::diesel::*;
+
+use super::user::User;
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable, Associations,
+)]
+#[diesel(table_name=user_invitations, belongs_to(User, foreign_key=invited_by))]
+/// An invitation to register, sent by an admin (`invited_by`) to `email`.
+///
+/// The invite link carries a signed token whose `jti` must match `token`, so revoking
+/// (deleting) the invitation invalidates the link. `roles` are assigned to the user
+/// once they register, and `accepted_at` is set.
+///
+/// invitations to join an organization (see [`Membership`](`super::Membership`)) also set
+/// `organization_id` and `organization_role`.
+pub struct UserInvitation {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub email: String,
+    /// comma-separated, see [`UserInvitation::roles`]
+    pub roles: String,
+    #[serde(skip_serializing)]
+    pub token: String,
+    pub invited_by: ID,
+    pub organization_id: Option<ID>,
+    pub organization_role: Option<String>,
+
+    pub accepted_at: Option<Utc>,
+    pub expires_at: Utc,
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=user_invitations)]
+pub struct UserInvitationChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub email: String,
+    pub roles: String,
+    pub token: String,
+    pub invited_by: ID,
+    pub organization_id: Option<ID>,
+    pub organization_role: Option<String>,
+    pub expires_at: Utc,
+}
+
+impl UserInvitation {
+    /// Create an entry in [`db`](`Connection`)'s `user_invitations` table using the data in [`item`](`UserInvitationChangeset`)
+    pub fn create(db: &mut Connection, item: &UserInvitationChangeset) -> QueryResult<Self> {
+        use super::schema::user_invitations::dsl::*;
+
+        insert_into(user_invitations)
+            .values(item)
+            .get_result::<UserInvitation>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `user_invitations`
+    /// who's primary key matches [`item_id`](`ID`)
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use super::schema::user_invitations::dsl::*;
+
+        user_invitations
+            .filter(id.eq(item_id))
+            .first::<UserInvitation>(db)
+    }
+
+    /// the invitations that weren't accepted yet, most recent first
+    pub fn fetch_all_pending(db: &mut Connection) -> QueryResult<Vec<Self>> {
+        use super::schema::user_invitations::dsl::*;
+
+        user_invitations
+            .filter(accepted_at.is_null())
+            .order(created_at.desc())
+            .load::<UserInvitation>(db)
+    }
+
+    /// sets `accepted_at` on the invitation [`item_id`](`ID`)
+    pub fn accept(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::user_invitations::dsl::*;
+
+        #[cfg(not(feature = "database_sqlite"))]
+        let now = chrono::Utc::now();
+        #[cfg(feature = "database_sqlite")]
+        let now = chrono::Utc::now().naive_utc();
+
+        diesel::update(user_invitations.filter(id.eq(item_id)))
+            .set(accepted_at.eq(Some(now)))
+            .execute(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `user_invitations` table who's
+    /// primary key matches [`item_id`](`ID`)
+    pub fn delete(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::user_invitations::dsl::*;
+
+        diesel::delete(user_invitations.filter(id.eq(item_id))).execute(db)
+    }
+
+    /// deletes the invitations sent by [`item_user_id`](`ID`)
+    pub fn delete_all_by_inviter(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::user_invitations::dsl::*;
+
+        diesel::delete(user_invitations.filter(invited_by.eq(item_user_id))).execute(db)
+    }
+
+    /// the roles assigned to the invitee when they register
+    pub fn roles(&self) -> Vec<String> {
+        self.roles
+            .split(',')
+            .map(str::trim)
+            .filter(|role| !role.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        #[cfg(not(feature = "database_sqlite"))]
+        let now = chrono::Utc::now();
+        #[cfg(feature = "database_sqlite")]
+        let now = chrono::Utc::now().naive_utc();
+
+        self.expires_at < now
+    }
+}
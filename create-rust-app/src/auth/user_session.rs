@@ -33,6 +33,9 @@ pub struct UserSession {
     pub created_at: Utc,
     #[cfg(not(feature = "database_sqlite"))]
     pub updated_at: Utc,
+
+    pub ip_address: Option<String>,
+    pub last_used_at: Utc,
 }
 
 #[tsync::tsync]
@@ -47,6 +50,7 @@ pub struct UserSessionChangeset {
     pub user_id: ID,
     pub refresh_token: String,
     pub device: Option<String>,
+    pub ip_address: Option<String>,
 }
 
 impl UserSession {
@@ -69,6 +73,17 @@ impl UserSession {
             .first::<UserSession>(db)
     }
 
+    /// Read from [`db`](`Connection`), querying for every entry in the `user_sessions`
+    /// table who's `user_id` matches [`item_user_id`](`ID`), most recently used first
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use super::schema::user_sessions::dsl::*;
+
+        user_sessions
+            .filter(user_id.eq(item_user_id))
+            .order(last_used_at.desc())
+            .load::<UserSession>(db)
+    }
+
     /// Query [`db`](`Connection`)'s `user_sessions` table for an entry
     /// who's `refresh_token` matches the given `item_refresh_token`
     pub fn find_by_refresh_token(
@@ -127,6 +142,16 @@ impl UserSession {
             .get_result(db)
     }
 
+    /// Set `last_used_at` to the current time for the entry in [`db`](`Connection`)'s
+    /// `user_sessions` table who's primary key matches [`item_id`](`ID`)
+    pub fn touch(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::user_sessions::dsl::*;
+
+        diesel::update(user_sessions.filter(id.eq(item_id)))
+            .set(last_used_at.eq(diesel::dsl::now))
+            .execute(db)
+    }
+
     /// Delete the entry in [`db`](`Connection`)'s `user_sessions` table who's
     /// primary key matches [`item_id`](`ID`)
     pub fn delete(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
@@ -142,4 +167,21 @@ impl UserSession {
 
         diesel::delete(user_sessions.filter(user_id.eq(item_user_id))).execute(db)
     }
+
+    /// Delete all entries in [`db`](`Connection`)'s `user_sessions` table who's
+    /// 'user_id' matches [`item_user_id`](`ID`), except the one who's primary key matches [`keep_id`](`ID`)
+    pub fn delete_all_for_user_except(
+        db: &mut Connection,
+        item_user_id: ID,
+        keep_id: ID,
+    ) -> QueryResult<usize> {
+        use super::schema::user_sessions::dsl::*;
+
+        diesel::delete(
+            user_sessions
+                .filter(user_id.eq(item_user_id))
+                .filter(id.ne(keep_id)),
+        )
+        .execute(db)
+    }
 }
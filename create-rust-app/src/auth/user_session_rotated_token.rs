@@ -0,0 +1,84 @@
+use super::schema::*;
+use crate::diesel::*;
+
+use super::user_session::UserSession;
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[tsync::tsync]
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+)]
+#[diesel(table_name=user_session_rotated_tokens, primary_key(refresh_token), belongs_to(UserSession, foreign_key=session_id))]
+/// A refresh token that was already exchanged for a new one.
+///
+/// Each `user_sessions` entry is a session "family": every refresh replaces its token,
+/// and the replaced token is recorded here. If a rotated token is ever presented again,
+/// it has most likely been stolen, and the whole family is revoked.
+pub struct UserSessionRotatedToken {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub refresh_token: String,
+    pub session_id: ID,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=user_session_rotated_tokens)]
+pub struct UserSessionRotatedTokenChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub refresh_token: String,
+    pub session_id: ID,
+}
+
+impl UserSessionRotatedToken {
+    /// Create an entry in [`db`](`Connection`)'s `user_session_rotated_tokens` table using the data in [`item`](`UserSessionRotatedTokenChangeset`)
+    pub fn create(
+        db: &mut Connection,
+        item: &UserSessionRotatedTokenChangeset,
+    ) -> QueryResult<Self> {
+        use super::schema::user_session_rotated_tokens::dsl::*;
+
+        insert_into(user_session_rotated_tokens)
+            .values(item)
+            .get_result::<UserSessionRotatedToken>(db)
+    }
+
+    /// Query [`db`](`Connection`)'s `user_session_rotated_tokens` table for an entry
+    /// who's `refresh_token` matches the given `item_refresh_token`
+    pub fn find_by_refresh_token(
+        db: &mut Connection,
+        item_refresh_token: &str,
+    ) -> QueryResult<Self> {
+        use super::schema::user_session_rotated_tokens::dsl::*;
+
+        user_session_rotated_tokens
+            .filter(refresh_token.eq(item_refresh_token))
+            .first::<UserSessionRotatedToken>(db)
+    }
+
+    /// Delete all entries in [`db`](`Connection`)'s `user_session_rotated_tokens` table who's
+    /// `session_id` matches [`item_session_id`](`ID`)
+    pub fn delete_all_for_session(db: &mut Connection, item_session_id: ID) -> QueryResult<usize> {
+        use super::schema::user_session_rotated_tokens::dsl::*;
+
+        diesel::delete(user_session_rotated_tokens.filter(session_id.eq(item_session_id)))
+            .execute(db)
+    }
+}
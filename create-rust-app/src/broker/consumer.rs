@@ -0,0 +1,218 @@
+//! Runs handlers on the messages of a [`Broker`], retrying them and dead-lettering the messages
+//! they keep failing on.
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
+
+use super::{Broker, BrokerFuture, Delivery, Message};
+
+/// appended to a topic to get its dead letter topic, see [`ConsumerConfig::dead_letter`]
+pub const DEAD_LETTER_SUFFIX: &str = ".dlq";
+
+#[derive(Debug, Clone)]
+/// how consumers handle their messages
+pub struct ConsumerConfig {
+    /// how many times a message is handled before it's dead-lettered (default: 5)
+    pub max_attempts: i32,
+    /// how long to wait before the first retry, doubled after every failure (default: 1 second)
+    pub base_delay: Duration,
+    /// the longest wait between two retries (default: 1 minute)
+    pub max_delay: Duration,
+    /// whether the messages failing `max_attempts` times are published to the topic's dead
+    /// letter topic (`<topic>.dlq`, with `x-error`, `x-attempts` and `x-group` headers) rather
+    /// than dropped (default: true)
+    pub dead_letter: bool,
+}
+
+impl Default for ConsumerConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            dead_letter: true,
+        }
+    }
+}
+
+impl ConsumerConfig {
+    /// how long to wait before retrying a message which failed `attempts` times
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let exponent = attempts.saturating_sub(1).clamp(0, 31) as u32;
+
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_delay)
+    }
+}
+
+type Handler = Arc<dyn Fn(Message) -> BrokerFuture<'static, ()> + Send + Sync>;
+
+struct Consumer {
+    topic: String,
+    group: String,
+    config: ConsumerConfig,
+    handler: Handler,
+}
+
+/// the consumers of a worker, see the [module documentation](`super`)
+pub struct Consumers {
+    broker: Broker,
+    config: ConsumerConfig,
+    consumers: Vec<Consumer>,
+}
+
+impl Consumers {
+    pub fn new(broker: Broker) -> Self {
+        Self {
+            broker,
+            config: ConsumerConfig::default(),
+            consumers: vec![],
+        }
+    }
+
+    /// the config of the consumers registered after this call
+    pub fn config(mut self, config: ConsumerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// handles the json messages of `topic` with `handler`, as a consumer of `group`
+    ///
+    /// the message is retried when `handler` returns an error, including when it can't be read
+    pub fn consume<T, F>(
+        self,
+        topic: &str,
+        group: &str,
+        handler: impl Fn(T) -> F + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+
+        self.consume_messages(topic, group, move |message| {
+            let handler = handler.clone();
+
+            async move { handler(message.decode::<T>()?).await }
+        })
+    }
+
+    /// [`Consumers::consume`], `handler` getting the raw [`Message`]s
+    pub fn consume_messages<F>(
+        mut self,
+        topic: &str,
+        group: &str,
+        handler: impl Fn(Message) -> F + Send + Sync + 'static,
+    ) -> Self
+    where
+        F: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.consumers.push(Consumer {
+            topic: topic.to_string(),
+            group: group.to_string(),
+            config: self.config.clone(),
+            handler: Arc::new(move |message| Box::pin(handler(message))),
+        });
+        self
+    }
+
+    /// subscribes the consumers and handles their messages, one at a time for each consumer,
+    /// returns when a subscription fails or ends
+    pub async fn run(self) -> Result<(), String> {
+        let broker = self.broker;
+
+        futures_util::future::try_join_all(self.consumers.into_iter().map(|consumer| {
+            let broker = broker.clone();
+
+            async move {
+                let mut deliveries = broker
+                    .provider()
+                    .subscribe(&consumer.topic, &consumer.group)
+                    .await
+                    .map_err(|err| {
+                        format!(
+                            "Could not subscribe to '{}' with {} (error: '{err}')",
+                            consumer.topic,
+                            broker.provider().name()
+                        )
+                    })?;
+                println!(
+                    "Consuming '{}' (group: '{}')",
+                    consumer.topic, consumer.group
+                );
+
+                while let Some(delivery) = deliveries.next().await {
+                    match delivery {
+                        Ok(delivery) => {
+                            if let Err(err) = handle(&broker, &consumer, delivery).await {
+                                println!("{err}");
+                            }
+                        }
+                        Err(err) => println!(
+                            "Could not receive a message of '{}' (error: '{err}')",
+                            consumer.topic
+                        ),
+                    }
+                }
+
+                Err::<(), _>(format!("The subscription to '{}' ended", consumer.topic))
+            }
+        }))
+        .await
+        .map(|_| ())
+    }
+}
+
+/// runs the consumer's handler on `delivery` until it succeeds or `max_attempts` is reached, then
+/// acknowledges it (a message which couldn't be dead-lettered isn't, so it's delivered again)
+async fn handle(broker: &Broker, consumer: &Consumer, delivery: Delivery) -> Result<(), String> {
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+
+        let err = match (consumer.handler)(delivery.message.clone()).await {
+            Ok(()) => break,
+            Err(err) => err,
+        };
+
+        if attempts < consumer.config.max_attempts {
+            let delay = consumer.config.backoff(attempts);
+            println!(
+                "A message of '{}' failed (group: '{}', attempt: {attempts}, error: '{err}'), retrying in {delay:?}",
+                consumer.topic, consumer.group
+            );
+            tokio::time::sleep(delay).await;
+            continue;
+        }
+
+        println!(
+            "A message of '{}' failed {attempts} times (group: '{}', error: '{err}'), giving up",
+            consumer.topic, consumer.group
+        );
+        if consumer.config.dead_letter {
+            let mut dead_letter = delivery
+                .message
+                .clone()
+                .with_header("x-error", err)
+                .with_header("x-attempts", attempts.to_string())
+                .with_header("x-group", consumer.group.clone());
+            dead_letter.topic = format!("{}{DEAD_LETTER_SUFFIX}", consumer.topic);
+
+            broker.publish_message(&dead_letter).await?;
+        }
+        break;
+    }
+
+    delivery.ack().await.map_err(|err| {
+        format!(
+            "Could not acknowledge a message of '{}' (error: '{err}')",
+            consumer.topic
+        )
+    })
+}
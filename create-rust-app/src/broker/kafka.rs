@@ -0,0 +1,160 @@
+//! Kafka (`plugin_broker_kafka` feature), with `BROKER_URL=kafka://` followed by the bootstrap
+//! servers (ex: `kafka://127.0.0.1:9092,127.0.0.1:9093`):
+//!
+//! ```rust,ignore
+//! use create_rust_app::broker::{Broker, KafkaProvider};
+//!
+//! let broker = Broker::with_provider(KafkaProvider::new("127.0.0.1:9092".to_string()));
+//! ```
+//!
+//! Groups are consumer groups, which start from the earliest message of a topic the first time
+//! they consume it. Offsets are committed once messages are acknowledged.
+use std::sync::Arc;
+use std::time::Duration;
+
+use once_cell::sync::OnceCell;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::{Header, Headers, Message as _, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::{Offset, TopicPartitionList};
+
+use super::{BrokerFuture, BrokerProvider, Delivery, DeliveryStream, Message};
+
+/// how long publishing waits for a message to be queued by the producer
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone)]
+/// a Kafka cluster, see the [module documentation](`self`)
+pub struct KafkaProvider {
+    /// created on first use
+    producer: Arc<OnceCell<Result<FutureProducer, String>>>,
+    servers: String,
+}
+
+impl KafkaProvider {
+    /// `servers` is a comma separated list of `host:port`
+    pub fn new(servers: String) -> KafkaProvider {
+        KafkaProvider {
+            producer: Default::default(),
+            servers,
+        }
+    }
+
+    fn producer(&self) -> Result<&FutureProducer, String> {
+        self.producer
+            .get_or_init(|| {
+                ClientConfig::new()
+                    .set("bootstrap.servers", &self.servers)
+                    .create::<FutureProducer>()
+                    .map_err(|err| err.to_string())
+            })
+            .as_ref()
+            .map_err(|error| {
+                format!(
+                    "Kafka is not available; did you set the right BROKER_URL? (error: '{error}')"
+                )
+            })
+    }
+}
+
+impl BrokerProvider for KafkaProvider {
+    fn name(&self) -> &'static str {
+        "kafka"
+    }
+
+    fn publish<'a>(&'a self, message: &'a Message) -> BrokerFuture<'a, ()> {
+        Box::pin(async move {
+            let producer = self.producer()?;
+
+            let mut headers = OwnedHeaders::new();
+            for (name, value) in &message.headers {
+                headers = headers.insert(Header {
+                    key: name,
+                    value: Some(value.as_str()),
+                });
+            }
+
+            let mut record: FutureRecord<str, Vec<u8>> = FutureRecord::to(&message.topic)
+                .payload(&message.payload)
+                .headers(headers);
+            if let Some(key) = &message.key {
+                record = record.key(key.as_str());
+            }
+
+            producer
+                .send(record, QUEUE_TIMEOUT)
+                .await
+                .map(|_| ())
+                .map_err(|(err, _)| err.to_string())
+        })
+    }
+
+    fn subscribe<'a>(&'a self, topic: &'a str, group: &'a str) -> BrokerFuture<'a, DeliveryStream> {
+        Box::pin(async move {
+            let consumer = ClientConfig::new()
+                .set("bootstrap.servers", &self.servers)
+                .set("group.id", group)
+                .set("enable.auto.commit", "false")
+                .set("auto.offset.reset", "earliest")
+                .create::<StreamConsumer>()
+                .map_err(|err| err.to_string())?;
+            consumer
+                .subscribe(&[topic])
+                .map_err(|err| err.to_string())?;
+
+            let deliveries =
+                futures_util::stream::unfold(Arc::new(consumer), |consumer| async move {
+                    let delivery = match consumer.recv().await {
+                        Ok(received) => {
+                            let headers = received
+                                .headers()
+                                .map(|headers| {
+                                    headers
+                                        .iter()
+                                        .filter_map(|header| {
+                                            Some((
+                                                header.key.to_string(),
+                                                String::from_utf8_lossy(header.value?).to_string(),
+                                            ))
+                                        })
+                                        .collect()
+                                })
+                                .unwrap_or_default();
+                            let message = Message {
+                                topic: received.topic().to_string(),
+                                key: received
+                                    .key()
+                                    .map(|key| String::from_utf8_lossy(key).to_string()),
+                                headers,
+                                payload: received.payload().map(<[u8]>::to_vec).unwrap_or_default(),
+                            };
+
+                            // the committed offset is the next message to consume
+                            let mut offsets = TopicPartitionList::new();
+                            let offset = offsets.add_partition_offset(
+                                received.topic(),
+                                received.partition(),
+                                Offset::Offset(received.offset() + 1),
+                            );
+                            let acker = consumer.clone();
+
+                            Ok(Delivery::new(message, move || {
+                                Box::pin(async move {
+                                    offset.map_err(|err| err.to_string())?;
+                                    acker
+                                        .commit(&offsets, CommitMode::Async)
+                                        .map_err(|err| err.to_string())
+                                })
+                            }))
+                        }
+                        Err(err) => Err(err.to_string()),
+                    };
+
+                    Some((delivery, consumer))
+                });
+
+            Ok(Box::pin(deliveries) as DeliveryStream)
+        })
+    }
+}
@@ -0,0 +1,80 @@
+//! Passes messages in memory, for development and tests:
+//!
+//! ```rust,ignore
+//! use create_rust_app::broker::{Broker, Consumers, MemoryBroker};
+//!
+//! let broker = Broker::with_provider(MemoryBroker::default());
+//! tokio::spawn(Consumers::new(broker.clone()).consume("orders.placed", "billing", charge).run());
+//!
+//! broker.publish("orders.placed", &OrderPlaced { order_id: 1 }).await?;
+//! ```
+//!
+//! Messages published to a topic no group subscribed to are dropped.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc;
+
+use super::{BrokerFuture, BrokerProvider, Delivery, DeliveryStream, Message};
+
+#[derive(Clone)]
+/// the messages of a group, shared by its subscribers
+struct Group {
+    sender: mpsc::UnboundedSender<Message>,
+    receiver: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<Message>>>,
+}
+
+#[derive(Clone, Default)]
+/// the groups of each topic, clones share the same topics
+pub struct MemoryBroker {
+    topics: Arc<Mutex<HashMap<String, HashMap<String, Group>>>>,
+}
+
+impl BrokerProvider for MemoryBroker {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn publish<'a>(&'a self, message: &'a Message) -> BrokerFuture<'a, ()> {
+        Box::pin(async move {
+            if let Some(groups) = self.topics.lock().unwrap().get(&message.topic) {
+                for group in groups.values() {
+                    // the group's receiver is kept with its sender, so it can't be closed
+                    let _ = group.sender.send(message.clone());
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    fn subscribe<'a>(&'a self, topic: &'a str, group: &'a str) -> BrokerFuture<'a, DeliveryStream> {
+        Box::pin(async move {
+            let receiver = self
+                .topics
+                .lock()
+                .unwrap()
+                .entry(topic.to_string())
+                .or_default()
+                .entry(group.to_string())
+                .or_insert_with(|| {
+                    let (sender, receiver) = mpsc::unbounded_channel();
+
+                    Group {
+                        sender,
+                        receiver: Arc::new(tokio::sync::Mutex::new(receiver)),
+                    }
+                })
+                .receiver
+                .clone();
+
+            let deliveries = futures_util::stream::unfold(receiver, |receiver| async move {
+                let message = receiver.lock().await.recv().await?;
+
+                Some((Ok(Delivery::unacknowledged(message)), receiver))
+            });
+
+            Ok(Box::pin(deliveries) as DeliveryStream)
+        })
+    }
+}
@@ -0,0 +1,243 @@
+//! Message brokers (`plugin_broker` feature): publishes messages to, and consumes them from, NATS
+//! (`plugin_broker_nats` feature), RabbitMQ (`plugin_broker_rabbitmq` feature) or Kafka
+//! (`plugin_broker_kafka` feature), picked from the `BROKER_URL` scheme.
+//!
+//! ```rust,ignore
+//! use create_rust_app::broker::{Broker, ConsumerConfig, Consumers};
+//!
+//! // in a handler
+//! let broker = Broker::new();
+//! broker.publish("orders.placed", &OrderPlaced { order_id: order.id }).await?;
+//!
+//! // in a worker binary: each message is handled by one consumer of the "billing" group, it's
+//! // retried when the handler fails, and sent to `orders.placed.dlq` when it keeps failing
+//! Consumers::new(broker)
+//!     .config(ConsumerConfig { max_attempts: 3, ..Default::default() })
+//!     .consume("orders.placed", "billing", |event: OrderPlaced| async move {
+//!         charge(event.order_id).await
+//!     })
+//!     .run()
+//!     .await?;
+//! ```
+//!
+//! Topics are NATS subjects, RabbitMQ fanout exchanges (each group consuming its
+//! `<topic>.<group>` queue) or Kafka topics. Without `BROKER_URL`, messages go through a
+//! [`MemoryBroker`], which only reaches the consumers of the same process.
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures_util::Stream;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+mod consumer;
+pub use consumer::{ConsumerConfig, Consumers, DEAD_LETTER_SUFFIX};
+
+mod memory;
+pub use memory::MemoryBroker;
+
+#[cfg(feature = "plugin_broker_kafka")]
+mod kafka;
+#[cfg(feature = "plugin_broker_kafka")]
+pub use kafka::KafkaProvider;
+
+#[cfg(feature = "plugin_broker_nats")]
+mod nats;
+#[cfg(feature = "plugin_broker_nats")]
+pub use nats::NatsProvider;
+
+#[cfg(feature = "plugin_broker_rabbitmq")]
+mod rabbitmq;
+#[cfg(feature = "plugin_broker_rabbitmq")]
+pub use rabbitmq::RabbitMqProvider;
+
+/// what the [`BrokerProvider`]s' methods return, errors are human readable messages
+pub type BrokerFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// the messages a subscription receives, see [`BrokerProvider::subscribe`]
+pub type DeliveryStream = Pin<Box<dyn Stream<Item = Result<Delivery, String>> + Send>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// a message published to, or received from, a broker
+pub struct Message {
+    pub topic: String,
+    /// Kafka's partition key (messages with the same key are consumed in order), RabbitMQ's
+    /// routing key, ignored by NATS
+    pub key: Option<String>,
+    pub headers: HashMap<String, String>,
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    pub fn new(topic: impl Into<String>, payload: Vec<u8>) -> Self {
+        Self {
+            topic: topic.into(),
+            key: None,
+            headers: HashMap::new(),
+            payload,
+        }
+    }
+
+    /// a message of `payload`, serialized as json
+    pub fn json<T: Serialize>(topic: impl Into<String>, payload: &T) -> Result<Self, String> {
+        let payload = serde_json::to_vec(payload).map_err(|err| err.to_string())?;
+
+        Ok(Self::new(topic, payload))
+    }
+
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// the payload, deserialized from json
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, String> {
+        serde_json::from_slice(&self.payload).map_err(|err| {
+            format!(
+                "Could not read the message of '{}' (error: '{err}')",
+                self.topic
+            )
+        })
+    }
+}
+
+type Ack = Box<dyn FnOnce() -> BrokerFuture<'static, ()> + Send>;
+
+/// a received [`Message`], the broker delivers it again (to the same group) when it isn't
+/// [acknowledged](`Delivery::ack`)
+pub struct Delivery {
+    pub message: Message,
+    ack: Option<Ack>,
+}
+
+impl Delivery {
+    /// `ack` tells the broker the message was handled
+    pub fn new(
+        message: Message,
+        ack: impl FnOnce() -> BrokerFuture<'static, ()> + Send + 'static,
+    ) -> Self {
+        Self {
+            message,
+            ack: Some(Box::new(ack)),
+        }
+    }
+
+    /// a message the broker doesn't expect an acknowledgement of (ex: NATS' core subjects)
+    pub fn unacknowledged(message: Message) -> Self {
+        Self { message, ack: None }
+    }
+
+    pub async fn ack(self) -> Result<(), String> {
+        match self.ack {
+            Some(ack) => ack().await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// where [`Broker`] publishes and consumes messages
+///
+/// ```rust,ignore
+/// use create_rust_app::broker::{Broker, BrokerFuture, BrokerProvider, DeliveryStream, Message};
+///
+/// struct Sqs { /* ... */ }
+///
+/// impl BrokerProvider for Sqs {
+///     fn name(&self) -> &'static str {
+///         "sqs"
+///     }
+///
+///     fn publish<'a>(&'a self, message: &'a Message) -> BrokerFuture<'a, ()> {
+///         Box::pin(async move { self.send_message(message).await })
+///     }
+///     // ...
+/// }
+///
+/// let broker = Broker::with_provider(Sqs::new());
+/// ```
+pub trait BrokerProvider: Send + Sync {
+    /// identifies the provider in logs
+    fn name(&self) -> &'static str;
+
+    fn publish<'a>(&'a self, message: &'a Message) -> BrokerFuture<'a, ()>;
+
+    /// the messages published to `topic`, each one is delivered to a single subscriber of `group`
+    /// (every group gets its own copy)
+    fn subscribe<'a>(&'a self, topic: &'a str, group: &'a str) -> BrokerFuture<'a, DeliveryStream>;
+}
+
+#[derive(Clone)]
+/// publishes messages, see the [module documentation](`self`), clones share the same connection
+pub struct Broker {
+    provider: Arc<dyn BrokerProvider>,
+}
+
+impl Broker {
+    pub fn with_provider(provider: impl BrokerProvider + 'static) -> Broker {
+        Broker {
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// uses the broker in `BROKER_URL`: `nats://` (`plugin_broker_nats` feature), `amqp://` or
+    /// `amqps://` (`plugin_broker_rabbitmq` feature), `kafka://` followed by the bootstrap servers
+    /// (`plugin_broker_kafka` feature), and a [`MemoryBroker`] when it isn't set
+    ///
+    /// the connection is opened on first use
+    pub fn new() -> Broker {
+        let url = std::env::var("BROKER_URL").unwrap_or_default();
+
+        #[cfg(feature = "plugin_broker_nats")]
+        if url.starts_with("nats://") || url.starts_with("tls://") {
+            return Broker::with_provider(NatsProvider::new(url));
+        }
+
+        #[cfg(feature = "plugin_broker_rabbitmq")]
+        if url.starts_with("amqp://") || url.starts_with("amqps://") {
+            return Broker::with_provider(RabbitMqProvider::new(url));
+        }
+
+        #[cfg(feature = "plugin_broker_kafka")]
+        if let Some(servers) = url.strip_prefix("kafka://") {
+            return Broker::with_provider(KafkaProvider::new(servers.to_string()));
+        }
+
+        if !url.is_empty() {
+            println!("BROKER_URL ('{url}') isn't supported: did you enable create-rust-app's plugin_broker_nats, plugin_broker_rabbitmq or plugin_broker_kafka feature? Messages only go through this process.");
+        }
+
+        Broker::with_provider(MemoryBroker::default())
+    }
+
+    pub fn provider(&self) -> &dyn BrokerProvider {
+        self.provider.as_ref()
+    }
+
+    /// publishes `payload` to `topic`, serialized as json
+    pub async fn publish<T: Serialize>(&self, topic: &str, payload: &T) -> Result<(), String> {
+        self.publish_message(&Message::json(topic, payload)?).await
+    }
+
+    pub async fn publish_message(&self, message: &Message) -> Result<(), String> {
+        self.provider.publish(message).await.map_err(|err| {
+            format!(
+                "Could not publish to '{}' with {} (error: '{err}')",
+                message.topic,
+                self.provider.name()
+            )
+        })
+    }
+}
+
+impl Default for Broker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
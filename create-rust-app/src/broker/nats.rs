@@ -0,0 +1,111 @@
+//! NATS (`plugin_broker_nats` feature), with `BROKER_URL=nats://...`:
+//!
+//! ```rust,ignore
+//! use create_rust_app::broker::{Broker, NatsProvider};
+//!
+//! let broker = Broker::with_provider(NatsProvider::new("nats://127.0.0.1:4222".to_string()));
+//! ```
+//!
+//! Groups are queue groups. Core NATS delivers messages at most once: they aren't acknowledged,
+//! and the ones published while no subscriber of a group is connected are lost (implement a
+//! [`BrokerProvider`] on JetStream for persistence).
+use std::sync::Arc;
+
+use async_nats::{Client, HeaderMap};
+use futures_util::StreamExt;
+use tokio::sync::OnceCell;
+
+use super::{BrokerFuture, BrokerProvider, Delivery, DeliveryStream, Message};
+
+#[derive(Clone)]
+/// a NATS server, see the [module documentation](`self`)
+pub struct NatsProvider {
+    /// connected on first use
+    client: Arc<OnceCell<Result<Client, String>>>,
+    url: String,
+}
+
+impl NatsProvider {
+    pub fn new(url: String) -> NatsProvider {
+        NatsProvider {
+            client: Default::default(),
+            url,
+        }
+    }
+
+    async fn client(&self) -> Result<&Client, String> {
+        self.client
+            .get_or_init(|| async {
+                async_nats::connect(&self.url)
+                    .await
+                    .map_err(|err| err.to_string())
+            })
+            .await
+            .as_ref()
+            .map_err(|error| {
+                format!(
+                    "NATS is not available; did you set the right BROKER_URL? (error: '{error}')"
+                )
+            })
+    }
+}
+
+impl BrokerProvider for NatsProvider {
+    fn name(&self) -> &'static str {
+        "nats"
+    }
+
+    fn publish<'a>(&'a self, message: &'a Message) -> BrokerFuture<'a, ()> {
+        Box::pin(async move {
+            let mut headers = HeaderMap::new();
+            for (name, value) in &message.headers {
+                headers.insert(name.as_str(), value.as_str());
+            }
+
+            self.client()
+                .await?
+                .publish_with_headers(
+                    message.topic.clone(),
+                    headers,
+                    message.payload.clone().into(),
+                )
+                .await
+                .map_err(|err| err.to_string())
+        })
+    }
+
+    fn subscribe<'a>(&'a self, topic: &'a str, group: &'a str) -> BrokerFuture<'a, DeliveryStream> {
+        Box::pin(async move {
+            let subscriber = self
+                .client()
+                .await?
+                .queue_subscribe(topic.to_string(), group.to_string())
+                .await
+                .map_err(|err| err.to_string())?;
+
+            let deliveries = subscriber.map(|message| {
+                let headers = message
+                    .headers
+                    .as_ref()
+                    .map(|headers| {
+                        headers
+                            .iter()
+                            .filter_map(|(name, values)| {
+                                Some((name.to_string(), values.first()?.as_str().to_string()))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                Ok(Delivery::unacknowledged(Message {
+                    topic: message.subject.to_string(),
+                    key: None,
+                    headers,
+                    payload: message.payload.to_vec(),
+                }))
+            });
+
+            Ok(Box::pin(deliveries) as DeliveryStream)
+        })
+    }
+}
@@ -0,0 +1,210 @@
+//! RabbitMQ (`plugin_broker_rabbitmq` feature), with `BROKER_URL=amqp://...`:
+//!
+//! ```rust,ignore
+//! use create_rust_app::broker::{Broker, RabbitMqProvider};
+//!
+//! let broker = Broker::with_provider(RabbitMqProvider::new("amqp://127.0.0.1:5672".to_string()));
+//! ```
+//!
+//! Topics are durable fanout exchanges, and each group consumes a durable queue bound to its
+//! topic's exchange (`<topic>.<group>`), declared when it subscribes: messages published before a
+//! group's first subscription don't reach it. Messages are persistent, and delivered again when
+//! they weren't acknowledged.
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+use lapin::options::{
+    BasicAckOptions, BasicConsumeOptions, BasicPublishOptions, ConfirmSelectOptions,
+    ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions,
+};
+use lapin::types::{AMQPValue, FieldTable, LongString, ShortString};
+use lapin::{BasicProperties, Channel, Connection, ConnectionProperties, ExchangeKind};
+use tokio::sync::OnceCell;
+
+use super::{BrokerFuture, BrokerProvider, Delivery, DeliveryStream, Message};
+
+/// `delivery_mode` of the messages which survive a restart of the broker
+const PERSISTENT: u8 = 2;
+
+#[derive(Clone)]
+/// a RabbitMQ server, see the [module documentation](`self`)
+pub struct RabbitMqProvider {
+    /// connected on first use, the connection is kept with its channel
+    channel: Arc<OnceCell<Result<(Connection, Channel), String>>>,
+    url: String,
+}
+
+impl RabbitMqProvider {
+    pub fn new(url: String) -> RabbitMqProvider {
+        RabbitMqProvider {
+            channel: Default::default(),
+            url,
+        }
+    }
+
+    async fn channel(&self) -> Result<&Channel, String> {
+        self.channel
+            .get_or_init(|| self.init())
+            .await
+            .as_ref()
+            .map(|(_, channel)| channel)
+            .map_err(|error| {
+                format!("RabbitMQ is not available; did you set the right BROKER_URL? (error: '{error}')")
+            })
+    }
+
+    async fn init(&self) -> Result<(Connection, Channel), String> {
+        let connection = Connection::connect(&self.url, ConnectionProperties::default())
+            .await
+            .map_err(|err| err.to_string())?;
+        let channel = connection
+            .create_channel()
+            .await
+            .map_err(|err| err.to_string())?;
+        // publishing waits for the broker to have the message
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await
+            .map_err(|err| err.to_string())?;
+
+        Ok((connection, channel))
+    }
+
+    async fn declare_topic(channel: &Channel, topic: &str) -> Result<(), String> {
+        channel
+            .exchange_declare(
+                topic,
+                ExchangeKind::Fanout,
+                ExchangeDeclareOptions {
+                    durable: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await
+            .map_err(|err| err.to_string())
+    }
+}
+
+impl BrokerProvider for RabbitMqProvider {
+    fn name(&self) -> &'static str {
+        "rabbitmq"
+    }
+
+    fn publish<'a>(&'a self, message: &'a Message) -> BrokerFuture<'a, ()> {
+        Box::pin(async move {
+            let channel = self.channel().await?;
+            Self::declare_topic(channel, &message.topic).await?;
+
+            let mut headers = FieldTable::default();
+            for (name, value) in &message.headers {
+                headers.insert(
+                    ShortString::from(name.clone()),
+                    AMQPValue::LongString(LongString::from(value.clone())),
+                );
+            }
+
+            channel
+                .basic_publish(
+                    &message.topic,
+                    message.key.as_deref().unwrap_or_default(),
+                    BasicPublishOptions::default(),
+                    &message.payload,
+                    BasicProperties::default()
+                        .with_headers(headers)
+                        .with_delivery_mode(PERSISTENT),
+                )
+                .await
+                .map_err(|err| err.to_string())?
+                .await
+                .map_err(|err| err.to_string())?;
+
+            Ok(())
+        })
+    }
+
+    fn subscribe<'a>(&'a self, topic: &'a str, group: &'a str) -> BrokerFuture<'a, DeliveryStream> {
+        Box::pin(async move {
+            let channel = self.channel().await?;
+            Self::declare_topic(channel, topic).await?;
+
+            let queue = format!("{topic}.{group}");
+            channel
+                .queue_declare(
+                    &queue,
+                    QueueDeclareOptions {
+                        durable: true,
+                        ..Default::default()
+                    },
+                    FieldTable::default(),
+                )
+                .await
+                .map_err(|err| err.to_string())?;
+            channel
+                .queue_bind(
+                    &queue,
+                    topic,
+                    "",
+                    QueueBindOptions::default(),
+                    FieldTable::default(),
+                )
+                .await
+                .map_err(|err| err.to_string())?;
+
+            let consumer = channel
+                .basic_consume(
+                    &queue,
+                    "",
+                    BasicConsumeOptions::default(),
+                    FieldTable::default(),
+                )
+                .await
+                .map_err(|err| err.to_string())?;
+
+            let topic = topic.to_string();
+            let deliveries = consumer.map(move |delivery| {
+                let delivery = delivery.map_err(|err| err.to_string())?;
+
+                let headers = delivery
+                    .properties
+                    .headers()
+                    .as_ref()
+                    .map(|headers| {
+                        headers
+                            .inner()
+                            .iter()
+                            .filter_map(|(name, value)| match value {
+                                AMQPValue::LongString(value) => Some((
+                                    name.to_string(),
+                                    String::from_utf8_lossy(value.as_bytes()).to_string(),
+                                )),
+                                _ => None,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let key = Some(delivery.routing_key.to_string()).filter(|key| !key.is_empty());
+                let acker = delivery.acker.clone();
+
+                Ok(Delivery::new(
+                    Message {
+                        topic: topic.clone(),
+                        key,
+                        headers,
+                        payload: delivery.data,
+                    },
+                    move || {
+                        Box::pin(async move {
+                            acker
+                                .ack(BasicAckOptions::default())
+                                .await
+                                .map_err(|err| err.to_string())
+                        })
+                    },
+                ))
+            });
+
+            Ok(Box::pin(deliveries) as DeliveryStream)
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+/// memoizes values for the duration of a request: every extraction of it in the same request
+/// (ex: by the handler and by extractors or helpers it calls) shares the values
+///
+/// ```rust,ignore
+/// let user = memo.memoize("current_user", || User::read(&mut db, auth.user_id));
+/// ```
+///
+/// with poem, the values are only shared if the app is wrapped with
+/// `.before(create_rust_app::cache::with_request_cache)`, every extraction memoizes its own
+/// otherwise
+pub struct RequestCache {
+    values: Arc<Mutex<HashMap<String, Box<dyn Any + Send>>>>,
+}
+
+impl std::fmt::Debug for RequestCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestCache").finish()
+    }
+}
+
+impl RequestCache {
+    /// the value memoized under `key`, or the one `compute` returns, which is memoized
+    ///
+    /// NOTE: `compute` runs without holding the lock, so it can use the request cache too
+    pub fn memoize<T: Clone + Send + 'static>(&self, key: &str, compute: impl FnOnce() -> T) -> T {
+        if let Some(value) = self.get::<T>(key) {
+            return value;
+        }
+
+        let value = compute();
+        self.insert(key, value.clone());
+
+        value
+    }
+
+    /// like [`memoize`](`Self::memoize`), errors aren't memoized
+    pub fn try_memoize<T: Clone + Send + 'static, E>(
+        &self,
+        key: &str,
+        compute: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        if let Some(value) = self.get::<T>(key) {
+            return Ok(value);
+        }
+
+        let value = compute()?;
+        self.insert(key, value.clone());
+
+        Ok(value)
+    }
+
+    /// the value memoized under `key`, if it's a `T`
+    pub fn get<T: Clone + 'static>(&self, key: &str) -> Option<T> {
+        let values = self.values.lock().ok()?;
+
+        values.get(key)?.downcast_ref::<T>().cloned()
+    }
+
+    pub fn insert<T: Send + 'static>(&self, key: &str, value: T) {
+        if let Ok(mut values) = self.values.lock() {
+            values.insert(key.to_string(), Box::new(value));
+        }
+    }
+
+    pub fn remove(&self, key: &str) {
+        if let Ok(mut values) = self.values.lock() {
+            values.remove(key);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::convert::Infallible;
+
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpRequest};
+use futures::future::{ready, Ready};
+
+use super::RequestCache;
+
+impl FromRequest for RequestCache {
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = Infallible;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let mut extensions = req.extensions_mut();
+        let cache = extensions
+            .get::<RequestCache>()
+            .cloned()
+            .unwrap_or_else(|| {
+                let cache = RequestCache::default();
+                extensions.insert(cache.clone());
+                cache
+            });
+
+        ready(Ok(cache))
+    }
+}
@@ -0,0 +1,22 @@
+use poem::{async_trait, FromRequest, Request, RequestBody, Result};
+
+use super::RequestCache;
+
+/// gives the request a [`RequestCache`], so its extractions share it:
+/// `app.before(create_rust_app::cache::with_request_cache)`
+pub async fn with_request_cache(mut req: Request) -> Result<Request> {
+    req.extensions_mut().insert(RequestCache::default());
+
+    Ok(req)
+}
+
+#[async_trait]
+impl<'a> FromRequest<'a> for RequestCache {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req
+            .extensions()
+            .get::<RequestCache>()
+            .cloned()
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,177 @@
+//! Caching (`plugin_cache` feature): a typed [`Cache`] for values that are expensive to compute
+//! or read (ex: the responses of read-heavy endpoints), kept for a TTL.
+//!
+//! Values are stored as JSON, in Redis when the `plugin_redis` feature is enabled and `REDIS_URL`
+//! is set (so they're shared by every instance of the app), in the process' memory otherwise.
+//! [`setup`](`crate::setup`) creates it, register it in the app's data like the database:
+//!
+//! ```rust,ignore
+//! #[get("/{id}")]
+//! async fn read(db: Data<Database>, cache: Data<Cache>, item_id: Path<i32>) -> HttpResponse {
+//!     let item_id = item_id.into_inner();
+//!     let result = cache.compute_if_absent(&format!("todos:{item_id}"), || {
+//!         Todo::read(&mut db.get_connection(), item_id)
+//!     });
+//!     ...
+//! }
+//!
+//! // and when the item changes
+//! cache.delete(&format!("todos:{item_id}")).ok();
+//! ```
+//!
+//! The cache is best effort: when the store fails (ex: Redis is down), values are computed as if
+//! they weren't cached.
+//!
+//! The [`RequestCache`] extractor memoizes values for the duration of a single request instead
+//! (ex: the current user, read by several helpers).
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+mod memo;
+pub use memo::RequestCache;
+#[cfg(feature = "backend_actix-web")]
+mod memo_actixweb;
+#[cfg(feature = "backend_poem")]
+mod memo_poem;
+#[cfg(feature = "backend_poem")]
+pub use memo_poem::with_request_cache;
+
+mod store;
+#[cfg(feature = "plugin_redis")]
+pub use store::RedisCacheStore;
+pub use store::{CacheStore, MemoryCacheStore};
+
+/// how long values are kept, unless told otherwise (see [`Cache::default_ttl`])
+pub const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+lazy_static::lazy_static! {
+    /// shared by every in-memory [`Cache`], so clones of the app's data share it
+    static ref MEMORY_STORE: Arc<MemoryCacheStore> = Arc::new(MemoryCacheStore::default());
+}
+
+#[derive(Clone)]
+/// caches JSON-serializable values, see the [module documentation](`self`)
+pub struct Cache {
+    store: Arc<dyn CacheStore>,
+    default_ttl: Option<Duration>,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self {
+            store: MEMORY_STORE.clone(),
+            default_ttl: Some(DEFAULT_TTL),
+        }
+    }
+}
+
+impl std::fmt::Debug for Cache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cache")
+            .field("default_ttl", &self.default_ttl)
+            .finish()
+    }
+}
+
+impl Cache {
+    pub fn new(store: impl CacheStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            default_ttl: Some(DEFAULT_TTL),
+        }
+    }
+
+    /// in Redis if the `plugin_redis` feature is enabled and `REDIS_URL` is set, in memory
+    /// otherwise
+    pub fn from_env() -> Self {
+        #[cfg(feature = "plugin_redis")]
+        if let Ok(url) = std::env::var("REDIS_URL") {
+            match RedisCacheStore::new(&url) {
+                Ok(store) => return Self::new(store),
+                Err(err) => println!(
+                    "Cache: could not connect to Redis ({err}), values won't be shared with other instances"
+                ),
+            }
+        }
+
+        Self::default()
+    }
+
+    /// how long [`set`](`Self::set`) and [`compute_if_absent`](`Self::compute_if_absent`) keep
+    /// values, `None` keeps them until they're deleted (default: [`DEFAULT_TTL`])
+    pub fn default_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// the value under `key`, if it was set, hasn't expired and is a `T` (values that aren't, ex:
+    /// because the type changed since they were cached, are misses)
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, String> {
+        Ok(self
+            .store
+            .get(key)?
+            .and_then(|value| serde_json::from_str(&value).ok()))
+    }
+
+    /// caches `value` under `key` for the default TTL
+    pub fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<(), String> {
+        self.set_with_ttl(key, value, self.default_ttl)
+    }
+
+    /// caches `value` under `key` for `ttl`, or until it's deleted if it's `None`
+    pub fn set_with_ttl<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+    ) -> Result<(), String> {
+        let value = serde_json::to_string(value).map_err(|err| err.to_string())?;
+
+        self.store.set(key, &value, ttl)
+    }
+
+    /// forgets the value under `key`, ex: when what it was computed from changes
+    pub fn delete(&self, key: &str) -> Result<(), String> {
+        self.store.delete(key)
+    }
+
+    /// the value under `key`, or the one `compute` returns, which is cached for the default TTL
+    /// (errors aren't)
+    pub fn compute_if_absent<T, E>(
+        &self,
+        key: &str,
+        compute: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.compute_if_absent_with_ttl(key, self.default_ttl, compute)
+    }
+
+    /// like [`compute_if_absent`](`Self::compute_if_absent`), caching the value for `ttl`
+    pub fn compute_if_absent_with_ttl<T, E>(
+        &self,
+        key: &str,
+        ttl: Option<Duration>,
+        compute: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        match self.get::<T>(key) {
+            Ok(Some(value)) => return Ok(value),
+            Ok(None) => {}
+            Err(err) => println!("Cache: could not read '{key}': {err}"),
+        }
+
+        let value = compute()?;
+        if let Err(err) = self.set_with_ttl(key, &value, ttl) {
+            println!("Cache: could not write '{key}': {err}");
+        }
+
+        Ok(value)
+    }
+}
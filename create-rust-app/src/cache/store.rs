@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// where cached values are kept, as JSON
+pub trait CacheStore: Send + Sync {
+    /// the value under `key`, if it was set and hasn't expired
+    fn get(&self, key: &str) -> Result<Option<String>, String>;
+
+    /// remembers `value` under `key`, until `ttl` is over (or forever if it's `None`)
+    fn set(&self, key: &str, value: &str, ttl: Option<Duration>) -> Result<(), String>;
+
+    /// forgets the value under `key`, if any
+    fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+#[derive(Default)]
+/// keeps values in this process' memory
+pub struct MemoryCacheStore {
+    entries: Mutex<HashMap<String, (String, Option<Instant>)>>,
+}
+
+impl CacheStore for MemoryCacheStore {
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let entries = self.entries.lock().map_err(|err| err.to_string())?;
+        let now = Instant::now();
+
+        Ok(entries
+            .get(key)
+            .filter(|(_, expires_at)| expires_at.map_or(true, |expires_at| expires_at > now))
+            .map(|(value, _)| value.clone()))
+    }
+
+    fn set(&self, key: &str, value: &str, ttl: Option<Duration>) -> Result<(), String> {
+        let mut entries = self.entries.lock().map_err(|err| err.to_string())?;
+        let now = Instant::now();
+
+        // forget about expired entries, so the map doesn't grow forever
+        if entries.len() > 10_000 {
+            entries.retain(|_, (_, expires_at)| {
+                expires_at.map_or(true, |expires_at| expires_at > now)
+            });
+        }
+
+        entries.insert(
+            key.to_string(),
+            (value.to_string(), ttl.map(|ttl| now + ttl)),
+        );
+
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        let mut entries = self.entries.lock().map_err(|err| err.to_string())?;
+        entries.remove(key);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "plugin_redis")]
+/// keeps values in Redis, so they're shared by every instance of the app
+pub struct RedisCacheStore {
+    client: redis::Client,
+    prefix: String,
+}
+
+#[cfg(feature = "plugin_redis")]
+impl RedisCacheStore {
+    /// `url` like `redis://127.0.0.1/`, keys are prefixed with `cra:cache:`
+    pub fn new(url: &str) -> redis::RedisResult<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            prefix: "cra:cache:".to_string(),
+        })
+    }
+
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+}
+
+#[cfg(feature = "plugin_redis")]
+impl CacheStore for RedisCacheStore {
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let mut conn = self
+            .client
+            .get_connection()
+            .map_err(|err| err.to_string())?;
+
+        redis::cmd("GET")
+            .arg(format!("{}{key}", self.prefix))
+            .query::<Option<String>>(&mut conn)
+            .map_err(|err| err.to_string())
+    }
+
+    fn set(&self, key: &str, value: &str, ttl: Option<Duration>) -> Result<(), String> {
+        let mut conn = self
+            .client
+            .get_connection()
+            .map_err(|err| err.to_string())?;
+
+        let mut cmd = redis::cmd("SET");
+        cmd.arg(format!("{}{key}", self.prefix)).arg(value);
+        if let Some(ttl) = ttl {
+            cmd.arg("PX").arg((ttl.as_millis() as u64).max(1));
+        }
+
+        cmd.query::<()>(&mut conn).map_err(|err| err.to_string())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        let mut conn = self
+            .client
+            .get_connection()
+            .map_err(|err| err.to_string())?;
+
+        redis::cmd("DEL")
+            .arg(format!("{}{key}", self.prefix))
+            .query::<()>(&mut conn)
+            .map_err(|err| err.to_string())
+    }
+}
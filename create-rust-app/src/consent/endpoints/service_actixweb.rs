@@ -0,0 +1,117 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Json, Path};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::consent::{self, PolicyDocument, UserConsent, ID};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../accept endpoint
+pub struct AcceptInput {
+    document_id: ID,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the .../documents endpoint
+///
+/// returns the latest version of every policy document
+#[get("/documents")]
+async fn documents(db: Data<Database>) -> Result<HttpResponse> {
+    let result = web::block(move || PolicyDocument::latest_all(&mut db.get_connection())).await?;
+
+    match result {
+        Ok(documents) => Ok(HttpResponse::Ok().json(documents)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch documents.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../documents/{kind} endpoint
+///
+/// returns the latest version of the [`kind`] policy document
+#[get("/documents/{kind}")]
+async fn document(db: Data<Database>, kind: Path<String>) -> Result<HttpResponse> {
+    let result =
+        web::block(move || PolicyDocument::latest(&mut db.get_connection(), &kind)).await?;
+
+    match result {
+        Ok(document) => Ok(HttpResponse::Ok().json(document)),
+        Err(diesel::result::Error::NotFound) => {
+            Ok(error_response(StatusCode::NOT_FOUND, "Document not found."))
+        }
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch document.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../pending endpoint
+///
+/// requires auth
+///
+/// returns the latest documents the user has not accepted yet
+#[get("/pending")]
+async fn pending(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result =
+        web::block(move || consent::pending(&mut db.get_connection(), auth.user_id)).await?;
+
+    match result {
+        Ok(documents) => Ok(HttpResponse::Ok().json(documents)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch documents.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../accept endpoint
+///
+/// requires auth
+///
+/// records that the user accepted the document with the given `document_id`
+#[post("/accept")]
+async fn accept(
+    db: Data<Database>,
+    auth: Auth,
+    Json(item): Json<AcceptInput>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let ip_address = crate::client_ip(&req);
+
+    let result = web::block(move || {
+        let mut db = db.get_connection();
+
+        PolicyDocument::read(&mut db, item.document_id)?;
+        UserConsent::accept(&mut db, auth.user_id, item.document_id, ip_address)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err(diesel::result::Error::NotFound) => {
+            Ok(error_response(StatusCode::NOT_FOUND, "Document not found."))
+        }
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not record consent.",
+        )),
+    }
+}
+
+/// returns the endpoints for the consent service
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope
+        .service(documents)
+        .service(document)
+        .service(pending)
+        .service(accept)
+}
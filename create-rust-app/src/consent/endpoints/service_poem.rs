@@ -0,0 +1,129 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    post,
+    web::{Data, Json, Path},
+    Error, IntoResponse, Request, Response, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::consent::{self, PolicyDocument, UserConsent, ID};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../accept endpoint
+pub struct AcceptInput {
+    document_id: ID,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for GET requests at the .../documents endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the latest version of every [`PolicyDocument`]
+/// | 500 | Json payload : {"message": "Could not fetch documents."}
+async fn documents(db: Data<&Database>) -> Result<impl IntoResponse> {
+    PolicyDocument::latest_all(&mut db.get_connection())
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch documents.",
+            )
+        })
+}
+
+#[handler]
+/// handler for GET requests at the .../documents/{kind} endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the latest version of the [`PolicyDocument`]
+/// | 404 | Json payload : {"message": "Document not found."}
+/// | 500 | Json payload : {"message": "Could not fetch document."}
+async fn document(db: Data<&Database>, Path(kind): Path<String>) -> Result<impl IntoResponse> {
+    match PolicyDocument::latest(&mut db.get_connection(), &kind) {
+        Ok(document) => Ok(Json(document)),
+        Err(diesel::result::Error::NotFound) => {
+            Err(error_response(StatusCode::NOT_FOUND, "Document not found."))
+        }
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch document.",
+        )),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../pending endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the latest [`PolicyDocument`]s the user has not accepted yet
+/// | 500 | Json payload : {"message": "Could not fetch documents."}
+async fn pending(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    consent::pending(&mut db.get_connection(), auth.user_id)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch documents.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../accept endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | the consent was recorded
+/// | 404 | Json payload : {"message": "Document not found."}
+/// | 500 | Json payload : {"message": "Could not record consent."}
+async fn accept(
+    db: Data<&Database>,
+    auth: Auth,
+    Json(item): Json<AcceptInput>,
+    req: &Request,
+) -> Result<impl IntoResponse> {
+    let mut db = db.get_connection();
+    let ip_address = crate::client_ip(req);
+
+    let result = PolicyDocument::read(&mut db, item.document_id)
+        .and_then(|_| UserConsent::accept(&mut db, auth.user_id, item.document_id, ip_address));
+
+    match result {
+        Ok(()) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err(diesel::result::Error::NotFound) => {
+            Err(error_response(StatusCode::NOT_FOUND, "Document not found."))
+        }
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not record consent.",
+        )),
+    }
+}
+
+/// returns endpoints for the consent service
+pub fn api() -> Route {
+    Route::new()
+        .at("/documents", get(documents))
+        .at("/documents/:kind", get(document))
+        .at("/pending", get(pending))
+        .at("/accept", post(accept))
+}
@@ -0,0 +1,99 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::web::{self, Data};
+use actix_web::{Error, HttpResponse};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use serde_json::json;
+use std::rc::Rc;
+
+use crate::consent;
+use crate::Database;
+
+/// rejects requests from users who haven't accepted the latest version of every policy document
+/// with a `403` and the list of pending document kinds
+///
+/// ```rust,ignore
+/// api_scope = api_scope.service(
+///     services::todo::endpoints(web::scope("/todos")).wrap(create_rust_app::consent::RequireConsent)
+/// );
+/// ```
+///
+/// don't wrap the `/consent` or `/auth` endpoints, users need them to accept the documents
+pub struct RequireConsent;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireConsent
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireConsentMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireConsentMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequireConsentMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireConsentMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let user_id = super::authenticated_user(
+                req.headers()
+                    .get("Authorization")
+                    .and_then(|header| header.to_str().ok()),
+            );
+            let db = req.app_data::<Data<Database>>().cloned();
+
+            if let (Some(user_id), Some(db)) = (user_id, db) {
+                let pending =
+                    web::block(move || consent::pending(&mut db.get_connection(), user_id)).await?;
+
+                let response = match pending {
+                    Ok(documents) if documents.is_empty() => None,
+                    Ok(documents) => Some(
+                        HttpResponse::Forbidden().body(
+                            json!({
+                                "message": "Please accept the latest terms to continue.",
+                                "pending": documents.iter().map(|d| &d.kind).collect::<Vec<_>>(),
+                            })
+                            .to_string(),
+                        ),
+                    ),
+                    Err(_) => Some(HttpResponse::InternalServerError().body(
+                        json!({ "message": "An internal server error occurred." }).to_string(),
+                    )),
+                };
+
+                if let Some(response) = response {
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            }
+
+            service
+                .call(req)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
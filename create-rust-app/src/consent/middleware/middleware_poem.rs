@@ -0,0 +1,68 @@
+use poem::{
+    async_trait, http::StatusCode, Endpoint, Error, IntoResponse, Middleware, Request, Response,
+    Result,
+};
+use serde_json::json;
+
+use crate::consent;
+use crate::Database;
+
+/// rejects requests from users who haven't accepted the latest version of every policy document
+/// with a `403` and the list of pending document kinds
+///
+/// ```rust,ignore
+/// api_routes = api_routes.nest(
+///     "/todos",
+///     services::todo::api().with(create_rust_app::consent::RequireConsent),
+/// );
+/// ```
+///
+/// don't wrap the `/consent` or `/auth` endpoints, users need them to accept the documents
+pub struct RequireConsent;
+
+impl<E: Endpoint> Middleware<E> for RequireConsent {
+    type Output = RequireConsentEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RequireConsentEndpoint { ep }
+    }
+}
+
+pub struct RequireConsentEndpoint<E> {
+    ep: E,
+}
+
+#[async_trait]
+impl<E: Endpoint> Endpoint for RequireConsentEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let user_id = super::authenticated_user(
+            req.headers()
+                .get("Authorization")
+                .and_then(|header| header.to_str().ok()),
+        );
+
+        if let (Some(user_id), Some(db)) = (user_id, req.data::<Database>()) {
+            let pending = consent::pending(&mut db.get_connection(), user_id).map_err(|_| {
+                Error::from_string(
+                    json!({ "message": "An internal server error occurred." }).to_string(),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                )
+            })?;
+
+            if !pending.is_empty() {
+                return Err(Error::from_string(
+                    json!({
+                        "message": "Please accept the latest terms to continue.",
+                        "pending": pending.iter().map(|d| &d.kind).collect::<Vec<_>>(),
+                    })
+                    .to_string(),
+                    StatusCode::FORBIDDEN,
+                ));
+            }
+        }
+
+        self.ep.call(req).await.map(IntoResponse::into_response)
+    }
+}
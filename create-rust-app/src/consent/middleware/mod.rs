@@ -0,0 +1,30 @@
+#[cfg(feature = "backend_actix-web")]
+mod middleware_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use middleware_actixweb::RequireConsent;
+
+#[cfg(feature = "backend_poem")]
+mod middleware_poem;
+#[cfg(feature = "backend_poem")]
+pub use middleware_poem::RequireConsent;
+
+use super::ID;
+use crate::auth::{jwt, AccessTokenClaims};
+
+/// the user making the request, if it carries a valid access token
+///
+/// requests without one are let through: routes that need a user are guarded by [`Auth`](`crate::auth::Auth`)
+fn authenticated_user(authorization: Option<&str>) -> Option<ID> {
+    let access_token =
+        jwt::decode_token::<AccessTokenClaims>(authorization?.strip_prefix("Bearer ")?).ok()?;
+
+    if !access_token
+        .claims
+        .token_type
+        .eq_ignore_ascii_case("access_token")
+    {
+        return None;
+    }
+
+    Some(access_token.claims.sub)
+}
@@ -0,0 +1,252 @@
+//! Versioned legal documents (terms of service, privacy policy, ...) and tracking of which
+//! version each user accepted.
+//!
+//! Publishing a new version of a document makes it pending for every user until they accept it.
+//! Wrap the routes that require an up-to-date consent with [`RequireConsent`] to reject requests
+//! from users with pending documents (the frontend's `ConsentGate` then asks them to accept).
+//!
+//! ```rust,ignore
+//! use create_rust_app::consent::{self, PolicyDocument};
+//!
+//! let mut db = database.get_connection();
+//! PolicyDocument::publish(&mut db, consent::TERMS, "Terms of Service", include_str!("terms.md"))?;
+//! ```
+use crate::diesel::*;
+use crate::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+mod endpoints;
+pub use endpoints::*;
+mod middleware;
+pub use middleware::*;
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// the terms of service
+pub const TERMS: &str = "terms";
+/// the privacy policy
+pub const PRIVACY: &str = "privacy";
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=policy_documents)]
+/// one version of a legal document
+pub struct PolicyDocument {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub kind: String,
+    pub version: i32,
+    pub title: String,
+    pub content: String,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=policy_documents)]
+pub struct PolicyDocumentChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub kind: String,
+    pub version: i32,
+    pub title: String,
+    pub content: String,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Associations)]
+#[diesel(table_name=user_consents, primary_key(user_id, document_id), belongs_to(PolicyDocument, foreign_key=document_id))]
+/// a user's acceptance of a [`PolicyDocument`]
+pub struct UserConsent {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub document_id: ID,
+    pub ip_address: Option<String>,
+
+    pub accepted_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=user_consents)]
+pub struct UserConsentChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub document_id: ID,
+    pub ip_address: Option<String>,
+}
+
+impl PolicyDocument {
+    /// Create an entry in [`db`](`Connection`)'s `policy_documents` table using the data in [`item`](`PolicyDocumentChangeset`)
+    pub fn create(db: &mut Connection, item: &PolicyDocumentChangeset) -> QueryResult<Self> {
+        use schema::policy_documents::dsl::*;
+
+        insert_into(policy_documents)
+            .values(item)
+            .get_result::<PolicyDocument>(db)
+    }
+
+    /// creates the next version of the [`item_kind`] document, every user will have to accept it
+    pub fn publish(
+        db: &mut Connection,
+        item_kind: &str,
+        item_title: &str,
+        item_content: &str,
+    ) -> QueryResult<Self> {
+        let next_version = match Self::latest(db, item_kind) {
+            Ok(latest) => latest.version + 1,
+            Err(diesel::result::Error::NotFound) => 1,
+            Err(e) => return Err(e),
+        };
+
+        Self::create(
+            db,
+            &PolicyDocumentChangeset {
+                kind: item_kind.to_string(),
+                version: next_version,
+                title: item_title.to_string(),
+                content: item_content.to_string(),
+            },
+        )
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `policy_documents`
+    /// who's primary key matches [`item_id`](`ID`)
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use schema::policy_documents::dsl::*;
+
+        policy_documents
+            .filter(id.eq(item_id))
+            .first::<PolicyDocument>(db)
+    }
+
+    /// the most recent version of the [`item_kind`] document
+    pub fn latest(db: &mut Connection, item_kind: &str) -> QueryResult<Self> {
+        use schema::policy_documents::dsl::*;
+
+        policy_documents
+            .filter(kind.eq(item_kind))
+            .order(version.desc())
+            .first::<PolicyDocument>(db)
+    }
+
+    /// the most recent version of every kind of document
+    pub fn latest_all(db: &mut Connection) -> QueryResult<Vec<Self>> {
+        use schema::policy_documents::dsl::*;
+
+        let mut documents = policy_documents
+            .order((kind.asc(), version.desc()))
+            .load::<PolicyDocument>(db)?;
+
+        // documents are ordered by kind, newest version first
+        documents.dedup_by(|a, b| a.kind == b.kind);
+
+        Ok(documents)
+    }
+}
+
+impl UserConsent {
+    /// records that [`item_user_id`](`ID`) accepted [`item_document_id`](`ID`),
+    /// accepting the same document twice keeps the first acceptance
+    pub fn accept(
+        db: &mut Connection,
+        item_user_id: ID,
+        item_document_id: ID,
+        item_ip_address: Option<String>,
+    ) -> QueryResult<()> {
+        use schema::user_consents::dsl::*;
+
+        insert_into(user_consents)
+            .values(&UserConsentChangeset {
+                user_id: item_user_id,
+                document_id: item_document_id,
+                ip_address: item_ip_address,
+            })
+            .on_conflict((user_id, document_id))
+            .do_nothing()
+            .execute(db)
+            .map(|_| ())
+    }
+
+    /// every document version [`item_user_id`](`ID`) accepted, most recent first
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use schema::user_consents::dsl::*;
+
+        user_consents
+            .filter(user_id.eq(item_user_id))
+            .order(accepted_at.desc())
+            .load::<UserConsent>(db)
+    }
+
+    /// forgets every acceptance of [`item_user_id`](`ID`)
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::user_consents::dsl::*;
+
+        diesel::delete(user_consents.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
+
+/// the latest documents [`user_id`](`ID`) has not accepted yet
+pub fn pending(db: &mut Connection, user_id: ID) -> QueryResult<Vec<PolicyDocument>> {
+    let accepted = UserConsent::fetch_all_for_user(db, user_id)?
+        .into_iter()
+        .map(|consent| consent.document_id)
+        .collect::<Vec<_>>();
+
+    Ok(PolicyDocument::latest_all(db)?
+        .into_iter()
+        .filter(|document| !accepted.contains(&document.id))
+        .collect())
+}
+
+/// includes the accepted document versions in account exports, and forgets them when the account is deleted
+pub(crate) struct ConsentAccountHook;
+
+impl crate::auth::account::AccountHook for ConsentAccountHook {
+    fn name(&self) -> &'static str {
+        "consent"
+    }
+
+    fn export(&self, db: &mut Connection, user_id: ID) -> QueryResult<serde_json::Value> {
+        let mut accepted = vec![];
+
+        for consent in UserConsent::fetch_all_for_user(db, user_id)? {
+            let document = PolicyDocument::read(db, consent.document_id)?;
+
+            accepted.push(serde_json::json!({
+                "kind": document.kind,
+                "version": document.version,
+                "title": document.title,
+                "ip_address": consent.ip_address,
+                "accepted_at": consent.accepted_at,
+            }));
+        }
+
+        Ok(serde_json::Value::Array(accepted))
+    }
+
+    fn delete(&self, db: &mut Connection, user_id: ID) -> QueryResult<()> {
+        UserConsent::delete_all_for_user(db, user_id).map(|_| ())
+    }
+}
@@ -0,0 +1,23 @@
+table! {
+  policy_documents (id) {
+      id -> Int4,
+      kind -> Text,
+      version -> Int4,
+      title -> Text,
+      content -> Text,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  user_consents (user_id, document_id) {
+      user_id -> Int4,
+      document_id -> Int4,
+      ip_address -> Nullable<Text>,
+      accepted_at -> Timestamptz,
+  }
+}
+
+joinable!(user_consents -> policy_documents (document_id));
+
+allow_tables_to_appear_in_same_query!(policy_documents, user_consents,);
@@ -1,5 +1,9 @@
 use diesel::r2d2::{self, ConnectionManager, PooledConnection};
 use once_cell::sync::OnceCell;
+use std::sync::Arc;
+
+#[cfg(feature = "plugin_tenancy")]
+pub mod tenancy;
 
 #[cfg(feature = "database_postgres")]
 type DbCon = diesel::PgConnection;
@@ -21,7 +25,7 @@ pub type Connection = PooledConnection<ConnectionManager<DbCon>>;
 #[derive(Clone)]
 /// wrapper function for a database pool
 pub struct Database {
-    pub pool: &'static Pool,
+    pub pool: Arc<Pool>,
 }
 
 impl Default for Database {
@@ -34,7 +38,7 @@ impl Database {
     /// create a new [`Database`]
     pub fn new() -> Database {
         Database {
-            pool: Self::get_or_init_pool(),
+            pool: Self::get_or_init_pool().clone(),
         }
     }
 
@@ -43,17 +47,22 @@ impl Database {
         self.pool.get().unwrap()
     }
 
-    fn get_or_init_pool() -> &'static Pool {
+    fn get_or_init_pool() -> &'static Arc<Pool> {
         #[cfg(debug_assertions)]
         crate::load_env_vars();
 
-        static POOL: OnceCell<Pool> = OnceCell::new();
+        static POOL: OnceCell<Arc<Pool>> = OnceCell::new();
 
         POOL.get_or_init(|| {
-            Pool::builder()
-                .connection_timeout(std::time::Duration::from_secs(5))
-                .build(ConnectionManager::<DbCon>::new(Self::database_url()))
-                .unwrap()
+            let builder = Pool::builder().connection_timeout(std::time::Duration::from_secs(5));
+            #[cfg(feature = "plugin_observability")]
+            let builder = builder.event_handler(Box::new(crate::observability::PoolEvents));
+
+            Arc::new(
+                builder
+                    .build(ConnectionManager::<DbCon>::new(Self::database_url()))
+                    .unwrap(),
+            )
         })
     }
 
@@ -0,0 +1,56 @@
+use crate::auth::OrgAuth;
+use crate::Database;
+
+type ID = i32;
+
+#[derive(Debug, Clone)]
+/// the tenant a request acts for: the user's active organization (see [`OrgAuth`])
+///
+/// use it instead of [`OrgAuth`] in handlers working with tenant-scoped tables (which have a
+/// `tenant_id` column holding the organization's id): filter their queries on [`Tenant::id`], and
+/// use [`Tenant::database`], which is routed to the tenant's schema or database when
+/// `TENANT_ISOLATION` is set. Requests made without an active organization are rejected with a
+/// 403.
+pub struct Tenant {
+    /// the organization's id, the value of the `tenant_id` columns
+    pub id: ID,
+    /// the user acting within the tenant
+    pub auth: OrgAuth,
+    /// the database holding the tenant's rows, see [`Database::for_tenant`]
+    pub database: Database,
+}
+
+impl Tenant {
+    /// the tenant of `auth`'s active organization
+    pub fn new(auth: OrgAuth) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: auth.organization_id,
+            database: Database::for_tenant(&key(auth.organization_id))?,
+            auth,
+        })
+    }
+
+    /// the identifier of the tenant's schema or database, see [`key`]
+    pub fn key(&self) -> String {
+        key(self.id)
+    }
+
+    /// whether `item` belongs to the tenant
+    pub fn owns<T: TenantScoped>(&self, item: &T) -> bool {
+        item.tenant_id() == self.id
+    }
+}
+
+/// the identifier of the organization [`tenant_id`](`ID`)'s schema or database, ex: `tenant_42`
+///
+/// pass it to [`provision`](`super::provision`) when an organization is created, if
+/// `TENANT_ISOLATION` is set
+pub fn key(tenant_id: ID) -> String {
+    format!("tenant_{tenant_id}")
+}
+
+/// a model of a tenant-scoped table
+pub trait TenantScoped {
+    /// the organization the row belongs to, its `tenant_id` column
+    fn tenant_id(&self) -> ID;
+}
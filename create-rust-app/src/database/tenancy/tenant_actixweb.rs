@@ -0,0 +1,38 @@
+use actix_http::StatusCode;
+use actix_web::dev::Payload;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse};
+use futures::future::LocalBoxFuture;
+use serde_json::json;
+
+use super::Tenant;
+use crate::auth::OrgAuth;
+
+impl FromRequest for Tenant {
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+    type Error = actix_web::Error;
+
+    /// extracts the [`Tenant`] of the request's [`OrgAuth`]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth = OrgAuth::from_request(req, payload).into_inner();
+
+        Box::pin(async move {
+            let auth = auth?;
+
+            // the tenant's pool is built on the first request for it, which connects to its
+            // database: keep that off the async workers
+            web::block(move || Tenant::new(auth)).await?.map_err(|err| {
+                println!("{err:#?}");
+                actix_web::error::InternalError::from_response(
+                    "Could not reach the tenant's database",
+                    HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR).body(
+                        json!({
+                          "message": "Could not reach the tenant's database"
+                        })
+                        .to_string(),
+                    ),
+                )
+                .into()
+            })
+        })
+    }
+}
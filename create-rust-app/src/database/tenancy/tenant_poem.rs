@@ -0,0 +1,25 @@
+use poem::{async_trait, http::StatusCode, Error, FromRequest, Request, RequestBody, Result};
+
+use super::Tenant;
+use crate::auth::OrgAuth;
+
+#[async_trait]
+impl<'a> FromRequest<'a> for Tenant {
+    /// extracts the [`Tenant`] of the request's [`OrgAuth`]
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let auth = OrgAuth::from_request(req, body).await?;
+
+        // the tenant's pool is built on the first request for it, which connects to its
+        // database: keep that off the async workers
+        tokio::task::spawn_blocking(move || Tenant::new(auth))
+            .await
+            .map_err(|err| Error::from_string(err.to_string(), StatusCode::INTERNAL_SERVER_ERROR))?
+            .map_err(|err| {
+                println!("{err:#?}");
+                Error::from_string(
+                    "Could not reach the tenant's database",
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                )
+            })
+    }
+}
@@ -0,0 +1,62 @@
+//! Support for running the backend inside the desktop app (`plugin_desktop` feature): the Tauri
+//! shell in `desktop/` either connects to a backend (the one `cargo fullstack` runs in
+//! development, or `DESKTOP_BACKEND_URL`), or starts the release build of the backend it bundles
+//! as a sidecar, on a free local port.
+//!
+//! ```rust,ignore
+//! let app_data = create_rust_app::setup();
+//! // the embedded backend has its own SQLite database, in the app's data directory
+//! create_rust_app::desktop::migrate(&app_data.database).unwrap();
+//!
+//! HttpServer::new(move || { /* ... */ })
+//!     .bind(create_rust_app::desktop::bind_address())?
+//! ```
+//!
+//! The shell sets `CRA_DESKTOP=1`, `BIND_ADDRESS`, `DATABASE_URL` and `SECRET_KEY` (generated on
+//! the first launch and kept in the OS keychain) for the sidecar, and runs it from its bundled
+//! resources so `frontend/dist`, `backend/views` and `migrations` are found where they are in the
+//! project.
+//!
+//! Webviews don't keep cookies reliably across launches, so the desktop frontend sends
+//! `X-Auth-Client: desktop` to get the refresh token in the auth plugin's responses, keeps it in
+//! the keychain and sends it back in the `X-Refresh-Token` header (see
+//! `create_rust_app::auth::controller::CLIENT_HEADER`).
+use diesel_migrations::{FileBasedMigrations, MigrationHarness};
+
+use crate::Database;
+
+/// the address the backend listens on when `BIND_ADDRESS` isn't set
+pub const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0:3000";
+
+/// `BIND_ADDRESS`, set by the desktop shell for the backend it embeds, or
+/// [`DEFAULT_BIND_ADDRESS`]
+pub fn bind_address() -> String {
+    std::env::var("BIND_ADDRESS").unwrap_or_else(|_| DEFAULT_BIND_ADDRESS.to_string())
+}
+
+/// whether the backend was started by the desktop shell (`CRA_DESKTOP` is set)
+pub fn is_embedded() -> bool {
+    std::env::var("CRA_DESKTOP").is_ok()
+}
+
+/// runs the pending migrations (from the `migrations/` folder) when the backend is
+/// [embedded](`is_embedded`), there's no one to run them on the user's machine
+///
+/// does nothing otherwise, migrations being run by `cargo fullstack` in development and by the
+/// deployment in production
+pub fn migrate(db: &Database) -> Result<(), String> {
+    if !is_embedded() {
+        return Ok(());
+    }
+
+    let source = FileBasedMigrations::find_migrations_directory()
+        .map_err(|err| format!("could not find the migrations: {err}"))?;
+    let mut db = db
+        .pool
+        .get()
+        .map_err(|err| format!("could not connect to the database: {err}"))?;
+
+    db.run_pending_migrations(source)
+        .map(|_| ())
+        .map_err(|err| format!("could not run the migrations: {err}"))
+}
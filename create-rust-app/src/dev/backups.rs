@@ -0,0 +1,304 @@
+//! Development database backups: snapshots of the whole database, taken and restored from the
+//! admin portal's "Migrations" page (ex: before trying out a destructive migration).
+//!
+//! Backups are kept in `target/backups` (or the directory in `CRA_BACKUPS_DIR`):
+//!
+//! - with postgres, they're plain SQL dumps made with `pg_dump`, and restored with `psql`; both
+//!   must be installed (and be at least as recent as the server). Restoring drops the `public`
+//!   schema first, so the database ends up exactly as it was, tables created since included.
+//! - with sqlite, they're copies of the database file made with `VACUUM INTO`. Restoring replaces
+//!   every table (and its indexes, triggers and views) with the backup's.
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Database;
+
+#[cfg(feature = "database_postgres")]
+const EXTENSION: &str = "sql";
+#[cfg(feature = "database_sqlite")]
+const EXTENSION: &str = "sqlite3";
+
+#[derive(Debug, Clone, Serialize)]
+/// a backup file, see [`list`]
+pub struct Backup {
+    /// the file's name, ex: `1700000000-before-users-migration.sql`
+    pub name: String,
+    pub size_bytes: u64,
+    /// unix timestamp, in seconds
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// body of `POST /db/backups`
+pub struct BackupInput {
+    /// added to the backup's name, to tell it apart
+    pub label: Option<String>,
+}
+
+fn backups_dir() -> PathBuf {
+    match std::env::var("CRA_BACKUPS_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from("target/backups"),
+    }
+}
+
+/// `None` unless `name` is a backup's name (which keeps requests from reading other files)
+fn backup_path(name: &str) -> Option<PathBuf> {
+    let stem = name.strip_suffix(&format!(".{EXTENSION}"))?;
+    if stem.is_empty()
+        || !stem
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+
+    Some(backups_dir().join(name))
+}
+
+fn backup(path: &std::path::Path) -> Option<Backup> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let created_at = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| modified.as_secs())
+        .unwrap_or_default();
+
+    Some(Backup {
+        name: path.file_name()?.to_string_lossy().to_string(),
+        size_bytes: metadata.len(),
+        created_at,
+    })
+}
+
+/// the backups, newest first
+pub fn list() -> Vec<Backup> {
+    let entries = match std::fs::read_dir(backups_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut backups = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| backup_path(&name.to_string_lossy()))
+                .is_some()
+        })
+        .filter_map(|path| backup(&path))
+        .collect::<Vec<_>>();
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+
+    backups
+}
+
+/// backs the database up, `label` is added to the backup's name
+pub fn create(db: &Database, label: Option<&str>) -> Result<Backup, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let label = label
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    let label = label.trim_matches('-');
+    let name = if label.is_empty() {
+        format!("{now}.{EXTENSION}")
+    } else {
+        format!("{now}-{label}.{EXTENSION}")
+    };
+    let path = backup_path(&name).ok_or_else(|| "Invalid backup name.".to_string())?;
+
+    std::fs::create_dir_all(backups_dir())
+        .map_err(|err| format!("Could not create the backups folder: {err}"))?;
+    if path.exists() {
+        return Err(format!("Backup '{name}' already exists."));
+    }
+
+    dump(db, &path)?;
+
+    backup(&path).ok_or_else(|| format!("Could not read backup '{name}'."))
+}
+
+/// restores the backup `name`, replacing the database's content
+pub fn restore(db: &Database, name: &str) -> Result<(), String> {
+    let path = backup_path(name)
+        .filter(|path| path.is_file())
+        .ok_or_else(|| format!("Backup '{name}' not found."))?;
+
+    load(db, &path)
+}
+
+/// deletes the backup `name`
+pub fn delete(name: &str) -> Result<(), String> {
+    let path = backup_path(name)
+        .filter(|path| path.is_file())
+        .ok_or_else(|| format!("Backup '{name}' not found."))?;
+
+    std::fs::remove_file(path).map_err(|err| format!("Could not delete backup '{name}': {err}"))
+}
+
+#[cfg(feature = "database_postgres")]
+fn database_url() -> Result<String, String> {
+    std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL is not set.".to_string())
+}
+
+/// runs `program` with `args`, fails with its stderr
+#[cfg(feature = "database_postgres")]
+fn run(program: &str, args: &[&std::ffi::OsStr]) -> Result<(), String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| format!("Could not run `{program}` (is it installed?): {err}"))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "`{program}` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "database_postgres")]
+fn dump(_db: &Database, path: &std::path::Path) -> Result<(), String> {
+    let url = database_url()?;
+
+    run(
+        "pg_dump",
+        &[
+            "--no-owner".as_ref(),
+            "--no-privileges".as_ref(),
+            "--file".as_ref(),
+            path.as_os_str(),
+            url.as_ref(),
+        ],
+    )
+}
+
+#[cfg(feature = "database_postgres")]
+fn load(_db: &Database, path: &std::path::Path) -> Result<(), String> {
+    let url = database_url()?;
+
+    run(
+        "psql",
+        &[
+            "--quiet".as_ref(),
+            "--single-transaction".as_ref(),
+            "--set=ON_ERROR_STOP=1".as_ref(),
+            "--command=DROP SCHEMA public CASCADE; CREATE SCHEMA public;".as_ref(),
+            "--file".as_ref(),
+            path.as_os_str(),
+            url.as_ref(),
+        ],
+    )
+}
+
+/// `value` as an sqlite string literal
+#[cfg(feature = "database_sqlite")]
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(feature = "database_sqlite")]
+fn dump(db: &Database, path: &std::path::Path) -> Result<(), String> {
+    use diesel::connection::SimpleConnection;
+
+    db.get_connection()
+        .batch_execute(&format!("VACUUM INTO {}", quote(&path.to_string_lossy())))
+        .map_err(|err| format!("Could not back the database up: {err}"))
+}
+
+#[cfg(feature = "database_sqlite")]
+fn load(db: &Database, path: &std::path::Path) -> Result<(), String> {
+    use diesel::connection::SimpleConnection;
+    use diesel::sql_types::{Nullable, Text};
+    use diesel::RunQueryDsl;
+
+    #[derive(QueryableByName)]
+    struct SchemaObject {
+        #[diesel(sql_type=Text)]
+        kind: String,
+        #[diesel(sql_type=Text)]
+        name: String,
+        #[diesel(sql_type=Nullable<Text>)]
+        sql: Option<String>,
+    }
+
+    let schema_objects = |db: &mut crate::Connection, schema: &str| {
+        diesel::sql_query(format!(
+            "SELECT type AS kind, name, sql FROM {schema}.sqlite_master ORDER BY type = 'table' DESC"
+        ))
+        .load::<SchemaObject>(db)
+        .map_err(|err| format!("Could not read the {schema} database's schema: {err}"))
+    };
+
+    let mut db = db.get_connection();
+    db.batch_execute(&format!(
+        "PRAGMA foreign_keys = OFF; ATTACH DATABASE {} AS backup;",
+        quote(&path.to_string_lossy())
+    ))
+    .map_err(|err| format!("Could not open the backup: {err}"))?;
+
+    let result = (|| {
+        let current = schema_objects(&mut db, "main")?;
+        let backup = schema_objects(&mut db, "backup")?;
+
+        // sqlite's own tables (ex: `sqlite_sequence`) can't be created or dropped
+        let is_internal = |object: &SchemaObject| object.name.starts_with("sqlite_");
+
+        let mut sql = String::from("BEGIN;");
+        // dropping a table drops its indexes and triggers as well
+        for object in current.iter().filter(|object| !is_internal(object)) {
+            if object.kind == "table" || object.kind == "view" {
+                sql.push_str(&format!(
+                    "DROP {} IF EXISTS main.\"{}\";",
+                    object.kind.to_uppercase(),
+                    object.name.replace('"', "\"\"")
+                ));
+            }
+        }
+        // tables first, then their indexes, triggers and views
+        for object in backup.iter().filter(|object| !is_internal(object)) {
+            if let Some(create) = &object.sql {
+                sql.push_str(create);
+                sql.push(';');
+            }
+            if object.kind == "table" {
+                let table = object.name.replace('"', "\"\"");
+                sql.push_str(&format!(
+                    "INSERT INTO main.\"{table}\" SELECT * FROM backup.\"{table}\";"
+                ));
+            }
+        }
+        // the AUTOINCREMENT counters, the tables using them were recreated so main has it as well
+        if backup.iter().any(|object| object.name == "sqlite_sequence") {
+            sql.push_str("DELETE FROM main.sqlite_sequence;");
+            sql.push_str("INSERT INTO main.sqlite_sequence SELECT * FROM backup.sqlite_sequence;");
+        }
+        sql.push_str("COMMIT;");
+
+        db.batch_execute(&sql).map_err(|err| {
+            db.batch_execute("ROLLBACK;").ok();
+            format!("Could not restore the backup: {err}")
+        })
+    })();
+
+    db.batch_execute("DETACH DATABASE backup; PRAGMA foreign_keys = ON;")
+        .ok();
+
+    result
+}
@@ -21,19 +21,62 @@ pub struct MySqlQuery {
     pub query: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct MetricsQuery {
+    /// how many minutes of requests to return (default: 15, at most 60)
+    pub minutes: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct HealthCheckResponse {
     pub message: String,
 }
 
+/// statement timeout of the SQL console, unless `CRA_SQL_CONSOLE_TIMEOUT_MS` is set
+const SQL_CONSOLE_TIMEOUT_MS: u64 = 5000;
+
+/// whether the SQL console is enabled, it's opt-in: set `CRA_SQL_CONSOLE=true`
+pub fn sql_console_enabled() -> bool {
+    std::env::var("CRA_SQL_CONSOLE")
+        .map(|enabled| enabled.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
 /// /db/query
+///
+/// runs [`body.query`](`MySqlQuery`) in a read-only transaction, and returns its rows as a json
+/// array. Queries are cancelled after `CRA_SQL_CONSOLE_TIMEOUT_MS` milliseconds (default: 5000).
+///
+/// callers must check [`sql_console_enabled`] first
 pub fn query_db(db: &Database, body: &MySqlQuery) -> Result<String, diesel::result::Error> {
-    let q = format!("SELECT json_agg(q) as json FROM ({}) q;", body.query);
+    let query = body.query.trim().trim_end_matches(';');
+    let q = format!("SELECT CAST(COALESCE(json_agg(q), '[]') AS TEXT) as json FROM ({query}) q;");
     let mut db = db.pool.get().unwrap();
 
-    Ok(sql_query(q.as_str())
-        .get_result::<MyQueryResult>(&mut db)?
-        .json)
+    let timeout = std::env::var("CRA_SQL_CONSOLE_TIMEOUT_MS")
+        .ok()
+        .and_then(|timeout| timeout.parse::<u64>().ok())
+        .unwrap_or(SQL_CONSOLE_TIMEOUT_MS);
+
+    #[cfg(not(feature = "database_sqlite"))]
+    {
+        db.build_transaction().read_only().run(|db| {
+            sql_query(format!("SET LOCAL statement_timeout = {timeout}")).execute(db)?;
+
+            Ok(sql_query(q.as_str()).get_result::<MyQueryResult>(db)?.json)
+        })
+    }
+
+    // sqlite has no statement timeout, a busy database is only waited for that long
+    #[cfg(feature = "database_sqlite")]
+    {
+        sql_query(format!("PRAGMA busy_timeout = {timeout}")).execute(&mut db)?;
+        sql_query("PRAGMA query_only = ON").execute(&mut db)?;
+        let result = sql_query(q.as_str()).get_result::<MyQueryResult>(&mut db);
+        sql_query("PRAGMA query_only = OFF").execute(&mut db)?;
+
+        Ok(result?.json)
+    }
 }
 
 /// /db/is-connected
@@ -130,5 +173,53 @@ pub fn migrate_db(db: &Database) -> (bool, /* error message: */ Option<String>)
     }
 }
 
+/// /db/migrations/next
+/// runs the oldest pending migration, returns its version (`None` when there was none)
+pub fn run_next_migration(db: &Database) -> Result<Option<String>, String> {
+    let mut db = db.pool.clone().get().unwrap();
+
+    let source = FileBasedMigrations::find_migrations_directory().map_err(|err| err.to_string())?;
+    let has_pending_migrations = MigrationHarness::has_pending_migration(&mut db, source.clone())
+        .map_err(|err| err.to_string())?;
+
+    if !has_pending_migrations {
+        return Ok(None);
+    }
+
+    match MigrationHarness::run_next_migration(&mut db, source) {
+        Ok(version) => Ok(Some(version.to_string())),
+        Err(err) => {
+            println!("{err:#?}");
+            Err(err.to_string())
+        }
+    }
+}
+
+/// /db/migrations/revert
+/// reverts the last applied migration (with its `down.sql`), returns its version
+pub fn revert_last_migration(db: &Database) -> Result<String, String> {
+    let mut db = db.pool.clone().get().unwrap();
+
+    let source = FileBasedMigrations::find_migrations_directory().map_err(|err| err.to_string())?;
+    match MigrationHarness::revert_last_migration(&mut db, source) {
+        Ok(version) => Ok(version.to_string()),
+        Err(err) => {
+            println!("{err:#?}");
+            Err(err.to_string())
+        }
+    }
+}
+
+/// /db/seed
+/// applies the seeds in `seeds/`, returns their names, see [`crate::seeds`]
+pub fn run_seeds(db: &Database) -> Result<Vec<String>, String> {
+    let mut db = db.pool.clone().get().unwrap();
+
+    crate::seeds::run(&mut db).map_err(|err| {
+        println!("{err}");
+        err
+    })
+}
+
 /// /health
 pub fn health() {}
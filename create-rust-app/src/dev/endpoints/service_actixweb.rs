@@ -1,19 +1,237 @@
-use crate::{dev::controller, dev::controller::MySqlQuery, Database};
+use crate::{
+    admin::AdminAccess, dev::backups, dev::controller, dev::controller::MetricsQuery,
+    dev::controller::MySqlQuery, dev::mail_preview, dev::mailbox, inspector, metrics, seeds,
+    Database,
+};
 use actix_web::{
-    post,
-    web::{Data, Json},
+    delete, get, post,
+    web::{Data, Json, Path, Query},
     HttpResponse, Scope,
 };
 use std::ops::Deref;
 
+/// runs a read-only query, see [`controller::query_db`]
 #[post("/db/query")]
-async fn query_db(db: Data<Database>, body: Json<MySqlQuery>) -> HttpResponse {
+async fn query_db(db: Data<Database>, _admin: AdminAccess, body: Json<MySqlQuery>) -> HttpResponse {
+    if !controller::sql_console_enabled() {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "message": "The SQL console is disabled, set CRA_SQL_CONSOLE=true to enable it."
+        }));
+    }
+
     match controller::query_db(&db, body.deref()) {
-        Ok(result) => HttpResponse::Ok().body(result),
-        Err(_) => HttpResponse::InternalServerError().finish(),
+        Ok(result) => HttpResponse::Ok()
+            .content_type("application/json")
+            .body(result),
+        Err(err) => {
+            HttpResponse::BadRequest().json(serde_json::json!({ "message": err.to_string() }))
+        }
+    }
+}
+
+/// the migrations in `migrations/`, and whether they were applied
+#[get("/db/migrations")]
+async fn migrations(db: Data<Database>, _admin: AdminAccess) -> HttpResponse {
+    HttpResponse::Ok().json(controller::get_migrations(&db))
+}
+
+/// runs the pending migrations, returns the updated migrations
+#[post("/db/migrate")]
+async fn migrate(db: Data<Database>, _admin: AdminAccess) -> HttpResponse {
+    match controller::migrate_db(&db) {
+        (true, _) => HttpResponse::Ok().json(controller::get_migrations(&db)),
+        (false, error) => HttpResponse::InternalServerError()
+            .json(serde_json::json!({ "message": error.unwrap_or_default() })),
+    }
+}
+
+/// runs the oldest pending migration, returns the updated migrations
+#[post("/db/migrations/next")]
+async fn migrate_next(db: Data<Database>, _admin: AdminAccess) -> HttpResponse {
+    match controller::run_next_migration(&db) {
+        Ok(_) => HttpResponse::Ok().json(controller::get_migrations(&db)),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({ "message": err })),
+    }
+}
+
+/// reverts the last applied migration, returns the updated migrations
+#[post("/db/migrations/revert")]
+async fn migrate_revert(db: Data<Database>, _admin: AdminAccess) -> HttpResponse {
+    match controller::revert_last_migration(&db) {
+        Ok(_) => HttpResponse::Ok().json(controller::get_migrations(&db)),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({ "message": err })),
+    }
+}
+
+/// the seed files in `seeds/`, see [`seeds`]
+#[get("/db/seeds")]
+async fn seed_list(_admin: AdminAccess) -> HttpResponse {
+    match seeds::list() {
+        Ok(seeds) => HttpResponse::Ok().json(seeds),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({ "message": err })),
+    }
+}
+
+/// (re)applies the seeds, returns the names of the ones applied
+#[post("/db/seed")]
+async fn seed(db: Data<Database>, _admin: AdminAccess) -> HttpResponse {
+    match controller::run_seeds(&db) {
+        Ok(applied) => HttpResponse::Ok().json(applied),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({ "message": err })),
+    }
+}
+
+/// the database backups, newest first, see [`backups`]
+#[get("/db/backups")]
+async fn backup_list(_admin: AdminAccess) -> HttpResponse {
+    HttpResponse::Ok().json(backups::list())
+}
+
+/// backs the database up, returns the new backup
+#[post("/db/backups")]
+async fn backup_create(
+    db: Data<Database>,
+    _admin: AdminAccess,
+    Json(input): Json<backups::BackupInput>,
+) -> HttpResponse {
+    match backups::create(&db, input.label.as_deref()) {
+        Ok(backup) => HttpResponse::Ok().json(backup),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({ "message": err })),
+    }
+}
+
+/// replaces the database's content with a backup, returns the (restored) migrations
+#[post("/db/backups/{name}/restore")]
+async fn backup_restore(
+    db: Data<Database>,
+    _admin: AdminAccess,
+    name: Path<String>,
+) -> HttpResponse {
+    match backups::restore(&db, &name) {
+        Ok(()) => HttpResponse::Ok().json(controller::get_migrations(&db)),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({ "message": err })),
+    }
+}
+
+/// deletes a backup
+#[delete("/db/backups/{name}")]
+async fn backup_delete(_admin: AdminAccess, name: Path<String>) -> HttpResponse {
+    match backups::delete(&name) {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(err) => HttpResponse::NotFound().json(serde_json::json!({ "message": err })),
+    }
+}
+
+/// the app's [`metrics`] over the last `?minutes=` (default: 15)
+#[get("/metrics")]
+async fn metrics_snapshot(
+    db: Data<Database>,
+    _admin: AdminAccess,
+    Query(query): Query<MetricsQuery>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(metrics::snapshot(&db, query.minutes.unwrap_or(15)))
+}
+
+/// the requests recorded by the [`inspector`], newest first
+#[get("/requests")]
+async fn requests(
+    _admin: AdminAccess,
+    Query(filter): Query<inspector::RequestFilter>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(inspector::list(&filter))
+}
+
+/// forgets the requests recorded by the [`inspector`]
+#[delete("/requests")]
+async fn requests_clear(_admin: AdminAccess) -> HttpResponse {
+    inspector::clear();
+    HttpResponse::Ok().finish()
+}
+
+/// the emails captured by the development mailbox, newest first
+#[get("/mailbox")]
+async fn mailbox_list(_admin: AdminAccess) -> HttpResponse {
+    HttpResponse::Ok().json(mailbox::list())
+}
+
+/// a captured email, with its content and headers
+#[get("/mailbox/{id}")]
+async fn mailbox_read(_admin: AdminAccess, id: Path<String>) -> HttpResponse {
+    match mailbox::read(&id) {
+        Some(message) => HttpResponse::Ok().json(message),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+/// empties the development mailbox
+#[delete("/mailbox")]
+async fn mailbox_clear(_admin: AdminAccess) -> HttpResponse {
+    match mailbox::clear() {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(err) => {
+            println!("{err}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// the mail templates which can be previewed
+#[get("/mail/templates")]
+async fn mail_template_list(_admin: AdminAccess) -> HttpResponse {
+    HttpResponse::Ok().json(mail_preview::templates())
+}
+
+/// renders a mail template with its sample context, see [`mail_preview`]
+#[get("/mail/preview")]
+async fn mail_template_preview(
+    _admin: AdminAccess,
+    Query(query): Query<mail_preview::PreviewQuery>,
+) -> HttpResponse {
+    let rendered = match mail_preview::preview(&query) {
+        Ok(rendered) => rendered,
+        Err(err) => {
+            return HttpResponse::BadRequest()
+                .content_type("text/plain")
+                .body(err)
+        }
+    };
+
+    match query.format.as_deref().unwrap_or("html") {
+        "html" => HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(rendered.html),
+        "text" => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(rendered.text),
+        "subject" => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(rendered.subject),
+        "json" => HttpResponse::Ok().json(rendered),
+        _ => HttpResponse::BadRequest()
+            .content_type("text/plain")
+            .body("Unknown format (expected 'html', 'text', 'subject' or 'json')"),
     }
 }
 
 pub fn endpoints(scope: Scope) -> Scope {
-    scope.service(query_db)
+    scope
+        .service(query_db)
+        .service(migrations)
+        .service(migrate)
+        .service(migrate_next)
+        .service(migrate_revert)
+        .service(seed_list)
+        .service(seed)
+        .service(backup_list)
+        .service(backup_create)
+        .service(backup_restore)
+        .service(backup_delete)
+        .service(metrics_snapshot)
+        .service(requests)
+        .service(requests_clear)
+        .service(mailbox_list)
+        .service(mailbox_read)
+        .service(mailbox_clear)
+        .service(mail_template_list)
+        .service(mail_template_preview)
 }
@@ -1,23 +1,248 @@
 use poem::{
-    handler,
+    delete, get, handler,
     http::StatusCode,
     post,
-    web::{Data, Json},
-    Error, IntoResponse, Result, Route,
+    web::{Data, Json, Path, Query},
+    Error, IntoResponse, Response, Result, Route,
 };
 
-use crate::dev::{controller, controller::MySqlQuery};
+use crate::dev::{
+    backups, controller, controller::MetricsQuery, controller::MySqlQuery, mail_preview, mailbox,
+    CreateRustAppMigration,
+};
 
+use crate::admin::AdminAccess;
+use crate::inspector;
+use crate::metrics;
+use crate::seeds;
 use crate::Database;
 
 #[handler]
-async fn query(db: Data<&Database>, body: Json<MySqlQuery>) -> Result<impl IntoResponse> {
+/// runs a read-only query, see [`controller::query_db`]
+async fn query(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+    body: Json<MySqlQuery>,
+) -> Result<impl IntoResponse> {
+    if !controller::sql_console_enabled() {
+        return Err(Error::from_string(
+            serde_json::json!({
+                "message": "The SQL console is disabled, set CRA_SQL_CONSOLE=true to enable it."
+            })
+            .to_string(),
+            StatusCode::FORBIDDEN,
+        ));
+    }
+
     match controller::query_db(db.0, &body) {
-        Ok(result) => Ok(result),
-        Err(_) => Err(Error::from_status(StatusCode::INTERNAL_SERVER_ERROR)),
+        Ok(result) => Ok(result.with_content_type("application/json")),
+        Err(err) => Err(Error::from_string(
+            serde_json::json!({ "message": err.to_string() }).to_string(),
+            StatusCode::BAD_REQUEST,
+        )),
+    }
+}
+
+fn migration_error(message: String) -> Error {
+    Error::from_string(
+        serde_json::json!({ "message": message }).to_string(),
+        StatusCode::INTERNAL_SERVER_ERROR,
+    )
+}
+
+#[handler]
+/// the migrations in `migrations/`, and whether they were applied
+async fn migrations(db: Data<&Database>, _admin: AdminAccess) -> Json<Vec<CreateRustAppMigration>> {
+    Json(controller::get_migrations(db.0))
+}
+
+#[handler]
+/// runs the pending migrations, returns the updated migrations
+async fn migrate(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+) -> Result<Json<Vec<CreateRustAppMigration>>> {
+    match controller::migrate_db(db.0) {
+        (true, _) => Ok(Json(controller::get_migrations(db.0))),
+        (false, error) => Err(migration_error(error.unwrap_or_default())),
+    }
+}
+
+#[handler]
+/// runs the oldest pending migration, returns the updated migrations
+async fn migrate_next(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+) -> Result<Json<Vec<CreateRustAppMigration>>> {
+    controller::run_next_migration(db.0)
+        .map(|_| Json(controller::get_migrations(db.0)))
+        .map_err(migration_error)
+}
+
+#[handler]
+/// the database backups, newest first, see [`backups`]
+async fn backup_list(_admin: AdminAccess) -> Json<Vec<backups::Backup>> {
+    Json(backups::list())
+}
+
+#[handler]
+/// backs the database up, returns the new backup
+async fn backup_create(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+    Json(input): Json<backups::BackupInput>,
+) -> Result<Json<backups::Backup>> {
+    backups::create(db.0, input.label.as_deref())
+        .map(Json)
+        .map_err(migration_error)
+}
+
+#[handler]
+/// replaces the database's content with a backup, returns the (restored) migrations
+async fn backup_restore(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<CreateRustAppMigration>>> {
+    backups::restore(db.0, &name)
+        .map(|_| Json(controller::get_migrations(db.0)))
+        .map_err(migration_error)
+}
+
+#[handler]
+/// deletes a backup
+async fn backup_delete(_admin: AdminAccess, Path(name): Path<String>) -> Result<()> {
+    backups::delete(&name).map_err(migration_error)
+}
+
+#[handler]
+/// the app's [`metrics`] over the last `?minutes=` (default: 15)
+async fn metrics_snapshot(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+    Query(query): Query<MetricsQuery>,
+) -> Json<metrics::Metrics> {
+    Json(metrics::snapshot(db.0, query.minutes.unwrap_or(15)))
+}
+
+#[handler]
+/// the seed files in `seeds/`, see [`seeds`]
+async fn seed_list(_admin: AdminAccess) -> Result<Json<Vec<seeds::Seed>>> {
+    seeds::list().map(Json).map_err(migration_error)
+}
+
+#[handler]
+/// (re)applies the seeds, returns the names of the ones applied
+async fn seed(db: Data<&Database>, _admin: AdminAccess) -> Result<Json<Vec<String>>> {
+    controller::run_seeds(db.0)
+        .map(Json)
+        .map_err(migration_error)
+}
+
+#[handler]
+/// reverts the last applied migration, returns the updated migrations
+async fn migrate_revert(
+    db: Data<&Database>,
+    _admin: AdminAccess,
+) -> Result<Json<Vec<CreateRustAppMigration>>> {
+    controller::revert_last_migration(db.0)
+        .map(|_| Json(controller::get_migrations(db.0)))
+        .map_err(migration_error)
+}
+
+#[handler]
+/// the requests recorded by the [`inspector`], newest first
+async fn requests(
+    _admin: AdminAccess,
+    Query(filter): Query<inspector::RequestFilter>,
+) -> Json<Vec<inspector::RecordedRequest>> {
+    Json(inspector::list(&filter))
+}
+
+#[handler]
+/// forgets the requests recorded by the [`inspector`]
+async fn requests_clear(_admin: AdminAccess) {
+    inspector::clear();
+}
+
+#[handler]
+/// the emails captured by the development mailbox, newest first
+async fn mailbox_list(_admin: AdminAccess) -> Json<Vec<mailbox::MailboxEntry>> {
+    Json(mailbox::list())
+}
+
+#[handler]
+/// a captured email, with its content and headers
+async fn mailbox_read(
+    _admin: AdminAccess,
+    Path(id): Path<String>,
+) -> Result<Json<mailbox::MailboxMessage>> {
+    mailbox::read(&id)
+        .map(Json)
+        .ok_or_else(|| Error::from_status(StatusCode::NOT_FOUND))
+}
+
+#[handler]
+/// empties the development mailbox
+async fn mailbox_clear(_admin: AdminAccess) -> Result<()> {
+    mailbox::clear().map_err(|err| {
+        println!("{err}");
+        Error::from_status(StatusCode::INTERNAL_SERVER_ERROR)
+    })
+}
+
+#[handler]
+/// the mail templates which can be previewed
+async fn mail_template_list(_admin: AdminAccess) -> Json<Vec<mail_preview::MailTemplate>> {
+    Json(mail_preview::templates())
+}
+
+#[handler]
+/// renders a mail template with its sample context, see [`mail_preview`]
+async fn mail_template_preview(
+    _admin: AdminAccess,
+    Query(query): Query<mail_preview::PreviewQuery>,
+) -> Result<Response> {
+    let rendered = mail_preview::preview(&query)
+        .map_err(|err| Error::from_string(err, StatusCode::BAD_REQUEST))?;
+
+    match query.format.as_deref().unwrap_or("html") {
+        "html" => Ok(rendered
+            .html
+            .with_content_type("text/html; charset=utf-8")
+            .into_response()),
+        "text" => Ok(rendered
+            .text
+            .with_content_type("text/plain; charset=utf-8")
+            .into_response()),
+        "subject" => Ok(rendered
+            .subject
+            .with_content_type("text/plain; charset=utf-8")
+            .into_response()),
+        "json" => Ok(Json(rendered).into_response()),
+        _ => Err(Error::from_string(
+            "Unknown format (expected 'html', 'text', 'subject' or 'json')",
+            StatusCode::BAD_REQUEST,
+        )),
     }
 }
 
 pub fn api() -> Route {
-    Route::new().at("/db/query", post(query))
+    Route::new()
+        .at("/db/query", post(query))
+        .at("/db/migrations", get(migrations))
+        .at("/db/migrate", post(migrate))
+        .at("/db/migrations/next", post(migrate_next))
+        .at("/db/migrations/revert", post(migrate_revert))
+        .at("/db/seeds", get(seed_list))
+        .at("/db/seed", post(seed))
+        .at("/db/backups", get(backup_list).post(backup_create))
+        .at("/db/backups/:name", delete(backup_delete))
+        .at("/db/backups/:name/restore", post(backup_restore))
+        .at("/metrics", get(metrics_snapshot))
+        .at("/requests", get(requests).delete(requests_clear))
+        .at("/mailbox", get(mailbox_list).delete(mailbox_clear))
+        .at("/mailbox/:id", get(mailbox_read))
+        .at("/mail/templates", get(mail_template_list))
+        .at("/mail/preview", get(mail_template_preview))
 }
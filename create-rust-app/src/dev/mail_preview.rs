@@ -0,0 +1,138 @@
+//! Mail template previews: renders the [`Mailer`](`crate::Mailer`)'s templates with sample data,
+//! so they can be iterated on in the browser without sending any email.
+//!
+//! Templates are reloaded for every preview, so edits show up on refresh. The sample context of a
+//! template is read from a `{name}.sample.json` file next to it (ex:
+//! `backend/mail/templates/example.sample.json`), and can be overridden field by field with the
+//! preview's `context` query parameter.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tera::{Context, Tera};
+
+use crate::mailer::{render_template, Mailer, RenderedTemplate};
+
+/// the template parts a previewable template has
+const PARTS: [&str; 3] = ["subject.txt", "txt", "html"];
+
+#[derive(Debug, Clone, Serialize)]
+/// a mail template, see [`Mailer::send_template`](`crate::Mailer::send_template`)
+pub struct MailTemplate {
+    pub name: String,
+    /// the locales it's translated in
+    pub locales: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// what [`preview`] renders
+pub struct PreviewQuery {
+    pub template: String,
+    pub locale: Option<String>,
+    /// a json object, merged into the template's sample context
+    pub context: Option<String>,
+    /// `html` (default), `text`, `subject` or `json`
+    pub format: Option<String>,
+}
+
+/// the templates which have all their parts, and their translations
+pub fn templates() -> Vec<MailTemplate> {
+    let templates = Mailer::load_templates();
+    let mut found = BTreeMap::<String, Vec<String>>::new();
+
+    for name in templates.get_template_names() {
+        let base = match name.strip_suffix(".html") {
+            Some(base) => base,
+            None => continue,
+        };
+        let (name, locale) = match base.split_once('.') {
+            Some((name, locale)) => (name, Some(locale)),
+            None => (base, None),
+        };
+        let prefix = match locale {
+            Some(locale) => format!("{name}.{locale}"),
+            None => name.to_string(),
+        };
+        let complete = PARTS.iter().all(|part| {
+            templates
+                .get_template_names()
+                .any(|loaded| loaded == format!("{prefix}.{part}"))
+        });
+        if !complete {
+            continue;
+        }
+
+        let locales = found.entry(name.to_string()).or_default();
+        if let Some(locale) = locale {
+            locales.push(locale.to_string());
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|(name, mut locales)| {
+            locales.sort();
+            MailTemplate { name, locales }
+        })
+        .collect()
+}
+
+/// renders the template in `query` with freshly loaded templates
+pub fn preview(query: &PreviewQuery) -> Result<RenderedTemplate, String> {
+    let templates = Mailer::load_templates();
+
+    let mut context = sample_context(&templates, &query.template)?;
+    if let Some(overrides) = query.context.as_deref().filter(|c| !c.trim().is_empty()) {
+        let overrides = serde_json::from_str::<Value>(overrides)
+            .map_err(|err| format!("Invalid preview context (error: '{err}')"))?;
+        merge(&mut context, overrides);
+    }
+
+    let context = Context::from_value(context).map_err(|err| {
+        format!(
+            "Invalid mail template context (template: '{template}', error: '{err}')",
+            template = query.template
+        )
+    })?;
+
+    render_template(
+        &templates,
+        &query.template,
+        query.locale.as_deref(),
+        &context,
+    )
+}
+
+/// placeholders for the variables the auth emails use, and the `{name}.sample.json` file's data
+fn sample_context(templates: &Tera, name: &str) -> Result<Value, String> {
+    let mut context = json!({
+        "link": "http://localhost:3000/",
+        "new_email": "new-address@localhost",
+    });
+
+    let path = templates
+        .get_template(&format!("{name}.sample.json"))
+        .ok()
+        .and_then(|template| template.path.clone());
+    if let Some(path) = path {
+        let sample = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Could not read '{path}' (error: '{err}')"))?;
+        let sample = serde_json::from_str::<Value>(&sample)
+            .map_err(|err| format!("Invalid sample context in '{path}' (error: '{err}')"))?;
+        merge(&mut context, sample);
+    }
+
+    Ok(context)
+}
+
+/// overwrites `value`'s fields with `with`'s, recursively
+fn merge(value: &mut Value, with: Value) {
+    match (value, with) {
+        (Value::Object(value), Value::Object(with)) => {
+            for (key, with) in with {
+                merge(value.entry(key).or_insert(Value::Null), with);
+            }
+        }
+        (value, with) => *value = with,
+    }
+}
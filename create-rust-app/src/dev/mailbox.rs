@@ -0,0 +1,164 @@
+//! Development mailbox: in debug builds, every email the [`Mailer`](`crate::Mailer`) sends is
+//! captured here, and can be browsed in the admin portal's "Mailbox" page. Emails are only
+//! delivered for real when `SEND_MAIL` is `true`.
+//!
+//! Messages are kept as json files in `target/mailbox` (or the directory in `CRA_MAILBOX_DIR`),
+//! so they survive the backend's restarts.
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::mailer::Email;
+
+/// how many messages the mailbox lists, the older ones are still kept
+const MAILBOX_PAGE_SIZE: usize = 100;
+
+/// tells apart the messages captured during the same nanosecond
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a captured email
+pub struct MailboxMessage {
+    /// sortable, newer messages have greater ids
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+    /// the message's MIME headers, as they'd be sent
+    pub headers: String,
+    /// the file names of its attachments and inline images
+    #[serde(default)]
+    pub attachments: Vec<String>,
+    /// unix timestamp, in seconds
+    pub sent_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a captured email, without its content
+pub struct MailboxEntry {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub sent_at: u64,
+}
+
+fn mailbox_dir() -> PathBuf {
+    match std::env::var("CRA_MAILBOX_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from("target/mailbox"),
+    }
+}
+
+/// `None` unless `id` is a message id (which keeps requests from reading other files)
+fn message_path(id: &str) -> Option<PathBuf> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return None;
+    }
+
+    Some(mailbox_dir().join(format!("{id}.json")))
+}
+
+/// stores `email` in the mailbox, returns the message's id
+pub fn capture(email: &Email) -> Result<String, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let id = format!(
+        "{nanos:020}-{counter:010}",
+        nanos = now.as_nanos(),
+        counter = COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+
+    let headers = email
+        .to_message()
+        .map(|message| message.headers().to_string())
+        .unwrap_or_default();
+
+    let message = MailboxMessage {
+        id: id.clone(),
+        from: email.from.clone(),
+        to: email.to.clone(),
+        subject: email.subject.clone(),
+        text: email.text.clone(),
+        html: email.html.clone(),
+        headers,
+        attachments: email
+            .attachments
+            .iter()
+            .map(|attachment| attachment.file_name.clone())
+            .collect(),
+        sent_at: now.as_secs(),
+    };
+
+    let dir = mailbox_dir();
+    let error = |err: std::io::Error| {
+        format!(
+            "Could not capture email (to: '{to}', mailbox: '{dir}', error: '{err}')",
+            to = email.to,
+            dir = dir.display()
+        )
+    };
+
+    std::fs::create_dir_all(&dir).map_err(error)?;
+    std::fs::write(
+        dir.join(format!("{id}.json")),
+        serde_json::to_vec(&message).unwrap(),
+    )
+    .map_err(error)?;
+
+    Ok(id)
+}
+
+/// the most recent messages, newest first
+pub fn list() -> Vec<MailboxEntry> {
+    let mut paths = match std::fs::read_dir(mailbox_dir()) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .collect::<Vec<_>>(),
+        Err(_) => return vec![],
+    };
+    paths.sort();
+
+    paths
+        .iter()
+        .rev()
+        .take(MAILBOX_PAGE_SIZE)
+        .filter_map(|path| read_message(path))
+        .map(|message| MailboxEntry {
+            id: message.id,
+            from: message.from,
+            to: message.to,
+            subject: message.subject,
+            sent_at: message.sent_at,
+        })
+        .collect()
+}
+
+/// the message `id`, if it's in the mailbox
+pub fn read(id: &str) -> Option<MailboxMessage> {
+    read_message(&message_path(id)?)
+}
+
+/// removes every message from the mailbox
+pub fn clear() -> Result<(), String> {
+    let dir = mailbox_dir();
+
+    match std::fs::remove_dir_all(&dir) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(format!(
+            "Could not clear the mailbox (mailbox: '{dir}', error: '{err}')",
+            dir = dir.display()
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn read_message(path: &std::path::Path) -> Option<MailboxMessage> {
+    let contents = std::fs::read(path).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
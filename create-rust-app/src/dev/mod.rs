@@ -2,7 +2,10 @@ mod backend_compiling_server;
 mod dev_server;
 mod frontend_dev_server;
 
+pub mod backups;
 pub mod controller;
+pub mod mail_preview;
+pub mod mailbox;
 use cargo_metadata::CompilerMessage;
 use cargo_toml::Manifest;
 use serde::Serialize;
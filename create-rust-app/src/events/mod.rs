@@ -0,0 +1,197 @@
+//! An in-process event bus (`plugin_events` feature): code publishes typed events, and the
+//! subscribers the app registered at startup handle them, without the publisher knowing about them.
+//!
+//! ```rust,ignore
+//! use create_rust_app::events::{self, Event};
+//!
+//! #[derive(Debug, Clone, Serialize, Deserialize)]
+//! pub struct OrderPlaced {
+//!     pub order_id: i32,
+//! }
+//!
+//! impl Event for OrderPlaced {
+//!     const NAME: &'static str = "order_placed";
+//! }
+//!
+//! // in main(), before starting the server
+//! events::subscribe(|db, event: &OrderPlaced| update_stock(db, event.order_id));
+//! // run by the tasks plugin's workers (they register it as well), retried when it fails
+//! events::subscribe_queued("send_receipt", |db, event: &OrderPlaced| send_receipt(db, event.order_id));
+//!
+//! // in a handler
+//! events::publish(OrderPlaced { order_id: order.id });
+//! ```
+//!
+//! The auth plugin publishes its [hooks' events](`crate::auth::AuthHooks`) (`auth.registered`,
+//! `auth.login`, `auth.password_changed` and `auth.session_revoked`), and the storage plugin
+//! publishes [`AttachmentAttached`](`crate::storage::AttachmentAttached`) and
+//! [`AttachmentDetached`](`crate::storage::AttachmentDetached`).
+//!
+//! Subscribers run in registration order, on the publisher's thread, and can't fail the
+//! publisher: do slow or fallible work in queued subscribers (requires `plugin_tasks`), which get
+//! the event serialized in a task and run on the workers.
+use crate::{Connection, Database};
+use once_cell::sync::Lazy;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+#[cfg(feature = "plugin_tasks")]
+mod tasks;
+#[cfg(feature = "plugin_tasks")]
+pub use tasks::DispatchEvent;
+
+/// an event published on the bus, see the [module's documentation](`self`)
+pub trait Event: Clone + Send + Sync + 'static {
+    /// identifies the event's subscribers, and its queued dispatches: keep it stable
+    const NAME: &'static str;
+}
+
+type Handler = Arc<dyn Fn(&mut Connection, &dyn Any) + Send + Sync>;
+
+#[cfg(feature = "plugin_tasks")]
+type QueuedHandler =
+    Arc<dyn Fn(&mut Connection, serde_json::Value) -> Result<(), String> + Send + Sync>;
+
+#[derive(Clone)]
+enum Subscriber {
+    Inline(Handler),
+    #[cfg(feature = "plugin_tasks")]
+    Queued {
+        name: &'static str,
+        serialize: Arc<dyn Fn(&dyn Any) -> Result<serde_json::Value, String> + Send + Sync>,
+        handler: QueuedHandler,
+    },
+}
+
+/// the subscribers of each event, by [`Event::NAME`]
+static SUBSCRIBERS: Lazy<RwLock<HashMap<&'static str, Vec<Subscriber>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn subscribers_of(event: &str) -> Vec<Subscriber> {
+    SUBSCRIBERS
+        .read()
+        .unwrap()
+        .get(event)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn register(event: &'static str, subscriber: Subscriber) {
+    SUBSCRIBERS
+        .write()
+        .unwrap()
+        .entry(event)
+        .or_default()
+        .push(subscriber);
+}
+
+/// calls `handler` with every `E` published, on the publisher's thread
+pub fn subscribe<E: Event>(handler: impl Fn(&mut Connection, &E) + Send + Sync + 'static) {
+    register(
+        E::NAME,
+        Subscriber::Inline(Arc::new(move |db, event| {
+            if let Some(event) = event.downcast_ref::<E>() {
+                handler(db, event)
+            }
+        })),
+    );
+}
+
+#[cfg(feature = "plugin_tasks")]
+/// calls `handler` with every `E` published, in a task run by the tasks plugin's workers: they
+/// must register it too (ex: by calling the same function as the server at startup)
+///
+/// `name` identifies the subscriber in its tasks, keep it stable. The task fails, and is retried,
+/// when `handler` returns an error.
+pub fn subscribe_queued<E: Event + serde::Serialize + serde::de::DeserializeOwned>(
+    name: &'static str,
+    handler: impl Fn(&mut Connection, &E) -> Result<(), String> + Send + Sync + 'static,
+) {
+    register(
+        E::NAME,
+        Subscriber::Queued {
+            name,
+            serialize: Arc::new(|event| {
+                let event = event
+                    .downcast_ref::<E>()
+                    .ok_or_else(|| format!("Expected a '{}' event", E::NAME))?;
+                serde_json::to_value(event).map_err(|err| err.to_string())
+            }),
+            handler: Arc::new(move |db, payload| {
+                let event = serde_json::from_value::<E>(payload)
+                    .map_err(|err| format!("Could not read the '{}' event: {err}", E::NAME))?;
+                handler(db, &event)
+            }),
+        },
+    );
+}
+
+/// publishes `event` to its subscribers, they get a connection of the app's database
+pub fn publish<E: Event>(event: E) {
+    dispatch(None, &event);
+}
+
+/// [`publish`], the subscribers getting `db` (ex: the connection of the request which published
+/// `event`)
+pub fn publish_with<E: Event>(db: &mut Connection, event: E) {
+    dispatch(Some(db), &event);
+}
+
+fn dispatch<E: Event>(mut db: Option<&mut Connection>, event: &E) {
+    // the connection isn't taken from the pool if only queued subscribers listen
+    let mut pooled: Option<Connection> = None;
+
+    for subscriber in subscribers_of(E::NAME) {
+        match subscriber {
+            Subscriber::Inline(handler) => {
+                let db: &mut Connection = match db {
+                    Some(ref mut db) => db,
+                    None => pooled.get_or_insert_with(|| Database::new().get_connection()),
+                };
+
+                handler(db, event);
+            }
+            #[cfg(feature = "plugin_tasks")]
+            Subscriber::Queued {
+                name, serialize, ..
+            } => {
+                if let Err(err) =
+                    serialize(event).and_then(|payload| tasks::enqueue(E::NAME, name, payload))
+                {
+                    println!(
+                        "Could not queue the '{}' event for '{name}': {err}",
+                        E::NAME
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "plugin_tasks")]
+/// runs the queued subscriber `name` of `event` with its serialized `payload`
+fn run_queued(
+    db: &mut Connection,
+    event: &str,
+    name: &str,
+    payload: serde_json::Value,
+) -> Result<(), String> {
+    let handler = subscribers_of(event)
+        .into_iter()
+        .find_map(|subscriber| match subscriber {
+            Subscriber::Queued {
+                name: subscriber,
+                handler,
+                ..
+            } if subscriber == name => Some(handler),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            format!(
+                "'{name}' isn't subscribed to '{event}' in this process: subscribe it at startup"
+            )
+        })?;
+
+    handler(db, payload)
+}
@@ -0,0 +1,46 @@
+use fang::serde::{Deserialize, Serialize};
+use fang::typetag;
+use fang::{FangError, Queueable, Runnable};
+
+use crate::Database;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "fang::serde")]
+/// runs a queued subscriber (see [`subscribe_queued`](`super::subscribe_queued`)) with the event
+/// it was published with, queued by [`publish`](`super::publish`)
+pub struct DispatchEvent {
+    pub event: String,
+    pub subscriber: String,
+    pub payload: serde_json::Value,
+}
+
+pub(super) fn enqueue(
+    event: &str,
+    subscriber: &str,
+    payload: serde_json::Value,
+) -> Result<(), String> {
+    crate::tasks::queue()
+        .insert_task(&DispatchEvent {
+            event: event.to_string(),
+            subscriber: subscriber.to_string(),
+            payload,
+        })
+        .map(|_| ())
+        .map_err(|err| format!("{err:?}"))
+}
+
+#[typetag::serde]
+impl Runnable for DispatchEvent {
+    fn run(&self, _queue: &dyn Queueable) -> Result<(), FangError> {
+        let mut db = Database::new().get_connection();
+
+        super::run_queued(&mut db, &self.event, &self.subscriber, self.payload.clone()).map_err(
+            |err| FangError {
+                description: format!(
+                    "Could not dispatch '{}' to '{}': {err}",
+                    self.event, self.subscriber
+                ),
+            },
+        )
+    }
+}
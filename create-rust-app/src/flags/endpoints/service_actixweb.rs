@@ -0,0 +1,117 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Json, Path};
+use actix_web::{delete, get, put, web, HttpResponse, Result};
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::flags::{FlagOverrides, Flags, ADMIN_PERMISSION};
+use crate::Database;
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the .../ endpoint
+///
+/// returns whether each flag is on for the current user (or for anonymous visitors), by key
+#[get("")]
+async fn evaluate(db: Data<Database>, auth: Option<Auth>) -> Result<HttpResponse> {
+    let user_id = auth.map(|auth| auth.user_id);
+
+    let flags =
+        web::block(move || Flags::global().evaluate_all(&mut db.get_connection(), user_id)).await?;
+
+    Ok(HttpResponse::Ok().json(flags))
+}
+
+/// handler for GET requests at the .../admin endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// returns the flags, by key, with their defaults and overrides
+#[get("/admin")]
+async fn list(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let result = web::block(move || Flags::global().overview(&mut db.get_connection())).await?;
+
+    match result {
+        Ok(flags) => Ok(HttpResponse::Ok().json(flags)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the flags.",
+        )),
+    }
+}
+
+/// handler for PUT requests at the .../admin/{key} endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// replaces the flag's overrides, see [`Flags::update`]
+#[put("/admin/{key}")]
+async fn update(
+    db: Data<Database>,
+    auth: Auth,
+    key: Path<String>,
+    Json(item): Json<FlagOverrides>,
+) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    if matches!(item.rollout_percentage, Some(percentage) if !(0..=100).contains(&percentage)) {
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "The rollout percentage must be between 0 and 100.",
+        ));
+    }
+
+    let key = key.into_inner();
+    let result =
+        web::block(move || Flags::global().update(&mut db.get_connection(), &key, &item)).await?;
+
+    match result {
+        Ok(Some(flag)) => Ok(HttpResponse::Ok().json(flag)),
+        Ok(None) => Ok(error_response(StatusCode::NOT_FOUND, "Unknown flag.")),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not update the flag.",
+        )),
+    }
+}
+
+/// handler for DELETE requests at the .../admin/{key} endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// deletes the flag, see [`Flags::delete`]
+#[delete("/admin/{key}")]
+async fn destroy(db: Data<Database>, auth: Auth, key: Path<String>) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let key = key.into_inner();
+    let result = web::block(move || Flags::global().delete(&mut db.get_connection(), &key)).await?;
+
+    match result {
+        Ok(0) => Ok(error_response(StatusCode::NOT_FOUND, "Unknown flag.")),
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not delete the flag.",
+        )),
+    }
+}
+
+/// returns the endpoints of the feature flags' API
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope
+        .service(evaluate)
+        .service(list)
+        .service(update)
+        .service(destroy)
+}
@@ -0,0 +1,135 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    web::{Data, Json, Path},
+    Error, IntoResponse, Result, Route,
+};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+use crate::auth::Auth;
+use crate::flags::{FlagOverrides, Flags, ADMIN_PERMISSION};
+use crate::Database;
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for GET requests at the .../ endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : whether each flag is on for the current user (or for anonymous visitors), by key
+async fn evaluate(db: Data<&Database>, auth: Option<Auth>) -> Json<BTreeMap<String, bool>> {
+    Json(Flags::global().evaluate_all(&mut db.get_connection(), auth.map(|auth| auth.user_id)))
+}
+
+#[handler]
+/// handler for GET requests at the .../admin endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`FlagOverview`](`crate::flags::FlagOverview`)s, by key
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch the flags."}
+async fn list(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    Flags::global()
+        .overview(&mut db.get_connection())
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the flags.",
+            )
+        })
+}
+
+#[handler]
+/// handler for PUT requests at the .../admin/:key endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// replaces the flag's overrides, see [`Flags::update`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the updated [`FeatureFlag`](`crate::flags::FeatureFlag`)
+/// | 400 | Json payload : {"message": "The rollout percentage must be between 0 and 100."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Unknown flag."}
+/// | 500 | Json payload : {"message": "Could not update the flag."}
+async fn update(
+    db: Data<&Database>,
+    auth: Auth,
+    Path(key): Path<String>,
+    Json(item): Json<FlagOverrides>,
+) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    if matches!(item.rollout_percentage, Some(percentage) if !(0..=100).contains(&percentage)) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "The rollout percentage must be between 0 and 100.",
+        ));
+    }
+
+    Flags::global()
+        .update(&mut db.get_connection(), &key, &item)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not update the flag.",
+            )
+        })?
+        .map(Json)
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Unknown flag."))
+}
+
+#[handler]
+/// handler for DELETE requests at the .../admin/:key endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// deletes the flag, see [`Flags::delete`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Unknown flag."}
+/// | 500 | Json payload : {"message": "Could not delete the flag."}
+async fn destroy(db: Data<&Database>, auth: Auth, Path(key): Path<String>) -> Result<StatusCode> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    match Flags::global().delete(&mut db.get_connection(), &key) {
+        Ok(0) => Err(error_response(StatusCode::NOT_FOUND, "Unknown flag.")),
+        Ok(_) => Ok(StatusCode::OK),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not delete the flag.",
+        )),
+    }
+}
+
+/// returns the endpoints of the feature flags' API
+pub fn api() -> Route {
+    Route::new()
+        .at("/", get(evaluate))
+        .at("/admin", get(list))
+        .at("/admin/:key", poem::put(update).delete(destroy))
+}
@@ -0,0 +1,483 @@
+//! Feature flags (`plugin_flags` feature): features turned on and off without a deploy, per
+//! environment, for a percentage of the users or for some of them.
+//!
+//! Flags are defined in code, with their default in each environment (`APP_ENV`, see
+//! [`environment`]), and registered when the app starts:
+//!
+//! ```rust,ignore
+//! create_rust_app::flags::register(&mut db, vec![
+//!     Flag::new("new-checkout")
+//!         .description("the one-page checkout")
+//!         .in_environment("development", true),
+//! ])?;
+//!
+//! if create_rust_app::flags::is_enabled(&mut db, "new-checkout", Some(auth.user_id)) {
+//!     // ...
+//! }
+//! ```
+//!
+//! Registering them records them in the `feature_flags` table, where the admin portal's "Feature
+//! flags" page overrides their default, rolls them out to a percentage of the users, or turns them
+//! on for given users (see [`FeatureFlag::evaluate`]). Rolled out flags are on for the same users
+//! as the percentage grows.
+//!
+//! The API (mount it like the other services) serves the frontend and the admin portal:
+//!
+//! - `GET /` returns every flag's value for the current user (or anonymous visitors)
+//! - `GET /admin` lists the flags, with their defaults and overrides
+//! - `PUT /admin/{key}` replaces a flag's overrides
+//! - `DELETE /admin/{key}` deletes a flag (ex: one no longer defined)
+//!
+//! The `/admin` endpoints require the [`ADMIN_PERMISSION`].
+use crate::diesel::*;
+use crate::Connection;
+use diesel::upsert::excluded;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// permission required to use the `/admin` endpoints
+pub const ADMIN_PERMISSION: &str = "admin:flags";
+
+/// how long the flags are read from memory before they're read from the database again, which is
+/// how long an override takes to reach the other instances of the app
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+lazy_static::lazy_static! {
+    static ref GLOBAL: Flags = Flags::new(environment());
+}
+
+/// the environment the app runs in: `APP_ENV`, or `development` in debug builds and `production`
+/// otherwise
+pub fn environment() -> String {
+    std::env::var("APP_ENV").unwrap_or_else(|_| {
+        if cfg!(debug_assertions) {
+            "development".to_string()
+        } else {
+            "production".to_string()
+        }
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// a flag, as defined in code
+pub struct Flag {
+    /// ex: `new-checkout`
+    pub key: String,
+    pub description: String,
+    /// whether the flag is on in the environments missing from [`environments`](`Flag::environments`)
+    pub enabled: bool,
+    /// whether the flag is on, by environment
+    pub environments: BTreeMap<String, bool>,
+}
+
+impl Flag {
+    /// a flag which is off everywhere
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            description: String::new(),
+            enabled: false,
+            environments: BTreeMap::new(),
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// whether the flag is on in the environments without their own default
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// whether the flag is on in `environment`, ex: `.in_environment("staging", true)`
+    pub fn in_environment(mut self, environment: impl Into<String>, enabled: bool) -> Self {
+        self.environments.insert(environment.into(), enabled);
+        self
+    }
+
+    /// whether the flag is on in `environment`, before any override
+    pub fn default_for(&self, environment: &str) -> bool {
+        self.environments
+            .get(environment)
+            .copied()
+            .unwrap_or(self.enabled)
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=feature_flags)]
+/// a flag's overrides, managed in the admin portal
+pub struct FeatureFlag {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    /// the [`Flag`]'s key
+    pub key: String,
+    /// the [`Flag`]'s description, as of its last registration
+    pub description: String,
+    /// overrides the [`Flag`]'s default when set
+    pub enabled: Option<bool>,
+    /// only the given percentage of the users (0 to 100) get the flag when set, anonymous
+    /// visitors don't (unless it's 100)
+    pub rollout_percentage: Option<i32>,
+    /// the json array of the users who get the flag in any case
+    pub user_ids: String,
+
+    pub created_at: Utc,
+    pub updated_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=feature_flags)]
+pub struct FeatureFlagChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub key: String,
+    pub description: String,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// a flag's overrides, which replace the current ones, see [`Flags::update`]
+pub struct FlagOverrides {
+    /// `None` to go back to the flag's default
+    pub enabled: Option<bool>,
+    /// from 0 to 100, `None` for everyone
+    pub rollout_percentage: Option<i32>,
+    #[serde(default)]
+    pub user_ids: Vec<ID>,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name=feature_flags, treat_none_as_null = true)]
+struct FeatureFlagOverrides {
+    enabled: Option<bool>,
+    rollout_percentage: Option<i32>,
+    user_ids: String,
+    updated_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// a [`FeatureFlag`] with its default, see [`Flags::overview`]
+pub struct FlagOverview {
+    pub flag: FeatureFlag,
+    /// whether it's defined in code, flags which aren't are always off
+    pub defined: bool,
+    /// its default in the current environment
+    pub default: bool,
+    /// its defaults by environment
+    pub environments: BTreeMap<String, bool>,
+}
+
+impl FeatureFlag {
+    /// the users who get the flag in any case
+    pub fn targeted_users(&self) -> Vec<ID> {
+        serde_json::from_str(&self.user_ids).unwrap_or_default()
+    }
+
+    /// whether the flag is on for [`user_id`](`ID`) (`None` for anonymous visitors), given its
+    /// `default`:
+    ///
+    /// 1. it's on for its [targeted users](`FeatureFlag::user_ids`)
+    /// 2. it's off if it's [turned off](`FeatureFlag::enabled`), or off by default
+    /// 3. it's on for its [rollout percentage](`FeatureFlag::rollout_percentage`) of the users
+    pub fn evaluate(&self, default: bool, user_id: Option<ID>) -> bool {
+        if let Some(user_id) = user_id {
+            if self.targeted_users().contains(&user_id) {
+                return true;
+            }
+        }
+
+        if !self.enabled.unwrap_or(default) {
+            return false;
+        }
+
+        match (self.rollout_percentage, user_id) {
+            (None, _) => true,
+            (Some(percentage), Some(user_id)) => bucket(&self.key, user_id) < percentage,
+            (Some(percentage), None) => percentage >= 100,
+        }
+    }
+
+    /// every flag, by key
+    pub fn list(db: &mut Connection) -> QueryResult<Vec<Self>> {
+        use schema::feature_flags::dsl::*;
+
+        feature_flags.order(key.asc()).load::<FeatureFlag>(db)
+    }
+
+    /// records `item`'s flag, or updates its description
+    fn register(db: &mut Connection, item: &FeatureFlagChangeset) -> QueryResult<usize> {
+        use schema::feature_flags::dsl::*;
+
+        insert_into(feature_flags)
+            .values(item)
+            .on_conflict(key)
+            .do_update()
+            .set(description.eq(excluded(description)))
+            .execute(db)
+    }
+
+    fn update(
+        db: &mut Connection,
+        item_key: &str,
+        item: &FeatureFlagOverrides,
+    ) -> QueryResult<Self> {
+        use schema::feature_flags::dsl::*;
+
+        diesel::update(feature_flags.filter(key.eq(item_key)))
+            .set(item)
+            .get_result::<FeatureFlag>(db)
+    }
+
+    fn delete(db: &mut Connection, item_key: &str) -> QueryResult<usize> {
+        use schema::feature_flags::dsl::*;
+
+        diesel::delete(feature_flags.filter(key.eq(item_key))).execute(db)
+    }
+}
+
+/// which of 100 buckets [`user_id`](`ID`) falls in for the flag `key` (FNV-1a): stable across
+/// releases, and different for each flag so the same users aren't always the first to get them
+fn bucket(key: &str, user_id: ID) -> i32 {
+    let hash = format!("{key}:{user_id}")
+        .bytes()
+        .fold(0x811c9dc5_u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+
+    (hash % 100) as i32
+}
+
+#[derive(Clone)]
+/// the flags defined in code, and their evaluation, see the [module documentation](`self`)
+pub struct Flags {
+    environment: String,
+    definitions: Arc<RwLock<BTreeMap<String, Flag>>>,
+    /// the [`FeatureFlag`]s, as read from the database at that time
+    cache: Arc<Mutex<Option<(Instant, Vec<FeatureFlag>)>>>,
+}
+
+impl std::fmt::Debug for Flags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Flags")
+            .field("environment", &self.environment)
+            .finish()
+    }
+}
+
+impl Flags {
+    /// flags evaluated in `environment`, see [`environment`]
+    pub fn new(environment: impl Into<String>) -> Self {
+        Self {
+            environment: environment.into(),
+            definitions: Arc::new(RwLock::new(BTreeMap::new())),
+            cache: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// the process' flags, used by [`register`], [`is_enabled`] and the endpoints
+    pub fn global() -> Self {
+        GLOBAL.clone()
+    }
+
+    pub fn environment(&self) -> &str {
+        &self.environment
+    }
+
+    /// defines `flags`, and records them in the database so they can be managed in the admin
+    /// portal; their overrides are kept
+    pub fn register(&self, db: &mut Connection, flags: Vec<Flag>) -> QueryResult<()> {
+        for flag in &flags {
+            FeatureFlag::register(
+                db,
+                &FeatureFlagChangeset {
+                    key: flag.key.clone(),
+                    description: flag.description.clone(),
+                },
+            )?;
+        }
+
+        let mut definitions = self.definitions.write().unwrap();
+        for flag in flags {
+            definitions.insert(flag.key.clone(), flag);
+        }
+        self.invalidate();
+
+        Ok(())
+    }
+
+    /// the [`FeatureFlag`]s, read from the database at most every few seconds
+    fn rows(&self, db: &mut Connection) -> QueryResult<Vec<FeatureFlag>> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((read_at, rows)) = cache.as_ref() {
+            if read_at.elapsed() < CACHE_TTL {
+                return Ok(rows.clone());
+            }
+        }
+
+        let rows = FeatureFlag::list(db)?;
+        *cache = Some((Instant::now(), rows.clone()));
+
+        Ok(rows)
+    }
+
+    fn invalidate(&self) {
+        *self.cache.lock().unwrap() = None;
+    }
+
+    /// whether the flag `key` is on for [`user_id`](`ID`) (`None` for anonymous visitors), see
+    /// [`FeatureFlag::evaluate`]
+    ///
+    /// falls back to the flag's default when the database can't be read; undefined flags are off
+    pub fn is_enabled(&self, db: &mut Connection, key: &str, user_id: Option<ID>) -> bool {
+        let rows = self.rows_or_defaults(db);
+        let definitions = self.definitions.read().unwrap();
+
+        match definitions.get(key) {
+            Some(flag) => self.evaluate(flag, &rows, user_id),
+            None => false,
+        }
+    }
+
+    /// whether each defined flag is on for [`user_id`](`ID`), by key
+    pub fn evaluate_all(&self, db: &mut Connection, user_id: Option<ID>) -> BTreeMap<String, bool> {
+        let rows = self.rows_or_defaults(db);
+        let definitions = self.definitions.read().unwrap();
+
+        definitions
+            .values()
+            .map(|flag| (flag.key.clone(), self.evaluate(flag, &rows, user_id)))
+            .collect()
+    }
+
+    fn evaluate(&self, flag: &Flag, rows: &[FeatureFlag], user_id: Option<ID>) -> bool {
+        let default = flag.default_for(&self.environment);
+
+        rows.iter()
+            .find(|row| row.key == flag.key)
+            .map(|row| row.evaluate(default, user_id))
+            .unwrap_or(default)
+    }
+
+    /// the [`FeatureFlag`]s, or none (so the defaults apply) when the database can't be read
+    fn rows_or_defaults(&self, db: &mut Connection) -> Vec<FeatureFlag> {
+        self.rows(db).unwrap_or_else(|err| {
+            println!("flags: could not read the flags, using the defaults: {err}");
+            vec![]
+        })
+    }
+
+    /// every recorded flag, by key, with its defaults
+    pub fn overview(&self, db: &mut Connection) -> QueryResult<Vec<FlagOverview>> {
+        let definitions = self.definitions.read().unwrap();
+
+        Ok(FeatureFlag::list(db)?
+            .into_iter()
+            .map(|flag| {
+                let definition = definitions.get(&flag.key);
+
+                FlagOverview {
+                    defined: definition.is_some(),
+                    default: definition
+                        .map(|definition| definition.default_for(&self.environment))
+                        .unwrap_or(false),
+                    environments: definition
+                        .map(|definition| definition.environments.clone())
+                        .unwrap_or_default(),
+                    flag,
+                }
+            })
+            .collect())
+    }
+
+    /// replaces the overrides of the flag `key`, returns `None` if there's no such flag, and an
+    /// error if the rollout percentage isn't between 0 and 100
+    pub fn update(
+        &self,
+        db: &mut Connection,
+        key: &str,
+        overrides: &FlagOverrides,
+    ) -> Result<Option<FeatureFlag>, String> {
+        if let Some(percentage) = overrides.rollout_percentage {
+            if !(0..=100).contains(&percentage) {
+                return Err("The rollout percentage must be between 0 and 100.".to_string());
+            }
+        }
+
+        let mut user_ids = overrides.user_ids.clone();
+        user_ids.sort_unstable();
+        user_ids.dedup();
+
+        let flag = FeatureFlag::update(
+            db,
+            key,
+            &FeatureFlagOverrides {
+                enabled: overrides.enabled,
+                rollout_percentage: overrides.rollout_percentage,
+                user_ids: serde_json::to_string(&user_ids).unwrap(),
+                updated_at: now(),
+            },
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+        self.invalidate();
+
+        Ok(flag)
+    }
+
+    /// deletes the flag `key` and its overrides; a flag still defined in code is recorded again
+    /// the next time it's registered
+    pub fn delete(&self, db: &mut Connection, key: &str) -> QueryResult<usize> {
+        let deleted = FeatureFlag::delete(db, key)?;
+        self.invalidate();
+
+        Ok(deleted)
+    }
+}
+
+/// defines `flags` in the [global](`Flags::global`) flags, see [`Flags::register`]
+pub fn register(db: &mut Connection, flags: Vec<Flag>) -> QueryResult<()> {
+    Flags::global().register(db, flags)
+}
+
+/// whether the flag `key` is on for [`user_id`](`ID`), see [`Flags::is_enabled`]
+pub fn is_enabled(db: &mut Connection, key: &str, user_id: Option<ID>) -> bool {
+    Flags::global().is_enabled(db, key, user_id)
+}
+
+#[cfg(not(feature = "database_sqlite"))]
+fn now() -> Utc {
+    chrono::Utc::now()
+}
+
+#[cfg(feature = "database_sqlite")]
+fn now() -> Utc {
+    chrono::Utc::now().naive_utc()
+}
@@ -0,0 +1,12 @@
+table! {
+  feature_flags (id) {
+      id -> Int4,
+      key -> Text,
+      description -> Text,
+      enabled -> Nullable<Bool>,
+      rollout_percentage -> Nullable<Int4>,
+      user_ids -> Text,
+      created_at -> Timestamptz,
+      updated_at -> Timestamptz,
+  }
+}
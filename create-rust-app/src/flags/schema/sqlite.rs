@@ -0,0 +1,12 @@
+table! {
+  feature_flags (id) {
+      id -> Integer,
+      key -> Text,
+      description -> Text,
+      enabled -> Nullable<Bool>,
+      rollout_percentage -> Nullable<Integer>,
+      user_ids -> Text,
+      created_at -> Timestamp,
+      updated_at -> Timestamp,
+  }
+}
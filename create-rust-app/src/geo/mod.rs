@@ -0,0 +1,220 @@
+//! PostGIS geometries (`plugin_geo` feature, requires Postgres with the `postgis` extension):
+//! `geometry` columns of WGS84 (longitude/latitude) coordinates are read and written as
+//! [`Geometry`]s, which serialize to GeoJSON geometries.
+//!
+//! ```rust,ignore
+//! // a `stores` table with a `location geometry(Point, 4326) NOT NULL` column
+//! use create_rust_app::geo::{self, Geometry};
+//!
+//! let here = Geometry::point(2.3522, 48.8566);
+//! // the stores within 5km, the nearest first
+//! let stores = stores::table
+//!     .filter(geo::within(stores::location, here.clone(), 5_000.0))
+//!     .order(geo::distance(stores::location, here))
+//!     .load::<Store>(&mut db)?;
+//! ```
+//!
+//! The schema types geometry columns with [`sql_types::Geometry`] (`import_types` in
+//! `diesel.toml`), and the models with [`Geometry`], which the frontend knows as a GeoJSON
+//! geometry (`frontend/src/types/geo.d.ts`). Distances are computed on the geographies of the
+//! columns: index them with `CREATE INDEX ... USING GIST (geography(column))`.
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::{Bool, Double};
+use postgis_diesel::types::{
+    GeometryContainer, LineString, MultiLineString, MultiPoint, MultiPolygon, Point, Polygon,
+};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// the SQL types of PostGIS' columns, imported by the schema (`import_types` in `diesel.toml`)
+pub mod sql_types {
+    pub use postgis_diesel::sql_types::{Geography, Geometry};
+}
+
+/// the SRID of WGS84 (longitude/latitude, in degrees), the coordinates of [`Geometry`]s
+pub const WGS84: u32 = 4326;
+
+/// `[longitude, latitude]`
+pub type Position = [f64; 2];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, AsExpression, FromSqlRow)]
+#[serde(tag = "type", content = "coordinates")]
+#[diesel(sql_type = sql_types::Geometry)]
+/// a geometry of WGS84 coordinates, (de)serialized as a GeoJSON geometry (ex:
+/// `{"type": "Point", "coordinates": [2.3522, 48.8566]}`)
+pub enum Geometry {
+    Point(Position),
+    LineString(Vec<Position>),
+    /// its exterior ring, then its holes
+    Polygon(Vec<Vec<Position>>),
+    MultiPoint(Vec<Position>),
+    MultiLineString(Vec<Vec<Position>>),
+    MultiPolygon(Vec<Vec<Vec<Position>>>),
+}
+
+impl Geometry {
+    pub fn point(longitude: f64, latitude: f64) -> Self {
+        Geometry::Point([longitude, latitude])
+    }
+}
+
+fn point(position: &Position) -> Point {
+    Point {
+        x: position[0],
+        y: position[1],
+        srid: Some(WGS84),
+    }
+}
+
+fn points(positions: &[Position]) -> Vec<Point> {
+    positions.iter().map(point).collect()
+}
+
+fn rings(rings: &[Vec<Position>]) -> Vec<Vec<Point>> {
+    rings.iter().map(|ring| points(ring)).collect()
+}
+
+fn positions(points: &[Point]) -> Vec<Position> {
+    points.iter().map(|point| [point.x, point.y]).collect()
+}
+
+fn ring_positions(rings: &[Vec<Point>]) -> Vec<Vec<Position>> {
+    rings.iter().map(|ring| positions(ring)).collect()
+}
+
+impl From<&Geometry> for GeometryContainer<Point> {
+    fn from(geometry: &Geometry) -> Self {
+        let srid = Some(WGS84);
+
+        match geometry {
+            Geometry::Point(position) => GeometryContainer::Point(point(position)),
+            Geometry::LineString(line) => GeometryContainer::LineString(LineString {
+                points: points(line),
+                srid,
+            }),
+            Geometry::Polygon(polygon) => GeometryContainer::Polygon(Polygon {
+                rings: rings(polygon),
+                srid,
+            }),
+            Geometry::MultiPoint(positions) => GeometryContainer::MultiPoint(MultiPoint {
+                points: points(positions),
+                srid,
+            }),
+            Geometry::MultiLineString(lines) => {
+                GeometryContainer::MultiLineString(MultiLineString {
+                    lines: lines
+                        .iter()
+                        .map(|line| LineString {
+                            points: points(line),
+                            srid,
+                        })
+                        .collect(),
+                    srid,
+                })
+            }
+            Geometry::MultiPolygon(polygons) => GeometryContainer::MultiPolygon(MultiPolygon {
+                polygons: polygons
+                    .iter()
+                    .map(|polygon| Polygon {
+                        rings: rings(polygon),
+                        srid,
+                    })
+                    .collect(),
+                srid,
+            }),
+        }
+    }
+}
+
+impl TryFrom<GeometryContainer<Point>> for Geometry {
+    type Error = String;
+
+    fn try_from(geometry: GeometryContainer<Point>) -> Result<Self, Self::Error> {
+        Ok(match geometry {
+            GeometryContainer::Point(point) => Geometry::Point([point.x, point.y]),
+            GeometryContainer::LineString(line) => Geometry::LineString(positions(&line.points)),
+            GeometryContainer::Polygon(polygon) => {
+                Geometry::Polygon(ring_positions(&polygon.rings))
+            }
+            GeometryContainer::MultiPoint(multi) => Geometry::MultiPoint(positions(&multi.points)),
+            GeometryContainer::MultiLineString(multi) => Geometry::MultiLineString(
+                multi
+                    .lines
+                    .iter()
+                    .map(|line| positions(&line.points))
+                    .collect(),
+            ),
+            GeometryContainer::MultiPolygon(multi) => Geometry::MultiPolygon(
+                multi
+                    .polygons
+                    .iter()
+                    .map(|polygon| ring_positions(&polygon.rings))
+                    .collect(),
+            ),
+            GeometryContainer::GeometryCollection(_) => {
+                return Err("Geometry collections aren't supported".to_string())
+            }
+        })
+    }
+}
+
+impl ToSql<sql_types::Geometry, Pg> for Geometry {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        let geometry = GeometryContainer::from(self);
+
+        ToSql::<sql_types::Geometry, Pg>::to_sql(&geometry, &mut out.reborrow())
+    }
+}
+
+impl FromSql<sql_types::Geometry, Pg> for Geometry {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let geometry =
+            <GeometryContainer<Point> as FromSql<sql_types::Geometry, Pg>>::from_sql(bytes)?;
+
+        Ok(Geometry::try_from(geometry)?)
+    }
+}
+
+diesel::sql_function! {
+    /// `geometry` as a geography, whose distances are in meters
+    #[sql_name = "geography"]
+    fn geography(geometry: sql_types::Geometry) -> sql_types::Geography;
+}
+
+diesel::sql_function! {
+    /// the distance between two geographies, in meters
+    #[sql_name = "ST_Distance"]
+    fn st_distance(a: sql_types::Geography, b: sql_types::Geography) -> Double;
+}
+
+diesel::sql_function! {
+    /// whether two geographies are within `meters` of each other
+    #[sql_name = "ST_DWithin"]
+    fn st_dwithin(a: sql_types::Geography, b: sql_types::Geography, meters: Double) -> Bool;
+}
+
+/// the distance between `geometry` (ex: a column) and `from`, in meters
+pub fn distance<G>(
+    geometry: G,
+    from: Geometry,
+) -> st_distance::HelperType<geography::HelperType<G>, geography::HelperType<Geometry>>
+where
+    G: AsExpression<sql_types::Geometry>,
+{
+    st_distance(geography(geometry), geography(from))
+}
+
+/// whether `geometry` (ex: a column) is within `meters` of `from`
+pub fn within<G>(
+    geometry: G,
+    from: Geometry,
+    meters: f64,
+) -> st_dwithin::HelperType<geography::HelperType<G>, geography::HelperType<Geometry>, f64>
+where
+    G: AsExpression<sql_types::Geometry>,
+{
+    st_dwithin(geography(geometry), geography(from), meters)
+}
@@ -0,0 +1,105 @@
+//! Authorization of individual fields and mutations, with the auth plugin's roles and
+//! permissions (the endpoint itself only requires a logged in user).
+//!
+//! ```rust,ignore
+//! #[Object]
+//! impl TodoMutation {
+//!     #[graphql(guard = "PermissionGuard::new(\"todos:delete\")")]
+//!     async fn delete_todo(&self, ctx: &Context<'_>, id: i32) -> Result<bool> {
+//!         // ...
+//!     }
+//!
+//!     #[graphql(guard = "RoleGuard::new(\"admin\").or(PermissionGuard::new(\"todos:write\"))")]
+//!     async fn update_todo(&self, ctx: &Context<'_>, id: i32, text: String) -> Result<TodoObject> {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! A rejected field resolves to `null` with an error, whose `code` extension is `UNAUTHENTICATED`
+//! when the request has no [`Auth`], or `FORBIDDEN` along with the missing `role` or
+//! `permission`. Resolvers make the same checks with [`require_role`] and [`require_permission`]
+//! (ex: when the requirement depends on the arguments).
+use async_graphql::{Context, Error, ErrorExtensions, Guard, Result};
+
+use crate::auth::Auth;
+
+/// the request's [`Auth`], fails with `UNAUTHENTICATED` without one
+pub fn authenticated<'a>(ctx: &Context<'a>) -> Result<&'a Auth> {
+    ctx.data_opt::<Auth>().ok_or_else(|| {
+        Error::new("Authentication required")
+            .extend_with(|_, extensions| extensions.set("code", "UNAUTHENTICATED"))
+    })
+}
+
+/// the request's [`Auth`], fails with `FORBIDDEN` unless the user has `role`
+pub fn require_role<'a>(ctx: &Context<'a>, role: &str) -> Result<&'a Auth> {
+    let auth = authenticated(ctx)?;
+
+    if !auth.has_role(role.to_string()) {
+        return Err(
+            Error::new(format!("The '{role}' role is required")).extend_with(|_, extensions| {
+                extensions.set("code", "FORBIDDEN");
+                extensions.set("role", role);
+            }),
+        );
+    }
+
+    Ok(auth)
+}
+
+/// the request's [`Auth`], fails with `FORBIDDEN` unless the user has `permission`
+pub fn require_permission<'a>(ctx: &Context<'a>, permission: &str) -> Result<&'a Auth> {
+    let auth = authenticated(ctx)?;
+
+    if !auth.has_permission(permission.to_string()) {
+        return Err(
+            Error::new(format!("The '{permission}' permission is required")).extend_with(
+                |_, extensions| {
+                    extensions.set("code", "FORBIDDEN");
+                    extensions.set("permission", permission);
+                },
+            ),
+        );
+    }
+
+    Ok(auth)
+}
+
+/// guards a field: the user must have the role, see [`require_role`]
+pub struct RoleGuard {
+    role: String,
+}
+
+impl RoleGuard {
+    pub fn new(role: impl Into<String>) -> Self {
+        Self { role: role.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl Guard for RoleGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        require_role(ctx, &self.role).map(|_| ())
+    }
+}
+
+/// guards a field: the user must have the permission, see [`require_permission`]
+pub struct PermissionGuard {
+    permission: String,
+}
+
+impl PermissionGuard {
+    pub fn new(permission: impl Into<String>) -> Self {
+        Self {
+            permission: permission.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Guard for PermissionGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        require_permission(ctx, &self.permission).map(|_| ())
+    }
+}
@@ -0,0 +1,14 @@
+//! Helpers for the GraphQL plugin's schema (`plugin_graphql` feature): Relay-style pagination of
+//! lists, see [`paginate`], authorization guards for fields and mutations, see [`RoleGuard`] and
+//! [`PermissionGuard`], persisted queries (and their allow-list), see [`PersistedQueries`], and
+//! file uploads stored as attachments, see `attach_upload` (with the storage plugin).
+mod guards;
+pub use guards::*;
+mod pagination;
+pub use pagination::*;
+mod persisted_queries;
+pub use persisted_queries::*;
+#[cfg(feature = "plugin_storage")]
+mod uploads;
+#[cfg(feature = "plugin_storage")]
+pub use uploads::*;
@@ -0,0 +1,111 @@
+//! Relay-style pagination.
+//!
+//! A paginated field returns a [`Connection`] (`edges { cursor node }`, `pageInfo` and
+//! `totalCount`) and takes the `first`/`after` and `last`/`before` arguments, which is what
+//! Relay-compliant clients and their pagination helpers expect (ex: Apollo's
+//! `relayStylePagination`). [`paginate`] turns these arguments into an offset and a limit:
+//!
+//! ```rust,ignore
+//! async fn todos(&self, ctx: &Context<'_>, after: Option<String>, before: Option<String>, first: Option<i32>, last: Option<i32>) -> Result<OffsetConnection<TodoObject>> {
+//!     let mut db = ctx.data::<Database>()?.get_connection();
+//!     let total = todos::table.count().get_result::<i64>(&mut db)?;
+//!
+//!     paginate(after, before, first, last, total, |offset, limit| {
+//!         let page = todos::table.order(todos::id).offset(offset).limit(limit).load::<Todo>(&mut db)?;
+//!         Ok(page.into_iter().map(TodoObject).collect())
+//!     })
+//!     .await
+//! }
+//! ```
+//!
+//! Cursors are the nodes' positions in the whole list, so the list's order must be stable (ex:
+//! order by the primary key).
+use async_graphql::connection::{query, Connection, Edge, EmptyFields};
+use async_graphql::{OutputType, Result, SimpleObject};
+
+/// the most nodes a page has, whatever `first` or `last` ask for
+pub const MAX_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, SimpleObject)]
+/// fields of every [`OffsetConnection`]
+pub struct ConnectionFields {
+    /// how many nodes there are, on every page
+    pub total_count: usize,
+}
+
+/// a page of `T`s, see [`paginate`]
+pub type OffsetConnection<T> = Connection<usize, T, ConnectionFields, EmptyFields>;
+
+/// resolves a connection field from its Relay arguments: `first` nodes `after` a cursor, or the
+/// `last` nodes `before` one (at most [`MAX_PAGE_SIZE`], which is also the page size when
+/// neither is given)
+///
+/// `total` is the number of nodes in the whole list, and `load(offset, limit)` loads a page of it
+pub async fn paginate<T, L>(
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    total: i64,
+    load: L,
+) -> Result<OffsetConnection<T>>
+where
+    T: OutputType,
+    L: FnOnce(i64, i64) -> Result<Vec<T>>,
+{
+    query(
+        after,
+        before,
+        first,
+        last,
+        |after: Option<usize>, before: Option<usize>, first, last| async move {
+            let total = usize::try_from(total).unwrap_or_default();
+            let (start, end) = window(total, after, before, first, last);
+
+            let nodes = if start < end {
+                load(start as i64, (end - start) as i64)?
+            } else {
+                vec![]
+            };
+
+            let mut connection = Connection::with_additional_fields(
+                start > 0,
+                end < total,
+                ConnectionFields { total_count: total },
+            );
+            connection.append(
+                nodes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, node)| Edge::new(start + index, node)),
+            );
+
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
+
+/// the nodes' positions (from `start` to `end`, excluded) the Relay arguments select
+fn window(
+    total: usize,
+    after: Option<usize>,
+    before: Option<usize>,
+    first: Option<usize>,
+    last: Option<usize>,
+) -> (usize, usize) {
+    let mut start = after.map_or(0, |after| after + 1).min(total);
+    let mut end = before.unwrap_or(total).clamp(start, total);
+
+    if let Some(first) = first {
+        end = end.min(start + first.min(MAX_PAGE_SIZE));
+    }
+    if let Some(last) = last {
+        start = start.max(end.saturating_sub(last.min(MAX_PAGE_SIZE)));
+    }
+    if first.is_none() && last.is_none() {
+        end = end.min(start + MAX_PAGE_SIZE);
+    }
+
+    (start, end)
+}
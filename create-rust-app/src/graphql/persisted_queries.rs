@@ -0,0 +1,194 @@
+//! [Automatic persisted queries](https://www.apollographql.com/docs/apollo-server/performance/apq/):
+//! clients send a query's SHA-256 hash instead of the query, and only send the whole query (once)
+//! when the server doesn't know the hash yet.
+//!
+//! In production, the registry can be locked down to an allow-list of known queries: only those are
+//! executed, whether they're sent by hash or in full, and nothing gets registered. The allow-list is
+//! a JSON manifest of `{"<sha256 hash>": "<query>"}`, like the one the registry exports in
+//! development (see [`PersistedQueries::manifest`]):
+//!
+//! ```rust,ignore
+//! // CRA_GRAPHQL_PERSISTED_QUERIES=persisted-queries.json enables the allow-list
+//! let persisted_queries = PersistedQueries::from_env();
+//! let schema = Schema::build(query, mutation, subscription)
+//!     .extension(persisted_queries.clone())
+//!     .finish();
+//! ```
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest,
+};
+use async_graphql::{Request, ServerError, ServerResult};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// how many queries the automatic registry remembers, the oldest are forgotten first
+pub const MAX_PERSISTED_QUERIES: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// the request's `persistedQuery` extension
+struct PersistedQuery {
+    version: i32,
+    sha256_hash: String,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    queries: HashMap<String, String>,
+    /// the hashes, oldest first
+    order: VecDeque<String>,
+    /// the allow-list's queries, `None` in automatic mode
+    allowed: Option<HashSet<String>>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// the persisted queries' registry, an extension of the schema; clones share it
+pub struct PersistedQueries {
+    registry: Arc<RwLock<Registry>>,
+}
+
+impl PersistedQueries {
+    /// registers the queries clients send (up to [`MAX_PERSISTED_QUERIES`]), any query is executed
+    pub fn automatic() -> Self {
+        Self::default()
+    }
+
+    /// only executes the `queries` (by their SHA-256 hash), nothing else gets registered
+    pub fn allow_list(queries: HashMap<String, String>) -> Self {
+        let allowed = queries.values().cloned().collect();
+
+        Self {
+            registry: Arc::new(RwLock::new(Registry {
+                order: queries.keys().cloned().collect(),
+                queries,
+                allowed: Some(allowed),
+            })),
+        }
+    }
+
+    /// the allow-list of the manifest at `path`, see [`PersistedQueries::manifest`]
+    pub fn from_manifest(path: &str) -> Result<Self, String> {
+        let manifest = std::fs::read_to_string(path)
+            .map_err(|err| format!("Could not read the persisted queries '{path}': {err}"))?;
+        let queries = serde_json::from_str::<HashMap<String, String>>(&manifest)
+            .map_err(|err| format!("Invalid persisted queries '{path}': {err}"))?;
+
+        Ok(Self::allow_list(queries))
+    }
+
+    /// the allow-list of the manifest in `CRA_GRAPHQL_PERSISTED_QUERIES`, or the automatic registry
+    /// when it isn't set
+    ///
+    /// panics if the manifest can't be read: the app mustn't start without its allow-list
+    pub fn from_env() -> Self {
+        match std::env::var("CRA_GRAPHQL_PERSISTED_QUERIES") {
+            Ok(path) if !path.is_empty() => Self::from_manifest(&path).unwrap(),
+            _ => Self::automatic(),
+        }
+    }
+
+    /// whether only the allow-list's queries are executed
+    pub fn is_allow_list(&self) -> bool {
+        self.registry.read().unwrap().allowed.is_some()
+    }
+
+    /// the known queries by hash, the allow-list's manifest format
+    pub fn manifest(&self) -> HashMap<String, String> {
+        self.registry.read().unwrap().queries.clone()
+    }
+
+    fn get(&self, hash: &str) -> Option<String> {
+        self.registry.read().unwrap().queries.get(hash).cloned()
+    }
+
+    fn is_allowed(&self, query: &str) -> bool {
+        match &self.registry.read().unwrap().allowed {
+            Some(allowed) => allowed.contains(query),
+            None => true,
+        }
+    }
+
+    fn register(&self, hash: String, query: String) {
+        let mut registry = self.registry.write().unwrap();
+        if registry.allowed.is_some() || registry.queries.contains_key(&hash) {
+            return;
+        }
+
+        while registry.order.len() >= MAX_PERSISTED_QUERIES {
+            if let Some(oldest) = registry.order.pop_front() {
+                registry.queries.remove(&oldest);
+            }
+        }
+        registry.order.push_back(hash.clone());
+        registry.queries.insert(hash, query);
+    }
+
+    /// `request` with its query, when it was sent by hash
+    fn resolve(&self, mut request: Request) -> ServerResult<Request> {
+        let persisted_query = match request.extensions.remove("persistedQuery") {
+            Some(value) => Some(
+                async_graphql::from_value::<PersistedQuery>(value)
+                    .map_err(|_| ServerError::new("Invalid \"persistedQuery\" extension.", None))?,
+            ),
+            None => None,
+        };
+
+        if let Some(persisted_query) = &persisted_query {
+            if persisted_query.version != 1 {
+                return Err(ServerError::new("PersistedQueryNotSupported", None));
+            }
+
+            if request.query.is_empty() {
+                // the client sends the whole query when it gets this error
+                let query = self
+                    .get(&persisted_query.sha256_hash)
+                    .ok_or_else(|| ServerError::new("PersistedQueryNotFound", None))?;
+
+                return Ok(Request { query, ..request });
+            }
+        }
+
+        let hash = format!("{:x}", Sha256::digest(request.query.as_bytes()));
+        if let Some(persisted_query) = &persisted_query {
+            if hash != persisted_query.sha256_hash {
+                return Err(ServerError::new(
+                    "The query doesn't match its persisted query hash.",
+                    None,
+                ));
+            }
+        }
+
+        if !self.is_allowed(&request.query) {
+            return Err(ServerError::new("PersistedQueryNotAllowed", None));
+        }
+
+        // queries sent in full are registered as well (ex: the websocket's subscriptions), so the
+        // manifest has every query the clients use
+        self.register(hash, request.query.clone());
+
+        Ok(request)
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(self.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for PersistedQueries {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let request = self.resolve(request)?;
+
+        next.run(ctx, request).await
+    }
+}
@@ -0,0 +1,117 @@
+//! File uploads, stored as attachments by the storage plugin (`plugin_storage` feature).
+//!
+//! The GraphQL endpoints accept the
+//! [multipart request spec](https://github.com/jaydenseric/graphql-multipart-request-spec): a
+//! mutation takes an `Upload` argument, and [`attach_upload`] stores the file as an attachment of
+//! a [`HasAttachments`] record.
+//!
+//! ```rust,ignore
+//! async fn attach_todo_file(&self, ctx: &Context<'_>, id: i32, name: String, file: Upload) -> Result<AttachmentObject> {
+//!     Ok(attach_upload::<Todo>(ctx, file, id, name, true, false).await?.into())
+//! }
+//! ```
+use std::io::Read;
+
+use async_graphql::{Context, Result, SimpleObject, Upload};
+
+use crate::auth::Auth;
+use crate::storage::{Attachment, AttachmentData, AttachmentInfo, HasAttachments, Storage};
+use crate::Database;
+
+type ID = i32;
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "Attachment")]
+/// an [`AttachmentInfo`] in the schema
+pub struct AttachmentObject {
+    pub id: ID,
+    pub name: String,
+    pub key: String,
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub byte_size: i64,
+    pub private: bool,
+    /// a [`ScanStatus`](`crate::storage::ScanStatus`)
+    pub scan_status: String,
+}
+
+impl From<AttachmentInfo> for AttachmentObject {
+    fn from(info: AttachmentInfo) -> Self {
+        Self {
+            id: info.id,
+            name: info.name,
+            key: info.key,
+            file_name: info.file_name,
+            content_type: info.content_type,
+            byte_size: info.byte_size,
+            private: info.private,
+            scan_status: info.scan_status,
+        }
+    }
+}
+
+/// stores `upload` as the `name` attachment of `R`'s record `record_id`, see
+/// [`Attachment::attach_to`]; the uploader is the request's user (if any), whom
+/// [`HasAttachments::can_write`] must allow
+///
+/// the schema's data must have the [`Database`] and the [`Storage`]
+pub async fn attach_upload<R: HasAttachments>(
+    ctx: &Context<'_>,
+    upload: Upload,
+    record_id: ID,
+    name: impl Into<String>,
+    allow_multiple: bool,
+    overwrite_existing: bool,
+) -> Result<AttachmentInfo> {
+    let database = ctx.data::<Database>()?;
+    let storage = ctx.data::<Storage>()?;
+    let user_id = ctx.data_opt::<Auth>().map(|auth| auth.user_id);
+
+    if !R::can_write(&mut database.get_connection(), record_id, user_id) {
+        return Err("You can't change this record's attachments.".into());
+    }
+
+    let upload = upload.value(ctx)?;
+    let file_name = upload.filename.clone();
+    let mut data = vec![];
+    upload.into_read().read_to_end(&mut data)?;
+
+    let data = AttachmentData {
+        data,
+        file_name: Some(file_name),
+    };
+
+    #[cfg(feature = "backend_actix-web")]
+    let key = {
+        let mut db = database.get_connection();
+        Attachment::attach_to::<R>(
+            &mut db,
+            storage,
+            record_id,
+            name.into(),
+            data,
+            user_id,
+            allow_multiple,
+            overwrite_existing,
+        )
+        .await?
+    };
+    #[cfg(feature = "backend_poem")]
+    let key = Attachment::attach_to::<R>(
+        std::sync::Arc::new(database.pool.as_ref()),
+        storage,
+        record_id,
+        name.into(),
+        data,
+        user_id,
+        allow_multiple,
+        overwrite_existing,
+    )
+    .await?;
+
+    let mut db = database.get_connection();
+    Attachment::list_for::<R>(&mut db, record_id)?
+        .into_iter()
+        .find(|attachment| attachment.key == key)
+        .ok_or_else(|| format!("Attachment '{key}' not found").into())
+}
@@ -0,0 +1,107 @@
+//! Helpers for the gRPC services of the app (`plugin_grpc` feature), served with
+//! [tonic](https://github.com/hyperium/tonic) alongside the HTTP server.
+//!
+//! ```rust,ignore
+//! tonic::transport::Server::builder()
+//!     .layer(tonic::service::interceptor(create_rust_app::grpc::AuthInterceptor::default()))
+//!     .add_service(todo::service(db.clone()))
+//!     .serve(create_rust_app::grpc::address())
+//!     .await
+//! ```
+//!
+//! The services share the HTTP server's [`Database`], running their queries with [`blocking`].
+//! With the auth plugin, the [`AuthInterceptor`] authenticates the requests sent with an
+//! `authorization: Bearer <access token>` metadata, the same access tokens as the HTTP API's, and
+//! [`auth`] gives the handlers the [`Auth`](`crate::auth::Auth`) of their request.
+use std::net::SocketAddr;
+use tonic::Status;
+
+use crate::{Connection, Database};
+
+/// where the gRPC server listens: `GRPC_ADDRESS`, `0.0.0.0:50051` by default
+pub fn address() -> SocketAddr {
+    std::env::var("GRPC_ADDRESS")
+        .ok()
+        .and_then(|address| address.parse().ok())
+        .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 50051)))
+}
+
+/// the [`Status`] of a failed query: `NOT_FOUND` for [`NotFound`](`diesel::result::Error::NotFound`),
+/// `INTERNAL` otherwise
+pub fn status_of(err: diesel::result::Error) -> Status {
+    match err {
+        diesel::result::Error::NotFound => Status::not_found("Not found."),
+        err => {
+            println!("grpc: query failed: {err}");
+            Status::internal("Internal error.")
+        }
+    }
+}
+
+/// runs `query` with a connection of `db` on tokio's blocking threads, so it doesn't hold up the
+/// other requests
+pub async fn blocking<T, F>(db: &Database, query: F) -> Result<T, Status>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> diesel::QueryResult<T> + Send + 'static,
+{
+    let pool = db.pool.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut db = pool
+            .get()
+            .map_err(|_| Status::unavailable("No database connection available."))?;
+
+        query(&mut db).map_err(status_of)
+    })
+    .await
+    .map_err(|_| Status::internal("Internal error."))?
+}
+
+#[cfg(feature = "plugin_auth")]
+#[derive(Clone)]
+/// authenticates the requests with an `authorization` metadata, rejecting them (`UNAUTHENTICATED`)
+/// when its access token is invalid; requests without one go through unauthenticated, see [`auth`]
+pub struct AuthInterceptor {
+    /// checked like the HTTP [`Auth`](`crate::auth::Auth`) extractor does, `None` skips it
+    pub revocation: Option<crate::auth::TokenRevocation>,
+}
+
+#[cfg(feature = "plugin_auth")]
+impl Default for AuthInterceptor {
+    fn default() -> Self {
+        Self {
+            revocation: Some(crate::auth::TokenRevocation::default()),
+        }
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+impl tonic::service::Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        let access_token = match request.metadata().get("authorization") {
+            Some(value) => value
+                .to_str()
+                .map_err(|_| Status::unauthenticated("Invalid authorization metadata"))?
+                .to_string(),
+            None => return Ok(request),
+        };
+
+        let auth = crate::auth::Auth::from_access_token(&access_token, self.revocation.as_ref())
+            .map_err(Status::unauthenticated)?;
+        request.extensions_mut().insert(auth);
+
+        Ok(request)
+    }
+}
+
+/// the [`Auth`](`crate::auth::Auth`) the [`AuthInterceptor`] authenticated the request with,
+/// `UNAUTHENTICATED` if it wasn't
+#[cfg(feature = "plugin_auth")]
+pub fn auth<T>(request: &tonic::Request<T>) -> Result<crate::auth::Auth, Status> {
+    request
+        .extensions()
+        .get::<crate::auth::Auth>()
+        .cloned()
+        .ok_or_else(|| Status::unauthenticated("Authorization required"))
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+/// the messages of a catalog file, by id
+///
+/// catalogs use a subset of [Fluent](https://projectfluent.org)'s syntax: `id = message` lines,
+/// indented lines continuing the previous message, `{ $name }` placeholders and `#` comments
+///
+/// ```text
+/// # the home page
+/// welcome = Welcome back, { $name }!
+/// signup-terms =
+///     By signing up, you agree to our terms.
+///     We won't share your email.
+/// ```
+pub fn parse(source: &str) -> Result<HashMap<String, String>, String> {
+    let mut messages: HashMap<String, String> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        // an indented line continues the current message
+        if line.starts_with(' ') || line.starts_with('\t') {
+            let id = current
+                .as_ref()
+                .ok_or_else(|| format!("line {line_number}: indented line without a message"))?;
+            let message = messages.entry(id.clone()).or_default();
+            if !message.is_empty() {
+                message.push('\n');
+            }
+            message.push_str(line.trim());
+            continue;
+        }
+
+        let (id, message) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {line_number}: expected `id = message`"))?;
+        let id = id.trim();
+        if !is_valid_id(id) {
+            return Err(format!("line {line_number}: invalid message id '{id}'"));
+        }
+        if messages.contains_key(id) {
+            return Err(format!("line {line_number}: duplicate message id '{id}'"));
+        }
+
+        messages.insert(id.to_string(), message.trim().to_string());
+        current = Some(id.to_string());
+    }
+
+    Ok(messages)
+}
+
+/// ids start with a letter, followed by letters, digits, `-` and `_`
+fn is_valid_id(id: &str) -> bool {
+    let mut chars = id.chars();
+
+    chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// `message` with its `{ $name }` placeholders replaced by the `args` of the same name, the
+/// others are kept as they are
+pub fn format(message: &str, args: &[(&str, &str)]) -> String {
+    let mut formatted = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(start) = rest.find('{') {
+        formatted.push_str(&rest[..start]);
+
+        let placeholder = &rest[start..];
+        let end = match placeholder.find('}') {
+            Some(end) => end,
+            None => {
+                rest = placeholder;
+                break;
+            }
+        };
+        let name = placeholder[1..end].trim();
+        let value = name
+            .strip_prefix('$')
+            .and_then(|name| args.iter().find(|(arg, _)| *arg == name))
+            .map(|(_, value)| *value);
+
+        match value {
+            Some(value) => formatted.push_str(value),
+            None => formatted.push_str(&placeholder[..=end]),
+        }
+        rest = &placeholder[end + 1..];
+    }
+    formatted.push_str(rest);
+
+    formatted
+}
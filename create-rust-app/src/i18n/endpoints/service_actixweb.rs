@@ -0,0 +1,111 @@
+use actix_http::header::{ACCEPT_LANGUAGE, COOKIE};
+use actix_http::StatusCode;
+use actix_web::dev::Payload;
+use actix_web::web::{Data, Path};
+use actix_web::{get, Error as AWError, FromRequest, HttpRequest, HttpResponse};
+use futures::future::LocalBoxFuture;
+use serde_json::json;
+
+#[cfg(feature = "plugin_auth")]
+use crate::i18n::cookie_locale;
+use crate::i18n::{preferences, Catalogs, I18n};
+
+/// the [`Catalogs`] registered in the app's data, or the global ones
+fn catalogs(catalogs: Option<&Data<Catalogs>>) -> Catalogs {
+    catalogs
+        .map(|catalogs| catalogs.get_ref().clone())
+        .unwrap_or_else(Catalogs::global)
+}
+
+impl FromRequest for I18n {
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+    type Error = AWError;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let catalogs = catalogs(req.app_data::<Data<Catalogs>>());
+        let header = |name| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+        };
+        let cookie = header(COOKIE);
+        let accept_language = header(ACCEPT_LANGUAGE);
+
+        // the cookie comes first, the profile is only read without it
+        #[cfg(feature = "plugin_auth")]
+        let profile = match cookie.as_deref().and_then(cookie_locale) {
+            Some(_) => None,
+            None => profile(req, _payload),
+        };
+
+        Box::pin(async move {
+            #[cfg(feature = "plugin_auth")]
+            let profile_locale = match profile {
+                Some((config, db, user_id)) => actix_web::web::block(move || {
+                    config
+                        .profile
+                        .as_ref()
+                        .and_then(|profiles| profiles.locale(&mut db.get_connection(), user_id))
+                })
+                .await
+                .ok()
+                .flatten(),
+                None => None,
+            };
+            #[cfg(not(feature = "plugin_auth"))]
+            let profile_locale = None;
+
+            let locale = catalogs.negotiate(preferences(
+                cookie.as_deref(),
+                profile_locale,
+                accept_language.as_deref(),
+            ));
+
+            Ok(I18n::new(catalogs, locale))
+        })
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+/// what reading the user's profile takes, if the request is authenticated and the app has profiles
+fn profile(
+    req: &HttpRequest,
+    payload: &mut Payload,
+) -> Option<(Data<crate::auth::AuthConfig>, Data<crate::Database>, i32)> {
+    let config = req.app_data::<Data<crate::auth::AuthConfig>>()?;
+    config.profile.as_ref()?;
+    let db = req.app_data::<Data<crate::Database>>()?;
+    let auth = crate::auth::Auth::from_request(req, payload)
+        .into_inner()
+        .ok()?;
+
+    Some((config.clone(), db.clone(), auth.user_id))
+}
+
+/// handler for GET requests at the .../ endpoint
+///
+/// returns the supported locales and the default one
+#[get("")]
+async fn locales(catalogs: Option<Data<Catalogs>>) -> HttpResponse {
+    HttpResponse::Ok().json(self::catalogs(catalogs.as_ref()).locales())
+}
+
+/// handler for GET requests at the .../{locale} endpoint
+///
+/// returns the messages of the `locale`'s catalog (completed with the default locale's), by id
+#[get("/{locale}")]
+async fn messages(catalogs: Option<Data<Catalogs>>, locale: Path<String>) -> HttpResponse {
+    let catalogs = self::catalogs(catalogs.as_ref());
+
+    match catalogs.supported(&locale) {
+        Some(locale) => HttpResponse::Ok().json(catalogs.messages(&locale)),
+        None => HttpResponse::build(StatusCode::NOT_FOUND)
+            .body(json!({ "message": "Unsupported locale." }).to_string()),
+    }
+}
+
+/// returns the endpoints serving the locales and their catalogs
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(locales).service(messages)
+}
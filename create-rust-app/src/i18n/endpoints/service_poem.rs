@@ -0,0 +1,89 @@
+use poem::http::header::{ACCEPT_LANGUAGE, COOKIE};
+use poem::http::StatusCode;
+use poem::web::{Data, Json, Path};
+use poem::{
+    async_trait, get, handler, Error, FromRequest, IntoResponse, Request, RequestBody, Result,
+    Route,
+};
+use serde_json::json;
+
+#[cfg(feature = "plugin_auth")]
+use crate::i18n::cookie_locale;
+use crate::i18n::{preferences, Catalogs, I18n};
+
+/// the [`Catalogs`] added to the app's data, or the global ones
+fn catalogs(catalogs: Option<&Catalogs>) -> Catalogs {
+    catalogs.cloned().unwrap_or_else(Catalogs::global)
+}
+
+#[async_trait]
+impl<'a> FromRequest<'a> for I18n {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let catalogs = catalogs(req.data::<Catalogs>());
+        let header = |name| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let cookie = header(COOKIE);
+
+        // the cookie comes first, the profile is only read without it
+        #[cfg(feature = "plugin_auth")]
+        let profile_locale = match cookie.and_then(cookie_locale) {
+            Some(_) => None,
+            None => profile_locale(req, _body).await,
+        };
+        #[cfg(not(feature = "plugin_auth"))]
+        let profile_locale = None;
+
+        let locale =
+            catalogs.negotiate(preferences(cookie, profile_locale, header(ACCEPT_LANGUAGE)));
+
+        Ok(I18n::new(catalogs, locale))
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+/// the `locale` of the user's profile, if the request is authenticated and the app has profiles
+async fn profile_locale(req: &Request, body: &mut RequestBody) -> Option<String> {
+    let config = req.data::<crate::auth::AuthConfig>()?;
+    let profiles = config.profile.as_ref()?;
+    let db = req.data::<crate::Database>()?;
+    let auth = crate::auth::Auth::from_request(req, body).await.ok()?;
+
+    profiles.locale(&mut db.get_connection(), auth.user_id)
+}
+
+#[handler]
+/// handler for GET requests at the .../ endpoint
+///
+/// returns the supported locales and the default one
+async fn locales(catalogs: Option<Data<&Catalogs>>) -> impl IntoResponse {
+    Json(self::catalogs(catalogs.map(|catalogs| catalogs.0)).locales())
+}
+
+#[handler]
+/// handler for GET requests at the .../:locale endpoint
+///
+/// returns the messages of the `locale`'s catalog (completed with the default locale's), by id
+async fn messages(
+    catalogs: Option<Data<&Catalogs>>,
+    Path(locale): Path<String>,
+) -> Result<impl IntoResponse> {
+    let catalogs = self::catalogs(catalogs.map(|catalogs| catalogs.0));
+
+    match catalogs.supported(&locale) {
+        Some(locale) => Ok(Json(catalogs.messages(&locale))),
+        None => Err(Error::from_string(
+            json!({ "message": "Unsupported locale." }).to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+    }
+}
+
+/// returns the endpoints serving the locales and their catalogs
+pub fn api() -> Route {
+    Route::new()
+        .at("/", get(locales))
+        .at("/:locale", get(messages))
+}
@@ -0,0 +1,273 @@
+//! Internationalization (`plugin_i18n` feature): message catalogs, one per locale, and the [`I18n`]
+//! extractor, which translates messages in the locale negotiated for the request.
+//!
+//! Catalogs are the `<locale>.ftl` files of `backend/locales` (or `CRA_LOCALES_DIR`), ex:
+//! `en.ftl`, `fr.ftl` or `pt-BR.ftl`, in a subset of Fluent's syntax (see [`catalog::parse`]):
+//!
+//! ```text
+//! welcome = Welcome back, { $name }!
+//! ```
+//!
+//! ```rust,ignore
+//! #[get("/greeting")]
+//! async fn greeting(i18n: I18n, auth: Auth) -> HttpResponse {
+//!     HttpResponse::Ok().body(i18n.t_args("welcome", &[("name", &auth.user_id.to_string())]))
+//! }
+//! ```
+//!
+//! The locale is the first of these the catalogs support (or, for `pt-BR`, `pt`): the `locale`
+//! cookie (set by the frontend when the user picks a language), the `locale` field of the user's
+//! profile (`plugin_auth` feature, see [`Profiles::locale`](`crate::auth::Profiles::locale`)), and
+//! the `Accept-Language` header. Otherwise, it's the default locale: `CRA_DEFAULT_LOCALE`, or `en`.
+//! Messages missing from a catalog are read from the default locale's.
+//!
+//! The endpoints serve the locales (`GET /`) and their catalogs (`GET /{locale}`), so the frontend
+//! uses the same ones.
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::util::workspace_utils::LOCALES_DIR;
+
+pub mod catalog;
+
+mod endpoints;
+pub use endpoints::*;
+
+/// the name of the cookie holding the locale the user picked
+pub const LOCALE_COOKIE: &str = "locale";
+
+lazy_static::lazy_static! {
+    static ref GLOBAL: Catalogs = Catalogs::from_env();
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// the locales the catalogs support, served by the endpoints
+pub struct Locales {
+    pub default_locale: String,
+    /// sorted, the default locale included
+    pub locales: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct CatalogsInner {
+    default_locale: String,
+    messages: HashMap<String, HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// the app's message catalogs, by locale
+pub struct Catalogs {
+    inner: Arc<CatalogsInner>,
+}
+
+impl Catalogs {
+    /// the `<locale>.ftl` files of `dir`
+    pub fn load(dir: impl AsRef<Path>, default_locale: &str) -> Result<Self, String> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir)
+            .map_err(|err| format!("Could not read '{}': {err}", dir.display()))?;
+
+        let mut messages = HashMap::new();
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("ftl") {
+                continue;
+            }
+            let locale = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(locale) => locale,
+                None => continue,
+            };
+
+            let source = std::fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read '{}': {err}", path.display()))?;
+            let catalog = catalog::parse(&source)
+                .map_err(|err| format!("Invalid catalog '{}': {err}", path.display()))?;
+            messages.insert(locale.to_string(), catalog);
+        }
+
+        Ok(Self::from_messages(default_locale, messages))
+    }
+
+    /// catalogs built in code, ex: for tests
+    pub fn from_messages(
+        default_locale: &str,
+        messages: HashMap<String, HashMap<String, String>>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(CatalogsInner {
+                default_locale: default_locale.to_string(),
+                messages,
+            }),
+        }
+    }
+
+    /// the catalogs of `backend/locales` (or `CRA_LOCALES_DIR`), the default locale being
+    /// `CRA_DEFAULT_LOCALE` (or `en`); none when they can't be read
+    pub fn from_env() -> Self {
+        let default_locale =
+            std::env::var("CRA_DEFAULT_LOCALE").unwrap_or_else(|_| "en".to_string());
+
+        Self::load(LOCALES_DIR.as_str(), &default_locale).unwrap_or_else(|err| {
+            println!("I18n: {err}");
+            Self::from_messages(&default_locale, HashMap::new())
+        })
+    }
+
+    /// the process' catalogs, used by the extractor and the endpoints unless the app registers
+    /// its own in its data
+    pub fn global() -> Self {
+        GLOBAL.clone()
+    }
+
+    pub fn default_locale(&self) -> &str {
+        &self.inner.default_locale
+    }
+
+    pub fn locales(&self) -> Locales {
+        let mut locales = self.inner.messages.keys().cloned().collect::<Vec<_>>();
+        if !locales.contains(&self.inner.default_locale) {
+            locales.push(self.inner.default_locale.clone());
+        }
+        locales.sort();
+
+        Locales {
+            default_locale: self.inner.default_locale.clone(),
+            locales,
+        }
+    }
+
+    /// the supported locale matching `locale` (case-insensitively), or its language's (`pt` for
+    /// `pt-BR`)
+    pub fn supported(&self, locale: &str) -> Option<String> {
+        let locale = locale.trim().replace('_', "-");
+        let find = |wanted: &str| {
+            self.inner
+                .messages
+                .keys()
+                .chain(std::iter::once(&self.inner.default_locale))
+                .find(|supported| supported.eq_ignore_ascii_case(wanted))
+                .cloned()
+        };
+
+        find(&locale).or_else(|| find(locale.split('-').next()?))
+    }
+
+    /// the first of `preferences` that's supported, or the default locale
+    pub fn negotiate<S: AsRef<str>>(&self, preferences: impl IntoIterator<Item = S>) -> String {
+        preferences
+            .into_iter()
+            .find_map(|locale| self.supported(locale.as_ref()))
+            .unwrap_or_else(|| self.inner.default_locale.clone())
+    }
+
+    /// the messages of `locale`'s catalog, completed with the default locale's
+    pub fn messages(&self, locale: &str) -> HashMap<String, String> {
+        let mut messages = self
+            .inner
+            .messages
+            .get(&self.inner.default_locale)
+            .cloned()
+            .unwrap_or_default();
+        if let Some(catalog) = self.inner.messages.get(locale) {
+            messages.extend(catalog.clone());
+        }
+
+        messages
+    }
+
+    /// the message `id` in `locale`, or in the default locale if it's missing
+    pub fn message(&self, locale: &str, id: &str) -> Option<&str> {
+        [locale, self.inner.default_locale.as_str()]
+            .iter()
+            .find_map(|locale| self.inner.messages.get(*locale)?.get(id))
+            .map(String::as_str)
+    }
+}
+
+/// the locales of an `Accept-Language` header, by preference
+pub fn accept_language(header: &str) -> Vec<String> {
+    let mut locales = header
+        .split(',')
+        .filter_map(|part| {
+            let mut params = part.split(';');
+            let locale = params.next()?.trim();
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|quality| quality.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            (!locale.is_empty() && locale != "*" && quality > 0.0)
+                .then(|| (locale.to_string(), quality))
+        })
+        .collect::<Vec<_>>();
+    // stable, so locales of the same quality keep their order
+    locales.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    locales.into_iter().map(|(locale, _)| locale).collect()
+}
+
+/// the [`LOCALE_COOKIE`] of a `Cookie` header
+fn cookie_locale(header: &str) -> Option<String> {
+    header
+        .split(';')
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == LOCALE_COOKIE)
+        .map(|(_, value)| value.trim_matches('"').to_string())
+        .filter(|locale| !locale.is_empty())
+}
+
+/// the locales the request asks for, by preference: the cookie's, the profile's, then the
+/// `Accept-Language` header's
+fn preferences(
+    cookie: Option<&str>,
+    profile_locale: Option<String>,
+    accept_language: Option<&str>,
+) -> Vec<String> {
+    cookie
+        .and_then(cookie_locale)
+        .into_iter()
+        .chain(profile_locale)
+        .chain(
+            accept_language
+                .map(self::accept_language)
+                .unwrap_or_default(),
+        )
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+/// translates messages in the locale negotiated for the request, see the
+/// [module documentation](`self`)
+pub struct I18n {
+    pub locale: String,
+    catalogs: Catalogs,
+}
+
+impl I18n {
+    pub fn new(catalogs: Catalogs, locale: impl Into<String>) -> Self {
+        Self {
+            locale: locale.into(),
+            catalogs,
+        }
+    }
+
+    /// the message `id`, or `id` itself if no catalog has it
+    pub fn t(&self, id: &str) -> String {
+        self.t_args(id, &[])
+    }
+
+    /// the message `id` with its `{ $name }` placeholders replaced by `args`
+    pub fn t_args(&self, id: &str, args: &[(&str, &str)]) -> String {
+        match self.catalogs.message(&self.locale, id) {
+            Some(message) => catalog::format(message, args),
+            None => id.to_string(),
+        }
+    }
+
+    pub fn catalogs(&self) -> &Catalogs {
+        &self.catalogs
+    }
+}
@@ -0,0 +1,241 @@
+//! Verification of the webhooks third-party services send to the app (`plugin_inbound_webhooks`
+//! feature).
+//!
+//! Handlers take a [`Verified`] request body instead of the raw one: the request is rejected
+//! (`400`, `{"message": "Invalid signature."}`) unless its provider's signature is valid.
+//!
+//! ```rust,ignore
+//! #[post("/github")]
+//! async fn github(webhook: Verified<GitHub>) -> HttpResponse {
+//!     let event: serde_json::Value = webhook.json()?;
+//!     ...
+//! }
+//! ```
+//!
+//! The providers' secrets are read from the environment ([`Stripe`]: `STRIPE_WEBHOOK_SECRET`,
+//! [`GitHub`]: `GITHUB_WEBHOOK_SECRET`, [`Slack`]: `SLACK_SIGNING_SECRET`), or from the
+//! [`WebhookSecrets`] registered in the app's data. Other providers are supported by implementing
+//! [`Provider`].
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+mod providers;
+pub use providers::{GitHub, Slack, Stripe};
+#[cfg(feature = "backend_actix-web")]
+mod verified_actixweb;
+#[cfg(feature = "backend_poem")]
+mod verified_poem;
+
+lazy_static::lazy_static! {
+    static ref GLOBAL: WebhookSecrets = WebhookSecrets::default();
+}
+
+/// a service sending webhooks, and how it signs them
+pub trait Provider: Send + Sync + 'static {
+    /// ex: `github`, the key of its secret in [`WebhookSecrets`]
+    const NAME: &'static str;
+    /// the environment variable its secret is read from, ex: `GITHUB_WEBHOOK_SECRET`
+    const SECRET_ENV: &'static str;
+
+    /// checks the signature of a request, whose headers are looked up with `header`
+    ///
+    /// `tolerance` is how old (in seconds) signed timestamps can be, for the providers signing
+    /// one (so captured requests can't be replayed later)
+    fn verify(
+        header: &dyn Fn(&str) -> Option<String>,
+        body: &[u8],
+        secret: &str,
+        tolerance: i64,
+    ) -> Result<(), String>;
+}
+
+#[derive(Debug, Clone)]
+/// the providers' secrets
+///
+/// register it in the app's data to set them in code, they're read from the environment otherwise
+pub struct WebhookSecrets {
+    secrets: HashMap<&'static str, String>,
+    /// how old a signed timestamp can be, in seconds (default: 300)
+    pub tolerance: i64,
+}
+
+impl Default for WebhookSecrets {
+    fn default() -> Self {
+        Self {
+            secrets: HashMap::new(),
+            tolerance: 300,
+        }
+    }
+}
+
+impl WebhookSecrets {
+    /// the secrets read from the environment, see [`Provider::SECRET_ENV`]
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    /// sets the secret of `P`
+    pub fn secret<P: Provider>(mut self, secret: impl Into<String>) -> Self {
+        self.secrets.insert(P::NAME, secret.into());
+        self
+    }
+
+    pub fn tolerance(mut self, seconds: i64) -> Self {
+        self.tolerance = seconds;
+        self
+    }
+
+    /// the secret of `P`, falls back to its environment variable
+    pub fn get<P: Provider>(&self) -> Option<String> {
+        self.secrets
+            .get(P::NAME)
+            .cloned()
+            .or_else(|| std::env::var(P::SECRET_ENV).ok())
+            .filter(|secret| !secret.is_empty())
+    }
+}
+
+/// a webhook's body, whose signature was verified (see [`Provider::verify`])
+pub struct Verified<P: Provider> {
+    body: Vec<u8>,
+    provider: PhantomData<fn() -> P>,
+}
+
+impl<P: Provider> std::fmt::Debug for Verified<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Verified")
+            .field("provider", &P::NAME)
+            .field("body", &String::from_utf8_lossy(&self.body))
+            .finish()
+    }
+}
+
+impl<P: Provider> Verified<P> {
+    /// verifies `body` with `P`'s secret in [`secrets`](`WebhookSecrets`)
+    pub fn new(
+        header: &dyn Fn(&str) -> Option<String>,
+        body: Vec<u8>,
+        secrets: &WebhookSecrets,
+    ) -> Result<Self, String> {
+        // anyone could sign with an empty secret
+        let secret = match secrets.get::<P>() {
+            Some(secret) => secret,
+            None => {
+                println!(
+                    "inbound webhooks: rejected a {} webhook, {} isn't set",
+                    P::NAME,
+                    P::SECRET_ENV
+                );
+                return Err(format!("No {} webhook secret configured", P::NAME));
+            }
+        };
+
+        P::verify(header, &body, &secret, secrets.tolerance)?;
+
+        Ok(Self {
+            body,
+            provider: PhantomData,
+        })
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+
+    /// deserializes the body
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+/// whether `signature` (hex) is the HMAC-SHA256 of `message` with `secret`, compared in constant
+/// time
+pub fn hmac_sha256_matches(secret: &str, message: &[u8], signature: &str) -> bool {
+    let signature = match decode_hex(signature) {
+        Some(signature) => signature,
+        None => return false,
+    };
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(message);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// whether `timestamp` (seconds since the epoch) is at most `tolerance` seconds away
+///
+/// timestamps too far off to subtract (ex: `i64::MIN` from a forged header) aren't recent
+fn is_recent(timestamp: i64, tolerance: i64) -> bool {
+    is_recent_at(chrono::Utc::now().timestamp(), timestamp, tolerance)
+}
+
+fn is_recent_at(now: i64, timestamp: i64, tolerance: i64) -> bool {
+    now.checked_sub(timestamp).map_or(false, |difference| {
+        difference
+            .checked_abs()
+            .map_or(false, |difference| difference <= tolerance)
+    })
+}
+
+/// the bytes of `hex`, `None` unless it's an even number of hex digits
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    // `from_str_radix` would accept a sign (ex: "+f")
+    if hex.len() % 2 != 0 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod inbound_webhooks_tests {
+    use super::{decode_hex, is_recent, is_recent_at};
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex(""), Some(vec![]));
+        assert_eq!(decode_hex("00ff7a"), Some(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(decode_hex("DEADbeef"), Some(vec![0xde, 0xad, 0xbe, 0xef]));
+    }
+
+    #[test]
+    fn test_decode_hex_rejects_invalid_input() {
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex("+f"), None);
+        assert_eq!(decode_hex("-1"), None);
+        assert_eq!(decode_hex("é1"), None);
+    }
+
+    #[test]
+    fn test_is_recent() {
+        assert!(is_recent_at(1_000, 1_000, 300));
+        assert!(is_recent_at(1_000, 700, 300));
+        assert!(is_recent_at(1_000, 1_300, 300));
+        assert!(!is_recent_at(1_000, 699, 300));
+        assert!(!is_recent_at(1_000, 1_301, 300));
+
+        let now = chrono::Utc::now().timestamp();
+        assert!(is_recent(now, 300));
+        assert!(!is_recent(now - 3_600, 300));
+    }
+
+    #[test]
+    fn test_is_recent_does_not_overflow() {
+        assert!(!is_recent_at(1_000, i64::MIN, 300));
+        assert!(!is_recent_at(-1, i64::MAX, 300));
+        assert!(!is_recent_at(i64::MIN, 0, i64::MAX));
+        assert!(!is_recent(i64::MIN, 300));
+        assert!(!is_recent(i64::MAX, 300));
+    }
+}
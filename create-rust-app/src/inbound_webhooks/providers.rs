@@ -0,0 +1,108 @@
+use super::{hmac_sha256_matches, is_recent, Provider};
+
+/// [Stripe](https://stripe.com/docs/webhooks/signatures): the `Stripe-Signature` header's `v1`
+/// signatures are HMAC-SHA256s of `<t>.<body>`, `t` being its timestamp
+pub struct Stripe;
+
+impl Provider for Stripe {
+    const NAME: &'static str = "stripe";
+    const SECRET_ENV: &'static str = "STRIPE_WEBHOOK_SECRET";
+
+    fn verify(
+        header: &dyn Fn(&str) -> Option<String>,
+        body: &[u8],
+        secret: &str,
+        tolerance: i64,
+    ) -> Result<(), String> {
+        let header = header("Stripe-Signature").ok_or_else(|| "No signature".to_string())?;
+
+        let mut timestamp = None;
+        let mut signatures = vec![];
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some(("v1", value)) => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let timestamp = timestamp.ok_or_else(|| "No timestamp in the signature".to_string())?;
+        if !is_recent(timestamp, tolerance) {
+            return Err("The signature's timestamp is too old".to_string());
+        }
+
+        let signed_payload = [timestamp.to_string().as_bytes(), b".", body].concat();
+        if signatures
+            .iter()
+            .any(|signature| hmac_sha256_matches(secret, &signed_payload, signature))
+        {
+            Ok(())
+        } else {
+            Err("No valid signature".to_string())
+        }
+    }
+}
+
+/// [GitHub](https://docs.github.com/en/webhooks/using-webhooks/validating-webhook-deliveries):
+/// the `X-Hub-Signature-256` header is `sha256=` the HMAC-SHA256 of the body (it isn't
+/// timestamped, use the `X-GitHub-Delivery` id to ignore replayed deliveries)
+pub struct GitHub;
+
+impl Provider for GitHub {
+    const NAME: &'static str = "github";
+    const SECRET_ENV: &'static str = "GITHUB_WEBHOOK_SECRET";
+
+    fn verify(
+        header: &dyn Fn(&str) -> Option<String>,
+        body: &[u8],
+        secret: &str,
+        _tolerance: i64,
+    ) -> Result<(), String> {
+        let header = header("X-Hub-Signature-256").ok_or_else(|| "No signature".to_string())?;
+        let signature = header
+            .strip_prefix("sha256=")
+            .ok_or_else(|| "Unknown signature scheme".to_string())?;
+
+        if hmac_sha256_matches(secret, body, signature) {
+            Ok(())
+        } else {
+            Err("Invalid signature".to_string())
+        }
+    }
+}
+
+/// [Slack](https://api.slack.com/authentication/verifying-requests-from-slack): the
+/// `X-Slack-Signature` header is `v0=` the HMAC-SHA256 of `v0:<timestamp>:<body>`, the timestamp
+/// being the `X-Slack-Request-Timestamp` header
+pub struct Slack;
+
+impl Provider for Slack {
+    const NAME: &'static str = "slack";
+    const SECRET_ENV: &'static str = "SLACK_SIGNING_SECRET";
+
+    fn verify(
+        header: &dyn Fn(&str) -> Option<String>,
+        body: &[u8],
+        secret: &str,
+        tolerance: i64,
+    ) -> Result<(), String> {
+        let timestamp = header("X-Slack-Request-Timestamp")
+            .and_then(|timestamp| timestamp.parse::<i64>().ok())
+            .ok_or_else(|| "No timestamp".to_string())?;
+        if !is_recent(timestamp, tolerance) {
+            return Err("The request's timestamp is too old".to_string());
+        }
+
+        let header = header("X-Slack-Signature").ok_or_else(|| "No signature".to_string())?;
+        let signature = header
+            .strip_prefix("v0=")
+            .ok_or_else(|| "Unknown signature scheme".to_string())?;
+
+        let signed_payload = [format!("v0:{timestamp}:").as_bytes(), body].concat();
+        if hmac_sha256_matches(secret, &signed_payload, signature) {
+            Ok(())
+        } else {
+            Err("Invalid signature".to_string())
+        }
+    }
+}
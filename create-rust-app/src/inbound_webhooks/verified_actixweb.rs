@@ -0,0 +1,43 @@
+use actix_http::StatusCode;
+use actix_web::dev::Payload;
+use actix_web::web::{Bytes, Data};
+use actix_web::{Error as AWError, FromRequest, HttpRequest, HttpResponse};
+use futures::future::LocalBoxFuture;
+use serde_json::json;
+
+use super::{Provider, Verified, WebhookSecrets};
+
+impl<P: Provider> FromRequest for Verified<P> {
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+    type Error = AWError;
+
+    /// reads the request's body, and verifies its signature with the [`WebhookSecrets`] of the
+    /// app's data (or the global ones)
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let secrets = req
+            .app_data::<Data<WebhookSecrets>>()
+            .map(|secrets| secrets.get_ref().clone())
+            .unwrap_or_else(|| WebhookSecrets::global().clone());
+        let headers = req.headers().clone();
+        let body = Bytes::from_request(req, payload);
+
+        Box::pin(async move {
+            let body = body.await?;
+            let header = |name: &str| {
+                headers
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(String::from)
+            };
+
+            Verified::new(&header, body.to_vec(), &secrets).map_err(|_| {
+                actix_web::error::InternalError::from_response(
+                    "Invalid signature.",
+                    HttpResponse::build(StatusCode::BAD_REQUEST)
+                        .body(json!({ "message": "Invalid signature." }).to_string()),
+                )
+                .into()
+            })
+        })
+    }
+}
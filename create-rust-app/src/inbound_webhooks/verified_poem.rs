@@ -0,0 +1,25 @@
+use poem::{async_trait, http::StatusCode, Error, FromRequest, Request, RequestBody, Result};
+use serde_json::json;
+
+use super::{Provider, Verified, WebhookSecrets};
+
+#[async_trait]
+impl<'a, P: Provider> FromRequest<'a> for Verified<P> {
+    /// reads the request's body, and verifies its signature with the [`WebhookSecrets`] of the
+    /// app's data (or the global ones)
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let secrets = req
+            .data::<WebhookSecrets>()
+            .cloned()
+            .unwrap_or_else(|| WebhookSecrets::global().clone());
+        let body = Vec::<u8>::from_request(req, body).await?;
+        let header = |name: &str| req.header(name).map(String::from);
+
+        Verified::new(&header, body, &secrets).map_err(|_| {
+            Error::from_string(
+                json!({ "message": "Invalid signature." }).to_string(),
+                StatusCode::BAD_REQUEST,
+            )
+        })
+    }
+}
@@ -0,0 +1,96 @@
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::time::Instant;
+
+/// records the requests it handles in the [inspector](`super`), in debug builds
+///
+/// ```rust,ignore
+/// let app = App::new().wrap(create_rust_app::inspector::RequestInspector);
+/// ```
+pub struct RequestInspector;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestInspector
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestInspectorMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestInspectorMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequestInspectorMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestInspectorMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            if !cfg!(debug_assertions) || !super::is_recorded(req.path()) {
+                return service.call(req).await;
+            }
+
+            let method = req.method().to_string();
+            let path = req.path().to_string();
+            let query = req.query_string().to_string();
+            let header = |name: &str| {
+                req.headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+            };
+            let user_id = super::user_id(header("Authorization"));
+            let request_size = super::content_length(header("Content-Length"));
+
+            let start = Instant::now();
+            let result = service.call(req).await;
+            let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+            let (status, response_size) = match &result {
+                Ok(response) => (
+                    response.status().as_u16(),
+                    match response.response().body().size() {
+                        BodySize::Sized(size) => Some(size),
+                        _ => None,
+                    },
+                ),
+                Err(err) => (err.as_response_error().status_code().as_u16(), None),
+            };
+
+            super::record(
+                &method,
+                &path,
+                Some(&query),
+                status,
+                latency_ms,
+                user_id,
+                request_size,
+                response_size,
+            );
+
+            result
+        })
+    }
+}
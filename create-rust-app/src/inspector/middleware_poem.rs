@@ -0,0 +1,73 @@
+use poem::{async_trait, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use std::time::Instant;
+
+/// records the requests it handles in the [inspector](`super`), in debug builds
+///
+/// ```rust,ignore
+/// let app = app.with(create_rust_app::inspector::RequestInspector);
+/// ```
+pub struct RequestInspector;
+
+impl<E: Endpoint> Middleware<E> for RequestInspector {
+    type Output = RequestInspectorEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RequestInspectorEndpoint { ep }
+    }
+}
+
+pub struct RequestInspectorEndpoint<E> {
+    ep: E,
+}
+
+#[async_trait]
+impl<E: Endpoint> Endpoint for RequestInspectorEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if !cfg!(debug_assertions) || !super::is_recorded(req.uri().path()) {
+            return self.ep.call(req).await.map(IntoResponse::into_response);
+        }
+
+        let method = req.method().to_string();
+        let path = req.uri().path().to_string();
+        let query = req.uri().query().map(str::to_string);
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let user_id = super::user_id(header("Authorization"));
+        let request_size = super::content_length(header("Content-Length"));
+
+        let start = Instant::now();
+        let result = self.ep.call(req).await.map(IntoResponse::into_response);
+        let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let (status, response_size) = match &result {
+            Ok(response) => (
+                response.status().as_u16(),
+                super::content_length(
+                    response
+                        .headers()
+                        .get("Content-Length")
+                        .and_then(|value| value.to_str().ok()),
+                ),
+            ),
+            Err(err) => (err.status().as_u16(), None),
+        };
+
+        super::record(
+            &method,
+            &path,
+            query.as_deref(),
+            status,
+            latency_ms,
+            user_id,
+            request_size,
+            response_size,
+        );
+
+        result
+    }
+}
@@ -0,0 +1,166 @@
+//! Request inspector (`plugin_dev` feature): in debug builds, the [`RequestInspector`] middleware
+//! records the app's recent HTTP requests (method, path, status, latency, user and body sizes),
+//! which are shown live in the admin portal's "Requests" page, and counts them in the
+//! [`metrics`](`crate::metrics`). In release builds it does nothing.
+//!
+//! Only the last 500 requests are kept (or `CRA_INSPECTOR_CAPACITY`), in memory.
+//!
+//! ```rust,ignore
+//! // actix-web
+//! let app = App::new().wrap(create_rust_app::inspector::RequestInspector);
+//! // poem
+//! let app = app.with(create_rust_app::inspector::RequestInspector);
+//! ```
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "backend_actix-web")]
+mod middleware_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use middleware_actixweb::RequestInspector;
+
+#[cfg(feature = "backend_poem")]
+mod middleware_poem;
+#[cfg(feature = "backend_poem")]
+pub use middleware_poem::RequestInspector;
+
+/// how many requests are kept, unless `CRA_INSPECTOR_CAPACITY` is set
+const DEFAULT_CAPACITY: usize = 500;
+
+lazy_static::lazy_static! {
+    static ref CAPACITY: usize = std::env::var("CRA_INSPECTOR_CAPACITY")
+        .ok()
+        .and_then(|capacity| capacity.parse().ok())
+        .unwrap_or(DEFAULT_CAPACITY);
+    static ref REQUESTS: Mutex<VecDeque<RecordedRequest>> = Mutex::new(VecDeque::new());
+}
+
+/// tells the recorded requests apart
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// an HTTP request handled by the app
+pub struct RecordedRequest {
+    /// newer requests have greater ids
+    pub id: u64,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub status: u16,
+    /// how long the app took to respond, in milliseconds
+    pub latency_ms: f64,
+    /// the user whose access token the request carried (`plugin_auth` feature)
+    pub user_id: Option<i32>,
+    /// from the request's `Content-Length`
+    pub request_size: Option<u64>,
+    /// the response body's size, when it's known before it's streamed
+    pub response_size: Option<u64>,
+    /// unix timestamp, in milliseconds
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// which requests [`list`] returns, every field is optional
+pub struct RequestFilter {
+    /// only the requests whose path starts with this (ex: "/api/todos")
+    pub path: Option<String>,
+    pub method: Option<String>,
+    /// only the requests whose status is at least this (ex: 400 for the failed ones)
+    pub min_status: Option<u16>,
+    /// only the requests newer than this id, to poll for new ones
+    pub after: Option<u64>,
+}
+
+/// the recorded requests matching `filter`, newest first
+pub fn list(filter: &RequestFilter) -> Vec<RecordedRequest> {
+    let requests = REQUESTS.lock().unwrap();
+
+    requests
+        .iter()
+        .rev()
+        .filter(|request| match &filter.path {
+            Some(path) => request.path.starts_with(path.as_str()),
+            None => true,
+        })
+        .filter(|request| match &filter.method {
+            Some(method) => request.method.eq_ignore_ascii_case(method),
+            None => true,
+        })
+        .filter(|request| request.status >= filter.min_status.unwrap_or(0))
+        .filter(|request| request.id > filter.after.unwrap_or(0))
+        .cloned()
+        .collect()
+}
+
+/// forgets the recorded requests
+pub fn clear() {
+    REQUESTS.lock().unwrap().clear();
+}
+
+/// whether a request to `path` is recorded: the APIs of the inspector and of the
+/// [`metrics`](`crate::metrics`) are polled by the admin portal, recording them would drown out
+/// the app's requests
+fn is_recorded(path: &str) -> bool {
+    !path.ends_with("/development/requests") && !path.ends_with("/development/metrics")
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record(
+    method: &str,
+    path: &str,
+    query: Option<&str>,
+    status: u16,
+    latency_ms: f64,
+    user_id: Option<i32>,
+    request_size: Option<u64>,
+    response_size: Option<u64>,
+) {
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    crate::metrics::record_request(status, latency_ms);
+
+    let mut requests = REQUESTS.lock().unwrap();
+    while requests.len() >= (*CAPACITY).max(1) {
+        requests.pop_front();
+    }
+    requests.push_back(RecordedRequest {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.filter(|query| !query.is_empty()).map(str::to_string),
+        status,
+        latency_ms,
+        user_id,
+        request_size,
+        response_size,
+        at,
+    });
+}
+
+/// the user whose access token is in the `Authorization` header, if it's valid
+#[cfg(feature = "plugin_auth")]
+fn user_id(authorization: Option<&str>) -> Option<i32> {
+    use crate::auth::{jwt, AccessTokenClaims};
+
+    let access_token =
+        jwt::decode_token::<AccessTokenClaims>(authorization?.strip_prefix("Bearer ")?).ok()?;
+
+    Some(access_token.claims.sub)
+}
+
+#[cfg(not(feature = "plugin_auth"))]
+fn user_id(_authorization: Option<&str>) -> Option<i32> {
+    None
+}
+
+/// the `Content-Length` header's value
+fn content_length(value: Option<&str>) -> Option<u64> {
+    value?.parse().ok()
+}
@@ -25,13 +25,121 @@ pub mod auth;
 #[cfg(feature = "plugin_tasks")]
 pub mod tasks;
 
+#[cfg(feature = "plugin_scheduler")]
+pub mod scheduler;
+
+#[cfg(feature = "plugin_realtime")]
+pub mod realtime;
+
+#[cfg(feature = "plugin_cache")]
+pub mod cache;
+
+#[cfg(feature = "plugin_rate_limit")]
+pub mod rate_limit;
+#[cfg(feature = "plugin_cache")]
+pub use cache::Cache;
+
+#[cfg(feature = "plugin_i18n")]
+pub mod i18n;
+
+#[cfg(feature = "plugin_metering")]
+pub mod metering;
+
+#[cfg(feature = "plugin_consent")]
+pub mod consent;
+
+#[cfg(feature = "plugin_notifications")]
+pub mod notifications;
+
+#[cfg(feature = "plugin_referrals")]
+pub mod referrals;
+
+#[cfg(feature = "plugin_payments")]
+pub mod payments;
+
+#[cfg(feature = "plugin_inbound_webhooks")]
+pub mod inbound_webhooks;
+
+#[cfg(feature = "plugin_observability")]
+pub mod observability;
+
+#[cfg(feature = "plugin_desktop")]
+pub mod desktop;
+
+#[cfg(feature = "plugin_prometheus")]
+pub mod prometheus;
+
+#[cfg(feature = "plugin_pwa")]
+pub mod pwa;
+
+#[cfg(feature = "plugin_rpc")]
+pub mod rpc;
+
+#[cfg(feature = "plugin_search")]
+pub mod search;
+
+#[cfg(feature = "plugin_sentry")]
+pub mod sentry;
+
+#[cfg(feature = "plugin_soft_delete")]
+pub mod soft_delete;
+
+#[cfg(feature = "plugin_events")]
+pub mod events;
+
+#[cfg(feature = "plugin_outbox")]
+pub mod outbox;
+
+#[cfg(feature = "plugin_broker")]
+pub mod broker;
+
+#[cfg(feature = "plugin_geo")]
+pub mod geo;
+
+#[cfg(feature = "plugin_flags")]
+pub mod flags;
+
+#[cfg(feature = "plugin_webhooks")]
+pub mod webhooks;
+
+#[cfg(feature = "plugin_mail_log")]
+pub mod mail_log;
+#[cfg(feature = "plugin_mail_queue")]
+pub mod mail_queue;
+
+#[cfg(feature = "plugin_audit")]
+pub mod audit;
+
+#[cfg(feature = "plugin_graphql")]
+pub mod graphql;
+
+#[cfg(feature = "plugin_grpc")]
+pub mod grpc;
+
+#[cfg(any(
+    feature = "plugin_admin",
+    all(feature = "plugin_dev", debug_assertions)
+))]
+pub mod admin;
+
 #[cfg(all(feature = "plugin_dev", debug_assertions))]
 pub mod dev;
+#[cfg(feature = "plugin_dev")]
+pub mod inspector;
+#[cfg(feature = "plugin_dev")]
+pub mod metrics;
 #[cfg(all(feature = "plugin_dev", debug_assertions))]
 pub use dev::setup_development;
 
 mod database;
+#[cfg(feature = "plugin_tenancy")]
+pub use database::tenancy;
 pub use database::{Connection, Database, Pool};
+pub mod seeds;
+
+pub mod build_info;
+
+pub mod sse;
 
 #[cfg(feature = "backend_poem")]
 mod logger;
@@ -40,11 +148,11 @@ mod logger;
 pub use logger::Logger as PoemLogger;
 
 #[cfg(feature = "plugin_storage")]
-mod storage;
+pub mod storage;
 #[cfg(feature = "plugin_storage")]
 pub use storage::{Attachment, AttachmentBlob, AttachmentData, Storage};
 
-mod mailer;
+pub mod mailer;
 pub use mailer::Mailer;
 #[cfg(feature = "plugin_auth")]
 pub use mailer::{DefaultMailTemplates, EmailTemplates};
@@ -69,6 +177,11 @@ pub struct AppData {
     ///
     /// see [`Storage`]
     pub storage: Storage,
+    #[cfg(feature = "plugin_cache")]
+    /// typed cache, in Redis or in memory
+    ///
+    /// see [`Cache`]
+    pub cache: Cache,
 }
 
 #[cfg(feature = "plugin_auth")]
@@ -77,7 +190,7 @@ impl AppData {
         mut self,
         templates: T,
     ) -> Self {
-        self.mailer = Mailer::new(Box::new(templates));
+        self.mailer.templates = Box::new(templates);
         self
     }
 }
@@ -95,7 +208,8 @@ fn load_env_vars() {
 
 /// ensures required environment variables are present,
 ///  
-/// initialize a [`Mailer`], [`Database`], and [`Storage`] (is `Storage` plugin was enabled ("plugin_storage" feature enabled))
+/// initialize a [`Mailer`], [`Database`], [`Storage`] (is `Storage` plugin was enabled ("plugin_storage" feature enabled)),
+/// and `Cache` (if the "plugin_cache" feature is enabled)
 ///
 /// and wraps them in a [`AppData`] struct that is then returned
 pub fn setup() -> AppData {
@@ -117,11 +231,26 @@ pub fn setup() -> AppData {
         panic!("No DATABASE_URL environment variable set!");
     }
 
+    let database = Database::new();
+
+    #[allow(unused_mut)]
+    let mut mailer = Mailer::default();
+    #[cfg(feature = "plugin_mail_queue")]
+    {
+        mailer = mailer.with_queue(database.clone());
+    }
+    #[cfg(feature = "plugin_mail_log")]
+    {
+        mailer = mailer.with_log(database.clone());
+    }
+
     AppData {
-        mailer: Mailer::default(),
-        database: Database::new(),
+        mailer,
+        database,
         #[cfg(feature = "plugin_storage")]
         storage: Storage::new(),
+        #[cfg(feature = "plugin_cache")]
+        cache: Cache::from_env(),
     }
 }
 
@@ -0,0 +1,94 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Path, Query};
+use actix_web::{get, web, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::mail_log::{EmailLogFilter, EmailStatus, LoggedEmail, ADMIN_PERMISSION};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../ endpoint
+pub struct ListQuery {
+    recipient: Option<String>,
+    template: Option<String>,
+    /// only list the emails with this [`EmailStatus`]
+    status: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the .../ endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// returns the logged emails, most recent first (50 per page by default)
+#[get("")]
+async fn list(
+    db: Data<Database>,
+    auth: Auth,
+    Query(query): Query<ListQuery>,
+) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let status = match query.status.as_deref().map(EmailStatus::parse) {
+        Some(None) => return Ok(error_response(StatusCode::BAD_REQUEST, "Unknown status.")),
+        Some(status) => status,
+        None => None,
+    };
+    let filter = EmailLogFilter {
+        recipient: query.recipient.filter(|recipient| !recipient.is_empty()),
+        template: query.template.filter(|template| !template.is_empty()),
+        status,
+    };
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    let result =
+        web::block(move || LoggedEmail::list(&mut db.get_connection(), &filter, page, page_size))
+            .await?;
+
+    match result {
+        Ok(emails) => Ok(HttpResponse::Ok().json(emails)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the email log.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../{id} endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+#[get("/{id}")]
+async fn read(db: Data<Database>, auth: Auth, item_id: Path<i32>) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let item_id = item_id.into_inner();
+    let result = web::block(move || LoggedEmail::read(&mut db.get_connection(), item_id)).await?;
+
+    match result {
+        Ok(email) => Ok(HttpResponse::Ok().json(email)),
+        Err(diesel::result::Error::NotFound) => {
+            Ok(error_response(StatusCode::NOT_FOUND, "Email not found."))
+        }
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the email log.",
+        )),
+    }
+}
+
+/// returns the endpoints of the email log's API
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(list).service(read)
+}
@@ -0,0 +1,109 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    web::{Data, Json, Path, Query},
+    Error, IntoResponse, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::mail_log::{EmailLogFilter, EmailStatus, LoggedEmail, ADMIN_PERMISSION};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../ endpoint
+pub struct ListQuery {
+    recipient: Option<String>,
+    template: Option<String>,
+    /// only list the emails with this [`EmailStatus`]
+    status: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for GET requests at the .../ endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`LoggedEmail`]s, most recent first (50 per page by default)
+/// | 400 | Json payload : {"message": "Unknown status."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch the email log."}
+async fn list(
+    db: Data<&Database>,
+    auth: Auth,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let status = match query.status.as_deref().map(EmailStatus::parse) {
+        Some(None) => return Err(error_response(StatusCode::BAD_REQUEST, "Unknown status.")),
+        Some(status) => status,
+        None => None,
+    };
+    let filter = EmailLogFilter {
+        recipient: query.recipient.filter(|recipient| !recipient.is_empty()),
+        template: query.template.filter(|template| !template.is_empty()),
+        status,
+    };
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    LoggedEmail::list(&mut db.get_connection(), &filter, page, page_size)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the email log.",
+            )
+        })
+}
+
+#[handler]
+/// handler for GET requests at the .../:id endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : [`LoggedEmail`]
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Email not found."}
+/// | 500 | Json payload : {"message": "Could not fetch the email log."}
+async fn read(
+    db: Data<&Database>,
+    auth: Auth,
+    Path(item_id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    match LoggedEmail::read(&mut db.get_connection(), item_id) {
+        Ok(email) => Ok(Json(email)),
+        Err(diesel::result::Error::NotFound) => {
+            Err(error_response(StatusCode::NOT_FOUND, "Email not found."))
+        }
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the email log.",
+        )),
+    }
+}
+
+/// returns the endpoints of the email log's API
+pub fn api() -> Route {
+    Route::new().at("/", get(list)).at("/:id", get(read))
+}
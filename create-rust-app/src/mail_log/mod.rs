@@ -0,0 +1,176 @@
+//! A log of the emails the app sends (`plugin_mail_log` feature), for support and deliverability
+//! debugging.
+//!
+//! Once the log is enabled on a [`Mailer`](`crate::Mailer`) (which [`setup`](`crate::setup`) does),
+//! every delivery is recorded in the `emails` table: its recipient, subject, template, [`EmailStatus`],
+//! the provider's message id and the error when it failed. Email contents aren't kept.
+//!
+//! ```rust,ignore
+//! let emails = LoggedEmail::list(
+//!     &mut db,
+//!     &EmailLogFilter { recipient: Some("a@b.com".to_string()), ..Default::default() },
+//!     0,
+//!     50,
+//! )?;
+//! ```
+//!
+//! The API (mount it like the other services) requires the [`ADMIN_PERMISSION`], and backs the
+//! admin portal's "Emails" page:
+//!
+//! - `GET /` lists the logged emails, filtered by `?recipient=`, `?template=` and `?status=`
+//! - `GET /{id}` reads one
+use crate::diesel::*;
+use crate::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// permission required to use the API
+pub const ADMIN_PERMISSION: &str = "admin:mail";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// how a [`LoggedEmail`]'s delivery went
+pub enum EmailStatus {
+    /// accepted by the transport
+    Sent,
+    /// the transport returned an error, see [`LoggedEmail::error`]
+    Failed,
+    /// not sent because `SEND_MAIL` isn't `true`
+    Skipped,
+}
+
+impl EmailStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sent" => Some(Self::Sent),
+            "failed" => Some(Self::Failed),
+            "skipped" => Some(Self::Skipped),
+            _ => None,
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=emails)]
+pub struct LoggedEmail {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub recipient: String,
+    pub subject: String,
+    /// the template it was rendered from, see [`Mailer::send_template`](`crate::Mailer::send_template`)
+    pub template: Option<String>,
+    /// one of [`EmailStatus`]
+    pub status: String,
+    /// the id the email provider gave the message, when it gives one
+    pub provider_message_id: Option<String>,
+    /// why the delivery failed
+    pub error: Option<String>,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=emails)]
+pub struct LoggedEmailChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub recipient: String,
+    pub subject: String,
+    pub template: Option<String>,
+    pub status: String,
+    pub provider_message_id: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+/// which emails [`LoggedEmail::list`] returns, every field is optional
+pub struct EmailLogFilter {
+    pub recipient: Option<String>,
+    pub template: Option<String>,
+    pub status: Option<EmailStatus>,
+}
+
+impl LoggedEmail {
+    /// Create an entry in [`db`](`Connection`)'s `emails` table using the data in [`item`](`LoggedEmailChangeset`)
+    pub fn record(db: &mut Connection, item: &LoggedEmailChangeset) -> QueryResult<Self> {
+        use schema::emails::dsl::*;
+
+        insert_into(emails)
+            .values(item)
+            .get_result::<LoggedEmail>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `emails`
+    /// who's primary key matches [`item_id`](`ID`)
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use schema::emails::dsl::*;
+
+        emails.filter(id.eq(item_id)).first::<LoggedEmail>(db)
+    }
+
+    /// the logged emails matching `filter`, most recent first
+    pub fn list(
+        db: &mut Connection,
+        filter: &EmailLogFilter,
+        page: i64,
+        page_size: i64,
+    ) -> QueryResult<Vec<Self>> {
+        use schema::emails::dsl::*;
+
+        let mut query = emails.into_boxed();
+        if let Some(item_recipient) = &filter.recipient {
+            query = query.filter(recipient.eq(item_recipient.clone()));
+        }
+        if let Some(item_template) = &filter.template {
+            query = query.filter(template.eq(item_template.clone()));
+        }
+        if let Some(item_status) = filter.status {
+            query = query.filter(status.eq(item_status.as_str()));
+        }
+
+        query
+            .order((created_at.desc(), id.desc()))
+            .limit(page_size)
+            .offset(page * page_size)
+            .load::<LoggedEmail>(db)
+    }
+
+    /// deletes the emails logged before `before`, returns how many there were
+    ///
+    /// the log isn't pruned automatically, call this periodically (ex: from a scheduled task) to
+    /// keep it from growing forever
+    pub fn delete_before(db: &mut Connection, before: Utc) -> QueryResult<usize> {
+        use schema::emails::dsl::*;
+
+        diesel::delete(emails.filter(created_at.lt(before))).execute(db)
+    }
+}
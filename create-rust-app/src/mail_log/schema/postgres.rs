@@ -0,0 +1,12 @@
+table! {
+  emails (id) {
+      id -> Int4,
+      recipient -> Text,
+      subject -> Text,
+      template -> Nullable<Text>,
+      status -> Text,
+      provider_message_id -> Nullable<Text>,
+      error -> Nullable<Text>,
+      created_at -> Timestamptz,
+  }
+}
@@ -0,0 +1,12 @@
+table! {
+  emails (id) {
+      id -> Integer,
+      recipient -> Text,
+      subject -> Text,
+      template -> Nullable<Text>,
+      status -> Text,
+      provider_message_id -> Nullable<Text>,
+      error -> Nullable<Text>,
+      created_at -> Timestamp,
+  }
+}
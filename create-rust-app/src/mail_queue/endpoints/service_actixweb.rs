@@ -0,0 +1,112 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Path, Query};
+use actix_web::{get, post, web, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::mail_queue::{MailStatus, QueuedMail, ADMIN_PERMISSION};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../ endpoint
+pub struct ListQuery {
+    /// only list the emails with this [`MailStatus`]
+    status: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the .../ endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// returns the queued emails, most recent first (50 per page by default)
+#[get("")]
+async fn list(
+    db: Data<Database>,
+    auth: Auth,
+    Query(query): Query<ListQuery>,
+) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let status = match query.status.as_deref().map(MailStatus::parse) {
+        Some(None) => return Ok(error_response(StatusCode::BAD_REQUEST, "Unknown status.")),
+        Some(status) => status,
+        None => None,
+    };
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    let result =
+        web::block(move || QueuedMail::list(&mut db.get_connection(), status, page, page_size))
+            .await?;
+
+    match result {
+        Ok(mails) => Ok(HttpResponse::Ok().json(mails)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the mail queue.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../stats endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// returns how many emails are pending, sent and dead-lettered
+#[get("/stats")]
+async fn stats(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let result = web::block(move || QueuedMail::stats(&mut db.get_connection())).await?;
+
+    match result {
+        Ok(stats) => Ok(HttpResponse::Ok().json(stats)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the mail queue.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../{id}/retry endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// puts a dead-lettered email back in the queue
+#[post("/{id}/retry")]
+async fn retry(db: Data<Database>, auth: Auth, item_id: Path<i32>) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let item_id = item_id.into_inner();
+    let result = web::block(move || QueuedMail::retry(&mut db.get_connection(), item_id)).await?;
+
+    match result {
+        Ok(0) => Ok(error_response(
+            StatusCode::NOT_FOUND,
+            "No dead-lettered email with this id.",
+        )),
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not retry the email.",
+        )),
+    }
+}
+
+/// returns the endpoints of the mail queue's status API
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(list).service(stats).service(retry)
+}
@@ -0,0 +1,131 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    post,
+    web::{Data, Json, Path, Query},
+    Error, IntoResponse, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::mail_queue::{MailStatus, QueuedMail, ADMIN_PERMISSION};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../ endpoint
+pub struct ListQuery {
+    /// only list the emails with this [`MailStatus`]
+    status: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for GET requests at the .../ endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`QueuedMail`]s, most recent first (50 per page by default)
+/// | 400 | Json payload : {"message": "Unknown status."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch the mail queue."}
+async fn list(
+    db: Data<&Database>,
+    auth: Auth,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let status = match query.status.as_deref().map(MailStatus::parse) {
+        Some(None) => return Err(error_response(StatusCode::BAD_REQUEST, "Unknown status.")),
+        Some(status) => status,
+        None => None,
+    };
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    QueuedMail::list(&mut db.get_connection(), status, page, page_size)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the mail queue.",
+            )
+        })
+}
+
+#[handler]
+/// handler for GET requests at the .../stats endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : [`MailQueueStats`](`crate::mail_queue::MailQueueStats`)
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch the mail queue."}
+async fn stats(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    QueuedMail::stats(&mut db.get_connection())
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the mail queue.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../:id/retry endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// puts a dead-lettered email back in the queue
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "No dead-lettered email with this id."}
+/// | 500 | Json payload : {"message": "Could not retry the email."}
+async fn retry(db: Data<&Database>, auth: Auth, Path(item_id): Path<i32>) -> Result<()> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    match QueuedMail::retry(&mut db.get_connection(), item_id) {
+        Ok(0) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            "No dead-lettered email with this id.",
+        )),
+        Ok(_) => Ok(()),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not retry the email.",
+        )),
+    }
+}
+
+/// returns the endpoints of the mail queue's status API
+pub fn api() -> Route {
+    Route::new()
+        .at("/", get(list))
+        .at("/stats", get(stats))
+        .at("/:id/retry", post(retry))
+}
@@ -0,0 +1,390 @@
+//! A persistent queue of outgoing emails (`plugin_mail_queue` feature).
+//!
+//! Once the queue is enabled on a [`Mailer`] (which [`setup`](`crate::setup`) does), calls to
+//! [`Mailer::send`] store the email in the `mail_queue` table and return right away, and a
+//! background worker ([`spawn_worker`]) delivers it. Failed deliveries are retried with an
+//! exponential backoff (see [`MailQueueConfig`]). Emails still failing after
+//! [`MailQueueConfig::max_attempts`] are dead-lettered: they're kept with the `dead` status until
+//! they're retried with [`QueuedMail::retry`].
+//!
+//! ```rust,ignore
+//! let app_data = create_rust_app::setup();
+//! create_rust_app::mail_queue::spawn_worker(app_data.database.clone(), MailQueueConfig::default());
+//! ```
+//!
+//! The status API (mount it like the other services) requires the [`ADMIN_PERMISSION`]:
+//!
+//! - `GET /` lists the queued emails, `?status=dead` only lists the dead-lettered ones
+//! - `GET /stats` counts them by status
+//! - `POST /{id}/retry` puts a dead-lettered email back in the queue
+use crate::diesel::*;
+use crate::mailer::Email;
+use crate::{Connection, Database, Mailer};
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// permission required to use the status API
+pub const ADMIN_PERMISSION: &str = "admin:mail";
+
+/// how long an email is hidden from other workers while one of them delivers it
+const DELIVERY_LEASE_MINUTES: i64 = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// where a [`QueuedMail`] is in its delivery
+pub enum MailStatus {
+    /// waiting for its first delivery, or for a retry
+    Pending,
+    Sent,
+    /// failed [`MailQueueConfig::max_attempts`] times
+    Dead,
+}
+
+impl MailStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Dead => "dead",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "sent" => Some(Self::Sent),
+            "dead" => Some(Self::Dead),
+            _ => None,
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=mail_queue)]
+pub struct QueuedMail {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub to_address: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+    /// the mail templates it was rendered from, for the [`mail_log`](`crate::mail_log`)
+    pub template: Option<String>,
+    /// one of [`MailStatus`]
+    pub status: String,
+    /// how many deliveries failed
+    pub attempts: i32,
+    /// the error of the last failed delivery
+    pub last_error: Option<String>,
+    /// when the worker (re)tries to deliver the email
+    pub next_attempt_at: Utc,
+    pub sent_at: Option<Utc>,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=mail_queue)]
+pub struct QueuedMailChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub to_address: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+    pub template: Option<String>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+/// how many queued emails there are of each [`MailStatus`]
+pub struct MailQueueStats {
+    pub pending: i64,
+    pub sent: i64,
+    pub dead: i64,
+}
+
+#[derive(Debug, Clone)]
+/// how the worker delivers queued emails
+pub struct MailQueueConfig {
+    /// how often the worker looks for emails to deliver (default: 5 seconds)
+    pub poll_interval: Duration,
+    /// the maximum number of emails delivered per poll (default: 50)
+    pub batch_size: i64,
+    /// how many times an email is tried before it's dead-lettered (default: 8)
+    pub max_attempts: i32,
+    /// how long to wait before the first retry, doubled after every failure (default: 30 seconds)
+    pub base_delay: Duration,
+    /// the longest wait between two retries (default: 6 hours)
+    pub max_delay: Duration,
+}
+
+impl Default for MailQueueConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            batch_size: 50,
+            max_attempts: 8,
+            base_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(6 * 60 * 60),
+        }
+    }
+}
+
+impl MailQueueConfig {
+    /// how long to wait before retrying an email which failed `attempts` times
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let exponent = attempts.saturating_sub(1).clamp(0, 31) as u32;
+
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_delay)
+    }
+}
+
+impl QueuedMail {
+    /// Create an entry in [`db`](`Connection`)'s `mail_queue` table using the data in [`item`](`QueuedMailChangeset`)
+    ///
+    /// the email is delivered by the next poll of the worker
+    pub fn enqueue(db: &mut Connection, item: &QueuedMailChangeset) -> QueryResult<Self> {
+        use schema::mail_queue::dsl::*;
+
+        insert_into(mail_queue)
+            .values(item)
+            .get_result::<QueuedMail>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `mail_queue`
+    /// who's primary key matches [`item_id`](`ID`)
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use schema::mail_queue::dsl::*;
+
+        mail_queue.filter(id.eq(item_id)).first::<QueuedMail>(db)
+    }
+
+    /// the queued emails, most recent first, optionally only those of [`item_status`](`MailStatus`)
+    pub fn list(
+        db: &mut Connection,
+        item_status: Option<MailStatus>,
+        page: i64,
+        page_size: i64,
+    ) -> QueryResult<Vec<Self>> {
+        use schema::mail_queue::dsl::*;
+
+        let mut query = mail_queue.into_boxed();
+        if let Some(item_status) = item_status {
+            query = query.filter(status.eq(item_status.as_str()));
+        }
+
+        query
+            .order(created_at.desc())
+            .limit(page_size)
+            .offset(page * page_size)
+            .load::<QueuedMail>(db)
+    }
+
+    /// counts the queued emails by status
+    pub fn stats(db: &mut Connection) -> QueryResult<MailQueueStats> {
+        use schema::mail_queue::dsl::*;
+
+        let counts = mail_queue
+            .group_by(status)
+            .select((status, diesel::dsl::count_star()))
+            .load::<(String, i64)>(db)?;
+
+        let mut stats = MailQueueStats::default();
+        for (item_status, count) in counts {
+            match MailStatus::parse(&item_status) {
+                Some(MailStatus::Pending) => stats.pending = count,
+                Some(MailStatus::Sent) => stats.sent = count,
+                Some(MailStatus::Dead) => stats.dead = count,
+                None => {}
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// puts the dead-lettered email [`item_id`](`ID`) back in the queue, returns `0` if there's no
+    /// such dead-lettered email
+    pub fn retry(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use schema::mail_queue::dsl::*;
+
+        diesel::update(
+            mail_queue
+                .filter(id.eq(item_id))
+                .filter(status.eq(MailStatus::Dead.as_str())),
+        )
+        .set((
+            status.eq(MailStatus::Pending.as_str()),
+            attempts.eq(0),
+            next_attempt_at.eq(now()),
+        ))
+        .execute(db)
+    }
+
+    /// the pending emails whose delivery is due, oldest first
+    fn due(db: &mut Connection, limit: i64) -> QueryResult<Vec<Self>> {
+        use schema::mail_queue::dsl::*;
+
+        mail_queue
+            .filter(status.eq(MailStatus::Pending.as_str()))
+            .filter(next_attempt_at.le(now()))
+            .order(next_attempt_at.asc())
+            .limit(limit)
+            .load::<QueuedMail>(db)
+    }
+
+    /// pushes back the email's next attempt while it's delivered, returns `false` if another
+    /// worker got to it first
+    fn claim(&self, db: &mut Connection) -> QueryResult<bool> {
+        use schema::mail_queue::dsl::*;
+
+        let current_time = now();
+        let claimed = diesel::update(
+            mail_queue
+                .filter(id.eq(self.id))
+                .filter(status.eq(MailStatus::Pending.as_str()))
+                .filter(next_attempt_at.le(current_time)),
+        )
+        .set(next_attempt_at.eq(current_time + chrono::Duration::minutes(DELIVERY_LEASE_MINUTES)))
+        .execute(db)?;
+
+        Ok(claimed == 1)
+    }
+
+    fn mark_sent(&self, db: &mut Connection) -> QueryResult<usize> {
+        use schema::mail_queue::dsl::*;
+
+        diesel::update(mail_queue.filter(id.eq(self.id)))
+            .set((
+                status.eq(MailStatus::Sent.as_str()),
+                sent_at.eq(Some(now())),
+                last_error.eq(None::<String>),
+            ))
+            .execute(db)
+    }
+
+    /// schedules the next attempt, or dead-letters the email after [`MailQueueConfig::max_attempts`]
+    fn mark_failed(
+        &self,
+        db: &mut Connection,
+        error: &str,
+        config: &MailQueueConfig,
+    ) -> QueryResult<usize> {
+        use schema::mail_queue::dsl::*;
+
+        let failed_attempts = self.attempts + 1;
+        let (next_status, next_attempt) = if failed_attempts >= config.max_attempts {
+            (MailStatus::Dead, now())
+        } else {
+            let delay = chrono::Duration::from_std(config.backoff(failed_attempts))
+                .unwrap_or_else(|_| chrono::Duration::days(1));
+
+            (MailStatus::Pending, now() + delay)
+        };
+
+        diesel::update(mail_queue.filter(id.eq(self.id)))
+            .set((
+                status.eq(next_status.as_str()),
+                attempts.eq(failed_attempts),
+                last_error.eq(Some(error)),
+                next_attempt_at.eq(next_attempt),
+            ))
+            .execute(db)
+    }
+}
+
+/// delivers the emails whose delivery is due with [`mailer`](`Mailer`), returns how many were sent
+///
+/// `mailer` must deliver emails itself, its [`queue`](`Mailer::queue`) is ignored
+pub fn deliver_due(
+    db: &mut Connection,
+    mailer: &Mailer,
+    config: &MailQueueConfig,
+) -> QueryResult<usize> {
+    let mut sent = 0;
+
+    for mail in QueuedMail::due(db, config.batch_size)? {
+        if !mail.claim(db)? {
+            continue;
+        }
+
+        let email = Email {
+            from: mailer.from_address.clone(),
+            to: mail.to_address.clone(),
+            subject: mail.subject.clone(),
+            text: mail.text_body.clone(),
+            html: mail.html_body.clone(),
+            attachments: vec![],
+        };
+
+        match mailer.deliver_email(email, mail.template.as_deref()) {
+            Ok(()) => {
+                mail.mark_sent(db)?;
+                sent += 1;
+            }
+            Err(err) => {
+                println!("{err}");
+                mail.mark_failed(db, &err, config)?;
+            }
+        }
+    }
+
+    Ok(sent)
+}
+
+/// starts a thread delivering the queued emails every [`MailQueueConfig::poll_interval`]
+///
+/// workers can run in several instances of the app, an email is only delivered by one of them
+pub fn spawn_worker(database: Database, config: MailQueueConfig) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        #[cfg(not(feature = "plugin_mail_log"))]
+        let mailer = Mailer::default();
+        #[cfg(feature = "plugin_mail_log")]
+        let mailer = Mailer::default().with_log(database.clone());
+
+        loop {
+            match database.pool.get() {
+                Ok(mut db) => {
+                    if let Err(err) = deliver_due(&mut db, &mailer, &config) {
+                        println!("Could not deliver queued emails: {err}");
+                    }
+                }
+                Err(err) => println!("Could not deliver queued emails: {err}"),
+            }
+
+            std::thread::sleep(config.poll_interval);
+        }
+    })
+}
+
+fn now() -> Utc {
+    #[cfg(not(feature = "database_sqlite"))]
+    return chrono::Utc::now();
+
+    #[cfg(feature = "database_sqlite")]
+    return chrono::Utc::now().naive_utc();
+}
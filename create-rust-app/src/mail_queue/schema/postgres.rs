@@ -0,0 +1,16 @@
+table! {
+  mail_queue (id) {
+      id -> Int4,
+      to_address -> Text,
+      subject -> Text,
+      text_body -> Text,
+      html_body -> Text,
+      template -> Nullable<Text>,
+      status -> Text,
+      attempts -> Int4,
+      last_error -> Nullable<Text>,
+      next_attempt_at -> Timestamptz,
+      sent_at -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
@@ -0,0 +1,16 @@
+table! {
+  mail_queue (id) {
+      id -> Integer,
+      to_address -> Text,
+      subject -> Text,
+      text_body -> Text,
+      html_body -> Text,
+      template -> Nullable<Text>,
+      status -> Text,
+      attempts -> Integer,
+      last_error -> Nullable<Text>,
+      next_attempt_at -> Timestamp,
+      sent_at -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
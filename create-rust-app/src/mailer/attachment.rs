@@ -0,0 +1,111 @@
+//! Files sent with emails, either as attachments or as inline images the html refers to by their
+//! content id:
+//!
+//! ```rust,ignore
+//! let logo = std::fs::read("backend/mail/logo.png")?;
+//! let invoice = EmailAttachment::from_storage(&storage, &attachment_info).await?;
+//!
+//! mailer.send_with_attachments(
+//!     "user@example.com",
+//!     "Your invoice",
+//!     "Your invoice is attached.",
+//!     r#"<img src="cid:logo"> Your invoice is attached."#,
+//!     vec![EmailAttachment::inline("logo", "logo.png", "image/png", logo), invoice],
+//! );
+//! ```
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, SinglePart};
+
+#[cfg(feature = "plugin_storage")]
+use crate::storage::{AttachmentInfo, Storage};
+
+#[derive(Debug, Clone)]
+/// a file sent with an [`Email`](`super::Email`)
+pub struct EmailAttachment {
+    pub file_name: String,
+    /// ex: "application/pdf"
+    pub content_type: String,
+    pub content: Vec<u8>,
+    /// set for inline files, which the html shows with `<img src="cid:{content_id}">`
+    pub content_id: Option<String>,
+}
+
+impl EmailAttachment {
+    /// a file attached to the email
+    pub fn new(
+        file_name: impl Into<String>,
+        content_type: impl Into<String>,
+        content: Vec<u8>,
+    ) -> Self {
+        Self {
+            file_name: file_name.into(),
+            content_type: content_type.into(),
+            content,
+            content_id: None,
+        }
+    }
+
+    /// a file (usually an image) shown in the email's html, at `cid:{content_id}`
+    pub fn inline(
+        content_id: impl Into<String>,
+        file_name: impl Into<String>,
+        content_type: impl Into<String>,
+        content: Vec<u8>,
+    ) -> Self {
+        Self {
+            content_id: Some(content_id.into()),
+            ..Self::new(file_name, content_type, content)
+        }
+    }
+
+    /// whether the file is shown in the html rather than attached
+    pub fn is_inline(&self) -> bool {
+        self.content_id.is_some()
+    }
+
+    /// the attachment's file, downloaded from `storage`
+    ///
+    /// quarantined attachments (see [`ScanStatus`](`crate::storage::ScanStatus`)) are never sent,
+    /// and private ones are sent as is: check the recipient may read them first
+    #[cfg(feature = "plugin_storage")]
+    pub async fn from_storage(
+        storage: &Storage,
+        attachment: &AttachmentInfo,
+    ) -> Result<Self, String> {
+        if attachment.is_quarantined() {
+            return Err(format!(
+                "The attachment is quarantined (id: '{}', key: '{}')",
+                attachment.id, attachment.key
+            ));
+        }
+
+        let content = storage.provider().get(&attachment.key).await?;
+
+        Ok(Self::new(
+            attachment.file_name.clone(),
+            attachment
+                .content_type
+                .clone()
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            content,
+        ))
+    }
+
+    /// the file's MIME part
+    pub(crate) fn to_part(&self) -> Result<SinglePart, String> {
+        let content_type = ContentType::parse(&self.content_type).map_err(|err| {
+            format!(
+                "Invalid attachment content type (file: '{file_name}', content type: '{content_type}', error: '{err}')",
+                file_name = self.file_name,
+                content_type = self.content_type
+            )
+        })?;
+
+        let attachment = match &self.content_id {
+            Some(content_id) => Attachment::new_inline(content_id.clone()),
+            None => Attachment::new(self.file_name.clone()),
+        };
+
+        Ok(attachment.body(self.content.clone(), content_type))
+    }
+}
@@ -0,0 +1,100 @@
+//! Sends emails through Mailgun's API (`plugin_mail_transports` feature), see
+//! [`transport`](`super::transport`).
+//!
+//! ```text
+//! MAIL_TRANSPORT=mailgun
+//! MAILGUN_DOMAIN=mg.my-domain.com
+//! MAILGUN_API_KEY=key-xxxxxxxx
+//! # for domains in Mailgun's EU region
+//! MAILGUN_API_URL=https://api.eu.mailgun.net
+//! ```
+use reqwest::blocking::multipart::{Form, Part};
+use serde_json::Value;
+
+use super::transport::{send_request, Email, MailTransport};
+
+const MAILGUN_API_URL: &str = "https://api.mailgun.net";
+
+#[derive(Debug, Clone)]
+/// sends emails with Mailgun's `messages` endpoint
+pub struct MailgunTransport {
+    api_url: String,
+    domain: String,
+    api_key: String,
+}
+
+impl MailgunTransport {
+    pub fn new(domain: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            api_url: MAILGUN_API_URL.to_string(),
+            domain: domain.into(),
+            api_key: api_key.into(),
+        }
+    }
+
+    /// the API of another region (ex: "https://api.eu.mailgun.net")
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// the domain in `MAILGUN_DOMAIN`, with the key in `MAILGUN_API_KEY` and the API in
+    /// `MAILGUN_API_URL` when it's set
+    pub fn from_env() -> Self {
+        let transport = Self::new(
+            std::env::var("MAILGUN_DOMAIN").expect("No MAILGUN_DOMAIN environment variable set!"),
+            std::env::var("MAILGUN_API_KEY").expect("No MAILGUN_API_KEY environment variable set!"),
+        );
+
+        match std::env::var("MAILGUN_API_URL") {
+            Ok(api_url) if !api_url.is_empty() => transport.with_api_url(api_url),
+            _ => transport,
+        }
+    }
+}
+
+impl MailTransport for MailgunTransport {
+    fn send(&self, email: &Email) -> Result<Option<String>, String> {
+        let url = format!(
+            "{api_url}/v3/{domain}/messages",
+            api_url = self.api_url,
+            domain = self.domain
+        );
+        let mut form = Form::new()
+            .text("from", email.from.clone())
+            .text("to", email.to.clone())
+            .text("subject", email.subject.clone())
+            .text("text", email.text.clone())
+            .text("html", email.html.clone());
+
+        // inline images are referred to by their file name, which must be their content id
+        for attachment in &email.attachments {
+            let (field, file_name) = match &attachment.content_id {
+                Some(content_id) => ("inline", content_id.clone()),
+                None => ("attachment", attachment.file_name.clone()),
+            };
+            let part = Part::bytes(attachment.content.clone())
+                .file_name(file_name)
+                .mime_str(&attachment.content_type)
+                .map_err(|err| {
+                    format!(
+                        "Invalid attachment content type (file: '{file_name}', error: '{err}')",
+                        file_name = attachment.file_name
+                    )
+                })?;
+            form = form.part(field, part);
+        }
+
+        let (_, body) = send_request(email, |client| {
+            client
+                .post(url)
+                .basic_auth("api", Some(&self.api_key))
+                .multipart(form)
+        })?;
+
+        // ex: {"id": "<20230601.1@mg.my-domain.com>", "message": "Queued. Thank you."}
+        Ok(serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|response| response["id"].as_str().map(String::from)))
+    }
+}
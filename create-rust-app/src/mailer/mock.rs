@@ -0,0 +1,62 @@
+//! Records emails in memory instead of sending them, for tests:
+//!
+//! ```rust,ignore
+//! use create_rust_app::Mailer;
+//!
+//! let mailer = Mailer::mock();
+//!
+//! controller::register(&db, &item, &mailer, &config)?;
+//!
+//! let sent = mailer.sent_to("a@b.com");
+//! assert_eq!(sent.len(), 1);
+//! assert!(sent[0].text.contains("activate?token="));
+//! ```
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+use super::transport::{Email, MailTransport};
+
+#[derive(Debug, Clone, Default)]
+/// keeps the emails it's given, clones share the same emails
+pub struct MockTransport {
+    sent: Arc<Mutex<Vec<Email>>>,
+}
+
+impl MockTransport {
+    /// every email sent, oldest first
+    pub fn sent(&self) -> Vec<Email> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// the emails sent to `to`, oldest first
+    pub fn sent_to(&self, to: &str) -> Vec<Email> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|email| email.to.eq_ignore_ascii_case(to))
+            .cloned()
+            .collect()
+    }
+
+    /// forgets the emails sent so far
+    pub fn clear(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+}
+
+impl MailTransport for MockTransport {
+    fn send(&self, email: &Email) -> Result<Option<String>, String> {
+        // fails on the same invalid emails the other transports would
+        email.to_message()?;
+
+        let mut sent = self.sent.lock().unwrap();
+        sent.push(email.clone());
+
+        Ok(Some(format!("mock-{count}", count = sent.len())))
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
@@ -0,0 +1,696 @@
+#[cfg(feature = "plugin_auth")]
+use crate::auth::mail::{
+    auth_activated, auth_email_change, auth_email_changed, auth_invitation, auth_password_changed,
+    auth_password_reset, auth_recover_existent_account, auth_recover_nonexistent_account,
+    auth_register,
+};
+#[cfg(feature = "plugin_auth")]
+use dyn_clone::{clone_trait_object, DynClone};
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::util::workspace_utils::MAIL_TEMPLATES_GLOB;
+
+mod attachment;
+pub use attachment::EmailAttachment;
+#[cfg(feature = "plugin_mail_dkim")]
+pub mod dkim;
+mod mock;
+pub use mock::MockTransport;
+pub mod transport;
+pub use transport::{Email, MailTransport, SmtpTransport, StubTransport};
+
+#[cfg(feature = "plugin_mail_transports")]
+mod mailgun;
+#[cfg(feature = "plugin_mail_transports")]
+pub use mailgun::MailgunTransport;
+#[cfg(feature = "plugin_mail_transports")]
+mod postmark;
+#[cfg(feature = "plugin_mail_transports")]
+pub use postmark::PostmarkTransport;
+#[cfg(feature = "plugin_mail_transports")]
+mod sendgrid;
+#[cfg(feature = "plugin_mail_transports")]
+pub use sendgrid::SendGridTransport;
+#[cfg(feature = "plugin_mail_transports")]
+mod ses;
+#[cfg(feature = "plugin_mail_transports")]
+pub use ses::SesTransport;
+
+// the DyncClone trait bound is for cloning, and the
+// Send trait bound is for thread-safety
+#[cfg(feature = "plugin_auth")]
+/// A trait that defines the behavior of an email template
+pub trait EmailTemplates: DynClone + Sync + Send {
+    fn send_activated(&self, mailer: &Mailer, to_email: &str);
+    fn send_password_changed(&self, mailer: &Mailer, to_email: &str);
+    fn send_password_reset(&self, mailer: &Mailer, to_email: &str);
+    fn send_recover_existent_account(&self, mailer: &Mailer, to_email: &str, link: &str);
+    fn send_recover_nonexistent_account(&self, mailer: &Mailer, to_email: &str, link: &str);
+    fn send_register(&self, mailer: &Mailer, to_email: &str, link: &str);
+    /// the invite link of a [`UserInvitation`](`crate::auth::UserInvitation`), sent with the registration email unless overridden
+    fn send_invitation(&self, mailer: &Mailer, to_email: &str, link: &str) {
+        self.send_register(mailer, to_email, link);
+    }
+    /// the confirmation link sent to the new address when a user changes their email, sent with
+    /// the registration email unless overridden
+    fn send_email_change(&self, mailer: &Mailer, to_email: &str, link: &str) {
+        self.send_register(mailer, to_email, link);
+    }
+    /// tells the previous address of a user that their email was changed to `new_email`
+    fn send_email_changed(&self, mailer: &Mailer, to_email: &str, new_email: &str) {
+        auth_email_changed::send(mailer, to_email, new_email);
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+clone_trait_object!(EmailTemplates);
+
+#[derive(Clone)]
+/// struct used to handle sending emails
+pub struct Mailer {
+    /// the email address emails should be sent from
+    ///
+    /// set by the `SMTP_FROM_ADDRESS` environment variable
+    pub from_address: String,
+    /// the smtp server to connect to for purposes of sending emails
+    ///
+    /// set by the `SMTP_SERVER` environment variable
+    pub smtp_server: String,
+    /// username used to log into `SMTP_SERVER`
+    ///
+    /// set by the `SMTP_USERNAME` environment variable
+    pub smtp_username: String,
+    /// the password used to log into `SMTP_SERVER`
+    ///
+    /// set by the `SMTP_PASSWORD' environment variable
+    pub smtp_password: String,
+    /// whether or not emails should actually be sent when requested
+    ///
+    /// it may be useful to set this to false in some devolopment environments
+    /// while setting it to true in production
+    ///
+    /// set by the `SEND_MAIL` environment variable
+    pub actually_send: bool,
+    /// delivers the emails when [`actually_send`](`Mailer::actually_send`) is `true`
+    ///
+    /// selected by the `MAIL_TRANSPORT` environment variable (default: SMTP), see
+    /// [`transport`](`self::transport`)
+    pub transport: Arc<dyn MailTransport>,
+    /// the templates rendered by [`Mailer::send_template`]
+    ///
+    /// loaded from `backend/mail/templates`, or the glob in the `CRA_MAIL_TEMPLATES_GLOB`
+    /// environment variable
+    pub mail_templates: Arc<Tera>,
+    /// the locale [`Mailer::send_template`] uses when it isn't given one, see
+    /// [`Mailer::with_locale`]
+    pub locale: Option<String>,
+    #[cfg(feature = "plugin_mail_queue")]
+    /// when set, [`Mailer::send`] puts emails in the [`mail_queue`](`crate::mail_queue`) instead of
+    /// delivering them, see [`Mailer::with_queue`]
+    pub queue: Option<crate::Database>,
+    #[cfg(feature = "plugin_mail_log")]
+    /// when set, deliveries are recorded in the [`mail_log`](`crate::mail_log`), see
+    /// [`Mailer::with_log`]
+    pub log: Option<crate::Database>,
+    #[cfg(feature = "plugin_auth")]
+    // Structure containing email templates to be used for various purposes
+    pub templates: Box<dyn EmailTemplates + Sync + Send>,
+}
+
+impl Default for Mailer {
+    #[cfg(feature = "plugin_auth")]
+    fn default() -> Self {
+        Self::new(Box::<DefaultMailTemplates>::default())
+    }
+    #[cfg(not(feature = "plugin_auth"))]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mailer {
+    /// using information stored in the `SMTP_FROM_ADDRESS`, `SMTP_SERVER`, `SMTP_USERNAME`, `SMTP_PASSWORD`, and `SEND_MAIL`
+    /// environment variables to connect to a remote SMTP server (or to the email provider selected
+    /// by `MAIL_TRANSPORT`, see [`transport`](`self::transport`)),
+    ///
+    /// allows webservers to send emails to users for purposes
+    /// like marketing, user authentification, etc.
+    #[cfg(not(feature = "plugin_auth"))]
+    pub fn new() -> Self {
+        Mailer::check_environment_variables();
+
+        let from_address: String = std::env::var("SMTP_FROM_ADDRESS")
+            .unwrap_or_else(|_| "create-rust-app@localhost".to_string());
+        let smtp_server: String = std::env::var("SMTP_SERVER").unwrap_or_else(|_| "".to_string());
+        let smtp_username: String =
+            std::env::var("SMTP_USERNAME").unwrap_or_else(|_| "".to_string());
+        let smtp_password: String =
+            std::env::var("SMTP_PASSWORD").unwrap_or_else(|_| "".to_string());
+        let actually_send: bool = std::env::var("SEND_MAIL")
+            .unwrap_or_else(|_| "false".to_string())
+            .eq_ignore_ascii_case("true");
+        Mailer {
+            from_address,
+            smtp_server,
+            smtp_username,
+            smtp_password,
+            actually_send,
+            transport: transport::from_env(),
+            mail_templates: Mailer::load_templates(),
+            locale: None,
+            #[cfg(feature = "plugin_mail_queue")]
+            queue: None,
+            #[cfg(feature = "plugin_mail_log")]
+            log: None,
+        }
+    }
+
+    #[cfg(feature = "plugin_auth")]
+    pub fn new(templates: Box<dyn EmailTemplates + Sync + Send>) -> Self {
+        Mailer::check_environment_variables();
+
+        let from_address: String = std::env::var("SMTP_FROM_ADDRESS")
+            .unwrap_or_else(|_| "create-rust-app@localhost".to_string());
+        let smtp_server: String = std::env::var("SMTP_SERVER").unwrap_or_else(|_| "".to_string());
+        let smtp_username: String =
+            std::env::var("SMTP_USERNAME").unwrap_or_else(|_| "".to_string());
+        let smtp_password: String =
+            std::env::var("SMTP_PASSWORD").unwrap_or_else(|_| "".to_string());
+        let actually_send: bool = std::env::var("SEND_MAIL")
+            .unwrap_or_else(|_| "false".to_string())
+            .eq_ignore_ascii_case("true");
+        Mailer {
+            from_address,
+            smtp_server,
+            smtp_username,
+            smtp_password,
+            actually_send,
+            transport: transport::from_env(),
+            mail_templates: Mailer::load_templates(),
+            locale: None,
+            #[cfg(feature = "plugin_mail_queue")]
+            queue: None,
+            #[cfg(feature = "plugin_mail_log")]
+            log: None,
+            templates,
+        }
+    }
+
+    /// the templates at `MAIL_TEMPLATES_GLOB`, along with the auth emails' (`plugin_auth` feature)
+    /// and the notification digests' (`plugin_notifications` feature) templates the app doesn't
+    /// override
+    pub(crate) fn load_templates() -> Arc<Tera> {
+        #[allow(unused_mut)]
+        let mut tera = Tera::new(MAIL_TEMPLATES_GLOB.as_str()).unwrap_or_else(|err| {
+            println!("Warning: Could not load mail templates: {err}");
+            Tera::default()
+        });
+
+        #[allow(unused_mut)]
+        let mut defaults: Vec<(&str, &str)> = vec![];
+        #[cfg(feature = "plugin_auth")]
+        defaults.extend(crate::auth::mail::DEFAULT_TEMPLATES);
+        #[cfg(feature = "plugin_notifications")]
+        defaults.extend(crate::notifications::digest::DEFAULT_TEMPLATES);
+
+        let defaults = defaults
+            .into_iter()
+            .filter(|(name, _)| !tera.get_template_names().any(|loaded| loaded == *name))
+            .collect::<Vec<_>>();
+
+        if let Err(err) = tera.add_raw_templates(defaults) {
+            println!("Warning: Could not load the default mail templates: {err}");
+        }
+
+        Arc::new(tera)
+    }
+
+    /// checks that the required environment variables are set
+    ///
+    /// prints messages denoting which, if any, of the required
+    /// environment variables were not set
+    pub fn check_environment_variables() {
+        let mut vars = vec!["SMTP_FROM_ADDRESS", "SEND_MAIL"];
+        vars.extend(transport::required_variables());
+
+        let unset_vars = vars
+            .into_iter()
+            .filter(|v| std::env::var(v).is_err())
+            .collect::<Vec<_>>();
+
+        if !unset_vars.is_empty() {
+            println!(
+                "Warning: Mailing disabled; the following variables must be set: {}",
+                unset_vars.join(", ")
+            );
+        }
+
+        let send_mail_value = std::env::var("SEND_MAIL").unwrap_or_default();
+        if !send_mail_value.eq_ignore_ascii_case("true")
+            && !send_mail_value.eq_ignore_ascii_case("false")
+        {
+            println!("Warning: SEND_MAIL must be `true` or `false`");
+        }
+    }
+
+    /// delivers emails with `transport` instead of the one selected by `MAIL_TRANSPORT`
+    pub fn with_transport(mut self, transport: impl MailTransport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// a mailer recording emails in a [`MockTransport`] instead of sending them, for tests: see
+    /// [`Mailer::sent`] and [`Mailer::sent_to`]
+    pub fn mock() -> Self {
+        let mut mailer = Mailer::default().with_transport(MockTransport::default());
+        mailer.actually_send = true;
+        mailer
+    }
+
+    /// the emails recorded by the mailer's [`MockTransport`], oldest first
+    ///
+    /// panics if the mailer doesn't have one, see [`Mailer::mock`]
+    pub fn sent(&self) -> Vec<Email> {
+        self.mock_transport().sent()
+    }
+
+    /// the emails recorded by the mailer's [`MockTransport`] for `to`, oldest first
+    ///
+    /// panics if the mailer doesn't have one, see [`Mailer::mock`]
+    pub fn sent_to(&self, to: &str) -> Vec<Email> {
+        self.mock_transport().sent_to(to)
+    }
+
+    fn mock_transport(&self) -> &MockTransport {
+        self.transport
+            .as_any()
+            .and_then(|transport| transport.downcast_ref::<MockTransport>())
+            .expect("The mailer doesn't record emails, use Mailer::mock()")
+    }
+
+    /// records every delivery in the [`mail_log`](`crate::mail_log`) of `database`
+    #[cfg(feature = "plugin_mail_log")]
+    pub fn with_log(mut self, database: crate::Database) -> Self {
+        self.log = Some(database);
+        self
+    }
+
+    /// renders templates in `locale` (ex: "fr" or "pt-BR") when [`Mailer::send_template`] isn't
+    /// given one, ex: the locale of the recipient
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// puts emails in the [`mail_queue`](`crate::mail_queue`) of `database`, they're delivered
+    /// by its worker
+    #[cfg(feature = "plugin_mail_queue")]
+    pub fn with_queue(mut self, database: crate::Database) -> Self {
+        self.queue = Some(database);
+        self
+    }
+
+    /// send an email with the specifified content and subject to the specified user
+    ///
+    /// will only send an email if the `SEND_MAIL` environment variable was set to true when
+    /// this mailer was initialized.
+    ///
+    /// with the `plugin_mail_queue` feature, the email is queued instead when the mailer has a
+    /// [`queue`](`Mailer::queue`), falling back to sending it right away if it can't be queued
+    ///
+    /// # Arguments
+    /// * `to` - a string slice that holds the email address of the intended recipient
+    /// * `subject` - subject field of the email
+    /// * `text` - text content of the email
+    /// * `html` - html content of the email
+    pub fn send(&self, to: &str, subject: &str, text: &str, html: &str) {
+        self.send_with_attachments(to, subject, text, html, vec![]);
+    }
+
+    /// like [`Mailer::send`], with files attached or shown inline, see [`EmailAttachment`]
+    ///
+    /// emails with attachments are never queued, they're sent right away
+    pub fn send_with_attachments(
+        &self,
+        to: &str,
+        subject: &str,
+        text: &str,
+        html: &str,
+        attachments: Vec<EmailAttachment>,
+    ) {
+        self.send_email(
+            Email {
+                from: self.from_address.clone(),
+                to: to.to_string(),
+                subject: subject.to_string(),
+                text: text.to_string(),
+                html: html.to_string(),
+                attachments,
+            },
+            None,
+        );
+    }
+
+    /// queues `email` or delivers it, see [`Mailer::send`]
+    fn send_email(&self, email: Email, template: Option<&str>) {
+        #[cfg(feature = "plugin_mail_queue")]
+        if let Some(database) = self.queue.as_ref().filter(|_| email.attachments.is_empty()) {
+            let item = crate::mail_queue::QueuedMailChangeset {
+                to_address: email.to.clone(),
+                subject: email.subject.clone(),
+                text_body: email.text.clone(),
+                html_body: email.html.clone(),
+                template: template.map(String::from),
+            };
+
+            match crate::mail_queue::QueuedMail::enqueue(&mut database.get_connection(), &item) {
+                Ok(_) => {
+                    #[cfg(feature = "plugin_prometheus")]
+                    crate::prometheus::record_email("queued");
+                    return;
+                }
+                Err(err) => println!(
+                    "Could not queue email, sending it right away (to: '{to}', error: '{err}')",
+                    to = email.to
+                ),
+            }
+        }
+
+        if let Err(err) = self.deliver_email(email, template) {
+            println!("{err}");
+        }
+    }
+
+    /// sends an email right away with the mailer's [`transport`](`Mailer::transport`), see
+    /// [`Mailer::send`]
+    ///
+    /// in debug builds with the `plugin_dev` feature, the email is also captured by the
+    /// [development mailbox](`crate::dev::mailbox`)
+    pub fn deliver(
+        &self,
+        to: &str,
+        subject: &str,
+        text: &str,
+        html: &str,
+        attachments: Vec<EmailAttachment>,
+    ) -> Result<(), String> {
+        self.deliver_email(
+            Email {
+                from: self.from_address.clone(),
+                to: to.to_string(),
+                subject: subject.to_string(),
+                text: text.to_string(),
+                html: html.to_string(),
+                attachments,
+            },
+            None,
+        )
+    }
+
+    /// sends `email`, rendered from the `template` mail templates if it's given, see
+    /// [`Mailer::deliver`]
+    #[cfg_attr(not(feature = "plugin_mail_log"), allow(unused_variables))]
+    pub(crate) fn deliver_email(&self, email: Email, template: Option<&str>) -> Result<(), String> {
+        #[allow(unused_mut)]
+        let mut captured = false;
+
+        // in development, emails are captured by the dev mailbox instead of being printed (but not
+        // the ones tests record, see `Mailer::mock`)
+        #[cfg(all(feature = "plugin_dev", debug_assertions))]
+        if !self
+            .transport
+            .as_any()
+            .map_or(false, |transport| transport.is::<MockTransport>())
+        {
+            match crate::dev::mailbox::capture(&email) {
+                Ok(_) => println!(
+                    "Email to {to} captured, see the mailbox in the admin portal",
+                    to = email.to
+                ),
+                Err(err) => println!("{err}"),
+            }
+            captured = true;
+        }
+
+        let result = if self.actually_send {
+            self.transport.send(&email)
+        } else {
+            StubTransport.send(&email)
+        };
+
+        if !captured {
+            println!(
+                r#"====================
+Sent email {:#?}
+--------------------
+to: {:?}
+from: {}
+message:
+{}
+===================="#,
+                result, email.to, self.from_address, email.text
+            );
+        }
+
+        #[cfg(feature = "plugin_mail_log")]
+        self.log(&email, template, &result);
+
+        #[cfg(feature = "plugin_prometheus")]
+        crate::prometheus::record_email(if result.is_ok() { "sent" } else { "failed" });
+
+        result.map(|_| ())
+    }
+
+    /// records the delivery of `email` in the [`mail_log`](`crate::mail_log`), if the mailer has one
+    #[cfg(feature = "plugin_mail_log")]
+    fn log(&self, email: &Email, template: Option<&str>, result: &Result<Option<String>, String>) {
+        use crate::mail_log::{EmailStatus, LoggedEmail, LoggedEmailChangeset};
+
+        let database = match &self.log {
+            Some(database) => database,
+            None => return,
+        };
+
+        let status = match result {
+            Err(_) => EmailStatus::Failed,
+            Ok(_) if self.actually_send => EmailStatus::Sent,
+            Ok(_) => EmailStatus::Skipped,
+        };
+        let item = LoggedEmailChangeset {
+            recipient: email.to.clone(),
+            subject: email.subject.clone(),
+            template: template.map(String::from),
+            status: status.as_str().to_string(),
+            provider_message_id: result.clone().ok().flatten(),
+            error: result.clone().err(),
+        };
+
+        if let Err(err) = LoggedEmail::record(&mut database.get_connection(), &item) {
+            println!(
+                "Could not log email (to: '{to}', error: '{err}')",
+                to = email.to
+            );
+        }
+    }
+
+    /// renders the `{name}.subject.txt`, `{name}.txt` and `{name}.html` templates (see
+    /// [`Mailer::mail_templates`]) with `context`, and sends them to `to`
+    ///
+    /// ```rust,ignore
+    /// mailer.send_template(
+    ///     "user@example.com",
+    ///     "welcome",
+    ///     Some("pt-BR"),
+    ///     &serde_json::json!({ "name": "Jane" }),
+    /// )?;
+    /// ```
+    ///
+    /// templates are translated by adding the locale to their name (ex: `welcome.pt-BR.html`, or
+    /// `welcome.pt.html` for every Portuguese variant): each one is rendered in `locale` (or the
+    /// mailer's [`locale`](`Mailer::locale`) when it's `None`), falling back to the language's, and
+    /// then to the untranslated template
+    ///
+    /// the `.html` template is autoescaped, the others aren't
+    pub fn send_template(
+        &self,
+        to: &str,
+        name: &str,
+        locale: Option<&str>,
+        context: &impl Serialize,
+    ) -> Result<(), String> {
+        self.send_template_with_attachments(to, name, locale, context, vec![])
+    }
+
+    /// like [`Mailer::send_template`], with files attached or shown inline (ex: a logo the html
+    /// template shows with `<img src="cid:logo">`), see [`EmailAttachment`]
+    pub fn send_template_with_attachments(
+        &self,
+        to: &str,
+        name: &str,
+        locale: Option<&str>,
+        context: &impl Serialize,
+        attachments: Vec<EmailAttachment>,
+    ) -> Result<(), String> {
+        let RenderedTemplate {
+            subject,
+            text,
+            html,
+        } = self.render_template(name, locale, context)?;
+
+        self.send_email(
+            Email {
+                from: self.from_address.clone(),
+                to: to.to_string(),
+                subject,
+                text,
+                html,
+                attachments,
+            },
+            Some(name),
+        );
+
+        Ok(())
+    }
+
+    /// renders the `{name}.subject.txt`, `{name}.txt` and `{name}.html` templates with `context`,
+    /// translated in `locale` (or [`Mailer::locale`]) when they are, without sending anything
+    pub fn render_template(
+        &self,
+        name: &str,
+        locale: Option<&str>,
+        context: &impl Serialize,
+    ) -> Result<RenderedTemplate, String> {
+        let context = Context::from_serialize(context).map_err(|err| {
+            format!("Invalid mail template context (template: '{name}', error: '{err}')")
+        })?;
+
+        render_template(
+            &self.mail_templates,
+            name,
+            locale.or(self.locale.as_deref()),
+            &context,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// the parts of an email rendered from a mail template, see [`Mailer::render_template`]
+pub struct RenderedTemplate {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// renders the `name` mail template's parts from `templates`, see [`Mailer::render_template`]
+pub(crate) fn render_template(
+    templates: &Tera,
+    name: &str,
+    locale: Option<&str>,
+    context: &Context,
+) -> Result<RenderedTemplate, String> {
+    let render = |part: &str| {
+        let template = localized_template(templates, name, part, locale);
+        templates.render(&template, context).map_err(|err| {
+            format!("Could not render mail template (template: '{template}', error: '{err}')")
+        })
+    };
+
+    Ok(RenderedTemplate {
+        subject: render("subject.txt")?.trim().to_string(),
+        text: render("txt")?,
+        html: render("html")?,
+    })
+}
+
+/// the name of the `{name}.{part}` template's translation in `locale` (ex: "pt-BR"), or in its
+/// language ("pt"), or the untranslated template's name if there is none
+fn localized_template(templates: &Tera, name: &str, part: &str, locale: Option<&str>) -> String {
+    let mut locales = vec![];
+    if let Some(locale) = locale.filter(|locale| !locale.is_empty()) {
+        locales.push(locale);
+        if let Some((language, _)) = locale.split_once(['-', '_']) {
+            locales.push(language);
+        }
+    }
+
+    locales
+        .into_iter()
+        .map(|locale| format!("{name}.{locale}.{part}"))
+        .find(|template| {
+            templates
+                .get_template_names()
+                .any(|loaded| loaded == template)
+        })
+        .unwrap_or_else(|| format!("{name}.{part}"))
+}
+
+#[cfg(feature = "plugin_auth")]
+#[derive(Clone)]
+pub struct DefaultMailTemplates {
+    pub base_url: String,
+}
+#[cfg(feature = "plugin_auth")]
+impl DefaultMailTemplates {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+        }
+    }
+}
+#[cfg(feature = "plugin_auth")]
+impl Default for DefaultMailTemplates {
+    fn default() -> Self {
+        Self::new("http://localhost:3000/")
+    }
+}
+#[cfg(feature = "plugin_auth")]
+impl EmailTemplates for DefaultMailTemplates {
+    fn send_activated(&self, mailer: &Mailer, to_email: &str) {
+        auth_activated::send(mailer, to_email);
+    }
+    fn send_password_changed(&self, mailer: &Mailer, to_email: &str) {
+        auth_password_changed::send(mailer, to_email);
+    }
+    fn send_password_reset(&self, mailer: &Mailer, to_email: &str) {
+        auth_password_reset::send(mailer, to_email);
+    }
+    fn send_recover_existent_account(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_recover_existent_account::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_recover_nonexistent_account(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_recover_nonexistent_account::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_register(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_register::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_invitation(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_invitation::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_email_change(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_email_change::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+}
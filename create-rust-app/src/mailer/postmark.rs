@@ -0,0 +1,93 @@
+//! Sends emails through Postmark's API (`plugin_mail_transports` feature), see
+//! [`transport`](`super::transport`).
+//!
+//! ```text
+//! MAIL_TRANSPORT=postmark
+//! POSTMARK_SERVER_TOKEN=xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx
+//! # optional, Postmark's default is "outbound"
+//! POSTMARK_MESSAGE_STREAM=outbound
+//! ```
+use serde_json::{json, Value};
+
+use super::transport::{encode_base64, send_request, Email, MailTransport};
+
+const POSTMARK_API_URL: &str = "https://api.postmarkapp.com/email";
+
+#[derive(Debug, Clone)]
+/// sends emails with Postmark's `email` endpoint
+pub struct PostmarkTransport {
+    server_token: String,
+    message_stream: Option<String>,
+}
+
+impl PostmarkTransport {
+    pub fn new(server_token: impl Into<String>) -> Self {
+        Self {
+            server_token: server_token.into(),
+            message_stream: None,
+        }
+    }
+
+    /// sends emails through the `message_stream` of the server
+    pub fn with_message_stream(mut self, message_stream: impl Into<String>) -> Self {
+        self.message_stream = Some(message_stream.into());
+        self
+    }
+
+    /// the server token in `POSTMARK_SERVER_TOKEN`, and the stream in `POSTMARK_MESSAGE_STREAM`
+    /// when it's set
+    pub fn from_env() -> Self {
+        let transport = Self::new(
+            std::env::var("POSTMARK_SERVER_TOKEN")
+                .expect("No POSTMARK_SERVER_TOKEN environment variable set!"),
+        );
+
+        match std::env::var("POSTMARK_MESSAGE_STREAM") {
+            Ok(message_stream) if !message_stream.is_empty() => {
+                transport.with_message_stream(message_stream)
+            }
+            _ => transport,
+        }
+    }
+}
+
+impl MailTransport for PostmarkTransport {
+    fn send(&self, email: &Email) -> Result<Option<String>, String> {
+        let mut body = json!({
+            "From": email.from,
+            "To": email.to,
+            "Subject": email.subject,
+            "TextBody": email.text,
+            "HtmlBody": email.html,
+        });
+        if let Some(message_stream) = &self.message_stream {
+            body["MessageStream"] = json!(message_stream);
+        }
+        if !email.attachments.is_empty() {
+            body["Attachments"] = email
+                .attachments
+                .iter()
+                .map(|attachment| {
+                    json!({
+                        "Name": attachment.file_name,
+                        "Content": encode_base64(&attachment.content),
+                        "ContentType": attachment.content_type,
+                        "ContentID": attachment.content_id.as_ref().map(|id| format!("cid:{id}")),
+                    })
+                })
+                .collect();
+        }
+
+        let (_, response) = send_request(email, |client| {
+            client
+                .post(POSTMARK_API_URL)
+                .header("Accept", "application/json")
+                .header("X-Postmark-Server-Token", &self.server_token)
+                .json(&body)
+        })?;
+
+        Ok(serde_json::from_str::<Value>(&response)
+            .ok()
+            .and_then(|response| response["MessageID"].as_str().map(String::from)))
+    }
+}
@@ -0,0 +1,75 @@
+//! Sends emails through SendGrid's v3 API (`plugin_mail_transports` feature), see
+//! [`transport`](`super::transport`).
+//!
+//! ```text
+//! MAIL_TRANSPORT=sendgrid
+//! SENDGRID_API_KEY=SG.xxxxxxxx
+//! ```
+use serde_json::json;
+
+use super::transport::{encode_base64, send_request, Email, MailTransport};
+
+const SENDGRID_API_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+
+#[derive(Debug, Clone)]
+/// sends emails with SendGrid's `mail/send` endpoint
+pub struct SendGridTransport {
+    api_key: String,
+}
+
+impl SendGridTransport {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+        }
+    }
+
+    /// the API key in `SENDGRID_API_KEY`
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("SENDGRID_API_KEY")
+                .expect("No SENDGRID_API_KEY environment variable set!"),
+        )
+    }
+}
+
+impl MailTransport for SendGridTransport {
+    fn send(&self, email: &Email) -> Result<Option<String>, String> {
+        let mut body = json!({
+            "personalizations": [{ "to": [{ "email": email.to }] }],
+            "from": { "email": email.from },
+            "subject": email.subject,
+            "content": [
+                { "type": "text/plain", "value": email.text },
+                { "type": "text/html", "value": email.html },
+            ],
+        });
+        if !email.attachments.is_empty() {
+            body["attachments"] = email
+                .attachments
+                .iter()
+                .map(|attachment| {
+                    json!({
+                        "content": encode_base64(&attachment.content),
+                        "type": attachment.content_type,
+                        "filename": attachment.file_name,
+                        "disposition": if attachment.is_inline() { "inline" } else { "attachment" },
+                        "content_id": attachment.content_id,
+                    })
+                })
+                .collect();
+        }
+
+        let (headers, _) = send_request(email, |client| {
+            client
+                .post(SENDGRID_API_URL)
+                .bearer_auth(&self.api_key)
+                .json(&body)
+        })?;
+
+        Ok(headers
+            .get("X-Message-Id")
+            .and_then(|id| id.to_str().ok())
+            .map(String::from))
+    }
+}
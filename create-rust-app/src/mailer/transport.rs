@@ -0,0 +1,301 @@
+//! How the [`Mailer`](`super::Mailer`) delivers emails.
+//!
+//! SMTP is used unless `MAIL_TRANSPORT` selects another transport. With the
+//! `plugin_mail_transports` feature, emails can be sent through the HTTP APIs of email providers,
+//! for deployments where SMTP ports are blocked:
+//!
+//! | `MAIL_TRANSPORT` | transport | configuration |
+//! |:-----------------|-----------|---------------|
+//! | `smtp` (default) | [`SmtpTransport`] | `SMTP_SERVER`, `SMTP_USERNAME`, `SMTP_PASSWORD` |
+//! | `ses` | `SesTransport` | `SES_REGION`, `SES_ACCESS_KEY_ID`, `SES_SECRET_ACCESS_KEY` |
+//! | `sendgrid` | `SendGridTransport` | `SENDGRID_API_KEY` |
+//! | `mailgun` | `MailgunTransport` | `MAILGUN_DOMAIN`, `MAILGUN_API_KEY`, `MAILGUN_API_URL` (optional) |
+//! | `postmark` | `PostmarkTransport` | `POSTMARK_SERVER_TOKEN`, `POSTMARK_MESSAGE_STREAM` (optional) |
+//!
+//! or with your own transport:
+//!
+//! ```rust,ignore
+//! let mailer = Mailer::default().with_transport(MyTransport::new());
+//! ```
+//!
+//! Tests can record emails instead with [`MockTransport`](`super::MockTransport`), see
+//! [`Mailer::mock`](`super::Mailer::mock`).
+use std::sync::Arc;
+
+use lettre::message::{Message, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::Transport;
+
+use super::EmailAttachment;
+#[cfg(feature = "plugin_mail_transports")]
+use super::{MailgunTransport, PostmarkTransport, SendGridTransport, SesTransport};
+
+#[derive(Debug, Clone)]
+/// an email, as handed to a [`MailTransport`]
+pub struct Email {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+    /// attached files and inline images, see [`EmailAttachment`]
+    pub attachments: Vec<EmailAttachment>,
+}
+
+impl Email {
+    /// the email as a MIME message, with its text and html alternatives, the inline images related
+    /// to them, and its attachments
+    pub fn to_message(&self) -> Result<Message, String> {
+        let to = self.to.parse().map_err(|err| {
+            format!(
+                "Invalid recipient (to: '{to}', error: '{err}')",
+                to = self.to
+            )
+        })?;
+        let from = self.from.parse().map_err(|err| {
+            format!(
+                "Invalid sender (from: '{from}', error: '{err}')",
+                from = self.from
+            )
+        })?;
+
+        let mut body = MultiPart::alternative_plain_html(self.text.clone(), self.html.clone());
+
+        let (inline, attached): (Vec<_>, Vec<_>) = self
+            .attachments
+            .iter()
+            .partition(|attachment| attachment.is_inline());
+
+        if !inline.is_empty() {
+            let mut related = MultiPart::related().multipart(body);
+            for attachment in inline {
+                related = related.singlepart(attachment.to_part()?);
+            }
+            body = related;
+        }
+
+        if !attached.is_empty() {
+            let mut mixed = MultiPart::mixed().multipart(body);
+            for attachment in attached {
+                mixed = mixed.singlepart(attachment.to_part()?);
+            }
+            body = mixed;
+        }
+
+        Message::builder()
+            .to(to)
+            .from(from)
+            .subject(&self.subject)
+            .multipart(body)
+            .map_err(|err| {
+                format!(
+                    "Could not build email (to: '{to}', error: '{err}')",
+                    to = self.to
+                )
+            })
+    }
+}
+
+/// delivers emails, see the [module documentation](`self`)
+pub trait MailTransport: Send + Sync {
+    /// sends `email`, returns the provider's id of the message when it gives one
+    fn send(&self, email: &Email) -> Result<Option<String>, String>;
+
+    /// the transport, for downcasting (ex: [`Mailer::sent`](`super::Mailer::sent`) finds its
+    /// [`MockTransport`](`super::MockTransport`) with it), `None` by default
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+/// sends emails through an SMTP relay
+pub struct SmtpTransport {
+    server: String,
+    username: String,
+    password: String,
+    #[cfg(feature = "plugin_mail_dkim")]
+    dkim: Option<lettre::message::dkim::DkimConfig>,
+}
+
+impl SmtpTransport {
+    pub fn new(
+        server: impl Into<String>,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            server: server.into(),
+            username: username.into(),
+            password: password.into(),
+            #[cfg(feature = "plugin_mail_dkim")]
+            dkim: None,
+        }
+    }
+
+    /// signs emails with `dkim`, see [`dkim`](`super::dkim`)
+    #[cfg(feature = "plugin_mail_dkim")]
+    pub fn with_dkim(mut self, dkim: lettre::message::dkim::DkimConfig) -> Self {
+        self.dkim = Some(dkim);
+        self
+    }
+
+    /// the relay configured by the `SMTP_SERVER`, `SMTP_USERNAME` and `SMTP_PASSWORD` variables
+    ///
+    /// `SMTP_SERVER` is the host of a TLS relay, or a URL such as `smtp://localhost:1025` (a
+    /// development mail catcher), and the credentials are only sent when `SMTP_USERNAME` is set
+    ///
+    /// with the `plugin_mail_dkim` feature, emails are signed when `DKIM_SELECTOR` is set, see
+    /// [`dkim`](`super::dkim`)
+    pub fn from_env() -> Self {
+        #[allow(unused_mut)]
+        let mut transport = Self::new(
+            std::env::var("SMTP_SERVER").unwrap_or_default(),
+            std::env::var("SMTP_USERNAME").unwrap_or_default(),
+            std::env::var("SMTP_PASSWORD").unwrap_or_default(),
+        );
+
+        #[cfg(feature = "plugin_mail_dkim")]
+        if let Some(dkim) = super::dkim::from_env() {
+            transport = transport.with_dkim(dkim);
+        }
+
+        transport
+    }
+}
+
+impl MailTransport for SmtpTransport {
+    fn send(&self, email: &Email) -> Result<Option<String>, String> {
+        #[allow(unused_mut)]
+        let mut message = email.to_message()?;
+
+        #[cfg(feature = "plugin_mail_dkim")]
+        if let Some(dkim) = &self.dkim {
+            message.sign(dkim);
+        }
+
+        // a URL (ex: `smtp://mailpit:1025`, a local mail catcher without TLS) or a TLS relay's host
+        let builder = if self.server.contains("://") {
+            lettre::SmtpTransport::from_url(&self.server)
+        } else {
+            lettre::SmtpTransport::relay(&self.server)
+        };
+        let mut builder = builder.map_err(|err| {
+            format!(
+                "Invalid SMTP server (server: '{server}', error: '{err}')",
+                server = self.server
+            )
+        })?;
+        if !self.username.is_empty() {
+            builder = builder.credentials(Credentials::new(
+                self.username.to_string(),
+                self.password.to_string(),
+            ));
+        }
+        let mailer = builder.build();
+
+        mailer.send(&message).map(|_| None).map_err(|err| {
+            format!(
+                "Could not send email (to: '{to}', error: '{err}')",
+                to = email.to
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// doesn't send emails, used when `SEND_MAIL` isn't `true`
+pub struct StubTransport;
+
+impl MailTransport for StubTransport {
+    fn send(&self, email: &Email) -> Result<Option<String>, String> {
+        let message = email.to_message()?;
+
+        lettre::transport::stub::StubTransport::new_ok()
+            .send(&message)
+            .map(|_| None)
+            .map_err(|err| {
+                format!(
+                    "Could not send email (to: '{to}', error: '{err}')",
+                    to = email.to
+                )
+            })
+    }
+}
+
+/// the transport selected by `MAIL_TRANSPORT`, see the [module documentation](`self`)
+pub fn from_env() -> Arc<dyn MailTransport> {
+    let transport = std::env::var("MAIL_TRANSPORT").unwrap_or_default();
+
+    match transport.to_lowercase().as_str() {
+        "" | "smtp" => Arc::new(SmtpTransport::from_env()),
+        #[cfg(feature = "plugin_mail_transports")]
+        "ses" => Arc::new(SesTransport::from_env()),
+        #[cfg(feature = "plugin_mail_transports")]
+        "sendgrid" => Arc::new(SendGridTransport::from_env()),
+        #[cfg(feature = "plugin_mail_transports")]
+        "mailgun" => Arc::new(MailgunTransport::from_env()),
+        #[cfg(feature = "plugin_mail_transports")]
+        "postmark" => Arc::new(PostmarkTransport::from_env()),
+        _ => panic!(
+            "Unknown MAIL_TRANSPORT '{transport}' (is the plugin_mail_transports feature enabled?)"
+        ),
+    }
+}
+
+/// the environment variables the transport selected by `MAIL_TRANSPORT` requires
+pub(crate) fn required_variables() -> Vec<&'static str> {
+    let transport = std::env::var("MAIL_TRANSPORT").unwrap_or_default();
+
+    match transport.to_lowercase().as_str() {
+        "ses" => vec!["SES_REGION", "SES_ACCESS_KEY_ID", "SES_SECRET_ACCESS_KEY"],
+        "sendgrid" => vec!["SENDGRID_API_KEY"],
+        "mailgun" => vec!["MAILGUN_DOMAIN", "MAILGUN_API_KEY"],
+        "postmark" => vec!["POSTMARK_SERVER_TOKEN"],
+        _ => vec!["SMTP_SERVER", "SMTP_USERNAME", "SMTP_PASSWORD"],
+    }
+}
+
+#[cfg(feature = "plugin_mail_transports")]
+/// sends the request built by `request` from a separate thread, the blocking http client can't be
+/// used from the async runtime the mailer is usually called from
+///
+/// returns the response's headers and body when it's successful
+pub(crate) fn send_request(
+    email: &Email,
+    request: impl FnOnce(&reqwest::blocking::Client) -> reqwest::blocking::RequestBuilder + Send,
+) -> Result<(reqwest::header::HeaderMap, String), String> {
+    let to = &email.to;
+
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                let client = reqwest::blocking::Client::new();
+                let response = request(&client)
+                    .send()
+                    .map_err(|err| format!("Could not send email (to: '{to}', error: '{err}')"))?;
+
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = response.text().unwrap_or_default();
+
+                if !status.is_success() {
+                    return Err(format!(
+                        "Could not send email (to: '{to}', status: '{status}', response: '{body}')"
+                    ));
+                }
+
+                Ok((headers, body))
+            })
+            .join()
+            .expect("the mail request's thread panicked")
+    })
+}
+
+#[cfg(feature = "plugin_mail_transports")]
+/// `bytes` in standard base64, as the providers' APIs expect attachments
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+    use base64::Engine;
+
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
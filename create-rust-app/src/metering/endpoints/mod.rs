@@ -0,0 +1,9 @@
+#[cfg(all(feature = "plugin_auth", feature = "backend_actix-web"))]
+mod service_actixweb;
+#[cfg(all(feature = "plugin_auth", feature = "backend_actix-web"))]
+pub use service_actixweb::endpoints;
+
+#[cfg(all(feature = "plugin_auth", feature = "backend_poem"))]
+mod service_poem;
+#[cfg(all(feature = "plugin_auth", feature = "backend_poem"))]
+pub use service_poem::api;
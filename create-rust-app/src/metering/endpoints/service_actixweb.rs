@@ -0,0 +1,58 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Query};
+use actix_web::{get, web, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::metering::{self, Utc};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the /usage endpoint
+pub struct UsageQuery {
+    metric: String,
+    /// defaults to 30 days ago
+    from: Option<Utc>,
+    /// defaults to now
+    to: Option<Utc>,
+}
+
+/// handler for GET requests at the .../usage endpoint
+///
+/// requires auth
+///
+/// returns the daily usage of the given metric, and its billable total, for the authenticated user
+/// (the `user:{id}` subject)
+#[get("")]
+async fn usage(
+    db: Data<Database>,
+    auth: Auth,
+    Query(query): Query<UsageQuery>,
+) -> Result<HttpResponse> {
+    let result = web::block(move || {
+        let mut db = db.get_connection();
+        let subject = format!("user:{}", auth.user_id);
+        let to = query.to.unwrap_or_else(metering::now);
+        let from = query
+            .from
+            .unwrap_or_else(|| to - chrono::Duration::days(30));
+
+        let days = metering::usage(&mut db, &subject, &query.metric, from, to)?;
+        let total = metering::total(&mut db, &subject, &query.metric, from, to)?;
+
+        Ok::<_, diesel::result::Error>(json!({ "days": days, "total": total }))
+    })
+    .await?;
+
+    match result {
+        Ok(usage) => Ok(HttpResponse::Ok().json(usage)),
+        Err(_) => Ok(HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(json!({ "message": "Could not fetch usage." }).to_string())),
+    }
+}
+
+/// returns the endpoints for the metering service
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(usage)
+}
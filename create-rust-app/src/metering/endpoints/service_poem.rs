@@ -0,0 +1,62 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    web::{Data, Json, Query},
+    Error, IntoResponse, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::metering::{self, Utc};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the /usage endpoint
+pub struct UsageQuery {
+    metric: String,
+    /// defaults to 30 days ago
+    from: Option<Utc>,
+    /// defaults to now
+    to: Option<Utc>,
+}
+
+#[handler]
+/// handler for GET requests at the .../usage endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"days": [`UsageAggregate`](`crate::metering::UsageAggregate`)[], "total": number}
+/// | 500 | Json payload : {"message": "Could not fetch usage."}
+async fn usage(
+    db: Data<&Database>,
+    auth: Auth,
+    Query(query): Query<UsageQuery>,
+) -> Result<impl IntoResponse> {
+    let mut db = db.get_connection();
+    let subject = format!("user:{}", auth.user_id);
+    let to = query.to.unwrap_or_else(metering::now);
+    let from = query
+        .from
+        .unwrap_or_else(|| to - chrono::Duration::days(30));
+
+    let error = || {
+        Error::from_string(
+            json!({ "message": "Could not fetch usage." }).to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    };
+
+    let days = metering::usage(&mut db, &subject, &query.metric, from, to).map_err(|_| error())?;
+    let total = metering::total(&mut db, &subject, &query.metric, from, to).map_err(|_| error())?;
+
+    Ok(Json(json!({ "days": days, "total": total })))
+}
+
+/// returns endpoints for the metering service
+pub fn api() -> Route {
+    Route::new().at("/", get(usage))
+}
@@ -0,0 +1,256 @@
+//! Usage metering for usage-based billing.
+//!
+//! Record billable events as they happen (API calls, storage bytes, seats, or any metric of your own),
+//! then roll them up into daily totals with [`aggregate`]. The payments plugin (or an external billing
+//! system) reads the totals through [`usage`] / [`total`] or the `/api/usage` endpoint.
+//!
+//! Every event carries an idempotency key: recording the same event twice (for example, when a request
+//! is retried) only counts it once.
+//!
+//! ```rust,ignore
+//! use create_rust_app::metering;
+//!
+//! let mut db = database.get_connection();
+//! metering::record_api_call(&mut db, &format!("user:{}", auth.user_id), &request_id)?;
+//! ```
+use crate::diesel::*;
+use crate::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+
+#[cfg(feature = "plugin_tasks")]
+mod tasks;
+#[cfg(feature = "plugin_tasks")]
+pub use tasks::AggregateUsage;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// number of requests made (counter)
+pub const API_CALLS: &str = "api_calls";
+/// bytes currently stored (gauge)
+pub const STORAGE_BYTES: &str = "storage_bytes";
+/// number of occupied seats (gauge)
+pub const SEATS: &str = "seats";
+
+/// gauges are snapshots: their daily total is the highest value seen that day, all other metrics are summed
+const GAUGE_METRICS: [&str; 2] = [STORAGE_BYTES, SEATS];
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=usage_events)]
+pub struct UsageEvent {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: i32,
+
+    pub idempotency_key: String,
+    pub subject: String,
+    pub metric: String,
+    pub quantity: i64,
+
+    pub recorded_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=usage_events)]
+pub struct UsageEventChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    /// unique per event, recording an event with an existing key is a no-op
+    pub idempotency_key: String,
+    /// who is billed, for example `user:1` or `org:42`
+    pub subject: String,
+    pub metric: String,
+    pub quantity: i64,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// the total of a metric for a subject over one day
+pub struct UsageAggregate {
+    pub subject: String,
+    pub metric: String,
+    pub period_start: Utc,
+    pub quantity: i64,
+    pub updated_at: Utc,
+}
+
+/// Create an entry in [`db`](`Connection`)'s `usage_events` table using the data in [`event`](`UsageEventChangeset`)
+///
+/// returns `false` if an event with the same `idempotency_key` was already recorded
+pub fn record(db: &mut Connection, event: &UsageEventChangeset) -> QueryResult<bool> {
+    use schema::usage_events::dsl::*;
+
+    insert_into(usage_events)
+        .values(event)
+        .on_conflict(idempotency_key)
+        .do_nothing()
+        .execute(db)
+        .map(|inserted| inserted > 0)
+}
+
+/// records one API call made by [`subject`]
+pub fn record_api_call(
+    db: &mut Connection,
+    subject: &str,
+    idempotency_key: &str,
+) -> QueryResult<bool> {
+    record(
+        db,
+        &UsageEventChangeset {
+            idempotency_key: idempotency_key.to_string(),
+            subject: subject.to_string(),
+            metric: API_CALLS.to_string(),
+            quantity: 1,
+        },
+    )
+}
+
+/// records a snapshot of the number of bytes [`subject`] currently stores
+pub fn record_storage_bytes(
+    db: &mut Connection,
+    subject: &str,
+    bytes: i64,
+    idempotency_key: &str,
+) -> QueryResult<bool> {
+    record(
+        db,
+        &UsageEventChangeset {
+            idempotency_key: idempotency_key.to_string(),
+            subject: subject.to_string(),
+            metric: STORAGE_BYTES.to_string(),
+            quantity: bytes,
+        },
+    )
+}
+
+/// records a snapshot of the number of seats [`subject`] currently occupies
+pub fn record_seats(
+    db: &mut Connection,
+    subject: &str,
+    seats: i64,
+    idempotency_key: &str,
+) -> QueryResult<bool> {
+    record(
+        db,
+        &UsageEventChangeset {
+            idempotency_key: idempotency_key.to_string(),
+            subject: subject.to_string(),
+            metric: SEATS.to_string(),
+            quantity: seats,
+        },
+    )
+}
+
+/// (re)computes the daily `usage_aggregates` for every day from [`since`] until now
+///
+/// aggregation is idempotent, so it's safe to run it periodically over an overlapping window
+/// (see [`AggregateUsage`] when using the tasks plugin)
+pub fn aggregate(db: &mut Connection, since: Utc) -> QueryResult<usize> {
+    let gauges = GAUGE_METRICS
+        .iter()
+        .map(|m| format!("'{m}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    #[cfg(feature = "database_postgres")]
+    let query = format!(
+        r#"
+        INSERT INTO usage_aggregates (subject, metric, period_start, quantity, updated_at)
+        SELECT subject, metric, date_trunc('day', recorded_at),
+            (CASE WHEN metric IN ({gauges}) THEN MAX(quantity) ELSE SUM(quantity) END)::BIGINT,
+            CURRENT_TIMESTAMP
+        FROM usage_events
+        WHERE recorded_at >= date_trunc('day', $1)
+        GROUP BY subject, metric, date_trunc('day', recorded_at)
+        ON CONFLICT (subject, metric, period_start)
+        DO UPDATE SET quantity = EXCLUDED.quantity, updated_at = EXCLUDED.updated_at
+        "#
+    );
+
+    #[cfg(feature = "database_sqlite")]
+    let query = format!(
+        r#"
+        INSERT INTO usage_aggregates (subject, metric, period_start, quantity, updated_at)
+        SELECT subject, metric, datetime(date(recorded_at)),
+            CASE WHEN metric IN ({gauges}) THEN MAX(quantity) ELSE SUM(quantity) END,
+            CURRENT_TIMESTAMP
+        FROM usage_events
+        WHERE recorded_at >= datetime(date(?))
+        GROUP BY subject, metric, date(recorded_at)
+        ON CONFLICT (subject, metric, period_start)
+        DO UPDATE SET quantity = excluded.quantity, updated_at = excluded.updated_at
+        "#
+    );
+
+    #[cfg(feature = "database_postgres")]
+    return sql_query(query)
+        .bind::<diesel::sql_types::Timestamptz, _>(since)
+        .execute(db);
+
+    #[cfg(feature = "database_sqlite")]
+    return sql_query(query)
+        .bind::<diesel::sql_types::Timestamp, _>(since)
+        .execute(db);
+}
+
+/// reads the daily aggregates of [`item_metric`] for [`item_subject`] between [`from`] and [`to`]
+pub fn usage(
+    db: &mut Connection,
+    item_subject: &str,
+    item_metric: &str,
+    from: Utc,
+    to: Utc,
+) -> QueryResult<Vec<UsageAggregate>> {
+    use schema::usage_aggregates::dsl::*;
+
+    usage_aggregates
+        .filter(subject.eq(item_subject))
+        .filter(metric.eq(item_metric))
+        .filter(period_start.ge(from))
+        .filter(period_start.lt(to))
+        .order(period_start)
+        .load::<UsageAggregate>(db)
+}
+
+/// the billable quantity of [`metric`] for [`subject`] between [`from`] and [`to`]:
+/// the sum of all days for counters, the highest daily value for gauges
+pub fn total(
+    db: &mut Connection,
+    subject: &str,
+    metric: &str,
+    from: Utc,
+    to: Utc,
+) -> QueryResult<i64> {
+    let days = usage(db, subject, metric, from, to)?;
+    let quantities = days.iter().map(|day| day.quantity);
+
+    Ok(if GAUGE_METRICS.contains(&metric) {
+        quantities.max().unwrap_or(0)
+    } else {
+        quantities.sum()
+    })
+}
+
+/// the current time, in the timestamp type used by the enabled database
+pub(crate) fn now() -> Utc {
+    #[cfg(not(feature = "database_sqlite"))]
+    return chrono::Utc::now();
+
+    #[cfg(feature = "database_sqlite")]
+    return chrono::Utc::now().naive_utc();
+}
@@ -0,0 +1,22 @@
+table! {
+  usage_aggregates (subject, metric, period_start) {
+      subject -> Text,
+      metric -> Text,
+      period_start -> Timestamptz,
+      quantity -> Int8,
+      updated_at -> Timestamptz,
+  }
+}
+
+table! {
+  usage_events (id) {
+      id -> Int4,
+      idempotency_key -> Text,
+      subject -> Text,
+      metric -> Text,
+      quantity -> Int8,
+      recorded_at -> Timestamptz,
+  }
+}
+
+allow_tables_to_appear_in_same_query!(usage_aggregates, usage_events,);
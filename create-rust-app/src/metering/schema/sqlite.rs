@@ -0,0 +1,22 @@
+table! {
+  usage_aggregates (subject, metric, period_start) {
+      subject -> Text,
+      metric -> Text,
+      period_start -> Timestamp,
+      quantity -> BigInt,
+      updated_at -> Timestamp,
+  }
+}
+
+table! {
+  usage_events (id) {
+      id -> Integer,
+      idempotency_key -> Text,
+      subject -> Text,
+      metric -> Text,
+      quantity -> BigInt,
+      recorded_at -> Timestamp,
+  }
+}
+
+allow_tables_to_appear_in_same_query!(usage_aggregates, usage_events,);
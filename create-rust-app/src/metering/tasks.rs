@@ -0,0 +1,39 @@
+use fang::serde::{Deserialize, Serialize};
+use fang::typetag;
+use fang::{FangError, Queueable, Runnable, Scheduled};
+
+use crate::Database;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "fang::serde")]
+/// periodically rolls `usage_events` up into `usage_aggregates`
+///
+/// schedule it once with `create_rust_app::tasks::queue().schedule_task(&AggregateUsage {})`
+pub struct AggregateUsage {}
+
+#[typetag::serde]
+impl Runnable for AggregateUsage {
+    fn run(&self, _queue: &dyn Queueable) -> Result<(), FangError> {
+        let mut db = Database::new().get_connection();
+
+        // re-aggregate yesterday as well, to account for events that arrived late
+        let since = super::now() - chrono::Duration::days(1);
+
+        super::aggregate(&mut db, since).map_err(|err| FangError {
+            description: format!("Could not aggregate usage: {err}"),
+        })?;
+
+        Ok(())
+    }
+
+    fn uniq(&self) -> bool {
+        true
+    }
+
+    fn cron(&self) -> Option<Scheduled> {
+        // every hour, on the hour
+        //               sec  min   hour   day of month   month   day of week   year
+        let expression = "0 0 * * * * *";
+        Some(Scheduled::CronPattern(expression.to_string()))
+    }
+}
@@ -0,0 +1,236 @@
+//! In-process metrics (`plugin_dev` feature), shown in the admin portal's "Health" page in debug
+//! builds: the database pool's connections, the process' memory and CPU usage, and the request
+//! throughput, latency and error rate over the last minutes.
+//!
+//! Requests are counted by the [`RequestInspector`](`crate::inspector::RequestInspector`)
+//! middleware, in one bucket per minute; only the last hour is kept, in memory. Memory and CPU
+//! usage are read from `/proc/self` and are only available on Linux.
+//!
+//! ```rust,ignore
+//! let metrics = create_rust_app::metrics::snapshot(&database, 15);
+//! println!("{} requests/min", metrics.requests.requests_per_minute);
+//! ```
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::Database;
+
+/// how many minutes of requests are kept
+const KEPT_MINUTES: u64 = 60;
+
+lazy_static::lazy_static! {
+    static ref MINUTES: Mutex<VecDeque<MinuteMetrics>> = Mutex::new(VecDeque::new());
+    /// the process' cpu time (in seconds) at the previous [`snapshot`], to compute the cpu usage since
+    static ref LAST_CPU_SAMPLE: Mutex<Option<(Instant, f64)>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// the requests handled during a minute
+pub struct MinuteMetrics {
+    /// unix timestamp of the minute's start, in seconds
+    pub at: u64,
+    pub requests: u64,
+    /// requests answered with a 5xx status
+    pub errors: u64,
+    /// sum of the requests' latencies, in milliseconds
+    pub latency_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// the database connection pool's state
+pub struct PoolMetrics {
+    /// the open connections, idle or in use
+    pub connections: u32,
+    pub idle_connections: u32,
+    pub max_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// the process' resource usage, `None` when it can't be read (ex: outside of Linux)
+pub struct ProcessMetrics {
+    /// resident memory, in bytes
+    pub memory_bytes: Option<u64>,
+    /// cpu usage since the previous [`snapshot`] (100 is one core fully used), `None` on the
+    /// first snapshot
+    pub cpu_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// the requests handled over the last [`minutes`](`RequestMetrics::minutes`)
+pub struct RequestMetrics {
+    pub minutes: u64,
+    pub total: u64,
+    pub errors: u64,
+    /// `errors / total`, between 0 and 1
+    pub error_rate: f64,
+    pub requests_per_minute: f64,
+    pub average_latency_ms: f64,
+    /// one entry per minute, oldest first; minutes without requests are included
+    pub timeline: Vec<MinuteMetrics>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// see [`snapshot`]
+pub struct Metrics {
+    pub pool: PoolMetrics,
+    pub process: ProcessMetrics,
+    pub requests: RequestMetrics,
+}
+
+fn current_minute() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    now - now % 60
+}
+
+/// counts a request answered with `status` after `latency_ms` milliseconds
+pub fn record_request(status: u16, latency_ms: f64) {
+    let minute = current_minute();
+    let mut minutes = MINUTES.lock().unwrap();
+
+    if minutes.back().map_or(true, |last| last.at != minute) {
+        minutes.push_back(MinuteMetrics {
+            at: minute,
+            requests: 0,
+            errors: 0,
+            latency_ms: 0.0,
+        });
+    }
+    while minutes
+        .front()
+        .map_or(false, |first| first.at + KEPT_MINUTES * 60 <= minute)
+    {
+        minutes.pop_front();
+    }
+
+    let last = minutes.back_mut().unwrap();
+    last.requests += 1;
+    if status >= 500 {
+        last.errors += 1;
+    }
+    last.latency_ms += latency_ms;
+}
+
+/// the requests handled over the last `minutes` minutes (at most an hour), including the current one
+pub fn requests(minutes: u64) -> RequestMetrics {
+    let minutes = minutes.clamp(1, KEPT_MINUTES);
+    let current = current_minute();
+    let recorded = MINUTES.lock().unwrap();
+
+    let timeline = (0..minutes)
+        .rev()
+        .map(|ago| current - ago * 60)
+        .map(|at| {
+            recorded
+                .iter()
+                .find(|minute| minute.at == at)
+                .cloned()
+                .unwrap_or(MinuteMetrics {
+                    at,
+                    requests: 0,
+                    errors: 0,
+                    latency_ms: 0.0,
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let total = timeline.iter().map(|minute| minute.requests).sum::<u64>();
+    let errors = timeline.iter().map(|minute| minute.errors).sum::<u64>();
+    let latency_ms = timeline.iter().map(|minute| minute.latency_ms).sum::<f64>();
+
+    RequestMetrics {
+        minutes,
+        total,
+        errors,
+        error_rate: if total > 0 {
+            errors as f64 / total as f64
+        } else {
+            0.0
+        },
+        requests_per_minute: total as f64 / minutes as f64,
+        average_latency_ms: if total > 0 {
+            latency_ms / total as f64
+        } else {
+            0.0
+        },
+        timeline,
+    }
+}
+
+/// the state of `db`'s connection pool
+pub fn pool(db: &Database) -> PoolMetrics {
+    let state = db.pool.state();
+
+    PoolMetrics {
+        connections: state.connections,
+        idle_connections: state.idle_connections,
+        max_size: db.pool.max_size(),
+    }
+}
+
+/// the process' memory and cpu usage
+pub fn process() -> ProcessMetrics {
+    let cpu_percent = cpu_seconds().and_then(|cpu_seconds| {
+        let now = Instant::now();
+        let previous = LAST_CPU_SAMPLE.lock().unwrap().replace((now, cpu_seconds));
+
+        previous.and_then(|(at, previous_cpu_seconds)| {
+            let elapsed = now.duration_since(at).as_secs_f64();
+            (elapsed > 0.0).then(|| (cpu_seconds - previous_cpu_seconds) / elapsed * 100.0)
+        })
+    });
+
+    ProcessMetrics {
+        memory_bytes: memory_bytes(),
+        cpu_percent,
+    }
+}
+
+/// everything the "Health" page shows, with the requests of the last `minutes` minutes
+pub fn snapshot(db: &Database, minutes: u64) -> Metrics {
+    Metrics {
+        pool: pool(db),
+        process: process(),
+        requests: requests(minutes),
+    }
+}
+
+/// the `VmRSS` line of `/proc/self/status`
+fn memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+
+    Some(kilobytes * 1024)
+}
+
+/// the user and system cpu time of the process, from `/proc/self/stat`
+fn cpu_seconds() -> Option<f64> {
+    // the kernel reports them in clock ticks, which are 1/100th of a second on every mainstream platform
+    const TICKS_PER_SECOND: f64 = 100.0;
+
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // the process name (2nd field) can contain spaces, the fields after it can't
+    let fields = stat
+        .rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    // utime and stime are the 14th and 15th fields, the 12th and 13th after the name
+    let utime = fields.get(11)?.parse::<f64>().ok()?;
+    let stime = fields.get(12)?.parse::<f64>().ok()?;
+
+    Some((utime + stime) / TICKS_PER_SECOND)
+}
@@ -0,0 +1,173 @@
+//! Email digests of unread notifications.
+//!
+//! Digests are the `notification_digest` mail templates (`notification_digest.subject.txt`,
+//! `notification_digest.txt` and `notification_digest.html`), rendered by the [`Mailer`] like the
+//! auth emails: add them to the app's `backend/mail/templates` to change their wording, or add
+//! translations (ex: `notification_digest.fr.html`, see [`Mailer::send_template`]). Each template
+//! receives:
+//!
+//! - `email`: the recipient's address
+//! - `base_url`: [`DigestConfig::base_url`]
+//! - `count`: the number of notifications in the digest
+//! - `notifications`: the [`Notification`]s (`title`, `body`, `link`, `kind`, `created_at`, ...)
+use super::{now, Notification, NotificationPreference, ID};
+use crate::auth::{Profiles, User};
+use crate::mailer::RenderedTemplate;
+use crate::{Connection, Mailer};
+use diesel::QueryResult;
+use serde_json::json;
+
+/// the name of the digests' mail templates
+pub const TEMPLATE: &str = "notification_digest";
+
+/// the digests' templates, used unless the app's `backend/mail/templates` directory overrides
+/// them (see [`Mailer::send_template`])
+pub(crate) const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "notification_digest.subject.txt",
+        include_str!("templates/notification_digest.subject.txt"),
+    ),
+    (
+        "notification_digest.txt",
+        include_str!("templates/notification_digest.txt"),
+    ),
+    (
+        "notification_digest.html",
+        include_str!("templates/notification_digest.html"),
+    ),
+];
+
+#[derive(Debug, Clone)]
+/// how digests are rendered and sent
+pub struct DigestConfig {
+    /// used to turn notification links into absolute urls (default: `http://localhost:3000`)
+    pub base_url: String,
+    /// the maximum number of digests sent per call to [`send_digests`], the remaining
+    /// recipients are picked up by the next call (default: 500)
+    pub batch_size: usize,
+    /// sending rate limit (default: 10 emails per second)
+    pub emails_per_second: u32,
+    /// the app's profiles (ex: `auth_config.profile.clone()`), whose `locale` field selects the
+    /// language each digest is sent in (see [`Profiles::locale`]), or the mailer's locale when
+    /// `None` (the default)
+    pub profiles: Option<Profiles>,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:3000".to_string(),
+            batch_size: 500,
+            emails_per_second: 10,
+            profiles: None,
+        }
+    }
+}
+
+/// the context the digest templates of [`notifications`] for [`email`] are rendered with
+fn context(
+    config: &DigestConfig,
+    email: &str,
+    notifications: &[Notification],
+) -> serde_json::Value {
+    json!({
+        "email": email,
+        "base_url": config.base_url.trim_end_matches('/'),
+        "count": notifications.len(),
+        "notifications": notifications,
+    })
+}
+
+/// renders the digest of [`notifications`] for [`email`] in `locale` (or the mailer's), without
+/// sending it
+pub fn render(
+    mailer: &Mailer,
+    config: &DigestConfig,
+    email: &str,
+    locale: Option<&str>,
+    notifications: &[Notification],
+) -> Result<RenderedTemplate, String> {
+    mailer.render_template(TEMPLATE, locale, &context(config, email, notifications))
+}
+
+/// the unread notifications that go into [`user_id`](`ID`)'s next digest, or `None` if
+/// they don't want digests or their last one was sent too recently
+pub fn pending_digest(
+    db: &mut Connection,
+    user_id: ID,
+) -> QueryResult<Option<(NotificationPreference, Vec<Notification>)>> {
+    let preference = NotificationPreference::read_or_default(db, user_id)?;
+
+    let period = match preference.frequency().period() {
+        Some(period) => period,
+        None => return Ok(None),
+    };
+
+    if let Some(last_digest_at) = preference.last_digest_at {
+        if now() - last_digest_at < period {
+            return Ok(None);
+        }
+    }
+
+    let notifications = Notification::unread_since(db, user_id, preference.last_digest_at)?;
+    if notifications.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some((preference, notifications)))
+}
+
+/// emails a digest to every user whose digest is due, up to [`config.batch_size`](`DigestConfig`) of them
+///
+/// returns the number of digests sent
+pub fn send_digests(
+    db: &mut Connection,
+    mailer: &Mailer,
+    config: &DigestConfig,
+) -> QueryResult<usize> {
+    let delay = std::time::Duration::from_secs(1) / config.emails_per_second.max(1);
+    let mut sent = 0;
+
+    for user_id in Notification::users_with_unread(db)? {
+        if sent >= config.batch_size {
+            break;
+        }
+
+        let (preference, notifications) = match pending_digest(db, user_id)? {
+            Some(digest) => digest,
+            None => continue,
+        };
+
+        let user = match User::read(db, user_id) {
+            Ok(user) => user,
+            Err(diesel::result::Error::NotFound) => continue,
+            Err(e) => return Err(e),
+        };
+
+        // guests have nowhere to send it to
+        let email = match &user.email {
+            Some(email) => email,
+            None => continue,
+        };
+
+        let locale = config
+            .profiles
+            .as_ref()
+            .and_then(|profiles| profiles.locale(db, user_id));
+
+        if sent > 0 {
+            std::thread::sleep(delay);
+        }
+
+        let context = context(config, email, &notifications);
+        if let Err(err) = mailer.send_template(email, TEMPLATE, locale.as_deref(), &context) {
+            println!("Could not send the notification digest: {err}");
+            continue;
+        }
+        NotificationPreference::set_last_digest_at(db, user_id, preference.frequency(), now())?;
+
+        sent += 1;
+    }
+
+    Ok(sent)
+}
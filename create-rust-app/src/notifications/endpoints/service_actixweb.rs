@@ -0,0 +1,289 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::{delete, get, post, put, web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::notifications::push::WebPush;
+use crate::notifications::{
+    DigestFrequency, Notification, NotificationPreference, PushSubscription,
+    PushSubscriptionChangeset,
+};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of the notifications endpoint
+pub struct ListQuery {
+    /// at most 100 (default: 20)
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+/// Json response of the notifications endpoint
+pub struct NotificationsJson {
+    notifications: Vec<Notification>,
+    unread_count: i64,
+}
+
+#[derive(Deserialize)]
+/// the keys of a browser's push subscription
+pub struct PushKeys {
+    p256dh: String,
+    auth: String,
+}
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../push/subscriptions endpoint, the browser's
+/// `PushSubscription`
+pub struct SubscribeInput {
+    endpoint: String,
+    keys: PushKeys,
+}
+
+#[derive(Deserialize)]
+/// Json body of DELETE requests to the .../push/subscriptions endpoint
+pub struct UnsubscribeInput {
+    endpoint: String,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the notifications endpoint
+///
+/// requires auth
+///
+/// returns the user's most recent notifications, and how many they haven't read
+#[get("")]
+async fn index(
+    db: Data<Database>,
+    auth: Auth,
+    Query(query): Query<ListQuery>,
+) -> Result<HttpResponse> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+
+    let result = web::block(move || {
+        let mut db = db.get_connection();
+
+        Ok::<_, diesel::result::Error>(NotificationsJson {
+            notifications: Notification::recent_for_user(&mut db, auth.user_id, limit)?,
+            unread_count: Notification::unread_count(&mut db, auth.user_id)?,
+        })
+    })
+    .await?;
+
+    match result {
+        Ok(notifications) => Ok(HttpResponse::Ok().json(notifications)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch notifications.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../unread-count endpoint
+///
+/// requires auth
+///
+/// returns how many notifications the user hasn't read
+#[get("/unread-count")]
+async fn unread_count(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result =
+        web::block(move || Notification::unread_count(&mut db.get_connection(), auth.user_id))
+            .await?;
+
+    match result {
+        Ok(count) => Ok(HttpResponse::Ok().json(json!({ "count": count }))),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch notifications.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../{id}/read endpoint
+///
+/// requires auth
+///
+/// marks one of the user's notifications as read
+#[post("/{id}/read")]
+async fn read(db: Data<Database>, auth: Auth, item_id: Path<i32>) -> Result<HttpResponse> {
+    let item_id = item_id.into_inner();
+
+    let result = web::block(move || {
+        Notification::mark_read(&mut db.get_connection(), auth.user_id, item_id)
+    })
+    .await?;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not update the notification.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../read-all endpoint
+///
+/// requires auth
+///
+/// marks all the user's notifications as read
+#[post("/read-all")]
+async fn read_all(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result =
+        web::block(move || Notification::mark_all_read(&mut db.get_connection(), auth.user_id))
+            .await?;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not update the notifications.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../push/public-key endpoint
+///
+/// returns the key browsers subscribe to web pushes with, 404 if they aren't configured
+#[get("/push/public-key")]
+async fn push_public_key() -> Result<HttpResponse> {
+    match WebPush::global() {
+        Some(web_push) => {
+            Ok(HttpResponse::Ok().json(json!({ "public_key": web_push.public_key() })))
+        }
+        None => Ok(error_response(
+            StatusCode::NOT_FOUND,
+            "Web push is not configured.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../push/subscriptions endpoint
+///
+/// requires auth
+///
+/// subscribes the user's browser to web pushes
+#[post("/push/subscriptions")]
+async fn subscribe(
+    db: Data<Database>,
+    auth: Auth,
+    Json(item): Json<SubscribeInput>,
+) -> Result<HttpResponse> {
+    let result = web::block(move || {
+        PushSubscription::subscribe(
+            &mut db.get_connection(),
+            &PushSubscriptionChangeset {
+                user_id: auth.user_id,
+                endpoint: item.endpoint,
+                p256dh: item.keys.p256dh,
+                auth: item.keys.auth,
+            },
+        )
+    })
+    .await?;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not subscribe.",
+        )),
+    }
+}
+
+/// handler for DELETE requests at the .../push/subscriptions endpoint
+///
+/// requires auth
+///
+/// unsubscribes the user's browser from web pushes
+#[delete("/push/subscriptions")]
+async fn unsubscribe(
+    db: Data<Database>,
+    auth: Auth,
+    Json(item): Json<UnsubscribeInput>,
+) -> Result<HttpResponse> {
+    let result = web::block(move || {
+        PushSubscription::unsubscribe(&mut db.get_connection(), auth.user_id, &item.endpoint)
+    })
+    .await?;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not unsubscribe.",
+        )),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+/// Json body of requests to the .../preferences endpoint
+pub struct PreferencesJson {
+    digest_frequency: DigestFrequency,
+}
+
+/// handler for GET requests at the .../preferences endpoint
+///
+/// requires auth
+///
+/// returns how often the user receives notification digests
+#[get("/preferences")]
+async fn preferences(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result = web::block(move || {
+        NotificationPreference::read_or_default(&mut db.get_connection(), auth.user_id)
+    })
+    .await?;
+
+    match result {
+        Ok(preference) => Ok(HttpResponse::Ok().json(PreferencesJson {
+            digest_frequency: preference.frequency(),
+        })),
+        Err(_) => Ok(HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(json!({ "message": "Could not fetch preferences." }).to_string())),
+    }
+}
+
+/// handler for PUT requests at the .../preferences endpoint
+///
+/// requires auth
+///
+/// changes how often the user receives notification digests
+#[put("/preferences")]
+async fn update_preferences(
+    db: Data<Database>,
+    auth: Auth,
+    Json(item): Json<PreferencesJson>,
+) -> Result<HttpResponse> {
+    let result = web::block(move || {
+        NotificationPreference::set_frequency(
+            &mut db.get_connection(),
+            auth.user_id,
+            item.digest_frequency,
+        )
+    })
+    .await?;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(json!({ "message": "Could not update preferences." }).to_string())),
+    }
+}
+
+/// returns the endpoints for the notifications service
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope
+        .service(index)
+        .service(unread_count)
+        .service(read_all)
+        .service(read)
+        .service(preferences)
+        .service(update_preferences)
+        .service(push_public_key)
+        .service(subscribe)
+        .service(unsubscribe)
+}
@@ -0,0 +1,279 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    post,
+    web::{Data, Json, Path, Query},
+    Error, IntoResponse, Response, Result, Route,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::notifications::push::WebPush;
+use crate::notifications::{
+    DigestFrequency, Notification, NotificationPreference, PushSubscription,
+    PushSubscriptionChangeset,
+};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of the notifications endpoint
+pub struct ListQuery {
+    /// at most 100 (default: 20)
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+/// Json response of the notifications endpoint
+pub struct NotificationsJson {
+    notifications: Vec<Notification>,
+    unread_count: i64,
+}
+
+#[derive(Deserialize)]
+/// the keys of a browser's push subscription
+pub struct PushKeys {
+    p256dh: String,
+    auth: String,
+}
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../push/subscriptions endpoint, the browser's
+/// `PushSubscription`
+pub struct SubscribeInput {
+    endpoint: String,
+    keys: PushKeys,
+}
+
+#[derive(Deserialize)]
+/// Json body of DELETE requests to the .../push/subscriptions endpoint
+pub struct UnsubscribeInput {
+    endpoint: String,
+}
+
+fn error(message: &str) -> Error {
+    Error::from_string(
+        json!({ "message": message }).to_string(),
+        StatusCode::INTERNAL_SERVER_ERROR,
+    )
+}
+
+#[handler]
+/// handler for GET requests at the notifications endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"notifications": [Notification], "unread_count": number}, most recent first
+/// | 500 | Json payload : {"message": "Could not fetch notifications."}
+async fn index(
+    db: Data<&Database>,
+    auth: Auth,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse> {
+    let mut db = db.get_connection();
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+
+    let notifications = Notification::recent_for_user(&mut db, auth.user_id, limit)
+        .map_err(|_| error("Could not fetch notifications."))?;
+    let unread_count = Notification::unread_count(&mut db, auth.user_id)
+        .map_err(|_| error("Could not fetch notifications."))?;
+
+    Ok(Json(NotificationsJson {
+        notifications,
+        unread_count,
+    }))
+}
+
+#[handler]
+/// handler for GET requests at the .../unread-count endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"count": number}
+/// | 500 | Json payload : {"message": "Could not fetch notifications."}
+async fn unread_count(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    match Notification::unread_count(&mut db.get_connection(), auth.user_id) {
+        Ok(count) => Ok(Json(json!({ "count": count }))),
+        Err(_) => Err(error("Could not fetch notifications.")),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../:id/read endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | the notification was marked as read
+/// | 500 | Json payload : {"message": "Could not update the notification."}
+async fn read(
+    db: Data<&Database>,
+    auth: Auth,
+    Path(item_id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    match Notification::mark_read(&mut db.get_connection(), auth.user_id, item_id) {
+        Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err(_) => Err(error("Could not update the notification.")),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../read-all endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | the notifications were marked as read
+/// | 500 | Json payload : {"message": "Could not update the notifications."}
+async fn read_all(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    match Notification::mark_all_read(&mut db.get_connection(), auth.user_id) {
+        Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err(_) => Err(error("Could not update the notifications.")),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../push/public-key endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"public_key": string}, the browsers' `applicationServerKey`
+/// | 404 | Json payload : {"message": "Web push is not configured."}
+async fn push_public_key() -> Result<impl IntoResponse> {
+    match WebPush::global() {
+        Some(web_push) => Ok(Json(json!({ "public_key": web_push.public_key() }))),
+        None => Err(Error::from_string(
+            json!({ "message": "Web push is not configured." }).to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../push/subscriptions endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | the browser was subscribed
+/// | 500 | Json payload : {"message": "Could not subscribe."}
+async fn subscribe(
+    db: Data<&Database>,
+    auth: Auth,
+    Json(item): Json<SubscribeInput>,
+) -> Result<impl IntoResponse> {
+    match PushSubscription::subscribe(
+        &mut db.get_connection(),
+        &PushSubscriptionChangeset {
+            user_id: auth.user_id,
+            endpoint: item.endpoint,
+            p256dh: item.keys.p256dh,
+            auth: item.keys.auth,
+        },
+    ) {
+        Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err(_) => Err(error("Could not subscribe.")),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../push/subscriptions endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | the browser was unsubscribed
+/// | 500 | Json payload : {"message": "Could not unsubscribe."}
+async fn unsubscribe(
+    db: Data<&Database>,
+    auth: Auth,
+    Json(item): Json<UnsubscribeInput>,
+) -> Result<impl IntoResponse> {
+    match PushSubscription::unsubscribe(&mut db.get_connection(), auth.user_id, &item.endpoint) {
+        Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err(_) => Err(error("Could not unsubscribe.")),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+/// Json body of requests to the .../preferences endpoint
+pub struct PreferencesJson {
+    digest_frequency: DigestFrequency,
+}
+
+#[handler]
+/// handler for GET requests at the .../preferences endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"digest_frequency": "never" \| "daily" \| "weekly"}
+/// | 500 | Json payload : {"message": "Could not fetch preferences."}
+async fn preferences(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    match NotificationPreference::read_or_default(&mut db.get_connection(), auth.user_id) {
+        Ok(preference) => Ok(Json(PreferencesJson {
+            digest_frequency: preference.frequency(),
+        })),
+        Err(_) => Err(Error::from_string(
+            json!({ "message": "Could not fetch preferences." }).to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )),
+    }
+}
+
+#[handler]
+/// handler for PUT requests at the .../preferences endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | the preferences were updated
+/// | 500 | Json payload : {"message": "Could not update preferences."}
+async fn update_preferences(
+    db: Data<&Database>,
+    auth: Auth,
+    Json(item): Json<PreferencesJson>,
+) -> Result<impl IntoResponse> {
+    match NotificationPreference::set_frequency(
+        &mut db.get_connection(),
+        auth.user_id,
+        item.digest_frequency,
+    ) {
+        Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err(_) => Err(Error::from_string(
+            json!({ "message": "Could not update preferences." }).to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )),
+    }
+}
+
+/// returns endpoints for the notifications service
+pub fn api() -> Route {
+    Route::new()
+        .at("/", get(index))
+        .at("/unread-count", get(unread_count))
+        .at("/read-all", post(read_all))
+        .at("/:id/read", post(read))
+        .at("/preferences", get(preferences).put(update_preferences))
+        .at("/push/public-key", get(push_public_key))
+        .at("/push/subscriptions", post(subscribe).delete(unsubscribe))
+}
@@ -0,0 +1,443 @@
+//! In-app notifications, web pushes, and periodic email digests of the ones users haven't read yet.
+//!
+//! Send notifications with [`Notify`], which fans them out to the user's notification bell, their
+//! email and the browsers they subscribed to web pushes from (see [`push`]). Users choose how often they'd like to receive
+//! a digest of their unread notifications ([`DigestFrequency`], stored in `notification_preferences`),
+//! and [`digest::send_digests`] emails everyone whose digest is due. With the tasks plugin, the
+//! [`SendDigests`] task does this every hour.
+//!
+//! ```rust,ignore
+//! use create_rust_app::notifications::{Notify, Payload};
+//!
+//! Notify::user(todo.owner_id).send(
+//!     &mut db,
+//!     Payload::new("todo_assigned", "A todo was assigned to you")
+//!         .body(todo.text.clone())
+//!         .link(format!("/todos/{}", todo.id)),
+//! )?;
+//! ```
+use crate::diesel::*;
+use crate::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+pub mod digest;
+mod endpoints;
+pub use endpoints::*;
+mod notify;
+pub use notify::{Channel, Notify, Payload};
+pub mod push;
+mod schema;
+use schema::*;
+
+#[cfg(feature = "plugin_tasks")]
+mod tasks;
+#[cfg(feature = "plugin_tasks")]
+pub use tasks::SendDigests;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=notifications)]
+pub struct Notification {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub user_id: ID,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub link: Option<String>,
+    pub read_at: Option<Utc>,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=notifications)]
+pub struct NotificationChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    /// an identifier of your choosing, for example `comment_reply`
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    /// where the notification leads to in the app, relative to its base url
+    pub link: Option<String>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// how often a user receives an email digest of their unread notifications
+pub enum DigestFrequency {
+    Never,
+    Daily,
+    Weekly,
+}
+
+impl Default for DigestFrequency {
+    fn default() -> Self {
+        Self::Daily
+    }
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    /// unknown values fall back to the default
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "never" => Self::Never,
+            "daily" => Self::Daily,
+            "weekly" => Self::Weekly,
+            _ => Self::default(),
+        }
+    }
+
+    /// the minimum time between two digests, `None` if the user doesn't want any
+    pub fn period(&self) -> Option<chrono::Duration> {
+        match self {
+            Self::Never => None,
+            Self::Daily => Some(chrono::Duration::days(1)),
+            Self::Weekly => Some(chrono::Duration::weeks(1)),
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable)]
+#[diesel(table_name=notification_preferences, primary_key(user_id))]
+pub struct NotificationPreference {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    /// one of [`DigestFrequency`]
+    pub digest_frequency: String,
+    pub last_digest_at: Option<Utc>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=push_subscriptions)]
+/// a browser a user subscribed to web pushes from
+pub struct PushSubscription {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub user_id: ID,
+    /// the push service's url for the browser
+    pub endpoint: String,
+    /// the browser's public key (base64url)
+    pub p256dh: String,
+    /// the browser's authentication secret (base64url)
+    pub auth: String,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=push_subscriptions)]
+pub struct PushSubscriptionChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+}
+
+impl Notification {
+    /// Create an entry in [`db`](`Connection`)'s `notifications` table using the data in [`item`](`NotificationChangeset`)
+    pub fn create(db: &mut Connection, item: &NotificationChangeset) -> QueryResult<Self> {
+        use schema::notifications::dsl::*;
+
+        insert_into(notifications)
+            .values(item)
+            .get_result::<Notification>(db)
+    }
+
+    /// marks the notification [`item_id`](`ID`) of [`item_user_id`](`ID`) as read
+    pub fn mark_read(db: &mut Connection, item_user_id: ID, item_id: ID) -> QueryResult<usize> {
+        use schema::notifications::dsl::*;
+
+        diesel::update(
+            notifications
+                .filter(id.eq(item_id))
+                .filter(user_id.eq(item_user_id))
+                .filter(read_at.is_null()),
+        )
+        .set(read_at.eq(Some(now())))
+        .execute(db)
+    }
+
+    /// marks every notification of [`item_user_id`](`ID`) as read
+    pub fn mark_all_read(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::notifications::dsl::*;
+
+        diesel::update(
+            notifications
+                .filter(user_id.eq(item_user_id))
+                .filter(read_at.is_null()),
+        )
+        .set(read_at.eq(Some(now())))
+        .execute(db)
+    }
+
+    /// the number of notifications [`item_user_id`](`ID`) hasn't read
+    pub fn unread_count(db: &mut Connection, item_user_id: ID) -> QueryResult<i64> {
+        use schema::notifications::dsl::*;
+
+        notifications
+            .filter(user_id.eq(item_user_id))
+            .filter(read_at.is_null())
+            .count()
+            .get_result(db)
+    }
+
+    /// the [`limit`] most recent notifications of [`item_user_id`](`ID`), most recent first
+    pub fn recent_for_user(
+        db: &mut Connection,
+        item_user_id: ID,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        use schema::notifications::dsl::*;
+
+        notifications
+            .filter(user_id.eq(item_user_id))
+            .order(created_at.desc())
+            .limit(limit)
+            .load::<Notification>(db)
+    }
+
+    /// the unread notifications of [`item_user_id`](`ID`) created after [`since`], oldest first
+    pub fn unread_since(
+        db: &mut Connection,
+        item_user_id: ID,
+        since: Option<Utc>,
+    ) -> QueryResult<Vec<Self>> {
+        use schema::notifications::dsl::*;
+
+        let mut query = notifications
+            .filter(user_id.eq(item_user_id))
+            .filter(read_at.is_null())
+            .into_boxed();
+
+        if let Some(since) = since {
+            query = query.filter(created_at.gt(since));
+        }
+
+        query.order(created_at.asc()).load::<Notification>(db)
+    }
+
+    /// the ids of all users who have at least one unread notification
+    pub fn users_with_unread(db: &mut Connection) -> QueryResult<Vec<ID>> {
+        use schema::notifications::dsl::*;
+
+        notifications
+            .filter(read_at.is_null())
+            .select(user_id)
+            .distinct()
+            .load::<ID>(db)
+    }
+
+    /// every notification of [`item_user_id`](`ID`), most recent first
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use schema::notifications::dsl::*;
+
+        notifications
+            .filter(user_id.eq(item_user_id))
+            .order(created_at.desc())
+            .load::<Notification>(db)
+    }
+
+    /// deletes every notification of [`item_user_id`](`ID`)
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::notifications::dsl::*;
+
+        diesel::delete(notifications.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
+
+impl NotificationPreference {
+    /// the preferences of [`item_user_id`](`ID`), or the defaults if they never changed them
+    pub fn read_or_default(db: &mut Connection, item_user_id: ID) -> QueryResult<Self> {
+        use schema::notification_preferences::dsl::*;
+
+        let preference = notification_preferences
+            .filter(user_id.eq(item_user_id))
+            .first::<NotificationPreference>(db)
+            .optional()?;
+
+        Ok(preference.unwrap_or(NotificationPreference {
+            user_id: item_user_id,
+            digest_frequency: DigestFrequency::default().as_str().to_string(),
+            last_digest_at: None,
+        }))
+    }
+
+    /// the [`DigestFrequency`] stored in [`self.digest_frequency`]
+    pub fn frequency(&self) -> DigestFrequency {
+        DigestFrequency::parse(&self.digest_frequency)
+    }
+
+    /// sets how often [`item_user_id`](`ID`) receives digests
+    pub fn set_frequency(
+        db: &mut Connection,
+        item_user_id: ID,
+        frequency: DigestFrequency,
+    ) -> QueryResult<usize> {
+        use schema::notification_preferences::dsl::*;
+
+        insert_into(notification_preferences)
+            .values((
+                user_id.eq(item_user_id),
+                digest_frequency.eq(frequency.as_str()),
+            ))
+            .on_conflict(user_id)
+            .do_update()
+            .set(digest_frequency.eq(frequency.as_str()))
+            .execute(db)
+    }
+
+    /// records that [`item_user_id`](`ID`) was sent a digest at [`sent_at`]
+    pub fn set_last_digest_at(
+        db: &mut Connection,
+        item_user_id: ID,
+        frequency: DigestFrequency,
+        sent_at: Utc,
+    ) -> QueryResult<usize> {
+        use schema::notification_preferences::dsl::*;
+
+        insert_into(notification_preferences)
+            .values((
+                user_id.eq(item_user_id),
+                digest_frequency.eq(frequency.as_str()),
+                last_digest_at.eq(Some(sent_at)),
+            ))
+            .on_conflict(user_id)
+            .do_update()
+            .set(last_digest_at.eq(Some(sent_at)))
+            .execute(db)
+    }
+
+    /// deletes the preferences of [`item_user_id`](`ID`)
+    pub fn delete(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::notification_preferences::dsl::*;
+
+        diesel::delete(notification_preferences.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
+
+impl PushSubscription {
+    /// records a browser's subscription, or hands the existing subscription of its endpoint over
+    /// to [`item.user_id`](`PushSubscriptionChangeset`)
+    pub fn subscribe(db: &mut Connection, item: &PushSubscriptionChangeset) -> QueryResult<usize> {
+        use schema::push_subscriptions::dsl::*;
+
+        insert_into(push_subscriptions)
+            .values(item)
+            .on_conflict(endpoint)
+            .do_update()
+            .set(item)
+            .execute(db)
+    }
+
+    /// deletes the subscription of [`item_endpoint`] if it's [`item_user_id`](`ID`)'s
+    pub fn unsubscribe(
+        db: &mut Connection,
+        item_user_id: ID,
+        item_endpoint: &str,
+    ) -> QueryResult<usize> {
+        use schema::push_subscriptions::dsl::*;
+
+        diesel::delete(
+            push_subscriptions
+                .filter(user_id.eq(item_user_id))
+                .filter(endpoint.eq(item_endpoint)),
+        )
+        .execute(db)
+    }
+
+    /// the browsers [`item_user_id`](`ID`) subscribed from
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use schema::push_subscriptions::dsl::*;
+
+        push_subscriptions
+            .filter(user_id.eq(item_user_id))
+            .load::<PushSubscription>(db)
+    }
+
+    pub fn delete(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use schema::push_subscriptions::dsl::*;
+
+        diesel::delete(push_subscriptions.filter(id.eq(item_id))).execute(db)
+    }
+
+    /// deletes every subscription of [`item_user_id`](`ID`)
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::push_subscriptions::dsl::*;
+
+        diesel::delete(push_subscriptions.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
+
+/// includes notifications and digest preferences in account exports, and deletes them with the account
+pub(crate) struct NotificationsAccountHook;
+
+impl crate::auth::account::AccountHook for NotificationsAccountHook {
+    fn name(&self) -> &'static str {
+        "notifications"
+    }
+
+    fn export(&self, db: &mut Connection, user_id: ID) -> QueryResult<serde_json::Value> {
+        Ok(serde_json::json!({
+            "preferences": NotificationPreference::read_or_default(db, user_id)?,
+            "notifications": Notification::fetch_all_for_user(db, user_id)?,
+        }))
+    }
+
+    fn delete(&self, db: &mut Connection, user_id: ID) -> QueryResult<()> {
+        Notification::delete_all_for_user(db, user_id)?;
+        NotificationPreference::delete(db, user_id)?;
+        PushSubscription::delete_all_for_user(db, user_id)?;
+
+        Ok(())
+    }
+}
+
+/// the current time, in the timestamp type used by the enabled database
+pub(crate) fn now() -> Utc {
+    #[cfg(not(feature = "database_sqlite"))]
+    return chrono::Utc::now();
+
+    #[cfg(feature = "database_sqlite")]
+    return chrono::Utc::now().naive_utc();
+}
@@ -0,0 +1,221 @@
+use super::push::{Delivery, WebPush};
+use super::{Notification, NotificationChangeset, PushSubscription, ID};
+use crate::auth::User;
+use crate::{Connection, Mailer};
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use tera::{Context, Tera};
+
+const EMAIL_TEXT_TEMPLATE: &str = r#"
+(This is an automated message.)
+
+{{ notification.title }}
+
+{{ notification.body }}{% if notification.link %}
+
+{{ base_url }}{{ notification.link }}{% endif %}
+"#;
+
+const EMAIL_HTML_TEMPLATE: &str = r#"
+<p>(This is an automated message.)</p>
+
+<h3>{% if notification.link %}<a href="{{ base_url }}{{ notification.link }}">{{ notification.title }}</a>{% else %}{{ notification.title }}{% endif %}</h3>
+<p>{{ notification.body }}</p>
+"#;
+
+/// pushes are limited to a few kilobytes, longer bodies are cut
+const MAX_PUSH_BODY_LENGTH: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// where a notification is delivered
+pub enum Channel {
+    /// the app's notification bell (with the realtime plugin, open tabs get it right away on the
+    /// user's `user:<id>` channel)
+    InApp,
+    /// an email, sent right away rather than in the next digest
+    Email,
+    /// a web push to the browsers the user subscribed from
+    Push,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// what a notification says
+pub struct Payload {
+    /// an identifier of your choosing, for example `comment_reply`
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    /// where the notification leads to in the app, relative to its base url
+    pub link: Option<String>,
+}
+
+impl Payload {
+    pub fn new(kind: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            title: title.into(),
+            body: String::new(),
+            link: None,
+        }
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn link(mut self, link: impl Into<String>) -> Self {
+        self.link = Some(link.into());
+        self
+    }
+}
+
+/// sends a notification to a user, on each of its [`Channel`]s (in-app and web push by default)
+///
+/// ```rust,ignore
+/// Notify::user(todo.owner_id)
+///     .via(&[Channel::InApp, Channel::Email])
+///     .send(&mut db, Payload::new("todo_assigned", "A todo was assigned to you").link("/todos"))?;
+/// ```
+pub struct Notify {
+    user_id: ID,
+    channels: Vec<Channel>,
+    mailer: Option<Mailer>,
+    base_url: String,
+}
+
+impl Notify {
+    pub fn user(user_id: ID) -> Self {
+        Self {
+            user_id,
+            channels: vec![Channel::InApp, Channel::Push],
+            mailer: None,
+            base_url: "http://localhost:3000".to_string(),
+        }
+    }
+
+    pub fn via(mut self, channels: &[Channel]) -> Self {
+        self.channels = channels.to_vec();
+        self
+    }
+
+    /// the mailer of the [`Channel::Email`] (default: [`Mailer::default`])
+    pub fn mailer(mut self, mailer: &Mailer) -> Self {
+        self.mailer = Some(mailer.clone());
+        self
+    }
+
+    /// used to turn the link into an absolute url in emails (default: `http://localhost:3000`)
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// delivers `payload` on every channel, returns the in-app notification if it was one of them
+    ///
+    /// only storing the in-app notification can fail: emails and pushes that can't be delivered
+    /// are logged (and the subscriptions of browsers that unsubscribed deleted)
+    pub fn send(&self, db: &mut Connection, payload: Payload) -> QueryResult<Option<Notification>> {
+        let notification = if self.channels.contains(&Channel::InApp) {
+            let notification = Notification::create(
+                db,
+                &NotificationChangeset {
+                    user_id: self.user_id,
+                    kind: payload.kind.clone(),
+                    title: payload.title.clone(),
+                    body: payload.body.clone(),
+                    link: payload.link.clone(),
+                },
+            )?;
+
+            #[cfg(feature = "plugin_realtime")]
+            if let Err(err) = crate::realtime::broadcast(
+                &format!("user:{}", self.user_id),
+                &serde_json::json!({ "type": "notification", "notification": notification }),
+            ) {
+                println!("notifications: could not broadcast notification: {err}");
+            }
+
+            Some(notification)
+        } else {
+            None
+        };
+
+        if self.channels.contains(&Channel::Email) {
+            self.email(db, &payload)?;
+        }
+
+        if self.channels.contains(&Channel::Push) {
+            self.push(db, &payload, notification.as_ref().map(|n| n.id))?;
+        }
+
+        Ok(notification)
+    }
+
+    fn email(&self, db: &mut Connection, payload: &Payload) -> QueryResult<()> {
+        let user = match User::read(db, self.user_id) {
+            Ok(user) => user,
+            Err(diesel::result::Error::NotFound) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        // guests have nowhere to send it to
+        let email = match &user.email {
+            Some(email) => email,
+            None => return Ok(()),
+        };
+
+        let mut context = Context::new();
+        context.insert("notification", payload);
+        context.insert("base_url", self.base_url.trim_end_matches('/'));
+
+        let rendered = Tera::one_off(EMAIL_TEXT_TEMPLATE, &context, false)
+            .and_then(|text| Ok((text, Tera::one_off(EMAIL_HTML_TEMPLATE, &context, true)?)));
+        let (text, html) = match rendered {
+            Ok(rendered) => rendered,
+            Err(err) => {
+                println!("notifications: could not render the email: {err:#?}");
+                return Ok(());
+            }
+        };
+
+        match &self.mailer {
+            Some(mailer) => mailer.send(email, &payload.title, &text, &html),
+            None => Mailer::default().send(email, &payload.title, &text, &html),
+        }
+
+        Ok(())
+    }
+
+    fn push(
+        &self,
+        db: &mut Connection,
+        payload: &Payload,
+        notification_id: Option<ID>,
+    ) -> QueryResult<()> {
+        let web_push = match WebPush::global() {
+            Some(web_push) => web_push,
+            None => return Ok(()),
+        };
+
+        let data = serde_json::json!({
+            "id": notification_id,
+            "kind": payload.kind,
+            "title": payload.title,
+            "body": payload.body.chars().take(MAX_PUSH_BODY_LENGTH).collect::<String>(),
+            "link": payload.link,
+        });
+
+        for subscription in PushSubscription::fetch_all_for_user(db, self.user_id)? {
+            match web_push.send(&subscription, &data) {
+                Ok(Delivery::Sent) => {}
+                Ok(Delivery::Expired) => {
+                    PushSubscription::delete(db, subscription.id)?;
+                }
+                Err(err) => println!("notifications: could not push notification: {err}"),
+            }
+        }
+
+        Ok(())
+    }
+}
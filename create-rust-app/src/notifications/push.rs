@@ -0,0 +1,246 @@
+//! Web push notifications, sent to the browsers the users subscribed from.
+//!
+//! Pushes are encrypted ([RFC 8291](https://www.rfc-editor.org/rfc/rfc8291)) and signed with the
+//! app's VAPID key ([RFC 8292](https://www.rfc-editor.org/rfc/rfc8292)), set by the
+//! `VAPID_PRIVATE_KEY` environment variable (the base64url-encoded private key, ex: the one
+//! `npx web-push generate-vapid-keys` prints). The frontend subscribes with the public key served
+//! by the `GET .../push/public-key` endpoint.
+use super::PushSubscription;
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes128Gcm, KeyInit, Nonce};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use p256::ecdh::EphemeralSecret;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::PublicKey;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// the size of the encrypted records, pushes are sent in a single one
+const RECORD_SIZE: u32 = 4096;
+
+lazy_static::lazy_static! {
+    static ref GLOBAL: Option<WebPush> = WebPush::from_env();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// what became of a push
+pub enum Delivery {
+    Sent,
+    /// the browser unsubscribed, the subscription should be deleted
+    Expired,
+}
+
+#[derive(Clone)]
+/// sends web pushes, signed with the app's VAPID key
+pub struct WebPush {
+    signing_key: SigningKey,
+    public_key: String,
+    /// a way for push services to reach the app's operators, ex: `mailto:admin@example.com`
+    subject: String,
+    /// how long push services keep pushes for offline browsers, in seconds
+    ttl: u32,
+}
+
+impl std::fmt::Debug for WebPush {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebPush")
+            .field("public_key", &self.public_key)
+            .field("subject", &self.subject)
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+impl WebPush {
+    /// `private_key` is the base64url-encoded VAPID private key
+    pub fn new(private_key: &str, subject: impl Into<String>) -> Result<Self, String> {
+        let signing_key = SigningKey::from_slice(&decode(private_key)?)
+            .map_err(|_| "Invalid VAPID private key".to_string())?;
+        let public_key = URL_SAFE_NO_PAD.encode(
+            signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes(),
+        );
+
+        Ok(Self {
+            signing_key,
+            public_key,
+            subject: subject.into(),
+            ttl: 24 * 60 * 60,
+        })
+    }
+
+    /// reads the key from `VAPID_PRIVATE_KEY`, and the subject from `VAPID_SUBJECT` (default:
+    /// `mailto:` the `SMTP_FROM_ADDRESS`); `None` if there's no key
+    pub fn from_env() -> Option<Self> {
+        let private_key = std::env::var("VAPID_PRIVATE_KEY").ok()?;
+        let subject = std::env::var("VAPID_SUBJECT").unwrap_or_else(|_| {
+            format!(
+                "mailto:{}",
+                std::env::var("SMTP_FROM_ADDRESS")
+                    .unwrap_or_else(|_| "create-rust-app@localhost".to_string())
+            )
+        });
+
+        match Self::new(&private_key, subject) {
+            Ok(web_push) => Some(web_push),
+            Err(err) => {
+                println!("Web push is disabled: {err}");
+                None
+            }
+        }
+    }
+
+    /// the process' web push, `None` if `VAPID_PRIVATE_KEY` isn't set
+    pub fn global() -> Option<&'static Self> {
+        GLOBAL.as_ref()
+    }
+
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// the base64url-encoded key browsers subscribe with (their `applicationServerKey`)
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// pushes `payload` to `subscription`'s browser, whose service worker gets it as JSON
+    pub fn send(
+        &self,
+        subscription: &PushSubscription,
+        payload: &serde_json::Value,
+    ) -> Result<Delivery, String> {
+        let body = encrypt(subscription, payload.to_string().as_bytes())?;
+        let authorization = self.authorization(&subscription.endpoint)?;
+
+        // the blocking client is used in its own thread so it works from async handlers too
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let response = reqwest::blocking::Client::new()
+                        .post(&subscription.endpoint)
+                        .header("TTL", self.ttl.to_string())
+                        .header("Content-Encoding", "aes128gcm")
+                        .header("Content-Type", "application/octet-stream")
+                        .header("Authorization", authorization)
+                        .body(body)
+                        .send()
+                        .map_err(|err| format!("Could not reach the push service: {err}"))?;
+                    let status = response.status();
+
+                    match status.as_u16() {
+                        404 | 410 => Ok(Delivery::Expired),
+                        _ if status.is_success() => Ok(Delivery::Sent),
+                        _ => Err(format!(
+                            "The push service responded with {status}: {}",
+                            response.text().unwrap_or_default()
+                        )),
+                    }
+                })
+                .join()
+                .expect("the push request's thread panicked")
+        })
+    }
+
+    /// the VAPID `Authorization` header for the push service of `endpoint`
+    fn authorization(&self, endpoint: &str) -> Result<String, String> {
+        let endpoint = reqwest::Url::parse(endpoint)
+            .map_err(|_| "Invalid push subscription endpoint".to_string())?;
+
+        let header = URL_SAFE_NO_PAD.encode(r#"{"typ":"JWT","alg":"ES256"}"#);
+        let claims = URL_SAFE_NO_PAD.encode(
+            serde_json::json!({
+                "aud": endpoint.origin().ascii_serialization(),
+                "exp": chrono::Utc::now().timestamp() + 12 * 60 * 60,
+                "sub": self.subject,
+            })
+            .to_string(),
+        );
+        let message = format!("{header}.{claims}");
+        let signature: Signature = self.signing_key.sign(message.as_bytes());
+
+        Ok(format!(
+            "vapid t={message}.{}, k={}",
+            URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+            self.public_key
+        ))
+    }
+}
+
+/// browsers' keys are base64url-encoded, with or without padding
+fn decode(value: &str) -> Result<Vec<u8>, String> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| "Invalid base64url key".to_string())
+}
+
+/// HKDF-SHA256 of at most 32 bytes
+fn hkdf(salt: &[u8], ikm: &[u8], info: &[u8], length: usize) -> Vec<u8> {
+    let mut extract =
+        <HmacSha256 as Mac>::new_from_slice(salt).expect("HMAC takes keys of any size");
+    extract.update(ikm);
+    let prk = extract.finalize().into_bytes();
+
+    let mut expand =
+        <HmacSha256 as Mac>::new_from_slice(&prk).expect("HMAC takes keys of any size");
+    expand.update(info);
+    expand.update(&[1]);
+    expand.finalize().into_bytes()[..length].to_vec()
+}
+
+/// `payload` encrypted for `subscription`'s browser, in a single `aes128gcm` record
+fn encrypt(subscription: &PushSubscription, payload: &[u8]) -> Result<Vec<u8>, String> {
+    let user_agent_key = decode(&subscription.p256dh)?;
+    let user_agent_public_key = PublicKey::from_sec1_bytes(&user_agent_key)
+        .map_err(|_| "Invalid push subscription key".to_string())?;
+    let auth_secret = decode(&subscription.auth)?;
+
+    let secret = EphemeralSecret::random(&mut OsRng);
+    let public_key = secret.public_key().to_encoded_point(false);
+    let shared_secret = secret.diffie_hellman(&user_agent_public_key);
+
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(&user_agent_key);
+    key_info.extend_from_slice(public_key.as_bytes());
+    let ikm = hkdf(
+        &auth_secret,
+        shared_secret.raw_secret_bytes(),
+        &key_info,
+        32,
+    );
+
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let content_key = hkdf(&salt, &ikm, b"Content-Encoding: aes128gcm\0", 16);
+    let nonce = hkdf(&salt, &ikm, b"Content-Encoding: nonce\0", 12);
+
+    // the last (and only) record is delimited by a 2
+    let mut plaintext = payload.to_vec();
+    plaintext.push(2);
+    if plaintext.len() + 16 > RECORD_SIZE as usize - 86 {
+        return Err("The push's payload is too large".to_string());
+    }
+
+    let ciphertext = Aes128Gcm::new_from_slice(&content_key)
+        .map_err(|err| err.to_string())?
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| "Could not encrypt the push".to_string())?;
+
+    let mut body = salt.to_vec();
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(public_key.as_bytes().len() as u8);
+    body.extend_from_slice(public_key.as_bytes());
+    body.extend(ciphertext);
+
+    Ok(body)
+}
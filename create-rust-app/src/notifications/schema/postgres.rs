@@ -0,0 +1,33 @@
+table! {
+  notification_preferences (user_id) {
+      user_id -> Int4,
+      digest_frequency -> Text,
+      last_digest_at -> Nullable<Timestamptz>,
+  }
+}
+
+table! {
+  notifications (id) {
+      id -> Int4,
+      user_id -> Int4,
+      kind -> Text,
+      title -> Text,
+      body -> Text,
+      link -> Nullable<Text>,
+      read_at -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  push_subscriptions (id) {
+      id -> Int4,
+      user_id -> Int4,
+      endpoint -> Text,
+      p256dh -> Text,
+      auth -> Text,
+      created_at -> Timestamptz,
+  }
+}
+
+allow_tables_to_appear_in_same_query!(notification_preferences, notifications, push_subscriptions,);
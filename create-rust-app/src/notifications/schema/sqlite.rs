@@ -0,0 +1,33 @@
+table! {
+  notification_preferences (user_id) {
+      user_id -> Integer,
+      digest_frequency -> Text,
+      last_digest_at -> Nullable<Timestamp>,
+  }
+}
+
+table! {
+  notifications (id) {
+      id -> Integer,
+      user_id -> Integer,
+      kind -> Text,
+      title -> Text,
+      body -> Text,
+      link -> Nullable<Text>,
+      read_at -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  push_subscriptions (id) {
+      id -> Integer,
+      user_id -> Integer,
+      endpoint -> Text,
+      p256dh -> Text,
+      auth -> Text,
+      created_at -> Timestamp,
+  }
+}
+
+allow_tables_to_appear_in_same_query!(notification_preferences, notifications, push_subscriptions,);
@@ -0,0 +1,48 @@
+use fang::serde::{Deserialize, Serialize};
+use fang::typetag;
+use fang::{FangError, Queueable, Runnable, Scheduled};
+
+use super::digest::{self, DigestConfig};
+use crate::{Database, Mailer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "fang::serde")]
+/// periodically emails digests of unread notifications to the users whose digest is due
+///
+/// schedule it once with `create_rust_app::tasks::queue().schedule_task(&SendDigests { base_url: None })`
+///
+/// digests link to `base_url` (default: `http://localhost:3000`)
+pub struct SendDigests {
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+#[typetag::serde]
+impl Runnable for SendDigests {
+    fn run(&self, _queue: &dyn Queueable) -> Result<(), FangError> {
+        let mut db = Database::new().get_connection();
+        let mailer = Mailer::default();
+
+        let mut config = DigestConfig::default();
+        if let Some(base_url) = &self.base_url {
+            config.base_url = base_url.clone();
+        }
+
+        digest::send_digests(&mut db, &mailer, &config).map_err(|err| FangError {
+            description: format!("Could not send notification digests: {err}"),
+        })?;
+
+        Ok(())
+    }
+
+    fn uniq(&self) -> bool {
+        true
+    }
+
+    fn cron(&self) -> Option<Scheduled> {
+        // every hour, on the hour
+        //               sec  min   hour   day of month   month   day of week   year
+        let expression = "0 0 * * * * *";
+        Some(Scheduled::CronPattern(expression.to_string()))
+    }
+}
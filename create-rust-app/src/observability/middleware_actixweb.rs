@@ -0,0 +1,82 @@
+use actix_http::header::{HeaderName, HeaderValue};
+use actix_web::body::MessageBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use tracing::Instrument;
+
+/// traces the requests it handles, see [`observability`](`super`)
+///
+/// ```rust,ignore
+/// let app = App::new().wrap(create_rust_app::observability::TraceContext);
+/// ```
+pub struct TraceContext;
+
+impl<S, B> Transform<S, ServiceRequest> for TraceContext
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TraceContextMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TraceContextMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct TraceContextMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TraceContextMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let span = super::request_span(
+            req.method().as_str(),
+            req.match_pattern().as_deref().unwrap_or(req.path()),
+            req.headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+        );
+
+        Box::pin(async move {
+            let result = service.call(req).instrument(span.clone()).await;
+
+            match result {
+                Ok(mut response) => {
+                    let trace = super::response_trace(&span, response.status().as_u16());
+                    if let Some(value) = trace.and_then(|trace| HeaderValue::from_str(&trace).ok())
+                    {
+                        response
+                            .headers_mut()
+                            .insert(HeaderName::from_static("traceresponse"), value);
+                    }
+
+                    Ok(response)
+                }
+                Err(err) => {
+                    super::response_trace(&span, err.as_response_error().status_code().as_u16());
+
+                    Err(err)
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use poem::http::HeaderValue;
+use poem::{async_trait, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use tracing::Instrument;
+
+/// traces the requests it handles, see [`observability`](`super`)
+///
+/// ```rust,ignore
+/// let app = app.with(create_rust_app::observability::TraceContext);
+/// ```
+pub struct TraceContext;
+
+impl<E: Endpoint> Middleware<E> for TraceContext {
+    type Output = TraceContextEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        TraceContextEndpoint { ep }
+    }
+}
+
+pub struct TraceContextEndpoint<E> {
+    ep: E,
+}
+
+#[async_trait]
+impl<E: Endpoint> Endpoint for TraceContextEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let span = super::request_span(
+            req.method().as_str(),
+            req.uri().path(),
+            req.headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+        );
+
+        let result = self
+            .ep
+            .call(req)
+            .instrument(span.clone())
+            .await
+            .map(IntoResponse::into_response);
+
+        match result {
+            Ok(mut response) => {
+                let trace = super::response_trace(&span, response.status().as_u16());
+                if let Some(value) = trace.and_then(|trace| HeaderValue::from_str(&trace).ok()) {
+                    response.headers_mut().insert("traceresponse", value);
+                }
+
+                Ok(response)
+            }
+            Err(err) => {
+                super::response_trace(&span, err.status().as_u16());
+
+                Err(err)
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Tracing for the observability plugin (`plugin_observability` feature): logs and spans through
+//! [`tracing`], exported to an OpenTelemetry collector over OTLP.
+//!
+//! ```rust,ignore
+//! let _observability = create_rust_app::observability::init("my-app");
+//! // actix-web
+//! let app = App::new().wrap(create_rust_app::observability::TraceContext);
+//! // poem
+//! let app = app.with(create_rust_app::observability::TraceContext);
+//! ```
+//!
+//! [`init`] logs to stdout (filtered by `RUST_LOG`, `info` by default), and exports the spans when
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` is set (ex: `http://localhost:4317`, the collector's gRPC port).
+//! The [`TraceContext`] middleware opens a span for every request, continuing the trace of the
+//! request's `traceparent` header ([W3C Trace Context](https://www.w3.org/TR/trace-context/)) and
+//! returning its own in the `traceresponse` header, so the frontend can link its requests to
+//! their traces.
+//!
+//! The auth plugin's login, registration and refresh, and the storage plugin's uploads, downloads
+//! and deletions have their own spans, and checking a database connection out of the pool records
+//! how long it took (a debug event, `RUST_LOG=info,create_rust_app=debug` to see it). Add your own spans with [`tracing::instrument`]. With actix-web, the
+//! closures given to `web::block` run on another thread: enter the request's span in them to keep
+//! their spans in its trace (`let span = tracing::Span::current();` then
+//! `web::block(move || span.in_scope(|| ...))`).
+use opentelemetry::global;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::Resource;
+use std::collections::HashMap;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+#[cfg(feature = "backend_actix-web")]
+mod middleware_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use middleware_actixweb::TraceContext;
+#[cfg(feature = "backend_poem")]
+mod middleware_poem;
+#[cfg(feature = "backend_poem")]
+pub use middleware_poem::TraceContext;
+
+/// flushes the spans left to export when dropped, keep it until the app exits
+#[must_use]
+pub struct ObservabilityGuard {
+    exporting: bool,
+}
+
+impl Drop for ObservabilityGuard {
+    fn drop(&mut self) {
+        if self.exporting {
+            global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// sets up the global [`tracing`] subscriber, and the OTLP exporter when
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set
+///
+/// `service_name` names the app in the traces, unless `OTEL_SERVICE_NAME` is set
+pub fn init(service_name: &str) -> ObservabilityGuard {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let service_name =
+        std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| service_name.to_string());
+    let tracer = if std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_ok() {
+        let installed = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(opentelemetry_otlp::new_exporter().tonic())
+            .with_trace_config(
+                opentelemetry_sdk::trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", service_name),
+                ])),
+            )
+            .install_batch(opentelemetry_sdk::runtime::Tokio);
+
+        match installed {
+            Ok(tracer) => Some(tracer),
+            Err(err) => {
+                println!("Could not export traces: {err}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let exporting = tracer.is_some();
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let initialized = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
+        .try_init();
+    if let Err(err) = initialized {
+        println!("Could not set up tracing: {err}");
+    }
+
+    ObservabilityGuard { exporting }
+}
+
+/// the span of a request, child of the trace in its `traceparent` header (if any)
+fn request_span<'a>(
+    method: &str,
+    path: &str,
+    headers: impl Iterator<Item = (&'a str, &'a str)>,
+) -> tracing::Span {
+    let headers = headers
+        .map(|(name, value)| (name.to_lowercase(), value.to_string()))
+        .collect::<HashMap<String, String>>();
+    let parent = global::get_text_map_propagator(|propagator| propagator.extract(&headers));
+
+    let span = tracing::info_span!(
+        "request",
+        otel.name = %format!("{method} {path}"),
+        otel.kind = "server",
+        http.method = %method,
+        http.target = %path,
+        http.status_code = tracing::field::Empty,
+    );
+    span.set_parent(parent);
+
+    span
+}
+
+/// records the response's status in the request's span, and its `traceresponse` header (`None`
+/// when the span isn't exported)
+fn response_trace(span: &tracing::Span, status: u16) -> Option<String> {
+    span.record("http.status_code", status);
+
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    if !span_context.is_valid() {
+        return None;
+    }
+
+    Some(format!(
+        "00-{}-{}-{:02x}",
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    ))
+}
+
+/// traces the connections checked out of the database pool, see [`Database`](`crate::Database`)
+#[derive(Debug)]
+pub(crate) struct PoolEvents;
+
+impl diesel::r2d2::HandleEvent for PoolEvents {
+    fn handle_checkout(&self, event: diesel::r2d2::event::CheckoutEvent) {
+        tracing::debug!(
+            db.connection_id = event.connection_id(),
+            db.wait_ms = event.duration().as_secs_f64() * 1000.0,
+            "database connection checked out"
+        );
+    }
+
+    fn handle_timeout(&self, event: diesel::r2d2::event::TimeoutEvent) {
+        tracing::warn!(
+            db.timeout_ms = event.timeout().as_millis() as u64,
+            "timed out waiting for a database connection"
+        );
+    }
+}
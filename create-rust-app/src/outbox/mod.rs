@@ -0,0 +1,491 @@
+//! Transactional outbox (`plugin_outbox` feature): events, webhooks and emails are recorded in the
+//! `outbox_messages` table, in the same transaction as the changes which triggered them, and a
+//! relay delivers them once it's committed. They're never sent for a change which was rolled back,
+//! nor lost when the app stops right after a commit.
+//!
+//! ```rust,ignore
+//! use create_rust_app::outbox::{self, OutboxConfig, Relay};
+//!
+//! diesel::Connection::transaction::<_, diesel::result::Error, _>(&mut db, |db| {
+//!     let order = Order::create(db, &item)?;
+//!     outbox::publish(db, &OrderPlaced { order_id: order.id })?;
+//!     outbox::send_email(db, &email, "Thanks for your order!", &text, &html)?;
+//!     Ok(order)
+//! })?;
+//!
+//! // in main(): the events published through the outbox have to be registered with the relay
+//! Relay::new(OutboxConfig::default())
+//!     .event::<OrderPlaced>()
+//!     .mailer(app_data.mailer.clone())
+//!     .spawn(app_data.database.clone());
+//! ```
+//!
+//! The relay retries failed messages with an exponential backoff (see [`OutboxConfig`]), so they're
+//! delivered at least once: subscribers should be idempotent. Apps can relay messages of their own
+//! kinds with [`Relay::handler`].
+use crate::diesel::*;
+use crate::{Connection, Database, Mailer};
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// the [`OutboxMessage::kind`] of events, see [`publish`]
+pub const EVENT: &str = "event";
+/// the [`OutboxMessage::kind`] of webhooks, see [`dispatch_webhook`]
+pub const WEBHOOK: &str = "webhook";
+/// the [`OutboxMessage::kind`] of emails, see [`send_email`]
+pub const EMAIL: &str = "email";
+
+/// how long a message is hidden from other relays while one of them delivers it
+const DELIVERY_LEASE_MINUTES: i64 = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// where an [`OutboxMessage`] is in its delivery
+pub enum MessageStatus {
+    /// waiting for the transaction's commit and the relay, or for a retry
+    Pending,
+    Delivered,
+    /// failed [`OutboxConfig::max_attempts`] times
+    Failed,
+}
+
+impl MessageStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "delivered" => Some(Self::Delivered),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=outbox_messages)]
+/// an event, webhook or email to deliver once the transaction which recorded it is committed
+pub struct OutboxMessage {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    /// what delivers it: [`EVENT`], [`WEBHOOK`], [`EMAIL`] or one of the app's [`Relay::handler`]s
+    pub kind: String,
+    /// the event's name or the webhook's event type, empty for emails
+    pub topic: String,
+    /// what's delivered (json)
+    pub payload: String,
+    /// one of [`MessageStatus`]
+    pub status: String,
+    /// how many attempts failed
+    pub attempts: i32,
+    /// why the last attempt failed
+    pub last_error: Option<String>,
+    /// when the relay (re)tries to deliver it
+    pub next_attempt_at: Utc,
+    pub delivered_at: Option<Utc>,
+
+    pub created_at: Utc,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=outbox_messages)]
+pub struct OutboxMessageChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub kind: String,
+    pub topic: String,
+    pub payload: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// the payload of [`EMAIL`] messages
+pub struct OutboxEmail {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+#[derive(Debug, Clone)]
+/// how the relay delivers messages
+pub struct OutboxConfig {
+    /// how often the relay looks for messages to deliver (default: 1 second)
+    pub poll_interval: Duration,
+    /// the maximum number of messages delivered per poll (default: 100)
+    pub batch_size: i64,
+    /// how many times a message is attempted before it's failed (default: 10)
+    pub max_attempts: i32,
+    /// how long to wait before the first retry, doubled after every failure (default: 30 seconds)
+    pub base_delay: Duration,
+    /// the longest wait between two retries (default: 1 hour)
+    pub max_delay: Duration,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            batch_size: 100,
+            max_attempts: 10,
+            base_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl OutboxConfig {
+    /// how long to wait before retrying a message which failed `attempts` times
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let exponent = attempts.saturating_sub(1).clamp(0, 31) as u32;
+
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_delay)
+    }
+}
+
+impl OutboxMessage {
+    /// records a message in the outbox, call it in the transaction of the change which triggers it
+    pub fn add<T: Serialize>(
+        db: &mut Connection,
+        kind: &str,
+        topic: &str,
+        payload: &T,
+    ) -> QueryResult<Self> {
+        let payload = serde_json::to_string(payload)
+            .map_err(|err| diesel::result::Error::SerializationError(Box::new(err)))?;
+
+        insert_into(outbox_messages::table)
+            .values(&OutboxMessageChangeset {
+                kind: kind.to_string(),
+                topic: topic.to_string(),
+                payload,
+            })
+            .get_result::<Self>(db)
+    }
+
+    /// the messages which failed [`OutboxConfig::max_attempts`] times, most recent first
+    pub fn failed(db: &mut Connection, limit: i64) -> QueryResult<Vec<Self>> {
+        use schema::outbox_messages::dsl::*;
+
+        outbox_messages
+            .filter(status.eq(MessageStatus::Failed.as_str()))
+            .order(created_at.desc())
+            .limit(limit)
+            .load::<OutboxMessage>(db)
+    }
+
+    /// queues a failed message again, returns 0 if there's no such failed message
+    pub fn retry(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use schema::outbox_messages::dsl::*;
+
+        diesel::update(
+            outbox_messages
+                .filter(id.eq(item_id))
+                .filter(status.eq(MessageStatus::Failed.as_str())),
+        )
+        .set((
+            status.eq(MessageStatus::Pending.as_str()),
+            attempts.eq(0),
+            next_attempt_at.eq(now()),
+        ))
+        .execute(db)
+    }
+
+    /// deletes the messages delivered before `delivered_before`, returns how many were deleted
+    pub fn prune(db: &mut Connection, delivered_before: Utc) -> QueryResult<usize> {
+        use schema::outbox_messages::dsl::*;
+
+        diesel::delete(
+            outbox_messages
+                .filter(status.eq(MessageStatus::Delivered.as_str()))
+                .filter(delivered_at.lt(delivered_before)),
+        )
+        .execute(db)
+    }
+
+    /// the pending messages which are due, oldest first
+    fn due(db: &mut Connection, limit: i64) -> QueryResult<Vec<Self>> {
+        use schema::outbox_messages::dsl::*;
+
+        outbox_messages
+            .filter(status.eq(MessageStatus::Pending.as_str()))
+            .filter(next_attempt_at.le(now()))
+            .order(id.asc())
+            .limit(limit)
+            .load::<OutboxMessage>(db)
+    }
+
+    /// pushes back the message's next attempt while it's delivered, returns `false` if another
+    /// relay got to it first
+    fn claim(&self, db: &mut Connection) -> QueryResult<bool> {
+        use schema::outbox_messages::dsl::*;
+
+        let current_time = now();
+        let claimed = diesel::update(
+            outbox_messages
+                .filter(id.eq(self.id))
+                .filter(status.eq(MessageStatus::Pending.as_str()))
+                .filter(next_attempt_at.le(current_time)),
+        )
+        .set(next_attempt_at.eq(current_time + chrono::Duration::minutes(DELIVERY_LEASE_MINUTES)))
+        .execute(db)?;
+
+        Ok(claimed == 1)
+    }
+
+    fn mark_delivered(&self, db: &mut Connection) -> QueryResult<usize> {
+        use schema::outbox_messages::dsl::*;
+
+        diesel::update(outbox_messages.filter(id.eq(self.id)))
+            .set((
+                status.eq(MessageStatus::Delivered.as_str()),
+                last_error.eq(None::<String>),
+                delivered_at.eq(Some(now())),
+            ))
+            .execute(db)
+    }
+
+    /// schedules the next attempt, or fails the message after [`OutboxConfig::max_attempts`]
+    fn mark_failed(
+        &self,
+        db: &mut Connection,
+        error: &str,
+        config: &OutboxConfig,
+    ) -> QueryResult<usize> {
+        use schema::outbox_messages::dsl::*;
+
+        let failed_attempts = self.attempts + 1;
+        let (next_status, next_attempt) = if failed_attempts >= config.max_attempts {
+            (MessageStatus::Failed, now())
+        } else {
+            let delay = chrono::Duration::from_std(config.backoff(failed_attempts))
+                .unwrap_or_else(|_| chrono::Duration::days(1));
+
+            (MessageStatus::Pending, now() + delay)
+        };
+
+        diesel::update(outbox_messages.filter(id.eq(self.id)))
+            .set((
+                status.eq(next_status.as_str()),
+                attempts.eq(failed_attempts),
+                last_error.eq(Some(error)),
+                next_attempt_at.eq(next_attempt),
+            ))
+            .execute(db)
+    }
+}
+
+#[cfg(feature = "plugin_events")]
+/// records `event`, the relay [publishes](`crate::events::publish_with`) it once the transaction is
+/// committed (if it was registered with [`Relay::event`])
+pub fn publish<E: crate::events::Event + Serialize>(
+    db: &mut Connection,
+    event: &E,
+) -> QueryResult<OutboxMessage> {
+    OutboxMessage::add(db, EVENT, E::NAME, event)
+}
+
+#[cfg(feature = "plugin_webhooks")]
+/// records a webhook event, the relay [dispatches](`crate::webhooks::dispatch`) it to the
+/// subscribed endpoints once the transaction is committed
+pub fn dispatch_webhook<T: Serialize>(
+    db: &mut Connection,
+    event_type: &str,
+    data: &T,
+) -> QueryResult<OutboxMessage> {
+    OutboxMessage::add(db, WEBHOOK, event_type, data)
+}
+
+/// records an email, the relay sends it with its [`Relay::mailer`] once the transaction is
+/// committed
+pub fn send_email(
+    db: &mut Connection,
+    to: &str,
+    subject: &str,
+    text: &str,
+    html: &str,
+) -> QueryResult<OutboxMessage> {
+    OutboxMessage::add(
+        db,
+        EMAIL,
+        "",
+        &OutboxEmail {
+            to: to.to_string(),
+            subject: subject.to_string(),
+            text: text.to_string(),
+            html: html.to_string(),
+        },
+    )
+}
+
+type Handler = Arc<dyn Fn(&mut Connection, &OutboxMessage) -> Result<(), String> + Send + Sync>;
+
+#[derive(Clone)]
+/// delivers the outbox's messages once their transaction is committed, by their
+/// [`kind`](`OutboxMessage::kind`)
+pub struct Relay {
+    config: OutboxConfig,
+    handlers: HashMap<String, Handler>,
+    /// the handlers of [`EVENT`] messages, by [`Event::NAME`](`crate::events::Event::NAME`)
+    events: HashMap<String, Handler>,
+}
+
+impl Relay {
+    /// a relay of [`WEBHOOK`] messages (with the webhooks plugin): register the events it
+    /// publishes, its mailer and the app's own kinds of messages
+    pub fn new(config: OutboxConfig) -> Self {
+        #[allow(unused_mut)]
+        let mut relay = Self {
+            config,
+            handlers: HashMap::new(),
+            events: HashMap::new(),
+        };
+
+        #[cfg(feature = "plugin_webhooks")]
+        {
+            relay = relay.handler(WEBHOOK, |db, message| {
+                let data = serde_json::from_str::<serde_json::Value>(&message.payload)
+                    .map_err(|err| err.to_string())?;
+
+                crate::webhooks::dispatch(db, &message.topic, &data)
+                    .map(|_| ())
+                    .map_err(|err| err.to_string())
+            });
+        }
+
+        relay
+    }
+
+    /// delivers the messages of `kind` with `handler`, they're retried when it returns an error
+    pub fn handler(
+        mut self,
+        kind: &str,
+        handler: impl Fn(&mut Connection, &OutboxMessage) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.handlers.insert(kind.to_string(), Arc::new(handler));
+        self
+    }
+
+    #[cfg(feature = "plugin_events")]
+    /// publishes the `E` events recorded with [`publish`] on the [event bus](`crate::events`)
+    pub fn event<E: crate::events::Event + serde::de::DeserializeOwned>(mut self) -> Self {
+        self.events.insert(
+            E::NAME.to_string(),
+            Arc::new(|db, message| {
+                let event = serde_json::from_str::<E>(&message.payload)
+                    .map_err(|err| format!("Could not read the '{}' event: {err}", E::NAME))?;
+                crate::events::publish_with(db, event);
+
+                Ok(())
+            }),
+        );
+        self
+    }
+
+    /// sends the [`EMAIL`] messages with `mailer`
+    pub fn mailer(self, mailer: Mailer) -> Self {
+        self.handler(EMAIL, move |_db, message| {
+            let email = serde_json::from_str::<OutboxEmail>(&message.payload)
+                .map_err(|err| err.to_string())?;
+            mailer.send(&email.to, &email.subject, &email.text, &email.html);
+
+            Ok(())
+        })
+    }
+
+    fn deliver(&self, db: &mut Connection, message: &OutboxMessage) -> Result<(), String> {
+        let handler = if message.kind == EVENT {
+            self.events.get(&message.topic).ok_or_else(|| {
+                format!(
+                    "The '{}' event isn't registered with the relay (`Relay::event`)",
+                    message.topic
+                )
+            })?
+        } else {
+            self.handlers.get(&message.kind).ok_or_else(|| {
+                format!(
+                    "There's no handler of '{}' messages (`Relay::handler`)",
+                    message.kind
+                )
+            })?
+        };
+
+        handler(db, message)
+    }
+
+    /// delivers the messages which are due, in the order they were recorded, returns how many
+    /// were delivered
+    pub fn deliver_due(&self, db: &mut Connection) -> QueryResult<usize> {
+        let mut delivered = 0;
+
+        for message in OutboxMessage::due(db, self.config.batch_size)? {
+            if !message.claim(db)? {
+                continue;
+            }
+
+            match self.deliver(db, &message) {
+                Ok(()) => {
+                    message.mark_delivered(db)?;
+                    delivered += 1;
+                }
+                Err(err) => {
+                    message.mark_failed(db, &err, &self.config)?;
+                }
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// starts a thread delivering the messages every [`OutboxConfig::poll_interval`]
+    ///
+    /// relays can run in several instances of the app, a message is only delivered by one of them
+    pub fn spawn(self, database: Database) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || loop {
+            match database.pool.get() {
+                Ok(mut db) => {
+                    if let Err(err) = self.deliver_due(&mut db) {
+                        println!("Could not relay the outbox: {err}");
+                    }
+                }
+                Err(err) => println!("Could not relay the outbox: {err}"),
+            }
+
+            std::thread::sleep(self.config.poll_interval);
+        })
+    }
+}
+
+fn now() -> Utc {
+    #[cfg(not(feature = "database_sqlite"))]
+    return chrono::Utc::now();
+
+    #[cfg(feature = "database_sqlite")]
+    return chrono::Utc::now().naive_utc();
+}
@@ -0,0 +1,9 @@
+#[cfg(feature = "database_sqlite")]
+mod sqlite;
+#[cfg(feature = "database_sqlite")]
+pub use sqlite::*;
+
+#[cfg(feature = "database_postgres")]
+mod postgres;
+#[cfg(feature = "database_postgres")]
+pub use postgres::*;
@@ -0,0 +1,14 @@
+table! {
+  outbox_messages (id) {
+      id -> Int4,
+      kind -> Text,
+      topic -> Text,
+      payload -> Text,
+      status -> Text,
+      attempts -> Int4,
+      last_error -> Nullable<Text>,
+      next_attempt_at -> Timestamptz,
+      delivered_at -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
@@ -0,0 +1,14 @@
+table! {
+  outbox_messages (id) {
+      id -> Integer,
+      kind -> Text,
+      topic -> Text,
+      payload -> Text,
+      status -> Text,
+      attempts -> Integer,
+      last_error -> Nullable<Text>,
+      next_attempt_at -> Timestamp,
+      delivered_at -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
@@ -0,0 +1,252 @@
+use actix_http::StatusCode;
+use actix_web::web::{Bytes, Data, Json};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::{Auth, User};
+use crate::payments::{self, stripe, Customer, Event, PaymentsConfig};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../checkout endpoint
+pub struct CheckoutInput {
+    /// one of the [`PaymentsConfig::prices`], the first one if `None`
+    price_id: Option<String>,
+    /// where Stripe sends the user back to, ex: the billing page
+    return_url: String,
+}
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../portal endpoint
+pub struct PortalInput {
+    return_url: String,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+fn config(config: Option<Data<PaymentsConfig>>) -> Data<PaymentsConfig> {
+    config.unwrap_or_else(|| Data::new(PaymentsConfig::from_env()))
+}
+
+/// handler for GET requests at the .../status endpoint
+///
+/// requires auth
+///
+/// returns whether the user is subscribed, see [`payments::status`]
+#[get("/status")]
+async fn status(
+    db: Data<Database>,
+    config: Option<Data<PaymentsConfig>>,
+    auth: Auth,
+) -> Result<HttpResponse> {
+    let config = self::config(config);
+
+    let result =
+        web::block(move || payments::status(&mut db.get_connection(), auth.user_id, &config))
+            .await?;
+
+    match result {
+        Ok(status) => Ok(HttpResponse::Ok().json(status)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the subscription.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../checkout endpoint
+///
+/// requires auth
+///
+/// creates the user's Stripe customer if they don't have one yet, and returns the url of a
+/// Checkout session subscribing them to the price
+#[post("/checkout")]
+async fn checkout(
+    db: Data<Database>,
+    config: Option<Data<PaymentsConfig>>,
+    auth: Auth,
+    Json(item): Json<CheckoutInput>,
+) -> Result<HttpResponse> {
+    let config = self::config(config);
+    if !config.stripe.is_configured() {
+        return Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Payments are not configured.",
+        ));
+    }
+
+    let price_id = match config.checkout_price(item.price_id.as_deref()) {
+        Some(price_id) => price_id.to_string(),
+        None => return Ok(error_response(StatusCode::BAD_REQUEST, "Unknown price.")),
+    };
+
+    let customer_id = match customer_id(db, &config, auth.user_id).await {
+        Ok(customer_id) => customer_id,
+        Err(response) => return Ok(response),
+    };
+
+    let session = config
+        .stripe
+        .create_checkout_session(&customer_id, &price_id, &item.return_url, &item.return_url)
+        .await;
+
+    match session {
+        Ok(url) => Ok(HttpResponse::Ok().json(json!({ "url": url }))),
+        Err(_) => Ok(error_response(
+            StatusCode::BAD_GATEWAY,
+            "Could not start the checkout.",
+        )),
+    }
+}
+
+/// the id of [`user_id`](`i32`)'s Stripe customer, created if they don't have one yet
+async fn customer_id(
+    db: Data<Database>,
+    config: &PaymentsConfig,
+    user_id: i32,
+) -> std::result::Result<String, HttpResponse> {
+    let db_ = db.clone();
+    let existing = web::block(move || {
+        let mut db = db_.get_connection();
+
+        Ok::<_, diesel::result::Error>((
+            Customer::find_for_user(&mut db, user_id)?,
+            User::read(&mut db, user_id)?,
+        ))
+    })
+    .await;
+
+    let user = match existing {
+        Ok(Ok((Some(customer), _))) => return Ok(customer.customer_id),
+        Ok(Ok((None, user))) => user,
+        _ => {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the customer.",
+            ))
+        }
+    };
+
+    let customer_id = config
+        .stripe
+        .create_customer(user.email.as_deref(), user_id)
+        .await
+        .map_err(|_| error_response(StatusCode::BAD_GATEWAY, "Could not create the customer."))?;
+
+    match web::block(move || Customer::create(&mut db.get_connection(), user_id, &customer_id))
+        .await
+    {
+        Ok(Ok(customer)) => Ok(customer.customer_id),
+        _ => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not record the customer.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../portal endpoint
+///
+/// requires auth
+///
+/// returns the url of a billing portal session, where the user manages their subscription
+#[post("/portal")]
+async fn portal(
+    db: Data<Database>,
+    config: Option<Data<PaymentsConfig>>,
+    auth: Auth,
+    Json(item): Json<PortalInput>,
+) -> Result<HttpResponse> {
+    let config = self::config(config);
+
+    let result =
+        web::block(move || Customer::find_for_user(&mut db.get_connection(), auth.user_id)).await?;
+
+    let customer = match result {
+        Ok(Some(customer)) => customer,
+        Ok(None) => {
+            return Ok(error_response(
+                StatusCode::NOT_FOUND,
+                "No billing account yet.",
+            ))
+        }
+        Err(_) => {
+            return Ok(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the customer.",
+            ))
+        }
+    };
+
+    match config
+        .stripe
+        .create_portal_session(&customer.customer_id, &item.return_url)
+        .await
+    {
+        Ok(url) => Ok(HttpResponse::Ok().json(json!({ "url": url }))),
+        Err(_) => Ok(error_response(
+            StatusCode::BAD_GATEWAY,
+            "Could not open the billing portal.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../webhook endpoint, called by Stripe
+///
+/// verifies the `Stripe-Signature` header, then records the event, see [`payments::handle_event`]
+#[post("/webhook")]
+async fn webhook(
+    db: Data<Database>,
+    config: Option<Data<PaymentsConfig>>,
+    req: HttpRequest,
+    body: Bytes,
+) -> Result<HttpResponse> {
+    let config = self::config(config);
+
+    let signature = req
+        .headers()
+        .get("Stripe-Signature")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if stripe::verify_signature(
+        &body,
+        signature,
+        &config.webhook_secret,
+        config.webhook_tolerance,
+    )
+    .is_err()
+    {
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "Invalid signature.",
+        ));
+    }
+
+    let event = match serde_json::from_slice::<Event>(&body) {
+        Ok(event) => event,
+        Err(_) => return Ok(error_response(StatusCode::BAD_REQUEST, "Invalid event.")),
+    };
+
+    let result =
+        web::block(move || payments::handle_event(&mut db.get_connection(), &event, &config))
+            .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().json(json!({ "received": true }))),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not handle the event.",
+        )),
+    }
+}
+
+/// returns the endpoints for the payments service
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope
+        .service(status)
+        .service(checkout)
+        .service(portal)
+        .service(webhook)
+}
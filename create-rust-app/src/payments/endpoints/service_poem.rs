@@ -0,0 +1,240 @@
+use poem::{
+    get, handler,
+    http::{HeaderMap, StatusCode},
+    post,
+    web::{Data, Json},
+    Error, IntoResponse, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::{Auth, User};
+use crate::payments::{self, stripe, Customer, Event, PaymentsConfig};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../checkout endpoint
+pub struct CheckoutInput {
+    /// one of the [`PaymentsConfig::prices`], the first one if `None`
+    price_id: Option<String>,
+    /// where Stripe sends the user back to, ex: the billing page
+    return_url: String,
+}
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../portal endpoint
+pub struct PortalInput {
+    return_url: String,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+fn config(config: Option<Data<&PaymentsConfig>>) -> PaymentsConfig {
+    config
+        .map(|config| config.0.clone())
+        .unwrap_or_else(PaymentsConfig::from_env)
+}
+
+#[handler]
+/// handler for GET requests at the .../status endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the user's [`BillingStatus`](`crate::payments::BillingStatus`)
+/// | 500 | Json payload : {"message": "Could not fetch the subscription."}
+async fn status(
+    db: Data<&Database>,
+    config: Option<Data<&PaymentsConfig>>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    payments::status(
+        &mut db.get_connection(),
+        auth.user_id,
+        &self::config(config),
+    )
+    .map(Json)
+    .map_err(|_| {
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the subscription.",
+        )
+    })
+}
+
+#[handler]
+/// handler for POST requests at the .../checkout endpoint
+///
+/// requires auth
+///
+/// creates the user's Stripe customer if they don't have one yet
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"url": "..."}, the Checkout session to send the user to
+/// | 400 | Json payload : {"message": "Unknown price."}
+/// | 500 | Json payload : {"message": "Payments are not configured."}
+/// | 500 | Json payload : {"message": "Could not fetch the customer."}
+/// | 500 | Json payload : {"message": "Could not record the customer."}
+/// | 502 | Json payload : {"message": "Could not create the customer."}
+/// | 502 | Json payload : {"message": "Could not start the checkout."}
+async fn checkout(
+    db: Data<&Database>,
+    config: Option<Data<&PaymentsConfig>>,
+    auth: Auth,
+    Json(item): Json<CheckoutInput>,
+) -> Result<impl IntoResponse> {
+    let config = self::config(config);
+    if !config.stripe.is_configured() {
+        return Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Payments are not configured.",
+        ));
+    }
+
+    let price_id = config
+        .checkout_price(item.price_id.as_deref())
+        .ok_or_else(|| error_response(StatusCode::BAD_REQUEST, "Unknown price."))?;
+
+    let customer_id = customer_id(db.0, &config, auth.user_id).await?;
+
+    config
+        .stripe
+        .create_checkout_session(&customer_id, price_id, &item.return_url, &item.return_url)
+        .await
+        .map(|url| Json(json!({ "url": url })))
+        .map_err(|_| error_response(StatusCode::BAD_GATEWAY, "Could not start the checkout."))
+}
+
+/// the id of [`user_id`](`i32`)'s Stripe customer, created if they don't have one yet
+async fn customer_id(db: &Database, config: &PaymentsConfig, user_id: i32) -> Result<String> {
+    let fetch_error = || {
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the customer.",
+        )
+    };
+
+    if let Some(customer) =
+        Customer::find_for_user(&mut db.get_connection(), user_id).map_err(|_| fetch_error())?
+    {
+        return Ok(customer.customer_id);
+    }
+    let user = User::read(&mut db.get_connection(), user_id).map_err(|_| fetch_error())?;
+
+    let customer_id = config
+        .stripe
+        .create_customer(user.email.as_deref(), user_id)
+        .await
+        .map_err(|_| error_response(StatusCode::BAD_GATEWAY, "Could not create the customer."))?;
+
+    Customer::create(&mut db.get_connection(), user_id, &customer_id)
+        .map(|customer| customer.customer_id)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not record the customer.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../portal endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"url": "..."}, the billing portal session to send the user to
+/// | 404 | Json payload : {"message": "No billing account yet."}
+/// | 500 | Json payload : {"message": "Could not fetch the customer."}
+/// | 502 | Json payload : {"message": "Could not open the billing portal."}
+async fn portal(
+    db: Data<&Database>,
+    config: Option<Data<&PaymentsConfig>>,
+    auth: Auth,
+    Json(item): Json<PortalInput>,
+) -> Result<impl IntoResponse> {
+    let config = self::config(config);
+
+    let customer = Customer::find_for_user(&mut db.get_connection(), auth.user_id)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the customer.",
+            )
+        })?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "No billing account yet."))?;
+
+    config
+        .stripe
+        .create_portal_session(&customer.customer_id, &item.return_url)
+        .await
+        .map(|url| Json(json!({ "url": url })))
+        .map_err(|_| {
+            error_response(
+                StatusCode::BAD_GATEWAY,
+                "Could not open the billing portal.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../webhook endpoint, called by Stripe
+///
+/// verifies the `Stripe-Signature` header, then records the event, see [`payments::handle_event`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"received": true}
+/// | 400 | Json payload : {"message": "Invalid signature."}
+/// | 400 | Json payload : {"message": "Invalid event."}
+/// | 500 | Json payload : {"message": "Could not handle the event."}
+async fn webhook(
+    db: Data<&Database>,
+    config: Option<Data<&PaymentsConfig>>,
+    headers: &HeaderMap,
+    body: Vec<u8>,
+) -> Result<impl IntoResponse> {
+    let config = self::config(config);
+
+    let signature = headers
+        .get("Stripe-Signature")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    stripe::verify_signature(
+        &body,
+        signature,
+        &config.webhook_secret,
+        config.webhook_tolerance,
+    )
+    .map_err(|_| error_response(StatusCode::BAD_REQUEST, "Invalid signature."))?;
+
+    let event = serde_json::from_slice::<Event>(&body)
+        .map_err(|_| error_response(StatusCode::BAD_REQUEST, "Invalid event."))?;
+
+    payments::handle_event(&mut db.get_connection(), &event, &config)
+        .map(|_| Json(json!({ "received": true })))
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not handle the event.",
+            )
+        })
+}
+
+/// returns the endpoints for the payments service
+pub fn api() -> Route {
+    Route::new()
+        .at("/status", get(status))
+        .at("/checkout", post(checkout))
+        .at("/portal", post(portal))
+        .at("/webhook", post(webhook))
+}
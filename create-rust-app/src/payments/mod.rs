@@ -0,0 +1,482 @@
+//! Stripe subscriptions: each user gets a Stripe customer the first time they check out, and
+//! their [`Subscription`]s are kept in sync by Stripe's webhooks.
+//!
+//! The endpoints send the user to Stripe Checkout (to subscribe to one of the
+//! [`PaymentsConfig::prices`]) or to the billing portal (to manage their subscription), and
+//! receive the webhooks, whose signatures are verified with the endpoint's secret:
+//!
+//! ```text
+//! STRIPE_SECRET_KEY=sk_test_...
+//! STRIPE_WEBHOOK_SECRET=whsec_...
+//! STRIPE_PRICE_IDS=price_...,price_...
+//! ```
+//!
+//! What a subscription unlocks is up to the app, see [`Entitlements`]:
+//!
+//! ```rust,ignore
+//! use create_rust_app::payments::Entitlements;
+//!
+//! #[get("/reports")]
+//! async fn reports(db: Data<Database>, auth: Auth) -> HttpResponse {
+//!     match web::block(move || auth.has_active_subscription(&mut db.get_connection())).await {
+//!         Ok(Ok(true)) => HttpResponse::Ok().json(...),
+//!         _ => HttpResponse::PaymentRequired().finish(),
+//!     }
+//! }
+//! ```
+//!
+//! To react to other events (ex: `invoice.payment_failed`), add a [`PaymentEventHandler`] to the
+//! [`PaymentsConfig`] registered in the app's data.
+use crate::auth::{Auth, User};
+use crate::diesel::*;
+use crate::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+pub mod stripe;
+pub use stripe::{Event, Stripe};
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// the statuses of subscriptions that grant access, see
+/// <https://stripe.com/docs/billing/subscriptions/overview#subscription-statuses>
+pub const ACTIVE_STATUSES: &[&str] = &["active", "trialing"];
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=stripe_customers, primary_key(user_id))]
+/// the Stripe customer of a user
+pub struct Customer {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    /// ex: `cus_...`
+    pub customer_id: String,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=stripe_subscriptions)]
+/// a user's Stripe subscription, as of the last webhook received about it
+pub struct Subscription {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub user_id: ID,
+    /// ex: `sub_...`
+    pub subscription_id: String,
+    /// the price of the subscription's first item
+    pub price_id: Option<String>,
+    /// ex: `active`, `past_due` or `canceled`
+    pub status: String,
+    pub current_period_end: Option<Utc>,
+    pub cancel_at_period_end: bool,
+
+    pub created_at: Utc,
+    pub updated_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=stripe_subscriptions)]
+pub struct SubscriptionChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub subscription_id: String,
+    pub price_id: Option<String>,
+    pub status: String,
+    pub current_period_end: Option<Utc>,
+    pub cancel_at_period_end: bool,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// whether a user is subscribed, and to what
+pub struct BillingStatus {
+    pub active: bool,
+    /// the active subscription, or else the latest one
+    pub subscription: Option<Subscription>,
+    /// the prices the user can subscribe to
+    pub prices: Vec<String>,
+}
+
+/// reacts to the webhook events of your choice, ex: emails the user when a payment fails
+///
+/// handlers run after the plugin's own handling, in the same transaction: returning an error
+/// fails the webhook, and Stripe sends it again later
+pub trait PaymentEventHandler: Send + Sync {
+    fn handle(&self, db: &mut Connection, event: &Event) -> QueryResult<()>;
+}
+
+#[derive(Clone)]
+/// how the endpoints talk to Stripe
+///
+/// register it in the app's data, one read from the environment is used otherwise
+pub struct PaymentsConfig {
+    pub stripe: Stripe,
+    /// the signing secret of the webhook endpoint (`STRIPE_WEBHOOK_SECRET`)
+    pub webhook_secret: String,
+    /// the prices users can subscribe to, the first one being the default (`STRIPE_PRICE_IDS`,
+    /// comma separated)
+    pub prices: Vec<String>,
+    /// how old a webhook's signature can be, in seconds (default: 300)
+    pub webhook_tolerance: i64,
+    pub handlers: Vec<Arc<dyn PaymentEventHandler>>,
+}
+
+impl Default for PaymentsConfig {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+impl std::fmt::Debug for PaymentsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PaymentsConfig")
+            .field("configured", &self.stripe.is_configured())
+            .field("prices", &self.prices)
+            .field("webhook_tolerance", &self.webhook_tolerance)
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
+
+impl PaymentsConfig {
+    pub fn new(stripe: Stripe, webhook_secret: impl Into<String>) -> Self {
+        Self {
+            stripe,
+            webhook_secret: webhook_secret.into(),
+            prices: vec![],
+            webhook_tolerance: 300,
+            handlers: vec![],
+        }
+    }
+
+    /// reads `STRIPE_SECRET_KEY`, `STRIPE_WEBHOOK_SECRET` and `STRIPE_PRICE_IDS`
+    pub fn from_env() -> Self {
+        let env = |name| std::env::var(name).unwrap_or_default();
+
+        let mut config = Self::new(
+            Stripe::new(env("STRIPE_SECRET_KEY")),
+            env("STRIPE_WEBHOOK_SECRET"),
+        );
+        config.prices = env("STRIPE_PRICE_IDS")
+            .split(',')
+            .map(str::trim)
+            .filter(|price| !price.is_empty())
+            .map(String::from)
+            .collect();
+
+        config
+    }
+
+    pub fn price(mut self, price_id: impl Into<String>) -> Self {
+        self.prices.push(price_id.into());
+        self
+    }
+
+    pub fn webhook_tolerance(mut self, seconds: i64) -> Self {
+        self.webhook_tolerance = seconds;
+        self
+    }
+
+    pub fn handler(mut self, handler: impl PaymentEventHandler + 'static) -> Self {
+        self.handlers.push(Arc::new(handler));
+        self
+    }
+
+    /// the `requested` price if users can subscribe to it, or the default one
+    pub fn checkout_price(&self, requested: Option<&str>) -> Option<&str> {
+        match requested {
+            Some(requested) => self
+                .prices
+                .iter()
+                .find(|price| *price == requested)
+                .map(String::as_str),
+            None => self.prices.first().map(String::as_str),
+        }
+    }
+}
+
+impl Customer {
+    /// the customer of [`item_user_id`](`ID`), if they ever checked out
+    pub fn find_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Option<Self>> {
+        use schema::stripe_customers::dsl::*;
+
+        stripe_customers
+            .filter(user_id.eq(item_user_id))
+            .first::<Customer>(db)
+            .optional()
+    }
+
+    /// the customer whose Stripe id is [`item_customer_id`]
+    pub fn find_by_customer_id(
+        db: &mut Connection,
+        item_customer_id: &str,
+    ) -> QueryResult<Option<Self>> {
+        use schema::stripe_customers::dsl::*;
+
+        stripe_customers
+            .filter(customer_id.eq(item_customer_id))
+            .first::<Customer>(db)
+            .optional()
+    }
+
+    /// records that [`item_customer_id`] is the customer of [`item_user_id`](`ID`), returns the
+    /// existing customer instead if another request recorded one first
+    pub fn create(
+        db: &mut Connection,
+        item_user_id: ID,
+        item_customer_id: &str,
+    ) -> QueryResult<Self> {
+        use schema::stripe_customers::dsl::*;
+
+        insert_into(stripe_customers)
+            .values((user_id.eq(item_user_id), customer_id.eq(item_customer_id)))
+            .on_conflict(user_id)
+            .do_nothing()
+            .execute(db)?;
+
+        stripe_customers
+            .filter(user_id.eq(item_user_id))
+            .first::<Customer>(db)
+    }
+
+    /// Delete the customer of [`item_user_id`](`ID`)
+    pub fn delete(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::stripe_customers::dsl::*;
+
+        diesel::delete(stripe_customers.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
+
+impl Subscription {
+    /// Create or update the entry of [`item`](`SubscriptionChangeset`)'s subscription in [`db`](`Connection`)'s `stripe_subscriptions` table
+    pub fn upsert(db: &mut Connection, item: &SubscriptionChangeset) -> QueryResult<Self> {
+        use schema::stripe_subscriptions::dsl::*;
+
+        insert_into(stripe_subscriptions)
+            .values(item)
+            .on_conflict(subscription_id)
+            .do_update()
+            .set((item, updated_at.eq(now())))
+            .get_result::<Subscription>(db)
+    }
+
+    /// the subscriptions of [`item_user_id`](`ID`), most recent first
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use schema::stripe_subscriptions::dsl::*;
+
+        stripe_subscriptions
+            .filter(user_id.eq(item_user_id))
+            .order(created_at.desc())
+            .load::<Subscription>(db)
+    }
+
+    /// the most recent of [`item_user_id`](`ID`)'s subscriptions that grant access
+    pub fn find_active_for_user(
+        db: &mut Connection,
+        item_user_id: ID,
+    ) -> QueryResult<Option<Self>> {
+        use schema::stripe_subscriptions::dsl::*;
+
+        stripe_subscriptions
+            .filter(user_id.eq(item_user_id))
+            .filter(status.eq_any(ACTIVE_STATUSES.to_vec()))
+            .order(created_at.desc())
+            .first::<Subscription>(db)
+            .optional()
+    }
+
+    /// Delete the subscriptions of [`item_user_id`](`ID`)
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::stripe_subscriptions::dsl::*;
+
+        diesel::delete(stripe_subscriptions.filter(user_id.eq(item_user_id))).execute(db)
+    }
+
+    pub fn is_active(&self) -> bool {
+        ACTIVE_STATUSES.contains(&self.status.as_str())
+    }
+}
+
+/// what a user's subscription entitles them to, implemented for [`User`] and [`Auth`]
+pub trait Entitlements {
+    fn subscriber_id(&self) -> ID;
+
+    /// the subscription that grants the user access, if any
+    fn active_subscription(&self, db: &mut Connection) -> QueryResult<Option<Subscription>> {
+        Subscription::find_active_for_user(db, self.subscriber_id())
+    }
+
+    fn has_active_subscription(&self, db: &mut Connection) -> QueryResult<bool> {
+        Ok(self.active_subscription(db)?.is_some())
+    }
+
+    /// whether the user's active subscription is to [`price_id`]
+    fn is_subscribed_to(&self, db: &mut Connection, price_id: &str) -> QueryResult<bool> {
+        Ok(self.active_subscription(db)?.map_or(false, |subscription| {
+            subscription.price_id.as_deref() == Some(price_id)
+        }))
+    }
+}
+
+impl Entitlements for User {
+    fn subscriber_id(&self) -> ID {
+        self.id
+    }
+}
+
+impl Entitlements for Auth {
+    fn subscriber_id(&self) -> ID {
+        self.user_id
+    }
+}
+
+/// whether [`user_id`](`ID`) is subscribed, and the prices of the [`config`](`PaymentsConfig`)
+pub fn status(
+    db: &mut Connection,
+    user_id: ID,
+    config: &PaymentsConfig,
+) -> QueryResult<BillingStatus> {
+    let subscription = match Subscription::find_active_for_user(db, user_id)? {
+        Some(subscription) => Some(subscription),
+        None => Subscription::fetch_all_for_user(db, user_id)?
+            .into_iter()
+            .next(),
+    };
+
+    Ok(BillingStatus {
+        active: subscription.as_ref().map_or(false, Subscription::is_active),
+        subscription,
+        prices: config.prices.clone(),
+    })
+}
+
+/// records the changes of the `customer.subscription.*` events, then runs the
+/// [`PaymentEventHandler`]s of the [`config`](`PaymentsConfig`)
+pub fn handle_event(
+    db: &mut Connection,
+    event: &Event,
+    config: &PaymentsConfig,
+) -> QueryResult<()> {
+    diesel::connection::Connection::transaction(db, |db| {
+        match event.kind.as_str() {
+            "customer.subscription.created"
+            | "customer.subscription.updated"
+            | "customer.subscription.deleted"
+            | "customer.subscription.paused"
+            | "customer.subscription.resumed" => sync_subscription(db, &event.data.object)?,
+            _ => {}
+        }
+
+        for handler in &config.handlers {
+            handler.handle(db, event)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// records the state of a Stripe subscription object, unless its customer isn't one of the app's
+fn sync_subscription(db: &mut Connection, object: &serde_json::Value) -> QueryResult<()> {
+    let customer_id = object["customer"]
+        .as_str()
+        .or_else(|| object["customer"]["id"].as_str());
+    let customer = match customer_id {
+        Some(customer_id) => Customer::find_by_customer_id(db, customer_id)?,
+        None => None,
+    };
+    let (customer, subscription_id) = match (customer, object["id"].as_str()) {
+        (Some(customer), Some(subscription_id)) => (customer, subscription_id),
+        _ => return Ok(()),
+    };
+
+    let item = &object["items"]["data"][0];
+    // moved to the subscription's items in recent versions of the API
+    let current_period_end = object["current_period_end"]
+        .as_i64()
+        .or_else(|| item["current_period_end"].as_i64());
+
+    Subscription::upsert(
+        db,
+        &SubscriptionChangeset {
+            user_id: customer.user_id,
+            subscription_id: subscription_id.to_string(),
+            price_id: item["price"]["id"].as_str().map(String::from),
+            status: object["status"]
+                .as_str()
+                .unwrap_or("incomplete")
+                .to_string(),
+            current_period_end: current_period_end.and_then(timestamp),
+            cancel_at_period_end: object["cancel_at_period_end"].as_bool().unwrap_or(false),
+        },
+    )?;
+
+    Ok(())
+}
+
+/// includes the user's customer and subscriptions in account exports, and deletes them with the
+/// account (the subscriptions themselves are left to be canceled in Stripe)
+pub(crate) struct PaymentsAccountHook;
+
+impl crate::auth::account::AccountHook for PaymentsAccountHook {
+    fn name(&self) -> &'static str {
+        "payments"
+    }
+
+    fn export(&self, db: &mut Connection, user_id: ID) -> QueryResult<serde_json::Value> {
+        Ok(serde_json::json!({
+            "customer": Customer::find_for_user(db, user_id)?,
+            "subscriptions": Subscription::fetch_all_for_user(db, user_id)?,
+        }))
+    }
+
+    fn delete(&self, db: &mut Connection, user_id: ID) -> QueryResult<()> {
+        Subscription::delete_all_for_user(db, user_id)?;
+        Customer::delete(db, user_id)?;
+
+        Ok(())
+    }
+}
+
+/// a unix timestamp, in the timestamp type used by the enabled database
+fn timestamp(seconds: i64) -> Option<Utc> {
+    use chrono::TimeZone;
+
+    let timestamp = chrono::Utc.timestamp_opt(seconds, 0).single()?;
+
+    #[cfg(not(feature = "database_sqlite"))]
+    return Some(timestamp);
+
+    #[cfg(feature = "database_sqlite")]
+    return Some(timestamp.naive_utc());
+}
+
+/// the current time, in the timestamp type used by the enabled database
+fn now() -> Utc {
+    #[cfg(not(feature = "database_sqlite"))]
+    return chrono::Utc::now();
+
+    #[cfg(feature = "database_sqlite")]
+    return chrono::Utc::now().naive_utc();
+}
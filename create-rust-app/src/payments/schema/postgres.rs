@@ -0,0 +1,23 @@
+table! {
+  stripe_customers (user_id) {
+      user_id -> Int4,
+      customer_id -> Text,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  stripe_subscriptions (id) {
+      id -> Int4,
+      user_id -> Int4,
+      subscription_id -> Text,
+      price_id -> Nullable<Text>,
+      status -> Text,
+      current_period_end -> Nullable<Timestamptz>,
+      cancel_at_period_end -> Bool,
+      created_at -> Timestamptz,
+      updated_at -> Timestamptz,
+  }
+}
+
+allow_tables_to_appear_in_same_query!(stripe_customers, stripe_subscriptions,);
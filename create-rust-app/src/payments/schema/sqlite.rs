@@ -0,0 +1,23 @@
+table! {
+  stripe_customers (user_id) {
+      user_id -> Integer,
+      customer_id -> Text,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  stripe_subscriptions (id) {
+      id -> Integer,
+      user_id -> Integer,
+      subscription_id -> Text,
+      price_id -> Nullable<Text>,
+      status -> Text,
+      current_period_end -> Nullable<Timestamp>,
+      cancel_at_period_end -> Bool,
+      created_at -> Timestamp,
+      updated_at -> Timestamp,
+  }
+}
+
+allow_tables_to_appear_in_same_query!(stripe_customers, stripe_subscriptions,);
@@ -0,0 +1,175 @@
+//! A minimal client for the parts of Stripe's API the payments plugin uses, and the verification
+//! of its webhooks' signatures.
+use crate::inbound_webhooks::{self, Provider};
+use serde::Deserialize;
+use serde_json::Value;
+
+const STRIPE_API_URL: &str = "https://api.stripe.com/v1";
+
+#[derive(Debug, Clone)]
+/// calls Stripe's API with the account's secret key
+pub struct Stripe {
+    secret_key: String,
+    api_url: String,
+    client: reqwest::Client,
+}
+
+impl Stripe {
+    pub fn new(secret_key: impl Into<String>) -> Self {
+        Self {
+            secret_key: secret_key.into(),
+            api_url: STRIPE_API_URL.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// ex: for a mock of Stripe's API
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into();
+        self
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.secret_key.is_empty()
+    }
+
+    /// creates a customer for [`user_id`](`i32`), returns its id
+    pub async fn create_customer(
+        &self,
+        email: Option<&str>,
+        user_id: i32,
+    ) -> Result<String, String> {
+        let mut params = vec![("metadata[user_id]", user_id.to_string())];
+        if let Some(email) = email {
+            params.push(("email", email.to_string()));
+        }
+
+        let customer = self.post("/customers", &params).await?;
+
+        id(&customer)
+    }
+
+    /// creates a Checkout session subscribing `customer_id` to `price_id`, returns the url to send
+    /// the user to
+    pub async fn create_checkout_session(
+        &self,
+        customer_id: &str,
+        price_id: &str,
+        success_url: &str,
+        cancel_url: &str,
+    ) -> Result<String, String> {
+        let session = self
+            .post(
+                "/checkout/sessions",
+                &[
+                    ("mode", "subscription".to_string()),
+                    ("customer", customer_id.to_string()),
+                    ("line_items[0][price]", price_id.to_string()),
+                    ("line_items[0][quantity]", "1".to_string()),
+                    ("success_url", success_url.to_string()),
+                    ("cancel_url", cancel_url.to_string()),
+                ],
+            )
+            .await?;
+
+        url(&session)
+    }
+
+    /// creates a billing portal session for `customer_id`, where they manage their subscription
+    /// and payment methods, returns the url to send the user to
+    pub async fn create_portal_session(
+        &self,
+        customer_id: &str,
+        return_url: &str,
+    ) -> Result<String, String> {
+        let session = self
+            .post(
+                "/billing_portal/sessions",
+                &[
+                    ("customer", customer_id.to_string()),
+                    ("return_url", return_url.to_string()),
+                ],
+            )
+            .await?;
+
+        url(&session)
+    }
+
+    async fn post(&self, path: &str, params: &[(&str, String)]) -> Result<Value, String> {
+        let response = self
+            .client
+            .post(format!("{}{path}", self.api_url))
+            .bearer_auth(&self.secret_key)
+            .form(params)
+            .send()
+            .await
+            .map_err(|err| format!("Could not reach Stripe: {err}"))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|err| format!("Invalid response from Stripe: {err}"))?;
+        let body = serde_json::from_str::<Value>(&body)
+            .map_err(|err| format!("Invalid response from Stripe: {err}"))?;
+
+        if !status.is_success() {
+            return Err(format!(
+                "Stripe responded with {status}: {}",
+                body["error"]["message"].as_str().unwrap_or("unknown error")
+            ));
+        }
+
+        Ok(body)
+    }
+}
+
+fn id(object: &Value) -> Result<String, String> {
+    object["id"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| "Stripe's response has no id".to_string())
+}
+
+fn url(session: &Value) -> Result<String, String> {
+    session["url"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| "Stripe's response has no url".to_string())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// an event sent to the webhook endpoint, see <https://stripe.com/docs/api/events/object>
+pub struct Event {
+    pub id: String,
+    /// ex: `customer.subscription.updated`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// unix timestamp
+    pub created: i64,
+    pub data: EventData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventData {
+    /// the object the event is about, ex: a subscription
+    pub object: Value,
+}
+
+/// checks the `Stripe-Signature` header of a webhook request: one of its `v1` signatures must be
+/// the HMAC-SHA256 of `{timestamp}.{payload}` with the endpoint's `secret`, and its timestamp
+/// at most `tolerance` seconds away (so captured requests can't be replayed later)
+pub fn verify_signature(
+    payload: &[u8],
+    header: &str,
+    secret: &str,
+    tolerance: i64,
+) -> Result<(), String> {
+    // anyone could sign with an empty secret
+    if secret.is_empty() {
+        return Err("No webhook secret configured".to_string());
+    }
+
+    let header = |name: &str| (name == "Stripe-Signature").then(|| header.to_string());
+    <inbound_webhooks::Stripe as Provider>::verify(&header, payload, secret, tolerance)
+}
@@ -0,0 +1,36 @@
+use actix_http::header::AUTHORIZATION;
+use actix_http::StatusCode;
+use actix_web::web::Data;
+use actix_web::{get, web, HttpRequest, HttpResponse, Result};
+use serde_json::json;
+
+use crate::prometheus::{is_authorized, render};
+use crate::Database;
+
+/// handler for GET requests at the .../metrics endpoint
+///
+/// requires `Authorization: Bearer <METRICS_TOKEN>` when `METRICS_TOKEN` is set
+///
+/// returns every metric, in Prometheus' text format
+#[get("")]
+async fn metrics(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse> {
+    let authorization = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !is_authorized(authorization) {
+        return Ok(HttpResponse::build(StatusCode::UNAUTHORIZED)
+            .body(json!({ "message": "Unauthorized." }).to_string()));
+    }
+
+    let body = web::block(move || render(&db)).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(body))
+}
+
+/// returns the endpoint Prometheus scrapes
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(metrics)
+}
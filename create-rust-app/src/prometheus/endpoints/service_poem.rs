@@ -0,0 +1,41 @@
+use poem::{
+    get, handler,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    web::Data,
+    Error, IntoResponse, Response, Result, Route,
+};
+use serde_json::json;
+
+use crate::prometheus::{is_authorized, render};
+use crate::Database;
+
+#[handler]
+/// handler for GET requests at the .../metrics endpoint
+///
+/// requires `Authorization: Bearer <METRICS_TOKEN>` when `METRICS_TOKEN` is set
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | every metric, in Prometheus' text format
+/// | 401 | Json payload : {"message": "Unauthorized."}
+async fn metrics(db: Data<&Database>, headers: &HeaderMap) -> Result<impl IntoResponse> {
+    let authorization = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !is_authorized(authorization) {
+        return Err(Error::from_string(
+            json!({ "message": "Unauthorized." }).to_string(),
+            StatusCode::UNAUTHORIZED,
+        ));
+    }
+
+    Ok(Response::builder()
+        .content_type("text/plain; version=0.0.4")
+        .body(render(&db)))
+}
+
+/// returns the endpoint Prometheus scrapes
+pub fn api() -> Route {
+    Route::new().at("/", get(metrics))
+}
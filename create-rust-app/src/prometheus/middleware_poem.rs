@@ -0,0 +1,60 @@
+use poem::{async_trait, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use std::time::Instant;
+
+/// records the duration of the requests it handles, see [`prometheus`](`super`)
+///
+/// ```rust,ignore
+/// let app = app.with(create_rust_app::prometheus::HttpMetrics);
+/// ```
+pub struct HttpMetrics;
+
+impl<E: Endpoint> Middleware<E> for HttpMetrics {
+    type Output = HttpMetricsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        HttpMetricsEndpoint { ep }
+    }
+}
+
+pub struct HttpMetricsEndpoint<E> {
+    ep: E,
+}
+
+#[async_trait]
+impl<E: Endpoint> Endpoint for HttpMetricsEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let method = req.method().to_string();
+        let route = route_of(req.uri().path());
+        let started = Instant::now();
+
+        let result = self.ep.call(req).await.map(IntoResponse::into_response);
+        let status = match &result {
+            Ok(response) => response.status().as_u16(),
+            Err(err) => err.status().as_u16(),
+        };
+        super::record_request(&method, &route, status, started.elapsed());
+
+        result
+    }
+}
+
+/// `path` with its numeric and uuid segments replaced with `{id}`, so requests to the same
+/// resource share a route
+fn route_of(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            let is_number = !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit());
+            let is_uuid =
+                segment.len() == 36 && segment.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+
+            if is_number || is_uuid {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
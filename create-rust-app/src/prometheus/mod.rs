@@ -0,0 +1,296 @@
+//! Prometheus metrics (`plugin_prometheus` feature), exposed in the
+//! [text format](https://prometheus.io/docs/instrumenting/exposition_formats/) at `/api/metrics`.
+//!
+//! ```rust,ignore
+//! // actix-web
+//! let app = App::new()
+//!     .wrap(create_rust_app::prometheus::HttpMetrics)
+//!     .service(create_rust_app::prometheus::endpoints(web::scope("/metrics")));
+//! // poem
+//! let app = app
+//!     .nest("/metrics", create_rust_app::prometheus::api())
+//!     .with(create_rust_app::prometheus::HttpMetrics);
+//! ```
+//!
+//! | metric | type | labels |
+//! |:-------|------|--------|
+//! | `http_request_duration_seconds` | histogram | `method`, `route`, `status` |
+//! | `db_pool_connections`, `db_pool_idle_connections`, `db_pool_max_connections` | gauge | |
+//! | `job_queue_depth` | gauge | `queue` (`mail`, `webhooks`, `tasks`), `status` |
+//! | `mailer_emails_total` | counter | `outcome` (`sent`, `failed`, `queued`) |
+//!
+//! The [`HttpMetrics`] middleware records the requests: with actix-web, their `route` is the
+//! pattern they matched (ex: `/api/todos/{id}`), `unmatched` for the others; poem's middleware
+//! only sees the path, so numbers and uuids in it are replaced with `{id}`. The queues are only
+//! there when their plugin is (`plugin_mail_queue`, `plugin_webhooks`, `plugin_tasks`), and are
+//! counted when Prometheus scrapes the endpoint.
+//!
+//! Anyone can scrape the endpoint unless `METRICS_TOKEN` is set, in which case it requires an
+//! `Authorization: Bearer <METRICS_TOKEN>` header.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::{Connection, Database};
+
+mod endpoints;
+pub use endpoints::*;
+#[cfg(feature = "backend_actix-web")]
+mod middleware_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use middleware_actixweb::HttpMetrics;
+#[cfg(feature = "backend_poem")]
+mod middleware_poem;
+#[cfg(feature = "backend_poem")]
+pub use middleware_poem::HttpMetrics;
+
+/// the upper bounds of the request duration histogram's buckets, in seconds
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// how many observations fell in each bucket (not cumulative, unlike the rendered ones)
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+lazy_static::lazy_static! {
+    /// keyed by method, route and status
+    static ref REQUESTS: Mutex<BTreeMap<(String, String, u16), Histogram>> = Mutex::new(BTreeMap::new());
+    /// keyed by outcome
+    static ref EMAILS: Mutex<BTreeMap<&'static str, u64>> = Mutex::new(BTreeMap::new());
+}
+
+/// records a request to `route` answered with `status` after `duration`
+pub fn record_request(method: &str, route: &str, status: u16, duration: Duration) {
+    let seconds = duration.as_secs_f64();
+    let mut requests = REQUESTS.lock().unwrap();
+    let histogram = requests
+        .entry((method.to_string(), route.to_string(), status))
+        .or_default();
+
+    if let Some(bucket) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+        histogram.buckets[bucket] += 1;
+    }
+    histogram.sum += seconds;
+    histogram.count += 1;
+}
+
+/// counts an email the [`Mailer`](`crate::Mailer`) `sent`, `failed` to send or `queued`
+pub(crate) fn record_email(outcome: &'static str) {
+    *EMAILS.lock().unwrap().entry(outcome).or_insert(0) += 1;
+}
+
+/// whether the request may scrape the metrics, given its `Authorization` header
+fn is_authorized(authorization: Option<&str>) -> bool {
+    match std::env::var("METRICS_TOKEN") {
+        Ok(token) if !token.is_empty() => {
+            authorization.and_then(|value| value.strip_prefix("Bearer ")) == Some(token.as_str())
+        }
+        _ => true,
+    }
+}
+
+/// escapes a label value, see the text format's spec
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// writes the `# HELP` and `# TYPE` lines of a metric
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn render_requests(out: &mut String) {
+    let requests = REQUESTS.lock().unwrap().clone();
+
+    header(
+        out,
+        "http_request_duration_seconds",
+        "histogram",
+        "How long the HTTP requests took to answer.",
+    );
+    for ((method, route, status), histogram) in requests {
+        let labels = format!(
+            r#"method="{}",route="{}",status="{status}""#,
+            escape(&method),
+            escape(&route)
+        );
+
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(histogram.buckets) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                r#"http_request_duration_seconds_bucket{{{labels},le="{bound}"}} {cumulative}"#
+            );
+        }
+        let _ = writeln!(
+            out,
+            r#"http_request_duration_seconds_bucket{{{labels},le="+Inf"}} {}"#,
+            histogram.count
+        );
+        let _ = writeln!(
+            out,
+            "http_request_duration_seconds_sum{{{labels}}} {}",
+            histogram.sum
+        );
+        let _ = writeln!(
+            out,
+            "http_request_duration_seconds_count{{{labels}}} {}",
+            histogram.count
+        );
+    }
+}
+
+fn render_pool(out: &mut String, db: &Database) {
+    let state = db.pool.state();
+
+    header(
+        out,
+        "db_pool_connections",
+        "gauge",
+        "The connections the database pool holds.",
+    );
+    let _ = writeln!(out, "db_pool_connections {}", state.connections);
+    header(
+        out,
+        "db_pool_idle_connections",
+        "gauge",
+        "The database pool's connections which aren't in use.",
+    );
+    let _ = writeln!(out, "db_pool_idle_connections {}", state.idle_connections);
+    header(
+        out,
+        "db_pool_max_connections",
+        "gauge",
+        "The maximum number of connections of the database pool.",
+    );
+    let _ = writeln!(out, "db_pool_max_connections {}", db.pool.max_size());
+}
+
+/// the number of jobs in each queue, by status, as `(queue, status, count)`
+#[cfg_attr(
+    not(any(
+        feature = "plugin_mail_queue",
+        feature = "plugin_webhooks",
+        all(feature = "plugin_tasks", feature = "database_postgres")
+    )),
+    allow(unused_variables, unused_mut)
+)]
+fn queue_depths(db: &mut Connection) -> Vec<(&'static str, String, i64)> {
+    let mut depths = vec![];
+
+    #[cfg(feature = "plugin_mail_queue")]
+    match crate::mail_queue::QueuedMail::stats(db) {
+        Ok(stats) => depths.extend([
+            ("mail", "pending".to_string(), stats.pending),
+            ("mail", "sent".to_string(), stats.sent),
+            ("mail", "dead".to_string(), stats.dead),
+        ]),
+        Err(err) => println!("prometheus: could not count the queued emails: {err}"),
+    }
+
+    #[cfg(feature = "plugin_webhooks")]
+    match crate::webhooks::WebhookDelivery::counts(db) {
+        Ok(counts) => depths.extend(
+            counts
+                .into_iter()
+                .map(|(status, count)| ("webhooks", status, count)),
+        ),
+        Err(err) => println!("prometheus: could not count the webhook deliveries: {err}"),
+    }
+
+    #[cfg(all(feature = "plugin_tasks", feature = "database_postgres"))]
+    {
+        use diesel::prelude::*;
+        use diesel::sql_types::{BigInt, Text};
+
+        #[derive(QueryableByName)]
+        struct TaskCount {
+            #[diesel(sql_type = Text)]
+            state: String,
+            #[diesel(sql_type = BigInt)]
+            count: i64,
+        }
+
+        match diesel::sql_query(
+            "SELECT state::text AS state, count(*) AS count FROM fang_tasks GROUP BY state",
+        )
+        .load::<TaskCount>(db)
+        {
+            Ok(counts) => depths.extend(
+                counts
+                    .into_iter()
+                    .map(|task_count| ("tasks", task_count.state, task_count.count)),
+            ),
+            Err(err) => println!("prometheus: could not count the tasks: {err}"),
+        }
+    }
+
+    depths
+}
+
+fn render_queues(out: &mut String, db: &Database) {
+    let depths = match db.pool.get() {
+        Ok(mut connection) => queue_depths(&mut connection),
+        Err(err) => {
+            println!("prometheus: could not count the queued jobs: {err}");
+            vec![]
+        }
+    };
+
+    header(
+        out,
+        "job_queue_depth",
+        "gauge",
+        "The jobs in each background queue, by status.",
+    );
+    for (queue, status, count) in depths {
+        let _ = writeln!(
+            out,
+            r#"job_queue_depth{{queue="{queue}",status="{}"}} {count}"#,
+            escape(&status)
+        );
+    }
+}
+
+fn render_emails(out: &mut String) {
+    let emails = EMAILS.lock().unwrap().clone();
+
+    header(
+        out,
+        "mailer_emails_total",
+        "counter",
+        "The emails the mailer sent, failed to send or queued.",
+    );
+    for outcome in ["sent", "failed", "queued"] {
+        let _ = writeln!(
+            out,
+            r#"mailer_emails_total{{outcome="{outcome}"}} {}"#,
+            emails.get(outcome).copied().unwrap_or(0)
+        );
+    }
+}
+
+/// every metric, in Prometheus' text format
+///
+/// counting the queued jobs runs queries, call it from a blocking context
+pub fn render(db: &Database) -> String {
+    let mut out = String::new();
+
+    render_requests(&mut out);
+    render_pool(&mut out, db);
+    render_queues(&mut out, db);
+    render_emails(&mut out);
+
+    out
+}
@@ -0,0 +1,75 @@
+use actix_http::header::{HeaderName, HeaderValue, AUTHORIZATION, CACHE_CONTROL};
+use actix_web::body::MessageBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+use super::{cache_control, SERVICE_WORKER_PATH};
+
+/// sets the `Cache-Control` of the responses without one, see [`pwa`](`super`)
+///
+/// ```rust,ignore
+/// let app = App::new().wrap(create_rust_app::pwa::CacheHeaders);
+/// ```
+pub struct CacheHeaders;
+
+impl<S, B> Transform<S, ServiceRequest> for CacheHeaders
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = CacheHeadersMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CacheHeadersMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct CacheHeadersMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for CacheHeadersMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let path = req.path().to_string();
+        let authenticated = req.headers().contains_key(AUTHORIZATION);
+
+        Box::pin(async move {
+            let mut response = service.call(req).await?;
+
+            let headers = response.headers_mut();
+            if !headers.contains_key(CACHE_CONTROL) {
+                headers.insert(
+                    CACHE_CONTROL,
+                    HeaderValue::from_static(cache_control(&path, authenticated)),
+                );
+            }
+            if path == SERVICE_WORKER_PATH {
+                headers.insert(
+                    HeaderName::from_static("service-worker-allowed"),
+                    HeaderValue::from_static("/"),
+                );
+            }
+
+            Ok(response)
+        })
+    }
+}
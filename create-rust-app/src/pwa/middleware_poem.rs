@@ -0,0 +1,48 @@
+use poem::http::header::{HeaderValue, AUTHORIZATION, CACHE_CONTROL};
+use poem::{async_trait, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+use super::{cache_control, SERVICE_WORKER_PATH};
+
+/// sets the `Cache-Control` of the responses without one, see [`pwa`](`super`)
+///
+/// ```rust,ignore
+/// let app = app.with(create_rust_app::pwa::CacheHeaders);
+/// ```
+pub struct CacheHeaders;
+
+impl<E: Endpoint> Middleware<E> for CacheHeaders {
+    type Output = CacheHeadersEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        CacheHeadersEndpoint { ep }
+    }
+}
+
+pub struct CacheHeadersEndpoint<E> {
+    ep: E,
+}
+
+#[async_trait]
+impl<E: Endpoint> Endpoint for CacheHeadersEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let path = req.uri().path().to_string();
+        let authenticated = req.headers().contains_key(AUTHORIZATION);
+
+        let mut response = self.ep.call(req).await?.into_response();
+
+        let headers = response.headers_mut();
+        if !headers.contains_key(CACHE_CONTROL) {
+            headers.insert(
+                CACHE_CONTROL,
+                HeaderValue::from_static(cache_control(&path, authenticated)),
+            );
+        }
+        if path == SERVICE_WORKER_PATH {
+            headers.insert("Service-Worker-Allowed", HeaderValue::from_static("/"));
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,75 @@
+//! Cache headers for the PWA plugin (`plugin_pwa` feature), telling the frontend's service worker
+//! which responses it can keep.
+//!
+//! ```rust,ignore
+//! // actix-web
+//! let app = App::new().wrap(create_rust_app::pwa::CacheHeaders);
+//! // poem
+//! let app = app.with(create_rust_app::pwa::CacheHeaders);
+//! ```
+//!
+//! The [`CacheHeaders`] middleware sets the `Cache-Control` of the responses without one (see
+//! [`cache_control`]): the built assets are cached forever (their names change with their
+//! contents), the service worker, the manifest and the pages are revalidated, and the API's
+//! responses are only stored (to be shown offline) when they aren't a signed in user's.
+//!
+//! The service worker (`frontend/src/sw.ts`) follows them: `no-store` responses are never cached,
+//! fresh `max-age` ones are served from its cache, and the others come from the network, falling
+//! back on its cache when the app is offline. Handlers opt their responses in with
+//! [`public_for`] and [`private_for`].
+use std::time::Duration;
+
+#[cfg(feature = "backend_actix-web")]
+mod middleware_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use middleware_actixweb::CacheHeaders;
+#[cfg(feature = "backend_poem")]
+mod middleware_poem;
+#[cfg(feature = "backend_poem")]
+pub use middleware_poem::CacheHeaders;
+
+/// the built assets' `Cache-Control`, their names being hashes of their contents
+pub const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// stored, but checked with the backend before being used (the service worker only uses them
+/// offline)
+pub const REVALIDATE: &str = "no-cache";
+/// never stored
+pub const NO_STORE: &str = "no-store";
+
+/// where vite writes the built scripts and styles
+const ASSETS_PATH: &str = "/assets/";
+/// the service worker's path, see `frontend/pwa.config.ts`
+pub const SERVICE_WORKER_PATH: &str = "/sw.js";
+/// the manifest's path, see `frontend/pwa.config.ts`
+pub const MANIFEST_PATH: &str = "/manifest.webmanifest";
+
+/// `Cache-Control` of a response anyone can be served from the caches for `duration`
+///
+/// ```rust,ignore
+/// HttpResponse::Ok()
+///     .insert_header((CACHE_CONTROL, create_rust_app::pwa::public_for(Duration::from_secs(60))))
+///     .json(posts)
+/// ```
+pub fn public_for(duration: Duration) -> String {
+    format!("public, max-age={}", duration.as_secs())
+}
+
+/// `Cache-Control` of a response only the user's browser can keep, fresh for `duration`
+///
+/// the service worker's cache of the API is cleared when the user logs out
+pub fn private_for(duration: Duration) -> String {
+    format!("private, max-age={}", duration.as_secs())
+}
+
+/// the `Cache-Control` [`CacheHeaders`] sets on a response to a request for `path` (with an
+/// `Authorization` header when `authenticated`), for the responses without one
+pub fn cache_control(path: &str, authenticated: bool) -> &'static str {
+    if path.starts_with(ASSETS_PATH) {
+        IMMUTABLE
+    } else if path.starts_with("/api/") && authenticated {
+        // another user of the device could be shown it offline
+        NO_STORE
+    } else {
+        REVALIDATE
+    }
+}
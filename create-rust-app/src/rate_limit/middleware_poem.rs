@@ -0,0 +1,50 @@
+use poem::http::StatusCode;
+use poem::{async_trait, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+use super::RateLimiting;
+
+impl<E: Endpoint> Middleware<E> for RateLimiting {
+    type Output = RateLimitingEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RateLimitingEndpoint {
+            ep,
+            rate_limiting: self.clone(),
+        }
+    }
+}
+
+pub struct RateLimitingEndpoint<E> {
+    ep: E,
+    rate_limiting: RateLimiting,
+}
+
+#[async_trait]
+impl<E: Endpoint> Endpoint for RateLimitingEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let ip_address = crate::client_ip(&req);
+        let authorization = req
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok());
+
+        let result = self.rate_limiting.check(
+            req.method().as_str(),
+            req.uri().path(),
+            ip_address.as_deref(),
+            authorization,
+        );
+
+        if let Err(retry_after) = result {
+            return Ok(Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .header("Retry-After", retry_after.as_secs().max(1).to_string())
+                .content_type("application/json")
+                .body(super::too_many_requests_body()));
+        }
+
+        self.ep.call(req).await.map(IntoResponse::into_response)
+    }
+}
@@ -0,0 +1,242 @@
+//! Rate limiting middleware (`plugin_rate_limit` feature): the [`RateLimiting`] middleware
+//! counts the requests it handles against its [`Policy`]s, and responds with `429 Too Many
+//! Requests` and a `Retry-After` header once one of them is over its limit.
+//!
+//! Policies count requests per IP address or per user (requests without a valid access token
+//! are counted per IP address), optionally for each path separately, and can be restricted to
+//! some paths and methods:
+//!
+//! ```rust,ignore
+//! use create_rust_app::auth::RateLimit;
+//! use create_rust_app::rate_limit::{Policy, RateLimiting};
+//! use std::time::Duration;
+//!
+//! let rate_limiting = RateLimiting::new()
+//!     .policy(Policy::per_ip(RateLimit::new(300, Duration::from_secs(60))))
+//!     .policy(
+//!         Policy::per_user(RateLimit::new(10, Duration::from_secs(60)))
+//!             .path("/api/reports")
+//!             .method("POST")
+//!             .per_path(),
+//!     );
+//!
+//! // actix-web
+//! let app = App::new().wrap(rate_limiting);
+//! // poem
+//! let app = app.with(rate_limiting);
+//! ```
+//!
+//! Like the auth endpoints' limits, requests are counted in memory by default, which is only
+//! shared by the workers of a single process: use a [`RedisStore`](`crate::auth::RedisStore`)
+//! (`plugin_redis` feature) when running several instances. Requests are let through when the
+//! store fails (ex: Redis is down).
+//!
+//! Requests are counted per connection peer address: behind a reverse proxy, list it in
+//! `TRUSTED_PROXIES` so that the client's address is read from the forwarding headers it sets
+//! instead (see [`client_ip`](`crate::util::net::client_ip`)), or every request shares the
+//! proxy's limit.
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth::{MemoryStore, RateLimit, RateLimitStore};
+
+#[cfg(feature = "backend_actix-web")]
+mod middleware_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use middleware_actixweb::RateLimitingMiddleware;
+
+#[cfg(feature = "backend_poem")]
+mod middleware_poem;
+#[cfg(feature = "backend_poem")]
+pub use middleware_poem::RateLimitingEndpoint;
+
+lazy_static::lazy_static! {
+    /// shared by every [`RateLimiting::new`], so the workers (which each build the app) share it
+    static ref MEMORY_STORE: Arc<MemoryStore> = Arc::new(MemoryStore::default());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// who requests are counted for
+pub enum RateLimitKey {
+    /// the client's IP address
+    Ip,
+    /// the user whose access token the request carries, or the IP address without one
+    User,
+}
+
+#[derive(Debug, Clone)]
+/// a limit on the requests a [`RateLimiting`] middleware handles
+pub struct Policy {
+    pub limit: RateLimit,
+    pub key: RateLimitKey,
+    /// whether requests to each path are counted separately
+    pub per_path: bool,
+    /// the path prefixes it applies to (default: every path)
+    pub paths: Vec<String>,
+    /// the methods it applies to (default: every method)
+    pub methods: Vec<String>,
+}
+
+impl Policy {
+    pub fn new(key: RateLimitKey, limit: RateLimit) -> Self {
+        Self {
+            limit,
+            key,
+            per_path: false,
+            paths: vec![],
+            methods: vec![],
+        }
+    }
+
+    pub fn per_ip(limit: RateLimit) -> Self {
+        Self::new(RateLimitKey::Ip, limit)
+    }
+
+    pub fn per_user(limit: RateLimit) -> Self {
+        Self::new(RateLimitKey::User, limit)
+    }
+
+    /// counts requests to each path separately
+    pub fn per_path(mut self) -> Self {
+        self.per_path = true;
+        self
+    }
+
+    /// applies the policy to the paths starting with `prefix` (ex: `/api/reports`), can be called
+    /// several times
+    pub fn path(mut self, prefix: impl Into<String>) -> Self {
+        self.paths.push(prefix.into());
+        self
+    }
+
+    /// applies the policy to the requests with this `method` (ex: `POST`), can be called several
+    /// times
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.methods.push(method.into().to_uppercase());
+        self
+    }
+
+    fn applies_to(&self, method: &str, path: &str) -> bool {
+        (self.paths.is_empty() || self.paths.iter().any(|prefix| path.starts_with(prefix)))
+            && (self.methods.is_empty() || self.methods.iter().any(|m| m == method))
+    }
+}
+
+#[derive(Clone)]
+/// the rate limiting middleware, see the [module documentation](`self`)
+pub struct RateLimiting {
+    policies: Arc<Vec<Policy>>,
+    store: Arc<dyn RateLimitStore>,
+    prefix: String,
+}
+
+impl Default for RateLimiting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for RateLimiting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiting")
+            .field("policies", &self.policies)
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl RateLimiting {
+    /// without policies (every request is let through), counting requests in memory
+    pub fn new() -> Self {
+        Self {
+            policies: Arc::new(vec![]),
+            store: MEMORY_STORE.clone(),
+            prefix: "requests".to_string(),
+        }
+    }
+
+    pub fn policy(mut self, policy: Policy) -> Self {
+        Arc::make_mut(&mut self.policies).push(policy);
+        self
+    }
+
+    pub fn store(mut self, store: impl RateLimitStore + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// tells apart the requests counted by several middlewares sharing a store (default:
+    /// `requests`), each one should have its own
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// counts a request against the policies that apply to it, returns how long to wait if any
+    /// of them is over its limit
+    ///
+    /// `authorization` is the request's `Authorization` header, read by per-user policies
+    pub fn check(
+        &self,
+        method: &str,
+        path: &str,
+        ip_address: Option<&str>,
+        authorization: Option<&str>,
+    ) -> Result<(), Duration> {
+        let mut retry_after: Option<Duration> = None;
+        // read once, by the first per-user policy
+        let mut request_user_id: Option<Option<i32>> = None;
+
+        for (index, policy) in self.policies.iter().enumerate() {
+            if !policy.applies_to(method, path) {
+                continue;
+            }
+
+            let user = match policy.key {
+                RateLimitKey::User => {
+                    *request_user_id.get_or_insert_with(|| user_id(authorization))
+                }
+                RateLimitKey::Ip => None,
+            };
+            let subject = match user {
+                Some(user_id) => format!("user:{user_id}"),
+                None => match ip_address {
+                    Some(ip_address) => format!("ip:{ip_address}"),
+                    None => continue,
+                },
+            };
+            let mut key = format!("{}:{index}:{subject}", self.prefix);
+            if policy.per_path {
+                key.push_str(&format!(":{method} {path}"));
+            }
+
+            match self.store.hit(&key, policy.limit.window) {
+                Ok((count, reset_in)) if count > policy.limit.max_attempts => {
+                    retry_after = Some(retry_after.map_or(reset_in, |d| d.max(reset_in)));
+                }
+                Ok(_) => {}
+                Err(err) => println!("rate limiting failed: {err}"),
+            }
+        }
+
+        match retry_after {
+            Some(retry_after) => Err(retry_after),
+            None => Ok(()),
+        }
+    }
+}
+
+/// the user whose access token is in the `Authorization` header, if it's valid
+fn user_id(authorization: Option<&str>) -> Option<i32> {
+    use crate::auth::{jwt, AccessTokenClaims};
+
+    let access_token =
+        jwt::decode_token::<AccessTokenClaims>(authorization?.strip_prefix("Bearer ")?).ok()?;
+
+    Some(access_token.claims.sub)
+}
+
+/// the body of the `429 Too Many Requests` responses
+fn too_many_requests_body() -> String {
+    serde_json::json!({ "message": "Too many requests, please try again later." }).to_string()
+}
@@ -0,0 +1,95 @@
+use actix_web::web::{Data, Payload};
+use actix_web::{get, HttpRequest, HttpResponse, Result};
+use actix_ws::{CloseCode, CloseReason, Message};
+
+use crate::auth::{AuthConfig, TokenRevocation};
+use crate::realtime::{
+    Realtime, ServerMessage, Session, AUTHENTICATION_TIMEOUT, HEARTBEAT_INTERVAL,
+};
+
+async fn send(ws: &mut actix_ws::Session, message: &ServerMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(text) => ws.text(text).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+/// handler for GET requests at the .../ endpoint
+///
+/// upgrades the connection to a websocket, see the [module documentation](`crate::realtime`)
+/// for its protocol
+#[get("")]
+async fn connect(
+    req: HttpRequest,
+    body: Payload,
+    auth_config: Option<Data<AuthConfig>>,
+) -> Result<HttpResponse> {
+    let revocation = match auth_config {
+        Some(config) => config.revocation.clone(),
+        None => Some(TokenRevocation::default()),
+    };
+
+    let (response, ws, stream) = actix_ws::handle(&req, body)?;
+    let session = Realtime::global().connect(revocation);
+
+    actix_web::rt::spawn(run(session, ws, stream));
+
+    Ok(response)
+}
+
+async fn run(mut session: Session, mut ws: actix_ws::Session, mut stream: actix_ws::MessageStream) {
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let authentication_deadline = tokio::time::sleep(AUTHENTICATION_TIMEOUT);
+    tokio::pin!(authentication_deadline);
+
+    let reason = loop {
+        tokio::select! {
+            message = stream.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    if let Some(answer) = session.handle(&text) {
+                        if !send(&mut ws, &answer).await {
+                            break None;
+                        }
+                    }
+                }
+                Some(Ok(Message::Ping(bytes))) => {
+                    if ws.pong(&bytes).await.is_err() {
+                        break None;
+                    }
+                }
+                Some(Ok(Message::Close(reason))) => break reason,
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => break None,
+            },
+            Some(message) = session.next_message() => {
+                if !send(&mut ws, &message).await {
+                    break None;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if session.is_expired() {
+                    break Some(CloseReason {
+                        code: CloseCode::Policy,
+                        description: Some("Access token expired.".to_string()),
+                    });
+                }
+                if ws.ping(b"").await.is_err() {
+                    break None;
+                }
+            }
+            _ = &mut authentication_deadline, if !session.is_authenticated() => {
+                break Some(CloseReason {
+                    code: CloseCode::Policy,
+                    description: Some("Not authenticated.".to_string()),
+                });
+            }
+        }
+    };
+
+    ws.close(reason).await.ok();
+}
+
+/// returns the realtime websocket's endpoint
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(connect)
+}
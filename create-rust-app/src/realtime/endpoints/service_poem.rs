@@ -0,0 +1,80 @@
+use futures_util::{SinkExt, StreamExt};
+use poem::{
+    get, handler,
+    web::websocket::{CloseCode, Message, WebSocket},
+    web::Data,
+    IntoResponse, Route,
+};
+
+use crate::auth::{AuthConfig, TokenRevocation};
+use crate::realtime::{
+    Realtime, ServerMessage, Session, AUTHENTICATION_TIMEOUT, HEARTBEAT_INTERVAL,
+};
+
+fn text(message: &ServerMessage) -> Option<Message> {
+    serde_json::to_string(message).ok().map(Message::Text)
+}
+
+#[handler]
+/// handler for GET requests at the .../ endpoint
+///
+/// upgrades the connection to a websocket, see the [module documentation](`crate::realtime`)
+/// for its protocol
+async fn connect(ws: WebSocket, auth_config: Option<Data<&AuthConfig>>) -> impl IntoResponse {
+    let revocation = match auth_config {
+        Some(config) => config.0.revocation.clone(),
+        None => Some(TokenRevocation::default()),
+    };
+    let session = Realtime::global().connect(revocation);
+
+    ws.on_upgrade(move |socket| run(session, socket))
+}
+
+async fn run(mut session: Session, socket: poem::web::websocket::WebSocketStream) {
+    let (mut sink, mut stream) = socket.split();
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let authentication_deadline = tokio::time::sleep(AUTHENTICATION_TIMEOUT);
+    tokio::pin!(authentication_deadline);
+
+    let reason = loop {
+        tokio::select! {
+            message = stream.next() => match message {
+                Some(Ok(Message::Text(message))) => {
+                    if let Some(answer) = session.handle(&message).as_ref().and_then(text) {
+                        if sink.send(answer).await.is_err() {
+                            break None;
+                        }
+                    }
+                }
+                // pings are answered by the websocket itself
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break None,
+                Some(Ok(_)) => {}
+            },
+            Some(message) = session.next_message() => {
+                if let Some(message) = text(&message) {
+                    if sink.send(message).await.is_err() {
+                        break None;
+                    }
+                }
+            }
+            _ = heartbeat.tick() => {
+                if session.is_expired() {
+                    break Some((CloseCode::Policy, "Access token expired.".to_string()));
+                }
+                if sink.send(Message::Ping(vec![])).await.is_err() {
+                    break None;
+                }
+            }
+            _ = &mut authentication_deadline, if !session.is_authenticated() => {
+                break Some((CloseCode::Policy, "Not authenticated.".to_string()));
+            }
+        }
+    };
+
+    sink.send(Message::Close(reason)).await.ok();
+}
+
+/// returns the realtime websocket's endpoint
+pub fn api() -> Route {
+    Route::new().at("/", get(connect))
+}
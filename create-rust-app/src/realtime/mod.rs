@@ -0,0 +1,511 @@
+//! Realtime channels (`plugin_realtime` feature): clients subscribe to named channels over an
+//! authenticated websocket, and the app pushes events to them with [`broadcast`].
+//!
+//! ```rust,ignore
+//! // every subscriber of `todos` gets `{"type": "message", "channel": "todos", "data": {...}}`
+//! create_rust_app::realtime::broadcast("todos", &TodoEvent::Created { id: todo.id })?;
+//! ```
+//!
+//! Mount the websocket like the other services (`GET /`); the frontend's `RealtimeClient` speaks
+//! its protocol, JSON text messages tagged by their `type`:
+//!
+//! - the client authenticates first, with `{"type": "authenticate", "token": "<access token>"}`
+//!   (connections that don't within [`AUTHENTICATION_TIMEOUT`] are closed, like the ones whose
+//!   token expires), then `subscribe`s to and `unsubscribe`s from channels (`{"type": "subscribe",
+//!   "channel": "todos"}`), and can `publish` to them (`{"type": "publish", "channel": "chat",
+//!   "data": {...}}`)
+//! - the server answers with `authenticated`, `subscribed`, `unsubscribed` or `error` messages,
+//!   and forwards the channels' `message`s
+//!
+//! Who can subscribe and publish to which channel is up to the [`Realtime::authorize`]
+//! callback, by default ([`default_authorizer`]) authenticated users can subscribe to any channel
+//! but other users' `user:<id>` ones, and only the server publishes.
+//!
+//! Channels live in the memory of a single process: with the `plugin_redis` feature and
+//! `REDIS_URL` set, broadcasts go through Redis instead, so they reach the clients of every
+//! instance of the app (and can be made from other processes, ex: the scheduler or the tasks
+//! queue).
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::auth::{Auth, TokenRevocation};
+
+mod endpoints;
+pub use endpoints::*;
+
+/// how long a connection has to authenticate
+pub const AUTHENTICATION_TIMEOUT: Duration = Duration::from_secs(10);
+/// how often connections are pinged (which keeps proxies from closing idle ones), and checked
+/// for an expired access token
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// how many channels a connection can subscribe to
+pub const MAX_SUBSCRIPTIONS: usize = 100;
+const MAX_CHANNEL_NAME_LENGTH: usize = 200;
+
+lazy_static::lazy_static! {
+    static ref GLOBAL: Realtime = Realtime::from_env();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// what a client wants to do with a channel, see [`Realtime::authorize`]
+pub enum Access {
+    Subscribe,
+    Publish,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// the messages clients send
+pub enum ClientMessage {
+    /// (re)authenticates the connection, ex: with a refreshed access token
+    Authenticate {
+        token: String,
+    },
+    Subscribe {
+        channel: String,
+    },
+    Unsubscribe {
+        channel: String,
+    },
+    Publish {
+        channel: String,
+        data: Value,
+    },
+    Ping,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// the messages the server sends
+pub enum ServerMessage {
+    Authenticated {
+        user_id: i32,
+    },
+    Subscribed {
+        channel: String,
+    },
+    Unsubscribed {
+        channel: String,
+    },
+    /// something was broadcast to a channel the client subscribed to
+    Message {
+        channel: String,
+        data: Value,
+    },
+    Error {
+        /// the channel the failed message was about
+        channel: Option<String>,
+        message: String,
+    },
+    Pong,
+}
+
+type Authorizer = Arc<dyn Fn(&Auth, &str, Access) -> bool + Send + Sync>;
+type Subscribers = HashMap<String, HashMap<u64, UnboundedSender<ServerMessage>>>;
+
+/// users can subscribe to any channel but other users' `user:<id>` ones, only the server
+/// publishes
+pub fn default_authorizer(auth: &Auth, channel: &str, access: Access) -> bool {
+    match access {
+        Access::Publish => false,
+        Access::Subscribe => match channel.strip_prefix("user:") {
+            Some(user_id) => user_id == auth.user_id.to_string(),
+            None => true,
+        },
+    }
+}
+
+struct Hub {
+    subscribers: RwLock<Subscribers>,
+    next_session_id: AtomicU64,
+    authorizer: RwLock<Authorizer>,
+    #[cfg(feature = "plugin_redis")]
+    redis: Option<RedisFanout>,
+}
+
+#[cfg(feature = "plugin_redis")]
+struct RedisFanout {
+    client: redis::Client,
+    prefix: String,
+}
+
+#[derive(Clone)]
+/// the channels and their subscribers, see the [module documentation](`self`)
+pub struct Realtime {
+    hub: Arc<Hub>,
+}
+
+impl std::fmt::Debug for Realtime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Realtime").finish()
+    }
+}
+
+impl Default for Realtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Realtime {
+    /// channels kept in this process' memory
+    pub fn new() -> Self {
+        Self {
+            hub: Arc::new(Hub {
+                subscribers: RwLock::new(HashMap::new()),
+                next_session_id: AtomicU64::new(1),
+                authorizer: RwLock::new(Arc::new(default_authorizer)),
+                #[cfg(feature = "plugin_redis")]
+                redis: None,
+            }),
+        }
+    }
+
+    #[cfg(feature = "plugin_redis")]
+    /// channels shared by every instance of the app through Redis' pub/sub, `url` like
+    /// `redis://127.0.0.1/`; Redis channels are prefixed with `cra:realtime:`
+    pub fn redis(url: &str) -> redis::RedisResult<Self> {
+        let prefix = "cra:realtime:".to_string();
+        let client = redis::Client::open(url)?;
+        // fail now rather than in the listener
+        client.get_connection()?;
+
+        let realtime = Self {
+            hub: Arc::new(Hub {
+                subscribers: RwLock::new(HashMap::new()),
+                next_session_id: AtomicU64::new(1),
+                authorizer: RwLock::new(Arc::new(default_authorizer)),
+                redis: Some(RedisFanout {
+                    client: client.clone(),
+                    prefix: prefix.clone(),
+                }),
+            }),
+        };
+        listen(client, prefix, Arc::downgrade(&realtime.hub));
+
+        Ok(realtime)
+    }
+
+    /// through Redis if the `plugin_redis` feature is enabled and `REDIS_URL` is set, in memory
+    /// otherwise
+    pub fn from_env() -> Self {
+        #[cfg(feature = "plugin_redis")]
+        if let Ok(url) = std::env::var("REDIS_URL") {
+            match Self::redis(&url) {
+                Ok(realtime) => return realtime,
+                Err(err) => println!(
+                    "Realtime: could not connect to Redis ({err}), channels won't be shared with other instances"
+                ),
+            }
+        }
+
+        Self::new()
+    }
+
+    /// the process' channels, used by the websocket endpoint and [`broadcast`]
+    pub fn global() -> Self {
+        GLOBAL.clone()
+    }
+
+    /// decides who can subscribe and publish to which channel (default: [`default_authorizer`])
+    ///
+    /// ```rust,ignore
+    /// Realtime::global().authorize(|auth, channel, access| match channel.strip_prefix("admin:") {
+    ///     Some(_) => auth.has_role("admin".to_string()),
+    ///     None => default_authorizer(auth, channel, access),
+    /// });
+    /// ```
+    pub fn authorize(
+        &self,
+        authorizer: impl Fn(&Auth, &str, Access) -> bool + Send + Sync + 'static,
+    ) -> &Self {
+        *self.hub.authorizer.write().unwrap() = Arc::new(authorizer);
+        self
+    }
+
+    /// sends `data` to the subscribers of `channel`
+    pub fn broadcast<T: Serialize>(&self, channel: &str, data: &T) -> Result<(), String> {
+        let data = serde_json::to_value(data).map_err(|err| err.to_string())?;
+
+        #[cfg(feature = "plugin_redis")]
+        if let Some(redis) = &self.hub.redis {
+            // the listener delivers it, here as well as in the other instances
+            let mut conn = redis
+                .client
+                .get_connection()
+                .map_err(|err| err.to_string())?;
+
+            return redis::cmd("PUBLISH")
+                .arg(format!("{}{channel}", redis.prefix))
+                .arg(data.to_string())
+                .query::<()>(&mut conn)
+                .map_err(|err| err.to_string());
+        }
+
+        self.hub.deliver(channel, data);
+
+        Ok(())
+    }
+
+    /// how many connections (of this process) subscribed to `channel`
+    pub fn subscriber_count(&self, channel: &str) -> usize {
+        self.hub
+            .subscribers
+            .read()
+            .unwrap()
+            .get(channel)
+            .map_or(0, |subscribers| subscribers.len())
+    }
+
+    /// a new connection, the websocket endpoint's side of it
+    ///
+    /// `revocation` is checked when it authenticates, like the [`Auth`] extractor does; `None`
+    /// skips it
+    pub fn connect(&self, revocation: Option<TokenRevocation>) -> Session {
+        let (sender, receiver) = unbounded_channel();
+
+        Session {
+            id: self.hub.next_session_id.fetch_add(1, Ordering::Relaxed),
+            realtime: self.clone(),
+            revocation,
+            auth: None,
+            channels: HashSet::new(),
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl Hub {
+    fn deliver(&self, channel: &str, data: Value) {
+        let subscribers = self.subscribers.read().unwrap();
+
+        if let Some(subscribers) = subscribers.get(channel) {
+            for sender in subscribers.values() {
+                // the receiver is gone when its session is being dropped, which unsubscribes it
+                sender
+                    .send(ServerMessage::Message {
+                        channel: channel.to_string(),
+                        data: data.clone(),
+                    })
+                    .ok();
+            }
+        }
+    }
+
+    fn is_authorized(&self, auth: &Auth, channel: &str, access: Access) -> bool {
+        let authorizer = self.authorizer.read().unwrap().clone();
+
+        authorizer(auth, channel, access)
+    }
+}
+
+/// sends `data` to the subscribers of `channel`, see [`Realtime::broadcast`]
+pub fn broadcast<T: Serialize>(channel: &str, data: &T) -> Result<(), String> {
+    Realtime::global().broadcast(channel, data)
+}
+
+#[cfg(feature = "plugin_redis")]
+/// delivers the messages published to Redis (by any instance) until the hub is dropped,
+/// reconnecting when the connection is lost
+fn listen(client: redis::Client, prefix: String, hub: std::sync::Weak<Hub>) {
+    std::thread::spawn(move || loop {
+        let result = (|| -> redis::RedisResult<()> {
+            let mut conn = client.get_connection()?;
+            let mut pubsub = conn.as_pubsub();
+            pubsub.psubscribe(format!("{prefix}*"))?;
+
+            loop {
+                let message = pubsub.get_message()?;
+                let hub = match hub.upgrade() {
+                    Some(hub) => hub,
+                    None => return Ok(()),
+                };
+
+                let payload = message.get_payload::<String>()?;
+                if let (Some(channel), Ok(data)) = (
+                    message.get_channel_name().strip_prefix(&prefix),
+                    serde_json::from_str::<Value>(&payload),
+                ) {
+                    hub.deliver(channel, data);
+                }
+            }
+        })();
+
+        match result {
+            Ok(()) => return,
+            Err(err) => {
+                println!("Realtime: lost the Redis connection ({err}), reconnecting...");
+                std::thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+}
+
+/// a websocket connection: its user, its channels and the messages to send it
+///
+/// dropping it unsubscribes it from its channels
+pub struct Session {
+    id: u64,
+    realtime: Realtime,
+    revocation: Option<TokenRevocation>,
+    auth: Option<Auth>,
+    channels: HashSet<String>,
+    sender: UnboundedSender<ServerMessage>,
+    receiver: UnboundedReceiver<ServerMessage>,
+}
+
+impl Session {
+    /// the connection's user, once it authenticated
+    pub fn auth(&self) -> Option<&Auth> {
+        self.auth.as_ref()
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.auth.is_some()
+    }
+
+    /// whether the access token it authenticated with expired
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as usize;
+
+        self.auth.as_ref().map_or(false, |auth| auth.exp <= now)
+    }
+
+    /// the next message broadcast to its channels
+    pub async fn next_message(&mut self) -> Option<ServerMessage> {
+        self.receiver.recv().await
+    }
+
+    /// handles a text message of the client, returns the answer to send it
+    pub fn handle(&mut self, text: &str) -> Option<ServerMessage> {
+        let message = match serde_json::from_str::<ClientMessage>(text) {
+            Ok(message) => message,
+            Err(_) => return Some(error(None, "Invalid message.")),
+        };
+
+        let auth = match (&message, &self.auth) {
+            (ClientMessage::Authenticate { token }, _) => return Some(self.authenticate(token)),
+            (ClientMessage::Ping, _) => return Some(ServerMessage::Pong),
+            (_, Some(auth)) => auth.clone(),
+            (_, None) => return Some(error(None, "Not authenticated.")),
+        };
+
+        match message {
+            ClientMessage::Subscribe { channel } => Some(self.subscribe(&auth, channel)),
+            ClientMessage::Unsubscribe { channel } => {
+                self.unsubscribe(&channel);
+                Some(ServerMessage::Unsubscribed { channel })
+            }
+            ClientMessage::Publish { channel, data } => {
+                if !is_valid_channel(&channel)
+                    || !self
+                        .realtime
+                        .hub
+                        .is_authorized(&auth, &channel, Access::Publish)
+                {
+                    return Some(error(Some(channel), "Forbidden."));
+                }
+
+                match self.realtime.broadcast(&channel, &data) {
+                    Ok(()) => None,
+                    Err(_) => Some(error(Some(channel), "Could not publish the message.")),
+                }
+            }
+            ClientMessage::Authenticate { .. } | ClientMessage::Ping => None,
+        }
+    }
+
+    fn authenticate(&mut self, token: &str) -> ServerMessage {
+        let auth = match Auth::from_access_token(token, self.revocation.as_ref()) {
+            Ok(auth) => auth,
+            Err(message) => return error(None, &message),
+        };
+
+        // another user's token: their channels may not be open to this one
+        if self
+            .auth
+            .as_ref()
+            .map_or(false, |previous| previous.user_id != auth.user_id)
+        {
+            for channel in self.channels.clone() {
+                self.unsubscribe(&channel);
+            }
+        }
+
+        let user_id = auth.user_id;
+        self.auth = Some(auth);
+
+        ServerMessage::Authenticated { user_id }
+    }
+
+    fn subscribe(&mut self, auth: &Auth, channel: String) -> ServerMessage {
+        if !is_valid_channel(&channel) {
+            return error(Some(channel), "Invalid channel name.");
+        }
+        if !self
+            .realtime
+            .hub
+            .is_authorized(auth, &channel, Access::Subscribe)
+        {
+            return error(Some(channel), "Forbidden.");
+        }
+        if !self.channels.contains(&channel) && self.channels.len() >= MAX_SUBSCRIPTIONS {
+            return error(Some(channel), "Too many subscriptions.");
+        }
+
+        self.realtime
+            .hub
+            .subscribers
+            .write()
+            .unwrap()
+            .entry(channel.clone())
+            .or_default()
+            .insert(self.id, self.sender.clone());
+        self.channels.insert(channel.clone());
+
+        ServerMessage::Subscribed { channel }
+    }
+
+    fn unsubscribe(&mut self, channel: &str) {
+        self.channels.remove(channel);
+
+        let mut subscribers = self.realtime.hub.subscribers.write().unwrap();
+        if let Some(channel_subscribers) = subscribers.get_mut(channel) {
+            channel_subscribers.remove(&self.id);
+            if channel_subscribers.is_empty() {
+                subscribers.remove(channel);
+            }
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        for channel in self.channels.clone() {
+            self.unsubscribe(&channel);
+        }
+    }
+}
+
+fn error(channel: Option<String>, message: &str) -> ServerMessage {
+    ServerMessage::Error {
+        channel,
+        message: message.to_string(),
+    }
+}
+
+fn is_valid_channel(channel: &str) -> bool {
+    !channel.is_empty()
+        && channel.len() <= MAX_CHANNEL_NAME_LENGTH
+        && !channel.chars().any(|c| c.is_whitespace() || c.is_control())
+}
@@ -0,0 +1,110 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Json};
+use actix_web::{get, post, web, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::referrals::{self, ClaimError, ReferralConfig, STATS_PERMISSION};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../claim endpoint
+pub struct ClaimInput {
+    code: String,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the .../me endpoint
+///
+/// requires auth
+///
+/// returns the user's referral code and how many users joined with it
+#[get("/me")]
+async fn me(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result =
+        web::block(move || referrals::stats_for_user(&mut db.get_connection(), auth.user_id))
+            .await?;
+
+    match result {
+        Ok(stats) => Ok(HttpResponse::Ok().json(stats)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch referrals.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../claim endpoint
+///
+/// requires auth
+///
+/// attributes the user to the owner of the given `code`, see [`referrals::claim`]
+#[post("/claim")]
+async fn claim(
+    db: Data<Database>,
+    config: Option<Data<ReferralConfig>>,
+    auth: Auth,
+    Json(item): Json<ClaimInput>,
+) -> Result<HttpResponse> {
+    let config = config.unwrap_or_else(|| Data::new(ReferralConfig::default()));
+
+    let result = web::block(move || {
+        referrals::claim(&mut db.get_connection(), auth.user_id, &item.code, &config)
+    })
+    .await?;
+
+    match result {
+        Ok(referral) => Ok(HttpResponse::Ok().json(referral)),
+        Err(ClaimError::InvalidCode) => Ok(error_response(
+            StatusCode::NOT_FOUND,
+            "Invalid referral code.",
+        )),
+        Err(ClaimError::OwnCode) => Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "Cannot use your own referral code.",
+        )),
+        Err(ClaimError::AlreadyReferred) => Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "A referral code was already used.",
+        )),
+        Err(ClaimError::Expired) => Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "Referral codes can only be used right after signing up.",
+        )),
+        Err(ClaimError::Database(_)) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not record the referral.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../stats endpoint
+///
+/// requires auth, and the [`STATS_PERMISSION`]
+///
+/// returns the number of referrals, and the top 10 referrers
+#[get("/stats")]
+async fn stats(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    if !auth.has_permission(STATS_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let result = web::block(move || referrals::stats(&mut db.get_connection(), 10)).await?;
+
+    match result {
+        Ok(totals) => Ok(HttpResponse::Ok().json(totals)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch referrals.",
+        )),
+    }
+}
+
+/// returns the endpoints for the referrals service
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(me).service(claim).service(stats)
+}
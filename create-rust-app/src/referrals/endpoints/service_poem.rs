@@ -0,0 +1,127 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    post,
+    web::{Data, Json},
+    Error, IntoResponse, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::referrals::{self, ClaimError, ReferralConfig, STATS_PERMISSION};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// Json body of POST requests to the .../claim endpoint
+pub struct ClaimInput {
+    code: String,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for GET requests at the .../me endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the user's [`ReferralStats`](`crate::referrals::ReferralStats`)
+/// | 500 | Json payload : {"message": "Could not fetch referrals."}
+async fn me(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    referrals::stats_for_user(&mut db.get_connection(), auth.user_id)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch referrals.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../claim endpoint
+///
+/// requires auth
+///
+/// see [`referrals::claim`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the recorded [`Referral`](`crate::referrals::Referral`)
+/// | 400 | Json payload : {"message": "Cannot use your own referral code."}
+/// | 400 | Json payload : {"message": "A referral code was already used."}
+/// | 400 | Json payload : {"message": "Referral codes can only be used right after signing up."}
+/// | 404 | Json payload : {"message": "Invalid referral code."}
+/// | 500 | Json payload : {"message": "Could not record the referral."}
+async fn claim(
+    db: Data<&Database>,
+    config: Option<Data<&ReferralConfig>>,
+    auth: Auth,
+    Json(item): Json<ClaimInput>,
+) -> Result<impl IntoResponse> {
+    let config = config.map(|config| config.0.clone()).unwrap_or_default();
+
+    match referrals::claim(&mut db.get_connection(), auth.user_id, &item.code, &config) {
+        Ok(referral) => Ok(Json(referral)),
+        Err(ClaimError::InvalidCode) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            "Invalid referral code.",
+        )),
+        Err(ClaimError::OwnCode) => Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Cannot use your own referral code.",
+        )),
+        Err(ClaimError::AlreadyReferred) => Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "A referral code was already used.",
+        )),
+        Err(ClaimError::Expired) => Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Referral codes can only be used right after signing up.",
+        )),
+        Err(ClaimError::Database(_)) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not record the referral.",
+        )),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../stats endpoint
+///
+/// requires auth, and the [`STATS_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : [`ReferralTotals`](`crate::referrals::ReferralTotals`) with the top 10 referrers
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch referrals."}
+async fn stats(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    if !auth.has_permission(STATS_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    referrals::stats(&mut db.get_connection(), 10)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch referrals.",
+            )
+        })
+}
+
+/// returns the endpoints for the referrals service
+pub fn api() -> Route {
+    Route::new()
+        .at("/me", get(me))
+        .at("/claim", post(claim))
+        .at("/stats", get(stats))
+}
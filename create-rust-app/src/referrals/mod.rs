@@ -0,0 +1,464 @@
+//! Referral codes, attribution of new users to the user who invited them, and rewards.
+//!
+//! Every user gets a [`ReferralCode`] the first time they ask for it. Someone who signs up through
+//! a referral link claims the code once they're logged in ([`claim`]), which records a [`Referral`]
+//! and runs the [`ReferralReward`]s of the [`ReferralConfig`] registered in the app's data.
+//!
+//! ```rust,ignore
+//! use create_rust_app::referrals::{Referral, ReferralConfig, ReferralReward};
+//!
+//! struct FreeMonth;
+//!
+//! impl ReferralReward for FreeMonth {
+//!     fn reward(&self, db: &mut Connection, referral: &Referral) -> QueryResult<()> {
+//!         Subscription::extend(db, referral.referrer_id, chrono::Duration::days(30))
+//!     }
+//! }
+//!
+//! let referral_config = ReferralConfig::default().reward(FreeMonth);
+//! app = app.app_data(Data::new(referral_config.clone()));
+//! ```
+use crate::auth::User;
+use crate::diesel::*;
+use crate::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// permission required to see the referral stats of every user
+pub const STATS_PERMISSION: &str = "admin:referrals";
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable)]
+#[diesel(table_name=referral_codes, primary_key(user_id))]
+pub struct ReferralCode {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub code: String,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=referrals)]
+/// a user ([`referred_user_id`](`Referral::referred_user_id`)) who signed up with the code of [`referrer_id`](`Referral::referrer_id`)
+pub struct Referral {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub referrer_id: ID,
+    pub referred_user_id: ID,
+    pub code: String,
+    pub rewarded_at: Option<Utc>,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=referrals)]
+pub struct ReferralChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub referrer_id: ID,
+    pub referred_user_id: ID,
+    pub code: String,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// a user's referral code and how many people joined with it
+pub struct ReferralStats {
+    pub code: String,
+    pub referred: i64,
+    pub rewarded: i64,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// referral numbers across all users, see [`stats`]
+pub struct ReferralTotals {
+    pub referrals: i64,
+    pub rewarded: i64,
+    /// the users with the most referrals
+    pub top_referrers: Vec<TopReferrer>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TopReferrer {
+    pub user_id: ID,
+    pub referrals: i64,
+}
+
+/// grants the referrer (and/or the referred user) whatever your app gives for a referral
+///
+/// rewards run in the transaction recording the [`Referral`], returning an error undoes the claim
+pub trait ReferralReward: Send + Sync {
+    fn reward(&self, db: &mut Connection, referral: &Referral) -> QueryResult<()>;
+}
+
+#[derive(Clone)]
+/// how referrals are claimed and rewarded
+///
+/// register it in the app's data, the default one is used otherwise
+pub struct ReferralConfig {
+    /// how long after signing up a user can still claim a referral code (default: 7 days)
+    pub claim_window: chrono::Duration,
+    /// the rewards granted for each referral, in order
+    pub rewards: Vec<Arc<dyn ReferralReward>>,
+}
+
+impl Default for ReferralConfig {
+    fn default() -> Self {
+        Self {
+            claim_window: chrono::Duration::days(7),
+            rewards: vec![],
+        }
+    }
+}
+
+impl std::fmt::Debug for ReferralConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReferralConfig")
+            .field("claim_window", &self.claim_window)
+            .field("rewards", &self.rewards.len())
+            .finish()
+    }
+}
+
+impl ReferralConfig {
+    pub fn claim_window(mut self, window: chrono::Duration) -> Self {
+        self.claim_window = window;
+        self
+    }
+
+    pub fn reward(mut self, reward: impl ReferralReward + 'static) -> Self {
+        self.rewards.push(Arc::new(reward));
+        self
+    }
+}
+
+#[derive(Debug)]
+/// why a referral code could not be claimed
+pub enum ClaimError {
+    /// no user has this code
+    InvalidCode,
+    /// users can't refer themselves
+    OwnCode,
+    /// the user already claimed a code
+    AlreadyReferred,
+    /// the user signed up longer than [`ReferralConfig::claim_window`] ago
+    Expired,
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for ClaimError {
+    fn from(err: diesel::result::Error) -> Self {
+        Self::Database(err)
+    }
+}
+
+impl ReferralCode {
+    /// the code of [`item_user_id`](`ID`), created the first time it's asked for
+    pub fn for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Self> {
+        use schema::referral_codes::dsl::*;
+
+        let mut attempts = 0;
+
+        loop {
+            let inserted = insert_into(referral_codes)
+                .values((user_id.eq(item_user_id), code.eq(generate_code())))
+                .on_conflict(user_id)
+                .do_nothing()
+                .execute(db);
+
+            match inserted {
+                // another user already has the generated code, try a new one
+                Err(diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::UniqueViolation,
+                    _,
+                )) if attempts < 3 => attempts += 1,
+                Err(err) => return Err(err),
+                Ok(_) => break,
+            }
+        }
+
+        referral_codes
+            .filter(user_id.eq(item_user_id))
+            .first::<ReferralCode>(db)
+    }
+
+    /// the [`ReferralCode`] matching [`item_code`] (case insensitive)
+    pub fn find(db: &mut Connection, item_code: &str) -> QueryResult<Self> {
+        use schema::referral_codes::dsl::*;
+
+        referral_codes
+            .filter(code.eq(item_code.trim().to_uppercase()))
+            .first::<ReferralCode>(db)
+    }
+
+    /// Delete the code of [`item_user_id`](`ID`)
+    pub fn delete(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::referral_codes::dsl::*;
+
+        diesel::delete(referral_codes.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
+
+impl Referral {
+    /// Create an entry in [`db`](`Connection`)'s `referrals` table using the data in [`item`](`ReferralChangeset`)
+    pub fn create(db: &mut Connection, item: &ReferralChangeset) -> QueryResult<Self> {
+        use schema::referrals::dsl::*;
+
+        insert_into(referrals)
+            .values(item)
+            .get_result::<Referral>(db)
+    }
+
+    /// the referral that brought [`item_user_id`](`ID`) in, if any
+    pub fn find_for_referred_user(
+        db: &mut Connection,
+        item_user_id: ID,
+    ) -> QueryResult<Option<Self>> {
+        use schema::referrals::dsl::*;
+
+        referrals
+            .filter(referred_user_id.eq(item_user_id))
+            .first::<Referral>(db)
+            .optional()
+    }
+
+    /// the users referred by [`item_referrer_id`](`ID`), most recent first
+    pub fn fetch_all_for_referrer(
+        db: &mut Connection,
+        item_referrer_id: ID,
+    ) -> QueryResult<Vec<Self>> {
+        use schema::referrals::dsl::*;
+
+        referrals
+            .filter(referrer_id.eq(item_referrer_id))
+            .order(created_at.desc())
+            .load::<Referral>(db)
+    }
+
+    /// records that the rewards for [`item_id`](`ID`) were granted
+    pub fn mark_rewarded(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use schema::referrals::dsl::*;
+
+        diesel::update(referrals.filter(id.eq(item_id)))
+            .set(rewarded_at.eq(Some(now())))
+            .execute(db)
+    }
+
+    /// deletes the referrals made by or of [`item_user_id`](`ID`)
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use schema::referrals::dsl::*;
+
+        diesel::delete(
+            referrals.filter(
+                referrer_id
+                    .eq(item_user_id)
+                    .or(referred_user_id.eq(item_user_id)),
+            ),
+        )
+        .execute(db)
+    }
+}
+
+/// attributes [`user_id`](`ID`) to the owner of [`code`] and grants the rewards in [`config`](`ReferralConfig`)
+pub fn claim(
+    db: &mut Connection,
+    user_id: ID,
+    code: &str,
+    config: &ReferralConfig,
+) -> Result<Referral, ClaimError> {
+    diesel::connection::Connection::transaction(db, |db| {
+        let referral_code = match ReferralCode::find(db, code) {
+            Ok(referral_code) => referral_code,
+            Err(diesel::result::Error::NotFound) => return Err(ClaimError::InvalidCode),
+            Err(err) => return Err(err.into()),
+        };
+
+        if referral_code.user_id == user_id {
+            return Err(ClaimError::OwnCode);
+        }
+
+        if Referral::find_for_referred_user(db, user_id)?.is_some() {
+            return Err(ClaimError::AlreadyReferred);
+        }
+
+        let user = User::read(db, user_id)?;
+        if now() - user.created_at > config.claim_window {
+            return Err(ClaimError::Expired);
+        }
+
+        let referral = Referral::create(
+            db,
+            &ReferralChangeset {
+                referrer_id: referral_code.user_id,
+                referred_user_id: user_id,
+                code: referral_code.code,
+            },
+        )?;
+
+        #[cfg(feature = "plugin_notifications")]
+        notify_referrer(db, &referral)?;
+
+        if !config.rewards.is_empty() {
+            for reward in &config.rewards {
+                reward.reward(db, &referral)?;
+            }
+
+            Referral::mark_rewarded(db, referral.id)?;
+        }
+
+        Ok(referral)
+    })
+}
+
+/// the code of [`user_id`](`ID`) and how many users they referred
+pub fn stats_for_user(db: &mut Connection, user_id: ID) -> QueryResult<ReferralStats> {
+    use schema::referrals::dsl as referrals;
+
+    let code = ReferralCode::for_user(db, user_id)?;
+
+    let referred = referrals::referrals
+        .filter(referrals::referrer_id.eq(user_id))
+        .count()
+        .get_result::<i64>(db)?;
+
+    let rewarded = referrals::referrals
+        .filter(referrals::referrer_id.eq(user_id))
+        .filter(referrals::rewarded_at.is_not_null())
+        .count()
+        .get_result::<i64>(db)?;
+
+    Ok(ReferralStats {
+        code: code.code,
+        referred,
+        rewarded,
+    })
+}
+
+/// referral numbers across all users, with the [`top`] referrers
+pub fn stats(db: &mut Connection, top: i64) -> QueryResult<ReferralTotals> {
+    use diesel::dsl::count_star;
+    use schema::referrals::dsl::*;
+
+    let total = referrals.count().get_result::<i64>(db)?;
+
+    let rewarded = referrals
+        .filter(rewarded_at.is_not_null())
+        .count()
+        .get_result::<i64>(db)?;
+
+    let top_referrers = referrals
+        .group_by(referrer_id)
+        .select((referrer_id, count_star()))
+        .order(count_star().desc())
+        .limit(top)
+        .load::<(ID, i64)>(db)?
+        .into_iter()
+        .map(|(user_id, count)| TopReferrer {
+            user_id,
+            referrals: count,
+        })
+        .collect();
+
+    Ok(ReferralTotals {
+        referrals: total,
+        rewarded,
+        top_referrers,
+    })
+}
+
+#[cfg(feature = "plugin_notifications")]
+/// lets the referrer know someone joined with their code
+fn notify_referrer(db: &mut Connection, referral: &Referral) -> QueryResult<()> {
+    use crate::notifications::{Notification, NotificationChangeset};
+
+    Notification::create(
+        db,
+        &NotificationChangeset {
+            user_id: referral.referrer_id,
+            kind: "referral".to_string(),
+            title: "Someone joined with your invite".to_string(),
+            body: "A new user signed up with your referral link.".to_string(),
+            link: None,
+        },
+    )
+    .map(|_| ())
+}
+
+/// includes the user's code and referrals in account exports, and deletes them with the account
+pub(crate) struct ReferralsAccountHook;
+
+impl crate::auth::account::AccountHook for ReferralsAccountHook {
+    fn name(&self) -> &'static str {
+        "referrals"
+    }
+
+    fn export(&self, db: &mut Connection, user_id: ID) -> QueryResult<serde_json::Value> {
+        use schema::referral_codes::dsl as referral_codes;
+
+        let code = referral_codes::referral_codes
+            .filter(referral_codes::user_id.eq(user_id))
+            .first::<ReferralCode>(db)
+            .optional()?;
+
+        Ok(serde_json::json!({
+            "code": code,
+            "referred_by": Referral::find_for_referred_user(db, user_id)?,
+            "referrals": Referral::fetch_all_for_referrer(db, user_id)?,
+        }))
+    }
+
+    fn delete(&self, db: &mut Connection, user_id: ID) -> QueryResult<()> {
+        Referral::delete_all_for_user(db, user_id)?;
+        ReferralCode::delete(db, user_id)?;
+
+        Ok(())
+    }
+}
+
+/// 8 characters, without the ones that are easily confused (0/O, 1/I/L)
+fn generate_code() -> String {
+    use rand::Rng;
+
+    const ALPHABET: &[u8] = b"23456789ABCDEFGHJKMNPQRSTUVWXYZ";
+    let mut rng = rand::thread_rng();
+
+    (0..8)
+        .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+        .collect()
+}
+
+/// the current time, in the timestamp type used by the enabled database
+fn now() -> Utc {
+    #[cfg(not(feature = "database_sqlite"))]
+    return chrono::Utc::now();
+
+    #[cfg(feature = "database_sqlite")]
+    return chrono::Utc::now().naive_utc();
+}
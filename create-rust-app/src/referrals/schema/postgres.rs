@@ -0,0 +1,20 @@
+table! {
+  referral_codes (user_id) {
+      user_id -> Int4,
+      code -> Text,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  referrals (id) {
+      id -> Int4,
+      referrer_id -> Int4,
+      referred_user_id -> Int4,
+      code -> Text,
+      rewarded_at -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
+
+allow_tables_to_appear_in_same_query!(referral_codes, referrals,);
@@ -0,0 +1,20 @@
+table! {
+  referral_codes (user_id) {
+      user_id -> Integer,
+      code -> Text,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  referrals (id) {
+      id -> Integer,
+      referrer_id -> Integer,
+      referred_user_id -> Integer,
+      code -> Text,
+      rewarded_at -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
+
+allow_tables_to_appear_in_same_query!(referral_codes, referrals,);
@@ -0,0 +1,63 @@
+use actix_http::StatusCode;
+use actix_web::web::{Bytes, Data, Path};
+use actix_web::{post, HttpRequest, HttpResponse, Responder};
+use futures_util::stream::StreamExt;
+
+use crate::rpc::{Reply, Router, RpcContext, RpcError};
+use crate::sse::{Event, Sse};
+use crate::{Database, Mailer};
+
+fn error_response(err: RpcError) -> HttpResponse {
+    HttpResponse::build(
+        StatusCode::from_u16(err.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+    )
+    .json(err)
+}
+
+/// the context of the request's procedure
+async fn context(req: &HttpRequest, db: Database, mailer: Mailer) -> RpcContext {
+    #[cfg(feature = "plugin_auth")]
+    {
+        use actix_web::FromRequest;
+
+        RpcContext {
+            db,
+            mailer,
+            auth: crate::auth::Auth::extract(req).await.ok(),
+        }
+    }
+    #[cfg(not(feature = "plugin_auth"))]
+    {
+        let _ = req;
+        RpcContext { db, mailer }
+    }
+}
+
+/// handler for POST requests at the .../{procedure} endpoint
+///
+/// the body is the procedure's input, as JSON (empty for `()` inputs)
+///
+/// returns the output of queries and mutations as JSON, and the outputs of subscriptions as
+/// server-sent events; errors are returned as `{"code": ..., "message": ...}`
+#[post("/{procedure}")]
+async fn procedure(
+    req: HttpRequest,
+    router: Data<Router>,
+    db: Data<Database>,
+    mailer: Data<Mailer>,
+    name: Path<String>,
+    body: Bytes,
+) -> HttpResponse {
+    let ctx = context(&req, db.get_ref().clone(), mailer.get_ref().clone()).await;
+
+    match router.handle(&name, ctx, &body).await {
+        Ok(Reply::Value(output)) => HttpResponse::Ok().json(output),
+        Ok(Reply::Stream(outputs)) => Sse::new(outputs.map(Event::new)).respond_to(&req),
+        Err(err) => error_response(err),
+    }
+}
+
+/// returns the endpoint the procedures of the app's [`Router`] are called at
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(procedure)
+}
@@ -0,0 +1,72 @@
+use futures_util::stream::StreamExt;
+use poem::{
+    handler,
+    http::StatusCode,
+    post,
+    web::{Data, Path},
+    IntoResponse, Request, Response, Route,
+};
+
+use crate::rpc::{Reply, Router, RpcContext, RpcError};
+use crate::sse::{Event, Sse};
+use crate::{Database, Mailer};
+
+fn error_response(err: RpcError) -> Response {
+    Response::builder()
+        .status(StatusCode::from_u16(err.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR))
+        .content_type("application/json")
+        .body(serde_json::to_string(&err).unwrap_or_default())
+}
+
+/// the context of the request's procedure
+async fn context(req: &Request, db: Database, mailer: Mailer) -> RpcContext {
+    #[cfg(feature = "plugin_auth")]
+    {
+        use poem::FromRequest;
+
+        RpcContext {
+            db,
+            mailer,
+            auth: crate::auth::Auth::from_request_without_body(req).await.ok(),
+        }
+    }
+    #[cfg(not(feature = "plugin_auth"))]
+    {
+        let _ = req;
+        RpcContext { db, mailer }
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../:procedure endpoint
+///
+/// the body is the procedure's input, as JSON (empty for `()` inputs)
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | the output of queries and mutations as JSON, the outputs of subscriptions as server-sent events
+/// | 4xx, 5xx | Json payload : {"code": ..., "message": ...}, see [`RpcError`]
+async fn procedure(
+    req: &Request,
+    router: Data<&Router>,
+    db: Data<&Database>,
+    mailer: Data<&Mailer>,
+    Path(name): Path<String>,
+    body: Vec<u8>,
+) -> Response {
+    let ctx = context(req, db.clone(), mailer.clone()).await;
+
+    match router.handle(&name, ctx, &body).await {
+        Ok(Reply::Value(output)) => Response::builder()
+            .content_type("application/json")
+            .body(output.to_string()),
+        Ok(Reply::Stream(outputs)) => Sse::new(outputs.map(Event::new)).into_response(),
+        Err(err) => error_response(err),
+    }
+}
+
+/// returns the endpoint the procedures of the app's [`Router`] are called at
+pub fn api() -> Route {
+    Route::new().at("/:procedure", post(procedure))
+}
@@ -0,0 +1,523 @@
+//! A procedure-based RPC layer (`plugin_rpc` feature), an alternative to REST endpoints: Rust
+//! functions registered in a [`Router`] are called by name from a typed TypeScript client
+//! generated from the router.
+//!
+//! ```rust,ignore
+//! let router = Router::new()
+//!     .query("todos.get", |ctx: RpcContext, id: i32| async move {
+//!         ctx.blocking(move |db| Todo::read(db, id)).await
+//!     })
+//!     .mutation("todos.create", |ctx: RpcContext, input: CreateTodo| async move {
+//!         ctx.blocking(move |db| Todo::create(db, &input)).await
+//!     })
+//!     .subscription("clock.ticks", |_ctx: RpcContext, every: u64| async move {
+//!         Ok(IntervalStream::new(interval(Duration::from_secs(every))).map(|_| Utc::now()))
+//!     })
+//!     // writes `frontend/src/rpc.generated.ts` in debug builds
+//!     .export();
+//!
+//! // actix-web
+//! app = app.app_data(Data::new(router));
+//! api_scope = api_scope.service(create_rust_app::rpc::endpoints(web::scope("/rpc")));
+//! // poem
+//! api_routes = api_routes.nest("/rpc", create_rust_app::rpc::api());
+//! app.with(AddData::new(router))
+//! ```
+//!
+//! Procedures are called with `POST /api/rpc/<name>` and their input as the JSON body, queries and
+//! mutations responding with their output as JSON, subscriptions with a stream of outputs as
+//! [server-sent events](`crate::sse`). Inputs are deserialized and then checked with
+//! [`Input::validate`]; invalid ones, and the [`RpcError`]s of the handlers, are returned as
+//! `{"code": ..., "message": ...}` with their status code.
+//!
+//! The client types the procedures' inputs and outputs with their Rust names (`CreateTodo`,
+//! `Todo`, ...), so they should be `#[tsync::tsync]` structs, defined in the frontend's
+//! `rust.d.ts`. Primitives and the standard containers are mapped to their TypeScript
+//! equivalents (`Vec<T>`: `T[]`, `Option<T>`: `T | null`, `HashMap<K, V>`: `Record<K, V>`, ...).
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use futures_util::future::{ready, BoxFuture};
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use futures_util::FutureExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{Connection, Database, Mailer};
+
+mod endpoints;
+pub use endpoints::*;
+mod typescript;
+
+/// where [`Router::export`] writes the client, relative to the frontend's directory
+pub const CLIENT_PATH: &str = "src/rpc.generated.ts";
+
+#[derive(Debug, Clone, Serialize)]
+/// the error of a procedure, sent to the client as `{"code": ..., "message": ...}`
+pub struct RpcError {
+    /// the response's status code
+    #[serde(skip)]
+    pub status: u16,
+    /// ex: `not_found`, for the client to tell errors apart
+    pub code: String,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(status: u16, code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(400, "bad_request", message)
+    }
+
+    /// the input couldn't be deserialized, or isn't [valid](`Input::validate`)
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(400, "invalid_input", message)
+    }
+
+    pub fn unauthorized() -> Self {
+        Self::new(401, "unauthorized", "Unauthorized.")
+    }
+
+    pub fn forbidden() -> Self {
+        Self::new(403, "forbidden", "Forbidden.")
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(404, "not_found", message)
+    }
+
+    /// logs `message`, which isn't sent to the client
+    pub fn internal(message: impl std::fmt::Display) -> Self {
+        println!("rpc: {message}");
+        Self::new(500, "internal", "Internal error.")
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.code, self.status, self.message)
+    }
+}
+
+impl From<diesel::result::Error> for RpcError {
+    fn from(err: diesel::result::Error) -> Self {
+        match err {
+            diesel::result::Error::NotFound => Self::not_found("Not found."),
+            err => Self::internal(format!("query failed: {err}")),
+        }
+    }
+}
+
+/// the input of a procedure, deserialized from the request's JSON body
+///
+/// implement it for the input types, overriding [`validate`](`Input::validate`) to reject invalid
+/// values before the handler runs:
+///
+/// ```rust,ignore
+/// impl Input for CreateTodo {
+///     fn validate(&self) -> Result<(), String> {
+///         if self.text.trim().is_empty() {
+///             return Err("The text can't be empty.".to_string());
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Input: DeserializeOwned + Send + 'static {
+    /// `Err(message)` rejects the input, the message being sent to the client
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_input {
+    ($($ty:ty),*) => {
+        $(impl Input for $ty {})*
+    };
+}
+
+impl_input!(
+    (),
+    bool,
+    char,
+    String,
+    i8,
+    i16,
+    i32,
+    i64,
+    u8,
+    u16,
+    u32,
+    u64,
+    f32,
+    f64,
+    Value
+);
+
+impl<T: Input> Input for Vec<T> {
+    fn validate(&self) -> Result<(), String> {
+        self.iter().try_for_each(Input::validate)
+    }
+}
+
+impl<T: Input> Input for Option<T> {
+    fn validate(&self) -> Result<(), String> {
+        self.as_ref().map_or(Ok(()), Input::validate)
+    }
+}
+
+impl<A: Input, B: Input> Input for (A, B) {
+    fn validate(&self) -> Result<(), String> {
+        self.0.validate()?;
+        self.1.validate()
+    }
+}
+
+#[derive(Clone)]
+/// what the handlers get besides their input
+pub struct RpcContext {
+    pub db: Database,
+    pub mailer: Mailer,
+    /// `None` when the request has no valid access token
+    #[cfg(feature = "plugin_auth")]
+    pub auth: Option<crate::auth::Auth>,
+}
+
+impl RpcContext {
+    /// the [`Auth`](`crate::auth::Auth`) of the request, or [`RpcError::unauthorized`]
+    #[cfg(feature = "plugin_auth")]
+    pub fn auth(&self) -> Result<&crate::auth::Auth, RpcError> {
+        self.auth.as_ref().ok_or_else(RpcError::unauthorized)
+    }
+
+    /// runs `query` with a connection of the database on tokio's blocking threads, so it doesn't
+    /// hold up the other requests
+    pub async fn blocking<T, F>(&self, query: F) -> Result<T, RpcError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> diesel::QueryResult<T> + Send + 'static,
+    {
+        let pool = self.db.pool.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut db = pool.get().map_err(|_| {
+                RpcError::new(503, "unavailable", "No database connection available.")
+            })?;
+
+            query(&mut db).map_err(RpcError::from)
+        })
+        .await
+        .map_err(RpcError::internal)?
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// reads data, called with `rpc.<name>(input)`
+    Query,
+    /// changes data, called with `rpc.<name>(input)`
+    Mutation,
+    /// a stream of outputs, see [`Router::subscription`]
+    Subscription,
+}
+
+type CallHandler =
+    Arc<dyn Fn(RpcContext, Value) -> BoxFuture<'static, Result<Value, RpcError>> + Send + Sync>;
+type SubscribeHandler = Arc<
+    dyn Fn(RpcContext, Value) -> BoxFuture<'static, Result<BoxStream<'static, Value>, RpcError>>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone)]
+enum Handler {
+    Call(CallHandler),
+    Subscribe(SubscribeHandler),
+}
+
+#[derive(Clone)]
+struct Procedure {
+    kind: Kind,
+    /// the Rust types of the input and output, see [`std::any::type_name`]
+    input: &'static str,
+    output: &'static str,
+    handler: Handler,
+}
+
+/// the reply of a procedure, see [`Router::handle`]
+pub(crate) enum Reply {
+    Value(Value),
+    Stream(BoxStream<'static, Value>),
+}
+
+#[derive(Clone, Default)]
+/// the procedures, by name
+///
+/// names are dot-separated identifiers (ex: `todos.list`), the client grouping the procedures by
+/// their prefixes (`rpc.todos.list(...)`)
+pub struct Router {
+    procedures: BTreeMap<String, Procedure>,
+}
+
+/// deserializes and validates a procedure's input
+fn parse_input<I: Input>(input: Value) -> Result<I, RpcError> {
+    let input = serde_json::from_value::<I>(input)
+        .map_err(|err| RpcError::invalid_input(format!("Invalid input: {err}")))?;
+    input.validate().map_err(RpcError::invalid_input)?;
+
+    Ok(input)
+}
+
+fn to_value<O: Serialize>(output: O) -> Result<Value, RpcError> {
+    serde_json::to_value(output)
+        .map_err(|err| RpcError::internal(format!("could not serialize an output: {err}")))
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// registers a procedure reading data
+    ///
+    /// panics if `name` isn't a dot-separated list of identifiers, or is already taken
+    pub fn query<I, O, F, Fut>(self, name: &str, handler: F) -> Self
+    where
+        I: Input,
+        O: Serialize + 'static,
+        F: Fn(RpcContext, I) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<O, RpcError>> + Send + 'static,
+    {
+        self.call(Kind::Query, name, handler)
+    }
+
+    /// registers a procedure changing data
+    ///
+    /// panics if `name` isn't a dot-separated list of identifiers, or is already taken
+    pub fn mutation<I, O, F, Fut>(self, name: &str, handler: F) -> Self
+    where
+        I: Input,
+        O: Serialize + 'static,
+        F: Fn(RpcContext, I) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<O, RpcError>> + Send + 'static,
+    {
+        self.call(Kind::Mutation, name, handler)
+    }
+
+    /// registers a procedure streaming outputs until its stream ends or the client unsubscribes
+    ///
+    /// panics if `name` isn't a dot-separated list of identifiers, or is already taken
+    pub fn subscription<I, O, S, F, Fut>(self, name: &str, handler: F) -> Self
+    where
+        I: Input,
+        O: Serialize + 'static,
+        S: Stream<Item = O> + Send + 'static,
+        F: Fn(RpcContext, I) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<S, RpcError>> + Send + 'static,
+    {
+        let handler: SubscribeHandler = Arc::new(move |ctx, input| {
+            let input = match parse_input::<I>(input) {
+                Ok(input) => input,
+                Err(err) => return ready(Err(err)).boxed(),
+            };
+
+            handler(ctx, input)
+                .map(|stream| {
+                    stream.map(|stream| {
+                        stream
+                            .filter_map(|output| ready(to_value(output).ok()))
+                            .boxed()
+                    })
+                })
+                .boxed()
+        });
+
+        self.register(
+            name,
+            Procedure {
+                kind: Kind::Subscription,
+                input: std::any::type_name::<I>(),
+                output: std::any::type_name::<O>(),
+                handler: Handler::Subscribe(handler),
+            },
+        )
+    }
+
+    fn call<I, O, F, Fut>(self, kind: Kind, name: &str, handler: F) -> Self
+    where
+        I: Input,
+        O: Serialize + 'static,
+        F: Fn(RpcContext, I) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<O, RpcError>> + Send + 'static,
+    {
+        let handler: CallHandler = Arc::new(move |ctx, input| {
+            let input = match parse_input::<I>(input) {
+                Ok(input) => input,
+                Err(err) => return ready(Err(err)).boxed(),
+            };
+
+            handler(ctx, input)
+                .map(|output| output.and_then(to_value))
+                .boxed()
+        });
+
+        self.register(
+            name,
+            Procedure {
+                kind,
+                input: std::any::type_name::<I>(),
+                output: std::any::type_name::<O>(),
+                handler: Handler::Call(handler),
+            },
+        )
+    }
+
+    /// adds the procedures of `router`, their names prefixed with `prefix.`
+    pub fn merge(mut self, prefix: &str, router: Router) -> Self {
+        for (name, procedure) in router.procedures {
+            self = self.register(&format!("{prefix}.{name}"), procedure);
+        }
+
+        self
+    }
+
+    fn register(mut self, name: &str, procedure: Procedure) -> Self {
+        let is_identifier = |segment: &str| {
+            segment
+                .chars()
+                .next()
+                .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        if !name.split('.').all(is_identifier) {
+            panic!("rpc: `{name}` isn't a valid procedure name (ex: `todos.list`)");
+        }
+
+        // `todos` and `todos.list` would both be `rpc.todos` in the client
+        let clashing = self.procedures.keys().find(|registered| {
+            *registered == name
+                || registered.starts_with(&format!("{name}."))
+                || name.starts_with(&format!("{registered}."))
+        });
+        if let Some(registered) = clashing {
+            panic!("rpc: the procedure `{name}` clashes with `{registered}`");
+        }
+
+        self.procedures.insert(name.to_string(), procedure);
+        self
+    }
+
+    /// the kind of the procedure named `name`, if there's one
+    pub fn kind(&self, name: &str) -> Option<Kind> {
+        self.procedures.get(name).map(|procedure| procedure.kind)
+    }
+
+    /// calls the query or mutation named `name`
+    pub async fn call_procedure(
+        &self,
+        name: &str,
+        ctx: RpcContext,
+        input: Value,
+    ) -> Result<Value, RpcError> {
+        match self
+            .procedures
+            .get(name)
+            .map(|procedure| &procedure.handler)
+        {
+            Some(Handler::Call(handler)) => handler(ctx, input).await,
+            Some(Handler::Subscribe(_)) => Err(RpcError::bad_request(format!(
+                "`{name}` is a subscription."
+            ))),
+            None => Err(RpcError::not_found(format!("No procedure named `{name}`."))),
+        }
+    }
+
+    /// subscribes to the subscription named `name`
+    pub async fn subscribe(
+        &self,
+        name: &str,
+        ctx: RpcContext,
+        input: Value,
+    ) -> Result<BoxStream<'static, Value>, RpcError> {
+        match self
+            .procedures
+            .get(name)
+            .map(|procedure| &procedure.handler)
+        {
+            Some(Handler::Subscribe(handler)) => handler(ctx, input).await,
+            Some(Handler::Call(_)) => Err(RpcError::bad_request(format!(
+                "`{name}` isn't a subscription."
+            ))),
+            None => Err(RpcError::not_found(format!("No procedure named `{name}`."))),
+        }
+    }
+
+    /// runs the procedure named `name` with `body` (JSON, empty for `()` inputs) as its input
+    pub(crate) async fn handle(
+        &self,
+        name: &str,
+        ctx: RpcContext,
+        body: &[u8],
+    ) -> Result<Reply, RpcError> {
+        let input = if body.iter().all(u8::is_ascii_whitespace) {
+            Value::Null
+        } else {
+            serde_json::from_slice(body)
+                .map_err(|_| RpcError::invalid_input("The input isn't valid JSON."))?
+        };
+
+        match self.kind(name) {
+            Some(Kind::Subscription) => self.subscribe(name, ctx, input).await.map(Reply::Stream),
+            _ => self
+                .call_procedure(name, ctx, input)
+                .await
+                .map(Reply::Value),
+        }
+    }
+
+    /// the TypeScript client of the procedures, see [`export`](`Router::export`)
+    pub fn typescript(&self) -> String {
+        typescript::client(
+            self.procedures
+                .iter()
+                .map(|(name, procedure)| typescript::Signature {
+                    name,
+                    kind: procedure.kind,
+                    input: procedure.input,
+                    output: procedure.output,
+                }),
+        )
+    }
+
+    /// writes the TypeScript client to the frontend's [`CLIENT_PATH`] in debug builds (when it
+    /// changed, so the frontend's dev server doesn't reload for nothing)
+    pub fn export(self) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            let path = format!(
+                "{frontend_dir}/{CLIENT_PATH}",
+                frontend_dir = *crate::util::workspace_utils::FRONTEND_DIR
+            );
+            let client = self.typescript();
+
+            if std::fs::read_to_string(&path).ok().as_deref() != Some(client.as_str()) {
+                if let Err(err) = std::fs::write(&path, client) {
+                    println!("rpc: could not write the client to {path}: {err}");
+                }
+            }
+        }
+
+        self
+    }
+}
@@ -0,0 +1,215 @@
+//! The TypeScript client of a [`Router`](`super::Router`), its types derived from the Rust type
+//! names of the procedures' inputs and outputs (see [`std::any::type_name`]).
+use std::collections::BTreeMap;
+
+use super::Kind;
+
+pub(super) struct Signature<'a> {
+    pub name: &'a str,
+    pub kind: Kind,
+    pub input: &'static str,
+    pub output: &'static str,
+}
+
+enum Node<'a> {
+    Procedure(Signature<'a>),
+    Group(BTreeMap<&'a str, Node<'a>>),
+}
+
+/// the client of the procedures, grouped by the prefixes of their names
+pub(super) fn client<'a>(signatures: impl Iterator<Item = Signature<'a>>) -> String {
+    let mut root = BTreeMap::new();
+    for signature in signatures {
+        let mut segments = signature.name.split('.').collect::<Vec<_>>();
+        let last = segments.pop().unwrap_or_default();
+
+        let mut group = &mut root;
+        for segment in segments {
+            // names are checked for clashes when they're registered
+            group = match group
+                .entry(segment)
+                .or_insert_with(|| Node::Group(BTreeMap::new()))
+            {
+                Node::Group(group) => group,
+                Node::Procedure(_) => unreachable!("clashing procedure names"),
+            };
+        }
+        group.insert(last, Node::Procedure(signature));
+    }
+
+    let mut client = String::from(
+        "// generated by the backend's RPC router (`Router::export`), don't edit it\n\
+         import { call, subscribe, RpcOptions, SubscriptionHandlers } from './rpc'\n\
+         \n\
+         export const createRpcClient = (options: RpcOptions = {}) => ({\n",
+    );
+    render_group(&root, 1, &mut client);
+    client.push_str(
+        "})\n\
+         \n\
+         export type RpcClient = ReturnType<typeof createRpcClient>\n",
+    );
+
+    client
+}
+
+fn render_group(group: &BTreeMap<&str, Node>, depth: usize, client: &mut String) {
+    let indent = "  ".repeat(depth);
+
+    for (key, node) in group {
+        match node {
+            Node::Group(group) => {
+                client.push_str(&format!("{indent}{key}: {{\n"));
+                render_group(group, depth + 1, client);
+                client.push_str(&format!("{indent}}},\n"));
+            }
+            Node::Procedure(signature) => {
+                let output = ts_type(signature.output);
+                let (params, input) = match ts_type(signature.input).as_str() {
+                    "void" => (vec![], "null".to_string()),
+                    input => (vec![format!("input: {input}")], "input".to_string()),
+                };
+
+                let (comment, params, call) = match signature.kind {
+                    Kind::Query | Kind::Mutation => (
+                        format!("{:?}", signature.kind).to_lowercase(),
+                        params,
+                        format!("call<{output}>(options, '{}', {input})", signature.name),
+                    ),
+                    Kind::Subscription => (
+                        "subscription, returns a function unsubscribing".to_string(),
+                        [
+                            params,
+                            vec![format!("handlers: SubscriptionHandlers<{output}>")],
+                        ]
+                        .concat(),
+                        format!(
+                            "subscribe<{output}>(options, '{}', {input}, handlers)",
+                            signature.name
+                        ),
+                    ),
+                };
+
+                client.push_str(&format!("{indent}/** {comment} */\n"));
+                client.push_str(&format!(
+                    "{indent}{key}: ({}) => {call},\n",
+                    params.join(", ")
+                ));
+            }
+        }
+    }
+}
+
+/// the TypeScript type of a Rust type name, ex: `alloc::vec::Vec<app::models::todos::Todo>` is
+/// `Todo[]`
+pub(super) fn ts_type(type_name: &str) -> String {
+    let mut parser = Parser { rest: type_name };
+    parser.ty()
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// types separated by commas, until `end`
+    fn list(&mut self, end: &str) -> Vec<String> {
+        let mut types = vec![];
+        while !self.eat(end) {
+            let before = self.rest.len();
+            types.push(self.ty());
+            // stops at unexpected tokens instead of looping on them
+            if !self.eat(",") && self.rest.len() == before {
+                break;
+            }
+        }
+
+        types
+    }
+
+    fn ty(&mut self) -> String {
+        if self.eat("&") {
+            self.eat("mut ");
+            return self.ty();
+        }
+
+        if self.eat("(") {
+            let types = self.list(")");
+            return match types.len() {
+                0 => "void".to_string(),
+                1 => types[0].clone(),
+                _ => format!("[{}]", types.join(", ")),
+            };
+        }
+
+        if self.eat("[") {
+            let item = self.ty();
+            // the length of arrays
+            if self.eat(";") {
+                let end = self.rest.find(']').unwrap_or(self.rest.len());
+                self.rest = &self.rest[end..];
+            }
+            self.eat("]");
+            return array_of(&item);
+        }
+
+        self.skip_whitespace();
+        let end = self
+            .rest
+            .find(|c: char| matches!(c, '<' | '>' | ',' | ';' | ')' | ']') || c.is_whitespace())
+            .unwrap_or(self.rest.len());
+        let path = &self.rest[..end];
+        self.rest = &self.rest[end..];
+
+        let arguments = if self.eat("<") {
+            self.list(">")
+        } else {
+            vec![]
+        };
+        let argument = |index: usize| {
+            arguments
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_string())
+        };
+
+        let name = path.rsplit("::").next().unwrap_or(path);
+        match name {
+            "bool" => "boolean".to_string(),
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+            | "u128" | "usize" | "f32" | "f64" | "BigDecimal" => "number".to_string(),
+            "str" | "String" | "char" | "Uuid" | "NaiveDate" | "NaiveTime" | "NaiveDateTime"
+            | "DateTime" | "PathBuf" => "string".to_string(),
+            "Value" => "any".to_string(),
+            "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => array_of(&argument(0)),
+            "Option" => format!("{} | null", argument(0)),
+            "HashMap" | "BTreeMap" => format!("Record<{}, {}>", argument(0), argument(1)),
+            "Box" | "Arc" | "Rc" | "Cow" => argument(0),
+            _ if arguments.is_empty() => name.to_string(),
+            _ => format!("{name}<{}>", arguments.join(", ")),
+        }
+    }
+}
+
+fn array_of(item: &str) -> String {
+    if item.contains(' ') {
+        format!("({item})[]")
+    } else {
+        format!("{item}[]")
+    }
+}
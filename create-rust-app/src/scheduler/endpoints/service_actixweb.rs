@@ -0,0 +1,86 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Query};
+use actix_web::{get, web, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::scheduler::{ScheduledTask, TaskRun, ADMIN_PERMISSION};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../runs endpoint
+pub struct RunsQuery {
+    /// only list the runs of the task with this name
+    task: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the .../ endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// returns the scheduled tasks, by name, with their last run
+#[get("")]
+async fn list(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let result = web::block(move || ScheduledTask::overview(&mut db.get_connection())).await?;
+
+    match result {
+        Ok(tasks) => Ok(HttpResponse::Ok().json(tasks)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the scheduled tasks.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../runs endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// returns the runs, most recent first (50 per page by default)
+#[get("/runs")]
+async fn runs(
+    db: Data<Database>,
+    auth: Auth,
+    Query(query): Query<RunsQuery>,
+) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    let result = web::block(move || {
+        TaskRun::list(
+            &mut db.get_connection(),
+            query.task.as_deref(),
+            page,
+            page_size,
+        )
+    })
+    .await?;
+
+    match result {
+        Ok(runs) => Ok(HttpResponse::Ok().json(runs)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the runs.",
+        )),
+    }
+}
+
+/// returns the endpoints of the scheduler's API
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(list).service(runs)
+}
@@ -0,0 +1,94 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    web::{Data, Json, Query},
+    Error, IntoResponse, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::scheduler::{ScheduledTask, TaskRun, ADMIN_PERMISSION};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../runs endpoint
+pub struct RunsQuery {
+    /// only list the runs of the task with this name
+    task: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for GET requests at the .../ endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`ScheduledTaskOverview`](`crate::scheduler::ScheduledTaskOverview`)s, by name
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch the scheduled tasks."}
+async fn list(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    ScheduledTask::overview(&mut db.get_connection())
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the scheduled tasks.",
+            )
+        })
+}
+
+#[handler]
+/// handler for GET requests at the .../runs endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`TaskRun`]s, most recent first (50 per page by default)
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch the runs."}
+async fn runs(
+    db: Data<&Database>,
+    auth: Auth,
+    Query(query): Query<RunsQuery>,
+) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    TaskRun::list(
+        &mut db.get_connection(),
+        query.task.as_deref(),
+        page,
+        page_size,
+    )
+    .map(Json)
+    .map_err(|_| {
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the runs.",
+        )
+    })
+}
+
+/// returns the endpoints of the scheduler's API
+pub fn api() -> Route {
+    Route::new().at("/", get(list)).at("/runs", get(runs))
+}
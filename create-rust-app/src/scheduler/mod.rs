@@ -0,0 +1,630 @@
+//! Scheduled tasks (`plugin_scheduler` feature): functions run on a cron schedule, inside the
+//! server or in a dedicated worker binary.
+//!
+//! A [`Scheduler`] runs its registered [`Job`]s when their cron expression says so. Several
+//! schedulers can run at the same time (ex: one in every instance of the app, and one in a worker):
+//! their state is kept in the `scheduled_tasks` table, and every run is claimed there first, so it
+//! only happens once. A job never overlaps itself: a run due while the previous one is still going
+//! is skipped (see [`Job::timeout`]). Every run is recorded in the `scheduled_task_runs` table.
+//!
+//! ```rust,ignore
+//! Scheduler::new(app_data.database.clone())
+//!     // every day at 3:00 (UTC)
+//!     .add(Job::new("prune-audit-log", "0 3 * * *", |db| {
+//!         AuditEntry::delete_before(db, one_month_ago()).map(|_| ()).map_err(|err| err.to_string())
+//!     }).missed_runs(MissedRuns::RunOnce))?
+//!     .spawn();
+//! ```
+//!
+//! When the schedulers are down at the time of a run (ex: during a deploy), what happens to it is
+//! the job's [`MissedRuns`] policy.
+//!
+//! The API (mount it like the other services) requires the [`ADMIN_PERMISSION`], and backs the
+//! admin portal's "Schedules" page:
+//!
+//! - `GET /` lists the scheduled tasks, with their next and last runs
+//! - `GET /runs` lists the runs, most recent first, `?task=` only lists a task's
+use crate::diesel::*;
+use crate::{Connection, Database};
+use cron::Schedule as CronSchedule;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// permission required to use the API
+pub const ADMIN_PERMISSION: &str = "admin:scheduler";
+
+/// the most missed runs made up for at once, see [`MissedRuns::CatchUp`]
+pub const MAX_CATCH_UP_RUNS: usize = 100;
+
+/// how late a run can start and still be on time, see [`MissedRuns::Skip`]
+const MISFIRE_GRACE_SECONDS: i64 = 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// how a [`TaskRun`] went
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// not run, because the previous run was still going or the run was missed
+    Skipped,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// what happens to the runs which should have happened while no scheduler was running
+pub enum MissedRuns {
+    /// they're skipped (and recorded as such), the job runs at its next time (the default)
+    #[default]
+    Skip,
+    /// the job runs once to make up for all of them
+    RunOnce,
+    /// the job runs once for each of them (up to [`MAX_CATCH_UP_RUNS`]), one after the other
+    CatchUp,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=scheduled_tasks)]
+/// a [`Job`]'s schedule, shared by the schedulers
+pub struct ScheduledTask {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    /// the [`Job`]'s name
+    pub name: String,
+    /// the [`Job`]'s cron expression
+    pub cron: String,
+    /// the next time the job runs, or the first of its missed runs
+    pub next_run_at: Utc,
+    /// set while the job runs, so it doesn't overlap itself; a crashed run holds it until then
+    pub locked_until: Option<Utc>,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=scheduled_tasks)]
+pub struct ScheduledTaskChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub name: String,
+    pub cron: String,
+    pub next_run_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=scheduled_task_runs)]
+/// a run of a [`ScheduledTask`]
+pub struct TaskRun {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub task_name: String,
+    /// the time the cron expression scheduled it at
+    pub scheduled_for: Utc,
+    /// one of [`RunStatus`]
+    pub status: String,
+    /// why it failed, or was skipped
+    pub error: Option<String>,
+    pub started_at: Utc,
+    pub finished_at: Option<Utc>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=scheduled_task_runs)]
+pub struct TaskRunChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub task_name: String,
+    pub scheduled_for: Utc,
+    pub status: String,
+    pub error: Option<String>,
+    pub finished_at: Option<Utc>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// a [`ScheduledTask`] and its last run, see [`ScheduledTask::overview`]
+pub struct ScheduledTaskOverview {
+    pub task: ScheduledTask,
+    pub last_run: Option<TaskRun>,
+}
+
+impl ScheduledTask {
+    /// Read from [`db`](`Connection`), querying for the entry in the `scheduled_tasks` named
+    /// [`task_name`](`String`)
+    pub fn read(db: &mut Connection, task_name: &str) -> QueryResult<Self> {
+        use schema::scheduled_tasks::dsl::*;
+
+        scheduled_tasks
+            .filter(name.eq(task_name))
+            .first::<ScheduledTask>(db)
+    }
+
+    /// every scheduled task, by name
+    pub fn list(db: &mut Connection) -> QueryResult<Vec<Self>> {
+        use schema::scheduled_tasks::dsl::*;
+
+        scheduled_tasks.order(name.asc()).load::<ScheduledTask>(db)
+    }
+
+    /// every scheduled task, by name, with its last run
+    pub fn overview(db: &mut Connection) -> QueryResult<Vec<ScheduledTaskOverview>> {
+        Self::list(db)?
+            .into_iter()
+            .map(|task| {
+                let last_run = TaskRun::list(db, Some(&task.name), 0, 1)?.pop();
+
+                Ok(ScheduledTaskOverview { task, last_run })
+            })
+            .collect()
+    }
+
+    /// the task of `job`: created on its first registration, rescheduled when its cron expression
+    /// changed
+    fn register(db: &mut Connection, job: &Job) -> QueryResult<Self> {
+        use schema::scheduled_tasks::dsl::*;
+
+        match Self::read(db, &job.name) {
+            Ok(task) if task.cron == job.cron => Ok(task),
+            Ok(task) => diesel::update(scheduled_tasks.filter(id.eq(task.id)))
+                .set((
+                    cron.eq(&job.cron),
+                    next_run_at.eq(from_utc(job.next_after(chrono::Utc::now()))),
+                ))
+                .get_result::<ScheduledTask>(db),
+            Err(diesel::result::Error::NotFound) => insert_into(scheduled_tasks)
+                .values(&ScheduledTaskChangeset {
+                    name: job.name.clone(),
+                    cron: job.cron.clone(),
+                    next_run_at: from_utc(job.next_after(chrono::Utc::now())),
+                })
+                .get_result::<ScheduledTask>(db),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// moves the task's next run from [`due_at`](`ScheduledTask::next_run_at`) to `next_at`, and
+    /// locks it for `timeout`; returns `false` if another scheduler got to it first, or if the
+    /// previous run is still going
+    fn claim(&self, db: &mut Connection, next_at: Utc, timeout: Duration) -> QueryResult<bool> {
+        use schema::scheduled_tasks::dsl::*;
+
+        let current_time = now();
+        let timeout =
+            chrono::Duration::from_std(timeout).unwrap_or_else(|_| chrono::Duration::days(1));
+        let claimed = diesel::update(
+            scheduled_tasks
+                .filter(id.eq(self.id))
+                .filter(next_run_at.eq(self.next_run_at))
+                .filter(locked_until.is_null().or(locked_until.le(current_time))),
+        )
+        .set((
+            next_run_at.eq(next_at),
+            locked_until.eq(Some(current_time + timeout)),
+        ))
+        .execute(db)?;
+
+        Ok(claimed == 1)
+    }
+
+    /// moves the task's next run to `next_at` without running it, when the previous run is still
+    /// going; returns `false` if another scheduler got to it first
+    fn skip(&self, db: &mut Connection, next_at: Utc) -> QueryResult<bool> {
+        use schema::scheduled_tasks::dsl::*;
+
+        let skipped = diesel::update(
+            scheduled_tasks
+                .filter(id.eq(self.id))
+                .filter(next_run_at.eq(self.next_run_at)),
+        )
+        .set(next_run_at.eq(next_at))
+        .execute(db)?;
+
+        Ok(skipped == 1)
+    }
+
+    fn unlock(&self, db: &mut Connection) -> QueryResult<usize> {
+        use schema::scheduled_tasks::dsl::*;
+
+        diesel::update(scheduled_tasks.filter(id.eq(self.id)))
+            .set(locked_until.eq(None::<Utc>))
+            .execute(db)
+    }
+}
+
+impl TaskRun {
+    /// the runs, most recent first, optionally only those of the task named `item_task_name`
+    pub fn list(
+        db: &mut Connection,
+        item_task_name: Option<&str>,
+        page: i64,
+        page_size: i64,
+    ) -> QueryResult<Vec<Self>> {
+        use schema::scheduled_task_runs::dsl::*;
+
+        let mut query = scheduled_task_runs.into_boxed();
+        if let Some(item_task_name) = item_task_name {
+            query = query.filter(task_name.eq(item_task_name.to_string()));
+        }
+
+        query
+            .order((started_at.desc(), id.desc()))
+            .limit(page_size)
+            .offset(page * page_size)
+            .load::<TaskRun>(db)
+    }
+
+    /// deletes the runs started more than `age` ago, returns how many there were
+    ///
+    /// runs aren't pruned automatically, call this periodically (ex: from a scheduled job)
+    pub fn delete_older_than(db: &mut Connection, age: Duration) -> QueryResult<usize> {
+        use schema::scheduled_task_runs::dsl::*;
+
+        let age = chrono::Duration::from_std(age).unwrap_or_else(|_| chrono::Duration::days(36500));
+        diesel::delete(scheduled_task_runs.filter(started_at.lt(now() - age))).execute(db)
+    }
+
+    fn record(db: &mut Connection, item: &TaskRunChangeset) -> QueryResult<Self> {
+        use schema::scheduled_task_runs::dsl::*;
+
+        insert_into(scheduled_task_runs)
+            .values(item)
+            .get_result::<TaskRun>(db)
+    }
+
+    fn finish(&self, db: &mut Connection, result: Result<(), String>) -> QueryResult<usize> {
+        use schema::scheduled_task_runs::dsl::*;
+
+        let item_status = match result {
+            Ok(()) => RunStatus::Succeeded,
+            Err(_) => RunStatus::Failed,
+        };
+
+        diesel::update(scheduled_task_runs.filter(id.eq(self.id)))
+            .set((
+                status.eq(item_status.as_str()),
+                error.eq(result.err()),
+                finished_at.eq(Some(now())),
+            ))
+            .execute(db)
+    }
+}
+
+type JobFn = dyn Fn(&mut Connection) -> Result<(), String> + Send + Sync;
+
+#[derive(Clone)]
+/// a function run on a cron schedule, by a [`Scheduler`]
+pub struct Job {
+    pub name: String,
+    /// `minute hour day-of-month month day-of-week` (ex: `*/15 * * * *`), in UTC; a leading
+    /// seconds field is allowed
+    pub cron: String,
+    pub missed_runs: MissedRuns,
+    /// the longest a run is expected to take (default: 1 hour): the job doesn't run again until
+    /// the previous run is done, or until this long after it started (in case it crashed)
+    pub timeout: Duration,
+    schedule: CronSchedule,
+    run: Arc<JobFn>,
+}
+
+impl std::fmt::Debug for Job {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Job")
+            .field("name", &self.name)
+            .field("cron", &self.cron)
+            .field("missed_runs", &self.missed_runs)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl Job {
+    /// the job `name` running `run` on the `cron` schedule
+    ///
+    /// panics if `cron` isn't a valid cron expression, see [`Job::try_new`]
+    pub fn new(
+        name: impl Into<String>,
+        cron: &str,
+        run: impl Fn(&mut Connection) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        Self::try_new(name, cron, run).unwrap()
+    }
+
+    /// the job `name` running `run` on the `cron` schedule, fails if `cron` isn't a valid cron
+    /// expression
+    pub fn try_new(
+        name: impl Into<String>,
+        cron: &str,
+        run: impl Fn(&mut Connection) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Result<Self, String> {
+        let cron = cron.split_whitespace().collect::<Vec<_>>().join(" ");
+        // the cron crate wants the seconds first
+        let expression = match cron.split(' ').count() {
+            5 => format!("0 {cron}"),
+            _ => cron.clone(),
+        };
+        let schedule = CronSchedule::from_str(&expression)
+            .map_err(|err| format!("Invalid cron expression '{cron}': {err}"))?;
+        if schedule.upcoming(chrono::Utc).next().is_none() {
+            return Err(format!("The cron expression '{cron}' never runs."));
+        }
+
+        Ok(Self {
+            name: name.into(),
+            cron,
+            missed_runs: MissedRuns::default(),
+            timeout: Duration::from_secs(60 * 60),
+            schedule,
+            run: Arc::new(run),
+        })
+    }
+
+    /// what happens to the runs missed while no scheduler was running
+    pub fn missed_runs(mut self, missed_runs: MissedRuns) -> Self {
+        self.missed_runs = missed_runs;
+        self
+    }
+
+    /// the longest a run is expected to take, see [`Job::timeout`](`Job#structfield.timeout`)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// the first time the job runs after `after`
+    pub fn next_after(
+        &self,
+        after: chrono::DateTime<chrono::Utc>,
+    ) -> chrono::DateTime<chrono::Utc> {
+        self.schedule
+            .after(&after)
+            .next()
+            .unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC)
+    }
+
+    /// runs the job for the time `scheduled_for`, and records the run
+    fn execute(&self, db: &mut Connection, scheduled_for: Utc) -> QueryResult<()> {
+        let run = TaskRun::record(
+            db,
+            &TaskRunChangeset {
+                task_name: self.name.clone(),
+                scheduled_for,
+                status: RunStatus::Running.as_str().to_string(),
+                error: None,
+                finished_at: None,
+            },
+        )?;
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| (self.run)(db)))
+            .unwrap_or_else(|_| Err("The job panicked.".to_string()));
+        if let Err(err) = &result {
+            println!("Scheduled task '{}' failed: {err}", self.name);
+        }
+
+        run.finish(db, result).map(|_| ())
+    }
+
+    /// records a run which didn't happen
+    fn record_skipped(
+        &self,
+        db: &mut Connection,
+        scheduled_for: Utc,
+        reason: &str,
+    ) -> QueryResult<()> {
+        TaskRun::record(
+            db,
+            &TaskRunChangeset {
+                task_name: self.name.clone(),
+                scheduled_for,
+                status: RunStatus::Skipped.as_str().to_string(),
+                error: Some(reason.to_string()),
+                finished_at: Some(now()),
+            },
+        )
+        .map(|_| ())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// runs [`Job`]s on their schedules
+pub struct Scheduler {
+    database: Database,
+    jobs: Vec<Job>,
+    /// how often the scheduler checks whether jobs are due (default: 10 seconds)
+    pub poll_interval: Duration,
+}
+
+impl Scheduler {
+    pub fn new(database: Database) -> Self {
+        Self {
+            database,
+            jobs: vec![],
+            poll_interval: Duration::from_secs(10),
+        }
+    }
+
+    /// registers `job`, fails if another job has the same name or its task can't be saved
+    pub fn add(mut self, job: Job) -> Result<Self, String> {
+        if self.jobs.iter().any(|other| other.name == job.name) {
+            return Err(format!(
+                "There's already a scheduled job named '{}'.",
+                job.name
+            ));
+        }
+
+        ScheduledTask::register(&mut self.database.get_connection(), &job)
+            .map_err(|err| format!("Could not register the scheduled job '{}': {err}", job.name))?;
+        self.jobs.push(job);
+
+        Ok(self)
+    }
+
+    /// the registered jobs
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    /// starts the jobs which are due, each in its own thread; returns how many were started
+    pub fn tick(&self) -> QueryResult<usize> {
+        let mut db = self.database.get_connection();
+        let current_time = chrono::Utc::now();
+        let mut started = 0;
+
+        for job in &self.jobs {
+            let task = ScheduledTask::read(&mut db, &job.name)?;
+            let due_at = to_utc(task.next_run_at);
+            if due_at > current_time {
+                continue;
+            }
+
+            // the run due, and the ones missed after it
+            let mut due = vec![due_at];
+            for at in job.schedule.after(&due_at) {
+                if at > current_time || due.len() >= MAX_CATCH_UP_RUNS {
+                    break;
+                }
+                due.push(at);
+            }
+            let next_at = from_utc(job.next_after(current_time));
+
+            if !task.claim(&mut db, next_at, job.timeout)? {
+                let is_running = task.locked_until.map_or(false, |until| until > now());
+                if is_running && task.skip(&mut db, next_at)? {
+                    for at in &due {
+                        job.record_skipped(
+                            &mut db,
+                            from_utc(*at),
+                            "The previous run was still going.",
+                        )?;
+                    }
+                }
+                continue;
+            }
+
+            let on_time = |at: &chrono::DateTime<chrono::Utc>| {
+                current_time - *at <= chrono::Duration::seconds(MISFIRE_GRACE_SECONDS)
+            };
+            let (runs, skipped) = match job.missed_runs {
+                MissedRuns::CatchUp => (due, vec![]),
+                MissedRuns::RunOnce => (due.split_off(due.len() - 1), due),
+                // only the last run can be on time
+                MissedRuns::Skip if on_time(&due[due.len() - 1]) => {
+                    (due.split_off(due.len() - 1), due)
+                }
+                MissedRuns::Skip => (vec![], due),
+            };
+            for at in skipped {
+                job.record_skipped(
+                    &mut db,
+                    from_utc(at),
+                    "Missed while no scheduler was running.",
+                )?;
+            }
+            if runs.is_empty() {
+                task.unlock(&mut db)?;
+                continue;
+            }
+
+            let job = job.clone();
+            let database = self.database.clone();
+            started += 1;
+            std::thread::spawn(move || {
+                let mut db = database.get_connection();
+                for at in runs {
+                    if let Err(err) = job.execute(&mut db, from_utc(at)) {
+                        println!(
+                            "Could not record the run of scheduled task '{}': {err}",
+                            job.name
+                        );
+                    }
+                }
+                if let Err(err) = task.unlock(&mut db) {
+                    println!("Could not unlock scheduled task '{}': {err}", job.name);
+                }
+            });
+        }
+
+        Ok(started)
+    }
+
+    /// checks for due jobs every [`poll_interval`](`Scheduler::poll_interval`), forever (ex: in
+    /// a worker binary)
+    pub fn run(self) -> ! {
+        loop {
+            if let Err(err) = self.tick() {
+                println!("Could not run the scheduled tasks: {err}");
+            }
+
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// starts a thread running the scheduler (ex: inside the server)
+    pub fn spawn(self) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || self.run())
+    }
+}
+
+fn now() -> Utc {
+    from_utc(chrono::Utc::now())
+}
+
+#[cfg(not(feature = "database_sqlite"))]
+fn from_utc(time: chrono::DateTime<chrono::Utc>) -> Utc {
+    time
+}
+
+#[cfg(feature = "database_sqlite")]
+fn from_utc(time: chrono::DateTime<chrono::Utc>) -> Utc {
+    time.naive_utc()
+}
+
+#[cfg(not(feature = "database_sqlite"))]
+fn to_utc(time: Utc) -> chrono::DateTime<chrono::Utc> {
+    time
+}
+
+#[cfg(feature = "database_sqlite")]
+fn to_utc(time: Utc) -> chrono::DateTime<chrono::Utc> {
+    chrono::TimeZone::from_utc_datetime(&chrono::Utc, &time)
+}
@@ -0,0 +1,22 @@
+table! {
+  scheduled_tasks (id) {
+      id -> Int4,
+      name -> Text,
+      cron -> Text,
+      next_run_at -> Timestamptz,
+      locked_until -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  scheduled_task_runs (id) {
+      id -> Int4,
+      task_name -> Text,
+      scheduled_for -> Timestamptz,
+      status -> Text,
+      error -> Nullable<Text>,
+      started_at -> Timestamptz,
+      finished_at -> Nullable<Timestamptz>,
+  }
+}
@@ -0,0 +1,22 @@
+table! {
+  scheduled_tasks (id) {
+      id -> Integer,
+      name -> Text,
+      cron -> Text,
+      next_run_at -> Timestamp,
+      locked_until -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  scheduled_task_runs (id) {
+      id -> Integer,
+      task_name -> Text,
+      scheduled_for -> Timestamp,
+      status -> Text,
+      error -> Nullable<Text>,
+      started_at -> Timestamp,
+      finished_at -> Nullable<Timestamp>,
+  }
+}
@@ -0,0 +1,39 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Query};
+use actix_web::{get, web, HttpResponse, Result};
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::search::{Search, SearchQuery};
+use crate::Database;
+
+/// handler for GET requests at the .../ endpoint
+///
+/// returns the documents matching `q` (in the `index` if given), see [`Search::search`]; the
+/// documents of a user are only returned to them
+#[get("")]
+async fn search(
+    db: Data<Database>,
+    search: Option<Data<Search>>,
+    auth: Option<Auth>,
+    Query(query): Query<SearchQuery>,
+) -> Result<HttpResponse> {
+    let search = search
+        .map(|search| search.get_ref().clone())
+        .unwrap_or_else(Search::global);
+    let user_id = auth.map(|auth| auth.user_id);
+
+    let result =
+        web::block(move || search.search(&mut db.get_connection(), &query, user_id)).await?;
+
+    match result {
+        Ok(hits) => Ok(HttpResponse::Ok().json(hits)),
+        Err(_) => Ok(HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(json!({ "message": "Could not search." }).to_string())),
+    }
+}
+
+/// returns the endpoints for the search service
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(search)
+}
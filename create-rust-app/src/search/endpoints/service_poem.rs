@@ -0,0 +1,51 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    web::{Data, Json, Query},
+    Error, IntoResponse, Result, Route,
+};
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::search::{Search, SearchQuery};
+use crate::Database;
+
+#[handler]
+/// handler for GET requests at the .../ endpoint
+///
+/// the documents of a user are only returned to them, see [`Search::search`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`SearchHit`](`crate::search::SearchHit`)s matching `q` (in the `index` if given)
+/// | 500 | Json payload : {"message": "Could not search."}
+async fn search(
+    db: Data<&Database>,
+    search: Option<Data<&Search>>,
+    auth: Option<Auth>,
+    Query(query): Query<SearchQuery>,
+) -> Result<impl IntoResponse> {
+    let search = search
+        .map(|search| search.0.clone())
+        .unwrap_or_else(Search::global);
+
+    search
+        .search(
+            &mut db.get_connection(),
+            &query,
+            auth.map(|auth| auth.user_id),
+        )
+        .map(Json)
+        .map_err(|_| {
+            Error::from_string(
+                json!({ "message": "Could not search." }).to_string(),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })
+}
+
+/// returns the endpoints for the search service
+pub fn api() -> Route {
+    Route::new().at("/", get(search))
+}
@@ -0,0 +1,184 @@
+use crate::Connection;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::{Document, SearchBackend, SearchHit, SearchQuery, ID};
+
+#[derive(Debug, Clone)]
+/// indexes documents in a [Meilisearch](https://www.meilisearch.com) index (`documents` by
+/// default), shared by every [`Searchable`](`super::Searchable`)
+pub struct Meilisearch {
+    url: String,
+    api_key: Option<String>,
+    index: String,
+    /// whether the index's settings were applied
+    configured: Arc<AtomicBool>,
+}
+
+impl Meilisearch {
+    /// ex: `Meilisearch::new("http://127.0.0.1:7700", Some(master_key))`
+    pub fn new(url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            api_key,
+            index: "documents".to_string(),
+            configured: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn with_index(mut self, index: impl Into<String>) -> Self {
+        self.index = index.into();
+        self
+    }
+
+    /// lets searches filter on the documents' index and owner, once
+    fn configure(&self) -> Result<(), String> {
+        if self.configured.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        self.send(
+            reqwest::Method::PATCH,
+            "/settings",
+            json!({
+                "searchableAttributes": ["title", "text"],
+                "filterableAttributes": ["index", "owner_id"],
+            }),
+        )?;
+        self.configured.store(true, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// sends a request to `path` (relative to the index), the blocking client being used in its
+    /// own thread so it works from async handlers too
+    fn send(&self, method: reqwest::Method, path: &str, body: Value) -> Result<Value, String> {
+        let url = format!("{}/indexes/{}{path}", self.url, self.index);
+
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let mut request = reqwest::blocking::Client::new()
+                        .request(method, &url)
+                        .header("Content-Type", "application/json");
+                    if let Some(api_key) = &self.api_key {
+                        request = request.bearer_auth(api_key);
+                    }
+                    if !body.is_null() {
+                        request = request.body(body.to_string());
+                    }
+
+                    let response = request
+                        .send()
+                        .map_err(|err| format!("Could not reach Meilisearch: {err}"))?;
+                    let status = response.status();
+                    let body = response.text().unwrap_or_default();
+
+                    if !status.is_success() {
+                        return Err(format!("Meilisearch responded with {status}: {body}"));
+                    }
+
+                    Ok(serde_json::from_str(&body).unwrap_or_default())
+                })
+                .join()
+                .expect("the search request's thread panicked")
+        })
+    }
+}
+
+/// Meilisearch's ids are limited to alphanumeric characters, `-` and `_`
+fn key(index: &str, id: &str) -> String {
+    format!("{index}-{id}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// `value` as a string of Meilisearch's filter expressions
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl SearchBackend for Meilisearch {
+    fn index(&self, _: &mut Connection, document: &Document) -> Result<(), String> {
+        self.configure()?;
+
+        self.send(
+            reqwest::Method::POST,
+            "/documents?primaryKey=key",
+            json!([{
+                "key": key(&document.index, &document.id),
+                "index": document.index,
+                "id": document.id,
+                "owner_id": document.owner_id,
+                "title": document.title,
+                "text": document.text,
+                "data": document.data,
+            }]),
+        )
+        .map(|_| ())
+    }
+
+    fn remove(&self, _: &mut Connection, index: &str, id: &str) -> Result<(), String> {
+        self.send(
+            reqwest::Method::DELETE,
+            &format!("/documents/{}", key(index, id)),
+            Value::Null,
+        )
+        .map(|_| ())
+    }
+
+    fn search(
+        &self,
+        _: &mut Connection,
+        query: &SearchQuery,
+        user_id: Option<ID>,
+    ) -> Result<Vec<SearchHit>, String> {
+        self.configure()?;
+
+        let mut filters = vec![match user_id {
+            Some(user_id) => format!("(owner_id IS NULL OR owner_id = {user_id})"),
+            None => "owner_id IS NULL".to_string(),
+        }];
+        if let Some(index) = &query.index {
+            filters.push(format!("index = {}", quote(index)));
+        }
+
+        let response = self.send(
+            reqwest::Method::POST,
+            "/search",
+            json!({
+                "q": query.q,
+                "limit": query.limit(),
+                "filter": filters.join(" AND "),
+                "attributesToCrop": ["text"],
+                "cropLength": 25,
+            }),
+        )?;
+
+        Ok(response["hits"]
+            .as_array()
+            .map(|hits| {
+                hits.iter()
+                    .map(|hit| SearchHit {
+                        index: hit["index"].as_str().unwrap_or_default().to_string(),
+                        id: hit["id"].as_str().unwrap_or_default().to_string(),
+                        title: hit["title"].as_str().unwrap_or_default().to_string(),
+                        snippet: hit["_formatted"]["text"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        data: hit["data"].clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,270 @@
+//! Full-text search (`plugin_search` feature): models implementing [`Searchable`] are indexed when
+//! they're created or updated, and found through the search endpoint.
+//!
+//! Documents are indexed in Postgres (a `tsvector` column, see [`PostgresSearch`]) or, when
+//! `MEILISEARCH_URL` is set, in [Meilisearch](https://www.meilisearch.com) (see [`Meilisearch`],
+//! the only backend with SQLite):
+//!
+//! ```rust,ignore
+//! impl Searchable for Todo {
+//!     const INDEX: &'static str = "todos";
+//!
+//!     fn search_id(&self) -> String {
+//!         self.id.to_string()
+//!     }
+//! }
+//!
+//! let todo = Todo::create(&mut db, &item)?;
+//! create_rust_app::search::index(&mut db, &todo);
+//! ```
+//!
+//! Resources created with `create-rust-app configure` can do this for you. The endpoint
+//! (`GET /?q=...&index=...`) only returns the documents of other users
+//! ([`Searchable::search_owner`]) to them.
+use crate::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+mod endpoints;
+pub use endpoints::*;
+mod meilisearch;
+pub use meilisearch::Meilisearch;
+#[cfg(feature = "database_postgres")]
+mod postgres;
+#[cfg(feature = "database_postgres")]
+pub use postgres::PostgresSearch;
+
+type ID = i32;
+
+/// the most results a search returns
+pub const MAX_LIMIT: i64 = 50;
+
+lazy_static::lazy_static! {
+    static ref GLOBAL: Search = Search::from_env();
+}
+
+/// a model the search endpoint can find
+///
+/// by default, a document's text is every string of the serialized model, and its title the first
+/// of them: override [`search_title`](`Searchable::search_title`) to pick the field
+pub trait Searchable: Serialize {
+    /// the index the model's documents belong to, ex: `todos`
+    const INDEX: &'static str;
+
+    /// the document's id within [`INDEX`](`Searchable::INDEX`), ex: the primary key
+    fn search_id(&self) -> String;
+
+    /// the user the document belongs to, who is the only one to find it; `None` (the default)
+    /// lets anyone find it
+    fn search_owner(&self) -> Option<ID> {
+        None
+    }
+
+    fn search_title(&self) -> String {
+        strings(&self.search_data())
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+    }
+
+    fn search_text(&self) -> String {
+        strings(&self.search_data()).join("\n")
+    }
+
+    /// what the results include, the serialized model by default
+    fn search_data(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn search_document(&self) -> Document {
+        Document {
+            index: Self::INDEX.to_string(),
+            id: self.search_id(),
+            owner_id: self.search_owner(),
+            title: self.search_title(),
+            text: self.search_text(),
+            data: self.search_data(),
+        }
+    }
+}
+
+/// the string values of `value`, in order
+fn strings(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::String(string) => vec![string.clone()],
+        serde_json::Value::Array(values) => values.iter().flat_map(strings).collect(),
+        serde_json::Value::Object(values) => values.values().flat_map(strings).collect(),
+        _ => vec![],
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// what's indexed for a [`Searchable`]
+pub struct Document {
+    pub index: String,
+    pub id: String,
+    pub owner_id: Option<ID>,
+    /// weighs more than the text when ranking results
+    pub title: String,
+    pub text: String,
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// a document matching the search, best matches first
+pub struct SearchHit {
+    pub index: String,
+    pub id: String,
+    pub title: String,
+    /// the part of the text matching the search
+    pub snippet: String,
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// query parameters of the search endpoint
+pub struct SearchQuery {
+    pub q: String,
+    /// only search this index
+    pub index: Option<String>,
+    /// at most [`MAX_LIMIT`] (default: 20)
+    pub limit: Option<i64>,
+}
+
+impl SearchQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(20).clamp(1, MAX_LIMIT)
+    }
+}
+
+/// where documents are indexed and searched
+pub trait SearchBackend: Send + Sync {
+    /// adds the document, or replaces the one of the same index and id
+    fn index(&self, db: &mut Connection, document: &Document) -> Result<(), String>;
+
+    fn remove(&self, db: &mut Connection, index: &str, id: &str) -> Result<(), String>;
+
+    /// the documents matching `query` that anyone, or [`user_id`](`ID`), can find
+    fn search(
+        &self,
+        db: &mut Connection,
+        query: &SearchQuery,
+        user_id: Option<ID>,
+    ) -> Result<Vec<SearchHit>, String>;
+}
+
+#[derive(Clone)]
+/// indexes and searches documents, see the [module documentation](`self`)
+///
+/// register it in the app's data, the global one is used otherwise
+pub struct Search {
+    backend: Arc<dyn SearchBackend>,
+}
+
+impl std::fmt::Debug for Search {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Search").finish()
+    }
+}
+
+impl Search {
+    pub fn new(backend: impl SearchBackend + 'static) -> Self {
+        Self {
+            backend: Arc::new(backend),
+        }
+    }
+
+    /// Meilisearch if `MEILISEARCH_URL` is set (with `MEILISEARCH_API_KEY`), the database otherwise
+    pub fn from_env() -> Self {
+        if let Ok(url) = std::env::var("MEILISEARCH_URL") {
+            return Self::new(Meilisearch::new(
+                url,
+                std::env::var("MEILISEARCH_API_KEY").ok(),
+            ));
+        }
+
+        #[cfg(feature = "database_postgres")]
+        return Self::new(PostgresSearch);
+
+        #[cfg(not(feature = "database_postgres"))]
+        return Self::new(Unavailable);
+    }
+
+    /// the process' search, used by [`index`], [`remove`] and the endpoints unless the app
+    /// registers its own in its data
+    pub fn global() -> Self {
+        GLOBAL.clone()
+    }
+
+    pub fn index<T: Searchable>(&self, db: &mut Connection, item: &T) -> Result<(), String> {
+        self.backend.index(db, &item.search_document())
+    }
+
+    /// indexes `items`, ex: the rows that existed before the model was searchable
+    pub fn index_all<T: Searchable>(&self, db: &mut Connection, items: &[T]) -> Result<(), String> {
+        items.iter().try_for_each(|item| self.index(db, item))
+    }
+
+    pub fn remove<T: Searchable>(&self, db: &mut Connection, id: &str) -> Result<(), String> {
+        self.backend.remove(db, T::INDEX, id)
+    }
+
+    pub fn search(
+        &self,
+        db: &mut Connection,
+        query: &SearchQuery,
+        user_id: Option<ID>,
+    ) -> Result<Vec<SearchHit>, String> {
+        if query.q.trim().is_empty() {
+            return Ok(vec![]);
+        }
+
+        self.backend.search(db, query, user_id)
+    }
+}
+
+/// indexes `item` with the [global](`Search::global`) search
+///
+/// failures are logged rather than returned, so they don't fail the write that triggered them
+pub fn index<T: Searchable>(db: &mut Connection, item: &T) {
+    if let Err(err) = Search::global().index(db, item) {
+        println!(
+            "search: could not index '{}' in '{}': {err}",
+            item.search_id(),
+            T::INDEX
+        );
+    }
+}
+
+/// removes the document `id` of `T`'s index from the [global](`Search::global`) search
+///
+/// failures are logged rather than returned, so they don't fail the delete that triggered them
+pub fn remove<T: Searchable>(db: &mut Connection, id: &str) {
+    if let Err(err) = Search::global().remove::<T>(db, id) {
+        println!("search: could not remove '{id}' from '{}': {err}", T::INDEX);
+    }
+}
+
+#[cfg(not(feature = "database_postgres"))]
+/// without Postgres, documents can only be indexed in Meilisearch
+struct Unavailable;
+
+#[cfg(not(feature = "database_postgres"))]
+impl SearchBackend for Unavailable {
+    fn index(&self, _: &mut Connection, _: &Document) -> Result<(), String> {
+        Err("Set MEILISEARCH_URL to index documents".to_string())
+    }
+
+    fn remove(&self, _: &mut Connection, _: &str, _: &str) -> Result<(), String> {
+        Err("Set MEILISEARCH_URL to index documents".to_string())
+    }
+
+    fn search(
+        &self,
+        _: &mut Connection,
+        _: &SearchQuery,
+        _: Option<ID>,
+    ) -> Result<Vec<SearchHit>, String> {
+        Err("Set MEILISEARCH_URL to search documents".to_string())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::Connection;
+use diesel::{
+    query_dsl::RunQueryDsl,
+    sql_query,
+    sql_types::{BigInt, Integer, Nullable, Text},
+};
+
+use super::{Document, SearchBackend, SearchHit, SearchQuery, ID};
+
+/// indexes documents in the `search_documents` table, whose `search_vector` column is generated
+/// from their title and text (english stemming, edit the migration for another language)
+///
+/// queries use the syntax of web search engines: `"exact phrase"`, `or`, `-excluded`
+pub struct PostgresSearch;
+
+#[derive(QueryableByName)]
+struct Row {
+    #[diesel(sql_type = Text)]
+    index_name: String,
+    #[diesel(sql_type = Text)]
+    document_id: String,
+    #[diesel(sql_type = Text)]
+    title: String,
+    #[diesel(sql_type = Text)]
+    snippet: String,
+    #[diesel(sql_type = Text)]
+    data: String,
+}
+
+impl SearchBackend for PostgresSearch {
+    fn index(&self, db: &mut Connection, document: &Document) -> Result<(), String> {
+        sql_query(
+            "INSERT INTO search_documents (index_name, document_id, owner_id, title, body, data)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (index_name, document_id) DO UPDATE SET
+                owner_id = EXCLUDED.owner_id,
+                title = EXCLUDED.title,
+                body = EXCLUDED.body,
+                data = EXCLUDED.data,
+                updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind::<Text, _>(&document.index)
+        .bind::<Text, _>(&document.id)
+        .bind::<Nullable<Integer>, _>(document.owner_id)
+        .bind::<Text, _>(&document.title)
+        .bind::<Text, _>(&document.text)
+        .bind::<Text, _>(document.data.to_string())
+        .execute(db)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+    }
+
+    fn remove(&self, db: &mut Connection, index: &str, id: &str) -> Result<(), String> {
+        sql_query("DELETE FROM search_documents WHERE index_name = $1 AND document_id = $2")
+            .bind::<Text, _>(index)
+            .bind::<Text, _>(id)
+            .execute(db)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+
+    fn search(
+        &self,
+        db: &mut Connection,
+        query: &SearchQuery,
+        user_id: Option<ID>,
+    ) -> Result<Vec<SearchHit>, String> {
+        let rows = sql_query(
+            "SELECT index_name, document_id, title, data,
+                ts_headline('english', body, query, 'StartSel=\"\", StopSel=\"\", MaxWords=25, MinWords=10') AS snippet
+            FROM search_documents, websearch_to_tsquery('english', $1) query
+            WHERE search_vector @@ query
+                AND ($2 IS NULL OR index_name = $2)
+                AND (owner_id IS NULL OR owner_id = $3)
+            ORDER BY ts_rank(search_vector, query) DESC
+            LIMIT $4",
+        )
+        .bind::<Text, _>(&query.q)
+        .bind::<Nullable<Text>, _>(&query.index)
+        .bind::<Nullable<Integer>, _>(user_id)
+        .bind::<BigInt, _>(query.limit())
+        .get_results::<Row>(db)
+        .map_err(|err| err.to_string())?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| SearchHit {
+                index: row.index_name,
+                id: row.document_id,
+                title: row.title,
+                snippet: row.snippet,
+                data: serde_json::from_str(&row.data).unwrap_or_default(),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,101 @@
+//! Seed data, kept in the project's `seeds/` folder (or `CRA_SEEDS_DIR`) as `.sql` files.
+//!
+//! [`run`] applies every seed in file name order, each in its own transaction. Seeds are meant to
+//! be applied again and again (after a reset, or whenever they change), so they must be
+//! idempotent: insert rows only if they're missing, and update the ones that may already exist.
+//!
+//! ```sql
+//! -- seeds/01_roles.sql
+//! INSERT INTO role_permissions (role, permission)
+//! VALUES ('admin', 'admin:write')
+//! ON CONFLICT DO NOTHING;
+//!
+//! INSERT INTO todos (text)
+//! SELECT 'Read the docs'
+//! WHERE NOT EXISTS (SELECT 1 FROM todos WHERE text = 'Read the docs');
+//! ```
+//!
+//! ```rust,ignore
+//! let mut db = app_data.database.get_connection();
+//! create_rust_app::seeds::run(&mut db).expect("Could not seed the database");
+//! ```
+//!
+//! In development, the admin portal's "Migrations" page lists the seeds and (re)applies them.
+use crate::Connection;
+use diesel::connection::SimpleConnection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize)]
+/// a seed file, see [`list`]
+pub struct Seed {
+    /// the file's name, ex: `01_roles.sql`
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// the folder the seeds are read from: `CRA_SEEDS_DIR`, or `seeds` in the working directory
+pub fn seeds_dir() -> PathBuf {
+    match std::env::var("CRA_SEEDS_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from("seeds"),
+    }
+}
+
+/// the `.sql` files of [`seeds_dir`], in the order they're applied
+pub fn list() -> Result<Vec<Seed>, String> {
+    list_in(&seeds_dir())
+}
+
+/// the `.sql` files of `dir`, in the order they're applied; none if `dir` doesn't exist
+pub fn list_in(dir: &Path) -> Result<Vec<Seed>, String> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let entries = std::fs::read_dir(dir)
+        .map_err(|err| format!("Could not read the seeds folder '{}': {err}", dir.display()))?;
+
+    let mut seeds = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().map_or(false, |ext| ext == "sql"))
+        .map(|path| Seed {
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            path,
+        })
+        .collect::<Vec<_>>();
+    seeds.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(seeds)
+}
+
+/// applies the seeds of [`seeds_dir`], returns the names of the ones applied
+///
+/// stops at the first one failing, its changes are rolled back but the seeds before it stay applied
+pub fn run(db: &mut Connection) -> Result<Vec<String>, String> {
+    run_in(db, &seeds_dir())
+}
+
+/// applies the seeds of `dir`, see [`run`]
+pub fn run_in(db: &mut Connection, dir: &Path) -> Result<Vec<String>, String> {
+    let mut applied = vec![];
+
+    for seed in list_in(dir)? {
+        let sql = std::fs::read_to_string(&seed.path)
+            .map_err(|err| format!("Could not read seed '{}': {err}", seed.name))?;
+
+        diesel::connection::Connection::transaction::<_, diesel::result::Error, _>(db, |db| {
+            db.batch_execute(&sql)
+        })
+        .map_err(|err| format!("Seed '{}' failed: {err}", seed.name))?;
+
+        applied.push(seed.name);
+    }
+
+    Ok(applied)
+}
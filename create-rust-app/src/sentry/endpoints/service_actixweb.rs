@@ -0,0 +1,17 @@
+use actix_web::{get, HttpResponse, Result};
+
+use crate::sentry::frontend_config;
+
+/// handler for GET requests at the .../sentry endpoint
+///
+/// returns the [`SentryConfig`](`crate::sentry::SentryConfig`) the frontend's SDK is initialized
+/// with
+#[get("")]
+async fn config() -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(frontend_config()))
+}
+
+/// returns the endpoint of the frontend's Sentry configuration
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope.service(config)
+}
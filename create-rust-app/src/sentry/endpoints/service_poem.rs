@@ -0,0 +1,19 @@
+use poem::{get, handler, web::Json, IntoResponse, Result, Route};
+
+use crate::sentry::frontend_config;
+
+#[handler]
+/// handler for GET requests at the .../sentry endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`SentryConfig`](`crate::sentry::SentryConfig`) the frontend's SDK is initialized with
+async fn config() -> Result<impl IntoResponse> {
+    Ok(Json(frontend_config()))
+}
+
+/// returns the endpoint of the frontend's Sentry configuration
+pub fn api() -> Route {
+    Route::new().at("/", get(config))
+}
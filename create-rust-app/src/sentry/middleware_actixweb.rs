@@ -0,0 +1,79 @@
+use ::sentry::SentryFutureExt;
+use actix_web::body::MessageBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+/// reports the panics and server errors of the requests it handles, see [`sentry`](`super`)
+///
+/// ```rust,ignore
+/// let app = App::new().wrap(create_rust_app::sentry::ErrorCapture);
+/// ```
+pub struct ErrorCapture;
+
+impl<S, B> Transform<S, ServiceRequest> for ErrorCapture
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ErrorCaptureMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ErrorCaptureMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct ErrorCaptureMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ErrorCaptureMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let hub = super::request_hub(
+            req.method().as_str(),
+            req.match_pattern().as_deref().unwrap_or(req.path()),
+            Some(req.query_string()).filter(|query| !query.is_empty()),
+            req.headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+        );
+
+        Box::pin(async move {
+            // the extractors and handlers running in the request's hub configure its scope
+            let result = service.call(req).bind_hub(hub.clone()).await;
+
+            match &result {
+                Ok(response) => super::capture_server_error(
+                    &hub,
+                    response.status().as_u16(),
+                    response.response().error().map(ToString::to_string),
+                ),
+                Err(err) => super::capture_server_error(
+                    &hub,
+                    err.as_response_error().status_code().as_u16(),
+                    Some(err.to_string()),
+                ),
+            }
+
+            result
+        })
+    }
+}
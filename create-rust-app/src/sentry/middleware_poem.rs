@@ -0,0 +1,54 @@
+use ::sentry::SentryFutureExt;
+use poem::{async_trait, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+/// reports the panics and server errors of the requests it handles, see [`sentry`](`super`)
+///
+/// ```rust,ignore
+/// let app = app.with(create_rust_app::sentry::ErrorCapture);
+/// ```
+pub struct ErrorCapture;
+
+impl<E: Endpoint> Middleware<E> for ErrorCapture {
+    type Output = ErrorCaptureEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ErrorCaptureEndpoint { ep }
+    }
+}
+
+pub struct ErrorCaptureEndpoint<E> {
+    ep: E,
+}
+
+#[async_trait]
+impl<E: Endpoint> Endpoint for ErrorCaptureEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let hub = super::request_hub(
+            req.method().as_str(),
+            req.uri().path(),
+            req.uri().query(),
+            req.headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+        );
+
+        // the extractors and handlers running in the request's hub configure its scope
+        let result = self
+            .ep
+            .call(req)
+            .bind_hub(hub.clone())
+            .await
+            .map(IntoResponse::into_response);
+
+        match &result {
+            Ok(response) => super::capture_server_error(&hub, response.status().as_u16(), None),
+            Err(err) => {
+                super::capture_server_error(&hub, err.status().as_u16(), Some(err.to_string()))
+            }
+        }
+
+        result
+    }
+}
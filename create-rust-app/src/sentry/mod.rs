@@ -0,0 +1,174 @@
+//! Error reporting to [Sentry](https://sentry.io) (`plugin_sentry` feature).
+//!
+//! ```rust,ignore
+//! let _sentry = create_rust_app::sentry::init(concat!(env!("CARGO_PKG_NAME"), "@", env!("CARGO_PKG_VERSION")));
+//! // actix-web
+//! let app = App::new()
+//!     .wrap(create_rust_app::sentry::ErrorCapture)
+//!     .service(create_rust_app::sentry::endpoints(web::scope("/sentry")));
+//! // poem
+//! let app = app
+//!     .nest("/sentry", create_rust_app::sentry::api())
+//!     .with(create_rust_app::sentry::ErrorCapture);
+//! ```
+//!
+//! Nothing is reported unless `SENTRY_DSN` is set. Events are tagged with the release given to
+//! [`init`] (or `SENTRY_RELEASE`) and the environment (`SENTRY_ENVIRONMENT`, or `APP_ENV`, or
+//! `development` in debug builds and `production` otherwise).
+//!
+//! The [`ErrorCapture`] middleware gives every request its own scope: panics, and the responses
+//! with a 5xx status, are reported with the request's method, route and headers (but not its
+//! `Authorization` and `Cookie` ones), and with the user the [`Auth`](`crate::auth::Auth`)
+//! extractor authenticated, if any. Report errors yourself with [`::sentry::capture_error`].
+//!
+//! The `/api/sentry` endpoint gives the frontend's SDK its configuration: `SENTRY_FRONTEND_DSN`
+//! (or `SENTRY_DSN`), and the same release and environment.
+use ::sentry::protocol::{Event, Request};
+use ::sentry::{ClientInitGuard, ClientOptions, Hub, Level};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+mod endpoints;
+pub use endpoints::*;
+#[cfg(feature = "backend_actix-web")]
+mod middleware_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use middleware_actixweb::ErrorCapture;
+#[cfg(feature = "backend_poem")]
+mod middleware_poem;
+#[cfg(feature = "backend_poem")]
+pub use middleware_poem::ErrorCapture;
+
+/// headers which aren't sent to Sentry
+const REDACTED_HEADERS: [&str; 3] = ["authorization", "cookie", "set-cookie"];
+
+#[derive(Debug, Clone, Serialize)]
+/// what the frontend's SDK is initialized with, see the `/api/sentry` endpoint
+pub struct SentryConfig {
+    /// `None` when the frontend shouldn't report anything
+    pub dsn: Option<String>,
+    pub release: Option<String>,
+    pub environment: String,
+}
+
+/// the environment events are tagged with: `SENTRY_ENVIRONMENT`, `APP_ENV`, or `development` in
+/// debug builds and `production` otherwise
+pub fn environment() -> String {
+    std::env::var("SENTRY_ENVIRONMENT")
+        .or_else(|_| std::env::var("APP_ENV"))
+        .unwrap_or_else(|_| {
+            if cfg!(debug_assertions) {
+                "development".to_string()
+            } else {
+                "production".to_string()
+            }
+        })
+}
+
+lazy_static::lazy_static! {
+    /// the release given to [`init`]
+    static ref RELEASE: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
+}
+
+/// the release events are tagged with: `SENTRY_RELEASE`, or the one given to [`init`]
+fn release() -> Option<String> {
+    std::env::var("SENTRY_RELEASE")
+        .ok()
+        .or_else(|| RELEASE.read().unwrap().clone())
+}
+
+/// sets up the Sentry client, reporting panics; does nothing when `SENTRY_DSN` isn't set
+///
+/// `release` tags the events unless `SENTRY_RELEASE` is set, ex: `my-app@1.0.0`. Keep the guard
+/// until the app exits, dropping it sends the events left.
+pub fn init(release: &str) -> Option<ClientInitGuard> {
+    *RELEASE.write().unwrap() = Some(release.to_string());
+
+    let dsn = std::env::var("SENTRY_DSN")
+        .ok()
+        .filter(|dsn| !dsn.is_empty())?;
+
+    let guard = ::sentry::init((
+        dsn,
+        ClientOptions {
+            release: self::release().map(Cow::Owned),
+            environment: Some(Cow::Owned(environment())),
+            ..Default::default()
+        },
+    ));
+    if !guard.is_enabled() {
+        println!("sentry: SENTRY_DSN is invalid, errors won't be reported");
+    }
+
+    Some(guard)
+}
+
+/// the configuration of the frontend's SDK
+pub fn frontend_config() -> SentryConfig {
+    SentryConfig {
+        dsn: std::env::var("SENTRY_FRONTEND_DSN")
+            .or_else(|_| std::env::var("SENTRY_DSN"))
+            .ok()
+            .filter(|dsn| !dsn.is_empty()),
+        release: release(),
+        environment: environment(),
+    }
+}
+
+/// the hub of a request, whose events describe it
+fn request_hub<'a>(
+    method: &str,
+    route: &str,
+    query: Option<&str>,
+    headers: impl Iterator<Item = (&'a str, &'a str)>,
+) -> Arc<Hub> {
+    let hub = Arc::new(Hub::new_from_top(Hub::main()));
+
+    let request = Request {
+        method: Some(method.to_string()),
+        query_string: query.map(String::from),
+        headers: headers
+            .filter(|(name, _)| !REDACTED_HEADERS.contains(&name.to_lowercase().as_str()))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        ..Default::default()
+    };
+    let transaction = format!("{method} {route}");
+
+    hub.configure_scope(|scope| {
+        scope.set_transaction(Some(&transaction));
+        scope.add_event_processor(move |mut event: Event<'static>| {
+            if event.request.is_none() {
+                event.request = Some(request.clone());
+            }
+            Some(event)
+        });
+    });
+
+    hub
+}
+
+/// reports a response with a 5xx `status`, `error` being why (when known)
+fn capture_server_error(hub: &Hub, status: u16, error: Option<String>) {
+    if status < 500 {
+        return;
+    }
+
+    let message = match error {
+        Some(error) => format!("{status}: {error}"),
+        None => format!("Answered with a {status} status"),
+    };
+    hub.capture_message(&message, Level::Error);
+}
+
+/// attaches the user authenticated by [`Auth`](`crate::auth::Auth`) to the request's events
+#[cfg(feature = "plugin_auth")]
+pub(crate) fn set_user(user_id: crate::auth::ID) {
+    ::sentry::configure_scope(|scope| {
+        scope.set_user(Some(::sentry::User {
+            id: Some(user_id.to_string()),
+            ..Default::default()
+        }));
+    });
+}
@@ -0,0 +1,148 @@
+//! Soft deletion (`plugin_soft_delete` feature): rows are marked deleted by setting their
+//! `deleted_at` column instead of being removed, so they can be restored, and are left out of the
+//! default queries.
+//!
+//! ```rust,ignore
+//! // a `todos` table with a `deleted_at TIMESTAMPTZ` (nullable) column
+//! use crate::schema::todos;
+//! use create_rust_app::soft_delete::SoftDelete;
+//!
+//! create_rust_app::soft_delete!(Todo, todos);
+//!
+//! // the default scope: the rows which weren't deleted
+//! let todos = Todo::active().order(todos::id).load::<Todo>(&mut db)?;
+//! // every row, or only the deleted ones (ex: a trash view)
+//! let all = Todo::with_deleted().load::<Todo>(&mut db)?;
+//! let trash = Todo::only_deleted().load::<Todo>(&mut db)?;
+//!
+//! Todo::soft_delete(&mut db, todo_id)?;
+//! Todo::restore(&mut db, todo_id)?;
+//! // deleted for good, once they've been in the trash for 30 days
+//! Todo::purge(&mut db, create_rust_app::soft_delete::now() - chrono::Duration::days(30))?;
+//! ```
+//!
+//! [`soft_delete!`](`crate::soft_delete!`) is invoked next to the model rather than in it, since
+//! `dsync` regenerates the models (`create-rust-app configure` puts it in the resource's service).
+//! The queries a model already has (`read`, `paginate`, ...) see every row: build on `active()`
+//! instead where deleted rows shouldn't show up.
+//!
+//! With the auth plugin, [`AccountDeletion::SoftDelete`](`crate::auth::account::AccountDeletion`)
+//! soft-deletes accounts (requires a `deleted_at` column on `users`).
+use crate::Connection;
+use diesel::QueryResult;
+
+#[cfg(feature = "database_postgres")]
+/// the diesel backend of the queries [`soft_delete!`](`crate::soft_delete!`) returns
+pub type Backend = diesel::pg::Pg;
+#[cfg(feature = "database_sqlite")]
+/// the diesel backend of the queries [`soft_delete!`](`crate::soft_delete!`) returns
+pub type Backend = diesel::sqlite::Sqlite;
+
+#[cfg(not(feature = "database_sqlite"))]
+/// the type of `deleted_at` columns (`TIMESTAMPTZ`, `TIMESTAMP` with SQLite)
+pub type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+/// the type of `deleted_at` columns (`TIMESTAMPTZ`, `TIMESTAMP` with SQLite)
+pub type Utc = chrono::NaiveDateTime;
+
+/// the current time, as stored in `deleted_at` columns
+pub fn now() -> Utc {
+    #[cfg(not(feature = "database_sqlite"))]
+    return chrono::Utc::now();
+    #[cfg(feature = "database_sqlite")]
+    return chrono::Utc::now().naive_utc();
+}
+
+/// a model of a table with a `deleted_at` column, implemented by
+/// [`soft_delete!`](`crate::soft_delete!`)
+pub trait SoftDelete {
+    /// sets the `deleted_at` of the row [`item_id`](`i32`), returns 0 when there's no such row or
+    /// it was already deleted
+    fn soft_delete(db: &mut Connection, item_id: i32) -> QueryResult<usize>;
+
+    /// clears the `deleted_at` of the row [`item_id`](`i32`), returns 0 when there's no such
+    /// deleted row
+    fn restore(db: &mut Connection, item_id: i32) -> QueryResult<usize>;
+
+    /// deletes the rows soft-deleted before `deleted_before` for good
+    fn purge(db: &mut Connection, deleted_before: Utc) -> QueryResult<usize>;
+}
+
+/// implements [`SoftDelete`] for `$model`, a model of the table `$table` (a module of the app's
+/// `schema` in scope), and adds its scoped queries:
+///
+/// - `$model::active()`: the rows which weren't deleted, the default scope
+/// - `$model::with_deleted()`: every row
+/// - `$model::only_deleted()`: the deleted rows
+///
+/// they're boxed, so they can be filtered and ordered further
+#[macro_export]
+macro_rules! soft_delete {
+    ($model:ty, $table:ident) => {
+        impl $crate::soft_delete::SoftDelete for $model {
+            fn soft_delete(
+                db: &mut $crate::Connection,
+                item_id: i32,
+            ) -> ::diesel::QueryResult<usize> {
+                use ::diesel::prelude::*;
+
+                ::diesel::update(
+                    $table::table
+                        .filter($table::id.eq(item_id))
+                        .filter($table::deleted_at.is_null()),
+                )
+                .set($table::deleted_at.eq($crate::soft_delete::now()))
+                .execute(db)
+            }
+
+            fn restore(db: &mut $crate::Connection, item_id: i32) -> ::diesel::QueryResult<usize> {
+                use ::diesel::prelude::*;
+
+                ::diesel::update(
+                    $table::table
+                        .filter($table::id.eq(item_id))
+                        .filter($table::deleted_at.is_not_null()),
+                )
+                .set($table::deleted_at.eq(None::<$crate::soft_delete::Utc>))
+                .execute(db)
+            }
+
+            fn purge(
+                db: &mut $crate::Connection,
+                deleted_before: $crate::soft_delete::Utc,
+            ) -> ::diesel::QueryResult<usize> {
+                use ::diesel::prelude::*;
+
+                ::diesel::delete($table::table.filter($table::deleted_at.lt(deleted_before)))
+                    .execute(db)
+            }
+        }
+
+        impl $model {
+            /// the rows which weren't soft-deleted
+            pub fn active() -> $table::BoxedQuery<'static, $crate::soft_delete::Backend> {
+                use ::diesel::prelude::*;
+
+                $table::table
+                    .filter($table::deleted_at.is_null())
+                    .into_boxed()
+            }
+
+            /// every row, soft-deleted or not
+            pub fn with_deleted() -> $table::BoxedQuery<'static, $crate::soft_delete::Backend> {
+                use ::diesel::prelude::*;
+
+                $table::table.into_boxed()
+            }
+
+            /// the soft-deleted rows
+            pub fn only_deleted() -> $table::BoxedQuery<'static, $crate::soft_delete::Backend> {
+                use ::diesel::prelude::*;
+
+                $table::table
+                    .filter($table::deleted_at.is_not_null())
+                    .into_boxed()
+            }
+        }
+    };
+}
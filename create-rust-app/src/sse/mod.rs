@@ -0,0 +1,154 @@
+//! Server-Sent Events: typed event streams sent as `text/event-stream` responses, for pushing
+//! updates to the frontend without a websocket.
+//!
+//! An [`Sse`] wraps a stream of [`Event`]s, whose data is sent as JSON, and is returned by
+//! handlers of both backends. While the stream is idle, comments are sent every
+//! [`DEFAULT_KEEP_ALIVE`] so proxies don't close the connection.
+//!
+//! Clients reconnect on their own when the connection is lost, sending the id of the last event
+//! they got in the `Last-Event-ID` header: the [`LastEventId`] extractor reads it, so the stream
+//! can resume after it.
+//!
+//! ```rust,ignore
+//! #[get("/events")]
+//! async fn events(last_event_id: LastEventId) -> Sse<impl Stream<Item = Event<Tick>>> {
+//!     let start = last_event_id.parse::<u64>().map_or(0, |id| id + 1);
+//!
+//!     Sse::new(futures_util::stream::unfold(start, |count| async move {
+//!         tokio::time::sleep(Duration::from_secs(1)).await;
+//!         Some((Event::new(Tick { count }).id(count), count + 1))
+//!     }))
+//! }
+//! ```
+//!
+//! `create-rust-app configure --new-sse` scaffolds an endpoint like this one, and a React hook
+//! receiving its events.
+use std::time::Duration;
+
+use serde::Serialize;
+
+#[cfg(feature = "backend_actix-web")]
+mod sse_actixweb;
+
+#[cfg(feature = "backend_poem")]
+mod sse_poem;
+
+/// how often comments are sent while the stream is idle, see [`Sse::keep_alive`]
+pub const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// a comment line, ignored by clients
+const KEEP_ALIVE_FRAME: &str = ":\n\n";
+
+#[derive(Debug, Clone)]
+/// an event of an [`Sse`] stream
+pub struct Event<T> {
+    /// sent as JSON
+    pub data: T,
+    /// the event's type, `message` when it's `None`
+    pub event: Option<String>,
+    /// sent back by the client in the `Last-Event-ID` header when it reconnects
+    pub id: Option<String>,
+    /// how long the client waits before reconnecting
+    pub retry: Option<Duration>,
+}
+
+impl<T: Serialize> Event<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            event: None,
+            id: None,
+            retry: None,
+        }
+    }
+
+    /// the event's type (clients listen to it with `addEventListener(name, ...)`)
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// the event's id, see [`LastEventId`]
+    pub fn id(mut self, id: impl ToString) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// how long the client waits before reconnecting, from now on
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// the event in the `text/event-stream` format
+    pub fn to_frame(&self) -> serde_json::Result<String> {
+        let mut frame = String::new();
+
+        if let Some(event) = &self.event {
+            frame.push_str(&format!("event: {}\n", single_line(event)));
+        }
+        if let Some(id) = &self.id {
+            frame.push_str(&format!("id: {}\n", single_line(id)));
+        }
+        if let Some(retry) = &self.retry {
+            frame.push_str(&format!("retry: {}\n", retry.as_millis()));
+        }
+        for line in serde_json::to_string(&self.data)?.lines() {
+            frame.push_str(&format!("data: {line}\n"));
+        }
+        frame.push('\n');
+
+        Ok(frame)
+    }
+}
+
+/// `value` without line breaks, which would end its field
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], "")
+}
+
+/// the frame of `event`, `None` (and logged) when its data can't be serialized
+fn frame<T: Serialize>(event: &Event<T>) -> Option<String> {
+    match event.to_frame() {
+        Ok(frame) => Some(frame),
+        Err(err) => {
+            println!("SSE: could not serialize an event, it was skipped: {err}");
+            None
+        }
+    }
+}
+
+/// a `text/event-stream` response sending the events of `stream`, until it ends
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<Duration>,
+}
+
+impl<S> Sse<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            keep_alive: Some(DEFAULT_KEEP_ALIVE),
+        }
+    }
+
+    /// how often comments are sent to keep the connection open (default: [`DEFAULT_KEEP_ALIVE`]),
+    /// `None` disables them
+    pub fn keep_alive(mut self, keep_alive: Option<Duration>) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// the id of the last event the client got before reconnecting, from the `Last-Event-ID` header
+///
+/// it's an extractor for both backends, `None` on the first connection
+pub struct LastEventId(pub Option<String>);
+
+impl LastEventId {
+    /// the id as a `T`, `None` if it's missing or isn't one
+    pub fn parse<T: std::str::FromStr>(&self) -> Option<T> {
+        self.0.as_deref().and_then(|id| id.trim().parse().ok())
+    }
+}
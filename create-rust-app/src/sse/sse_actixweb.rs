@@ -0,0 +1,75 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use actix_http::header::{CACHE_CONTROL, CONTENT_ENCODING};
+use actix_web::body::BoxBody;
+use actix_web::dev::Payload;
+use actix_web::rt::time::{interval_at, Instant};
+use actix_web::web::Bytes;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, Responder};
+use futures::future::{ready, Ready};
+use futures::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+
+use super::{frame, Event, LastEventId, Sse, KEEP_ALIVE_FRAME};
+
+/// a comment every `period`, forever
+fn keep_alive(period: Duration) -> impl Stream<Item = String> {
+    stream::unfold(
+        interval_at(Instant::now() + period, period),
+        |mut interval| async move {
+            interval.tick().await;
+            Some((KEEP_ALIVE_FRAME.to_string(), interval))
+        },
+    )
+}
+
+impl<S, T> Responder for Sse<S>
+where
+    S: Stream<Item = Event<T>> + 'static,
+    T: Serialize + 'static,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+        let events = self
+            .stream
+            .filter_map(|event| ready(frame(&event)))
+            .map(Some)
+            // marks the end of the events, the keep-alives would go on otherwise
+            .chain(stream::once(ready(None)));
+
+        let frames = match self.keep_alive {
+            Some(period) => stream::select(events, keep_alive(period).map(Some)).boxed_local(),
+            None => events.boxed_local(),
+        };
+        let body = frames
+            .take_while(|frame| ready(frame.is_some()))
+            .filter_map(ready)
+            .map(|frame| Ok::<_, Infallible>(Bytes::from(frame)));
+
+        HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header((CACHE_CONTROL, "no-cache"))
+            // compressing middlewares buffer the events
+            .insert_header((CONTENT_ENCODING, "identity"))
+            // so does nginx
+            .insert_header(("X-Accel-Buffering", "no"))
+            .streaming(body)
+    }
+}
+
+impl FromRequest for LastEventId {
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = Infallible;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let id = req
+            .headers()
+            .get("Last-Event-ID")
+            .and_then(|id| id.to_str().ok())
+            .map(|id| id.to_string());
+
+        ready(Ok(LastEventId(id)))
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use futures_util::future::ready;
+use futures_util::stream::{self, Stream, StreamExt};
+use poem::http::header::{CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE};
+use poem::{async_trait, Body, FromRequest, IntoResponse, Request, RequestBody, Response, Result};
+use serde::Serialize;
+use tokio::time::{interval_at, Instant};
+
+use super::{frame, Event, LastEventId, Sse, KEEP_ALIVE_FRAME};
+
+/// a comment every `period`, forever
+fn keep_alive(period: Duration) -> impl Stream<Item = String> {
+    stream::unfold(
+        interval_at(Instant::now() + period, period),
+        |mut interval| async move {
+            interval.tick().await;
+            Some((KEEP_ALIVE_FRAME.to_string(), interval))
+        },
+    )
+}
+
+impl<S, T> IntoResponse for Sse<S>
+where
+    S: Stream<Item = Event<T>> + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    fn into_response(self) -> Response {
+        let events = self
+            .stream
+            .filter_map(|event| ready(frame(&event)))
+            .map(Some)
+            // marks the end of the events, the keep-alives would go on otherwise
+            .chain(stream::once(ready(None)));
+
+        let frames = match self.keep_alive {
+            Some(period) => stream::select(events, keep_alive(period).map(Some)).boxed(),
+            None => events.boxed(),
+        };
+        let body = frames
+            .take_while(|frame| ready(frame.is_some()))
+            .filter_map(ready)
+            .map(Ok::<_, std::io::Error>);
+
+        Response::builder()
+            .header(CONTENT_TYPE, "text/event-stream")
+            .header(CACHE_CONTROL, "no-cache")
+            // compressing middlewares buffer the events
+            .header(CONTENT_ENCODING, "identity")
+            // so does nginx
+            .header("X-Accel-Buffering", "no")
+            .body(Body::from_bytes_stream(body))
+    }
+}
+
+#[async_trait]
+impl<'a> FromRequest<'a> for LastEventId {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let id = req
+            .headers()
+            .get("Last-Event-ID")
+            .and_then(|id| id.to_str().ok())
+            .map(|id| id.to_string());
+
+        Ok(LastEventId(id))
+    }
+}
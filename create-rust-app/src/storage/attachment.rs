@@ -1,5 +1,6 @@
 use diesel::result::Error;
 use diesel::QueryResult;
+use std::time::Duration;
 //use md5;
 //use mime_guess;
 use serde::{Deserialize, Serialize};
@@ -10,7 +11,7 @@ use crate::storage::attachment_blob::AttachmentBlobChangeset;
 use crate::storage::{schema, AttachmentBlob, Utc, ID};
 use crate::Connection;
 
-use super::{schema::*, Storage};
+use super::{scan, schema::*, DirectUpload, ScanStatus, Storage};
 
 #[derive(
     Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable, AsChangeset,
@@ -25,6 +26,13 @@ pub struct Attachment {
     pub blob_id: ID,
 
     pub created_at: Utc,
+    /// only downloadable by users allowed by [`HasAttachments::can_read_private`], through short-lived
+    /// links (see [`PRIVATE_LINK_TTL`])
+    pub private: bool,
+    /// who uploaded the file, their attachments count towards their [`StorageQuota`](`super::StorageQuota`)
+    pub user_id: Option<ID>,
+    /// a [`ScanStatus`], quarantined attachments never get download links
+    pub scan_status: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
@@ -34,6 +42,9 @@ pub struct AttachmentChangeset {
     pub record_type: String,
     pub record_id: ID,
     pub blob_id: ID,
+    pub private: bool,
+    pub user_id: Option<ID>,
+    pub scan_status: String,
 }
 
 pub struct AttachmentData {
@@ -41,7 +52,89 @@ pub struct AttachmentData {
     pub file_name: Option<String>,
 }
 
+/// a model attachments can belong to, they're found by `(RECORD_TYPE, record_id)`
+///
+/// ```rust,ignore
+/// impl HasAttachments for Todo {
+///     const RECORD_TYPE: &'static str = "todo";
+/// }
+///
+/// Attachment::attach_to::<Todo>(&mut db, &storage, todo.id, "photo".to_string(), data, Some(user_id), true, false).await?;
+/// let attachments = Attachment::list_for::<Todo>(&mut db, todo.id)?;
+/// ```
+pub trait HasAttachments {
+    /// stored in the attachments' `record_type`, must be unique among the app's models
+    const RECORD_TYPE: &'static str;
+
+    /// whether `user_id` (`None` when signed out) may download the [private](`Attachment::private`)
+    /// attachments of the record, nobody can by default
+    fn can_read_private(_db: &mut Connection, _record_id: ID, _user_id: Option<ID>) -> bool {
+        false
+    }
+
+    /// whether `user_id` (`None` when signed out) may add attachments to the record and detach
+    /// them through the [attachment endpoints](`crate::storage::attachment_endpoints`), nobody can
+    /// by default
+    fn can_write(_db: &mut Connection, _record_id: ID, _user_id: Option<ID>) -> bool {
+        false
+    }
+}
+
+/// how long the download links of [private](`Attachment::private`) attachments are valid for, at most
+pub const PRIVATE_LINK_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// an attachment with its blob, see [`Attachment::list_for`]
+pub struct AttachmentInfo {
+    pub id: ID,
+    pub name: String,
+    /// the blob's key in the [`Storage`]
+    pub key: String,
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub byte_size: i64,
+    pub created_at: Utc,
+    pub private: bool,
+    /// a [`ScanStatus`]
+    pub scan_status: String,
+}
+
+impl AttachmentInfo {
+    /// a link to download the attachment valid for `expires_in`, or [`PRIVATE_LINK_TTL`] at most if
+    /// it's [private](`Attachment::private`): private attachments never get public links, so check
+    /// the user can read them first (see [`HasAttachments::can_read_private`]); quarantined
+    /// attachments don't get any
+    pub async fn download_uri(
+        &self,
+        storage: &Storage,
+        expires_in: Option<Duration>,
+    ) -> Result<String, String> {
+        if self.is_quarantined() {
+            return Err(format!(
+                "The attachment is quarantined (id: '{}', key: '{}')",
+                self.id, self.key
+            ));
+        }
+
+        let expires_in = if self.private {
+            Some(expires_in.map_or(PRIVATE_LINK_TTL, |ttl| ttl.min(PRIVATE_LINK_TTL)))
+        } else {
+            expires_in
+        };
+
+        storage.download_uri(self.key.clone(), expires_in).await
+    }
+
+    /// whether the virus scanner found something in the file, see [`ScanStatus::Quarantined`]
+    pub fn is_quarantined(&self) -> bool {
+        self.scan_status == ScanStatus::Quarantined.as_str()
+    }
+}
+
 impl Attachment {
+    /// `user_id` is the uploader's, the file must fit in their
+    /// [`StorageQuota`](`super::StorageQuota`) and counts towards it
+    ///
     /// in actix_web we don't need to support send+sync handlers, so we can use the &mut Connection directly.
     #[allow(clippy::too_many_arguments)]
     #[cfg(feature = "backend_actix-web")]
@@ -52,6 +145,7 @@ impl Attachment {
         record_type: String,
         record_id: ID,
         data: AttachmentData,
+        user_id: Option<ID>,
         allow_multiple: bool,
         overwrite_existing: bool,
     ) -> Result<String, String> {
@@ -65,6 +159,17 @@ impl Attachment {
         .map(|t| t.to_string());
         let key = Uuid::new_v4().to_string();
 
+        if let Some(user_id) = user_id {
+            storage.quota().check(db, user_id, data.data.len() as u64)?;
+        }
+
+        let scan_status = scan::scan_upload(
+            storage,
+            data.file_name.as_deref().unwrap_or_default(),
+            &data.data,
+        )
+        .await?;
+
         if !allow_multiple {
             let existing =
                 Attachment::find_for_record(db, name.clone(), record_type.clone(), record_id);
@@ -87,7 +192,7 @@ impl Attachment {
                 db,
                 &AttachmentBlobChangeset {
                     byte_size: data.data.len() as i64,
-                    service_name: "s3".to_string(),
+                    service_name: storage.service_name().to_string(),
                     key: key.clone(),
                     checksum: checksum.clone(),
                     content_type: content_type.clone(),
@@ -102,6 +207,9 @@ impl Attachment {
                     record_id,
                     record_type,
                     name,
+                    private: false,
+                    user_id,
+                    scan_status: scan_status.as_str().to_string(),
                 },
             )?;
 
@@ -119,15 +227,19 @@ impl Attachment {
             .await
             .map(|_| key);
 
-        if upload_result.is_err() {
+        match &upload_result {
+            Ok(key) => publish_attached(db, attached, key),
             // attempt to delete the attachment
             // if it fails, it fails
-            Attachment::detach(db, storage, attached.id).await?;
+            Err(_) => Attachment::detach(db, storage, attached.id).await?,
         }
 
         upload_result
     }
 
+    /// `user_id` is the uploader's, the file must fit in their
+    /// [`StorageQuota`](`super::StorageQuota`) and counts towards it
+    ///
     /// in poem, we need to pass in the pool itself because the Connection is not Send+Sync which poem handlers require
     #[allow(clippy::too_many_arguments)]
     #[cfg(feature = "backend_poem")]
@@ -138,6 +250,7 @@ impl Attachment {
         record_type: String,
         record_id: ID,
         data: AttachmentData,
+        user_id: Option<ID>,
         allow_multiple: bool,
         overwrite_existing: bool,
     ) -> Result<String, String> {
@@ -153,6 +266,19 @@ impl Attachment {
         .map(|t| t.to_string());
         let key = Uuid::new_v4().to_string();
 
+        if let Some(user_id) = user_id {
+            storage
+                .quota()
+                .check(&mut db, user_id, data.data.len() as u64)?;
+        }
+
+        let scan_status = scan::scan_upload(
+            storage,
+            data.file_name.as_deref().unwrap_or_default(),
+            &data.data,
+        )
+        .await?;
+
         if !allow_multiple {
             let existing =
                 Attachment::find_for_record(&mut db, name.clone(), record_type.clone(), record_id);
@@ -176,7 +302,7 @@ impl Attachment {
                     db,
                     &AttachmentBlobChangeset {
                         byte_size: data.data.len() as i64,
-                        service_name: "s3".to_string(),
+                        service_name: storage.service_name().to_string(),
                         key: key.clone(),
                         checksum: checksum.clone(),
                         content_type: content_type.clone(),
@@ -191,6 +317,9 @@ impl Attachment {
                         record_id,
                         record_type,
                         name,
+                        private: false,
+                        user_id,
+                        scan_status: scan_status.as_str().to_string(),
                     },
                 )?;
 
@@ -208,15 +337,351 @@ impl Attachment {
             .await
             .map(|_| key);
 
-        if upload_result.is_err() {
+        match &upload_result {
+            Ok(key) => publish_attached(&mut db, attached, key),
             // attempt to delete the attachment
             // if it fails, it fails
-            Attachment::detach(pool.clone(), storage, attached.id).await?;
+            Err(_) => Attachment::detach(pool.clone(), storage, attached.id).await?,
         }
 
         upload_result
     }
 
+    /// creates the attachment of a [`DirectUpload`] once the browser uploaded the file, `token` is
+    /// the upload's and `private` marks the attachment [private](`Attachment::private`); see
+    /// [`Attachment::attach`] for the other arguments
+    ///
+    /// in actix_web we don't need to support send+sync handlers, so we can use the &mut Connection directly.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "backend_actix-web")]
+    pub async fn confirm_upload(
+        db: &mut Connection,
+        storage: &Storage,
+        name: String,
+        record_type: String,
+        record_id: ID,
+        token: String,
+        private: bool,
+        user_id: Option<ID>,
+        allow_multiple: bool,
+        overwrite_existing: bool,
+    ) -> Result<String, String> {
+        let (key, file_name) = DirectUpload::verify(&token)?;
+        let stat =
+            storage.provider().stat(&key).await.map_err(|err| {
+                format!("The file was not uploaded (key: '{key}', error: '{err}')")
+            })?;
+        let content_type = mime_guess::from_path(&file_name)
+            .first_raw()
+            .map(|t| t.to_string());
+
+        if let Some(user_id) = user_id {
+            if let Err(err) = storage.quota().check(db, user_id, stat.byte_size) {
+                // the file is already in the storage, it won't be attached
+                storage.delete(key).await.ok();
+                return Err(err);
+            }
+        }
+
+        let scan_status = scan::scan_stored(storage, &key, &file_name).await?;
+
+        if !allow_multiple {
+            let existing =
+                Attachment::find_for_record(db, name.clone(), record_type.clone(), record_id);
+
+            if existing.is_ok() {
+                // one already exists, we need to delete it
+                if overwrite_existing {
+                    Attachment::detach(db, storage, existing.unwrap().id).await.map_err(|_| {
+                        format!("Could not detach the existing attachment for '{name}' attachment on '{record_type}'", name=name.clone(), record_type=record_type.clone())
+                    })?;
+                } else {
+                    // throw the error
+                    return Err(format!("Only 1 attachment is allowed for '{name}' type attachments on '{record_type}'", name=name.clone(), record_type=record_type.clone()));
+                }
+            }
+        }
+
+        let attached = diesel::connection::Connection::transaction::<Self, Error, _>(db, |db| {
+            let blob = AttachmentBlob::create(
+                db,
+                &AttachmentBlobChangeset {
+                    byte_size: stat.byte_size as i64,
+                    service_name: storage.service_name().to_string(),
+                    key: key.clone(),
+                    checksum: stat.checksum.clone(),
+                    content_type: content_type.clone(),
+                    file_name: file_name.clone(),
+                },
+            )?;
+
+            Attachment::create(
+                db,
+                &AttachmentChangeset {
+                    blob_id: blob.id,
+                    record_id,
+                    record_type,
+                    name,
+                    private,
+                    user_id,
+                    scan_status: scan_status.as_str().to_string(),
+                },
+            )
+        })
+        .map_err(|err| err.to_string())?;
+        publish_attached(db, attached, &key);
+
+        Ok(key)
+    }
+
+    /// creates the attachment of a [`DirectUpload`] once the browser uploaded the file, `token` is
+    /// the upload's and `private` marks the attachment [private](`Attachment::private`); see
+    /// [`Attachment::attach`] for the other arguments
+    ///
+    /// in poem, we need to pass in the pool itself because the Connection is not Send+Sync which poem handlers require
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "backend_poem")]
+    pub async fn confirm_upload(
+        pool: std::sync::Arc<&crate::database::Pool>,
+        storage: &Storage,
+        name: String,
+        record_type: String,
+        record_id: ID,
+        token: String,
+        private: bool,
+        user_id: Option<ID>,
+        allow_multiple: bool,
+        overwrite_existing: bool,
+    ) -> Result<String, String> {
+        let mut db = pool.clone().get().unwrap();
+
+        let (key, file_name) = DirectUpload::verify(&token)?;
+        let stat =
+            storage.provider().stat(&key).await.map_err(|err| {
+                format!("The file was not uploaded (key: '{key}', error: '{err}')")
+            })?;
+        let content_type = mime_guess::from_path(&file_name)
+            .first_raw()
+            .map(|t| t.to_string());
+
+        if let Some(user_id) = user_id {
+            if let Err(err) = storage.quota().check(&mut db, user_id, stat.byte_size) {
+                // the file is already in the storage, it won't be attached
+                storage.delete(key).await.ok();
+                return Err(err);
+            }
+        }
+
+        let scan_status = scan::scan_stored(storage, &key, &file_name).await?;
+
+        if !allow_multiple {
+            let existing =
+                Attachment::find_for_record(&mut db, name.clone(), record_type.clone(), record_id);
+
+            if existing.is_ok() {
+                // one already exists, we need to delete it
+                if overwrite_existing {
+                    Attachment::detach(pool.clone(), storage, existing.unwrap().id).await.map_err(|_| {
+                        format!("Could not detach the existing attachment for '{name}' attachment on '{record_type}'", name=name.clone(), record_type=record_type.clone())
+                    })?;
+                } else {
+                    // throw the error
+                    return Err(format!("Only 1 attachment is allowed for '{name}' type attachments on '{record_type}'", name=name.clone(), record_type=record_type.clone()));
+                }
+            }
+        }
+
+        let attached =
+            diesel::connection::Connection::transaction::<Self, Error, _>(&mut db, |db| {
+                let blob = AttachmentBlob::create(
+                    db,
+                    &AttachmentBlobChangeset {
+                        byte_size: stat.byte_size as i64,
+                        service_name: storage.service_name().to_string(),
+                        key: key.clone(),
+                        checksum: stat.checksum.clone(),
+                        content_type: content_type.clone(),
+                        file_name: file_name.clone(),
+                    },
+                )?;
+
+                Attachment::create(
+                    db,
+                    &AttachmentChangeset {
+                        blob_id: blob.id,
+                        record_id,
+                        record_type,
+                        name,
+                        private,
+                        user_id,
+                        scan_status: scan_status.as_str().to_string(),
+                    },
+                )
+            })
+            .map_err(|err| err.to_string())?;
+        publish_attached(&mut db, attached, &key);
+
+        Ok(key)
+    }
+
+    /// [`Attachment::attach`] for a [`HasAttachments`] model
+    ///
+    /// in actix_web we don't need to support send+sync handlers, so we can use the &mut Connection directly.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "backend_actix-web")]
+    pub async fn attach_to<R: HasAttachments>(
+        db: &mut Connection,
+        storage: &Storage,
+        record_id: ID,
+        name: String,
+        data: AttachmentData,
+        user_id: Option<ID>,
+        allow_multiple: bool,
+        overwrite_existing: bool,
+    ) -> Result<String, String> {
+        Attachment::attach(
+            db,
+            storage,
+            name,
+            R::RECORD_TYPE.to_string(),
+            record_id,
+            data,
+            user_id,
+            allow_multiple,
+            overwrite_existing,
+        )
+        .await
+    }
+
+    /// [`Attachment::attach`] for a [`HasAttachments`] model
+    ///
+    /// in poem, we need to pass in the pool itself because the Connection is not Send+Sync which poem handlers require
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "backend_poem")]
+    pub async fn attach_to<R: HasAttachments>(
+        pool: std::sync::Arc<&crate::database::Pool>,
+        storage: &Storage,
+        record_id: ID,
+        name: String,
+        data: AttachmentData,
+        user_id: Option<ID>,
+        allow_multiple: bool,
+        overwrite_existing: bool,
+    ) -> Result<String, String> {
+        Attachment::attach(
+            pool,
+            storage,
+            name,
+            R::RECORD_TYPE.to_string(),
+            record_id,
+            data,
+            user_id,
+            allow_multiple,
+            overwrite_existing,
+        )
+        .await
+    }
+
+    /// every attachment of a [`HasAttachments`] model's record, oldest first
+    pub fn list_for<R: HasAttachments>(
+        db: &mut Connection,
+        record_id: ID,
+    ) -> QueryResult<Vec<AttachmentInfo>> {
+        let attached =
+            Attachment::find_all_for_record_type(db, R::RECORD_TYPE.to_string(), record_id)?;
+        let blobs = AttachmentBlob::find_all_by_id(
+            db,
+            attached.iter().map(|attached| attached.blob_id).collect(),
+        )?;
+
+        let mut infos = attached
+            .into_iter()
+            .filter_map(|attached| {
+                let blob = blobs.iter().find(|blob| blob.id == attached.blob_id)?;
+
+                Some(AttachmentInfo {
+                    id: attached.id,
+                    name: attached.name,
+                    key: blob.key.clone(),
+                    file_name: blob.file_name.clone(),
+                    content_type: blob.content_type.clone(),
+                    byte_size: blob.byte_size,
+                    created_at: attached.created_at,
+                    private: attached.private,
+                    scan_status: attached.scan_status,
+                })
+            })
+            .collect::<Vec<_>>();
+        infos.sort_by_key(|info| info.id);
+
+        Ok(infos)
+    }
+
+    /// marks the attachment [private](`Attachment::private`), or public
+    pub fn set_private(db: &mut Connection, item_id: ID, private: bool) -> QueryResult<Self> {
+        diesel::update(schema::attachments::table.filter(schema::attachments::id.eq(item_id)))
+            .set(schema::attachments::private.eq(private))
+            .get_result(db)
+    }
+
+    /// records what the virus scanner found, ex: after scanning the file again
+    pub fn set_scan_status(
+        db: &mut Connection,
+        item_id: ID,
+        status: ScanStatus,
+    ) -> QueryResult<Self> {
+        diesel::update(schema::attachments::table.filter(schema::attachments::id.eq(item_id)))
+            .set(schema::attachments::scan_status.eq(status.as_str()))
+            .get_result(db)
+    }
+
+    fn find_for<R: HasAttachments>(
+        db: &mut Connection,
+        record_id: ID,
+        item_id: ID,
+    ) -> QueryResult<Self> {
+        schema::attachments::table
+            .filter(schema::attachments::id.eq(item_id))
+            .filter(schema::attachments::record_type.eq(R::RECORD_TYPE))
+            .filter(schema::attachments::record_id.eq(record_id))
+            .first::<Self>(db)
+    }
+
+    /// [`Attachment::detach`], only if `item_id` belongs to the [`HasAttachments`] model's record
+    ///
+    /// in actix_web we don't need to support send+sync handlers, so we can use the &mut Connection directly.
+    #[cfg(feature = "backend_actix-web")]
+    pub async fn detach_from<R: HasAttachments>(
+        db: &mut Connection,
+        storage: &Storage,
+        record_id: ID,
+        item_id: ID,
+    ) -> Result<(), String> {
+        Attachment::find_for::<R>(db, record_id, item_id)
+            .map_err(|_| "Could not load attachment")?;
+
+        Attachment::detach(db, storage, item_id).await
+    }
+
+    /// [`Attachment::detach`], only if `item_id` belongs to the [`HasAttachments`] model's record
+    ///
+    /// in poem, we need to pass in the pool itself because the Connection is not Send+Sync which poem handlers require
+    #[cfg(feature = "backend_poem")]
+    pub async fn detach_from<R: HasAttachments>(
+        pool: std::sync::Arc<&crate::database::Pool>,
+        storage: &Storage,
+        record_id: ID,
+        item_id: ID,
+    ) -> Result<(), String> {
+        {
+            let mut db = pool.get().unwrap();
+            Attachment::find_for::<R>(&mut db, record_id, item_id)
+                .map_err(|_| "Could not load attachment")?;
+        }
+
+        Attachment::detach(pool, storage, item_id).await
+    }
+
     /// in actix_web we don't need to support send+sync handlers, so we can use the &mut Connection directly.
     #[cfg(feature = "backend_actix-web")]
     pub async fn detach(db: &mut Connection, storage: &Storage, item_id: ID) -> Result<(), String> {
@@ -242,6 +707,7 @@ impl Attachment {
             Ok(())
         })
         .map_err(|err| err.to_string())?;
+        publish_detached(db, attached, blob.key);
 
         Ok(())
     }
@@ -278,10 +744,169 @@ impl Attachment {
             Ok(())
         })
         .map_err(|err| err.to_string())?;
+        publish_detached(&mut db, attached, blob.key);
 
         Ok(())
     }
 
+    /// [`Attachment::confirm_upload`] for the [attachment endpoints](`crate::storage::attachment_endpoints`),
+    /// which always overwrite the existing attachment when `allow_multiple` is `false`: connections
+    /// are only taken from `db`'s pool for each query, which run on actix's blocking threads
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "backend_actix-web")]
+    pub(crate) async fn confirm_upload_with(
+        db: &crate::Database,
+        storage: &Storage,
+        name: String,
+        record_type: String,
+        record_id: ID,
+        token: String,
+        private: bool,
+        user_id: Option<ID>,
+        allow_multiple: bool,
+    ) -> Result<String, String> {
+        let (key, file_name) = DirectUpload::verify(&token)?;
+        let stat =
+            storage.provider().stat(&key).await.map_err(|err| {
+                format!("The file was not uploaded (key: '{key}', error: '{err}')")
+            })?;
+        let content_type = mime_guess::from_path(&file_name)
+            .first_raw()
+            .map(|t| t.to_string());
+
+        if let Some(user_id) = user_id {
+            let quota = storage.quota().clone();
+            let byte_size = stat.byte_size;
+
+            if let Err(err) = blocking(db, move |db| quota.check(db, user_id, byte_size)).await {
+                // the file is already in the storage, it won't be attached
+                storage.delete(key).await.ok();
+                return Err(err);
+            }
+        }
+
+        let scan_status = scan::scan_stored(storage, &key, &file_name).await?;
+
+        if !allow_multiple {
+            let existing = {
+                let (name, record_type) = (name.clone(), record_type.clone());
+                blocking(db, move |db| {
+                    Ok(Attachment::find_for_record(db, name, record_type, record_id).ok())
+                })
+                .await?
+            };
+
+            // one already exists, we need to delete it
+            if let Some(existing) = existing {
+                Attachment::detach_with(db, storage, existing.id).await.map_err(|err| {
+                    if err.starts_with(DATABASE_UNAVAILABLE) {
+                        err
+                    } else {
+                        format!("Could not detach the existing attachment for '{name}' attachment on '{record_type}'")
+                    }
+                })?;
+            }
+        }
+
+        let service_name = storage.service_name();
+        let blob_key = key.clone();
+        blocking(db, move |db| {
+            let attached =
+                diesel::connection::Connection::transaction::<Self, Error, _>(db, |db| {
+                    let blob = AttachmentBlob::create(
+                        db,
+                        &AttachmentBlobChangeset {
+                            byte_size: stat.byte_size as i64,
+                            service_name: service_name.to_string(),
+                            key: blob_key.clone(),
+                            checksum: stat.checksum.clone(),
+                            content_type: content_type.clone(),
+                            file_name: file_name.clone(),
+                        },
+                    )?;
+
+                    Attachment::create(
+                        db,
+                        &AttachmentChangeset {
+                            blob_id: blob.id,
+                            record_id,
+                            record_type,
+                            name,
+                            private,
+                            user_id,
+                            scan_status: scan_status.as_str().to_string(),
+                        },
+                    )
+                })
+                .map_err(|err| err.to_string())?;
+            publish_attached(db, attached, &blob_key);
+
+            Ok(())
+        })
+        .await?;
+
+        Ok(key)
+    }
+
+    /// [`Attachment::detach_from`] for the [attachment endpoints](`crate::storage::attachment_endpoints`),
+    /// see [`Attachment::confirm_upload_with`]
+    #[cfg(feature = "backend_actix-web")]
+    pub(crate) async fn detach_from_with<R: HasAttachments + 'static>(
+        db: &crate::Database,
+        storage: &Storage,
+        record_id: ID,
+        item_id: ID,
+    ) -> Result<(), String> {
+        blocking(db, move |db| {
+            Attachment::find_for::<R>(db, record_id, item_id)
+                .map(|_| ())
+                .map_err(|_| "Could not load attachment".to_string())
+        })
+        .await?;
+
+        Attachment::detach_with(db, storage, item_id).await
+    }
+
+    /// [`Attachment::detach`], see [`Attachment::confirm_upload_with`]
+    #[cfg(feature = "backend_actix-web")]
+    async fn detach_with(
+        db: &crate::Database,
+        storage: &Storage,
+        item_id: ID,
+    ) -> Result<(), String> {
+        let (attached, blob) = blocking(db, move |db| {
+            let attached =
+                Attachment::find_by_id(db, item_id).map_err(|_| "Could not load attachment")?;
+            let blob = AttachmentBlob::find_by_id(db, attached.blob_id)
+                .map_err(|_| "Could not load attachment blob")?;
+
+            Ok((attached, blob))
+        })
+        .await?;
+
+        let delete_result = storage.delete(blob.key.clone()).await;
+
+        if delete_result.is_err() {
+            // we continue even if there's an error deleting the actual object
+            println!("{}", delete_result.err().unwrap());
+        }
+
+        blocking(db, move |db| {
+            diesel::connection::Connection::transaction::<(), Error, _>(db, |db| {
+                // delete the attachment first because it references the blobs
+                Attachment::delete(db, attached.id)?;
+                AttachmentBlob::delete(db, blob.id)?;
+
+                Ok(())
+            })
+            .map_err(|err| err.to_string())?;
+            publish_detached(db, attached, blob.key);
+
+            Ok(())
+        })
+        .await
+    }
+
     pub async fn detach_all(
         db: &mut Connection,
         storage: &Storage,
@@ -324,6 +949,12 @@ impl Attachment {
         })
         .map_err(|err| err.to_string())?;
 
+        for attached in attached {
+            if let Some(blob) = blobs.iter().find(|blob| blob.id == attached.blob_id) {
+                publish_detached(db, attached, blob.key.clone());
+            }
+        }
+
         Ok(())
     }
 
@@ -380,6 +1011,59 @@ impl Attachment {
             .get_results::<Self>(db)
     }
 
+    /// the bytes taken by the files `user_id` uploaded, see [`StorageQuota`](`super::StorageQuota`)
+    pub fn total_bytes_for_user(db: &mut Connection, user_id: ID) -> QueryResult<i64> {
+        let byte_sizes = schema::attachment_blobs::table
+            .filter(
+                schema::attachment_blobs::id.eq_any(
+                    schema::attachments::table
+                        .filter(schema::attachments::user_id.eq(user_id))
+                        .select(schema::attachments::blob_id),
+                ),
+            )
+            .select(schema::attachment_blobs::byte_size)
+            .load::<i64>(db)?;
+
+        Ok(byte_sizes.into_iter().sum())
+    }
+
+    /// every attachment on the record, whatever its name
+    pub fn find_all_for_record_type(
+        db: &mut Connection,
+        item_record_type: String,
+        item_record_id: ID,
+    ) -> QueryResult<Vec<Self>> {
+        schema::attachments::table
+            .filter(schema::attachments::record_type.eq(item_record_type))
+            .filter(schema::attachments::record_id.eq(item_record_id))
+            .get_results::<Self>(db)
+    }
+
+    /// deletes every attachment on the record, and their blobs, without touching the stored objects
+    ///
+    /// returns the keys of the deleted blobs so the objects can be removed with [`Storage::delete_many`]
+    pub fn delete_all_for_record(
+        db: &mut Connection,
+        item_record_type: String,
+        item_record_id: ID,
+    ) -> QueryResult<Vec<String>> {
+        let attached = Attachment::find_all_for_record_type(db, item_record_type, item_record_id)?;
+        let blob_ids = attached
+            .iter()
+            .map(|attached| attached.blob_id)
+            .collect::<Vec<_>>();
+        let keys = AttachmentBlob::find_all_by_id(db, blob_ids.clone())?
+            .into_iter()
+            .map(|blob| blob.key)
+            .collect::<Vec<_>>();
+
+        // delete the attachments first because they reference the blobs
+        Attachment::delete_all(db, attached.iter().map(|attached| attached.id).collect())?;
+        AttachmentBlob::delete_all(db, blob_ids)?;
+
+        Ok(keys)
+    }
+
     // fn update(db: &mut Connection, item_id: ID, item: &AttachmentChangeset) -> QueryResult<Self> {
     //     use super::schema::attachments::dsl::*;
     //
@@ -400,3 +1084,48 @@ impl Attachment {
         diesel::delete(attachments.filter(schema::attachments::id.eq_any(item_ids))).execute(db)
     }
 }
+
+/// publishes [`AttachmentAttached`](`super::AttachmentAttached`) with the events plugin
+#[allow(unused_variables)]
+fn publish_attached(db: &mut Connection, attachment: Attachment, key: &str) {
+    #[cfg(feature = "plugin_events")]
+    crate::events::publish_with(
+        db,
+        super::AttachmentAttached {
+            attachment,
+            key: key.to_string(),
+        },
+    );
+}
+
+/// how the errors of [`blocking`] start when the pool has no connection to spare
+#[cfg(feature = "backend_actix-web")]
+pub(crate) const DATABASE_UNAVAILABLE: &str = "No database connection available";
+
+/// runs `query` with a connection from `db`'s pool on actix's blocking threads, so neither the
+/// query nor waiting for a connection blocks the async workers
+#[cfg(feature = "backend_actix-web")]
+pub(crate) async fn blocking<T, F>(db: &crate::Database, query: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<T, String> + Send + 'static,
+{
+    let pool = db.pool.clone();
+
+    actix_web::web::block(move || {
+        let mut db = pool
+            .get()
+            .map_err(|err| format!("{DATABASE_UNAVAILABLE} ({err})"))?;
+
+        query(&mut db)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// publishes [`AttachmentDetached`](`super::AttachmentDetached`) with the events plugin
+#[allow(unused_variables)]
+fn publish_detached(db: &mut Connection, attachment: Attachment, key: String) {
+    #[cfg(feature = "plugin_events")]
+    crate::events::publish_with(db, super::AttachmentDetached { attachment, key });
+}
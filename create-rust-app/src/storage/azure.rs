@@ -0,0 +1,199 @@
+//! Keeps files in an Azure Blob Storage container (`plugin_storage_azure` feature).
+//!
+//! Enabled by setting `AZURE_STORAGE_CONTAINER` in your `.env` (the `S3_*` variables are ignored then):
+//!
+//! ```text
+//! AZURE_STORAGE_ACCOUNT=myaccount
+//! AZURE_STORAGE_ACCESS_KEY=...
+//! AZURE_STORAGE_CONTAINER=attachments
+//! ```
+//!
+//! Links from [`Storage::download_uri`](`super::Storage::download_uri`) and
+//! [`Storage::upload_uri`](`super::Storage::upload_uri`) are SAS urls signed with the account key.
+//! Uploads must be sent with the [`PresignedURI`]'s headers (`x-ms-blob-type: BlockBlob`).
+use std::str::FromStr;
+use std::time::Duration;
+
+use azure_storage::StorageCredentials;
+use azure_storage_blobs::prelude::{
+    BlobClient, BlobSasPermissions, ClientBuilder, ContainerClient,
+};
+use http::header::HeaderValue;
+use http::{HeaderMap, Uri};
+
+use super::{PresignAction, PresignedURI, StorageFuture, StorageProvider};
+
+#[derive(Clone)]
+/// an Azure Blob Storage container, see the [module documentation](`self`)
+pub struct AzureProvider {
+    container: ContainerClient,
+    account: String,
+    container_name: String,
+}
+
+impl AzureProvider {
+    pub fn new(account: String, access_key: String, container: String) -> AzureProvider {
+        let credentials = StorageCredentials::access_key(account.clone(), access_key);
+        let container_client =
+            ClientBuilder::new(account.clone(), credentials).container_client(container.clone());
+
+        AzureProvider {
+            container: container_client,
+            account,
+            container_name: container,
+        }
+    }
+
+    /// reads `AZURE_STORAGE_ACCOUNT`, `AZURE_STORAGE_ACCESS_KEY` and `AZURE_STORAGE_CONTAINER`
+    pub fn from_env() -> AzureProvider {
+        AzureProvider::check_environment_variables();
+
+        let account = std::env::var("AZURE_STORAGE_ACCOUNT").unwrap_or_else(|_| "".to_string());
+        let access_key =
+            std::env::var("AZURE_STORAGE_ACCESS_KEY").unwrap_or_else(|_| "".to_string());
+        let container = std::env::var("AZURE_STORAGE_CONTAINER").unwrap_or_else(|_| "".to_string());
+
+        AzureProvider::new(account, access_key, container)
+    }
+
+    fn blob(&self, key: &str) -> BlobClient {
+        self.container.blob_client(key)
+    }
+
+    fn error_string(&self, message: &'static str, key: String, error: String) -> String {
+        let account = &self.account;
+        let container = &self.container_name;
+        format!("{message} (account: '{account}', container: '{container}', key: '{key}', error: '{error}')")
+    }
+
+    fn check_environment_variables() {
+        let vars = vec![
+            "AZURE_STORAGE_ACCOUNT",
+            "AZURE_STORAGE_ACCESS_KEY",
+            "AZURE_STORAGE_CONTAINER",
+        ];
+
+        let unset_vars = vars
+            .into_iter()
+            .filter(|v| std::env::var(v).is_err())
+            .collect::<Vec<_>>();
+
+        if !unset_vars.is_empty() {
+            println!(
+                "Warning: Storage disabled; the following variables must be set: {}",
+                unset_vars.join(", ")
+            );
+        }
+    }
+}
+
+impl StorageProvider for AzureProvider {
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        bytes: Vec<u8>,
+        content_type: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            self.blob(key)
+                .put_block_blob(bytes)
+                .content_type(content_type.to_string())
+                .await
+                .map_err(|err| {
+                    self.error_string("Could not upload object", key.to_string(), err.to_string())
+                })?;
+
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            self.blob(key).get_content().await.map_err(|err| {
+                self.error_string(
+                    "Could not download object",
+                    key.to_string(),
+                    err.to_string(),
+                )
+            })
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            match self.blob(key).delete().await {
+                Ok(_) => Ok(()),
+                Err(err)
+                    if err.as_http_error().map_or(false, |err| {
+                        err.status() == azure_core::StatusCode::NotFound
+                    }) =>
+                {
+                    Ok(())
+                }
+                Err(err) => Err(self.error_string(
+                    "Could not delete object",
+                    key.to_string(),
+                    err.to_string(),
+                )),
+            }
+        })
+    }
+
+    /// if `expires_in` is `None`, then we assume the container is publicly readable and return the
+    /// blob's URL
+    fn presign<'a>(
+        &'a self,
+        key: &'a str,
+        action: PresignAction,
+        expires_in: Option<Duration>,
+    ) -> StorageFuture<'a, PresignedURI> {
+        Box::pin(async move {
+            let message = match action {
+                PresignAction::Get => "Could not retrieve download URI",
+                PresignAction::Put => "Could not retrieve upload URI",
+            };
+            let error = |err: String| self.error_string(message, key.to_string(), err);
+
+            let blob = self.blob(key);
+
+            let url = match expires_in {
+                None => blob.url().map_err(|err| error(err.to_string()))?,
+                Some(expires_in) => {
+                    let permissions = match action {
+                        PresignAction::Get => BlobSasPermissions {
+                            read: true,
+                            ..Default::default()
+                        },
+                        PresignAction::Put => BlobSasPermissions {
+                            create: true,
+                            write: true,
+                            ..Default::default()
+                        },
+                    };
+                    let expiry = time::OffsetDateTime::now_utc() + expires_in;
+
+                    let signature = blob
+                        .shared_access_signature(permissions, expiry)
+                        .map_err(|err| error(err.to_string()))?;
+
+                    blob.generate_signed_blob_url(&signature)
+                        .map_err(|err| error(err.to_string()))?
+                }
+            };
+
+            let mut headers = HeaderMap::new();
+            if action == PresignAction::Put {
+                headers.insert("x-ms-blob-type", HeaderValue::from_static("BlockBlob"));
+            }
+
+            Ok(PresignedURI {
+                uri: Uri::from_str(url.as_str()).map_err(|err| error(err.to_string()))?,
+                headers,
+            })
+        })
+    }
+}
@@ -0,0 +1,132 @@
+//! Serves files through a CDN whose origin is the storage's bucket, and the `Cache-Control` headers
+//! of the files the backend serves.
+//!
+//! Enabled by setting `CDN_URL` in your `.env`:
+//!
+//! ```text
+//! CDN_URL=https://d111111abcdef8.cloudfront.net
+//! ```
+//!
+//! [`Storage::download_uri`](`super::Storage::download_uri`) then returns `{CDN_URL}/{key}` links
+//! when no expiration is given. Expiring links (ex: of private attachments) are signed by the CDN's
+//! [`UrlSigner`], or are the provider's presigned links when it has none. With the
+//! `plugin_storage_cloudfront` feature, CloudFront signed urls are used when
+//! `CLOUDFRONT_KEY_PAIR_ID` and `CLOUDFRONT_PRIVATE_KEY` are set, see `CloudFrontSigner`.
+//!
+//! Cache headers depend on the files' content type:
+//!
+//! ```rust,ignore
+//! let storage = Storage::new().with_cache_policy(
+//!     CachePolicy::new()
+//!         .rule("image/*", "public, max-age=31536000, immutable")
+//!         .rule("video/*", "public, max-age=86400")
+//!         .rule("*", "private, no-cache"),
+//! );
+//! ```
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "plugin_storage_cloudfront")]
+use super::CloudFrontSigner;
+
+/// signs CDN links so they can only be used until they expire
+pub trait UrlSigner: Send + Sync {
+    /// `url` with the parameters allowing access to it until `expires_at`
+    fn sign(&self, url: &str, expires_at: SystemTime) -> Result<String, String>;
+}
+
+#[derive(Clone)]
+/// a CDN in front of the storage, see the [module documentation](`self`)
+pub struct Cdn {
+    base_url: String,
+    signer: Option<Arc<dyn UrlSigner>>,
+}
+
+impl Cdn {
+    /// files are served at `{base_url}/{key}`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            signer: None,
+        }
+    }
+
+    /// signs expiring links with `signer`
+    pub fn with_signer(mut self, signer: impl UrlSigner + 'static) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// the CDN at `CDN_URL`, if it's set
+    pub fn from_env() -> Option<Self> {
+        let base_url = std::env::var("CDN_URL")
+            .ok()
+            .filter(|url| !url.is_empty())?;
+
+        #[allow(unused_mut)]
+        let mut cdn = Cdn::new(base_url);
+
+        #[cfg(feature = "plugin_storage_cloudfront")]
+        if std::env::var("CLOUDFRONT_KEY_PAIR_ID").map_or(false, |id| !id.is_empty()) {
+            cdn = cdn.with_signer(CloudFrontSigner::from_env());
+        }
+
+        Some(cdn)
+    }
+
+    /// the public link of `key`
+    pub fn url(&self, key: &str) -> String {
+        format!("{}/{key}", self.base_url)
+    }
+
+    /// a link to `key` valid for `expires_in`, `None` when the CDN has no [`UrlSigner`]
+    pub fn signed_url(&self, key: &str, expires_in: Duration) -> Result<Option<String>, String> {
+        self.signer
+            .as_ref()
+            .map(|signer| signer.sign(&self.url(key), SystemTime::now() + expires_in))
+            .transpose()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// the `Cache-Control` header of files, by content type, see the [module documentation](`self`)
+pub struct CachePolicy {
+    rules: Vec<(String, String)>,
+}
+
+impl CachePolicy {
+    /// no `Cache-Control` headers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// files whose content type matches `pattern` (ex: "image/png", "image/*" or "*") get
+    /// `cache_control`, rules are tried in the order they're added
+    pub fn rule(mut self, pattern: impl Into<String>, cache_control: impl Into<String>) -> Self {
+        self.rules.push((pattern.into(), cache_control.into()));
+        self
+    }
+
+    /// the `Cache-Control` header of files of `content_type`, from the first matching rule
+    pub fn cache_control(&self, content_type: &str) -> Option<&str> {
+        let content_type = content_type.split(';').next().unwrap_or_default().trim();
+
+        self.rules
+            .iter()
+            .find(|(pattern, _)| {
+                if pattern == "*" {
+                    return true;
+                }
+
+                match pattern.strip_suffix("/*") {
+                    Some(kind) => content_type
+                        .split_once('/')
+                        .map_or(false, |(content_kind, _)| {
+                            content_kind.eq_ignore_ascii_case(kind)
+                        }),
+                    None => pattern.eq_ignore_ascii_case(content_type),
+                }
+            })
+            .map(|(_, cache_control)| cache_control.as_str())
+    }
+}
@@ -0,0 +1,95 @@
+//! Signs CloudFront links with a canned policy (`plugin_storage_cloudfront` feature), see
+//! [`Cdn`](`super::Cdn`).
+//!
+//! Enabled by setting the distribution's key pair in your `.env`, along with `CDN_URL`:
+//!
+//! ```text
+//! CDN_URL=https://d111111abcdef8.cloudfront.net
+//! CLOUDFRONT_KEY_PAIR_ID=K2JCJMDEHXQW5F
+//! # the key pair's private key, its path or its PEM
+//! CLOUDFRONT_PRIVATE_KEY=./cloudfront.pem
+//! ```
+//!
+//! The key pair's public key must be in a trusted key group of the distribution's behavior.
+use std::time::SystemTime;
+
+use base64::Engine;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+use sha1::{Digest, Sha1};
+
+use super::UrlSigner;
+
+#[derive(Clone)]
+/// signs CloudFront links, see the [module documentation](`self`)
+pub struct CloudFrontSigner {
+    key_pair_id: String,
+    private_key: RsaPrivateKey,
+}
+
+impl CloudFrontSigner {
+    /// `private_key` is the key pair's PEM (PKCS#1 or PKCS#8)
+    pub fn new(key_pair_id: impl Into<String>, private_key: &str) -> Result<Self, String> {
+        let private_key = RsaPrivateKey::from_pkcs8_pem(private_key)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(private_key))
+            .map_err(|err| format!("Invalid CloudFront private key (error: '{err}')"))?;
+
+        Ok(Self {
+            key_pair_id: key_pair_id.into(),
+            private_key,
+        })
+    }
+
+    /// the key pair in `CLOUDFRONT_KEY_PAIR_ID` and `CLOUDFRONT_PRIVATE_KEY` (a path or a PEM)
+    pub fn from_env() -> Self {
+        let key_pair_id = std::env::var("CLOUDFRONT_KEY_PAIR_ID")
+            .expect("No CLOUDFRONT_KEY_PAIR_ID environment variable set!");
+        let private_key = std::env::var("CLOUDFRONT_PRIVATE_KEY")
+            .expect("No CLOUDFRONT_PRIVATE_KEY environment variable set!");
+
+        let private_key = if private_key.trim_start().starts_with("-----BEGIN") {
+            private_key
+        } else {
+            std::fs::read_to_string(&private_key)
+                .unwrap_or_else(|err| panic!("Could not read '{private_key}': {err}"))
+        };
+
+        Self::new(key_pair_id, &private_key).unwrap()
+    }
+}
+
+impl UrlSigner for CloudFrontSigner {
+    /// adds the `Expires`, `Signature` and `Key-Pair-Id` parameters of a canned policy
+    fn sign(&self, url: &str, expires_at: SystemTime) -> Result<String, String> {
+        let expires = expires_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let policy = format!(
+            r#"{{"Statement":[{{"Resource":"{url}","Condition":{{"DateLessThan":{{"AWS:EpochTime":{expires}}}}}}}]}}"#
+        );
+
+        let signature = self
+            .private_key
+            .sign(
+                Pkcs1v15Sign::new::<Sha1>(),
+                &Sha1::digest(policy.as_bytes()),
+            )
+            .map_err(|err| format!("Could not sign link (url: '{url}', error: '{err}')"))?;
+
+        // CloudFront's url-safe base64
+        let signature = base64::engine::general_purpose::STANDARD
+            .encode(signature)
+            .replace('+', "-")
+            .replace('=', "_")
+            .replace('/', "~");
+
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let key_pair_id = &self.key_pair_id;
+
+        Ok(format!(
+            "{url}{separator}Expires={expires}&Signature={signature}&Key-Pair-Id={key_pair_id}"
+        ))
+    }
+}
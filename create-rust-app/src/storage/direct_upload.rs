@@ -0,0 +1,126 @@
+//! Uploads sent by the browser straight to the storage, so large files don't stream through the
+//! backend:
+//!
+//! 1. the backend [prepares](`DirectUpload::prepare`) the upload and sends it to the browser
+//! 2. the browser `PUT`s the file at its `url`, with its `headers`
+//! 3. the browser sends the `token` back and the backend creates the attachment with
+//!    [`Attachment::confirm_upload`](`super::Attachment::confirm_upload`)
+//!
+//! The `token` is signed with the `SECRET_KEY`, so only keys handed out by the backend can be
+//! confirmed.
+use std::collections::HashMap;
+use std::time::Duration;
+
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{PresignedURI, Storage};
+
+/// how long [`DirectUpload`] links are valid for by default
+pub const DEFAULT_UPLOAD_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// how long after the link expires the upload can still be confirmed, for uploads started just
+/// before it did
+const CONFIRM_LEEWAY: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// an upload the browser can send to the storage, see the [module documentation](`self`)
+pub struct DirectUpload {
+    pub key: String,
+    /// where the file must be `PUT`
+    pub url: String,
+    /// headers the `PUT` request must be sent with
+    pub headers: HashMap<String, String>,
+    /// sent back to confirm the upload
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UploadClaims {
+    /// the file's key
+    sub: String,
+    file_name: String,
+    token_type: String,
+    exp: usize,
+}
+
+impl DirectUpload {
+    /// a new key for `file_name`, and a link to upload it valid for `expires_in`
+    pub async fn prepare(
+        storage: &Storage,
+        file_name: String,
+        expires_in: Duration,
+    ) -> Result<DirectUpload, String> {
+        let key = Uuid::new_v4().to_string();
+
+        let presigned = storage.upload_uri(key.clone(), expires_in).await?;
+
+        let headers = headers(&presigned);
+        let token = DirectUpload::sign(&key, file_name, expires_in + CONFIRM_LEEWAY)?;
+
+        Ok(DirectUpload {
+            key,
+            url: presigned.uri.to_string(),
+            headers,
+            token,
+        })
+    }
+
+    /// a token confirming `key` was uploaded as `file_name`, valid for `valid_for`
+    pub(crate) fn sign(
+        key: &str,
+        file_name: String,
+        valid_for: Duration,
+    ) -> Result<String, String> {
+        let claims = UploadClaims {
+            sub: key.to_string(),
+            file_name,
+            token_type: "direct_upload_token".to_string(),
+            exp: (std::time::SystemTime::now() + valid_for)
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as usize,
+        };
+
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret_key().as_bytes()),
+        )
+        .map_err(|err| {
+            let error = err.to_string();
+            format!("Could not sign upload (key: '{key}', error: '{error}')")
+        })
+    }
+
+    /// the key and file name of a [prepared](`DirectUpload::prepare`) upload's `token`
+    pub(crate) fn verify(token: &str) -> Result<(String, String), String> {
+        let claims = jsonwebtoken::decode::<UploadClaims>(
+            token,
+            &DecodingKey::from_secret(secret_key().as_bytes()),
+            &Validation::default(),
+        )
+        .map_err(|_| "Invalid or expired upload token".to_string())?
+        .claims;
+
+        if claims.token_type != "direct_upload_token" {
+            return Err("Invalid or expired upload token".to_string());
+        }
+
+        Ok((claims.sub, claims.file_name))
+    }
+}
+
+/// the headers of a presigned link, for the browser
+pub(crate) fn headers(presigned: &PresignedURI) -> HashMap<String, String> {
+    presigned
+        .headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+pub(crate) fn secret_key() -> String {
+    std::env::var("SECRET_KEY").expect("No SECRET_KEY environment variable set!")
+}
@@ -0,0 +1,363 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Json, Path};
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::storage::attachment::{blocking, DATABASE_UNAVAILABLE};
+use crate::storage::quota::QUOTA_EXCEEDED;
+use crate::storage::scan::FILE_REJECTED;
+use crate::storage::{
+    Attachment, AttachmentInfo, DirectUpload, HasAttachments, Storage, DEFAULT_UPLOAD_TTL, ID,
+};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// body of requests to the .../{id}/attachments/presign endpoint
+pub struct PresignInput {
+    file_name: String,
+}
+
+#[derive(Deserialize)]
+/// body of requests to the .../{id}/attachments/confirm endpoint
+pub struct ConfirmInput {
+    /// the attachment's name (ex: "photo")
+    name: String,
+    /// the [`DirectUpload`]'s token
+    token: String,
+    /// keep the record's other attachments with the same name (default: `true`)
+    #[serde(default = "default_allow_multiple")]
+    allow_multiple: bool,
+    /// see [`Attachment::private`] (default: `false`)
+    #[serde(default)]
+    private: bool,
+}
+
+fn default_allow_multiple() -> bool {
+    true
+}
+
+#[derive(Serialize)]
+/// an attachment, with a link to download it unless it's private and the user can't read it
+pub struct AttachmentResponse {
+    #[serde(flatten)]
+    info: AttachmentInfo,
+    url: Option<String>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// the signed in user, if any
+async fn user_id(req: &HttpRequest) -> Option<ID> {
+    #[cfg(feature = "plugin_auth")]
+    {
+        use actix_web::FromRequest;
+
+        crate::auth::Auth::extract(req)
+            .await
+            .ok()
+            .map(|auth| auth.user_id)
+    }
+    #[cfg(not(feature = "plugin_auth"))]
+    {
+        let _ = req;
+        None
+    }
+}
+
+/// responds with `500 Internal Server Error` when the pool has no connection to spare, with
+/// `status_code` and `message` otherwise
+fn database_error(err: String, status_code: StatusCode, message: &'static str) -> HttpResponse {
+    println!("{err}");
+
+    if err.starts_with(DATABASE_UNAVAILABLE) {
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "No database connection available.",
+        )
+    } else {
+        error_response(status_code, message)
+    }
+}
+
+/// errors unless the signed in user may change the record's attachments, see [`HasAttachments::can_write`]
+async fn can_write<R: HasAttachments + 'static>(
+    req: &HttpRequest,
+    db: &Database,
+    record_id: ID,
+) -> Result<(), HttpResponse> {
+    let user_id = user_id(req).await;
+
+    match blocking(db, move |db| Ok(R::can_write(db, record_id, user_id))).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(error_response(
+            StatusCode::FORBIDDEN,
+            "You can't change this record's attachments.",
+        )),
+        Err(err) => Err(database_error(
+            err,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "No database connection available.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../{id}/attachments endpoint
+///
+/// responds with the record's attachments and links to download them, private attachments only
+/// get one if [`HasAttachments::can_read_private`] allows the user and quarantined ones never do
+async fn list<R: HasAttachments + 'static>(
+    req: HttpRequest,
+    db: Data<Database>,
+    storage: Data<Storage>,
+    record_id: Path<ID>,
+) -> Result<HttpResponse> {
+    let record_id = record_id.into_inner();
+    let user_id = user_id(&req).await;
+
+    let result = blocking(&db, move |db| {
+        let attachments =
+            Attachment::list_for::<R>(db, record_id).map_err(|err| err.to_string())?;
+        let can_read_private = attachments.iter().any(|info| info.private)
+            && R::can_read_private(db, record_id, user_id);
+
+        Ok((attachments, can_read_private))
+    })
+    .await;
+
+    let (attachments, can_read_private) = match result {
+        Ok(result) => result,
+        Err(err) => {
+            return Ok(database_error(
+                err,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not load attachments.",
+            ))
+        }
+    };
+
+    let mut response = vec![];
+    for info in attachments {
+        if info.is_quarantined() || (info.private && !can_read_private) {
+            response.push(AttachmentResponse { info, url: None });
+            continue;
+        }
+
+        match info
+            .download_uri(&storage, Some(super::ATTACHMENT_LINK_TTL))
+            .await
+        {
+            Ok(url) => response.push(AttachmentResponse {
+                info,
+                url: Some(url),
+            }),
+            Err(err) => {
+                println!("{err}");
+                return Ok(error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Could not load attachments.",
+                ));
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// handler for GET requests at the .../{id}/attachments/{attachment_id}/url endpoint
+///
+/// responds with a fresh link to download the attachment, private attachments require
+/// [`HasAttachments::can_read_private`] to allow the user
+async fn url<R: HasAttachments + 'static>(
+    req: HttpRequest,
+    db: Data<Database>,
+    storage: Data<Storage>,
+    path: Path<(ID, ID)>,
+) -> Result<HttpResponse> {
+    let (record_id, attachment_id) = path.into_inner();
+    let user_id = user_id(&req).await;
+
+    let result = blocking(&db, move |db| {
+        let info = Attachment::list_for::<R>(db, record_id)
+            .ok()
+            .and_then(|infos| infos.into_iter().find(|info| info.id == attachment_id));
+        let can_read = match &info {
+            Some(info) if info.private => R::can_read_private(db, record_id, user_id),
+            _ => true,
+        };
+
+        Ok((info, can_read))
+    })
+    .await;
+
+    let info = match result {
+        Err(err) => {
+            return Ok(database_error(
+                err,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not sign the link.",
+            ))
+        }
+        Ok((None, _)) => {
+            return Ok(error_response(
+                StatusCode::NOT_FOUND,
+                "Attachment not found.",
+            ))
+        }
+        Ok((Some(info), _)) if info.is_quarantined() => {
+            return Ok(error_response(
+                StatusCode::FORBIDDEN,
+                "This attachment is quarantined.",
+            ))
+        }
+        Ok((Some(_), false)) => {
+            return Ok(error_response(
+                StatusCode::FORBIDDEN,
+                "You can't access this attachment.",
+            ))
+        }
+        Ok((Some(info), true)) => info,
+    };
+
+    match info
+        .download_uri(&storage, Some(super::ATTACHMENT_LINK_TTL))
+        .await
+    {
+        Ok(url) => Ok(HttpResponse::Ok().json(json!({ "url": url }))),
+        Err(err) => {
+            println!("{err}");
+            Ok(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not sign the link.",
+            ))
+        }
+    }
+}
+
+/// handler for POST requests at the .../{id}/attachments/presign endpoint
+///
+/// responds with a [`DirectUpload`] for the file, if [`HasAttachments::can_write`] allows the user
+async fn presign<R: HasAttachments + 'static>(
+    req: HttpRequest,
+    db: Data<Database>,
+    storage: Data<Storage>,
+    record_id: Path<ID>,
+    Json(item): Json<PresignInput>,
+) -> Result<HttpResponse> {
+    if let Err(response) = can_write::<R>(&req, &db, record_id.into_inner()).await {
+        return Ok(response);
+    }
+
+    match DirectUpload::prepare(&storage, item.file_name, DEFAULT_UPLOAD_TTL).await {
+        Ok(upload) => Ok(HttpResponse::Ok().json(upload)),
+        Err(err) => {
+            println!("{err}");
+            Ok(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not prepare the upload.",
+            ))
+        }
+    }
+}
+
+/// handler for POST requests at the .../{id}/attachments/confirm endpoint
+///
+/// attaches an uploaded [`DirectUpload`] to the record, if [`HasAttachments::can_write`] allows
+/// the user: its file counts towards the user's [`StorageQuota`](`crate::storage::StorageQuota`)
+/// and is checked by the storage's [`UploadScanner`](`crate::storage::UploadScanner`)
+async fn confirm<R: HasAttachments + 'static>(
+    req: HttpRequest,
+    db: Data<Database>,
+    storage: Data<Storage>,
+    record_id: Path<ID>,
+    Json(item): Json<ConfirmInput>,
+) -> Result<HttpResponse> {
+    let record_id = record_id.into_inner();
+    if let Err(response) = can_write::<R>(&req, &db, record_id).await {
+        return Ok(response);
+    }
+
+    let user_id = user_id(&req).await;
+
+    match Attachment::confirm_upload_with(
+        &db,
+        &storage,
+        item.name,
+        R::RECORD_TYPE.to_string(),
+        record_id,
+        item.token,
+        item.private,
+        user_id,
+        item.allow_multiple,
+    )
+    .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(err) if err.starts_with(QUOTA_EXCEEDED) => Ok(error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "Storage quota exceeded.",
+        )),
+        Err(err) if err.starts_with(FILE_REJECTED) => {
+            println!("{err}");
+            Ok(error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The file was rejected by the virus scanner.",
+            ))
+        }
+        Err(err) => Ok(database_error(
+            err,
+            StatusCode::BAD_REQUEST,
+            "Could not attach the file.",
+        )),
+    }
+}
+
+/// handler for DELETE requests at the .../{id}/attachments/{attachment_id} endpoint
+///
+/// detaches the attachment and deletes its file, if [`HasAttachments::can_write`] allows the user
+async fn detach<R: HasAttachments + 'static>(
+    req: HttpRequest,
+    db: Data<Database>,
+    storage: Data<Storage>,
+    path: Path<(ID, ID)>,
+) -> Result<HttpResponse> {
+    let (record_id, attachment_id) = path.into_inner();
+    if let Err(response) = can_write::<R>(&req, &db, record_id).await {
+        return Ok(response);
+    }
+
+    match Attachment::detach_from_with::<R>(&db, &storage, record_id, attachment_id).await {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err(err) => Ok(database_error(
+            err,
+            StatusCode::NOT_FOUND,
+            "Attachment not found.",
+        )),
+    }
+}
+
+/// adds the attachment endpoints of `R` to `scope`, next to its CRUD endpoints:
+///
+/// - `GET {id}/attachments`, `GET {id}/attachments/{attachment_id}/url`
+/// - `POST {id}/attachments/presign`, then `POST {id}/attachments/confirm` (see [`DirectUpload`])
+/// - `DELETE {id}/attachments/{attachment_id}`
+///
+/// uploading and detaching require [`HasAttachments::can_write`] to allow the user
+pub fn attachment_endpoints<R: HasAttachments + 'static>(
+    scope: actix_web::Scope,
+) -> actix_web::Scope {
+    scope
+        .route("/{id}/attachments", web::get().to(list::<R>))
+        .route(
+            "/{id}/attachments/{attachment_id}/url",
+            web::get().to(url::<R>),
+        )
+        .route("/{id}/attachments/presign", web::post().to(presign::<R>))
+        .route("/{id}/attachments/confirm", web::post().to(confirm::<R>))
+        .route(
+            "/{id}/attachments/{attachment_id}",
+            web::delete().to(detach::<R>),
+        )
+}
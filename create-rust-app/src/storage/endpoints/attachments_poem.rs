@@ -0,0 +1,341 @@
+use std::sync::Arc;
+
+use poem::{
+    delete, get, handler,
+    http::StatusCode,
+    post,
+    web::{Data, Json, Path},
+    Error, Request, Result, Route,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::storage::quota::QUOTA_EXCEEDED;
+use crate::storage::scan::FILE_REJECTED;
+use crate::storage::{
+    Attachment, AttachmentInfo, DirectUpload, HasAttachments, Storage, DEFAULT_UPLOAD_TTL, ID,
+};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// body of requests to the .../:id/attachments/presign endpoint
+pub struct PresignInput {
+    file_name: String,
+}
+
+#[derive(Deserialize)]
+/// body of requests to the .../:id/attachments/confirm endpoint
+pub struct ConfirmInput {
+    /// the attachment's name (ex: "photo")
+    name: String,
+    /// the [`DirectUpload`]'s token
+    token: String,
+    /// keep the record's other attachments with the same name (default: `true`)
+    #[serde(default = "default_allow_multiple")]
+    allow_multiple: bool,
+    /// see [`Attachment::private`] (default: `false`)
+    #[serde(default)]
+    private: bool,
+}
+
+fn default_allow_multiple() -> bool {
+    true
+}
+
+#[derive(Serialize)]
+/// an attachment, with a link to download it unless it's private and the user can't read it
+pub struct AttachmentResponse {
+    #[serde(flatten)]
+    info: AttachmentInfo,
+    url: Option<String>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+/// the signed in user, if any
+async fn user_id(req: &Request) -> Option<ID> {
+    #[cfg(feature = "plugin_auth")]
+    {
+        use poem::FromRequest;
+
+        crate::auth::Auth::from_request_without_body(req)
+            .await
+            .ok()
+            .map(|auth| auth.user_id)
+    }
+    #[cfg(not(feature = "plugin_auth"))]
+    {
+        let _ = req;
+        None
+    }
+}
+
+/// whether the signed in user may change the record's attachments, see [`HasAttachments::can_write`]
+async fn can_write<R: HasAttachments>(req: &Request, db: &Database, record_id: ID) -> Result<()> {
+    let user_id = user_id(req).await;
+    let mut db = db.pool.get().unwrap();
+
+    if R::can_write(&mut db, record_id, user_id) {
+        Ok(())
+    } else {
+        Err(error_response(
+            StatusCode::FORBIDDEN,
+            "You can't change this record's attachments.",
+        ))
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../:id/attachments endpoint
+///
+/// private attachments only get a link if [`HasAttachments::can_read_private`] allows the user,
+/// quarantined ones never do
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json [AttachmentResponse]
+/// | 500 | Json payload : {"message": "Could not load attachments."}
+async fn list<R: HasAttachments + Send + Sync + 'static>(
+    req: &Request,
+    db: Data<&Database>,
+    storage: Data<&Storage>,
+    Path(record_id): Path<ID>,
+) -> Result<Json<Vec<AttachmentResponse>>> {
+    let user_id = user_id(req).await;
+
+    let (attachments, can_read_private) = {
+        let mut db = db.pool.get().unwrap();
+
+        let attachments = Attachment::list_for::<R>(&mut db, record_id).map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not load attachments.",
+            )
+        })?;
+        let can_read_private = attachments.iter().any(|info| info.private)
+            && R::can_read_private(&mut db, record_id, user_id);
+
+        (attachments, can_read_private)
+    };
+
+    let mut response = vec![];
+    for info in attachments {
+        if info.is_quarantined() || (info.private && !can_read_private) {
+            response.push(AttachmentResponse { info, url: None });
+            continue;
+        }
+
+        let url = info
+            .download_uri(storage.0, Some(super::ATTACHMENT_LINK_TTL))
+            .await
+            .map_err(|err| {
+                println!("{err}");
+                error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Could not load attachments.",
+                )
+            })?;
+
+        response.push(AttachmentResponse {
+            info,
+            url: Some(url),
+        });
+    }
+
+    Ok(Json(response))
+}
+
+#[handler]
+/// handler for GET requests at the .../:id/attachments/:attachment_id/url endpoint
+///
+/// private attachments require [`HasAttachments::can_read_private`] to allow the user
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"url": "..."}
+/// | 403 | Json payload : {"message": "You can't access this attachment."}
+/// | 403 | Json payload : {"message": "This attachment is quarantined."}
+/// | 404 | Json payload : {"message": "Attachment not found."}
+/// | 500 | Json payload : {"message": "Could not sign the link."}
+async fn url<R: HasAttachments + Send + Sync + 'static>(
+    req: &Request,
+    db: Data<&Database>,
+    storage: Data<&Storage>,
+    Path((record_id, attachment_id)): Path<(ID, ID)>,
+) -> Result<Json<Value>> {
+    let user_id = user_id(req).await;
+
+    let info = {
+        let mut db = db.pool.get().unwrap();
+
+        let info = Attachment::list_for::<R>(&mut db, record_id)
+            .ok()
+            .and_then(|infos| infos.into_iter().find(|info| info.id == attachment_id))
+            .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Attachment not found."))?;
+
+        if info.is_quarantined() {
+            return Err(error_response(
+                StatusCode::FORBIDDEN,
+                "This attachment is quarantined.",
+            ));
+        }
+
+        if info.private && !R::can_read_private(&mut db, record_id, user_id) {
+            return Err(error_response(
+                StatusCode::FORBIDDEN,
+                "You can't access this attachment.",
+            ));
+        }
+
+        info
+    };
+
+    let url = info
+        .download_uri(storage.0, Some(super::ATTACHMENT_LINK_TTL))
+        .await
+        .map_err(|err| {
+            println!("{err}");
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not sign the link.",
+            )
+        })?;
+
+    Ok(Json(json!({ "url": url })))
+}
+
+#[handler]
+/// handler for POST requests at the .../:id/attachments/presign endpoint
+///
+/// requires [`HasAttachments::can_write`] to allow the user
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json [DirectUpload]
+/// | 403 | Json payload : {"message": "You can't change this record's attachments."}
+/// | 500 | Json payload : {"message": "Could not prepare the upload."}
+async fn presign<R: HasAttachments + Send + Sync + 'static>(
+    req: &Request,
+    db: Data<&Database>,
+    storage: Data<&Storage>,
+    Path(record_id): Path<ID>,
+    Json(item): Json<PresignInput>,
+) -> Result<Json<DirectUpload>> {
+    can_write::<R>(req, db.0, record_id).await?;
+
+    DirectUpload::prepare(storage.0, item.file_name, DEFAULT_UPLOAD_TTL)
+        .await
+        .map(Json)
+        .map_err(|err| {
+            println!("{err}");
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not prepare the upload.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../:id/attachments/confirm endpoint
+///
+/// requires [`HasAttachments::can_write`] to allow the user, the file counts towards the user's
+/// [`StorageQuota`](`crate::storage::StorageQuota`) and is checked by the storage's
+/// [`UploadScanner`](`crate::storage::UploadScanner`)
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 400 | Json payload : {"message": "Could not attach the file."}
+/// | 403 | Json payload : {"message": "You can't change this record's attachments."}
+/// | 413 | Json payload : {"message": "Storage quota exceeded."}
+/// | 422 | Json payload : {"message": "The file was rejected by the virus scanner."}
+async fn confirm<R: HasAttachments + Send + Sync + 'static>(
+    req: &Request,
+    db: Data<&Database>,
+    storage: Data<&Storage>,
+    Path(record_id): Path<ID>,
+    Json(item): Json<ConfirmInput>,
+) -> Result<()> {
+    can_write::<R>(req, db.0, record_id).await?;
+
+    let user_id = user_id(req).await;
+    let pool = Arc::new(db.pool.as_ref());
+
+    Attachment::confirm_upload(
+        pool,
+        storage.0,
+        item.name,
+        R::RECORD_TYPE.to_string(),
+        record_id,
+        item.token,
+        item.private,
+        user_id,
+        item.allow_multiple,
+        true,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|err| {
+        if err.starts_with(QUOTA_EXCEEDED) {
+            return error_response(StatusCode::PAYLOAD_TOO_LARGE, "Storage quota exceeded.");
+        }
+
+        println!("{err}");
+        if err.starts_with(FILE_REJECTED) {
+            return error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The file was rejected by the virus scanner.",
+            );
+        }
+
+        error_response(StatusCode::BAD_REQUEST, "Could not attach the file.")
+    })
+}
+
+#[handler]
+/// handler for DELETE requests at the .../:id/attachments/:attachment_id endpoint
+///
+/// requires [`HasAttachments::can_write`] to allow the user
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 403 | Json payload : {"message": "You can't change this record's attachments."}
+/// | 404 | Json payload : {"message": "Attachment not found."}
+async fn detach<R: HasAttachments + Send + Sync + 'static>(
+    req: &Request,
+    db: Data<&Database>,
+    storage: Data<&Storage>,
+    Path((record_id, attachment_id)): Path<(ID, ID)>,
+) -> Result<()> {
+    can_write::<R>(req, db.0, record_id).await?;
+
+    let pool = Arc::new(db.pool.as_ref());
+
+    Attachment::detach_from::<R>(pool, storage.0, record_id, attachment_id)
+        .await
+        .map_err(|_| error_response(StatusCode::NOT_FOUND, "Attachment not found."))
+}
+
+/// adds the attachment routes of `R` to `route`, next to its CRUD routes:
+///
+/// - `GET :id/attachments`, `GET :id/attachments/:attachment_id/url`
+/// - `POST :id/attachments/presign`, then `POST :id/attachments/confirm` (see [`DirectUpload`])
+/// - `DELETE :id/attachments/:attachment_id`
+///
+/// uploading and detaching require [`HasAttachments::can_write`] to allow the user
+pub fn attachment_api<R: HasAttachments + Send + Sync + 'static>(route: Route) -> Route {
+    route
+        .at("/:id/attachments", get(list::<R>))
+        .at("/:id/attachments/:attachment_id/url", get(url::<R>))
+        .at("/:id/attachments/presign", post(presign::<R>))
+        .at("/:id/attachments/confirm", post(confirm::<R>))
+        .at("/:id/attachments/:attachment_id", delete(detach::<R>))
+}
@@ -0,0 +1,72 @@
+//! Serves files through links signed by the backend (ex: [`LocalStorage`](`super::LocalStorage`)'s),
+//! mount them at `/api/storage`:
+//!
+//! ```rust,ignore
+//! // actix-web
+//! api_scope = api_scope.service(create_rust_app::storage::endpoints(web::scope("/storage")));
+//! // poem
+//! api_routes = api_routes.nest("/storage", create_rust_app::storage::api());
+//! ```
+//!
+//! Requests need the `token` of a signed link (see [`Storage::download_uri`](`super::Storage::download_uri`)
+//! and [`Storage::upload_uri`](`super::Storage::upload_uri`)) that the
+//! [`StorageProvider`](`super::StorageProvider::verify`) accepts; S3 links never are, they point at
+//! the bucket.
+//!
+//! Downloads are streamed from the provider and support `Range` (a single range, answered with a
+//! `206 Partial Content`) and `If-None-Match` (answered with a `304 Not Modified`) headers, so
+//! [`Storage::stream_uri`](`super::Storage::stream_uri`) links can serve videos, audio and large
+//! files from any provider. Their `Cache-Control` header comes from the storage's
+//! [`CachePolicy`](`super::CachePolicy`).
+//!
+//! The attachment endpoints of [`HasAttachments`](`super::HasAttachments`) models are added to
+//! their scope with `attachment_endpoints::<Model>(scope)` (actix-web) or
+//! `attachment_api::<Model>(route)` (poem).
+//!
+//! The endpoints of [`MultipartUpload`](`super::MultipartUpload`)s are mounted separately, at
+//! `/api/uploads`, with `multipart_endpoints(web::scope("/uploads"))` (actix-web) or
+//! `multipart_api()` (poem), along with `GET /api/uploads/usage` (`plugin_auth` feature), the
+//! signed in user's [`StorageUsage`](`super::StorageUsage`).
+use std::time::Duration;
+
+#[cfg(feature = "backend_actix-web")]
+mod attachments_actixweb;
+#[cfg(feature = "backend_actix-web")]
+mod multipart_actixweb;
+#[cfg(feature = "backend_actix-web")]
+mod service_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use attachments_actixweb::attachment_endpoints;
+#[cfg(feature = "backend_actix-web")]
+pub use multipart_actixweb::multipart_endpoints;
+#[cfg(feature = "backend_actix-web")]
+pub use service_actixweb::endpoints;
+
+#[cfg(feature = "backend_poem")]
+mod attachments_poem;
+#[cfg(feature = "backend_poem")]
+mod multipart_poem;
+#[cfg(feature = "backend_poem")]
+mod service_poem;
+#[cfg(feature = "backend_poem")]
+pub use attachments_poem::attachment_api;
+#[cfg(feature = "backend_poem")]
+pub use multipart_poem::multipart_api;
+#[cfg(feature = "backend_poem")]
+pub use service_poem::api;
+
+/// how long the download links of the attachment endpoints are valid for
+const ATTACHMENT_LINK_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// the largest file accepted by `PUT` requests
+pub const MAX_UPLOAD_SIZE: usize = 64 * 1024 * 1024;
+
+/// whether an `If-None-Match` header's value lists `etag`, ignoring weak validators' `W/`
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
@@ -0,0 +1,159 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Json};
+use actix_web::{get, post, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::storage::{MultipartUpload, Storage, UploadedPart};
+
+#[derive(Deserialize)]
+/// body of requests to the .../ endpoint
+pub struct CreateInput {
+    file_name: String,
+}
+
+#[derive(Deserialize)]
+/// body of requests to the .../parts/presign endpoint
+pub struct PartInput {
+    token: String,
+    part_number: i32,
+}
+
+#[derive(Deserialize)]
+/// body of requests to the .../parts and .../abort endpoints
+pub struct TokenInput {
+    token: String,
+}
+
+#[derive(Deserialize)]
+/// body of requests to the .../complete endpoint
+pub struct CompleteInput {
+    token: String,
+    parts: Vec<UploadedPart>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for POST requests at the .../ endpoint
+///
+/// starts a multipart upload, responds with its [`MultipartUpload`]
+#[post("")]
+async fn create(storage: Data<Storage>, Json(item): Json<CreateInput>) -> Result<HttpResponse> {
+    match MultipartUpload::create(&storage, item.file_name).await {
+        Ok(upload) => Ok(HttpResponse::Ok().json(upload)),
+        Err(err) => {
+            println!("{err}");
+            Ok(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not start the upload.",
+            ))
+        }
+    }
+}
+
+/// handler for POST requests at the .../parts/presign endpoint
+///
+/// responds with a link to upload the part
+#[post("/parts/presign")]
+async fn presign_part(storage: Data<Storage>, Json(item): Json<PartInput>) -> Result<HttpResponse> {
+    match MultipartUpload::presign_part(&storage, &item.token, item.part_number).await {
+        Ok(part) => Ok(HttpResponse::Ok().json(part)),
+        Err(err) => {
+            println!("{err}");
+            Ok(error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid or expired upload.",
+            ))
+        }
+    }
+}
+
+/// handler for POST requests at the .../parts endpoint
+///
+/// responds with the parts the storage received
+#[post("/parts")]
+async fn list_parts(storage: Data<Storage>, Json(item): Json<TokenInput>) -> Result<HttpResponse> {
+    match MultipartUpload::list_parts(&storage, &item.token).await {
+        Ok(parts) => Ok(HttpResponse::Ok().json(parts)),
+        Err(err) => {
+            println!("{err}");
+            Ok(error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid or expired upload.",
+            ))
+        }
+    }
+}
+
+/// handler for POST requests at the .../complete endpoint
+///
+/// puts the parts together, responds with the token to confirm the upload with
+#[post("/complete")]
+async fn complete(storage: Data<Storage>, Json(item): Json<CompleteInput>) -> Result<HttpResponse> {
+    match MultipartUpload::complete(&storage, &item.token, item.parts).await {
+        Ok(token) => Ok(HttpResponse::Ok().json(json!({ "token": token }))),
+        Err(err) => {
+            println!("{err}");
+            Ok(error_response(
+                StatusCode::BAD_REQUEST,
+                "Could not complete the upload.",
+            ))
+        }
+    }
+}
+
+/// handler for POST requests at the .../abort endpoint
+///
+/// cancels the upload
+#[post("/abort")]
+async fn abort(storage: Data<Storage>, Json(item): Json<TokenInput>) -> Result<HttpResponse> {
+    match MultipartUpload::abort(&storage, &item.token).await {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err(err) => {
+            println!("{err}");
+            Ok(error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid or expired upload.",
+            ))
+        }
+    }
+}
+
+/// handler for GET requests at the .../usage endpoint
+///
+/// responds with the signed in user's [`StorageUsage`](`crate::storage::StorageUsage`)
+#[cfg(feature = "plugin_auth")]
+#[get("/usage")]
+async fn usage(
+    db: Data<crate::Database>,
+    storage: Data<Storage>,
+    auth: crate::auth::Auth,
+) -> Result<HttpResponse> {
+    let mut db = db.pool.get().unwrap();
+
+    match storage.quota().usage(&mut db, auth.user_id) {
+        Ok(usage) => Ok(HttpResponse::Ok().json(usage)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not load the storage usage.",
+        )),
+    }
+}
+
+/// returns the endpoints of [`MultipartUpload`]s, and the signed in user's storage usage
+/// (`plugin_auth` feature)
+pub fn multipart_endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    let scope = scope
+        .service(create)
+        .service(presign_part)
+        .service(list_parts)
+        .service(complete)
+        .service(abort);
+
+    #[cfg(feature = "plugin_auth")]
+    let scope = scope.service(usage);
+
+    scope
+}
@@ -0,0 +1,187 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    post,
+    web::{Data, Json},
+    Error, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::storage::{MultipartUpload, PartUpload, Storage, UploadedPart};
+
+#[derive(Deserialize)]
+/// body of requests to the .../ endpoint
+pub struct CreateInput {
+    file_name: String,
+}
+
+#[derive(Deserialize)]
+/// body of requests to the .../parts/presign endpoint
+pub struct PartInput {
+    token: String,
+    part_number: i32,
+}
+
+#[derive(Deserialize)]
+/// body of requests to the .../parts and .../abort endpoints
+pub struct TokenInput {
+    token: String,
+}
+
+#[derive(Deserialize)]
+/// body of requests to the .../complete endpoint
+pub struct CompleteInput {
+    token: String,
+    parts: Vec<UploadedPart>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for POST requests at the .../ endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json [MultipartUpload]
+/// | 500 | Json payload : {"message": "Could not start the upload."}
+async fn create(
+    storage: Data<&Storage>,
+    Json(item): Json<CreateInput>,
+) -> Result<Json<MultipartUpload>> {
+    MultipartUpload::create(storage.0, item.file_name)
+        .await
+        .map(Json)
+        .map_err(|err| {
+            println!("{err}");
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not start the upload.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../parts/presign endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json [PartUpload]
+/// | 400 | Json payload : {"message": "Invalid or expired upload."}
+async fn presign_part(
+    storage: Data<&Storage>,
+    Json(item): Json<PartInput>,
+) -> Result<Json<PartUpload>> {
+    MultipartUpload::presign_part(storage.0, &item.token, item.part_number)
+        .await
+        .map(Json)
+        .map_err(|err| {
+            println!("{err}");
+            error_response(StatusCode::BAD_REQUEST, "Invalid or expired upload.")
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../parts endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json [UploadedPart]
+/// | 400 | Json payload : {"message": "Invalid or expired upload."}
+async fn list_parts(
+    storage: Data<&Storage>,
+    Json(item): Json<TokenInput>,
+) -> Result<Json<Vec<UploadedPart>>> {
+    MultipartUpload::list_parts(storage.0, &item.token)
+        .await
+        .map(Json)
+        .map_err(|err| {
+            println!("{err}");
+            error_response(StatusCode::BAD_REQUEST, "Invalid or expired upload.")
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../complete endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"token": "..."}
+/// | 400 | Json payload : {"message": "Could not complete the upload."}
+async fn complete(storage: Data<&Storage>, Json(item): Json<CompleteInput>) -> Result<Json<Value>> {
+    MultipartUpload::complete(storage.0, &item.token, item.parts)
+        .await
+        .map(|token| Json(json!({ "token": token })))
+        .map_err(|err| {
+            println!("{err}");
+            error_response(StatusCode::BAD_REQUEST, "Could not complete the upload.")
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../abort endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 400 | Json payload : {"message": "Invalid or expired upload."}
+async fn abort(storage: Data<&Storage>, Json(item): Json<TokenInput>) -> Result<()> {
+    MultipartUpload::abort(storage.0, &item.token)
+        .await
+        .map_err(|err| {
+            println!("{err}");
+            error_response(StatusCode::BAD_REQUEST, "Invalid or expired upload.")
+        })
+}
+
+#[cfg(feature = "plugin_auth")]
+#[handler]
+/// handler for GET requests at the .../usage endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json [StorageUsage](`crate::storage::StorageUsage`)
+/// | 401 | not signed in
+/// | 500 | Json payload : {"message": "Could not load the storage usage."}
+async fn usage(
+    db: Data<&crate::Database>,
+    storage: Data<&Storage>,
+    auth: crate::auth::Auth,
+) -> Result<Json<crate::storage::StorageUsage>> {
+    let mut db = db.pool.get().unwrap();
+
+    storage
+        .quota()
+        .usage(&mut db, auth.user_id)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not load the storage usage.",
+            )
+        })
+}
+
+/// returns the routes of [`MultipartUpload`]s, and the signed in user's storage usage
+/// (`plugin_auth` feature)
+pub fn multipart_api() -> Route {
+    let route = Route::new()
+        .at("/", post(create))
+        .at("/parts/presign", post(presign_part))
+        .at("/parts", post(list_parts))
+        .at("/complete", post(complete))
+        .at("/abort", post(abort));
+
+    #[cfg(feature = "plugin_auth")]
+    let route = route.at("/usage", get(usage));
+
+    route
+}
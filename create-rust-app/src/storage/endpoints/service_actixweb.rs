@@ -0,0 +1,150 @@
+use actix_http::header;
+use actix_http::StatusCode;
+use actix_web::web::{Bytes, Data, Path, PayloadConfig, Query};
+use actix_web::{get, put, HttpRequest, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{etag_matches, MAX_UPLOAD_SIZE};
+use crate::storage::{ByteRange, PresignAction, Storage};
+
+#[derive(Deserialize)]
+/// query of requests with a signed link
+pub struct LinkQuery {
+    token: String,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// a 403 response unless `token` allows `action` on `key`
+fn authorize(
+    storage: &Storage,
+    key: &str,
+    action: PresignAction,
+    token: &str,
+) -> Result<(), HttpResponse> {
+    if !storage.verify(key, action, token) {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "Invalid or expired link.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// handler for GET requests at the .../{key} endpoint
+///
+/// streams the file, requires the `token` of a download link; answers `Range` requests with a
+/// 206 (or a 416 when the range is outside of the file) and `If-None-Match` requests with a 304
+#[get("/{key:.*}")]
+async fn download(
+    req: HttpRequest,
+    storage: Data<Storage>,
+    key: Path<String>,
+    Query(query): Query<LinkQuery>,
+) -> Result<HttpResponse> {
+    if let Err(response) = authorize(&storage, &key, PresignAction::Get, &query.token) {
+        return Ok(response);
+    }
+
+    let header_value = |name: header::HeaderName| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    let range = header_value(header::RANGE).and_then(ByteRange::parse);
+
+    let reader = match storage.provider().open(&key, range).await {
+        Ok(reader) => reader,
+        Err(_) => return Ok(error_response(StatusCode::NOT_FOUND, "File not found.")),
+    };
+
+    let mut response = HttpResponse::Ok();
+    response.insert_header((header::ACCEPT_RANGES, "bytes"));
+
+    if let Some(etag) = &reader.etag {
+        response.insert_header((header::ETAG, etag.as_str()));
+
+        if header_value(header::IF_NONE_MATCH).map_or(false, |value| etag_matches(value, etag)) {
+            return Ok(response.status(StatusCode::NOT_MODIFIED).finish());
+        }
+    }
+
+    let length = match (range, reader.range) {
+        (_, Some((start, end))) => {
+            response.status(StatusCode::PARTIAL_CONTENT).insert_header((
+                header::CONTENT_RANGE,
+                format!("bytes {start}-{end}/{}", reader.byte_size),
+            ));
+
+            end - start + 1
+        }
+        (Some(range), None) if range.resolve(reader.byte_size).is_none() => {
+            return Ok(response
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .insert_header((
+                    header::CONTENT_RANGE,
+                    format!("bytes */{}", reader.byte_size),
+                ))
+                .body(json!({ "message": "Range not satisfiable." }).to_string()));
+        }
+        _ => reader.byte_size,
+    };
+
+    let content_type = reader.content_type.unwrap_or_else(|| {
+        mime_guess::from_path(key.as_str())
+            .first_or_octet_stream()
+            .to_string()
+    });
+
+    if let Some(cache_control) = storage.cache_control(&content_type) {
+        response.insert_header((header::CACHE_CONTROL, cache_control));
+    }
+
+    Ok(response
+        .content_type(content_type)
+        .no_chunking(length)
+        .streaming(reader.stream))
+}
+
+/// handler for PUT requests at the .../{key} endpoint
+///
+/// stores the request's body as the file, requires the `token` of an upload link
+#[put("/{key:.*}")]
+async fn upload(
+    storage: Data<Storage>,
+    key: Path<String>,
+    Query(query): Query<LinkQuery>,
+    body: Bytes,
+) -> Result<HttpResponse> {
+    if let Err(response) = authorize(&storage, &key, PresignAction::Put, &query.token) {
+        return Ok(response);
+    }
+
+    let content_type = mime_guess::from_path(key.as_str())
+        .first_or_octet_stream()
+        .to_string();
+
+    match storage
+        .provider()
+        .put(&key, body.to_vec(), &content_type)
+        .await
+    {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not store the file.",
+        )),
+    }
+}
+
+/// returns the endpoints serving the [`Storage`]'s signed links
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope
+        .app_data(PayloadConfig::new(MAX_UPLOAD_SIZE))
+        .service(download)
+        .service(upload)
+}
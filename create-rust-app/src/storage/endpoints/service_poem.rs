@@ -0,0 +1,160 @@
+use futures_util::TryStreamExt;
+use poem::{
+    get, handler,
+    http::{header, StatusCode},
+    web::{Data, Path, Query},
+    Body, Error, Request, Response, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::etag_matches;
+use crate::storage::{ByteRange, PresignAction, Storage};
+
+#[derive(Deserialize)]
+/// query of requests with a signed link
+pub struct LinkQuery {
+    token: String,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+/// a 403 error unless `token` allows `action` on `key`
+fn authorize(storage: &Storage, key: &str, action: PresignAction, token: &str) -> Result<()> {
+    if !storage.verify(key, action, token) {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "Invalid or expired link.",
+        ));
+    }
+
+    Ok(())
+}
+
+#[handler]
+/// handler for GET requests at the .../:key endpoint
+///
+/// streams the file, supports `Range` and `If-None-Match` headers
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | the file
+/// | 206 | the bytes of the `Range` header
+/// | 304 | (no content), the file matches the `If-None-Match` header
+/// | 403 | Json payload : {"message": "Invalid or expired link."}
+/// | 404 | Json payload : {"message": "File not found."}
+/// | 416 | Json payload : {"message": "Range not satisfiable."}
+async fn download(
+    req: &Request,
+    storage: Data<&Storage>,
+    Path(key): Path<String>,
+    Query(query): Query<LinkQuery>,
+) -> Result<Response> {
+    authorize(storage.0, &key, PresignAction::Get, &query.token)?;
+
+    let header_value = |name: header::HeaderName| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    let range = header_value(header::RANGE).and_then(ByteRange::parse);
+
+    let reader = storage
+        .provider()
+        .open(&key, range)
+        .await
+        .map_err(|_| error_response(StatusCode::NOT_FOUND, "File not found."))?;
+
+    let mut response = Response::builder().header(header::ACCEPT_RANGES, "bytes");
+
+    if let Some(etag) = &reader.etag {
+        response = response.header(header::ETAG, etag.as_str());
+
+        if header_value(header::IF_NONE_MATCH).map_or(false, |value| etag_matches(value, etag)) {
+            return Ok(response.status(StatusCode::NOT_MODIFIED).finish());
+        }
+    }
+
+    let length = match (range, reader.range) {
+        (_, Some((start, end))) => {
+            response = response.status(StatusCode::PARTIAL_CONTENT).header(
+                header::CONTENT_RANGE,
+                format!("bytes {start}-{end}/{}", reader.byte_size),
+            );
+
+            end - start + 1
+        }
+        (Some(range), None) if range.resolve(reader.byte_size).is_none() => {
+            return Ok(response
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes */{}", reader.byte_size),
+                )
+                .body(json!({ "message": "Range not satisfiable." }).to_string()));
+        }
+        _ => reader.byte_size,
+    };
+
+    let content_type = reader.content_type.unwrap_or_else(|| {
+        mime_guess::from_path(key.as_str())
+            .first_or_octet_stream()
+            .to_string()
+    });
+
+    if let Some(cache_control) = storage.cache_control(&content_type) {
+        response = response.header(header::CACHE_CONTROL, cache_control);
+    }
+
+    Ok(response
+        .content_type(content_type)
+        .header(header::CONTENT_LENGTH, length)
+        .body(Body::from_bytes_stream(reader.stream.map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::Other, err)
+        }))))
+}
+
+#[handler]
+/// handler for PUT requests at the .../:key endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 403 | Json payload : {"message": "Invalid or expired link."}
+/// | 500 | Json payload : {"message": "Could not store the file."}
+async fn upload(
+    storage: Data<&Storage>,
+    Path(key): Path<String>,
+    Query(query): Query<LinkQuery>,
+    body: Vec<u8>,
+) -> Result<Response> {
+    authorize(storage.0, &key, PresignAction::Put, &query.token)?;
+
+    if body.len() > super::MAX_UPLOAD_SIZE {
+        return Err(error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "File too large.",
+        ));
+    }
+
+    let content_type = mime_guess::from_path(key.as_str())
+        .first_or_octet_stream()
+        .to_string();
+
+    match storage.provider().put(&key, body, &content_type).await {
+        Ok(()) => Ok(Response::builder().status(StatusCode::OK).finish()),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not store the file.",
+        )),
+    }
+}
+
+/// returns the routes serving the [`Storage`]'s signed links
+pub fn api() -> Route {
+    Route::new().at("/*key", get(download).put(upload))
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use super::Attachment;
+use crate::events::Event;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a file was attached to a record (uploaded through the backend, or directly)
+pub struct AttachmentAttached {
+    pub attachment: Attachment,
+    /// the file's key in the [`Storage`](`super::Storage`)
+    pub key: String,
+}
+
+impl Event for AttachmentAttached {
+    const NAME: &'static str = "storage.attached";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// an attachment was deleted, along with its file
+pub struct AttachmentDetached {
+    pub attachment: Attachment,
+    /// the deleted file's key in the [`Storage`](`super::Storage`)
+    pub key: String,
+}
+
+impl Event for AttachmentDetached {
+    const NAME: &'static str = "storage.detached";
+}
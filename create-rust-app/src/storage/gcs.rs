@@ -0,0 +1,242 @@
+//! Keeps files in a Google Cloud Storage bucket (`plugin_storage_gcs` feature).
+//!
+//! Enabled by setting `GCS_BUCKET` in your `.env` (the `S3_*` variables are ignored then):
+//!
+//! ```text
+//! GCS_BUCKET=my-bucket
+//! # a service account key, its path or its json; leave it unset to use the application default
+//! # credentials (`GOOGLE_APPLICATION_CREDENTIALS`, or workload identity on GKE / Cloud Run)
+//! GCS_CREDENTIALS=./service-account.json
+//! ```
+//!
+//! Links from [`Storage::download_uri`](`super::Storage::download_uri`) and
+//! [`Storage::upload_uri`](`super::Storage::upload_uri`) are V4 signed urls; with workload identity
+//! they're signed through the IAM API, so the service account needs the
+//! `iam.serviceAccounts.signBlob` permission on itself.
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use google_cloud_storage::client::google_cloud_auth::credentials::CredentialsFile;
+use google_cloud_storage::client::{Client, ClientConfig};
+use google_cloud_storage::http::objects::delete::DeleteObjectRequest;
+use google_cloud_storage::http::objects::download::Range;
+use google_cloud_storage::http::objects::get::GetObjectRequest;
+use google_cloud_storage::http::objects::upload::{Media, UploadObjectRequest, UploadType};
+use google_cloud_storage::http::Error;
+use google_cloud_storage::sign::{SignedURLMethod, SignedURLOptions};
+use http::{HeaderMap, Uri};
+use tokio::sync::OnceCell;
+
+use super::{PresignAction, PresignedURI, StorageFuture, StorageProvider};
+
+#[derive(Clone)]
+/// a Google Cloud Storage bucket, see the [module documentation](`self`)
+pub struct GcsProvider {
+    /// created on first use, authenticating is async
+    client: Arc<OnceCell<Result<Client, String>>>,
+    bucket: String,
+    credentials: Option<String>,
+}
+
+impl GcsProvider {
+    /// `credentials` is a service account key's path or json, `None` uses the application default
+    /// credentials
+    pub fn new(bucket: String, credentials: Option<String>) -> GcsProvider {
+        GcsProvider {
+            client: Default::default(),
+            bucket,
+            credentials,
+        }
+    }
+
+    /// reads `GCS_BUCKET` and `GCS_CREDENTIALS`
+    pub fn from_env() -> GcsProvider {
+        let bucket = std::env::var("GCS_BUCKET").unwrap_or_else(|_| "".to_string());
+        let credentials = std::env::var("GCS_CREDENTIALS")
+            .ok()
+            .filter(|credentials| !credentials.is_empty());
+
+        GcsProvider::new(bucket, credentials)
+    }
+
+    fn error_string(&self, message: &'static str, key: String, error: String) -> String {
+        let bucket = &self.bucket;
+        format!("{message} (bucket: '{bucket}', key: '{key}', error: '{error}')")
+    }
+
+    async fn client_or_error(&self) -> Result<&Client, String> {
+        self.client
+            .get_or_init(|| self.init())
+            .await
+            .as_ref()
+            .map_err(|error| {
+                format!("The storage is not available; did you set the right environment variables? (error: '{error}')")
+            })
+    }
+
+    async fn init(&self) -> Result<Client, String> {
+        let config = match &self.credentials {
+            Some(credentials) => {
+                let credentials = if credentials.trim_start().starts_with('{') {
+                    CredentialsFile::new_from_str(credentials).await
+                } else {
+                    CredentialsFile::new_from_file(credentials.clone()).await
+                }
+                .map_err(|err| err.to_string())?;
+
+                ClientConfig::default()
+                    .with_credentials(credentials)
+                    .await
+                    .map_err(|err| err.to_string())?
+            }
+            None => ClientConfig::default()
+                .with_auth()
+                .await
+                .map_err(|err| err.to_string())?,
+        };
+
+        Ok(Client::new(config))
+    }
+}
+
+impl StorageProvider for GcsProvider {
+    fn name(&self) -> &'static str {
+        "gcs"
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        bytes: Vec<u8>,
+        content_type: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let client = self.client_or_error().await?;
+
+            let media = Media {
+                name: key.to_string().into(),
+                content_type: content_type.to_string().into(),
+                content_length: Some(bytes.len() as u64),
+            };
+
+            client
+                .upload_object(
+                    &UploadObjectRequest {
+                        bucket: self.bucket.clone(),
+                        ..Default::default()
+                    },
+                    bytes,
+                    &UploadType::Simple(media),
+                )
+                .await
+                .map_err(|err| {
+                    self.error_string("Could not upload object", key.to_string(), err.to_string())
+                })?;
+
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let client = self.client_or_error().await?;
+
+            client
+                .download_object(
+                    &GetObjectRequest {
+                        bucket: self.bucket.clone(),
+                        object: key.to_string(),
+                        ..Default::default()
+                    },
+                    &Range::default(),
+                )
+                .await
+                .map_err(|err| {
+                    self.error_string(
+                        "Could not download object",
+                        key.to_string(),
+                        err.to_string(),
+                    )
+                })
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let client = self.client_or_error().await?;
+
+            let result = client
+                .delete_object(&DeleteObjectRequest {
+                    bucket: self.bucket.clone(),
+                    object: key.to_string(),
+                    ..Default::default()
+                })
+                .await;
+
+            match result {
+                Ok(()) => Ok(()),
+                Err(Error::Response(err)) if err.code == 404 => Ok(()),
+                Err(err) => Err(self.error_string(
+                    "Could not delete object",
+                    key.to_string(),
+                    err.to_string(),
+                )),
+            }
+        })
+    }
+
+    /// if `expires_in` is `None`, then we assume the bucket is publicly readable and return the
+    /// public URL
+    fn presign<'a>(
+        &'a self,
+        key: &'a str,
+        action: PresignAction,
+        expires_in: Option<Duration>,
+    ) -> StorageFuture<'a, PresignedURI> {
+        Box::pin(async move {
+            let message = match action {
+                PresignAction::Get => "Could not retrieve download URI",
+                PresignAction::Put => "Could not retrieve upload URI",
+            };
+
+            let uri = match expires_in {
+                None => {
+                    let bucket = &self.bucket;
+                    format!("https://storage.googleapis.com/{bucket}/{key}")
+                }
+                Some(expires_in) => {
+                    let client = self.client_or_error().await?;
+
+                    let method = match action {
+                        PresignAction::Get => SignedURLMethod::GET,
+                        PresignAction::Put => SignedURLMethod::PUT,
+                    };
+
+                    client
+                        .signed_url(
+                            &self.bucket,
+                            key,
+                            None,
+                            None,
+                            SignedURLOptions {
+                                method,
+                                expires: expires_in,
+                                ..Default::default()
+                            },
+                        )
+                        .await
+                        .map_err(|err| {
+                            self.error_string(message, key.to_string(), err.to_string())
+                        })?
+                }
+            };
+
+            Ok(PresignedURI {
+                uri: Uri::from_str(&uri)
+                    .map_err(|err| self.error_string(message, key.to_string(), err.to_string()))?,
+                headers: HeaderMap::new(),
+            })
+        })
+    }
+}
@@ -0,0 +1,275 @@
+//! Keeps files in a local directory instead of an S3 bucket, for development and small deployments.
+//!
+//! Enabled by setting `STORAGE_DIR` in your `.env` (the `S3_*` variables are ignored then):
+//!
+//! ```text
+//! STORAGE_DIR=./storage
+//! ```
+//!
+//! Files are served by the backend at `/api/storage/{key}` (see [`endpoints`](`super::endpoints`)),
+//! only with a link from [`Storage::download_uri`](`super::Storage::download_uri`): links are signed
+//! with the `SECRET_KEY` and expire, like S3's presigned urls. Uploads through
+//! [`Storage::upload_uri`](`super::Storage::upload_uri`) work the same way, with `PUT` requests.
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{HeaderMap, Uri};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use super::{ByteRange, ObjectReader, PresignAction, PresignedURI, StorageFuture, StorageProvider};
+
+/// how many bytes [`StorageProvider::open`] streams at a time
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// how long [`Storage::download_uri`](`super::Storage::download_uri`) links are valid for when no
+/// expiration is given (local files are never public)
+pub const DEFAULT_LINK_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LinkClaims {
+    /// the file's key
+    sub: String,
+    act: PresignAction,
+    exp: usize,
+}
+
+#[derive(Debug, Clone)]
+/// a directory files are kept in, see the [module documentation](`self`)
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalStorage {
+    /// files are kept in `root` and served at `/api/storage`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            base_url: "/api/storage".to_string(),
+        }
+    }
+
+    /// where the [`endpoints`](`super::endpoints`) are mounted (default: `/api/storage`)
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// the path of `key`'s file, keys can't point outside of the directory
+    pub fn path(&self, key: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(key);
+
+        let is_contained = !key.is_empty()
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+
+        if !is_contained {
+            return Err(format!("Invalid key (key: '{key}')"));
+        }
+
+        Ok(self.root.join(relative))
+    }
+
+    pub async fn read(&self, key: &str) -> Result<Vec<u8>, String> {
+        tokio::fs::read(self.path(key)?)
+            .await
+            .map_err(|err| self.error_string("Could not read file", key, err.to_string()))
+    }
+
+    pub async fn write(&self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        let path = self.path(key)?;
+
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory)
+                .await
+                .map_err(|err| self.error_string("Could not write file", key, err.to_string()))?;
+        }
+
+        tokio::fs::write(path, bytes)
+            .await
+            .map_err(|err| self.error_string("Could not write file", key, err.to_string()))
+    }
+
+    /// removes `key`'s file, missing files are ignored
+    pub async fn remove(&self, key: &str) -> Result<(), String> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(self.error_string("Could not delete file", key, err.to_string())),
+        }
+    }
+
+    /// a link to `key` allowing `action` until `expires_in` from now
+    pub fn signed_uri(
+        &self,
+        key: &str,
+        action: PresignAction,
+        expires_in: Duration,
+    ) -> Result<String, String> {
+        sign_link(&self.base_url, key, action, expires_in)
+            .map_err(|err| self.error_string("Could not sign link", key, err))
+    }
+
+    /// whether `token` (from a [`LocalStorage::signed_uri`] link) allows `action` on `key`
+    pub fn verify(&self, key: &str, action: PresignAction, token: &str) -> bool {
+        verify_link(key, action, token)
+    }
+
+    fn error_string(&self, message: &'static str, key: &str, error: String) -> String {
+        let root = self.root.display();
+        format!("{message} (directory: '{root}', key: '{key}', error: '{error}')")
+    }
+}
+
+impl StorageProvider for LocalStorage {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        bytes: Vec<u8>,
+        _content_type: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move { self.write(key, &bytes).await })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Vec<u8>> {
+        Box::pin(self.read(key))
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(self.remove(key))
+    }
+
+    /// streams the file from disk, seeking to the start of `range`
+    fn open<'a>(
+        &'a self,
+        key: &'a str,
+        range: Option<ByteRange>,
+    ) -> StorageFuture<'a, ObjectReader> {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+        Box::pin(async move {
+            let error = |err: std::io::Error| {
+                self.error_string("Could not read file", key, err.to_string())
+            };
+
+            let mut file = tokio::fs::File::open(self.path(key)?)
+                .await
+                .map_err(error)?;
+            let metadata = file.metadata().await.map_err(error)?;
+            let byte_size = metadata.len();
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                .unwrap_or_default();
+
+            let range = range.and_then(|range| range.resolve(byte_size));
+            let (start, length) = match range {
+                Some((start, end)) => (start, end - start + 1),
+                None => (0, byte_size),
+            };
+
+            file.seek(std::io::SeekFrom::Start(start))
+                .await
+                .map_err(error)?;
+
+            let stream = futures_util::stream::try_unfold(
+                (file.take(length), key.to_string()),
+                |(mut file, key)| async move {
+                    let mut chunk = Vec::with_capacity(CHUNK_SIZE as usize);
+                    let read = (&mut file)
+                        .take(CHUNK_SIZE)
+                        .read_to_end(&mut chunk)
+                        .await
+                        .map_err(|err| {
+                            format!("Could not read file (key: '{key}', error: '{err}')")
+                        })?;
+
+                    Ok((read > 0).then(|| (Bytes::from(chunk), (file, key))))
+                },
+            );
+
+            Ok(ObjectReader {
+                stream: Box::pin(stream),
+                byte_size,
+                range,
+                etag: Some(format!("W/\"{byte_size:x}-{:x}\"", modified.as_millis())),
+                content_type: None,
+            })
+        })
+    }
+
+    /// local files are never public, without `expires_in` links are valid for [`DEFAULT_LINK_TTL`]
+    fn presign<'a>(
+        &'a self,
+        key: &'a str,
+        action: PresignAction,
+        expires_in: Option<Duration>,
+    ) -> StorageFuture<'a, PresignedURI> {
+        Box::pin(async move {
+            let uri = self.signed_uri(key, action, expires_in.unwrap_or(DEFAULT_LINK_TTL))?;
+
+            Ok(PresignedURI {
+                uri: Uri::from_str(&uri).map_err(|err| {
+                    self.error_string("Could not sign link", key, err.to_string())
+                })?,
+                headers: HeaderMap::new(),
+            })
+        })
+    }
+
+    fn verify(&self, key: &str, action: PresignAction, token: &str) -> bool {
+        LocalStorage::verify(self, key, action, token)
+    }
+}
+
+/// a `{base_url}/{key}?token=...` link signed with the `SECRET_KEY`, served by the
+/// [`endpoints`](`super::endpoints`)
+pub(crate) fn sign_link(
+    base_url: &str,
+    key: &str,
+    action: PresignAction,
+    expires_in: Duration,
+) -> Result<String, String> {
+    let claims = LinkClaims {
+        sub: key.to_string(),
+        act: action,
+        exp: (std::time::SystemTime::now() + expires_in)
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as usize,
+    };
+
+    let token = jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret_key().as_bytes()),
+    )
+    .map_err(|err| err.to_string())?;
+
+    Ok(format!("{base_url}/{key}?token={token}"))
+}
+
+/// whether `token` (from a [`sign_link`] link) allows `action` on `key`
+pub(crate) fn verify_link(key: &str, action: PresignAction, token: &str) -> bool {
+    jsonwebtoken::decode::<LinkClaims>(
+        token,
+        &DecodingKey::from_secret(secret_key().as_bytes()),
+        &Validation::default(),
+    )
+    .map_or(false, |token| {
+        token.claims.sub == key && token.claims.act == action
+    })
+}
+
+fn secret_key() -> String {
+    std::env::var("SECRET_KEY").expect("No SECRET_KEY environment variable set!")
+}
@@ -0,0 +1,100 @@
+//! Keeps files in memory, for tests:
+//!
+//! ```rust,ignore
+//! use create_rust_app::storage::MemoryStorage;
+//! use create_rust_app::Storage;
+//!
+//! let files = MemoryStorage::default();
+//! let storage = Storage::with_provider(files.clone());
+//!
+//! Attachment::attach(db, &storage, /* ... */).await?;
+//! assert_eq!(files.keys().len(), 1);
+//! ```
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use http::{HeaderMap, Uri};
+
+use super::{PresignAction, PresignedURI, StorageFuture, StorageProvider};
+
+#[derive(Debug, Clone, Default)]
+/// files kept in a map, clones share the same files
+pub struct MemoryStorage {
+    files: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    /// the keys of the stored files, sorted
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys = self
+            .files
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.files.lock().unwrap().contains_key(key)
+    }
+}
+
+impl StorageProvider for MemoryStorage {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        bytes: Vec<u8>,
+        _content_type: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            self.files.lock().unwrap().insert(key.to_string(), bytes);
+
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            self.files.lock().unwrap().get(key).cloned().ok_or_else(|| {
+                format!("Could not download object (key: '{key}', error: 'not found')")
+            })
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            self.files.lock().unwrap().remove(key);
+
+            Ok(())
+        })
+    }
+
+    /// `memory://{key}` links, they can't be followed
+    fn presign<'a>(
+        &'a self,
+        key: &'a str,
+        _action: PresignAction,
+        _expires_in: Option<Duration>,
+    ) -> StorageFuture<'a, PresignedURI> {
+        Box::pin(async move {
+            let uri = Uri::from_str(&format!("memory://{key}")).map_err(|err| {
+                let error = err.to_string();
+                format!("Could not retrieve URI (key: '{key}', error: '{error}')")
+            })?;
+
+            Ok(PresignedURI {
+                uri,
+                headers: HeaderMap::new(),
+            })
+        })
+    }
+}
@@ -1,24 +1,53 @@
 use std::path::PathBuf;
-use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
-use aws_sdk_s3::model::{Delete, ObjectIdentifier};
-use aws_sdk_s3::presigning::config::PresigningConfig;
-use aws_sdk_s3::types::ByteStream;
-//use aws_sdk_s3::types::SdkError::*;
-use aws_sdk_s3::{Client, Config, Endpoint};
-use aws_types::region::Region;
-use aws_types::Credentials;
-//use base64;
-use http::{HeaderMap, Uri};
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
-
-pub use attachment::{Attachment, AttachmentData};
+pub use attachment::{
+    Attachment, AttachmentData, AttachmentInfo, HasAttachments, PRIVATE_LINK_TTL,
+};
 pub use attachment_blob::AttachmentBlob;
+#[cfg(feature = "plugin_storage_azure")]
+pub use azure::AzureProvider;
+pub use cdn::{CachePolicy, Cdn, UrlSigner};
+#[cfg(feature = "plugin_storage_cloudfront")]
+pub use cloudfront::CloudFrontSigner;
+pub use direct_upload::{DirectUpload, DEFAULT_UPLOAD_TTL};
+pub use endpoints::*;
+#[cfg(feature = "plugin_events")]
+pub use events::{AttachmentAttached, AttachmentDetached};
+#[cfg(feature = "plugin_storage_gcs")]
+pub use gcs::GcsProvider;
+pub use local::LocalStorage;
+pub use memory::MemoryStorage;
+pub use multipart_upload::{MultipartUpload, PartUpload, MULTIPART_PART_SIZE};
+pub use provider::{
+    ByteRange, ObjectReader, ObjectStat, ObjectStream, PresignAction, PresignedURI, StorageFuture,
+    StorageProvider, UploadedPart,
+};
+pub use quota::{StorageQuota, StorageUsage};
+pub use s3::S3Provider;
+pub use scan::{ClamAvScanner, OnInfected, ScanStatus, ScanVerdict, UploadScanner};
 
 mod attachment;
 mod attachment_blob;
+#[cfg(feature = "plugin_storage_azure")]
+mod azure;
+mod cdn;
+#[cfg(feature = "plugin_storage_cloudfront")]
+mod cloudfront;
+mod direct_upload;
+mod endpoints;
+#[cfg(feature = "plugin_events")]
+mod events;
+#[cfg(feature = "plugin_storage_gcs")]
+mod gcs;
+mod local;
+mod memory;
+mod multipart_upload;
+mod provider;
+mod quota;
+mod s3;
+mod scan;
 mod schema;
 
 #[tsync::tsync]
@@ -31,94 +60,79 @@ type Utc = chrono::DateTime<chrono::Utc>;
 type Utc = chrono::NaiveDateTime;
 
 #[derive(Clone)]
+/// files of the app, kept by a [`StorageProvider`]
 pub struct Storage {
-    client: Option<Client>,
-    bucket: String,
-    host: String,
+    provider: Arc<dyn StorageProvider>,
+    base_url: String,
+    quota: StorageQuota,
+    scanner: Option<Arc<dyn UploadScanner>>,
+    on_infected: OnInfected,
+    cdn: Option<Cdn>,
+    cache_policy: CachePolicy,
 }
 
-pub struct UploadURI {
-    pub headers: HeaderMap,
-    pub uri: Uri,
-}
+pub type UploadURI = PresignedURI;
+
 impl Default for Storage {
     fn default() -> Self {
         Self::new()
     }
 }
 impl Storage {
+    #[cfg_attr(
+        feature = "plugin_observability",
+        tracing::instrument(name = "storage.download", skip_all, fields(key = %key))
+    )]
     pub async fn download(&self, key: String, to_path: PathBuf) -> Result<(), String> {
-        let client = self.client_or_error()?;
-
-        let response = client
-            .get_object()
-            .bucket(&self.bucket)
-            .key(key.clone())
-            .send()
-            .await
-            .map_err(|err| {
-                self.error_string("Could not download object", key.clone(), err.to_string())
-            })?;
-
-        let data = response.body.collect().await.map_err(|err| {
-            self.error_string("Could not download object", key.clone(), err.to_string())
-        })?;
-
-        let mut file = File::create(to_path).await.map_err(|err| {
-            self.error_string("Could not download object", key.clone(), err.to_string())
-        })?;
+        let data = self.provider.get(&key).await?;
 
-        file.write_all(&data.into_bytes()).await.map_err(|err| {
-            self.error_string("Could not download object", key.clone(), err.to_string())
-        })?;
-
-        Ok(())
+        tokio::fs::write(to_path, data).await.map_err(|err| {
+            let error = err.to_string();
+            format!("Could not download object (key: '{key}', error: '{error}')")
+        })
     }
 
-    /// if `expires_in` is `None`, then we assume the bucket is publicly accessible and return the
-    /// public URL. For this to work, you have to make sure the bucket's policy allows public access.
+    /// if `expires_in` is `None`, the provider's public URL is returned (ex: for S3, the bucket's
+    /// policy must allow public access), local files get a link valid for [`local::DEFAULT_LINK_TTL`]
+    ///
+    /// with a [`Cdn`], links point at it instead: public ones always, expiring ones when it can sign
+    /// them
     pub async fn download_uri(
         &self,
         key: String,
         expires_in: Option<Duration>,
     ) -> Result<String, String> {
-        if expires_in.is_none() {
-            let host = self.host.clone();
-            let host = if host.ends_with('/') {
-                host
-            } else {
-                format!("{host}/")
-            };
-            let bucket = &self.bucket;
-            return Ok(format!("{host}{bucket}/{key}"));
+        if let Some(cdn) = &self.cdn {
+            match expires_in {
+                None => return Ok(cdn.url(&key)),
+                Some(expires_in) => {
+                    if let Some(url) = cdn.signed_url(&key, expires_in)? {
+                        return Ok(url);
+                    }
+                }
+            }
         }
-        let expires_in = expires_in.unwrap();
-
-        let client = self.client_or_error()?;
-
-        let response = client
-            .get_object()
-            .bucket(&self.bucket)
-            .key(key.clone())
-            .presigned(PresigningConfig::expires_in(expires_in).map_err(|err| {
-                self.error_string(
-                    "Could not retrieve download URI",
-                    key.clone(),
-                    err.to_string(),
-                )
-            })?)
-            .await
-            .map_err(|err| {
-                self.error_string(
-                    "Could not retrieve download URI",
-                    key.clone(),
-                    err.to_string(),
-                )
-            })?;
-
-        Ok(response.uri().to_string())
+
+        let presigned = self
+            .provider
+            .presign(&key, PresignAction::Get, expires_in)
+            .await?;
+
+        Ok(presigned.uri.to_string())
     }
 
+    /// a link to the backend's [`endpoints`], which stream `key`'s file from the provider with
+    /// support for `Range` and `If-None-Match` headers (for video and audio players, or resuming
+    /// large downloads), valid for `expires_in`
+    pub fn stream_uri(&self, key: &str, expires_in: Duration) -> Result<String, String> {
+        local::sign_link(&self.base_url, key, PresignAction::Get, expires_in)
+            .map_err(|error| format!("Could not sign link (key: '{key}', error: '{error}')"))
+    }
+
+    #[cfg_attr(
+        feature = "plugin_observability",
+        tracing::instrument(name = "storage.upload", skip_all, fields(key = %key))
+    )]
     pub async fn upload(
         &self,
         key: String,
@@ -126,179 +140,151 @@ impl Storage {
         content_type: String,
         _content_md5: String,
     ) -> Result<(), String> {
-        let stream = ByteStream::from(bytes);
-
-        let client = self.client_or_error()?;
-
-        client
-            .put_object()
-            .bucket(&self.bucket)
-            .key(&key)
-            .body(stream)
-            .content_type(content_type)
-            // TODO: Error { code: \"InvalidDigest\", message: \"The Content-Md5 you specified is not valid.\", request_id: \"16DBB0A878146F1A\" }
-            // .content_md5(base64::encode(content_md5))
-            .send()
-            .await
-            .map_err(|err| {
-                self.error_string("Could not upload object", key.clone(), err.to_string())
-            })?;
-
-        Ok(())
+        self.provider.put(&key, bytes, &content_type).await
     }
 
     pub async fn upload_uri(&self, key: String, expires_in: Duration) -> Result<UploadURI, String> {
-        let client = self.client_or_error()?;
-
-        let response = client
-            .put_object()
-            .bucket(&self.bucket)
-            .key(&key)
-            .presigned(PresigningConfig::expires_in(expires_in).map_err(|err| {
-                self.error_string(
-                    "Could not retrieve upload URI",
-                    key.clone(),
-                    err.to_string(),
-                )
-            })?)
+        self.provider
+            .presign(&key, PresignAction::Put, Some(expires_in))
             .await
-            .map_err(|err| {
-                self.error_string("Could not retrieve upload URI", key, err.to_string())
-            })?;
+    }
 
-        let upload_uri = UploadURI {
-            uri: response.uri().clone(),
-            headers: response.headers().clone(),
-        };
+    #[cfg_attr(
+        feature = "plugin_observability",
+        tracing::instrument(name = "storage.delete", skip_all, fields(key = %key))
+    )]
+    pub async fn delete(&self, key: String) -> Result<(), String> {
+        self.provider.delete(&key).await
+    }
 
-        Ok(upload_uri)
+    #[cfg_attr(
+        feature = "plugin_observability",
+        tracing::instrument(name = "storage.delete_many", skip_all, fields(count = keys.len()))
+    )]
+    pub async fn delete_many(&self, keys: Vec<String>) -> Result<(), String> {
+        self.provider.delete_many(&keys).await
     }
 
-    pub async fn delete(&self, key: String) -> Result<(), String> {
-        let client = self.client_or_error()?;
+    /// whether `token` allows `action` on `key`, see [`StorageProvider::verify`], or is the token
+    /// of a [`Storage::stream_uri`] link
+    pub fn verify(&self, key: &str, action: PresignAction, token: &str) -> bool {
+        self.provider.verify(key, action, token)
+            || (action == PresignAction::Get && local::verify_link(key, action, token))
+    }
 
-        client
-            .delete_object()
-            .bucket(&self.bucket)
-            .key(&key)
-            .send()
-            .await
-            .map_err(|err| {
-                self.error_string("Could not delete object", key.clone(), err.to_string())
-            })?;
+    /// how many bytes each user may store, see [`StorageQuota`]
+    pub fn quota(&self) -> &StorageQuota {
+        &self.quota
+    }
 
-        Ok(())
+    /// replaces the quota from `STORAGE_QUOTA_BYTES`
+    pub fn with_quota(mut self, quota: StorageQuota) -> Self {
+        self.quota = quota;
+        self
     }
 
-    pub async fn delete_many(&self, keys: Vec<String>) -> Result<(), String> {
-        let client = self.client_or_error()?;
-
-        let ids = keys
-            .iter()
-            .map(|k| {
-                ObjectIdentifier::builder()
-                    .set_key(Some(k.to_string()))
-                    .build()
-            })
-            .collect::<Vec<ObjectIdentifier>>();
-        let delete = Delete::builder().set_objects(Some(ids)).build();
-
-        client
-            .delete_objects()
-            .bucket(&self.bucket)
-            .delete(delete)
-            .send()
-            .await
-            .map_err(|err| {
-                self.error_string(
-                    "Could not delete objects",
-                    format!("{keys:#?}"),
-                    err.to_string(),
-                )
-            })?;
-
-        Ok(())
+    /// what scans new attachments' files, see [`UploadScanner`]
+    pub fn scanner(&self) -> Option<&dyn UploadScanner> {
+        self.scanner.as_deref()
     }
 
-    fn error_string(&self, message: &'static str, key: String, error: String) -> String {
-        let bucket = &self.bucket;
-        format!("{message} (bucket: '{bucket}', key: '{key}', error: '{error}')")
+    pub fn on_infected(&self) -> OnInfected {
+        self.on_infected
     }
 
-    fn client_or_error(&self) -> Result<&Client, String> {
-        self.client.as_ref().ok_or_else(|| {
-            "The storage is not available; did you set the right environment variables?".to_string()
-        })
+    /// scans new attachments' files with `scanner` instead of the ClamAV daemon at
+    /// `CLAMAV_ADDRESS` (which rejects infected files)
+    pub fn with_scanner(
+        mut self,
+        scanner: impl UploadScanner + 'static,
+        on_infected: OnInfected,
+    ) -> Self {
+        self.scanner = Some(Arc::new(scanner));
+        self.on_infected = on_infected;
+        self
+    }
+
+    /// the CDN in front of the storage, from `CDN_URL` by default
+    pub fn cdn(&self) -> Option<&Cdn> {
+        self.cdn.as_ref()
+    }
+
+    /// serves download links through `cdn`, see [`Storage::download_uri`]
+    pub fn with_cdn(mut self, cdn: Cdn) -> Self {
+        self.cdn = Some(cdn);
+        self
+    }
+
+    /// the `Cache-Control` header of files of `content_type`, see [`CachePolicy`]
+    pub fn cache_control(&self, content_type: &str) -> Option<&str> {
+        self.cache_policy.cache_control(content_type)
+    }
+
+    /// sets the `Cache-Control` headers of the files served by the [`endpoints`] (none by default)
+    pub fn with_cache_policy(mut self, cache_policy: CachePolicy) -> Self {
+        self.cache_policy = cache_policy;
+        self
     }
 
-    fn check_environment_variables() {
-        let vars = vec![
-            "S3_HOST",
-            "S3_REGION",
-            "S3_BUCKET",
-            "S3_ACCESS_KEY_ID",
-            "S3_SECRET_ACCESS_KEY",
-        ];
-
-        let unset_vars = vars
-            .into_iter()
-            .filter(|v| std::env::var(v).is_err())
-            .collect::<Vec<_>>();
-
-        if !unset_vars.is_empty() {
-            println!(
-                "Warning: Storage disabled; the following variables must be set: {}",
-                unset_vars.join(", ")
-            );
+    pub fn provider(&self) -> &dyn StorageProvider {
+        self.provider.as_ref()
+    }
+
+    /// stored in [`AttachmentBlob`]s, the [provider's name](`StorageProvider::name`)
+    pub fn service_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    /// keeps files with a custom [`StorageProvider`]
+    pub fn with_provider(provider: impl StorageProvider + 'static) -> Storage {
+        Storage {
+            provider: Arc::new(provider),
+            base_url: "/api/storage".to_string(),
+            quota: StorageQuota::from_env(),
+            scanner: std::env::var("CLAMAV_ADDRESS")
+                .ok()
+                .filter(|address| !address.is_empty())
+                .map(|address| Arc::new(ClamAvScanner::new(address)) as Arc<dyn UploadScanner>),
+            on_infected: OnInfected::Reject,
+            cdn: Cdn::from_env(),
+            cache_policy: CachePolicy::new(),
         }
     }
 
-    fn init(
-        host: String,
-        region: String,
-        access_key_id: String,
-        secret_access_key: String,
-    ) -> Result<Option<Client>, String> {
-        Storage::check_environment_variables();
-
-        let host = host;
-        let region = Region::new(region);
-        let s3_config = Config::builder()
-            .region(region)
-            .endpoint_resolver(Endpoint::immutable(Uri::from_str(host.as_str()).map_err(
-                |err| {
-                    let error = err.to_string();
-                    format!("Could not initialize storage (error: '{error}')")
-                },
-            )?))
-            .credentials_provider(Credentials::new(
-                access_key_id,
-                secret_access_key,
-                None,
-                None,
-                "UNNAMED_PROVIDER",
-            ))
-            .build();
-        let client = Client::from_conf(s3_config);
-
-        Ok(Some(client))
+    /// where the [`endpoints`] are mounted, for [`Storage::stream_uri`] links (default:
+    /// `/api/storage`)
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
     }
 
+    /// keeps files in [`root`](`LocalStorage::new`) instead of S3
+    pub fn local(local: LocalStorage) -> Storage {
+        Storage::with_provider(local)
+    }
+
+    /// uses the local directory in `STORAGE_DIR` when it's set, see [`LocalStorage`], the Google
+    /// Cloud Storage bucket in `GCS_BUCKET` when it's set (`plugin_storage_gcs` feature), the Azure
+    /// Blob Storage container in `AZURE_STORAGE_CONTAINER` when it's set (`plugin_storage_azure`
+    /// feature), and the S3 bucket configured by the `S3_*` variables otherwise
     pub fn new() -> Storage {
-        let host = std::env::var("S3_HOST").unwrap_or_else(|_| "".to_string());
-        let region = std::env::var("S3_REGION").unwrap_or_else(|_| "".to_string());
-        let bucket = std::env::var("S3_BUCKET").unwrap_or_else(|_| "".to_string());
-        let access_key_id = std::env::var("S3_ACCESS_KEY_ID").unwrap_or_else(|_| "".to_string());
-        let secret_access_key =
-            std::env::var("S3_SECRET_ACCESS_KEY").unwrap_or_else(|_| "".to_string());
+        if let Ok(root) = std::env::var("STORAGE_DIR") {
+            if !root.is_empty() {
+                return Storage::local(LocalStorage::new(root));
+            }
+        }
 
-        let client =
-            Storage::init(host.clone(), region, access_key_id, secret_access_key).unwrap_or(None);
+        #[cfg(feature = "plugin_storage_gcs")]
+        if std::env::var("GCS_BUCKET").map_or(false, |bucket| !bucket.is_empty()) {
+            return Storage::with_provider(GcsProvider::from_env());
+        }
 
-        Storage {
-            client,
-            bucket,
-            host,
+        #[cfg(feature = "plugin_storage_azure")]
+        if std::env::var("AZURE_STORAGE_CONTAINER").map_or(false, |container| !container.is_empty())
+        {
+            return Storage::with_provider(AzureProvider::from_env());
         }
+
+        Storage::with_provider(S3Provider::from_env())
     }
 }
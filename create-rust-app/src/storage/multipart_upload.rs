@@ -0,0 +1,182 @@
+//! Resumable uploads of large files, sent by the browser straight to the storage in parts:
+//!
+//! 1. the backend [creates](`MultipartUpload::create`) the upload and sends it to the browser
+//! 2. the browser `PUT`s each [`part_size`](`MultipartUpload::part_size`) chunk of the file at a
+//!    [part link](`MultipartUpload::presign_part`), and keeps the `ETag` of each response
+//! 3. the backend [completes](`MultipartUpload::complete`) the upload with the parts, and returns a
+//!    [`DirectUpload`] token to confirm as usual
+//!
+//! After a network failure, the browser asks which [parts](`MultipartUpload::list_parts`) the
+//! storage received and only sends the others. Uploads that are never completed should be
+//! [aborted](`MultipartUpload::abort`), or cleaned up by the bucket's lifecycle rules: their parts
+//! are billed until then.
+//!
+//! Only S3 supports multipart uploads, see [`StorageProvider::create_multipart`](`super::StorageProvider::create_multipart`).
+use std::collections::HashMap;
+use std::time::Duration;
+
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::direct_upload::{headers, secret_key};
+use super::{DirectUpload, Storage, UploadedPart};
+
+/// the size of the parts, S3 requires at least 5 MiB except for the last one
+pub const MULTIPART_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// how long a multipart upload can be resumed for
+const MULTIPART_UPLOAD_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// how long part links are valid for
+const PART_LINK_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// how long the [`DirectUpload`] token of a completed upload is valid for
+const CONFIRM_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a multipart upload, see the [module documentation](`self`)
+pub struct MultipartUpload {
+    pub key: String,
+    /// sent back for every other step
+    pub token: String,
+    /// the size of every part but the last
+    pub part_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a link to upload a part
+pub struct PartUpload {
+    pub part_number: i32,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MultipartClaims {
+    /// the file's key
+    sub: String,
+    upload_id: String,
+    file_name: String,
+    token_type: String,
+    exp: usize,
+}
+
+impl MultipartUpload {
+    /// starts uploading `file_name` in parts
+    pub async fn create(storage: &Storage, file_name: String) -> Result<MultipartUpload, String> {
+        let key = Uuid::new_v4().to_string();
+        let content_type = mime_guess::from_path(&file_name)
+            .first_or_octet_stream()
+            .to_string();
+
+        let upload_id = storage
+            .provider()
+            .create_multipart(&key, &content_type)
+            .await?;
+
+        let claims = MultipartClaims {
+            sub: key.clone(),
+            upload_id,
+            file_name,
+            token_type: "multipart_upload_token".to_string(),
+            exp: (std::time::SystemTime::now() + MULTIPART_UPLOAD_TTL)
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as usize,
+        };
+
+        let token = jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret_key().as_bytes()),
+        )
+        .map_err(|err| {
+            let error = err.to_string();
+            format!("Could not sign upload (key: '{key}', error: '{error}')")
+        })?;
+
+        Ok(MultipartUpload {
+            key,
+            token,
+            part_size: MULTIPART_PART_SIZE,
+        })
+    }
+
+    /// a link to upload the part `part_number` (from 1 to 10000) of `token`'s upload
+    pub async fn presign_part(
+        storage: &Storage,
+        token: &str,
+        part_number: i32,
+    ) -> Result<PartUpload, String> {
+        if !(1..=10000).contains(&part_number) {
+            return Err(format!("Invalid part number (part: '{part_number}')"));
+        }
+
+        let claims = verify(token)?;
+
+        let presigned = storage
+            .provider()
+            .presign_part(&claims.sub, &claims.upload_id, part_number, PART_LINK_TTL)
+            .await?;
+
+        Ok(PartUpload {
+            part_number,
+            url: presigned.uri.to_string(),
+            headers: headers(&presigned),
+        })
+    }
+
+    /// the parts of `token`'s upload the storage received
+    pub async fn list_parts(storage: &Storage, token: &str) -> Result<Vec<UploadedPart>, String> {
+        let claims = verify(token)?;
+
+        storage
+            .provider()
+            .list_parts(&claims.sub, &claims.upload_id)
+            .await
+    }
+
+    /// puts `parts` together, and returns a token for
+    /// [`Attachment::confirm_upload`](`super::Attachment::confirm_upload`)
+    pub async fn complete(
+        storage: &Storage,
+        token: &str,
+        parts: Vec<UploadedPart>,
+    ) -> Result<String, String> {
+        let claims = verify(token)?;
+
+        storage
+            .provider()
+            .complete_multipart(&claims.sub, &claims.upload_id, parts)
+            .await?;
+
+        DirectUpload::sign(&claims.sub, claims.file_name, CONFIRM_TTL)
+    }
+
+    /// cancels `token`'s upload
+    pub async fn abort(storage: &Storage, token: &str) -> Result<(), String> {
+        let claims = verify(token)?;
+
+        storage
+            .provider()
+            .abort_multipart(&claims.sub, &claims.upload_id)
+            .await
+    }
+}
+
+fn verify(token: &str) -> Result<MultipartClaims, String> {
+    let claims = jsonwebtoken::decode::<MultipartClaims>(
+        token,
+        &DecodingKey::from_secret(secret_key().as_bytes()),
+        &Validation::default(),
+    )
+    .map_err(|_| "Invalid or expired upload token".to_string())?
+    .claims;
+
+    if claims.token_type != "multipart_upload_token" {
+        return Err("Invalid or expired upload token".to_string());
+    }
+
+    Ok(claims)
+}
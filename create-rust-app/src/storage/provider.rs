@@ -0,0 +1,297 @@
+//! Where [`Storage`](`super::Storage`) keeps files: an S3 bucket ([`S3Provider`](`super::S3Provider`),
+//! the default), a Google Cloud Storage bucket (`GcsProvider`, `plugin_storage_gcs` feature), an Azure
+//! Blob Storage container (`AzureProvider`, `plugin_storage_azure` feature), a local
+//! directory ([`LocalStorage`](`super::LocalStorage`)), memory
+//! ([`MemoryStorage`](`super::MemoryStorage`), for tests) or your own [`StorageProvider`]:
+//!
+//! ```rust,ignore
+//! use create_rust_app::storage::{PresignAction, PresignedURI, StorageFuture, StorageProvider};
+//! use create_rust_app::Storage;
+//!
+//! struct Azure { /* ... */ }
+//!
+//! impl StorageProvider for Azure {
+//!     fn name(&self) -> &'static str {
+//!         "azure"
+//!     }
+//!
+//!     fn put<'a>(&'a self, key: &'a str, bytes: Vec<u8>, content_type: &'a str) -> StorageFuture<'a, ()> {
+//!         Box::pin(async move { self.upload_blob(key, bytes, content_type).await })
+//!     }
+//!     // ...
+//! }
+//!
+//! let storage = Storage::with_provider(Azure::new());
+//! ```
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::Stream;
+use http::{HeaderMap, Uri};
+use serde::{Deserialize, Serialize};
+
+/// what the [`StorageProvider`]s' methods return, errors are human readable messages
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// the content of a file, in chunks, see [`StorageProvider::open`]
+pub type ObjectStream = Pin<Box<dyn Stream<Item = Result<Bytes, String>> + Send>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// what a presigned link allows
+pub enum PresignAction {
+    /// downloading the file
+    Get,
+    /// uploading the file, with a `PUT` request
+    Put,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// what a provider knows about a stored file, see [`StorageProvider::stat`]
+pub struct ObjectStat {
+    pub byte_size: u64,
+    /// the file's md5 (hex), or the provider's equivalent (ex: S3's ETag for multipart uploads)
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone)]
+/// a link giving access to a file without credentials, see [`StorageProvider::presign`]
+pub struct PresignedURI {
+    /// headers the request must be sent with
+    pub headers: HeaderMap,
+    pub uri: Uri,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// a part of a multipart upload the storage received, see [`StorageProvider::create_multipart`]
+pub struct UploadedPart {
+    /// from 1 to 10000
+    pub part_number: i32,
+    /// returned in the `ETag` header of the part's `PUT` response
+    pub etag: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// the bytes asked for by a `Range` header, see [`StorageProvider::open`]
+pub enum ByteRange {
+    /// `bytes=start-` or `bytes=start-end`, `end` included
+    From { start: u64, end: Option<u64> },
+    /// `bytes=-length`, the last `length` bytes
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// parses a `Range` header's value, multiple ranges (`bytes=0-1,5-6`) aren't supported
+    pub fn parse(header: &str) -> Option<ByteRange> {
+        let range = header.trim().strip_prefix("bytes=")?;
+
+        if range.contains(',') {
+            return None;
+        }
+
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (start.trim(), end.trim());
+
+        if start.is_empty() {
+            return end.parse().ok().map(ByteRange::Suffix);
+        }
+
+        let start = start.parse().ok()?;
+        let end = match end {
+            "" => None,
+            end => Some(end.parse().ok()?),
+        };
+
+        match end {
+            Some(end) if end < start => None,
+            _ => Some(ByteRange::From { start, end }),
+        }
+    }
+
+    /// the first and last (included) bytes of the range in a file of `byte_size` bytes, `None`
+    /// when the range is outside of the file
+    pub fn resolve(&self, byte_size: u64) -> Option<(u64, u64)> {
+        if byte_size == 0 {
+            return None;
+        }
+
+        match *self {
+            ByteRange::From { start, end } if start < byte_size => Some((
+                start,
+                end.map_or(byte_size - 1, |end| end.min(byte_size - 1)),
+            )),
+            ByteRange::From { .. } | ByteRange::Suffix(0) => None,
+            ByteRange::Suffix(length) => Some((byte_size.saturating_sub(length), byte_size - 1)),
+        }
+    }
+}
+
+impl std::fmt::Display for ByteRange {
+    /// the `Range` header's value
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ByteRange::From { start, end: None } => write!(f, "bytes={start}-"),
+            ByteRange::From {
+                start,
+                end: Some(end),
+            } => write!(f, "bytes={start}-{end}"),
+            ByteRange::Suffix(length) => write!(f, "bytes=-{length}"),
+        }
+    }
+}
+
+/// a file being read, see [`StorageProvider::open`]
+pub struct ObjectReader {
+    /// the bytes of `range`, or of the whole file
+    pub stream: ObjectStream,
+    /// the size of the whole file
+    pub byte_size: u64,
+    /// the first and last (included) bytes in `stream`, `None` when it has the whole file
+    pub range: Option<(u64, u64)>,
+    /// changes when the file does, for `ETag` headers (quotes included)
+    pub etag: Option<String>,
+    pub content_type: Option<String>,
+}
+
+/// stores files by key
+pub trait StorageProvider: Send + Sync {
+    /// identifies the provider in [`AttachmentBlob::service_name`](`super::AttachmentBlob`)
+    fn name(&self) -> &'static str;
+
+    /// stores `bytes` under `key`, replacing what was there
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        bytes: Vec<u8>,
+        content_type: &'a str,
+    ) -> StorageFuture<'a, ()>;
+
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Vec<u8>>;
+
+    /// removes `key`, missing keys aren't an error
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()>;
+
+    /// removes every key in `keys`, one by one unless the provider can do better
+    fn delete_many<'a>(&'a self, keys: &'a [String]) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            for key in keys {
+                self.delete(key).await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// the size and checksum of `key`'s file, downloads it unless the provider can do better
+    fn stat<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ObjectStat> {
+        Box::pin(async move {
+            let data = self.get(key).await?;
+
+            Ok(ObjectStat {
+                byte_size: data.len() as u64,
+                checksum: format!("{:x}", md5::compute(&data)),
+            })
+        })
+    }
+
+    /// reads `key`'s file as a stream, only the bytes in `range` when it's given and the provider
+    /// supports ranges (check [`ObjectReader::range`]); the default downloads the whole file
+    fn open<'a>(
+        &'a self,
+        key: &'a str,
+        range: Option<ByteRange>,
+    ) -> StorageFuture<'a, ObjectReader> {
+        Box::pin(async move {
+            let data = self.get(key).await?;
+            let byte_size = data.len() as u64;
+            let etag = format!("\"{:x}\"", md5::compute(&data));
+
+            let range = range.and_then(|range| range.resolve(byte_size));
+            let data = match range {
+                Some((start, end)) => data[start as usize..=end as usize].to_vec(),
+                None => data,
+            };
+
+            Ok(ObjectReader {
+                stream: Box::pin(futures_util::stream::once(
+                    async move { Ok(Bytes::from(data)) },
+                )),
+                byte_size,
+                range,
+                etag: Some(etag),
+                content_type: None,
+            })
+        })
+    }
+
+    /// a link allowing `action` on `key` until `expires_in` from now, or a permanent public link
+    /// when `expires_in` is `None` (if the provider has public links)
+    fn presign<'a>(
+        &'a self,
+        key: &'a str,
+        action: PresignAction,
+        expires_in: Option<Duration>,
+    ) -> StorageFuture<'a, PresignedURI>;
+
+    /// starts a multipart upload of `key` and returns its id; parts are uploaded with
+    /// [`StorageProvider::presign_part`] links, then put together with
+    /// [`StorageProvider::complete_multipart`]
+    fn create_multipart<'a>(
+        &'a self,
+        _key: &'a str,
+        _content_type: &'a str,
+    ) -> StorageFuture<'a, String> {
+        unsupported(self.name())
+    }
+
+    /// a link to `PUT` the part `part_number` of a multipart upload
+    fn presign_part<'a>(
+        &'a self,
+        _key: &'a str,
+        _upload_id: &'a str,
+        _part_number: i32,
+        _expires_in: Duration,
+    ) -> StorageFuture<'a, PresignedURI> {
+        unsupported(self.name())
+    }
+
+    /// the parts of a multipart upload the storage received so far, to resume it
+    fn list_parts<'a>(
+        &'a self,
+        _key: &'a str,
+        _upload_id: &'a str,
+    ) -> StorageFuture<'a, Vec<UploadedPart>> {
+        unsupported(self.name())
+    }
+
+    /// puts `parts` together as `key`'s file
+    fn complete_multipart<'a>(
+        &'a self,
+        _key: &'a str,
+        _upload_id: &'a str,
+        _parts: Vec<UploadedPart>,
+    ) -> StorageFuture<'a, ()> {
+        unsupported(self.name())
+    }
+
+    /// cancels a multipart upload, deleting the parts received
+    fn abort_multipart<'a>(&'a self, _key: &'a str, _upload_id: &'a str) -> StorageFuture<'a, ()> {
+        unsupported(self.name())
+    }
+
+    /// whether `token` allows `action` on `key`, for providers whose links point at the backend's
+    /// `/api/storage` endpoints (ex: [`LocalStorage`](`super::LocalStorage`))
+    fn verify(&self, _key: &str, _action: PresignAction, _token: &str) -> bool {
+        false
+    }
+}
+
+fn unsupported<'a, T>(name: &'static str) -> StorageFuture<'a, T> {
+    Box::pin(async move {
+        Err(format!(
+            "Multipart uploads are not supported by the '{name}' storage"
+        ))
+    })
+}
@@ -0,0 +1,113 @@
+//! Limits the bytes each user's attachments take in the [`Storage`](`super::Storage`), checked by
+//! [`Attachment::attach`] and [`Attachment::confirm_upload`] when they're given the uploader's id.
+//!
+//! Every user gets the same quota with `STORAGE_QUOTA_BYTES` in your `.env` (unlimited when it's
+//! not set):
+//!
+//! ```text
+//! STORAGE_QUOTA_BYTES=5000000000
+//! ```
+//!
+//! or each user gets their own (ex: from their plan):
+//!
+//! ```rust,ignore
+//! let storage = Storage::new().with_quota(StorageQuota::per_user(|db, user_id| {
+//!     Plan::for_user(db, user_id).ok().map(|plan| plan.storage_bytes)
+//! }));
+//! ```
+use std::sync::Arc;
+
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+use super::{Attachment, ID};
+use crate::Connection;
+
+/// how the errors of [`StorageQuota::check`] start
+pub(crate) const QUOTA_EXCEEDED: &str = "Storage quota exceeded";
+
+type Limit = dyn Fn(&mut Connection, ID) -> Option<u64> + Send + Sync;
+
+#[derive(Clone)]
+/// how many bytes a user may store, see the [module documentation](`self`)
+pub struct StorageQuota {
+    limit: Arc<Limit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// how many bytes a user's attachments take, see [`StorageQuota::usage`]
+pub struct StorageUsage {
+    pub used_bytes: i64,
+    /// `None` when the user's storage is unlimited
+    pub quota_bytes: Option<i64>,
+}
+
+impl Default for StorageQuota {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+impl StorageQuota {
+    pub fn unlimited() -> Self {
+        Self::per_user(|_, _| None)
+    }
+
+    /// every user may store `limit` bytes
+    pub fn bytes(limit: u64) -> Self {
+        Self::per_user(move |_, _| Some(limit))
+    }
+
+    /// `limit` returns the bytes a user may store, `None` when it's unlimited
+    pub fn per_user(
+        limit: impl Fn(&mut Connection, ID) -> Option<u64> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            limit: Arc::new(limit),
+        }
+    }
+
+    /// the bytes in `STORAGE_QUOTA_BYTES`, unlimited when it's not set
+    pub fn from_env() -> Self {
+        match std::env::var("STORAGE_QUOTA_BYTES") {
+            Ok(limit) if !limit.is_empty() => Self::bytes(
+                limit
+                    .parse()
+                    .expect("STORAGE_QUOTA_BYTES must be a number of bytes"),
+            ),
+            _ => Self::unlimited(),
+        }
+    }
+
+    /// the bytes `user_id` may store, `None` when it's unlimited
+    pub fn limit(&self, db: &mut Connection, user_id: ID) -> Option<u64> {
+        (self.limit)(db, user_id)
+    }
+
+    pub fn usage(&self, db: &mut Connection, user_id: ID) -> QueryResult<StorageUsage> {
+        Ok(StorageUsage {
+            used_bytes: Attachment::total_bytes_for_user(db, user_id)?,
+            quota_bytes: self.limit(db, user_id).map(|limit| limit as i64),
+        })
+    }
+
+    /// an error unless `user_id` can store `byte_size` more bytes
+    pub fn check(&self, db: &mut Connection, user_id: ID, byte_size: u64) -> Result<(), String> {
+        let limit = match self.limit(db, user_id) {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+
+        let used = Attachment::total_bytes_for_user(db, user_id)
+            .map_err(|err| err.to_string())?
+            .max(0) as u64;
+
+        if used + byte_size > limit {
+            return Err(format!(
+                "{QUOTA_EXCEEDED} (user: '{user_id}', used: {used} bytes, quota: {limit} bytes, upload: {byte_size} bytes)"
+            ));
+        }
+
+        Ok(())
+    }
+}
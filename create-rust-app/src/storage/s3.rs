@@ -0,0 +1,574 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use aws_sdk_s3::model::{CompletedMultipartUpload, CompletedPart, Delete, ObjectIdentifier};
+use aws_sdk_s3::presigning::config::PresigningConfig;
+use aws_sdk_s3::types::ByteStream;
+//use aws_sdk_s3::types::SdkError::*;
+use aws_sdk_s3::{Client, Config, Endpoint};
+use aws_types::region::Region;
+use aws_types::Credentials;
+//use base64;
+use http::{HeaderMap, Uri};
+
+use super::{
+    ByteRange, ObjectReader, ObjectStat, PresignAction, PresignedURI, StorageFuture,
+    StorageProvider, UploadedPart,
+};
+
+#[derive(Clone)]
+/// keeps files in an S3-compatible bucket, configured by the `S3_*` environment variables
+pub struct S3Provider {
+    client: Option<Client>,
+    bucket: String,
+    host: String,
+}
+
+impl S3Provider {
+    pub fn new(
+        host: String,
+        region: String,
+        bucket: String,
+        access_key_id: String,
+        secret_access_key: String,
+    ) -> S3Provider {
+        let client = S3Provider::init(host.clone(), region, access_key_id, secret_access_key)
+            .unwrap_or(None);
+
+        S3Provider {
+            client,
+            bucket,
+            host,
+        }
+    }
+
+    /// reads `S3_HOST`, `S3_REGION`, `S3_BUCKET`, `S3_ACCESS_KEY_ID` and `S3_SECRET_ACCESS_KEY`
+    pub fn from_env() -> S3Provider {
+        S3Provider::check_environment_variables();
+
+        let host = std::env::var("S3_HOST").unwrap_or_else(|_| "".to_string());
+        let region = std::env::var("S3_REGION").unwrap_or_else(|_| "".to_string());
+        let bucket = std::env::var("S3_BUCKET").unwrap_or_else(|_| "".to_string());
+        let access_key_id = std::env::var("S3_ACCESS_KEY_ID").unwrap_or_else(|_| "".to_string());
+        let secret_access_key =
+            std::env::var("S3_SECRET_ACCESS_KEY").unwrap_or_else(|_| "".to_string());
+
+        S3Provider::new(host, region, bucket, access_key_id, secret_access_key)
+    }
+
+    fn error_string(&self, message: &'static str, key: String, error: String) -> String {
+        let bucket = &self.bucket;
+        format!("{message} (bucket: '{bucket}', key: '{key}', error: '{error}')")
+    }
+
+    fn client_or_error(&self) -> Result<&Client, String> {
+        self.client.as_ref().ok_or_else(|| {
+            "The storage is not available; did you set the right environment variables?".to_string()
+        })
+    }
+
+    fn check_environment_variables() {
+        let vars = vec![
+            "S3_HOST",
+            "S3_REGION",
+            "S3_BUCKET",
+            "S3_ACCESS_KEY_ID",
+            "S3_SECRET_ACCESS_KEY",
+        ];
+
+        let unset_vars = vars
+            .into_iter()
+            .filter(|v| std::env::var(v).is_err())
+            .collect::<Vec<_>>();
+
+        if !unset_vars.is_empty() {
+            println!(
+                "Warning: Storage disabled; the following variables must be set: {}",
+                unset_vars.join(", ")
+            );
+        }
+    }
+
+    fn init(
+        host: String,
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+    ) -> Result<Option<Client>, String> {
+        let host = host;
+        let region = Region::new(region);
+        let s3_config = Config::builder()
+            .region(region)
+            .endpoint_resolver(Endpoint::immutable(Uri::from_str(host.as_str()).map_err(
+                |err| {
+                    let error = err.to_string();
+                    format!("Could not initialize storage (error: '{error}')")
+                },
+            )?))
+            .credentials_provider(Credentials::new(
+                access_key_id,
+                secret_access_key,
+                None,
+                None,
+                "UNNAMED_PROVIDER",
+            ))
+            .build();
+        let client = Client::from_conf(s3_config);
+
+        Ok(Some(client))
+    }
+}
+
+impl StorageProvider for S3Provider {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        bytes: Vec<u8>,
+        content_type: &'a str,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let stream = ByteStream::from(bytes);
+
+            let client = self.client_or_error()?;
+
+            client
+                .put_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .body(stream)
+                .content_type(content_type)
+                // TODO: Error { code: \"InvalidDigest\", message: \"The Content-Md5 you specified is not valid.\", request_id: \"16DBB0A878146F1A\" }
+                // .content_md5(base64::encode(content_md5))
+                .send()
+                .await
+                .map_err(|err| {
+                    self.error_string("Could not upload object", key.to_string(), err.to_string())
+                })?;
+
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            let response = client
+                .get_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .send()
+                .await
+                .map_err(|err| {
+                    self.error_string(
+                        "Could not download object",
+                        key.to_string(),
+                        err.to_string(),
+                    )
+                })?;
+
+            let data = response.body.collect().await.map_err(|err| {
+                self.error_string(
+                    "Could not download object",
+                    key.to_string(),
+                    err.to_string(),
+                )
+            })?;
+
+            Ok(data.into_bytes().to_vec())
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            client
+                .delete_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .send()
+                .await
+                .map_err(|err| {
+                    self.error_string("Could not delete object", key.to_string(), err.to_string())
+                })?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_many<'a>(&'a self, keys: &'a [String]) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            let ids = keys
+                .iter()
+                .map(|k| {
+                    ObjectIdentifier::builder()
+                        .set_key(Some(k.to_string()))
+                        .build()
+                })
+                .collect::<Vec<ObjectIdentifier>>();
+            let delete = Delete::builder().set_objects(Some(ids)).build();
+
+            client
+                .delete_objects()
+                .bucket(&self.bucket)
+                .delete(delete)
+                .send()
+                .await
+                .map_err(|err| {
+                    self.error_string(
+                        "Could not delete objects",
+                        format!("{keys:#?}"),
+                        err.to_string(),
+                    )
+                })?;
+
+            Ok(())
+        })
+    }
+
+    fn stat<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ObjectStat> {
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            let response = client
+                .head_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .send()
+                .await
+                .map_err(|err| {
+                    self.error_string("Could not find object", key.to_string(), err.to_string())
+                })?;
+
+            Ok(ObjectStat {
+                byte_size: response.content_length().max(0) as u64,
+                checksum: response
+                    .e_tag()
+                    .unwrap_or_default()
+                    .trim_matches('"')
+                    .to_string(),
+            })
+        })
+    }
+
+    /// sends `range` along with the request, S3 only returns the bytes asked for
+    fn open<'a>(
+        &'a self,
+        key: &'a str,
+        range: Option<ByteRange>,
+    ) -> StorageFuture<'a, ObjectReader> {
+        use futures_util::TryStreamExt;
+
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            let response = match client
+                .get_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .set_range(range.map(|range| range.to_string()))
+                .send()
+                .await
+            {
+                Ok(response) => response,
+                // a range outside of the object, the endpoints answer with a 416 when
+                // `ObjectReader::range` is missing
+                Err(err) if range.is_some() && err.to_string().contains("InvalidRange") => {
+                    let stat = self.stat(key).await?;
+
+                    return Ok(ObjectReader {
+                        stream: Box::pin(futures_util::stream::empty()),
+                        byte_size: stat.byte_size,
+                        range: None,
+                        etag: Some(format!("\"{}\"", stat.checksum)),
+                        content_type: None,
+                    });
+                }
+                Err(err) => {
+                    return Err(self.error_string(
+                        "Could not download object",
+                        key.to_string(),
+                        err.to_string(),
+                    ))
+                }
+            };
+
+            let content_length = response.content_length().max(0) as u64;
+            // "bytes {start}-{end}/{byte_size}"
+            let content_range = response.content_range().and_then(|content_range| {
+                let (range, byte_size) = content_range.strip_prefix("bytes ")?.split_once('/')?;
+                let (start, end) = range.split_once('-')?;
+
+                Some((
+                    (start.parse::<u64>().ok()?, end.parse::<u64>().ok()?),
+                    byte_size.parse::<u64>().ok()?,
+                ))
+            });
+            let (range, byte_size) = match content_range {
+                Some((range, byte_size)) => (Some(range), byte_size),
+                None => (None, content_length),
+            };
+
+            let etag = response.e_tag().map(str::to_string);
+            let content_type = response.content_type().map(str::to_string);
+            let key = key.to_string();
+
+            Ok(ObjectReader {
+                stream: Box::pin(response.body.map_err(move |err| {
+                    format!("Could not download object (key: '{key}', error: '{err}')")
+                })),
+                byte_size,
+                range,
+                etag,
+                content_type,
+            })
+        })
+    }
+
+    /// if `expires_in` is `None`, then we assume the bucket is publicly accessible and return the
+    /// public URL. For this to work, you have to make sure the bucket's policy allows public access.
+    fn presign<'a>(
+        &'a self,
+        key: &'a str,
+        action: PresignAction,
+        expires_in: Option<Duration>,
+    ) -> StorageFuture<'a, PresignedURI> {
+        Box::pin(async move {
+            let message = match action {
+                PresignAction::Get => "Could not retrieve download URI",
+                PresignAction::Put => "Could not retrieve upload URI",
+            };
+
+            let expires_in = match expires_in {
+                Some(expires_in) => expires_in,
+                None => {
+                    let host = self.host.clone();
+                    let host = if host.ends_with('/') {
+                        host
+                    } else {
+                        format!("{host}/")
+                    };
+                    let bucket = &self.bucket;
+
+                    return Ok(PresignedURI {
+                        uri: Uri::from_str(&format!("{host}{bucket}/{key}")).map_err(|err| {
+                            self.error_string(message, key.to_string(), err.to_string())
+                        })?,
+                        headers: HeaderMap::new(),
+                    });
+                }
+            };
+
+            let client = self.client_or_error()?;
+
+            let config = PresigningConfig::expires_in(expires_in)
+                .map_err(|err| self.error_string(message, key.to_string(), err.to_string()))?;
+
+            let response = match action {
+                PresignAction::Get => client
+                    .get_object()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .presigned(config)
+                    .await
+                    .map_err(|err| err.to_string()),
+                PresignAction::Put => client
+                    .put_object()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .presigned(config)
+                    .await
+                    .map_err(|err| err.to_string()),
+            }
+            .map_err(|err| self.error_string(message, key.to_string(), err))?;
+
+            Ok(PresignedURI {
+                uri: response.uri().clone(),
+                headers: response.headers().clone(),
+            })
+        })
+    }
+
+    fn create_multipart<'a>(
+        &'a self,
+        key: &'a str,
+        content_type: &'a str,
+    ) -> StorageFuture<'a, String> {
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            let response = client
+                .create_multipart_upload()
+                .bucket(&self.bucket)
+                .key(key)
+                .content_type(content_type)
+                .send()
+                .await
+                .map_err(|err| {
+                    self.error_string(
+                        "Could not start multipart upload",
+                        key.to_string(),
+                        err.to_string(),
+                    )
+                })?;
+
+            response
+                .upload_id()
+                .map(|id| id.to_string())
+                .ok_or_else(|| {
+                    self.error_string(
+                        "Could not start multipart upload",
+                        key.to_string(),
+                        "no upload id".to_string(),
+                    )
+                })
+        })
+    }
+
+    fn presign_part<'a>(
+        &'a self,
+        key: &'a str,
+        upload_id: &'a str,
+        part_number: i32,
+        expires_in: Duration,
+    ) -> StorageFuture<'a, PresignedURI> {
+        Box::pin(async move {
+            let message = "Could not retrieve part upload URI";
+            let client = self.client_or_error()?;
+
+            let config = PresigningConfig::expires_in(expires_in)
+                .map_err(|err| self.error_string(message, key.to_string(), err.to_string()))?;
+
+            let response = client
+                .upload_part()
+                .bucket(&self.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .presigned(config)
+                .await
+                .map_err(|err| self.error_string(message, key.to_string(), err.to_string()))?;
+
+            Ok(PresignedURI {
+                uri: response.uri().clone(),
+                headers: response.headers().clone(),
+            })
+        })
+    }
+
+    fn list_parts<'a>(
+        &'a self,
+        key: &'a str,
+        upload_id: &'a str,
+    ) -> StorageFuture<'a, Vec<UploadedPart>> {
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            let mut parts = vec![];
+            let mut marker: Option<String> = None;
+
+            // parts are listed 1000 at a time
+            loop {
+                let response = client
+                    .list_parts()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .upload_id(upload_id)
+                    .set_part_number_marker(marker.clone())
+                    .send()
+                    .await
+                    .map_err(|err| {
+                        self.error_string("Could not list parts", key.to_string(), err.to_string())
+                    })?;
+
+                parts.extend(response.parts().unwrap_or_default().iter().map(|part| {
+                    UploadedPart {
+                        part_number: part.part_number(),
+                        etag: part.e_tag().unwrap_or_default().to_string(),
+                    }
+                }));
+
+                marker = response.next_part_number_marker().map(|m| m.to_string());
+                if !response.is_truncated() || marker.is_none() {
+                    break;
+                }
+            }
+
+            Ok(parts)
+        })
+    }
+
+    fn complete_multipart<'a>(
+        &'a self,
+        key: &'a str,
+        upload_id: &'a str,
+        mut parts: Vec<UploadedPart>,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            parts.sort_by_key(|part| part.part_number);
+            let parts = parts
+                .into_iter()
+                .map(|part| {
+                    CompletedPart::builder()
+                        .part_number(part.part_number)
+                        .e_tag(part.etag)
+                        .build()
+                })
+                .collect::<Vec<_>>();
+
+            client
+                .complete_multipart_upload()
+                .bucket(&self.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .multipart_upload(
+                    CompletedMultipartUpload::builder()
+                        .set_parts(Some(parts))
+                        .build(),
+                )
+                .send()
+                .await
+                .map_err(|err| {
+                    self.error_string(
+                        "Could not complete multipart upload",
+                        key.to_string(),
+                        err.to_string(),
+                    )
+                })?;
+
+            Ok(())
+        })
+    }
+
+    fn abort_multipart<'a>(&'a self, key: &'a str, upload_id: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let client = self.client_or_error()?;
+
+            client
+                .abort_multipart_upload()
+                .bucket(&self.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .send()
+                .await
+                .map_err(|err| {
+                    self.error_string(
+                        "Could not abort multipart upload",
+                        key.to_string(),
+                        err.to_string(),
+                    )
+                })?;
+
+            Ok(())
+        })
+    }
+}
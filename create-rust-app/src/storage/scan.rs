@@ -0,0 +1,208 @@
+//! Scans files for viruses before they're attached, with an [`UploadScanner`]:
+//! [`Attachment::attach`](`super::Attachment::attach`) scans the bytes it's given and
+//! [`Attachment::confirm_upload`](`super::Attachment::confirm_upload`) downloads the uploaded file
+//! to scan it. The result is kept in [`Attachment::scan_status`](`super::Attachment::scan_status`).
+//!
+//! Infected files are either rejected (deleted, the attachment isn't created) or quarantined (the
+//! attachment is created without download links), see [`OnInfected`]. When the scanner fails, the
+//! attachment is created with the [`ScanStatus::Failed`] status so it can be checked later.
+//!
+//! A [ClamAV](https://www.clamav.net) daemon is used when `CLAMAV_ADDRESS` is set in your `.env`:
+//!
+//! ```text
+//! CLAMAV_ADDRESS=localhost:3310
+//! ```
+//!
+//! or with your own scanner:
+//!
+//! ```rust,ignore
+//! let storage = Storage::new().with_scanner(MyScanner::new(), OnInfected::Quarantine);
+//! ```
+use std::str::FromStr;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::{Storage, StorageFuture};
+
+/// how the errors of rejected files start, see [`OnInfected::Reject`]
+pub(crate) const FILE_REJECTED: &str = "File rejected by the virus scanner";
+
+/// how many bytes are sent to clamd at a time
+const CLAMAV_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// what an [`UploadScanner`] found in a file
+pub enum ScanVerdict {
+    Clean,
+    /// `signature` names the virus (ex: "Eicar-Test-Signature")
+    Infected {
+        signature: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// what happens to infected files
+pub enum OnInfected {
+    /// the attachment is created as [`ScanStatus::Quarantined`], it never gets download links
+    Quarantine,
+    /// the file is deleted and the attachment isn't created
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// stored in [`Attachment::scan_status`](`super::Attachment::scan_status`)
+pub enum ScanStatus {
+    /// no scanner was configured when the file was attached
+    Unscanned,
+    Clean,
+    /// infected, see [`OnInfected::Quarantine`]
+    Quarantined,
+    /// the scanner couldn't scan the file
+    Failed,
+}
+
+impl ScanStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanStatus::Unscanned => "unscanned",
+            ScanStatus::Clean => "clean",
+            ScanStatus::Quarantined => "quarantined",
+            ScanStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for ScanStatus {
+    type Err = String;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "unscanned" => Ok(ScanStatus::Unscanned),
+            "clean" => Ok(ScanStatus::Clean),
+            "quarantined" => Ok(ScanStatus::Quarantined),
+            "failed" => Ok(ScanStatus::Failed),
+            _ => Err(format!("Unknown scan status '{status}'")),
+        }
+    }
+}
+
+/// checks files before they're attached, see the [module documentation](`self`)
+pub trait UploadScanner: Send + Sync {
+    fn scan<'a>(&'a self, file_name: &'a str, data: &'a [u8]) -> StorageFuture<'a, ScanVerdict>;
+}
+
+#[derive(Debug, Clone)]
+/// scans files with a ClamAV daemon (clamd), through its `INSTREAM` command
+pub struct ClamAvScanner {
+    address: String,
+}
+
+impl ClamAvScanner {
+    /// `address` is clamd's TCP socket (ex: "localhost:3310")
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+        }
+    }
+
+    /// the daemon at `CLAMAV_ADDRESS`
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("CLAMAV_ADDRESS").expect("No CLAMAV_ADDRESS environment variable set!"),
+        )
+    }
+}
+
+impl UploadScanner for ClamAvScanner {
+    fn scan<'a>(&'a self, file_name: &'a str, data: &'a [u8]) -> StorageFuture<'a, ScanVerdict> {
+        Box::pin(async move {
+            let address = &self.address;
+            let error = |err: std::io::Error| {
+                format!("Could not scan file (address: '{address}', file: '{file_name}', error: '{err}')")
+            };
+
+            let mut stream = tokio::net::TcpStream::connect(address)
+                .await
+                .map_err(error)?;
+
+            stream.write_all(b"zINSTREAM\0").await.map_err(error)?;
+            for chunk in data.chunks(CLAMAV_CHUNK_SIZE) {
+                stream
+                    .write_all(&(chunk.len() as u32).to_be_bytes())
+                    .await
+                    .map_err(error)?;
+                stream.write_all(chunk).await.map_err(error)?;
+            }
+            stream.write_all(&0u32.to_be_bytes()).await.map_err(error)?;
+
+            // clamd answers "stream: OK", "stream: {signature} FOUND" or "{reason} ERROR", then
+            // closes the connection
+            let mut response = vec![];
+            stream.read_to_end(&mut response).await.map_err(error)?;
+            let response = String::from_utf8_lossy(&response);
+            let response = response.trim_end_matches('\0').trim();
+
+            if response == "stream: OK" {
+                return Ok(ScanVerdict::Clean);
+            }
+
+            match response
+                .strip_prefix("stream: ")
+                .and_then(|found| found.strip_suffix(" FOUND"))
+            {
+                Some(signature) => Ok(ScanVerdict::Infected {
+                    signature: signature.to_string(),
+                }),
+                None => Err(format!(
+                    "Could not scan file (address: '{address}', file: '{file_name}', response: '{response}')"
+                )),
+            }
+        })
+    }
+}
+
+/// the scan status of a new attachment's file, or an error when it's rejected
+pub(crate) async fn scan_upload(
+    storage: &Storage,
+    file_name: &str,
+    data: &[u8],
+) -> Result<ScanStatus, String> {
+    let scanner = match storage.scanner() {
+        Some(scanner) => scanner,
+        None => return Ok(ScanStatus::Unscanned),
+    };
+
+    match scanner.scan(file_name, data).await {
+        Ok(ScanVerdict::Clean) => Ok(ScanStatus::Clean),
+        Ok(ScanVerdict::Infected { signature }) => match storage.on_infected() {
+            OnInfected::Quarantine => Ok(ScanStatus::Quarantined),
+            OnInfected::Reject => Err(format!(
+                "{FILE_REJECTED} (file: '{file_name}', signature: '{signature}')"
+            )),
+        },
+        Err(err) => {
+            println!("{err}");
+            Ok(ScanStatus::Failed)
+        }
+    }
+}
+
+/// [`scan_upload`] for a file already in the storage, which is deleted when it's rejected
+pub(crate) async fn scan_stored(
+    storage: &Storage,
+    key: &str,
+    file_name: &str,
+) -> Result<ScanStatus, String> {
+    if storage.scanner().is_none() {
+        return Ok(ScanStatus::Unscanned);
+    }
+
+    let data = storage.provider().get(key).await?;
+    let status = scan_upload(storage, file_name, &data).await;
+
+    if status.is_err() {
+        storage.delete(key.to_string()).await.ok();
+    }
+
+    status
+}
@@ -19,6 +19,9 @@ table! {
         record_id -> Int4,
         blob_id -> Int4,
         created_at -> Timestamptz,
+        private -> Bool,
+        user_id -> Nullable<Int4>,
+        scan_status -> Text,
     }
 }
 
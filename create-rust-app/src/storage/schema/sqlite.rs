@@ -19,6 +19,9 @@ table! {
       record_id -> Integer,
       blob_id -> Integer,
       created_at -> Timestamp,
+      private -> Bool,
+      user_id -> Nullable<Integer>,
+      scan_status -> Text,
   }
 }
 
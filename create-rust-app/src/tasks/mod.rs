@@ -13,6 +13,6 @@ pub fn queue() -> &'static Queue {
     QUEUE.get_or_init(|| {
         let db = Database::new();
 
-        Queue::builder().connection_pool(db.pool.clone()).build()
+        Queue::builder().connection_pool((*db.pool).clone()).build()
     })
 }
@@ -3,7 +3,11 @@ use std::str::FromStr;
 #[cfg(debug_assertions)]
 use std::sync::Mutex;
 
+#[cfg(debug_assertions)]
+use super::frontend::serves_dist_in_development;
 use super::template_utils::SinglePageApplication;
+use super::workspace_utils::DIST_DIR;
+#[cfg(debug_assertions)]
 use super::workspace_utils::FRONTEND_DIR;
 use crate::util::template_utils::{to_template_name, DEFAULT_TEMPLATE, TEMPLATES};
 use actix_files::NamedFile;
@@ -46,7 +50,7 @@ static REQUEST_REFRESH_COUNT: Mutex<i32> = Mutex::new(0);
 /// for a html file/template at the matching path (in this case, ./foo/bar.html),
 /// defaults to index.html
 ///
-/// then, your frontend (all the css files, scripts, etc. in your frontend's vite manifest (at ./frontend/dist/manifest.json))
+/// then, your frontend (all the css files, scripts, etc. in your frontend's build (by default the vite manifest at ./frontend/dist/manifest.json, see [`frontend`](`crate::frontend`)))
 /// will be compiled and injected into the template wherever `{{ bundle(name="index.tsx") }}` is (the `index.tsx` can be any .tsx file in ./frontend/bundles)
 ///
 /// then, that compiled html is sent to the client
@@ -114,12 +118,19 @@ pub async fn render_views(req: HttpRequest) -> HttpResponse {
                 println!("PUBLIC_FILE {path} => {public_path}");
                 return NamedFile::open(public_path).unwrap().into_response(&req);
             }
+
+            // bundlers other than vite write their development builds to the dist directory
+            let dist_path = &format!("{dist_dir}{path}", dist_dir = *DIST_DIR);
+            if serves_dist_in_development() && std::path::PathBuf::from(dist_path).is_file() {
+                println!("DIST_FILE {path} => {dist_path}");
+                return NamedFile::open(dist_path).unwrap().into_response(&req);
+            }
         }
 
         #[cfg(not(debug_assertions))]
         {
             // production asset serving
-            let static_path = &format!("{frontend_dir}/dist{path}", frontend_dir = *FRONTEND_DIR);
+            let static_path = &format!("{dist_dir}{path}", dist_dir = *DIST_DIR);
             if std::path::PathBuf::from(static_path).is_file() {
                 return NamedFile::open(static_path).unwrap().into_response(&req);
             }
@@ -146,7 +157,7 @@ fn template_response(_req: HttpRequest, content: String) -> HttpResponse {
     #[cfg(debug_assertions)]
     let mut content = content;
     #[cfg(debug_assertions)]
-    {
+    if !serves_dist_in_development() {
         let uri = Uri::from_str(_req.connection_info().host());
         let hostname = match &uri {
             Ok(uri) => uri.host().unwrap_or("localhost"),
@@ -178,3 +189,27 @@ fn template_response(_req: HttpRequest, content: String) -> HttpResponse {
         .content_type("text/html")
         .body(content)
 }
+
+/// the IP address of the client that sent `req`: its peer's, or the one forwarded by a trusted
+/// reverse proxy, see [`net::client_ip`](`super::net::client_ip`)
+pub fn client_ip(req: &HttpRequest) -> Option<String> {
+    let header = |name: &str| {
+        let values = req
+            .headers()
+            .get_all(name)
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>();
+
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.join(","))
+        }
+    };
+
+    super::net::client_ip(
+        req.peer_addr().map(|address| address.ip()),
+        header("X-Forwarded-For").as_deref(),
+        header("Forwarded").as_deref(),
+    )
+}
@@ -0,0 +1,278 @@
+//! Resolves the files of the frontend's production build, so the `{{ bundle(name="index.tsx") }}`
+//! template function can inject them.
+//!
+//! The bundler is picked with the `CRA_FRONTEND_BUILD` environment variable:
+//! - `vite` (default): reads Vite's manifest (`CRA_MANIFEST_PATH`), and uses the Vite dev server in debug builds
+//! - `rsbuild`: reads Rsbuild's manifest (`output.manifest: true`, also at `CRA_MANIFEST_PATH`)
+//! - `static`: a prebuilt directory (`CRA_DIST_DIR`, e.g. Parcel with `--no-content-hash`), where
+//!   the bundle `index.tsx` is served as `index.js` (and `index.css` if it exists)
+//!
+//! Other bundlers can be plugged in by implementing [`AssetManifest`] and registering it with
+//! [`set_asset_manifest`] before the first page is rendered.
+//!
+//! Paths are relative to the dist directory, which is served at the root of the app.
+use super::workspace_utils::{DIST_DIR, MANIFEST_PATH};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// the files a bundle needs, relative to the dist directory
+#[derive(Debug, Clone, Default)]
+pub struct BundleAssets {
+    /// scripts to load, in order
+    pub scripts: Vec<String>,
+    /// stylesheets to load
+    pub styles: Vec<String>,
+    /// scripts the bundle imports lazily
+    pub dynamic_imports: Vec<String>,
+    /// whether the scripts are ES modules (`<script type="module">`) or classic scripts (`<script defer>`)
+    pub module: bool,
+}
+
+impl BundleAssets {
+    /// the tags that load the bundle
+    pub fn to_html(&self) -> String {
+        let script = |file: &String| {
+            if self.module {
+                format!(r#"<script type="module" src="/{file}"></script>"#)
+            } else {
+                format!(r#"<script defer src="/{file}"></script>"#)
+            }
+        };
+
+        self.scripts
+            .iter()
+            .map(script)
+            .chain(
+                self.styles
+                    .iter()
+                    .map(|file| format!(r#"<link rel="stylesheet" href="/{file}" />"#)),
+            )
+            .chain(self.dynamic_imports.iter().map(script))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// maps the bundles in `frontend/bundles` to the files of the production build
+pub trait AssetManifest: Send + Sync {
+    /// the files of the bundle `name` (its file name in `frontend/bundles`, like `index.tsx`)
+    fn bundle(&self, name: &str) -> Option<BundleAssets>;
+}
+
+/// which bundler built the frontend, see the [module documentation](`self`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontendBuild {
+    Vite,
+    Rsbuild,
+    Static,
+}
+
+impl FrontendBuild {
+    /// reads `CRA_FRONTEND_BUILD` (default: [`FrontendBuild::Vite`])
+    pub fn from_env() -> Self {
+        match std::env::var("CRA_FRONTEND_BUILD")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "vite" => FrontendBuild::Vite,
+            "rsbuild" => FrontendBuild::Rsbuild,
+            "static" => FrontendBuild::Static,
+            other => {
+                panic!("unknown CRA_FRONTEND_BUILD `{other}`, expected vite, rsbuild or static")
+            }
+        }
+    }
+
+    /// the manifest of this build, loaded from `CRA_MANIFEST_PATH` / `CRA_DIST_DIR`
+    pub fn manifest(&self) -> Box<dyn AssetManifest> {
+        match self {
+            FrontendBuild::Vite => Box::new(ViteManifest::load(MANIFEST_PATH.as_str())),
+            FrontendBuild::Rsbuild => Box::new(RsbuildManifest::load(MANIFEST_PATH.as_str())),
+            FrontendBuild::Static => Box::new(StaticAssets::new(DIST_DIR.as_str())),
+        }
+    }
+}
+
+static ASSET_MANIFEST: OnceCell<Box<dyn AssetManifest>> = OnceCell::new();
+
+/// use [`manifest`](`AssetManifest`) to resolve bundles instead of the one picked by `CRA_FRONTEND_BUILD`
+///
+/// it is used in debug builds too (the Vite dev server isn't), and can only be set once: returns
+/// `false` if a manifest was already set or used
+pub fn set_asset_manifest(manifest: impl AssetManifest + 'static) -> bool {
+    ASSET_MANIFEST.set(Box::new(manifest)).is_ok()
+}
+
+/// the HTML that loads the bundle `name`
+pub(crate) fn inject_bundle(name: &str) -> Option<String> {
+    #[cfg(debug_assertions)]
+    {
+        if ASSET_MANIFEST.get().is_none() {
+            let build = FrontendBuild::from_env();
+
+            if build == FrontendBuild::Vite {
+                return Some(format!(
+                    r#"<script>
+                    // create a script tag for the vite dev server
+                    const script = document.createElement('script');
+                    script.type = 'module';
+                    script.src = `http://${{window.location.hostname}}:21012/bundles/{name}`;
+                    document.head.appendChild(script);
+                    </script>"#
+                ));
+            }
+
+            // reloaded on every render, so a bundler running in watch mode is picked up
+            return build.manifest().bundle(name).map(|assets| assets.to_html());
+        }
+    }
+
+    ASSET_MANIFEST
+        .get_or_init(|| FrontendBuild::from_env().manifest())
+        .bundle(name)
+        .map(|assets| format!("<!-- production mode -->\n{}", assets.to_html()))
+}
+
+/// whether the dist directory is served in debug builds (when the Vite dev server isn't used)
+#[cfg(debug_assertions)]
+pub(crate) fn serves_dist_in_development() -> bool {
+    ASSET_MANIFEST.get().is_some() || FrontendBuild::from_env() != FrontendBuild::Vite
+}
+
+fn read_manifest(path: &str) -> String {
+    std::fs::read_to_string(PathBuf::from(path))
+        .unwrap_or_else(|err| panic!("could not read the frontend manifest at `{path}`: {err}"))
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct ViteManifestEntry {
+    /// Script content to load for this entry
+    file: String,
+
+    /// Script content to lazy-load for this entry
+    dynamicImports: Option<Vec<String>>, // using `import(..)`
+
+    /// Style content to load for this entry
+    css: Option<Vec<String>>, // using import '*.css'
+}
+
+/// the `manifest.json` written by `vite build` (with `build.manifest` set)
+pub struct ViteManifest(HashMap<String, ViteManifestEntry>);
+
+impl ViteManifest {
+    pub fn load(path: &str) -> Self {
+        Self(
+            serde_json::from_str(&read_manifest(path))
+                .expect("invalid vite manifest (or perhaps the create-rust-app parser broke!)"),
+        )
+    }
+}
+
+impl AssetManifest for ViteManifest {
+    fn bundle(&self, name: &str) -> Option<BundleAssets> {
+        let entry = self.0.get(&format!("bundles/{name}"))?;
+
+        Some(BundleAssets {
+            scripts: vec![entry.file.clone()],
+            styles: entry.css.clone().unwrap_or_default(),
+            dynamic_imports: entry.dynamicImports.clone().unwrap_or_default(),
+            module: true,
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RsbuildFiles {
+    #[serde(default)]
+    js: Vec<String>,
+    #[serde(default)]
+    css: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RsbuildEntry {
+    #[serde(default)]
+    initial: RsbuildFiles,
+}
+
+#[derive(Debug, Deserialize)]
+struct RsbuildManifestJson {
+    entries: HashMap<String, RsbuildEntry>,
+}
+
+/// the `manifest.json` written by `rsbuild build` (with `output.manifest` set)
+///
+/// entries are named after the bundle without its extension (`index.tsx` is the `index` entry),
+/// and only their initial chunks are injected (async chunks are loaded by Rsbuild's runtime)
+pub struct RsbuildManifest(HashMap<String, RsbuildEntry>);
+
+impl RsbuildManifest {
+    pub fn load(path: &str) -> Self {
+        let manifest: RsbuildManifestJson =
+            serde_json::from_str(&read_manifest(path)).expect("invalid rsbuild manifest");
+
+        Self(manifest.entries)
+    }
+}
+
+impl AssetManifest for RsbuildManifest {
+    fn bundle(&self, name: &str) -> Option<BundleAssets> {
+        let entry = self.0.get(bundle_stem(name))?;
+        let relative = |files: &Vec<String>| {
+            files
+                .iter()
+                .map(|file| file.trim_start_matches('/').to_string())
+                .collect()
+        };
+
+        Some(BundleAssets {
+            scripts: relative(&entry.initial.js),
+            styles: relative(&entry.initial.css),
+            dynamic_imports: vec![],
+            module: false,
+        })
+    }
+}
+
+/// a prebuilt directory where the bundle `index.tsx` was built into `index.js` (and `index.css`)
+pub struct StaticAssets {
+    dir: PathBuf,
+}
+
+impl StaticAssets {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl AssetManifest for StaticAssets {
+    fn bundle(&self, name: &str) -> Option<BundleAssets> {
+        let stem = bundle_stem(name);
+        let script = format!("{stem}.js");
+        let style = format!("{stem}.css");
+
+        if !self.dir.join(&script).is_file() {
+            return None;
+        }
+
+        Some(BundleAssets {
+            scripts: vec![script],
+            styles: if self.dir.join(&style).is_file() {
+                vec![style]
+            } else {
+                vec![]
+            },
+            dynamic_imports: vec![],
+            module: true,
+        })
+    }
+}
+
+/// `index.tsx` => `index`
+fn bundle_stem(name: &str) -> &str {
+    name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(name)
+}
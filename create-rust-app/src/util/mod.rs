@@ -21,4 +21,7 @@ pub use poem_utils::*;
 /// expose template_utils for all backends.
 mod template_utils;
 
+/// resolves the frontend's build for the `bundle` template function
+pub mod frontend;
+
 pub mod net;
@@ -1,5 +1,5 @@
 use std::{
-    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6, TcpListener, ToSocketAddrs},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6, TcpListener, ToSocketAddrs},
     ops::Range,
 };
 
@@ -22,3 +22,276 @@ pub fn is_port_free(port: u16) -> bool {
 pub fn find_free_port(mut range: Range<u16>) -> Option<u16> {
     range.find(|port| is_port_free(*port))
 }
+
+lazy_static::lazy_static! {
+    static ref TRUSTED_PROXIES: Vec<IpRange> = std::env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty())
+        .filter_map(|proxy| {
+            let range = IpRange::parse(proxy);
+            if range.is_none() {
+                println!("TRUSTED_PROXIES: ignoring '{proxy}', which isn't an IP address or range");
+            }
+            range
+        })
+        .collect();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// an IP address, or a CIDR range of them (ex: `10.0.0.0/8`)
+pub struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (address, prefix) = match value.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix.parse::<u8>().ok()?)),
+            None => (value, None),
+        };
+        let network = canonical(address.parse::<IpAddr>().ok()?);
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max_prefix);
+
+        if prefix > max_prefix {
+            return None;
+        }
+
+        Some(Self { network, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, canonical(ip)) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// IPv4-mapped IPv6 addresses (ex: `::ffff:10.0.0.1`, from dual-stack sockets) as IPv4 ones
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// whether `ip` is one of the reverse proxies listed in `TRUSTED_PROXIES` (comma-separated IP
+/// addresses or CIDR ranges, none by default)
+pub fn is_trusted_proxy(ip: IpAddr) -> bool {
+    let ip = canonical(ip);
+
+    TRUSTED_PROXIES.iter().any(|range| range.contains(ip))
+}
+
+/// an address of a forwarding header (ex: `203.0.113.7`, `"[2001:db8::1]:4711"`, `10.0.0.1:80`)
+fn parse_forwarded_address(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+
+    let ip = match value.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next()?.parse::<IpAddr>().ok(),
+        None => value.parse::<IpAddr>().ok().or_else(|| {
+            value
+                .parse::<std::net::SocketAddr>()
+                .ok()
+                .map(|address| address.ip())
+        }),
+    };
+
+    ip.map(canonical)
+}
+
+/// the IP address of the client behind a request's `peer`: the peer's, unless it's a trusted proxy
+/// (see [`is_trusted_proxy`]), in which case the `X-Forwarded-For` (or else the `Forwarded`)
+/// header is read from its end, skipping the other trusted proxies
+///
+/// forwarding headers of untrusted peers are ignored, as clients can set them to anything
+pub fn client_ip(
+    peer: Option<IpAddr>,
+    x_forwarded_for: Option<&str>,
+    forwarded: Option<&str>,
+) -> Option<String> {
+    client_ip_with(&TRUSTED_PROXIES, peer, x_forwarded_for, forwarded)
+}
+
+/// [`client_ip`], with `trusted` as the reverse proxies
+fn client_ip_with(
+    trusted: &[IpRange],
+    peer: Option<IpAddr>,
+    x_forwarded_for: Option<&str>,
+    forwarded: Option<&str>,
+) -> Option<String> {
+    let is_trusted_proxy = |ip: IpAddr| trusted.iter().any(|range| range.contains(ip));
+
+    let peer = canonical(peer?);
+    if !is_trusted_proxy(peer) {
+        return Some(peer.to_string());
+    }
+
+    let hops: Vec<&str> = match (x_forwarded_for, forwarded) {
+        (Some(header), _) => header.split(',').collect(),
+        (None, Some(header)) => header
+            .split(',')
+            .filter_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (name, value) = pair.split_once('=')?;
+                    name.trim().eq_ignore_ascii_case("for").then_some(value)
+                })
+            })
+            .collect(),
+        (None, None) => vec![],
+    };
+
+    let mut client = peer;
+    for hop in hops.iter().rev() {
+        match parse_forwarded_address(hop) {
+            Some(ip) => {
+                client = ip;
+                if !is_trusted_proxy(ip) {
+                    break;
+                }
+            }
+            // obfuscated or unknown (ex: `for=unknown`): the last trusted proxy is all we know
+            None => break,
+        }
+    }
+
+    Some(client.to_string())
+}
+
+#[cfg(test)]
+mod net_tests {
+    use super::{client_ip_with, IpRange};
+    use std::net::IpAddr;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn range(value: &str) -> IpRange {
+        IpRange::parse(value).unwrap()
+    }
+
+    #[test]
+    fn test_ip_range_parse_v4() {
+        let range = range("10.0.0.0/8");
+        assert!(range.contains(ip("10.0.0.1")));
+        assert!(range.contains(ip("10.255.255.255")));
+        assert!(range.contains(ip("::ffff:10.1.2.3")));
+        assert!(!range.contains(ip("11.0.0.1")));
+        assert!(!range.contains(ip("::1")));
+
+        let single = IpRange::parse("192.168.1.1").unwrap();
+        assert!(single.contains(ip("192.168.1.1")));
+        assert!(!single.contains(ip("192.168.1.2")));
+
+        assert!(IpRange::parse("0.0.0.0/0")
+            .unwrap()
+            .contains(ip("203.0.113.7")));
+    }
+
+    #[test]
+    fn test_ip_range_parse_v6() {
+        let range = range("2001:db8::/32");
+        assert!(range.contains(ip("2001:db8::1")));
+        assert!(range.contains(ip("2001:db8:ffff::1")));
+        assert!(!range.contains(ip("2001:db9::1")));
+        assert!(!range.contains(ip("10.0.0.1")));
+
+        // IPv4-mapped ranges are IPv4 ones
+        assert!(IpRange::parse("::ffff:10.0.0.1")
+            .unwrap()
+            .contains(ip("10.0.0.1")));
+    }
+
+    #[test]
+    fn test_ip_range_parse_rejects_invalid_input() {
+        assert_eq!(IpRange::parse(""), None);
+        assert_eq!(IpRange::parse("localhost"), None);
+        assert_eq!(IpRange::parse("10.0.0.0/33"), None);
+        assert_eq!(IpRange::parse("2001:db8::/129"), None);
+        assert_eq!(IpRange::parse("10.0.0.0/"), None);
+        assert_eq!(IpRange::parse("10.0.0.0/-1"), None);
+        assert_eq!(IpRange::parse("10.0.0/8"), None);
+    }
+
+    #[test]
+    fn test_client_ip_ignores_headers_of_untrusted_peers() {
+        let trusted = [range("10.0.0.0/8")];
+
+        assert_eq!(
+            client_ip_with(&trusted, Some(ip("203.0.113.7")), Some("1.2.3.4"), None),
+            Some("203.0.113.7".to_string())
+        );
+        assert_eq!(
+            client_ip_with(&trusted, Some(ip("203.0.113.7")), None, Some("for=1.2.3.4")),
+            Some("203.0.113.7".to_string())
+        );
+        // nothing is trusted by default
+        assert_eq!(
+            client_ip_with(&[], Some(ip("10.0.0.1")), Some("1.2.3.4"), None),
+            Some("10.0.0.1".to_string())
+        );
+        assert_eq!(client_ip_with(&trusted, None, Some("1.2.3.4"), None), None);
+    }
+
+    #[test]
+    fn test_client_ip_walks_trusted_proxies_from_the_right() {
+        let trusted = [range("10.0.0.0/8"), range("2001:db8::/32")];
+
+        // a spoofed first hop is skipped: the client is the first untrusted address from the end
+        assert_eq!(
+            client_ip_with(
+                &trusted,
+                Some(ip("10.0.0.1")),
+                Some("1.2.3.4, 203.0.113.7, 10.0.0.3, 10.0.0.2"),
+                None
+            ),
+            Some("203.0.113.7".to_string())
+        );
+        assert_eq!(
+            client_ip_with(
+                &trusted,
+                Some(ip("::ffff:10.0.0.1")),
+                None,
+                Some(r#"for=1.2.3.4, for="[2001:db8::2]:4711";proto=https, for=10.0.0.2"#)
+            ),
+            Some("1.2.3.4".to_string())
+        );
+        // every hop is trusted: the furthest one is the client
+        assert_eq!(
+            client_ip_with(
+                &trusted,
+                Some(ip("10.0.0.1")),
+                Some("10.0.0.3, 10.0.0.2"),
+                None
+            ),
+            Some("10.0.0.3".to_string())
+        );
+        // an unknown hop stops the walk at the last trusted proxy
+        assert_eq!(
+            client_ip_with(
+                &trusted,
+                Some(ip("10.0.0.1")),
+                None,
+                Some("for=1.2.3.4, for=unknown, for=10.0.0.2")
+            ),
+            Some("10.0.0.2".to_string())
+        );
+        // without forwarding headers, the trusted peer is all we know
+        assert_eq!(
+            client_ip_with(&trusted, Some(ip("10.0.0.1")), None, None),
+            Some("10.0.0.1".to_string())
+        );
+    }
+}
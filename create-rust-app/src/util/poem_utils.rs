@@ -1,5 +1,9 @@
 use std::sync::Mutex;
 
+#[cfg(debug_assertions)]
+use super::frontend::serves_dist_in_development;
+use super::workspace_utils::DIST_DIR;
+#[cfg(debug_assertions)]
 use super::workspace_utils::FRONTEND_DIR;
 use poem::http::{StatusCode, Uri};
 use poem::middleware::{AddData, AddDataEndpoint};
@@ -46,7 +50,7 @@ static REQUEST_REFRESH_COUNT: Mutex<i32> = Mutex::new(0);
 /// for a html file/template at the matching path (in this case, ./foo/bar.html),
 /// defaults to index.html
 ///
-/// then, your frontend (all the css files, scripts, etc. in your frontend's vite manifest (at ./frontend/dist/manifest.json))
+/// then, your frontend (all the css files, scripts, etc. in your frontend's build (by default the vite manifest at ./frontend/dist/manifest.json, see [`frontend`](`crate::frontend`)))
 /// will be compiled and injected into the template wherever `{{ bundle(name="index.tsx") }}` is (the `index.tsx` can be any .tsx file in ./frontend/bundles)
 ///
 /// then, that compiled html is sent to the client
@@ -109,12 +113,20 @@ pub async fn render_views(uri: &Uri) -> impl IntoResponse {
 
                 return file_response(public_path).await;
             }
+
+            // bundlers other than vite write their development builds to the dist directory
+            let dist_path = &format!("{dist_dir}{path}", dist_dir = *DIST_DIR);
+            if serves_dist_in_development() && std::path::PathBuf::from(dist_path).is_file() {
+                println!("DIST_FILE {path} => {dist_path}");
+
+                return file_response(dist_path).await;
+            }
         }
 
         #[cfg(not(debug_assertions))]
         {
             // production asset serving
-            let static_path = &format!("{frontend_dir}/dist{path}", frontend_dir = *FRONTEND_DIR);
+            let static_path = &format!("{dist_dir}{path}", dist_dir = *DIST_DIR);
             if std::path::PathBuf::from(static_path).is_file() {
                 return file_response(static_path).await;
             }
@@ -141,7 +153,7 @@ fn template_response(_uri: &Uri, content: String) -> Response {
     #[cfg(debug_assertions)]
     let mut content = content;
     #[cfg(debug_assertions)]
-    {
+    if !serves_dist_in_development() {
         let hostname = _uri.host().unwrap_or("localhost");
         let inject: &str = &format!(
             r##"
@@ -180,3 +192,30 @@ async fn file_response(path: &String) -> Response {
     response = response.status(StatusCode::OK);
     response.body(Body::from(file))
 }
+
+/// the IP address of the client that sent `req`: its peer's, or the one forwarded by a trusted
+/// reverse proxy, see [`net::client_ip`](`super::net::client_ip`)
+pub fn client_ip(req: &poem::Request) -> Option<String> {
+    let header = |name: &str| {
+        let values = req
+            .headers()
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>();
+
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.join(","))
+        }
+    };
+
+    super::net::client_ip(
+        req.remote_addr()
+            .as_socket_addr()
+            .map(|address| address.ip()),
+        header("X-Forwarded-For").as_deref(),
+        header("Forwarded").as_deref(),
+    )
+}
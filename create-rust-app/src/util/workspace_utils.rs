@@ -59,6 +59,13 @@ lazy_static!(
             }
         }
     };
+    /// constant for the path to the directory the frontend is built into, served at the root of the app
+    pub(crate) static ref DIST_DIR: String = {
+        match std::env::var("CRA_DIST_DIR") {
+            Ok(dir) => dir,
+            Err(_) => format!("{frontend_dir}/dist", frontend_dir = *FRONTEND_DIR),
+        }
+    };
     /// constant for the path to the project's views directory
     pub(crate) static ref VIEWS_GLOB: String = {
         match std::env::var("CRA_VIEWS_GLOB") {
@@ -79,6 +86,47 @@ lazy_static!(
             }
         }
     };
+    /// constant for the glob of the project's mail templates, see [`Mailer::send_template`](`crate::Mailer::send_template`)
+    pub(crate) static ref MAIL_TEMPLATES_GLOB: String = {
+        match std::env::var("CRA_MAIL_TEMPLATES_GLOB") {
+            Ok(dir) => dir,
+            Err(_) => {
+                #[cfg(not(feature = "plugin_workspace_support"))]
+                {
+                    "backend/mail/templates/**/*".to_string()
+                }
+                #[cfg(feature = "plugin_workspace_support")]
+                {
+                    if *WORKSPACE_DIR == std::env::current_dir().unwrap() {
+                        return "backend/mail/templates/**/*".to_string();
+                    } else {
+                        return "mail/templates/**/*".to_string();
+                    }
+                }
+            }
+        }
+    };
+    #[cfg(feature = "plugin_i18n")]
+    /// constant for the path to the project's message catalogs, see [`Catalogs`](`crate::i18n::Catalogs`)
+    pub(crate) static ref LOCALES_DIR: String = {
+        match std::env::var("CRA_LOCALES_DIR") {
+            Ok(dir) => dir,
+            Err(_) => {
+                #[cfg(not(feature = "plugin_workspace_support"))]
+                {
+                    "backend/locales".to_string()
+                }
+                #[cfg(feature = "plugin_workspace_support")]
+                {
+                    if *WORKSPACE_DIR == std::env::current_dir().unwrap() {
+                        return "backend/locales".to_string();
+                    } else {
+                        return "locales".to_string();
+                    }
+                }
+            }
+        }
+    };
 
 
 );
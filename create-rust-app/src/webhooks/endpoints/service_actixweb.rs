@@ -0,0 +1,300 @@
+use actix_http::StatusCode;
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::{delete, get, post, put, web, HttpResponse, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::webhooks::{
+    event_types, DeliveryStatus, EndpointForm, WebhookDelivery, WebhookEndpoint, ADMIN_PERMISSION,
+};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../endpoints/{id}/deliveries and
+/// .../admin/deliveries endpoints
+pub struct DeliveriesQuery {
+    /// only list the deliveries with this [`DeliveryStatus`] (admin only)
+    status: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> HttpResponse {
+    HttpResponse::build(status_code).body(json!({ "message": message }).to_string())
+}
+
+/// handler for GET requests at the .../event-types endpoint
+///
+/// returns the [`EventType`](`crate::webhooks::EventType`)s endpoints can subscribe to
+#[get("/event-types")]
+async fn list_event_types() -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(event_types()))
+}
+
+/// handler for GET requests at the .../endpoints endpoint
+///
+/// requires auth
+///
+/// returns the user's [`WebhookEndpoint`]s
+#[get("/endpoints")]
+async fn list_endpoints(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result = web::block(move || {
+        WebhookEndpoint::fetch_all_for_user(&mut db.get_connection(), auth.user_id)
+    })
+    .await?;
+
+    match result {
+        Ok(endpoints) => Ok(HttpResponse::Ok().json(endpoints)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the endpoints.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../endpoints endpoint
+///
+/// requires auth
+///
+/// registers an endpoint, returns it along with its secret
+#[post("/endpoints")]
+async fn create_endpoint(
+    db: Data<Database>,
+    auth: Auth,
+    Json(item): Json<EndpointForm>,
+) -> Result<HttpResponse> {
+    // resolves the url's host
+    let validation = web::block({
+        let item = item.clone();
+        move || item.validate()
+    })
+    .await?;
+    if let Err(message) = validation {
+        return Ok(error_response(StatusCode::BAD_REQUEST, message));
+    }
+
+    let result = web::block(move || {
+        WebhookEndpoint::register(&mut db.get_connection(), auth.user_id, &item)
+    })
+    .await?;
+
+    match result {
+        Ok(endpoint) => Ok(HttpResponse::Created().json(endpoint)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not register the endpoint.",
+        )),
+    }
+}
+
+/// handler for PUT requests at the .../endpoints/{id} endpoint
+///
+/// requires auth
+///
+/// updates the endpoint, its secret stays the same
+#[put("/endpoints/{id}")]
+async fn update_endpoint(
+    db: Data<Database>,
+    auth: Auth,
+    item_id: Path<i32>,
+    Json(item): Json<EndpointForm>,
+) -> Result<HttpResponse> {
+    // resolves the url's host
+    let validation = web::block({
+        let item = item.clone();
+        move || item.validate()
+    })
+    .await?;
+    if let Err(message) = validation {
+        return Ok(error_response(StatusCode::BAD_REQUEST, message));
+    }
+
+    let item_id = item_id.into_inner();
+    let result = web::block(move || {
+        WebhookEndpoint::modify(&mut db.get_connection(), auth.user_id, item_id, &item)
+    })
+    .await?;
+
+    match result {
+        Ok(endpoint) => Ok(HttpResponse::Ok().json(endpoint)),
+        Err(diesel::result::Error::NotFound) => {
+            Ok(error_response(StatusCode::NOT_FOUND, "Endpoint not found."))
+        }
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not update the endpoint.",
+        )),
+    }
+}
+
+/// handler for DELETE requests at the .../endpoints/{id} endpoint
+///
+/// requires auth
+///
+/// deletes the endpoint, and its deliveries
+#[delete("/endpoints/{id}")]
+async fn delete_endpoint(
+    db: Data<Database>,
+    auth: Auth,
+    item_id: Path<i32>,
+) -> Result<HttpResponse> {
+    let item_id = item_id.into_inner();
+    let result = web::block(move || {
+        WebhookEndpoint::delete(&mut db.get_connection(), auth.user_id, item_id)
+    })
+    .await?;
+
+    match result {
+        Ok(0) => Ok(error_response(StatusCode::NOT_FOUND, "Endpoint not found.")),
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not delete the endpoint.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../endpoints/{id}/deliveries endpoint
+///
+/// requires auth
+///
+/// returns the endpoint's [`EndpointDelivery`](`crate::webhooks::EndpointDelivery`)s, most recent
+/// first (20 per page by default)
+#[get("/endpoints/{id}/deliveries")]
+async fn list_endpoint_deliveries(
+    db: Data<Database>,
+    auth: Auth,
+    item_id: Path<i32>,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<HttpResponse> {
+    let item_id = item_id.into_inner();
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(20).clamp(1, 100);
+
+    let result = web::block(move || {
+        let mut db = db.get_connection();
+        let endpoint = WebhookEndpoint::read_for_user(&mut db, auth.user_id, item_id)?;
+
+        WebhookDelivery::list_for_endpoint(&mut db, endpoint.id, page, page_size)
+    })
+    .await?;
+
+    match result {
+        Ok(deliveries) => Ok(HttpResponse::Ok().json(deliveries)),
+        Err(diesel::result::Error::NotFound) => {
+            Ok(error_response(StatusCode::NOT_FOUND, "Endpoint not found."))
+        }
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the deliveries.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../deliveries/{id}/retry endpoint
+///
+/// requires auth
+///
+/// sends a delivery to one of the user's endpoints again
+#[post("/deliveries/{id}/retry")]
+async fn retry_delivery(
+    db: Data<Database>,
+    auth: Auth,
+    item_id: Path<i32>,
+) -> Result<HttpResponse> {
+    let item_id = item_id.into_inner();
+    let result = web::block(move || {
+        WebhookDelivery::retry(&mut db.get_connection(), Some(auth.user_id), item_id)
+    })
+    .await?;
+
+    match result {
+        Ok(0) => Ok(error_response(StatusCode::NOT_FOUND, "Delivery not found.")),
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not retry the delivery.",
+        )),
+    }
+}
+
+/// handler for GET requests at the .../admin/deliveries endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// returns every delivery, with its endpoint's url, most recent first (50 per page by default)
+#[get("/admin/deliveries")]
+async fn admin_list_deliveries(
+    db: Data<Database>,
+    auth: Auth,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let status = match query.status.as_deref().map(DeliveryStatus::parse) {
+        Some(None) => return Ok(error_response(StatusCode::BAD_REQUEST, "Unknown status.")),
+        Some(status) => status,
+        None => None,
+    };
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    let result = web::block(move || {
+        WebhookDelivery::list(&mut db.get_connection(), status, page, page_size)
+    })
+    .await?;
+
+    match result {
+        Ok(deliveries) => Ok(HttpResponse::Ok().json(deliveries)),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the deliveries.",
+        )),
+    }
+}
+
+/// handler for POST requests at the .../admin/deliveries/{id}/retry endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// sends any delivery again
+#[post("/admin/deliveries/{id}/retry")]
+async fn admin_retry_delivery(
+    db: Data<Database>,
+    auth: Auth,
+    item_id: Path<i32>,
+) -> Result<HttpResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let item_id = item_id.into_inner();
+    let result =
+        web::block(move || WebhookDelivery::retry(&mut db.get_connection(), None, item_id)).await?;
+
+    match result {
+        Ok(0) => Ok(error_response(StatusCode::NOT_FOUND, "Delivery not found.")),
+        Ok(_) => Ok(HttpResponse::Ok().finish()),
+        Err(_) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not retry the delivery.",
+        )),
+    }
+}
+
+/// returns the endpoints of the webhooks' API
+pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+    scope
+        .service(list_event_types)
+        .service(list_endpoints)
+        .service(create_endpoint)
+        .service(update_endpoint)
+        .service(delete_endpoint)
+        .service(list_endpoint_deliveries)
+        .service(retry_delivery)
+        .service(admin_list_deliveries)
+        .service(admin_retry_delivery)
+}
@@ -0,0 +1,300 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    post, put,
+    web::{Data, Json, Path, Query},
+    Error, IntoResponse, Result, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::Auth;
+use crate::webhooks::{
+    event_types, DeliveryStatus, EndpointForm, WebhookDelivery, WebhookEndpoint, ADMIN_PERMISSION,
+};
+use crate::Database;
+
+#[derive(Deserialize)]
+/// query parameters of GET requests to the .../endpoints/:id/deliveries and
+/// .../admin/deliveries endpoints
+pub struct DeliveriesQuery {
+    /// only list the deliveries with this [`DeliveryStatus`] (admin only)
+    status: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+fn error_response(status_code: StatusCode, message: &'static str) -> Error {
+    Error::from_string(json!({ "message": message }).to_string(), status_code)
+}
+
+#[handler]
+/// handler for GET requests at the .../event-types endpoint
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the [`EventType`](`crate::webhooks::EventType`)s endpoints can subscribe to
+async fn list_event_types() -> Result<impl IntoResponse> {
+    Ok(Json(event_types()))
+}
+
+#[handler]
+/// handler for GET requests at the .../endpoints endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the user's [`WebhookEndpoint`]s
+/// | 500 | Json payload : {"message": "Could not fetch the endpoints."}
+async fn list_endpoints(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    WebhookEndpoint::fetch_all_for_user(&mut db.get_connection(), auth.user_id)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the endpoints.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../endpoints endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the registered [`WebhookEndpoint`], with its secret
+/// | 400 | Json payload : {"message": "<why the endpoint is invalid>"}
+/// | 500 | Json payload : {"message": "Could not register the endpoint."}
+async fn create_endpoint(
+    db: Data<&Database>,
+    auth: Auth,
+    Json(item): Json<EndpointForm>,
+) -> Result<impl IntoResponse> {
+    if let Err(message) = item.validate() {
+        return Err(error_response(StatusCode::BAD_REQUEST, message));
+    }
+
+    WebhookEndpoint::register(&mut db.get_connection(), auth.user_id, &item)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not register the endpoint.",
+            )
+        })
+}
+
+#[handler]
+/// handler for PUT requests at the .../endpoints/:id endpoint
+///
+/// requires auth
+///
+/// updates the endpoint, its secret stays the same
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the updated [`WebhookEndpoint`]
+/// | 400 | Json payload : {"message": "<why the endpoint is invalid>"}
+/// | 404 | Json payload : {"message": "Endpoint not found."}
+/// | 500 | Json payload : {"message": "Could not update the endpoint."}
+async fn update_endpoint(
+    db: Data<&Database>,
+    auth: Auth,
+    Path(item_id): Path<i32>,
+    Json(item): Json<EndpointForm>,
+) -> Result<impl IntoResponse> {
+    if let Err(message) = item.validate() {
+        return Err(error_response(StatusCode::BAD_REQUEST, message));
+    }
+
+    match WebhookEndpoint::modify(&mut db.get_connection(), auth.user_id, item_id, &item) {
+        Ok(endpoint) => Ok(Json(endpoint)),
+        Err(diesel::result::Error::NotFound) => {
+            Err(error_response(StatusCode::NOT_FOUND, "Endpoint not found."))
+        }
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not update the endpoint.",
+        )),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../endpoints/:id endpoint
+///
+/// requires auth
+///
+/// deletes the endpoint, and its deliveries
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 404 | Json payload : {"message": "Endpoint not found."}
+/// | 500 | Json payload : {"message": "Could not delete the endpoint."}
+async fn delete_endpoint(db: Data<&Database>, auth: Auth, Path(item_id): Path<i32>) -> Result<()> {
+    match WebhookEndpoint::delete(&mut db.get_connection(), auth.user_id, item_id) {
+        Ok(0) => Err(error_response(StatusCode::NOT_FOUND, "Endpoint not found.")),
+        Ok(_) => Ok(()),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not delete the endpoint.",
+        )),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../endpoints/:id/deliveries endpoint
+///
+/// requires auth
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the endpoint's [`EndpointDelivery`](`crate::webhooks::EndpointDelivery`)s, most recent first (20 per page by default)
+/// | 404 | Json payload : {"message": "Endpoint not found."}
+/// | 500 | Json payload : {"message": "Could not fetch the deliveries."}
+async fn list_endpoint_deliveries(
+    db: Data<&Database>,
+    auth: Auth,
+    Path(item_id): Path<i32>,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<impl IntoResponse> {
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(20).clamp(1, 100);
+    let mut db = db.get_connection();
+
+    let result =
+        WebhookEndpoint::read_for_user(&mut db, auth.user_id, item_id).and_then(|endpoint| {
+            WebhookDelivery::list_for_endpoint(&mut db, endpoint.id, page, page_size)
+        });
+
+    match result {
+        Ok(deliveries) => Ok(Json(deliveries)),
+        Err(diesel::result::Error::NotFound) => {
+            Err(error_response(StatusCode::NOT_FOUND, "Endpoint not found."))
+        }
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not fetch the deliveries.",
+        )),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../deliveries/:id/retry endpoint
+///
+/// requires auth
+///
+/// sends a delivery to one of the user's endpoints again
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 404 | Json payload : {"message": "Delivery not found."}
+/// | 500 | Json payload : {"message": "Could not retry the delivery."}
+async fn retry_delivery(db: Data<&Database>, auth: Auth, Path(item_id): Path<i32>) -> Result<()> {
+    match WebhookDelivery::retry(&mut db.get_connection(), Some(auth.user_id), item_id) {
+        Ok(0) => Err(error_response(StatusCode::NOT_FOUND, "Delivery not found.")),
+        Ok(_) => Ok(()),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not retry the delivery.",
+        )),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../admin/deliveries endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : every delivery with its endpoint's url ([`DeliveryOverview`](`crate::webhooks::DeliveryOverview`)s), most recent first (50 per page by default)
+/// | 400 | Json payload : {"message": "Unknown status."}
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 500 | Json payload : {"message": "Could not fetch the deliveries."}
+async fn admin_list_deliveries(
+    db: Data<&Database>,
+    auth: Auth,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<impl IntoResponse> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    let status = match query.status.as_deref().map(DeliveryStatus::parse) {
+        Some(None) => return Err(error_response(StatusCode::BAD_REQUEST, "Unknown status.")),
+        Some(status) => status,
+        None => None,
+    };
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+
+    WebhookDelivery::list(&mut db.get_connection(), status, page, page_size)
+        .map(Json)
+        .map_err(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not fetch the deliveries.",
+            )
+        })
+}
+
+#[handler]
+/// handler for POST requests at the .../admin/deliveries/:id/retry endpoint
+///
+/// requires auth, and the [`ADMIN_PERMISSION`]
+///
+/// sends any delivery again
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | (no content)
+/// | 403 | Json payload : {"message": "Forbidden."}
+/// | 404 | Json payload : {"message": "Delivery not found."}
+/// | 500 | Json payload : {"message": "Could not retry the delivery."}
+async fn admin_retry_delivery(
+    db: Data<&Database>,
+    auth: Auth,
+    Path(item_id): Path<i32>,
+) -> Result<()> {
+    if !auth.has_permission(ADMIN_PERMISSION.to_string()) {
+        return Err(error_response(StatusCode::FORBIDDEN, "Forbidden."));
+    }
+
+    match WebhookDelivery::retry(&mut db.get_connection(), None, item_id) {
+        Ok(0) => Err(error_response(StatusCode::NOT_FOUND, "Delivery not found.")),
+        Ok(_) => Ok(()),
+        Err(_) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not retry the delivery.",
+        )),
+    }
+}
+
+/// returns the endpoints of the webhooks' API
+pub fn api() -> Route {
+    Route::new()
+        .at("/event-types", get(list_event_types))
+        .at("/endpoints", get(list_endpoints).post(create_endpoint))
+        .at(
+            "/endpoints/:id",
+            put(update_endpoint).delete(delete_endpoint),
+        )
+        .at("/endpoints/:id/deliveries", get(list_endpoint_deliveries))
+        .at("/deliveries/:id/retry", post(retry_delivery))
+        .at("/admin/deliveries", get(admin_list_deliveries))
+        .at("/admin/deliveries/:id/retry", post(admin_retry_delivery))
+}
@@ -0,0 +1,988 @@
+//! Outgoing webhooks (`plugin_webhooks` feature): the app's users register endpoints which are
+//! called when the events they subscribed to happen.
+//!
+//! The app defines its event types when it starts, and dispatches events as they happen:
+//!
+//! ```rust,ignore
+//! create_rust_app::webhooks::register_event_types(vec![
+//!     EventType::new("todo.created", "A todo was created"),
+//! ]);
+//!
+//! create_rust_app::webhooks::dispatch(&mut db, "todo.created", &todo)?;
+//! ```
+//!
+//! Dispatching an event records a delivery for each of the endpoints subscribed to it, which a
+//! background worker ([`spawn_worker`]) sends. Deliveries are `POST` requests whose body is the
+//! [`Event`], signed with the endpoint's secret (see [`sign`]) in the `Webhook-Signature` header:
+//! `t=<unix timestamp>,v1=<hex HMAC-SHA256 of "<timestamp>.<body>">`. Failed deliveries are retried
+//! with an exponential backoff (see [`WebhookConfig`]), and every attempt's response is logged.
+//!
+//! The API (mount it like the other services) lets users manage their endpoints:
+//!
+//! - `GET /event-types` lists the event types
+//! - `GET /endpoints` lists the user's endpoints, `POST /endpoints` registers one
+//! - `PUT /endpoints/{id}` and `DELETE /endpoints/{id}` update and delete them
+//! - `GET /endpoints/{id}/deliveries` lists an endpoint's deliveries, most recent first (without
+//!   the endpoint's responses, see [`EndpointDelivery`])
+//! - `POST /deliveries/{id}/retry` sends a delivery again
+//!
+//! and the admin portal's "Webhooks" page to look into every delivery (`GET /admin/deliveries`,
+//! `POST /admin/deliveries/{id}/retry`), which requires the [`ADMIN_PERMISSION`].
+//!
+//! Endpoints can't point to the app's network: their host must only resolve to public addresses
+//! (not loopback, private, link-local or unique-local ones), which is checked
+//! when they're registered and again before every delivery, against the address it's sent to.
+//! Redirects aren't followed.
+use crate::diesel::*;
+use crate::{Connection, Database};
+use diesel::QueryResult;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::RwLock;
+use std::time::Duration;
+
+mod endpoints;
+pub use endpoints::*;
+mod schema;
+use schema::*;
+
+type ID = i32;
+
+#[cfg(not(feature = "database_sqlite"))]
+type Utc = chrono::DateTime<chrono::Utc>;
+#[cfg(feature = "database_sqlite")]
+type Utc = chrono::NaiveDateTime;
+
+/// permission required to use the `/admin` endpoints
+pub const ADMIN_PERMISSION: &str = "admin:webhooks";
+
+/// how long a delivery is hidden from other workers while one of them sends it
+const DELIVERY_LEASE_MINUTES: i64 = 5;
+
+/// the longest response body kept in the delivery log
+const MAX_RESPONSE_BODY_LENGTH: usize = 2000;
+
+lazy_static::lazy_static! {
+    static ref EVENT_TYPES: RwLock<BTreeMap<String, EventType>> = RwLock::new(BTreeMap::new());
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// an event users can subscribe their endpoints to
+pub struct EventType {
+    /// ex: `todo.created`
+    pub name: String,
+    pub description: String,
+}
+
+impl EventType {
+    pub fn new(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+        }
+    }
+}
+
+/// defines the event types users can subscribe to, in addition to the ones already registered
+pub fn register_event_types(event_types: Vec<EventType>) {
+    let mut registered = EVENT_TYPES.write().unwrap();
+
+    for event_type in event_types {
+        registered.insert(event_type.name.clone(), event_type);
+    }
+}
+
+/// the registered event types, by name
+pub fn event_types() -> Vec<EventType> {
+    EVENT_TYPES.read().unwrap().values().cloned().collect()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// where a [`WebhookDelivery`] is in its delivery
+pub enum DeliveryStatus {
+    /// waiting for its first attempt, or for a retry
+    Pending,
+    Succeeded,
+    /// failed [`WebhookConfig::max_attempts`] times
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "succeeded" => Some(Self::Succeeded),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=webhook_endpoints)]
+/// a url a user wants events to be sent to
+pub struct WebhookEndpoint {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    /// the endpoint's owner
+    pub user_id: ID,
+    pub url: String,
+    pub description: String,
+    /// the key deliveries are signed with
+    pub secret: String,
+    /// json array of the names of the event types it's subscribed to, `["*"]` for all of them
+    pub event_types: String,
+    /// disabled endpoints don't get new deliveries
+    pub enabled: bool,
+
+    pub created_at: Utc,
+    pub updated_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=webhook_endpoints)]
+pub struct WebhookEndpointChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub url: String,
+    pub description: String,
+    pub secret: String,
+    pub event_types: String,
+    pub enabled: bool,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name=webhook_deliveries)]
+/// an event sent, or to be sent, to an endpoint
+pub struct WebhookDelivery {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    pub endpoint_id: ID,
+    pub event_type: String,
+    /// the event's data (json)
+    pub payload: String,
+    /// one of [`DeliveryStatus`]
+    pub status: String,
+    /// how many attempts failed
+    pub attempts: i32,
+    /// the status code of the last attempt's response
+    pub response_status: Option<i32>,
+    /// the (truncated) body of the last attempt's response
+    pub response_body: Option<String>,
+    /// why the last attempt failed
+    pub last_error: Option<String>,
+    /// when the worker (re)tries to send it
+    pub next_attempt_at: Utc,
+    pub delivered_at: Option<Utc>,
+
+    pub created_at: Utc,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// a delivery as its endpoint's owner sees it: without the endpoint's response, which would let
+/// users read whatever the endpoint's url points to
+pub struct EndpointDelivery {
+    pub id: ID,
+    pub endpoint_id: ID,
+    pub event_type: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: Utc,
+    pub delivered_at: Option<Utc>,
+    pub created_at: Utc,
+}
+
+impl From<WebhookDelivery> for EndpointDelivery {
+    fn from(delivery: WebhookDelivery) -> Self {
+        Self {
+            id: delivery.id,
+            endpoint_id: delivery.endpoint_id,
+            event_type: delivery.event_type,
+            payload: delivery.payload,
+            status: delivery.status,
+            attempts: delivery.attempts,
+            response_status: delivery.response_status,
+            last_error: delivery.last_error,
+            next_attempt_at: delivery.next_attempt_at,
+            delivered_at: delivery.delivered_at,
+            created_at: delivery.created_at,
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name=webhook_deliveries)]
+pub struct WebhookDeliveryChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub endpoint_id: ID,
+    pub event_type: String,
+    pub payload: String,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// a delivery, with the url it's sent to (the admin portal's deliveries)
+pub struct DeliveryOverview {
+    pub delivery: WebhookDelivery,
+    pub url: String,
+    pub user_id: ID,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// the body of a delivery
+pub struct Event {
+    /// the delivery's id, which is the same for its retries: receivers can use it to ignore the
+    /// deliveries they already handled
+    pub id: ID,
+    /// ex: `todo.created`
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub created_at: Utc,
+    pub data: serde_json::Value,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// what users send to register or update an endpoint
+pub struct EndpointForm {
+    pub url: String,
+    pub description: Option<String>,
+    /// the names of the event types to subscribe to, `["*"]` for all of them
+    pub event_types: Vec<String>,
+    /// (default: `true`)
+    pub enabled: Option<bool>,
+}
+
+impl EndpointForm {
+    /// checks the url is an http(s) one whose host only resolves to public addresses, and that the
+    /// event types are registered
+    ///
+    /// this resolves the url's host, which blocks
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) if url.scheme() == "https" || url.scheme() == "http" => {
+                resolve_public(&url)?;
+            }
+            _ => return Err("The url must be an http(s) url."),
+        }
+
+        if self.event_types.is_empty() {
+            return Err("Subscribe to at least one event type.");
+        }
+
+        let registered = EVENT_TYPES.read().unwrap();
+        if self
+            .event_types
+            .iter()
+            .any(|event_type| event_type != "*" && !registered.contains_key(event_type))
+        {
+            return Err("Unknown event type.");
+        }
+
+        Ok(())
+    }
+
+    fn changeset(&self, user_id: ID, secret: String) -> WebhookEndpointChangeset {
+        WebhookEndpointChangeset {
+            user_id,
+            url: self.url.clone(),
+            description: self.description.clone().unwrap_or_default(),
+            secret,
+            event_types: serde_json::to_string(&self.event_types).unwrap_or_default(),
+            enabled: self.enabled.unwrap_or(true),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// how the worker sends deliveries
+pub struct WebhookConfig {
+    /// how often the worker looks for deliveries to send (default: 5 seconds)
+    pub poll_interval: Duration,
+    /// the maximum number of deliveries sent per poll (default: 50)
+    pub batch_size: i64,
+    /// how many times a delivery is attempted before it's failed (default: 8)
+    pub max_attempts: i32,
+    /// how long to wait before the first retry, doubled after every failure (default: 1 minute)
+    pub base_delay: Duration,
+    /// the longest wait between two retries (default: 6 hours)
+    pub max_delay: Duration,
+    /// how long endpoints have to respond (default: 10 seconds)
+    pub timeout: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            batch_size: 50,
+            max_attempts: 8,
+            base_delay: Duration::from_secs(60),
+            max_delay: Duration::from_secs(6 * 60 * 60),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// how long to wait before retrying a delivery which failed `attempts` times
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let exponent = attempts.saturating_sub(1).clamp(0, 31) as u32;
+
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_delay)
+    }
+}
+
+impl WebhookEndpoint {
+    /// Create an entry in [`db`](`Connection`)'s `webhook_endpoints` table using the data in [`item`](`WebhookEndpointChangeset`)
+    pub fn create(db: &mut Connection, item: &WebhookEndpointChangeset) -> QueryResult<Self> {
+        use schema::webhook_endpoints::dsl::*;
+
+        insert_into(webhook_endpoints)
+            .values(item)
+            .get_result::<WebhookEndpoint>(db)
+    }
+
+    /// registers an endpoint of [`item_user_id`](`ID`), with a new secret
+    ///
+    /// validate the [`form`](`EndpointForm`) first
+    pub fn register(
+        db: &mut Connection,
+        item_user_id: ID,
+        form: &EndpointForm,
+    ) -> QueryResult<Self> {
+        Self::create(db, &form.changeset(item_user_id, generate_secret()))
+    }
+
+    /// updates the endpoint [`item_id`](`ID`) of [`item_user_id`](`ID`), keeping its secret
+    ///
+    /// validate the [`form`](`EndpointForm`) first
+    pub fn modify(
+        db: &mut Connection,
+        item_user_id: ID,
+        item_id: ID,
+        form: &EndpointForm,
+    ) -> QueryResult<Self> {
+        let endpoint = Self::read_for_user(db, item_user_id, item_id)?;
+
+        Self::update(db, item_id, &form.changeset(item_user_id, endpoint.secret))
+    }
+
+    /// the endpoint [`item_id`](`ID`) of [`item_user_id`](`ID`)
+    pub fn read_for_user(db: &mut Connection, item_user_id: ID, item_id: ID) -> QueryResult<Self> {
+        use schema::webhook_endpoints::dsl::*;
+
+        webhook_endpoints
+            .filter(id.eq(item_id))
+            .filter(user_id.eq(item_user_id))
+            .first::<WebhookEndpoint>(db)
+    }
+
+    /// the endpoints of [`item_user_id`](`ID`), oldest first
+    pub fn fetch_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use schema::webhook_endpoints::dsl::*;
+
+        webhook_endpoints
+            .filter(user_id.eq(item_user_id))
+            .order(created_at.asc())
+            .load::<WebhookEndpoint>(db)
+    }
+
+    /// Update the endpoint [`item_id`](`ID`) of [`item.user_id`](`WebhookEndpointChangeset`)
+    pub fn update(
+        db: &mut Connection,
+        item_id: ID,
+        item: &WebhookEndpointChangeset,
+    ) -> QueryResult<Self> {
+        use schema::webhook_endpoints::dsl::*;
+
+        diesel::update(
+            webhook_endpoints
+                .filter(id.eq(item_id))
+                .filter(user_id.eq(item.user_id)),
+        )
+        .set((item, updated_at.eq(now())))
+        .get_result(db)
+    }
+
+    /// Delete the endpoint [`item_id`](`ID`) of [`item_user_id`](`ID`), and its deliveries
+    pub fn delete(db: &mut Connection, item_user_id: ID, item_id: ID) -> QueryResult<usize> {
+        use schema::webhook_endpoints::dsl::*;
+
+        let endpoint = match Self::read_for_user(db, item_user_id, item_id) {
+            Ok(endpoint) => endpoint,
+            Err(diesel::result::Error::NotFound) => return Ok(0),
+            Err(e) => return Err(e),
+        };
+
+        diesel::delete(
+            webhook_deliveries::table.filter(webhook_deliveries::endpoint_id.eq(endpoint.id)),
+        )
+        .execute(db)?;
+
+        diesel::delete(
+            webhook_endpoints
+                .filter(id.eq(item_id))
+                .filter(user_id.eq(item_user_id)),
+        )
+        .execute(db)
+    }
+
+    /// the names of the event types it's subscribed to
+    pub fn subscriptions(&self) -> Vec<String> {
+        serde_json::from_str(&self.event_types).unwrap_or_default()
+    }
+
+    pub fn is_subscribed_to(&self, event_type: &str) -> bool {
+        self.subscriptions()
+            .iter()
+            .any(|subscription| subscription == "*" || subscription == event_type)
+    }
+
+    /// the enabled endpoints subscribed to [`event_type`]
+    fn subscribed_to(db: &mut Connection, event_type: &str) -> QueryResult<Vec<Self>> {
+        use schema::webhook_endpoints::dsl::*;
+
+        Ok(webhook_endpoints
+            .filter(enabled.eq(true))
+            .load::<WebhookEndpoint>(db)?
+            .into_iter()
+            .filter(|endpoint| endpoint.is_subscribed_to(event_type))
+            .collect())
+    }
+}
+
+impl WebhookDelivery {
+    /// the deliveries of [`item_endpoint_id`](`ID`), most recent first, as its owner sees them
+    pub fn list_for_endpoint(
+        db: &mut Connection,
+        item_endpoint_id: ID,
+        page: i64,
+        page_size: i64,
+    ) -> QueryResult<Vec<EndpointDelivery>> {
+        use schema::webhook_deliveries::dsl::*;
+
+        Ok(webhook_deliveries
+            .filter(endpoint_id.eq(item_endpoint_id))
+            .order(created_at.desc())
+            .limit(page_size)
+            .offset(page * page_size)
+            .load::<WebhookDelivery>(db)?
+            .into_iter()
+            .map(EndpointDelivery::from)
+            .collect())
+    }
+
+    /// every delivery, most recent first, optionally only those of [`item_status`](`DeliveryStatus`)
+    pub fn list(
+        db: &mut Connection,
+        item_status: Option<DeliveryStatus>,
+        page: i64,
+        page_size: i64,
+    ) -> QueryResult<Vec<DeliveryOverview>> {
+        use schema::webhook_deliveries::dsl::*;
+
+        let mut query = webhook_deliveries
+            .inner_join(webhook_endpoints::table)
+            .select((
+                schema::webhook_deliveries::all_columns,
+                webhook_endpoints::url,
+                webhook_endpoints::user_id,
+            ))
+            .into_boxed();
+        if let Some(item_status) = item_status {
+            query = query.filter(status.eq(item_status.as_str()));
+        }
+
+        Ok(query
+            .order(created_at.desc())
+            .limit(page_size)
+            .offset(page * page_size)
+            .load::<(WebhookDelivery, String, ID)>(db)?
+            .into_iter()
+            .map(|(delivery, url, user_id)| DeliveryOverview {
+                delivery,
+                url,
+                user_id,
+            })
+            .collect())
+    }
+
+    /// counts the deliveries by status
+    pub fn counts(db: &mut Connection) -> QueryResult<Vec<(String, i64)>> {
+        use schema::webhook_deliveries::dsl::*;
+
+        webhook_deliveries
+            .group_by(status)
+            .select((status, diesel::dsl::count_star()))
+            .load::<(String, i64)>(db)
+    }
+
+    /// sends the delivery [`item_id`](`ID`) again, returns `0` if there's no such delivery (of
+    /// [`item_user_id`](`ID`)'s endpoints, if given)
+    pub fn retry(db: &mut Connection, item_user_id: Option<ID>, item_id: ID) -> QueryResult<usize> {
+        use schema::webhook_deliveries::dsl::*;
+
+        if let Some(item_user_id) = item_user_id {
+            let owned = webhook_deliveries
+                .inner_join(webhook_endpoints::table)
+                .filter(id.eq(item_id))
+                .filter(webhook_endpoints::user_id.eq(item_user_id))
+                .count()
+                .get_result::<i64>(db)?;
+
+            if owned == 0 {
+                return Ok(0);
+            }
+        }
+
+        diesel::update(webhook_deliveries.filter(id.eq(item_id)))
+            .set((
+                status.eq(DeliveryStatus::Pending.as_str()),
+                attempts.eq(0),
+                next_attempt_at.eq(now()),
+            ))
+            .execute(db)
+    }
+
+    /// the pending deliveries which are due, oldest first
+    fn due(db: &mut Connection, limit: i64) -> QueryResult<Vec<Self>> {
+        use schema::webhook_deliveries::dsl::*;
+
+        webhook_deliveries
+            .filter(status.eq(DeliveryStatus::Pending.as_str()))
+            .filter(next_attempt_at.le(now()))
+            .order(next_attempt_at.asc())
+            .limit(limit)
+            .load::<WebhookDelivery>(db)
+    }
+
+    /// pushes back the delivery's next attempt while it's sent, returns `false` if another
+    /// worker got to it first
+    fn claim(&self, db: &mut Connection) -> QueryResult<bool> {
+        use schema::webhook_deliveries::dsl::*;
+
+        let current_time = now();
+        let claimed = diesel::update(
+            webhook_deliveries
+                .filter(id.eq(self.id))
+                .filter(status.eq(DeliveryStatus::Pending.as_str()))
+                .filter(next_attempt_at.le(current_time)),
+        )
+        .set(next_attempt_at.eq(current_time + chrono::Duration::minutes(DELIVERY_LEASE_MINUTES)))
+        .execute(db)?;
+
+        Ok(claimed == 1)
+    }
+
+    fn mark_succeeded(&self, db: &mut Connection, response: &Response) -> QueryResult<usize> {
+        use schema::webhook_deliveries::dsl::*;
+
+        diesel::update(webhook_deliveries.filter(id.eq(self.id)))
+            .set((
+                status.eq(DeliveryStatus::Succeeded.as_str()),
+                response_status.eq(Some(response.status)),
+                response_body.eq(Some(&response.body)),
+                last_error.eq(None::<String>),
+                delivered_at.eq(Some(now())),
+            ))
+            .execute(db)
+    }
+
+    /// schedules the next attempt, or fails the delivery after [`WebhookConfig::max_attempts`]
+    fn mark_failed(
+        &self,
+        db: &mut Connection,
+        response: Option<&Response>,
+        error: &str,
+        config: &WebhookConfig,
+    ) -> QueryResult<usize> {
+        use schema::webhook_deliveries::dsl::*;
+
+        let failed_attempts = self.attempts + 1;
+        let (next_status, next_attempt) = if failed_attempts >= config.max_attempts {
+            (DeliveryStatus::Failed, now())
+        } else {
+            let delay = chrono::Duration::from_std(config.backoff(failed_attempts))
+                .unwrap_or_else(|_| chrono::Duration::days(1));
+
+            (DeliveryStatus::Pending, now() + delay)
+        };
+
+        diesel::update(webhook_deliveries.filter(id.eq(self.id)))
+            .set((
+                status.eq(next_status.as_str()),
+                attempts.eq(failed_attempts),
+                response_status.eq(response.map(|response| response.status)),
+                response_body.eq(response.map(|response| response.body.clone())),
+                last_error.eq(Some(error)),
+                next_attempt_at.eq(next_attempt),
+            ))
+            .execute(db)
+    }
+}
+
+/// includes webhook endpoints in account exports (without their secrets), and deletes them with
+/// the account
+pub(crate) struct WebhooksAccountHook;
+
+impl crate::auth::account::AccountHook for WebhooksAccountHook {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    fn export(&self, db: &mut Connection, user_id: ID) -> QueryResult<serde_json::Value> {
+        let endpoints = WebhookEndpoint::fetch_all_for_user(db, user_id)?
+            .into_iter()
+            .map(|endpoint| {
+                serde_json::json!({
+                    "url": endpoint.url,
+                    "description": endpoint.description,
+                    "event_types": endpoint.subscriptions(),
+                    "enabled": endpoint.enabled,
+                    "created_at": endpoint.created_at,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::json!({ "endpoints": endpoints }))
+    }
+
+    fn delete(&self, db: &mut Connection, user_id: ID) -> QueryResult<()> {
+        for endpoint in WebhookEndpoint::fetch_all_for_user(db, user_id)? {
+            WebhookEndpoint::delete(db, user_id, endpoint.id)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// records a delivery of the [`event_type`] event for every enabled endpoint subscribed to it,
+/// returns how many there are
+///
+/// they're sent by the worker, see [`spawn_worker`]
+pub fn dispatch<T: Serialize>(
+    db: &mut Connection,
+    event_type: &str,
+    data: &T,
+) -> QueryResult<usize> {
+    if !EVENT_TYPES.read().unwrap().contains_key(event_type) {
+        println!("webhooks: dispatching '{event_type}', which isn't a registered event type");
+    }
+
+    let payload = serde_json::to_string(data).unwrap_or_else(|_| "null".to_string());
+    let deliveries = WebhookEndpoint::subscribed_to(db, event_type)?
+        .into_iter()
+        .map(|endpoint| WebhookDeliveryChangeset {
+            endpoint_id: endpoint.id,
+            event_type: event_type.to_string(),
+            payload: payload.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    if deliveries.is_empty() {
+        return Ok(0);
+    }
+
+    insert_into(webhook_deliveries::table)
+        .values(&deliveries)
+        .execute(db)
+}
+
+/// a secret to sign an endpoint's deliveries with
+pub fn generate_secret() -> String {
+    use rand::Rng;
+
+    format!("whsec_{:032x}", rand::thread_rng().gen::<u128>())
+}
+
+/// the `Webhook-Signature` of a delivery of `body` at `timestamp` (seconds since the epoch):
+/// `t=<timestamp>,v1=<hex HMAC-SHA256 of "<timestamp>.<body>" with the secret>`
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+
+    let signature = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+
+    format!("t={timestamp},v1={signature}")
+}
+
+/// whether deliveries can be sent to `ip`: it mustn't be one of the app's network (loopback,
+/// private, link-local, unique-local, shared, ...) nor a special one
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, third, _] = ip.octets();
+
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // 0.0.0.0/8, 100.64.0.0/10 (shared), 192.0.0.0/24 (protocol assignments),
+                // 198.18.0.0/15 (benchmarking), 240.0.0.0/4
+                || first == 0
+                || (first == 100 && second & 0xc0 == 64)
+                || (first == 192 && second == 0 && third == 0)
+                || (first == 198 && second & 0xfe == 18)
+                || first >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // fc00::/7 (unique-local), fe80::/10 (link-local), fec0::/10 (site-local)
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80
+                || first & 0xffc0 == 0xfec0
+                // 64:ff9b::/96 (NAT64) and 2002::/16 (6to4), which can reach any IPv4 address
+                || ip.segments()[..6] == [0x64, 0xff9b, 0, 0, 0, 0]
+                || first == 0x2002)
+        }
+    }
+}
+
+/// the addresses the url's host resolves to, fails unless they're all public (see [`is_public`])
+fn resolve_public(url: &reqwest::Url) -> Result<Vec<SocketAddr>, &'static str> {
+    let addresses = url
+        .socket_addrs(|| None)
+        .map_err(|_| "The url's host could not be resolved.")?;
+
+    if addresses.is_empty() || !addresses.iter().all(|address| is_public(address.ip())) {
+        return Err("The url must point to a public address.");
+    }
+
+    Ok(addresses)
+}
+
+/// an endpoint's response
+struct Response {
+    status: i32,
+    body: String,
+}
+
+/// `POST`s the delivery to the endpoint
+///
+/// the endpoint's host is resolved again, as it may not point to a public address anymore, and
+/// the request is sent to the address which was checked, without following redirects
+fn send(
+    config: &WebhookConfig,
+    endpoint: &WebhookEndpoint,
+    delivery: &WebhookDelivery,
+) -> Result<Response, String> {
+    let url = reqwest::Url::parse(&endpoint.url).map_err(|err| err.to_string())?;
+    let addresses = resolve_public(&url).map_err(String::from)?;
+
+    let mut client = reqwest::blocking::Client::builder()
+        .timeout(config.timeout)
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(host) = url.host_str() {
+        client = client.resolve(host, addresses[0]);
+    }
+    let client = client.build().map_err(|err| err.to_string())?;
+
+    let body = serde_json::to_string(&Event {
+        id: delivery.id,
+        event_type: delivery.event_type.clone(),
+        created_at: delivery.created_at,
+        data: serde_json::from_str(&delivery.payload).unwrap_or_default(),
+    })
+    .map_err(|err| err.to_string())?;
+
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("Webhook-Id", delivery.id.to_string())
+        .header("Webhook-Event", &delivery.event_type)
+        .header(
+            "Webhook-Signature",
+            sign(&endpoint.secret, chrono::Utc::now().timestamp(), &body),
+        )
+        .body(body)
+        .send()
+        .map_err(|err| format!("Could not reach the endpoint: {err}"))?;
+
+    Ok(Response {
+        status: response.status().as_u16() as i32,
+        body: response
+            .text()
+            .unwrap_or_default()
+            .chars()
+            .take(MAX_RESPONSE_BODY_LENGTH)
+            .collect(),
+    })
+}
+
+/// sends the deliveries which are due, returns how many succeeded
+pub fn deliver_due(db: &mut Connection, config: &WebhookConfig) -> QueryResult<usize> {
+    let mut succeeded = 0;
+
+    for delivery in WebhookDelivery::due(db, config.batch_size)? {
+        if !delivery.claim(db)? {
+            continue;
+        }
+
+        let endpoint = webhook_endpoints::table
+            .find(delivery.endpoint_id)
+            .first::<WebhookEndpoint>(db)?;
+
+        match send(config, &endpoint, &delivery) {
+            Ok(response) if (200..300).contains(&response.status) => {
+                delivery.mark_succeeded(db, &response)?;
+                succeeded += 1;
+            }
+            Ok(response) => {
+                let error = format!("The endpoint responded with {}", response.status);
+                delivery.mark_failed(db, Some(&response), &error, config)?;
+            }
+            Err(err) => {
+                delivery.mark_failed(db, None, &err, config)?;
+            }
+        }
+    }
+
+    Ok(succeeded)
+}
+
+/// starts a thread sending the deliveries every [`WebhookConfig::poll_interval`]
+///
+/// workers can run in several instances of the app, a delivery is only sent by one of them
+pub fn spawn_worker(database: Database, config: WebhookConfig) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || loop {
+        match database.pool.get() {
+            Ok(mut db) => {
+                if let Err(err) = deliver_due(&mut db, &config) {
+                    println!("Could not send webhooks: {err}");
+                }
+            }
+            Err(err) => println!("Could not send webhooks: {err}"),
+        }
+
+        std::thread::sleep(config.poll_interval);
+    })
+}
+
+fn now() -> Utc {
+    #[cfg(not(feature = "database_sqlite"))]
+    return chrono::Utc::now();
+
+    #[cfg(feature = "database_sqlite")]
+    return chrono::Utc::now().naive_utc();
+}
+
+#[cfg(test)]
+mod webhooks_tests {
+    use super::is_public;
+    use std::net::IpAddr;
+
+    fn public(ip: &str) -> bool {
+        is_public(ip.parse::<IpAddr>().unwrap())
+    }
+
+    #[test]
+    fn test_is_public() {
+        assert!(public("93.184.216.34"));
+        assert!(public("1.1.1.1"));
+        assert!(public("198.20.0.1"));
+        assert!(public("2606:4700:4700::1111"));
+        assert!(public("::ffff:93.184.216.34"));
+    }
+
+    #[test]
+    fn test_is_public_rejects_loopback_and_unspecified() {
+        assert!(!public("127.0.0.1"));
+        assert!(!public("127.1.2.3"));
+        assert!(!public("0.0.0.0"));
+        assert!(!public("::1"));
+        assert!(!public("::"));
+    }
+
+    #[test]
+    fn test_is_public_rejects_private_networks() {
+        assert!(!public("10.0.0.1"));
+        assert!(!public("172.16.0.1"));
+        assert!(!public("192.168.1.1"));
+        assert!(!public("fc00::1"));
+        assert!(!public("fd12:3456::1"));
+    }
+
+    #[test]
+    fn test_is_public_rejects_link_local() {
+        assert!(!public("169.254.169.254"));
+        assert!(!public("fe80::1"));
+    }
+
+    #[test]
+    fn test_is_public_rejects_shared_address_space() {
+        assert!(!public("100.64.0.1"));
+        assert!(!public("100.127.255.254"));
+        assert!(public("100.128.0.1"));
+    }
+
+    #[test]
+    fn test_is_public_rejects_ipv4_mapped_addresses() {
+        assert!(!public("::ffff:127.0.0.1"));
+        assert!(!public("::ffff:10.0.0.1"));
+        assert!(!public("::ffff:169.254.169.254"));
+    }
+
+    #[test]
+    fn test_is_public_rejects_benchmarking_and_6to4() {
+        assert!(!public("198.18.0.1"));
+        assert!(!public("198.19.255.254"));
+        assert!(!public("2002:7f00:1::1"));
+        assert!(!public("2002:a00:1::1"));
+        assert!(!public("64:ff9b::7f00:1"));
+    }
+}
@@ -0,0 +1,34 @@
+table! {
+  webhook_deliveries (id) {
+      id -> Int4,
+      endpoint_id -> Int4,
+      event_type -> Text,
+      payload -> Text,
+      status -> Text,
+      attempts -> Int4,
+      response_status -> Nullable<Int4>,
+      response_body -> Nullable<Text>,
+      last_error -> Nullable<Text>,
+      next_attempt_at -> Timestamptz,
+      delivered_at -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  webhook_endpoints (id) {
+      id -> Int4,
+      user_id -> Int4,
+      url -> Text,
+      description -> Text,
+      secret -> Text,
+      event_types -> Text,
+      enabled -> Bool,
+      created_at -> Timestamptz,
+      updated_at -> Timestamptz,
+  }
+}
+
+joinable!(webhook_deliveries -> webhook_endpoints (endpoint_id));
+
+allow_tables_to_appear_in_same_query!(webhook_deliveries, webhook_endpoints,);
@@ -0,0 +1,34 @@
+table! {
+  webhook_deliveries (id) {
+      id -> Integer,
+      endpoint_id -> Integer,
+      event_type -> Text,
+      payload -> Text,
+      status -> Text,
+      attempts -> Integer,
+      response_status -> Nullable<Integer>,
+      response_body -> Nullable<Text>,
+      last_error -> Nullable<Text>,
+      next_attempt_at -> Timestamp,
+      delivered_at -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  webhook_endpoints (id) {
+      id -> Integer,
+      user_id -> Integer,
+      url -> Text,
+      description -> Text,
+      secret -> Text,
+      event_types -> Text,
+      enabled -> Bool,
+      created_at -> Timestamp,
+      updated_at -> Timestamp,
+  }
+}
+
+joinable!(webhook_deliveries -> webhook_endpoints (endpoint_id));
+
+allow_tables_to_appear_in_same_query!(webhook_deliveries, webhook_endpoints,);
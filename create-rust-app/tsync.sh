@@ -2,4 +2,9 @@
 
 tsync -i src/auth -o plugin-auth.d.ts
 tsync -i src/dev -o plugin-dev.d.ts
-tsync -i src/storage -o plugin-storage.d.ts
\ No newline at end of file
+tsync -i src/storage -o plugin-storage.d.ts
+tsync -i src/metering -o plugin-metering.d.ts
+
+tsync -i src/consent -o plugin-consent.d.ts
+tsync -i src/notifications -o plugin-notifications.d.ts
+tsync -i src/referrals -o plugin-referrals.d.ts
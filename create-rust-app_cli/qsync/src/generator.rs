@@ -0,0 +1,198 @@
+//! Generate react-query hooks programmatically, from build scripts, watch modes or IDE
+//! integrations:
+//!
+//! ```rust,ignore
+//! let generated = qsync::Generator::new()
+//!     .input("backend/services")
+//!     .generate();
+//!
+//! generated.write("frontend/src/api.generated.ts").unwrap();
+//! ```
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::processor::{process_service_file, BuildState};
+
+#[derive(Debug, Clone)]
+/// generates a react-query hook for each `#[qsync]` handler in its inputs
+pub struct Generator {
+    input_paths: Vec<PathBuf>,
+    react_query_module: String,
+    auth_hook_module: String,
+    debug: bool,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            input_paths: vec![],
+            react_query_module: "react-query".to_string(),
+            auth_hook_module: "./hooks/useAuth".to_string(),
+            debug: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// the output of [`Generator::generate`]
+pub struct Generated {
+    /// the typescript file, with every hook
+    pub code: String,
+    /// names of the generated hooks (ex: `useTodosIndex`)
+    pub hooks: Vec<String>,
+    /// files which couldn't be read or parsed
+    pub unprocessed_files: Vec<PathBuf>,
+}
+
+impl Generated {
+    /// writes [`code`](`Generated::code`) to `output_path`
+    pub fn write(&self, output_path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::File::create(output_path)?.write_all(self.code.as_bytes())
+    }
+}
+
+impl Generator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a rust file, or a directory whose rust files are processed recursively
+    ///
+    /// hook names are derived from the path under the `services` directory
+    /// (`backend/services/todos.rs` => `useTodos...`)
+    pub fn input(mut self, path: impl Into<PathBuf>) -> Self {
+        self.input_paths.push(path.into());
+        self
+    }
+
+    pub fn inputs(mut self, paths: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        self.input_paths.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// where `useQuery`, `useMutation` and `useQueryClient` are imported from (default: `react-query`)
+    pub fn react_query_module(mut self, module: impl Into<String>) -> Self {
+        self.react_query_module = module.into();
+        self
+    }
+
+    /// where `useAuth` is imported from, relative to the output file (default: `./hooks/useAuth`)
+    pub fn auth_hook_module(mut self, module: impl Into<String>) -> Self {
+        self.auth_hook_module = module.into();
+        self
+    }
+
+    /// print what's being processed
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// parses the inputs and generates the hooks, without writing anything
+    pub fn generate(&self) -> Generated {
+        let is_debug = self.debug;
+        let mut state: BuildState = BuildState {
+            types: String::new(),
+            hooks: vec![],
+            unprocessed_files: Vec::<PathBuf>::new(),
+            is_debug,
+        };
+
+        state.types.push_str(
+            r#"/**
+    Hooks in this file were generated by create-rust-app's query-sync feature.
+
+    1 — Generating hooks
+    -=-=-=-=-=-=-=-=-=-=-=-
+    Execute `create-rust-app` in your project folder and select "query-sync".
+    This will generate react-hooks which are missing in this file for all
+    functions defined in the `backend/services` folder which have a
+    `#[qsync(returns = "<typescript return type>"[, mutate])]` attribute
+    as well as one of the following actix_web attributes: `#[post(...)]`,
+    `#[get(...)]`, `#[put(...)]`, `#[delete(...)]`, or `#[patch(...)]`.
+
+    2 — Editing hooks
+    -=-=-=-=-=-=-=-=-=-
+    You may edit the hooks as you see fit, they will not regenerate so long as
+    a constant with their name is present. For example:
+
+        const useTodo = ( ... ) => { ... }
+
+    If the "const useTodo" is present, query-sync will not regenerate this hook
+    and any changes you make will sustain through subsequent generation. If you
+    don't want a particular hook (for whatever reason), you can do this:
+
+        const useTodo = null
+
+    Now, because "const useTodo" is already defined, this hook will not be
+    regenerated. As it follows, if you delete "const useTodo = ...", query-sync
+    will regenerate that hook, which is useful if you want to start over.
+*/
+"#,
+        );
+
+        state.types.push_str(&format!(
+            "import {{ useMutation, useQuery, useQueryClient }} from '{}'\n",
+            self.react_query_module
+        ));
+
+        state.types.push_str(&format!(
+            "\nimport {{ useAuth }} from '{}'\n",
+            self.auth_hook_module
+        ));
+
+        for input_path in self.input_paths.iter().cloned() {
+            if !input_path.exists() {
+                if is_debug {
+                    println!("Path `{input_path:#?}` does not exist");
+                }
+
+                state.unprocessed_files.push(input_path);
+                continue;
+            }
+
+            if input_path.is_dir() {
+                for entry in WalkDir::new(input_path.clone()).sort_by_file_name() {
+                    match entry {
+                        Ok(dir_entry) => {
+                            let path = dir_entry.into_path();
+
+                            // skip dir files because they're going to be recursively crawled by WalkDir
+                            if !path.is_dir() {
+                                // make sure it is a rust file
+                                let extension = path.extension();
+                                if extension.is_some()
+                                    && extension.unwrap().eq_ignore_ascii_case("rs")
+                                {
+                                    process_service_file(path, &mut state);
+                                } else if is_debug {
+                                    println!(
+                                        "Encountered non-service or non-rust file `{path:#?}`"
+                                    );
+                                }
+                            } else if is_debug {
+                                println!("Encountered directory `{path:#?}`");
+                            }
+                        }
+                        Err(_) => {
+                            println!(
+                                "An error occurred whilst walking directory `{:#?}`...",
+                                input_path.clone()
+                            );
+                            continue;
+                        }
+                    }
+                }
+            } else {
+                process_service_file(input_path, &mut state);
+            }
+        }
+
+        Generated {
+            code: state.types,
+            hooks: state.hooks.into_iter().map(|hook| hook.hook_name).collect(),
+            unprocessed_files: state.unprocessed_files,
+        }
+    }
+}
@@ -1,9 +1,11 @@
 extern crate syn;
 
+mod generator;
 mod hook;
 mod params;
 mod processor;
 mod utils;
+pub use generator::{Generated, Generator};
 pub use processor::process;
 
 /// the #[qsync] attribute macro which marks structs and types to be translated into queries
@@ -4,18 +4,17 @@ use darling::FromMeta;
 use regex::Regex;
 use std::fs::File;
 use std::io::Read;
-use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 
 use crate::utils::file_path_to_vec_string;
+use crate::Generator;
 use syn::FnArg;
 use syn::Pat;
 use syn::PatType;
 use syn::PathArguments;
 use syn::Type;
 use syn::TypePath;
-use walkdir::WalkDir;
 
 extern crate inflector;
 use super::processor::inflector::Inflector;
@@ -272,7 +271,7 @@ fn extract_endpoint_information(
     hook.endpoint_verb = verb;
 }
 
-struct BuildState /*<'a>*/ {
+pub(crate) struct BuildState /*<'a>*/ {
     pub types: String,
     pub hooks: Vec<Hook>,
     pub unprocessed_files: Vec<PathBuf>,
@@ -288,7 +287,7 @@ fn generate_hook_name(input_path: &Path, fn_name: String) -> String {
     hook_name.join("")
 }
 
-fn process_service_file(input_path: PathBuf, state: &mut BuildState) {
+pub(crate) fn process_service_file(input_path: PathBuf, state: &mut BuildState) {
     if state.is_debug {
         println!(
             "processing rust file: {:?}",
@@ -425,116 +424,21 @@ fn process_service_file(input_path: PathBuf, state: &mut BuildState) {
     }
 }
 
+/// generates hooks for the `#[qsync]` handlers in `input_paths` and writes them to `output_path`
+/// (or prints them, when `is_debug` is set)
+///
+/// see [`Generator`](`crate::Generator`) to generate them programmatically
 pub fn process(input_paths: Vec<PathBuf>, output_path: PathBuf, is_debug: bool) {
-    let mut state: BuildState = BuildState {
-        types: String::new(),
-        hooks: vec![],
-        unprocessed_files: Vec::<PathBuf>::new(),
-        is_debug,
-    };
-
-    state.types.push_str(
-        r#"/**
-    Hooks in this file were generated by create-rust-app's query-sync feature.
-
-    1 — Generating hooks
-    -=-=-=-=-=-=-=-=-=-=-=-
-    Execute `create-rust-app` in your project folder and select "query-sync".
-    This will generate react-hooks which are missing in this file for all
-    functions defined in the `backend/services` folder which have a
-    `#[qsync(returns = "<typescript return type>"[, mutate])]` attribute
-    as well as one of the following actix_web attributes: `#[post(...)]`,
-    `#[get(...)]`, `#[put(...)]`, `#[delete(...)]`, or `#[patch(...)]`.
-
-    2 — Editing hooks
-    -=-=-=-=-=-=-=-=-=-
-    You may edit the hooks as you see fit, they will not regenerate so long as
-    a constant with their name is present. For example:
-
-        const useTodo = ( ... ) => { ... }
-
-    If the "const useTodo" is present, query-sync will not regenerate this hook
-    and any changes you make will sustain through subsequent generation. If you
-    don't want a particular hook (for whatever reason), you can do this:
-
-        const useTodo = null
-
-    Now, because "const useTodo" is already defined, this hook will not be
-    regenerated. As it follows, if you delete "const useTodo = ...", query-sync
-    will regenerate that hook, which is useful if you want to start over.
-*/
-"#,
-    );
-
-    // state
-    //     .types
-    //     .push_str("\nimport type { QueryKey } from 'react-query'\n");
-
-    state
-        .types
-        .push_str("import { useMutation, useQuery, useQueryClient } from 'react-query'\n");
-
-    state
-        .types
-        .push_str("\nimport { useAuth } from './hooks/useAuth'\n");
-
-    // state
-    //     .types
-    //     .push_str("\n/* Placeholder for types which need to be defined */\ntype TODO = unknown\n");
-
-    for input_path in input_paths {
-        if !input_path.exists() {
-            if is_debug {
-                println!("Path `{input_path:#?}` does not exist");
-            }
-
-            state.unprocessed_files.push(input_path);
-            continue;
-        }
-
-        if input_path.is_dir() {
-            for entry in WalkDir::new(input_path.clone()).sort_by_file_name() {
-                match entry {
-                    Ok(dir_entry) => {
-                        let path = dir_entry.into_path();
-
-                        // skip dir files because they're going to be recursively crawled by WalkDir
-                        if !path.is_dir() {
-                            // make sure it is a rust file
-                            let extension = path.extension();
-                            if extension.is_some() && extension.unwrap().eq_ignore_ascii_case("rs")
-                            // && !path
-                            //     .file_name()
-                            //     .unwrap_or_default()
-                            //     .eq_ignore_ascii_case("storage")
-                            {
-                                process_service_file(path, &mut state);
-                            } else if is_debug {
-                                println!("Encountered non-service or non-rust file `{path:#?}`");
-                            }
-                        } else if is_debug {
-                            println!("Encountered directory `{path:#?}`");
-                        }
-                    }
-                    Err(_) => {
-                        println!(
-                            "An error occurred whilst walking directory `{:#?}`...",
-                            input_path.clone()
-                        );
-                        continue;
-                    }
-                }
-            }
-        } else {
-            process_service_file(input_path, &mut state);
-        }
-    }
+    let state = Generator::new()
+        .inputs(input_paths)
+        .debug(is_debug)
+        .generate();
 
     if is_debug {
         println!("======================================");
         println!("FINAL FILE:");
         println!("======================================");
-        println!("{}", state.types);
+        println!("{}", state.code);
         println!("======================================");
         println!("Note: Nothing is written in debug mode");
         println!("======================================");
@@ -581,8 +485,7 @@ pub fn process(input_paths: Vec<PathBuf>, output_path: PathBuf, is_debug: bool)
         //         added, existing, &output_path
         //     )
         // } else {
-        let mut file: File = File::create(&output_path).expect("Unable to write to file");
-        match file.write_all(state.types.as_bytes()) {
+        match state.write(&output_path) {
             Ok(_) => println!("Successfully generated hooks, see {output_path:#?}"),
             Err(_) => println!("Failed to generate types, an error occurred."),
         }
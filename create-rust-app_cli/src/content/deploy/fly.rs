@@ -0,0 +1,83 @@
+use crate::content::deploy::{write_files, Project};
+use crate::logger;
+use crate::BackendDatabase;
+use anyhow::Result;
+
+/// `fly.toml`: the container plugin's image on Fly.io, the migrations running as its release command
+pub fn generate(project: &Project) -> Result<()> {
+    project.require_dockerfile("Fly.io")?;
+
+    let name = &project.name;
+    let is_sqlite = project.database == BackendDatabase::Sqlite;
+
+    // the release command runs on a temporary machine, without the SQLite database's volume
+    let deploy = if project.image_runs_migrations() && !is_sqlite {
+        r#"
+[deploy]
+  release_command = "diesel migration run"
+"#
+    } else {
+        ""
+    };
+    let (secrets, mounts) = match project.database {
+        BackendDatabase::Postgres => (
+            "# `fly postgres create` then `fly postgres attach` (sets DATABASE_URL)",
+            "",
+        ),
+        BackendDatabase::Sqlite => (
+            "# `fly volumes create data --size 1` and `fly secrets set DATABASE_URL=/app/data/database.sqlite`",
+            r#"
+# the SQLite database (keep a single machine: `fly scale count 1`)
+[mounts]
+  source = "data"
+  destination = "/app/data"
+"#,
+        ),
+    };
+    let redis = if project.uses_redis() {
+        "\n# `fly redis create` then `fly secrets set REDIS_URL=...` (read with create-rust-app's plugin_redis feature)"
+    } else {
+        ""
+    };
+
+    let fly_toml = format!(
+        r#"# `fly launch --no-deploy` (keeping this config), then `fly deploy`
+#
+# `fly secrets set SECRET_KEY=...`
+{secrets}{redis}
+
+app = "{name}"
+primary_region = "iad"
+
+[build]
+  dockerfile = "Dockerfile"
+{deploy}
+[env]
+  APP_ENV = "production"
+{mounts}
+[http_service]
+  internal_port = 3000
+  force_https = true
+  auto_stop_machines = true
+  auto_start_machines = true
+  min_machines_running = 1
+
+[[http_service.checks]]
+  grace_period = "10s"
+  interval = "30s"
+  method = "GET"
+  path = "/"
+  timeout = "5s"
+"#
+    );
+
+    write_files(&[("fly.toml".to_string(), fly_toml)])?;
+
+    logger::message("Fly.io: set the secrets listed in fly.toml, then `fly deploy`.");
+    if is_sqlite {
+        logger::message("Fly.io: migrate the SQLite database with `fly ssh console -C \"diesel migration run\"`.");
+    }
+    project.log_notes();
+
+    Ok(())
+}
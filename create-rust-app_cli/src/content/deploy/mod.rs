@@ -0,0 +1,132 @@
+///
+/// Deployment configurations for the project in the current directory (`create-rust-app generate`),
+/// matched to its database and plugins.
+///
+pub mod fly;
+pub mod k8s;
+pub mod railway;
+pub mod render;
+
+use crate::logger;
+use crate::BackendDatabase;
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+/// the project deployment configurations are generated for, see [`Project::read`]
+pub struct Project {
+    pub name: String,
+    pub database: BackendDatabase,
+    /// create-rust-app's features (ex: `plugin_cache`)
+    pub features: Vec<String>,
+}
+
+impl Project {
+    /// reads the project's name, database and create-rust-app's features from its Cargo.toml
+    pub fn read() -> Result<Project> {
+        let toml = std::fs::read_to_string("Cargo.toml")
+            .map_err(|_| anyhow!("Cargo.toml not found, run this in your project's directory."))?;
+        let toml = toml.parse::<toml::Value>()?;
+
+        let name = toml
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+            .ok_or_else(|| anyhow!("Cargo.toml doesn't have a package name."))?
+            .to_string();
+        let features: Vec<String> = toml
+            .get("dependencies")
+            .and_then(|dependencies| dependencies.get("create-rust-app"))
+            .ok_or_else(|| anyhow!("This isn't a create-rust-app project."))?
+            .get("features")
+            .and_then(|features| features.as_array())
+            .map(|features| {
+                features
+                    .iter()
+                    .filter_map(|feature| feature.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let database = if features.iter().any(|feature| feature == "database_sqlite") {
+            BackendDatabase::Sqlite
+        } else {
+            BackendDatabase::Postgres
+        };
+
+        Ok(Project {
+            name,
+            database,
+            features,
+        })
+    }
+
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|enabled| enabled == feature)
+    }
+
+    /// whether the app's plugins can share their state through Redis (`REDIS_URL`)
+    pub fn uses_redis(&self) -> bool {
+        self.has_feature("plugin_cache") || self.has_feature("plugin_realtime")
+    }
+
+    /// fails unless the project has the container plugin's Dockerfile, which `platform` builds
+    pub fn require_dockerfile(&self, platform: &str) -> Result<()> {
+        if PathBuf::from("Dockerfile").is_file() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{platform} builds the app with the container plugin's Dockerfile, add it first."
+            ))
+        }
+    }
+
+    /// whether the container plugin's image can run the migrations (`diesel migration run`)
+    pub fn image_runs_migrations(&self) -> bool {
+        std::fs::read_to_string("Dockerfile")
+            .map(|dockerfile| dockerfile.contains("AS diesel"))
+            .unwrap_or(false)
+    }
+
+    /// the notes about the project's setup the generated configurations can't cover
+    pub fn log_notes(&self) {
+        if !self.image_runs_migrations() {
+            logger::message("The migrations run with the image built by the container plugin's Dockerfile (`diesel migration run`): add the plugin, or run them yourself.");
+        }
+        if self.has_feature("plugin_realtime") && !self.has_feature("plugin_redis") {
+            logger::message("Realtime: with several instances of the app, enable create-rust-app's plugin_redis feature so that broadcasts reach every client.");
+        }
+
+        let workers: Vec<&str> = [
+            ("plugin_tasks", "queue"),
+            ("plugin_scheduler", "scheduler"),
+            ("plugin_broker", "consumers"),
+        ]
+        .iter()
+        .filter(|(feature, _)| self.has_feature(feature))
+        .map(|(_, bin)| *bin)
+        .collect();
+        if !workers.is_empty() {
+            logger::message(&format!(
+                "The image only runs the app: build and deploy the {} binaries too.",
+                workers.join(", ")
+            ));
+        }
+    }
+}
+
+/// writes the generated files, failing if any of them exists
+pub fn write_files(files: &[(String, String)]) -> Result<()> {
+    if let Some((path, _)) = files.iter().find(|(path, _)| PathBuf::from(path).exists()) {
+        return Err(anyhow!("'{path}' already exists."));
+    }
+
+    for (path, contents) in files {
+        let mut directory = PathBuf::from(path);
+        directory.pop();
+        std::fs::create_dir_all(directory)?;
+
+        logger::add_file_msg(path);
+        std::fs::write(path, contents)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use crate::content::deploy::{write_files, Project};
+use crate::logger;
+use crate::BackendDatabase;
+use anyhow::Result;
+
+/// `railway.toml`: the container plugin's image on Railway, the migrations running as its
+/// pre-deploy command
+pub fn generate(project: &Project) -> Result<()> {
+    project.require_dockerfile("Railway")?;
+
+    let is_sqlite = project.database == BackendDatabase::Sqlite;
+
+    // the pre-deploy command runs without the SQLite database's volume
+    let pre_deploy = if project.image_runs_migrations() && !is_sqlite {
+        "\npreDeployCommand = [\"diesel migration run\"]"
+    } else {
+        ""
+    };
+    let database = match project.database {
+        BackendDatabase::Postgres => {
+            "# - add a PostgreSQL database, and set DATABASE_URL=${{Postgres.DATABASE_URL}}"
+        }
+        BackendDatabase::Sqlite => {
+            "# - add a volume mounted at /app/data, and set DATABASE_URL=/app/data/database.sqlite"
+        }
+    };
+    let redis = if project.uses_redis() {
+        "\n# - add Redis, and set REDIS_URL=${{Redis.REDIS_URL}} (read with create-rust-app's plugin_redis feature)"
+    } else {
+        ""
+    };
+
+    let railway_toml = format!(
+        r#"# Railway's config as code, read when deploying the repository (`railway up`)
+#
+# In the service's settings:
+# - set PORT=3000, APP_ENV=production and SECRET_KEY
+{database}{redis}
+
+[build]
+builder = "DOCKERFILE"
+dockerfilePath = "Dockerfile"
+
+[deploy]{pre_deploy}
+healthcheckPath = "/"
+healthcheckTimeout = 100
+restartPolicyType = "ON_FAILURE"
+restartPolicyMaxRetries = 10
+"#
+    );
+
+    write_files(&[("railway.toml".to_string(), railway_toml)])?;
+
+    logger::message("Railway: set the variables listed in railway.toml, then `railway up`.");
+    if is_sqlite {
+        logger::message(
+            "Railway: migrate the SQLite database with `railway ssh diesel migration run`.",
+        );
+    }
+    project.log_notes();
+
+    Ok(())
+}
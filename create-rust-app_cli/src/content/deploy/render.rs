@@ -0,0 +1,104 @@
+use crate::content::deploy::{write_files, Project};
+use crate::logger;
+use crate::BackendDatabase;
+use anyhow::Result;
+
+/// `render.yaml`: a Render Blueprint deploying the container plugin's image with its database
+/// (and Redis when the plugins use it), the migrations running as its pre-deploy command
+pub fn generate(project: &Project) -> Result<()> {
+    project.require_dockerfile("Render")?;
+
+    let name = &project.name;
+    let is_sqlite = project.database == BackendDatabase::Sqlite;
+
+    // the pre-deploy command runs without the SQLite database's disk
+    let pre_deploy = if project.image_runs_migrations() && !is_sqlite {
+        "\n    preDeployCommand: diesel migration run"
+    } else {
+        ""
+    };
+    let (database_url, disk, databases) = match project.database {
+        BackendDatabase::Postgres => (
+            format!(
+                r#"
+      - key: DATABASE_URL
+        fromDatabase:
+          name: {name}-db
+          property: connectionString"#
+            ),
+            String::new(),
+            format!(
+                r#"
+databases:
+  - name: {name}-db
+    databaseName: database
+"#
+            ),
+        ),
+        BackendDatabase::Sqlite => (
+            r#"
+      - key: DATABASE_URL
+        value: /app/data/database.sqlite"#
+                .to_string(),
+            r#"
+    # the SQLite database
+    disk:
+      name: data
+      mountPath: /app/data
+      sizeGB: 1"#
+                .to_string(),
+            String::new(),
+        ),
+    };
+    let (redis_url, redis) = if project.uses_redis() {
+        (
+            format!(
+                r#"
+      # read with create-rust-app's plugin_redis feature
+      - key: REDIS_URL
+        fromService:
+          type: redis
+          name: {name}-redis
+          property: connectionString"#
+            ),
+            format!(
+                r#"
+  - type: redis
+    name: {name}-redis
+    ipAllowList: [] # only reachable from the Render services
+"#
+            ),
+        )
+    } else {
+        (String::new(), String::new())
+    };
+
+    let render_yaml = format!(
+        r#"# a Render Blueprint: in the dashboard, New > Blueprint, then pick the repository
+services:
+  - type: web
+    name: {name}
+    runtime: docker
+    dockerfilePath: ./Dockerfile{pre_deploy}{disk}
+    envVars:
+      - key: PORT
+        value: "3000"
+      - key: APP_ENV
+        value: production
+      - key: SECRET_KEY
+        generateValue: true{database_url}{redis_url}
+{redis}{databases}"#
+    );
+
+    write_files(&[("render.yaml".to_string(), render_yaml)])?;
+
+    logger::message(
+        "Render: create a Blueprint from the repository (New > Blueprint) to deploy render.yaml.",
+    );
+    if is_sqlite {
+        logger::message("Render: migrate the SQLite database from the service's shell with `diesel migration run`.");
+    }
+    project.log_notes();
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Result};
+use inflector::Inflector;
+
+/// the kinds of PostGIS geometries a generated resource's column can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryKind {
+    Point,
+    LineString,
+    Polygon,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+}
+
+impl GeometryKind {
+    fn parse(kind: &str) -> Option<GeometryKind> {
+        match kind.to_snake_case().as_str() {
+            "point" => Some(GeometryKind::Point),
+            "line_string" | "linestring" => Some(GeometryKind::LineString),
+            "polygon" => Some(GeometryKind::Polygon),
+            "multi_point" | "multipoint" => Some(GeometryKind::MultiPoint),
+            "multi_line_string" | "multilinestring" => Some(GeometryKind::MultiLineString),
+            "multi_polygon" | "multipolygon" => Some(GeometryKind::MultiPolygon),
+            _ => None,
+        }
+    }
+
+    /// PostGIS' name of the kind, for the `geometry(<kind>, 4326)` columns
+    pub fn postgis_type(&self) -> &'static str {
+        match self {
+            GeometryKind::Point => "Point",
+            GeometryKind::LineString => "LineString",
+            GeometryKind::Polygon => "Polygon",
+            GeometryKind::MultiPoint => "MultiPoint",
+            GeometryKind::MultiLineString => "MultiLineString",
+            GeometryKind::MultiPolygon => "MultiPolygon",
+        }
+    }
+}
+
+/// a geometry column of a generated resource (`location:point`), see the geo plugin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeometryColumn {
+    pub name: String,
+    pub kind: GeometryKind,
+}
+
+impl GeometryColumn {
+    /// the column's definition, of WGS84 (longitude/latitude) coordinates
+    pub fn sql(&self) -> String {
+        format!(
+            "{} geometry({}, 4326) NOT NULL",
+            self.name,
+            self.kind.postgis_type()
+        )
+    }
+
+    /// the index of the column's geography, which the distance queries use
+    pub fn index_sql(&self, table_name: &str) -> String {
+        format!(
+            "CREATE INDEX {table_name}_{name}_index ON {table_name} USING GIST (geography({name}));",
+            name = self.name
+        )
+    }
+}
+
+/// parses comma separated `name:kind` columns (ex: `location:point, area:polygon`)
+pub fn parse(columns: &str) -> Result<Vec<GeometryColumn>> {
+    columns
+        .split(',')
+        .map(str::trim)
+        .filter(|column| !column.is_empty())
+        .map(|column| {
+            let (name, kind) = column.split_once(':').ok_or_else(|| {
+                anyhow!("'{column}' isn't a geometry column, expected `name:kind` (ex: `location:point`)")
+            })?;
+            let kind = GeometryKind::parse(kind.trim()).ok_or_else(|| {
+                anyhow!("'{kind}' isn't a geometry kind, expected point, line_string, polygon, multi_point, multi_line_string or multi_polygon")
+            })?;
+
+            Ok(GeometryColumn {
+                name: name.trim().to_snake_case(),
+                kind,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,252 @@
+use crate::fs;
+use crate::logger;
+use anyhow::{anyhow, Result};
+use indoc::indoc;
+use inflector::Inflector;
+use std::path::PathBuf;
+
+const APOLLO_CLIENT_FILE: &str = "frontend/src/hooks/useAuthenticatedApolloClient.ts";
+
+/// served by the backend in development, see `index_sdl` in `backend/graphql`
+const SCHEMA_URL: &str = "http://localhost:3000/graphql/schema.graphql";
+const SCHEMA_FILE: &str = "frontend/src/graphql/schema.graphql";
+
+/// whether the project has the GraphQL plugin's `backend/graphql` module
+pub fn is_installed() -> bool {
+    PathBuf::from("backend/graphql/mod.rs").is_file()
+}
+
+/// whether the schema is an Apollo Federation subgraph (see `InstallConfig::graphql_federation`)
+pub fn is_federated() -> bool {
+    std::fs::read_to_string("backend/main.rs")
+        .map(|main| main.contains(".enable_federation()"))
+        .unwrap_or(false)
+}
+
+/// adds the resource's queries to the GraphQL schema: a Relay-style connection of the records, and a
+/// record by id resolved through a per-request loader (and its entity resolver in subgraphs);
+/// resources with attachments get a mutation uploading them as well
+pub fn create(resource_name: &str, has_attachments: bool) -> Result<()> {
+    let model_name = resource_name.to_pascal_case();
+    let file_name = model_name.to_snake_case();
+    let table_name = file_name.to_plural();
+
+    let contents_template: &str = indoc! {r#"
+    use std::collections::HashMap;
+
+    use async_graphql::dataloader::{DataLoader, Loader};
+    use async_graphql::{Context, Json, Object, Result};
+    use create_rust_app::graphql::{paginate, OffsetConnection};
+    use create_rust_app::Database;
+    use diesel::prelude::*;
+
+    use crate::models::$TABLE_NAME::$MODEL_NAME;
+
+    /// loads the $TABLE_NAME by id, see `loaders.rs`
+    pub struct $MODEL_NAMELoader(pub Database);
+
+    #[async_trait::async_trait]
+    impl Loader<i32> for $MODEL_NAMELoader {
+        type Value = $MODEL_NAME;
+        type Error = String;
+
+        async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+            use crate::schema::$TABLE_NAME::dsl::*;
+
+            let mut db = self.0.get_connection();
+            let items = $TABLE_NAME
+                .filter(id.eq_any(keys))
+                .load::<$MODEL_NAME>(&mut db)
+                .map_err(|err| err.to_string())?;
+
+            Ok(items.into_iter().map(|item| (item.id, item)).collect())
+        }
+    }
+
+    /// a $FILE_NAME in the GraphQL schema: its `id`, and the whole record as `json`; add the fields
+    /// to query here, and resolve its relations through their loaders
+    pub struct $MODEL_NAMEObject(pub $MODEL_NAME);
+
+    #[Object(name = "$MODEL_NAME")]
+    impl $MODEL_NAMEObject {
+        async fn id(&self) -> i32 {
+            self.0.id
+        }
+
+        async fn json(&self) -> Json<$MODEL_NAME> {
+            Json(self.0.clone())
+        }
+    }
+
+    #[derive(Default)]
+    pub struct $MODEL_NAMEQuery;
+
+    #[Object]
+    impl $MODEL_NAMEQuery {
+        /// the $TABLE_NAME, paginated Relay-style
+        async fn $TABLE_NAME(
+            &self,
+            ctx: &Context<'_>,
+            after: Option<String>,
+            before: Option<String>,
+            first: Option<i32>,
+            last: Option<i32>,
+        ) -> Result<OffsetConnection<$MODEL_NAMEObject>> {
+            use crate::schema::$TABLE_NAME::dsl::*;
+
+            let mut db = ctx.data::<Database>()?.get_connection();
+            let total = $TABLE_NAME.count().get_result::<i64>(&mut db)?;
+
+            paginate(after, before, first, last, total, |offset, limit| {
+                let items = $TABLE_NAME
+                    .order(id)
+                    .offset(offset)
+                    .limit(limit)
+                    .load::<$MODEL_NAME>(&mut db)?;
+
+                Ok(items.into_iter().map($MODEL_NAMEObject).collect())
+            })
+            .await
+        }
+
+        async fn $FILE_NAME(&self, ctx: &Context<'_>, id: i32) -> Result<Option<$MODEL_NAMEObject>> {
+            let loader = ctx.data::<DataLoader<$MODEL_NAMELoader>>()?;
+
+            Ok(loader.load_one(id).await?.map($MODEL_NAMEObject))
+        }
+    }
+    "#};
+
+    let mutation_template: &str = indoc! {r#"
+
+    #[derive(Default)]
+    pub struct $MODEL_NAMEMutation;
+
+    #[Object]
+    impl $MODEL_NAMEMutation {
+        /// stores `file` as the `name` attachment of the $FILE_NAME `id`
+        async fn attach_$FILE_NAME_file(
+            &self,
+            ctx: &Context<'_>,
+            id: i32,
+            name: String,
+            file: async_graphql::Upload,
+        ) -> Result<AttachmentObject> {
+            let attachment = attach_upload::<$MODEL_NAME>(ctx, file, id, name, true, false).await?;
+
+            Ok(attachment.into())
+        }
+    }
+    "#};
+
+    let entity_template: &str = indoc! {r#"
+    #[derive(Default)]
+    pub struct $MODEL_NAMEQuery;
+
+    #[Object]
+    impl $MODEL_NAMEQuery {
+        /// resolves the `$MODEL_NAME` entities referenced by other subgraphs, by `id` (its `@key`)
+        #[graphql(entity)]
+        async fn find_$FILE_NAME_by_id(&self, ctx: &Context<'_>, id: i32) -> Result<$MODEL_NAMEObject> {
+            let loader = ctx.data::<DataLoader<$MODEL_NAMELoader>>()?;
+
+            loader
+                .load_one(id)
+                .await?
+                .map($MODEL_NAMEObject)
+                .ok_or_else(|| format!("$MODEL_NAME #{id} not found").into())
+        }
+    "#};
+
+    let mut contents_template = contents_template.to_string();
+    if is_federated() {
+        contents_template = contents_template.replace(
+            "#[derive(Default)]\npub struct $MODEL_NAMEQuery;\n\n#[Object]\nimpl $MODEL_NAMEQuery {\n",
+            &format!("{entity_template}\n"),
+        );
+    }
+    if has_attachments {
+        contents_template = contents_template.replace(
+            "use create_rust_app::graphql::{paginate, OffsetConnection};",
+            "use create_rust_app::graphql::{attach_upload, paginate, AttachmentObject, OffsetConnection};",
+        );
+        contents_template.push_str(mutation_template);
+    }
+
+    let contents = contents_template
+        .replace("$MODEL_NAME", &model_name)
+        .replace("$TABLE_NAME", &table_name)
+        .replace("$FILE_NAME", &file_name);
+
+    fs::add_rust_file("backend/graphql", &file_name, &contents)?;
+
+    fs::replace(
+        "backend/graphql/query.rs",
+        "/* CRA: queries */",
+        &format!("super::{file_name}::{model_name}Query,\n    /* CRA: queries */"),
+    )?;
+
+    if has_attachments {
+        fs::replace(
+            "backend/graphql/mutation.rs",
+            "/* CRA: mutations */",
+            &format!("super::{file_name}::{model_name}Mutation,\n    /* CRA: mutations */"),
+        )?;
+    }
+
+    fs::replace(
+        "backend/graphql/loaders.rs",
+        "/* CRA: loaders */",
+        &format!("data.insert(DataLoader::new(super::{file_name}::{model_name}Loader(db.clone()), tokio::spawn));\n    /* CRA: loaders */"),
+    )?;
+
+    // lets the frontend's `fetchMore` append the connection's pages
+    if PathBuf::from(APOLLO_CLIENT_FILE).is_file() {
+        fs::replace(
+            APOLLO_CLIENT_FILE,
+            "/* CRA: connections */",
+            &format!(
+                "{}: relayStylePagination(),\n                        /* CRA: connections */",
+                table_name.to_camel_case()
+            ),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// regenerates the frontend's typed documents (see `frontend/codegen.ts`) from the running
+/// backend's schema, which is saved in `frontend/src/graphql/schema.graphql`
+pub fn codegen() -> Result<()> {
+    let schema_url =
+        std::env::var("CRA_GRAPHQL_SCHEMA_URL").unwrap_or_else(|_| SCHEMA_URL.to_string());
+
+    logger::message(&format!("Downloading the GraphQL schema from {schema_url}"));
+    let schema = ureq::get(&schema_url)
+        .call()
+        .map_err(|err| {
+            anyhow!("Could not download the schema, is the backend running in development (`cargo fullstack`)? {err}")
+        })?
+        .into_string()?;
+
+    std::fs::create_dir_all("frontend/src/graphql")?;
+    logger::add_file_msg(SCHEMA_FILE);
+    std::fs::write(SCHEMA_FILE, schema)?;
+
+    logger::command_msg("npm run graphql-codegen");
+    let status = std::process::Command::new("npm")
+        .args(["run", "graphql-codegen"])
+        .current_dir("frontend")
+        .status()
+        .map_err(|err| anyhow!("Could not run `npm` (is it installed?): {err}"))?;
+
+    if !status.success() {
+        return Err(anyhow!(
+            "`npm run graphql-codegen` failed, are the frontend's dependencies installed (`npm install`)?"
+        ));
+    }
+
+    logger::message("Generated the GraphQL types in frontend/src/graphql/generated");
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use crate::fs;
+use crate::logger;
+use anyhow::Result;
+use indoc::indoc;
+use inflector::Inflector;
+use std::path::PathBuf;
+
+/// whether the project has the gRPC plugin's `backend/grpc` module
+pub fn is_installed() -> bool {
+    PathBuf::from("backend/grpc/mod.rs").is_file()
+}
+
+/// adds a gRPC service for the resource: its `proto/<resource>.proto` (compiled by `build.rs`),
+/// and its implementation in `backend/grpc`, listing, reading, creating, updating and deleting the
+/// records (sent as JSON, add typed fields to the messages as needed)
+pub fn create(resource_name: &str) -> Result<()> {
+    let model_name = resource_name.to_pascal_case();
+    let file_name = model_name.to_snake_case();
+    let table_name = file_name.to_plural();
+
+    let proto_template: &str = indoc! {r#"
+    syntax = "proto3";
+
+    package $TABLE_NAME;
+
+    // a $FILE_NAME, serialized as JSON
+    message $MODEL_NAMEMessage {
+      int32 id = 1;
+      string json = 2;
+    }
+
+    message List$MODEL_NAMERequest {
+      int64 page = 1;
+      int64 page_size = 2;
+    }
+
+    message List$MODEL_NAMEResponse {
+      repeated $MODEL_NAMEMessage items = 1;
+      int64 total_items = 2;
+      int64 num_pages = 3;
+    }
+
+    message Get$MODEL_NAMERequest {
+      int32 id = 1;
+    }
+
+    // `json` is a `Create$MODEL_NAME`
+    message Create$MODEL_NAMERequest {
+      string json = 1;
+    }
+
+    // `json` is an `Update$MODEL_NAME`
+    message Update$MODEL_NAMERequest {
+      int32 id = 1;
+      string json = 2;
+    }
+
+    message Delete$MODEL_NAMERequest {
+      int32 id = 1;
+    }
+
+    message Delete$MODEL_NAMEResponse {
+      uint64 deleted = 1;
+    }
+
+    service $MODEL_NAMEService {
+      rpc List(List$MODEL_NAMERequest) returns (List$MODEL_NAMEResponse);
+      rpc Get(Get$MODEL_NAMERequest) returns ($MODEL_NAMEMessage);
+      rpc Create(Create$MODEL_NAMERequest) returns ($MODEL_NAMEMessage);
+      rpc Update(Update$MODEL_NAMERequest) returns ($MODEL_NAMEMessage);
+      rpc Delete(Delete$MODEL_NAMERequest) returns (Delete$MODEL_NAMEResponse);
+    }
+    "#};
+
+    let contents_template: &str = indoc! {r#"
+    use create_rust_app::grpc::blocking;
+    use create_rust_app::Database;
+    use tonic::{Request, Response, Status};
+
+    use crate::models::$TABLE_NAME::{Create$MODEL_NAME, $MODEL_NAME, Update$MODEL_NAME};
+
+    // the messages and the service of `proto/$FILE_NAME.proto`, generated by `build.rs`
+    tonic::include_proto!("$TABLE_NAME");
+
+    use $FILE_NAME_service_server::$MODEL_NAMEServiceServer;
+
+    /// the $TABLE_NAME' gRPC service; handlers requiring authentication start with
+    /// `let auth = create_rust_app::grpc::auth(&request)?;`
+    pub struct $MODEL_NAMEGrpc {
+        db: Database,
+    }
+
+    fn message(item: &$MODEL_NAME) -> Result<$MODEL_NAMEMessage, Status> {
+        Ok($MODEL_NAMEMessage {
+            id: item.id,
+            json: serde_json::to_string(item).map_err(|err| Status::internal(err.to_string()))?,
+        })
+    }
+
+    fn parse<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, Status> {
+        serde_json::from_str(json).map_err(|err| Status::invalid_argument(err.to_string()))
+    }
+
+    #[tonic::async_trait]
+    impl $FILE_NAME_service_server::$MODEL_NAMEService for $MODEL_NAMEGrpc {
+        async fn list(
+            &self,
+            request: Request<List$MODEL_NAMERequest>,
+        ) -> Result<Response<List$MODEL_NAMEResponse>, Status> {
+            let List$MODEL_NAMERequest { page, page_size } = request.into_inner();
+            let result = blocking(&self.db, move |db| {
+                $MODEL_NAME::paginate(db, page.max(0), page_size.clamp(1, 100))
+            })
+            .await?;
+
+            Ok(Response::new(List$MODEL_NAMEResponse {
+                items: result.items.iter().map(message).collect::<Result<_, _>>()?,
+                total_items: result.total_items,
+                num_pages: result.num_pages,
+            }))
+        }
+
+        async fn get(
+            &self,
+            request: Request<Get$MODEL_NAMERequest>,
+        ) -> Result<Response<$MODEL_NAMEMessage>, Status> {
+            let item_id = request.into_inner().id;
+            let item = blocking(&self.db, move |db| $MODEL_NAME::read(db, item_id)).await?;
+
+            Ok(Response::new(message(&item)?))
+        }
+
+        async fn create(
+            &self,
+            request: Request<Create$MODEL_NAMERequest>,
+        ) -> Result<Response<$MODEL_NAMEMessage>, Status> {
+            let item: Create$MODEL_NAME = parse(&request.into_inner().json)?;
+            let item = blocking(&self.db, move |db| $MODEL_NAME::create(db, &item)).await?;
+
+            Ok(Response::new(message(&item)?))
+        }
+
+        async fn update(
+            &self,
+            request: Request<Update$MODEL_NAMERequest>,
+        ) -> Result<Response<$MODEL_NAMEMessage>, Status> {
+            let request = request.into_inner();
+            let item_id = request.id;
+            let item: Update$MODEL_NAME = parse(&request.json)?;
+            let item = blocking(&self.db, move |db| $MODEL_NAME::update(db, item_id, &item)).await?;
+
+            Ok(Response::new(message(&item)?))
+        }
+
+        async fn delete(
+            &self,
+            request: Request<Delete$MODEL_NAMERequest>,
+        ) -> Result<Response<Delete$MODEL_NAMEResponse>, Status> {
+            let item_id = request.into_inner().id;
+            let deleted = blocking(&self.db, move |db| $MODEL_NAME::delete(db, item_id)).await?;
+
+            if deleted == 0 {
+                return Err(Status::not_found("Not found."));
+            }
+
+            Ok(Response::new(Delete$MODEL_NAMEResponse {
+                deleted: deleted as u64,
+            }))
+        }
+    }
+
+    pub fn service(db: Database) -> $MODEL_NAMEServiceServer<$MODEL_NAMEGrpc> {
+        $MODEL_NAMEServiceServer::new($MODEL_NAMEGrpc { db })
+    }
+    "#};
+
+    let replace = |template: &str| {
+        template
+            .replace("$MODEL_NAME", &model_name)
+            .replace("$TABLE_NAME", &table_name)
+            .replace("$FILE_NAME", &file_name)
+    };
+
+    std::fs::create_dir_all("proto")?;
+    let proto_file = format!("proto/{file_name}.proto");
+    logger::add_file_msg(&proto_file);
+    std::fs::write(&proto_file, replace(proto_template))?;
+
+    fs::add_rust_file("backend/grpc", &file_name, &replace(contents_template))?;
+
+    fs::replace(
+        "backend/grpc/mod.rs",
+        "/* CRA: services */",
+        &format!(".add_service({file_name}::service(db.clone()))\n        /* CRA: services */"),
+    )?;
+
+    Ok(())
+}
@@ -2,7 +2,13 @@ pub mod cargo_toml;
 ///
 /// This package contains helper functions which create content in create-rust-app projects.
 ///
+pub mod ci;
+pub mod deploy;
+pub mod geo;
+pub mod graphql;
+pub mod grpc;
 pub mod migration;
 pub mod model;
 pub mod project;
 pub mod service;
+pub mod sse;
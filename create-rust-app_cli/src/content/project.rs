@@ -1,4 +1,5 @@
 use crate::content::cargo_toml::add_dependency;
+use crate::content::geo::{GeometryColumn, GeometryKind};
 use crate::utils::git;
 use crate::utils::logger;
 use crate::BackendDatabase;
@@ -93,6 +94,32 @@ path = "backend/queue.rs"
         ""
     };
 
+    let scheduler_bin = if creations_options
+        .cra_enabled_features
+        .contains(&"plugin_scheduler".to_string())
+    {
+        r##"
+[[bin]]
+name = "scheduler"
+path = "backend/scheduler.rs"
+"##
+    } else {
+        ""
+    };
+
+    let consumers_bin = if creations_options
+        .cra_enabled_features
+        .contains(&"plugin_broker".to_string())
+    {
+        r##"
+[[bin]]
+name = "consumers"
+path = "backend/consumers.rs"
+"##
+    } else {
+        ""
+    };
+
     let append_to_toml = format!(
         r#"
 [[bin]]
@@ -118,7 +145,7 @@ path = ".cargo/bin/frontend.rs"
 [[bin]]
 name = "{project_name}"
 path = "backend/main.rs"
-{queue_bin}
+{queue_bin}{scheduler_bin}{consumers_bin}
 [profile.dev]
 debug-assertions=true
 "#
@@ -629,6 +656,14 @@ pub fn create_resource(
     backend: BackendFramework,
     resource_name: &str,
     include_qsync_attr: bool,
+    has_attachments: bool,
+    is_audited: bool,
+    is_cached: bool,
+    is_searchable: bool,
+    is_tenant_scoped: bool,
+    is_soft_deletable: bool,
+    uses_outbox: bool,
+    geometry_columns: &[GeometryColumn],
 ) -> Result<()> {
     let resource_name = resource_name.to_pascal_case();
 
@@ -640,8 +675,98 @@ pub fn create_resource(
         &format!("services::{}::api()", &resource_name),
         &resource_name.to_snake_case(),
         include_qsync_attr,
+        has_attachments,
+        is_audited,
+        is_cached,
+        is_searchable,
+        is_tenant_scoped,
+        is_soft_deletable,
+        uses_outbox,
+        geometry_columns,
     )?;
 
+    if is_tenant_scoped {
+        logger::message(&format!(
+            "'{}' needs a `tenant_id INTEGER NOT NULL REFERENCES organizations(id)` column: add it to its migration, then regenerate the models with `dsync`",
+            resource_name.to_snake_case().to_plural()
+        ));
+
+        if is_searchable {
+            logger::message(
+                "Search documents aren't scoped to organizations: override `Searchable::search_owner` to restrict who finds them",
+            );
+        }
+    }
+
+    if is_soft_deletable {
+        logger::message(&format!(
+            "'{}' needs a nullable `deleted_at TIMESTAMPTZ` column (`DATETIME` with SQLite): add it to its migration, then regenerate the models with `dsync`",
+            resource_name.to_snake_case().to_plural()
+        ));
+    }
+
+    if uses_outbox {
+        logger::message(&format!(
+            "Its changes are published through the outbox: register `services::{}::{}Changed` with the outbox's relay (`.event::<...>()`) in `backend/main.rs` to subscribe to them",
+            resource_name.to_snake_case(),
+            resource_name
+        ));
+    }
+
+    if !geometry_columns.is_empty() {
+        let table_name = resource_name.to_snake_case().to_plural();
+
+        logger::message(&format!(
+            "'{table_name}' needs its geometry columns (requires the geo plugin): add {} to its migration, index them with {}, then regenerate the models with `dsync`",
+            geometry_columns
+                .iter()
+                .map(|column| format!("`{}`", column.sql()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            geometry_columns
+                .iter()
+                .map(|column| format!("`{}`", column.index_sql(&table_name)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ));
+
+        if let Some(column) = geometry_columns
+            .iter()
+            .find(|column| column.kind == GeometryKind::Point)
+        {
+            logger::message(&format!(
+                "Its `/near` endpoint lists the '{table_name}' whose `{}` is within `meters` of a `longitude`/`latitude`, the nearest first",
+                column.name
+            ));
+        }
+    }
+
+    if crate::content::graphql::is_installed() {
+        crate::content::graphql::create(&resource_name, has_attachments)?;
+
+        if is_soft_deletable {
+            logger::message(
+                "Its GraphQL queries aren't soft-delete aware: filter on `deleted_at` in `backend/graphql` as well",
+            );
+        }
+    }
+
+    if crate::content::grpc::is_installed() {
+        crate::content::grpc::create(&resource_name)?;
+
+        if is_tenant_scoped {
+            logger::message(
+                "Its gRPC service isn't scoped to organizations: filter on `tenant_id` in `backend/grpc` as well",
+            );
+        }
+
+        if is_soft_deletable {
+            logger::message(
+                "Its gRPC service isn't soft-delete aware: filter on `deleted_at` in `backend/grpc` as well",
+            );
+        }
+    }
+
     Ok(())
 }
 
@@ -1,3 +1,4 @@
+use crate::content::geo::{GeometryColumn, GeometryKind};
 use crate::logger::register_service_msg;
 use crate::BackendFramework;
 use anyhow::Result;
@@ -21,10 +22,39 @@ pub fn create(
     service_api_fn: &str,
     base_endpoint_path: &str,
     include_qsync_attr: bool,
+    has_attachments: bool,
+    is_audited: bool,
+    is_cached: bool,
+    is_searchable: bool,
+    is_tenant_scoped: bool,
+    is_soft_deletable: bool,
+    uses_outbox: bool,
+    geometry_columns: &[GeometryColumn],
 ) -> Result<()> {
     let resource = match backend {
-        BackendFramework::ActixWeb => generate_actix(resource_name, include_qsync_attr),
-        BackendFramework::Poem => generate_poem(resource_name),
+        BackendFramework::ActixWeb => generate_actix(
+            resource_name,
+            include_qsync_attr,
+            has_attachments,
+            is_audited,
+            is_cached,
+            is_searchable,
+            is_tenant_scoped,
+            is_soft_deletable,
+            uses_outbox,
+            geometry_columns,
+        ),
+        BackendFramework::Poem => generate_poem(
+            resource_name,
+            has_attachments,
+            is_audited,
+            is_cached,
+            is_searchable,
+            is_tenant_scoped,
+            is_soft_deletable,
+            uses_outbox,
+            geometry_columns,
+        ),
     };
 
     crate::fs::add_rust_file(
@@ -60,7 +90,289 @@ fn config(service_name: &str) -> ServiceConfig {
     }
 }
 
-fn generate_poem(service_name: &str) -> Service {
+/// lets the resource own attachments, served by the storage plugin's attachment endpoints
+fn attachments_impl(has_attachments: bool) -> &'static str {
+    if has_attachments {
+        indoc! {"
+
+        impl create_rust_app::storage::HasAttachments for $MODEL_NAME {
+            const RECORD_TYPE: &'static str = \"$FILE_NAME\";
+
+            // who can upload and detach the attachments: any signed in user, restrict it to the
+            // record's owners
+            fn can_write(_db: &mut create_rust_app::Connection, _record_id: i32, user_id: Option<i32>) -> bool {
+                user_id.is_some()
+            }
+        }
+        "}
+    } else {
+        ""
+    }
+}
+
+/// lets the search plugin index the resource (see `create_rust_app::search`)
+fn search_impl(is_searchable: bool) -> &'static str {
+    if is_searchable {
+        indoc! {"
+
+        impl create_rust_app::search::Searchable for $MODEL_NAME {
+            const INDEX: &'static str = \"$TABLE_NAME\";
+
+            fn search_id(&self) -> String {
+                self.id.to_string()
+            }
+        }
+        "}
+    } else {
+        ""
+    }
+}
+
+/// `replacement` when the resource is indexed for search, nothing otherwise
+fn searchable(is_searchable: bool, replacement: &'static str) -> &'static str {
+    if is_searchable {
+        replacement
+    } else {
+        ""
+    }
+}
+
+/// tenant-scoped handlers take the `Tenant`, and use its database, instead of the app's database
+fn scope_to_tenant(contents: String, is_tenant_scoped: bool) -> String {
+    if !is_tenant_scoped {
+        return contents;
+    }
+
+    contents
+        .replace(
+            "use create_rust_app::Database;",
+            "use create_rust_app::tenancy::Tenant;",
+        )
+        .replace("db: Data<&Database>", "tenant: Tenant")
+        .replace("db: Data<Database>", "tenant: Tenant")
+        .replace(
+            "let db = db.pool.get().unwrap();",
+            "let mut db = tenant.database.get_connection();",
+        )
+        .replace(
+            "let mut db = db.pool.get().unwrap();",
+            "let mut db = tenant.database.get_connection();",
+        )
+}
+
+/// `replacement` when the resource is soft-deleted (see `create_rust_app::soft_delete`), nothing
+/// otherwise
+fn soft_deletable(is_soft_deletable: bool, replacement: &'static str) -> &'static str {
+    if is_soft_deletable {
+        replacement
+    } else {
+        ""
+    }
+}
+
+/// the imports of the handlers querying the table themselves (tenant-scoped, soft-deleted or
+/// geometry resources), and of `SoftDelete` when they use its functions
+fn query_imports(is_tenant_scoped: bool, is_soft_deletable: bool, has_near: bool) -> &'static str {
+    match (is_tenant_scoped, is_soft_deletable) {
+        (false, true) => "use crate::schema::$TABLE_NAME;\nuse create_rust_app::soft_delete::SoftDelete;\nuse diesel::prelude::*;\n",
+        (true, _) => "use crate::schema::$TABLE_NAME;\nuse diesel::prelude::*;\n",
+        (false, false) if has_near => "use crate::schema::$TABLE_NAME;\nuse diesel::prelude::*;\n",
+        (false, false) => "",
+    }
+}
+
+/// the first point column of the resource, which its `/near` endpoint searches by distance
+fn first_point_column(geometry_columns: &[GeometryColumn]) -> Option<&str> {
+    geometry_columns
+        .iter()
+        .find(|column| column.kind == GeometryKind::Point)
+        .map(|column| column.name.as_str())
+}
+
+/// the rows within `info.meters` of `from`, the nearest first
+fn near_items(
+    near_column: Option<&str>,
+    is_tenant_scoped: bool,
+    is_soft_deletable: bool,
+    indent: &str,
+) -> String {
+    let column = match near_column {
+        Some(column) => column,
+        None => return String::new(),
+    };
+
+    format!(
+        "{}
+{indent}.filter(create_rust_app::geo::within($TABLE_NAME::{column}, from.clone(), info.meters))
+{indent}.order(create_rust_app::geo::distance($TABLE_NAME::{column}, from))
+{indent}.limit(info.limit)
+{indent}.load::<$MODEL_NAME>(&mut db)",
+        scoped_rows(is_tenant_scoped, is_soft_deletable, indent)
+    )
+}
+
+/// `replacement` when the resource has a point column, nothing otherwise
+fn near(near_column: Option<&str>, replacement: &'static str) -> &'static str {
+    if near_column.is_some() {
+        replacement
+    } else {
+        ""
+    }
+}
+
+/// the rows the handlers see: the ones which weren't soft-deleted, and the tenant's
+fn scoped_rows(is_tenant_scoped: bool, is_soft_deletable: bool, indent: &str) -> String {
+    let rows = if is_soft_deletable {
+        "$MODEL_NAME::active()"
+    } else {
+        "$TABLE_NAME::table"
+    };
+
+    format!("{rows}{}", tenant_filter(is_tenant_scoped, indent))
+}
+
+/// the soft-deleted rows (of the tenant)
+fn deleted_rows(is_tenant_scoped: bool, indent: &str) -> String {
+    format!(
+        "$MODEL_NAME::only_deleted(){}",
+        tenant_filter(is_tenant_scoped, indent)
+    )
+}
+
+fn tenant_filter(is_tenant_scoped: bool, indent: &str) -> String {
+    if is_tenant_scoped {
+        format!("\n{indent}.filter($TABLE_NAME::tenant_id.eq(tenant.id))")
+    } else {
+        String::new()
+    }
+}
+
+/// the row `item_id` for updates and deletions, if it's the tenant's and wasn't soft-deleted
+fn item_target(is_tenant_scoped: bool, is_soft_deletable: bool) -> String {
+    let indent = "                ";
+    let mut target = String::from(
+        "$TABLE_NAME::table
+                .filter($TABLE_NAME::id.eq(item_id))",
+    );
+    target.push_str(&tenant_filter(is_tenant_scoped, indent));
+    if is_soft_deletable {
+        target.push_str(&format!(
+            "\n{indent}.filter($TABLE_NAME::deleted_at.is_null())"
+        ));
+    }
+
+    target
+}
+
+/// restores the row `item_id`, returning how many were restored
+fn restore_item(is_tenant_scoped: bool) -> &'static str {
+    if is_tenant_scoped {
+        "diesel::update(
+            $TABLE_NAME::table
+                .filter($TABLE_NAME::id.eq(item_id))
+                .filter($TABLE_NAME::tenant_id.eq(tenant.id))
+                .filter($TABLE_NAME::deleted_at.is_not_null()),
+        )
+        .set($TABLE_NAME::deleted_at.eq(None::<create_rust_app::soft_delete::Utc>))
+        .execute(&mut db)"
+    } else {
+        "$MODEL_NAME::restore(&mut db, item_id)"
+    }
+}
+
+/// soft-deletes the row `item_id`, returning how many were deleted
+fn soft_delete_item(is_tenant_scoped: bool) -> String {
+    if is_tenant_scoped {
+        format!(
+            "diesel::update(
+            {},
+        )
+        .set($TABLE_NAME::deleted_at.eq(create_rust_app::soft_delete::now()))
+        .execute(&mut db)",
+            item_target(true, true)
+        )
+    } else {
+        "$MODEL_NAME::soft_delete(&mut db, item_id)".to_string()
+    }
+}
+
+/// the event recording the resource's changes in the outbox, see [`with_outbox`]
+fn outbox_impl(uses_outbox: bool) -> &'static str {
+    if uses_outbox {
+        indoc! {"
+
+        #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+        /// published through the outbox once a $MODEL_NAME was created, updated or deleted: register it
+        /// with the outbox's relay (`Relay::event::<$MODEL_NAMEChanged>()`) to subscribe to it
+        pub enum $MODEL_NAMEChanged {
+            Created($MODEL_NAME),
+            Updated($MODEL_NAME),
+            Deleted(i32),
+        }
+
+        impl create_rust_app::events::Event for $MODEL_NAMEChanged {
+            const NAME: &'static str = \"$TABLE_NAME.changed\";
+        }
+        "}
+    } else {
+        ""
+    }
+}
+
+/// runs the write `query` in a transaction recording `change` (a `$MODEL_NAMEChanged` built from
+/// its `result`) in the outbox, so it's only published once the write is committed
+fn with_outbox(uses_outbox: bool, query: String, change: &str) -> String {
+    if !uses_outbox {
+        return query;
+    }
+
+    format!(
+        "diesel::Connection::transaction::<_, diesel::result::Error, _>(&mut db, |db| {{
+            let result = {}?;
+            {change}
+            Ok(result)
+        }})",
+        query.replace("&mut db", "db").replace("&db", "db")
+    )
+}
+
+const OUTBOX_CREATED: &str =
+    "create_rust_app::outbox::publish(db, &$MODEL_NAMEChanged::Created(result.clone()))?;";
+const OUTBOX_UPDATED: &str =
+    "create_rust_app::outbox::publish(db, &$MODEL_NAMEChanged::Updated(result.clone()))?;";
+const OUTBOX_DELETED: &str = "if result > 0 {
+                create_rust_app::outbox::publish(db, &$MODEL_NAMEChanged::Deleted(item_id))?;
+            }";
+
+/// `replacement` when the resource's changes are recorded in the audit log, nothing otherwise
+fn audited(is_audited: bool, replacement: &'static str) -> &'static str {
+    if is_audited {
+        replacement
+    } else {
+        ""
+    }
+}
+
+/// `replacement` when the resource's reads are cached (see `create_rust_app::cache`), nothing otherwise
+fn cached(is_cached: bool, replacement: &'static str) -> &'static str {
+    if is_cached {
+        replacement
+    } else {
+        ""
+    }
+}
+
+fn generate_poem(
+    service_name: &str,
+    has_attachments: bool,
+    is_audited: bool,
+    is_cached: bool,
+    is_searchable: bool,
+    is_tenant_scoped: bool,
+    is_soft_deletable: bool,
+    uses_outbox: bool,
+    geometry_columns: &[GeometryColumn],
+) -> Service {
     let config = config(service_name);
     let contents_template: &str = indoc! {"\
     use create_rust_app::Database;
@@ -71,7 +383,7 @@ fn generate_poem(service_name: &str) -> Service {
     use poem::web::{Data, Json, Path, Query};
     use crate::models::$FILE_NAME::{$MODEL_NAME, $MODEL_NAMEChangeset};
     use crate::models::{PaginationParams, ID};
-
+    $TENANT_IMPORT$CACHE_IMPORT$AUDIT_IMPORT$ATTACHMENTS_IMPL$SEARCH_IMPL$SOFT_DELETE_IMPL$OUTBOX_IMPL
 
     #[handler]
     async fn index(
@@ -80,69 +392,384 @@ fn generate_poem(service_name: &str) -> Service {
     ) -> Result<impl IntoResponse> {
         let db = db.pool.get().unwrap();
 
-        Ok($MODEL_NAME::read_all(&db, &info)
+        Ok($LIST_ITEMS
             .map(|items| Json(items).with_status(StatusCode::OK)
             .map_err(|_| InternalServerError)?)
     }
 
     #[handler]
     async fn read(
-        db: Data<&Database>,
+        db: Data<&Database>,$CACHE_ARG
         Path(item_id): Path<ID>,
     ) -> Result<impl IntoResponse> {
         let db = db.pool.get().unwrap();
 
-        Ok($MODEL_NAME::read(&db, item_id)
+        Ok($READ_ITEM
             .map(|item| Json(item).with_status(StatusCode::FOUND))
             .map_err(|_| NotFound)?)
     }
 
     #[handler]
     async fn create(
-        db: Data<&Database>,
+        db: Data<&Database>,$AUDITOR_ARG
         Json(item): Json<$MODEL_NAMEChangeset>,
     ) -> Result<impl IntoResponse> {
         let db = db.pool.get().unwrap();
 
-        Ok($MODEL_NAME::create(&db, &item)
+        Ok($CREATE_ITEM$AUDIT_CREATE$SEARCH_INDEX
             .map(|item| Json(item).with_status(StatusCode::CREATED))
             .map_err(|_| InternalServerError)?)
     }
 
     #[handler]
     async fn update(
-        db: Data<&Database>,
+        db: Data<&Database>,$AUDITOR_ARG$CACHE_ARG
         Path(item_id): Path<ID>,
         Json(item): Json<$MODEL_NAMEChangeset>,
     ) -> Result<impl IntoResponse> {
-        let db = db.pool.get().unwrap();
+        let db = db.pool.get().unwrap();$AUDIT_BEFORE
 
-        Ok($MODEL_NAME::update(&db, item_id, &item)
+        Ok($UPDATE_ITEM$AUDIT_UPDATE$CACHE_INVALIDATE$SEARCH_INDEX
             .map(|item| Json(item))
             .map_err(|_| InternalServerError)?)
     }
 
     #[handler]
     async fn destroy(
-        db: Data<&Database>,
+        db: Data<&Database>,$AUDITOR_ARG$CACHE_ARG
         Path(item_id): Path<ID>,
     ) -> Result<impl IntoResponse> {
-        let db = db.pool.get().unwrap();
+        let db = db.pool.get().unwrap();$AUDIT_BEFORE
 
-        Ok($MODEL_NAME::delete(&db, item_id)
+        Ok($DELETE_ITEM$AUDIT_DESTROY$CACHE_INVALIDATE$SEARCH_REMOVE
             .map(|_| Response::builder().status(StatusCode::NO_CONTENT))
             .map_err(|_| InternalServerError)?)
     }
 
-    pub fn api() -> Route {
-        Route::new()
-            .at(\"/\", get(index).post(create))
-            .at(\"/:id\", get(read).put(update).delete(destroy))
+    $NEAR_HANDLER$SOFT_DELETE_HANDLERSpub fn api() -> Route {
+        let route = Route::new()
+            .at(\"/\", get(index).post(create))$SOFT_DELETE_ROUTES$NEAR_ROUTES
+            .at(\"/:id\", get(read).put(update).delete(destroy));
+
+        $ATTACHMENTS_ROUTESroute
     }
     "};
 
-    let contents = String::from(contents_template)
+    let near_column = first_point_column(geometry_columns);
+    let mut template = contents_template
+        .replace(
+            "$NEAR_HANDLER",
+            near(
+                near_column,
+                indoc! {"
+                #[derive(serde::Deserialize)]
+                struct Near$MODEL_NAMEParams {
+                    longitude: f64,
+                    latitude: f64,
+                    /// the search radius, in meters
+                    meters: f64,
+                    limit: i64,
+                }
+
+                #[handler]
+                async fn near(
+                    db: Data<&Database>,
+                    Query(info): Query<Near$MODEL_NAMEParams>,
+                ) -> Result<impl IntoResponse> {
+                    let db = db.pool.get().unwrap();
+                    let from = create_rust_app::geo::Geometry::point(info.longitude, info.latitude);
+
+                    Ok($NEAR_ITEMS
+                        .map(|items| Json(items).with_status(StatusCode::OK))
+                        .map_err(|_| InternalServerError)?)
+                }
+
+                "},
+            ),
+        )
+        .replace(
+            "$SOFT_DELETE_HANDLERS",
+            soft_deletable(
+                is_soft_deletable,
+                indoc! {"
+            #[handler]
+            async fn deleted(
+                db: Data<&Database>,
+                Query(info): Query<PaginationParams>,
+            ) -> Result<impl IntoResponse> {
+                let db = db.pool.get().unwrap();
+
+                Ok($DELETED_ITEMS
+                    .map(|items| Json(items).with_status(StatusCode::OK))
+                    .map_err(|_| InternalServerError)?)
+            }
+
+            #[handler]
+            async fn restore(
+                db: Data<&Database>,$CACHE_ARG
+                Path(item_id): Path<ID>,
+            ) -> Result<impl IntoResponse> {
+                let db = db.pool.get().unwrap();
+
+                Ok($RESTORE_ITEM$CACHE_INVALIDATE$SEARCH_RESTORE
+                    .map(|_| Response::builder().status(StatusCode::NO_CONTENT))
+                    .map_err(|_| InternalServerError)?)
+            }
+
+            "},
+            ),
+        );
+    // the soft-delete aware and distance queries are diesel's, and transactions, which take the
+    // connection mutably
+    if is_soft_deletable || uses_outbox || near_column.is_some() {
+        template = template.replace(
+            "let db = db.pool.get().unwrap();",
+            "let mut db = db.pool.get().unwrap();",
+        );
+    }
+
+    let list_items = if is_tenant_scoped || is_soft_deletable {
+        format!(
+            "{}
+            .order($TABLE_NAME::id)
+            .limit(info.page_size)
+            .offset(info.page * info.page_size)
+            .load::<$MODEL_NAME>(&mut db)",
+            scoped_rows(is_tenant_scoped, is_soft_deletable, "            ")
+        )
+    } else {
+        "$MODEL_NAME::read_all(&db, &info)".to_string()
+    };
+    let read_query = if is_tenant_scoped || is_soft_deletable {
+        format!(
+            "{}
+            .filter($TABLE_NAME::id.eq(item_id))
+            .first::<$MODEL_NAME>(&mut db)",
+            scoped_rows(is_tenant_scoped, is_soft_deletable, "            ")
+        )
+    } else {
+        "$MODEL_NAME::read(&db, item_id)".to_string()
+    };
+    let update_item = if is_tenant_scoped || is_soft_deletable {
+        format!(
+            "diesel::update(
+            {},
+        )
+        .set(&{})
+        .get_result::<$MODEL_NAME>(&mut db)",
+            item_target(is_tenant_scoped, is_soft_deletable),
+            if is_tenant_scoped {
+                "$MODEL_NAMEChangeset { tenant_id: tenant.id, ..item }"
+            } else {
+                "item"
+            }
+        )
+    } else {
+        "$MODEL_NAME::update(&db, item_id, &item)".to_string()
+    };
+    let create_item = if is_tenant_scoped {
+        "$MODEL_NAME::create(&db, &$MODEL_NAMEChangeset { tenant_id: tenant.id, ..item })"
+    } else {
+        "$MODEL_NAME::create(&db, &item)"
+    };
+    let delete_item = if is_soft_deletable {
+        soft_delete_item(is_tenant_scoped)
+    } else if is_tenant_scoped {
+        "diesel::delete(
+            $TABLE_NAME::table
+                .filter($TABLE_NAME::id.eq(item_id))
+                .filter($TABLE_NAME::tenant_id.eq(tenant.id)),
+        )
+        .execute(&mut db)"
+            .to_string()
+    } else {
+        "$MODEL_NAME::delete(&db, item_id)".to_string()
+    };
+    let deleted_items = format!(
+        "{}
+            .order($TABLE_NAME::deleted_at.desc())
+            .limit(info.page_size)
+            .offset(info.page * info.page_size)
+            .load::<$MODEL_NAME>(&mut db)",
+        deleted_rows(is_tenant_scoped, "            ")
+    );
+
+    let contents = scope_to_tenant(template, is_tenant_scoped)
+        .replace("$ATTACHMENTS_IMPL", attachments_impl(has_attachments))
+        .replace(
+            "$CACHE_IMPORT",
+            cached(is_cached, "use create_rust_app::Cache;\n"),
+        )
+        .replace("$CACHE_ARG", cached(is_cached, "\n    cache: Data<&Cache>,"))
+        .replace(
+            "$READ_ITEM",
+            if is_cached {
+                "cache.compute_if_absent(&format!($CACHE_KEY), || $READ_QUERY)"
+            } else {
+                "$READ_QUERY"
+            },
+        )
+        .replace(
+            "$CACHE_INVALIDATE",
+            cached(
+                is_cached,
+                "
+        .map(|result| {
+            cache.delete(&format!($CACHE_KEY)).ok();
+            result
+        })",
+            ),
+        )
+        .replace(
+            "$TENANT_IMPORT",
+            query_imports(is_tenant_scoped, is_soft_deletable, near_column.is_some()),
+        )
+        .replace("$LIST_ITEMS", &list_items)
+        .replace("$READ_QUERY", &read_query)
+        .replace(
+            "$CREATE_ITEM",
+            &with_outbox(uses_outbox, create_item.to_string(), OUTBOX_CREATED),
+        )
+        .replace(
+            "$UPDATE_ITEM",
+            &with_outbox(uses_outbox, update_item, OUTBOX_UPDATED),
+        )
+        .replace(
+            "$DELETE_ITEM",
+            &with_outbox(uses_outbox, delete_item, OUTBOX_DELETED),
+        )
+        .replace("$DELETED_ITEMS", &deleted_items)
+        .replace("$RESTORE_ITEM", restore_item(is_tenant_scoped))
+        .replace(
+            "$CACHE_KEY",
+            if is_tenant_scoped {
+                "\"$TABLE_NAME:{}:{item_id}\", tenant.id"
+            } else {
+                "\"$TABLE_NAME:{item_id}\""
+            },
+        )
+        .replace(
+            "$AUDIT_IMPORT",
+            audited(
+                is_audited,
+                "use create_rust_app::audit::{AuditEvent, Auditor};\n",
+            ),
+        )
+        .replace("$AUDITOR_ARG", audited(is_audited, "\n    auditor: Auditor,"))
+        .replace(
+            "$AUDIT_BEFORE",
+            audited(
+                is_audited,
+                "\n    let before = $MODEL_NAME::read(&db, item_id).ok();",
+            ),
+        )
+        .replace(
+            "$AUDIT_CREATE",
+            audited(
+                is_audited,
+                "
+        .map(|item| {
+            auditor.record(&db, AuditEvent::created(\"$TABLE_NAME\", &item)).ok();
+            item
+        })",
+            ),
+        )
+        .replace(
+            "$AUDIT_UPDATE",
+            audited(
+                is_audited,
+                "
+        .map(|item| {
+            auditor.record(&db, AuditEvent::updated(\"$TABLE_NAME\", item_id, before.as_ref(), &item)).ok();
+            item
+        })",
+            ),
+        )
+        .replace(
+            "$AUDIT_DESTROY",
+            audited(
+                is_audited,
+                "
+        .map(|deleted| {
+            auditor.record(&db, AuditEvent::deleted(\"$TABLE_NAME\", item_id, before.as_ref())).ok();
+            deleted
+        })",
+            ),
+        )
+        .replace("$SEARCH_IMPL", search_impl(is_searchable))
+        .replace(
+            "$SEARCH_INDEX",
+            searchable(
+                is_searchable,
+                "
+        .map(|item| {
+            create_rust_app::search::index(&db, &item);
+            item
+        })",
+            ),
+        )
+        .replace(
+            "$SEARCH_REMOVE",
+            searchable(
+                is_searchable,
+                "
+        .map(|deleted| {
+            create_rust_app::search::remove::<$MODEL_NAME>(&db, &item_id.to_string());
+            deleted
+        })",
+            ),
+        )
+        .replace(
+            "$SEARCH_RESTORE",
+            searchable(
+                is_searchable,
+                "
+        .map(|restored| {
+            if let Ok(item) = $MODEL_NAME::read(&mut db, item_id) {
+                create_rust_app::search::index(&mut db, &item);
+            }
+            restored
+        })",
+            ),
+        )
+        .replace(
+            "$SOFT_DELETE_IMPL",
+            soft_deletable(
+                is_soft_deletable,
+                "\ncreate_rust_app::soft_delete!($MODEL_NAME, $TABLE_NAME);\n",
+            ),
+        )
+        .replace("$OUTBOX_IMPL", outbox_impl(uses_outbox))
+        .replace(
+            "$SOFT_DELETE_ROUTES",
+            soft_deletable(
+                is_soft_deletable,
+                "\n        .at(\"/deleted\", get(deleted))\n        .at(\"/:id/restore\", poem::post(restore))",
+            ),
+        )
+        .replace(
+            "$NEAR_ROUTES",
+            near(near_column, "\n        .at(\"/near\", get(near))"),
+        )
+        .replace(
+            "$NEAR_ITEMS",
+            &near_items(
+                near_column,
+                is_tenant_scoped,
+                is_soft_deletable,
+                "            ",
+            ),
+        )
+        .replace(
+            "$ATTACHMENTS_ROUTES",
+            if has_attachments {
+                "let route = create_rust_app::storage::attachment_api::<$MODEL_NAME>(route);\n    "
+            } else {
+                ""
+            },
+        )
         .replace("$MODEL_NAME", config.model_name.as_str())
+        .replace("$TABLE_NAME", config.file_name.to_plural().as_str())
         .replace("$FILE_NAME", config.file_name.as_str());
 
     Service {
@@ -151,7 +778,18 @@ fn generate_poem(service_name: &str) -> Service {
     }
 }
 
-fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
+fn generate_actix(
+    service_name: &str,
+    include_qsync_attr: bool,
+    has_attachments: bool,
+    is_audited: bool,
+    is_cached: bool,
+    is_searchable: bool,
+    is_tenant_scoped: bool,
+    is_soft_deletable: bool,
+    uses_outbox: bool,
+    geometry_columns: &[GeometryColumn],
+) -> Service {
     let config = config(service_name);
     let contents_template: &str = indoc! {r#"
     use actix_web::{delete, get, post, put};
@@ -166,7 +804,7 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
     use tsync::tsync;
 
     use crate::models::$TABLE_NAME::{$MODEL_NAME, Create$MODEL_NAME, Update$MODEL_NAME};
-    
+    $TENANT_IMPORT$CACHE_IMPORT$AUDIT_IMPORT$ATTACHMENTS_IMPL$SEARCH_IMPL$SOFT_DELETE_IMPL$OUTBOX_IMPL
     #[tsync]
     #[derive(Deserialize)]
     struct List$MODEL_NAMERequest {
@@ -181,7 +819,7 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
     ) -> HttpResponse {
       let mut db = db.pool.get().unwrap();
     
-      let results = $MODEL_NAME::paginate(&mut db, info.page, info.page_size);
+      let results = $LIST_ITEMS;
 
       match results {
         Ok(results) => HttpResponse::Ok().json(results),
@@ -191,12 +829,13 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
     
     $READ_QSYNC_ATTR#[get("/{id}")]
     async fn read(
-      db: Data<Database>,
+      db: Data<Database>,$CACHE_ARG
       item_id: Path<i32>
     ) -> HttpResponse {
         let mut db = db.pool.get().unwrap();
+        let item_id = item_id.into_inner();
 
-        let result = $MODEL_NAME::read(&mut db, item_id.into_inner()).optional();
+        let result = $READ_ITEM;
 
         match result {
             Ok(result) => match result {
@@ -209,12 +848,12 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
     
     $CREATE_QSYNC_ATTR#[post("")]
     async fn create(
-      db: Data<Database>,
+      db: Data<Database>,$AUDITOR_ARG
       item: Json<Create$MODEL_NAME>
     ) -> HttpResponse {
         let mut db = db.pool.get().unwrap();
 
-        let result = $MODEL_NAME::create(&mut db, &item);
+        let result = $CREATE_ITEM;$AUDIT_CREATE$SEARCH_INDEX
     
         match result {
             Ok(result) => HttpResponse::Ok().json(result),
@@ -224,13 +863,14 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
     
     $UPDATE_QSYNC_ATTR#[put("/{id}")]
     async fn update(
-      db: Data<Database>,
+      db: Data<Database>,$AUDITOR_ARG$CACHE_ARG
       item_id: Path<i32>,
       item: Json<Update$MODEL_NAME>
     ) -> HttpResponse {
         let mut db = db.pool.get().unwrap();
+        let item_id = item_id.into_inner();$AUDIT_BEFORE
 
-        let result = $MODEL_NAME::update(&mut db, item_id.into_inner(), &item);
+        let result = $UPDATE_ITEM;$AUDIT_UPDATE$CACHE_INVALIDATE$SEARCH_INDEX
     
         match result {
             Ok(result) => HttpResponse::Ok().json(result),
@@ -239,10 +879,11 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
     }
     
     $DESTROY_QSYNC_ATTR#[delete("/{id}")]
-    async fn destroy(db: Data<Database>, item_id: Path<i32>) -> HttpResponse {
+    async fn destroy(db: Data<Database>,$AUDITOR_INLINE_ARG$CACHE_INLINE_ARG item_id: Path<i32>) -> HttpResponse {
         let mut db = db.pool.get().unwrap();
+        let item_id = item_id.into_inner();$AUDIT_BEFORE
     
-        let result = $MODEL_NAME::delete(&mut db, item_id.into_inner());
+        let result = $DELETE_ITEM;$AUDIT_DESTROY$CACHE_INVALIDATE$SEARCH_REMOVE
     
         match result {
             Ok(result) => match result {
@@ -253,9 +894,9 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
         }
     }
     
-    pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
-      return scope
-        .service(list)
+    $NEAR_HANDLER$SOFT_DELETE_HANDLERSpub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+      $ATTACHMENTS_ENDPOINTSreturn scope
+        .service(list)$SOFT_DELETE_SERVICES$NEAR_SERVICES
         .service(read)
         .service(create)
         .service(update)
@@ -267,9 +908,323 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
     let update_qsync_attr = "#[qsync(return_type=\"$MODEL_NAME\")]\n";
     let create_qsync_attr = "#[qsync(return_type=\"$MODEL_NAME\")]\n";
     let read_qsync_attr = "#[qsync(return_type=\"$MODEL_NAME\")]\n";
-    let list_qsync_attr = "#[qsync(return_type=\"PaginationResult<$MODEL_NAME>\")]\n";
+    let deleted_qsync_attr = "#[qsync(return_type=\"$MODEL_NAME[]\")]\n";
+    let restore_qsync_attr = "#[qsync(return_type=\"number\")]\n";
+    let near_qsync_attr = "#[qsync(return_type=\"$MODEL_NAME[]\")]\n";
+    // tenant-scoped and soft-deleted lists aren't paginated by the model (its `paginate` isn't
+    // scoped)
+    let list_qsync_attr = if is_tenant_scoped || is_soft_deletable {
+        "#[qsync(return_type=\"$MODEL_NAME[]\")]\n"
+    } else {
+        "#[qsync(return_type=\"PaginationResult<$MODEL_NAME>\")]\n"
+    };
+
+    let near_column = first_point_column(geometry_columns);
+    let template = contents_template
+        .replace(
+            "$NEAR_HANDLER",
+            near(
+                near_column,
+                indoc! {r#"
+                #[tsync]
+                #[derive(Deserialize)]
+                struct Near$MODEL_NAMERequest {
+                    longitude: f64,
+                    latitude: f64,
+                    /// the search radius, in meters
+                    meters: f64,
+                    limit: i64,
+                }
+
+                $NEAR_QSYNC_ATTR#[get("/near")]
+                async fn near(
+                  db: Data<Database>,
+                  info: Query<Near$MODEL_NAMERequest>
+                ) -> HttpResponse {
+                  let mut db = db.pool.get().unwrap();
+                  let from = create_rust_app::geo::Geometry::point(info.longitude, info.latitude);
+
+                  let results = $NEAR_ITEMS;
+
+                  match results {
+                    Ok(results) => HttpResponse::Ok().json(results),
+                    Err(_) => HttpResponse::InternalServerError().finish(),
+                  }
+                }
+
+                "#},
+            ),
+        )
+        .replace(
+        "$SOFT_DELETE_HANDLERS",
+        soft_deletable(
+            is_soft_deletable,
+            indoc! {r#"
+            $DELETED_QSYNC_ATTR#[get("/deleted")]
+            async fn deleted(
+              db: Data<Database>,
+              info: Query<List$MODEL_NAMERequest>
+            ) -> HttpResponse {
+              let mut db = db.pool.get().unwrap();
+
+              let results = $DELETED_ITEMS;
+
+              match results {
+                Ok(results) => HttpResponse::Ok().json(results),
+                Err(_) => HttpResponse::InternalServerError().finish(),
+              }
+            }
+
+            $RESTORE_QSYNC_ATTR#[post("/{id}/restore")]
+            async fn restore(db: Data<Database>,$CACHE_INLINE_ARG item_id: Path<i32>) -> HttpResponse {
+                let mut db = db.pool.get().unwrap();
+                let item_id = item_id.into_inner();
+
+                let result = $RESTORE_ITEM;$CACHE_INVALIDATE$SEARCH_RESTORE
+
+                match result {
+                    Ok(result) => match result {
+                        0 => HttpResponse::NotFound().finish(),
+                        usize => HttpResponse::Ok().json(usize)
+                    },
+                    Err(_) => HttpResponse::InternalServerError().finish()
+                }
+            }
+
+            "#},
+        ),
+    );
 
-    let contents = String::from(contents_template)
+    let list_items = if is_tenant_scoped || is_soft_deletable {
+        format!(
+            "{}
+        .order($TABLE_NAME::id)
+        .limit(info.page_size)
+        .offset(info.page * info.page_size)
+        .load::<$MODEL_NAME>(&mut db)",
+            scoped_rows(is_tenant_scoped, is_soft_deletable, "        ")
+        )
+    } else {
+        "$MODEL_NAME::paginate(&mut db, info.page, info.page_size)".to_string()
+    };
+    let read_query = if is_tenant_scoped || is_soft_deletable {
+        format!(
+            "{}
+            .filter($TABLE_NAME::id.eq(item_id))
+            .first::<$MODEL_NAME>(&mut db)
+            .optional()",
+            scoped_rows(is_tenant_scoped, is_soft_deletable, "            ")
+        )
+    } else {
+        "$MODEL_NAME::read(&mut db, item_id).optional()".to_string()
+    };
+    let update_item = if is_tenant_scoped || is_soft_deletable {
+        format!(
+            "diesel::update(
+            {},
+        )
+        .set(&{})
+        .get_result::<$MODEL_NAME>(&mut db)",
+            item_target(is_tenant_scoped, is_soft_deletable),
+            if is_tenant_scoped {
+                "Update$MODEL_NAME { tenant_id: Some(tenant.id), ..item.into_inner() }"
+            } else {
+                "item.into_inner()"
+            }
+        )
+    } else {
+        "$MODEL_NAME::update(&mut db, item_id, &item)".to_string()
+    };
+    let create_item = if is_tenant_scoped {
+        "$MODEL_NAME::create(&mut db, &Create$MODEL_NAME { tenant_id: tenant.id, ..item.into_inner() })"
+    } else {
+        "$MODEL_NAME::create(&mut db, &item)"
+    };
+    let delete_item = if is_soft_deletable {
+        soft_delete_item(is_tenant_scoped)
+    } else if is_tenant_scoped {
+        "diesel::delete(
+            $TABLE_NAME::table
+                .filter($TABLE_NAME::id.eq(item_id))
+                .filter($TABLE_NAME::tenant_id.eq(tenant.id)),
+        )
+        .execute(&mut db)"
+            .to_string()
+    } else {
+        "$MODEL_NAME::delete(&mut db, item_id)".to_string()
+    };
+    let deleted_items = format!(
+        "{}
+        .order($TABLE_NAME::deleted_at.desc())
+        .limit(info.page_size)
+        .offset(info.page * info.page_size)
+        .load::<$MODEL_NAME>(&mut db)",
+        deleted_rows(is_tenant_scoped, "        ")
+    );
+
+    let contents = scope_to_tenant(template, is_tenant_scoped)
+        .replace("$ATTACHMENTS_IMPL", attachments_impl(has_attachments))
+        .replace(
+            "$CACHE_IMPORT",
+            cached(is_cached, "use create_rust_app::Cache;\n"),
+        )
+        .replace("$CACHE_ARG", cached(is_cached, "\n  cache: Data<Cache>,"))
+        .replace("$CACHE_INLINE_ARG", cached(is_cached, " cache: Data<Cache>,"))
+        .replace(
+            "$READ_ITEM",
+            if is_cached {
+                "cache.compute_if_absent(&format!($CACHE_KEY), || {
+            $READ_QUERY
+        })"
+            } else {
+                "$READ_QUERY"
+            },
+        )
+        .replace(
+            "$CACHE_INVALIDATE",
+            cached(
+                is_cached,
+                "
+    if result.is_ok() {
+        cache.delete(&format!($CACHE_KEY)).ok();
+    }",
+            ),
+        )
+        .replace(
+            "$TENANT_IMPORT",
+            query_imports(is_tenant_scoped, is_soft_deletable, near_column.is_some()),
+        )
+        .replace("$LIST_ITEMS", &list_items)
+        .replace("$READ_QUERY", &read_query)
+        .replace(
+            "$CREATE_ITEM",
+            &with_outbox(uses_outbox, create_item.to_string(), OUTBOX_CREATED),
+        )
+        .replace(
+            "$UPDATE_ITEM",
+            &with_outbox(uses_outbox, update_item, OUTBOX_UPDATED),
+        )
+        .replace(
+            "$DELETE_ITEM",
+            &with_outbox(uses_outbox, delete_item, OUTBOX_DELETED),
+        )
+        .replace("$DELETED_ITEMS", &deleted_items)
+        .replace("$RESTORE_ITEM", restore_item(is_tenant_scoped))
+        .replace(
+            "$CACHE_KEY",
+            if is_tenant_scoped {
+                "\"$TABLE_NAME:{}:{item_id}\", tenant.id"
+            } else {
+                "\"$TABLE_NAME:{item_id}\""
+            },
+        )
+        .replace(
+            "$AUDIT_IMPORT",
+            audited(
+                is_audited,
+                "use create_rust_app::audit::{AuditEvent, Auditor};\n",
+            ),
+        )
+        .replace("$AUDITOR_ARG", audited(is_audited, "\n  auditor: Auditor,"))
+        .replace("$AUDITOR_INLINE_ARG", audited(is_audited, " auditor: Auditor,"))
+        .replace(
+            "$AUDIT_BEFORE",
+            audited(
+                is_audited,
+                "\n    let before = $MODEL_NAME::read(&mut db, item_id).ok();",
+            ),
+        )
+        .replace(
+            "$AUDIT_CREATE",
+            audited(
+                is_audited,
+                "
+    if let Ok(item) = &result {
+        auditor.record(&mut db, AuditEvent::created(\"$TABLE_NAME\", item)).ok();
+    }",
+            ),
+        )
+        .replace(
+            "$AUDIT_UPDATE",
+            audited(
+                is_audited,
+                "
+    if let Ok(item) = &result {
+        auditor.record(&mut db, AuditEvent::updated(\"$TABLE_NAME\", item_id, before.as_ref(), item)).ok();
+    }",
+            ),
+        )
+        .replace(
+            "$AUDIT_DESTROY",
+            audited(
+                is_audited,
+                "
+    if matches!(result, Ok(deleted) if deleted > 0) {
+        auditor.record(&mut db, AuditEvent::deleted(\"$TABLE_NAME\", item_id, before.as_ref())).ok();
+    }",
+            ),
+        )
+        .replace("$SEARCH_IMPL", search_impl(is_searchable))
+        .replace(
+            "$SEARCH_INDEX",
+            searchable(
+                is_searchable,
+                "
+    if let Ok(item) = &result {
+        create_rust_app::search::index(&mut db, item);
+    }",
+            ),
+        )
+        .replace(
+            "$SEARCH_REMOVE",
+            searchable(
+                is_searchable,
+                "
+    if matches!(result, Ok(deleted) if deleted > 0) {
+        create_rust_app::search::remove::<$MODEL_NAME>(&mut db, &item_id.to_string());
+    }",
+            ),
+        )
+        .replace(
+            "$SEARCH_RESTORE",
+            searchable(
+                is_searchable,
+                "
+    if matches!(result, Ok(restored) if restored > 0) {
+        if let Ok(item) = $MODEL_NAME::read(&mut db, item_id) {
+            create_rust_app::search::index(&mut db, &item);
+        }
+    }",
+            ),
+        )
+        .replace(
+            "$SOFT_DELETE_IMPL",
+            soft_deletable(
+                is_soft_deletable,
+                "\ncreate_rust_app::soft_delete!($MODEL_NAME, $TABLE_NAME);\n",
+            ),
+        )
+        .replace("$OUTBOX_IMPL", outbox_impl(uses_outbox))
+        .replace(
+            "$SOFT_DELETE_SERVICES",
+            soft_deletable(
+                is_soft_deletable,
+                "\n    .service(deleted)\n    .service(restore)",
+            ),
+        )
+        .replace("$NEAR_SERVICES", near(near_column, "\n    .service(near)"))
+        .replace(
+            "$NEAR_ITEMS",
+            &near_items(near_column, is_tenant_scoped, is_soft_deletable, "      "),
+        )
+        .replace(
+            "$ATTACHMENTS_ENDPOINTS",
+            if has_attachments {
+                "let scope = create_rust_app::storage::attachment_endpoints::<$MODEL_NAME>(scope);\n  "
+            } else {
+                ""
+            },
+        )
         .replace(
             "$DESTROY_QSYNC_ATTR",
             if include_qsync_attr {
@@ -302,6 +1257,22 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
                 ""
             },
         )
+        .replace(
+            "$DELETED_QSYNC_ATTR",
+            if include_qsync_attr {
+                deleted_qsync_attr
+            } else {
+                ""
+            },
+        )
+        .replace(
+            "$RESTORE_QSYNC_ATTR",
+            if include_qsync_attr {
+                restore_qsync_attr
+            } else {
+                ""
+            },
+        )
         .replace(
             "$LIST_QSYNC_ATTR",
             if include_qsync_attr {
@@ -310,8 +1281,17 @@ fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
                 ""
             },
         )
+        .replace(
+            "$NEAR_QSYNC_ATTR",
+            if include_qsync_attr {
+                near_qsync_attr
+            } else {
+                ""
+            },
+        )
         .replace("$MODEL_NAME", config.model_name.as_str())
-        .replace("$TABLE_NAME", config.file_name.to_plural().as_str());
+        .replace("$TABLE_NAME", config.file_name.to_plural().as_str())
+        .replace("$FILE_NAME", config.file_name.as_str());
 
     Service {
         config,
@@ -0,0 +1,286 @@
+use crate::fs;
+use crate::logger;
+use crate::BackendFramework;
+use anyhow::{anyhow, Result};
+use indoc::indoc;
+use inflector::Inflector;
+use std::path::PathBuf;
+
+/// shared by the generated hooks, written with the first one
+const EVENT_STREAM_HOOK_FILE: &str = "frontend/src/hooks/useEventStream.ts";
+
+/// whether the project has the auth plugin's `useAuth` hook (the endpoints then require auth)
+fn has_auth() -> bool {
+    PathBuf::from("frontend/src/hooks/useAuth.tsx").is_file()
+}
+
+/// scaffolds a Server-Sent Events endpoint streaming `$MODEL_NAMEEvent`s at
+/// `/api/$FILE_NAME/events` (see `create_rust_app::sse`), and a React hook receiving them
+pub fn create(backend: BackendFramework, name: &str) -> Result<()> {
+    let model_name = name.to_pascal_case();
+    let file_name = model_name.to_snake_case();
+    let service_name = format!("{file_name}_events");
+    let hook_file = format!("frontend/src/hooks/use{model_name}Events.ts");
+    let is_authenticated = has_auth();
+
+    if PathBuf::from(format!("backend/services/{service_name}.rs")).exists()
+        || PathBuf::from(&hook_file).exists()
+    {
+        return Err(anyhow!("The '{model_name}' events already exist."));
+    }
+
+    logger::message(&format!("Creating the '{model_name}' events"));
+
+    let contents = match backend {
+        BackendFramework::ActixWeb => indoc! {r#"
+        use std::time::Duration;
+
+        use actix_web::get;$AUTH_IMPORT
+        use create_rust_app::sse::{Event, LastEventId, Sse};
+        use futures_util::stream::{self, Stream};
+        use serde::Serialize;
+
+        $EVENT_STRUCT
+
+        /// streams the $MODEL_NAME events, clients that reconnect resume after the last one they got
+        #[get("")]
+        async fn events($AUTH_ARGlast_event_id: LastEventId) -> Sse<impl Stream<Item = Event<$MODEL_NAMEEvent>>> {
+            $EVENTS_BODY
+        }
+
+        pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
+            scope.service(events)
+        }
+        "#},
+        BackendFramework::Poem => indoc! {r#"
+        use std::time::Duration;
+
+        use poem::{get, handler, Route};$AUTH_IMPORT
+        use create_rust_app::sse::{Event, LastEventId, Sse};
+        use futures_util::stream::{self, Stream};
+        use serde::Serialize;
+
+        $EVENT_STRUCT
+
+        /// streams the $MODEL_NAME events, clients that reconnect resume after the last one they got
+        #[handler]
+        async fn events($AUTH_ARGlast_event_id: LastEventId) -> Sse<impl Stream<Item = Event<$MODEL_NAMEEvent>> + Send> {
+            $EVENTS_BODY
+        }
+
+        pub fn api() -> Route {
+            Route::new().at("/", get(events))
+        }
+        "#},
+    };
+
+    let event_struct = indoc! {r#"
+    #[tsync::tsync]
+    #[derive(Debug, Clone, Serialize)]
+    /// an event of the `/api/$FILE_NAME/events` stream, received by `use$MODEL_NAMEEvents` in the frontend
+    pub struct $MODEL_NAMEEvent {
+        /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+        Add the event's fields here
+        -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+        pub sequence: u64,
+    }"#};
+
+    let events_body = indoc! {r#"
+    let start = last_event_id.parse::<u64>().map_or(0, |id| id + 1);
+
+        // an event every second: replace it with your events' source, ex: a `tokio::sync::broadcast`
+        // channel your services send to
+        Sse::new(stream::unfold(start, |sequence| async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            Some((Event::new($MODEL_NAMEEvent { sequence }).id(sequence), sequence + 1))
+        }))"#};
+
+    let contents = contents
+        .replace("$EVENT_STRUCT", event_struct)
+        .replace("$EVENTS_BODY", events_body)
+        .replace(
+            "$AUTH_IMPORT",
+            if is_authenticated {
+                "\nuse create_rust_app::auth::Auth;"
+            } else {
+                ""
+            },
+        )
+        .replace(
+            "$AUTH_ARG",
+            if is_authenticated {
+                "_auth: Auth, "
+            } else {
+                ""
+            },
+        )
+        .replace("$MODEL_NAME", &model_name)
+        .replace("$FILE_NAME", &file_name);
+
+    fs::add_rust_file("backend/services", &service_name, &contents)?;
+
+    match backend {
+        BackendFramework::ActixWeb => crate::content::service::register_actix(
+            &service_name,
+            &format!("services::{service_name}::endpoints(web::scope(\"/{file_name}/events\"))"),
+        )?,
+        BackendFramework::Poem => crate::content::service::register_poem(
+            &service_name,
+            &format!("services::{service_name}::api()"),
+            &format!("/{file_name}/events"),
+        )?,
+    };
+
+    if !PathBuf::from(EVENT_STREAM_HOOK_FILE).exists() {
+        logger::add_file_msg(EVENT_STREAM_HOOK_FILE);
+        std::fs::write(EVENT_STREAM_HOOK_FILE, EVENT_STREAM_HOOK)?;
+    }
+
+    let hook = if is_authenticated {
+        indoc! {r#"
+        import { useAuth } from './useAuth'
+        import { useEventStream } from './useEventStream'
+
+        /**
+         * calls `onEvent` with the events of `/api/$FILE_NAME/events` (see `backend/services/$FILE_NAME_events.rs`)
+         * while the user is logged in, their type is generated by tsync
+         */
+        export const use$MODEL_NAMEEvents = (onEvent: (event: $MODEL_NAMEEvent) => void) => {
+          const auth = useAuth()
+
+          useEventStream<$MODEL_NAMEEvent>('/api/$FILE_NAME/events', onEvent, {
+            accessToken: auth.accessToken,
+            enabled: auth.isAuthenticated,
+          })
+        }
+        "#}
+    } else {
+        indoc! {r#"
+        import { useEventStream } from './useEventStream'
+
+        /**
+         * calls `onEvent` with the events of `/api/$FILE_NAME/events` (see `backend/services/$FILE_NAME_events.rs`),
+         * their type is generated by tsync
+         */
+        export const use$MODEL_NAMEEvents = (onEvent: (event: $MODEL_NAMEEvent) => void) => {
+          useEventStream<$MODEL_NAMEEvent>('/api/$FILE_NAME/events', onEvent)
+        }
+        "#}
+    };
+
+    logger::add_file_msg(&hook_file);
+    std::fs::write(
+        &hook_file,
+        hook.replace("$MODEL_NAME", &model_name)
+            .replace("$FILE_NAME", &file_name),
+    )?;
+
+    Ok(())
+}
+
+const EVENT_STREAM_HOOK: &str = indoc! {r#"
+import { useEffect, useRef } from 'react'
+
+interface EventStreamOptions {
+  /** sent as a bearer token (the stream is read with `fetch` rather than `EventSource`, which can't send it) */
+  accessToken?: string
+  /** nothing is received while it's `false` (default: `true`) */
+  enabled?: boolean
+}
+
+/** how long to wait before reconnecting, unless the stream says otherwise (its events' `retry`) */
+const DEFAULT_RETRY = 3000
+
+/**
+ * calls `onEvent` with the data (parsed JSON) and type of the events streamed by `url`, see `create_rust_app::sse`
+ *
+ * reconnects when the connection is lost, resuming after the last event received (`Last-Event-ID`)
+ */
+export const useEventStream = <T>(
+  url: string,
+  onEvent: (data: T, event: string) => void,
+  options: EventStreamOptions = {}
+) => {
+  const { accessToken, enabled = true } = options
+  const onEventRef = useRef(onEvent)
+  onEventRef.current = onEvent
+  const lastEventId = useRef<string>()
+
+  useEffect(() => {
+    if (!enabled) return
+
+    const controller = new AbortController()
+    let retry = DEFAULT_RETRY
+    let timer: number | undefined
+
+    const connect = async () => {
+      try {
+        const headers: Record<string, string> = { Accept: 'text/event-stream' }
+        if (accessToken) headers['Authorization'] = `Bearer ${accessToken}`
+        if (lastEventId.current) headers['Last-Event-ID'] = lastEventId.current
+
+        const response = await fetch(url, { headers, signal: controller.signal })
+        if (!response.ok || !response.body) throw new Error(`${url} answered ${response.status}`)
+
+        const reader = response.body.pipeThrough(new TextDecoderStream()).getReader()
+        let buffer = ''
+        let event = 'message'
+        let data: string[] = []
+
+        while (true) {
+          const { value, done } = await reader.read()
+          if (done) break
+
+          buffer += value
+          const lines = buffer.split('\n')
+          buffer = lines.pop() ?? ''
+
+          for (const rawLine of lines) {
+            const line = rawLine.endsWith('\r') ? rawLine.slice(0, -1) : rawLine
+
+            // a blank line ends the event
+            if (line === '') {
+              if (data.length > 0) onEventRef.current(JSON.parse(data.join('\n')), event)
+              event = 'message'
+              data = []
+              continue
+            }
+            // comments keep the connection alive
+            if (line.startsWith(':')) continue
+
+            const colon = line.indexOf(':')
+            const field = colon === -1 ? line : line.slice(0, colon)
+            const value = colon === -1 ? '' : line.slice(colon + 1).replace(/^ /, '')
+            switch (field) {
+              case 'event':
+                event = value
+                break
+              case 'data':
+                data.push(value)
+                break
+              case 'id':
+                lastEventId.current = value
+                break
+              case 'retry':
+                if (/^\d+$/.test(value)) retry = parseInt(value)
+                break
+            }
+          }
+        }
+      } catch (err) {
+        if (controller.signal.aborted) return
+        console.error(err)
+      }
+
+      if (!controller.signal.aborted) timer = window.setTimeout(connect, retry)
+    }
+    connect()
+
+    return () => {
+      controller.abort()
+      window.clearTimeout(timer)
+    }
+  }, [url, accessToken, enabled])
+}
+"#};
@@ -0,0 +1,6 @@
+//! The `create-rust-app` binary is what you'll want most of the time, this library exposes the
+//! parts of it that other tools (build scripts, watch modes, IDE integrations) can reuse.
+
+/// react-query hooks generation, see [`qsync::Generator`]
+pub use qsync;
+pub use qsync::{Generated, Generator};
@@ -15,7 +15,7 @@ use crate::project::CreationOptions;
 use content::project;
 use dialoguer::{console::Term, theme::ColorfulTheme, Confirm, Input, MultiSelect, Select};
 
-use utils::{fs, logger};
+use utils::{fs, logger, telemetry};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
 pub enum BackendFramework {
@@ -29,6 +29,26 @@ pub enum BackendDatabase {
     Sqlite,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CiProvider {
+    /// GitHub Actions (.github/workflows/ci.yml)
+    Github,
+    /// GitLab CI (.gitlab-ci.yml)
+    Gitlab,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DeployTarget {
+    /// Kubernetes manifests (deploy/k8s)
+    K8s,
+    /// Fly.io (fly.toml)
+    Fly,
+    /// Render Blueprint (render.yaml)
+    Render,
+    /// Railway (railway.toml)
+    Railway,
+}
+
 /// Struct to describe the CLI
 #[derive(Parser)]
 #[command(
@@ -48,6 +68,13 @@ struct Cli {
         value_hint = ValueHint::DirPath,
     )]
     name: Option<String>,
+
+    #[arg(
+        long = "no-telemetry",
+        global = true,
+        help = "Don't record or send anonymous usage statistics for this run (see `create-rust-app stats`)"
+    )]
+    no_telemetry: bool,
 }
 
 /// enum for the various available subcommands
@@ -107,10 +134,57 @@ enum Commands {
                 PossibleValue::new("storage").help("Storage Plugin: adds S3 file storage capabilities"),
                 PossibleValue::new("graphql").help("GraphQL Plugin: bootstraps a GraphQL setup including a playground"),
                 PossibleValue::new("utoipa").help("Utoipa Plugin: Autogenerated OpenAPI documentation served in a SwaggerUI playground"),
+                PossibleValue::new("metering").help("Metering Plugin: records usage events for usage-based billing"),
+                PossibleValue::new("consent").help("Consent Plugin: versioned terms/privacy documents and consent tracking"),
+                PossibleValue::new("notifications").help("Notifications Plugin: in-app notifications with email digests"),
+                PossibleValue::new("referrals").help("Referrals Plugin: invite codes with signup attribution and rewards"),
+                PossibleValue::new("mail_queue").help("Mail Queue Plugin: emails are queued and delivered in the background, with retries"),
+                PossibleValue::new("mail_log").help("Mail Log Plugin: records every email sent, browsable in the admin portal"),
+                PossibleValue::new("audit").help("Audit Plugin: records who did what, browsable in the admin portal"),
+                PossibleValue::new("scheduler").help("Scheduler Plugin: runs tasks on cron schedules, with their history in the admin portal"),
+                PossibleValue::new("realtime").help("Realtime Plugin: authenticated WebSocket channels the backend broadcasts to"),
+                PossibleValue::new("cache").help("Cache Plugin: a typed cache for read-heavy endpoints, in Redis or in memory"),
+                PossibleValue::new("rate_limit").help("Rate Limit Plugin: per-IP, per-user and per-route rate limiting middleware, applied to the auth endpoints"),
+                PossibleValue::new("i18n").help("I18n Plugin: backend message catalogs and a locale-negotiating extractor, shared with the frontend"),
+                PossibleValue::new("payments").help("Payments Plugin: Stripe subscriptions, with their webhooks and a billing page"),
+                PossibleValue::new("search").help("Search Plugin: full-text search of your models, in Postgres or Meilisearch"),
+                PossibleValue::new("flags").help("Feature Flags Plugin: per-environment defaults, percentage rollouts and user targeting, managed in the admin portal"),
+                PossibleValue::new("tenancy").help("Tenancy Plugin: tenant-scoped resources for each organization, or a schema or database per tenant"),
+                PossibleValue::new("webhooks").help("Webhooks Plugin: signed, retried deliveries of your app's events to the URLs your users register"),
+                PossibleValue::new("observability").help("Observability Plugin: tracing, exported over OTLP, with the trace context shared with the frontend"),
+                PossibleValue::new("prometheus").help("Prometheus Plugin: a /metrics endpoint, and Grafana dashboards with the Container plugin"),
+                PossibleValue::new("sentry").help("Sentry Plugin: panics and server errors reported to Sentry, from the backend and the frontend"),
+                PossibleValue::new("grpc").help("gRPC Plugin: tonic services alongside the HTTP server, sharing its database and auth, generated for new resources"),
+                PossibleValue::new("rpc").help("RPC Plugin: Rust procedures called from a generated, typed TypeScript client, with validated inputs and streaming subscriptions"),
+                PossibleValue::new("pwa").help("PWA Plugin: an installable frontend working offline, its service worker following the backend's cache headers"),
+                PossibleValue::new("desktop").help("Desktop Plugin: a Tauri desktop app around the frontend, embedding the backend or connecting to it"),
+                PossibleValue::new("soft_delete").help("Soft Delete Plugin: deleted_at columns, restorable resources and accounts, left out of the default queries"),
+                PossibleValue::new("events").help("Events Plugin: an in-process typed event bus, its subscribers run inline or queued as tasks"),
+                PossibleValue::new("outbox").help("Outbox Plugin: events, webhooks and emails recorded in the changes' transactions, relayed once they're committed"),
+                PossibleValue::new("broker").help("Broker Plugin: publishes to and consumes from NATS, RabbitMQ or Kafka, with retries, dead letter topics and a consumers binary"),
+                PossibleValue::new("geo").help("Geo Plugin: PostGIS geometry columns serialized as GeoJSON, distance queries and `location:point` resource columns"),
+                PossibleValue::new("admin").help("Admin Plugin: serves the admin portal in production, restricted to the admin role"),
             ],
             ignore_case=true,
         )]
         plugins: Option<Vec<String>>,
+
+        #[arg(
+            long = "graphql-federation",
+            name = "graphql federation",
+            help = "Scaffold the GraphQL plugin as an Apollo Federation subgraph (requires the GraphQL plugin)"
+        )]
+        graphql_federation: bool,
+
+        #[arg(
+            long = "ci",
+            name = "ci",
+            help = "CI pipeline to generate: building, migrating and testing the project, and building its Docker image",
+            value_name = "CI",
+            value_parser = EnumValueParser::<CiProvider>::new(),
+            ignore_case = true
+        )]
+        ci: Option<CiProvider>,
     },
     // named Configure instead of Update because people would naturally assume that Update updates the version of the CLI
     /// Configure an existing rust project
@@ -167,6 +241,45 @@ enum Commands {
             conflicts_with = "query-sync"
         )]
         add_new_service: bool,
+
+        #[arg(
+            long = "graphql-codegen",
+            name = "graphql codegen",
+            help = "Generate the frontend's GraphQL types from the running backend's schema. (requires the GraphQL plugin)",
+            conflicts_with_all = ["query-sync", "add new service"]
+        )]
+        graphql_codegen: bool,
+
+        #[arg(
+            long = "new-sse",
+            name = "add sse endpoint",
+            help = "Add a Server-Sent Events endpoint, and a React hook receiving its events. (beta)",
+            conflicts_with_all = ["query-sync", "add new service", "graphql codegen"]
+        )]
+        add_sse_endpoint: bool,
+    },
+    /// Generate deployment configurations for the project in the current directory
+    Generate {
+        #[arg(
+            name = "target",
+            help = "What to deploy to",
+            value_name = "TARGET",
+            value_parser = EnumValueParser::<DeployTarget>::new(),
+            ignore_case = true
+        )]
+        target: DeployTarget,
+    },
+    /// Show the anonymous usage statistics recorded on this machine, or opt in/out of sharing them
+    Stats {
+        #[arg(
+            long = "enable",
+            help = "Share anonymous usage statistics",
+            conflicts_with = "disable"
+        )]
+        enable: bool,
+
+        #[arg(long = "disable", help = "Stop sharing anonymous usage statistics")]
+        disable: bool,
     },
 }
 
@@ -175,11 +288,12 @@ enum Commands {
 /// A MODERN WAY TO BOOTSTRAP A RUST+REACT APP IN A SINGLE COMMAND
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let no_telemetry = cli.no_telemetry;
 
     project::check_cli_version()?;
 
     // did user provide sub commands?
-    match cli.command {
+    let (result, run) = match cli.command {
         Some(command) => {
             // determine command based on the subcommand used
             match command {
@@ -189,32 +303,83 @@ fn main() -> Result<()> {
                     database,
                     backendframework,
                     plugins,
-                } => create_project(cli_mode, name, database, backendframework, plugins)?,
+                    graphql_federation,
+                    ci,
+                } => {
+                    let mut run = telemetry::Run::new("create");
+                    if !cli_mode {
+                        telemetry::ask_once(no_telemetry)?;
+                    }
+                    let result = create_project(
+                        cli_mode,
+                        name,
+                        database,
+                        backendframework,
+                        plugins,
+                        graphql_federation,
+                        ci,
+                        &mut run,
+                    );
+                    (result, run)
+                }
                 Commands::Configure {
                     query_sync,
                     qsync_input_files,
                     qsync_output_file,
                     qsync_debug,
                     add_new_service,
-                } => configure_project(
-                    query_sync,
-                    qsync_input_files,
-                    qsync_output_file,
-                    qsync_debug,
-                    add_new_service,
-                )?,
-            };
+                    graphql_codegen,
+                    add_sse_endpoint,
+                } => {
+                    let mut run = telemetry::Run::new("configure");
+                    let result = configure_project(
+                        query_sync,
+                        qsync_input_files,
+                        qsync_output_file,
+                        qsync_debug,
+                        add_new_service,
+                        graphql_codegen,
+                        add_sse_endpoint,
+                        &mut run,
+                    );
+                    (result, run)
+                }
+                Commands::Generate { target } => {
+                    let mut run = telemetry::Run::new("generate");
+                    let result = generate_deployment(target, &mut run);
+                    (result, run)
+                }
+                Commands::Stats { enable, disable } => {
+                    if enable || disable {
+                        telemetry::set_enabled(enable)?;
+                    }
+                    return telemetry::print_stats();
+                }
+            }
         }
         None => {
             // base command on presence of Name arg
             match cli.name {
-                Some(name) => create_project(false, name, None, None, None)?,
-                None => configure_project(false, None, None, false, false)?,
-            };
+                Some(name) => {
+                    let mut run = telemetry::Run::new("create");
+                    telemetry::ask_once(no_telemetry)?;
+                    let result =
+                        create_project(false, name, None, None, None, false, None, &mut run);
+                    (result, run)
+                }
+                None => {
+                    let mut run = telemetry::Run::new("configure");
+                    let result =
+                        configure_project(false, None, None, false, false, false, false, &mut run);
+                    (result, run)
+                }
+            }
         }
-    }
+    };
 
-    Ok(())
+    run.finish(result.is_ok(), no_telemetry);
+
+    result
 }
 
 fn create_project(
@@ -223,6 +388,9 @@ fn create_project(
     database: Option<BackendDatabase>,
     framework: Option<BackendFramework>,
     plugins: Option<Vec<String>>,
+    graphql_federation: bool,
+    ci: Option<CiProvider>,
+    run: &mut telemetry::Run,
 ) -> anyhow::Result<()> {
     // if we try making a project in an existing directory, throw an error
     if PathBuf::from(&project_name).exists() {
@@ -289,6 +457,36 @@ fn create_project(
                 "storage" => "plugin_storage".to_string(),
                 "graphql" => "plugin_graphql".to_string(),
                 "utoipa" => "plugin_utoipa".to_string(),
+                "metering" => "plugin_metering".to_string(),
+                "consent" => "plugin_consent".to_string(),
+                "notifications" => "plugin_notifications".to_string(),
+                "referrals" => "plugin_referrals".to_string(),
+                "mail_queue" => "plugin_mail_queue".to_string(),
+                "mail_log" => "plugin_mail_log".to_string(),
+                "audit" => "plugin_audit".to_string(),
+                "scheduler" => "plugin_scheduler".to_string(),
+                "realtime" => "plugin_realtime".to_string(),
+                "cache" => "plugin_cache".to_string(),
+                "rate_limit" => "plugin_rate_limit".to_string(),
+                "i18n" => "plugin_i18n".to_string(),
+                "payments" => "plugin_payments".to_string(),
+                "search" => "plugin_search".to_string(),
+                "flags" => "plugin_flags".to_string(),
+                "tenancy" => "plugin_tenancy".to_string(),
+                "webhooks" => "plugin_webhooks".to_string(),
+                "observability" => "plugin_observability".to_string(),
+                "prometheus" => "plugin_prometheus".to_string(),
+                "sentry" => "plugin_sentry".to_string(),
+                "grpc" => "plugin_grpc".to_string(),
+                "rpc" => "plugin_rpc".to_string(),
+                "pwa" => "plugin_pwa".to_string(),
+                "desktop" => "plugin_desktop".to_string(),
+                "soft_delete" => "plugin_soft_delete".to_string(),
+                "events" => "plugin_events".to_string(),
+                "outbox" => "plugin_outbox".to_string(),
+                "broker" => "plugin_broker".to_string(),
+                "geo" => "plugin_geo".to_string(),
+                "admin" => "plugin_admin".to_string(),
                 _ => panic!("Fatal: Unknown plugin specified"),
             })
             .collect(),
@@ -306,6 +504,36 @@ fn create_project(
                     "Tasks plugin: adds a task queue for background jobs", // 3
                     "GraphQL Plugin: bootstraps a GraphQL setup including a playground", // 4
                     "Utoipa Plugin: Autogenerated OpenAPI documentation served in a SwaggerUI playground", // 5
+                    "Metering Plugin: records usage events for usage-based billing", // 6
+                    "Consent Plugin: versioned terms/privacy documents and consent tracking", // 7
+                    "Notifications Plugin: in-app notifications with email digests", // 8
+                    "Referrals Plugin: invite codes with signup attribution and rewards", // 9
+                    "Admin Plugin: serves the admin portal in production, restricted to the admin role", // 10
+                    "Mail Queue Plugin: emails are queued and delivered in the background, with retries", // 11
+                    "Mail Log Plugin: records every email sent, browsable in the admin portal", // 12
+                    "Audit Plugin: records who did what, browsable in the admin portal", // 13
+                    "Scheduler Plugin: runs tasks on cron schedules, with their history in the admin portal", // 14
+                    "Realtime Plugin: authenticated WebSocket channels the backend broadcasts to", // 15
+                    "Cache Plugin: a typed cache for read-heavy endpoints, in Redis or in memory", // 16
+                    "Rate Limit Plugin: per-IP, per-user and per-route rate limiting middleware, applied to the auth endpoints", // 17
+                    "I18n Plugin: backend message catalogs and a locale-negotiating extractor, shared with the frontend", // 18
+                    "Payments Plugin: Stripe subscriptions, with their webhooks and a billing page", // 19
+                    "Search Plugin: full-text search of your models, in Postgres or Meilisearch", // 20
+                    "Feature Flags Plugin: per-environment defaults, percentage rollouts and user targeting", // 21
+                    "Tenancy Plugin: tenant-scoped resources for each organization, or a schema or database per tenant", // 22
+                    "Webhooks Plugin: signed, retried deliveries of your app's events to the URLs your users register", // 23
+                    "Observability Plugin: tracing, exported over OTLP, with the trace context shared with the frontend", // 24
+                    "Prometheus Plugin: a /metrics endpoint, and Grafana dashboards with the Container plugin", // 25
+                    "Sentry Plugin: panics and server errors reported to Sentry, from the backend and the frontend", // 26
+                    "gRPC Plugin: tonic services alongside the HTTP server, sharing its database and auth, generated for new resources", // 27
+                    "RPC Plugin: Rust procedures called from a generated, typed TypeScript client, with validated inputs and streaming subscriptions", // 28
+                    "PWA Plugin: an installable frontend working offline, its service worker following the backend's cache headers", // 29
+                    "Desktop Plugin: a Tauri desktop app around the frontend, embedding the backend or connecting to it", // 30
+                    "Soft Delete Plugin: deleted_at columns, restorable resources and accounts, left out of the default queries", // 31
+                    "Events Plugin: an in-process typed event bus, its subscribers run inline or queued as tasks", // 32
+                    "Outbox Plugin: events, webhooks and emails recorded in the changes' transactions, relayed once they're committed", // 33
+                    "Broker Plugin: publishes to and consumes from NATS, RabbitMQ or Kafka, with retries, dead letter topics and a consumers binary", // 34
+                    "Geo Plugin: PostGIS geometry columns serialized as GeoJSON, distance queries and `location:point` resource columns", // 35
                 ];
                 let chosen: Vec<usize> = MultiSelect::with_theme(&ColorfulTheme::default())
                     .items(&items)
@@ -318,6 +546,36 @@ fn create_project(
                 let add_plugin_tasks = chosen.iter().any(|x| *x == 3);
                 let add_plugin_graphql = chosen.iter().any(|x| *x == 4);
                 let add_plugin_utoipa = chosen.iter().any(|x| *x == 5);
+                let add_plugin_metering = chosen.iter().any(|x| *x == 6);
+                let add_plugin_consent = chosen.iter().any(|x| *x == 7);
+                let add_plugin_notifications = chosen.iter().any(|x| *x == 8);
+                let add_plugin_referrals = chosen.iter().any(|x| *x == 9);
+                let add_plugin_admin = chosen.iter().any(|x| *x == 10);
+                let add_plugin_mail_queue = chosen.iter().any(|x| *x == 11);
+                let add_plugin_mail_log = chosen.iter().any(|x| *x == 12);
+                let add_plugin_audit = chosen.iter().any(|x| *x == 13);
+                let add_plugin_scheduler = chosen.iter().any(|x| *x == 14);
+                let add_plugin_realtime = chosen.iter().any(|x| *x == 15);
+                let add_plugin_cache = chosen.iter().any(|x| *x == 16);
+                let add_plugin_rate_limit = chosen.iter().any(|x| *x == 17);
+                let add_plugin_i18n = chosen.iter().any(|x| *x == 18);
+                let add_plugin_payments = chosen.iter().any(|x| *x == 19);
+                let add_plugin_search = chosen.iter().any(|x| *x == 20);
+                let add_plugin_flags = chosen.iter().any(|x| *x == 21);
+                let add_plugin_tenancy = chosen.iter().any(|x| *x == 22);
+                let add_plugin_webhooks = chosen.iter().any(|x| *x == 23);
+                let add_plugin_observability = chosen.iter().any(|x| *x == 24);
+                let add_plugin_prometheus = chosen.iter().any(|x| *x == 25);
+                let add_plugin_sentry = chosen.iter().any(|x| *x == 26);
+                let add_plugin_grpc = chosen.iter().any(|x| *x == 27);
+                let add_plugin_rpc = chosen.iter().any(|x| *x == 28);
+                let add_plugin_pwa = chosen.iter().any(|x| *x == 29);
+                let add_plugin_desktop = chosen.iter().any(|x| *x == 30);
+                let add_plugin_soft_delete = chosen.iter().any(|x| *x == 31);
+                let add_plugin_events = chosen.iter().any(|x| *x == 32);
+                let add_plugin_outbox = chosen.iter().any(|x| *x == 33);
+                let add_plugin_broker = chosen.iter().any(|x| *x == 34);
+                let add_plugin_geo = chosen.iter().any(|x| *x == 35);
 
                 let mut features: Vec<String> = vec![];
                 if add_plugin_auth {
@@ -338,6 +596,96 @@ fn create_project(
                 if add_plugin_utoipa {
                     features.push("plugin_utoipa".to_string());
                 }
+                if add_plugin_metering {
+                    features.push("plugin_metering".to_string());
+                }
+                if add_plugin_consent {
+                    features.push("plugin_consent".to_string());
+                }
+                if add_plugin_notifications {
+                    features.push("plugin_notifications".to_string());
+                }
+                if add_plugin_referrals {
+                    features.push("plugin_referrals".to_string());
+                }
+                if add_plugin_admin {
+                    features.push("plugin_admin".to_string());
+                }
+                if add_plugin_mail_queue {
+                    features.push("plugin_mail_queue".to_string());
+                }
+                if add_plugin_mail_log {
+                    features.push("plugin_mail_log".to_string());
+                }
+                if add_plugin_audit {
+                    features.push("plugin_audit".to_string());
+                }
+                if add_plugin_scheduler {
+                    features.push("plugin_scheduler".to_string());
+                }
+                if add_plugin_realtime {
+                    features.push("plugin_realtime".to_string());
+                }
+                if add_plugin_cache {
+                    features.push("plugin_cache".to_string());
+                }
+                if add_plugin_rate_limit {
+                    features.push("plugin_rate_limit".to_string());
+                }
+                if add_plugin_i18n {
+                    features.push("plugin_i18n".to_string());
+                }
+                if add_plugin_payments {
+                    features.push("plugin_payments".to_string());
+                }
+                if add_plugin_search {
+                    features.push("plugin_search".to_string());
+                }
+                if add_plugin_flags {
+                    features.push("plugin_flags".to_string());
+                }
+                if add_plugin_tenancy {
+                    features.push("plugin_tenancy".to_string());
+                }
+                if add_plugin_webhooks {
+                    features.push("plugin_webhooks".to_string());
+                }
+                if add_plugin_observability {
+                    features.push("plugin_observability".to_string());
+                }
+                if add_plugin_prometheus {
+                    features.push("plugin_prometheus".to_string());
+                }
+                if add_plugin_sentry {
+                    features.push("plugin_sentry".to_string());
+                }
+                if add_plugin_grpc {
+                    features.push("plugin_grpc".to_string());
+                }
+                if add_plugin_rpc {
+                    features.push("plugin_rpc".to_string());
+                }
+                if add_plugin_pwa {
+                    features.push("plugin_pwa".to_string());
+                }
+                if add_plugin_desktop {
+                    features.push("plugin_desktop".to_string());
+                }
+                if add_plugin_soft_delete {
+                    features.push("plugin_soft_delete".to_string());
+                }
+                if add_plugin_events {
+                    features.push("plugin_events".to_string());
+                }
+                if add_plugin_outbox {
+                    features.push("plugin_outbox".to_string());
+                }
+                if add_plugin_broker {
+                    features.push("plugin_broker".to_string());
+                }
+                if add_plugin_geo {
+                    features.push("plugin_geo".to_string());
+                }
 
                 features
             } else {
@@ -348,6 +696,35 @@ fn create_project(
     //add the dev plugin
     cra_enabled_features.push("plugin_dev".to_string());
 
+    let plugin_graphql = cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_graphql");
+    let graphql_federation = if graphql_federation || !plugin_graphql || cli_mode {
+        graphql_federation && plugin_graphql
+    } else {
+        Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Scaffold the GraphQL plugin as an Apollo Federation subgraph (composed with other services behind a router)?")
+            .default(false)
+            .interact()?
+    };
+
+    let ci = if ci.is_some() || cli_mode {
+        ci
+    } else {
+        let items = vec!["GitHub Actions", "GitLab CI", "None"];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Generate a CI pipeline? (builds, migrates and tests the project)")
+            .items(&items)
+            .default(0)
+            .interact_on_opt(&Term::stderr())?;
+
+        match selection {
+            Some(0) => Some(CiProvider::Github),
+            Some(1) => Some(CiProvider::Gitlab),
+            _ => None,
+        }
+    };
+
     // add database and framework to enabled features
     cra_enabled_features.push(match backend_database {
         BackendDatabase::Postgres => "database_postgres".to_string(),
@@ -358,6 +735,27 @@ fn create_project(
         BackendFramework::Poem => "backend_poem".to_string(),
     });
 
+    run.interactive = !cli_mode;
+    run.backend_framework = Some(
+        match backend_framework {
+            BackendFramework::ActixWeb => "actix-web",
+            BackendFramework::Poem => "poem",
+        }
+        .to_string(),
+    );
+    run.database = Some(
+        match backend_database {
+            BackendDatabase::Postgres => "postgres",
+            BackendDatabase::Sqlite => "sqlite",
+        }
+        .to_string(),
+    );
+    run.plugins = cra_enabled_features
+        .iter()
+        .filter_map(|feature| feature.strip_prefix("plugin_"))
+        .map(String::from)
+        .collect();
+
     project::create(
         project_name.as_ref(),
         CreationOptions {
@@ -404,6 +802,97 @@ fn create_project(
         plugin_utoipa: cra_enabled_features
             .iter()
             .any(|feature| feature == "plugin_utoipa"),
+        plugin_metering: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_metering"),
+        plugin_consent: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_consent"),
+        plugin_notifications: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_notifications"),
+        plugin_referrals: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_referrals"),
+        plugin_admin: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_admin"),
+        plugin_mail_queue: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_mail_queue"),
+        plugin_mail_log: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_mail_log"),
+        plugin_audit: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_audit"),
+        plugin_scheduler: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_scheduler"),
+        plugin_realtime: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_realtime"),
+        plugin_cache: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_cache"),
+        plugin_rate_limit: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_rate_limit"),
+        plugin_i18n: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_i18n"),
+        plugin_payments: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_payments"),
+        plugin_search: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_search"),
+        plugin_flags: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_flags"),
+        plugin_tenancy: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_tenancy"),
+        plugin_webhooks: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_webhooks"),
+        plugin_observability: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_observability"),
+        plugin_prometheus: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_prometheus"),
+        plugin_sentry: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_sentry"),
+        plugin_grpc: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_grpc"),
+        plugin_rpc: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_rpc"),
+        plugin_pwa: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_pwa"),
+        plugin_desktop: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_desktop"),
+        plugin_soft_delete: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_soft_delete"),
+        plugin_events: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_events"),
+        plugin_outbox: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_outbox"),
+        plugin_broker: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_broker"),
+        plugin_geo: cra_enabled_features
+            .iter()
+            .any(|feature| feature == "plugin_geo"),
+        graphql_federation,
     };
 
     if cra_enabled_features
@@ -448,6 +937,196 @@ fn create_project(
     {
         plugins::install(plugins::utoipa::Utoipa {}, install_config.clone())?;
     }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_metering")
+    {
+        plugins::install(plugins::metering::Metering {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_consent")
+    {
+        plugins::install(plugins::consent::Consent {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_notifications")
+    {
+        plugins::install(
+            plugins::notifications::Notifications {},
+            install_config.clone(),
+        )?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_referrals")
+    {
+        plugins::install(plugins::referrals::Referrals {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_admin")
+    {
+        plugins::install(plugins::admin::Admin {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_mail_queue")
+    {
+        plugins::install(plugins::mail_queue::MailQueue {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_mail_log")
+    {
+        plugins::install(plugins::mail_log::MailLog {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_audit")
+    {
+        plugins::install(plugins::audit::Audit {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_scheduler")
+    {
+        plugins::install(plugins::scheduler::Scheduler {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_realtime")
+    {
+        plugins::install(plugins::realtime::Realtime {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_cache")
+    {
+        plugins::install(plugins::cache::Cache {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_rate_limit")
+    {
+        plugins::install(plugins::rate_limit::RateLimit {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_i18n")
+    {
+        plugins::install(plugins::i18n::I18n {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_payments")
+    {
+        plugins::install(plugins::payments::Payments {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_search")
+    {
+        plugins::install(plugins::search::Search {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_flags")
+    {
+        plugins::install(plugins::flags::Flags {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_tenancy")
+    {
+        plugins::install(plugins::tenancy::Tenancy {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_webhooks")
+    {
+        plugins::install(plugins::webhooks::Webhooks {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_observability")
+    {
+        plugins::install(
+            plugins::observability::Observability {},
+            install_config.clone(),
+        )?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_prometheus")
+    {
+        plugins::install(plugins::prometheus::Prometheus {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_sentry")
+    {
+        plugins::install(plugins::sentry::Sentry {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_grpc")
+    {
+        plugins::install(plugins::grpc::Grpc {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_rpc")
+    {
+        plugins::install(plugins::rpc::Rpc {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_pwa")
+    {
+        plugins::install(plugins::pwa::Pwa {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_desktop")
+    {
+        plugins::install(plugins::desktop::Desktop {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_soft_delete")
+    {
+        plugins::install(plugins::soft_delete::SoftDelete {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_events")
+    {
+        plugins::install(plugins::events::Events {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_outbox")
+    {
+        plugins::install(plugins::outbox::Outbox {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_broker")
+    {
+        plugins::install(plugins::broker::Broker {}, install_config.clone())?;
+    }
+    if cra_enabled_features
+        .iter()
+        .any(|feature| feature == "plugin_geo")
+    {
+        plugins::install(plugins::geo::Geo {}, install_config.clone())?;
+    }
+
+    if let Some(ci) = ci {
+        content::ci::create(ci, &install_config)?;
+    }
 
     // cd into project dir and make a copy of the env file
     let example_env_file = PathBuf::from("./.env.example");
@@ -469,6 +1148,9 @@ fn configure_project(
     qsync_output_file: Option<PathBuf>,
     qsync_debug: bool,
     new_service: bool,
+    graphql_codegen: bool,
+    new_sse: bool,
+    run: &mut telemetry::Run,
 ) -> Result<()> {
     let current_dir: PathBuf = fs::get_current_working_directory()?;
 
@@ -494,10 +1176,16 @@ fn configure_project(
         Some(0)
     } else if new_service {
         Some(1)
+    } else if graphql_codegen {
+        Some(2)
+    } else if new_sse {
+        Some(3)
     } else {
         let items = vec![
             "Generate react-query hooks (beta)",
             "Add a model & service (beta)",
+            "Generate the frontend's GraphQL types",
+            "Add a Server-Sent Events endpoint (beta)",
             "Cancel",
         ];
 
@@ -507,6 +1195,15 @@ fn configure_project(
             .interact_on_opt(&Term::stderr())?
     };
 
+    run.interactive = !(query_sync || new_service || graphql_codegen || new_sse);
+    run.action = match selection {
+        Some(0) => Some("qsync"),
+        Some(1) => Some("new-service"),
+        Some(2) => Some("graphql-codegen"),
+        Some(3) => Some("new-sse"),
+        _ => None,
+    };
+
     if let Some(index) = selection {
         match index {
             0 => {
@@ -525,12 +1222,30 @@ fn configure_project(
                     _ => panic!("Fatal: Unknown backend framework specified."),
                 };
 
-                qsync::process(
-                    qsync_input_files.unwrap_or_else(|| vec![PathBuf::from("backend/services")]),
-                    qsync_output_file
-                        .unwrap_or_else(|| PathBuf::from("frontend/src/api.generated.ts")),
-                    qsync_debug,
-                );
+                let generated = qsync::Generator::new()
+                    .inputs(
+                        qsync_input_files
+                            .unwrap_or_else(|| vec![PathBuf::from("backend/services")]),
+                    )
+                    .debug(qsync_debug)
+                    .generate();
+                let output_file = qsync_output_file
+                    .unwrap_or_else(|| PathBuf::from("frontend/src/api.generated.ts"));
+
+                if qsync_debug {
+                    println!("{}", generated.code);
+                    logger::message("Note: nothing is written in debug mode");
+                } else {
+                    generated.write(&output_file)?;
+                    logger::message(&format!(
+                        "Generated {} hooks, see {output_file:#?}",
+                        generated.hooks.len()
+                    ));
+                }
+
+                for unprocessed_file in generated.unprocessed_files {
+                    logger::error(&format!("Could not parse {unprocessed_file:#?}"));
+                }
             }
             1 => {
                 // Add resource
@@ -566,14 +1281,114 @@ fn configure_project(
                     false
                 };
 
+                let has_attachments = Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Does it have attachments? (requires the storage plugin)")
+                    .default(false)
+                    .interact()
+                    .unwrap();
+
+                let is_audited = Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Record its changes in the audit log? (requires the audit plugin)")
+                    .default(false)
+                    .interact()
+                    .unwrap();
+
+                let is_cached = Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Cache its reads? (requires the cache plugin)")
+                    .default(false)
+                    .interact()
+                    .unwrap();
+
+                let is_searchable = Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Index it for search? (requires the search plugin)")
+                    .default(false)
+                    .interact()
+                    .unwrap();
+
+                let is_tenant_scoped = Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt(
+                        "Scope it to the user's organization? (requires the tenancy plugin)",
+                    )
+                    .default(false)
+                    .interact()
+                    .unwrap();
+
+                let is_soft_deletable = Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Soft-delete it? (requires the soft delete plugin)")
+                    .default(false)
+                    .interact()
+                    .unwrap();
+
+                let uses_outbox = Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt(
+                        "Publish its changes through the outbox? (requires the events and outbox plugins)",
+                    )
+                    .default(false)
+                    .interact()
+                    .unwrap();
+
+                let geometry_columns: String = Input::new()
+                    .with_prompt("Geometry columns, ex: `location:point, area:polygon` (requires the geo plugin)")
+                    .default("".into())
+                    .interact_text()?;
+                let geometry_columns = match content::geo::parse(&geometry_columns) {
+                    Ok(geometry_columns) => geometry_columns,
+                    Err(err) => {
+                        logger::error(&err.to_string());
+                        std::process::exit(1);
+                    }
+                };
+
                 project::create_resource(
                     backend_framework,
                     resource_name.as_ref(),
                     include_qsync_attr,
+                    has_attachments,
+                    is_audited,
+                    is_cached,
+                    is_searchable,
+                    is_tenant_scoped,
+                    is_soft_deletable,
+                    uses_outbox,
+                    &geometry_columns,
                 )?;
                 std::process::exit(0);
             }
-            2 => return Ok(()),
+            2 => {
+                if !content::graphql::is_installed() {
+                    logger::error("The GraphQL plugin isn't installed in this project.");
+                    std::process::exit(1);
+                }
+
+                content::graphql::codegen()?;
+            }
+            3 => {
+                let name: String = Input::new()
+                    .with_prompt("Events name (ex: Notification)")
+                    .default("".into())
+                    .interact_text()?;
+
+                if name.is_empty() {
+                    return Ok(());
+                }
+
+                logger::message("Which backend framework are you using?");
+                logger::message("Use UP/DOWN arrows to navigate and SPACE or ENTER to confirm.");
+                let items = vec!["actix_web", "poem"];
+                let selection = Select::with_theme(&ColorfulTheme::default())
+                    .items(&items)
+                    .default(0)
+                    .interact_on_opt(&Term::stderr())?;
+
+                let backend_framework: BackendFramework = match selection {
+                    Some(0) => BackendFramework::ActixWeb,
+                    Some(1) => BackendFramework::Poem,
+                    _ => panic!("Fatal: Unknown backend framework specified."),
+                };
+
+                content::sse::create(backend_framework, &name)?;
+            }
+            4 => return Ok(()),
             _ => {
                 logger::error("Not implemented");
                 std::process::exit(1);
@@ -583,3 +1398,35 @@ fn configure_project(
 
     Ok(())
 }
+
+fn generate_deployment(target: DeployTarget, run: &mut telemetry::Run) -> Result<()> {
+    let project = content::deploy::Project::read()?;
+
+    run.interactive = false;
+    run.database = Some(
+        match project.database {
+            BackendDatabase::Postgres => "postgres",
+            BackendDatabase::Sqlite => "sqlite",
+        }
+        .to_string(),
+    );
+
+    match target {
+        DeployTarget::K8s => {
+            run.action = Some("k8s");
+            content::deploy::k8s::generate(&project)
+        }
+        DeployTarget::Fly => {
+            run.action = Some("fly");
+            content::deploy::fly::generate(&project)
+        }
+        DeployTarget::Render => {
+            run.action = Some("render");
+            content::deploy::render::generate(&project)
+        }
+        DeployTarget::Railway => {
+            run.action = Some("railway");
+            content::deploy::railway::generate(&project)
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::logger::register_service_msg;
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::BackendFramework;
+use anyhow::Result;
+
+pub struct Admin {}
+
+impl Plugin for Admin {
+    fn name(&self) -> &'static str {
+        "Admin"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            crate::logger::error("The Admin plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        // the portal's bundle (.cargo/admin/dist) is copied by the Dev plugin, which only mounts
+        // it in debug builds when this plugin isn't installed
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                register_service_msg("/admin");
+                fs::replace(
+                    "backend/main.rs",
+                    "app = app.service(api_scope);",
+                    r#"// Mount the admin portal on /admin (requires the "admin" role)
+        api_scope = api_scope.service(create_rust_app::admin::endpoints(web::scope("/admin")));
+        app = app.service(web::scope("/admin").service(Files::new("/", ".cargo/admin/dist/").index_file("admin.html")));
+
+        app = app.service(api_scope);"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                register_service_msg("/admin");
+                fs::replace(
+                    "backend/main.rs",
+                    r#"app = app.nest("/api", api_routes);"#,
+                    r#"// Mount the admin portal on /admin (requires the "admin" role)
+    api_routes = api_routes.nest("/admin", create_rust_app::admin::api());
+    app = app.at("/admin", StaticFilesEndpoint::new(".cargo/admin/dist").index_file("admin.html"));
+
+    app = app.nest("/api", api_routes);"#,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::logger;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+
+pub struct Audit {}
+
+impl Plugin for Audit {
+    fn name(&self) -> &'static str {
+        "Audit"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Audit plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        crate::content::migration::create(
+            "plugin_audit",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE audit_log (
+        id SERIAL PRIMARY KEY,
+        actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+        action TEXT NOT NULL,
+        target_type TEXT NOT NULL,
+        target_id TEXT,
+        diff TEXT,
+        ip_address TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX audit_log_actor_id_index ON audit_log(actor_id);
+      CREATE INDEX audit_log_target_index ON audit_log(target_type, target_id);
+      CREATE INDEX audit_log_created_at_index ON audit_log(created_at);
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE audit_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+        action TEXT NOT NULL,
+        target_type TEXT NOT NULL,
+        target_id TEXT,
+        diff TEXT,
+        ip_address TEXT,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX audit_log_actor_id_index ON audit_log(actor_id);
+      CREATE INDEX audit_log_target_index ON audit_log(target_type, target_id);
+      CREATE INDEX audit_log_created_at_index ON audit_log(created_at);
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE audit_log;
+    "#},
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "audit-log",
+                    r#"create_rust_app::audit::endpoints(web::scope("/audit-log"))"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "audit-log",
+                    "create_rust_app::audit::api()",
+                    "/audit-log",
+                )?;
+            }
+        };
+
+        Ok(())
+    }
+}
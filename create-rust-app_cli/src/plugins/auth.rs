@@ -31,6 +31,22 @@ impl Plugin for Auth {
             std::fs::write(file_path, file_contents.data)?;
         }
 
+        fs::append(
+            ".env.example",
+            r#"
+# CAPTCHA on the login, register and forgot password forms (hcaptcha, turnstile or recaptcha)
+#CAPTCHA_PROVIDER=turnstile
+#CAPTCHA_SITE_KEY=
+#CAPTCHA_SECRET_KEY=
+
+# OAuth apps users can link their account to (redirect to <your app>/account)
+#GOOGLE_CLIENT_ID=
+#GOOGLE_CLIENT_SECRET=
+#GITHUB_CLIENT_ID=
+#GITHUB_CLIENT_SECRET=
+"#,
+        )?;
+
         // ===============================
         // PATCH FRONTEND
         // ===============================
@@ -41,11 +57,13 @@ impl Plugin for Auth {
             "frontend/src/App.tsx",
             r#"import { useAuth, useAuthCheck } from './hooks/useAuth'
 import { AccountPage } from './containers/AccountPage'
+import { SecurityPage } from './containers/SecurityPage'
 import { LoginPage } from './containers/LoginPage'
 import { ActivationPage } from './containers/ActivationPage'
 import { RegistrationPage } from './containers/RegistrationPage'
 import { RecoveryPage } from './containers/RecoveryPage'
-import { ResetPage } from './containers/ResetPage'"#,
+import { ResetPage } from './containers/ResetPage'
+import { ConfirmEmailPage } from './containers/ConfirmEmailPage'"#,
         )?;
         fs::prepend(
             "frontend/bundles/index.tsx",
@@ -67,15 +85,18 @@ import { ResetPage } from './containers/ResetPage'"#,
             <Route path="/recovery" element={<RecoveryPage />} />
             <Route path="/reset" element={<ResetPage />} />
             <Route path="/activate" element={<ActivationPage />} />
+            <Route path="/confirm-email" element={<ConfirmEmailPage />} />
             <Route path="/register" element={<RegistrationPage />} />
             <Route path="/account" element={<AccountPage />} />
+            <Route path="/security" element={<SecurityPage />} />
     "#,
         )?;
         fs::replace(
             "frontend/src/App.tsx",
             "{/* CRA: left-aligned nav buttons */}",
             r#"{/* CRA: left-aligned nav buttons */}
-          <a className="NavButton" onClick={() => navigate('/account')}>Account</a>"#,
+          <a className="NavButton" onClick={() => navigate('/account')}>Account</a>
+          { auth.isAuthenticated && <a className="NavButton" onClick={() => navigate('/security')}>Security</a> }"#,
         )?;
         fs::replace(
             "frontend/src/App.tsx",
@@ -190,16 +211,408 @@ import { ResetPage } from './containers/ResetPage'"#,
     "#},
         )?;
 
+        // guests are users without an email address or password until they sign up (SQLite can't
+        // drop a NOT NULL constraint, the table is rebuilt instead)
+        crate::content::migration::create(
+            "users_nullable_credentials",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      ALTER TABLE users ALTER COLUMN email DROP NOT NULL;
+      ALTER TABLE users ALTER COLUMN hash_password DROP NOT NULL;
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE users_new (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        email TEXT,
+        hash_password TEXT,
+        activated BOOLEAN NOT NULL DEFAULT FALSE,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      INSERT INTO users_new (id, email, hash_password, activated, created_at)
+        SELECT id, email, hash_password, activated, created_at FROM users;
+
+      DROP TABLE users;
+      ALTER TABLE users_new RENAME TO users;
+    "#},
+            },
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      DELETE FROM user_sessions WHERE user_id IN (SELECT id FROM users WHERE email IS NULL OR hash_password IS NULL);
+      DELETE FROM user_permissions WHERE user_id IN (SELECT id FROM users WHERE email IS NULL OR hash_password IS NULL);
+      DELETE FROM user_roles WHERE user_id IN (SELECT id FROM users WHERE email IS NULL OR hash_password IS NULL);
+      DELETE FROM users WHERE email IS NULL OR hash_password IS NULL;
+      ALTER TABLE users ALTER COLUMN email SET NOT NULL;
+      ALTER TABLE users ALTER COLUMN hash_password SET NOT NULL;
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      DELETE FROM user_sessions WHERE user_id IN (SELECT id FROM users WHERE email IS NULL OR hash_password IS NULL);
+      DELETE FROM user_permissions WHERE user_id IN (SELECT id FROM users WHERE email IS NULL OR hash_password IS NULL);
+      DELETE FROM user_roles WHERE user_id IN (SELECT id FROM users WHERE email IS NULL OR hash_password IS NULL);
+
+      CREATE TABLE users_old (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        email TEXT NOT NULL,
+        hash_password TEXT NOT NULL,
+        activated BOOLEAN NOT NULL DEFAULT FALSE,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      INSERT INTO users_old (id, email, hash_password, activated, created_at)
+        SELECT id, email, hash_password, activated, created_at FROM users
+        WHERE email IS NOT NULL AND hash_password IS NOT NULL;
+
+      DROP TABLE users;
+      ALTER TABLE users_old RENAME TO users;
+    "#},
+            },
+        )?;
+
+        // where and when each session was last used, for the account's list of devices (SQLite
+        // can't add a column defaulting to CURRENT_TIMESTAMP, the table is rebuilt instead)
+        crate::content::migration::create(
+            "user_sessions_last_used",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      ALTER TABLE user_sessions ADD COLUMN ip_address TEXT;
+      ALTER TABLE user_sessions ADD COLUMN last_used_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE user_sessions_new (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        refresh_token TEXT NOT NULL,
+        device TEXT,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        ip_address TEXT,
+        last_used_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      INSERT INTO user_sessions_new (id, user_id, refresh_token, device, created_at)
+        SELECT id, user_id, refresh_token, device, created_at FROM user_sessions;
+
+      DROP TABLE user_sessions;
+      ALTER TABLE user_sessions_new RENAME TO user_sessions;
+    "#},
+            },
+            indoc! {r#"
+      ALTER TABLE user_sessions DROP COLUMN last_used_at;
+      ALTER TABLE user_sessions DROP COLUMN ip_address;
+    "#},
+        )?;
+
+        // refresh tokens which were exchanged for new ones, to detect their reuse
+        crate::content::migration::create(
+            "user_session_rotated_tokens",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE user_session_rotated_tokens (
+        refresh_token TEXT PRIMARY KEY,
+        session_id SERIAL NOT NULL REFERENCES user_sessions(id) ON DELETE CASCADE,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE user_session_rotated_tokens (
+        refresh_token TEXT PRIMARY KEY NOT NULL,
+        session_id INTEGER NOT NULL REFERENCES user_sessions(id) ON DELETE CASCADE,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE user_session_rotated_tokens;
+    "#},
+        )?;
+
+        // the OAuth accounts users linked to theirs
+        crate::content::migration::create(
+            "user_identities",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE user_identities (
+        id SERIAL PRIMARY KEY,
+        user_id SERIAL NOT NULL REFERENCES users(id),
+        provider TEXT NOT NULL,
+        provider_user_id TEXT NOT NULL,
+        email TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (provider, provider_user_id),
+        UNIQUE (user_id, provider)
+      );
+
+      SELECT manage_updated_at('user_identities');
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE user_identities (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        provider TEXT NOT NULL,
+        provider_user_id TEXT NOT NULL,
+        email TEXT,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (provider, provider_user_id),
+        UNIQUE (user_id, provider)
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE user_identities;
+    "#},
+        )?;
+
+        // the admins' impersonations of users
+        crate::content::migration::create(
+            "user_impersonations",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE user_impersonations (
+        id SERIAL PRIMARY KEY,
+        impersonator_id SERIAL NOT NULL REFERENCES users(id),
+        user_id SERIAL NOT NULL REFERENCES users(id),
+        reason TEXT,
+        ip_address TEXT,
+        started_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        ended_at TIMESTAMPTZ
+      );
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE user_impersonations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        impersonator_id INTEGER NOT NULL REFERENCES users(id),
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        reason TEXT,
+        ip_address TEXT,
+        started_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        ended_at DATETIME
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE user_impersonations;
+    "#},
+        )?;
+
+        // impersonations outlive their admin's account, which only forgets who made them (SQLite
+        // can't drop a NOT NULL constraint, the table is rebuilt instead)
+        crate::content::migration::create(
+            "user_impersonations_nullable_impersonator",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      ALTER TABLE user_impersonations ALTER COLUMN impersonator_id DROP NOT NULL;
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE user_impersonations_new (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        impersonator_id INTEGER REFERENCES users(id),
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        reason TEXT,
+        ip_address TEXT,
+        started_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        ended_at DATETIME
+      );
+
+      INSERT INTO user_impersonations_new (id, impersonator_id, user_id, reason, ip_address, started_at, ended_at)
+        SELECT id, impersonator_id, user_id, reason, ip_address, started_at, ended_at FROM user_impersonations;
+
+      DROP TABLE user_impersonations;
+      ALTER TABLE user_impersonations_new RENAME TO user_impersonations;
+    "#},
+            },
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      DELETE FROM user_impersonations WHERE impersonator_id IS NULL;
+      ALTER TABLE user_impersonations ALTER COLUMN impersonator_id SET NOT NULL;
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE user_impersonations_old (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        impersonator_id INTEGER NOT NULL REFERENCES users(id),
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        reason TEXT,
+        ip_address TEXT,
+        started_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        ended_at DATETIME
+      );
+
+      INSERT INTO user_impersonations_old (id, impersonator_id, user_id, reason, ip_address, started_at, ended_at)
+        SELECT id, impersonator_id, user_id, reason, ip_address, started_at, ended_at FROM user_impersonations
+        WHERE impersonator_id IS NOT NULL;
+
+      DROP TABLE user_impersonations;
+      ALTER TABLE user_impersonations_old RENAME TO user_impersonations;
+    "#},
+            },
+        )?;
+
+        // the banned users
+        crate::content::migration::create(
+            "user_bans",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE user_bans (
+        id SERIAL PRIMARY KEY,
+        user_id INTEGER NOT NULL UNIQUE REFERENCES users(id),
+        reason TEXT,
+        banned_by INTEGER REFERENCES users(id),
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE user_bans (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL UNIQUE REFERENCES users(id),
+        reason TEXT,
+        banned_by INTEGER REFERENCES users(id),
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE user_bans;
+    "#},
+        )?;
+
+        // organizations and their members
+        crate::content::migration::create(
+            "organizations",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE organizations (
+        id SERIAL PRIMARY KEY,
+        name TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE memberships (
+        organization_id SERIAL NOT NULL REFERENCES organizations(id),
+        user_id SERIAL NOT NULL REFERENCES users(id),
+        role TEXT NOT NULL,
+        active_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (organization_id, user_id)
+      );
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE organizations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        name TEXT NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE memberships (
+        organization_id INTEGER NOT NULL REFERENCES organizations(id),
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        role TEXT NOT NULL,
+        active_at DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (organization_id, user_id)
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE memberships;
+      DROP TABLE organizations;
+    "#},
+        )?;
+
+        // the invitations to register (and to join an organization)
+        crate::content::migration::create(
+            "user_invitations",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE user_invitations (
+        id SERIAL PRIMARY KEY,
+        email TEXT NOT NULL,
+        roles TEXT NOT NULL DEFAULT '',
+        token TEXT NOT NULL,
+        invited_by SERIAL NOT NULL REFERENCES users(id),
+        organization_id INTEGER REFERENCES organizations(id),
+        organization_role TEXT,
+        accepted_at TIMESTAMPTZ,
+        expires_at TIMESTAMPTZ NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE user_invitations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        email TEXT NOT NULL,
+        roles TEXT NOT NULL DEFAULT '',
+        token TEXT NOT NULL,
+        invited_by INTEGER NOT NULL REFERENCES users(id),
+        organization_id INTEGER REFERENCES organizations(id),
+        organization_role TEXT,
+        accepted_at DATETIME,
+        expires_at DATETIME NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE user_invitations;
+    "#},
+        )?;
+
+        // the app's own user fields, see backend/models/user_profiles.rs
+        crate::content::migration::create(
+            "user_profiles",
+            indoc! {r#"
+      CREATE TABLE user_profiles (
+        user_id INTEGER PRIMARY KEY REFERENCES users(id),
+        display_name TEXT,
+        locale TEXT
+      );
+    "#},
+            indoc! {r#"
+      DROP TABLE user_profiles;
+    "#},
+        )?;
+        fs::append("backend/models/mod.rs", "pub mod user_profiles;")?;
+
+        // token lifetimes and refresh token cookie attributes
+        fs::replace(
+            "backend/main.rs",
+            "create_rust_app::setup();",
+            r#"create_rust_app::setup();
+
+    let auth_config = create_rust_app::auth::Auth::configure()
+        .access_ttl(std::time::Duration::from_secs(15 * 60))
+        .refresh_ttl(std::time::Duration::from_secs(24 * 60 * 60))
+        .cookie_same_site(create_rust_app::auth::SameSite::Strict)
+        .open_registration(true)
+        .user_profile::<models::user_profiles::Profile>();"#,
+        )?;
+
         match install_config.backend_framework {
-            BackendFramework::ActixWeb => crate::content::service::register_actix(
-                "auth",
-                r#"create_rust_app::auth::endpoints(web::scope("/auth"))"#,
-            )?,
-            BackendFramework::Poem => crate::content::service::register_poem(
-                "auth",
-                "create_rust_app::auth::api()",
-                "/auth",
-            )?,
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "auth",
+                    r#"create_rust_app::auth::endpoints(web::scope("/auth"))"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "app = app.app_data(Data::new(app_data.mailer.clone()));",
+                    r#"app = app.app_data(Data::new(app_data.mailer.clone()));
+        app = app.app_data(Data::new(auth_config.clone()));"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "auth",
+                    "create_rust_app::auth::api()",
+                    "/auth",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    ".with(AddData::new(data.database))",
+                    ".with(AddData::new(data.database))
+                .with(AddData::new(auth_config))",
+                )?;
+            }
         };
 
         Ok(())
@@ -0,0 +1,47 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::BackendFramework;
+use anyhow::Result;
+
+pub struct Cache {}
+
+impl Plugin for Cache {
+    fn name(&self) -> &'static str {
+        "Cache"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                fs::replace(
+                    "backend/main.rs",
+                    "app = app.app_data(Data::new(app_data.mailer.clone()));",
+                    r#"app = app.app_data(Data::new(app_data.mailer.clone()));
+        app = app.app_data(Data::new(app_data.cache.clone()));"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                // the `RequestCache` extractor shares its values within a request
+                fs::replace(
+                    "backend/main.rs",
+                    ".with(AddData::new(data.database))",
+                    ".with(AddData::new(data.database))
+                .with(AddData::new(data.cache))
+                .before(create_rust_app::cache::with_request_cache)",
+                )?;
+            }
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# keep the cache in Redis, shared by every instance of the app (needs create-rust-app's
+# "plugin_redis" feature), instead of in the process' memory
+#REDIS_URL=redis://127.0.0.1/
+"#,
+        )?;
+
+        Ok(())
+    }
+}
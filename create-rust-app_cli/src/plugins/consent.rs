@@ -0,0 +1,125 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Consent {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-consent"]
+struct Asset;
+
+impl Plugin for Consent {
+    fn name(&self) -> &'static str {
+        "Consent"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            crate::logger::error("The Consent plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        // ===============================
+        // PATCH FRONTEND
+        // ===============================
+
+        fs::prepend(
+            "frontend/src/App.tsx",
+            r#"import { ConsentGate } from './components/ConsentGate'
+import { PolicyPage } from './containers/PolicyPage'"#,
+        )?;
+
+        fs::replace(
+            "frontend/src/App.tsx",
+            r#"<div style={{ margin: '0 auto', maxWidth: '800px' }}>"#,
+            r#"<ConsentGate />
+      <div style={{ margin: '0 auto', maxWidth: '800px' }}>"#,
+        )?;
+
+        fs::replace(
+            "frontend/src/App.tsx",
+            r#"{/* CRA: routes */}"#,
+            r#"{/* CRA: routes */}
+            <Route path="/legal/:kind" element={<PolicyPage />} />"#,
+        )?;
+
+        crate::content::migration::create(
+            "plugin_consent",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE policy_documents (
+        id SERIAL PRIMARY KEY,
+        kind TEXT NOT NULL,
+        version INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        content TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (kind, version)
+      );
+
+      CREATE TABLE user_consents (
+        user_id SERIAL NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        document_id SERIAL NOT NULL REFERENCES policy_documents(id),
+        ip_address TEXT,
+        accepted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (user_id, document_id)
+      );
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE policy_documents (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        kind TEXT NOT NULL,
+        version INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        content TEXT NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (kind, version)
+      );
+
+      CREATE TABLE user_consents (
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        document_id INTEGER NOT NULL REFERENCES policy_documents(id),
+        ip_address TEXT,
+        accepted_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (user_id, document_id)
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE user_consents;
+      DROP TABLE policy_documents;
+    "#},
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => crate::content::service::register_actix(
+                "consent",
+                r#"create_rust_app::consent::endpoints(web::scope("/consent"))"#,
+            )?,
+            BackendFramework::Poem => crate::content::service::register_poem(
+                "consent",
+                "create_rust_app::consent::api()",
+                "/consent",
+            )?,
+        };
+
+        Ok(())
+    }
+}
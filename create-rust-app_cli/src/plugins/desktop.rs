@@ -0,0 +1,145 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Desktop {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-desktop"]
+struct Asset;
+
+impl Plugin for Desktop {
+    fn name(&self) -> &'static str {
+        "Desktop"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        for filename in Asset::iter() {
+            // keeps the refresh token in the OS keychain, only needed with the auth plugin
+            if filename.contains("frontend/src/desktop.ts") && !install_config.plugin_auth {
+                continue;
+            }
+
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        // identifiers only allow alphanumerics, hyphens and periods
+        let identifier = install_config.project_name.replace('_', "-");
+        fs::replace(
+            "desktop/tauri.conf.json",
+            "com.create-rust-app.desktop",
+            &format!("com.{identifier}.desktop"),
+        )?;
+        fs::replace(
+            "desktop/tauri.conf.json",
+            r#""create-rust-app""#,
+            &format!(r#""{}""#, install_config.project_name),
+        )?;
+
+        // `cargo tauri icon desktop/icons/icon.png` generates the other sizes and formats
+        let mut icon = std::path::PathBuf::from(&install_config.project_dir);
+        icon.push("desktop/icons/icon.png");
+        let mut logo = std::path::PathBuf::from(&install_config.project_dir);
+        logo.push("frontend/public/images/logo512.png");
+        if logo.exists() {
+            add_file_msg("desktop/icons/icon.png");
+            std::fs::create_dir_all(icon.parent().unwrap())?;
+            std::fs::copy(logo, icon)?;
+        }
+
+        fs::append(
+            "Cargo.toml",
+            r#"
+[[bin]]
+name = "desktop"
+path = ".cargo/bin/desktop.rs"
+"#,
+        )?;
+        fs::replace(
+            ".cargo/config",
+            r#"frontend="run --bin frontend""#,
+            r#"frontend="run --bin frontend"
+desktop="run --bin desktop""#,
+        )?;
+        fs::append(".gitignore", "/desktop/binaries/\n")?;
+
+        if install_config.plugin_auth {
+            fs::replace(
+                "frontend/package.json",
+                r##""dependencies": {"##,
+                r##""dependencies": {
+    "@tauri-apps/api": "^1.5.1","##,
+            )?;
+            fs::prepend(
+                "frontend/bundles/index.tsx",
+                "import { installDesktopAuth } from '../src/desktop'",
+            )?;
+            fs::replace(
+                "frontend/bundles/index.tsx",
+                "ReactDOM.createRoot(",
+                "// before the auth plugin's first refresh\ninstallDesktopAuth()\n\nReactDOM.createRoot(",
+            )?;
+        }
+
+        // the shell starts the backend on a free port, running the migrations of its database
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    create_rust_app::desktop::migrate(&app_data.database).unwrap();"#,
+                )?;
+                fs::replace(
+                    "backend/main.rs",
+                    r#".bind("0.0.0.0:3000")"#,
+                    ".bind(create_rust_app::desktop::bind_address())",
+                )?;
+            }
+            BackendFramework::Poem => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    create_rust_app::desktop::migrate(&data.database).unwrap();"#,
+                )?;
+                fs::replace(
+                    "backend/main.rs",
+                    r#"TcpListener::bind("0.0.0.0:3000")"#,
+                    "TcpListener::bind(create_rust_app::desktop::bind_address())",
+                )?;
+            }
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# where the backend listens (the desktop app sets it for the backend it embeds)
+#BIND_ADDRESS=0.0.0.0:3000
+# the backend the desktop app connects to instead of embedding one (also read when building it)
+#DESKTOP_BACKEND_URL=https://example.com
+"#,
+        )?;
+
+        if let BackendDatabase::Postgres = install_config.backend_database {
+            logger::message(
+                "The desktop app embeds the backend with an SQLite database: with Postgres, build it with DESKTOP_BACKEND_URL set to connect to your deployed backend instead.",
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -62,32 +62,82 @@ impl Plugin for Dev {
         "##},
         )?;
 
+        fs::append(
+            ".env.example",
+            r#"
+# enables the admin portal's SQL console (debug builds only), queries are read-only
+#CRA_SQL_CONSOLE=true
+# cancels the console's queries after that long (default: 5000)
+#CRA_SQL_CONSOLE_TIMEOUT_MS=5000
+# lets everyone use the admin portal and the development API (debug builds only), instead of
+# requiring the "admin" role; ex: to assign the first admin
+#CRA_ADMIN_OPEN=true
+"#,
+        )?;
+
+        // the Admin plugin mounts the portal outside of the development-only routes
+        let mount_admin = !install_config.plugin_admin;
+
         match install_config.backend_framework {
             BackendFramework::ActixWeb => {
+                // records the requests for the admin portal's inspector (a no-op in release builds)
+                fs::replace(
+                    "backend/main.rs",
+                    r#".wrap(Logger::default());"#,
+                    r#".wrap(Logger::default())
+            .wrap(create_rust_app::inspector::RequestInspector);"#,
+                )?;
+
                 register_service_msg("(dev-only) /development");
-                register_service_msg("(dev-only) /admin");
                 fs::replace(
                     "backend/main.rs",
                     r#"/* Development-only routes */"#,
                     r#"/* Development-only routes */
             // Mount development-only API routes
-            api_scope = api_scope.service(create_rust_app::dev::endpoints(web::scope("/development")));
+            api_scope = api_scope.service(create_rust_app::dev::endpoints(web::scope("/development")));"#,
+                )?;
+
+                if mount_admin {
+                    register_service_msg("(dev-only) /admin");
+                    fs::replace(
+                        "backend/main.rs",
+                        r#"api_scope = api_scope.service(create_rust_app::dev::endpoints(web::scope("/development")));"#,
+                        r#"api_scope = api_scope.service(create_rust_app::dev::endpoints(web::scope("/development")));
             // Mount the admin dashboard on /admin
+            api_scope = api_scope.service(create_rust_app::admin::endpoints(web::scope("/admin")));
             app = app.service(web::scope("/admin").service(Files::new("/", ".cargo/admin/dist/").index_file("admin.html")));"#,
-                )?;
+                    )?;
+                }
             }
             BackendFramework::Poem => {
+                // records the requests for the admin portal's inspector (a no-op in release builds)
+                fs::replace(
+                    "backend/main.rs",
+                    r#".with(Tracing)"#,
+                    r#".with(Tracing)
+                .with(create_rust_app::inspector::RequestInspector)"#,
+                )?;
+
                 register_service_msg("(dev-only) /development");
-                register_service_msg("(dev-only) /admin");
                 fs::replace(
                     "backend/main.rs",
                     r#"/* Development-only routes */"#,
                     r#"/* Development-only routes */
         // Mount development-only API routes
-        api_routes = api_routes.nest("/development", create_rust_app::dev::api());
+        api_routes = api_routes.nest("/development", create_rust_app::dev::api());"#,
+                )?;
+
+                if mount_admin {
+                    register_service_msg("(dev-only) /admin");
+                    fs::replace(
+                        "backend/main.rs",
+                        r#"api_routes = api_routes.nest("/development", create_rust_app::dev::api());"#,
+                        r#"api_routes = api_routes.nest("/development", create_rust_app::dev::api());
         // Mount the admin dashboard on /admin
+        api_routes = api_routes.nest("/admin", create_rust_app::admin::api());
         app = app.at("/admin", StaticFilesEndpoint::new(".cargo/admin/dist").index_file("admin.html"));"#,
-                )?;
+                    )?;
+                }
             }
         }
 
@@ -0,0 +1,85 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Events {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-events"]
+struct Asset;
+
+impl Plugin for Events {
+    fn name(&self) -> &'static str {
+        "Events"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        if install_config.plugin_auth {
+            fs::replace(
+                "backend/events.rs",
+                r#"pub fn subscribe() {"#,
+                r#"pub fn subscribe() {
+    // the auth plugin publishes its hooks' events (registered, login, ...)
+    events::subscribe(|_db, event: &create_rust_app::auth::RegisteredEvent| {
+        println!("User #{} registered", event.user.id);
+    });
+"#,
+            )?;
+        }
+
+        if install_config.plugin_tasks {
+            fs::replace(
+                "backend/events.rs",
+                r#"        println!("Todo #{} was created", event.todo_id);
+    });"#,
+                r#"        println!("Todo #{} was created", event.todo_id);
+    });
+    // run by the tasks queue's workers (`cargo run --bin queue`), retried when it fails
+    events::subscribe_queued("log_todo_created", |_db, event: &TodoCreated| {
+        println!("Todo #{} was created (queued)", event.todo_id);
+        Ok(())
+    });"#,
+            )?;
+
+            // the workers run the queued subscribers they registered
+            fs::replace("backend/queue.rs", "mod tasks;", "mod events;\nmod tasks;")?;
+            fs::replace(
+                "backend/queue.rs",
+                "let queue = create_rust_app::tasks::queue();",
+                "events::subscribe();\n    let queue = create_rust_app::tasks::queue();",
+            )?;
+        }
+
+        fs::replace("backend/main.rs", "mod mail;", "mod events;\nmod mail;")?;
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => fs::replace(
+                "backend/main.rs",
+                "let app_data = create_rust_app::setup();",
+                "let app_data = create_rust_app::setup();\n    events::subscribe();",
+            )?,
+            BackendFramework::Poem => fs::replace(
+                "backend/main.rs",
+                "let data = create_rust_app::setup();",
+                "let data = create_rust_app::setup();\n    events::subscribe();",
+            )?,
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Flags {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-flags"]
+struct Asset;
+
+impl Plugin for Flags {
+    fn name(&self) -> &'static str {
+        "Flags"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Feature Flags plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        crate::content::migration::create(
+            "plugin_flags",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE feature_flags (
+        id SERIAL PRIMARY KEY,
+        key TEXT NOT NULL UNIQUE,
+        description TEXT NOT NULL DEFAULT '',
+        enabled BOOLEAN,
+        rollout_percentage INTEGER CHECK (rollout_percentage BETWEEN 0 AND 100),
+        user_ids TEXT NOT NULL DEFAULT '[]',
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE feature_flags (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        key TEXT NOT NULL UNIQUE,
+        description TEXT NOT NULL DEFAULT '',
+        enabled BOOLEAN,
+        rollout_percentage INTEGER CHECK (rollout_percentage BETWEEN 0 AND 100),
+        user_ids TEXT NOT NULL DEFAULT '[]',
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE feature_flags;
+    "#},
+        )?;
+
+        fs::replace("backend/main.rs", "mod mail;", "mod mail;\nmod flags;")?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "flags",
+                    r#"create_rust_app::flags::endpoints(web::scope("/flags"))"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    // defines the feature flags of `flags.rs`
+    flags::register(&app_data.database);"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "flags",
+                    "create_rust_app::flags::api()",
+                    "/flags",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    // defines the feature flags of `flags.rs`
+    flags::register(&data.database);"#,
+                )?;
+            }
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# the environment the feature flags' defaults are picked for (default: development in debug
+# builds, production otherwise)
+#APP_ENV=staging
+"#,
+        )?;
+
+        Ok(())
+    }
+}
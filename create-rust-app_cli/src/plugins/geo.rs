@@ -0,0 +1,114 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::BackendDatabase;
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Geo {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-geo"]
+struct Asset;
+
+impl Plugin for Geo {
+    fn name(&self) -> &'static str {
+        "Geo"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if install_config.backend_database != BackendDatabase::Postgres {
+            return Err(anyhow::anyhow!(
+                "The geo plugin only supports Postgres (with PostGIS)"
+            ));
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        crate::content::migration::create(
+            "plugin_geo",
+            indoc! {r#"
+      CREATE EXTENSION IF NOT EXISTS postgis;
+    "#},
+            indoc! {r#"
+      DROP EXTENSION IF EXISTS postgis;
+    "#},
+        )?;
+
+        // the schema types geometry columns with PostGIS' SQL types, instead of generating its own
+        fs::replace(
+            "diesel.toml",
+            r#"file = "backend/schema.rs""#,
+            r#"file = "backend/schema.rs"
+import_types = ["diesel::sql_types::*", "create_rust_app::geo::sql_types::*"]
+generate_missing_sql_type_definitions = false"#,
+        )?;
+
+        // dsync doesn't know PostGIS' types: the models' geometry columns are typed after it runs
+        fs::replace(
+            ".cargo/bin/dsync.rs",
+            "dsync::generate_files(schema_file, models_dir, ",
+            "dsync::generate_files(schema_file, models_dir.clone(), ",
+        )?;
+        fs::replace(
+            ".cargo/bin/dsync.rs",
+            r#"         ]),
+    });
+}"#,
+            r#"         ]),
+    });
+
+    // plugin_geo
+    type_geometry_columns(&models_dir);
+}"#,
+        )?;
+        fs::append(
+            ".cargo/bin/dsync.rs",
+            r#"
+/// plugin_geo: types the models' geometry columns (`Geometry` in the schema) with
+/// `create_rust_app::geo::Geometry`, which serializes to GeoJSON
+pub fn type_geometry_columns(models_dir: &PathBuf) {
+    for entry in walkdir(models_dir) {
+        let content = std::fs::read_to_string(&entry).unwrap_or_default();
+        let typed = content
+            .replace(": Geometry,", ": create_rust_app::geo::Geometry,")
+            .replace(": Option<Geometry>,", ": Option<create_rust_app::geo::Geometry>,");
+
+        if typed != content {
+            std::fs::write(&entry, typed).unwrap();
+        }
+    }
+}
+
+fn walkdir(dir: &PathBuf) -> Vec<PathBuf> {
+    let mut files = vec![];
+
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(walkdir(&path));
+        } else if path.extension().map_or(false, |extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+
+    files
+}
+"#,
+        )?;
+
+        Ok(())
+    }
+}
@@ -39,7 +39,12 @@ impl Plugin for GraphQL {
         add_dependency(
             &install_config.project_dir,
             "async-graphql",
-            r#"async-graphql = "3.0.38""#,
+            r#"async-graphql = { version = "3.0.38", features = ["dataloader"] }"#,
+        )?;
+        add_dependency(
+            &install_config.project_dir,
+            "async-trait",
+            r#"async-trait = "0.1.53""#,
         )?;
         add_dependency(
             &install_config.project_dir,
@@ -111,10 +116,28 @@ ReactDOM.createRoot"##,
             r##""dependencies": {"##,
             r##""dependencies": {
     "@apollo/client": "^3.5.10",
+    "apollo-upload-client": "^17.0.0",
+    "crypto-hash": "^2.0.1",
     "graphql-ws": "^5.6.4",
     "graphql": "^16.3.0","##,
         )?;
 
+        // typed documents generated from the schema, see `content::graphql::codegen`
+        fs::replace(
+            "frontend/package.json",
+            r##""devDependencies": {"##,
+            r##""devDependencies": {
+    "@graphql-codegen/cli": "^3.3.1",
+    "@graphql-codegen/client-preset": "^3.0.1",
+    "@types/apollo-upload-client": "^17.0.2","##,
+        )?;
+        fs::replace(
+            "frontend/package.json",
+            r##""scripts": {"##,
+            r##""scripts": {
+    "graphql-codegen": "graphql-codegen","##,
+        )?;
+
         fs::replace("backend/main.rs", "mod mail;", "mod mail;\nmod graphql;")?;
 
         // update auth plugin's logout button
@@ -134,6 +157,42 @@ ReactDOM.createRoot"##,
             "</AuthenticatedApolloProvider>\n      </AuthProvider>",
         )?;
 
+        // a subgraph: the `_service` and `_entities` queries, and the types' `@key`s from the
+        // entity resolvers (`#[graphql(entity)]`)
+        let mut federation = String::new();
+        if install_config.graphql_federation {
+            federation.push_str(
+                r#"
+        .enable_federation()"#,
+            );
+
+            fs::replace(
+                "backend/graphql/users.rs",
+                "impl UserQuery {\n",
+                r#"impl UserQuery {
+    /// resolves the `User` entities referenced by other subgraphs, by `id` (its `@key`); users only
+    /// resolve themselves, admins anyone
+    #[graphql(entity)]
+    async fn find_user_by_id(&self, ctx: &Context<'_>, id: i32) -> Result<User> {
+        if authenticated(ctx)?.user_id != id {
+            require_role(ctx, "admin")?;
+        }
+
+        ctx.data::<DataLoader<UserLoader>>()?
+            .load_one(id)
+            .await?
+            .ok_or_else(|| format!("User #{id} not found").into())
+    }
+
+"#,
+            )?;
+            fs::replace(
+                "backend/graphql/users.rs",
+                "use create_rust_app::graphql::RoleGuard;",
+                "use create_rust_app::graphql::{authenticated, require_role, RoleGuard};",
+            )?;
+        }
+
         match install_config.backend_framework {
             BackendFramework::ActixWeb => {
                 fs::replace(
@@ -148,7 +207,8 @@ use actix_web::guard;"##,
                     "backend/main.rs",
                     "app = app.app_data(Data::new(app_data.mailer.clone()));",
                     r#"app = app.app_data(Data::new(app_data.mailer.clone()));
-        app = app.app_data(Data::new(schema.clone()));"#,
+        app = app.app_data(Data::new(schema.clone()));
+        app = app.app_data(Data::new(persisted_queries.clone()));"#,
                 )?;
 
                 // GraphQL subscription endpoint
@@ -183,9 +243,14 @@ use actix_web::guard;"##,
                     &format!(
                         r##"create_rust_app::setup();
 
-    let schema = async_graphql::Schema::build(graphql::QueryRoot, graphql::MutationRoot, graphql::SubscriptionRoot)
+    // the allow-list in CRA_GRAPHQL_PERSISTED_QUERIES, or every query clients send
+    let persisted_queries = create_rust_app::graphql::PersistedQueries::from_env();
+
+    let schema = async_graphql::Schema::build(graphql::QueryRoot::default(), graphql::MutationRoot::default(), graphql::SubscriptionRoot)
+        .extension(persisted_queries.clone()){federation}
         .data(app_data.database.clone())
         .data(app_data.mailer.clone()){other_data}
+        .data(graphql::MessageBus::default())
         .finish();
 "##
                     ),
@@ -200,8 +265,35 @@ use actix_web::guard;"##,
                     r#"/* Development-only routes */"#,
                     r#"/* Development-only routes */
             // Mount the GraphQL playground on /graphql
-            app = app.route("/graphql", web::get().to(graphql::index_playground));"#,
+            app = app.route("/graphql", web::get().to(graphql::index_playground));
+            // the schema, for the frontend's codegen
+            app = app.route("/graphql/schema.graphql", web::get().to(graphql::index_sdl));
+            // the queries sent so far, to deploy as the persisted queries' allow-list
+            app = app.route("/graphql/persisted-queries.json", web::get().to(graphql::index_persisted_queries));"#,
                 )?;
+
+                if install_config.graphql_federation {
+                    // the plugin's files are renamed for the backend after the install
+                    fs::replace(
+                        "backend/graphql/mod.rs+actix_web",
+                        "/// the queries registered so far",
+                        r#"/// the subgraph's schema in SDL, with the federation's directives (ex: `@key`)
+pub async fn index_subgraph_sdl(schema: web::Data<GraphQLSchema>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(schema.federation_sdl())
+}
+
+/// the queries registered so far"#,
+                    )?;
+                    fs::replace(
+                        "backend/main.rs",
+                        r#"/* Development-only routes */"#,
+                        r#"/* Development-only routes */
+            // the subgraph's schema, to publish it to the router (ex: `rover subgraph publish`)
+            app = app.route("/graphql/subgraph.graphql", web::get().to(graphql::index_subgraph_sdl));"#,
+                    )?;
+                }
             }
             BackendFramework::Poem => {
                 // GraphQL Schema building
@@ -209,14 +301,21 @@ use actix_web::guard;"##,
                 fs::replace(
                     "backend/main.rs",
                     "create_rust_app::setup();",
-                    r##"create_rust_app::setup();
+                    &format!(
+                        r##"create_rust_app::setup();
 
-    let schema = async_graphql::Schema::build(graphql::QueryRoot, graphql::MutationRoot, graphql::SubscriptionRoot)
+    // the allow-list in CRA_GRAPHQL_PERSISTED_QUERIES, or every query clients send
+    let persisted_queries = create_rust_app::graphql::PersistedQueries::from_env();
+
+    let schema = async_graphql::Schema::build(graphql::QueryRoot::default(), graphql::MutationRoot::default(), graphql::SubscriptionRoot)
+        .extension(persisted_queries.clone()){federation}
         .data(data.database.clone())
         .data(data.mailer.clone())
         .data(data.storage.clone())
+        .data(graphql::MessageBus::default())
         .finish();
-"##,
+"##
+                    ),
                 )?;
 
                 // GraphQL subscription + query endpoints
@@ -238,16 +337,43 @@ use actix_web::guard;"##,
                     r#"/* Development-only routes */"#,
                     r#"/* Development-only routes */
             // Mount the GraphQL playground on /graphql
-        app = app.at("/graphql", poem::get(graphql::playground));"#,
+        app = app.at("/graphql", poem::get(graphql::playground));
+        // the schema, for the frontend's codegen
+        app = app.at("/graphql/schema.graphql", poem::get(graphql::sdl));
+        // the queries sent so far, to deploy as the persisted queries' allow-list
+        app = app.at("/graphql/persisted-queries.json", poem::get(graphql::persisted_queries));"#,
                 )?;
 
+                if install_config.graphql_federation {
+                    // the plugin's files are renamed for the backend after the install
+                    fs::replace(
+                        "backend/graphql/mod.rs+poem",
+                        "/// the queries registered so far",
+                        r#"/// the subgraph's schema in SDL, with the federation's directives (ex: `@key`)
+#[handler]
+pub async fn subgraph_sdl(schema: poem::web::Data<&GraphQLSchema>) -> String {
+    schema.federation_sdl()
+}
+
+/// the queries registered so far"#,
+                    )?;
+                    fs::replace(
+                        "backend/main.rs",
+                        r#"/* Development-only routes */"#,
+                        r#"/* Development-only routes */
+        // the subgraph's schema, to publish it to the router (ex: `rover subgraph publish`)
+        app = app.at("/graphql/subgraph.graphql", poem::get(graphql::subgraph_sdl));"#,
+                    )?;
+                }
+
                 // Adding Schema to exposed data
                 //
                 fs::replace(
                     "backend/main.rs",
                     ".with(AddData::new(data.database))",
                     ".with(AddData::new(data.database))
-                .with(AddData::new(schema))",
+                .with(AddData::new(schema))
+                .with(AddData::new(persisted_queries))",
                 )?;
             }
         };
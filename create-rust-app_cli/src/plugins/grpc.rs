@@ -0,0 +1,116 @@
+use crate::content::cargo_toml::add_dependency;
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Grpc {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-grpc"]
+struct Asset;
+
+impl Plugin for Grpc {
+    fn name(&self) -> &'static str {
+        "gRPC"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        add_dependency(&install_config.project_dir, "tonic", r#"tonic = "0.10.2""#)?;
+        add_dependency(&install_config.project_dir, "prost", r#"prost = "0.12.1""#)?;
+        fs::append(
+            "Cargo.toml",
+            r#"
+[build-dependencies]
+tonic-build = "0.10.2"
+"#,
+        )?;
+
+        // compiles every proto/*.proto before the backend
+        fs::replace(
+            "build.rs",
+            "fn main() {\n    build_info();\n",
+            r#"/// generates the gRPC services of `proto/*.proto`, see `backend/grpc`
+fn grpc() {
+    let protos = std::fs::read_dir("proto")
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().map_or(false, |extension| extension == "proto"))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    tonic_build::configure()
+        // `rerun-if-changed` would stop the build info from being refreshed
+        .emit_rerun_if_changed(false)
+        .compile(&protos, &["proto"])
+        .expect("Could not generate the gRPC services (is `protoc` installed?)");
+}
+
+fn main() {
+    build_info();
+    grpc();
+"#,
+        )?;
+
+        if install_config.plugin_auth {
+            fs::replace(
+                "backend/grpc/mod.rs",
+                "/* CRA: layers */",
+                r#"// authenticates the requests sent with an access token, see `create_rust_app::grpc::auth`
+        .layer(tonic::service::interceptor(
+            create_rust_app::grpc::AuthInterceptor::default(),
+        ))"#,
+            )?;
+        }
+
+        // the todos' service, like the ones `create-rust-app configure` adds for new resources
+        crate::content::grpc::create("Todo")?;
+
+        fs::replace("backend/main.rs", "mod mail;", "mod mail;\nmod grpc;")?;
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    actix_web::rt::spawn(grpc::serve(app_data.database.clone()));"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    tokio::spawn(grpc::serve(data.database.clone()));"#,
+                )?;
+            }
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# where the gRPC server listens
+#GRPC_ADDRESS=0.0.0.0:50051
+"#,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct I18n {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-i18n"]
+struct Asset;
+
+impl Plugin for I18n {
+    fn name(&self) -> &'static str {
+        "I18n"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "i18n",
+                    r#"create_rust_app::i18n::endpoints(web::scope("/i18n"))"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "i18n",
+                    "create_rust_app::i18n::api()",
+                    "/i18n",
+                )?;
+            }
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# the locale of the messages when the request's can't be negotiated; the catalogs are the
+# `<locale>.ftl` files of backend/locales (or CRA_LOCALES_DIR)
+CRA_DEFAULT_LOCALE=en
+"#,
+        )?;
+
+        // around the router, so it nests inside the providers wrapped at the CRA markers
+        fs::prepend(
+            "frontend/bundles/index.tsx",
+            "import { I18nProvider } from '../src/hooks/useI18n'",
+        )?;
+        fs::replace(
+            "frontend/bundles/index.tsx",
+            "<BrowserRouter>",
+            "<I18nProvider>\n<BrowserRouter>",
+        )?;
+        fs::replace(
+            "frontend/bundles/index.tsx",
+            "</BrowserRouter>",
+            "</BrowserRouter>\n</I18nProvider>",
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::logger;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+
+pub struct MailLog {}
+
+impl Plugin for MailLog {
+    fn name(&self) -> &'static str {
+        "MailLog"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Mail Log plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        crate::content::migration::create(
+            "plugin_mail_log",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE emails (
+        id SERIAL PRIMARY KEY,
+        recipient TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        template TEXT,
+        status TEXT NOT NULL,
+        provider_message_id TEXT,
+        error TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX emails_recipient_index ON emails(recipient);
+      CREATE INDEX emails_created_at_index ON emails(created_at);
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE emails (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        recipient TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        template TEXT,
+        status TEXT NOT NULL,
+        provider_message_id TEXT,
+        error TEXT,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX emails_recipient_index ON emails(recipient);
+      CREATE INDEX emails_created_at_index ON emails(created_at);
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE emails;
+    "#},
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "mail-log",
+                    r#"create_rust_app::mail_log::endpoints(web::scope("/mail-log"))"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "mail-log",
+                    "create_rust_app::mail_log::api()",
+                    "/mail-log",
+                )?;
+            }
+        };
+
+        Ok(())
+    }
+}
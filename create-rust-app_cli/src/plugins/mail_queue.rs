@@ -0,0 +1,101 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+
+pub struct MailQueue {}
+
+impl Plugin for MailQueue {
+    fn name(&self) -> &'static str {
+        "MailQueue"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Mail Queue plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        crate::content::migration::create(
+            "plugin_mail_queue",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE mail_queue (
+        id SERIAL PRIMARY KEY,
+        to_address TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        text_body TEXT NOT NULL,
+        html_body TEXT NOT NULL,
+        template TEXT,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        sent_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX mail_queue_due_index ON mail_queue(next_attempt_at) WHERE status = 'pending';
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE mail_queue (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        to_address TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        text_body TEXT NOT NULL,
+        html_body TEXT NOT NULL,
+        template TEXT,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        next_attempt_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        sent_at DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX mail_queue_due_index ON mail_queue(next_attempt_at) WHERE status = 'pending';
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE mail_queue;
+    "#},
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "mail-queue",
+                    r#"create_rust_app::mail_queue::endpoints(web::scope("/mail-queue"))"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    // delivers the emails queued by `app_data.mailer`
+    create_rust_app::mail_queue::spawn_worker(app_data.database.clone(), Default::default());"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "mail-queue",
+                    "create_rust_app::mail_queue::api()",
+                    "/mail-queue",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    // delivers the emails queued by `data.mailer`
+    create_rust_app::mail_queue::spawn_worker(data.database.clone(), Default::default());"#,
+                )?;
+            }
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+
+pub struct Metering {}
+
+impl Plugin for Metering {
+    fn name(&self) -> &'static str {
+        "Metering"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        crate::content::migration::create(
+            "plugin_metering",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE usage_events (
+        id SERIAL PRIMARY KEY,
+        idempotency_key TEXT NOT NULL UNIQUE,
+        subject TEXT NOT NULL,
+        metric TEXT NOT NULL,
+        quantity BIGINT NOT NULL,
+        recorded_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX usage_events_recorded_at_index ON usage_events(recorded_at);
+
+      CREATE TABLE usage_aggregates (
+        subject TEXT NOT NULL,
+        metric TEXT NOT NULL,
+        period_start TIMESTAMPTZ NOT NULL,
+        quantity BIGINT NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (subject, metric, period_start)
+      );
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE usage_events (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        idempotency_key TEXT NOT NULL UNIQUE,
+        subject TEXT NOT NULL,
+        metric TEXT NOT NULL,
+        quantity BIGINT NOT NULL,
+        recorded_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX usage_events_recorded_at_index ON usage_events(recorded_at);
+
+      CREATE TABLE usage_aggregates (
+        subject TEXT NOT NULL,
+        metric TEXT NOT NULL,
+        period_start DATETIME NOT NULL,
+        quantity BIGINT NOT NULL,
+        updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (subject, metric, period_start)
+      );
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE usage_aggregates;
+      DROP TABLE usage_events;
+    "#},
+        )?;
+
+        // the usage endpoints are scoped to the authenticated user
+        if install_config.plugin_auth {
+            match install_config.backend_framework {
+                BackendFramework::ActixWeb => crate::content::service::register_actix(
+                    "usage",
+                    r#"create_rust_app::metering::endpoints(web::scope("/usage"))"#,
+                )?,
+                BackendFramework::Poem => crate::content::service::register_poem(
+                    "usage",
+                    "create_rust_app::metering::api()",
+                    "/usage",
+                )?,
+            };
+        }
+
+        if install_config.plugin_tasks {
+            fs::replace(
+                "backend/main.rs",
+                "    let queue = create_rust_app::tasks::queue();",
+                r#"    let queue = create_rust_app::tasks::queue();
+    // roll usage events up into daily totals every hour
+    queue.schedule_task(&create_rust_app::metering::AggregateUsage {}).unwrap();"#,
+            )?;
+        } else {
+            logger::message("Metering: call `create_rust_app::metering::aggregate()` periodically (or add the tasks plugin) to compute usage totals.");
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,40 @@
+pub mod admin;
+pub mod audit;
 pub mod auth;
+pub mod broker;
+pub mod cache;
+pub mod consent;
 pub mod container;
+pub mod desktop;
 pub mod dev;
+pub mod events;
+pub mod flags;
+pub mod geo;
 pub mod graphql;
+pub mod grpc;
+pub mod i18n;
+pub mod mail_log;
+pub mod mail_queue;
+pub mod metering;
+pub mod notifications;
+pub mod observability;
+pub mod outbox;
+pub mod payments;
+pub mod prometheus;
+pub mod pwa;
+pub mod rate_limit;
+pub mod realtime;
+pub mod referrals;
+pub mod rpc;
+pub mod scheduler;
+pub mod search;
+pub mod sentry;
+pub mod soft_delete;
 pub mod storage;
 pub mod tasks;
+pub mod tenancy;
 pub mod utoipa;
+pub mod webhooks;
 
 use crate::{project, BackendFramework};
 use crate::{utils::logger, BackendDatabase};
@@ -24,6 +54,38 @@ pub struct InstallConfig {
     pub plugin_tasks: bool,
     pub plugin_graphql: bool,
     pub plugin_utoipa: bool,
+    pub plugin_metering: bool,
+    pub plugin_consent: bool,
+    pub plugin_notifications: bool,
+    pub plugin_referrals: bool,
+    pub plugin_admin: bool,
+    pub plugin_mail_queue: bool,
+    pub plugin_mail_log: bool,
+    pub plugin_audit: bool,
+    pub plugin_scheduler: bool,
+    pub plugin_realtime: bool,
+    pub plugin_cache: bool,
+    pub plugin_rate_limit: bool,
+    pub plugin_i18n: bool,
+    pub plugin_payments: bool,
+    pub plugin_search: bool,
+    pub plugin_flags: bool,
+    pub plugin_tenancy: bool,
+    pub plugin_webhooks: bool,
+    pub plugin_observability: bool,
+    pub plugin_prometheus: bool,
+    pub plugin_sentry: bool,
+    pub plugin_grpc: bool,
+    pub plugin_rpc: bool,
+    pub plugin_pwa: bool,
+    pub plugin_desktop: bool,
+    pub plugin_soft_delete: bool,
+    pub plugin_events: bool,
+    pub plugin_outbox: bool,
+    pub plugin_broker: bool,
+    pub plugin_geo: bool,
+    /// scaffold the GraphQL plugin as an Apollo Federation subgraph
+    pub graphql_federation: bool,
 }
 
 pub trait Plugin {
@@ -0,0 +1,161 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Notifications {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-notifications"]
+struct Asset;
+
+impl Plugin for Notifications {
+    fn name(&self) -> &'static str {
+        "Notifications"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Notifications plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        fs::prepend(
+            "frontend/src/App.tsx",
+            "import { NotificationBell } from './components/NotificationBell'",
+        )?;
+
+        fs::replace(
+            "frontend/src/App.tsx",
+            "{/* CRA: right-aligned nav buttons */}",
+            r#"{/* CRA: right-aligned nav buttons */}
+          { auth.isAuthenticated && <NotificationBell /> }"#,
+        )?;
+
+        crate::content::migration::create(
+            "plugin_notifications",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE notifications (
+        id SERIAL PRIMARY KEY,
+        user_id SERIAL NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        link TEXT,
+        read_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX notifications_unread_index ON notifications(user_id, created_at) WHERE read_at IS NULL;
+
+      CREATE TABLE notification_preferences (
+        user_id SERIAL PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+        digest_frequency TEXT NOT NULL DEFAULT 'daily',
+        last_digest_at TIMESTAMPTZ
+      );
+
+      CREATE TABLE push_subscriptions (
+        id SERIAL PRIMARY KEY,
+        user_id SERIAL NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        endpoint TEXT NOT NULL UNIQUE,
+        p256dh TEXT NOT NULL,
+        auth TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX push_subscriptions_user_id_index ON push_subscriptions(user_id);
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE notifications (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        link TEXT,
+        read_at DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX notifications_unread_index ON notifications(user_id, created_at) WHERE read_at IS NULL;
+
+      CREATE TABLE notification_preferences (
+        user_id INTEGER PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        digest_frequency TEXT NOT NULL DEFAULT 'daily',
+        last_digest_at DATETIME
+      );
+
+      CREATE TABLE push_subscriptions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        endpoint TEXT NOT NULL UNIQUE,
+        p256dh TEXT NOT NULL,
+        auth TEXT NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX push_subscriptions_user_id_index ON push_subscriptions(user_id);
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE push_subscriptions;
+      DROP TABLE notification_preferences;
+      DROP TABLE notifications;
+    "#},
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => crate::content::service::register_actix(
+                "notifications",
+                r#"create_rust_app::notifications::endpoints(web::scope("/notifications"))"#,
+            )?,
+            BackendFramework::Poem => crate::content::service::register_poem(
+                "notifications",
+                "create_rust_app::notifications::api()",
+                "/notifications",
+            )?,
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# the VAPID private key web pushes are signed with (generate a key pair with
+# `npx web-push generate-vapid-keys`), and a contact for the push services
+#VAPID_PRIVATE_KEY=
+#VAPID_SUBJECT=mailto:admin@example.com
+"#,
+        )?;
+
+        if install_config.plugin_tasks {
+            fs::replace(
+                "backend/main.rs",
+                "    let queue = create_rust_app::tasks::queue();",
+                r#"    let queue = create_rust_app::tasks::queue();
+    // email digests of unread notifications every hour
+    queue.schedule_task(&create_rust_app::notifications::SendDigests { base_url: None }).unwrap();"#,
+            )?;
+        } else {
+            logger::message("Notifications: call `create_rust_app::notifications::digest::send_digests()` periodically (or add the tasks plugin) to email notification digests.");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use crate::content::cargo_toml::add_dependency;
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Observability {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-observability"]
+struct Asset;
+
+impl Plugin for Observability {
+    fn name(&self) -> &'static str {
+        "Observability"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        add_dependency(
+            &install_config.project_dir,
+            "tracing",
+            r#"tracing = "0.1.37""#,
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    // logs, and exports the traces when OTEL_EXPORTER_OTLP_ENDPOINT is set
+    let _observability = create_rust_app::observability::init(env!("CARGO_PKG_NAME"));"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    ".wrap(Logger::default())",
+                    r#".wrap(Logger::default())
+            .wrap(create_rust_app::observability::TraceContext)"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                // replaced by the observability plugin's subscriber and middleware
+                fs::replace(
+                    "backend/main.rs",
+                    "    tracing_subscriber::fmt::init();\n",
+                    "",
+                )?;
+                fs::replace(
+                    "backend/main.rs",
+                    "CookieJarManager, Tracing}",
+                    "CookieJarManager}",
+                )?;
+                fs::replace(
+                    "backend/main.rs",
+                    ".with(Tracing)",
+                    ".with(create_rust_app::observability::TraceContext)",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    // logs, and exports the traces when OTEL_EXPORTER_OTLP_ENDPOINT is set
+    let _observability = create_rust_app::observability::init(env!("CARGO_PKG_NAME"));"#,
+                )?;
+            }
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# export the traces to an OpenTelemetry collector (OTLP over gRPC)
+#OTEL_EXPORTER_OTLP_ENDPOINT=http://localhost:4317
+#OTEL_SERVICE_NAME=app
+#RUST_LOG=info,create_rust_app=debug
+"#,
+        )?;
+
+        Ok(())
+    }
+}
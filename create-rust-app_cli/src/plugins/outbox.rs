@@ -0,0 +1,88 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+
+pub struct Outbox {}
+
+impl Plugin for Outbox {
+    fn name(&self) -> &'static str {
+        "Outbox"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        crate::content::migration::create(
+            "plugin_outbox",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE outbox_messages (
+        id SERIAL PRIMARY KEY,
+        kind TEXT NOT NULL,
+        topic TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        delivered_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX outbox_messages_due_index ON outbox_messages(next_attempt_at) WHERE status = 'pending';
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE outbox_messages (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        kind TEXT NOT NULL,
+        topic TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        next_attempt_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        delivered_at DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX outbox_messages_due_index ON outbox_messages(next_attempt_at) WHERE status = 'pending';
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE outbox_messages;
+    "#},
+        )?;
+
+        // the relay starts after the event bus' subscribers are registered
+        let (setup, app_data) = match install_config.backend_framework {
+            BackendFramework::ActixWeb => ("let app_data = create_rust_app::setup();", "app_data"),
+            BackendFramework::Poem => ("let data = create_rust_app::setup();", "data"),
+        };
+        let anchor = if install_config.plugin_events {
+            format!("{setup}\n    events::subscribe();")
+        } else {
+            setup.to_string()
+        };
+        let events = if install_config.plugin_events {
+            "\n        .event::<events::TodoCreated>()"
+        } else {
+            ""
+        };
+
+        fs::replace(
+            "backend/main.rs",
+            &anchor,
+            &format!(
+                r#"{anchor}
+    // delivers the events, webhooks and emails recorded in the outbox once their transaction
+    // is committed (register the events published through it with `.event::<...>()`)
+    create_rust_app::outbox::Relay::new(Default::default()){events}
+        .mailer({app_data}.mailer.clone())
+        .spawn({app_data}.database.clone());"#
+            ),
+        )?;
+
+        Ok(())
+    }
+}
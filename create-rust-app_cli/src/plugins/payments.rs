@@ -0,0 +1,139 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Payments {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-payments"]
+struct Asset;
+
+impl Plugin for Payments {
+    fn name(&self) -> &'static str {
+        "Payments"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            crate::logger::error("The Payments plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        // ===============================
+        // PATCH FRONTEND
+        // ===============================
+
+        fs::prepend(
+            "frontend/src/App.tsx",
+            r#"import { BillingPage } from './containers/BillingPage'"#,
+        )?;
+
+        fs::replace(
+            "frontend/src/App.tsx",
+            r#"{/* CRA: routes */}"#,
+            r#"{/* CRA: routes */}
+            <Route path="/billing" element={<BillingPage />} />"#,
+        )?;
+
+        fs::replace(
+            "frontend/src/App.tsx",
+            "{/* CRA: left-aligned nav buttons */}",
+            r#"{/* CRA: left-aligned nav buttons */}
+          { auth.isAuthenticated && <a className="NavButton" onClick={() => navigate('/billing')}>Billing</a> }"#,
+        )?;
+
+        fs::append(
+            ".env.example",
+            r#"
+# Stripe's secret key, the signing secret of the webhook endpoint (/api/payments/webhook), and the
+# prices users can subscribe to (comma separated, the first one is the default)
+STRIPE_SECRET_KEY=sk_test_...
+STRIPE_WEBHOOK_SECRET=whsec_...
+STRIPE_PRICE_IDS=price_...
+"#,
+        )?;
+
+        crate::content::migration::create(
+            "plugin_payments",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE stripe_customers (
+        user_id SERIAL PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+        customer_id TEXT NOT NULL UNIQUE,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE stripe_subscriptions (
+        id SERIAL PRIMARY KEY,
+        user_id SERIAL NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        subscription_id TEXT NOT NULL UNIQUE,
+        price_id TEXT,
+        status TEXT NOT NULL,
+        current_period_end TIMESTAMPTZ,
+        cancel_at_period_end BOOLEAN NOT NULL DEFAULT FALSE,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX stripe_subscriptions_user_index ON stripe_subscriptions(user_id);
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE stripe_customers (
+        user_id INTEGER PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        customer_id TEXT NOT NULL UNIQUE,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE stripe_subscriptions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        subscription_id TEXT NOT NULL UNIQUE,
+        price_id TEXT,
+        status TEXT NOT NULL,
+        current_period_end DATETIME,
+        cancel_at_period_end BOOLEAN NOT NULL DEFAULT FALSE,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX stripe_subscriptions_user_index ON stripe_subscriptions(user_id);
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE stripe_subscriptions;
+      DROP TABLE stripe_customers;
+    "#},
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => crate::content::service::register_actix(
+                "payments",
+                r#"create_rust_app::payments::endpoints(web::scope("/payments"))"#,
+            )?,
+            BackendFramework::Poem => crate::content::service::register_poem(
+                "payments",
+                "create_rust_app::payments::api()",
+                "/payments",
+            )?,
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Prometheus {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-prometheus"]
+struct Asset;
+
+impl Plugin for Prometheus {
+    fn name(&self) -> &'static str {
+        "Prometheus"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if install_config.plugin_container {
+            for filename in Asset::iter() {
+                let file_contents = Asset::get(filename.as_ref()).unwrap();
+                let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+                file_path.push(filename.as_ref());
+                let mut directory_path = std::path::PathBuf::from(&file_path);
+                directory_path.pop();
+
+                add_file_msg(filename.as_ref());
+                std::fs::create_dir_all(directory_path)?;
+                std::fs::write(file_path, file_contents.data)?;
+            }
+
+            // the container plugin's docker-compose.yml runs the app and its services
+            fs::replace(
+                "docker-compose.yml",
+                "\nvolumes:\n",
+                r#"
+  # Prometheus scraping the app's metrics and Grafana graphing them
+  # Prometheus: http://localhost:9090, Grafana: http://localhost:3001 (admin/admin, see the
+  # "App" dashboard)
+  prometheus:
+    image: prom/prometheus:v2.47.0
+    volumes:
+      - ./monitoring/prometheus.yml:/etc/prometheus/prometheus.yml:ro
+      - prometheus-data:/prometheus
+    ports:
+      - "9090:9090"
+    depends_on:
+      - app
+
+  grafana:
+    image: grafana/grafana:10.1.2
+    environment:
+      GF_SECURITY_ADMIN_USER: admin
+      GF_SECURITY_ADMIN_PASSWORD: admin
+    volumes:
+      - ./monitoring/grafana/provisioning:/etc/grafana/provisioning:ro
+      - ./monitoring/grafana/dashboards:/var/lib/grafana/dashboards:ro
+      - grafana-data:/var/lib/grafana
+    ports:
+      - "3001:3000"
+    depends_on:
+      - prometheus
+
+volumes:
+  prometheus-data:
+  grafana-data:
+"#,
+            )?;
+        }
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "metrics",
+                    r#"create_rust_app::prometheus::endpoints(web::scope("/metrics"))"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    ".wrap(Logger::default())",
+                    r#".wrap(Logger::default())
+            .wrap(create_rust_app::prometheus::HttpMetrics)"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "metrics",
+                    "create_rust_app::prometheus::api()",
+                    "/metrics",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    ".with(CookieJarManager::new())",
+                    r#".with(CookieJarManager::new())
+                .with(create_rust_app::prometheus::HttpMetrics)"#,
+                )?;
+            }
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# require `Authorization: Bearer <METRICS_TOKEN>` to scrape /api/metrics
+#METRICS_TOKEN=
+"#,
+        )?;
+
+        Ok(())
+    }
+}
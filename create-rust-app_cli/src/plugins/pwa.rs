@@ -0,0 +1,110 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Pwa {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-pwa"]
+struct Asset;
+
+impl Plugin for Pwa {
+    fn name(&self) -> &'static str {
+        "Pwa"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        // the manifest is generated from `frontend/pwa.config.ts` now
+        let mut static_manifest = std::path::PathBuf::from(&install_config.project_dir);
+        static_manifest.push("frontend/public/pwa.json");
+        if static_manifest.exists() {
+            logger::remove_file_msg(&format!("{:#?}", &static_manifest));
+            std::fs::remove_file(static_manifest)?;
+        }
+        fs::replace(
+            "backend/views/index.html",
+            r#"<link rel="manifest" href="/pwa.json"/>"#,
+            r##"<link rel="manifest" href="/manifest.webmanifest"/>
+    <meta name="theme-color" content="#000000"/>"##,
+        )?;
+
+        fs::replace(
+            "frontend/package.json",
+            r##""devDependencies": {"##,
+            r##""devDependencies": {
+    "vite-plugin-pwa": "^0.16.5",
+    "workbox-core": "^7.0.0",
+    "workbox-expiration": "^7.0.0",
+    "workbox-precaching": "^7.0.0",
+    "workbox-routing": "^7.0.0",
+    "workbox-strategies": "^7.0.0","##,
+        )?;
+        fs::replace(
+            "frontend/vite.config.ts",
+            "import { defineConfig } from 'vite'",
+            "import { defineConfig } from 'vite'\nimport { pwa } from './pwa.config'",
+        )?;
+        fs::replace(
+            "frontend/vite.config.ts",
+            "        react(),\n",
+            "        react(),\n        pwa(),\n",
+        )?;
+
+        fs::prepend(
+            "frontend/bundles/index.tsx",
+            "import { registerServiceWorker } from '../src/pwa'",
+        )?;
+        fs::append("frontend/bundles/index.tsx", "\nregisterServiceWorker()")?;
+
+        // the API responses cached while the user was signed in are theirs
+        if install_config.plugin_auth {
+            fs::prepend(
+                "frontend/src/hooks/useAuth.tsx",
+                "import { clearApiCache } from '../pwa'",
+            )?;
+            fs::replace(
+                "frontend/src/hooks/useAuth.tsx",
+                "    // sending the access token revokes it\n",
+                "    await clearApiCache()\n    // sending the access token revokes it\n",
+            )?;
+        }
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                fs::replace(
+                    "backend/main.rs",
+                    ".wrap(Logger::default())",
+                    r#".wrap(Logger::default())
+            .wrap(create_rust_app::pwa::CacheHeaders)"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                fs::replace(
+                    "backend/main.rs",
+                    ".with(CookieJarManager::new())",
+                    r#".with(CookieJarManager::new())
+                .with(create_rust_app::pwa::CacheHeaders)"#,
+                )?;
+            }
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct RateLimit {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-rate-limit"]
+struct Asset;
+
+impl Plugin for RateLimit {
+    fn name(&self) -> &'static str {
+        "RateLimit"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Rate Limit plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        // the auth endpoints are rate limited by default
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                fs::replace(
+                    "backend/main.rs",
+                    r#"create_rust_app::auth::endpoints(web::scope("/auth"))"#,
+                    r#"create_rust_app::auth::endpoints(web::scope("/auth")).wrap(rate_limits::auth())"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                fs::replace(
+                    "backend/main.rs",
+                    "create_rust_app::auth::api()",
+                    "create_rust_app::auth::api().with(rate_limits::auth())",
+                )?;
+            }
+        };
+
+        fs::replace(
+            "backend/main.rs",
+            "mod mail;",
+            "mod mail;\nmod rate_limits;",
+        )?;
+
+        Ok(())
+    }
+}
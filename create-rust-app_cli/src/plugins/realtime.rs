@@ -0,0 +1,75 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Realtime {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-realtime"]
+struct Asset;
+
+impl Plugin for Realtime {
+    fn name(&self) -> &'static str {
+        "Realtime"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Realtime plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "realtime",
+                    r#"create_rust_app::realtime::endpoints(web::scope("/realtime"))"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "realtime",
+                    "create_rust_app::realtime::api()",
+                    "/realtime",
+                )?;
+            }
+        };
+
+        fs::replace("backend/main.rs", "mod mail;", "mod mail;\nmod realtime;")?;
+
+        // connected while the user is logged in, so it goes inside the auth provider
+        fs::prepend(
+            "frontend/bundles/index.tsx",
+            "import { RealtimeProvider } from '../src/hooks/useRealtime'",
+        )?;
+        fs::replace(
+            "frontend/bundles/index.tsx",
+            "<AuthProvider>",
+            "<AuthProvider>\n        <RealtimeProvider>",
+        )?;
+        fs::replace(
+            "frontend/bundles/index.tsx",
+            "</AuthProvider>",
+            "</RealtimeProvider>\n      </AuthProvider>",
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Referrals {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-referrals"]
+struct Asset;
+
+impl Plugin for Referrals {
+    fn name(&self) -> &'static str {
+        "Referrals"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            crate::logger::error("The Referrals plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        // ===============================
+        // PATCH FRONTEND
+        // ===============================
+
+        fs::prepend(
+            "frontend/src/App.tsx",
+            r#"import { useReferralAttribution } from './hooks/useReferral'
+import { ReferralsPage } from './containers/ReferralsPage'"#,
+        )?;
+
+        fs::replace(
+            "frontend/src/App.tsx",
+            "/* CRA: app hooks */",
+            "/* CRA: app hooks */\n  useReferralAttribution()",
+        )?;
+
+        fs::replace(
+            "frontend/src/App.tsx",
+            r#"{/* CRA: routes */}"#,
+            r#"{/* CRA: routes */}
+            <Route path="/referrals" element={<ReferralsPage />} />"#,
+        )?;
+
+        fs::replace(
+            "frontend/src/App.tsx",
+            "{/* CRA: left-aligned nav buttons */}",
+            r#"{/* CRA: left-aligned nav buttons */}
+          { auth.isAuthenticated && <a className="NavButton" onClick={() => navigate('/referrals')}>Invite</a> }"#,
+        )?;
+
+        crate::content::migration::create(
+            "plugin_referrals",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE referral_codes (
+        user_id SERIAL PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+        code TEXT NOT NULL UNIQUE,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE referrals (
+        id SERIAL PRIMARY KEY,
+        referrer_id SERIAL NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        referred_user_id SERIAL NOT NULL UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+        code TEXT NOT NULL,
+        rewarded_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX referrals_referrer_index ON referrals(referrer_id);
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE referral_codes (
+        user_id INTEGER PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        code TEXT NOT NULL UNIQUE,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE referrals (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        referrer_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        referred_user_id INTEGER NOT NULL UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+        code TEXT NOT NULL,
+        rewarded_at DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX referrals_referrer_index ON referrals(referrer_id);
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE referrals;
+      DROP TABLE referral_codes;
+    "#},
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => crate::content::service::register_actix(
+                "referrals",
+                r#"create_rust_app::referrals::endpoints(web::scope("/referrals"))"#,
+            )?,
+            BackendFramework::Poem => crate::content::service::register_poem(
+                "referrals",
+                "create_rust_app::referrals::api()",
+                "/referrals",
+            )?,
+        };
+
+        Ok(())
+    }
+}
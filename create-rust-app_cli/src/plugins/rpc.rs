@@ -0,0 +1,102 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Rpc {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-rpc"]
+struct Asset;
+
+impl Plugin for Rpc {
+    fn name(&self) -> &'static str {
+        "Rpc"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        fs::replace("backend/main.rs", "mod mail;", "mod mail;\nmod rpc;")?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    // the procedures of `rpc.rs`
+    let rpc = rpc::router();"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "app = app.app_data(Data::new(app_data.mailer.clone()));",
+                    r#"app = app.app_data(Data::new(app_data.mailer.clone()));
+        app = app.app_data(Data::new(rpc.clone()));"#,
+                )?;
+
+                crate::content::service::register_actix(
+                    "rpc",
+                    r#"create_rust_app::rpc::endpoints(web::scope("/rpc"))"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    // the procedures of `rpc.rs`
+    let rpc = rpc::router();"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    ".with(AddData::new(data.database))",
+                    ".with(AddData::new(data.database))
+                .with(AddData::new(rpc))",
+                )?;
+
+                crate::content::service::register_poem(
+                    "rpc",
+                    "create_rust_app::rpc::api()",
+                    "/rpc",
+                )?;
+            }
+        };
+
+        // the procedures get the `Auth` of the requests sent with an access token
+        if install_config.plugin_auth {
+            fs::replace(
+                "frontend/src/hooks/useRpc.ts",
+                "import { createRpcClient } from '../rpc.generated'",
+                "import { createRpcClient } from '../rpc.generated'\nimport { useAuth } from './useAuth'",
+            )?;
+            fs::replace(
+                "frontend/src/hooks/useRpc.ts",
+                "  return useMemo(() => createRpcClient(), [])",
+                r#"  const auth = useAuth()
+
+  return useMemo(
+    () => createRpcClient({ accessToken: auth.accessToken }),
+    [auth.accessToken]
+  )"#,
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Scheduler {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-scheduler"]
+struct Asset;
+
+impl Plugin for Scheduler {
+    fn name(&self) -> &'static str {
+        "Scheduler"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Scheduler plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        crate::content::migration::create(
+            "plugin_scheduler",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE scheduled_tasks (
+        id SERIAL PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        cron TEXT NOT NULL,
+        next_run_at TIMESTAMPTZ NOT NULL,
+        locked_until TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE scheduled_task_runs (
+        id SERIAL PRIMARY KEY,
+        task_name TEXT NOT NULL,
+        scheduled_for TIMESTAMPTZ NOT NULL,
+        status TEXT NOT NULL,
+        error TEXT,
+        started_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        finished_at TIMESTAMPTZ
+      );
+
+      CREATE INDEX scheduled_task_runs_task_name_index ON scheduled_task_runs(task_name, started_at);
+      CREATE INDEX scheduled_task_runs_started_at_index ON scheduled_task_runs(started_at);
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE scheduled_tasks (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        name TEXT NOT NULL UNIQUE,
+        cron TEXT NOT NULL,
+        next_run_at DATETIME NOT NULL,
+        locked_until DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE scheduled_task_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        task_name TEXT NOT NULL,
+        scheduled_for DATETIME NOT NULL,
+        status TEXT NOT NULL,
+        error TEXT,
+        started_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        finished_at DATETIME
+      );
+
+      CREATE INDEX scheduled_task_runs_task_name_index ON scheduled_task_runs(task_name, started_at);
+      CREATE INDEX scheduled_task_runs_started_at_index ON scheduled_task_runs(started_at);
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE scheduled_task_runs;
+      DROP TABLE scheduled_tasks;
+    "#},
+        )?;
+
+        fs::replace("backend/main.rs", "mod mail;", "mod mail;\nmod schedules;")?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "scheduler",
+                    r#"create_rust_app::scheduler::endpoints(web::scope("/scheduler"))"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    // runs the jobs of `schedules.rs`, unless the `scheduler` binary does (`CRA_SCHEDULER=worker`)
+    if std::env::var("CRA_SCHEDULER").as_deref() != Ok("worker") {
+        schedules::scheduler(app_data.database.clone()).spawn();
+    }"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "scheduler",
+                    "create_rust_app::scheduler::api()",
+                    "/scheduler",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    // runs the jobs of `schedules.rs`, unless the `scheduler` binary does (`CRA_SCHEDULER=worker`)
+    if std::env::var("CRA_SCHEDULER").as_deref() != Ok("worker") {
+        schedules::scheduler(data.database.clone()).spawn();
+    }"#,
+                )?;
+            }
+        };
+
+        Ok(())
+    }
+}
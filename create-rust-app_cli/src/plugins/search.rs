@@ -0,0 +1,97 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Search {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-search"]
+struct Asset;
+
+impl Plugin for Search {
+    fn name(&self) -> &'static str {
+        "Search"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Search plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        match install_config.backend_database {
+            BackendDatabase::Postgres => {
+                crate::content::migration::create(
+                    "plugin_search",
+                    indoc! {r#"
+      CREATE TABLE search_documents (
+        index_name TEXT NOT NULL,
+        document_id TEXT NOT NULL,
+        owner_id INTEGER,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        data TEXT NOT NULL,
+        search_vector TSVECTOR GENERATED ALWAYS AS (
+          setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', body), 'B')
+        ) STORED,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (index_name, document_id)
+      );
+
+      CREATE INDEX search_documents_vector_index ON search_documents USING GIN (search_vector);
+    "#},
+                    indoc! {r#"
+      DROP TABLE search_documents;
+    "#},
+                )?;
+            }
+            BackendDatabase::Sqlite => {
+                // full-text search needs Postgres' tsvector, or Meilisearch
+                logger::message(
+                    "Search: with SQLite, documents are indexed in Meilisearch, set MEILISEARCH_URL in .env",
+                );
+            }
+        };
+
+        fs::append(
+            ".env.example",
+            r#"
+# index documents in Meilisearch instead of the database (required with SQLite)
+#MEILISEARCH_URL=http://127.0.0.1:7700
+#MEILISEARCH_API_KEY=
+"#,
+        )?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => crate::content::service::register_actix(
+                "search",
+                r#"create_rust_app::search::endpoints(web::scope("/search"))"#,
+            )?,
+            BackendFramework::Poem => crate::content::service::register_poem(
+                "search",
+                "create_rust_app::search::api()",
+                "/search",
+            )?,
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger::add_file_msg;
+use crate::BackendFramework;
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+pub struct Sentry {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-sentry"]
+struct Asset;
+
+impl Plugin for Sentry {
+    fn name(&self) -> &'static str {
+        "Sentry"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    // reports errors when SENTRY_DSN is set
+    let _sentry = create_rust_app::sentry::init(concat!(env!("CARGO_PKG_NAME"), "@", env!("CARGO_PKG_VERSION")));"#,
+                )?;
+
+                crate::content::service::register_actix(
+                    "sentry",
+                    r#"create_rust_app::sentry::endpoints(web::scope("/sentry"))"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    ".wrap(Logger::default())",
+                    r#".wrap(Logger::default())
+            .wrap(create_rust_app::sentry::ErrorCapture)"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    // reports errors when SENTRY_DSN is set
+    let _sentry = create_rust_app::sentry::init(concat!(env!("CARGO_PKG_NAME"), "@", env!("CARGO_PKG_VERSION")));"#,
+                )?;
+
+                crate::content::service::register_poem(
+                    "sentry",
+                    "create_rust_app::sentry::api()",
+                    "/sentry",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    ".with(CookieJarManager::new())",
+                    r#".with(CookieJarManager::new())
+                .with(create_rust_app::sentry::ErrorCapture)"#,
+                )?;
+            }
+        };
+
+        fs::replace(
+            "frontend/package.json",
+            r##""dependencies": {"##,
+            r##""dependencies": {
+    "@sentry/react": "^7.73.0","##,
+        )?;
+        fs::prepend(
+            "frontend/bundles/index.tsx",
+            "import { ErrorBoundary, initSentry } from '../src/sentry'",
+        )?;
+        fs::replace(
+            "frontend/bundles/index.tsx",
+            "ReactDOM.createRoot",
+            "initSentry()\n\nReactDOM.createRoot",
+        )?;
+        // around every other provider, so their errors are caught too
+        fs::replace(
+            "frontend/bundles/index.tsx",
+            "<React.StrictMode>",
+            "<React.StrictMode>\n<ErrorBoundary>",
+        )?;
+        fs::replace(
+            "frontend/bundles/index.tsx",
+            "</React.StrictMode>",
+            "</ErrorBoundary>\n</React.StrictMode>",
+        )?;
+
+        if install_config.plugin_auth {
+            fs::prepend(
+                "frontend/src/App.tsx",
+                "import { useSentryUser } from './sentry'",
+            )?;
+            fs::replace(
+                "frontend/src/App.tsx",
+                "  const auth = useAuth()\n",
+                "  const auth = useAuth()\n  useSentryUser(auth.session?.userId)\n",
+            )?;
+        }
+
+        fs::append(
+            ".env.example",
+            r#"
+# report errors to Sentry (the frontend uses SENTRY_FRONTEND_DSN, or SENTRY_DSN)
+#SENTRY_DSN=
+#SENTRY_FRONTEND_DSN=
+#SENTRY_ENVIRONMENT=development
+"#,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::BackendDatabase;
+use anyhow::Result;
+use indoc::indoc;
+
+pub struct SoftDelete {}
+
+impl Plugin for SoftDelete {
+    fn name(&self) -> &'static str {
+        "SoftDelete"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        // the handlers set `deleted_at`, clients can't
+        fs::replace(
+            ".cargo/bin/dsync.rs",
+            r#".autogenerated_columns(vec!["id", "created_at", "updated_at"])"#,
+            r#".autogenerated_columns(vec!["id", "created_at", "updated_at", "deleted_at"])"#,
+        )?;
+
+        // for `AccountDeletion::SoftDelete`
+        if install_config.plugin_auth {
+            crate::content::migration::create(
+                "plugin_soft_delete",
+                match install_config.backend_database {
+                    BackendDatabase::Postgres => indoc! {r#"
+      ALTER TABLE users ADD COLUMN deleted_at TIMESTAMPTZ;
+
+      CREATE INDEX users_deleted_at_index ON users(deleted_at);
+    "#},
+                    BackendDatabase::Sqlite => indoc! {r#"
+      ALTER TABLE users ADD COLUMN deleted_at DATETIME;
+
+      CREATE INDEX users_deleted_at_index ON users(deleted_at);
+    "#},
+                },
+                indoc! {r#"
+      DROP INDEX users_deleted_at_index;
+      ALTER TABLE users DROP COLUMN deleted_at;
+    "#},
+            )?;
+        }
+
+        Ok(())
+    }
+}
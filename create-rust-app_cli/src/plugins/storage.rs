@@ -43,6 +43,31 @@ S3_REGION=minio
 S3_BUCKET=bucket
 S3_ACCESS_KEY_ID=access_key
 S3_SECRET_ACCESS_KEY=secret_key
+
+# keep files in a local directory instead of S3 (served at /api/storage)
+#STORAGE_DIR=./storage
+
+# keep files in Google Cloud Storage instead of S3 (needs create-rust-app's "plugin_storage_gcs" feature);
+# GCS_CREDENTIALS is a service account key's path or json, leave it unset for workload identity
+#GCS_BUCKET=bucket
+#GCS_CREDENTIALS=./service-account.json
+
+# keep files in Azure Blob Storage instead of S3 (needs create-rust-app's "plugin_storage_azure" feature)
+#AZURE_STORAGE_ACCOUNT=account
+#AZURE_STORAGE_ACCESS_KEY=access_key
+#AZURE_STORAGE_CONTAINER=container
+
+# the bytes each user's attachments may take (unlimited when unset)
+#STORAGE_QUOTA_BYTES=5000000000
+
+# scan uploaded files with a ClamAV daemon, infected files are rejected
+#CLAMAV_ADDRESS=localhost:3310
+
+# serve download links through a CDN whose origin is the bucket; expiring links are CloudFront
+# signed urls with the key pair below (needs create-rust-app's "plugin_storage_cloudfront" feature)
+#CDN_URL=https://d111111abcdef8.cloudfront.net
+#CLOUDFRONT_KEY_PAIR_ID=K2JCJMDEHXQW5F
+#CLOUDFRONT_PRIVATE_KEY=./cloudfront.pem
 "#,
         )?;
 
@@ -92,8 +117,13 @@ CREATE TABLE attachments(
   record_id SERIAL NOT NULL,
   blob_id SERIAL REFERENCES attachment_blobs(id) NOT NULL,
 
-  created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+  created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  private BOOLEAN NOT NULL DEFAULT FALSE,
+  user_id INTEGER,
+  scan_status TEXT NOT NULL DEFAULT 'unscanned'
 );
+
+CREATE INDEX attachments_user_id_idx ON attachments(user_id);
 "#},
                 BackendDatabase::Sqlite => indoc! {r#"
 CREATE TABLE attachment_blobs(
@@ -117,8 +147,13 @@ CREATE TABLE attachments(
   record_id INTEGER NOT NULL,
   blob_id INTEGER REFERENCES attachment_blobs(id) NOT NULL,
 
-  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  private BOOLEAN NOT NULL DEFAULT FALSE,
+  user_id INTEGER,
+  scan_status TEXT NOT NULL DEFAULT 'unscanned'
 );
+
+CREATE INDEX attachments_user_id_idx ON attachments(user_id);
 "#},
             },
             indoc! {r#"
@@ -133,6 +168,14 @@ DROP TABLE attachments;
                     "file",
                     r#"services::file::endpoints(web::scope("/files"))"#,
                 )?;
+                crate::content::service::register_actix(
+                    "storage",
+                    r#"create_rust_app::storage::endpoints(web::scope("/storage"))"#,
+                )?;
+                crate::content::service::register_actix(
+                    "uploads",
+                    r#"create_rust_app::storage::multipart_endpoints(web::scope("/uploads"))"#,
+                )?;
 
                 fs::replace(
                     "backend/main.rs",
@@ -143,6 +186,16 @@ DROP TABLE attachments;
             }
             BackendFramework::Poem => {
                 crate::content::service::register_poem("file", "services::file::api()", "/files")?;
+                crate::content::service::register_poem(
+                    "storage",
+                    "create_rust_app::storage::api()",
+                    "/storage",
+                )?;
+                crate::content::service::register_poem(
+                    "uploads",
+                    "create_rust_app::storage::multipart_api()",
+                    "/uploads",
+                )?;
 
                 fs::replace(
                     "backend/main.rs",
@@ -154,6 +207,7 @@ DROP TABLE attachments;
         };
 
         fs::append("backend/services/mod.rs", "pub mod file;")?;
+        fs::append(".gitignore", "/storage")?;
 
         Ok(())
     }
@@ -0,0 +1,154 @@
+use crate::plugins::InstallConfig;
+use crate::plugins::Plugin;
+use crate::utils::fs;
+use crate::utils::logger;
+use crate::utils::logger::add_file_msg;
+use crate::{BackendDatabase, BackendFramework};
+use anyhow::Result;
+use indoc::indoc;
+use rust_embed::RustEmbed;
+
+pub struct Webhooks {}
+
+#[derive(RustEmbed)]
+#[folder = "template-plugin-webhooks"]
+struct Asset;
+
+impl Plugin for Webhooks {
+    fn name(&self) -> &'static str {
+        "Webhooks"
+    }
+
+    fn install(&self, install_config: InstallConfig) -> Result<()> {
+        if !install_config.plugin_auth {
+            logger::error("The Webhooks plugin requires the Auth plugin!");
+            std::process::exit(1);
+        }
+
+        for filename in Asset::iter() {
+            let file_contents = Asset::get(filename.as_ref()).unwrap();
+            let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
+            file_path.push(filename.as_ref());
+            let mut directory_path = std::path::PathBuf::from(&file_path);
+            directory_path.pop();
+
+            add_file_msg(filename.as_ref());
+            std::fs::create_dir_all(directory_path)?;
+            std::fs::write(file_path, file_contents.data)?;
+        }
+
+        crate::content::migration::create(
+            "plugin_webhooks",
+            match install_config.backend_database {
+                BackendDatabase::Postgres => indoc! {r#"
+      CREATE TABLE webhook_endpoints (
+        id SERIAL PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        url TEXT NOT NULL,
+        description TEXT NOT NULL DEFAULT '',
+        secret TEXT NOT NULL,
+        event_types TEXT NOT NULL DEFAULT '[]',
+        enabled BOOLEAN NOT NULL DEFAULT TRUE,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX webhook_endpoints_user_id_index ON webhook_endpoints(user_id);
+
+      CREATE TABLE webhook_deliveries (
+        id SERIAL PRIMARY KEY,
+        endpoint_id INTEGER NOT NULL REFERENCES webhook_endpoints(id),
+        event_type TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        response_status INTEGER,
+        response_body TEXT,
+        last_error TEXT,
+        next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        delivered_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX webhook_deliveries_endpoint_id_index ON webhook_deliveries(endpoint_id, created_at);
+      CREATE INDEX webhook_deliveries_due_index ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+    "#},
+                BackendDatabase::Sqlite => indoc! {r#"
+      CREATE TABLE webhook_endpoints (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        url TEXT NOT NULL,
+        description TEXT NOT NULL DEFAULT '',
+        secret TEXT NOT NULL,
+        event_types TEXT NOT NULL DEFAULT '[]',
+        enabled BOOLEAN NOT NULL DEFAULT TRUE,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX webhook_endpoints_user_id_index ON webhook_endpoints(user_id);
+
+      CREATE TABLE webhook_deliveries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        endpoint_id INTEGER NOT NULL REFERENCES webhook_endpoints(id),
+        event_type TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        response_status INTEGER,
+        response_body TEXT,
+        last_error TEXT,
+        next_attempt_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        delivered_at DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX webhook_deliveries_endpoint_id_index ON webhook_deliveries(endpoint_id, created_at);
+      CREATE INDEX webhook_deliveries_due_index ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+    "#},
+            },
+            indoc! {r#"
+      DROP TABLE webhook_deliveries;
+      DROP TABLE webhook_endpoints;
+    "#},
+        )?;
+
+        fs::replace("backend/main.rs", "mod mail;", "mod mail;\nmod webhooks;")?;
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => {
+                crate::content::service::register_actix(
+                    "webhooks",
+                    r#"create_rust_app::webhooks::endpoints(web::scope("/webhooks"))"#,
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let app_data = create_rust_app::setup();",
+                    r#"let app_data = create_rust_app::setup();
+    // defines the event types of `webhooks.rs`, and sends their deliveries
+    webhooks::register();
+    create_rust_app::webhooks::spawn_worker(app_data.database.clone(), Default::default());"#,
+                )?;
+            }
+            BackendFramework::Poem => {
+                crate::content::service::register_poem(
+                    "webhooks",
+                    "create_rust_app::webhooks::api()",
+                    "/webhooks",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    r#"let data = create_rust_app::setup();
+    // defines the event types of `webhooks.rs`, and sends their deliveries
+    webhooks::register();
+    create_rust_app::webhooks::spawn_worker(data.database.clone(), Default::default());"#,
+                )?;
+            }
+        };
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
 pub mod fs;
 pub mod git;
 pub mod logger;
+pub mod telemetry;
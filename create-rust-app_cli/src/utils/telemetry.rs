@@ -0,0 +1,325 @@
+//! Opt-in, anonymous usage telemetry.
+//!
+//! Nothing is recorded or sent until the user agrees to it (they're asked once, when creating a
+//! project interactively), and it can be turned off at any time:
+//! - for a single run: `--no-telemetry`, or the `CRA_NO_TELEMETRY` / `DO_NOT_TRACK` environment variables
+//! - for good: `create-rust-app stats --disable`
+//!
+//! Every event sent is also appended to a local log, summarized by `create-rust-app stats`.
+//!
+//! # Schema (version 1)
+//!
+//! Each command run produces one json object:
+//!
+//! | field               | type             | description                                                           |
+//! |---------------------|------------------|-----------------------------------------------------------------------|
+//! | `schema`            | number           | version of this schema (1)                                            |
+//! | `installation_id`   | string           | random id generated when telemetry was enabled (not tied to the user) |
+//! | `cli_version`       | string           | version of create-rust-app_cli                                        |
+//! | `os`                | string           | `linux`, `macos`, `windows`, ...                                      |
+//! | `command`           | string           | `create`, `configure` or `generate`                                   |
+//! | `action`            | string?          | the action, ex: `qsync`, `new-service`, `new-sse`, `k8s` or `fly`     |
+//! | `interactive`       | bool             | whether the TUI was used (as opposed to `--cli`)                      |
+//! | `backend_framework` | string?          | for `create`: `actix-web` or `poem`                                   |
+//! | `database`          | string?          | for `create` and `generate`: `postgres` or `sqlite`                   |
+//! | `plugins`           | array of strings | for `create`: the plugins installed (ex: `["auth", "dev"]`)           |
+//! | `success`           | bool             | whether the command succeeded                                         |
+//! | `duration_ms`       | number           | how long the command took                                             |
+//! | `timestamp`         | number           | when the command finished (unix time, in seconds)                     |
+//!
+//! Project names, paths, error messages and anything else typed in are never recorded.
+use crate::utils::logger;
+use anyhow::Result;
+use console::style;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// where events are sent, override it with `CRA_TELEMETRY_ENDPOINT`
+const TELEMETRY_ENDPOINT: &str = "https://create-rust-app.dev/api/telemetry";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+/// the user's choice, saved in `<config dir>/create-rust-app/telemetry.toml`
+struct Settings {
+    /// whether the user was asked to opt in
+    asked: bool,
+    enabled: bool,
+    installation_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a command run, see the [module documentation](`self`) for what each field means
+pub struct Event {
+    pub schema: u32,
+    pub installation_id: String,
+    pub cli_version: String,
+    pub os: String,
+    pub command: String,
+    pub action: Option<String>,
+    pub interactive: bool,
+    pub backend_framework: Option<String>,
+    pub database: Option<String>,
+    pub plugins: Vec<String>,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub timestamp: u64,
+}
+
+/// collects what a command did, and records it with [`Run::finish`] if telemetry is enabled
+pub struct Run {
+    started_at: Instant,
+    command: &'static str,
+    pub action: Option<&'static str>,
+    pub interactive: bool,
+    pub backend_framework: Option<String>,
+    pub database: Option<String>,
+    pub plugins: Vec<String>,
+}
+
+impl Run {
+    pub fn new(command: &'static str) -> Self {
+        Self {
+            started_at: Instant::now(),
+            command,
+            action: None,
+            interactive: true,
+            backend_framework: None,
+            database: None,
+            plugins: vec![],
+        }
+    }
+
+    /// records the run (locally, and sends it) if the user opted in and `no_telemetry` isn't set
+    pub fn finish(self, success: bool, no_telemetry: bool) {
+        if no_telemetry || disabled_by_env() {
+            return;
+        }
+
+        let settings = load_settings();
+        let installation_id = match (settings.enabled, settings.installation_id) {
+            (true, Some(installation_id)) => installation_id,
+            _ => return,
+        };
+
+        let event = Event {
+            schema: SCHEMA_VERSION,
+            installation_id,
+            cli_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            command: self.command.to_string(),
+            action: self.action.map(String::from),
+            interactive: self.interactive,
+            backend_framework: self.backend_framework,
+            database: self.database,
+            plugins: self.plugins,
+            success,
+            duration_ms: self.started_at.elapsed().as_millis() as u64,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default(),
+        };
+
+        // telemetry never gets in the way: failures are ignored
+        let _ = append_event(&event);
+        send(&event);
+    }
+}
+
+/// asks the user to opt in, unless they were asked before (or telemetry is disabled for this run)
+pub fn ask_once(no_telemetry: bool) -> Result<()> {
+    if no_telemetry || disabled_by_env() || load_settings().asked {
+        return Ok(());
+    }
+
+    logger::message("Help improve create-rust-app by sharing anonymous usage statistics?");
+    logger::message(
+        "Only the backend framework, database, plugins and whether commands succeed are shared (never names or paths).",
+    );
+    logger::message(&format!(
+        "See what's been shared with {}, and change your mind with {}.",
+        style("create-rust-app stats").yellow(),
+        style("create-rust-app stats --enable/--disable").yellow()
+    ));
+
+    let enabled = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Share anonymous usage statistics?")
+        .default(false)
+        .interact()?;
+
+    set_enabled(enabled)
+}
+
+/// opts in or out
+pub fn set_enabled(enabled: bool) -> Result<()> {
+    let mut settings = load_settings();
+    settings.asked = true;
+    settings.enabled = enabled;
+    if enabled && settings.installation_id.is_none() {
+        settings.installation_id = Some(new_installation_id());
+    }
+
+    save_settings(&settings)
+}
+
+/// `create-rust-app stats`: prints a summary of the events recorded on this machine
+pub fn print_stats() -> Result<()> {
+    let settings = load_settings();
+
+    logger::message(&format!(
+        "Telemetry is {}{}",
+        if settings.enabled && !disabled_by_env() {
+            style("enabled").green()
+        } else {
+            style("disabled").red()
+        },
+        if disabled_by_env() {
+            " (by CRA_NO_TELEMETRY / DO_NOT_TRACK)"
+        } else {
+            ""
+        }
+    ));
+    if let Some(dir) = config_dir() {
+        logger::message(&format!("Settings and events are stored in {dir:?}"));
+    }
+
+    let events = read_events();
+    if events.is_empty() {
+        logger::message("No events were recorded.");
+        return Ok(());
+    }
+
+    let succeeded = events.iter().filter(|event| event.success).count();
+    logger::message(&format!(
+        "{} commands recorded: {} succeeded, {} failed",
+        events.len(),
+        succeeded,
+        events.len() - succeeded
+    ));
+
+    let mut commands: BTreeMap<String, usize> = BTreeMap::new();
+    let mut choices: BTreeMap<String, usize> = BTreeMap::new();
+    for event in &events {
+        let command = match &event.action {
+            Some(action) => format!("{} {}", event.command, action),
+            None => event.command.clone(),
+        };
+        *commands.entry(command).or_default() += 1;
+
+        for choice in event
+            .backend_framework
+            .iter()
+            .chain(event.database.iter())
+            .chain(event.plugins.iter())
+        {
+            *choices.entry(choice.clone()).or_default() += 1;
+        }
+    }
+
+    logger::message("Commands:");
+    for (command, count) in commands {
+        println!("  {command:<24} {count}");
+    }
+
+    if !choices.is_empty() {
+        logger::message("Frameworks, databases and plugins chosen:");
+        for (choice, count) in choices {
+            println!("  {choice:<24} {count}");
+        }
+    }
+
+    Ok(())
+}
+
+fn disabled_by_env() -> bool {
+    ["CRA_NO_TELEMETRY", "DO_NOT_TRACK"].iter().any(|var| {
+        std::env::var(var)
+            .map(|value| !value.is_empty() && value != "0" && value != "false")
+            .unwrap_or(false)
+    })
+}
+
+/// `$XDG_CONFIG_HOME/create-rust-app`, `~/.config/create-rust-app` or `%APPDATA%\create-rust-app`
+fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(base.join("create-rust-app"))
+}
+
+fn load_settings() -> Settings {
+    config_dir()
+        .and_then(|dir| std::fs::read_to_string(dir.join("telemetry.toml")).ok())
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(settings: &Settings) -> Result<()> {
+    let dir = config_dir().ok_or_else(|| anyhow::anyhow!("could not find a config directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("telemetry.toml"), toml::to_string(settings)?)?;
+
+    Ok(())
+}
+
+fn append_event(event: &Event) -> Result<()> {
+    let dir = config_dir().ok_or_else(|| anyhow::anyhow!("could not find a config directory"))?;
+    std::fs::create_dir_all(&dir)?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("events.jsonl"))?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+
+    Ok(())
+}
+
+fn read_events() -> Vec<Event> {
+    let file = match config_dir().and_then(|dir| std::fs::File::open(dir.join("events.jsonl")).ok())
+    {
+        Some(file) => file,
+        None => return vec![],
+    };
+
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+fn send(event: &Event) {
+    let endpoint =
+        std::env::var("CRA_TELEMETRY_ENDPOINT").unwrap_or_else(|_| TELEMETRY_ENDPOINT.to_string());
+
+    let _ = ureq::post(&endpoint)
+        .timeout(Duration::from_secs(2))
+        .send_json(event);
+}
+
+/// random enough to tell installations apart, without identifying anyone
+fn new_installation_id() -> String {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    (0..2)
+        .map(|_| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|time| time.as_nanos())
+                    .unwrap_or_default(),
+            );
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
@@ -0,0 +1,41 @@
+//! Your app's user fields, served by the auth plugin at `/api/auth/profile`.
+//!
+//! This file and the `user_profiles` table belong to your app (dsync skips them): add columns with
+//! a new migration, then add them to `Profile` in the same order as the schema.
+use crate::schema::user_profiles;
+use create_rust_app::auth::UserProfile;
+use create_rust_app::Connection;
+use diesel::prelude::*;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[tsync::tsync]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Queryable, Insertable, AsChangeset)]
+#[diesel(table_name=user_profiles, primary_key(user_id), treat_none_as_null = true)]
+pub struct Profile {
+    pub user_id: i32,
+    pub display_name: Option<String>,
+    /// the language of the auth emails (ex: "fr"), see `backend/mail/templates`
+    pub locale: Option<String>,
+}
+
+impl UserProfile for Profile {
+    fn read(db: &mut Connection, user_id: i32) -> QueryResult<Option<Self>> {
+        user_profiles::table.find(user_id).first(db).optional()
+    }
+
+    fn save(db: &mut Connection, user_id: i32, profile: Self) -> QueryResult<Self> {
+        let profile = Profile { user_id, ..profile };
+
+        diesel::insert_into(user_profiles::table)
+            .values(&profile)
+            .on_conflict(user_profiles::user_id)
+            .do_update()
+            .set(&profile)
+            .get_result(db)
+    }
+
+    fn delete(db: &mut Connection, user_id: i32) -> QueryResult<usize> {
+        diesel::delete(user_profiles::table.find(user_id)).execute(db)
+    }
+}
@@ -0,0 +1,108 @@
+import React, { useEffect, useRef, useState } from 'react'
+
+type CaptchaProvider = 'hcaptcha' | 'turnstile' | 'recaptcha'
+
+interface CaptchaSettings {
+  provider: CaptchaProvider | null
+  site_key: string | null
+}
+
+interface CaptchaWidgets {
+  render: (
+    element: HTMLElement,
+    options: {
+      sitekey: string
+      callback: (token: string) => void
+      'expired-callback': () => void
+    }
+  ) => unknown
+}
+
+const SCRIPTS: Record<CaptchaProvider, { src: string; global: string }> = {
+  hcaptcha: {
+    src: 'https://js.hcaptcha.com/1/api.js?render=explicit',
+    global: 'hcaptcha',
+  },
+  turnstile: {
+    src: 'https://challenges.cloudflare.com/turnstile/v0/api.js?render=explicit',
+    global: 'turnstile',
+  },
+  recaptcha: {
+    src: 'https://www.google.com/recaptcha/api.js?render=explicit',
+    global: 'grecaptcha',
+  },
+}
+
+let settings: Promise<CaptchaSettings> | undefined
+const loadSettings = () => {
+  if (!settings) {
+    settings = fetch('/api/auth/captcha').then((response) => response.json())
+  }
+  return settings
+}
+
+const loadScript = (provider: CaptchaProvider): Promise<CaptchaWidgets> => {
+  const { src, global } = SCRIPTS[provider]
+  const widgets = () => (window as any)[global] as CaptchaWidgets | undefined
+
+  return new Promise((resolve) => {
+    if (!document.querySelector(`script[src="${src}"]`)) {
+      const script = document.createElement('script')
+      script.src = src
+      script.async = true
+      document.head.appendChild(script)
+    }
+
+    // the widgets' apis are ready a little after their script loaded (grecaptcha.render in particular)
+    const wait = () => {
+      const loaded = widgets()
+      if (loaded && typeof loaded.render === 'function') resolve(loaded)
+      else setTimeout(wait, 50)
+    }
+    wait()
+  })
+}
+
+/**
+ * Shows the CAPTCHA configured on the backend (see `CAPTCHA_PROVIDER` in `.env`), or nothing if
+ * CAPTCHAs are disabled.
+ *
+ * `onToken` receives the token to send as `captcha_token`, or `undefined` once it expires.
+ * Tokens can only be used once: re-mount the component (change its `key`) after each submit.
+ */
+export const Captcha = (props: { onToken: (token: string | undefined) => void }) => {
+  const container = useRef<HTMLDivElement>(null)
+  const [error, setError] = useState<string>('')
+  const onToken = useRef(props.onToken)
+  onToken.current = props.onToken
+
+  useEffect(() => {
+    let cancelled = false
+
+    loadSettings()
+      .then(async ({ provider, site_key }) => {
+        if (!provider || !site_key) return
+
+        const widgets = await loadScript(provider)
+        if (cancelled || !container.current) return
+
+        widgets.render(container.current, {
+          sitekey: site_key,
+          callback: (token) => onToken.current(token),
+          'expired-callback': () => onToken.current(undefined),
+        })
+      })
+      .catch(() => setError('Could not load the CAPTCHA.'))
+
+    return () => {
+      cancelled = true
+    }
+  }, [])
+
+  return (
+    <div style={{ margin: '10px 0' }}>
+      {error && <div style={{ color: 'red' }}>{error}</div>}
+      <div ref={container} />
+    </div>
+  )
+}
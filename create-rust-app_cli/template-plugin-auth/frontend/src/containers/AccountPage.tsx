@@ -1,6 +1,8 @@
 import React, { useEffect, useState } from 'react'
 import { useNavigate } from 'react-router-dom'
 import { useAuth } from '../hooks/useAuth'
+import { useQueryParam } from '../hooks/useQueryParam'
+import { Captcha } from '../components/Captcha'
 
 export const AccountPage = () => {
   const auth = useAuth()
@@ -10,6 +12,30 @@ export const AccountPage = () => {
   const [originalPassword, setOriginalPassword] = useState<string>('')
   const [password, setPassword] = useState<string>('')
 
+  const [newEmail, setNewEmail] = useState<string>('')
+  const [emailPassword, setEmailPassword] = useState<string>('')
+  const [emailMessage, setEmailMessage] = useState<string>('')
+
+  const [deletePassword, setDeletePassword] = useState<string>('')
+
+  const [guestEmail, setGuestEmail] = useState<string>('')
+  const [guestPassword, setGuestPassword] = useState<string>('')
+  const [guestError, setGuestError] = useState<string>('')
+  const [guestMessage, setGuestMessage] = useState<string>('')
+  const invitationToken = useQueryParam('invitation_token')
+  const [captchaToken, setCaptchaToken] = useState<string>()
+  const [attempts, setAttempts] = useState<number>(0)
+
+  const [identities, setIdentities] = useState<UserIdentityResponse>({
+    identities: [],
+    providers: [],
+  })
+  const [identityError, setIdentityError] = useState<string>('')
+
+  // `null` when the backend doesn't have profiles enabled
+  const [profile, setProfile] = useState<Record<string, any> | null>(null)
+  const [profileError, setProfileError] = useState<string>('')
+
   const [page, setPage] = useState<number>(0)
   const [pageSize, setPageSize] = useState<number>(10)
 
@@ -84,6 +110,111 @@ export const AccountPage = () => {
     fetchSessions()
   }, [auth.isAuthenticated, page, pageSize])
 
+  const fetchIdentities = async () => {
+    if (!auth.isAuthenticated || auth.isGuest) return
+
+    const response = await fetch('/api/auth/identities', {
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    })
+
+    if (response.ok) setIdentities(await response.json())
+  }
+
+  // the provider sends the user back here with a `code` and a `state` once they linked their account
+  const linkIdentity = async (code: string, state: string) => {
+    window.history.replaceState(null, '', window.location.pathname)
+
+    const response = await fetch('/api/auth/identities', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({ code, state }),
+    })
+
+    setIdentityError(response.ok ? '' : (await response.json()).message)
+    await fetchIdentities()
+  }
+
+  useEffect(() => {
+    if (!auth.isAuthenticated) return
+
+    const params = new URLSearchParams(window.location.search)
+    const code = params.get('code')
+    const state = params.get('state')
+
+    if (code && state) linkIdentity(code, state)
+    else fetchIdentities()
+  }, [auth.isAuthenticated])
+
+  const authorizeIdentity = async (provider: string) => {
+    const response = await fetch(`/api/auth/identities/${provider}/authorize`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({
+        redirect_uri: `${window.location.origin}${window.location.pathname}`,
+      }),
+    })
+    const json = await response.json()
+
+    if (response.ok) window.location.href = json.url
+    else setIdentityError(json.message)
+  }
+
+  const unlinkIdentity = async (id: number) => {
+    const response = await fetch(`/api/auth/identities/${id}`, {
+      method: 'DELETE',
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    })
+
+    setIdentityError(response.ok ? '' : (await response.json()).message)
+    await fetchIdentities()
+  }
+
+  const fetchProfile = async () => {
+    if (!auth.isAuthenticated) return
+
+    const response = await fetch('/api/auth/profile', {
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    })
+
+    setProfile(response.ok ? await response.json() : null)
+  }
+
+  useEffect(() => {
+    fetchProfile()
+  }, [auth.isAuthenticated])
+
+  const saveProfile = async () => {
+    if (!profile) return
+
+    setProcessing(true)
+    const { user_id, ...changes } = profile
+    const response = await fetch('/api/auth/profile', {
+      method: 'PATCH',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify(changes),
+    })
+    const json = await response.json()
+
+    if (response.ok) setProfile(json)
+    setProfileError(response.ok ? '' : json.message)
+    setProcessing(false)
+  }
+
   const changePassword = async () => {
     setProcessing(true)
     const response = await (
@@ -105,6 +236,88 @@ export const AccountPage = () => {
     setProcessing(false)
   }
 
+  const changeEmail = async () => {
+    setProcessing(true)
+    const response = await fetch('/api/auth/email/change', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({ password: emailPassword, new_email: newEmail }),
+    })
+
+    setEmailMessage((await response.json()).message)
+    if (response.ok) setNewEmail('')
+    setEmailPassword('')
+    setProcessing(false)
+  }
+
+  const exportAccount = async () => {
+    setProcessing(true)
+    const response = await fetch('/api/auth/account/export', {
+      method: 'GET',
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    })
+
+    if (response.ok) {
+      const url = URL.createObjectURL(await response.blob())
+      const link = document.createElement('a')
+      link.href = url
+      link.download = 'account.json'
+      link.click()
+      URL.revokeObjectURL(url)
+    }
+    setProcessing(false)
+  }
+
+  const saveGuest = async (action: 'upgrade' | 'merge') => {
+    setProcessing(true)
+    const error = await auth.saveGuest(
+      action,
+      guestEmail,
+      guestPassword,
+      captchaToken,
+      invitationToken || undefined
+    )
+    setGuestError(error || '')
+    // CAPTCHA tokens can only be used once
+    setCaptchaToken(undefined)
+    setAttempts(attempts + 1)
+    if (!error && action === 'upgrade' && !invitationToken) {
+      // the account keeps the guest role until it's activated
+      setGuestMessage('Follow the link we emailed you to activate your account.')
+    }
+    setGuestPassword('')
+    setProcessing(false)
+  }
+
+  const deleteAccount = async () => {
+    if (!window.confirm('Delete your account and all of its data? This cannot be undone.')) {
+      return
+    }
+
+    setProcessing(true)
+    const response = await fetch('/api/auth/account', {
+      method: 'DELETE',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({ password: deletePassword }),
+    })
+
+    if (response.ok) {
+      window.location.href = '/'
+    } else {
+      console.log(await response.json())
+      setDeletePassword('')
+      setProcessing(false)
+    }
+  }
+
   return (
     <div style={{ textAlign: 'left' }}>
       <h1>Account</h1>
@@ -112,6 +325,38 @@ export const AccountPage = () => {
       {auth.isAuthenticated && (
         <div>
           User # {auth.session?.userId}
+          {guestMessage && <div>{guestMessage}</div>}
+          {auth.isGuest && !guestMessage && (
+            <div className="Form" style={{ textAlign: 'left' }}>
+              <h1>Save your account</h1>
+              <p>You're using a guest account, sign up to keep your data.</p>
+              {guestError && <div style={{ color: 'red' }}>{guestError}</div>}
+              <div style={{ display: 'flex', flexFlow: 'column' }}>
+                <label>Email</label>
+                <input
+                  value={guestEmail}
+                  onChange={(e) => setGuestEmail(e.target.value)}
+                />
+              </div>
+              <div style={{ display: 'flex', flexFlow: 'column' }}>
+                <label>Password</label>
+                <input
+                  type="password"
+                  value={guestPassword}
+                  onChange={(e) => setGuestPassword(e.target.value)}
+                />
+              </div>
+              <Captcha key={attempts} onToken={setCaptchaToken} />
+              <div style={{ display: 'flex', flexFlow: 'column' }}>
+                <button disabled={processing} onClick={() => saveGuest('upgrade')}>
+                  Create my account
+                </button>
+                <button disabled={processing} onClick={() => saveGuest('merge')}>
+                  I already have an account
+                </button>
+              </div>
+            </div>
+          )}
           <div className="Form" style={{ textAlign: 'left' }}>
             <h1>Permissions</h1>
             <pre>
@@ -126,7 +371,33 @@ export const AccountPage = () => {
               )}
             </pre>
           </div>
-          <div className="Form" style={{ textAlign: 'left' }}>
+          {profile && (
+            <div className="Form" style={{ textAlign: 'left' }}>
+              <h1>Profile</h1>
+              {profileError && <div style={{ color: 'red' }}>{profileError}</div>}
+              {/* text fields of your `Profile` model (backend/models/user_profiles.rs) */}
+              {Object.keys(profile)
+                .filter((field) => field !== 'user_id')
+                .filter((field) => profile[field] === null || typeof profile[field] === 'string')
+                .map((field) => (
+                  <div key={field} style={{ display: 'flex', flexFlow: 'column' }}>
+                    <label>{field}</label>
+                    <input
+                      value={profile[field] || ''}
+                      onChange={(e) =>
+                        setProfile({ ...profile, [field]: e.target.value || null })
+                      }
+                    />
+                  </div>
+                ))}
+              <div style={{ display: 'flex', flexFlow: 'column' }}>
+                <button disabled={processing} onClick={saveProfile}>
+                  Save profile
+                </button>
+              </div>
+            </div>
+          )}
+          {!auth.isGuest && <div className="Form" style={{ textAlign: 'left' }}>
             <h1>Change password</h1>
             <br />
             <div style={{ display: 'flex', flexFlow: 'column' }}>
@@ -150,7 +421,61 @@ export const AccountPage = () => {
                 Change Password
               </button>
             </div>
-          </div>
+          </div>}
+          {!auth.isGuest && <div className="Form" style={{ textAlign: 'left' }}>
+            <h1>Change email</h1>
+            <br />
+            {emailMessage && <div>{emailMessage}</div>}
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <label>New Email</label>
+              <input
+                value={newEmail}
+                onChange={(e) => setNewEmail(e.target.value)}
+              />
+            </div>
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <label>Password</label>
+              <input
+                type="password"
+                value={emailPassword}
+                onChange={(e) => setEmailPassword(e.target.value)}
+              />
+            </div>
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <button disabled={processing || !newEmail || !emailPassword} onClick={changeEmail}>
+                Change Email
+              </button>
+            </div>
+          </div>}
+          {!auth.isGuest && identities.providers.length > 0 && (
+            <div className="Form" style={{ textAlign: 'left' }}>
+              <h1>Linked accounts</h1>
+              {identityError && <div style={{ color: 'red' }}>{identityError}</div>}
+              {identities.providers.map((provider) => {
+                const identity = identities.identities.find(
+                  (identity) => identity.provider === provider
+                )
+
+                return (
+                  <div key={provider} style={{ display: 'flex', flexFlow: 'column' }}>
+                    <label>
+                      {provider}
+                      {identity && ` (${identity.email || 'linked'})`}
+                    </label>
+                    {identity ? (
+                      <button disabled={processing} onClick={() => unlinkIdentity(identity.id)}>
+                        Unlink
+                      </button>
+                    ) : (
+                      <button disabled={processing} onClick={() => authorizeIdentity(provider)}>
+                        Link
+                      </button>
+                    )}
+                  </div>
+                )
+              })}
+            </div>
+          )}
           <div>
             <h1>Sessions</h1>
             <button disabled={isDeleting} onClick={() => deleteAllSessions()}>
@@ -182,6 +507,31 @@ export const AccountPage = () => {
               >{`>>`}</button>
             </div>
           </div>
+          <div className="Form" style={{ textAlign: 'left' }}>
+            <h1>Your data</h1>
+            <br />
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <button disabled={processing} onClick={exportAccount}>
+                Download my data
+              </button>
+            </div>
+            {!auth.isGuest && <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <label>Password</label>
+              <input
+                type="password"
+                value={deletePassword}
+                onChange={(e) => setDeletePassword(e.target.value)}
+              />
+            </div>}
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <button
+                disabled={processing || (!auth.isGuest && !deletePassword)}
+                onClick={deleteAccount}
+              >
+                Delete my account
+              </button>
+            </div>
+          </div>
         </div>
       )}
       {!auth.isAuthenticated && (
@@ -0,0 +1,49 @@
+import React, { useState } from 'react'
+import { useNavigate } from 'react-router'
+import { useQueryParam } from '../hooks/useQueryParam'
+
+export const ConfirmEmailPage = () => {
+  const navigate = useNavigate()
+  const token = useQueryParam('token') || ''
+  const [emailChangeToken, setEmailChangeToken] = useState<string>(token)
+  const [processing, setProcessing] = useState<boolean>(false)
+  const [error, setError] = useState<string>('')
+
+  const confirm = async () => {
+    setProcessing(true)
+    const response = await fetch('/api/auth/email/confirm', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ email_change_token: emailChangeToken }),
+    })
+    if (response.ok) {
+      navigate('/account')
+    } else {
+      setError((await response.json()).message)
+    }
+    setProcessing(false)
+  }
+
+  return (
+    <div className="Form" style={{ textAlign: 'left' }}>
+      <h1>Confirm your new email</h1>
+      <br />
+      {error && <div style={{ color: 'red' }}>{error}</div>}
+      <div style={{ display: 'flex', flexFlow: 'column' }}>
+        <label>Confirmation Token</label>
+        <input
+          type="password"
+          value={emailChangeToken}
+          onChange={(e) => setEmailChangeToken(e.target.value)}
+        />
+      </div>
+      <div style={{ display: 'flex', flexFlow: 'column' }}>
+        <button disabled={processing} onClick={confirm}>
+          Confirm
+        </button>
+      </div>
+    </div>
+  )
+}
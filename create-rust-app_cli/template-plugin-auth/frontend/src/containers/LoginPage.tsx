@@ -1,6 +1,7 @@
 import React, { useState } from 'react'
 import { useNavigate } from 'react-router-dom'
 import { useAuth } from '../hooks/useAuth'
+import { Captcha } from '../components/Captcha'
 
 export const LoginPage = () => {
   const auth = useAuth()
@@ -8,10 +9,15 @@ export const LoginPage = () => {
   const [email, setEmail] = useState<string>('')
   const [password, setPassword] = useState<string>('')
   const [processing, setProcessing] = useState<boolean>(false)
+  const [captchaToken, setCaptchaToken] = useState<string>()
+  const [attempts, setAttempts] = useState<number>(0)
 
   const login = async () => {
     setProcessing(true)
-    await auth.login(email, password)
+    await auth.login(email, password, captchaToken)
+    // CAPTCHA tokens can only be used once
+    setCaptchaToken(undefined)
+    setAttempts(attempts + 1)
     setProcessing(false)
   }
 
@@ -36,6 +42,7 @@ export const LoginPage = () => {
           onChange={(e) => setPassword(e.target.value)}
         />
       </div>
+      <Captcha key={attempts} onToken={setCaptchaToken} />
       <div style={{ display: 'flex', flexFlow: 'column' }}>
         <button disabled={processing} onClick={login}>
           Login
@@ -1,12 +1,15 @@
 import React, { useState } from 'react'
 import { useNavigate } from 'react-router-dom'
 import { useAuth } from '../hooks/useAuth'
+import { Captcha } from '../components/Captcha'
 
 export const RecoveryPage = () => {
   const auth = useAuth()
   const navigate = useNavigate()
   const [email, setEmail] = useState<string>('')
   const [processing, setProcessing] = useState<boolean>(false)
+  const [captchaToken, setCaptchaToken] = useState<string>()
+  const [attempts, setAttempts] = useState<number>(0)
 
   const recover = async () => {
     setProcessing(true)
@@ -16,10 +19,13 @@ export const RecoveryPage = () => {
         headers: {
           'Content-Type': 'application/json',
         },
-        body: JSON.stringify({ email }),
+        body: JSON.stringify({ email, captcha_token: captchaToken }),
       })
     ).json()
     console.log(response)
+    // CAPTCHA tokens can only be used once
+    setCaptchaToken(undefined)
+    setAttempts(attempts + 1)
     setProcessing(false)
     setEmail('')
   }
@@ -37,6 +43,7 @@ export const RecoveryPage = () => {
         <label>Email</label>
         <input value={email} onChange={(e) => setEmail(e.target.value)} />
       </div>
+      <Captcha key={attempts} onToken={setCaptchaToken} />
       <div style={{ display: 'flex', flexFlow: 'column' }}>
         <button disabled={processing} onClick={recover}>
           Recover
@@ -1,37 +1,79 @@
-import React, { useState } from 'react'
+import React, { useEffect, useState } from 'react'
 import { useNavigate } from 'react-router-dom'
 import { useAuth } from '../hooks/useAuth'
+import { useQueryParam } from '../hooks/useQueryParam'
+import { Captcha } from '../components/Captcha'
 
 export const RegistrationPage = () => {
   const auth = useAuth()
   const navigate = useNavigate()
+  const invitationToken = useQueryParam('invitation_token')
   const [email, setEmail] = useState<string>('')
   const [password, setPassword] = useState<string>('')
   const [processing, setProcessing] = useState<boolean>(false)
+  const [error, setError] = useState<string>('')
+  const [captchaToken, setCaptchaToken] = useState<string>()
+  const [attempts, setAttempts] = useState<number>(0)
+
+  useEffect(() => {
+    if (!invitationToken) return
+
+    // fill in the email address the invitation was sent to
+    fetch(`/api/auth/invitation?invitation_token=${invitationToken}`)
+      .then(async (response) => {
+        const json = await response.json()
+        if (response.ok) {
+          setEmail((json as UserInvitation).email)
+        } else {
+          setError(json.message)
+        }
+      })
+      .catch(() => setError('Could not load the invitation.'))
+  }, [invitationToken])
 
   const register = async () => {
     setProcessing(true)
-    const response = (
-      await fetch('/api/auth/register', {
-        method: 'POST',
-        headers: {
-          'Content-Type': 'application/json',
-        },
-        body: JSON.stringify({ email, password }),
-      })
-    ).json()
-    console.log(response)
+    setError('')
+    const response = await fetch('/api/auth/register', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({
+        email,
+        password,
+        invitation_token: invitationToken || undefined,
+        captcha_token: captchaToken,
+      }),
+    })
+    const json = await response.json()
+    console.log(json)
+    // CAPTCHA tokens can only be used once
+    setCaptchaToken(undefined)
+    setAttempts(attempts + 1)
     setProcessing(false)
-    navigate('/activate')
+    if (!response.ok) {
+      setError(json.message)
+    } else if (invitationToken) {
+      // the invite link proved the email address, no activation needed
+      navigate('/login')
+    } else {
+      navigate('/activate')
+    }
   }
 
   return (
     <div className="Form" style={{ textAlign: 'left' }}>
       <h1>Registration</h1>
       <br />
+      {error && <div style={{ color: 'red' }}>{error}</div>}
       <div style={{ display: 'flex', flexFlow: 'column' }}>
         <label>Email</label>
-        <input value={email} onChange={(e) => setEmail(e.target.value)} />
+        <input
+          value={email}
+          readOnly={!!invitationToken}
+          onChange={(e) => setEmail(e.target.value)}
+        />
       </div>
       <div style={{ display: 'flex', flexFlow: 'column' }}>
         <label>Password</label>
@@ -41,6 +83,7 @@ export const RegistrationPage = () => {
           onChange={(e) => setPassword(e.target.value)}
         />
       </div>
+      <Captcha key={attempts} onToken={setCaptchaToken} />
       <div style={{ display: 'flex', flexFlow: 'column' }}>
         <button disabled={processing} onClick={register}>
           Register
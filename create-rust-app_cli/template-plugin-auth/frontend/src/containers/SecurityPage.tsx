@@ -0,0 +1,152 @@
+import React, { useEffect, useState } from 'react'
+import { useNavigate } from 'react-router-dom'
+import { useAuth } from '../hooks/useAuth'
+
+const formatDate = (date: UTC) => new Date(date).toLocaleString()
+
+export const SecurityPage = () => {
+  const auth = useAuth()
+  const navigate = useNavigate()
+
+  const [page, setPage] = useState<number>(0)
+  const [pageSize] = useState<number>(10)
+
+  const [isFetchingSessions, setFetchingSessions] = useState<boolean>(false)
+  const [sessions, setSessions] = useState<UserSessionResponse>({
+    sessions: [],
+    num_pages: 1,
+  })
+
+  const [isRevoking, setRevoking] = useState<boolean>(false)
+
+  const fetchSessions = async () => {
+    setFetchingSessions(true)
+
+    if (!auth.isAuthenticated) {
+      setSessions({ sessions: [], num_pages: 1 })
+      setFetchingSessions(false)
+      return
+    }
+
+    const sessions = await (
+      await fetch(`/api/auth/sessions?page=${page}&page_size=${pageSize}`, {
+        method: 'GET',
+        headers: {
+          Authorization: `Bearer ${auth.accessToken}`,
+        },
+      })
+    ).json()
+
+    setSessions(sessions)
+    setFetchingSessions(false)
+  }
+
+  const revokeSession = async (id: number) => {
+    setRevoking(true)
+
+    const response = await fetch(`/api/auth/sessions/${id}`, {
+      method: 'DELETE',
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    })
+
+    if (response.ok) {
+      if (sessions.sessions.length === 1 && page !== 0) {
+        setPage(page - 1)
+      }
+      await fetchSessions()
+    }
+
+    setRevoking(false)
+  }
+
+  const revokeOtherSessions = async () => {
+    setRevoking(true)
+
+    const response = await fetch(`/api/auth/sessions/others`, {
+      method: 'DELETE',
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    })
+
+    if (response.ok) {
+      setPage(0)
+      await fetchSessions()
+    }
+
+    setRevoking(false)
+  }
+
+  useEffect(() => {
+    fetchSessions()
+  }, [auth.isAuthenticated, page, pageSize])
+
+  if (!auth.isAuthenticated) {
+    return (
+      <div>
+        <a href="#" onClick={() => navigate('/login')}>
+          Login to manage your sessions
+        </a>
+      </div>
+    )
+  }
+
+  return (
+    <div style={{ textAlign: 'left' }}>
+      <h1>Security</h1>
+      <br />
+      <h2>Active sessions</h2>
+      <p>These are the devices that are currently signed in to your account.</p>
+      <button disabled={isRevoking} onClick={revokeOtherSessions}>
+        Sign out of all other sessions
+      </button>
+      <table style={{ width: '100%', textAlign: 'left' }}>
+        <thead>
+          <tr>
+            <th>Device</th>
+            <th>IP address</th>
+            <th>Last used</th>
+            <th>Signed in</th>
+            <th></th>
+          </tr>
+        </thead>
+        <tbody>
+          {sessions.sessions.map((session) => (
+            <tr key={session.id}>
+              <td>
+                {session.device || 'Unknown device'}
+                {session.current && <strong> (this device)</strong>}
+              </td>
+              <td>{session.ip_address || '-'}</td>
+              <td>{formatDate(session.last_used_at)}</td>
+              <td>{formatDate(session.created_at)}</td>
+              <td>
+                {!session.current && (
+                  <button
+                    disabled={isRevoking}
+                    onClick={() => revokeSession(session.id)}
+                  >
+                    Revoke
+                  </button>
+                )}
+              </td>
+            </tr>
+          ))}
+        </tbody>
+      </table>
+      {isFetchingSessions && <div>Fetching sessions...</div>}
+      <div>
+        <button disabled={page <= 0} onClick={() => setPage(page - 1)}>{`<<`}</button>
+        <span>
+          {page + 1} / {sessions.num_pages}
+        </span>
+        <button
+          disabled={page + 1 >= sessions.num_pages}
+          onClick={() => setPage(page + 1)}
+        >{`>>`}</button>
+      </div>
+    </div>
+  )
+}
@@ -81,28 +81,36 @@ export const AuthProvider = (props: AuthWrapperProps) => {
 export const useAuth = () => {
   const context = useContext(Context)
 
-  const login = async (email: string, password: string): Promise<boolean> => {
+  const setAccessToken = (accessToken: string) => {
+    const parsedToken = parseJwt(accessToken) as AccessTokenClaims
+    const permissions = new Permissions(parsedToken.roles, parsedToken.permissions)
+    context.setAccessToken(accessToken)
+    context.setSession({
+      userId: parsedToken.sub,
+      expiresOnUTC: parsedToken.exp,
+      roles: permissions.roles,
+      permissions: permissions.permissions,
+      hasPermission: permissions.hasPermission,
+      hasRole: permissions.hasRole,
+    })
+  }
+
+  const login = async (
+    email: string,
+    password: string,
+    captchaToken?: string
+  ): Promise<boolean> => {
     const response = await fetch('/api/auth/login', {
       method: 'POST',
       headers: {
         'Content-Type': 'application/json',
       },
-      body: JSON.stringify({ email, password }),
+      body: JSON.stringify({ email, password, captcha_token: captchaToken }),
     })
 
     if (response.ok) {
       const responseJson = await response.json()
-      const parsedToken = parseJwt(responseJson.access_token) as AccessTokenClaims
-      const permissions = new Permissions(parsedToken.roles, parsedToken.permissions)
-      context.setAccessToken(responseJson.access_token)
-      context.setSession({
-        userId: parsedToken.sub,
-        expiresOnUTC: parsedToken.exp,
-        roles: permissions.roles,
-        permissions: permissions.permissions,
-        hasPermission: permissions.hasPermission,
-        hasRole: permissions.hasRole,
-      })
+      setAccessToken(responseJson.access_token)
       return true
     } else {
       context.setAccessToken(undefined)
@@ -111,9 +119,64 @@ export const useAuth = () => {
     }
   }
 
+  /** starts a session without an account (the backend needs `guest_sessions` enabled) */
+  const loginAsGuest = async (): Promise<boolean> => {
+    const response = await fetch('/api/auth/guest', {
+      method: 'POST',
+    })
+
+    if (response.ok) {
+      const responseJson = await response.json()
+      setAccessToken(responseJson.access_token)
+      return true
+    } else {
+      return false
+    }
+  }
+
+  /**
+   * saves the guest's data: `upgrade` turns the guest into a new account, `merge` moves it to the
+   * existing account with these credentials and logs into it
+   *
+   * resolves to an error message, or `undefined` on success
+   */
+  const saveGuest = async (
+    action: 'upgrade' | 'merge',
+    email: string,
+    password: string,
+    captchaToken?: string,
+    invitationToken?: string
+  ): Promise<string | undefined> => {
+    const response = await fetch(`/api/auth/guest/${action}`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${context.accessToken}`,
+      },
+      body: JSON.stringify({
+        email,
+        password,
+        captcha_token: captchaToken,
+        invitation_token: invitationToken,
+      }),
+    })
+    const responseJson = await response.json()
+
+    if (response.ok) {
+      setAccessToken(responseJson.access_token)
+      return undefined
+    } else {
+      return responseJson.message
+    }
+  }
+
   const logout = async (): Promise<boolean> => {
+    // sending the access token revokes it
     const response = await fetch('/api/auth/logout', {
       method: 'POST',
+      headers: context.accessToken
+        ? { Authorization: `Bearer ${context.accessToken}` }
+        : undefined,
     })
 
     if (response.ok) {
@@ -130,7 +193,10 @@ export const useAuth = () => {
     session: context.session,
     isCheckingAuth: context.isCheckingAuth,
     isAuthenticated: !!context.accessToken,
+    isGuest: !!context.session?.hasRole('guest'),
     login,
+    loginAsGuest,
+    saveGuest,
     logout,
   }
 }
@@ -9,15 +9,21 @@ interface PaginationParams {
 
 interface AccessTokenClaims {
     exp: number
+    iat: number
+    jti: string
     sub: number
     token_type: string
     roles: Array<string>
     permissions: Array<Permission>
+    impersonator: number | undefined
 }
 
 interface UserSessionJson {
     id: number
     device: string | undefined
+    ip_address: string | undefined
+    last_used_at: UTC
+    current: boolean
     created_atDate
     updated_atDate
 }
@@ -27,6 +33,18 @@ interface UserSessionResponse {
     num_pages: number
 }
 
+interface UserIdentityJson {
+    id: number
+    provider: string
+    email: string | undefined
+    created_at: UTC
+}
+
+interface UserIdentityResponse {
+    identities: Array<UserIdentityJson>
+    providers: Array<string>
+}
+
 interface Permission {
     from_role: string
     permission: string
@@ -52,16 +70,16 @@ interface UserRole {
 
 interface User {
     id: number
-    email: string
-    hash_password: string
+    email: string | undefined
+    hash_password: string | undefined
     activated: boolean
     created_atDate
     updated_atDate
 }
 
 interface UserChangeset {
-    email: string
-    hash_password: string
+    email: string | undefined
+    hash_password: string | undefined
     activated: boolean
 }
 
@@ -72,10 +90,13 @@ interface UserSession {
     device: string | undefined
     created_atDate
     updated_atDate
+    ip_address: string | undefined
+    last_used_at: UTC
 }
 
 interface UserSessionChangeset {
     user_id: number
     refresh_token: string
     device: string | undefined
+    ip_address: string | undefined
 }
@@ -0,0 +1,33 @@
+///
+/// This binary runs the message consumers of `messages.rs`
+///
+/// Run as many instances as needed: each message is handled by a single consumer of its group
+///
+/// Remember to add your imports (mod statements) here as well,
+/// otherwise your `messages.rs` may not compile either since this binary (`consumers.rs`)
+/// will have a different set of included modules compared to `main.rs`
+///
+/// Use `cargo run --bin consumers` in development
+/// Use `cargo run --bin consumers --release` in production
+///
+
+extern crate diesel;
+
+use create_rust_app::broker::Broker;
+
+mod schema;
+mod models;
+mod messages;
+
+#[tokio::main]
+async fn main() {
+    // loads the `.env` (BROKER_URL, ...)
+    let _app_data = create_rust_app::setup();
+
+    println!("Running the message consumers...");
+
+    if let Err(err) = messages::consumers(Broker::new()).run().await {
+        println!("{err}");
+        std::process::exit(1);
+    }
+}
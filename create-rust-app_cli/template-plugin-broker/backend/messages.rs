@@ -0,0 +1,28 @@
+//! The app's messages, and their consumers (see `create_rust_app::broker`)
+//!
+//! Add `mod messages;` to `main.rs` to publish them from the server:
+//! `Broker::new().publish(messages::TODO_CREATED, &TodoCreated { todo_id: todo.id }).await`
+use create_rust_app::broker::{Broker, ConsumerConfig, Consumers};
+use serde::{Deserialize, Serialize};
+
+/// the topic of [`TodoCreated`]
+pub const TODO_CREATED: &str = "todos.created";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// an example message
+pub struct TodoCreated {
+    pub todo_id: i32,
+}
+
+/// the app's consumers, register yours here: they run in the `consumers` binary
+///
+/// a message is retried when its handler fails, and published to `<topic>.dlq` when it keeps
+/// failing (see `ConsumerConfig`)
+pub fn consumers(broker: Broker) -> Consumers {
+    Consumers::new(broker)
+        .config(ConsumerConfig::default())
+        .consume(TODO_CREATED, "log", |message: TodoCreated| async move {
+            println!("Todo #{} was created", message.todo_id);
+            Ok(())
+        })
+}
@@ -0,0 +1,63 @@
+import React, { useState } from 'react'
+import { useConsent } from '../hooks/useConsent'
+
+export const ConsentGate = () => {
+  const consent = useConsent()
+  const [isAccepting, setAccepting] = useState<boolean>(false)
+
+  const document = consent.pending[0]
+  if (!document) return null
+
+  const accept = async () => {
+    setAccepting(true)
+    await consent.accept(document)
+    setAccepting(false)
+  }
+
+  return (
+    <div
+      style={{
+        position: 'fixed',
+        inset: 0,
+        background: 'rgba(0, 0, 0, 0.5)',
+        display: 'flex',
+        alignItems: 'center',
+        justifyContent: 'center',
+        zIndex: 1000,
+      }}
+    >
+      <div
+        style={{
+          background: 'white',
+          color: 'black',
+          maxWidth: '600px',
+          width: '100%',
+          padding: '16px',
+          textAlign: 'left',
+        }}
+      >
+        <h2>{document.title}</h2>
+        <p>
+          {document.version > 1
+            ? `We've updated our ${document.title} (version ${document.version}). Please review and accept it to continue.`
+            : `Please review and accept our ${document.title} to continue.`}
+        </p>
+        <div
+          style={{
+            maxHeight: '50vh',
+            overflowY: 'auto',
+            whiteSpace: 'pre-wrap',
+            border: '1px solid #ccc',
+            padding: '8px',
+          }}
+        >
+          {document.content}
+        </div>
+        <br />
+        <button disabled={isAccepting} onClick={accept}>
+          I accept
+        </button>
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,32 @@
+import React, { useEffect, useState } from 'react'
+import { useParams } from 'react-router-dom'
+
+export const PolicyPage = () => {
+  const { kind } = useParams()
+  const [document, setDocument] = useState<PolicyDocument | undefined>(undefined)
+  const [isFetching, setFetching] = useState<boolean>(false)
+
+  useEffect(() => {
+    const fetchDocument = async () => {
+      setFetching(true)
+      const response = await fetch(`/api/consent/documents/${kind}`)
+      setDocument(response.ok ? await response.json() : undefined)
+      setFetching(false)
+    }
+
+    fetchDocument()
+  }, [kind])
+
+  if (isFetching) return <div>Loading...</div>
+  if (!document) return <div>Document not found.</div>
+
+  return (
+    <div style={{ textAlign: 'left' }}>
+      <h1>{document.title}</h1>
+      <p>
+        Version {document.version}, published {new Date(document.created_at).toLocaleDateString()}
+      </p>
+      <div style={{ whiteSpace: 'pre-wrap' }}>{document.content}</div>
+    </div>
+  )
+}
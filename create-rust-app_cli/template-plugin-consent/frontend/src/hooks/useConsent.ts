@@ -0,0 +1,49 @@
+import { useEffect, useState } from 'react'
+import { useAuth } from './useAuth'
+
+export const useConsent = () => {
+  const auth = useAuth()
+  const [pending, setPending] = useState<PolicyDocument[]>([])
+  const [isFetching, setFetching] = useState<boolean>(false)
+
+  const fetchPending = async () => {
+    if (!auth.isAuthenticated) {
+      setPending([])
+      return
+    }
+
+    setFetching(true)
+    const response = await fetch('/api/consent/pending', {
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    })
+    if (response.ok) {
+      setPending(await response.json())
+    }
+    setFetching(false)
+  }
+
+  const accept = async (document: PolicyDocument): Promise<boolean> => {
+    const response = await fetch('/api/consent/accept', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({ document_id: document.id }),
+    })
+
+    if (response.ok) {
+      setPending(pending.filter((d) => d.id !== document.id))
+    }
+
+    return response.ok
+  }
+
+  useEffect(() => {
+    fetchPending()
+  }, [auth.isAuthenticated])
+
+  return { pending, isFetching, accept, refetch: fetchPending }
+}
@@ -0,0 +1,30 @@
+/* This file is generated and managed by tsync */
+
+interface PolicyDocument {
+  id: number
+  kind: string
+  version: number
+  title: string
+  content: string
+  created_at: Date
+}
+
+interface PolicyDocumentChangeset {
+  kind: string
+  version: number
+  title: string
+  content: string
+}
+
+interface UserConsent {
+  user_id: number
+  document_id: number
+  ip_address?: string
+  accepted_at: Date
+}
+
+interface UserConsentChangeset {
+  user_id: number
+  document_id: number
+  ip_address?: string
+}
@@ -0,0 +1,63 @@
+use std::{path::PathBuf, process::Command};
+
+/// `cargo desktop`: runs the desktop app, loading the backend `cargo fullstack` runs
+///
+/// `cargo desktop build`: builds the desktop app's installers (with the Tauri CLI, `cargo install
+/// tauri-cli`), embedding the release build of the backend and the frontend
+pub fn main() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let release = std::env::args().nth(1).as_deref() == Some("build");
+
+    if release {
+        let built = Command::new("npm")
+            .args(["run", "build"])
+            .current_dir(dir.join("frontend"))
+            .status()
+            .unwrap();
+        if !built.success() {
+            panic!("Could not build the frontend");
+        }
+    }
+
+    // tauri bundles the backend's binary from `desktop/binaries/backend-<target triple>`
+    let mut build = Command::new("cargo");
+    build.args(["build", "--bin", env!("CARGO_PKG_NAME")]);
+    if release {
+        build.arg("--release");
+    }
+    if !build.current_dir(&dir).status().unwrap().success() {
+        panic!("Could not build the backend");
+    }
+
+    let rustc = Command::new("rustc").arg("-vV").output().unwrap();
+    let target = String::from_utf8_lossy(&rustc.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: ").map(String::from))
+        .expect("Could not find the target triple");
+    let binaries = dir.join("desktop/binaries");
+    std::fs::create_dir_all(&binaries).unwrap();
+    std::fs::copy(
+        dir.join(".cargo/.build")
+            .join(if release { "release" } else { "debug" })
+            .join(format!(
+                "{}{}",
+                env!("CARGO_PKG_NAME"),
+                std::env::consts::EXE_SUFFIX
+            )),
+        binaries.join(format!("backend-{target}{}", std::env::consts::EXE_SUFFIX)),
+    )
+    .unwrap();
+
+    let mut desktop = Command::new("cargo");
+    if release {
+        desktop
+            .args(["tauri", "build"])
+            .current_dir(dir.join("desktop"));
+    } else {
+        desktop
+            .args(["run", "--manifest-path", "desktop/Cargo.toml"])
+            .current_dir(&dir);
+    }
+
+    desktop.spawn().unwrap().wait_with_output().unwrap();
+}
@@ -0,0 +1,170 @@
+// no console window on Windows in release builds
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use std::collections::HashMap;
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use tauri::api::process::{Command, CommandChild, CommandEvent};
+use tauri::{AppHandle, Manager, RunEvent, Window};
+
+/// how long the embedded backend has to start listening
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// the embedded backend's process, killed when the app exits
+struct Backend(Mutex<Option<CommandChild>>);
+
+/// the OS keychain's entry `name`, stored under the app's identifier
+fn keychain(app: &AppHandle, name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(&app.config().tauri.bundle.identifier, name).map_err(|err| err.to_string())
+}
+
+/// the refresh token of the signed in user, see `frontend/src/desktop.ts`
+#[tauri::command]
+fn refresh_token(app: AppHandle) -> Result<Option<String>, String> {
+    match keychain(&app, "refresh_token")?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// stores the refresh token of the signed in user, or forgets it (`None`)
+#[tauri::command]
+fn set_refresh_token(app: AppHandle, token: Option<String>) -> Result<(), String> {
+    let entry = keychain(&app, "refresh_token")?;
+
+    match token {
+        Some(token) => entry.set_password(&token).map_err(|err| err.to_string()),
+        None => match entry.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(err.to_string()),
+        },
+    }
+}
+
+/// the embedded backend's `SECRET_KEY`, generated on the first launch
+fn secret_key(app: &AppHandle) -> Result<String, String> {
+    let entry = keychain(app, "secret_key")?;
+
+    match entry.get_password() {
+        Ok(key) => Ok(key),
+        Err(keyring::Error::NoEntry) => {
+            let [high, low] = rand::thread_rng().gen::<[u128; 2]>();
+            let key = format!("{high:032x}{low:032x}");
+            entry.set_password(&key).map_err(|err| err.to_string())?;
+            Ok(key)
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// starts the backend bundled as a sidecar on a free port, returns its URL
+///
+/// it runs from the bundled resources (`frontend/dist`, `backend/views` and `migrations`, under
+/// `_up_/` as they're outside of `desktop/`), with an SQLite database in the app's data directory
+fn start_backend(app: &AppHandle) -> Result<(u16, String), String> {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map_err(|err| format!("could not find a free port: {err}"))?
+        .port();
+    let resources = app
+        .path_resolver()
+        .resource_dir()
+        .ok_or("could not find the resources")?;
+    let data_dir = app
+        .path_resolver()
+        .app_data_dir()
+        .ok_or("could not find the data directory")?;
+    std::fs::create_dir_all(&data_dir).map_err(|err| err.to_string())?;
+
+    let envs = HashMap::from([
+        ("CRA_DESKTOP".to_string(), "1".to_string()),
+        ("BIND_ADDRESS".to_string(), format!("127.0.0.1:{port}")),
+        (
+            "DATABASE_URL".to_string(),
+            data_dir.join("database.sqlite").display().to_string(),
+        ),
+        ("SECRET_KEY".to_string(), secret_key(app)?),
+    ]);
+
+    let (mut events, child) = Command::new_sidecar("backend")
+        .map_err(|err| err.to_string())?
+        .current_dir(resources.join("_up_"))
+        .envs(envs)
+        .spawn()
+        .map_err(|err| format!("could not start the backend: {err}"))?;
+    app.state::<Backend>().0.lock().unwrap().replace(child);
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = events.recv().await {
+            match event {
+                CommandEvent::Stdout(line) | CommandEvent::Stderr(line) => {
+                    println!("backend: {line}")
+                }
+                CommandEvent::Terminated(payload) => {
+                    println!("backend: exited ({:?})", payload.code)
+                }
+                _ => {}
+            }
+        }
+    });
+
+    Ok((port, format!("http://localhost:{port}")))
+}
+
+fn navigate(window: &Window, url: &str) {
+    if let Err(err) = window.eval(&format!("window.location.replace({url:?})")) {
+        println!("Could not load {url}: {err}");
+    }
+}
+
+fn main() {
+    tauri::Builder::default()
+        .manage(Backend(Mutex::new(None)))
+        .invoke_handler(tauri::generate_handler![refresh_token, set_refresh_token])
+        .setup(|app| {
+            let window = app.get_window("main").expect("no main window");
+
+            // connects to a running backend instead of embedding one
+            if let Some(url) = std::env::var("DESKTOP_BACKEND_URL")
+                .ok()
+                .or_else(|| option_env!("DESKTOP_BACKEND_URL").map(String::from))
+            {
+                navigate(&window, &url);
+                return Ok(());
+            }
+
+            // in development, the window loads the backend `cargo fullstack` runs (`devPath`)
+            if cfg!(debug_assertions) {
+                return Ok(());
+            }
+
+            let (port, url) = start_backend(&app.handle())?;
+            std::thread::spawn(move || {
+                let started = Instant::now();
+                while TcpStream::connect(("127.0.0.1", port)).is_err() {
+                    if started.elapsed() > STARTUP_TIMEOUT {
+                        let _ = window.eval("document.body.dataset.failed = ''");
+                        return;
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+
+                navigate(&window, &url);
+            });
+
+            Ok(())
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building the desktop app")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                if let Some(child) = app.state::<Backend>().0.lock().unwrap().take() {
+                    let _ = child.kill();
+                }
+            }
+        });
+}
@@ -0,0 +1,49 @@
+import { invoke } from '@tauri-apps/api/tauri'
+
+/** whether the frontend runs in the desktop app's window (see `desktop/`) */
+export const isDesktop = () => '__TAURI_IPC__' in window
+
+const requestUrl = (input: RequestInfo | URL) =>
+  new URL(input instanceof Request ? input.url : input.toString(), window.location.href)
+
+/**
+ * Makes the auth plugin's requests work in the desktop app, whose webview doesn't keep the
+ * refresh token's cookie reliably: they ask for the refresh token in the responses
+ * (`X-Auth-Client: desktop`), which is kept in the OS keychain and sent back in the
+ * `X-Refresh-Token` header.
+ *
+ * Does nothing in browsers.
+ */
+export const installDesktopAuth = () => {
+  if (!isDesktop()) return
+
+  const fetch = window.fetch.bind(window)
+
+  window.fetch = async (input, init) => {
+    const url = requestUrl(input)
+    if (url.origin !== window.location.origin || !url.pathname.startsWith('/api/auth/')) {
+      return fetch(input, init)
+    }
+
+    const headers = new Headers(init?.headers ?? (input instanceof Request ? input.headers : undefined))
+    headers.set('X-Auth-Client', 'desktop')
+    const refreshToken = await invoke<string | null>('refresh_token')
+    if (refreshToken) headers.set('X-Refresh-Token', refreshToken)
+
+    const response = await fetch(input, { ...init, headers })
+
+    const signedOut =
+      (url.pathname === '/api/auth/logout' && response.ok) ||
+      (url.pathname === '/api/auth/refresh' && response.status === 401)
+    if (signedOut) {
+      await invoke('set_refresh_token', { token: null })
+    } else if (response.ok) {
+      const body = await response.clone().json().catch(() => undefined)
+      if (body?.refresh_token) {
+        await invoke('set_refresh_token', { token: body.refresh_token })
+      }
+    }
+
+    return response
+  }
+}
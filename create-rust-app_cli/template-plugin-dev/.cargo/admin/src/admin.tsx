@@ -1,19 +1,270 @@
-import React, { useState } from 'react'
+import React, { useEffect, useRef, useState } from 'react'
 import ReactDOM from 'react-dom'
-import { QueryClient, QueryClientProvider, useQuery } from 'react-query'
+import { QueryClient, QueryClientProvider, useQuery, useQueryClient } from 'react-query'
 
-const fetchQuery = (query) => fetch('/api/development/db/query', { method: 'POST', body: JSON.stringify({query: query}), headers: { 'Content-Type': 'application/json' } }).then(r => r.json())
+let accessToken: string | undefined = undefined
 
-const useTableCount = (tableName: string) => {
-  return useQuery(`table-${tableName}-count`, () => fetchQuery(`SELECT COUNT(*) FROM ${tableName}`).then(r => r[0]['count']))
+/**
+ * Calls the admin API. It requires an access token with the "admin" role (unless the portal is
+ * open, see `AdminInfo.open`): the portal gets one from the app's refresh token cookie (log into the app first).
+ */
+const api = <T,>(path: string, init: RequestInit = {}): Promise<T> => request(`/api/admin${path}`, init)
+
+/** request to another API of the app, with the portal's access token */
+const request = async <T,>(url: string, init: RequestInit = {}): Promise<T> => {
+  const body = await (await authorizedFetch(url, init)).text()
+  return body ? JSON.parse(body) : undefined
+}
+
+/** fetches `url` with the portal's access token, refreshing it once if it expired; throws on errors */
+const authorizedFetch = async (url: string, init: RequestInit = {}, retry: boolean = true): Promise<Response> => {
+  const response = await fetch(url, {
+    ...init,
+    headers: {
+      ...(init.body ? { 'Content-Type': 'application/json' } : {}),
+      ...(accessToken ? { 'Authorization': `Bearer ${accessToken}` } : {}),
+    },
+  })
+
+  if (response.status === 401 && retry) {
+    const refresh = await fetch('/api/auth/refresh', { method: 'POST' })
+    if (refresh.ok) {
+      accessToken = (await refresh.json()).access_token
+      return authorizedFetch(url, init, false)
+    }
+  }
+
+  if (!response.ok) {
+    throw new ApiError(response.status, (await response.json().catch(() => ({}))).message)
+  }
+
+  return response
+}
+
+class ApiError extends Error {
+  status: number
+  constructor(status: number, message?: string) {
+    super(message || `Request failed (${status})`)
+    this.status = status
+  }
+}
+
+/** development-only: runs raw SQL, see `AdminInfo.dev_tools` */
+const fetchQuery = (query: string): Promise<Record<string, any>[]> => request('/api/development/db/query', { method: 'POST', body: JSON.stringify({query: query}) })
+
+/** development-only: the emails captured by the backend's mailbox */
+const fetchMailbox = <T,>(path: string = '', method: string = 'GET'): Promise<T> => request(`/api/development/mailbox${path}`, { method })
+
+interface MailboxEntry {
+  id: string,
+  from: string,
+  to: string,
+  subject: string,
+  sent_at: number
+}
+
+interface MailboxMessage extends MailboxEntry {
+  text: string,
+  html: string,
+  headers: string,
+  attachments: string[]
+}
+
+/** development-only: a migration of the `migrations/` folder */
+interface Migration {
+  name: string,
+  version: string,
+  status: 'Applied' | 'Pending' | 'AppliedButMissingLocally' | 'Unknown'
+}
+
+/** development-only: an HTTP request recorded by the backend's inspector */
+interface MinuteMetrics {
+  at: number,
+  requests: number,
+  errors: number,
+  latency_ms: number
+}
+
+interface Metrics {
+  pool: { connections: number, idle_connections: number, max_size: number },
+  process: { memory_bytes?: number, cpu_percent?: number },
+  requests: {
+    minutes: number,
+    total: number,
+    errors: number,
+    error_rate: number,
+    requests_per_minute: number,
+    average_latency_ms: number,
+    timeline: MinuteMetrics[]
+  }
+}
+
+interface RecordedRequest {
+  id: number,
+  method: string,
+  path: string,
+  query?: string,
+  status: number,
+  latency_ms: number,
+  user_id?: number,
+  request_size?: number,
+  response_size?: number,
+  at: number
+}
+
+/** development-only: a mail template which can be previewed */
+interface MailTemplate {
+  name: string,
+  locales: string[]
+}
+
+interface AdminInfo {
+  dev_tools: boolean,
+  sql_console: boolean,
+  mail_log: boolean,
+  audit_log: boolean,
+  scheduler: boolean,
+  flags: boolean,
+  webhooks: boolean,
+  users: boolean,
+  /** debug build: the environment and build info are available */
+  environment: boolean,
+  /** debug build with `CRA_ADMIN_OPEN=true` (or without the auth plugin): no admin role required */
+  open: boolean
+}
+
+interface UserBan {
+  id: number,
+  user_id: number,
+  reason?: string,
+  banned_by?: number,
+  created_at: string
+}
+
+interface AdminUser {
+  id: number,
+  email?: string,
+  activated: boolean,
+  guest: boolean,
+  roles: string[],
+  ban?: UserBan,
+  created_at: string
+}
+
+interface AdminUserDetails extends AdminUser {
+  permissions: { from_role: string, permission: string }[],
+  sessions: number
+}
+
+interface LoggedEmail {
+  id: number,
+  recipient: string,
+  subject: string,
+  template?: string,
+  status: 'sent' | 'failed' | 'skipped',
+  provider_message_id?: string,
+  error?: string,
+  created_at: string
+}
+
+interface Environment {
+  name?: string,
+  version?: string,
+  git_commit?: string,
+  built_at?: string,
+  profile: 'debug' | 'release',
+  create_rust_app_version: string,
+  features: string[],
+  dependencies: { name: string, version: string }[],
+  env: { name: string, value: string, redacted: boolean }[]
 }
 
-const TableLink = (props: {name: string, onClick: () => void}) => {
-  const countQuery = useTableCount(props.name)
-  
-  return <>
-    <button onClick={props.onClick} className="flex-1 truncate text-left hover:underline text-blue-500 hover:text-blue-700">{props.name}</button> ({typeof countQuery.data === 'number' ? countQuery.data : countQuery.isFetching ? '…' : '-'})
-  </>
+interface Backup {
+  name: string,
+  size_bytes: number,
+  created_at: number
+}
+
+interface AuditEntry {
+  id: number,
+  actor_id?: number,
+  action: string,
+  target_type: string,
+  target_id?: string,
+  /** JSON: `{"field": {"from": ..., "to": ...}}` */
+  diff?: string,
+  ip_address?: string,
+  created_at: string
+}
+
+interface ScheduledTask {
+  id: number,
+  name: string,
+  cron: string,
+  next_run_at: string,
+  /** set while the task runs */
+  locked_until?: string,
+  created_at: string
+}
+
+interface TaskRun {
+  id: number,
+  task_name: string,
+  scheduled_for: string,
+  status: 'running' | 'succeeded' | 'failed' | 'skipped',
+  error?: string,
+  started_at: string,
+  finished_at?: string
+}
+
+interface FeatureFlag {
+  id: number,
+  key: string,
+  description: string,
+  /** overrides the flag's default when set */
+  enabled?: boolean,
+  rollout_percentage?: number,
+  /** json array of the targeted users' ids */
+  user_ids: string,
+  created_at: string,
+  updated_at: string
+}
+
+interface FlagOverview {
+  flag: FeatureFlag,
+  /** false for flags no longer defined in code, they're always off */
+  defined: boolean,
+  /** the default in the current environment */
+  default: boolean,
+  environments: Record<string, boolean>
+}
+
+interface WebhookDelivery {
+  id: number,
+  endpoint_id: number,
+  event_type: string,
+  /** the event's data (json) */
+  payload: string,
+  status: 'pending' | 'succeeded' | 'failed',
+  /** how many attempts failed */
+  attempts: number,
+  response_status?: number,
+  response_body?: string,
+  last_error?: string,
+  next_attempt_at: string,
+  delivered_at?: string,
+  created_at: string
+}
+
+interface DeliveryOverview {
+  delivery: WebhookDelivery,
+  url: string,
+  user_id: number
+}
+
+interface TableInfo {
+  name: string,
+  count: number
 }
 
 interface TableColumn {
@@ -21,72 +272,1276 @@ interface TableColumn {
   data_type: string
 }
 
-const RowView = (props: {columns: TableColumn[], table: string }) => {
-  const rowsQuery = useQuery<Record<string, any>>(`table-${props.table}-rows`, () => fetchQuery(`SELECT * FROM ${props.table} LIMIT 50`))
-  
-  return rowsQuery.data && rowsQuery.data.map(row => <tr className="odd:bg-grey-400">
-    {props.columns.map(column => <td className="max-w-xs truncate">{JSON.stringify(row[column.column_name])}</td>)}
-  </tr>) || null
+interface TableRows {
+  columns: TableColumn[],
+  /** their values are redacted, and can't be set */
+  secret_columns: string[],
+  rows: Record<string, any>[],
+  total: number
+}
+
+interface SchemaColumn {
+  column_name: string,
+  data_type: string,
+  nullable: boolean,
+  primary_key: boolean
+}
+
+interface ForeignKey {
+  table_name: string,
+  column_name: string,
+  foreign_table_name: string,
+  foreign_column_name: string
+}
+
+interface Schema {
+  tables: { name: string, columns: SchemaColumn[] }[],
+  foreign_keys: ForeignKey[]
+}
+
+interface SearchResult {
+  resource: string,
+  id: string,
+  title: string,
+  subtitle?: string
+}
+
+const PAGE_SIZE = 50
+
+const searchRecords = (query: string): Promise<SearchResult[]> => api(`/search?q=${encodeURIComponent(query)}`)
+
+/** the value typed in a record form, as json (empty inputs are null) */
+const formValue = (value: string): any => value === '' ? null : value
+
+/** creates a record (without `recordId`) or edits one, with an input per column (except the secret ones) */
+const RecordForm = (props: {table: string, columns: TableColumn[], secretColumns: string[], recordId?: string, record?: Record<string, any>, onDone: () => void}) => {
+  const initial = Object.fromEntries(props.columns.map(column => {
+    const value = props.record?.[column.column_name]
+    return [column.column_name, value === null || value === undefined ? '' : typeof value === 'object' ? JSON.stringify(value) : `${value}`]
+  }))
+  const [values, setValues] = useState<Record<string, string>>(initial)
+  const [error, setError] = useState<string | undefined>(undefined)
+  const [saving, setSaving] = useState<boolean>(false)
+
+  const save = async () => {
+    // only the edited columns are sent, the others keep their (default) value
+    const changed = Object.fromEntries(Object.entries(values)
+      .filter(([name, value]) => value !== initial[name])
+      .map(([name, value]) => [name, formValue(value)]))
+    const path = `/tables/${encodeURIComponent(props.table)}${props.recordId !== undefined ? `/${encodeURIComponent(props.recordId)}` : ''}`
+
+    setSaving(true)
+    setError(undefined)
+    try {
+      await api(path, { method: props.recordId !== undefined ? 'PUT' : 'POST', body: JSON.stringify(changed) })
+      props.onDone()
+    } catch (e) {
+      setError((e as Error).message)
+    }
+    setSaving(false)
+  }
+
+  return <div className="border-2 p-2 my-2">
+    <h2 className="font-bold">{props.recordId !== undefined ? `Edit #${props.recordId}` : 'New record'}</h2>
+    {props.columns.map(column =>
+      <label key={column.column_name} className="flex my-1">
+        <span className="w-48 truncate">{column.column_name} <span className="text-xs text-gray-500">({column.data_type})</span></span>
+        <input className="flex-1 border-2 px-2" disabled={(props.recordId !== undefined && column.column_name === 'id') || props.secretColumns.includes(column.column_name)} placeholder={props.recordId === undefined ? 'default' : 'null'} value={values[column.column_name]} onChange={e => setValues({ ...values, [column.column_name]: e.target.value })} />
+      </label>
+    )}
+    {error && <div className="text-red-500">{error}</div>}
+    <button disabled={saving} onClick={save} className="hover:underline text-blue-500 hover:text-blue-700">Save</button>
+    <button onClick={props.onDone} className="ml-4 hover:underline text-blue-500 hover:text-blue-700">Cancel</button>
+  </div>
 }
 
-const TableView = (props: {name: string}) => {
-  const tableCountQuery = useTableCount(props.name)
-  const viewQuery = useQuery<TableColumn[]>(`table-${props.name}-columns`, () => fetchQuery(`
-    SELECT *
-    FROM information_schema.columns
-    WHERE table_name   = '${props.name}'
-  `))
+const TableView = (props: {name: string, recordId?: string, onShowAll: () => void}) => {
+  const client = useQueryClient()
+  const [page, setPage] = useState<number>(0)
+  const [search, setSearch] = useState<string>('')
+  const [sort, setSort] = useState<{column: string, desc: boolean} | undefined>(undefined)
+  const [editing, setEditing] = useState<{id?: string, record?: Record<string, any>} | undefined>(undefined)
+  const [error, setError] = useState<string | undefined>(undefined)
+  useEffect(() => { setPage(0); setSearch(''); setSort(undefined); setEditing(undefined); setError(undefined) }, [props.name, props.recordId])
+
+  const params = new URLSearchParams({ page: `${page}`, page_size: `${PAGE_SIZE}` })
+  if (props.recordId !== undefined) params.set('id', props.recordId)
+  if (search) params.set('search', search)
+  if (sort) {
+    params.set('sort', sort.column)
+    params.set('desc', `${sort.desc}`)
+  }
+
+  const rowsQuery = useQuery<TableRows>(['table', props.name, props.recordId ?? 'all', page, search, sort?.column, sort?.desc], () => api(`/tables/${encodeURIComponent(props.name)}?${params}`), { keepPreviousData: true })
+  const total = rowsQuery.data?.total
+  const pages = Math.max(1, Math.ceil((total ?? 0) / PAGE_SIZE))
+  const hasId = rowsQuery.data?.columns.some(column => column.column_name === 'id')
+
+  const sortBy = (column: string) => {
+    if (rowsQuery.data?.secret_columns.includes(column)) return
+    setSort(sort?.column === column ? { column, desc: !sort.desc } : { column, desc: false })
+    setPage(0)
+  }
+
+  const refresh = () => {
+    setEditing(undefined)
+    client.invalidateQueries(['table', props.name])
+    client.invalidateQueries('tables')
+  }
+
+  const remove = async (id: string) => {
+    if (!window.confirm(`Delete ${props.name} #${id}?`)) return
+    setError(undefined)
+    try {
+      await api(`/tables/${encodeURIComponent(props.name)}/${encodeURIComponent(id)}`, { method: 'DELETE' })
+      refresh()
+    } catch (e) {
+      setError((e as Error).message)
+    }
+  }
 
   return <div>
-    <h1 className="font-bold text-xl">{props.name} {viewQuery.isFetching && <span className="text-grey-500 text-xs">(Loading...)</span>}</h1>
+    <h1 className="font-bold text-xl">{props.name} {rowsQuery.isFetching && <span className="text-grey-500 text-xs">(Loading...)</span>}</h1>
+    {props.recordId !== undefined && <div className="text-sm">
+      Showing record #{props.recordId} <button onClick={props.onShowAll} className="hover:underline text-blue-500 hover:text-blue-700">(show all)</button>
+    </div>}
+    <div className="flex my-2">
+      <input className="border-2 px-2" placeholder="Search" value={search} onChange={e => { setSearch(e.target.value); setPage(0) }} />
+      <button onClick={() => setEditing({})} className="ml-4 hover:underline text-blue-500 hover:text-blue-700">New record</button>
+    </div>
+    {editing && rowsQuery.data && <RecordForm key={editing.id ?? 'new'} table={props.name} columns={rowsQuery.data.columns} secretColumns={rowsQuery.data.secret_columns} recordId={editing.id} record={editing.record} onDone={refresh} />}
+    {rowsQuery.error && <div className="text-red-500">{(rowsQuery.error as Error).message}</div>}
+    {error && <div className="text-red-500">{error}</div>}
     <table className="table-auto w-full border-grey-500 border-2">
       <thead>
         <tr className="text-left align-top border-b-2">
-          {viewQuery.data && viewQuery.data.map(col => 
-            <th className="p-2">{col.column_name}<br/><span className="text-xs">({col.data_type})</span></th>
+          {rowsQuery.data && rowsQuery.data.columns.map(col =>
+            <th className="p-2 cursor-pointer" onClick={() => sortBy(col.column_name)}>
+              {col.column_name} {sort?.column === col.column_name && (sort.desc ? '▼' : '▲')}<br/><span className="text-xs">({col.data_type})</span>
+            </th>
           )}
+          {hasId && <th></th>}
+        </tr>
+      </thead>
+      <tbody>
+        {rowsQuery.data && rowsQuery.data.rows.map(row => <tr className="odd:bg-grey-400">
+          {rowsQuery.data.columns.map(column => <td className="max-w-xs truncate">{JSON.stringify(row[column.column_name])}</td>)}
+          {hasId && <td className="whitespace-nowrap">
+            <button onClick={() => setEditing({ id: `${row.id}`, record: row })} className="hover:underline text-blue-500 hover:text-blue-700">Edit</button>
+            <button onClick={() => remove(`${row.id}`)} className="ml-2 hover:underline text-red-500 hover:text-red-700">Delete</button>
+          </td>}
+        </tr>)}
+      </tbody>
+    </table>
+    <div className="flex">
+      <div className="flex-1"></div>
+      <button disabled={page === 0} onClick={() => setPage(page - 1)} className="px-2 disabled:text-gray-400">‹</button>
+      <div>{total ? page * PAGE_SIZE + 1 : 0} - {Math.min((page + 1) * PAGE_SIZE, total ?? 0)} of {typeof total === 'number' ? total : '?'}</div>
+      <button disabled={page + 1 >= pages} onClick={() => setPage(page + 1)} className="px-2 disabled:text-gray-400">›</button>
+    </div>
+  </div>
+}
+
+/** development-only SQL console */
+const SqlConsole = (props: {enabled: boolean}) => {
+  const [query, setQuery] = useState<string>('SELECT 1')
+  const [rows, setRows] = useState<Record<string, any>[] | undefined>(undefined)
+  const [error, setError] = useState<string | undefined>(undefined)
+  const [running, setRunning] = useState<boolean>(false)
+  const columns = Array.from(new Set((rows ?? []).flatMap(row => Object.keys(row))))
+
+  const run = async () => {
+    setRunning(true)
+    setError(undefined)
+    try {
+      setRows(await fetchQuery(query))
+    } catch (e) {
+      setRows(undefined)
+      setError((e as Error).message)
+    }
+    setRunning(false)
+  }
+
+  if (!props.enabled) {
+    return <div>
+      <h1 className="font-bold text-xl">SQL console <span className="text-xs text-gray-500">(development only)</span></h1>
+      <div className="text-gray-500">The SQL console is disabled. Set <code>CRA_SQL_CONSOLE=true</code> in the backend's environment to enable it.</div>
+    </div>
+  }
+
+  return <div>
+    <h1 className="font-bold text-xl">SQL console <span className="text-xs text-gray-500">(development only, read-only)</span></h1>
+    <textarea className="w-full border-2 p-2 font-mono" rows={4} value={query} onChange={e => setQuery(e.target.value)} />
+    <button disabled={running} onClick={run} className="hover:underline text-blue-500 hover:text-blue-700">Run</button>
+    {error && <pre className="text-red-500 text-xs whitespace-pre-wrap">{error}</pre>}
+    {rows && <div className="text-xs text-gray-500">{rows.length} row{rows.length === 1 ? '' : 's'}</div>}
+    {rows && rows.length > 0 && <div className="overflow-auto">
+      <table className="table-auto w-full border-grey-500 border-2">
+        <thead>
+          <tr className="text-left border-b-2">{columns.map(column => <th key={column} className="p-2">{column}</th>)}</tr>
+        </thead>
+        <tbody>
+          {rows.map((row, i) => <tr key={i} className="odd:bg-grey-400">
+            {columns.map(column => <td key={column} className="max-w-xs truncate">{JSON.stringify(row[column])}</td>)}
+          </tr>)}
+        </tbody>
+      </table>
+    </div>}
+  </div>
+}
+
+/** development-only: the requests recorded by the backend's inspector, refreshed live */
+const Requests = () => {
+  const client = useQueryClient()
+  const [path, setPath] = useState<string>('')
+  const [method, setMethod] = useState<string>('')
+  const [errorsOnly, setErrorsOnly] = useState<boolean>(false)
+  const [live, setLive] = useState<boolean>(true)
+  const params = new URLSearchParams()
+  if (path) params.set('path', path)
+  if (method) params.set('method', method)
+  if (errorsOnly) params.set('min_status', '400')
+  const requestsQuery = useQuery<RecordedRequest[]>(['requests', path, method, errorsOnly], () => request(`/api/development/requests?${params}`), { refetchInterval: live ? 2000 : false, keepPreviousData: true })
+
+  const clear = async () => {
+    await request('/api/development/requests', { method: 'DELETE' })
+    client.invalidateQueries('requests')
+  }
+
+  return <div>
+    <h1 className="font-bold text-xl">Requests <span className="text-xs text-gray-500">(development only)</span></h1>
+    <div className="flex my-2 items-center">
+      <input className="border-2 px-2" placeholder="Path prefix, ex: /api/todos" value={path} onChange={e => setPath(e.target.value)} />
+      <select className="border-2 ml-2" value={method} onChange={e => setMethod(e.target.value)}>
+        <option value="">Any method</option>
+        {['GET', 'POST', 'PUT', 'PATCH', 'DELETE'].map(m => <option key={m} value={m}>{m}</option>)}
+      </select>
+      <label className="ml-4"><input type="checkbox" checked={errorsOnly} onChange={e => setErrorsOnly(e.target.checked)} /> errors only</label>
+      <label className="ml-4"><input type="checkbox" checked={live} onChange={e => setLive(e.target.checked)} /> live</label>
+      <button onClick={clear} className="ml-4 hover:underline text-blue-500 hover:text-blue-700">Clear</button>
+    </div>
+    <table className="table-auto w-full border-grey-500 border-2 text-sm">
+      <thead>
+        <tr className="text-left border-b-2">
+          <th className="p-2">time</th>
+          <th className="p-2">method</th>
+          <th className="p-2">path</th>
+          <th className="p-2">status</th>
+          <th className="p-2">latency</th>
+          <th className="p-2">user</th>
+          <th className="p-2">request / response</th>
+        </tr>
+      </thead>
+      <tbody>
+        {requestsQuery.data?.length === 0 && <tr><td colSpan={7} className="p-2 text-gray-500">No requests yet.</td></tr>}
+        {requestsQuery.data?.map(r => <tr key={r.id} className="odd:bg-grey-400">
+          <td className="p-2 whitespace-nowrap text-xs">{new Date(r.at).toLocaleTimeString()}</td>
+          <td className="p-2">{r.method}</td>
+          <td className="p-2 max-w-xs truncate" title={r.query ? `${r.path}?${r.query}` : r.path}>
+            <button onClick={() => setPath(r.path)} className="hover:underline">{r.path}</button>{r.query && <span className="text-gray-500">?{r.query}</span>}
+          </td>
+          <td className={`p-2 ${r.status >= 500 ? 'text-red-500' : r.status >= 400 ? 'text-orange-500' : 'text-green-600'}`}>{r.status}</td>
+          <td className="p-2 whitespace-nowrap">{r.latency_ms.toFixed(1)} ms</td>
+          <td className="p-2">{r.user_id ?? '-'}</td>
+          <td className="p-2 whitespace-nowrap text-xs">{r.request_size ?? '-'} B / {r.response_size ?? '-'} B</td>
+        </tr>)}
+      </tbody>
+    </table>
+  </div>
+}
+
+/** development-only: the applied and pending migrations, which can be run and reverted */
+/** development-only: pool, process and request metrics, see `create_rust_app::metrics` */
+const Health = () => {
+  const [minutes, setMinutes] = useState<number>(15)
+  const metricsQuery = useQuery<Metrics>(['metrics', minutes], () => request(`/api/development/metrics?minutes=${minutes}`), { refetchInterval: 5000, keepPreviousData: true })
+  const metrics = metricsQuery.data
+  const busiest = Math.max(1, ...(metrics?.requests.timeline.map(m => m.requests) ?? []))
+
+  const stat = (label: string, value: string) => <div className="border-2 p-2 mr-2 mb-2 w-48">
+    <div className="text-xs text-gray-500">{label}</div>
+    <div className="text-lg">{value}</div>
+  </div>
+
+  return <div>
+    <h1 className="font-bold text-xl">Health <span className="text-xs text-gray-500">(development only)</span></h1>
+    <div className="my-2">
+      <select className="border-2" value={minutes} onChange={e => setMinutes(Number(e.target.value))}>
+        {[5, 15, 30, 60].map(m => <option key={m} value={m}>last {m} minutes</option>)}
+      </select>
+    </div>
+    {metrics && <>
+      <h2 className="text-xs mt-2">process</h2>
+      <div className="flex flex-wrap">
+        {stat('memory', metrics.process.memory_bytes !== undefined && metrics.process.memory_bytes !== null ? `${(metrics.process.memory_bytes / 1024 / 1024).toFixed(1)} MB` : 'n/a')}
+        {stat('cpu', metrics.process.cpu_percent !== undefined && metrics.process.cpu_percent !== null ? `${metrics.process.cpu_percent.toFixed(1)}%` : 'n/a')}
+      </div>
+      <h2 className="text-xs mt-2">database pool</h2>
+      <div className="flex flex-wrap">
+        {stat('connections in use', `${metrics.pool.connections - metrics.pool.idle_connections} / ${metrics.pool.max_size}`)}
+        {stat('idle connections', `${metrics.pool.idle_connections}`)}
+      </div>
+      <h2 className="text-xs mt-2">requests</h2>
+      <div className="flex flex-wrap">
+        {stat('requests', `${metrics.requests.total} (${metrics.requests.requests_per_minute.toFixed(1)}/min)`)}
+        {stat('error rate (5xx)', `${(metrics.requests.error_rate * 100).toFixed(1)}%`)}
+        {stat('average latency', `${metrics.requests.average_latency_ms.toFixed(1)} ms`)}
+      </div>
+      <div className="flex items-end h-32 border-b-2 mt-2">
+        {metrics.requests.timeline.map(m =>
+          <div key={m.at} className="flex-1 mx-px flex flex-col justify-end h-full" title={`${new Date(m.at * 1000).toLocaleTimeString()}: ${m.requests} requests, ${m.errors} errors`}>
+            <div className="bg-red-500" style={{ height: `${m.errors / busiest * 100}%` }} />
+            <div className="bg-blue-500" style={{ height: `${(m.requests - m.errors) / busiest * 100}%` }} />
+          </div>
+        )}
+      </div>
+      <div className="text-xs text-gray-500">requests per minute, errors in red (counted by the <code>RequestInspector</code> middleware)</div>
+    </>}
+  </div>
+}
+
+const Migrations = () => {
+  const client = useQueryClient()
+  const [running, setRunning] = useState<boolean>(false)
+  const [error, setError] = useState<string | undefined>(undefined)
+  const migrationsQuery = useQuery<Migration[]>('migrations', () => request('/api/development/db/migrations'))
+  const seedsQuery = useQuery<{name: string}[]>('seeds', () => request('/api/development/db/seeds'))
+  const [seeded, setSeeded] = useState<string[] | undefined>(undefined)
+  const backupsQuery = useQuery<Backup[]>('backups', () => request('/api/development/db/backups'))
+  const [label, setLabel] = useState<string>('')
+  const pending = migrationsQuery.data?.filter(m => m.status === 'Pending').length ?? 0
+  const applied = migrationsQuery.data?.filter(m => m.status === 'Applied' || m.status === 'AppliedButMissingLocally').length ?? 0
+
+  const run = async (path: string, confirmation?: string) => {
+    if (confirmation && !window.confirm(confirmation)) return
+    setRunning(true)
+    setError(undefined)
+    try {
+      client.setQueryData('migrations', await request<Migration[]>(`/api/development/db/${path}`, { method: 'POST' }))
+    } catch (e) {
+      setError((e as Error).message)
+      client.invalidateQueries('migrations')
+    }
+    client.invalidateQueries('tables')
+    setRunning(false)
+  }
+
+  const seed = async () => {
+    setRunning(true)
+    setError(undefined)
+    setSeeded(undefined)
+    try {
+      setSeeded(await request<string[]>('/api/development/db/seed', { method: 'POST' }))
+    } catch (e) {
+      setError((e as Error).message)
+    }
+    client.invalidateQueries('tables')
+    setRunning(false)
+  }
+
+  const backup = async () => {
+    setRunning(true)
+    setError(undefined)
+    try {
+      await request('/api/development/db/backups', { method: 'POST', body: JSON.stringify({ label: label || undefined }) })
+      setLabel('')
+    } catch (e) {
+      setError((e as Error).message)
+    }
+    client.invalidateQueries('backups')
+    setRunning(false)
+  }
+
+  const deleteBackup = async (name: string) => {
+    if (!window.confirm(`Delete the backup ${name}?`)) return
+    setError(undefined)
+    try {
+      await request(`/api/development/db/backups/${encodeURIComponent(name)}`, { method: 'DELETE' })
+    } catch (e) {
+      setError((e as Error).message)
+    }
+    client.invalidateQueries('backups')
+  }
+
+  return <div>
+    <h1 className="font-bold text-xl">Migrations <span className="text-xs text-gray-500">(development only)</span></h1>
+    <div className="flex my-2">
+      <button disabled={running || pending === 0} onClick={() => run('migrate')} className="hover:underline text-blue-500 hover:text-blue-700 disabled:text-gray-400">Run all pending ({pending})</button>
+      <button disabled={running || pending === 0} onClick={() => run('migrations/next')} className="ml-4 hover:underline text-blue-500 hover:text-blue-700 disabled:text-gray-400">Run next</button>
+      <button disabled={running || applied === 0} onClick={() => run('migrations/revert', 'Revert the last applied migration? Its down.sql may drop data.')} className="ml-4 hover:underline text-red-500 hover:text-red-700 disabled:text-gray-400">Revert last</button>
+    </div>
+    {error && <pre className="text-red-500 text-xs whitespace-pre-wrap">{error}</pre>}
+    <table className="table-auto w-full border-grey-500 border-2">
+      <thead>
+        <tr className="text-left border-b-2">
+          <th className="p-2">version</th>
+          <th className="p-2">name</th>
+          <th className="p-2">status</th>
+        </tr>
+      </thead>
+      <tbody>
+        {migrationsQuery.data?.map(m => <tr key={m.version} className="odd:bg-grey-400">
+          <td className="p-2 font-mono text-xs">{m.version}</td>
+          <td className="p-2">{m.name}</td>
+          <td className={`p-2 ${m.status === 'Pending' ? 'text-orange-500' : m.status === 'Applied' ? 'text-green-600' : 'text-red-500'}`}>
+            {m.status === 'AppliedButMissingLocally' ? 'applied, missing locally' : m.status.toLowerCase()}
+          </td>
+        </tr>)}
+      </tbody>
+    </table>
+    <h2 className="font-bold mt-4">Seeds</h2>
+    <div className="flex my-2">
+      <button disabled={running || !seedsQuery.data?.length} onClick={seed} className="hover:underline text-blue-500 hover:text-blue-700 disabled:text-gray-400">Apply all seeds</button>
+      {seeded && <span className="ml-4 text-green-600">Applied {seeded.length} seed(s)</span>}
+    </div>
+    {seedsQuery.data?.length === 0 && <div className="text-gray-500">No seeds, add <code>.sql</code> files to <code>seeds/</code>.</div>}
+    <ul className="font-mono text-xs">
+      {seedsQuery.data?.map(s => <li key={s.name}>{s.name}</li>)}
+    </ul>
+    <h2 className="font-bold mt-4">Backups</h2>
+    <div className="flex my-2">
+      <input className="border-2 px-2" placeholder="Label (optional)" value={label} onChange={e => setLabel(e.target.value)} />
+      <button disabled={running} onClick={backup} className="ml-2 hover:underline text-blue-500 hover:text-blue-700 disabled:text-gray-400">Back up the database</button>
+    </div>
+    {backupsQuery.data?.length === 0 && <div className="text-gray-500">No backups yet, take one before trying out a destructive migration.</div>}
+    <table className="text-sm">
+      <tbody>
+        {backupsQuery.data?.map(b => <tr key={b.name}>
+          <td className="pr-4 font-mono text-xs">{b.name}</td>
+          <td className="pr-4 text-gray-500">{new Date(b.created_at * 1000).toLocaleString()}</td>
+          <td className="pr-4 text-gray-500">{(b.size_bytes / 1024).toFixed(1)} KB</td>
+          <td className="pr-4"><button disabled={running} onClick={() => run(`backups/${encodeURIComponent(b.name)}/restore`, `Restore ${b.name}? The database's current content will be lost.`)} className="hover:underline text-red-500 hover:text-red-700 disabled:text-gray-400">Restore</button></td>
+          <td><button disabled={running} onClick={() => deleteBackup(b.name)} className="hover:underline text-blue-500 hover:text-blue-700 disabled:text-gray-400">Delete</button></td>
+        </tr>)}
+      </tbody>
+    </table>
+  </div>
+}
+
+/** development-only: renders the mail templates with their sample context (`{name}.sample.json`) */
+const MailPreview = () => {
+  const [template, setTemplate] = useState<string>('')
+  const [locale, setLocale] = useState<string>('')
+  const [context, setContext] = useState<string>('')
+  const [view, setView] = useState<'html' | 'text'>('html')
+  const [reload, setReload] = useState<number>(0)
+  const templatesQuery = useQuery<MailTemplate[]>('mail-templates', () => request('/api/development/mail/templates'))
+  const selected = templatesQuery.data?.find(t => t.name === template)
+
+  const params = new URLSearchParams({ template, format: view })
+  if (locale) params.set('locale', locale)
+  if (context) params.set('context', context)
+  // fetched rather than loaded by the iframe itself, which couldn't send the access token
+  const previewQuery = useQuery<string, ApiError>(['mail-preview', params.toString(), reload], () => authorizedFetch(`/api/development/mail/preview?${params}`).then(r => r.text()), { enabled: !!template, retry: false })
+
+  return <div className="flex flex-col h-full">
+    <h1 className="font-bold text-xl">Mail templates <span className="text-xs text-gray-500">(development only)</span></h1>
+    <div className="flex my-2">
+      <select className="border-2" value={template} onChange={e => { setTemplate(e.target.value); setLocale('') }}>
+        <option value="">Select a template</option>
+        {templatesQuery.data?.map(t => <option key={t.name} value={t.name}>{t.name}</option>)}
+      </select>
+      <select className="border-2 ml-2" value={locale} onChange={e => setLocale(e.target.value)}>
+        <option value="">Default locale</option>
+        {selected?.locales.map(l => <option key={l} value={l}>{l}</option>)}
+      </select>
+      <button onClick={() => setReload(reload + 1)} className="ml-4 hover:underline text-blue-500 hover:text-blue-700">Reload</button>
+    </div>
+    <textarea className="w-full border-2 p-2 font-mono text-xs" rows={2} placeholder='Context overrides, ex: {"link": "http://localhost:3000/activate"}' value={context} onChange={e => setContext(e.target.value)} />
+    {!template && <div className="text-gray-500">No template selected.</div>}
+    {template && <>
+      <div className="flex text-xs mt-2">
+        {(['html', 'text'] as const).map(tab =>
+          <button key={tab} onClick={() => setView(tab)} className={`mr-4 hover:underline ${view === tab ? 'font-bold' : 'text-blue-500'}`}>{tab}</button>
+        )}
+      </div>
+      {previewQuery.error && <div className="text-red-500 mt-2">{previewQuery.error.message}</div>}
+      {view === 'html'
+        ? <iframe className="flex-1 w-full border-2 mt-2" sandbox="" srcDoc={previewQuery.data || ''} />
+        : <pre className="flex-1 w-full border-2 mt-2 p-2 text-xs whitespace-pre-wrap overflow-auto">{previewQuery.data}</pre>}
+    </>}
+  </div>
+}
+
+const EMAIL_PAGE_SIZE = 50
+
+/** the emails recorded by the mail log plugin (`/api/mail-log`, requires the `admin:mail` permission) */
+const EmailLog = () => {
+  const [recipient, setRecipient] = useState<string>('')
+  const [status, setStatus] = useState<string>('')
+  const [page, setPage] = useState<number>(0)
+  const params = new URLSearchParams({ page: `${page}`, page_size: `${EMAIL_PAGE_SIZE}` })
+  if (recipient) params.set('recipient', recipient)
+  if (status) params.set('status', status)
+  const emailsQuery = useQuery<LoggedEmail[]>(['emails', recipient, status, page], () => request(`/api/mail-log?${params}`), { keepPreviousData: true })
+
+  return <div>
+    <h1 className="font-bold text-xl">Emails</h1>
+    <div className="flex my-2">
+      <input className="border-2 px-2" placeholder="Recipient" value={recipient} onChange={e => { setRecipient(e.target.value); setPage(0) }} />
+      <select className="border-2 ml-2" value={status} onChange={e => { setStatus(e.target.value); setPage(0) }}>
+        <option value="">Any status</option>
+        <option value="sent">sent</option>
+        <option value="failed">failed</option>
+        <option value="skipped">skipped</option>
+      </select>
+    </div>
+    {emailsQuery.error && <div className="text-red-500">{(emailsQuery.error as ApiError).message}</div>}
+    <table className="w-full text-sm">
+      <thead>
+        <tr className="text-left">
+          <th>sent at</th><th>recipient</th><th>subject</th><th>template</th><th>status</th><th>provider id</th><th>error</th>
+        </tr>
+      </thead>
+      <tbody>
+        {emailsQuery.data?.map(email =>
+          <tr key={email.id} className="border-t">
+            <td className="whitespace-nowrap">{new Date(email.created_at).toLocaleString()}</td>
+            <td>{email.recipient}</td>
+            <td>{email.subject}</td>
+            <td>{email.template ?? '-'}</td>
+            <td className={email.status === 'failed' ? 'text-red-500' : ''}>{email.status}</td>
+            <td className="font-mono text-xs">{email.provider_message_id ?? '-'}</td>
+            <td className="text-xs">{email.error ?? ''}</td>
+          </tr>
+        )}
+      </tbody>
+    </table>
+    <div className="flex">
+      <div className="flex-1"></div>
+      <button disabled={page === 0} onClick={() => setPage(page - 1)} className="px-2 disabled:text-gray-400">‹</button>
+      <div>page {page + 1}</div>
+      <button disabled={(emailsQuery.data?.length ?? 0) < EMAIL_PAGE_SIZE} onClick={() => setPage(page + 1)} className="px-2 disabled:text-gray-400">›</button>
+    </div>
+  </div>
+}
+
+/** what's running: the app's build, create-rust-app's features, the crates' versions and the (sanitized) environment */
+const EnvironmentInfo = () => {
+  const environmentQuery = useQuery<Environment>('environment', () => api('/environment'))
+  const [filter, setFilter] = useState<string>('')
+  const environment = environmentQuery.data
+  const matches = (name: string) => name.toLowerCase().includes(filter.toLowerCase())
+
+  if (!environment) return <div>{environmentQuery.error ? <span className="text-red-500">{(environmentQuery.error as ApiError).message}</span> : 'Loading...'}</div>
+
+  return <div>
+    <h1 className="font-bold text-xl">Environment</h1>
+    <table className="text-sm my-2">
+      <tbody>
+        <tr><td className="pr-4 text-gray-500">app</td><td>{environment.name ? `${environment.name} ${environment.version}` : <span className="text-gray-500">unknown, register <code>create_rust_app::build_info!()</code> in the app's data</span>}</td></tr>
+        <tr><td className="pr-4 text-gray-500">git commit</td><td className="font-mono">{environment.git_commit ?? '-'}</td></tr>
+        <tr><td className="pr-4 text-gray-500">built at</td><td>{environment.built_at ? new Date(Number(environment.built_at) * 1000).toLocaleString() : '-'}</td></tr>
+        <tr><td className="pr-4 text-gray-500">profile</td><td>{environment.profile}</td></tr>
+        <tr><td className="pr-4 text-gray-500">create-rust-app</td><td>{environment.create_rust_app_version}</td></tr>
+        <tr><td className="pr-4 text-gray-500 align-top">features</td><td>{environment.features.map(feature => <span key={feature} className="inline-block mr-2 px-1 bg-gray-100 font-mono text-xs">{feature}</span>)}</td></tr>
+      </tbody>
+    </table>
+    <input className="border-2 px-2 my-2" placeholder="Filter" value={filter} onChange={e => setFilter(e.target.value)} />
+    <div className="flex">
+      <div className="flex-1 mr-4">
+        <h2 className="font-bold">Variables</h2>
+        <table className="text-xs font-mono">
+          <tbody>
+            {environment.env.filter(v => matches(v.name)).map(v => <tr key={v.name}>
+              <td className="pr-4 align-top">{v.name}</td>
+              <td className={`break-all ${v.redacted ? 'text-gray-500' : ''}`}>{v.value}</td>
+            </tr>)}
+          </tbody>
+        </table>
+      </div>
+      <div>
+        <h2 className="font-bold">Crates</h2>
+        {environment.dependencies.length === 0 && <div className="text-gray-500 text-xs">Unknown, they're listed by the project's <code>build.rs</code>.</div>}
+        <table className="text-xs font-mono">
+          <tbody>
+            {environment.dependencies.filter(d => matches(d.name)).map(d => <tr key={`${d.name}@${d.version}`}>
+              <td className="pr-4">{d.name}</td>
+              <td>{d.version}</td>
+            </tr>)}
+          </tbody>
+        </table>
+      </div>
+    </div>
+  </div>
+}
+
+const AUDIT_PAGE_SIZE = 50
+
+/** the entries recorded by the audit plugin (`/api/audit-log`, requires the `admin:audit` permission) */
+const AuditLog = () => {
+  const [actorId, setActorId] = useState<string>('')
+  const [action, setAction] = useState<string>('')
+  const [targetType, setTargetType] = useState<string>('')
+  const [targetId, setTargetId] = useState<string>('')
+  const [page, setPage] = useState<number>(0)
+  const [expanded, setExpanded] = useState<number | undefined>(undefined)
+  const params = new URLSearchParams({ page: `${page}`, page_size: `${AUDIT_PAGE_SIZE}` })
+  if (actorId) params.set('actor_id', actorId)
+  if (action) params.set('action', action)
+  if (targetType) params.set('target_type', targetType)
+  if (targetId) params.set('target_id', targetId)
+  const entriesQuery = useQuery<AuditEntry[]>(['audit-log', actorId, action, targetType, targetId, page], () => request(`/api/audit-log?${params}`), { keepPreviousData: true })
+
+  const filter = (set: (value: string) => void) => (e: React.ChangeEvent<HTMLInputElement>) => { set(e.target.value); setPage(0) }
+
+  return <div>
+    <h1 className="font-bold text-xl">Audit log</h1>
+    <div className="flex my-2">
+      <input className="border-2 px-2 w-24" placeholder="Actor id" value={actorId} onChange={filter(value => setActorId(value.replace(/[^0-9]/g, '')))} />
+      <input className="border-2 px-2 ml-2" placeholder="Action" value={action} onChange={filter(setAction)} />
+      <input className="border-2 px-2 ml-2" placeholder="Target type" value={targetType} onChange={filter(setTargetType)} />
+      <input className="border-2 px-2 ml-2 w-24" placeholder="Target id" value={targetId} onChange={filter(setTargetId)} />
+    </div>
+    {entriesQuery.error && <div className="text-red-500">{(entriesQuery.error as ApiError).message}</div>}
+    <table className="w-full text-sm">
+      <thead>
+        <tr className="text-left">
+          <th>at</th><th>actor</th><th>action</th><th>target</th><th>ip</th><th>changes</th>
+        </tr>
+      </thead>
+      <tbody>
+        {entriesQuery.data?.map(entry => {
+          const diff: Record<string, { from: any, to: any }> = entry.diff ? JSON.parse(entry.diff) : {}
+          const fields = Object.keys(diff)
+          return <React.Fragment key={entry.id}>
+            <tr className="border-t">
+              <td className="whitespace-nowrap">{new Date(entry.created_at).toLocaleString()}</td>
+              <td>{entry.actor_id !== undefined && entry.actor_id !== null
+                ? <button onClick={() => { setActorId(`${entry.actor_id}`); setPage(0) }} className="hover:underline text-blue-500">#{entry.actor_id}</button>
+                : '-'}</td>
+              <td>{entry.action}</td>
+              <td>
+                <button onClick={() => { setTargetType(entry.target_type); setTargetId(entry.target_id ?? ''); setPage(0) }} className="hover:underline text-blue-500">
+                  {entry.target_type}{entry.target_id ? ` #${entry.target_id}` : ''}
+                </button>
+              </td>
+              <td className="font-mono text-xs">{entry.ip_address ?? '-'}</td>
+              <td>{fields.length > 0
+                ? <button onClick={() => setExpanded(expanded === entry.id ? undefined : entry.id)} className="hover:underline text-blue-500">{fields.length} field{fields.length > 1 ? 's' : ''}</button>
+                : '-'}</td>
+            </tr>
+            {expanded === entry.id && <tr>
+              <td colSpan={6}>
+                <table className="ml-4 my-2 text-xs font-mono">
+                  <tbody>
+                    {fields.map(field => <tr key={field}>
+                      <td className="pr-4 font-bold">{field}</td>
+                      <td className="pr-4 text-red-500">{JSON.stringify(diff[field].from)}</td>
+                      <td className="text-green-600">{JSON.stringify(diff[field].to)}</td>
+                    </tr>)}
+                  </tbody>
+                </table>
+              </td>
+            </tr>}
+          </React.Fragment>
+        })}
+      </tbody>
+    </table>
+    <div className="flex">
+      <div className="flex-1"></div>
+      <button disabled={page === 0} onClick={() => setPage(page - 1)} className="px-2 disabled:text-gray-400">‹</button>
+      <div>page {page + 1}</div>
+      <button disabled={(entriesQuery.data?.length ?? 0) < AUDIT_PAGE_SIZE} onClick={() => setPage(page + 1)} className="px-2 disabled:text-gray-400">›</button>
+    </div>
+  </div>
+}
+
+const RUN_PAGE_SIZE = 50
+
+const RUN_STATUS_COLORS: Record<TaskRun['status'], string> = {
+  running: 'text-blue-500',
+  succeeded: 'text-green-600',
+  failed: 'text-red-500',
+  skipped: 'text-gray-500',
+}
+
+/** the scheduler's tasks and their runs (`/api/scheduler`, requires the `admin:scheduler` permission) */
+const Schedules = () => {
+  const [task, setTask] = useState<string>('')
+  const [page, setPage] = useState<number>(0)
+  const tasksQuery = useQuery<{task: ScheduledTask, last_run?: TaskRun}[]>('scheduled-tasks', () => request('/api/scheduler'), { refetchInterval: 10000 })
+  const params = new URLSearchParams({ page: `${page}`, page_size: `${RUN_PAGE_SIZE}` })
+  if (task) params.set('task', task)
+  const runsQuery = useQuery<TaskRun[]>(['task-runs', task, page], () => request(`/api/scheduler/runs?${params}`), { keepPreviousData: true, refetchInterval: 10000 })
+
+  const duration = (run: TaskRun) => run.finished_at ? `${((new Date(run.finished_at).getTime() - new Date(run.started_at).getTime()) / 1000).toFixed(1)}s` : '-'
+
+  return <div>
+    <h1 className="font-bold text-xl">Schedules</h1>
+    {tasksQuery.error && <div className="text-red-500">{(tasksQuery.error as ApiError).message}</div>}
+    <table className="w-full text-sm my-2">
+      <thead>
+        <tr className="text-left">
+          <th>task</th><th>cron (UTC)</th><th>next run</th><th>last run</th><th>status</th>
+        </tr>
+      </thead>
+      <tbody>
+        {tasksQuery.data?.map(({ task: scheduled, last_run }) => <tr key={scheduled.id} className="border-t">
+          <td><button onClick={() => { setTask(scheduled.name); setPage(0) }} className="hover:underline text-blue-500">{scheduled.name}</button></td>
+          <td className="font-mono text-xs">{scheduled.cron}</td>
+          <td className="whitespace-nowrap">{new Date(scheduled.next_run_at).toLocaleString()}</td>
+          <td className="whitespace-nowrap">{last_run ? new Date(last_run.started_at).toLocaleString() : '-'}</td>
+          <td className={last_run ? RUN_STATUS_COLORS[last_run.status] : ''}>{last_run?.status ?? '-'}</td>
+        </tr>)}
+      </tbody>
+    </table>
+    {tasksQuery.data?.length === 0 && <div className="text-gray-500">No scheduled tasks, they're registered when a scheduler starts.</div>}
+    <h2 className="font-bold mt-4">Runs {task && <>of {task} <button onClick={() => { setTask(''); setPage(0) }} className="text-xs hover:underline text-blue-500">(all)</button></>}</h2>
+    {runsQuery.error && <div className="text-red-500">{(runsQuery.error as ApiError).message}</div>}
+    <table className="w-full text-sm">
+      <thead>
+        <tr className="text-left">
+          <th>task</th><th>scheduled for</th><th>started</th><th>duration</th><th>status</th><th>error</th>
+        </tr>
+      </thead>
+      <tbody>
+        {runsQuery.data?.map(run => <tr key={run.id} className="border-t">
+          <td>{run.task_name}</td>
+          <td className="whitespace-nowrap">{new Date(run.scheduled_for).toLocaleString()}</td>
+          <td className="whitespace-nowrap">{new Date(run.started_at).toLocaleString()}</td>
+          <td>{duration(run)}</td>
+          <td className={RUN_STATUS_COLORS[run.status]}>{run.status}</td>
+          <td className="text-xs">{run.error ?? ''}</td>
+        </tr>)}
+      </tbody>
+    </table>
+    <div className="flex">
+      <div className="flex-1"></div>
+      <button disabled={page === 0} onClick={() => setPage(page - 1)} className="px-2 disabled:text-gray-400">‹</button>
+      <div>page {page + 1}</div>
+      <button disabled={(runsQuery.data?.length ?? 0) < RUN_PAGE_SIZE} onClick={() => setPage(page + 1)} className="px-2 disabled:text-gray-400">›</button>
+    </div>
+  </div>
+}
+
+/** the feature flags' defaults and overrides (`/api/flags/admin`, requires the `admin:flags` permission) */
+const FeatureFlags = () => {
+  const client = useQueryClient()
+  const [error, setError] = useState<string | undefined>(undefined)
+  const flagsQuery = useQuery<FlagOverview[]>('flags', () => request('/api/flags/admin'))
+
+  const save = async (flag: FeatureFlag, changes: Partial<{ enabled: boolean | null, rollout_percentage: number | null, user_ids: number[] }>) => {
+    setError(undefined)
+    const overrides = {
+      enabled: flag.enabled ?? null,
+      rollout_percentage: flag.rollout_percentage ?? null,
+      user_ids: JSON.parse(flag.user_ids) as number[],
+      ...changes,
+    }
+    try {
+      await request(`/api/flags/admin/${encodeURIComponent(flag.key)}`, { method: 'PUT', body: JSON.stringify(overrides) })
+    } catch (e) {
+      setError((e as Error).message)
+    }
+    client.invalidateQueries('flags')
+  }
+
+  const setRollout = (flag: FeatureFlag) => {
+    const value = window.prompt(`Roll ${flag.key} out to which percentage of the users? (0 to 100, empty for everyone)`, `${flag.rollout_percentage ?? ''}`)
+    if (value === null) return
+    save(flag, { rollout_percentage: value.trim() === '' ? null : parseInt(value, 10) })
+  }
+
+  const setUsers = (flag: FeatureFlag) => {
+    const value = window.prompt(`Turn ${flag.key} on for which users? (comma-separated ids)`, (JSON.parse(flag.user_ids) as number[]).join(', '))
+    if (value === null) return
+    save(flag, { user_ids: value.split(',').map(id => parseInt(id.trim(), 10)).filter(id => !isNaN(id)) })
+  }
+
+  const remove = async (flag: FeatureFlag) => {
+    if (!window.confirm(`Delete ${flag.key} and its overrides?`)) return
+    setError(undefined)
+    try {
+      await request(`/api/flags/admin/${encodeURIComponent(flag.key)}`, { method: 'DELETE' })
+    } catch (e) {
+      setError((e as Error).message)
+    }
+    client.invalidateQueries('flags')
+  }
+
+  return <div>
+    <h1 className="font-bold text-xl">Feature flags</h1>
+    {flagsQuery.error && <div className="text-red-500">{(flagsQuery.error as ApiError).message}</div>}
+    {error && <div className="text-red-500">{error}</div>}
+    <table className="w-full text-sm my-2">
+      <thead>
+        <tr className="text-left">
+          <th>flag</th><th>default</th><th>state</th><th>rollout</th><th>users</th><th></th>
+        </tr>
+      </thead>
+      <tbody>
+        {flagsQuery.data?.map(({ flag, defined, default: byDefault, environments }) => <tr key={flag.id} className="border-t align-top">
+          <td>
+            <div className="font-mono">{flag.key}</div>
+            <div className="text-xs text-gray-500">{defined ? flag.description : 'no longer defined, always off'}</div>
+          </td>
+          <td title={Object.entries(environments).map(([env, on]) => `${env}: ${on ? 'on' : 'off'}`).join('\n')}>{byDefault ? 'on' : 'off'}</td>
+          <td>
+            <select disabled={!defined} className="border" value={flag.enabled === undefined || flag.enabled === null ? 'default' : flag.enabled ? 'on' : 'off'} onChange={e => save(flag, { enabled: e.target.value === 'default' ? null : e.target.value === 'on' })}>
+              <option value="default">default</option>
+              <option value="on">on</option>
+              <option value="off">off</option>
+            </select>
+          </td>
+          <td><button disabled={!defined} onClick={() => setRollout(flag)} className="hover:underline text-blue-500 disabled:text-gray-400">{flag.rollout_percentage ?? 100}%</button></td>
+          <td><button disabled={!defined} onClick={() => setUsers(flag)} className="hover:underline text-blue-500 disabled:text-gray-400">{(JSON.parse(flag.user_ids) as number[]).length || 'none'}</button></td>
+          <td><button onClick={() => remove(flag)} className="hover:underline text-red-500">delete</button></td>
+        </tr>)}
+      </tbody>
+    </table>
+    {flagsQuery.data?.length === 0 && <div className="text-gray-500">No feature flags, they're recorded when the app registers them.</div>}
+    <div className="text-xs text-gray-500">Targeted users always get a flag; otherwise it's on if its state (or its default) is, for its rollout percentage of the users. Changes reach the other instances within a few seconds.</div>
+  </div>
+}
+
+const DELIVERY_PAGE_SIZE = 50
+
+/** the webhooks' deliveries to every endpoint (`/api/webhooks/admin/deliveries`, requires the `admin:webhooks` permission) */
+const Webhooks = () => {
+  const client = useQueryClient()
+  const [status, setStatus] = useState<string>('')
+  const [page, setPage] = useState<number>(0)
+  const [expanded, setExpanded] = useState<number | undefined>(undefined)
+  const [error, setError] = useState<string | undefined>(undefined)
+  const params = new URLSearchParams({ page: `${page}`, page_size: `${DELIVERY_PAGE_SIZE}` })
+  if (status) params.set('status', status)
+  const deliveriesQuery = useQuery<DeliveryOverview[]>(['webhook-deliveries', status, page], () => request(`/api/webhooks/admin/deliveries?${params}`), { keepPreviousData: true })
+
+  const retry = async (delivery: WebhookDelivery) => {
+    setError(undefined)
+    try {
+      await request(`/api/webhooks/admin/deliveries/${delivery.id}/retry`, { method: 'POST' })
+    } catch (e) {
+      setError((e as Error).message)
+    }
+    client.invalidateQueries('webhook-deliveries')
+  }
+
+  return <div>
+    <h1 className="font-bold text-xl">Webhooks</h1>
+    <div className="flex my-2">
+      <select className="border-2" value={status} onChange={e => { setStatus(e.target.value); setPage(0) }}>
+        <option value="">Any status</option>
+        <option value="pending">pending</option>
+        <option value="succeeded">succeeded</option>
+        <option value="failed">failed</option>
+      </select>
+    </div>
+    {deliveriesQuery.error && <div className="text-red-500">{(deliveriesQuery.error as ApiError).message}</div>}
+    {error && <div className="text-red-500">{error}</div>}
+    <table className="w-full text-sm">
+      <thead>
+        <tr className="text-left">
+          <th>created at</th><th>event</th><th>endpoint</th><th>user</th><th>status</th><th>attempts</th><th>response</th><th></th>
         </tr>
       </thead>
       <tbody>
-        {viewQuery.data && <RowView columns={viewQuery.data || []} table={props.name} />}
+        {deliveriesQuery.data?.map(({ delivery, url, user_id }) => <React.Fragment key={delivery.id}>
+          <tr className="border-t">
+            <td className="whitespace-nowrap">{new Date(delivery.created_at).toLocaleString()}</td>
+            <td className="font-mono">{delivery.event_type}</td>
+            <td className="break-all">{url}</td>
+            <td>{user_id}</td>
+            <td className={delivery.status === 'failed' ? 'text-red-500' : ''} title={delivery.status === 'pending' ? `next attempt: ${new Date(delivery.next_attempt_at).toLocaleString()}` : undefined}>{delivery.status}</td>
+            <td>{delivery.attempts}</td>
+            <td><button onClick={() => setExpanded(expanded === delivery.id ? undefined : delivery.id)} className="hover:underline text-blue-500">{delivery.response_status ?? '-'}</button></td>
+            <td>{delivery.status !== 'pending' && <button onClick={() => retry(delivery)} className="hover:underline text-blue-500">retry</button>}</td>
+          </tr>
+          {expanded === delivery.id && <tr>
+            <td colSpan={8} className="text-xs">
+              {delivery.last_error && <div className="text-red-500">{delivery.last_error}</div>}
+              <div className="text-gray-500">payload</div>
+              <pre className="bg-gray-100 p-1 whitespace-pre-wrap break-all">{delivery.payload}</pre>
+              <div className="text-gray-500">response</div>
+              <pre className="bg-gray-100 p-1 whitespace-pre-wrap break-all">{delivery.response_body || '(empty)'}</pre>
+            </td>
+          </tr>}
+        </React.Fragment>)}
       </tbody>
     </table>
     <div className="flex">
       <div className="flex-1"></div>
-      <div>1 - {Math.min(typeof tableCountQuery.data === 'number' ? tableCountQuery.data : 0, 50)} of {typeof tableCountQuery.data === 'number' ? tableCountQuery.data : (tableCountQuery.isFetching ? '…' : '?')}</div>
+      <button disabled={page === 0} onClick={() => setPage(page - 1)} className="px-2 disabled:text-gray-400">‹</button>
+      <div>page {page + 1}</div>
+      <button disabled={(deliveriesQuery.data?.length ?? 0) < DELIVERY_PAGE_SIZE} onClick={() => setPage(page + 1)} className="px-2 disabled:text-gray-400">›</button>
+    </div>
+  </div>
+}
+
+const USER_PAGE_SIZE = 50
+
+/** the auth plugin's users: search, verify, reset passwords, roles/permissions, bans and sessions */
+const Users = () => {
+  const client = useQueryClient()
+  const [q, setQ] = useState<string>('')
+  const [page, setPage] = useState<number>(0)
+  const [selected, setSelected] = useState<number | undefined>(undefined)
+  const [role, setRole] = useState<string>('')
+  const [permission, setPermission] = useState<string>('')
+  const [error, setError] = useState<string | undefined>(undefined)
+  const params = new URLSearchParams({ q, page: `${page}`, page_size: `${USER_PAGE_SIZE}` })
+  const usersQuery = useQuery<{users: AdminUser[], total: number}>(['users', q, page], () => api(`/users?${params}`), { keepPreviousData: true })
+  const userQuery = useQuery<AdminUserDetails>(['user', selected], () => api(`/users/${selected}`), { enabled: selected !== undefined })
+  const user = userQuery.data
+
+  const run = async (path: string, init: RequestInit, confirmation?: string) => {
+    if (confirmation && !window.confirm(confirmation)) return
+    setError(undefined)
+    try {
+      await api(`/users/${selected}${path}`, init)
+    } catch (e) {
+      setError((e as Error).message)
+    }
+    client.invalidateQueries('users')
+    client.invalidateQueries('user')
+  }
+
+  const resetPassword = () => {
+    const password = window.prompt(`New password for ${user?.email}? Their sessions will be revoked.`)
+    if (password) run('/password', { method: 'PUT', body: JSON.stringify({ password }) })
+  }
+
+  const ban = () => {
+    const reason = window.prompt(`Ban ${user?.email ?? `user #${selected}`}? Reason (optional):`)
+    if (reason !== null) run('/ban', { method: 'POST', body: JSON.stringify({ reason }) })
+  }
+
+  return <div className="flex">
+    <div className="flex-1">
+      <h1 className="font-bold text-xl">Users</h1>
+      <input className="border-2 px-2 my-2" placeholder="Email or id" value={q} onChange={e => { setQ(e.target.value); setPage(0) }} />
+      {usersQuery.error && <div className="text-red-500">{(usersQuery.error as ApiError).message}</div>}
+      <table className="w-full text-sm">
+        <thead>
+          <tr className="text-left">
+            <th>id</th><th>email</th><th>roles</th><th>status</th><th>created at</th>
+          </tr>
+        </thead>
+        <tbody>
+          {usersQuery.data?.users.map(u =>
+            <tr key={u.id} onClick={() => { setSelected(u.id); setError(undefined) }} className={`border-t cursor-pointer hover:bg-gray-100 ${selected === u.id ? 'font-bold' : ''}`}>
+              <td>{u.id}</td>
+              <td>{u.email ?? <span className="text-gray-500">guest</span>}</td>
+              <td>{u.roles.join(', ')}</td>
+              <td className={u.ban ? 'text-red-500' : u.activated ? '' : 'text-orange-500'}>{u.ban ? 'banned' : u.activated ? 'active' : 'not verified'}</td>
+              <td className="whitespace-nowrap">{new Date(u.created_at).toLocaleString()}</td>
+            </tr>
+          )}
+        </tbody>
+      </table>
+      <div className="flex">
+        <div className="flex-1 text-xs text-gray-500">{usersQuery.data?.total ?? 0} users</div>
+        <button disabled={page === 0} onClick={() => setPage(page - 1)} className="px-2 disabled:text-gray-400">‹</button>
+        <div>page {page + 1}</div>
+        <button disabled={(page + 1) * USER_PAGE_SIZE >= (usersQuery.data?.total ?? 0)} onClick={() => setPage(page + 1)} className="px-2 disabled:text-gray-400">›</button>
+      </div>
+    </div>
+    {user && <div className="w-96 ml-4 pl-4 border-l-2 text-sm">
+      <h2 className="font-bold">{user.email ?? 'Guest'} <span className="text-gray-500">#{user.id}</span></h2>
+      <div>{user.sessions} active session(s)</div>
+      {user.ban && <div className="text-red-500">Banned {new Date(user.ban.created_at).toLocaleString()}{user.ban.reason && `: ${user.ban.reason}`}</div>}
+      {error && <div className="text-red-500">{error}</div>}
+      <div className="my-2">
+        {!user.activated && <button onClick={() => run('/verify', { method: 'POST' })} className="block hover:underline text-blue-500 hover:text-blue-700">Verify email</button>}
+        {!user.guest && <button onClick={resetPassword} className="block hover:underline text-blue-500 hover:text-blue-700">Reset password</button>}
+        <button disabled={user.sessions === 0} onClick={() => run('/sessions', { method: 'DELETE' }, 'Log this user out of every device?')} className="block hover:underline text-blue-500 hover:text-blue-700 disabled:text-gray-400">Revoke all sessions</button>
+        {user.ban
+          ? <button onClick={() => run('/ban', { method: 'DELETE' })} className="block hover:underline text-blue-500 hover:text-blue-700">Unban</button>
+          : <button onClick={ban} className="block hover:underline text-red-500 hover:text-red-700">Ban</button>}
+      </div>
+      <h3 className="text-xs mt-4">roles</h3>
+      {user.roles.map(r => <div key={r} className="flex">
+        <span className="flex-1">{r}</span>
+        <button onClick={() => run(`/roles/${encodeURIComponent(r)}`, { method: 'DELETE' })} className="hover:underline text-red-500">remove</button>
+      </div>)}
+      <form className="flex mt-1" onSubmit={e => { e.preventDefault(); run('/roles', { method: 'POST', body: JSON.stringify({ role }) }); setRole('') }}>
+        <input className="flex-1 border-2 px-2" placeholder="Role" value={role} onChange={e => setRole(e.target.value)} />
+        <button disabled={!role} className="ml-2 hover:underline text-blue-500 disabled:text-gray-400">Assign</button>
+      </form>
+      <h3 className="text-xs mt-4">permissions</h3>
+      {user.permissions.map(p => <div key={`${p.from_role}-${p.permission}`} className="flex">
+        <span className="flex-1">{p.permission} {p.from_role && <span className="text-gray-500">(from {p.from_role})</span>}</span>
+        {!p.from_role && <button onClick={() => run(`/permissions/${encodeURIComponent(p.permission)}`, { method: 'DELETE' })} className="hover:underline text-red-500">revoke</button>}
+      </div>)}
+      <form className="flex mt-1" onSubmit={e => { e.preventDefault(); run('/permissions', { method: 'POST', body: JSON.stringify({ permission }) }); setPermission('') }}>
+        <input className="flex-1 border-2 px-2" placeholder="Permission" value={permission} onChange={e => setPermission(e.target.value)} />
+        <button disabled={!permission} className="ml-2 hover:underline text-blue-500 disabled:text-gray-400">Grant</button>
+      </form>
+    </div>}
+  </div>
+}
+
+const DIAGRAM_TABLE_WIDTH = 240
+const DIAGRAM_GAP = 80
+const DIAGRAM_HEADER_HEIGHT = 28
+const DIAGRAM_ROW_HEIGHT = 20
+
+/** ER diagram of the database: tables laid out on a grid, foreign keys drawn as lines */
+const SchemaDiagram = (props: {onSelectTable: (name: string) => void}) => {
+  const schemaQuery = useQuery<Schema>('schema', () => api('/schema'))
+  const [highlighted, setHighlighted] = useState<string | undefined>(undefined)
+  const tables = schemaQuery.data?.tables.filter(t => !t.name.startsWith('__')) ?? []
+  const foreignKeys = schemaQuery.data?.foreign_keys ?? []
+
+  const perRow = Math.max(1, Math.ceil(Math.sqrt(tables.length)))
+  const heights = tables.map(t => DIAGRAM_HEADER_HEIGHT + t.columns.length * DIAGRAM_ROW_HEIGHT)
+  const rowTops: number[] = []
+  for (let row = 0; row * perRow < tables.length; row++) {
+    const previous = row === 0 ? 0 : rowTops[row - 1] + Math.max(...heights.slice((row - 1) * perRow, row * perRow)) + DIAGRAM_GAP
+    rowTops.push(previous)
+  }
+  const positions: Record<string, {x: number, y: number, columns: SchemaColumn[]}> = {}
+  tables.forEach((t, i) => {
+    positions[t.name] = { x: (i % perRow) * (DIAGRAM_TABLE_WIDTH + DIAGRAM_GAP), y: rowTops[Math.floor(i / perRow)], columns: t.columns }
+  })
+  const width = perRow * (DIAGRAM_TABLE_WIDTH + DIAGRAM_GAP)
+  const height = rowTops.length ? rowTops[rowTops.length - 1] + Math.max(...heights.slice((rowTops.length - 1) * perRow)) + DIAGRAM_GAP : 0
+
+  const columnY = (table: string, column: string) => {
+    const index = positions[table].columns.findIndex(c => c.column_name === column)
+    return positions[table].y + DIAGRAM_HEADER_HEIGHT + (Math.max(index, 0) + 0.5) * DIAGRAM_ROW_HEIGHT
+  }
+  const isHighlighted = (fk: ForeignKey) => highlighted === fk.table_name || highlighted === fk.foreign_table_name
+
+  return <div>
+    <h1 className="font-bold text-xl">Schema</h1>
+    <div className="text-xs text-gray-500 mb-2">{tables.length} tables, {foreignKeys.length} foreign keys. Hover a table to highlight its relations, click it to browse its records.</div>
+    {schemaQuery.error && <div className="text-red-500">{(schemaQuery.error as ApiError).message}</div>}
+    <div className="overflow-auto border-2">
+      <div className="relative" style={{ width, height }}>
+        <svg className="absolute inset-0 pointer-events-none" width={width} height={height}>
+          {foreignKeys.filter(fk => positions[fk.table_name] && positions[fk.foreign_table_name]).map(fk => {
+            const from = positions[fk.table_name]
+            const to = positions[fk.foreign_table_name]
+            const fromRight = from.x < to.x
+            const x1 = from.x + (fromRight ? DIAGRAM_TABLE_WIDTH : 0)
+            const x2 = to.x + (fromRight ? 0 : DIAGRAM_TABLE_WIDTH)
+            const y1 = columnY(fk.table_name, fk.column_name)
+            const y2 = fk.foreign_column_name ? columnY(fk.foreign_table_name, fk.foreign_column_name) : to.y + DIAGRAM_HEADER_HEIGHT / 2
+            const bend = (fromRight ? 1 : -1) * DIAGRAM_GAP / 2
+            return <path key={`${fk.table_name}.${fk.column_name}->${fk.foreign_table_name}`}
+              d={`M ${x1} ${y1} C ${x1 + bend} ${y1}, ${x2 - bend} ${y2}, ${x2} ${y2}`}
+              fill="none" stroke={isHighlighted(fk) ? '#3b82f6' : '#9ca3af'} strokeWidth={isHighlighted(fk) ? 2 : 1} />
+          })}
+        </svg>
+        {tables.map(t => <div key={t.name} className="absolute bg-white border-2 text-xs shadow"
+          style={{ left: positions[t.name].x, top: positions[t.name].y, width: DIAGRAM_TABLE_WIDTH }}
+          onMouseEnter={() => setHighlighted(t.name)} onMouseLeave={() => setHighlighted(undefined)}>
+          <button onClick={() => props.onSelectTable(t.name)} className="block w-full text-left font-bold px-2 bg-gray-100 hover:underline text-blue-500" style={{ height: DIAGRAM_HEADER_HEIGHT }}>{t.name}</button>
+          {t.columns.map(c => <div key={c.column_name} className="flex px-2" style={{ height: DIAGRAM_ROW_HEIGHT }}>
+            <span className={`flex-1 truncate ${c.primary_key ? 'font-bold' : ''}`}>{c.primary_key ? '🔑 ' : ''}{c.column_name}</span>
+            <span className="text-gray-500 truncate">{c.data_type}{c.nullable ? '?' : ''}</span>
+          </div>)}
+        </div>)}
+      </div>
+    </div>
+  </div>
+}
+
+interface PaletteItem {
+  key: string,
+  label: string,
+  detail?: string,
+  run: () => void
+}
+
+/**
+ * Keyboard-driven command palette (Ctrl+K / ⌘K): jump to a table, run an action,
+ * or search records across the resources registered with `AdminSearch`.
+ */
+const CommandPalette = (props: {tables: string[], onSelectTable: (name: string, recordId?: string) => void, actions: PaletteItem[]}) => {
+  const [isOpen, setOpen] = useState<boolean>(false)
+  const [query, setQuery] = useState<string>('')
+  const [debouncedQuery, setDebouncedQuery] = useState<string>('')
+  const [selected, setSelected] = useState<number>(0)
+  const inputRef = useRef<HTMLInputElement>(null)
+
+  useEffect(() => {
+    const onKeyDown = (e: KeyboardEvent) => {
+      if ((e.ctrlKey || e.metaKey) && e.key.toLowerCase() === 'k') {
+        e.preventDefault()
+        setOpen(open => !open)
+      }
+    }
+    window.addEventListener('keydown', onKeyDown)
+    return () => window.removeEventListener('keydown', onKeyDown)
+  }, [])
+
+  useEffect(() => {
+    if (isOpen) {
+      setQuery('')
+      setSelected(0)
+      inputRef.current?.focus()
+    }
+  }, [isOpen])
+
+  useEffect(() => {
+    const timeout = setTimeout(() => setDebouncedQuery(query), 200)
+    return () => clearTimeout(timeout)
+  }, [query])
+
+  const searchQuery = useQuery<SearchResult[]>(['search', debouncedQuery], () => searchRecords(debouncedQuery), { enabled: isOpen && debouncedQuery.trim().length > 1 })
+
+  const matches = (label: string) => label.toLowerCase().includes(query.trim().toLowerCase())
+  const items: PaletteItem[] = [
+    ...props.actions.filter(action => matches(action.label)),
+    ...props.tables.filter(matches).map(table => ({ key: `table-${table}`, label: table, detail: 'table', run: () => props.onSelectTable(table) })),
+    ...(query.trim().length > 1 && searchQuery.data || []).map(result => ({
+      key: `record-${result.resource}-${result.id}`,
+      label: result.title,
+      detail: `${result.resource} #${result.id}${result.subtitle ? ` · ${result.subtitle}` : ''}`,
+      run: () => props.onSelectTable(result.resource, result.id),
+    })),
+  ]
+
+  const run = (item?: PaletteItem) => {
+    if (!item) return
+    item.run()
+    setOpen(false)
+  }
+
+  const onKeyDown = (e: React.KeyboardEvent) => {
+    if (e.key === 'ArrowDown') { e.preventDefault(); setSelected(Math.min(selected + 1, items.length - 1)) }
+    else if (e.key === 'ArrowUp') { e.preventDefault(); setSelected(Math.max(selected - 1, 0)) }
+    else if (e.key === 'Enter') { e.preventDefault(); run(items[selected]) }
+    else if (e.key === 'Escape') { setOpen(false) }
+  }
+
+  if (!isOpen) return null
+
+  return <div className="fixed inset-0 bg-black bg-opacity-25 flex justify-center items-start pt-24" onClick={() => setOpen(false)}>
+    <div className="bg-white w-full max-w-xl shadow-lg rounded" onClick={e => e.stopPropagation()}>
+      <input
+        ref={inputRef}
+        className="w-full p-3 border-b-2 outline-none"
+        placeholder="Search tables, records and actions…"
+        value={query}
+        onChange={e => { setQuery(e.target.value); setSelected(0) }}
+        onKeyDown={onKeyDown}
+      />
+      <ul className="max-h-96 overflow-y-auto">
+        {items.map((item, index) =>
+          <li key={item.key} onMouseEnter={() => setSelected(index)} onClick={() => run(item)} className={`p-2 cursor-pointer flex ${index === selected ? 'bg-blue-100' : ''}`}>
+            <span className="flex-1 truncate">{item.label}</span>
+            {item.detail && <span className="text-xs text-gray-500 truncate">{item.detail}</span>}
+          </li>
+        )}
+        {searchQuery.isFetching && <li className="p-2 text-gray-500 text-xs">Searching…</li>}
+        {items.length === 0 && !searchQuery.isFetching && <li className="p-2 text-gray-500 text-xs">No results.</li>}
+      </ul>
     </div>
   </div>
 }
 
 const AdminPage = () => {
-  /*
-    SELECT tablename AS name, (SELECT COUNT(*) FROM `tablename`) AS count FROM (SELECT * FROM pg_catalog.pg_tables WHERE schemaname != 'pg_catalog' AND schemaname != 'information_schema')
-  */
+  const client = useQueryClient()
 
-  const tableQuery = useQuery<{name: string}[]>('tables', () => fetchQuery(`SELECT tablename AS name FROM pg_catalog.pg_tables WHERE schemaname != 'pg_catalog' AND schemaname != 'information_schema'`))
+  const infoQuery = useQuery<AdminInfo>('info', () => api('/info'), { retry: false })
+  const tableQuery = useQuery<TableInfo[]>('tables', () => api('/tables'), { enabled: infoQuery.isSuccess })
 
   const [selectedTable, setSelectedTable] = useState<string | undefined>(undefined)
-  
+  const [selectedRecord, setSelectedRecord] = useState<string | undefined>(undefined)
+  const [tool, setTool] = useState<'schema' | 'sql' | 'migrations' | 'requests' | 'health' | 'mailbox' | 'mail-preview' | 'emails' | 'audit' | 'users' | 'environment' | 'schedules' | 'flags' | 'webhooks' | undefined>(undefined)
+
+  const selectTable = (name: string, recordId?: string) => {
+    setTool(undefined)
+    setSelectedTable(name)
+    setSelectedRecord(recordId)
+  }
+
+  const actions: PaletteItem[] = [
+    { key: 'action-refresh', label: 'Refresh data', run: () => client.invalidateQueries() },
+    { key: 'action-app', label: 'Open the app', run: () => { window.location.href = '/' } },
+    { key: 'action-schema', label: 'Show the schema diagram', run: () => setTool('schema') },
+    ...(infoQuery.data?.environment ? [{ key: 'action-environment', label: 'Show the environment and build info', run: () => setTool('environment') }] : []),
+    ...(infoQuery.data?.users ? [{ key: 'action-users', label: 'Manage the users', run: () => setTool('users') }] : []),
+    ...(infoQuery.data?.mail_log ? [{ key: 'action-emails', label: 'Open the email log', run: () => setTool('emails') }] : []),
+    ...(infoQuery.data?.audit_log ? [{ key: 'action-audit', label: 'Open the audit log', run: () => setTool('audit') }] : []),
+    ...(infoQuery.data?.scheduler ? [{ key: 'action-schedules', label: 'Show the scheduled tasks', run: () => setTool('schedules') }] : []),
+    ...(infoQuery.data?.flags ? [{ key: 'action-flags', label: 'Manage the feature flags', run: () => setTool('flags') }] : []),
+    ...(infoQuery.data?.webhooks ? [{ key: 'action-webhooks', label: 'Open the webhook deliveries', run: () => setTool('webhooks') }] : []),
+    ...(infoQuery.data?.dev_tools ? [
+      { key: 'action-sql', label: 'Open the SQL console', run: () => setTool('sql') },
+      { key: 'action-migrations', label: 'Open the migrations', run: () => setTool('migrations') },
+      { key: 'action-requests', label: 'Open the request inspector', run: () => setTool('requests') },
+      { key: 'action-health', label: 'Open the health dashboard', run: () => setTool('health') },
+      { key: 'action-mailbox', label: 'Open the mailbox', run: () => setTool('mailbox') },
+      { key: 'action-mail-preview', label: 'Preview the mail templates', run: () => setTool('mail-preview') },
+    ] : []),
+  ]
+
+  if (infoQuery.error) {
+    const status = (infoQuery.error as ApiError).status
+    return <div className="p-4">
+      {status === 401 || status === 403
+        ? <>The admin portal is restricted to users with the <code>admin</code> role. <a href="/login" className="text-blue-500 hover:underline">Log in</a> with an admin account, then reload this page. In development, set <code>CRA_ADMIN_OPEN=true</code> to open it to everyone.</>
+        : <>Could not reach the admin API.</>}
+    </div>
+  }
+
   return (
     <div className="flex h-full flex flex-col">
-      <a href="admin" className="p-4 text-blue-500 hover:underline hover:text-blue-700">Admin Portal <span className="text-xs">Create Rust App</span></a>
+      <CommandPalette tables={tableQuery.data?.map(table => table.name) || []} onSelectTable={selectTable} actions={actions} />
+      <div className="flex items-center">
+        <a href="admin" className="flex-1 p-4 text-blue-500 hover:underline hover:text-blue-700">Admin Portal <span className="text-xs">Create Rust App</span></a>
+        {infoQuery.data?.open && <span className="p-4 text-xs text-yellow-600">Open to everyone (development)</span>}
+        <span className="p-4 text-xs text-gray-500">Press Ctrl+K to search</span>
+      </div>
       <div className="flex-1 flex">
         <div className="p-4 w-50 border-r-2 border-grey-50">
           <h2 className="text-xs">tables {tableQuery.isFetching && <span className="text-gray-500">(Loading...)</span>}</h2>
+          <button onClick={() => setTool('schema')} className="block text-xs hover:underline text-blue-500 hover:text-blue-700">schema diagram</button>
           <ul className="flex-col">
             {tableQuery.data && tableQuery.data.map(table =>
               <li className="flex">
-                <TableLink name={table.name} onClick={() => setSelectedTable(table.name)} />
+                <button onClick={() => selectTable(table.name)} className="flex-1 truncate text-left hover:underline text-blue-500 hover:text-blue-700">{table.name}</button> ({table.count >= 0 ? table.count : '-'})
               </li>
             )}
-            
           </ul>
+          <h2 className="text-xs mt-4">app</h2>
+          {infoQuery.data?.environment && <button onClick={() => setTool('environment')} className="block hover:underline text-blue-500 hover:text-blue-700">Environment</button>}
+          {infoQuery.data?.scheduler && <button onClick={() => setTool('schedules')} className="block hover:underline text-blue-500 hover:text-blue-700">Schedules</button>}
+          {infoQuery.data?.flags && <button onClick={() => setTool('flags')} className="block hover:underline text-blue-500 hover:text-blue-700">Feature flags</button>}
+          {infoQuery.data?.webhooks && <button onClick={() => setTool('webhooks')} className="block hover:underline text-blue-500 hover:text-blue-700">Webhooks</button>}
+          {infoQuery.data?.users && <>
+            <h2 className="text-xs mt-4">auth</h2>
+            <button onClick={() => setTool('users')} className="block hover:underline text-blue-500 hover:text-blue-700">Users</button>
+            {infoQuery.data?.audit_log && <button onClick={() => setTool('audit')} className="block hover:underline text-blue-500 hover:text-blue-700">Audit log</button>}
+          </>}
+          {infoQuery.data?.mail_log && <>
+            <h2 className="text-xs mt-4">mail</h2>
+            <button onClick={() => setTool('emails')} className="block hover:underline text-blue-500 hover:text-blue-700">Emails</button>
+          </>}
+          {infoQuery.data?.dev_tools && <>
+            <h2 className="text-xs mt-4">development</h2>
+            <button onClick={() => setTool('sql')} className="block hover:underline text-blue-500 hover:text-blue-700">SQL console</button>
+            <button onClick={() => setTool('migrations')} className="block hover:underline text-blue-500 hover:text-blue-700">Migrations</button>
+            <button onClick={() => setTool('requests')} className="block hover:underline text-blue-500 hover:text-blue-700">Requests</button>
+            <button onClick={() => setTool('health')} className="block hover:underline text-blue-500 hover:text-blue-700">Health</button>
+            <button onClick={() => setTool('mailbox')} className="block hover:underline text-blue-500 hover:text-blue-700">Mailbox</button>
+            <button onClick={() => setTool('mail-preview')} className="block hover:underline text-blue-500 hover:text-blue-700">Mail templates</button>
+          </>}
         </div>
         <div className="p-4 flex-1">
-          {!selectedTable && <div className="text-gray-500">
+          {tool === 'schema' && <SchemaDiagram onSelectTable={selectTable} />}
+          {tool === 'sql' && <SqlConsole enabled={!!infoQuery.data?.sql_console} />}
+          {tool === 'migrations' && <Migrations />}
+          {tool === 'requests' && <Requests />}
+          {tool === 'health' && <Health />}
+          {tool === 'mailbox' && <Mailbox />}
+          {tool === 'mail-preview' && <MailPreview />}
+          {tool === 'emails' && <EmailLog />}
+          {tool === 'audit' && <AuditLog />}
+          {tool === 'users' && <Users />}
+          {tool === 'environment' && <EnvironmentInfo />}
+          {tool === 'schedules' && <Schedules />}
+          {tool === 'flags' && <FeatureFlags />}
+          {tool === 'webhooks' && <Webhooks />}
+          {!tool && !selectedTable && <div className="text-gray-500">
             No table selected.
           </div>}
-          {selectedTable && <TableView name={selectedTable}/>}
+          {!tool && selectedTable && <TableView name={selectedTable} recordId={selectedRecord} onShowAll={() => setSelectedRecord(undefined)}/>}
         </div>
       </div>
     </div>
@@ -0,0 +1,21 @@
+//! The app's events, and their subscribers (see `create_rust_app::events`)
+use create_rust_app::events::{self, Event};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// an example event, published with `events::publish(TodoCreated { todo_id: todo.id })`
+pub struct TodoCreated {
+    pub todo_id: i32,
+}
+
+impl Event for TodoCreated {
+    const NAME: &'static str = "todo_created";
+}
+
+/// registers the subscribers, at the startup of the server (and of the tasks queue's workers,
+/// which run the queued subscribers)
+pub fn subscribe() {
+    events::subscribe(|_db, event: &TodoCreated| {
+        println!("Todo #{} was created", event.todo_id);
+    });
+}
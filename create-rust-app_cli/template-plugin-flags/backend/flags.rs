@@ -0,0 +1,18 @@
+use create_rust_app::flags::Flag;
+use create_rust_app::Database;
+
+/// the app's feature flags: off by default, unless turned on in an environment (`APP_ENV`)
+///
+/// once registered, they're managed in the admin portal's "Feature flags" page
+pub fn definitions() -> Vec<Flag> {
+    vec![Flag::new("example")
+        .description("an example flag, on in development")
+        .in_environment("development", true)]
+}
+
+/// records the flags, so they can be evaluated and managed
+pub fn register(db: &Database) {
+    if let Err(err) = create_rust_app::flags::register(&mut db.get_connection(), definitions()) {
+        println!("Could not register the feature flags: {err}");
+    }
+}
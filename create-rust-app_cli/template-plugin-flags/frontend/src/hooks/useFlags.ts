@@ -0,0 +1,51 @@
+import { useEffect, useState } from 'react'
+import { useAuth } from './useAuth'
+
+type Flags = Record<string, boolean>
+
+/** the flags of the current user, fetched once for every component using them */
+let shared: { accessToken?: string; flags: Promise<Flags> } | undefined
+
+const fetchFlags = (accessToken?: string): Promise<Flags> => {
+  if (!shared || shared.accessToken !== accessToken) {
+    const flags = fetch('/api/flags', {
+      headers: accessToken ? { Authorization: `Bearer ${accessToken}` } : {},
+    })
+      .then((response) => (response.ok ? response.json() : {}))
+      .catch(() => ({}))
+    shared = { accessToken, flags }
+  }
+
+  return shared.flags
+}
+
+/**
+ * Whether each feature flag is on for the current user (or for anonymous
+ * visitors), by key. They're evaluated by the backend, see
+ * `create_rust_app::flags`, and fetched again when the user logs in or out.
+ */
+export const useFlags = () => {
+  const auth = useAuth()
+  const [flags, setFlags] = useState<Flags>({})
+  const [isLoading, setLoading] = useState<boolean>(true)
+
+  useEffect(() => {
+    let active = true
+    setLoading(true)
+
+    fetchFlags(auth.accessToken).then((flags) => {
+      if (!active) return
+      setFlags(flags)
+      setLoading(false)
+    })
+
+    return () => {
+      active = false
+    }
+  }, [auth.accessToken])
+
+  return { flags, isLoading }
+}
+
+/** whether the flag `key` is on, off while the flags load (or if it isn't defined) */
+export const useFlag = (key: string): boolean => useFlags().flags[key] ?? false
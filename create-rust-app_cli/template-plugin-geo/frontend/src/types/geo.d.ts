@@ -0,0 +1,13 @@
+// GeoJSON geometries: the TypeScript type of `create_rust_app::geo::Geometry`, which the models'
+// geometry columns have in `rust.d.ts`
+
+/** [longitude, latitude] */
+type Position = [number, number]
+
+type Geometry =
+  | { type: 'Point'; coordinates: Position }
+  | { type: 'LineString'; coordinates: Position[] }
+  | { type: 'Polygon'; coordinates: Position[][] }
+  | { type: 'MultiPoint'; coordinates: Position[] }
+  | { type: 'MultiLineString'; coordinates: Position[][] }
+  | { type: 'MultiPolygon'; coordinates: Position[][][] }
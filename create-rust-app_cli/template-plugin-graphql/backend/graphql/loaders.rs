@@ -0,0 +1,13 @@
+use async_graphql::dataloader::DataLoader;
+use async_graphql::Data;
+use create_rust_app::Database;
+
+/// adds the loaders to a request's (or a subscription's connection's) data
+///
+/// resolvers load records through them (`ctx.data::<DataLoader<UserLoader>>()?.load_one(id)`)
+/// instead of querying the database themselves: the lookups made while resolving a request are
+/// batched into one query per loader, rather than one per record (the N+1 queries problem)
+pub fn register(data: &mut Data, db: &Database) {
+    data.insert(DataLoader::new(super::users::UserLoader(db.clone()), tokio::spawn));
+    /* CRA: loaders */
+}
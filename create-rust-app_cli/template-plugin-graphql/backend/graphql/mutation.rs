@@ -1 +1,30 @@
-pub use async_graphql::EmptyMutation as MutationRoot;
+use async_graphql::{Context, MergedObject, Object, Result};
+use create_rust_app::auth::Auth;
+
+use super::subscription::{Message, MessageBus};
+
+/// the mutations of every resource, merged
+#[derive(MergedObject, Default)]
+pub struct MutationRoot(
+    MessageMutation,
+    /* CRA: mutations */
+);
+
+#[derive(Default)]
+pub struct MessageMutation;
+
+#[Object]
+impl MessageMutation {
+    /// sends `text` to the `messages` subscribers
+    async fn send_message(&self, ctx: &Context<'_>, text: String) -> Result<Message> {
+        let auth = ctx.data::<Auth>()?;
+        let message = Message {
+            user_id: auth.user_id,
+            text,
+        };
+
+        ctx.data::<MessageBus>()?.publish(message.clone());
+
+        Ok(message)
+    }
+}
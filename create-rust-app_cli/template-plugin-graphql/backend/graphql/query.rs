@@ -1,12 +1,21 @@
-use async_graphql::{Context, Object};
+use async_graphql::{Context, MergedObject, Object};
 use create_rust_app::auth::Auth;
 
-pub struct QueryRoot;
+/// the queries of every resource, merged
+#[derive(MergedObject, Default)]
+pub struct QueryRoot(
+    PingQuery,
+    super::users::UserQuery,
+    /* CRA: queries */
+);
+
+#[derive(Default)]
+pub struct PingQuery;
 
 #[Object]
-impl QueryRoot {
+impl PingQuery {
     async fn ping(&self, ctx: &Context<'_>) -> String {
         let auth = ctx.data::<Auth>().unwrap();
         format!("Hello user#{}", auth.user_id)
     }
-}
\ No newline at end of file
+}
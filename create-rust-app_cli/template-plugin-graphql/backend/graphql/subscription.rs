@@ -1 +1,68 @@
-pub use async_graphql::EmptySubscription as SubscriptionRoot;
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, Result, SimpleObject, Subscription};
+use create_rust_app::auth::Auth;
+use futures_util::Stream;
+use tokio::sync::broadcast;
+
+use super::users::{User, UserLoader};
+
+#[derive(Clone, Debug, SimpleObject)]
+#[graphql(complex)]
+pub struct Message {
+    pub user_id: i32,
+    pub text: String,
+}
+
+#[ComplexObject]
+impl Message {
+    /// who sent it
+    async fn user(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        Ok(ctx.data::<DataLoader<UserLoader>>()?.load_one(self.user_id).await?)
+    }
+}
+
+/// publishes the [`Message`]s sent with the `sendMessage` mutation to the `messages` subscribers
+///
+/// it's in the schema's data; use the same pattern to push your own events to subscribers
+#[derive(Clone)]
+pub struct MessageBus(broadcast::Sender<Message>);
+
+impl Default for MessageBus {
+    fn default() -> Self {
+        Self(broadcast::channel(100).0)
+    }
+}
+
+impl MessageBus {
+    pub fn publish(&self, message: Message) {
+        // fails when there are no subscribers, the message is dropped
+        self.0.send(message).ok();
+    }
+
+    /// the messages published from now on
+    pub fn subscribe(&self) -> impl Stream<Item = Message> {
+        futures_util::stream::unfold(self.0.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(message) => return Some((message, receiver)),
+                    // a subscriber too slow to keep up misses the oldest messages
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// the messages sent by everyone, as they're sent
+    async fn messages(&self, ctx: &Context<'_>) -> Result<impl Stream<Item = Message>> {
+        // the websocket's connection was authenticated, see `on_connection_init`
+        ctx.data::<Auth>()?;
+
+        Ok(ctx.data::<MessageBus>()?.subscribe())
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{Context, Object, Result, SimpleObject};
+use create_rust_app::auth::Auth;
+use create_rust_app::graphql::RoleGuard;
+use create_rust_app::Database;
+
+#[derive(Clone, Debug, SimpleObject)]
+pub struct User {
+    pub id: i32,
+    pub email: Option<String>,
+    pub activated: bool,
+}
+
+impl From<create_rust_app::auth::User> for User {
+    fn from(user: create_rust_app::auth::User) -> Self {
+        Self {
+            id: user.id,
+            email: user.email,
+            activated: user.activated,
+        }
+    }
+}
+
+/// loads the users by id, see `loaders.rs`
+pub struct UserLoader(pub Database);
+
+#[async_trait::async_trait]
+impl Loader<i32> for UserLoader {
+    type Value = User;
+    type Error = String;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let mut db = self.0.get_connection();
+        let users = create_rust_app::auth::User::read_many(&mut db, keys)
+            .map_err(|err| err.to_string())?;
+
+        Ok(users.into_iter().map(|user| (user.id, user.into())).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct UserQuery;
+
+#[Object]
+impl UserQuery {
+    /// the logged in user
+    async fn me(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let auth = ctx.data::<Auth>()?;
+
+        Ok(ctx.data::<DataLoader<UserLoader>>()?.load_one(auth.user_id).await?)
+    }
+
+    /// any user, for admins only (guards authorize single fields, see `create_rust_app::graphql`)
+    #[graphql(guard = "RoleGuard::new(\"admin\")")]
+    async fn user(&self, ctx: &Context<'_>, id: i32) -> Result<Option<User>> {
+        Ok(ctx.data::<DataLoader<UserLoader>>()?.load_one(id).await?)
+    }
+}
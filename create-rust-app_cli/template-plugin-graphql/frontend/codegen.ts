@@ -0,0 +1,25 @@
+import type {CodegenConfig} from '@graphql-codegen/cli'
+
+/**
+ * Generates `src/graphql/generated` from the schema in `src/graphql/schema.graphql`: `graphql()`
+ * parses the queries written with it into typed documents, so Apollo's hooks know their variables
+ * and results:
+ *
+ *   const ME = graphql(`query Me { me { id email } }`)
+ *   const { data } = useQuery(ME) // data?.me?.email is a `string | null | undefined`
+ *
+ * Run `create-rust-app configure --graphql-codegen` (with the backend running) after changing the
+ * schema: it downloads it and regenerates. `npm run graphql-codegen` only regenerates.
+ */
+const config: CodegenConfig = {
+    schema: 'src/graphql/schema.graphql',
+    documents: ['src/**/*.{ts,tsx}', '!src/graphql/generated/**'],
+    generates: {
+        'src/graphql/generated/': {
+            preset: 'client',
+        },
+    },
+    ignoreNoDocuments: true,
+}
+
+export default config
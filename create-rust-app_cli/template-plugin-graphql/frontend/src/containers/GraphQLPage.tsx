@@ -1,5 +1,5 @@
-import React from 'react'
-import {gql, useQuery} from '@apollo/client'
+import React, {useEffect, useState} from 'react'
+import {gql, useMutation, useQuery, useSubscription} from '@apollo/client'
 import {useAuth} from '../hooks/useAuth'
 
 const PING = gql`
@@ -8,15 +8,62 @@ const PING = gql`
   }
 `;
 
+const MESSAGES = gql`
+  subscription Messages {
+    messages {
+      userId
+      text
+    }
+  }
+`;
+
+const SEND_MESSAGE = gql`
+  mutation SendMessage($text: String!) {
+    sendMessage(text: $text) {
+      userId
+      text
+    }
+  }
+`;
+
+interface Message {
+    userId: number
+    text: string
+}
+
 export const usePing = () => {
     return useQuery<string>(PING)
 }
+
+/** the messages received since the page was opened, through the `messages` subscription */
+export const useMessages = (skip: boolean) => {
+    const [messages, setMessages] = useState<Message[]>([])
+    const subscription = useSubscription<{ messages: Message }>(MESSAGES, { skip })
+
+    useEffect(() => {
+        const message = subscription.data?.messages
+        if (message) setMessages(messages => [...messages, message])
+    }, [subscription.data])
+
+    return { messages, error: subscription.error }
+}
+
 export const GraphQLPage = () => {
     const auth = useAuth()
     const pong = usePing()
+    const { messages, error } = useMessages(!auth.isAuthenticated)
+    const [sendMessage] = useMutation<{ sendMessage: Message }, { text: string }>(SEND_MESSAGE)
+    const [text, setText] = useState<string>('')
 
     console.log('Response from server:', pong.loading ? 'loading' : pong.data)
 
+    const send = async (e: React.FormEvent) => {
+        e.preventDefault()
+        if (!text) return
+        await sendMessage({ variables: { text } })
+        setText('')
+    }
+
     return (
         <div style={{height: '100%', fontSize: '1rem', textAlign: 'left'}}>
             <h1>GraphQL</h1>
@@ -31,6 +78,23 @@ export const GraphQLPage = () => {
                 {auth.isAuthenticated && (pong.loading ? 'Executing GraphQL query...' : JSON.stringify(pong.data, null, 2))}
             </pre>
 
+            <h4>Subscription:</h4>
+            <pre>
+                {`subscription Messages {\n  messages {\n    userId\n    text\n  }\n}`}
+            </pre>
+            {!auth.isAuthenticated && <pre>Please login to test the GraphQL subscription.</pre>}
+            {auth.isAuthenticated && <>
+                <form onSubmit={send}>
+                    <input value={text} placeholder="Send a message to everyone on this page" onChange={e => setText(e.target.value)} />
+                    <button type="submit">Send</button>
+                </form>
+                <pre>
+                    {error && `Subscription failed: ${error.message}`}
+                    {!error && messages.length === 0 && 'Waiting for messages... (open this page in another tab, and send one)'}
+                    {messages.map((message, index) => <div key={index}>user#{message.userId}: {message.text}</div>)}
+                </pre>
+            </>}
+
             <p><a href={"/graphql"}>Visit Playground</a></p>
         </div>
     )
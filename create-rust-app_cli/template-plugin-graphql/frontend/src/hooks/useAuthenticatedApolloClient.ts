@@ -1,22 +1,33 @@
-import {ApolloClient, ApolloLink, concat, HttpLink, InMemoryCache, split} from '@apollo/client'
+import {ApolloClient, ApolloLink, concat, InMemoryCache, split} from '@apollo/client'
 import {GraphQLWsLink} from '@apollo/client/link/subscriptions'
 import {createClient} from 'graphql-ws'
-import {getMainDefinition} from '@apollo/client/utilities'
+import createUploadLink from 'apollo-upload-client/public/createUploadLink.js'
+import {createPersistedQueryLink} from '@apollo/client/link/persisted-queries'
+import {sha256} from 'crypto-hash'
+import {getMainDefinition, relayStylePagination} from '@apollo/client/utilities'
 import {useAuth} from './useAuth'
-import {useMemo} from 'react'
+import {useEffect, useMemo} from 'react'
 
 export const useAuthenticatedApolloClient = () => {
     const auth = useAuth()
 
+    // subscriptions go through a websocket, authenticated by its first message (see `on_connection_init` in `backend/graphql`)
+    const wsClient = useMemo(() => createClient({
+        url: `${window.location.origin.replace("http", "ws")}/api/graphql/ws`,
+        connectionParams: {
+            token: auth.accessToken,
+        },
+    }), [auth.accessToken])
+
+    // close the websocket authenticated with the previous token
+    useEffect(() => () => { wsClient.dispose() }, [wsClient])
+
     return useMemo(() => {
-        const httpLink = new HttpLink({ uri: '/api/graphql' })
+        // sends the queries' hashes instead of the queries once the backend knows them (see `PersistedQueries`),
+        // and the operations with `File` variables as multipart requests (the `Upload` scalar)
+        const httpLink = createPersistedQueryLink({ sha256 }).concat(createUploadLink({ uri: '/api/graphql' }))
 
-        const wsLink = new GraphQLWsLink(createClient({
-            url: `${window.location.origin.replace("http", "ws")}/api/graphql/ws`,
-            connectionParams: {
-                token: auth.accessToken,
-            },
-        }))
+        const wsLink = new GraphQLWsLink(wsClient)
 
         const splitLink = split(
             ({ query }) => {
@@ -35,7 +46,7 @@ export const useAuthenticatedApolloClient = () => {
             operation.setContext(({ headers = {} }) => ({
                 headers: {
                     ...headers,
-                    Authorization: auth.accessToken,
+                    Authorization: `Bearer ${auth.accessToken}`,
                 }
             }))
 
@@ -43,8 +54,17 @@ export const useAuthenticatedApolloClient = () => {
         })
 
         return new ApolloClient({
-            cache: new InMemoryCache(),
+            cache: new InMemoryCache({
+                typePolicies: {
+                    Query: {
+                        fields: {
+                            // the connections' pages, appended by `fetchMore({ variables: { after: pageInfo.endCursor } })`
+                            /* CRA: connections */
+                        },
+                    },
+                },
+            }),
             link: concat(authMiddleware, splitLink)
         })
-    }, [auth.accessToken])
+    }, [auth.accessToken, wsClient])
 }
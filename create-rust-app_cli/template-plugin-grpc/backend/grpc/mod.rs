@@ -0,0 +1,23 @@
+//! The gRPC services, served alongside the HTTP server (see `main.rs`) on `GRPC_ADDRESS`
+//! (`0.0.0.0:50051` by default).
+//!
+//! Each service is defined in `proto/` (compiled by `build.rs`, which needs `protoc`), and
+//! implemented in this module. `create-rust-app configure` adds one for every resource it adds.
+use create_rust_app::Database;
+use tonic::transport::Server;
+
+/// serves the gRPC services until the app exits
+pub async fn serve(db: Database) {
+    let address = create_rust_app::grpc::address();
+    println!("gRPC server listening on {address}");
+
+    let served = Server::builder()
+        /* CRA: layers */
+        /* CRA: services */
+        .serve(address)
+        .await;
+
+    if let Err(err) = served {
+        println!("gRPC server error: {err}");
+    }
+}
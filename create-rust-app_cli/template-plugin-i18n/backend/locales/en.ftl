@@ -0,0 +1,5 @@
+# The messages of the default locale (CRA_DEFAULT_LOCALE, or `en`): the other catalogs fall back
+# to them. Add a `<locale>.ftl` file next to this one to support another locale.
+app-title = Create Rust App
+welcome = Welcome, { $name }!
+language = Language
@@ -0,0 +1,3 @@
+app-title = Create Rust App
+welcome = Bienvenue, { $name } !
+language = Langue
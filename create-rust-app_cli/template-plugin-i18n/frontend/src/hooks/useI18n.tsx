@@ -0,0 +1,95 @@
+import React, { createContext, useCallback, useContext, useEffect, useState } from 'react'
+
+const LOCALE_COOKIE = 'locale'
+
+interface Locales {
+  default_locale: string
+  locales: string[]
+}
+
+interface I18nContext {
+  /** the locale the messages are in, `undefined` until the locales are loaded */
+  locale: string | undefined
+  /** the locales the backend's catalogs support */
+  locales: string[]
+  /** switches to `locale`, the backend reads it from the `locale` cookie too */
+  setLocale: (locale: string) => void
+  /** the message `id` with its `{ $name }` placeholders replaced by `args`, or `id` if it's missing */
+  t: (id: string, args?: Record<string, string | number>) => string
+}
+
+const I18nContext = createContext<I18nContext | undefined>(undefined)
+
+const readCookie = (name: string) =>
+  document.cookie
+    .split(';')
+    .map((cookie) => cookie.trim().split('='))
+    .find(([key]) => key === name)?.[1]
+
+/** the first of the user's locales (their pick, then the browser's) that's supported, ex: `pt` for `pt-BR` */
+const negotiate = (supported: Locales) => {
+  const preferences = [readCookie(LOCALE_COOKIE), localStorage.getItem(LOCALE_COOKIE), ...navigator.languages]
+  const find = (wanted: string) => supported.locales.find((locale) => locale.toLowerCase() === wanted.toLowerCase())
+
+  for (const preference of preferences) {
+    if (!preference) continue
+    const locale = find(preference) || find(preference.split('-')[0])
+    if (locale) return locale
+  }
+
+  return supported.default_locale
+}
+
+const format = (message: string, args: Record<string, string | number> = {}) =>
+  message.replace(/\{\s*\$([\w-]+)\s*\}/g, (placeholder, name) =>
+    name in args ? String(args[name]) : placeholder
+  )
+
+/** loads the catalog of the user's locale from the backend, so both use the same locales */
+export const I18nProvider = (props: { children: React.ReactNode }) => {
+  const [locales, setLocales] = useState<Locales>()
+  const [locale, setLocaleState] = useState<string>()
+  const [messages, setMessages] = useState<Record<string, string>>({})
+
+  useEffect(() => {
+    fetch('/api/i18n')
+      .then((response) => response.json())
+      .then((locales: Locales) => {
+        setLocales(locales)
+        setLocaleState(negotiate(locales))
+      })
+  }, [])
+
+  useEffect(() => {
+    if (!locale) return
+
+    fetch(`/api/i18n/${locale}`)
+      .then((response) => response.json())
+      .then(setMessages)
+    document.documentElement.lang = locale
+  }, [locale])
+
+  const setLocale = useCallback((locale: string) => {
+    localStorage.setItem(LOCALE_COOKIE, locale)
+    document.cookie = `${LOCALE_COOKIE}=${locale}; path=/; max-age=31536000; samesite=lax`
+    setLocaleState(locale)
+  }, [])
+
+  const t = useCallback(
+    (id: string, args?: Record<string, string | number>) => format(messages[id] ?? id, args),
+    [messages]
+  )
+
+  return (
+    <I18nContext.Provider value={{ locale, locales: locales?.locales ?? [], setLocale, t }}>
+      {props.children}
+    </I18nContext.Provider>
+  )
+}
+
+export const useI18n = () => {
+  const context = useContext(I18nContext)
+  if (!context) throw new Error('useI18n must be used within an I18nProvider')
+
+  return context
+}
@@ -0,0 +1,24 @@
+// Shows the web pushes of the notifications plugin, and opens their link when they're clicked.
+self.addEventListener('push', (event) => {
+  const notification = event.data ? event.data.json() : {}
+
+  event.waitUntil(
+    self.registration.showNotification(notification.title || 'New notification', {
+      body: notification.body,
+      tag: notification.id ? `notification-${notification.id}` : undefined,
+      data: { link: notification.link },
+    })
+  )
+})
+
+self.addEventListener('notificationclick', (event) => {
+  event.notification.close()
+
+  const url = new URL(event.notification.data?.link || '/', self.location.origin).href
+  event.waitUntil(
+    self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then((windows) => {
+      const existing = windows.find((client) => client.url === url)
+      return existing ? existing.focus() : self.clients.openWindow(url)
+    })
+  )
+})
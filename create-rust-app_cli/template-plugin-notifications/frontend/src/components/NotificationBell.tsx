@@ -0,0 +1,95 @@
+import React, { useState } from 'react'
+import { useNavigate } from 'react-router-dom'
+import { InAppNotification, useNotifications, usePushNotifications } from '../hooks/useNotifications'
+
+export const NotificationBell = () => {
+  const navigate = useNavigate()
+  const { notifications, unreadCount, markRead, markAllRead } = useNotifications()
+  const push = usePushNotifications()
+  const [isOpen, setOpen] = useState<boolean>(false)
+
+  const open = async (notification: InAppNotification) => {
+    await markRead(notification)
+    setOpen(false)
+    if (notification.link) navigate(notification.link)
+  }
+
+  return (
+    <div style={{ position: 'relative', display: 'inline-block' }}>
+      <button aria-label="Notifications" onClick={() => setOpen(!isOpen)}>
+        🔔
+        {unreadCount > 0 && (
+          <span
+            style={{
+              marginLeft: '4px',
+              padding: '0 6px',
+              borderRadius: '8px',
+              background: '#d33',
+              color: 'white',
+              fontSize: '12px',
+            }}
+          >
+            {unreadCount > 99 ? '99+' : unreadCount}
+          </span>
+        )}
+      </button>
+      {isOpen && (
+        <div
+          style={{
+            position: 'absolute',
+            right: 0,
+            width: '320px',
+            maxHeight: '400px',
+            overflowY: 'auto',
+            background: 'white',
+            color: 'black',
+            border: '1px solid #ccc',
+            textAlign: 'left',
+            zIndex: 1000,
+          }}
+        >
+          <div style={{ display: 'flex', justifyContent: 'space-between', padding: '8px' }}>
+            <b>Notifications</b>
+            {unreadCount > 0 && (
+              <a
+                href="#"
+                onClick={(e) => {
+                  e.preventDefault()
+                  markAllRead()
+                }}
+              >
+                Mark all as read
+              </a>
+            )}
+          </div>
+          {notifications.length === 0 && <p style={{ padding: '8px' }}>You're all caught up!</p>}
+          {notifications.map((notification) => (
+            <div
+              key={notification.id}
+              onClick={() => open(notification)}
+              style={{
+                padding: '8px',
+                borderTop: '1px solid #eee',
+                cursor: 'pointer',
+                background: notification.read_at ? 'white' : '#eef4ff',
+              }}
+            >
+              <div style={{ fontWeight: notification.read_at ? 'normal' : 'bold' }}>
+                {notification.title}
+              </div>
+              {notification.body && <div style={{ fontSize: '14px' }}>{notification.body}</div>}
+              <div style={{ fontSize: '12px', color: '#666' }}>
+                {new Date(notification.created_at).toLocaleString()}
+              </div>
+            </div>
+          ))}
+          {push.isSupported && !push.isSubscribed && (
+            <div style={{ padding: '8px', borderTop: '1px solid #eee' }}>
+              <button onClick={push.subscribe}>Get notified in this browser</button>
+            </div>
+          )}
+        </div>
+      )}
+    </div>
+  )
+}
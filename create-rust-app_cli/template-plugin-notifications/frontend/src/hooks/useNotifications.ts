@@ -0,0 +1,152 @@
+import { useCallback, useEffect, useState } from 'react'
+import { useAuth } from './useAuth'
+
+/** a notification of the bell (`create_rust_app::notifications::Notification`) */
+export interface InAppNotification {
+  id: number
+  user_id: number
+  kind: string
+  title: string
+  body: string
+  link?: string
+  read_at?: string
+  created_at: string
+}
+
+/** how often the bell checks for new notifications */
+const POLL_INTERVAL = 30 * 1000
+
+/**
+ * The user's most recent notifications and how many they haven't read,
+ * refreshed every 30 seconds and whenever the tab gets the focus back.
+ */
+export const useNotifications = () => {
+  const auth = useAuth()
+  const [notifications, setNotifications] = useState<InAppNotification[]>([])
+  const [unreadCount, setUnreadCount] = useState<number>(0)
+
+  const request = useCallback(
+    (path: string, init?: RequestInit) =>
+      fetch(`/api/notifications${path}`, {
+        ...init,
+        headers: {
+          'Content-Type': 'application/json',
+          Authorization: `Bearer ${auth.accessToken}`,
+        },
+      }),
+    [auth.accessToken]
+  )
+
+  const refresh = useCallback(async () => {
+    if (!auth.isAuthenticated) return
+
+    const response = await request('')
+    if (!response.ok) return
+
+    const json = await response.json()
+    setNotifications(json.notifications)
+    setUnreadCount(json.unread_count)
+  }, [auth.isAuthenticated, request])
+
+  useEffect(() => {
+    if (!auth.isAuthenticated) {
+      setNotifications([])
+      setUnreadCount(0)
+      return
+    }
+
+    refresh()
+    const interval = setInterval(refresh, POLL_INTERVAL)
+    window.addEventListener('focus', refresh)
+
+    return () => {
+      clearInterval(interval)
+      window.removeEventListener('focus', refresh)
+    }
+  }, [auth.isAuthenticated, refresh])
+
+  const markRead = async (notification: InAppNotification) => {
+    if (notification.read_at) return
+
+    await request(`/${notification.id}/read`, { method: 'POST' })
+    await refresh()
+  }
+
+  const markAllRead = async () => {
+    await request('/read-all', { method: 'POST' })
+    await refresh()
+  }
+
+  return { notifications, unreadCount, refresh, markRead, markAllRead }
+}
+
+/** the VAPID key is sent base64url-encoded, browsers want its bytes */
+const decodeKey = (key: string): Uint8Array => {
+  const base64 = (key + '='.repeat((4 - (key.length % 4)) % 4)).replace(/-/g, '+').replace(/_/g, '/')
+  return Uint8Array.from(atob(base64), (c) => c.charCodeAt(0))
+}
+
+/**
+ * Web pushes to this browser: `subscribe()` asks for the permission to show
+ * notifications, then registers the browser with the backend (which pushes
+ * notifications sent with `Notify` to it).
+ */
+export const usePushNotifications = () => {
+  const auth = useAuth()
+  const isSupported = 'serviceWorker' in navigator && 'PushManager' in window
+  const [isSubscribed, setSubscribed] = useState<boolean>(false)
+
+  useEffect(() => {
+    if (!isSupported) return
+
+    navigator.serviceWorker
+      .getRegistration('/notifications-sw.js')
+      .then((registration) => registration?.pushManager.getSubscription())
+      .then((subscription) => setSubscribed(!!subscription))
+  }, [isSupported])
+
+  const subscribe = async () => {
+    if (!isSupported || !auth.isAuthenticated) return
+
+    const response = await fetch('/api/notifications/push/public-key')
+    if (!response.ok) return
+    const { public_key } = await response.json()
+
+    if ((await Notification.requestPermission()) !== 'granted') return
+
+    const registration = await navigator.serviceWorker.register('/notifications-sw.js')
+    const subscription = await registration.pushManager.subscribe({
+      userVisibleOnly: true,
+      applicationServerKey: decodeKey(public_key),
+    })
+
+    await fetch('/api/notifications/push/subscriptions', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify(subscription.toJSON()),
+    })
+    setSubscribed(true)
+  }
+
+  const unsubscribe = async () => {
+    const registration = await navigator.serviceWorker.getRegistration('/notifications-sw.js')
+    const subscription = await registration?.pushManager.getSubscription()
+    if (!subscription) return
+
+    await fetch('/api/notifications/push/subscriptions', {
+      method: 'DELETE',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({ endpoint: subscription.endpoint }),
+    })
+    await subscription.unsubscribe()
+    setSubscribed(false)
+  }
+
+  return { isSupported, isSubscribed, subscribe, unsubscribe }
+}
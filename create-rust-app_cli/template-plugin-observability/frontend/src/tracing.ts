@@ -0,0 +1,28 @@
+/**
+ * W3C trace context (https://www.w3.org/TR/trace-context/) for the requests
+ * to the backend, which continues their trace and returns its own context in
+ * the `traceresponse` header (see `create_rust_app::observability`).
+ */
+
+const randomHex = (bytes: number): string =>
+  Array.from(crypto.getRandomValues(new Uint8Array(bytes)))
+    .map((byte) => byte.toString(16).padStart(2, '0'))
+    .join('')
+
+/** a `traceparent` header starting a new (sampled) trace */
+export const traceparent = (): string => `00-${randomHex(16)}-${randomHex(8)}-01`
+
+/**
+ * the trace id of a backend response, ex: to show it along with an error so
+ * it can be looked up in the traces
+ */
+export const traceIdOf = (response: Response): string | undefined =>
+  (response.headers.get('traceresponse') ?? undefined)?.split('-')[1]
+
+/** `fetch`, starting a trace the backend's spans join */
+export const tracedFetch = (input: RequestInfo, init: RequestInit = {}): Promise<Response> => {
+  const headers = new Headers(init.headers)
+  if (!headers.has('traceparent')) headers.set('traceparent', traceparent())
+
+  return fetch(input, { ...init, headers })
+}
@@ -0,0 +1,49 @@
+import React from 'react'
+import { useNavigate } from 'react-router-dom'
+import { useAuth } from '../hooks/useAuth'
+import { useBilling } from '../hooks/useBilling'
+
+const Billing = () => {
+  const { status, error, subscribe, manage } = useBilling()
+
+  if (!status) return null
+
+  const subscription = status.subscription
+  const periodEnd = subscription?.current_period_end && new Date(subscription.current_period_end).toLocaleDateString()
+
+  return (
+    <div className="Form" style={{ textAlign: 'left' }}>
+      {status.active && subscription && (
+        <p>
+          Your subscription is {subscription.status}
+          {periodEnd && (subscription.cancel_at_period_end ? `, it ends on ${periodEnd}` : `, it renews on ${periodEnd}`)}.
+        </p>
+      )}
+      {!status.active && <p>You don't have a subscription{subscription && ` (the last one is ${subscription.status})`}.</p>}
+      <div style={{ display: 'flex', gap: '8px' }}>
+        {!status.active && status.prices.length > 0 && <button onClick={() => subscribe()}>Subscribe</button>}
+        {subscription && <button onClick={manage}>Manage billing</button>}
+      </div>
+      {error && <p style={{ color: 'red' }}>{error}</p>}
+    </div>
+  )
+}
+
+export const BillingPage = () => {
+  const auth = useAuth()
+  const navigate = useNavigate()
+
+  return (
+    <div style={{ textAlign: 'left' }}>
+      <h1>Billing</h1>
+      {auth.isAuthenticated && <Billing />}
+      {!auth.isAuthenticated && (
+        <div>
+          <a href="#" onClick={() => navigate('/login')}>
+            Login to manage your subscription
+          </a>
+        </div>
+      )}
+    </div>
+  )
+}
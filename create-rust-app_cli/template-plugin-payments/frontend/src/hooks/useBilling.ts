@@ -0,0 +1,53 @@
+import { useCallback, useEffect, useState } from 'react'
+import { useAuth } from './useAuth'
+
+/**
+ * The user's subscription, and the actions sending them to Stripe: Checkout to
+ * subscribe, the billing portal to manage their subscription. Stripe sends them
+ * back to the current page.
+ */
+export const useBilling = () => {
+  const auth = useAuth()
+  const [status, setStatus] = useState<BillingStatus | undefined>(undefined)
+  const [error, setError] = useState<string | undefined>(undefined)
+
+  useEffect(() => {
+    if (!auth.isAuthenticated) {
+      setStatus(undefined)
+      return
+    }
+
+    fetch('/api/payments/status', {
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    }).then(async (response) => {
+      if (response.ok) setStatus(await response.json())
+    })
+  }, [auth.isAuthenticated])
+
+  const redirect = useCallback(
+    async (path: string, body: object) => {
+      setError(undefined)
+
+      const response = await fetch(`/api/payments/${path}`, {
+        method: 'POST',
+        headers: {
+          'Content-Type': 'application/json',
+          Authorization: `Bearer ${auth.accessToken}`,
+        },
+        body: JSON.stringify({ return_url: window.location.href, ...body }),
+      })
+      const json = await response.json()
+
+      if (response.ok) window.location.href = json.url
+      else setError(json.message)
+    },
+    [auth.accessToken]
+  )
+
+  const subscribe = (priceId?: string) => redirect('checkout', { price_id: priceId })
+  const manage = () => redirect('portal', {})
+
+  return { status, error, subscribe, manage }
+}
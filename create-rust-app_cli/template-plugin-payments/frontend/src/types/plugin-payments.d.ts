@@ -0,0 +1,34 @@
+/* This file is generated and managed by tsync */
+
+interface Customer {
+  user_id: number
+  customer_id: string
+  created_at: Date
+}
+
+interface Subscription {
+  id: number
+  user_id: number
+  subscription_id: string
+  price_id?: string
+  status: string
+  current_period_end?: Date
+  cancel_at_period_end: boolean
+  created_at: Date
+  updated_at: Date
+}
+
+interface SubscriptionChangeset {
+  user_id: number
+  subscription_id: string
+  price_id?: string
+  status: string
+  current_period_end?: Date
+  cancel_at_period_end: boolean
+}
+
+interface BillingStatus {
+  active: boolean
+  subscription?: Subscription
+  prices: Array<string>
+}
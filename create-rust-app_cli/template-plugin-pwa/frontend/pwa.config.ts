@@ -0,0 +1,46 @@
+import { VitePWA } from 'vite-plugin-pwa'
+
+/**
+ * The PWA's manifest (written to `dist/manifest.webmanifest`) and service
+ * worker (`src/sw.ts`, built to `dist/sw.js`), see `src/pwa.ts`.
+ */
+export const pwa = () =>
+  VitePWA({
+    strategies: 'injectManifest',
+    srcDir: 'src',
+    filename: 'sw.ts',
+    // registered by `registerServiceWorker`
+    injectRegister: null,
+    manifestFilename: 'manifest.webmanifest',
+    manifest: {
+      short_name: 'Create Rust App',
+      name: 'Create Rust App Sample',
+      icons: [
+        {
+          src: '/images/favicon.ico',
+          sizes: '64x64 32x32 24x24 16x16',
+          type: 'image/x-icon',
+        },
+        {
+          src: '/images/logo192.png',
+          type: 'image/png',
+          sizes: '192x192',
+        },
+        {
+          src: '/images/logo512.png',
+          type: 'image/png',
+          sizes: '512x512',
+        },
+      ],
+      start_url: '/',
+      scope: '/',
+      display: 'standalone',
+      theme_color: '#000000',
+      background_color: '#ffffff',
+    },
+    injectManifest: {
+      // precached when the service worker installs; the pages are rendered by
+      // the backend, see `sw.ts`
+      globPatterns: ['**/*.{js,css,ico,png,svg,woff2}'],
+    },
+  })
@@ -0,0 +1,24 @@
+/**
+ * Registers the service worker (`sw.ts`) in production builds, so the app
+ * works offline and can be installed. The development server doesn't build it.
+ */
+export const registerServiceWorker = () => {
+  if (!import.meta.env.PROD || !('serviceWorker' in navigator)) return
+
+  window.addEventListener('load', () => {
+    navigator.serviceWorker
+      .register('/sw.js', { scope: '/' })
+      .catch((error) => console.error('Could not register the service worker', error))
+  })
+}
+
+/**
+ * Forgets the API responses the service worker cached, ex: when the user logs
+ * out (the backend only lets it cache theirs when they're `private`).
+ */
+export const clearApiCache = async () => {
+  if (!('serviceWorker' in navigator)) return
+
+  const registration = await navigator.serviceWorker.getRegistration()
+  registration?.active?.postMessage({ type: 'CLEAR_API_CACHE' })
+}
@@ -0,0 +1,102 @@
+/// <reference lib="webworker" />
+import { clientsClaim } from 'workbox-core'
+import type { WorkboxPlugin } from 'workbox-core'
+import { ExpirationPlugin } from 'workbox-expiration'
+import { cleanupOutdatedCaches, precacheAndRoute } from 'workbox-precaching'
+import type { PrecacheEntry } from 'workbox-precaching'
+import { NavigationRoute, registerRoute } from 'workbox-routing'
+import { NetworkFirst } from 'workbox-strategies'
+
+declare const self: ServiceWorkerGlobalScope & {
+  __WB_MANIFEST: Array<PrecacheEntry | string>
+}
+
+/** the pages, rendered by the backend: `/` is the app shell shown offline */
+const SHELL_CACHE = 'app-shell'
+/** the API's GET responses, see `create_rust_app::pwa` */
+const API_CACHE = 'api'
+const SHELL = '/'
+
+// new versions take over right away, their assets replacing the old ones
+self.skipWaiting()
+clientsClaim()
+
+// the built scripts, styles and images (listed by vite-plugin-pwa)
+precacheAndRoute(self.__WB_MANIFEST)
+cleanupOutdatedCaches()
+
+self.addEventListener('install', (event) => {
+  event.waitUntil(caches.open(SHELL_CACHE).then((cache) => cache.add(SHELL)))
+})
+
+self.addEventListener('message', (event) => {
+  if (event.data?.type === 'CLEAR_API_CACHE') {
+    event.waitUntil(caches.delete(API_CACHE))
+  }
+})
+
+const cacheControl = (response: Response) =>
+  response.headers.get('Cache-Control') ?? ''
+
+/** `max-age` responses are served from the cache while they're fresh */
+const isFresh = (response: Response) => {
+  const maxAge = /max-age=(\d+)/.exec(cacheControl(response))
+  const date = Date.parse(response.headers.get('Date') ?? '')
+  if (!maxAge || Number.isNaN(date)) return false
+
+  return Date.now() - date < Number(maxAge[1]) * 1000
+}
+
+/** the backend marks what can't be stored (ex: a signed in user's data) with `no-store` */
+const respectCacheControl: WorkboxPlugin = {
+  cacheWillUpdate: async ({ response }) =>
+    response.ok && !cacheControl(response).includes('no-store')
+      ? response
+      : null,
+}
+
+// pages come from the network, the cached ones (or the app shell) are used offline
+const pages = new NetworkFirst({
+  cacheName: SHELL_CACHE,
+  networkTimeoutSeconds: 3,
+  plugins: [respectCacheControl],
+})
+registerRoute(
+  new NavigationRoute(
+    async (options) => {
+      try {
+        return await pages.handle(options)
+      } catch {
+        return (
+          (await caches.match(SHELL, { cacheName: SHELL_CACHE })) ??
+          Response.error()
+        )
+      }
+    },
+    { denylist: [/^\/api\//] }
+  )
+)
+
+// the API's responses come from the network too, unless they're fresh
+const api = new NetworkFirst({
+  cacheName: API_CACHE,
+  networkTimeoutSeconds: 5,
+  plugins: [
+    respectCacheControl,
+    new ExpirationPlugin({ maxEntries: 200, maxAgeSeconds: 7 * 24 * 60 * 60 }),
+  ],
+})
+registerRoute(
+  ({ url, request }) =>
+    url.origin === self.location.origin &&
+    url.pathname.startsWith('/api/') &&
+    // server-sent events are streams
+    request.headers.get('Accept') !== 'text/event-stream',
+  async (options) => {
+    const cached = await caches.match(options.request, { cacheName: API_CACHE })
+    if (cached && isFresh(cached)) return cached
+
+    return api.handle(options)
+  },
+  'GET'
+)
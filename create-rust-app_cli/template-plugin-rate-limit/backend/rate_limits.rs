@@ -0,0 +1,17 @@
+//! The app's rate limits, see `create_rust_app::rate_limit`
+//!
+//! Requests are counted in memory, use a `create_rust_app::auth::RedisStore` (`.store(...)`)
+//! when running several instances of the app.
+use std::time::Duration;
+
+use create_rust_app::auth::RateLimit;
+use create_rust_app::rate_limit::{Policy, RateLimiting};
+
+/// applied to the auth endpoints (`/api/auth`), on top of their limits on login, register and
+/// password reset attempts
+pub fn auth() -> RateLimiting {
+    RateLimiting::new()
+        .prefix("auth")
+        // 60 requests per minute per IP address
+        .policy(Policy::per_ip(RateLimit::new(60, Duration::from_secs(60))))
+}
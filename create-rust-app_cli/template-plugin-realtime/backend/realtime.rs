@@ -0,0 +1,24 @@
+//! The app's realtime channels and their events, see `create_rust_app::realtime`
+//!
+//! The events are tsync'd to `frontend/src/types/rust.d.ts`; map your channels to them in
+//! `frontend/src/realtime/channels.ts` to type their `useChannel` listeners.
+use create_rust_app::realtime;
+use serde::Serialize;
+
+#[tsync::tsync]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+#[allow(dead_code)]
+/// the events of a user's private channel, `user:<id>`
+pub enum UserEvent {
+    /// something the user should know about
+    Notification { message: String },
+}
+
+#[allow(dead_code)]
+/// sends `event` to the connections of the user `user_id`
+///
+/// ex: `realtime::notify_user(auth.user_id, &UserEvent::Notification { message: "Your export is ready".to_string() })`
+pub fn notify_user(user_id: i32, event: &UserEvent) -> Result<(), String> {
+    realtime::broadcast(&format!("user:{user_id}"), event)
+}
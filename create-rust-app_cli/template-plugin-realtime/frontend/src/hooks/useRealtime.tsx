@@ -0,0 +1,46 @@
+import React, { createContext, useContext, useEffect, useMemo, useRef } from 'react'
+import { useAuth } from './useAuth'
+import { ChannelEvent, ChannelName, RealtimeClient } from '../realtime/RealtimeClient'
+
+const RealtimeContext = createContext<RealtimeClient | undefined>(undefined)
+
+/** connects to the realtime channels while the user is logged in */
+export const RealtimeProvider = (props: { children: React.ReactNode }) => {
+  const auth = useAuth()
+  const client = useMemo(() => new RealtimeClient(), [])
+
+  useEffect(() => {
+    client.setToken(auth.accessToken)
+  }, [client, auth.accessToken])
+
+  useEffect(() => () => client.disconnect(), [client])
+
+  return <RealtimeContext.Provider value={client}>{props.children}</RealtimeContext.Provider>
+}
+
+export const useRealtime = () => {
+  const client = useContext(RealtimeContext)
+  if (!client) throw new Error('useRealtime must be used within a RealtimeProvider')
+
+  return client
+}
+
+/**
+ * calls `onEvent` with the events broadcast to `channel` (nothing is subscribed to while it's `undefined`)
+ *
+ * ex, the user's own channel: useChannel(auth.session && `user:${auth.session.userId}`, (event) => ...)
+ */
+export const useChannel = <C extends ChannelName>(
+  channel: C | undefined,
+  onEvent: (event: ChannelEvent<C>) => void
+) => {
+  const client = useRealtime()
+  const onEventRef = useRef(onEvent)
+  onEventRef.current = onEvent
+
+  useEffect(() => {
+    if (!channel) return
+
+    return client.subscribe(channel, (event) => onEventRef.current(event))
+  }, [client, channel])
+}
@@ -0,0 +1,129 @@
+import { Channels } from './channels'
+
+export type ChannelName = keyof Channels & string
+export type ChannelEvent<C extends ChannelName> = Channels[C]
+
+/** the messages of the server, see `create_rust_app::realtime::ServerMessage` */
+type ServerMessage =
+  | { type: 'authenticated'; user_id: number }
+  | { type: 'subscribed'; channel: string }
+  | { type: 'unsubscribed'; channel: string }
+  | { type: 'message'; channel: string; data: unknown }
+  | { type: 'error'; channel?: string | null; message: string }
+  | { type: 'pong' }
+
+type Listener = (event: any) => void
+
+const MAX_RECONNECT_DELAY = 30_000
+
+/**
+ * The websocket of `create_rust_app::realtime`: it authenticates with the access token,
+ * subscribes to the channels listened to, and reconnects (then resubscribes) when the
+ * connection is lost.
+ */
+export class RealtimeClient {
+  private socket?: WebSocket
+  private authenticated = false
+  private token?: string
+  private listeners = new Map<string, Set<Listener>>()
+  private reconnectDelay = 1000
+  private reconnectTimer?: number
+
+  constructor(
+    private url = `${window.location.origin.replace('http', 'ws')}/api/realtime`
+  ) {}
+
+  /** connects with `token`, or authenticates the connection again with it (ex: once it's refreshed) */
+  setToken(token: string | undefined) {
+    this.token = token
+
+    if (!token) {
+      this.disconnect()
+    } else if (this.socket?.readyState === WebSocket.OPEN) {
+      this.send({ type: 'authenticate', token })
+    } else if (!this.socket) {
+      this.connect()
+    }
+  }
+
+  /** calls `listener` with the events of `channel`, until the returned function is called */
+  subscribe<C extends ChannelName>(
+    channel: C,
+    listener: (event: ChannelEvent<C>) => void
+  ): () => void {
+    let listeners = this.listeners.get(channel)
+    if (!listeners) {
+      listeners = new Set()
+      this.listeners.set(channel, listeners)
+      this.send({ type: 'subscribe', channel })
+    }
+    listeners.add(listener)
+
+    return () => {
+      listeners!.delete(listener)
+      if (listeners!.size === 0 && this.listeners.get(channel) === listeners) {
+        this.listeners.delete(channel)
+        this.send({ type: 'unsubscribe', channel })
+      }
+    }
+  }
+
+  /** sends `data` to the subscribers of `channel`, if the backend allows it (see `Realtime::authorize`) */
+  publish<C extends ChannelName>(channel: C, data: ChannelEvent<C>) {
+    this.send({ type: 'publish', channel, data })
+  }
+
+  disconnect() {
+    window.clearTimeout(this.reconnectTimer)
+    const socket = this.socket
+    this.socket = undefined
+    this.authenticated = false
+    socket?.close()
+  }
+
+  private connect() {
+    const socket = new WebSocket(this.url)
+    this.socket = socket
+
+    socket.onopen = () => {
+      this.reconnectDelay = 1000
+      socket.send(JSON.stringify({ type: 'authenticate', token: this.token }))
+    }
+    socket.onmessage = (event) => this.receive(JSON.parse(event.data))
+    socket.onclose = () => {
+      // closed by `disconnect`, or replaced
+      if (this.socket !== socket) return
+
+      this.socket = undefined
+      this.authenticated = false
+      if (this.token) {
+        this.reconnectTimer = window.setTimeout(() => this.connect(), this.reconnectDelay)
+        this.reconnectDelay = Math.min(this.reconnectDelay * 2, MAX_RECONNECT_DELAY)
+      }
+    }
+  }
+
+  private receive(message: ServerMessage) {
+    switch (message.type) {
+      case 'authenticated':
+        if (!this.authenticated) {
+          this.authenticated = true
+          this.listeners.forEach((_, channel) => this.send({ type: 'subscribe', channel }))
+        }
+        break
+      case 'message':
+        this.listeners.get(message.channel)?.forEach((listener) => listener(message.data))
+        break
+      case 'error':
+        console.error(`Realtime${message.channel ? ` (${message.channel})` : ''}: ${message.message}`)
+        break
+    }
+  }
+
+  private send(message: object) {
+    // until then, `receive` (re)subscribes once authenticated
+    if (this.authenticated || (message as { type: string }).type === 'authenticate') {
+      if (this.socket?.readyState === WebSocket.OPEN) this.socket.send(JSON.stringify(message))
+    }
+  }
+}
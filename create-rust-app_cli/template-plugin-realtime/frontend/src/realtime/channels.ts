@@ -0,0 +1,10 @@
+/**
+ * The events of each channel, by channel name: `useChannel` and `RealtimeClient` type the
+ * listeners of a channel with it.
+ *
+ * The events' types are generated from `backend/realtime.rs` by tsync (`yarn tsync`, or
+ * `cargo fullstack`), add the channels your backend broadcasts to here.
+ */
+export interface Channels {
+  [channel: `user:${number}`]: UserEvent
+}
@@ -0,0 +1,40 @@
+import React, { useState } from 'react'
+import { useReferralStats } from '../hooks/useReferral'
+
+export const ReferralShare = () => {
+  const stats = useReferralStats()
+  const [copied, setCopied] = useState<boolean>(false)
+
+  if (!stats) return null
+
+  const link = `${window.location.origin}/register?ref=${stats.code}`
+
+  const copy = async () => {
+    await navigator.clipboard.writeText(link)
+    setCopied(true)
+    setTimeout(() => setCopied(false), 2000)
+  }
+
+  const share = async () => {
+    await navigator.share({ title: 'Join me', url: link })
+  }
+
+  return (
+    <div className="Form" style={{ textAlign: 'left' }}>
+      <h1>Invite friends</h1>
+      <div style={{ display: 'flex', flexFlow: 'column' }}>
+        <label>Your referral link</label>
+        <input readOnly value={link} onFocus={(e) => e.target.select()} />
+      </div>
+      <div style={{ display: 'flex', gap: '8px' }}>
+        <button onClick={copy}>{copied ? 'Copied!' : 'Copy link'}</button>
+        {'share' in navigator && <button onClick={share}>Share</button>}
+      </div>
+      <p>
+        {stats.referred} {stats.referred === 1 ? 'person' : 'people'} joined
+        with your link
+        {stats.rewarded > 0 && ` (${stats.rewarded} rewarded)`}
+      </p>
+    </div>
+  )
+}
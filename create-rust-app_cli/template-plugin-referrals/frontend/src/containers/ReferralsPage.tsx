@@ -0,0 +1,23 @@
+import React from 'react'
+import { useNavigate } from 'react-router-dom'
+import { ReferralShare } from '../components/ReferralShare'
+import { useAuth } from '../hooks/useAuth'
+
+export const ReferralsPage = () => {
+  const auth = useAuth()
+  const navigate = useNavigate()
+
+  return (
+    <div style={{ textAlign: 'left' }}>
+      <h1>Referrals</h1>
+      {auth.isAuthenticated && <ReferralShare />}
+      {!auth.isAuthenticated && (
+        <div>
+          <a href="#" onClick={() => navigate('/login')}>
+            Login to invite your friends
+          </a>
+        </div>
+      )}
+    </div>
+  )
+}
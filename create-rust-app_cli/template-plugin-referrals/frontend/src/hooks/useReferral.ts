@@ -0,0 +1,56 @@
+import { useEffect, useState } from 'react'
+import { useAuth } from './useAuth'
+
+const STORAGE_KEY = 'referral_code'
+
+/**
+ * Remembers the `?ref=` code of the link the visitor arrived with, and
+ * claims it once they're logged in (right after signing up).
+ */
+export const useReferralAttribution = () => {
+  const auth = useAuth()
+
+  useEffect(() => {
+    const code = new URLSearchParams(window.location.search).get('ref')
+    if (code) localStorage.setItem(STORAGE_KEY, code)
+  }, [])
+
+  useEffect(() => {
+    const code = localStorage.getItem(STORAGE_KEY)
+    if (!auth.isAuthenticated || !code) return
+
+    fetch('/api/referrals/claim', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({ code }),
+    }).then((response) => {
+      // only retry on server errors, an invalid or already used code won't become valid
+      if (response.status < 500) localStorage.removeItem(STORAGE_KEY)
+    })
+  }, [auth.isAuthenticated])
+}
+
+export const useReferralStats = () => {
+  const auth = useAuth()
+  const [stats, setStats] = useState<ReferralStats | undefined>(undefined)
+
+  useEffect(() => {
+    if (!auth.isAuthenticated) {
+      setStats(undefined)
+      return
+    }
+
+    fetch('/api/referrals/me', {
+      headers: {
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+    }).then(async (response) => {
+      if (response.ok) setStats(await response.json())
+    })
+  }, [auth.isAuthenticated])
+
+  return stats
+}
@@ -0,0 +1,39 @@
+/* This file is generated and managed by tsync */
+
+interface ReferralCode {
+  user_id: number
+  code: string
+  created_at: Date
+}
+
+interface Referral {
+  id: number
+  referrer_id: number
+  referred_user_id: number
+  code: string
+  rewarded_at?: Date
+  created_at: Date
+}
+
+interface ReferralChangeset {
+  referrer_id: number
+  referred_user_id: number
+  code: string
+}
+
+interface ReferralStats {
+  code: string
+  referred: number
+  rewarded: number
+}
+
+interface ReferralTotals {
+  referrals: number
+  rewarded: number
+  top_referrers: Array<TopReferrer>
+}
+
+interface TopReferrer {
+  user_id: number
+  referrals: number
+}
@@ -0,0 +1,75 @@
+//! The app's procedures, called from the frontend with the client `Router::export` generates in
+//! `frontend/src/rpc.generated.ts` (see `useRpc`). Their inputs and outputs are `#[tsync]` types,
+//! so the client is typed with them.
+use std::time::Duration;
+
+use create_rust_app::rpc::{Input, Router, RpcContext, RpcError};
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+use tsync::tsync;
+
+use crate::models::todos::{CreateTodo, PaginationResult, Todo};
+
+#[tsync]
+#[derive(Deserialize)]
+pub struct ListTodos {
+    pub page: i64,
+    pub page_size: i64,
+}
+
+impl Input for ListTodos {
+    fn validate(&self) -> Result<(), String> {
+        if self.page < 0 {
+            return Err("The page can't be negative.".to_string());
+        }
+        if !(1..=100).contains(&self.page_size) {
+            return Err("The page size must be between 1 and 100.".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl Input for CreateTodo {
+    fn validate(&self) -> Result<(), String> {
+        if self.text.trim().is_empty() {
+            return Err("The text can't be empty.".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+async fn list_todos(ctx: RpcContext, input: ListTodos) -> Result<PaginationResult<Todo>, RpcError> {
+    ctx.blocking(move |db| Todo::paginate(db, input.page, input.page_size))
+        .await
+}
+
+async fn create_todo(ctx: RpcContext, input: CreateTodo) -> Result<Todo, RpcError> {
+    ctx.blocking(move |db| Todo::create(db, &input)).await
+}
+
+/// the time, every `seconds`
+async fn clock(
+    _ctx: RpcContext,
+    seconds: u64,
+) -> Result<impl Stream<Item = String>, RpcError> {
+    if seconds == 0 {
+        return Err(RpcError::invalid_input("The period can't be 0 seconds."));
+    }
+
+    Ok(stream::unfold((), move |_| async move {
+        tokio::time::sleep(Duration::from_secs(seconds)).await;
+        Some((chrono::Utc::now().to_rfc3339(), ()))
+    }))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .query("todos.list", list_todos)
+        .mutation("todos.create", create_todo)
+        .subscription("clock.ticks", clock)
+        /* CRA: procedures */
+        // writes the frontend's client in debug builds
+        .export()
+}
@@ -0,0 +1,11 @@
+import { useMemo } from 'react'
+import { createRpcClient } from '../rpc.generated'
+
+/**
+ * The client of the backend's procedures (see `backend/rpc.rs`), ex:
+ * `const todos = await rpc.todos.list({ page: 0, page_size: 10 })`, or
+ * `useEffect(() => rpc.clock.ticks(1, { onData: setTime }), [rpc])`.
+ */
+export const useRpc = () => {
+  return useMemo(() => createRpcClient(), [])
+}
@@ -0,0 +1,17 @@
+// generated by the backend's RPC router (`Router::export`), don't edit it
+import { call, subscribe, RpcOptions, SubscriptionHandlers } from './rpc'
+
+export const createRpcClient = (options: RpcOptions = {}) => ({
+  clock: {
+    /** subscription, returns a function unsubscribing */
+    ticks: (input: number, handlers: SubscriptionHandlers<string>) => subscribe<string>(options, 'clock.ticks', input, handlers),
+  },
+  todos: {
+    /** mutation */
+    create: (input: CreateTodo) => call<Todo>(options, 'todos.create', input),
+    /** query */
+    list: (input: ListTodos) => call<PaginationResult<Todo>>(options, 'todos.list', input),
+  },
+})
+
+export type RpcClient = ReturnType<typeof createRpcClient>
@@ -0,0 +1,113 @@
+/**
+ * The runtime of the client generated in `rpc.generated.ts`: the procedures of
+ * `backend/rpc.rs` are called with `POST /api/rpc/<name>`, their input as the
+ * JSON body (see `create_rust_app::rpc`).
+ */
+
+export interface RpcOptions {
+  /** sent as `Authorization: Bearer <accessToken>` */
+  accessToken?: string
+}
+
+/** an error returned by a procedure, or rejecting its input (`invalid_input`) */
+export class RpcError extends Error {
+  constructor(readonly status: number, readonly code: string, message: string) {
+    super(message)
+    this.name = 'RpcError'
+  }
+}
+
+export interface SubscriptionHandlers<T> {
+  onData: (data: T) => void
+  /** the subscription failed, it isn't retried */
+  onError?: (error: RpcError) => void
+  /** the backend ended the stream */
+  onComplete?: () => void
+}
+
+const request = async (
+  options: RpcOptions,
+  name: string,
+  input: unknown,
+  signal?: AbortSignal
+): Promise<Response> => {
+  const response = await fetch(`/api/rpc/${name}`, {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+      ...(options.accessToken
+        ? { Authorization: `Bearer ${options.accessToken}` }
+        : {}),
+    },
+    body: JSON.stringify(input),
+    signal,
+  })
+
+  if (!response.ok) {
+    const error = await response.json().catch(() => ({}))
+    throw new RpcError(
+      response.status,
+      error.code ?? 'unknown',
+      error.message ?? response.statusText
+    )
+  }
+
+  return response
+}
+
+/** calls a query or a mutation, rejecting with an `RpcError` */
+export const call = async <T>(
+  options: RpcOptions,
+  name: string,
+  input: unknown
+): Promise<T> => {
+  const response = await request(options, name, input)
+  return response.json()
+}
+
+/** subscribes to a subscription's server-sent events, returns a function unsubscribing */
+export const subscribe = <T>(
+  options: RpcOptions,
+  name: string,
+  input: unknown,
+  handlers: SubscriptionHandlers<T>
+): (() => void) => {
+  const controller = new AbortController()
+
+  const read = async () => {
+    const response = await request(options, name, input, controller.signal)
+    const reader = response.body!.pipeThrough(new TextDecoderStream()).getReader()
+
+    let buffer = ''
+    for (;;) {
+      const { done, value } = await reader.read()
+      if (done) break
+
+      // events end with a blank line, the last one may not be complete yet
+      buffer += value
+      const frames = buffer.split('\n\n')
+      buffer = frames.pop() ?? ''
+
+      for (const frame of frames) {
+        const data = frame
+          .split('\n')
+          .filter((line) => line.startsWith('data:'))
+          .map((line) => line.slice('data:'.length).trimStart())
+          .join('\n')
+        // keep-alive comments have no data
+        if (data) handlers.onData(JSON.parse(data))
+      }
+    }
+
+    handlers.onComplete?.()
+  }
+
+  read().catch((error) => {
+    if (controller.signal.aborted) return
+    handlers.onError?.(
+      error instanceof RpcError ? error : new RpcError(0, 'network', String(error))
+    )
+  })
+
+  return () => controller.abort()
+}
@@ -0,0 +1,27 @@
+///
+/// This binary runs the scheduled jobs of `schedules.rs`
+///
+/// Set `CRA_SCHEDULER=worker` for the server not to run them itself (they can run in both: every
+/// run is claimed in the database first, so it only happens once)
+///
+/// Remember to add your imports (mod statements) here as well,
+/// otherwise your `schedules.rs` may not compile either since this binary (`scheduler.rs`)
+/// will have a different set of included modules compared to `main.rs`
+///
+/// Use `cargo run --bin scheduler` in development
+/// Use `cargo run --bin scheduler --release` in production
+///
+
+extern crate diesel;
+
+mod schema;
+mod models;
+mod schedules;
+
+pub fn main() {
+    let app_data = create_rust_app::setup();
+
+    println!("Running the scheduled jobs...");
+
+    schedules::scheduler(app_data.database).run();
+}
@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use create_rust_app::scheduler::{Job, MissedRuns, Scheduler, TaskRun};
+use create_rust_app::Database;
+
+/// the app's scheduled jobs, register yours here (see `create_rust_app::scheduler`)
+///
+/// they run inside the server, or in the `scheduler` binary when `CRA_SCHEDULER=worker`
+pub fn scheduler(database: Database) -> Scheduler {
+    Scheduler::new(database)
+        // every day at 4:00 (UTC): forgets the runs older than a month
+        .add(
+            Job::new("prune-task-runs", "0 4 * * *", |db| {
+                TaskRun::delete_older_than(db, Duration::from_secs(30 * 24 * 60 * 60))
+                    .map(|_| ())
+                    .map_err(|err| err.to_string())
+            })
+            .missed_runs(MissedRuns::RunOnce),
+        )
+        .expect("Could not register the scheduled jobs")
+}
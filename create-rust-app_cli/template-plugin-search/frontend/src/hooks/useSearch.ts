@@ -0,0 +1,55 @@
+import { useEffect, useState } from 'react'
+import { useAuth } from './useAuth'
+
+/** a document matching the search, see `create_rust_app::search::SearchHit` */
+export interface SearchHit<T = unknown> {
+  index: string
+  id: string
+  title: string
+  /** the part of the document's text matching the search */
+  snippet: string
+  /** the indexed model */
+  data: T
+}
+
+/**
+ * The documents matching `query` (in `index` if given), searched again as the
+ * query changes. Logged in users also find their own documents.
+ */
+export const useSearch = <T = unknown>(query: string, index?: string, debounceMs: number = 250) => {
+  const auth = useAuth()
+  const [hits, setHits] = useState<SearchHit<T>[]>([])
+  const [isLoading, setLoading] = useState<boolean>(false)
+
+  useEffect(() => {
+    if (!query.trim()) {
+      setHits([])
+      return
+    }
+
+    const controller = new AbortController()
+    const timeout = setTimeout(async () => {
+      const params = new URLSearchParams({ q: query })
+      if (index) params.set('index', index)
+
+      setLoading(true)
+      try {
+        const response = await fetch(`/api/search?${params}`, {
+          headers: auth.isAuthenticated ? { Authorization: `Bearer ${auth.accessToken}` } : {},
+          signal: controller.signal,
+        })
+        if (response.ok) setHits(await response.json())
+        setLoading(false)
+      } catch {
+        // aborted, a newer query replaced this one
+      }
+    }, debounceMs)
+
+    return () => {
+      clearTimeout(timeout)
+      controller.abort()
+    }
+  }, [query, index, auth.isAuthenticated])
+
+  return { hits, isLoading }
+}
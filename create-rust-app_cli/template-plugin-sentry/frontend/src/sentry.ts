@@ -0,0 +1,42 @@
+/**
+ * Error reporting to Sentry, with the configuration the backend gives (see
+ * `create_rust_app::sentry`): the DSN, release and environment are set once,
+ * in the backend's environment.
+ */
+import * as Sentry from '@sentry/react'
+import { useEffect } from 'react'
+
+interface SentryConfig {
+  /** null when the frontend shouldn't report anything */
+  dsn: string | null
+  release: string | null
+  environment: string
+}
+
+/** starts reporting errors, if the backend has a DSN for the frontend */
+export const initSentry = async (): Promise<void> => {
+  try {
+    const response = await fetch('/api/sentry')
+    if (!response.ok) return
+
+    const config: SentryConfig = await response.json()
+    if (!config.dsn) return
+
+    Sentry.init({
+      dsn: config.dsn,
+      release: config.release ?? undefined,
+      environment: config.environment,
+    })
+  } catch (err) {
+    console.error('Could not set up Sentry', err)
+  }
+}
+
+/** attaches the signed-in user to the errors reported */
+export const useSentryUser = (userId: number | undefined) => {
+  useEffect(() => {
+    Sentry.setUser(userId !== undefined ? { id: String(userId) } : null)
+  }, [userId])
+}
+
+export const ErrorBoundary = Sentry.ErrorBoundary
@@ -1,4 +1,6 @@
 import React, { useEffect, useState } from 'react'
+import { useDirectUpload } from '../hooks/useDirectUpload'
+import { useMultipartUpload } from '../hooks/useMultipartUpload'
 
 const FilesAPI = {
     all: async () =>
@@ -15,6 +17,8 @@ const FilesAPI = {
 export const Files = () => {
     const [files, setFiles] = useState<FileInfo[]>([])
     const [processing, setProcessing] = useState<boolean>(false)
+    const directUpload = useDirectUpload('/api/files')
+    const multipartUpload = useMultipartUpload('/api/files/confirm')
 
     const createFile = async (form: FormData) => {
         setProcessing(true)
@@ -25,6 +29,18 @@ export const Files = () => {
         setProcessing(false)
     }
 
+    // large files are better sent straight to the storage, without going through the backend
+    const uploadDirectly = async (file: File) => {
+        setProcessing(true)
+        // files over 100MB are sent in parts, which can resume after network failures (S3 only)
+        if (file.size > 100 * 1024 * 1024) await multipartUpload.upload(file)
+        else await directUpload.upload(file)
+        setFiles(await FilesAPI.all())
+        const el = document.getElementById("file")! as HTMLInputElement
+        el.value = ''
+        setProcessing(false)
+    }
+
     const deleteFile = async (file: FileInfo) => {
         setProcessing(true)
         await FilesAPI.delete(file.id)
@@ -84,7 +100,20 @@ export const Files = () => {
                     >
                         Upload
                     </button>
+                    <button
+                        disabled={processing}
+                        style={{ height: '40px' }}
+                        onClick={() => {
+                            const el = document.getElementById("file")! as HTMLInputElement
+                            uploadDirectly(el.files![0])
+                        }}
+                    >
+                        Upload directly
+                    </button>
                 </div>
+                {multipartUpload.progress !== undefined && <div>Uploading... {Math.round(multipartUpload.progress * 100)}%</div>}
+                {directUpload.error && <div>{directUpload.error}</div>}
+                {multipartUpload.error && <div>{multipartUpload.error}</div>}
             </div>
         </div>
     )
@@ -0,0 +1,56 @@
+import { useState } from 'react'
+
+interface DirectUpload {
+  key: string
+  url: string
+  headers: Record<string, string>
+  token: string
+}
+
+/**
+ * Uploads files straight to the storage instead of through the backend:
+ * asks `${endpoint}/presign` for a link, `PUT`s the file there, then
+ * creates the attachment with `${endpoint}/confirm`.
+ */
+export const useDirectUpload = (endpoint: string = '/api/files') => {
+  const [uploading, setUploading] = useState<boolean>(false)
+  const [error, setError] = useState<string | undefined>(undefined)
+
+  const upload = async (file: File): Promise<boolean> => {
+    setUploading(true)
+    setError(undefined)
+
+    try {
+      const presignResponse = await fetch(`${endpoint}/presign`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ file_name: file.name }),
+      })
+      if (!presignResponse.ok) throw new Error('Could not start the upload.')
+      const upload: DirectUpload = await presignResponse.json()
+
+      const putResponse = await fetch(upload.url, {
+        method: 'PUT',
+        headers: upload.headers,
+        body: file,
+      })
+      if (!putResponse.ok) throw new Error('Could not upload the file.')
+
+      const confirmResponse = await fetch(`${endpoint}/confirm`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ token: upload.token }),
+      })
+      if (!confirmResponse.ok) throw new Error('Could not save the file.')
+
+      return true
+    } catch (err) {
+      setError((err as Error).message)
+      return false
+    } finally {
+      setUploading(false)
+    }
+  }
+
+  return { upload, uploading, error }
+}
@@ -0,0 +1,159 @@
+import { useState } from 'react'
+
+interface MultipartUpload {
+  key: string
+  token: string
+  part_size: number
+}
+
+interface PartUpload {
+  part_number: number
+  url: string
+  headers: Record<string, string>
+}
+
+interface UploadedPart {
+  part_number: number
+  etag: string
+}
+
+const MAX_ATTEMPTS = 5
+const STORAGE_PREFIX = 'multipart_upload:'
+
+const post = async <T>(url: string, body: object): Promise<T> => {
+  const response = await fetch(url, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(body),
+  })
+  if (!response.ok) throw new Error(`Request to ${url} failed (${response.status})`)
+  const text = await response.text()
+  return (text ? JSON.parse(text) : undefined) as T
+}
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms))
+
+/** retries `attempt` with an exponential backoff, for flaky connections */
+const withRetries = async <T>(attempt: () => Promise<T>): Promise<T> => {
+  for (let i = 1; ; i++) {
+    try {
+      return await attempt()
+    } catch (err) {
+      if (i >= MAX_ATTEMPTS) throw err
+      await sleep(500 * 2 ** i)
+    }
+  }
+}
+
+/** identifies a file across page reloads, to resume its upload */
+const fingerprint = (file: File) =>
+  `${STORAGE_PREFIX}${file.name}:${file.size}:${file.lastModified}`
+
+/**
+ * Uploads `file` straight to the storage in parts (see `/api/uploads`), and
+ * returns the token to confirm it with (ex: `/api/files/confirm`).
+ *
+ * Parts are retried when the network fails, and an interrupted upload of the
+ * same file (even after a page reload) resumes with the parts the storage
+ * didn't receive. The bucket must expose the `ETag` header to the browser.
+ */
+export const uploadInParts = async (
+  file: File,
+  onProgress?: (uploadedBytes: number) => void,
+  endpoint: string = '/api/uploads',
+): Promise<string> => {
+  const saved = localStorage.getItem(fingerprint(file))
+  let upload: MultipartUpload | undefined = saved ? JSON.parse(saved) : undefined
+  let uploaded: UploadedPart[] = []
+
+  if (upload) {
+    try {
+      uploaded = await post<UploadedPart[]>(`${endpoint}/parts`, { token: upload.token })
+    } catch {
+      // the upload expired or was aborted, start over
+      upload = undefined
+    }
+  }
+
+  if (!upload) {
+    upload = await post<MultipartUpload>(endpoint, { file_name: file.name })
+    localStorage.setItem(fingerprint(file), JSON.stringify(upload))
+  }
+
+  const { token, part_size } = upload
+  const partCount = Math.max(1, Math.ceil(file.size / part_size))
+  const parts = new Map(uploaded.map((part) => [part.part_number, part]))
+  const partBytes = (partNumber: number) =>
+    Math.min(part_size, file.size - (partNumber - 1) * part_size)
+
+  let uploadedBytes = Array.from(parts.keys()).reduce((sum, n) => sum + partBytes(n), 0)
+  onProgress?.(uploadedBytes)
+
+  for (let partNumber = 1; partNumber <= partCount; partNumber++) {
+    if (parts.has(partNumber)) continue
+
+    const chunk = file.slice((partNumber - 1) * part_size, partNumber * part_size)
+    const etag = await withRetries(async () => {
+      // links expire, get a fresh one for every attempt
+      const part = await post<PartUpload>(`${endpoint}/parts/presign`, {
+        token,
+        part_number: partNumber,
+      })
+      const response = await fetch(part.url, { method: 'PUT', headers: part.headers, body: chunk })
+      const etag = response.headers.get('ETag')
+      if (!response.ok || !etag) throw new Error(`Could not upload part ${partNumber}`)
+      return etag
+    })
+
+    parts.set(partNumber, { part_number: partNumber, etag })
+    uploadedBytes += chunk.size
+    onProgress?.(uploadedBytes)
+  }
+
+  const completed = await withRetries(() =>
+    post<{ token: string }>(`${endpoint}/complete`, {
+      token,
+      parts: Array.from(parts.values()),
+    }),
+  )
+  localStorage.removeItem(fingerprint(file))
+
+  return completed.token
+}
+
+/** cancels the upload of `file`, if one was interrupted */
+export const abortUploadInParts = async (file: File, endpoint: string = '/api/uploads') => {
+  const saved = localStorage.getItem(fingerprint(file))
+  if (!saved) return
+
+  localStorage.removeItem(fingerprint(file))
+  const upload: MultipartUpload = JSON.parse(saved)
+  await post(`${endpoint}/abort`, { token: upload.token }).catch(() => undefined)
+}
+
+/**
+ * `uploadInParts`, then confirms the upload at `${confirmEndpoint}` like
+ * `useDirectUpload` does.
+ */
+export const useMultipartUpload = (confirmEndpoint: string = '/api/files/confirm') => {
+  const [progress, setProgress] = useState<number | undefined>(undefined)
+  const [error, setError] = useState<string | undefined>(undefined)
+
+  const upload = async (file: File): Promise<boolean> => {
+    setProgress(0)
+    setError(undefined)
+
+    try {
+      const token = await uploadInParts(file, (bytes) => setProgress(bytes / Math.max(1, file.size)))
+      await post(confirmEndpoint, { token })
+      return true
+    } catch (err) {
+      setError((err as Error).message)
+      return false
+    } finally {
+      setProgress(undefined)
+    }
+  }
+
+  return { upload, uploading: progress !== undefined, progress, error }
+}
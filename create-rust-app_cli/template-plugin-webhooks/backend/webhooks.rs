@@ -0,0 +1,16 @@
+use create_rust_app::webhooks::EventType;
+
+/// the events the app's users can subscribe their webhook endpoints to
+///
+/// send them with `create_rust_app::webhooks::dispatch(&mut db, "todo.created", &todo)`
+pub fn event_types() -> Vec<EventType> {
+    vec![
+        EventType::new("todo.created", "A todo was created"),
+        EventType::new("todo.deleted", "A todo was deleted"),
+    ]
+}
+
+/// defines the event types, so endpoints can subscribe to them
+pub fn register() {
+    create_rust_app::webhooks::register_event_types(event_types());
+}
@@ -26,6 +26,7 @@ pub fn main() {
             // plugin_auth
             ("role_permissions", TableOptions::default().ignore()),
             ("user_permissions", TableOptions::default().ignore()),
+            ("user_profiles", TableOptions::default().ignore()),
             ("user_roles", TableOptions::default().ignore()),
             ("user_sessions", TableOptions::default().ignore()),
             ("users", TableOptions::default().ignore()),
@@ -1,32 +1,12 @@
 use create_rust_app::Mailer;
+use serde_json::json;
 
+/// renders the `example` templates in `backend/mail/templates`
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str) {
-    let subject = "Example Email";
-    let text = format!(
-        r#"
-Hello,
+    let context = json!({ "link": "https://app.my-domain.com" });
 
-We hope you're having a good day. You have been invited to visit our website!
-
-{link}
-
-Warmest regards
-"#,
-        link = "https://app.my-domain.com"
-    );
-    let html = format!(
-        r#"
-<h1>Hello,</h1>
-
-<p>We hope you're having a good day. You have been invited to visit our website!
-
-<a href="{link}">{link}</a></p>
-
-<p>Warmest regards</p>
-"#,
-        link = "https://app.my-domain.com"
-    );
-
-    mailer.send(to_email, &subject, &text, &html);
+    if let Err(err) = mailer.send_template(to_email, "example", None, &context) {
+        println!("{err}");
+    }
 }
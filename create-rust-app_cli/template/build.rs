@@ -3,7 +3,11 @@ use std::fs::File;
 /// Build Script
 /// This is run as a pre-build step -- before the rust backend is compiled.
 ///
-use std::{io::Write, process::Command};
+use std::{
+    io::Write,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /*
  * Note: We're waiting for this feature: println!("cargo:info=...")
@@ -39,7 +43,50 @@ fn shell(command: &str) {
         .expect("Couldn't write to build log");
 }
 
+/// exposes the git commit, the build time and the crates' versions to `create_rust_app::build_info!()`
+fn build_info() {
+    // `GIT_COMMIT` takes precedence, for builds outside of the repository (ex: in a container)
+    let git_commit = std::env::var("GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        });
+    if let Some(git_commit) = git_commit {
+        println!("cargo:rustc-env=CRA_GIT_COMMIT={git_commit}");
+    }
+
+    let built_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=CRA_BUILT_AT={built_at}");
+
+    // the `name` and `version` of every [[package]] in Cargo.lock
+    if let Ok(lockfile) = std::fs::read_to_string("Cargo.lock") {
+        let mut dependencies = vec![];
+        let mut name = None;
+        for line in lockfile.lines() {
+            if let Some(value) = line.strip_prefix("name = ") {
+                name = Some(value.trim_matches('"').to_string());
+            } else if let Some(value) = line.strip_prefix("version = ") {
+                if let Some(name) = name.take() {
+                    dependencies.push(format!("{name}@{}", value.trim_matches('"')));
+                }
+            }
+        }
+        println!("cargo:rustc-env=CRA_DEPENDENCIES={}", dependencies.join(" "));
+    }
+}
+
 fn main() {
+    build_info();
+
     // Only install frontend dependencies when building release
     #[cfg(not(debug_assertions))]
     shell("cd frontend && npm install --frozen-lockfile");
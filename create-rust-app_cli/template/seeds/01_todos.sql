@@ -0,0 +1,5 @@
+-- Seeds are applied in file name order, again and again: keep them idempotent.
+-- Apply them from the admin portal's "Migrations" page, or with `create_rust_app::seeds::run`.
+INSERT INTO todos (text)
+SELECT 'Try the admin portal at /admin'
+WHERE NOT EXISTS (SELECT 1 FROM todos WHERE text = 'Try the admin portal at /admin');